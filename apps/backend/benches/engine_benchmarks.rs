@@ -0,0 +1,203 @@
+//! End-to-end engine benchmarks
+//!
+//! Drive `EngineRequest`s through the running `MatchingEngine` so each
+//! measurement covers validation, balance locking, matching, settlement and
+//! persistence - the full place -> match -> persist path a REST request takes.
+//!
+//! The engine talks to PostgreSQL and ClickHouse directly, so these run against
+//! the same testcontainers used by the integration tests and need Docker:
+//!
+//!     cargo bench -p backend --bench engine_benchmarks
+
+use backend::models::domain::Market;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use exchange_test_utils::{MarketBuilder, OrderBuilder, TestDb, TestEngine, UserBuilder};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+const PRICE: u128 = 50_000_000_000; // $50,000
+const LOT: u128 = 1_000_000; // 0.01 BTC
+
+/// Enough of each token that no benchmark run can exhaust it
+const BALANCE: u128 = 1_000_000_000_000_000_000_000_000;
+
+/// A running engine with a BTC/USDC market and funded `maker` and `taker` users
+struct Bench {
+    engine: TestEngine,
+    market: Market,
+    // Keeps the containers alive for the lifetime of the benchmark
+    _test_db: TestDb,
+}
+
+impl Bench {
+    async fn setup() -> Bench {
+        let test_db = TestDb::setup()
+            .await
+            .expect("Failed to start test containers (is Docker running?)");
+
+        let market = MarketBuilder::new("BTC", "USDC")
+            .create(&test_db)
+            .await
+            .expect("Failed to create market");
+
+        for user in ["maker", "taker"] {
+            UserBuilder::new(user)
+                .balance("BTC", BALANCE)
+                .balance("USDC", BALANCE)
+                .create(&test_db)
+                .await
+                .expect("Failed to create user");
+        }
+
+        let engine = TestEngine::new_with_users(&test_db, false).await;
+
+        Bench {
+            engine,
+            market,
+            _test_db: test_db,
+        }
+    }
+
+    /// Rest `count` one-lot asks at the same price
+    async fn rest_asks(&self, count: usize) {
+        for _ in 0..count {
+            let ask = OrderBuilder::sell("maker", &self.market.id)
+                .limit(PRICE)
+                .size(LOT)
+                .build();
+            self.engine
+                .place_order(ask)
+                .await
+                .expect("Failed to rest maker order");
+        }
+    }
+}
+
+/// Benchmark placing a limit order that rests without matching
+fn bench_engine_place_resting(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let bench = rt.block_on(Bench::setup());
+
+    let mut group = c.benchmark_group("engine_place_resting");
+    group.sample_size(50);
+    group.measurement_time(Duration::from_secs(10));
+
+    group.bench_function("place_limit_order", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let bench = &bench;
+            async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    // Below the ask so it never crosses
+                    let bid = OrderBuilder::buy("taker", &bench.market.id)
+                        .limit(PRICE / 2)
+                        .size(LOT)
+                        .build();
+                    let bid_id = bid.id;
+
+                    let start = Instant::now();
+                    let placed = bench.engine.place_order(bid).await.unwrap();
+                    elapsed += start.elapsed();
+                    black_box(placed);
+
+                    // Keep the book from growing across iterations
+                    bench
+                        .engine
+                        .cancel_order(bid_id, "taker".to_string())
+                        .await
+                        .unwrap();
+                }
+                elapsed
+            }
+        });
+    });
+
+    group.finish();
+}
+
+/// Benchmark cancelling a resting order (unlock + status update)
+fn bench_engine_cancel(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let bench = rt.block_on(Bench::setup());
+
+    let mut group = c.benchmark_group("engine_cancel");
+    group.sample_size(50);
+    group.measurement_time(Duration::from_secs(10));
+
+    group.bench_function("cancel_order", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let bench = &bench;
+            async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let bid = OrderBuilder::buy("taker", &bench.market.id)
+                        .limit(PRICE / 2)
+                        .size(LOT)
+                        .build();
+                    let bid_id = bid.id;
+                    bench.engine.place_order(bid).await.unwrap();
+
+                    let start = Instant::now();
+                    let cancelled = bench
+                        .engine
+                        .cancel_order(bid_id, "taker".to_string())
+                        .await
+                        .unwrap();
+                    elapsed += start.elapsed();
+                    black_box(cancelled);
+                }
+                elapsed
+            }
+        });
+    });
+
+    group.finish();
+}
+
+/// Benchmark a taker sweeping N resting makers, including settlement of every fill
+fn bench_engine_sweep(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let bench = rt.block_on(Bench::setup());
+
+    let mut group = c.benchmark_group("engine_sweep");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    for makers in [1usize, 10, 50].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(makers), makers, |b, &makers| {
+            b.to_async(&rt).iter_custom(|iters| {
+                let bench = &bench;
+                async move {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        bench.rest_asks(makers).await;
+
+                        let taker = OrderBuilder::buy("taker", &bench.market.id)
+                            .limit(PRICE)
+                            .size(LOT * makers as u128)
+                            .build();
+
+                        let start = Instant::now();
+                        let placed = bench.engine.place_order(taker).await.unwrap();
+                        elapsed += start.elapsed();
+
+                        assert_eq!(placed.trades.len(), makers);
+                        black_box(placed);
+                    }
+                    elapsed
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_engine_place_resting,
+    bench_engine_cancel,
+    bench_engine_sweep
+);
+criterion_main!(benches);
@@ -1,6 +1,8 @@
 use backend::engine::matcher::Matcher;
 use backend::engine::orderbook::Orderbook;
-use backend::models::domain::{Market, Order, OrderStatus, OrderType, Side};
+use backend::models::domain::{
+    Market, Order, OrderStatus, OrderType, Side, TimeInForce, TriggerSource,
+};
 use chrono::Utc;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::hint::black_box;
@@ -34,6 +36,11 @@ fn create_order(user: &str, market_id: &str, side: Side, price: u128, size: u128
         filled_size: 0,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        cancel_reason: None,
+        trigger_price: None,
+        trigger_source: TriggerSource::LastTrade,
+        time_in_force: TimeInForce::Gtc,
+        expires_at: None,
     }
 }
 
@@ -251,6 +258,11 @@ fn bench_worst_case_latency(c: &mut Criterion) {
                     filled_size: 0,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
+                    cancel_reason: None,
+                    trigger_price: None,
+                    trigger_source: TriggerSource::LastTrade,
+                    time_in_force: TimeInForce::Gtc,
+                    expires_at: None,
                 };
 
                 let matches = Matcher::match_order(black_box(&market_order), &orderbook);
@@ -1,6 +1,9 @@
+use backend::engine::ladder::LadderLayout;
 use backend::engine::matcher::Matcher;
 use backend::engine::orderbook::Orderbook;
-use backend::models::domain::{Market, Order, OrderStatus, OrderType, Side};
+use backend::models::domain::{
+    Market, Order, OrderStatus, OrderType, Side, TimeInForce, Trade, TriggerSource,
+};
 use chrono::Utc;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use std::hint::black_box;
@@ -32,6 +35,11 @@ fn create_order(user: &str, market_id: &str, side: Side, price: u128, size: u128
         filled_size: 0,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        cancel_reason: None,
+        trigger_price: None,
+        trigger_source: TriggerSource::LastTrade,
+        time_in_force: TimeInForce::Gtc,
+        expires_at: None,
     }
 }
 
@@ -156,6 +164,11 @@ fn bench_market_order_execution(c: &mut Criterion) {
                 filled_size: 0,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
+                cancel_reason: None,
+                trigger_price: None,
+                trigger_source: TriggerSource::LastTrade,
+                time_in_force: TimeInForce::Gtc,
+                expires_at: None,
             };
 
             let matches = Matcher::match_order(black_box(&market_order), &orderbook);
@@ -196,6 +209,166 @@ fn bench_price_time_priority(c: &mut Criterion) {
     });
 }
 
+/// Benchmark the full in-memory pipeline for a taker sweeping the book:
+/// match, then apply the resulting fills back onto the book
+fn bench_match_and_apply_at_depth(c: &mut Criterion) {
+    let market = create_test_market();
+    let mut group = c.benchmark_group("match_and_apply_depth");
+
+    for depth in [10, 100, 1000].iter() {
+        group.throughput(Throughput::Elements(*depth as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(depth), depth, |b, &depth| {
+            b.iter_batched(
+                || {
+                    let mut orderbook = Orderbook::new("BTC/USDC".to_string());
+                    for i in 0..depth {
+                        orderbook.add_order(create_order(
+                            &format!("seller{}", i),
+                            "BTC/USDC",
+                            Side::Sell,
+                            50_000_000_000 + (i as u128 * 1000),
+                            1_000_000,
+                        ));
+                    }
+                    orderbook
+                },
+                |mut orderbook| {
+                    // Sweeps all but the last level, partially filling it
+                    let buy_order = create_order(
+                        "buyer",
+                        "BTC/USDC",
+                        Side::Buy,
+                        60_000_000_000,
+                        (depth as u128) * 1_000_000 - 500_000,
+                    );
+
+                    let matches = Matcher::match_order(black_box(&buy_order), &orderbook);
+                    let trades: Vec<Trade> = matches
+                        .iter()
+                        .map(|m| Trade {
+                            id: Uuid::new_v4(),
+                            market_id: buy_order.market_id.clone(),
+                            buyer_address: buy_order.user_address.clone(),
+                            seller_address: m.maker_order.user_address.clone(),
+                            buyer_order_id: buy_order.id,
+                            seller_order_id: m.maker_order.id,
+                            price: m.price,
+                            size: m.size,
+                            side: buy_order.side,
+                            timestamp: Utc::now(),
+                            rfq: false,
+                        })
+                        .collect();
+                    orderbook.apply_trades(&buy_order, &trades, &market);
+                    black_box((matches, orderbook));
+                },
+                criterion::BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark a taker sweeping a bounded-price (0-1) book with each ladder layout
+fn bench_sweep_by_ladder(c: &mut Criterion) {
+    let mut market = create_test_market();
+    market.tick_size = 1000;
+    let ladder = LadderLayout::Array {
+        min_price: 0,
+        max_price: 1_000_000,
+        tick_size: 1000,
+    };
+    let mut group = c.benchmark_group("sweep_by_ladder");
+
+    for levels in [100, 900].iter() {
+        for (name, layout) in [("tree", LadderLayout::Tree), ("array", ladder)] {
+            group.throughput(Throughput::Elements(*levels as u64));
+            group.bench_with_input(BenchmarkId::new(name, levels), levels, |b, &levels| {
+                b.iter_batched(
+                    || {
+                        let mut orderbook = Orderbook::with_layout("BP/USDC".to_string(), layout);
+                        for i in 0..levels {
+                            orderbook.add_order(create_order(
+                                &format!("seller{}", i),
+                                "BP/USDC",
+                                Side::Sell,
+                                100_000 + (i as u128 * 1000),
+                                1_000_000,
+                            ));
+                        }
+                        orderbook
+                    },
+                    |mut orderbook| {
+                        let buy_order = create_order(
+                            "buyer",
+                            "BP/USDC",
+                            Side::Buy,
+                            1_000_000,
+                            (levels as u128) * 1_000_000,
+                        );
+
+                        let matches = Matcher::match_order(black_box(&buy_order), &orderbook);
+                        let trades: Vec<Trade> = matches
+                            .iter()
+                            .map(|m| Trade {
+                                id: Uuid::new_v4(),
+                                market_id: buy_order.market_id.clone(),
+                                buyer_address: buy_order.user_address.clone(),
+                                seller_address: m.maker_order.user_address.clone(),
+                                buyer_order_id: buy_order.id,
+                                seller_order_id: m.maker_order.id,
+                                price: m.price,
+                                size: m.size,
+                                side: buy_order.side,
+                                timestamp: Utc::now(),
+                                rfq: false,
+                            })
+                            .collect();
+                        orderbook.apply_trades(&buy_order, &trades, &market);
+                        black_box((matches, orderbook));
+                    },
+                    criterion::BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark full snapshot aggregation against the depth-limited one on deep books
+fn bench_snapshot_depth(c: &mut Criterion) {
+    let mut group = c.benchmark_group("orderbook_snapshot");
+
+    for levels in [1_000, 10_000].iter() {
+        let mut orderbook = Orderbook::new("BTC/USDC".to_string());
+        for i in 0..*levels {
+            let offset = i as u128 * 1000;
+            orderbook.add_order(create_order(
+                "buyer",
+                "BTC/USDC",
+                Side::Buy,
+                49_000_000_000 - offset,
+                1_000_000,
+            ));
+            orderbook.add_order(create_order(
+                "seller",
+                "BTC/USDC",
+                Side::Sell,
+                51_000_000_000 + offset,
+                1_000_000,
+            ));
+        }
+
+        group.bench_with_input(BenchmarkId::new("full", levels), &orderbook, |b, book| {
+            b.iter(|| black_box(book.snapshot()));
+        });
+        group.bench_with_input(BenchmarkId::new("top_20", levels), &orderbook, |b, book| {
+            b.iter(|| black_box(book.snapshot_top_n(20)));
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_match_single_order_empty_book,
@@ -203,5 +376,8 @@ criterion_group!(
     bench_partial_fill_matching,
     bench_market_order_execution,
     bench_price_time_priority,
+    bench_match_and_apply_at_depth,
+    bench_snapshot_depth,
+    bench_sweep_by_ladder,
 );
 criterion_main!(benches);
@@ -0,0 +1,299 @@
+// operational alerts pushed to Slack, Telegram or a generic webhook
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+
+/// Repeats of an alert within this window are counted instead of sent
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Orders a single cancel-all must take out to raise a mass-cancel alert
+pub const DEFAULT_MASS_CANCEL_ORDERS: usize = 100;
+
+/// Alerts waiting for the dispatcher; more are dropped rather than waited on
+const ALERT_BUFFER: usize = 256;
+
+/// How long a target has to accept one alert
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The engine's request queue is close to full
+    EngineQueueSaturated,
+    /// Trades are backing up on their way to ClickHouse
+    PersistenceQueueSaturated,
+    /// A consumer of engine events fell behind and skipped some
+    EventsLagging,
+    /// A write the engine depends on failed
+    PersistenceFailure,
+    /// A market, or the whole exchange, stopped trading
+    MarketHalted,
+    /// One request cancelled an unusual number of orders
+    MassCancel,
+    /// A market's best resting bid reached its best resting ask
+    CrossedBook,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertKind::EngineQueueSaturated => "engine_queue_saturated",
+            AlertKind::PersistenceQueueSaturated => "persistence_queue_saturated",
+            AlertKind::EventsLagging => "events_lagging",
+            AlertKind::PersistenceFailure => "persistence_failure",
+            AlertKind::MarketHalted => "market_halted",
+            AlertKind::MassCancel => "mass_cancel",
+            AlertKind::CrossedBook => "crossed_book",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Something an operator should look at now
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    /// What the alert is about, such as a market or user; alerts of one kind
+    /// are deduplicated per subject
+    pub subject: String,
+    pub message: String,
+    pub raised_at: DateTime<Utc>,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            message: message.into(),
+            raised_at: Utc::now(),
+        }
+    }
+
+    /// One line for chat targets, noting repeats held back since the last one
+    pub fn text(&self, suppressed: u64) -> String {
+        let mut text = format!("[{}] {}: {}", self.kind, self.subject, self.message);
+        if suppressed > 0 {
+            text.push_str(&format!(
+                " ({} similar alerts suppressed since the last one)",
+                suppressed
+            ));
+        }
+        text
+    }
+}
+
+/// Handle for raising alerts, cheap to clone into any task
+///
+/// `raise` never waits, so it is safe on the matching path. The default
+/// handle is disabled and drops everything.
+#[derive(Clone, Default)]
+pub struct Alerter {
+    alert_tx: Option<mpsc::Sender<Alert>>,
+}
+
+impl Alerter {
+    pub fn raise(&self, alert: Alert) {
+        let Some(alert_tx) = &self.alert_tx else {
+            return;
+        };
+        match alert_tx.try_send(alert) {
+            Ok(()) => {}
+            Err(TrySendError::Full(alert)) => {
+                log::warn!("Alert buffer full, dropped alert: {}", alert.text(0));
+            }
+            Err(TrySendError::Closed(alert)) => {
+                log::warn!("Alert dispatcher stopped, dropped alert: {}", alert.text(0));
+            }
+        }
+    }
+}
+
+/// Where alerts are sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertTarget {
+    /// Slack incoming webhook
+    Slack { webhook_url: String },
+    /// Telegram bot posting to a chat
+    Telegram { bot_token: String, chat_id: String },
+    /// Any URL taking the alert as JSON
+    Webhook { url: String },
+}
+
+impl AlertTarget {
+    /// Targets configured through `ALERT_*` environment variables
+    pub fn from_env() -> Vec<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        let mut targets = Vec::new();
+        if let Some(webhook_url) = var("ALERT_SLACK_WEBHOOK_URL") {
+            targets.push(AlertTarget::Slack { webhook_url });
+        }
+        match (
+            var("ALERT_TELEGRAM_BOT_TOKEN"),
+            var("ALERT_TELEGRAM_CHAT_ID"),
+        ) {
+            (Some(bot_token), Some(chat_id)) => {
+                targets.push(AlertTarget::Telegram { bot_token, chat_id })
+            }
+            (None, None) => {}
+            _ => log::warn!(
+                "Telegram alerts need both ALERT_TELEGRAM_BOT_TOKEN and ALERT_TELEGRAM_CHAT_ID"
+            ),
+        }
+        if let Some(url) = var("ALERT_WEBHOOK_URL") {
+            targets.push(AlertTarget::Webhook { url });
+        }
+        targets
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            AlertTarget::Slack { .. } => "Slack",
+            AlertTarget::Telegram { .. } => "Telegram",
+            AlertTarget::Webhook { .. } => "webhook",
+        }
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        alert: &Alert,
+        suppressed: u64,
+    ) -> reqwest::RequestBuilder {
+        match self {
+            AlertTarget::Slack { webhook_url } => client
+                .post(webhook_url)
+                .json(&json!({ "text": alert.text(suppressed) })),
+            AlertTarget::Telegram { bot_token, chat_id } => client
+                .post(format!("{}/bot{}/sendMessage", TELEGRAM_API_URL, bot_token))
+                .json(&json!({ "chat_id": chat_id, "text": alert.text(suppressed) })),
+            AlertTarget::Webhook { url } => client.post(url).json(&json!({
+                "kind": alert.kind,
+                "subject": alert.subject,
+                "message": alert.message,
+                "raised_at": alert.raised_at,
+                "suppressed": suppressed,
+            })),
+        }
+    }
+}
+
+/// Rate limit on alerts: one per kind and subject per window
+///
+/// Repeats inside the window are counted, and the count goes out with the
+/// next alert of that kind and subject let through.
+pub struct Deduplicator {
+    window: Duration,
+    /// When each kind and subject was last sent, and repeats held back since
+    seen: HashMap<(AlertKind, String), (Instant, u64)>,
+}
+
+impl Deduplicator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Whether to send `alert` raised at `now`; if so, with how many repeats
+    /// were held back before it
+    pub fn admit(&mut self, alert: &Alert, now: Instant) -> Option<u64> {
+        // Forget quiet keys; ones with held back repeats wait to report them
+        let window = self.window;
+        self.seen.retain(|_, (sent_at, suppressed)| {
+            *suppressed > 0 || now.duration_since(*sent_at) < window
+        });
+
+        let key = (alert.kind, alert.subject.clone());
+        match self.seen.get_mut(&key) {
+            Some((sent_at, suppressed)) if now.duration_since(*sent_at) < window => {
+                *suppressed += 1;
+                None
+            }
+            Some((sent_at, suppressed)) => {
+                *sent_at = now;
+                Some(std::mem::take(suppressed))
+            }
+            None => {
+                self.seen.insert(key, (now, 0));
+                Some(0)
+            }
+        }
+    }
+}
+
+/// Sends raised alerts to every configured target, deduplicated
+pub struct AlertDispatcher {
+    client: reqwest::Client,
+    targets: Vec<AlertTarget>,
+    window: Duration,
+}
+
+impl AlertDispatcher {
+    pub fn new(targets: Vec<AlertTarget>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()
+            .expect("Failed to build HTTP client");
+        Self {
+            client,
+            targets,
+            window: DEFAULT_DEDUP_WINDOW,
+        }
+    }
+
+    /// Hold back repeats of an alert for `window` after it's sent
+    pub fn with_dedup_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Start sending; the task stops once every [`Alerter`] is dropped and
+    /// the alerts already raised have gone out
+    pub fn spawn(self) -> (Alerter, JoinHandle<()>) {
+        let (alert_tx, alert_rx) = mpsc::channel(ALERT_BUFFER);
+        let handle = tokio::spawn(self.run(alert_rx));
+        (
+            Alerter {
+                alert_tx: Some(alert_tx),
+            },
+            handle,
+        )
+    }
+
+    async fn run(self, mut alert_rx: mpsc::Receiver<Alert>) {
+        let mut dedup = Deduplicator::new(self.window);
+
+        while let Some(alert) = alert_rx.recv().await {
+            let Some(suppressed) = dedup.admit(&alert, Instant::now()) else {
+                continue;
+            };
+
+            for target in &self.targets {
+                let result = target
+                    .request(&self.client, &alert, suppressed)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                if let Err(e) = result {
+                    log::error!(
+                        "Failed to send {} alert to {}: {}",
+                        alert.kind,
+                        target.name(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
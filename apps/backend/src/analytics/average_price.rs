@@ -0,0 +1,53 @@
+// volume and time weighted average prices
+
+use crate::db::Db;
+use crate::errors::Result;
+
+/// Volume-weighted average trade price of a market over `[from, to]`
+/// `None` when nothing traded in the range
+pub async fn vwap(db: &Db, market_id: &str, from: i64, to: i64) -> Result<Option<u128>> {
+    let (notional, volume) = db.get_notional_and_volume(market_id, from, to).await?;
+    Ok((volume > 0).then(|| notional / volume))
+}
+
+/// Time-weighted average trade price of a market over `[from, to]`
+/// `None` when the market had not traded by `to`
+pub async fn twap(db: &Db, market_id: &str, from: i64, to: i64) -> Result<Option<u128>> {
+    let (opening, points) = db.get_price_points(market_id, from, to).await?;
+    Ok(time_weighted(opening, &points, from, to))
+}
+
+/// Average a price path over `[from, to]`, each price holding until the next
+///
+/// `opening` is the price in force at `from`; without one the average starts at
+/// the first point. `points` are (unix seconds, price) in time order.
+pub fn time_weighted(
+    opening: Option<u128>,
+    points: &[(i64, u128)],
+    from: i64,
+    to: i64,
+) -> Option<u128> {
+    let mut current = opening.map(|price| (from, price));
+    let mut weighted: u128 = 0;
+    let mut duration: u128 = 0;
+
+    for &(timestamp, price) in points {
+        if let Some((since, previous)) = current {
+            let held = (timestamp - since).max(0) as u128;
+            weighted += previous * held;
+            duration += held;
+        }
+        current = Some((timestamp, price));
+    }
+
+    let (since, last) = current?;
+    let held = (to - since).max(0) as u128;
+    weighted += last * held;
+    duration += held;
+
+    // A single instant (e.g. one trade at `to`) has no duration to weight by
+    if duration == 0 {
+        return Some(last);
+    }
+    Some(weighted / duration)
+}
@@ -0,0 +1,64 @@
+// trader rankings
+
+use crate::analytics::pnl::PositionTracker;
+use crate::models::db::TraderNotionalRow;
+use crate::models::domain::{CostBasisMethod, Fill};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Quote volume per trader, summed over markets from their unscaled notionals
+pub fn volumes(
+    notionals: Vec<TraderNotionalRow>,
+    base_decimals: &HashMap<String, u8>,
+) -> HashMap<String, i128> {
+    let mut volumes = HashMap::new();
+    for row in notionals {
+        let Some(&decimals) = base_decimals.get(&row.market_id) else {
+            continue;
+        };
+        *volumes.entry(row.user_address).or_default() +=
+            (row.notional / 10u128.pow(decimals as u32)) as i128;
+    }
+    volumes
+}
+
+/// Realized PnL per trader on sales since `from`, FIFO cost basis
+///
+/// `fills` must hold each trader's full history in time order, so sales in
+/// the period are matched against purchases made before it. Traders with a
+/// fill in the period are included even when they realized nothing.
+pub fn realized_pnl_since(
+    fills: Vec<Fill>,
+    from: DateTime<Utc>,
+    base_decimals: &HashMap<String, u8>,
+) -> HashMap<String, i128> {
+    let mut trackers = HashMap::new();
+    let mut pnl = HashMap::new();
+    for fill in fills {
+        let Some(&decimals) = base_decimals.get(&fill.market_id) else {
+            continue;
+        };
+        let tracker = trackers
+            .entry((fill.user_address.clone(), fill.market_id))
+            .or_insert_with(|| PositionTracker::new(CostBasisMethod::Fifo, decimals));
+        let before = tracker.realized_pnl();
+        tracker.apply(fill.side, fill.price, fill.size);
+        if fill.timestamp >= from {
+            *pnl.entry(fill.user_address).or_default() += tracker.realized_pnl() - before;
+        }
+    }
+    pnl
+}
+
+/// The top `limit` traders as (rank, address, value), highest value first
+/// Ties go to the lower address so rankings are stable between requests
+pub fn rank(values: HashMap<String, i128>, limit: usize) -> Vec<(u32, String, i128)> {
+    let mut ranked: Vec<_> = values.into_iter().collect();
+    ranked.sort_by(|(a_user, a), (b_user, b)| b.cmp(a).then_with(|| a_user.cmp(b_user)));
+    ranked
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (user, value))| (i as u32 + 1, user, value))
+        .collect()
+}
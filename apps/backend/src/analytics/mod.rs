@@ -0,0 +1,5 @@
+// reporting over the ClickHouse trade history
+
+pub mod average_price;
+pub mod leaderboard;
+pub mod pnl;
@@ -0,0 +1,95 @@
+// realized pnl per user per market
+
+use crate::models::domain::{CostBasisMethod, Side};
+use std::collections::VecDeque;
+
+/// A user's position in one market, built up trade by trade in time order
+///
+/// Costs are kept as `price * size` and only scaled by the base token's
+/// decimals when PnL is realized, so repeated fills don't accumulate rounding.
+/// Sales beyond the tracked position (e.g. of deposited tokens) have no known
+/// cost and realize nothing.
+#[derive(Debug)]
+pub struct PositionTracker {
+    method: CostBasisMethod,
+    base_decimals_divisor: u128,
+    position: u128,
+    cost: u128,
+    // Open purchases as (price, size), oldest first; only used for FIFO
+    lots: VecDeque<(u128, u128)>,
+    realized_pnl: i128,
+}
+
+impl PositionTracker {
+    pub fn new(method: CostBasisMethod, base_decimals: u8) -> Self {
+        Self {
+            method,
+            base_decimals_divisor: 10u128.pow(base_decimals as u32),
+            position: 0,
+            cost: 0,
+            lots: VecDeque::new(),
+            realized_pnl: 0,
+        }
+    }
+
+    /// Apply one of the user's fills
+    pub fn apply(&mut self, side: Side, price: u128, size: u128) {
+        match side {
+            Side::Buy => {
+                self.position += size;
+                self.cost += price * size;
+                if self.method == CostBasisMethod::Fifo {
+                    self.lots.push_back((price, size));
+                }
+            }
+            Side::Sell => {
+                let closed = size.min(self.position);
+                if closed == 0 {
+                    return;
+                }
+                let closed_cost = match self.method {
+                    CostBasisMethod::Fifo => self.close_lots(closed),
+                    CostBasisMethod::AverageCost => self.cost * closed / self.position,
+                };
+                let proceeds = price * closed;
+                self.realized_pnl +=
+                    (proceeds as i128 - closed_cost as i128) / self.base_decimals_divisor as i128;
+                self.position -= closed;
+                self.cost -= closed_cost;
+            }
+        }
+    }
+
+    /// Take `size` off the oldest lots, returning their cost
+    fn close_lots(&mut self, mut size: u128) -> u128 {
+        let mut cost = 0;
+        while size > 0 {
+            let Some((price, lot_size)) = self.lots.front_mut() else {
+                break;
+            };
+            let taken = size.min(*lot_size);
+            cost += *price * taken;
+            *lot_size -= taken;
+            size -= taken;
+            if *lot_size == 0 {
+                self.lots.pop_front();
+            }
+        }
+        cost
+    }
+
+    /// Realized PnL in quote token atoms
+    pub fn realized_pnl(&self) -> i128 {
+        self.realized_pnl
+    }
+
+    /// Open position in base token atoms
+    pub fn position(&self) -> u128 {
+        self.position
+    }
+
+    /// Average price paid for the open position; `None` when flat
+    pub fn average_entry_price(&self) -> Option<u128> {
+        (self.position > 0).then(|| self.cost / self.position)
+    }
+}
@@ -0,0 +1,201 @@
+// token amounts as decimal strings, for clients that send X-Amount-Format: decimal
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use std::collections::HashMap;
+
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::api::{ApiBalance, ApiMarket, ApiOrder, ApiTrade};
+use crate::AppState;
+use exchange_protocol::convert::{
+    atoms_to_decimal, decimal_to_atoms, signed_atoms_to_decimal, AmountFormat, AMOUNT_FORMAT_HEADER,
+};
+
+/// The amount format a request asked for, and the token decimals to apply it
+///
+/// In the default atoms format amounts pass through as integer strings. In
+/// the decimal format prices are read and written in whole quote tokens,
+/// sizes in whole base tokens and balances in whole tokens of their own,
+/// each with the token's decimals. An amount with more decimal places than
+/// its token has is refused rather than rounded, so an order can't quietly
+/// become zero-sized. Decimals are looked up once per token per request.
+pub struct Amounts {
+    format: AmountFormat,
+    db: Db,
+    tokens: HashMap<String, u8>,
+    /// Base and quote ticker of each market seen
+    markets: HashMap<String, (String, String)>,
+}
+
+impl FromRequestParts<AppState> for Amounts {
+    type Rejection = ExchangeError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self> {
+        let format = match parts.headers.get(AMOUNT_FORMAT_HEADER) {
+            None => AmountFormat::Atoms,
+            Some(value) => value
+                .to_str()
+                .map_err(|e| e.to_string())
+                .and_then(str::parse)
+                .map_err(|message| ExchangeError::InvalidParameter { message })?,
+        };
+        Ok(Self::new(format, state.db.clone()))
+    }
+}
+
+impl Amounts {
+    pub fn new(format: AmountFormat, db: Db) -> Self {
+        Self {
+            format,
+            db,
+            tokens: HashMap::new(),
+            markets: HashMap::new(),
+        }
+    }
+
+    pub fn format(&self) -> AmountFormat {
+        self.format
+    }
+
+    /// Atoms of a price in `market_id`
+    pub async fn parse_price(&mut self, market_id: &str, value: &str) -> Result<u128> {
+        if self.format == AmountFormat::Atoms {
+            return value.parse().map_err(|_| ExchangeError::InvalidPrice);
+        }
+        let (_, quote) = self.market(market_id).await?;
+        self.parse_decimal("price", value, &quote).await
+    }
+
+    /// Atoms of a size in `market_id`
+    pub async fn parse_size(&mut self, market_id: &str, value: &str) -> Result<u128> {
+        if self.format == AmountFormat::Atoms {
+            return value.parse().map_err(|_| ExchangeError::InvalidSize);
+        }
+        let (base, _) = self.market(market_id).await?;
+        self.parse_decimal("size", value, &base).await
+    }
+
+    /// Atoms of an amount of `ticker`
+    pub async fn parse_amount(&mut self, ticker: &str, value: &str) -> Result<u128> {
+        if self.format == AmountFormat::Atoms {
+            return value.parse().map_err(|_| ExchangeError::InvalidAmount);
+        }
+        self.parse_decimal("amount", value, ticker).await
+    }
+
+    /// An amount of `ticker` in atoms, written in the requested format
+    pub async fn amount(&mut self, ticker: &str, atoms: u128) -> Result<String> {
+        match self.format {
+            AmountFormat::Atoms => Ok(atoms.to_string()),
+            AmountFormat::Decimal => Ok(atoms_to_decimal(atoms, self.decimals(ticker).await?)),
+        }
+    }
+
+    pub async fn order(&mut self, mut order: ApiOrder) -> Result<ApiOrder> {
+        if self.format == AmountFormat::Atoms {
+            return Ok(order);
+        }
+        let (base, quote) = self.market(&order.market_id).await?;
+        order.price = self.rewrite(&order.price, &quote).await?;
+        order.size = self.rewrite(&order.size, &base).await?;
+        order.filled_size = self.rewrite(&order.filled_size, &base).await?;
+        if let Some(trigger_price) = &order.trigger_price {
+            order.trigger_price = Some(self.rewrite(trigger_price, &quote).await?);
+        }
+        Ok(order)
+    }
+
+    pub async fn orders(&mut self, orders: Vec<ApiOrder>) -> Result<Vec<ApiOrder>> {
+        let mut rewritten = Vec::with_capacity(orders.len());
+        for order in orders {
+            rewritten.push(self.order(order).await?);
+        }
+        Ok(rewritten)
+    }
+
+    pub async fn trade(&mut self, mut trade: ApiTrade) -> Result<ApiTrade> {
+        if self.format == AmountFormat::Atoms {
+            return Ok(trade);
+        }
+        let (base, quote) = self.market(&trade.market_id).await?;
+        trade.price = self.rewrite(&trade.price, &quote).await?;
+        trade.size = self.rewrite(&trade.size, &base).await?;
+        if let (Some(fee), Some(ticker)) = (&trade.fee, &trade.fee_ticker) {
+            if let Ok(atoms) = fee.parse::<i128>() {
+                let decimals = self.decimals(ticker).await?;
+                trade.fee = Some(signed_atoms_to_decimal(atoms, decimals));
+            }
+        }
+        Ok(trade)
+    }
+
+    pub async fn trades(&mut self, trades: Vec<ApiTrade>) -> Result<Vec<ApiTrade>> {
+        let mut rewritten = Vec::with_capacity(trades.len());
+        for trade in trades {
+            rewritten.push(self.trade(trade).await?);
+        }
+        Ok(rewritten)
+    }
+
+    pub async fn balances(&mut self, balances: Vec<ApiBalance>) -> Result<Vec<ApiBalance>> {
+        if self.format == AmountFormat::Atoms {
+            return Ok(balances);
+        }
+        let mut rewritten = Vec::with_capacity(balances.len());
+        for mut balance in balances {
+            balance.amount = self.rewrite(&balance.amount, &balance.token_ticker).await?;
+            balance.open_interest = self
+                .rewrite(&balance.open_interest, &balance.token_ticker)
+                .await?;
+            rewritten.push(balance);
+        }
+        Ok(rewritten)
+    }
+
+    /// Tick size in quote tokens, lot and minimum size in base tokens
+    pub async fn market_details(&mut self, mut market: ApiMarket) -> Result<ApiMarket> {
+        if self.format == AmountFormat::Atoms {
+            return Ok(market);
+        }
+        let (base, quote) = (market.base_ticker.clone(), market.quote_ticker.clone());
+        market.tick_size = self.rewrite(&market.tick_size, &quote).await?;
+        market.lot_size = self.rewrite(&market.lot_size, &base).await?;
+        market.min_size = self.rewrite(&market.min_size, &base).await?;
+        Ok(market)
+    }
+
+    async fn parse_decimal(&mut self, field: &str, value: &str, ticker: &str) -> Result<u128> {
+        let decimals = self.decimals(ticker).await?;
+        decimal_to_atoms(value, decimals).map_err(|e| ExchangeError::InvalidParameter {
+            message: format!("Invalid {}: {}", field, e),
+        })
+    }
+
+    /// An integer atoms string as a decimal; anything else is left as it is
+    async fn rewrite(&mut self, atoms: &str, ticker: &str) -> Result<String> {
+        match atoms.parse::<u128>() {
+            Ok(atoms) => Ok(atoms_to_decimal(atoms, self.decimals(ticker).await?)),
+            Err(_) => Ok(atoms.to_string()),
+        }
+    }
+
+    async fn decimals(&mut self, ticker: &str) -> Result<u8> {
+        if let Some(decimals) = self.tokens.get(ticker) {
+            return Ok(*decimals);
+        }
+        let token = self.db.get_token(ticker).await?;
+        self.tokens.insert(token.ticker, token.decimals);
+        Ok(token.decimals)
+    }
+
+    async fn market(&mut self, market_id: &str) -> Result<(String, String)> {
+        if let Some(tickers) = self.markets.get(market_id) {
+            return Ok(tickers.clone());
+        }
+        let market = self.db.get_market(market_id).await?;
+        let tickers = (market.base_ticker, market.quote_ticker);
+        self.markets.insert(market.id, tickers.clone());
+        Ok(tickers)
+    }
+}
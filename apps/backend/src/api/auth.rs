@@ -0,0 +1,222 @@
+// API key authentication: resolves an `X-API-Key` header to its owner and scopes
+
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{ApiKey, ApiKeyScope, SubAccount};
+use crate::AppState;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr};
+use uuid::Uuid;
+
+/// Header carrying an API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Most API keys a single user may hold at once
+pub const MAX_API_KEYS_PER_USER: usize = 10;
+
+/// Characters of a key kept in the clear so its user can tell keys apart
+const KEY_PREFIX_LEN: usize = 11;
+
+/// The API key a request authenticated with, added to its extensions by [`authenticate`]
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    pub key_id: Uuid,
+    pub user_address: String,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl From<ApiKey> for ApiKeyAuth {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            key_id: key.id,
+            user_address: key.user_address,
+            scopes: key.scopes,
+        }
+    }
+}
+
+impl ApiKeyAuth {
+    /// Check the key may act for `user_address`, its own account or one of
+    /// its sub-accounts, with `scope`
+    pub fn permit(&self, user_address: &str, scope: ApiKeyScope) -> Result<()> {
+        if !SubAccount::owns(&self.user_address, user_address) {
+            return Err(ExchangeError::ApiKeyNotPermitted {
+                message: format!("the key can't act for '{}'", user_address),
+            });
+        }
+        if !self.scopes.contains(&scope) {
+            return Err(ExchangeError::ApiKeyNotPermitted {
+                message: format!("the key lacks the '{}' scope", scope),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Check a request may act for `user_address` with `scope`; `None` means no
+/// key may make it
+///
+/// A request with an API key is held to the key's owner, scopes and
+/// allowlist. Without one, `verify_signature` has to accept the request's
+/// signature, unless the request only reads: reads by address are public,
+/// as they are on the WebSocket's user channel.
+pub fn authorize(
+    auth: Option<&ApiKeyAuth>,
+    user_address: &str,
+    scope: Option<ApiKeyScope>,
+    verify_signature: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    match (auth, scope) {
+        (None, Some(ApiKeyScope::Read)) => Ok(()),
+        (None, _) => verify_signature(),
+        (Some(auth), Some(scope)) => auth.permit(user_address, scope),
+        (Some(_), None) => Err(ExchangeError::ApiKeyNotPermitted {
+            message: "this request needs the account's own signature".to_string(),
+        }),
+    }
+}
+
+/// Check the request's bearer token against the configured admin token
+///
+/// Every admin request is refused when no admin token is configured.
+pub fn authorize_admin(state: &AppState, headers: &HeaderMap) -> Result<()> {
+    let Some(expected) = state.admin_token.as_deref() else {
+        log::warn!("Rejected admin request: ADMIN_TOKEN is not configured");
+        return Err(ExchangeError::Unauthorized);
+    };
+
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(ExchangeError::Unauthorized)?;
+
+    if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err(ExchangeError::Unauthorized)
+    }
+}
+
+/// Compare without short-circuiting so timing doesn't leak the matching prefix
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// A new random API key
+pub fn new_key() -> String {
+    format!("ak_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// The start of a key, shown back to its user
+pub fn key_prefix(key: &str) -> String {
+    key.chars().take(KEY_PREFIX_LEN).collect()
+}
+
+/// Keys are stored and looked up by their hex SHA-256
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Allowlist entries are IP addresses or CIDR ranges
+pub fn validate_allowlist(allowlist: &[String]) -> Result<()> {
+    match allowlist.iter().find(|entry| parse_range(entry).is_none()) {
+        Some(entry) => Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Invalid allowlist entry '{}': expected an IP address or CIDR range",
+                entry
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Whether `ip` is one of `allowlist`'s addresses or in one of its ranges;
+/// an empty allowlist allows any address
+pub fn ip_allowed(allowlist: &[String], ip: IpAddr) -> bool {
+    let ip = ip.to_canonical();
+    allowlist.is_empty()
+        || allowlist
+            .iter()
+            .filter_map(|entry| parse_range(entry))
+            .any(|range| in_range(ip, range))
+}
+
+/// An allowlist entry as a network address and prefix length
+fn parse_range(entry: &str) -> Option<(IpAddr, u32)> {
+    let (address, bits) = match entry.split_once('/') {
+        Some((address, bits)) => (address, Some(bits)),
+        None => (entry, None),
+    };
+    let address: IpAddr = address.parse().ok()?;
+    let width = if address.is_ipv4() { 32 } else { 128 };
+    let bits = match bits {
+        Some(bits) => bits.parse().ok().filter(|bits| *bits <= width)?,
+        None => width,
+    };
+    Some((address, bits))
+}
+
+fn in_range(ip: IpAddr, (network, bits): (IpAddr, u32)) -> bool {
+    let (ip, network, width) = match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            (u32::from(ip) as u128, u32::from(network) as u128, 32)
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => (u128::from(ip), u128::from(network), 128),
+        _ => return false,
+    };
+    let shift = width - bits;
+    ip.checked_shr(shift).unwrap_or(0) == network.checked_shr(shift).unwrap_or(0)
+}
+
+/// Authenticate requests carrying an `X-API-Key` header
+///
+/// A valid, unrevoked key used from an allowed address is added to the
+/// request as an [`ApiKeyAuth`] for handlers to check with [`authorize`];
+/// anything else is rejected before reaching them. Requests without the
+/// header pass through untouched. The client address is the peer's, so
+/// allowlists only work when the server sees clients directly.
+pub async fn authenticate(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response> {
+    let Some(header) = request.headers().get(API_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = header.to_str().map_err(|_| ExchangeError::InvalidApiKey)?;
+    let api_key = state
+        .db
+        .get_api_key_by_hash(&hash_key(key))
+        .await?
+        .ok_or(ExchangeError::InvalidApiKey)?;
+
+    if !api_key.ip_allowlist.is_empty() {
+        let ip = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        match ip {
+            Some(ip) if ip_allowed(&api_key.ip_allowlist, ip) => {}
+            Some(ip) => {
+                return Err(ExchangeError::ApiKeyNotPermitted {
+                    message: format!("the key can't be used from {}", ip),
+                })
+            }
+            None => {
+                return Err(ExchangeError::ApiKeyNotPermitted {
+                    message: "the client address is unknown".to_string(),
+                })
+            }
+        }
+    }
+
+    if let Err(e) = state.db.touch_api_key(api_key.id).await {
+        log::warn!("Failed to record use of API key {}: {}", api_key.id, e);
+    }
+    request.extensions_mut().insert(ApiKeyAuth::from(api_key));
+    Ok(next.run(request).await)
+}
@@ -1,2 +1,5 @@
+pub mod amounts;
+pub mod auth;
 pub mod rest;
+pub mod signature;
 pub mod ws;
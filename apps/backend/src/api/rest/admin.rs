@@ -1,14 +1,26 @@
+use crate::api::auth;
+use crate::bootstrap;
+use crate::cache::keys;
+use crate::config::{MarketConfig, PriceLadderConfig};
 use crate::errors::{ErrorResponse, ExchangeError, Result};
 use crate::models::api::{AdminRequest, AdminResponse};
+use crate::models::domain::{EngineEvent, EngineRequest, EventStatus, MarketStatus};
 use crate::AppState;
-use axum::{extract::State, Json};
+use axum::{extract::State, http::HeaderMap, Json};
+use tokio::sync::oneshot;
 
-/// Admin endpoint for test/dev operations
+/// Admin endpoint for operating the exchange
 ///
 /// POST /api/admin
 ///
-/// Handles administrative operations like creating tokens, markets, and funding accounts.
-/// In production, this endpoint should be protected or disabled.
+/// Requires `Authorization: Bearer <ADMIN_TOKEN>`; the endpoint rejects every
+/// request when no admin token is configured.
+///
+/// Handles administrative operations like creating tokens, markets, funding accounts
+/// setting per-user limits, fee overrides, referrals, account status and price
+/// collars, and routing fees between the system accounts and auditing their ledger,
+/// creating and resolving prediction events, registering RFQ makers, and
+/// recording beneficial owners and reading trade surveillance alerts.
 #[utoipa::path(
     post,
     path = "/api/admin",
@@ -16,14 +28,18 @@ use axum::{extract::State, Json};
     responses(
         (status = 200, description = "Admin operation successful", body = AdminResponse),
         (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "admin"
 )]
 pub async fn admin_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(request): Json<AdminRequest>,
 ) -> Result<Json<AdminResponse>> {
+    auth::authorize_admin(&state, &headers)?;
+
     match request {
         AdminRequest::CreateToken {
             ticker,
@@ -31,6 +47,10 @@ pub async fn admin_handler(
             name,
         } => {
             let token = state.db.create_token(ticker, decimals, name).await?;
+            state
+                .cache
+                .invalidate(&[keys::TOKENS.to_string(), keys::token(&token.ticker)])
+                .await;
 
             Ok(Json(AdminResponse::CreateToken { token }))
         }
@@ -43,7 +63,37 @@ pub async fn admin_handler(
             min_size,
             maker_fee_bps,
             taker_fee_bps,
+            price_ladder,
+            price_collar_bps,
         } => {
+            // Check the ladder and collar before creating anything
+            let config = MarketConfig {
+                base_ticker: base_ticker.clone(),
+                quote_ticker: quote_ticker.clone(),
+                tick_size: tick_size.clone(),
+                lot_size: lot_size.clone(),
+                min_size: min_size.clone(),
+                maker_fee_bps,
+                taker_fee_bps,
+                price_ladder: price_ladder.map(|ladder| PriceLadderConfig {
+                    min_price: ladder.min_price.unwrap_or_else(|| "0".to_string()),
+                    max_price: ladder.max_price,
+                }),
+                price_collar_bps,
+                index_feed: None,
+                perpetual: None,
+            };
+            let layout = config
+                .ladder_layout()
+                .map_err(|e| ExchangeError::InvalidParameter {
+                    message: format!("{:#}", e),
+                })?;
+            if price_collar_bps == Some(0) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: "Price collar must be greater than 0 bps".to_string(),
+                });
+            }
+
             // Parse string values to u128
             let tick_size_u128 = tick_size.parse::<u128>()?;
             let lot_size_u128 = lot_size.parse::<u128>()?;
@@ -61,9 +111,26 @@ pub async fn admin_handler(
                     taker_fee_bps,
                 )
                 .await?;
+            // Aliases and the numeric id work as soon as the market is listed
+            if let Err(e) = state.symbols.refresh(&state.db).await {
+                log::warn!("Failed to refresh market symbols: {}", e);
+            }
+            state
+                .cache
+                .invalidate(&[keys::MARKETS.to_string(), keys::market(&market.id)])
+                .await;
+
+            // The engine builds its book so it trades and streams right away
+            bootstrap::open_market(
+                &state.engine_tx,
+                market.id.clone(),
+                layout,
+                price_collar_bps,
+            )
+            .await?;
 
             Ok(Json(AdminResponse::CreateMarket {
-                market: market.into(),
+                market: state.symbols.numbered(market.into()),
             }))
         }
 
@@ -86,6 +153,17 @@ pub async fn admin_handler(
                 .db
                 .add_balance(&user_address, &token_ticker, amount_u128)
                 .await?;
+            if let Err(e) = state
+                .db
+                .notify_balance_changed(&user_address, &token_ticker)
+                .await
+            {
+                log::warn!(
+                    "Failed to announce faucet credit to {}: {}",
+                    user_address,
+                    e
+                );
+            }
 
             Ok(Json(AdminResponse::Faucet {
                 user_address,
@@ -94,5 +172,351 @@ pub async fn admin_handler(
                 new_balance: balance.amount.to_string(),
             }))
         }
+
+        AdminRequest::SetUserLimits {
+            user_address,
+            market_id,
+            max_position,
+            max_open_notional,
+        } => {
+            let market_id = state.symbols.canonical(&market_id);
+
+            // Parse string values to u128
+            let max_position = max_position.map(|v| v.parse::<u128>()).transpose()?;
+            let max_open_notional = max_open_notional.map(|v| v.parse::<u128>()).transpose()?;
+
+            // Limits live in the engine, which enforces them at placement time
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetUserLimits {
+                    user_address,
+                    market_id,
+                    max_position,
+                    max_open_notional,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let limits = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetUserLimits {
+                limits: limits.into(),
+            }))
+        }
+
+        AdminRequest::SetMarketStatus { market_id, status } => {
+            let market_id = state.symbols.canonical(&market_id);
+
+            // The engine cancels resting orders when a market stops trading
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetMarketStatus {
+                    market_id: market_id.clone(),
+                    status,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let cancelled = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetMarketStatus {
+                market_id,
+                status,
+                cancelled_orders: cancelled.count,
+            }))
+        }
+
+        AdminRequest::SetReferral {
+            user_address,
+            referrer_address,
+            share_bps,
+        } => {
+            // Referrals live in the engine, which pays them out during settlement
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetReferral {
+                    user_address,
+                    referrer_address,
+                    share_bps,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let referral = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetReferral { referral }))
+        }
+
+        AdminRequest::SetUserStatus {
+            user_address,
+            status,
+            cancel_orders,
+        } => {
+            // The engine keeps the status cached and cancels orders it rests for the user
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetUserStatus {
+                    user_address: user_address.clone(),
+                    status,
+                    cancel_orders,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let cancelled = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetUserStatus {
+                user_address,
+                status,
+                cancelled_orders: cancelled.count,
+            }))
+        }
+
+        AdminRequest::SetPriceCollar {
+            market_id,
+            collar_bps,
+        } => {
+            let market_id = state.symbols.canonical(&market_id);
+
+            // Collars are checked by the engine at placement time
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetPriceCollar {
+                    market_id: market_id.clone(),
+                    collar_bps,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetPriceCollar {
+                market_id,
+                collar_bps,
+            }))
+        }
+        AdminRequest::SetFeeRoute {
+            source,
+            insurance_bps,
+        } => {
+            // Routes are applied by the engine when it settles trades
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetFeeRoute {
+                    source,
+                    insurance_bps,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let route = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetFeeRoute { route }))
+        }
+        AdminRequest::SetFeeOverride {
+            user_address,
+            market_id,
+            maker_fee_bps,
+            taker_fee_bps,
+        } => {
+            let market_id = state.symbols.canonical(&market_id);
+
+            // Overrides are applied by the engine when it settles trades
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetFeeOverride {
+                    user_address: user_address.clone(),
+                    market_id: market_id.clone(),
+                    maker_fee_bps,
+                    taker_fee_bps,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let fee_override = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(AdminResponse::SetFeeOverride {
+                user_address,
+                market_id,
+                fee_override,
+            }))
+        }
+        AdminRequest::SystemLedger {
+            account,
+            token_ticker,
+            limit,
+        } => {
+            let balances = state
+                .db
+                .list_balances_by_user(account.address())
+                .await?
+                .into_iter()
+                .filter(|b| token_ticker.as_ref().is_none_or(|t| *t == b.token_ticker))
+                .map(Into::into)
+                .collect();
+            let entries = state
+                .db
+                .list_ledger_entries(account, token_ticker.as_deref(), limit.unwrap_or(100))
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect();
+
+            Ok(Json(AdminResponse::SystemLedger {
+                account,
+                balances,
+                entries,
+            }))
+        }
+
+        AdminRequest::CreateEvent {
+            event_id,
+            title,
+            outcomes,
+        } => {
+            let outcomes: Vec<(String, String)> = outcomes
+                .into_iter()
+                .map(|outcome| (outcome.name, state.symbols.canonical(&outcome.market_id)))
+                .collect();
+            let event = state.db.create_event(&event_id, &title, &outcomes).await?;
+
+            Ok(Json(AdminResponse::CreateEvent {
+                event: event.into(),
+            }))
+        }
+
+        AdminRequest::ResolveEvent {
+            event_id,
+            winning_outcome,
+        } => {
+            let event = state.db.get_event(&event_id).await?;
+            if event.status == EventStatus::Resolved {
+                return Err(ExchangeError::EventAlreadyResolved { event_id });
+            }
+            if !event.outcomes.iter().any(|o| o.name == winning_outcome) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!("Event '{}' has no outcome '{}'", event_id, winning_outcome),
+                });
+            }
+
+            // Stop the outcome tokens trading first; the engine cancels resting
+            // orders and releases what they lock
+            for outcome in &event.outcomes {
+                let (response_tx, response_rx) = oneshot::channel();
+                state
+                    .engine_tx
+                    .send(EngineRequest::SetMarketStatus {
+                        market_id: outcome.market_id.clone(),
+                        status: MarketStatus::Delisted,
+                        response_tx,
+                    })
+                    .await
+                    .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+                response_rx
+                    .await
+                    .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+            }
+
+            let (event, plan) = state.db.resolve_event(&event_id, &winning_outcome).await?;
+            for (user_address, token_ticker) in plan.balance_changes.keys() {
+                if let Ok(balance) = state.db.get_balance(user_address, token_ticker).await {
+                    let _ = state.event_tx.send(EngineEvent::BalanceUpdated { balance });
+                }
+            }
+
+            Ok(Json(AdminResponse::ResolveEvent {
+                event: event.into(),
+                holders_paid: plan.holders_paid,
+                total_payout: plan.total_payout.to_string(),
+            }))
+        }
+
+        AdminRequest::RegisterRfqMaker { user_address } => {
+            state.db.register_rfq_maker(&user_address).await?;
+
+            Ok(Json(AdminResponse::RegisterRfqMaker { user_address }))
+        }
+
+        AdminRequest::RemoveRfqMaker { user_address } => {
+            let withdrawn_quotes = state.db.remove_rfq_maker(&user_address).await?;
+
+            Ok(Json(AdminResponse::RemoveRfqMaker {
+                user_address,
+                withdrawn_quotes,
+            }))
+        }
+
+        AdminRequest::ApproveMarketMaker { user_address } => {
+            state.db.approve_market_maker(&user_address).await?;
+
+            Ok(Json(AdminResponse::ApproveMarketMaker { user_address }))
+        }
+
+        AdminRequest::RevokeMarketMaker { user_address } => {
+            state.db.revoke_market_maker(&user_address).await?;
+
+            Ok(Json(AdminResponse::RevokeMarketMaker { user_address }))
+        }
+
+        AdminRequest::SetBeneficialOwner {
+            user_address,
+            owner,
+        } => {
+            state
+                .db
+                .set_beneficial_owner(&user_address, owner.as_deref())
+                .await?;
+
+            Ok(Json(AdminResponse::SetBeneficialOwner {
+                user_address,
+                owner,
+            }))
+        }
+
+        AdminRequest::SurveillanceAlerts {
+            kind,
+            market_id,
+            limit,
+        } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
+            let alerts = state
+                .db
+                .list_surveillance_alerts(kind, market_id.as_deref(), limit.unwrap_or(100))
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect();
+
+            Ok(Json(AdminResponse::SurveillanceAlerts { alerts }))
+        }
     }
 }
@@ -0,0 +1,110 @@
+use crate::analytics::average_price;
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::ApiAveragePrice;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Time range for average price and depth history queries
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TimeRangeQuery {
+    /// Unix timestamp in seconds
+    pub from: i64,
+    /// Unix timestamp in seconds
+    pub to: i64,
+}
+
+impl TimeRangeQuery {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.from < 0 || self.from >= self.to {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Invalid time range: from ({}) must be before to ({})",
+                    self.from, self.to
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Get a market's volume-weighted average price over a time range
+///
+/// GET /api/markets/{market_id}/vwap
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/vwap",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        TimeRangeQuery
+    ),
+    responses(
+        (status = 200, description = "VWAP computed successfully", body = ApiAveragePrice),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn vwap(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(range): Query<TimeRangeQuery>,
+) -> Result<Json<ApiAveragePrice>> {
+    let market_id = state.symbols.canonical(&market_id);
+    range.validate()?;
+    state.db.get_market(&market_id).await?;
+
+    let price = average_price::vwap(&state.db, &market_id, range.from, range.to).await?;
+
+    Ok(Json(ApiAveragePrice {
+        market_id,
+        price: price.map(|p| p.to_string()),
+        from: range.from,
+        to: range.to,
+    }))
+}
+
+/// Get a market's time-weighted average price over a time range
+///
+/// GET /api/markets/{market_id}/twap
+///
+/// Each trade price holds until the next trade; the price in force when the
+/// range starts is the last trade before it.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/twap",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        TimeRangeQuery
+    ),
+    responses(
+        (status = 200, description = "TWAP computed successfully", body = ApiAveragePrice),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn twap(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(range): Query<TimeRangeQuery>,
+) -> Result<Json<ApiAveragePrice>> {
+    let market_id = state.symbols.canonical(&market_id);
+    range.validate()?;
+    state.db.get_market(&market_id).await?;
+
+    let price = average_price::twap(&state.db, &market_id, range.from, range.to).await?;
+
+    Ok(Json(ApiAveragePrice {
+        market_id,
+        price: price.map(|p| p.to_string()),
+        from: range.from,
+        to: range.to,
+    }))
+}
@@ -1,3 +1,4 @@
+use crate::db::candles::CANDLE_INTERVALS;
 use crate::models::api::{CandlesRequest, CandlesResponse};
 use crate::AppState;
 use axum::{extract::State, Json};
@@ -5,6 +6,9 @@ use axum::{extract::State, Json};
 /// Get OHLCV candles for a market
 ///
 /// POST /api/candles
+///
+/// Intervals without trades are filled with the previous close and zero volume
+/// unless `fill_gaps` is false.
 #[utoipa::path(
     post,
     path = "/api/candles",
@@ -18,10 +22,12 @@ use axum::{extract::State, Json};
 )]
 pub async fn candles(
     State(state): State<AppState>,
-    Json(params): Json<CandlesRequest>,
+    Json(mut params): Json<CandlesRequest>,
 ) -> Result<Json<CandlesResponse>, String> {
+    params.market_id = state.symbols.canonical(&params.market_id);
+
     // Validate interval
-    if !["1m", "5m", "15m", "1h", "1d"].contains(&params.interval.as_str()) {
+    if !CANDLE_INTERVALS.contains(&params.interval.as_str()) {
         return Err("Invalid interval. Must be one of: 1m, 5m, 15m, 1h, 1d".to_string());
     }
 
@@ -34,6 +40,7 @@ pub async fn candles(
             params.from,
             params.to,
             params.count_back,
+            params.fill_gaps.unwrap_or(true),
         )
         .await
         .map_err(|e| format!("Failed to query candles: {}", e))?;
@@ -0,0 +1,76 @@
+use super::average_price::TimeRangeQuery;
+use crate::engine::depth::{DEPTH_METRICS_INTERVAL_SECS, DEPTH_METRICS_LEVELS};
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{ApiDepthSample, DepthHistoryResponse};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+/// Longest time range served in one request
+const MAX_DEPTH_HISTORY_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Get a market's sampled spread, top-of-book depth and imbalance over a time range
+///
+/// GET /api/markets/{market_id}/depth
+///
+/// The engine samples every market every `interval_secs`; gaps between
+/// samples mean the engine was not running.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/depth",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        TimeRangeQuery
+    ),
+    responses(
+        (status = 200, description = "Depth history retrieved successfully", body = DepthHistoryResponse),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn depth_history(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(range): Query<TimeRangeQuery>,
+) -> Result<Json<DepthHistoryResponse>> {
+    let market_id = state.symbols.canonical(&market_id);
+    range.validate()?;
+    if range.to - range.from > MAX_DEPTH_HISTORY_SECS {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Time range must not exceed {} seconds",
+                MAX_DEPTH_HISTORY_SECS
+            ),
+        });
+    }
+    state.db.get_market(&market_id).await?;
+
+    let samples = state
+        .db
+        .get_depth_metrics(&market_id, range.from, range.to)
+        .await?;
+
+    Ok(Json(DepthHistoryResponse {
+        market_id,
+        levels: DEPTH_METRICS_LEVELS,
+        interval_secs: DEPTH_METRICS_INTERVAL_SECS,
+        samples: samples
+            .into_iter()
+            .map(|sample| ApiDepthSample {
+                timestamp: sample.timestamp.timestamp(),
+                best_bid: sample.best_bid.map(|p| p.to_string()),
+                best_ask: sample.best_ask.map(|p| p.to_string()),
+                spread: sample.spread().map(|s| s.to_string()),
+                bid_depth: sample.bid_depth.to_string(),
+                ask_depth: sample.ask_depth.to_string(),
+                imbalance_bps: sample.imbalance_bps,
+            })
+            .collect(),
+        from: range.from,
+        to: range.to,
+    }))
+}
@@ -0,0 +1,446 @@
+use super::average_price::TimeRangeQuery;
+use crate::errors::ExchangeError;
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::{
+    ApiFundingRate, ApiMarginHealth, ApiOpenInterest, ApiPerpetualMarket, ApiPosition,
+    ApiPositionRisk, FundingHistoryResponse, MarginAccountResponse, MarketRiskResponse,
+    OpenInterestHistoryResponse, PositionsResponse,
+};
+use crate::models::domain::MarginMode;
+use crate::perps::{self, margin};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// How far above their maintenance margin positions are listed as at risk, by default
+pub const DEFAULT_RISK_WITHIN_BPS: u32 = 1000;
+
+/// Query parameters for positions at risk
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RiskQuery {
+    /// List positions whose equity is less than this many bps above their
+    /// maintenance margin (default 1000)
+    pub within_bps: Option<u32>,
+}
+
+/// Get a perpetual market's open interest over a time range
+///
+/// GET /api/markets/{market_id}/open-interest
+///
+/// Spot markets have no open interest and always return no snapshots.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/open-interest",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        TimeRangeQuery
+    ),
+    responses(
+        (status = 200, description = "Open interest history retrieved successfully", body = OpenInterestHistoryResponse),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn open_interest_history(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(range): Query<TimeRangeQuery>,
+) -> Result<Json<OpenInterestHistoryResponse>> {
+    let market_id = state.symbols.canonical(&market_id);
+    range.validate()?;
+    state.db.get_market(&market_id).await?;
+
+    let snapshots = state
+        .db
+        .get_open_interest(&market_id, range.from, range.to)
+        .await?;
+
+    Ok(Json(OpenInterestHistoryResponse {
+        market_id,
+        snapshots: snapshots
+            .into_iter()
+            .map(|snapshot| ApiOpenInterest {
+                timestamp: snapshot.timestamp.timestamp(),
+                open_interest: snapshot.open_interest.to_string(),
+                mark_price: snapshot.mark_price.to_string(),
+            })
+            .collect(),
+        from: range.from,
+        to: range.to,
+    }))
+}
+
+/// Get a perpetual market's funding rates over a time range
+///
+/// GET /api/markets/{market_id}/funding
+///
+/// Spot markets have no funding and always return no rates.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/funding",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        TimeRangeQuery
+    ),
+    responses(
+        (status = 200, description = "Funding history retrieved successfully", body = FundingHistoryResponse),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn funding_history(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(range): Query<TimeRangeQuery>,
+) -> Result<Json<FundingHistoryResponse>> {
+    let market_id = state.symbols.canonical(&market_id);
+    range.validate()?;
+    state.db.get_market(&market_id).await?;
+
+    let rates = state
+        .db
+        .get_funding_rates(&market_id, range.from, range.to)
+        .await?;
+
+    Ok(Json(FundingHistoryResponse {
+        market_id,
+        rates: rates
+            .into_iter()
+            .map(|rate| ApiFundingRate {
+                timestamp: rate.timestamp.timestamp(),
+                funding_rate_ppm: rate.funding_rate_ppm,
+                mark_price: rate.mark_price.to_string(),
+                index_price: rate.index_price.to_string(),
+            })
+            .collect(),
+        from: range.from,
+        to: range.to,
+    }))
+}
+
+/// Get a perpetual market's funding terms and latest mark price
+///
+/// GET /api/markets/{market_id}/perpetual
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/perpetual",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC-PERP%2FUSDC), an alias such as BTC-PERP-USDC, or its numeric ID")
+    ),
+    responses(
+        (status = 200, description = "Perpetual market retrieved successfully", body = ApiPerpetualMarket),
+        (status = 400, description = "Not a perpetual market", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn perpetual_market(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+) -> Result<Json<ApiPerpetualMarket>> {
+    let market_id = state.symbols.canonical(&market_id);
+    state.db.get_market(&market_id).await?;
+    let market = state
+        .db
+        .get_perpetual_market(&market_id)
+        .await?
+        .ok_or_else(|| ExchangeError::InvalidParameter {
+            message: format!("{} is not a perpetual market", market_id),
+        })?;
+
+    Ok(Json(ApiPerpetualMarket {
+        next_funding_at: market.next_funding_at().timestamp(),
+        market_id: market.market_id,
+        funding_interval_secs: market.funding_interval_secs,
+        max_funding_rate_ppm: market.max_funding_rate_ppm,
+        initial_margin_bps: market.initial_margin_bps,
+        maintenance_margin_bps: market.maintenance_margin_bps,
+        liquidation_fee_bps: market.liquidation_fee_bps,
+        mark_price: market.mark_price.map(|price| price.to_string()),
+        index_price: market.index_price.map(|price| price.to_string()),
+        mark_updated_at: market.mark_updated_at.map(|at| at.timestamp()),
+    }))
+}
+
+/// Get a user's positions in perpetual markets
+///
+/// GET /api/users/{address}/positions
+///
+/// Closed positions stay listed until their last PnL is settled at the next
+/// funding interval. Unrealized PnL is valued at the latest mark price.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/positions",
+    params(
+        ("address" = String, Path, description = "User address")
+    ),
+    responses(
+        (status = 200, description = "Positions retrieved successfully", body = PositionsResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn user_positions(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<PositionsResponse>> {
+    state.db.get_user(&address).await?;
+
+    let positions = state.db.list_positions_by_user(&address).await?;
+    let marks: std::collections::HashMap<String, u128> = state
+        .db
+        .list_perpetual_markets()
+        .await?
+        .into_iter()
+        .filter_map(|market| Some((market.market_id, market.mark_price?)))
+        .collect();
+    let base_decimals = state.db.get_base_decimals_by_market().await?;
+
+    Ok(Json(PositionsResponse {
+        user_address: address,
+        positions: positions
+            .into_iter()
+            .map(|position| {
+                let mark_price = marks.get(&position.market_id).copied();
+                let unrealized_pnl = mark_price
+                    .zip(base_decimals.get(&position.market_id))
+                    .and_then(|(mark, &decimals)| {
+                        perps::unrealized_pnl(&position, mark, 10u128.pow(decimals as u32))
+                    });
+                ApiPosition {
+                    market_id: position.market_id,
+                    size: position.size.to_string(),
+                    entry_price: position.entry_price.to_string(),
+                    margin: position.margin.to_string(),
+                    mark_price: mark_price.map(|price| price.to_string()),
+                    unrealized_pnl: unrealized_pnl.map(|pnl| pnl.to_string()),
+                    realized_pnl: position.realized_pnl.to_string(),
+                    funding_paid: position.funding_paid.to_string(),
+                    updated_at: position.updated_at.timestamp(),
+                }
+            })
+            .collect(),
+    }))
+}
+
+/// Get how a user's positions are margined and how close they are to liquidation
+///
+/// GET /api/users/{address}/margin
+///
+/// Positions are valued at the latest mark price, or their entry price until
+/// their market is first marked. Cross-margined users get one entry per quote
+/// token, backed by their free balance of it as well.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/margin",
+    params(
+        ("address" = String, Path, description = "User address")
+    ),
+    responses(
+        (status = 200, description = "Margin account retrieved successfully", body = MarginAccountResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn margin_account(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<MarginAccountResponse>> {
+    state.db.get_user(&address).await?;
+
+    let (mode, healths) = account_healths(&state, &address).await?;
+
+    Ok(Json(MarginAccountResponse {
+        user_address: address,
+        mode,
+        health: healths
+            .into_iter()
+            .map(|(token_ticker, market_ids, health)| {
+                margin_health(token_ticker, market_ids, health)
+            })
+            .collect(),
+    }))
+}
+
+/// Get a perpetual market's positions nearest to liquidation
+///
+/// GET /api/markets/{market_id}/risk
+///
+/// Lists the positions whose equity is less than `within_bps` above their
+/// maintenance margin at the latest mark, including those already due for
+/// liquidation, without their holders. Each comes with the worst price its
+/// liquidation order would fill at, so liquidity can be lined up for it.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/risk",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC-PERP%2FUSDC), an alias such as BTC-PERP-USDC, or its numeric ID"),
+        RiskQuery
+    ),
+    responses(
+        (status = 200, description = "Positions at risk retrieved successfully", body = MarketRiskResponse),
+        (status = 400, description = "Not a perpetual market", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn market_risk(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(query): Query<RiskQuery>,
+) -> Result<Json<MarketRiskResponse>> {
+    let market_id = state.symbols.canonical(&market_id);
+    let market = state.db.get_market(&market_id).await?;
+    let perpetual = state
+        .db
+        .get_perpetual_market(&market_id)
+        .await?
+        .ok_or_else(|| ExchangeError::InvalidParameter {
+            message: format!("{} is not a perpetual market", market_id),
+        })?;
+    let within_bps = query.within_bps.unwrap_or(DEFAULT_RISK_WITHIN_BPS) as i64;
+
+    // Nothing is liquidated until the market has been marked
+    let mut positions = Vec::new();
+    if let Some(mark_price) = perpetual.mark_price {
+        for position in state.db.list_positions_by_market(&market_id).await? {
+            if position.size == 0 {
+                continue;
+            }
+            let (mode, healths) = account_healths(&state, &position.user_address).await?;
+            let Some((_, _, health)) = healths
+                .into_iter()
+                .find(|(_, market_ids, _)| market_ids.contains(&market_id))
+            else {
+                continue;
+            };
+            let Some(margin_ratio_bps) = health.margin_ratio_bps() else {
+                continue;
+            };
+            if margin_ratio_bps >= 10_000 + within_bps {
+                continue;
+            }
+            let liquidation_price = margin::liquidation_limit_price(
+                &position,
+                mark_price,
+                perpetual.maintenance_margin_bps,
+                market.tick_size,
+            )
+            .ok_or(ExchangeError::OrderValueOverflow)?;
+
+            positions.push(ApiPositionRisk {
+                size: position.size.to_string(),
+                margin_mode: mode,
+                equity: health.equity.to_string(),
+                maintenance_margin: health.maintenance_margin.to_string(),
+                margin_ratio_bps,
+                liquidation_price: liquidation_price.to_string(),
+                liquidatable: health.is_liquidatable(),
+            });
+        }
+    }
+    positions.sort_by_key(|position| position.margin_ratio_bps);
+
+    Ok(Json(MarketRiskResponse {
+        market_id,
+        mark_price: perpetual.mark_price.map(|price| price.to_string()),
+        positions,
+    }))
+}
+
+/// Health of a user's open positions with the quote token and markets each
+/// covers: one per isolated position, or one per quote token when cross-margined
+async fn account_healths(
+    state: &AppState,
+    address: &str,
+) -> Result<(MarginMode, Vec<(String, Vec<String>, margin::Health)>)> {
+    let mode = state.db.get_margin_mode(address).await?;
+    let perpetuals: std::collections::HashMap<String, _> = state
+        .db
+        .list_perpetual_markets()
+        .await?
+        .into_iter()
+        .map(|market| (market.market_id.clone(), market))
+        .collect();
+    let base_decimals = state.db.get_base_decimals_by_market().await?;
+
+    // Health of each open position, with the token it is margined in
+    let mut positions = Vec::new();
+    for position in state.db.list_positions_by_user(address).await? {
+        let (Some(perpetual), Some(&decimals)) = (
+            perpetuals.get(&position.market_id),
+            base_decimals.get(&position.market_id),
+        ) else {
+            continue;
+        };
+        if position.size == 0 {
+            continue;
+        }
+        let market = state.db.get_market(&position.market_id).await?;
+        let health = margin::position_health(
+            &position,
+            perpetual.mark_price.unwrap_or(position.entry_price),
+            10u128.pow(decimals as u32),
+            perpetual.maintenance_margin_bps,
+        )
+        .ok_or(ExchangeError::OrderValueOverflow)?;
+        positions.push((market.quote_ticker, position.market_id, health));
+    }
+
+    let healths = match mode {
+        MarginMode::Isolated => positions
+            .into_iter()
+            .map(|(token_ticker, market_id, health)| (token_ticker, vec![market_id], health))
+            .collect(),
+        MarginMode::Cross => {
+            let mut by_token: std::collections::BTreeMap<String, Vec<_>> = Default::default();
+            for (token_ticker, market_id, health) in positions {
+                by_token
+                    .entry(token_ticker)
+                    .or_default()
+                    .push((market_id, health));
+            }
+            let mut healths = Vec::new();
+            for (token_ticker, positions) in by_token {
+                let free = match state.db.get_balance(address, &token_ticker).await {
+                    Ok(balance) => balance.amount.saturating_sub(balance.open_interest),
+                    Err(ExchangeError::BalanceNotFound { .. }) => 0,
+                    Err(e) => return Err(e),
+                };
+                let (market_ids, positions): (Vec<_>, Vec<_>) = positions.into_iter().unzip();
+                let account = margin::account_health(free, positions)
+                    .ok_or(ExchangeError::OrderValueOverflow)?;
+                healths.push((token_ticker, market_ids, account));
+            }
+            healths
+        }
+    };
+
+    Ok((mode, healths))
+}
+
+fn margin_health(
+    token_ticker: String,
+    market_ids: Vec<String>,
+    health: margin::Health,
+) -> ApiMarginHealth {
+    ApiMarginHealth {
+        token_ticker,
+        market_ids,
+        equity: health.equity.to_string(),
+        maintenance_margin: health.maintenance_margin.to_string(),
+        liquidatable: health.is_liquidatable(),
+    }
+}
@@ -1,14 +1,17 @@
 use axum::{extract::State, response::Json};
 
-use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::api::amounts::Amounts;
+use crate::errors::{ErrorResponse, Result};
 use crate::models::api::{DripRequest, DripResponse};
-use crate::models::domain::EngineEvent;
 
 /// Drip tokens to users (testing/development faucet)
 #[utoipa::path(
     post,
     path = "/api/drip",
     request_body = DripRequest,
+    params(
+        ("X-Amount-Format" = Option<String>, Header, description = "`atoms` (default) or `decimal`: with `decimal`, the amount and new balance are in whole tokens, using the token's decimals")
+    ),
     responses(
         (status = 200, description = "Tokens dripped successfully", body = DripResponse),
         (status = 400, description = "Invalid request parameters", body = ErrorResponse),
@@ -20,6 +23,7 @@ use crate::models::domain::EngineEvent;
 )]
 pub async fn drip(
     State(state): State<crate::AppState>,
+    mut amounts: Amounts,
     Json(request): Json<DripRequest>,
 ) -> Result<Json<DripResponse>> {
     match request {
@@ -32,9 +36,7 @@ pub async fn drip(
             // TODO: Verify signature (skip for dev/test faucet)
 
             // Parse amount from string to u128
-            let amount_value = amount
-                .parse::<u128>()
-                .map_err(|_| ExchangeError::InvalidAmount)?;
+            let amount_value = amounts.parse_amount(&token_ticker, &amount).await?;
 
             // Check token exists
             state.db.get_token(&token_ticker).await?;
@@ -48,16 +50,24 @@ pub async fn drip(
                 .add_balance(&user_address, &token_ticker, amount_value)
                 .await?;
 
-            // Broadcast balance update to WebSocket clients
-            let _ = state.event_tx.send(EngineEvent::BalanceUpdated {
-                balance: new_balance.clone(),
-            });
+            // Announce the credit; it reaches WebSocket clients as a balance update
+            if let Err(e) = state
+                .db
+                .notify_balance_changed(&user_address, &token_ticker)
+                .await
+            {
+                log::warn!(
+                    "Failed to announce faucet credit to {}: {}",
+                    user_address,
+                    e
+                );
+            }
 
             Ok(Json(DripResponse::Faucet {
+                amount: amounts.amount(&token_ticker, amount_value).await?,
+                new_balance: amounts.amount(&token_ticker, new_balance.amount).await?,
                 user_address,
                 token_ticker,
-                amount,
-                new_balance: new_balance.amount.to_string(),
             }))
         }
     }
@@ -0,0 +1,67 @@
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::{ApiPredictionEvent, EventsResponse};
+use crate::models::domain::EventStatus;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Query parameters for listing prediction events
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct EventsQuery {
+    /// Only events with this status (default: all)
+    pub status: Option<EventStatus>,
+}
+
+/// List prediction events and their outcome markets, newest first
+///
+/// GET /api/events
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "Events retrieved successfully", body = EventsResponse),
+        (status = 400, description = "Invalid parameters"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventsResponse>> {
+    let events = state.db.list_events(query.status).await?;
+
+    Ok(Json(EventsResponse {
+        events: events.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Get a prediction event and its outcome markets
+///
+/// GET /api/events/{event_id}
+#[utoipa::path(
+    get,
+    path = "/api/events/{event_id}",
+    params(
+        ("event_id" = String, Path, description = "Event ID (e.g. us-election-2028)")
+    ),
+    responses(
+        (status = 200, description = "Event retrieved successfully", body = ApiPredictionEvent),
+        (status = 404, description = "Event not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn get_event(
+    State(state): State<AppState>,
+    Path(event_id): Path<String>,
+) -> Result<Json<ApiPredictionEvent>> {
+    let event = state.db.get_event(&event_id).await?;
+
+    Ok(Json(event.into()))
+}
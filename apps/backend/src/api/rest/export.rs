@@ -0,0 +1,357 @@
+use super::average_price::TimeRangeQuery;
+use crate::db::Db;
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{ApiExportJob, ExportFormat, ExportJobStatus};
+use crate::AppState;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use clickhouse::query::BytesCursor;
+use futures::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::RwLock;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+/// Most fills streamed straight back; larger exports run as a background job
+pub const MAX_STREAMED_EXPORT_ROWS: u64 = 50_000;
+
+/// Most fills written by an export job
+pub const MAX_EXPORT_ROWS: u64 = 5_000_000;
+
+/// How long an export job and its file are kept, counted from its start
+pub const EXPORT_JOB_TTL_SECS: i64 = 60 * 60;
+
+/// Bytes read from an export file per streamed chunk
+const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Query parameters for a fills export
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExportQuery {
+    /// File format (default: csv)
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Unix timestamp in seconds
+    pub from: i64,
+    /// Unix timestamp in seconds
+    pub to: i64,
+}
+
+struct ExportJob {
+    info: ApiExportJob,
+    path: PathBuf,
+}
+
+/// Background fills exports, shared by all handlers
+///
+/// Files are written to `dir` and removed once their job expires. Jobs live in
+/// memory, so a restart forgets them (their files are left for the OS to clean).
+#[derive(Clone)]
+pub struct ExportJobs {
+    dir: PathBuf,
+    jobs: Arc<RwLock<HashMap<Uuid, ExportJob>>>,
+}
+
+impl Default for ExportJobs {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join("exchange-exports"))
+    }
+}
+
+impl ExportJobs {
+    /// Keep export files in `dir`, which is created on first use
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            jobs: Default::default(),
+        }
+    }
+
+    /// Start exporting a user's fills in the background
+    async fn start(
+        &self,
+        db: Db,
+        user_address: String,
+        format: ExportFormat,
+        from: i64,
+        to: i64,
+    ) -> ApiExportJob {
+        self.remove_expired().await;
+
+        let job_id = Uuid::new_v4();
+        let info = ApiExportJob {
+            job_id: job_id.to_string(),
+            user_address,
+            format,
+            status: ExportJobStatus::Pending,
+            download_url: format!("/api/exports/{}/download", job_id),
+            error: None,
+            from,
+            to,
+            expires_at: Utc::now().timestamp() + EXPORT_JOB_TTL_SECS,
+        };
+        let path = self.dir.join(format!("{}.{}", job_id, format));
+        self.jobs.write().await.insert(
+            job_id,
+            ExportJob {
+                info: info.clone(),
+                path: path.clone(),
+            },
+        );
+
+        let jobs = self.clone();
+        let job = info.clone();
+        tokio::spawn(async move {
+            let result = write_export(&db, &path, &job).await;
+            if let Err(e) = &result {
+                log::error!("Fills export {} failed: {:#}", job.job_id, e);
+                let _ = tokio::fs::remove_file(&path).await;
+            }
+            if let Some(entry) = jobs.jobs.write().await.get_mut(&job_id) {
+                match result {
+                    Ok(()) => entry.info.status = ExportJobStatus::Ready,
+                    Err(_) => {
+                        entry.info.status = ExportJobStatus::Failed;
+                        entry.info.error = Some("Export failed, please try again".to_string());
+                    }
+                }
+            }
+        });
+
+        info
+    }
+
+    /// Look up a job that hasn't expired
+    async fn get(&self, job_id: &Uuid) -> Option<(ApiExportJob, PathBuf)> {
+        let jobs = self.jobs.read().await;
+        jobs.get(job_id)
+            .filter(|job| job.info.expires_at > Utc::now().timestamp())
+            .map(|job| (job.info.clone(), job.path.clone()))
+    }
+
+    async fn remove_expired(&self) {
+        let now = Utc::now().timestamp();
+        let mut expired = Vec::new();
+        self.jobs.write().await.retain(|_, job| {
+            let keep = job.info.expires_at > now;
+            if !keep {
+                expired.push(job.path.clone());
+            }
+            keep
+        });
+        for path in expired {
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+    }
+}
+
+async fn write_export(db: &Db, path: &std::path::Path, job: &ApiExportJob) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    let mut cursor = db.export_user_fills(
+        &job.user_address,
+        job.from,
+        job.to,
+        job.format,
+        MAX_EXPORT_ROWS,
+    )?;
+    let mut file = tokio::fs::File::create(path).await?;
+    while let Some(chunk) = cursor.next().await? {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    Ok(())
+}
+
+fn content_type(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "text/csv",
+        ExportFormat::Parquet => "application/vnd.apache.parquet",
+    }
+}
+
+fn file_response(
+    format: ExportFormat,
+    filename: String,
+    body: impl Stream<Item = std::result::Result<Bytes, impl Into<axum::BoxError>>> + Send + 'static,
+) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type(format).to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+/// Yield the rest of a ClickHouse cursor, stopping after the first error
+fn cursor_stream(
+    cursor: BytesCursor,
+) -> impl Stream<Item = std::result::Result<Bytes, clickhouse::error::Error>> {
+    stream::unfold(Some(cursor), |cursor| async move {
+        let mut cursor = cursor?;
+        match cursor.next().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), Some(cursor))),
+            Ok(None) => None,
+            Err(e) => Some((Err(e), None)),
+        }
+    })
+}
+
+/// Export a user's fills for accounting and tax reporting
+///
+/// GET /api/users/{address}/fills/export
+///
+/// Up to `MAX_STREAMED_EXPORT_ROWS` fills are streamed back as a file. Larger
+/// ranges answer 202 with a job to poll at `/api/exports/{job_id}`; the file
+/// is then downloaded from the job's `download_url`.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/fills/export",
+    params(
+        ("address" = String, Path, description = "User address"),
+        ExportQuery
+    ),
+    responses(
+        (status = 200, description = "The fills as a CSV or Parquet file", body = String, content_type = "text/csv"),
+        (status = 202, description = "Too many fills to stream, exporting in the background", body = ApiExportJob),
+        (status = 400, description = "Invalid time range or format", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn export_fills(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response> {
+    let (from, to) = (query.from, query.to);
+    TimeRangeQuery { from, to }.validate()?;
+    state.db.get_user(&address).await?;
+
+    if state.db.count_user_fills(&address, from, to).await? > MAX_STREAMED_EXPORT_ROWS {
+        let job = state
+            .exports
+            .start(state.db.clone(), address, query.format, from, to)
+            .await;
+        return Ok((StatusCode::ACCEPTED, Json(job)).into_response());
+    }
+
+    let mut cursor =
+        state
+            .db
+            .export_user_fills(&address, from, to, query.format, MAX_STREAMED_EXPORT_ROWS)?;
+    // Read the first chunk before answering, so a failing query still gets an error response
+    let first = cursor.next().await?;
+    let body = stream::iter(first.map(Ok)).chain(cursor_stream(cursor));
+
+    Ok(file_response(
+        query.format,
+        format!("fills-{}-{}.{}", from, to, query.format),
+        body,
+    ))
+}
+
+/// Get the status of a background fills export
+///
+/// GET /api/exports/{job_id}
+#[utoipa::path(
+    get,
+    path = "/api/exports/{job_id}",
+    params(
+        ("job_id" = String, Path, description = "Export job ID")
+    ),
+    responses(
+        (status = 200, description = "Export job status", body = ApiExportJob),
+        (status = 404, description = "Export not found or expired", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn export_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<ApiExportJob>> {
+    let id = Uuid::parse_str(&job_id)?;
+    let (job, _) = state
+        .exports
+        .get(&id)
+        .await
+        .ok_or(ExchangeError::ExportNotFound { job_id })?;
+
+    Ok(Json(job))
+}
+
+/// Download the file of a finished fills export
+///
+/// GET /api/exports/{job_id}/download
+#[utoipa::path(
+    get,
+    path = "/api/exports/{job_id}/download",
+    params(
+        ("job_id" = String, Path, description = "Export job ID")
+    ),
+    responses(
+        (status = 200, description = "The fills as a CSV or Parquet file", body = String, content_type = "text/csv"),
+        (status = 404, description = "Export not found or expired", body = ErrorResponse),
+        (status = 409, description = "Export still running or failed", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn download_export(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Response> {
+    let id = Uuid::parse_str(&job_id)?;
+    let Some((job, path)) = state.exports.get(&id).await else {
+        return Err(ExchangeError::ExportNotFound { job_id });
+    };
+    if job.status != ExportJobStatus::Ready {
+        return Err(ExchangeError::ExportNotReady {
+            job_id,
+            status: job.status,
+        });
+    }
+
+    let file = tokio::fs::File::open(&path).await.map_err(|e| {
+        log::error!("Failed to open export {}: {}", job_id, e);
+        ExchangeError::ExportNotFound {
+            job_id: job_id.clone(),
+        }
+    })?;
+    let body = stream::unfold(Some(file), |file| async move {
+        let mut file = file?;
+        let mut chunk = Vec::with_capacity(DOWNLOAD_CHUNK_SIZE);
+        match (&mut file)
+            .take(DOWNLOAD_CHUNK_SIZE as u64)
+            .read_to_end(&mut chunk)
+            .await
+        {
+            Ok(0) => None,
+            Ok(_) => Some((Ok(Bytes::from(chunk)), Some(file))),
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+
+    Ok(file_response(
+        job.format,
+        format!("fills-{}-{}.{}", job.from, job.to, job.format),
+        body,
+    ))
+}
@@ -0,0 +1,71 @@
+use crate::engine::fees::FeeOverrides;
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::{ApiUserFees, UserFeesResponse};
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+
+/// Volume window reported alongside a user's fees
+const VOLUME_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Get the fees a user pays in every market
+///
+/// GET /api/users/{address}/fees
+///
+/// Returns the maker and taker fees the engine charges the user, the market
+/// schedule they derive from, any override set for the user, and their maker
+/// and taker volume over the last 30 days. There are no volume tiers: fees
+/// only differ from the market's when an admin has set an override.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/fees",
+    params(
+        ("address" = String, Path, description = "User address")
+    ),
+    responses(
+        (status = 200, description = "Fees retrieved successfully", body = UserFeesResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn user_fees(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<UserFeesResponse>> {
+    state.db.get_user(&address).await?;
+
+    let markets = state.db.list_markets().await?;
+    let mut overrides = state.db.get_user_fee_overrides(&address).await?;
+    let volumes = state
+        .db
+        .get_user_volumes(&address, Utc::now().timestamp() - VOLUME_WINDOW_SECS)
+        .await?;
+
+    // Resolve fees the way the engine does, so the two cannot disagree
+    let fees = FeeOverrides::new(overrides.values().cloned().collect());
+
+    Ok(Json(UserFeesResponse {
+        markets: markets
+            .into_iter()
+            .map(|market| {
+                let (maker_volume, taker_volume) =
+                    volumes.get(&market.id).copied().unwrap_or_default();
+                ApiUserFees {
+                    maker_fee_bps: fees.maker_fee_bps(&market, &address),
+                    taker_fee_bps: fees.taker_fee_bps(&market, &address),
+                    market_maker_fee_bps: market.maker_fee_bps,
+                    market_taker_fee_bps: market.taker_fee_bps,
+                    fee_override: overrides.remove(&market.id),
+                    maker_volume_30d: maker_volume.to_string(),
+                    taker_volume_30d: taker_volume.to_string(),
+                    market_id: market.id,
+                }
+            })
+            .collect(),
+        user_address: address,
+    }))
+}
@@ -0,0 +1,102 @@
+use super::average_price::TimeRangeQuery;
+use crate::db::candles::interval_secs;
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{ApiMakerVolume, ApiTakerFlow, FlowAnalyticsResponse};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Most taker flow buckets served in one request
+const MAX_FLOW_BUCKETS: i64 = 5_000;
+
+/// Query parameters for flow analytics
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FlowQuery {
+    /// Unix timestamp in seconds
+    pub from: i64,
+    /// Unix timestamp in seconds
+    pub to: i64,
+    /// Taker flow bucket width: 1m, 5m, 15m, 1h (default) or 1d
+    pub interval: Option<String>,
+}
+
+/// Get a market's maker/taker breakdown over a time range
+///
+/// GET /api/markets/{market_id}/flow
+///
+/// Reports each trader's maker ratio and the taker buy/sell imbalance per
+/// time bucket, for the market-maker program.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/flow",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        FlowQuery
+    ),
+    responses(
+        (status = 200, description = "Flow analytics computed successfully", body = FlowAnalyticsResponse),
+        (status = 400, description = "Invalid time range or interval", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn flow_analytics(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(params): Query<FlowQuery>,
+) -> Result<Json<FlowAnalyticsResponse>> {
+    let market_id = state.symbols.canonical(&market_id);
+    let (from, to) = (params.from, params.to);
+    TimeRangeQuery { from, to }.validate()?;
+    let interval = params.interval.unwrap_or_else(|| "1h".to_string());
+    let Some(bucket_secs) = interval_secs(&interval) else {
+        return Err(ExchangeError::InvalidParameter {
+            message: "Invalid interval. Must be one of: 1m, 5m, 15m, 1h, 1d".to_string(),
+        });
+    };
+    if (to - from) / bucket_secs as i64 > MAX_FLOW_BUCKETS {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Time range spans more than {} {} buckets",
+                MAX_FLOW_BUCKETS, interval
+            ),
+        });
+    }
+    state.db.get_market(&market_id).await?;
+
+    let makers = state.db.get_maker_volumes(&market_id, from, to).await?;
+    let taker_flow = state
+        .db
+        .get_taker_flow(&market_id, bucket_secs, from, to)
+        .await?;
+
+    Ok(Json(FlowAnalyticsResponse {
+        market_id,
+        interval,
+        makers: makers
+            .into_iter()
+            .map(|m| ApiMakerVolume {
+                maker_ratio_bps: m.maker_ratio_bps(),
+                user_address: m.user_address,
+                maker_volume: m.maker_volume.to_string(),
+                taker_volume: m.taker_volume.to_string(),
+            })
+            .collect(),
+        taker_flow: taker_flow
+            .into_iter()
+            .map(|bucket| ApiTakerFlow {
+                timestamp: bucket.timestamp.timestamp(),
+                imbalance_bps: bucket.imbalance_bps(),
+                buy_volume: bucket.buy_volume.to_string(),
+                sell_volume: bucket.sell_volume.to_string(),
+            })
+            .collect(),
+        from,
+        to,
+    }))
+}
@@ -0,0 +1,68 @@
+use super::average_price::TimeRangeQuery;
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{ApiIndexPrice, IndexPriceHistoryResponse};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+/// Longest time range served in one request
+const MAX_INDEX_HISTORY_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Get a market's external index prices over a time range
+///
+/// GET /api/markets/{market_id}/index-prices
+///
+/// Markets without an index feed always return no prices.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/index-prices",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        TimeRangeQuery
+    ),
+    responses(
+        (status = 200, description = "Index price history retrieved successfully", body = IndexPriceHistoryResponse),
+        (status = 400, description = "Invalid time range", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn index_price_history(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(range): Query<TimeRangeQuery>,
+) -> Result<Json<IndexPriceHistoryResponse>> {
+    let market_id = state.symbols.canonical(&market_id);
+    range.validate()?;
+    if range.to - range.from > MAX_INDEX_HISTORY_SECS {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Time range must not exceed {} seconds",
+                MAX_INDEX_HISTORY_SECS
+            ),
+        });
+    }
+    state.db.get_market(&market_id).await?;
+
+    let prices = state
+        .db
+        .get_index_prices(&market_id, range.from, range.to)
+        .await?;
+
+    Ok(Json(IndexPriceHistoryResponse {
+        market_id,
+        prices: prices
+            .into_iter()
+            .map(|price| ApiIndexPrice {
+                timestamp: price.timestamp.timestamp(),
+                price: price.price.to_string(),
+                source: price.source,
+            })
+            .collect(),
+        from: range.from,
+        to: range.to,
+    }))
+}
@@ -1,13 +1,21 @@
 use axum::{extract::State, response::Json};
 
+use crate::api::amounts::Amounts;
+use crate::cache::{keys, LISTINGS_TTL};
 use crate::errors::{ErrorResponse, Result};
 use crate::models::api::{InfoRequest, InfoResponse};
 
 /// Get information about tokens, markets, etc.
+///
+/// Answers are cached briefly, and dropped when an admin adds a token or market.
+/// Market sizes are cached in atoms and converted per request.
 #[utoipa::path(
     post,
     path = "/api/info",
     request_body = InfoRequest,
+    params(
+        ("X-Amount-Format" = Option<String>, Header, description = "`atoms` (default) or `decimal`: with `decimal`, a market's tick size is returned in whole quote tokens and its lot and minimum size in whole base tokens")
+    ),
     responses(
         (status = 200, description = "Success", body = InfoResponse),
         (status = 400, description = "Invalid request", body = ErrorResponse),
@@ -18,28 +26,66 @@ use crate::models::api::{InfoRequest, InfoResponse};
 )]
 pub async fn info(
     State(_state): State<crate::AppState>,
-    Json(request): Json<InfoRequest>,
+    mut amounts: Amounts,
+    Json(mut request): Json<InfoRequest>,
 ) -> Result<Json<InfoResponse>> {
-    match request {
+    if let InfoRequest::MarketDetails { market_id } = &mut request {
+        *market_id = _state.symbols.canonical(market_id);
+    }
+    let key = match &request {
+        InfoRequest::TokenDetails { ticker } => keys::token(ticker),
+        InfoRequest::MarketDetails { market_id } => keys::market(market_id),
+        InfoRequest::AllMarkets => keys::MARKETS.to_string(),
+        InfoRequest::AllTokens => keys::TOKENS.to_string(),
+    };
+    let response = match _state.cache.get(&key).await {
+        Some(response) => response,
+        None => {
+            let response = lookup(&_state, request).await?;
+            _state.cache.set(&key, &response, LISTINGS_TTL).await;
+            response
+        }
+    };
+
+    Ok(Json(match response {
+        InfoResponse::MarketDetails { market } => InfoResponse::MarketDetails {
+            market: amounts.market_details(market).await?,
+        },
+        InfoResponse::AllMarkets { markets } => {
+            let mut converted = Vec::with_capacity(markets.len());
+            for market in markets {
+                converted.push(amounts.market_details(market).await?);
+            }
+            InfoResponse::AllMarkets { markets: converted }
+        }
+        response => response,
+    }))
+}
+
+async fn lookup(_state: &crate::AppState, request: InfoRequest) -> Result<InfoResponse> {
+    Ok(match request {
         InfoRequest::TokenDetails { ticker } => {
             let token = _state.db.get_token(&ticker).await?;
-            Ok(Json(InfoResponse::TokenDetails { token }))
+            InfoResponse::TokenDetails { token }
         }
         InfoRequest::MarketDetails { market_id } => {
             let market = _state.db.get_market(&market_id).await?;
-            Ok(Json(InfoResponse::MarketDetails {
-                market: market.into(),
-            }))
+            InfoResponse::MarketDetails {
+                market: _state.symbols.numbered(market.into()),
+            }
         }
         InfoRequest::AllMarkets => {
             let markets = _state.db.list_markets().await?;
-            Ok(Json(InfoResponse::AllMarkets {
-                markets: markets.into_iter().map(|m| m.into()).collect(),
-            }))
+            InfoResponse::AllMarkets {
+                markets: markets
+                    .into_iter()
+                    .map(|m| _state.symbols.numbered(m.into()))
+                    .collect(),
+            }
         }
         InfoRequest::AllTokens => {
             let tokens = _state.db.list_tokens().await?;
-            Ok(Json(InfoResponse::AllTokens { tokens }))
+            InfoResponse::AllTokens { tokens }
         }
-    }
+    })
 }
@@ -0,0 +1,87 @@
+use axum::{extract::State, http::HeaderMap, response::Json};
+use tokio::sync::oneshot;
+
+use crate::api::auth;
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{KillSwitchRequest, KillSwitchResponse};
+use crate::models::domain::EngineRequest;
+use crate::AppState;
+
+/// Put the exchange or a single market into cancel-only mode
+///
+/// POST /api/kill-switch
+///
+/// Requires `Authorization: Bearer <ADMIN_TOKEN>`; the endpoint rejects every
+/// request when no admin token is configured.
+#[utoipa::path(
+    post,
+    path = "/api/kill-switch",
+    request_body = KillSwitchRequest,
+    responses(
+        (status = 200, description = "Success", body = KillSwitchResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "admin"
+)]
+pub async fn kill_switch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<KillSwitchRequest>,
+) -> Result<Json<KillSwitchResponse>> {
+    auth::authorize_admin(&state, &headers)?;
+
+    match request {
+        KillSwitchRequest::Status => {
+            let switches = state.db.list_kill_switches().await?;
+
+            Ok(Json(KillSwitchResponse::Status { switches }))
+        }
+        KillSwitchRequest::Engage {
+            market_id,
+            reason,
+            cancel_orders,
+        } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::EngageKillSwitch {
+                    market_id,
+                    reason,
+                    cancel_orders,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let (switch, cancelled) = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(KillSwitchResponse::Engaged {
+                switch,
+                cancelled_orders: cancelled.count,
+            }))
+        }
+        KillSwitchRequest::Release { market_id } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::ReleaseKillSwitch {
+                    market_id: market_id.clone(),
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(KillSwitchResponse::Released { market_id }))
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{ApiL3Book, ApiL3Order};
+use crate::models::domain::{EngineRequest, Order, Side};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+/// Least time between two reads of the per-order book by one market maker
+pub const DEFAULT_L3_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Query parameters identifying the market maker
+///
+/// `signature` is the market maker's signature of the query sent to
+/// `/api/orderbook/{market_id}/l3`, with the market's canonical ID (`BTC/USDC`).
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+pub struct L3Query {
+    pub user_address: String,
+    pub signature: String,
+}
+
+/// Who may read the per-order book how often, and how its orders are disguised
+///
+/// Order IDs are replaced by a keyed hash, so makers can follow an order's
+/// place in the queue without learning its ID or owner. The key is drawn at
+/// startup: the stand-in IDs change when the server restarts.
+#[derive(Clone)]
+pub struct L3Access {
+    key: Arc<[u8; 32]>,
+    interval: Duration,
+    last_read: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl Default for L3Access {
+    fn default() -> Self {
+        Self::new(DEFAULT_L3_INTERVAL)
+    }
+}
+
+impl L3Access {
+    /// Let each market maker read the book once per `interval`
+    pub fn new(interval: Duration) -> Self {
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        key[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        Self {
+            key: Arc::new(key),
+            interval,
+            last_read: Default::default(),
+        }
+    }
+
+    /// Count a read by `user_address` at `now`, refusing it if their last was too recent
+    pub fn admit(&self, user_address: &str, now: Instant) -> Result<()> {
+        let mut last_read = self.last_read.lock().expect("L3 rate limiter poisoned");
+        let interval = self.interval;
+        last_read.retain(|_, read_at| now.duration_since(*read_at) < interval);
+
+        if let Some(read_at) = last_read.get(user_address) {
+            let wait = interval - now.duration_since(*read_at);
+            return Err(ExchangeError::RateLimited {
+                retry_after_ms: wait.as_millis().max(1) as u64,
+            });
+        }
+        last_read.insert(user_address.to_string(), now);
+        Ok(())
+    }
+
+    /// The stand-in ID shown for an order
+    pub fn anonymize(&self, order_id: &Uuid) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key[..]).expect("HMAC accepts keys of any size");
+        mac.update(order_id.as_bytes());
+        hex::encode(&mac.finalize().into_bytes()[..16])
+    }
+
+    /// A market's per-order book from its resting orders, each price level's
+    /// in queue order
+    pub fn book(&self, market_id: &str, orders: &[Order], now: DateTime<Utc>) -> ApiL3Book {
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        for order in orders {
+            let remaining = order.size.saturating_sub(order.filled_size);
+            if remaining == 0 {
+                continue;
+            }
+            let side = match order.side {
+                Side::Buy => &mut bids,
+                Side::Sell => &mut asks,
+            };
+            side.push((order.price, self.level_order(order, remaining, now)));
+        }
+
+        // Stable sorts keep time priority within a price
+        bids.sort_by_key(|(price, _)| std::cmp::Reverse(*price));
+        asks.sort_by_key(|(price, _)| *price);
+
+        ApiL3Book {
+            market_id: market_id.to_string(),
+            bids: bids.into_iter().map(|(_, order)| order).collect(),
+            asks: asks.into_iter().map(|(_, order)| order).collect(),
+            timestamp: now.timestamp_millis(),
+        }
+    }
+
+    fn level_order(&self, order: &Order, remaining: u128, now: DateTime<Utc>) -> ApiL3Order {
+        ApiL3Order {
+            id: self.anonymize(&order.id),
+            price: order.price.to_string(),
+            size: remaining.to_string(),
+            age_ms: (now - order.created_at).num_milliseconds().max(0),
+        }
+    }
+}
+
+/// Get every resting order of a market (approved market makers only)
+///
+/// GET /api/orderbook/{market_id}/l3
+///
+/// Individual orders rather than price levels, with their remaining size and
+/// how long they've rested, as the matching engine holds them. Order IDs are
+/// disguised. Reads are signed (see [`L3Query`]), and each market maker can
+/// read the book once a second; faster reads are refused with 429.
+#[utoipa::path(
+    get,
+    path = "/api/orderbook/{market_id}/l3",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID"),
+        L3Query
+    ),
+    responses(
+        (status = 200, description = "Per-order book retrieved successfully", body = ApiL3Book),
+        (status = 401, description = "Invalid signature", body = ErrorResponse),
+        (status = 403, description = "Not an approved market maker", body = ErrorResponse),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 429, description = "Read too soon after the last one", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn l3_orderbook(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+    Query(query): Query<L3Query>,
+) -> Result<Json<ApiL3Book>> {
+    let market_id = state.symbols.canonical(&market_id);
+
+    state.signatures.verify(
+        &format!("/api/orderbook/{}/l3", market_id),
+        &query,
+        &query.user_address,
+        &query.signature,
+    )?;
+    if !state.db.is_market_maker(&query.user_address).await? {
+        return Err(ExchangeError::NotMarketMaker {
+            user_address: query.user_address,
+        });
+    }
+    state.l3.admit(&query.user_address, Instant::now())?;
+
+    // The engine's book, not the database, which trails it while fills are persisted
+    let market = state.db.get_market(&market_id).await?;
+    let (response_tx, response_rx) = oneshot::channel();
+    state
+        .engine_tx
+        .send(EngineRequest::RestingOrders {
+            market_id: market.id.clone(),
+            response_tx,
+        })
+        .await
+        .map_err(|_| ExchangeError::EngineSendFailed)?;
+    let orders = response_rx
+        .await
+        .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+    Ok(Json(state.l3.book(&market.id, &orders, Utc::now())))
+}
@@ -0,0 +1,83 @@
+use crate::analytics::leaderboard;
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::{
+    ApiLeaderboardEntry, LeaderboardMetric, LeaderboardPeriod, LeaderboardResponse,
+};
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Query parameters for the leaderboard
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LeaderboardQuery {
+    /// Period to rank over (default: 24h)
+    #[serde(default)]
+    pub period: LeaderboardPeriod,
+    /// What to rank by (default: volume)
+    #[serde(default)]
+    pub metric: LeaderboardMetric,
+    /// Show only opted-in display names, never addresses
+    #[serde(default)]
+    pub hide_addresses: bool,
+    /// Number of traders to return (default: 100, max: 1000)
+    pub limit: Option<u32>,
+}
+
+/// Get the top traders by volume or realized PnL
+///
+/// GET /api/leaderboard
+///
+/// Values are in quote token atoms, summed over every market.
+#[utoipa::path(
+    get,
+    path = "/api/leaderboard",
+    params(LeaderboardQuery),
+    responses(
+        (status = 200, description = "Leaderboard retrieved successfully", body = LeaderboardResponse),
+        (status = 400, description = "Invalid parameters"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardResponse>> {
+    let from = Utc::now().timestamp() - query.period.seconds();
+    let base_decimals = state.db.get_base_decimals_by_market().await?;
+
+    let values = match query.metric {
+        LeaderboardMetric::Volume => {
+            let notionals = state.db.get_trader_notionals(from).await?;
+            leaderboard::volumes(notionals, &base_decimals)
+        }
+        LeaderboardMetric::Pnl => {
+            let fills = state.db.get_active_trader_fills(from).await?;
+            let from = DateTime::from_timestamp(from, 0).unwrap_or(DateTime::UNIX_EPOCH);
+            leaderboard::realized_pnl_since(fills, from, &base_decimals)
+        }
+    };
+    let ranked = leaderboard::rank(values, query.limit.unwrap_or(100).min(1000) as usize);
+
+    let addresses: Vec<_> = ranked.iter().map(|(_, user, _)| user.clone()).collect();
+    let mut display_names = state.db.get_display_names(&addresses).await?;
+
+    Ok(Json(LeaderboardResponse {
+        period: query.period,
+        metric: query.metric,
+        entries: ranked
+            .into_iter()
+            .map(|(rank, user_address, value)| ApiLeaderboardEntry {
+                rank,
+                display_name: display_names.remove(&user_address),
+                user_address: (!query.hide_addresses).then_some(user_address),
+                value: value.to_string(),
+            })
+            .collect(),
+    }))
+}
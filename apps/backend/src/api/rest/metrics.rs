@@ -0,0 +1,24 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::AppState;
+
+/// Engine backlog, saturation and database metrics in the Prometheus text format
+///
+/// Engine queue and ClickHouse trade buffer depths, engine events skipped by
+/// each broadcast consumer, and whether market-data work is being shed.
+/// Queue depths are sampled once a second in the process running the engine.
+/// Database metrics cover the connection pool, latency of tagged queries and
+/// batches written to each ClickHouse table by this process.
+#[utoipa::path(
+    get,
+    path = "/api/metrics",
+    responses(
+        (status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain")
+    )
+)]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.saturation.render_metrics() + &state.db.render_metrics(),
+    )
+}
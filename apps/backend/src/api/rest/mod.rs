@@ -8,10 +8,28 @@ use utoipa_swagger_ui::SwaggerUi;
 use crate::models::ApiResponse;
 
 pub mod admin;
+pub mod average_price;
 pub mod candles;
+pub mod depth;
+pub mod derivatives;
 pub mod drip;
+pub mod events;
+pub mod export;
+pub mod fees;
+pub mod flow;
 pub mod health;
+pub mod index_prices;
 pub mod info;
+pub mod kill_switch;
+pub mod l3;
+pub mod leaderboard;
+pub mod metrics;
+pub mod oco;
+pub mod pnl;
+pub mod rfq;
+pub mod statements;
+pub mod stats;
+pub mod top_of_book;
 pub mod trade;
 pub mod user;
 
@@ -24,12 +42,40 @@ pub mod user;
     ),
     paths(
         health::health_check,
+        metrics::metrics,
         info::info,
         user::user,
         trade::trade,
+        oco::oco,
         drip::drip,
+        rfq::rfq,
         admin::admin_handler,
+        kill_switch::kill_switch,
         candles::candles,
+        stats::market_stats,
+        top_of_book::top_of_book,
+        l3::l3_orderbook,
+        pnl::user_pnl,
+        fees::user_fees,
+        statements::user_statements,
+        statements::user_statement,
+        leaderboard::leaderboard,
+        average_price::vwap,
+        average_price::twap,
+        depth::depth_history,
+        flow::flow_analytics,
+        derivatives::open_interest_history,
+        derivatives::funding_history,
+        derivatives::perpetual_market,
+        derivatives::user_positions,
+        derivatives::margin_account,
+        derivatives::market_risk,
+        index_prices::index_price_history,
+        events::list_events,
+        events::get_event,
+        export::export_fills,
+        export::export_job,
+        export::download_export,
     ),
     components(
         schemas(
@@ -45,26 +91,135 @@ pub mod user;
             // Trade types
             crate::models::api::TradeRequest,
             crate::models::api::TradeResponse,
+            crate::models::api::OrderPlaced,
+            crate::models::api::OrderCancelled,
+            crate::models::api::OrdersCancelled,
+            // OCO types
+            crate::models::api::OcoRequest,
+            crate::models::api::OcoLeg,
+            crate::models::api::OcoPlaced,
             // Drip types
             crate::models::api::DripRequest,
             crate::models::api::DripResponse,
+            // RFQ types
+            crate::models::api::RfqRequest,
+            crate::models::api::RfqResponse,
+            crate::models::api::ApiQuoteRequest,
+            crate::models::api::ApiQuote,
+            crate::models::domain::RfqStatus,
             // Admin types
             crate::models::api::AdminRequest,
             crate::models::api::AdminResponse,
+            // Kill switch types
+            crate::models::api::KillSwitchRequest,
+            crate::models::api::KillSwitchResponse,
+            crate::models::domain::KillSwitch,
             // Candles types
             crate::models::api::CandlesRequest,
             crate::models::api::ApiCandle,
             crate::models::api::CandlesResponse,
+            // Market stats types
+            crate::models::api::ApiMarketStats,
+            crate::models::api::ApiAveragePrice,
+            // Top of book types
+            crate::models::api::ApiTopOfBook,
+            crate::models::api::ApiBookLevel,
+            // Per-order book types
+            crate::models::api::ApiL3Order,
+            crate::models::api::ApiL3Book,
+            // Depth metrics types
+            crate::models::api::ApiDepthSample,
+            crate::models::api::DepthHistoryResponse,
+            // Flow analytics types
+            crate::models::api::ApiMakerVolume,
+            crate::models::api::ApiTakerFlow,
+            crate::models::api::FlowAnalyticsResponse,
+            crate::models::domain::LiquidityRole,
+            // Derivatives types
+            crate::models::api::ApiOpenInterest,
+            crate::models::api::OpenInterestHistoryResponse,
+            crate::models::api::ApiFundingRate,
+            crate::models::api::FundingHistoryResponse,
+            crate::models::api::ApiPerpetualMarket,
+            crate::models::api::ApiPosition,
+            crate::models::api::PositionsResponse,
+            crate::models::api::ApiMarginHealth,
+            crate::models::api::MarginAccountResponse,
+            crate::models::api::ApiPositionRisk,
+            crate::models::api::MarketRiskResponse,
+            crate::models::api::ApiLiquidation,
+            // Index price types
+            crate::models::api::ApiIndexPrice,
+            crate::models::api::IndexPriceHistoryResponse,
+            // Prediction event types
+            crate::models::api::ApiPredictionEvent,
+            crate::models::api::ApiEventOutcome,
+            crate::models::api::ApiEventOutcomeSpec,
+            crate::models::api::EventsResponse,
+            crate::models::domain::EventStatus,
+            // Export types
+            crate::models::api::ExportFormat,
+            crate::models::api::ExportJobStatus,
+            crate::models::api::ApiExportJob,
+            // Fee types
+            crate::models::api::ApiUserFees,
+            crate::models::api::UserFeesResponse,
+            crate::models::domain::FeeOverride,
+            // Statement types
+            crate::models::api::ApiStatement,
+            crate::models::api::ApiStatementBalance,
+            crate::models::api::ApiStatementMarket,
+            crate::models::api::StatementsResponse,
+            // PnL types
+            crate::models::api::ApiMarketPnl,
+            crate::models::api::UserPnlResponse,
+            crate::models::domain::CostBasisMethod,
+            // Leaderboard types
+            crate::models::api::LeaderboardPeriod,
+            crate::models::api::LeaderboardMetric,
+            crate::models::api::ApiLeaderboardEntry,
+            crate::models::api::LeaderboardResponse,
             // API types (only expose API layer in OpenAPI, not domain)
             crate::models::domain::Token,
             crate::models::api::ApiMarket,
+            crate::models::api::ApiPriceLadder,
             crate::models::api::ApiOrder,
             crate::models::api::ApiTrade,
             crate::models::api::ApiBalance,
+            crate::models::api::ApiUserLimits,
+            crate::models::api::ApiOpenOrderUsage,
+            crate::models::api::ApiQueuePosition,
+            crate::models::api::ApiTokenAmount,
+            crate::models::api::ApiUserSummary,
+            crate::models::api::ApiRebateTotal,
+            crate::models::api::ApiReferralEarnings,
+            crate::models::api::ApiWebhook,
+            crate::models::api::ApiWebhookDeadLetter,
+            crate::models::api::ApiDeposit,
+            crate::models::api::ApiWithdrawal,
+            crate::models::api::ApiSubAccount,
+            crate::models::api::ApiAccountTransfer,
+            crate::models::api::ApiAggregateBalance,
+            crate::models::api::ApiKeyDetails,
+            crate::models::domain::ApiKeyScope,
+            crate::models::domain::WithdrawalStatus,
+            crate::models::domain::MarginMode,
+            crate::models::domain::Referral,
+            crate::models::api::ApiLedgerEntry,
+            crate::models::api::ApiSurveillanceAlert,
+            crate::models::domain::FeeRoute,
             // Enums are shared between API and domain
             crate::models::domain::Side,
             crate::models::domain::OrderType,
             crate::models::domain::OrderStatus,
+            crate::models::domain::MarketStatus,
+            crate::models::domain::UserStatus,
+            crate::models::domain::CancelReason,
+            crate::models::domain::RejectReason,
+            crate::models::domain::SystemAccount,
+            crate::models::domain::RevenueSource,
+            crate::models::domain::LedgerEntryKind,
+            crate::models::domain::SurveillanceKind,
         )
     ),
     tags(
@@ -73,7 +228,8 @@ pub mod user;
         (name = "user", description = "User data endpoints"),
         (name = "trade", description = "Trading endpoints"),
         (name = "drip", description = "Get free money"),
-        (name = "admin", description = "Admin operations (test/dev only)"),
+        (name = "rfq", description = "Request-for-quote trading off the book"),
+        (name = "admin", description = "Admin operations"),
         (name = "candles", description = "OHLCV candle data")
     )
 )]
@@ -82,11 +238,75 @@ pub struct ApiDoc;
 pub fn create_rest() -> Router<crate::AppState> {
     Router::new()
         .route("/api/health", get(health::health_check))
+        .route("/api/metrics", get(metrics::metrics))
         .route("/api/info", post(info::info))
         .route("/api/user", post(user::user))
         .route("/api/trade", post(trade::trade))
+        .route("/api/oco", post(oco::oco))
         .route("/api/candles", post(candles::candles))
+        .route("/api/markets/{market_id}/stats", get(stats::market_stats))
+        .route(
+            "/api/markets/{market_id}/top-of-book",
+            get(top_of_book::top_of_book),
+        )
+        .route("/api/orderbook/{market_id}/l3", get(l3::l3_orderbook))
+        .route("/api/markets/{market_id}/vwap", get(average_price::vwap))
+        .route("/api/markets/{market_id}/twap", get(average_price::twap))
+        .route("/api/markets/{market_id}/depth", get(depth::depth_history))
+        .route("/api/markets/{market_id}/flow", get(flow::flow_analytics))
+        .route(
+            "/api/markets/{market_id}/open-interest",
+            get(derivatives::open_interest_history),
+        )
+        .route(
+            "/api/markets/{market_id}/funding",
+            get(derivatives::funding_history),
+        )
+        .route(
+            "/api/markets/{market_id}/perpetual",
+            get(derivatives::perpetual_market),
+        )
+        .route(
+            "/api/markets/{market_id}/risk",
+            get(derivatives::market_risk),
+        )
+        .route(
+            "/api/markets/{market_id}/index-prices",
+            get(index_prices::index_price_history),
+        )
+        .route("/api/users/{address}/pnl", get(pnl::user_pnl))
+        .route("/api/users/{address}/fees", get(fees::user_fees))
+        .route(
+            "/api/users/{address}/statements",
+            get(statements::user_statements),
+        )
+        .route(
+            "/api/users/{address}/statements/{month}",
+            get(statements::user_statement),
+        )
+        .route(
+            "/api/users/{address}/positions",
+            get(derivatives::user_positions),
+        )
+        .route(
+            "/api/users/{address}/margin",
+            get(derivatives::margin_account),
+        )
+        .route("/api/events", get(events::list_events))
+        .route("/api/events/{event_id}", get(events::get_event))
+        .route("/api/leaderboard", get(leaderboard::leaderboard))
+        .route(
+            "/api/users/{address}/fills/export",
+            get(export::export_fills),
+        )
+        .route("/api/exports/{job_id}", get(export::export_job))
+        .route(
+            "/api/exports/{job_id}/download",
+            get(export::download_export),
+        )
         .route("/api/drip", post(drip::drip))
+        .route("/api/rfq", post(rfq::rfq))
         .route("/api/admin", post(admin::admin_handler))
+        .route("/api/kill-switch", post(kill_switch::kill_switch))
         .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
 }
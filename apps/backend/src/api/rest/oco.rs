@@ -0,0 +1,129 @@
+use axum::{extract::State, response::Json, Extension};
+use chrono::Utc;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::api::amounts::Amounts;
+use crate::api::auth::{self, ApiKeyAuth};
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{OcoLeg, OcoPlaced, OcoRequest};
+use crate::models::domain::{ApiKeyScope, EngineRequest, Order, OrderStatus, Side, TimeInForce};
+
+/// Place two linked orders, the first fill on either cancelling the other
+///
+/// POST /api/oco
+///
+/// One order is a limit order that rests, the other a stop order waiting for
+/// its trigger, for the same side and size; a limit order that would fill on
+/// arrival is refused. The stop triggering cancels the limit order too. Only
+/// one of them can fill, so the pair locks the balance its larger order
+/// needs once, and counts once against the open-order cap.
+#[utoipa::path(
+    post,
+    path = "/api/oco",
+    request_body = OcoRequest,
+    params(
+        ("X-Amount-Format" = Option<String>, Header, description = "`atoms` (default) or `decimal`: with `decimal`, order prices are read and returned in whole quote tokens and sizes in whole base tokens, using each token's decimals")
+    ),
+    responses(
+        (status = 200, description = "Success", body = OcoPlaced),
+        (status = 400, description = "Invalid request parameters", body = ErrorResponse),
+        (status = 401, description = "Invalid signature or API key", body = ErrorResponse),
+        (status = 403, description = "API key lacks the trade scope", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "trade"
+)]
+#[tracing::instrument(name = "api.oco", skip_all)]
+pub async fn oco(
+    State(state): State<crate::AppState>,
+    auth: Option<Extension<ApiKeyAuth>>,
+    mut amounts: Amounts,
+    Json(request): Json<OcoRequest>,
+) -> Result<Json<OcoPlaced>> {
+    auth::authorize(
+        auth.as_deref(),
+        &request.user_address,
+        Some(ApiKeyScope::Trade),
+        || {
+            state.signatures.verify_trading(
+                "/api/oco",
+                &request,
+                &request.user_address,
+                &request.signature,
+            )
+        },
+    )?;
+    let OcoRequest {
+        user_address,
+        market_id,
+        side,
+        size,
+        first,
+        second,
+        signature: _,
+    } = request;
+
+    let market_id = state.symbols.canonical(&market_id);
+    let size = amounts.parse_size(&market_id, &size).await?;
+    let first = leg_order(&mut amounts, &user_address, &market_id, side, size, first).await?;
+    let second = leg_order(&mut amounts, &user_address, &market_id, side, size, second).await?;
+
+    // Send to matching engine - engine handles validation, locking and linking
+    let (response_tx, response_rx) = oneshot::channel();
+    state
+        .engine_tx
+        .send(EngineRequest::PlaceOco {
+            first,
+            second,
+            span: tracing::Span::current(),
+            response_tx,
+        })
+        .await
+        .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+    let placed = response_rx
+        .await
+        .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+    Ok(Json(OcoPlaced {
+        oco_id: placed.oco_id,
+        first: amounts.order(placed.first).await?,
+        second: amounts.order(placed.second).await?,
+    }))
+}
+
+/// The pending order one leg of the pair asks for
+async fn leg_order(
+    amounts: &mut Amounts,
+    user_address: &str,
+    market_id: &str,
+    side: Side,
+    size: u128,
+    leg: OcoLeg,
+) -> Result<Order> {
+    let price = amounts.parse_price(market_id, &leg.price).await?;
+    let trigger_price = match leg.trigger_price {
+        Some(trigger_price) => Some(amounts.parse_price(market_id, &trigger_price).await?),
+        None => None,
+    };
+    let now = Utc::now();
+    Ok(Order {
+        id: Uuid::new_v4(),
+        user_address: user_address.to_string(),
+        market_id: market_id.to_string(),
+        side,
+        order_type: leg.order_type,
+        price,
+        size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: now,
+        updated_at: now,
+        cancel_reason: None,
+        trigger_price,
+        trigger_source: leg.trigger_source,
+        time_in_force: TimeInForce::Gtc,
+        expires_at: None,
+    })
+}
@@ -0,0 +1,75 @@
+use crate::analytics::pnl::PositionTracker;
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::{ApiMarketPnl, UserPnlResponse};
+use crate::models::domain::CostBasisMethod;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use utoipa::IntoParams;
+
+/// Query parameters for the PnL endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PnlQuery {
+    /// How sales are matched against purchases (default: fifo)
+    #[serde(default)]
+    pub method: CostBasisMethod,
+}
+
+/// Get a user's realized PnL per market
+///
+/// GET /api/users/{address}/pnl
+///
+/// Replays the user's trade history from ClickHouse to compute realized PnL,
+/// open position and average entry price in every market they have traded.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/pnl",
+    params(
+        ("address" = String, Path, description = "User address"),
+        PnlQuery
+    ),
+    responses(
+        (status = 200, description = "PnL computed successfully", body = UserPnlResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn user_pnl(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<PnlQuery>,
+) -> Result<Json<UserPnlResponse>> {
+    state.db.get_user(&address).await?;
+
+    let base_decimals = state.db.get_base_decimals_by_market().await?;
+
+    let mut trackers = BTreeMap::new();
+    for fill in state.db.get_user_fills(&address).await? {
+        let Some(&decimals) = base_decimals.get(&fill.market_id) else {
+            continue;
+        };
+        trackers
+            .entry(fill.market_id)
+            .or_insert_with(|| PositionTracker::new(query.method, decimals))
+            .apply(fill.side, fill.price, fill.size);
+    }
+
+    Ok(Json(UserPnlResponse {
+        user_address: address,
+        method: query.method,
+        markets: trackers
+            .into_iter()
+            .map(|(market_id, tracker)| ApiMarketPnl {
+                market_id,
+                realized_pnl: tracker.realized_pnl().to_string(),
+                position: tracker.position().to_string(),
+                average_entry_price: tracker.average_entry_price().map(|p| p.to_string()),
+            })
+            .collect(),
+    }))
+}
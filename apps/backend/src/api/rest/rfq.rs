@@ -0,0 +1,213 @@
+use axum::{extract::State, response::Json, Extension};
+use chrono::Utc;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::api::auth::{self, ApiKeyAuth};
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{RfqRequest, RfqResponse};
+use crate::models::domain::{ApiKeyScope, EngineRequest, QuoteRequest, RfqStatus};
+use crate::rfq;
+
+/// Request quotes from makers and trade large orders off the book
+///
+/// A taker requests a quote on a size, registered makers answer with firm
+/// prices until the request expires, and the taker accepts one to trade the
+/// whole size at that price. The trade is reported like any other, flagged
+/// as `rfq`; the quoted price is all-in and neither side pays trading fees.
+#[utoipa::path(
+    post,
+    path = "/api/rfq",
+    request_body = RfqRequest,
+    responses(
+        (status = 200, description = "Success", body = RfqResponse),
+        (status = 400, description = "Invalid request parameters", body = ErrorResponse),
+        (status = 401, description = "Invalid signature or API key", body = ErrorResponse),
+        (status = 403, description = "Not a registered RFQ maker, or API key lacks the scope", body = ErrorResponse),
+        (status = 404, description = "Request or quote not found", body = ErrorResponse),
+        (status = 409, description = "Request no longer open", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "rfq"
+)]
+pub async fn rfq(
+    State(state): State<crate::AppState>,
+    auth: Option<Extension<ApiKeyAuth>>,
+    Json(request): Json<RfqRequest>,
+) -> Result<Json<RfqResponse>> {
+    match &request {
+        RfqRequest::RequestQuote {
+            user_address,
+            signature,
+            ..
+        }
+        | RfqRequest::SubmitQuote {
+            user_address,
+            signature,
+            ..
+        }
+        | RfqRequest::AcceptQuote {
+            user_address,
+            signature,
+            ..
+        }
+        | RfqRequest::CancelRequest {
+            user_address,
+            signature,
+            ..
+        } => auth::authorize(
+            auth.as_deref(),
+            user_address,
+            Some(ApiKeyScope::Trade),
+            || {
+                state
+                    .signatures
+                    .verify_trading("/api/rfq", &request, user_address, signature)
+            },
+        )?,
+        RfqRequest::Quotes { user_address, .. } => auth::authorize(
+            auth.as_deref(),
+            user_address,
+            Some(ApiKeyScope::Read),
+            || Ok(()),
+        )?,
+        RfqRequest::OpenRequests { .. } => {}
+    }
+
+    match request {
+        RfqRequest::RequestQuote {
+            user_address,
+            market_id,
+            side,
+            size,
+            ttl_secs,
+            signature: _,
+        } => {
+            let market_id = state.symbols.canonical(&market_id);
+            let size = size
+                .parse::<u128>()
+                .map_err(|_| ExchangeError::InvalidSize)?;
+            let market = state.db.get_market(&market_id).await?;
+            if state.db.get_perpetual_market(&market_id).await?.is_some() {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!("{} is a perpetual market and takes no RFQs", market_id),
+                });
+            }
+            rfq::validate_size(&market, size)?;
+
+            let now = Utc::now();
+            let request = QuoteRequest {
+                id: Uuid::new_v4(),
+                taker_address: user_address,
+                market_id,
+                side,
+                size,
+                status: RfqStatus::Open,
+                trade_id: None,
+                expires_at: rfq::request_expiry(now, ttl_secs)?,
+                created_at: now,
+            };
+            state.db.create_quote_request(&request).await?;
+
+            Ok(Json(RfqResponse::RequestQuote {
+                request: request.into(),
+            }))
+        }
+        RfqRequest::SubmitQuote {
+            user_address,
+            request_id,
+            price,
+            ttl_secs,
+            signature: _,
+        } => {
+            let request_id = Uuid::parse_str(&request_id)?;
+            let price = price
+                .parse::<u128>()
+                .map_err(|_| ExchangeError::InvalidPrice)?;
+            let quote = state
+                .db
+                .submit_quote(request_id, &user_address, price, ttl_secs)
+                .await?;
+
+            Ok(Json(RfqResponse::SubmitQuote {
+                quote: quote.into(),
+            }))
+        }
+        RfqRequest::AcceptQuote {
+            user_address,
+            request_id,
+            quote_id,
+            signature: _,
+        } => {
+            let request_id = Uuid::parse_str(&request_id)?;
+            let quote_id = Uuid::parse_str(&quote_id)?;
+
+            // Settled by the engine, so it is ordered with the fills competing for the same balances
+            let (response_tx, response_rx) = oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::ExecuteRfq {
+                    request_id,
+                    quote_id,
+                    user_address,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+            let execution = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(RfqResponse::AcceptQuote {
+                request: execution.request.into(),
+                trade: execution.trade.into(),
+            }))
+        }
+        RfqRequest::CancelRequest {
+            user_address,
+            request_id,
+            signature: _,
+        } => {
+            let request_id = Uuid::parse_str(&request_id)?;
+            let request = state
+                .db
+                .cancel_quote_request(request_id, &user_address)
+                .await?;
+
+            Ok(Json(RfqResponse::CancelRequest {
+                request: request.into(),
+            }))
+        }
+        RfqRequest::OpenRequests { market_id } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
+            let requests = state
+                .db
+                .list_open_quote_requests(market_id.as_deref())
+                .await?;
+
+            Ok(Json(RfqResponse::OpenRequests {
+                requests: requests.into_iter().map(Into::into).collect(),
+            }))
+        }
+        RfqRequest::Quotes {
+            user_address,
+            request_id,
+        } => {
+            // Makers don't get to see each other's prices
+            let request_id = Uuid::parse_str(&request_id)?;
+            let request = state.db.get_quote_request(request_id).await?;
+            if request.taker_address != user_address {
+                return Err(ExchangeError::RfqRequestNotFound {
+                    request_id: request_id.to_string(),
+                });
+            }
+            let quotes = state.db.list_quotes(&request).await?;
+
+            Ok(Json(RfqResponse::Quotes {
+                request: request.into(),
+                quotes: quotes.into_iter().map(Into::into).collect(),
+            }))
+        }
+    }
+}
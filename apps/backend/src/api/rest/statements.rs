@@ -0,0 +1,109 @@
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{ApiStatement, StatementsResponse};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Query parameters for listing statements
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StatementsQuery {
+    /// Number of statements to return (default: 12, max: 120)
+    pub limit: Option<u32>,
+}
+
+/// List a user's monthly account statements
+///
+/// GET /api/users/{address}/statements
+///
+/// Statements are generated shortly after each calendar month (UTC) ends,
+/// for users who held or moved anything that month, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/statements",
+    params(
+        ("address" = String, Path, description = "User address"),
+        StatementsQuery
+    ),
+    responses(
+        (status = 200, description = "Statements retrieved successfully", body = StatementsResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn user_statements(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<StatementsQuery>,
+) -> Result<Json<StatementsResponse>> {
+    state.db.get_user(&address).await?;
+
+    let limit = query.limit.unwrap_or(12).min(120);
+    let statements = state
+        .db
+        .list_user_statements(&address, None, limit)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Json(StatementsResponse {
+        user_address: address,
+        statements,
+    }))
+}
+
+/// Get a user's statement for one month
+///
+/// GET /api/users/{address}/statements/{month}
+///
+/// `month` is the calendar month (UTC) as `YYYY-MM`.
+#[utoipa::path(
+    get,
+    path = "/api/users/{address}/statements/{month}",
+    params(
+        ("address" = String, Path, description = "User address"),
+        ("month" = String, Path, description = "Calendar month as YYYY-MM")
+    ),
+    responses(
+        (status = 200, description = "Statement retrieved successfully", body = ApiStatement),
+        (status = 400, description = "Invalid month", body = ErrorResponse),
+        (status = 404, description = "User or statement not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "user"
+)]
+pub async fn user_statement(
+    State(state): State<AppState>,
+    Path((address, month)): Path<(String, String)>,
+) -> Result<Json<ApiStatement>> {
+    let period_start = parse_month(&month)?;
+    state.db.get_user(&address).await?;
+
+    state
+        .db
+        .list_user_statements(&address, Some(period_start), 1)
+        .await?
+        .pop()
+        .map(|statement| Json(statement.into()))
+        .ok_or(ExchangeError::StatementNotFound {
+            user_address: address,
+            period: month,
+        })
+}
+
+/// Start of a `YYYY-MM` month in UTC
+fn parse_month(month: &str) -> Result<DateTime<Utc>> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|start| start.and_utc())
+        .ok_or_else(|| ExchangeError::InvalidParameter {
+            message: format!("Invalid month '{}': use YYYY-MM", month),
+        })
+}
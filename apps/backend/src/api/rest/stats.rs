@@ -0,0 +1,70 @@
+use crate::cache::{keys, ReadCache};
+use crate::db::Db;
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::ApiMarketStats;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use std::time::Duration;
+
+/// How long computed stats are served before ClickHouse is queried again,
+/// unless the market trades first
+pub const MARKET_STATS_TTL: Duration = Duration::from_secs(5);
+
+/// Window the stats are computed over
+const STATS_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// Get rolling 24h statistics for a market
+///
+/// GET /api/markets/{market_id}/stats
+///
+/// Volume, trade count and OHLC over the last 24 hours, computed from the
+/// ClickHouse tick data and cached for a few seconds or until the next trade.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/stats",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID")
+    ),
+    responses(
+        (status = 200, description = "Market stats retrieved successfully", body = ApiMarketStats),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn market_stats(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+) -> Result<Json<ApiMarketStats>> {
+    let market_id = state.symbols.canonical(&market_id);
+    let stats = cached_market_stats(&state.db, &state.cache, &market_id).await?;
+
+    Ok(Json(stats))
+}
+
+/// A market's rolling 24h stats, from the read cache when fresh
+pub(crate) async fn cached_market_stats(
+    db: &Db,
+    cache: &ReadCache,
+    market_id: &str,
+) -> Result<ApiMarketStats> {
+    let key = keys::ticker(market_id);
+    if let Some(stats) = cache.get(&key).await {
+        return Ok(stats);
+    }
+
+    let market = db.get_market(market_id).await?;
+    let base_token = db.get_token(&market.base_ticker).await?;
+
+    let to = Utc::now().timestamp();
+    let stats = db
+        .get_market_stats(market_id, base_token.decimals, to - STATS_WINDOW_SECS, to)
+        .await?;
+    cache.set(&key, &stats, MARKET_STATS_TTL).await;
+
+    Ok(stats)
+}
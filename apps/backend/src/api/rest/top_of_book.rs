@@ -0,0 +1,47 @@
+use crate::cache::keys;
+use crate::errors::{ErrorResponse, Result};
+use crate::models::api::ApiTopOfBook;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Get a market's best bid and ask
+///
+/// GET /api/markets/{market_id}/top-of-book
+///
+/// Taken from the engine's latest orderbook snapshot, which it broadcasts
+/// every second, so it can trail the live book by up to a second. Markets
+/// without a snapshot yet have never had a resting order.
+#[utoipa::path(
+    get,
+    path = "/api/markets/{market_id}/top-of-book",
+    params(
+        ("market_id" = String, Path, description = "Market ID, URL-encoded (e.g. BTC%2FUSDC), an alias such as BTC-USDC, or its numeric ID")
+    ),
+    responses(
+        (status = 200, description = "Top of book retrieved successfully", body = ApiTopOfBook),
+        (status = 404, description = "Market not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "info"
+)]
+pub async fn top_of_book(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+) -> Result<Json<ApiTopOfBook>> {
+    let market_id = state.symbols.canonical(&market_id);
+    if let Some(top) = state.cache.get(&keys::top_of_book(&market_id)).await {
+        return Ok(Json(top));
+    }
+
+    let market = state.db.get_market(&market_id).await?;
+    Ok(Json(ApiTopOfBook {
+        market_id: market.id,
+        best_bid: None,
+        best_ask: None,
+        version: 0,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    }))
+}
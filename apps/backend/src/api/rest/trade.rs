@@ -1,10 +1,12 @@
-use axum::{extract::State, response::Json};
+use axum::{extract::State, response::Json, Extension};
 use chrono::Utc;
 use uuid::Uuid;
 
+use crate::api::amounts::Amounts;
+use crate::api::auth::{self, ApiKeyAuth};
 use crate::errors::{ErrorResponse, ExchangeError, Result};
 use crate::models::api::{TradeRequest, TradeResponse};
-use crate::models::domain::{EngineRequest, Order, OrderStatus};
+use crate::models::domain::{ApiKeyScope, EngineRequest, Order, OrderStatus};
 use tokio::sync::oneshot;
 
 /// Execute trades (place/cancel orders)
@@ -12,19 +14,52 @@ use tokio::sync::oneshot;
     post,
     path = "/api/trade",
     request_body = TradeRequest,
+    params(
+        ("X-Amount-Format" = Option<String>, Header, description = "`atoms` (default) or `decimal`: with `decimal`, order prices are read and returned in whole quote tokens and sizes in whole base tokens, using each token's decimals")
+    ),
     responses(
         (status = 200, description = "Success", body = TradeResponse),
         (status = 400, description = "Invalid request parameters", body = ErrorResponse),
-        (status = 401, description = "Invalid signature", body = ErrorResponse),
+        (status = 401, description = "Invalid signature or API key", body = ErrorResponse),
+        (status = 403, description = "API key lacks the trade scope", body = ErrorResponse),
         (status = 404, description = "Order not found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "trade"
 )]
+#[tracing::instrument(name = "api.trade", skip_all)]
 pub async fn trade(
     State(state): State<crate::AppState>,
+    auth: Option<Extension<ApiKeyAuth>>,
+    mut amounts: Amounts,
     Json(request): Json<TradeRequest>,
 ) -> Result<Json<TradeResponse>> {
+    let (TradeRequest::PlaceOrder {
+        user_address,
+        signature,
+        ..
+    }
+    | TradeRequest::CancelOrder {
+        user_address,
+        signature,
+        ..
+    }
+    | TradeRequest::CancelAllOrders {
+        user_address,
+        signature,
+        ..
+    }) = &request;
+    auth::authorize(
+        auth.as_deref(),
+        user_address,
+        Some(ApiKeyScope::Trade),
+        || {
+            state
+                .signatures
+                .verify_trading("/api/trade", &request, user_address, signature)
+        },
+    )?;
+
     match request {
         TradeRequest::PlaceOrder {
             user_address,
@@ -34,16 +69,20 @@ pub async fn trade(
             price,
             size,
             signature: _,
+            trigger_price,
+            trigger_source,
+            time_in_force,
+            expires_at,
         } => {
-            // TODO: Verify signature
+            let market_id = state.symbols.canonical(&market_id);
 
             // Parse price and size from strings to u128
-            let price_value = price
-                .parse::<u128>()
-                .map_err(|_| ExchangeError::InvalidPrice)?;
-            let size_value = size
-                .parse::<u128>()
-                .map_err(|_| ExchangeError::InvalidSize)?;
+            let price_value = amounts.parse_price(&market_id, &price).await?;
+            let size_value = amounts.parse_size(&market_id, &size).await?;
+            let trigger_price_value = match trigger_price {
+                Some(trigger_price) => Some(amounts.parse_price(&market_id, &trigger_price).await?),
+                None => None,
+            };
 
             // Create order (validation and locking happens in engine)
             let order = Order {
@@ -58,13 +97,22 @@ pub async fn trade(
                 status: OrderStatus::Pending,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
+                cancel_reason: None,
+                trigger_price: trigger_price_value,
+                trigger_source,
+                time_in_force,
+                expires_at,
             };
 
             // Send to matching engine - engine handles validation and locking
             let (response_tx, response_rx) = oneshot::channel();
             state
                 .engine_tx
-                .send(EngineRequest::PlaceOrder { order, response_tx })
+                .send(EngineRequest::PlaceOrder {
+                    order,
+                    span: tracing::Span::current(),
+                    response_tx,
+                })
                 .await
                 .map_err(|_| ExchangeError::EngineSendFailed)?;
 
@@ -74,8 +122,8 @@ pub async fn trade(
                 .map_err(|_| ExchangeError::EngineReceiveFailed)??;
 
             Ok(Json(TradeResponse::PlaceOrder {
-                order: placed.order,
-                trades: placed.trades,
+                order: amounts.order(placed.order).await?,
+                trades: amounts.trades(placed.trades).await?,
             }))
         }
         TradeRequest::CancelOrder {
@@ -83,8 +131,6 @@ pub async fn trade(
             order_id,
             signature: _,
         } => {
-            // TODO: Verify signature
-
             // Parse order_id
             let order_uuid = Uuid::parse_str(&order_id)?;
 
@@ -95,6 +141,7 @@ pub async fn trade(
                 .send(EngineRequest::CancelOrder {
                     order_id: order_uuid,
                     user_address,
+                    span: tracing::Span::current(),
                     response_tx,
                 })
                 .await
@@ -119,13 +166,14 @@ pub async fn trade(
             market_id,
             signature: _,
         } => {
-            // TODO: Verify signature
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
 
             // Create engine request
             let (response_tx, response_rx) = tokio::sync::oneshot::channel();
             let engine_request = EngineRequest::CancelAllOrders {
                 user_address: user_address.clone(),
                 market_id: market_id.clone(),
+                span: tracing::Span::current(),
                 response_tx,
             };
 
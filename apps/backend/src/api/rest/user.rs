@@ -1,25 +1,55 @@
-use axum::{extract::State, response::Json};
+use axum::{extract::State, response::Json, Extension};
 
-use crate::errors::{ErrorResponse, Result};
-use crate::models::api::{UserRequest, UserResponse};
+use crate::api::amounts::Amounts;
+use crate::api::auth::{self, ApiKeyAuth, MAX_API_KEYS_PER_USER};
+use crate::engine::MAX_OPEN_ORDERS_PER_MARKET;
+use crate::errors::{ErrorResponse, ExchangeError, Result};
+use crate::models::api::{
+    ApiAccountTransfer, ApiAggregateBalance, ApiOpenOrderUsage, ApiRebateTotal,
+    ApiReferralEarnings, ApiTrade, ApiWithdrawal, UserRequest, UserResponse,
+};
+use crate::models::domain::{ApiKeyScope, EngineEvent, EngineRequest, SubAccount};
+use crate::sub_accounts::{self, MAX_SUB_ACCOUNTS_PER_USER};
+use crate::webhooks::{self, MAX_WEBHOOKS_PER_USER};
+use crate::withdrawals;
 
-/// Get user-specific data (orders, balances, trades)
+/// Get user-specific data (orders, balances, trades, open-order usage, an order's queue
+/// position, an account summary, referral earnings), set the user's leaderboard display name, manage their
+/// webhooks, list their deposits, request or cancel withdrawals, choose how their
+/// perpetual positions are margined, open, fund and report on their sub-accounts, and
+/// manage their API keys
 #[utoipa::path(
     post,
     path = "/api/user",
     request_body = UserRequest,
+    params(
+        ("X-Amount-Format" = Option<String>, Header, description = "`atoms` (default) or `decimal`: with `decimal`, order, trade, balance, withdrawal and transfer amounts are read and returned in whole tokens, using each token's decimals")
+    ),
     responses(
         (status = 200, description = "Success", body = UserResponse),
         (status = 400, description = "Invalid request", body = ErrorResponse),
-        (status = 404, description = "User or resource not found", body = ErrorResponse),
+        (status = 401, description = "Invalid API key or signature", body = ErrorResponse),
+        (status = 403, description = "User is frozen or banned, or API key lacks the scope", body = ErrorResponse),
+        (status = 404, description = "User, order, webhook, withdrawal, sub-account, API key or resource not found", body = ErrorResponse),
+        (status = 409, description = "Display name or sub-account name already taken, or withdrawal no longer pending", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "user"
 )]
 pub async fn user(
     State(state): State<crate::AppState>,
+    auth: Option<Extension<ApiKeyAuth>>,
+    mut amounts: Amounts,
     Json(request): Json<UserRequest>,
 ) -> Result<Json<UserResponse>> {
+    let (user_address, scope) = required_scope(&request);
+    auth::authorize(auth.as_deref(), user_address, scope, || {
+        let signature = signature_of(&request).unwrap_or_default();
+        state
+            .signatures
+            .verify("/api/user", &request, user_address, signature)
+    })?;
+
     match request {
         UserRequest::Orders {
             user_address,
@@ -27,6 +57,8 @@ pub async fn user(
             status,
             limit,
         } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
+
             // Parse status string to OrderStatus enum if provided
             use crate::models::domain::OrderStatus;
             let status_enum = status.as_ref().and_then(|s| match s.as_str() {
@@ -47,15 +79,17 @@ pub async fn user(
                 )
                 .await?;
 
+            let orders = orders.into_iter().map(|o| o.into()).collect();
             Ok(Json(UserResponse::Orders {
-                orders: orders.into_iter().map(|o| o.into()).collect(),
+                orders: amounts.orders(orders).await?,
             }))
         }
         UserRequest::Balances { user_address } => {
             let balances = state.db.list_balances_by_user(&user_address).await?;
 
+            let balances = balances.into_iter().map(|b| b.into()).collect();
             Ok(Json(UserResponse::Balances {
-                balances: balances.into_iter().map(|b| b.into()).collect(),
+                balances: amounts.balances(balances).await?,
             }))
         }
         UserRequest::Trades {
@@ -63,14 +97,563 @@ pub async fn user(
             market_id,
             limit,
         } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
             let trades = state
                 .db
                 .get_user_trades(&user_address, market_id.as_deref(), limit.unwrap_or(100))
                 .await?;
 
+            let trade_ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+            let mut fees = state
+                .db
+                .get_user_trade_fees(&user_address, &trade_ids)
+                .await?;
+            let rebate_totals = state.db.get_user_rebate_totals(&user_address).await?;
+
+            let trades = trades
+                .into_iter()
+                .map(|t| {
+                    let fee = fees.remove(&t.id);
+                    ApiTrade {
+                        fee: fee.as_ref().map(|f| f.fee.to_string()),
+                        fee_ticker: fee.map(|f| f.token_ticker),
+                        role: t.role_of(&user_address),
+                        ..t.into()
+                    }
+                })
+                .collect();
             Ok(Json(UserResponse::Trades {
-                trades: trades.into_iter().map(|t| t.into()).collect(),
+                trades: amounts.trades(trades).await?,
+                rebate_totals: rebate_totals
+                    .into_iter()
+                    .map(|(token_ticker, amount)| ApiRebateTotal {
+                        token_ticker,
+                        amount: amount.to_string(),
+                    })
+                    .collect(),
+            }))
+        }
+        UserRequest::OpenOrderUsage {
+            user_address,
+            market_id,
+        } => {
+            let market_id = market_id.map(|market_id| state.symbols.canonical(&market_id));
+            let mut counts = state
+                .db
+                .count_open_orders(&user_address, market_id.as_deref())
+                .await?;
+
+            // A requested market with nothing resting still reports its cap
+            if let Some(market_id) = market_id {
+                if counts.is_empty() {
+                    state.db.get_market(&market_id).await?;
+                    counts.push((market_id, 0));
+                }
+            }
+
+            Ok(Json(UserResponse::OpenOrderUsage {
+                usage: counts
+                    .into_iter()
+                    .map(|(market_id, open_orders)| ApiOpenOrderUsage {
+                        market_id,
+                        open_orders,
+                        max_open_orders: MAX_OPEN_ORDERS_PER_MARKET as u64,
+                    })
+                    .collect(),
+            }))
+        }
+        UserRequest::QueuePosition {
+            user_address,
+            order_id,
+        } => {
+            let order_id = uuid::Uuid::parse_str(&order_id)?;
+
+            let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::QueuePosition {
+                    order_id,
+                    user_address,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+            let position = response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(UserResponse::QueuePosition {
+                position: position.into(),
+            }))
+        }
+        UserRequest::Summary { user_address } => {
+            let summary = state.db.get_user_summary(&user_address).await?;
+
+            Ok(Json(UserResponse::Summary {
+                summary: summary.into(),
+            }))
+        }
+        UserRequest::ReferralEarnings { user_address } => {
+            let earnings = state.db.get_referral_earnings(&user_address).await?;
+
+            Ok(Json(UserResponse::ReferralEarnings {
+                earnings: earnings
+                    .into_iter()
+                    .map(
+                        |(token_ticker, amount, referred_users)| ApiReferralEarnings {
+                            token_ticker,
+                            amount: amount.to_string(),
+                            referred_users,
+                        },
+                    )
+                    .collect(),
+            }))
+        }
+        UserRequest::SetDisplayName {
+            user_address,
+            display_name,
+            signature: _,
+        } => {
+            if let Some(name) = &display_name {
+                validate_display_name(name)?;
+            }
+            state
+                .db
+                .set_display_name(&user_address, display_name.as_deref())
+                .await?;
+
+            Ok(Json(UserResponse::SetDisplayName {
+                user_address,
+                display_name,
+            }))
+        }
+        UserRequest::RegisterWebhook {
+            user_address,
+            url,
+            signature: _,
+        } => {
+            webhooks::validate_url(&url)?;
+            state.db.get_user(&user_address).await?;
+            let existing = state.db.list_webhooks(&user_address).await?;
+            if existing.len() >= MAX_WEBHOOKS_PER_USER {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "User '{}' already has {} webhooks",
+                        user_address, MAX_WEBHOOKS_PER_USER
+                    ),
+                });
+            }
+
+            let secret = webhooks::new_secret();
+            let webhook = state
+                .db
+                .create_webhook(&user_address, &url, &secret)
+                .await?;
+
+            Ok(Json(UserResponse::RegisterWebhook {
+                webhook: webhook.into(),
+                secret,
+            }))
+        }
+        UserRequest::Webhooks { user_address } => {
+            let webhooks = state.db.list_webhooks(&user_address).await?;
+
+            Ok(Json(UserResponse::Webhooks {
+                webhooks: webhooks.into_iter().map(|w| w.into()).collect(),
+            }))
+        }
+        UserRequest::DeleteWebhook {
+            user_address,
+            webhook_id,
+            signature: _,
+        } => {
+            let id =
+                uuid::Uuid::parse_str(&webhook_id).map_err(|_| ExchangeError::WebhookNotFound {
+                    webhook_id: webhook_id.clone(),
+                })?;
+            state.db.delete_webhook(&user_address, id).await?;
+
+            Ok(Json(UserResponse::DeleteWebhook { webhook_id }))
+        }
+        UserRequest::WebhookDeadLetters {
+            user_address,
+            limit,
+        } => {
+            let dead_letters = state
+                .db
+                .list_webhook_dead_letters(&user_address, limit.unwrap_or(100))
+                .await?;
+
+            Ok(Json(UserResponse::WebhookDeadLetters {
+                dead_letters: dead_letters.into_iter().map(|d| d.into()).collect(),
             }))
         }
+        UserRequest::Deposits {
+            user_address,
+            limit,
+        } => {
+            let deposits = state
+                .db
+                .list_user_deposits(&user_address, limit.unwrap_or(100))
+                .await?;
+
+            Ok(Json(UserResponse::Deposits {
+                deposits: deposits.into_iter().map(|d| d.into()).collect(),
+            }))
+        }
+        UserRequest::Withdraw {
+            user_address,
+            token_ticker,
+            amount,
+            destination,
+            signature: _,
+        } => {
+            let amount = Some(amounts.parse_amount(&token_ticker, &amount).await?)
+                .filter(|amount| *amount > 0)
+                .ok_or(ExchangeError::InvalidAmount)?;
+            withdrawals::validate_destination(&destination)?;
+            state.db.get_token(&token_ticker).await?;
+
+            let (withdrawal, balance) = state
+                .db
+                .create_withdrawal(&user_address, &token_ticker, amount, &destination)
+                .await?;
+            let _ = state.event_tx.send(EngineEvent::BalanceUpdated { balance });
+            let _ = state.event_tx.send(EngineEvent::WithdrawalUpdated {
+                withdrawal: withdrawal.clone(),
+            });
+
+            let amount = amounts.amount(&token_ticker, withdrawal.amount).await?;
+            Ok(Json(UserResponse::Withdraw {
+                withdrawal: ApiWithdrawal {
+                    amount,
+                    ..withdrawal.into()
+                },
+            }))
+        }
+        UserRequest::CancelWithdrawal {
+            user_address,
+            withdrawal_id,
+            signature: _,
+        } => {
+            let id = uuid::Uuid::parse_str(&withdrawal_id).map_err(|_| {
+                ExchangeError::WithdrawalNotFound {
+                    withdrawal_id: withdrawal_id.clone(),
+                }
+            })?;
+            let (withdrawal, balance) = state.db.cancel_withdrawal(&user_address, id).await?;
+            let _ = state.event_tx.send(EngineEvent::BalanceUpdated { balance });
+            let _ = state.event_tx.send(EngineEvent::WithdrawalUpdated {
+                withdrawal: withdrawal.clone(),
+            });
+
+            Ok(Json(UserResponse::CancelWithdrawal {
+                withdrawal: withdrawal.into(),
+            }))
+        }
+        UserRequest::Withdrawals {
+            user_address,
+            limit,
+        } => {
+            let withdrawals = state
+                .db
+                .list_user_withdrawals(&user_address, limit.unwrap_or(100))
+                .await?;
+
+            Ok(Json(UserResponse::Withdrawals {
+                withdrawals: withdrawals.into_iter().map(|w| w.into()).collect(),
+            }))
+        }
+        UserRequest::SetMarginMode {
+            user_address,
+            mode,
+            signature: _,
+        } => {
+            // The engine checks margin on every fill, so it keeps the mode cached
+            let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+            state
+                .engine_tx
+                .send(EngineRequest::SetMarginMode {
+                    user_address: user_address.clone(),
+                    mode,
+                    response_tx,
+                })
+                .await
+                .map_err(|_| ExchangeError::EngineSendFailed)?;
+            response_rx
+                .await
+                .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+            Ok(Json(UserResponse::SetMarginMode { user_address, mode }))
+        }
+        UserRequest::CreateSubAccount {
+            user_address,
+            name,
+            signature: _,
+        } => {
+            sub_accounts::validate_master(&user_address)?;
+            sub_accounts::validate_name(&name)?;
+            let existing = state.db.list_sub_accounts(&user_address).await?;
+            if existing.len() >= MAX_SUB_ACCOUNTS_PER_USER {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "User '{}' already has {} sub-accounts",
+                        user_address, MAX_SUB_ACCOUNTS_PER_USER
+                    ),
+                });
+            }
+
+            let sub_account = state.db.create_sub_account(&user_address, &name).await?;
+
+            Ok(Json(UserResponse::CreateSubAccount {
+                sub_account: sub_account.into(),
+            }))
+        }
+        UserRequest::SubAccounts { user_address } => {
+            let sub_accounts = state.db.list_sub_accounts(&user_address).await?;
+
+            Ok(Json(UserResponse::SubAccounts {
+                sub_accounts: sub_accounts.into_iter().map(|s| s.into()).collect(),
+            }))
+        }
+        UserRequest::InternalTransfer {
+            user_address,
+            from_address,
+            to_address,
+            token_ticker,
+            amount,
+            signature: _,
+        } => {
+            let amount = Some(amounts.parse_amount(&token_ticker, &amount).await?)
+                .filter(|amount| *amount > 0)
+                .ok_or(ExchangeError::InvalidAmount)?;
+            if from_address == to_address {
+                return Err(ExchangeError::InvalidParameter {
+                    message: "Transfer source and destination are the same account".to_string(),
+                });
+            }
+            // Only between the user's own accounts; anyone else's don't exist as far as it knows
+            let own = state.db.list_sub_accounts(&user_address).await?;
+            for address in [&from_address, &to_address] {
+                let owned = *address == user_address || own.iter().any(|s| s.address == *address);
+                if !owned || !SubAccount::owns(&user_address, address) {
+                    return Err(ExchangeError::SubAccountNotFound {
+                        address: address.clone(),
+                    });
+                }
+            }
+            state.db.get_token(&token_ticker).await?;
+
+            let (transfer, from_balance, to_balance) = state
+                .db
+                .transfer_between_accounts(
+                    &user_address,
+                    &from_address,
+                    &to_address,
+                    &token_ticker,
+                    amount,
+                )
+                .await?;
+            for balance in [&from_balance, &to_balance] {
+                let _ = state.event_tx.send(EngineEvent::BalanceUpdated {
+                    balance: balance.clone(),
+                });
+            }
+
+            let amount = amounts.amount(&token_ticker, transfer.amount).await?;
+            let balances = vec![from_balance.into(), to_balance.into()];
+            Ok(Json(UserResponse::InternalTransfer {
+                transfer: ApiAccountTransfer {
+                    amount,
+                    ..transfer.into()
+                },
+                balances: amounts.balances(balances).await?,
+            }))
+        }
+        UserRequest::AggregateBalances { user_address } => {
+            state.db.get_user(&user_address).await?;
+            let balances = state.db.list_account_group_balances(&user_address).await?;
+            let totals = sub_accounts::aggregate_balances(&balances)
+                .into_iter()
+                .map(
+                    |(token_ticker, amount, open_interest)| ApiAggregateBalance {
+                        token_ticker,
+                        amount: amount.to_string(),
+                        open_interest: open_interest.to_string(),
+                    },
+                )
+                .collect();
+
+            Ok(Json(UserResponse::AggregateBalances {
+                accounts: balances.into_iter().map(|b| b.into()).collect(),
+                totals,
+            }))
+        }
+        UserRequest::CreateApiKey {
+            user_address,
+            label,
+            scopes,
+            ip_allowlist,
+            signature: _,
+        } => {
+            if label.trim().is_empty() || label.len() > 64 {
+                return Err(ExchangeError::InvalidParameter {
+                    message: "API key labels must be 1-64 characters".to_string(),
+                });
+            }
+            if scopes.is_empty() {
+                return Err(ExchangeError::InvalidParameter {
+                    message: "API keys need at least one scope".to_string(),
+                });
+            }
+            auth::validate_allowlist(&ip_allowlist)?;
+            state.db.get_user(&user_address).await?;
+            let existing = state.db.list_api_keys(&user_address).await?;
+            if existing.len() >= MAX_API_KEYS_PER_USER {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "User '{}' already has {} API keys",
+                        user_address, MAX_API_KEYS_PER_USER
+                    ),
+                });
+            }
+
+            let mut scopes = scopes;
+            scopes.sort();
+            scopes.dedup();
+            let secret = auth::new_key();
+            let api_key = state
+                .db
+                .create_api_key(
+                    &user_address,
+                    &label,
+                    &auth::key_prefix(&secret),
+                    &auth::hash_key(&secret),
+                    &scopes,
+                    &ip_allowlist,
+                )
+                .await?;
+
+            Ok(Json(UserResponse::CreateApiKey {
+                api_key: api_key.into(),
+                secret,
+            }))
+        }
+        UserRequest::ApiKeys { user_address } => {
+            let api_keys = state.db.list_api_keys(&user_address).await?;
+
+            Ok(Json(UserResponse::ApiKeys {
+                api_keys: api_keys.into_iter().map(|k| k.into()).collect(),
+            }))
+        }
+        UserRequest::RotateApiKey {
+            user_address,
+            key_id,
+            signature: _,
+        } => {
+            let id = parse_key_id(&key_id)?;
+            let secret = auth::new_key();
+            let api_key = state
+                .db
+                .rotate_api_key(
+                    &user_address,
+                    id,
+                    &auth::key_prefix(&secret),
+                    &auth::hash_key(&secret),
+                )
+                .await?;
+
+            Ok(Json(UserResponse::RotateApiKey {
+                api_key: api_key.into(),
+                secret,
+            }))
+        }
+        UserRequest::RevokeApiKey {
+            user_address,
+            key_id,
+            signature: _,
+        } => {
+            let id = parse_key_id(&key_id)?;
+            state.db.revoke_api_key(&user_address, id).await?;
+
+            Ok(Json(UserResponse::RevokeApiKey { key_id }))
+        }
+    }
+}
+
+/// The account a request acts for and the scope an API key needs to make it
+///
+/// Managing the account itself (its keys, webhooks, display name and
+/// sub-accounts) needs no scope: it takes the account's own signature, never
+/// an API key.
+fn required_scope(request: &UserRequest) -> (&str, Option<ApiKeyScope>) {
+    match request {
+        UserRequest::Orders { user_address, .. }
+        | UserRequest::Balances { user_address }
+        | UserRequest::Trades { user_address, .. }
+        | UserRequest::OpenOrderUsage { user_address, .. }
+        | UserRequest::QueuePosition { user_address, .. }
+        | UserRequest::Summary { user_address }
+        | UserRequest::ReferralEarnings { user_address }
+        | UserRequest::Webhooks { user_address }
+        | UserRequest::WebhookDeadLetters { user_address, .. }
+        | UserRequest::Deposits { user_address, .. }
+        | UserRequest::Withdrawals { user_address, .. }
+        | UserRequest::SubAccounts { user_address }
+        | UserRequest::AggregateBalances { user_address }
+        | UserRequest::ApiKeys { user_address } => (user_address, Some(ApiKeyScope::Read)),
+        UserRequest::SetMarginMode { user_address, .. } => (user_address, Some(ApiKeyScope::Trade)),
+        UserRequest::Withdraw { user_address, .. }
+        | UserRequest::CancelWithdrawal { user_address, .. }
+        | UserRequest::InternalTransfer { user_address, .. } => {
+            (user_address, Some(ApiKeyScope::Withdraw))
+        }
+        UserRequest::SetDisplayName { user_address, .. }
+        | UserRequest::RegisterWebhook { user_address, .. }
+        | UserRequest::DeleteWebhook { user_address, .. }
+        | UserRequest::CreateSubAccount { user_address, .. }
+        | UserRequest::CreateApiKey { user_address, .. }
+        | UserRequest::RotateApiKey { user_address, .. }
+        | UserRequest::RevokeApiKey { user_address, .. } => (user_address, None),
+    }
+}
+
+/// The signature of a request that changes the account, `None` for reads
+fn signature_of(request: &UserRequest) -> Option<&str> {
+    match request {
+        UserRequest::SetDisplayName { signature, .. }
+        | UserRequest::RegisterWebhook { signature, .. }
+        | UserRequest::DeleteWebhook { signature, .. }
+        | UserRequest::Withdraw { signature, .. }
+        | UserRequest::CancelWithdrawal { signature, .. }
+        | UserRequest::SetMarginMode { signature, .. }
+        | UserRequest::CreateSubAccount { signature, .. }
+        | UserRequest::InternalTransfer { signature, .. }
+        | UserRequest::CreateApiKey { signature, .. }
+        | UserRequest::RotateApiKey { signature, .. }
+        | UserRequest::RevokeApiKey { signature, .. } => Some(signature),
+        _ => None,
+    }
+}
+
+fn parse_key_id(key_id: &str) -> Result<uuid::Uuid> {
+    uuid::Uuid::parse_str(key_id).map_err(|_| ExchangeError::ApiKeyNotFound {
+        key_id: key_id.to_string(),
+    })
+}
+
+/// Display names are 3-24 ASCII letters, digits, '_' or '-'
+fn validate_display_name(name: &str) -> Result<()> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !(3..=24).contains(&name.len()) || !valid_chars {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Invalid display name '{}': use 3-24 letters, digits, '_' or '-'",
+                name
+            ),
+        });
     }
+    Ok(())
 }
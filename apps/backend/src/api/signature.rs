@@ -0,0 +1,175 @@
+// Signed requests: checks a request's `signature` was made by the account it acts for
+//
+// A signature is `<expires_at>:<0x-prefixed r || s || v>`: an EIP-191
+// (`personal_sign`) signature over `signing_message`, valid until
+// `expires_at` (unix milliseconds) and accepted only once.
+
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::SubAccount;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde::Serialize;
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Longest a signature may stay valid for, in milliseconds
+pub const MAX_SIGNATURE_LIFETIME_MS: i64 = 300_000;
+
+/// The text a signature covers: the route, the request as JSON with its
+/// object keys sorted and its `signature` left out, and the signature's
+/// expiry, one per line
+///
+/// The JSON is the request as the server reads it, so fields a client
+/// leaves to their defaults are signed with their default values.
+pub fn signing_message(route: &str, request: &impl Serialize, expires_at: i64) -> Result<String> {
+    let mut request =
+        serde_json::to_value(request).map_err(|e| ExchangeError::InvalidSignature {
+            message: format!("request can't be encoded: {}", e),
+        })?;
+    if let Value::Object(fields) = &mut request {
+        fields.remove("signature");
+    }
+    Ok(format!("{}\n{}\n{}", route, sorted(request), expires_at))
+}
+
+/// Keccak-256 of `message` with the EIP-191 prefix, which is what gets signed
+pub fn message_hash(message: &str) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(format!("\x19Ethereum Signed Message:\n{}", message.len()))
+        .chain_update(message)
+        .finalize()
+        .into()
+}
+
+/// The 0x-prefixed, lowercase address of a public key
+pub fn address_of(key: &VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+/// Who signed `hash`, if `signature` is a well-formed 65-byte signature
+///
+/// High-`s` signatures are refused, as on chain, so a signature can't be
+/// altered into a second valid one.
+pub fn recover_address(hash: &[u8; 32], signature: &str) -> Option<String> {
+    let bytes = hex::decode(signature.strip_prefix("0x")?).ok()?;
+    if bytes.len() != 65 {
+        return None;
+    }
+    let recovery = RecoveryId::from_byte(bytes[64].checked_sub(27).unwrap_or(bytes[64]))?;
+    let signature = Signature::from_slice(&bytes[..64]).ok()?;
+    if signature.normalize_s().is_some() {
+        return None;
+    }
+    VerifyingKey::recover_from_prehash(hash, &signature, recovery)
+        .ok()
+        .map(|key| address_of(&key))
+}
+
+/// Checks signatures and remembers the ones used until they expire
+#[derive(Clone, Default)]
+pub struct SignatureCheck {
+    used: Arc<Mutex<HashMap<[u8; 32], i64>>>,
+    unsigned_trading: bool,
+}
+
+impl SignatureCheck {
+    /// Take orders without a signature, for local stacks whose frontend and
+    /// bots don't sign yet; funds and account management still need one
+    pub fn with_unsigned_trading(mut self, allowed: bool) -> Self {
+        self.unsigned_trading = allowed;
+        self
+    }
+
+    /// [`SignatureCheck::verify`] for placing and cancelling orders and
+    /// quotes, which passes anything while unsigned trading is allowed
+    pub fn verify_trading(
+        &self,
+        route: &str,
+        request: &impl Serialize,
+        user_address: &str,
+        signature: &str,
+    ) -> Result<()> {
+        if self.unsigned_trading {
+            return Ok(());
+        }
+        self.verify(route, request, user_address, signature)
+    }
+
+    /// Check `signature` is the signature of `user_address`, or of its
+    /// master if it's a sub-account, over `request` sent to `route`
+    pub fn verify(
+        &self,
+        route: &str,
+        request: &impl Serialize,
+        user_address: &str,
+        signature: &str,
+    ) -> Result<()> {
+        self.verify_at(
+            route,
+            request,
+            user_address,
+            signature,
+            chrono::Utc::now().timestamp_millis(),
+        )
+    }
+
+    /// [`SignatureCheck::verify`] as of `now`, in unix milliseconds
+    pub fn verify_at(
+        &self,
+        route: &str,
+        request: &impl Serialize,
+        user_address: &str,
+        signature: &str,
+        now: i64,
+    ) -> Result<()> {
+        let invalid = |message: &str| ExchangeError::InvalidSignature {
+            message: message.to_string(),
+        };
+        let (expires_at, signature) = signature
+            .split_once(':')
+            .and_then(|(expires_at, signature)| Some((expires_at.parse::<i64>().ok()?, signature)))
+            .ok_or_else(|| invalid("expected '<expires_at>:<0x-prefixed signature>'"))?;
+        if expires_at <= now {
+            return Err(invalid("the signature has expired"));
+        }
+        if expires_at > now + MAX_SIGNATURE_LIFETIME_MS {
+            return Err(invalid(&format!(
+                "signatures may be valid for at most {} ms",
+                MAX_SIGNATURE_LIFETIME_MS
+            )));
+        }
+
+        let hash = message_hash(&signing_message(route, request, expires_at)?);
+        let signer = recover_address(&hash, signature)
+            .ok_or_else(|| invalid("not a 65-byte low-s signature"))?;
+        if !signer.eq_ignore_ascii_case(SubAccount::owner_of(user_address)) {
+            return Err(invalid(&format!("not signed by '{}'", user_address)));
+        }
+
+        let mut used = self.used.lock().unwrap();
+        used.retain(|_, expires_at| *expires_at > now);
+        if used.insert(hash, expires_at).is_some() {
+            return Err(invalid("the signature has already been used"));
+        }
+        Ok(())
+    }
+}
+
+/// `value` with every object's keys in order
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, sorted(value)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(sorted).collect()),
+        value => value,
+    }
+}
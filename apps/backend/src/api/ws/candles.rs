@@ -0,0 +1,79 @@
+//! Live candles - the bar in progress per market and interval, built from the trade stream
+
+use std::collections::HashMap;
+
+use crate::db::candles::{interval_secs, CANDLE_INTERVALS};
+use crate::models::domain::Trade;
+
+/// One interval's bar in progress
+#[derive(Debug, Clone, Copy)]
+pub(super) struct LiveBar {
+    pub(super) start: i64, // Unix timestamp in seconds
+    pub(super) open: u128,
+    pub(super) high: u128,
+    pub(super) low: u128,
+    pub(super) close: u128,
+    pub(super) volume: u128,
+}
+
+/// Bars in progress for every market and interval
+///
+/// Every trade updates all intervals, subscribed or not, so a bar is complete
+/// whenever a connection starts following it. Bars are bucketed like the
+/// ClickHouse candles: epoch-aligned, in UTC, with volume in base atoms. Only
+/// the bar in progress at startup can miss trades, which clients fill from
+/// the candles history.
+#[derive(Default)]
+pub(super) struct LiveCandles {
+    bars: HashMap<(String, &'static str), LiveBar>,
+}
+
+impl LiveCandles {
+    /// Fold a trade into each interval's bar, returning the bars it moved
+    ///
+    /// A trade from before an interval's current bar (late delivery) only
+    /// reaches the history, not the live bar.
+    pub(super) fn update(&mut self, trade: &Trade) -> Vec<(&'static str, LiveBar)> {
+        let timestamp = trade.timestamp.timestamp();
+        let mut moved = Vec::with_capacity(CANDLE_INTERVALS.len());
+
+        for interval in CANDLE_INTERVALS {
+            let Some(secs) = interval_secs(interval) else {
+                continue;
+            };
+            let start = timestamp - timestamp.rem_euclid(secs as i64);
+            let bar = self
+                .bars
+                .entry((trade.market_id.clone(), interval))
+                .or_insert(LiveBar {
+                    start,
+                    open: trade.price,
+                    high: trade.price,
+                    low: trade.price,
+                    close: trade.price,
+                    volume: 0,
+                });
+
+            if start < bar.start {
+                continue;
+            }
+            if start > bar.start {
+                *bar = LiveBar {
+                    start,
+                    open: trade.price,
+                    high: trade.price,
+                    low: trade.price,
+                    close: trade.price,
+                    volume: 0,
+                };
+            }
+            bar.high = bar.high.max(trade.price);
+            bar.low = bar.low.min(trade.price);
+            bar.close = trade.price;
+            bar.volume = bar.volume.saturating_add(trade.size);
+            moved.push((interval, *bar));
+        }
+
+        moved
+    }
+}
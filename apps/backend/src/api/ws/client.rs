@@ -6,89 +6,48 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::Instant;
 
+use crate::errors::ErrorCode;
 use crate::models::api::{ClientMessage, ServerMessage};
 use crate::models::domain::Subscription;
+use crate::symbols::Symbols;
 
-use super::SocketState;
+use super::{RouterConnection, SocketState};
 
 /// Handle incoming messages from the client
 pub(super) async fn handle_client_messages(
     mut receiver: futures::stream::SplitStream<WebSocket>,
     socket_state: Arc<RwLock<SocketState>>,
+    connection: RouterConnection,
     ack_tx: tokio::sync::mpsc::UnboundedSender<ServerMessage>,
+    symbols: Symbols,
 ) {
     while let Some(msg) = receiver.next().await {
         match msg {
             Ok(Message::Text(text)) => {
                 // Parse and handle client message
-                if let Ok(client_msg) = serde_json::from_str::<ClientMessage>(&text) {
-                    match &client_msg {
-                        ClientMessage::Subscribe {
-                            channel,
-                            market_id,
-                            user_address,
-                        } => {
-                            if let Some(sub) = Subscription::from_message(&client_msg) {
-                                let mut state = socket_state.write().await;
-                                let was_added = state.subscriptions.subscribe(sub);
-                                state.last_subscription_change = Instant::now();
-                                drop(state);
-
-                                // Send acknowledgment
-                                let ack = ServerMessage::Subscribed {
-                                    channel: *channel,
-                                    market_id: market_id.clone(),
-                                    user_address: user_address.clone(),
-                                };
-                                let _ = ack_tx.send(ack);
-
-                                if was_added {
-                                    log::debug!("Client subscribed to {:?}", channel);
-                                } else {
-                                    log::debug!("Client already subscribed to {:?}", channel);
-                                }
-                            } else {
-                                log::warn!("Invalid subscription: missing required fields");
-                            }
-                        }
-
-                        ClientMessage::Unsubscribe {
-                            channel,
-                            market_id,
-                            user_address,
-                        } => {
-                            if let Some(sub) = Subscription::from_message(&client_msg) {
-                                let mut state = socket_state.write().await;
-                                let was_removed = state.subscriptions.unsubscribe(&sub);
-                                state.last_subscription_change = Instant::now();
-                                drop(state);
-
-                                // Send acknowledgment
-                                let ack = ServerMessage::Unsubscribed {
-                                    channel: *channel,
-                                    market_id: market_id.clone(),
-                                    user_address: user_address.clone(),
-                                };
-                                let _ = ack_tx.send(ack);
-
-                                if was_removed {
-                                    log::debug!("Client unsubscribed from {:?}", channel);
-                                } else {
-                                    log::debug!("Client was not subscribed to {:?}", channel);
-                                }
-                            } else {
-                                log::warn!("Invalid unsubscription: missing required fields");
-                            }
-                        }
-
-                        ClientMessage::Ping => {
-                            log::debug!("Received application ping, sending pong");
-                            if ack_tx.send(ServerMessage::Pong).is_err() {
-                                log::error!("Failed to send pong response");
-                            }
-                        }
+                let mut client_msg = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(client_msg) => client_msg,
+                    Err(e) => {
+                        let _ = ack_tx.send(ServerMessage::Error {
+                            code: ErrorCode::InvalidMessage,
+                            message: format!("Invalid message: {}", e),
+                        });
+                        continue;
                     }
+                };
+                // Subscriptions and their acknowledgments use the canonical market id
+                if let ClientMessage::Subscribe {
+                    market_id: Some(market_id),
+                    ..
+                }
+                | ClientMessage::Unsubscribe {
+                    market_id: Some(market_id),
+                    ..
+                } = &mut client_msg
+                {
+                    *market_id = symbols.canonical(market_id);
                 }
+                handle_client_message(client_msg, &socket_state, &connection, &ack_tx).await;
             }
             Ok(Message::Pong(_)) => {
                 socket_state.write().await.last_pong = Instant::now();
@@ -106,3 +65,94 @@ pub(super) async fn handle_client_messages(
         }
     }
 }
+
+/// Handle one parsed message from the client
+#[tracing::instrument(name = "ws.client_message", skip_all)]
+async fn handle_client_message(
+    client_msg: ClientMessage,
+    socket_state: &Arc<RwLock<SocketState>>,
+    connection: &RouterConnection,
+    ack_tx: &tokio::sync::mpsc::UnboundedSender<ServerMessage>,
+) {
+    match &client_msg {
+        ClientMessage::Subscribe {
+            channel,
+            market_id,
+            user_address,
+            interval,
+        } => {
+            if let Some(sub) = Subscription::from_message(&client_msg) {
+                connection.subscribe(sub.clone());
+                let mut state = socket_state.write().await;
+                let was_added = state.subscriptions.subscribe(sub);
+                state.last_subscription_change = Instant::now();
+                drop(state);
+
+                // Send acknowledgment
+                let ack = ServerMessage::Subscribed {
+                    channel: *channel,
+                    market_id: market_id.clone(),
+                    user_address: user_address.clone(),
+                    interval: interval.clone(),
+                };
+                let _ = ack_tx.send(ack);
+
+                if was_added {
+                    log::debug!("Client subscribed to {:?}", channel);
+                } else {
+                    log::debug!("Client already subscribed to {:?}", channel);
+                }
+            } else {
+                log::debug!("Invalid subscription: missing required fields");
+                send_invalid_subscription(ack_tx);
+            }
+        }
+
+        ClientMessage::Unsubscribe {
+            channel,
+            market_id,
+            user_address,
+            interval,
+        } => {
+            if let Some(sub) = Subscription::from_message(&client_msg) {
+                connection.unsubscribe(&sub);
+                let mut state = socket_state.write().await;
+                let was_removed = state.subscriptions.unsubscribe(&sub);
+                state.last_subscription_change = Instant::now();
+                drop(state);
+
+                // Send acknowledgment
+                let ack = ServerMessage::Unsubscribed {
+                    channel: *channel,
+                    market_id: market_id.clone(),
+                    user_address: user_address.clone(),
+                    interval: interval.clone(),
+                };
+                let _ = ack_tx.send(ack);
+
+                if was_removed {
+                    log::debug!("Client unsubscribed from {:?}", channel);
+                } else {
+                    log::debug!("Client was not subscribed to {:?}", channel);
+                }
+            } else {
+                log::debug!("Invalid unsubscription: missing required fields");
+                send_invalid_subscription(ack_tx);
+            }
+        }
+
+        ClientMessage::Ping => {
+            log::debug!("Received application ping, sending pong");
+            if ack_tx.send(ServerMessage::Pong).is_err() {
+                log::error!("Failed to send pong response");
+            }
+        }
+    }
+}
+
+fn send_invalid_subscription(ack_tx: &tokio::sync::mpsc::UnboundedSender<ServerMessage>) {
+    let _ = ack_tx.send(ServerMessage::Error {
+        code: ErrorCode::InvalidSubscription,
+        message: "Channel needs a market, user or interval the message didn't give".to_string(),
+    });
+}
@@ -1,6 +1,9 @@
+mod candles;
 mod client;
+mod router;
 mod server;
 mod state;
+mod tickers;
 
 use axum::{
     extract::{
@@ -18,6 +21,9 @@ use tokio::sync::RwLock;
 use crate::models::api::ServerMessage;
 use state::SocketState;
 
+pub use router::{EventRouter, RouterConnection, CONNECTION_BUFFER_SIZE};
+pub use tickers::{spawn_tickers, ticker_data, TICKER_INTERVAL};
+
 // Configuration constants
 pub(crate) const PING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
 pub(crate) const PONG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
@@ -36,7 +42,7 @@ async fn ws_handler(ws: WebSocketUpgrade, State(state): State<crate::AppState>)
 async fn handle_socket(socket: WebSocket, state: crate::AppState) {
     // sender sends to client, receiver receives from client
     let (sender, receiver) = socket.split();
-    let event_rx = state.event_tx.subscribe();
+    let (connection, message_rx) = state.event_router.connect();
 
     // Shared socket state
     let socket_state = Arc::new(RwLock::new(SocketState::new()));
@@ -47,16 +53,19 @@ async fn handle_socket(socket: WebSocket, state: crate::AppState) {
     // Task 1: Handle incoming messages from client (receiver)
     let recv_task = {
         let socket_state = socket_state.clone();
-        tokio::spawn(
-            async move { client::handle_client_messages(receiver, socket_state, ack_tx).await },
-        )
+        let symbols = state.symbols.clone();
+        tokio::spawn(async move {
+            client::handle_client_messages(receiver, socket_state, connection, ack_tx, symbols)
+                .await
+        })
     };
 
     // Task 2: Send outgoing messages to client (sender)
     let send_task = {
         let socket_state = socket_state.clone();
+        let shutdown = state.shutdown.triggered();
         tokio::spawn(async move {
-            server::handle_server_messages(sender, event_rx, socket_state, ack_rx).await
+            server::handle_server_messages(sender, message_rx, socket_state, ack_rx, shutdown).await
         })
     };
 
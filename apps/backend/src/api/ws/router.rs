@@ -0,0 +1,405 @@
+//! Topic router - fans engine events out to the connections subscribed to them
+
+use axum::extract::ws::Utf8Bytes;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+use crate::models::api::{
+    Notification, OrderbookData, PriceLevel, ServerMessage, TickerData, TradeData,
+};
+use crate::models::domain::{EngineEvent, OrderStatus, Subscription, Trade};
+use crate::saturation::Saturation;
+
+use super::candles::LiveCandles;
+
+/// Messages buffered per connection before new ones are dropped
+pub const CONNECTION_BUFFER_SIZE: usize = 1000;
+
+type ConnectionId = u64;
+type Outbox = mpsc::Sender<Utf8Bytes>;
+
+#[derive(Default)]
+struct Routes {
+    // topic -> connections subscribed to it
+    topics: HashMap<Subscription, HashMap<ConnectionId, Outbox>>,
+    next_connection: ConnectionId,
+}
+
+/// Routes engine events to WebSocket connections by topic
+///
+/// Each market and user is its own topic (a [`Subscription`]), so an event is
+/// converted once and delivered only to the connections subscribed to its
+/// topic - fan-out cost follows the relevant subscribers, not the number of
+/// open connections.
+///
+/// Messages are serialized to JSON once per event and the shared payload is
+/// handed to every subscriber, so sockets never re-encode the same message.
+///
+/// Trades also build the live candles, one topic per market and interval, so
+/// a connection can follow several intervals of a market at once.
+#[derive(Clone, Default)]
+pub struct EventRouter {
+    routes: Arc<RwLock<Routes>>,
+    candles: Arc<Mutex<LiveCandles>>,
+    saturation: Saturation,
+}
+
+impl EventRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report skipped events to `saturation`, and shed tickers while it's degraded
+    pub fn with_saturation(mut self, saturation: Saturation) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    pub fn saturation(&self) -> &Saturation {
+        &self.saturation
+    }
+
+    /// Start routing events from the engine's broadcast channel
+    pub fn spawn(&self, mut event_rx: broadcast::Receiver<EngineEvent>) -> JoinHandle<()> {
+        let router = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match event_rx.recv().await {
+                    Ok(event) => router.route(&event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Event router lagged, skipped {} engine events", skipped);
+                        router.saturation.record_lag("ws_router", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Register a connection; it receives nothing until it subscribes
+    pub fn connect(&self) -> (RouterConnection, mpsc::Receiver<Utf8Bytes>) {
+        let (outbox, inbox) = mpsc::channel(CONNECTION_BUFFER_SIZE);
+        let mut routes = self.routes.write().unwrap();
+        let id = routes.next_connection;
+        routes.next_connection += 1;
+
+        (
+            RouterConnection {
+                id,
+                outbox,
+                router: self.clone(),
+            },
+            inbox,
+        )
+    }
+
+    /// Deliver an event to every connection subscribed to its topics
+    pub fn route(&self, event: &EngineEvent) {
+        let routes = self.routes.read().unwrap();
+
+        match event {
+            EngineEvent::TradeExecuted { trade, .. } => {
+                let market = Subscription::Trades {
+                    market_id: trade.market_id.clone(),
+                };
+                if let Some(subscribers) = routes.topics.get(&market) {
+                    let message = ServerMessage::Trade {
+                        trade: trade_data(trade),
+                    };
+                    send_all(subscribers.iter(), message);
+                }
+
+                // A connection following both sides of a trade gets a single fill
+                let mut fills: HashMap<ConnectionId, &Outbox> = HashMap::new();
+                for user_address in [&trade.buyer_address, &trade.seller_address] {
+                    let topic = Subscription::UserFills {
+                        user_address: user_address.clone(),
+                    };
+                    if let Some(subscribers) = routes.topics.get(&topic) {
+                        fills.extend(subscribers.iter().map(|(id, outbox)| (*id, outbox)));
+                    }
+                }
+                if !fills.is_empty() {
+                    let message = ServerMessage::UserFill {
+                        trade: trade_data(trade),
+                    };
+                    send_all(fills.iter().map(|(id, outbox)| (id, *outbox)), message);
+                }
+
+                let bars = self.candles.lock().unwrap().update(trade);
+                for (interval, bar) in bars {
+                    let topic = Subscription::Candles {
+                        market_id: trade.market_id.clone(),
+                        interval: interval.to_string(),
+                    };
+                    if let Some(subscribers) = routes.topics.get(&topic) {
+                        let message = ServerMessage::Candle {
+                            market_id: trade.market_id.clone(),
+                            interval: interval.to_string(),
+                            timestamp: bar.start,
+                            open: bar.open.to_string(),
+                            high: bar.high.to_string(),
+                            low: bar.low.to_string(),
+                            close: bar.close.to_string(),
+                            volume: bar.volume.to_string(),
+                        };
+                        send_all(subscribers.iter(), message);
+                    }
+                }
+            }
+            EngineEvent::OrderPlaced { order } => {
+                let topic = Subscription::UserOrders {
+                    user_address: order.user_address.clone(),
+                };
+                if let Some(subscribers) = routes.topics.get(&topic) {
+                    let message = ServerMessage::UserOrder {
+                        order_id: order.id.to_string(),
+                        status: order.status,
+                        filled_size: order.filled_size.to_string(),
+                        reason: None,
+                    };
+                    send_all(subscribers.iter(), message);
+                }
+            }
+            EngineEvent::OrderCancelled {
+                order_id,
+                user_address,
+                reason,
+            } => {
+                let topic = Subscription::UserOrders {
+                    user_address: user_address.clone(),
+                };
+                if let Some(subscribers) = routes.topics.get(&topic) {
+                    let message = ServerMessage::UserOrder {
+                        order_id: order_id.to_string(),
+                        status: OrderStatus::Cancelled,
+                        filled_size: "0".to_string(),
+                        reason: *reason,
+                    };
+                    send_all(subscribers.iter(), message);
+                }
+
+                // Users already know about the cancels they asked for
+                if let Some(reason) = reason {
+                    let notification = Notification::OrderCancelled {
+                        order_id: order_id.to_string(),
+                        reason: *reason,
+                    };
+                    notify(&routes, user_address, notification);
+                }
+            }
+            EngineEvent::OrderRejected {
+                order_id,
+                user_address,
+                market_id,
+                code,
+                reason,
+                message,
+            } => {
+                let notification = Notification::OrderRejected {
+                    order_id: order_id.to_string(),
+                    market_id: market_id.clone(),
+                    code: *code,
+                    reason: *reason,
+                    message: message.clone(),
+                };
+                notify(&routes, user_address, notification);
+            }
+            EngineEvent::MarketStatusChanged { market_id, status } => {
+                let subscribers = routes
+                    .topics
+                    .iter()
+                    .filter(|(topic, _)| matches!(topic, Subscription::Notifications { .. }))
+                    .flat_map(|(_, subscribers)| subscribers.iter());
+                let message = ServerMessage::UserNotification {
+                    notification: Notification::MarketStatusChanged {
+                        market_id: market_id.clone(),
+                        status: *status,
+                    },
+                };
+                send_all(subscribers, message);
+            }
+            EngineEvent::BalanceUpdated { balance } => {
+                let topic = Subscription::UserBalances {
+                    user_address: balance.user_address.clone(),
+                };
+                if let Some(subscribers) = routes.topics.get(&topic) {
+                    let message = ServerMessage::UserBalance {
+                        user_address: balance.user_address.clone(),
+                        token_ticker: balance.token_ticker.clone(),
+                        available: balance
+                            .amount
+                            .saturating_sub(balance.open_interest)
+                            .to_string(),
+                        locked: balance.open_interest.to_string(),
+                        updated_at: balance.updated_at.timestamp(),
+                    };
+                    send_all(subscribers.iter(), message);
+                }
+            }
+            // Balance changes from withdrawals arrive as their own BalanceUpdated
+            EngineEvent::WithdrawalUpdated { withdrawal } => {
+                let notification = Notification::WithdrawalUpdated {
+                    withdrawal_id: withdrawal.id.to_string(),
+                    token_ticker: withdrawal.token_ticker.clone(),
+                    amount: withdrawal.amount.to_string(),
+                    status: withdrawal.status,
+                    tx_hash: withdrawal.tx_hash.clone(),
+                    error: withdrawal.error.clone(),
+                    updated_at: withdrawal.updated_at.timestamp(),
+                };
+                notify(&routes, &withdrawal.user_address, notification);
+            }
+            EngineEvent::PositionLiquidated { liquidation } => {
+                let topic = Subscription::UserOrders {
+                    user_address: liquidation.user_address.clone(),
+                };
+                if let Some(subscribers) = routes.topics.get(&topic) {
+                    let message = ServerMessage::UserLiquidation {
+                        liquidation: liquidation.clone().into(),
+                    };
+                    send_all(subscribers.iter(), message);
+                }
+            }
+            EngineEvent::OrderbookSnapshot { orderbook, .. } => {
+                let topic = Subscription::Orderbook {
+                    market_id: orderbook.market_id.clone(),
+                };
+                if let Some(subscribers) = routes.topics.get(&topic) {
+                    let levels = |levels: &[crate::models::domain::OrderbookLevel]| {
+                        levels
+                            .iter()
+                            .map(|level| PriceLevel {
+                                price: level.price.to_string(),
+                                size: level.size.to_string(),
+                            })
+                            .collect()
+                    };
+                    let message = ServerMessage::Orderbook {
+                        orderbook: OrderbookData {
+                            market_id: orderbook.market_id.clone(),
+                            bids: levels(&orderbook.bids),
+                            asks: levels(&orderbook.asks),
+                        },
+                    };
+                    send_all(subscribers.iter(), message);
+                }
+            }
+        }
+    }
+
+    /// Send every market's ticker to the connections following tickers
+    pub fn publish_tickers(&self, tickers: Vec<TickerData>) {
+        let routes = self.routes.read().unwrap();
+        if let Some(subscribers) = routes.topics.get(&Subscription::Tickers) {
+            let message = ServerMessage::Tickers {
+                tickers,
+                timestamp: chrono::Utc::now().timestamp_millis(),
+            };
+            send_all(subscribers.iter(), message);
+        }
+    }
+
+    /// Number of connections subscribed to a topic
+    pub fn subscriber_count(&self, topic: &Subscription) -> usize {
+        self.routes
+            .read()
+            .unwrap()
+            .topics
+            .get(topic)
+            .map_or(0, HashMap::len)
+    }
+}
+
+/// A connection's registration with the [`EventRouter`]
+///
+/// Dropping it removes the connection from every topic it subscribed to.
+pub struct RouterConnection {
+    id: ConnectionId,
+    outbox: Outbox,
+    router: EventRouter,
+}
+
+impl RouterConnection {
+    pub fn subscribe(&self, topic: Subscription) {
+        let mut routes = self.router.routes.write().unwrap();
+        routes
+            .topics
+            .entry(topic)
+            .or_default()
+            .insert(self.id, self.outbox.clone());
+    }
+
+    pub fn unsubscribe(&self, topic: &Subscription) {
+        let mut routes = self.router.routes.write().unwrap();
+        if let Some(subscribers) = routes.topics.get_mut(topic) {
+            subscribers.remove(&self.id);
+            if subscribers.is_empty() {
+                routes.topics.remove(topic);
+            }
+        }
+    }
+}
+
+impl Drop for RouterConnection {
+    fn drop(&mut self) {
+        let mut routes = self.router.routes.write().unwrap();
+        routes.topics.retain(|_, subscribers| {
+            subscribers.remove(&self.id);
+            !subscribers.is_empty()
+        });
+    }
+}
+
+/// Serialize a message once and queue it for each subscriber without waiting on slow clients
+fn send_all<'a>(
+    subscribers: impl Iterator<Item = (&'a ConnectionId, &'a Outbox)>,
+    message: ServerMessage,
+) {
+    let payload = match serde_json::to_string(&message) {
+        Ok(json) => Utf8Bytes::from(json),
+        Err(e) => {
+            log::error!("Failed to serialize {:?}: {}", message, e);
+            return;
+        }
+    };
+
+    for (id, outbox) in subscribers {
+        // Cloning the payload shares the encoded bytes
+        if let Err(mpsc::error::TrySendError::Full(_)) = outbox.try_send(payload.clone()) {
+            log::warn!("Connection {} is not keeping up, dropped a message", id);
+        }
+    }
+}
+
+/// Send a notification to the connections following a user's notifications
+fn notify(routes: &Routes, user_address: &str, notification: Notification) {
+    let topic = Subscription::Notifications {
+        user_address: user_address.to_string(),
+    };
+    if let Some(subscribers) = routes.topics.get(&topic) {
+        send_all(
+            subscribers.iter(),
+            ServerMessage::UserNotification { notification },
+        );
+    }
+}
+
+fn trade_data(trade: &Trade) -> TradeData {
+    TradeData {
+        id: trade.id.to_string(),
+        market_id: trade.market_id.clone(),
+        buyer_address: trade.buyer_address.clone(),
+        seller_address: trade.seller_address.clone(),
+        buyer_order_id: trade.buyer_order_id.to_string(),
+        seller_order_id: trade.seller_order_id.to_string(),
+        price: trade.price.to_string(),
+        size: trade.size.to_string(),
+        side: trade.side,
+        timestamp: trade.timestamp.timestamp(),
+        rfq: trade.rfq,
+    }
+}
@@ -3,7 +3,6 @@
 use std::collections::HashSet;
 use tokio::time::Instant;
 
-use crate::models::domain::EngineEvent;
 use crate::models::domain::Subscription;
 
 // ============================================================================
@@ -31,7 +30,7 @@ impl SocketState {
 // SubscriptionSet - Manages client subscriptions
 // ============================================================================
 
-/// Manages client subscriptions and determines which events to forward
+/// Tracks a client's subscriptions (delivery itself goes through the `EventRouter`)
 #[derive(Debug, Default)]
 pub(crate) struct SubscriptionSet {
     subs: HashSet<Subscription>,
@@ -52,47 +51,6 @@ impl SubscriptionSet {
         self.subs.remove(sub)
     }
 
-    pub(crate) fn has_subscription(&self, sub: &Subscription) -> bool {
-        self.subs.contains(sub)
-    }
-
-    pub(crate) fn wants_event(&self, event: &EngineEvent) -> bool {
-        match event {
-            EngineEvent::TradeExecuted { trade } => {
-                // Send to market trades subscription
-                self.subs.contains(&Subscription::Trades {
-                    market_id: trade.market_id.clone(),
-                })
-                // OR send to buyer's user fills subscription
-                || self.subs.contains(&Subscription::UserFills {
-                    user_address: trade.buyer_address.clone(),
-                })
-                // OR send to seller's user fills subscription
-                || self.subs.contains(&Subscription::UserFills {
-                    user_address: trade.seller_address.clone(),
-                })
-            }
-            EngineEvent::OrderPlaced { order } => self.subs.contains(&Subscription::UserOrders {
-                user_address: order.user_address.clone(),
-            }),
-            EngineEvent::OrderCancelled { user_address, .. } => {
-                self.subs.contains(&Subscription::UserOrders {
-                    user_address: user_address.clone(),
-                })
-            }
-            EngineEvent::BalanceUpdated { balance } => {
-                self.subs.contains(&Subscription::UserBalances {
-                    user_address: balance.user_address.clone(),
-                })
-            }
-            EngineEvent::OrderbookSnapshot { orderbook } => {
-                self.subs.contains(&Subscription::Orderbook {
-                    market_id: orderbook.market_id.clone(),
-                })
-            }
-        }
-    }
-
     pub(crate) fn is_empty(&self) -> bool {
         self.subs.is_empty()
     }
@@ -0,0 +1,74 @@
+//! Tickers - every market's summary on one throttled channel
+
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::api::rest::stats::cached_market_stats;
+use crate::cache::{keys, ReadCache};
+use crate::db::Db;
+use crate::models::api::{ApiMarketStats, ApiTopOfBook, TickerData};
+use crate::models::domain::Subscription;
+
+use super::EventRouter;
+
+/// How often the tickers channel is refreshed
+pub const TICKER_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A market's ticker from its 24h stats and latest top of book
+pub fn ticker_data(stats: &ApiMarketStats, top: Option<&ApiTopOfBook>) -> TickerData {
+    TickerData {
+        market_id: stats.market_id.clone(),
+        last: stats.close.clone(),
+        best_bid: top.and_then(|top| top.best_bid.as_ref().map(|level| level.price.clone())),
+        best_ask: top.and_then(|top| top.best_ask.as_ref().map(|level| level.price.clone())),
+        change_percent: stats.change_percent,
+        volume: stats.base_volume.clone(),
+    }
+}
+
+/// Send every market's ticker to the tickers channel each `interval`
+///
+/// Stats and top of book come through the read cache, so a refresh costs a
+/// ClickHouse query only for markets that traded since the last one. Nothing
+/// is read while no connection follows tickers.
+pub fn spawn_tickers(
+    router: EventRouter,
+    db: Db,
+    cache: ReadCache,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticks.tick().await;
+            // Tickers are the first market data to go when the exchange is saturated
+            if router.subscriber_count(&Subscription::Tickers) == 0
+                || router.saturation().is_degraded()
+            {
+                continue;
+            }
+
+            let markets = match db.list_markets().await {
+                Ok(markets) => markets,
+                Err(e) => {
+                    log::warn!("Failed to list markets for tickers: {}", e);
+                    continue;
+                }
+            };
+            let mut tickers = Vec::with_capacity(markets.len());
+            for market in markets {
+                let stats = match cached_market_stats(&db, &cache, &market.id).await {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        log::warn!("Failed to get {} stats for tickers: {}", market.id, e);
+                        continue;
+                    }
+                };
+                let top: Option<ApiTopOfBook> = cache.get(&keys::top_of_book(&market.id)).await;
+                tickers.push(ticker_data(&stats, top.as_ref()));
+            }
+            router.publish_tickers(tickers);
+        }
+    })
+}
@@ -0,0 +1,466 @@
+// daily Parquet archives of trades and candles in object storage
+
+use crate::db::Db;
+use crate::models::domain::{Side, Trade};
+use anyhow::{bail, Context};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use clickhouse::query::BytesCursor;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload, WriteMultipart};
+use parquet::file::reader::FileReader;
+use parquet::file::serialized_reader::SerializedFileReader;
+use parquet::record::Field;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Version of the manifest and file layout, bumped on every breaking change
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// Closed days the archiver looks back over for ones still missing
+pub const ARCHIVE_LOOKBACK_DAYS: u64 = 7;
+
+/// How often the archiver checks for days to archive
+const ARCHIVE_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Trades restored per ClickHouse insert
+pub(crate) const RESTORE_BATCH_SIZE: usize = 10_000;
+
+/// A dataset archived once per day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    Trades,
+    Candles,
+}
+
+impl Dataset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dataset::Trades => "trades",
+            Dataset::Candles => "candles",
+        }
+    }
+}
+
+/// One Parquet file of an archived day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveFile {
+    pub dataset: Dataset,
+    /// Location relative to the archive root
+    pub path: String,
+    pub rows: u64,
+    pub bytes: u64,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+}
+
+/// Lists the files of an archived day; written last, so a day without one
+/// was never archived or is still being written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub schema_version: u32,
+    pub date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<ArchiveFile>,
+}
+
+impl ArchiveManifest {
+    pub fn file(&self, dataset: Dataset) -> Option<&ArchiveFile> {
+        self.files.iter().find(|f| f.dataset == dataset)
+    }
+}
+
+/// Hive-style location of a dataset's file for a day, e.g.
+/// `trades/date=2025-11-20/trades.parquet`
+pub fn data_path(dataset: Dataset, date: NaiveDate) -> String {
+    format!(
+        "{}/date={}/{}.parquet",
+        dataset.as_str(),
+        date,
+        dataset.as_str()
+    )
+}
+
+/// Location of a day's manifest, e.g. `manifests/date=2025-11-20.json`
+pub fn manifest_path(date: NaiveDate) -> String {
+    format!("manifests/date={}.json", date)
+}
+
+/// Unix seconds bounding a UTC day, as [start, end)
+pub(crate) fn day_bounds(date: NaiveDate) -> (i64, i64) {
+    let start = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+    (start, start + 24 * 60 * 60)
+}
+
+/// Object storage the archives are written to, under a root prefix
+#[derive(Clone)]
+pub struct ArchiveStore {
+    store: Arc<dyn ObjectStore>,
+    root: Path,
+}
+
+impl ArchiveStore {
+    pub fn new(store: Arc<dyn ObjectStore>, root: impl Into<Path>) -> Self {
+        Self {
+            store,
+            root: root.into(),
+        }
+    }
+
+    /// Open `s3://bucket/prefix` or `file:///dir`
+    ///
+    /// S3 credentials, region and the endpoint of S3-compatible storage are
+    /// read from the usual `AWS_*` environment variables.
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        let parsed =
+            reqwest::Url::parse(url).with_context(|| format!("Invalid archive URL '{}'", url))?;
+        let aws_options = std::env::vars()
+            .filter(|(key, _)| key.starts_with("AWS_"))
+            .map(|(key, value)| (key.to_ascii_lowercase(), value));
+        let (store, root) = object_store::parse_url_opts(&parsed, aws_options)?;
+        Ok(Self::new(store.into(), root))
+    }
+
+    fn location(&self, path: &str) -> Path {
+        Path::from_iter(self.root.parts().chain(Path::from(path).parts()))
+    }
+
+    /// The manifest of an archived day, or `None` if the day isn't archived
+    pub async fn manifest(&self, date: NaiveDate) -> anyhow::Result<Option<ArchiveManifest>> {
+        self.get_json(&manifest_path(date)).await
+    }
+
+    async fn put_manifest(&self, manifest: &ArchiveManifest) -> anyhow::Result<()> {
+        self.put_json(&manifest_path(manifest.date), manifest).await
+    }
+
+    /// The JSON document at `path`, or `None` if there is none
+    pub(crate) async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<Option<T>> {
+        match self.store.get(&self.location(path)).await {
+            Ok(result) => Ok(Some(serde_json::from_slice(&result.bytes().await?)?)),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(crate) async fn put_json<T: Serialize>(&self, path: &str, value: &T) -> anyhow::Result<()> {
+        let body = serde_json::to_vec_pretty(value)?;
+        self.store
+            .put(&self.location(path), PutPayload::from(body))
+            .await?;
+        Ok(())
+    }
+
+    /// Names of the directories directly under `prefix`
+    pub(crate) async fn list_dirs(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        let listing = self
+            .store
+            .list_with_delimiter(Some(&self.location(prefix)))
+            .await?;
+        Ok(listing
+            .common_prefixes
+            .iter()
+            .filter_map(|dir| dir.filename().map(str::to_string))
+            .collect())
+    }
+
+    /// Upload a ClickHouse cursor as one file, hashing it on the way
+    pub(crate) async fn upload(
+        &self,
+        path: &str,
+        cursor: BytesCursor,
+    ) -> anyhow::Result<(u64, String)> {
+        let chunks = futures::stream::try_unfold(cursor, |mut cursor| async move {
+            Ok::<_, clickhouse::error::Error>(cursor.next().await?.map(|chunk| (chunk, cursor)))
+        });
+        self.upload_stream(path, Box::pin(chunks)).await
+    }
+
+    /// Upload a stream of chunks as one file, hashing it on the way
+    pub(crate) async fn upload_stream<S, E>(
+        &self,
+        path: &str,
+        mut chunks: S,
+    ) -> anyhow::Result<(u64, String)>
+    where
+        S: Stream<Item = Result<bytes::Bytes, E>> + Unpin,
+        E: Into<anyhow::Error>,
+    {
+        let upload = self.store.put_multipart(&self.location(path)).await?;
+        let mut writer = WriteMultipart::new(upload);
+        let mut hasher = Sha256::new();
+        let mut bytes = 0;
+
+        while let Some(chunk) = chunks.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    writer.wait_for_capacity(4).await?;
+                    hasher.update(&chunk);
+                    bytes += chunk.len() as u64;
+                    writer.write(&chunk);
+                }
+                Err(e) => {
+                    let _ = writer.abort().await;
+                    return Err(e.into());
+                }
+            }
+        }
+        writer.finish().await?;
+
+        Ok((bytes, hex::encode(hasher.finalize())))
+    }
+
+    /// The chunks of the file at `path` as they arrive, unchecked; callers
+    /// hash them and discard what they did with them on a mismatch
+    pub(crate) async fn download_stream(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<bytes::Bytes>>> {
+        let result = self.store.get(&self.location(path)).await?;
+        Ok(result.into_stream().map_err(anyhow::Error::from).boxed())
+    }
+
+    /// Download a file listed in a manifest, checking it against its hash
+    pub(crate) async fn download(&self, file: &ArchiveFile) -> anyhow::Result<bytes::Bytes> {
+        let data = self
+            .store
+            .get(&self.location(&file.path))
+            .await?
+            .bytes()
+            .await?;
+        let sha256 = hex::encode(Sha256::digest(&data));
+        if sha256 != file.sha256 {
+            bail!(
+                "{} is corrupt: SHA-256 {} but the manifest lists {}",
+                file.path,
+                sha256,
+                file.sha256
+            );
+        }
+        Ok(data)
+    }
+}
+
+/// Writes each closed UTC day's trades and candles to the archive store
+///
+/// Archiving is idempotent: a day with a manifest is skipped, and a day that
+/// failed halfway is rewritten in full on the next run.
+pub struct Archiver {
+    db: Db,
+    store: ArchiveStore,
+}
+
+impl Archiver {
+    pub fn new(db: Db, store: ArchiveStore) -> Self {
+        Self { db, store }
+    }
+
+    /// Archive one day unless it already is, returning its manifest
+    pub async fn archive_day(&self, date: NaiveDate) -> anyhow::Result<ArchiveManifest> {
+        if let Some(manifest) = self.store.manifest(date).await? {
+            return Ok(manifest);
+        }
+
+        let (from, to) = day_bounds(date);
+        let mut files = Vec::new();
+        for dataset in [Dataset::Trades, Dataset::Candles] {
+            let (rows, cursor) = match dataset {
+                Dataset::Trades => (
+                    self.db.count_trades_between(from, to).await?,
+                    self.db.archive_trades(from, to)?,
+                ),
+                Dataset::Candles => (
+                    self.db.count_candles_between(from, to).await?,
+                    self.db.archive_candles(from, to)?,
+                ),
+            };
+            let path = data_path(dataset, date);
+            let (bytes, sha256) = self
+                .store
+                .upload(&path, cursor)
+                .await
+                .with_context(|| format!("Failed to archive {}", path))?;
+            files.push(ArchiveFile {
+                dataset,
+                path,
+                rows,
+                bytes,
+                sha256,
+            });
+        }
+
+        let manifest = ArchiveManifest {
+            schema_version: ARCHIVE_SCHEMA_VERSION,
+            date,
+            created_at: Utc::now(),
+            files,
+        };
+        self.store.put_manifest(&manifest).await?;
+        Ok(manifest)
+    }
+
+    /// Archive every closed day in the lookback window that is still missing
+    pub async fn archive_closed_days(&self, now: DateTime<Utc>) -> anyhow::Result<()> {
+        let today = now.date_naive();
+        for days_ago in (1..=ARCHIVE_LOOKBACK_DAYS).rev() {
+            let date = today - Days::new(days_ago);
+            if self.store.manifest(date).await?.is_some() {
+                continue;
+            }
+            let manifest = self.archive_day(date).await?;
+            log::info!(
+                "Archived {}: {}",
+                date,
+                manifest
+                    .files
+                    .iter()
+                    .map(|f| format!("{} {} rows", f.dataset.as_str(), f.rows))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Archive closed days every hour until the task is dropped
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(ARCHIVE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.archive_closed_days(Utc::now()).await {
+                log::error!("Archival failed: {:#}", e);
+            }
+        }
+    }
+
+    /// Load an archived day's trades back into ClickHouse
+    ///
+    /// Candles are rebuilt from the restored trades by the candle views, so
+    /// the candle files are for external analytics only. Refuses to restore a
+    /// day that still has trades or candles unless `force` is set, since
+    /// restoring on top of them would count those trades twice.
+    pub async fn restore_day(&self, date: NaiveDate, force: bool) -> anyhow::Result<u64> {
+        let manifest = self
+            .store
+            .manifest(date)
+            .await?
+            .with_context(|| format!("{} is not archived", date))?;
+        if manifest.schema_version != ARCHIVE_SCHEMA_VERSION {
+            bail!(
+                "{} was archived with schema version {}, expected {}",
+                date,
+                manifest.schema_version,
+                ARCHIVE_SCHEMA_VERSION
+            );
+        }
+        let file = manifest
+            .file(Dataset::Trades)
+            .with_context(|| format!("Manifest for {} lists no trades", date))?;
+
+        let (from, to) = day_bounds(date);
+        if !force {
+            let trades = self.db.count_trades_between(from, to).await?;
+            let candles = self.db.count_candles_between(from, to).await?;
+            if trades > 0 || candles > 0 {
+                bail!(
+                    "{} still has {} trades and {} candles in ClickHouse; restoring would count them twice",
+                    date,
+                    trades,
+                    candles
+                );
+            }
+        }
+
+        let data = self.store.download(file).await?;
+        let trades = read_trades(data)?;
+        if trades.len() as u64 != file.rows {
+            bail!(
+                "{} has {} trades but the manifest lists {}",
+                file.path,
+                trades.len(),
+                file.rows
+            );
+        }
+        for batch in trades.chunks(RESTORE_BATCH_SIZE) {
+            self.db.insert_trades_to_clickhouse(batch).await?;
+        }
+        Ok(trades.len() as u64)
+    }
+}
+
+/// Decode an archived trades file
+pub fn read_trades(data: bytes::Bytes) -> anyhow::Result<Vec<Trade>> {
+    let reader = SerializedFileReader::new(data)?;
+    let mut trades = Vec::new();
+
+    for row in reader.get_row_iter(None)? {
+        let row = row?;
+        let mut id = None;
+        let mut market_id = None;
+        let mut buyer_address = None;
+        let mut seller_address = None;
+        let mut buyer_order_id = None;
+        let mut seller_order_id = None;
+        let mut price = None;
+        let mut size = None;
+        let mut side = None;
+        let mut timestamp = None;
+        // Files archived before RFQ trades existed have no such column
+        let mut rfq = false;
+
+        for (name, field) in row.get_column_iter() {
+            match name.as_str() {
+                "id" => id = Some(text(field)?.parse()?),
+                "market_id" => market_id = Some(text(field)?),
+                "buyer_address" => buyer_address = Some(text(field)?),
+                "seller_address" => seller_address = Some(text(field)?),
+                "buyer_order_id" => buyer_order_id = Some(text(field)?.parse()?),
+                "seller_order_id" => seller_order_id = Some(text(field)?.parse()?),
+                "price" => price = Some(text(field)?.parse()?),
+                "size" => size = Some(text(field)?.parse()?),
+                "side" => side = Some(text(field)?.parse::<Side>().map_err(anyhow::Error::msg)?),
+                "timestamp" => {
+                    let Field::Long(secs) = field else {
+                        bail!("Expected timestamp seconds, found {}", field);
+                    };
+                    timestamp = DateTime::from_timestamp(*secs, 0);
+                }
+                "rfq" => rfq = matches!(field, Field::Bool(true)),
+                _ => {}
+            }
+        }
+
+        trades.push(Trade {
+            id: id.context("Archived trade has no id")?,
+            market_id: market_id.context("Archived trade has no market_id")?,
+            buyer_address: buyer_address.context("Archived trade has no buyer_address")?,
+            seller_address: seller_address.context("Archived trade has no seller_address")?,
+            buyer_order_id: buyer_order_id.context("Archived trade has no buyer_order_id")?,
+            seller_order_id: seller_order_id.context("Archived trade has no seller_order_id")?,
+            price: price.context("Archived trade has no price")?,
+            size: size.context("Archived trade has no size")?,
+            side: side.context("Archived trade has no side")?,
+            timestamp: timestamp.context("Archived trade has no timestamp")?,
+            rfq,
+        });
+    }
+
+    Ok(trades)
+}
+
+/// A string column, which ClickHouse may write with or without the UTF-8 annotation
+fn text(field: &Field) -> anyhow::Result<String> {
+    match field {
+        Field::Str(s) => Ok(s.clone()),
+        Field::Bytes(b) => Ok(std::str::from_utf8(b.data())?.to_string()),
+        other => bail!("Expected a string, found {}", other),
+    }
+}
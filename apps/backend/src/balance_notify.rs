@@ -0,0 +1,93 @@
+// balance changes made outside the engine, announced over Postgres NOTIFY
+
+use crate::db::Db;
+use crate::models::domain::EngineEvent;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Postgres channel balance changes are announced on
+pub const BALANCE_CHANNEL: &str = "balance_updated";
+
+/// Wait before listening again after the connection drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Which balance changed; the new balance is read back when it arrives
+///
+/// Anything that writes balances can announce a change, including tools
+/// outside the backend: `SELECT pg_notify('balance_updated', '{"user_address":
+/// "...","token_ticker":"..."}')`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceNotice {
+    pub user_address: String,
+    pub token_ticker: String,
+}
+
+/// Turn every announced balance change into a `BalanceUpdated` event
+///
+/// Runs alongside the engine, so its events reach every gateway's
+/// subscribers the way engine-driven changes do.
+pub fn spawn_balance_listener(db: Db, event_tx: broadcast::Sender<EngineEvent>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = listen(&db, &event_tx).await {
+                log::warn!("Balance change listener stopped: {}", e);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    })
+}
+
+async fn listen(db: &Db, event_tx: &broadcast::Sender<EngineEvent>) -> Result<(), sqlx::Error> {
+    if let Some(memory) = &db.memory {
+        let mut notices = memory.balance_notices();
+        loop {
+            match notices.recv().await {
+                Ok(notice) => announce(db, event_tx, &notice).await,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    log::warn!("Missed {} balance notices", missed)
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    let mut listener = PgListener::connect_with(&db.postgres).await?;
+    listener.listen(BALANCE_CHANNEL).await?;
+
+    loop {
+        let notification = listener.recv().await?;
+        let notice: BalanceNotice = match serde_json::from_str(notification.payload()) {
+            Ok(notice) => notice,
+            Err(e) => {
+                log::warn!(
+                    "Ignoring malformed balance notice {:?}: {}",
+                    notification.payload(),
+                    e
+                );
+                continue;
+            }
+        };
+        announce(db, event_tx, &notice).await;
+    }
+}
+
+/// Send the announced balance out as it is now
+async fn announce(db: &Db, event_tx: &broadcast::Sender<EngineEvent>, notice: &BalanceNotice) {
+    match db
+        .get_balance(&notice.user_address, &notice.token_ticker)
+        .await
+    {
+        Ok(balance) => {
+            let _ = event_tx.send(EngineEvent::BalanceUpdated { balance });
+        }
+        Err(e) => log::warn!(
+            "Failed to read announced balance of {} {}: {}",
+            notice.user_address,
+            notice.token_ticker,
+            e
+        ),
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::{bail, Context, Result};
+use backend::archive::{ArchiveStore, Archiver};
+use backend::db::Db;
+use chrono::NaiveDate;
+
+const USAGE: &str = "Usage:
+  archive export <YYYY-MM-DD> [<YYYY-MM-DD>]   archive a day, or each day of a range
+  archive show <YYYY-MM-DD>                    print a day's manifest
+  archive restore <YYYY-MM-DD> [--force]       load a day's trades back into ClickHouse
+
+Reads ARCHIVE_URL and the database URLs from the environment.";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Load .env files for database URLs
+    let _ = dotenvy::from_path(".env.defaults");
+    let _ = dotenvy::from_path(".env");
+
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        bail!("{}", USAGE);
+    };
+    let date = |i: usize| -> Result<NaiveDate> {
+        let arg = args.get(i).with_context(|| USAGE.to_string())?;
+        arg.parse()
+            .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", arg))
+    };
+
+    let url = std::env::var("ARCHIVE_URL").context("ARCHIVE_URL must be set")?;
+    let store = ArchiveStore::from_url(&url)?;
+
+    match command.as_str() {
+        "show" => {
+            let date = date(1)?;
+            let manifest = store
+                .manifest(date)
+                .await?
+                .with_context(|| format!("{} is not archived", date))?;
+            println!("{}", serde_json::to_string_pretty(&manifest)?);
+        }
+        "export" => {
+            let from = date(1)?;
+            let to = if args.len() > 2 { date(2)? } else { from };
+            let db = Db::connect()
+                .await
+                .context("Failed to connect to database")?;
+            let archiver = Archiver::new(db, store);
+            for day in from.iter_days().take_while(|day| *day <= to) {
+                let manifest = archiver.archive_day(day).await?;
+                for file in &manifest.files {
+                    println!("{}  {} rows  {} bytes", file.path, file.rows, file.bytes);
+                }
+            }
+        }
+        "restore" => {
+            let date = date(1)?;
+            let force = args.iter().any(|arg| arg == "--force");
+            let db = Db::connect()
+                .await
+                .context("Failed to connect to database")?;
+            let restored = Archiver::new(db, store).restore_day(date, force).await?;
+            println!("Restored {} trades for {}", restored, date);
+        }
+        _ => bail!("{}", USAGE),
+    }
+
+    Ok(())
+}
@@ -1,27 +1,29 @@
-use std::fs;
-use utoipa::OpenApi;
-
-use backend::api::rest::ApiDoc;
+use backend::schema;
 
+/// Usage: generate_openapi [--check]
+///
+/// `--check` compares against the committed file instead of writing it and
+/// exits non-zero on backward-incompatible changes.
 fn main() {
-    println!("Generating OpenAPI specification...");
+    let file = schema::openapi();
 
-    // Generate the OpenAPI spec
-    let openapi_spec = ApiDoc::openapi();
-    let openapi_json =
-        serde_json::to_string_pretty(&openapi_spec).expect("Failed to serialize OpenAPI spec");
+    if std::env::args().any(|arg| arg == "--check") {
+        if !schema::report(&[file]) {
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    // Ensure the output directory exists
-    let output_dir = "../../packages/shared";
-    fs::create_dir_all(output_dir).expect("Failed to create output directory");
+    println!("Generating OpenAPI specification...");
 
-    // Write directly to the shared package
-    let output_path = "../../packages/shared/openapi.json";
-    if let Err(e) = fs::write(output_path, &openapi_json) {
-        eprintln!("Error: Could not write {}: {}", output_path, e);
-        std::process::exit(1);
+    match file.write() {
+        Ok(path) => {
+            println!("✅ Generated {}", path.display());
+            println!("📄 {} bytes written", file.contents.len());
+        }
+        Err(e) => {
+            eprintln!("Error: Could not write {}: {}", file.path().display(), e);
+            std::process::exit(1);
+        }
     }
-
-    println!("✅ Generated {}", output_path);
-    println!("📄 {} bytes written", openapi_json.len());
 }
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use backend::bootstrap;
 use backend::config::Config;
 use backend::db::Db;
 
@@ -13,7 +14,9 @@ async fn main() -> Result<()> {
     println!("🚀 Initializing Exchange...\n");
 
     // Load backend configuration
-    let config = Config::load().context("Failed to load config.toml")?;
+    let config_path = Config::path();
+    let config = Config::load_from(&config_path)
+        .with_context(|| format!("Failed to load {}", config_path.display()))?;
 
     // Connect to database
     let db = Db::connect()
@@ -21,75 +24,31 @@ async fn main() -> Result<()> {
         .context("Failed to connect to database")?;
     log::info!("✅ Connected to databases");
 
-    // Create tokens
-    println!("📦 Creating tokens...");
-    for token_config in &config.tokens {
-        match db
-            .create_token(
-                token_config.ticker.clone(),
-                token_config.decimals,
-                token_config.name.clone(),
-            )
-            .await
-        {
-            Ok(_) => println!(
+    // Create the tokens and markets that don't exist yet (the backend also does this on startup)
+    let report = bootstrap::create_missing(&db, &config).await?;
+
+    println!("📦 Tokens:");
+    for token in &config.tokens {
+        if report.tokens_created.contains(&token.ticker) {
+            println!(
                 "  ✓ Created token: {} ({}, {} decimals)",
-                token_config.ticker, token_config.name, token_config.decimals
-            ),
-            Err(e) => {
-                // Token might already exist, that's ok
-                log::debug!(
-                    "Token {} already exists or error: {}",
-                    token_config.ticker,
-                    e
-                );
-                println!("  ⊙ Token {} already exists", token_config.ticker);
-            }
+                token.ticker, token.name, token.decimals
+            );
+        } else {
+            println!("  ⊙ Token {} already exists", token.ticker);
         }
     }
 
-    // Create markets
-    println!("\n🏪 Creating markets...");
-    for market_config in &config.markets {
-        let market_id = format!(
-            "{}/{}",
-            market_config.base_ticker, market_config.quote_ticker
-        );
-
-        // Parse string values to u128
-        let tick_size = market_config
-            .tick_size
-            .parse::<u128>()
-            .context("Invalid tick_size")?;
-        let lot_size = market_config
-            .lot_size
-            .parse::<u128>()
-            .context("Invalid lot_size")?;
-        let min_size = market_config
-            .min_size
-            .parse::<u128>()
-            .context("Invalid min_size")?;
-
-        match db
-            .create_market(
-                market_config.base_ticker.clone(),
-                market_config.quote_ticker.clone(),
-                tick_size,
-                lot_size,
-                min_size,
-                market_config.maker_fee_bps,
-                market_config.taker_fee_bps,
-            )
-            .await
-        {
-            Ok(_) => println!(
+    println!("\n🏪 Markets:");
+    for market in &config.markets {
+        let market_id = market.market_id();
+        if report.markets_created.contains(&market_id) {
+            println!(
                 "  ✓ Created market: {} (tick: {}, lot: {}, min: {})",
-                market_id, tick_size, lot_size, min_size
-            ),
-            Err(e) => {
-                log::debug!("Market {} already exists or error: {}", market_id, e);
-                println!("  ⊙ Market {} already exists", market_id);
-            }
+                market_id, market.tick_size, market.lot_size, market.min_size
+            );
+        } else {
+            println!("  ⊙ Market {} already exists", market_id);
         }
     }
 
@@ -0,0 +1,200 @@
+use anyhow::Context;
+use axum::Router;
+use backend::api::rest;
+use backend::api::signature::SignatureCheck;
+use backend::api::{auth, ws};
+use backend::balance_notify;
+use backend::bootstrap;
+use backend::cache::ReadCache;
+use backend::config::Config;
+use backend::db::Db;
+use backend::engine::MatchingEngine;
+use backend::models::domain::{EngineEvent, EngineRequest};
+use backend::saturation::Saturation;
+use backend::shutdown::{self, Shutdown};
+use backend::sim::{SimMarket, SyntheticTrader, MAKER_ADDRESS, TAKER_ADDRESS};
+use backend::symbols::{self, Symbols};
+use backend::AppState;
+use tokio::sync::{broadcast, mpsc};
+use tower_http::cors::CorsLayer;
+
+const USAGE: &str = "Usage: exchange-sim [--no-trader]
+
+Runs the REST and WebSocket API and the matching engine with everything kept
+in memory, seeded with the tokens and markets of CONFIG_PATH (default
+config.toml). A synthetic trader quotes and trades every market unless
+--no-trader is given. Listens on HOST:PORT (default localhost:8888).";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Load .env files for HOST, PORT and ADMIN_TOKEN
+    let _ = dotenvy::from_path(".env.defaults");
+    let _ = dotenvy::from_path_override(".env");
+
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let trader = match args.as_slice() {
+        [] => true,
+        ["--no-trader"] => false,
+        ["help" | "--help" | "-h"] => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        _ => anyhow::bail!("{}", USAGE),
+    };
+
+    let config_path = Config::path();
+    let mut config = Config::load_from(&config_path).context("Failed to load configuration")?;
+    let host = std::env::var("HOST").unwrap_or_else(|_| "localhost".to_string());
+    let port = std::env::var("PORT").unwrap_or_else(|_| "8888".to_string());
+    let addr = format!("{}:{}", host, port);
+
+    // Positions, funding and margin need the databases
+    config.markets.retain(|market| {
+        if market.perpetual.is_some() {
+            log::warn!("Skipping perpetual market {}", market.market_id());
+        }
+        market.perpetual.is_none()
+    });
+
+    let db = Db::in_memory()?;
+    bootstrap::create_missing(&db, &config)
+        .await
+        .context("Failed to set up tokens and markets from configuration")?;
+
+    // ===============================
+    // Run matching engine
+    // ===============================
+    let (engine_tx, engine_rx) = mpsc::channel::<EngineRequest>(100);
+    let (event_tx, _) = broadcast::channel::<EngineEvent>(1000);
+    let shutdown = Shutdown::new();
+    let saturation = Saturation::default();
+
+    let mut engine = MatchingEngine::new(db.clone(), engine_rx, event_tx.clone());
+    engine.set_saturation(saturation.clone());
+    let mut sim_markets = Vec::new();
+    for market in &config.markets {
+        let market_id = market.market_id();
+        let layout = market.ladder_layout()?;
+        engine.set_ladder_layout(&market_id, layout).await;
+        if let Some(collar_bps) = market.price_collar_bps {
+            engine.set_price_collar(&market_id, collar_bps);
+        }
+        let quote_decimals = config
+            .token_decimals(&market.quote_ticker)
+            .with_context(|| format!("Unknown quote token for {}", market_id))?;
+        sim_markets.push(SimMarket::new(
+            db.get_market(&market_id).await?,
+            quote_decimals,
+            layout,
+        ));
+    }
+    engine
+        .load_state()
+        .await
+        .context("Failed to load engine state")?;
+    let engine_handle = tokio::spawn(engine.run());
+
+    // Faucet credits reach WebSocket clients as balance updates
+    balance_notify::spawn_balance_listener(db.clone(), event_tx.clone());
+
+    if trader {
+        let trader = SyntheticTrader::new(engine_tx.clone(), sim_markets);
+        trader.fund(&db).await?;
+        tokio::spawn(trader.run());
+    }
+
+    // ===============================
+    // Create axum app
+    // ===============================
+    let cache = ReadCache::default();
+    cache
+        .clone()
+        .with_saturation(saturation.clone())
+        .spawn_updater(event_tx.subscribe());
+
+    let event_router = ws::EventRouter::new().with_saturation(saturation.clone());
+    event_router.spawn(event_tx.subscribe());
+    ws::spawn_tickers(
+        event_router.clone(),
+        db.clone(),
+        cache.clone(),
+        ws::TICKER_INTERVAL,
+    );
+
+    let symbols = Symbols::load(&db).await?;
+    symbols
+        .clone()
+        .spawn_refresher(db.clone(), symbols::REFRESH_INTERVAL);
+
+    let state = AppState {
+        db,
+        engine_tx,
+        event_tx,
+        event_router,
+        admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+        cache,
+        exports: Default::default(),
+        l3: Default::default(),
+        // The frontend doesn't sign orders yet
+        signatures: SignatureCheck::default().with_unsigned_trading(true),
+        shutdown: shutdown.clone(),
+        saturation,
+        symbols,
+    };
+
+    let app = Router::new()
+        .merge(rest::create_rest())
+        .merge(ws::create_ws())
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::authenticate,
+        ))
+        .with_state(state)
+        .layer(CorsLayer::permissive());
+
+    // ===============================
+    // Start server
+    // ===============================
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .context(format!("Failed to bind to {}", addr))?;
+
+    println!("\n🧪 Simulated exchange running on http://{}", addr);
+    println!("📖 OpenAPI docs: http://{}/api/docs", addr);
+    println!(
+        "🏪 Markets: {}",
+        config
+            .markets
+            .iter()
+            .map(|m| m.market_id())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if trader {
+        println!(
+            "🤖 Synthetic trader: {} quoting, {} taking",
+            MAKER_ADDRESS, TAKER_ADDRESS
+        );
+    }
+    println!("\n💡 Nothing is saved: state is gone when the simulator stops\n");
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown::terminate().await;
+            shutdown.trigger();
+        }
+    })
+    .await
+    .context("Server error")?;
+
+    engine_handle.abort();
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anyhow::{bail, Context, Result};
+use backend::archive::ArchiveStore;
+use backend::db::Db;
+use backend::snapshot::{self, Snapshotter, DEFAULT_TRADE_DAYS};
+
+const USAGE: &str = "Usage:
+  snapshot take <name> [--trade-days <n>]   snapshot Postgres and the last n days of trades (default 7)
+  snapshot show <name>                      print a snapshot's manifest
+  snapshot list                             list the snapshots in SNAPSHOT_URL
+  snapshot restore <name> [--force]         load a snapshot into this environment
+
+Restore into a freshly migrated environment before starting its backend;
+--force replaces a database that already has data.
+
+Reads SNAPSHOT_URL and the database URLs from the environment.";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Load .env files for database URLs
+    let _ = dotenvy::from_path(".env.defaults");
+    let _ = dotenvy::from_path(".env");
+
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        bail!("{}", USAGE);
+    };
+    let name = || -> Result<&str> {
+        args.get(1)
+            .filter(|arg| !arg.starts_with("--"))
+            .map(String::as_str)
+            .with_context(|| USAGE.to_string())
+    };
+
+    let url = std::env::var("SNAPSHOT_URL").context("SNAPSHOT_URL must be set")?;
+    let store = ArchiveStore::from_url(&url)?;
+    let db = || async { Db::connect().await.context("Failed to connect to database") };
+
+    match command.as_str() {
+        "take" => {
+            let name = name()?;
+            let trade_days = match args.iter().position(|arg| arg == "--trade-days") {
+                Some(i) => {
+                    let arg = args.get(i + 1).with_context(|| USAGE.to_string())?;
+                    arg.parse()
+                        .with_context(|| format!("Invalid --trade-days '{}'", arg))?
+                }
+                None => DEFAULT_TRADE_DAYS,
+            };
+            let manifest = Snapshotter::new(db().await?, store)
+                .take(name, trade_days)
+                .await?;
+            for table in &manifest.tables {
+                println!("{}  {} rows  {} bytes", table.path, table.rows, table.bytes);
+            }
+            for day in &manifest.trades {
+                println!(
+                    "{}  {} rows  {} bytes",
+                    day.file.path, day.file.rows, day.file.bytes
+                );
+            }
+            println!(
+                "Snapshot '{}' taken at {}",
+                manifest.name, manifest.taken_at
+            );
+        }
+        "show" => {
+            let name = name()?;
+            let manifest = snapshot::manifest(&store, name)
+                .await?
+                .with_context(|| format!("No snapshot named '{}'", name))?;
+            println!("{}", serde_json::to_string_pretty(&manifest)?);
+        }
+        "list" => {
+            for manifest in snapshot::list(&store).await? {
+                println!(
+                    "{}  {}  migration {}  {} rows  {} days of trades",
+                    manifest.name,
+                    manifest.taken_at,
+                    manifest.migration,
+                    manifest.rows(),
+                    manifest.trades.len()
+                );
+            }
+        }
+        "restore" => {
+            let name = name()?;
+            let force = args.iter().any(|arg| arg == "--force");
+            let report = Snapshotter::new(db().await?, store)
+                .restore(name, force)
+                .await?;
+            for date in &report.skipped_days {
+                println!("Skipped trades of {}, which ClickHouse already has", date);
+            }
+            println!(
+                "Restored {} rows into {} tables and {} trades from '{}'",
+                report.rows, report.tables, report.trades, name
+            );
+        }
+        _ => bail!("{}", USAGE),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,294 @@
+// declarative setup: the tokens and markets of config.toml, created while running
+
+use crate::config::{Config, MarketConfig};
+use crate::db::Db;
+use crate::engine::ladder::LadderLayout;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{EngineRequest, Market};
+use anyhow::Context;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::sync::{mpsc, oneshot};
+
+/// What applying a configuration created
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BootstrapReport {
+    pub tokens_created: Vec<String>,
+    pub markets_created: Vec<String>,
+}
+
+/// A configured market's sizes, checked and parsed
+struct MarketSpec {
+    market_id: String,
+    tick_size: u128,
+    lot_size: u128,
+    min_size: u128,
+}
+
+impl MarketSpec {
+    fn parse(market: &MarketConfig) -> anyhow::Result<Self> {
+        let market_id = market.market_id();
+        let parse = |field: &str, value: &str| {
+            value
+                .parse::<u128>()
+                .with_context(|| format!("Invalid {} for {}: '{}'", field, market_id, value))
+        };
+        market.ladder_layout()?;
+        if let Some(perpetual) = &market.perpetual {
+            anyhow::ensure!(
+                market.index_feed.is_some(),
+                "Perpetual market {} needs an index_feed for its mark price",
+                market_id
+            );
+            anyhow::ensure!(
+                perpetual.funding_interval_secs > 0,
+                "Perpetual market {} needs a positive funding_interval_secs",
+                market_id
+            );
+            anyhow::ensure!(
+                (1..=10000).contains(&perpetual.initial_margin_bps),
+                "Perpetual market {} needs an initial_margin_bps between 1 and 10000",
+                market_id
+            );
+            anyhow::ensure!(
+                (1..=perpetual.initial_margin_bps).contains(&perpetual.maintenance_margin_bps),
+                "Perpetual market {} needs a maintenance_margin_bps between 1 and its initial_margin_bps",
+                market_id
+            );
+            anyhow::ensure!(
+                perpetual.liquidation_fee_bps <= 10000,
+                "Perpetual market {} needs a liquidation_fee_bps of at most 10000",
+                market_id
+            );
+        }
+        Ok(Self {
+            tick_size: parse("tick_size", &market.tick_size)?,
+            lot_size: parse("lot_size", &market.lot_size)?,
+            min_size: parse("min_size", &market.min_size)?,
+            market_id,
+        })
+    }
+
+    /// Settings of an existing market that differ from the configuration
+    fn differences(&self, market: &Market, config: &MarketConfig) -> Vec<String> {
+        let sizes = [
+            ("tick_size", market.tick_size, self.tick_size),
+            ("lot_size", market.lot_size, self.lot_size),
+            ("min_size", market.min_size, self.min_size),
+        ];
+        let fees = [
+            ("maker_fee_bps", market.maker_fee_bps, config.maker_fee_bps),
+            ("taker_fee_bps", market.taker_fee_bps, config.taker_fee_bps),
+        ];
+
+        sizes
+            .iter()
+            .filter(|(_, live, configured)| live != configured)
+            .map(|(field, live, configured)| {
+                format!("{} is {}, config has {}", field, live, configured)
+            })
+            .chain(
+                fees.iter()
+                    .filter(|(_, live, configured)| live != configured)
+                    .map(|(field, live, configured)| {
+                        format!("{} is {}, config has {}", field, live, configured)
+                    }),
+            )
+            .collect()
+    }
+}
+
+/// Create the configured tokens and markets that don't exist yet
+///
+/// The whole configuration is checked before anything is written. Existing
+/// tokens and markets are never modified - their decimals and tick sizes
+/// back live balances and orders - so differences from the configuration
+/// are only logged.
+pub async fn create_missing(db: &Db, config: &Config) -> anyhow::Result<BootstrapReport> {
+    let specs = config
+        .markets
+        .iter()
+        .map(MarketSpec::parse)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    for market in &config.markets {
+        for ticker in [&market.base_ticker, &market.quote_ticker] {
+            let configured = config.tokens.iter().any(|token| &token.ticker == ticker);
+            if !configured && db.get_token(ticker).await.is_err() {
+                anyhow::bail!(
+                    "Market {} needs unknown token {}",
+                    market.market_id(),
+                    ticker
+                );
+            }
+        }
+    }
+
+    let mut report = BootstrapReport::default();
+
+    let tokens: HashMap<String, u8> = db
+        .list_tokens()
+        .await?
+        .into_iter()
+        .map(|token| (token.ticker, token.decimals))
+        .collect();
+    for token in &config.tokens {
+        match tokens.get(&token.ticker) {
+            Some(&decimals) if decimals != token.decimals => log::warn!(
+                "Token {} has {} decimals, config has {}; keeping {}",
+                token.ticker,
+                decimals,
+                token.decimals,
+                decimals
+            ),
+            Some(_) => {}
+            None => {
+                db.create_token(token.ticker.clone(), token.decimals, token.name.clone())
+                    .await?;
+                log::info!("Created token {}", token.ticker);
+                report.tokens_created.push(token.ticker.clone());
+            }
+        }
+    }
+
+    let markets: HashMap<String, Market> = db
+        .list_markets()
+        .await?
+        .into_iter()
+        .map(|market| (market.id.clone(), market))
+        .collect();
+    for (market, spec) in config.markets.iter().zip(&specs) {
+        if let Some(existing) = markets.get(&spec.market_id) {
+            for difference in spec.differences(existing, market) {
+                log::warn!("Market {}: {}; keeping it", spec.market_id, difference);
+            }
+            let perpetual = db.get_perpetual_market(&spec.market_id).await?.is_some();
+            if perpetual != market.perpetual.is_some() {
+                log::warn!(
+                    "Market {} is {}, config has it {}; keeping it",
+                    spec.market_id,
+                    if perpetual { "perpetual" } else { "spot" },
+                    if perpetual { "spot" } else { "perpetual" }
+                );
+            }
+            continue;
+        }
+        db.create_market(
+            market.base_ticker.clone(),
+            market.quote_ticker.clone(),
+            spec.tick_size,
+            spec.lot_size,
+            spec.min_size,
+            market.maker_fee_bps,
+            market.taker_fee_bps,
+        )
+        .await?;
+        if let Some(perpetual) = &market.perpetual {
+            db.set_perpetual_market(
+                &spec.market_id,
+                perpetual.funding_interval_secs,
+                perpetual.max_funding_rate_ppm,
+                perpetual.initial_margin_bps,
+                perpetual.maintenance_margin_bps,
+                perpetual.liquidation_fee_bps,
+            )
+            .await?;
+        }
+        log::info!("Created market {}", spec.market_id);
+        report.markets_created.push(spec.market_id.clone());
+    }
+
+    Ok(report)
+}
+
+/// Open a market in the running engine with its configured layout and collar
+pub async fn open_market(
+    engine_tx: &mpsc::Sender<EngineRequest>,
+    market_id: String,
+    layout: LadderLayout,
+    collar_bps: Option<u32>,
+) -> Result<()> {
+    let (response_tx, response_rx) = oneshot::channel();
+    engine_tx
+        .send(EngineRequest::OpenMarket {
+            market_id,
+            layout,
+            collar_bps,
+            response_tx,
+        })
+        .await
+        .map_err(|_| ExchangeError::EngineSendFailed)?;
+
+    response_rx
+        .await
+        .map_err(|_| ExchangeError::EngineReceiveFailed)?
+}
+
+/// Bring a running exchange in line with a configuration
+///
+/// Missing tokens and markets are created, and every created market is
+/// opened in the engine so it trades, and streams over WebSocket, right away.
+/// Index feeds only start on restart.
+pub async fn apply(
+    db: &Db,
+    engine_tx: &mpsc::Sender<EngineRequest>,
+    config: &Config,
+) -> anyhow::Result<BootstrapReport> {
+    let report = create_missing(db, config).await?;
+
+    for market in &config.markets {
+        let market_id = market.market_id();
+        if !report.markets_created.contains(&market_id) {
+            continue;
+        }
+        if market.index_feed.is_some() {
+            log::warn!("Index feed for {} starts on the next restart", market_id);
+        }
+        let layout = market.ladder_layout()?;
+        open_market(
+            engine_tx,
+            market_id.clone(),
+            layout,
+            market.price_collar_bps,
+        )
+        .await
+        .with_context(|| format!("Failed to open market {}", market_id))?;
+    }
+
+    Ok(report)
+}
+
+/// Apply the configuration at `path` again on every SIGHUP
+pub async fn reload_on_hangup(db: Db, engine_tx: mpsc::Sender<EngineRequest>, path: PathBuf) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                log::error!("Failed to listen for SIGHUP, config reload disabled: {}", e);
+                return;
+            }
+        };
+        while hangups.recv().await.is_some() {
+            log::info!("Reloading {}", path.display());
+            let result = match Config::load_from(&path) {
+                Ok(config) => apply(&db, &engine_tx, &config).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(report) => log::info!(
+                    "Config reloaded: {} tokens and {} markets created",
+                    report.tokens_created.len(),
+                    report.markets_created.len()
+                ),
+                Err(e) => log::error!("Config reload failed: {:#}", e),
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (db, engine_tx, path);
+        std::future::pending::<()>().await;
+    }
+}
@@ -0,0 +1,219 @@
+// short-lived cache for hot public reads: market lists, tickers, top of book
+
+use crate::models::api::{ApiBookLevel, ApiTopOfBook};
+use crate::models::domain::{EngineEvent, OrderbookSnapshot};
+use crate::saturation::Saturation;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+
+/// Key prefix used when `CACHE_PREFIX` is unset
+pub const DEFAULT_KEY_PREFIX: &str = "exchange";
+
+/// Token and market lists; they only change when an admin adds one
+pub const LISTINGS_TTL: Duration = Duration::from_secs(30);
+
+/// Top of book; the engine refreshes it every second while a book is open
+pub const TOP_OF_BOOK_TTL: Duration = Duration::from_secs(5);
+
+/// Cache keys, before the prefix
+pub mod keys {
+    pub const MARKETS: &str = "markets";
+    pub const TOKENS: &str = "tokens";
+
+    pub fn market(market_id: &str) -> String {
+        format!("market:{}", market_id)
+    }
+
+    pub fn token(ticker: &str) -> String {
+        format!("token:{}", ticker)
+    }
+
+    pub fn ticker(market_id: &str) -> String {
+        format!("ticker:{}", market_id)
+    }
+
+    pub fn top_of_book(market_id: &str) -> String {
+        format!("book:{}", market_id)
+    }
+}
+
+/// Where cached values live
+#[derive(Clone)]
+enum Store {
+    /// This process only
+    Memory(Arc<RwLock<HashMap<String, (Instant, String)>>>),
+    /// Shared by every API replica
+    Redis(ConnectionManager),
+}
+
+/// Cache of public read responses, shared by all handlers
+///
+/// Values are stored as JSON under short TTLs and dropped as soon as an
+/// engine event or admin change makes them stale. With `REDIS_URL` set every
+/// API replica shares one cache; otherwise each process keeps its own.
+/// Redis errors are logged and treated as misses, so reads fall through to
+/// Postgres and ClickHouse rather than fail.
+#[derive(Clone)]
+pub struct ReadCache {
+    store: Store,
+    prefix: String,
+    saturation: Saturation,
+}
+
+impl Default for ReadCache {
+    fn default() -> Self {
+        Self {
+            store: Store::Memory(Default::default()),
+            prefix: DEFAULT_KEY_PREFIX.to_string(),
+            saturation: Saturation::default(),
+        }
+    }
+}
+
+impl ReadCache {
+    /// Share the cache through the Redis server at `url` (`redis://host:port/db`)
+    pub async fn connect(url: &str, prefix: impl Into<String>) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = client.get_connection_manager().await?;
+        Ok(Self {
+            store: Store::Redis(conn),
+            prefix: prefix.into(),
+            saturation: Saturation::default(),
+        })
+    }
+
+    /// Report events the updater skips to `saturation`
+    pub fn with_saturation(mut self, saturation: Saturation) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:cache:{}", self.prefix, key)
+    }
+
+    /// The cached value under `key`, if there is a fresh one
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let key = self.key(key);
+        let json = match &self.store {
+            Store::Memory(entries) => {
+                let entries = entries.read().await;
+                let (expires_at, json) = entries.get(&key)?;
+                if *expires_at <= Instant::now() {
+                    return None;
+                }
+                json.clone()
+            }
+            Store::Redis(conn) => match conn.clone().get::<_, Option<String>>(&key).await {
+                Ok(json) => json?,
+                Err(e) => {
+                    log::warn!("Cache read of {} failed: {}", key, e);
+                    return None;
+                }
+            },
+        };
+
+        match serde_json::from_str(&json) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                // Most likely written by a different version of the backend
+                log::warn!("Ignoring unreadable cache entry {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Cache `value` under `key` for `ttl`
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        let key = self.key(key);
+        let json = match serde_json::to_string(value) {
+            Ok(json) => json,
+            Err(e) => {
+                log::warn!("Failed to serialize cache entry {}: {}", key, e);
+                return;
+            }
+        };
+
+        match &self.store {
+            Store::Memory(entries) => {
+                let mut entries = entries.write().await;
+                let now = Instant::now();
+                entries.retain(|_, (expires_at, _)| *expires_at > now);
+                entries.insert(key, (now + ttl, json));
+            }
+            Store::Redis(conn) => {
+                let ttl_ms = ttl.as_millis().max(1) as u64;
+                if let Err(e) = conn.clone().pset_ex::<_, _, ()>(&key, json, ttl_ms).await {
+                    log::warn!("Cache write of {} failed: {}", key, e);
+                }
+            }
+        }
+    }
+
+    /// Drop the entries under `keys` so the next read goes to the source
+    pub async fn invalidate(&self, keys: &[String]) {
+        let keys: Vec<String> = keys.iter().map(|key| self.key(key)).collect();
+        match &self.store {
+            Store::Memory(entries) => {
+                let mut entries = entries.write().await;
+                for key in &keys {
+                    entries.remove(key);
+                }
+            }
+            Store::Redis(conn) => {
+                if let Err(e) = conn.clone().del::<_, ()>(&keys).await {
+                    // Whatever is left expires with its TTL
+                    log::warn!("Cache invalidation of {:?} failed: {}", keys, e);
+                }
+            }
+        }
+    }
+
+    /// Keep the cache in step with the engine: refresh top of book from each
+    /// orderbook snapshot and drop a market's ticker when it trades
+    pub fn spawn_updater(self, mut events: broadcast::Receiver<EngineEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(EngineEvent::TradeExecuted { trade, .. }) => {
+                        self.invalidate(&[keys::ticker(&trade.market_id)]).await;
+                    }
+                    Ok(EngineEvent::OrderbookSnapshot { orderbook, .. }) => {
+                        let key = keys::top_of_book(&orderbook.market_id);
+                        self.set(&key, &top_of_book(&orderbook), TOP_OF_BOOK_TTL)
+                            .await;
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        // Missed invalidations are covered by the TTLs
+                        log::warn!("Read cache lagged, skipped {} engine events", skipped);
+                        self.saturation.record_lag("read_cache", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Best bid and ask of an orderbook snapshot
+pub fn top_of_book(orderbook: &OrderbookSnapshot) -> ApiTopOfBook {
+    let level = |level: &crate::models::domain::OrderbookLevel| ApiBookLevel {
+        price: level.price.to_string(),
+        size: level.size.to_string(),
+    };
+    ApiTopOfBook {
+        market_id: orderbook.market_id.clone(),
+        best_bid: orderbook.bids.first().map(level),
+        best_ask: orderbook.asks.first().map(level),
+        version: orderbook.version,
+        timestamp: orderbook.timestamp.timestamp_millis(),
+    }
+}
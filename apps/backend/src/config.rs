@@ -1,11 +1,18 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::engine::ladder::{LadderLayout, MAX_LADDER_SLOTS};
+use crate::price_feed::sources::IndexSource;
 
 /// Backend configuration (from apps/backend/config.toml)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub markets: Vec<MarketConfig>,
     pub tokens: Vec<TokenConfig>,
+    /// On-chain deposits to watch, and the chain withdrawals go out on; both need `DEPOSIT_RPC_URL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposits: Option<DepositConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +24,108 @@ pub struct MarketConfig {
     pub min_size: String,
     pub maker_fee_bps: i32,
     pub taker_fee_bps: i32,
+    /// Bounded price range; when set the market uses an array-indexed orderbook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_ladder: Option<PriceLadderConfig>,
+    /// Reject limit orders priced further than this from the last trade, in basis points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_collar_bps: Option<u32>,
+    /// External index price; when fresh it replaces the last trade as the collar reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_feed: Option<IndexSource>,
+    /// Trade perpetual futures margined in the quote token instead of spot;
+    /// needs an index feed for the mark price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perpetual: Option<PerpetualConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpetualConfig {
+    #[serde(default = "default_funding_interval_secs")]
+    pub funding_interval_secs: u32,
+    /// Cap on one interval's funding rate, in millionths of notional
+    #[serde(default = "default_max_funding_rate_ppm")]
+    pub max_funding_rate_ppm: u32,
+    /// Share of notional orders lock as margin, in basis points; 10000 is unleveraged
+    #[serde(default = "default_initial_margin_bps")]
+    pub initial_margin_bps: u32,
+    /// Share of notional below which positions are liquidated, in basis points
+    #[serde(default = "default_maintenance_margin_bps")]
+    pub maintenance_margin_bps: u32,
+    /// Charged on the notional a liquidation closes, in basis points
+    #[serde(default)]
+    pub liquidation_fee_bps: u32,
+}
+
+fn default_funding_interval_secs() -> u32 {
+    crate::perps::DEFAULT_FUNDING_INTERVAL_SECS
+}
+
+fn default_max_funding_rate_ppm() -> u32 {
+    crate::perps::DEFAULT_MAX_FUNDING_RATE_PPM
+}
+
+fn default_initial_margin_bps() -> u32 {
+    crate::perps::DEFAULT_INITIAL_MARGIN_BPS
+}
+
+fn default_maintenance_margin_bps() -> u32 {
+    crate::perps::DEFAULT_MAINTENANCE_MARGIN_BPS
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceLadderConfig {
+    #[serde(default = "default_min_price")]
+    pub min_price: String,
+    pub max_price: String,
+}
+
+fn default_min_price() -> String {
+    "0".to_string()
+}
+
+impl MarketConfig {
+    /// Market id in `BASE/QUOTE` form
+    pub fn market_id(&self) -> String {
+        format!("{}/{}", self.base_ticker, self.quote_ticker)
+    }
+
+    /// Orderbook layout for this market
+    pub fn ladder_layout(&self) -> Result<LadderLayout> {
+        let Some(ladder) = &self.price_ladder else {
+            return Ok(LadderLayout::Tree);
+        };
+
+        let tick_size = self
+            .tick_size
+            .parse::<u128>()
+            .context("Invalid tick_size")?;
+        let min_price = ladder
+            .min_price
+            .parse::<u128>()
+            .context("Invalid price_ladder.min_price")?;
+        let max_price = ladder
+            .max_price
+            .parse::<u128>()
+            .context("Invalid price_ladder.max_price")?;
+        anyhow::ensure!(
+            tick_size > 0 && min_price <= max_price,
+            "price_ladder for {} needs a positive tick_size and min_price <= max_price",
+            self.market_id()
+        );
+        anyhow::ensure!(
+            (max_price - min_price) / tick_size < MAX_LADDER_SLOTS,
+            "price_ladder for {} spans more than {} ticks",
+            self.market_id(),
+            MAX_LADDER_SLOTS
+        );
+
+        Ok(LadderLayout::Array {
+            min_price,
+            max_price,
+            tick_size,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,13 +135,56 @@ pub struct TokenConfig {
     pub name: String,
 }
 
+/// ERC-20 transfers to `deposit_addresses` are credited to their sender
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositConfig {
+    pub chain_id: u64,
+    /// Blocks a transfer needs on top of it, counting its own, before it is credited
+    pub confirmations: u64,
+    pub deposit_addresses: Vec<String>,
+    /// First block to scan on a fresh database; defaults to the current confirmed block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_block: Option<u64>,
+    pub tokens: Vec<DepositTokenConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositTokenConfig {
+    pub ticker: String,
+    /// ERC-20 contract address
+    pub contract: String,
+    /// Decimals of the contract, when they differ from the exchange token's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+}
+
 impl Config {
-    /// Load backend configuration from config.toml
-    /// Uses CARGO_MANIFEST_DIR so the path is consistent regardless of where the binary is run from
+    /// Decimals of a configured token
+    pub fn token_decimals(&self, ticker: &str) -> Option<u8> {
+        self.tokens
+            .iter()
+            .find(|token| token.ticker == ticker)
+            .map(|token| token.decimals)
+    }
+
+    /// Load backend configuration from `Config::path()`
     pub fn load() -> Result<Self> {
-        let config_path = format!("{}/config.toml", env!("CARGO_MANIFEST_DIR"));
-        let contents = std::fs::read_to_string(&config_path)?;
-        let config: Config = toml::from_str(&contents)?;
+        Self::load_from(&Self::path())
+    }
+
+    /// Where the configuration lives: `CONFIG_PATH`, or config.toml next to
+    /// the backend's Cargo.toml so the path doesn't depend on the working directory
+    pub fn path() -> PathBuf {
+        std::env::var("CONFIG_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.toml"))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Config = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
         Ok(config)
     }
 }
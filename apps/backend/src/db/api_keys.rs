@@ -0,0 +1,167 @@
+use crate::db::rfq::user_not_found;
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{ApiKey, ApiKeyScope};
+use chrono::Utc;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+
+const API_KEY_COLUMNS: &str = "id, user_address, label, key_prefix, scopes, ip_allowlist, \
+     created_at, rotated_at, last_used_at";
+
+impl Db {
+    /// Store a new API key for a user by its hash
+    pub async fn create_api_key(
+        &self,
+        user_address: &str,
+        label: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &[ApiKeyScope],
+        ip_allowlist: &[String],
+    ) -> Result<ApiKey> {
+        let api_key = ApiKey {
+            id: Uuid::new_v4(),
+            user_address: user_address.to_string(),
+            label: label.to_string(),
+            key_prefix: key_prefix.to_string(),
+            scopes: scopes.to_vec(),
+            ip_allowlist: ip_allowlist.to_vec(),
+            created_at: Utc::now(),
+            rotated_at: None,
+            last_used_at: None,
+        };
+        let scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys
+                (id, user_address, label, key_prefix, key_hash, scopes, ip_allowlist, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(api_key.id)
+        .bind(&api_key.user_address)
+        .bind(&api_key.label)
+        .bind(&api_key.key_prefix)
+        .bind(key_hash)
+        .bind(&scopes)
+        .bind(&api_key.ip_allowlist)
+        .bind(api_key.created_at)
+        .execute(&self.postgres)
+        .await
+        .map_err(user_not_found(user_address))?;
+
+        Ok(api_key)
+    }
+
+    /// A user's API keys that haven't been revoked, oldest first
+    pub async fn list_api_keys(&self, user_address: &str) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM api_keys WHERE user_address = $1 AND revoked_at IS NULL ORDER BY created_at",
+            API_KEY_COLUMNS
+        ))
+        .bind(user_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(api_key_from_row).collect())
+    }
+
+    /// The unrevoked API key with this hash, if any
+    pub async fn get_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL",
+            API_KEY_COLUMNS
+        ))
+        .bind(key_hash)
+        .fetch_optional(&self.postgres)
+        .await?;
+
+        Ok(row.as_ref().map(api_key_from_row))
+    }
+
+    /// Replace the hash of one of a user's unrevoked keys
+    pub async fn rotate_api_key(
+        &self,
+        user_address: &str,
+        key_id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<ApiKey> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE api_keys
+            SET key_prefix = $3, key_hash = $4, rotated_at = NOW()
+            WHERE id = $1 AND user_address = $2 AND revoked_at IS NULL
+            RETURNING {}
+            "#,
+            API_KEY_COLUMNS
+        ))
+        .bind(key_id)
+        .bind(user_address)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .fetch_optional(&self.postgres)
+        .await?
+        .ok_or_else(|| ExchangeError::ApiKeyNotFound {
+            key_id: key_id.to_string(),
+        })?;
+
+        Ok(api_key_from_row(&row))
+    }
+
+    /// Revoke one of a user's keys; it stops authenticating at once
+    pub async fn revoke_api_key(&self, user_address: &str, key_id: Uuid) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_address = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_id)
+        .bind(user_address)
+        .execute(&self.postgres)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ExchangeError::ApiKeyNotFound {
+                key_id: key_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Note that a key was just used, at most once a minute
+    pub async fn touch_api_key(&self, key_id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE id = $1
+              AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+            "#,
+        )
+        .bind(key_id)
+        .execute(&self.postgres)
+        .await?;
+        Ok(())
+    }
+}
+
+fn api_key_from_row(row: &PgRow) -> ApiKey {
+    let scopes: Vec<String> = row.get("scopes");
+    ApiKey {
+        id: row.get("id"),
+        user_address: row.get("user_address"),
+        label: row.get("label"),
+        key_prefix: row.get("key_prefix"),
+        scopes: scopes.iter().filter_map(|s| s.parse().ok()).collect(),
+        ip_allowlist: row.get("ip_allowlist"),
+        created_at: row.get("created_at"),
+        rotated_at: row.get("rotated_at"),
+        last_used_at: row.get("last_used_at"),
+    }
+}
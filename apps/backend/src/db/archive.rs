@@ -0,0 +1,93 @@
+use crate::db::Db;
+use crate::errors::Result;
+use clickhouse::query::BytesCursor;
+
+/// Trades within [from, to), written with prices and sizes as decimal strings
+/// since Parquet has no 128-bit integers
+const ARCHIVED_TRADES_QUERY: &str = "SELECT
+        id,
+        market_id,
+        buyer_address,
+        seller_address,
+        buyer_order_id,
+        seller_order_id,
+        toString(price) AS price,
+        toString(size) AS size,
+        side,
+        toInt64(toUnixTimestamp(timestamp)) AS timestamp,
+        rfq
+    FROM exchange.trades
+    WHERE timestamp >= ? AND timestamp < ?
+    ORDER BY market_id, timestamp, id";
+
+/// Finalized candles of every market and interval starting within [from, to)
+const ARCHIVED_CANDLES_QUERY: &str = "SELECT
+        market_id,
+        interval,
+        toInt64(toUnixTimestamp(timestamp)) AS timestamp,
+        toString(argMinMerge(open_state)) AS open,
+        toString(maxMerge(high_state)) AS high,
+        toString(minMerge(low_state)) AS low,
+        toString(argMaxMerge(close_state)) AS close,
+        toString(sumMerge(volume_state)) AS volume
+    FROM exchange.candles
+    WHERE timestamp >= ? AND timestamp < ?
+    GROUP BY market_id, interval, timestamp
+    ORDER BY market_id, interval, timestamp";
+
+/// Snappy is the codec every Parquet reader supports
+const PARQUET_COMPRESSION: &str = "snappy";
+
+impl Db {
+    /// Count trades within [from, to)
+    pub async fn count_trades_between(&self, from: i64, to: i64) -> Result<u64> {
+        let count = self
+            .clickhouse
+            .query("SELECT count() FROM exchange.trades WHERE timestamp >= ? AND timestamp < ?")
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_one::<u64>()
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Count finalized candles starting within [from, to)
+    pub async fn count_candles_between(&self, from: i64, to: i64) -> Result<u64> {
+        let count = self
+            .clickhouse
+            .query(&format!("SELECT count() FROM ({})", ARCHIVED_CANDLES_QUERY))
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_one::<u64>()
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Stream trades within [from, to) as a Parquet file
+    pub fn archive_trades(&self, from: i64, to: i64) -> Result<BytesCursor> {
+        self.archive_parquet(ARCHIVED_TRADES_QUERY, from, to)
+    }
+
+    /// Stream finalized candles starting within [from, to) as a Parquet file
+    pub fn archive_candles(&self, from: i64, to: i64) -> Result<BytesCursor> {
+        self.archive_parquet(ARCHIVED_CANDLES_QUERY, from, to)
+    }
+
+    fn archive_parquet(&self, query: &str, from: i64, to: i64) -> Result<BytesCursor> {
+        let cursor = self
+            .clickhouse
+            .query(query)
+            .bind(from as u32)
+            .bind(to as u32)
+            .with_option(
+                "output_format_parquet_compression_method",
+                PARQUET_COMPRESSION,
+            )
+            .with_option("output_format_parquet_string_as_string", "1")
+            .fetch_bytes("Parquet")?;
+
+        Ok(cursor)
+    }
+}
@@ -1,21 +1,86 @@
+use crate::balance_notify::{BalanceNotice, BALANCE_CHANNEL};
 use crate::db::Db;
 use crate::errors::{ExchangeError, Result};
 use crate::models::db::BalanceRow;
 use crate::models::domain::Balance;
 use chrono::Utc;
+use std::collections::BTreeMap;
+
+/// Net effect of a settlement batch on one (user, token) balance
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub credit: u128, // Added to amount
+    pub debit: u128,  // Subtracted from amount
+    pub unlock: u128, // Released from open_interest
+}
+
+/// Balance changes keyed by (user_address, token_ticker)
+///
+/// Accumulating into one entry per key lets a whole batch be written with a
+/// single statement; the BTreeMap also gives a stable row order, so concurrent
+/// batches touching the same balances take their row locks in the same order.
+pub type BalanceChanges = BTreeMap<(String, String), BalanceChange>;
+
+fn balance_notice(user_address: &str, token_ticker: &str) -> String {
+    let notice = BalanceNotice {
+        user_address: user_address.to_string(),
+        token_ticker: token_ticker.to_string(),
+    };
+    serde_json::to_string(&notice).expect("balance notices always serialize")
+}
 
 impl Db {
+    /// Announce that a balance changed outside the engine
+    pub async fn notify_balance_changed(
+        &self,
+        user_address: &str,
+        token_ticker: &str,
+    ) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            memory.notify_balance_changed(user_address, token_ticker);
+            return Ok(());
+        }
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(BALANCE_CHANNEL)
+            .bind(balance_notice(user_address, token_ticker))
+            .execute(&self.postgres)
+            .await?;
+        Ok(())
+    }
+
+    /// Announce a balance change once the transaction commits
+    pub async fn notify_balance_changed_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        user_address: &str,
+        token_ticker: &str,
+    ) -> Result<()> {
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(BALANCE_CHANNEL)
+            .bind(balance_notice(user_address, token_ticker))
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
     /// Get balance for a specific user and token
+    #[tracing::instrument(name = "db.get_balance", skip(self))]
     pub async fn get_balance(&self, user_address: &str, token_ticker: &str) -> Result<Balance> {
-        let row: BalanceRow = sqlx::query_as(
+        if let Some(memory) = &self.memory {
+            return memory.get_balance(user_address, token_ticker);
+        }
+
+        let row = sqlx::query_as!(
+            BalanceRow,
             r#"
             SELECT user_address, token_ticker, amount, open_interest, updated_at
             FROM balances
             WHERE user_address = $1 AND token_ticker = $2
             "#,
+            user_address,
+            token_ticker
         )
-        .bind(user_address)
-        .bind(token_ticker)
         .fetch_optional(&self.postgres)
         .await?
         .ok_or_else(|| ExchangeError::BalanceNotFound {
@@ -23,23 +88,31 @@ impl Db {
             token_ticker: token_ticker.to_string(),
         })?;
 
-        Ok(row.into())
+        Ok(row.try_into()?)
     }
 
     /// List all balances for a user
     pub async fn list_balances_by_user(&self, user_address: &str) -> Result<Vec<Balance>> {
-        let rows: Vec<BalanceRow> = sqlx::query_as(
+        if let Some(memory) = &self.memory {
+            return memory.list_balances_by_user(user_address);
+        }
+
+        let rows = sqlx::query_as!(
+            BalanceRow,
             r#"
             SELECT user_address, token_ticker, amount, open_interest, updated_at
             FROM balances
             WHERE user_address = $1
             "#,
+            user_address
         )
-        .bind(user_address)
         .fetch_all(&self.postgres)
         .await?;
 
-        Ok(rows.into_iter().map(|row| row.into()).collect())
+        Ok(rows
+            .into_iter()
+            .map(Balance::try_from)
+            .collect::<std::result::Result<_, _>>()?)
     }
 
     /// Update or insert balance (upsert)
@@ -85,6 +158,10 @@ impl Db {
         token_ticker: &str,
         amount_delta: u128,
     ) -> Result<Balance> {
+        if let Some(memory) = &self.memory {
+            return memory.add_balance(user_address, token_ticker, amount_delta);
+        }
+
         let delta_str = amount_delta.to_string();
         let now = Utc::now();
 
@@ -137,17 +214,22 @@ impl Db {
 
     /// Lock funds in open_interest (when placing an order)
     /// Returns error if insufficient available balance
+    #[tracing::instrument(name = "db.lock_balance", skip(self))]
     pub async fn lock_balance(
         &self,
         user_address: &str,
         token_ticker: &str,
         amount: u128,
     ) -> Result<Balance> {
+        if let Some(memory) = &self.memory {
+            return memory.lock_balance(user_address, token_ticker, amount);
+        }
+
         let amount_str = amount.to_string();
         let now = Utc::now();
 
         // Check if user has sufficient available balance (amount - open_interest >= amount to lock)
-        let result = sqlx::query(
+        let query = sqlx::query(
             r#"
             UPDATE balances
             SET open_interest = open_interest + $3::numeric, updated_at = $4
@@ -160,8 +242,8 @@ impl Db {
         .bind(token_ticker)
         .bind(&amount_str)
         .bind(now)
-        .execute(&self.postgres)
-        .await?;
+        .execute(&self.postgres);
+        let result = self.timed("lock_balance", query).await?;
 
         if result.rows_affected() == 0 {
             return Err(crate::errors::ExchangeError::InsufficientBalance {
@@ -176,16 +258,21 @@ impl Db {
 
     /// Unlock funds from open_interest (when cancelling/filling an order)
     /// If balance doesn't exist, this is a no-op (nothing to unlock)
+    #[tracing::instrument(name = "db.unlock_balance", skip(self))]
     pub async fn unlock_balance(
         &self,
         user_address: &str,
         token_ticker: &str,
         amount: u128,
     ) -> Result<Balance> {
+        if let Some(memory) = &self.memory {
+            return memory.unlock_balance(user_address, token_ticker, amount);
+        }
+
         let amount_str = amount.to_string();
         let now = Utc::now();
 
-        sqlx::query(
+        let query = sqlx::query(
             r#"
             UPDATE balances
             SET open_interest = GREATEST(open_interest - $3::numeric, 0),
@@ -197,8 +284,8 @@ impl Db {
         .bind(token_ticker)
         .bind(&amount_str)
         .bind(now)
-        .execute(&self.postgres)
-        .await?;
+        .execute(&self.postgres);
+        self.timed("unlock_balance", query).await?;
 
         // Return a stub balance - callers don't use it anyway
         // This avoids the problematic get_balance() call that can panic on conversion
@@ -336,4 +423,65 @@ impl Db {
 
         Ok(())
     }
+
+    /// Apply a batch of balance changes within a transaction with one statement
+    /// Existing balances are updated in place; missing ones are created from the credit
+    #[tracing::instrument(name = "db.apply_balance_changes", skip_all)]
+    pub async fn apply_balance_changes_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        changes: &BalanceChanges,
+    ) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut user_addresses = Vec::with_capacity(changes.len());
+        let mut token_tickers = Vec::with_capacity(changes.len());
+        let mut credits = Vec::with_capacity(changes.len());
+        let mut debits = Vec::with_capacity(changes.len());
+        let mut unlocks = Vec::with_capacity(changes.len());
+        for ((user_address, token_ticker), change) in changes {
+            user_addresses.push(user_address.as_str());
+            token_tickers.push(token_ticker.as_str());
+            credits.push(change.credit.to_string());
+            debits.push(change.debit.to_string());
+            unlocks.push(change.unlock.to_string());
+        }
+
+        let query = sqlx::query(
+            r#"
+            WITH changes AS (
+                SELECT * FROM UNNEST($1::text[], $2::text[], $3::numeric[], $4::numeric[], $5::numeric[])
+                    AS c(user_address, token_ticker, credit, debit, unlock)
+            ),
+            updated AS (
+                UPDATE balances b
+                SET amount = b.amount + c.credit - c.debit,
+                    open_interest = GREATEST(b.open_interest - c.unlock, 0),
+                    updated_at = $6
+                FROM changes c
+                WHERE b.user_address = c.user_address AND b.token_ticker = c.token_ticker
+                RETURNING b.user_address, b.token_ticker
+            )
+            INSERT INTO balances (user_address, token_ticker, amount, open_interest, updated_at)
+            SELECT c.user_address, c.token_ticker, c.credit - c.debit, 0, $6
+            FROM changes c
+            WHERE NOT EXISTS (
+                SELECT 1 FROM updated u
+                WHERE u.user_address = c.user_address AND u.token_ticker = c.token_ticker
+            )
+            "#,
+        )
+        .bind(&user_addresses)
+        .bind(&token_tickers)
+        .bind(&credits)
+        .bind(&debits)
+        .bind(&unlocks)
+        .bind(Utc::now())
+        .execute(&mut **tx);
+        self.timed("apply_balance_changes", query).await?;
+
+        Ok(())
+    }
 }
@@ -1,39 +1,140 @@
 use crate::db::Db;
 use crate::errors::{ExchangeError, Result};
 use crate::models::{
-    api::ApiCandle,
-    db::{CandleRow, ClickHouseTradeRow},
-    domain::{Candle, Trade},
+    api::{ApiCandle, ApiMarketStats},
+    db::{
+        CandleRow, ClickHouseTradeRow, DepthMetricsRow, FillRow, LastPriceRow, MakerVolumeRow,
+        MarketStatsRow, MarketVolumeRow, NotionalVolumeRow, PricePointRow, TakerFlowRow,
+        TraderNotionalRow,
+    },
+    domain::{Candle, DepthMetrics, Fill, MakerVolume, TakerFlow, Trade},
 };
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Each trade once per trader (buyer and seller), leaving out trades with themselves
+const FILLS_QUERY: &str = "SELECT
+        user_address,
+        market_id,
+        if(user_address = buyer_address, 'buy', 'sell') as side,
+        price,
+        size,
+        timestamp
+    FROM exchange.trades
+    ARRAY JOIN [buyer_address, seller_address] AS user_address
+    WHERE buyer_address != seller_address";
+
+/// Most bars returned for a gap-filled candle request
+pub const MAX_FILLED_CANDLES: usize = 10_000;
+
+/// Candle intervals, shortest first
+pub const CANDLE_INTERVALS: [&str; 5] = ["1m", "5m", "15m", "1h", "1d"];
+
+/// Length in seconds of a candle interval: 1m, 5m, 15m, 1h or 1d
+pub fn interval_secs(interval: &str) -> Option<u32> {
+    match interval {
+        "1m" => Some(60),
+        "5m" => Some(5 * 60),
+        "15m" => Some(15 * 60),
+        "1h" => Some(60 * 60),
+        "1d" => Some(24 * 60 * 60),
+        _ => None,
+    }
+}
+
+/// Fill the intervals between candles that traded, up to `last_bucket`
+///
+/// Missing intervals get a flat candle at the previous close with zero volume.
+/// `previous_close` is the price before `from`; without one, filling starts at
+/// the first candle, since there is no price to carry into earlier intervals.
+pub fn fill_candle_gaps(
+    candles: Vec<ApiCandle>,
+    previous_close: Option<u128>,
+    interval_secs: u32,
+    from: i64,
+    last_bucket: i64,
+) -> Vec<ApiCandle> {
+    let step = interval_secs as i64;
+    let flat = |timestamp: i64, price: u128| ApiCandle {
+        timestamp: timestamp as u32,
+        open: price,
+        high: price,
+        low: price,
+        close: price,
+        volume: 0,
+    };
+
+    let mut close = previous_close;
+    // First interval starting at or after `from`
+    let mut bucket = (from + step - 1).div_euclid(step) * step;
+    let mut filled = Vec::with_capacity(candles.len());
+
+    for candle in candles {
+        if let Some(price) = close {
+            while bucket < candle.timestamp as i64 {
+                filled.push(flat(bucket, price));
+                bucket += step;
+            }
+        }
+        // Continue from the candle itself in case intervals aren't epoch-aligned
+        bucket = candle.timestamp as i64 + step;
+        close = Some(candle.close);
+        filled.push(candle);
+    }
+
+    if let Some(price) = close {
+        while bucket <= last_bucket {
+            filled.push(flat(bucket, price));
+            bucket += step;
+        }
+    }
+
+    filled
+}
 
 impl Db {
     /// Insert a trade into ClickHouse for tick data
     /// This will automatically trigger the materialized views to aggregate into candles
     /// The AggregatingMergeTree will handle merging and pre-aggregating the data
     pub async fn insert_trade_to_clickhouse(&self, trade: &Trade) -> Result<()> {
-        let trade_row = ClickHouseTradeRow {
-            id: trade.id.to_string(),
-            market_id: trade.market_id.clone(),
-            buyer_address: trade.buyer_address.clone(),
-            seller_address: trade.seller_address.clone(),
-            buyer_order_id: trade.buyer_order_id.to_string(),
-            seller_order_id: trade.seller_order_id.to_string(),
-            price: trade.price,
-            size: trade.size,
-            side: match trade.side {
-                crate::models::domain::Side::Buy => "buy".to_string(),
-                crate::models::domain::Side::Sell => "sell".to_string(),
-            },
-            timestamp: trade.timestamp.timestamp() as u32,
-        };
+        self.insert_trades_to_clickhouse(std::slice::from_ref(trade))
+            .await
+    }
 
-        let mut insert = self
-            .clickhouse
-            .insert::<ClickHouseTradeRow>("trades")
-            .await?;
-        insert.write(&trade_row).await?;
-        insert.end().await?;
+    /// Insert a batch of trades into ClickHouse in a single insert
+    pub async fn insert_trades_to_clickhouse(&self, trades: &[Trade]) -> Result<()> {
+        // In memory, settlement already keeps the trades that candles are built from
+        if trades.is_empty() || self.memory.is_some() {
+            return Ok(());
+        }
+
+        let insert = async {
+            let mut insert = self
+                .clickhouse
+                .insert::<ClickHouseTradeRow>("trades")
+                .await?;
+            for trade in trades {
+                let trade_row = ClickHouseTradeRow {
+                    id: trade.id.to_string(),
+                    market_id: trade.market_id.clone(),
+                    buyer_address: trade.buyer_address.clone(),
+                    seller_address: trade.seller_address.clone(),
+                    buyer_order_id: trade.buyer_order_id.to_string(),
+                    seller_order_id: trade.seller_order_id.to_string(),
+                    price: trade.price,
+                    size: trade.size,
+                    side: match trade.side {
+                        crate::models::domain::Side::Buy => "buy".to_string(),
+                        crate::models::domain::Side::Sell => "sell".to_string(),
+                    },
+                    timestamp: trade.timestamp.timestamp() as u32,
+                    rfq: trade.rfq,
+                };
+                insert.write(&trade_row).await?;
+            }
+            insert.end().await
+        };
+        self.timed_insert("trades", trades.len(), insert).await?;
 
         Ok(())
     }
@@ -88,7 +189,10 @@ impl Db {
 
     /// Get candles for API with support for countBack parameter
     /// Returns candles as ApiCandle with timestamp aggregation and optional limit
-    /// Uses -Merge combinators to finalize aggregate states
+    ///
+    /// With `fill_gaps`, intervals without trades up to the one in progress are
+    /// filled with the previous close and zero volume, at most
+    /// `MAX_FILLED_CANDLES` bars
     pub async fn get_candles_for_api(
         &self,
         market_id: &str,
@@ -96,7 +200,54 @@ impl Db {
         from: i64,
         to: i64,
         count_back: Option<usize>,
+        fill_gaps: bool,
     ) -> Result<Vec<ApiCandle>> {
+        let Some(interval_secs) = interval_secs(interval).filter(|_| fill_gaps) else {
+            return self
+                .fetch_api_candles(market_id, interval, from, to, count_back)
+                .await;
+        };
+
+        // Never fill past the interval in progress
+        let step = interval_secs as i64;
+        let last_bucket = to.min(Utc::now().timestamp()).div_euclid(step) * step;
+        let bars = count_back
+            .filter(|&n| n > 0)
+            .unwrap_or(MAX_FILLED_CANDLES)
+            .min(MAX_FILLED_CANDLES);
+        let from = from.max(last_bucket - (bars as i64 - 1) * step);
+
+        let candles = self
+            .fetch_api_candles(market_id, interval, from, to, None)
+            .await?;
+        let previous_close = self.get_last_price_before(market_id, from).await?;
+
+        let mut filled =
+            fill_candle_gaps(candles, previous_close, interval_secs, from, last_bucket);
+        if filled.len() > bars {
+            filled.drain(..filled.len() - bars);
+        }
+        Ok(filled)
+    }
+
+    /// Candles that traded within [from, to], the `count_back` most recent if set
+    /// Uses -Merge combinators to finalize aggregate states
+    async fn fetch_api_candles(
+        &self,
+        market_id: &str,
+        interval: &str,
+        from: i64,
+        to: i64,
+        count_back: Option<usize>,
+    ) -> Result<Vec<ApiCandle>> {
+        if let Some(memory) = &self.memory {
+            let interval_secs =
+                interval_secs(interval).ok_or_else(|| ExchangeError::InvalidParameter {
+                    message: format!("Invalid candle interval {}", interval),
+                })?;
+            return memory.get_candles(market_id, interval_secs, from, to, count_back);
+        }
+
         // Build the base query with -Merge combinators
         // Note: We GROUP BY all three key columns even though market_id and interval
         // are in WHERE clause, to ensure proper aggregation of unmerged parts
@@ -173,13 +324,363 @@ impl Db {
         Ok(candles)
     }
 
+    /// Get trade statistics for a market over `[from, to]` straight from the tick data
+    /// Quote volume is summed per trade the way settlement computes it
+    pub async fn get_market_stats(
+        &self,
+        market_id: &str,
+        base_decimals: u8,
+        from: i64,
+        to: i64,
+    ) -> Result<ApiMarketStats> {
+        let base_decimals_divisor = 10u128.pow(base_decimals as u32);
+
+        let row = match &self.memory {
+            Some(memory) => memory.get_market_stats(market_id, base_decimals, from, to)?,
+            None => {
+                self.clickhouse
+                    .query(
+                        "SELECT
+                count() as trade_count,
+                sum(size) as base_volume,
+                toUInt128(sum(intDiv(price * size, toUInt128(?)))) as quote_volume,
+                argMin(price, timestamp) as open,
+                max(price) as high,
+                min(price) as low,
+                argMax(price, timestamp) as close
+            FROM exchange.trades
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?",
+                    )
+                    .bind(base_decimals_divisor.to_string())
+                    .bind(market_id)
+                    .bind(from as u32)
+                    .bind(to as u32)
+                    .fetch_one::<MarketStatsRow>()
+                    .await?
+            }
+        };
+
+        let traded = row.trade_count > 0;
+        let price = |p: u128| traded.then(|| p.to_string());
+        let change_percent = (traded && row.open > 0)
+            .then(|| (row.close as f64 - row.open as f64) / row.open as f64 * 100.0);
+
+        Ok(ApiMarketStats {
+            market_id: market_id.to_string(),
+            base_volume: row.base_volume.to_string(),
+            quote_volume: row.quote_volume.to_string(),
+            trade_count: row.trade_count,
+            open: price(row.open),
+            high: price(row.high),
+            low: price(row.low),
+            close: price(row.close),
+            change_percent,
+            from,
+            to,
+        })
+    }
+
+    /// Get a market's traded notional (price * size, unscaled) and size over `[from, to]`
+    pub async fn get_notional_and_volume(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<(u128, u128)> {
+        let row = self
+            .clickhouse
+            .query(
+                "SELECT
+                toUInt128(sum(price * size)) as notional,
+                sum(size) as volume
+            FROM exchange.trades
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_one::<NotionalVolumeRow>()
+            .await?;
+
+        Ok((row.notional, row.volume))
+    }
+
+    /// Get a market's last trade price strictly before `before`
+    /// `None` when the market had not traded by then
+    pub async fn get_last_price_before(
+        &self,
+        market_id: &str,
+        before: i64,
+    ) -> Result<Option<u128>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_last_price_before(market_id, before);
+        }
+
+        let row = self
+            .clickhouse
+            .query(
+                "SELECT count() as trade_count, argMax(price, timestamp) as price
+            FROM exchange.trades
+            WHERE market_id = ? AND timestamp < ?",
+            )
+            .bind(market_id)
+            .bind(before as u32)
+            .fetch_one::<LastPriceRow>()
+            .await?;
+
+        Ok((row.trade_count > 0).then_some(row.price))
+    }
+
+    /// Get a market's price path over `[from, to]`: the last price before `from`, if
+    /// any, and the mean trade price of every second with trades, oldest first
+    pub async fn get_price_points(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<(Option<u128>, Vec<(i64, u128)>)> {
+        let opening = self.get_last_price_before(market_id, from).await?;
+
+        let points = self
+            .clickhouse
+            .query(
+                "SELECT timestamp, toUInt128(intDiv(sum(price), count())) as price
+            FROM exchange.trades
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?
+            GROUP BY timestamp
+            ORDER BY timestamp",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<PricePointRow>()
+            .await?;
+
+        Ok((
+            opening,
+            points
+                .into_iter()
+                .map(|row| (row.timestamp as i64, row.price))
+                .collect(),
+        ))
+    }
+
+    /// Insert a batch of depth samples into ClickHouse in a single insert
+    pub async fn insert_depth_metrics(&self, samples: &[DepthMetrics]) -> Result<()> {
+        if samples.is_empty() || self.memory.is_some() {
+            return Ok(());
+        }
+
+        let insert = async {
+            let mut insert = self
+                .clickhouse
+                .insert::<DepthMetricsRow>("depth_metrics")
+                .await?;
+            for sample in samples {
+                insert.write(&DepthMetricsRow::from(sample)).await?;
+            }
+            insert.end().await
+        };
+        self.timed_insert("depth_metrics", samples.len(), insert)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get a market's depth samples within [from, to], oldest first
+    pub async fn get_depth_metrics(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<DepthMetrics>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT market_id, timestamp, best_bid, best_ask, spread, bid_depth, ask_depth, imbalance_bps
+            FROM exchange.depth_metrics
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<DepthMetricsRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(DepthMetrics::from).collect())
+    }
+
+    /// Get each trader's maker and taker volume in a market within [from, to],
+    /// largest maker first, leaving out trades with yourself
+    /// The trade side is the taker's, so the trader on the other side made it
+    pub async fn get_maker_volumes(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<MakerVolume>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT
+                user_address,
+                toUInt128(sumIf(size, (user_address = buyer_address) != (side = 'buy'))) as maker_volume,
+                toUInt128(sumIf(size, (user_address = buyer_address) = (side = 'buy'))) as taker_volume
+            FROM exchange.trades
+            ARRAY JOIN [buyer_address, seller_address] AS user_address
+            WHERE buyer_address != seller_address
+                AND market_id = ? AND timestamp >= ? AND timestamp <= ?
+            GROUP BY user_address
+            ORDER BY maker_volume DESC, user_address",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<MakerVolumeRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(MakerVolume::from).collect())
+    }
+
+    /// Get a trader's maker and taker volume in every market they traded
+    /// since `from`, as (maker, taker) per market, leaving out trades with yourself
+    pub async fn get_user_volumes(
+        &self,
+        user_address: &str,
+        from: i64,
+    ) -> Result<HashMap<String, (u128, u128)>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT
+                market_id,
+                toUInt128(sumIf(size, (buyer_address = ?) != (side = 'buy'))) as maker_volume,
+                toUInt128(sumIf(size, (buyer_address = ?) = (side = 'buy'))) as taker_volume
+            FROM exchange.trades
+            WHERE (buyer_address = ? OR seller_address = ?)
+                AND buyer_address != seller_address
+                AND timestamp >= ?
+            GROUP BY market_id",
+            )
+            .bind(user_address)
+            .bind(user_address)
+            .bind(user_address)
+            .bind(user_address)
+            .bind(from as u32)
+            .fetch_all::<MarketVolumeRow>()
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.market_id, (row.maker_volume, row.taker_volume)))
+            .collect())
+    }
+
+    /// Get a market's taker buy and sell volume per `interval_secs` bucket within
+    /// [from, to], oldest first, leaving out trades with yourself
+    /// Buckets without trades are omitted
+    pub async fn get_taker_flow(
+        &self,
+        market_id: &str,
+        interval_secs: u32,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<TakerFlow>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT
+                toUInt32(intDiv(toUInt32(t.timestamp), ?) * ?) as timestamp,
+                toUInt128(sumIf(t.size, t.side = 'buy')) as buy_volume,
+                toUInt128(sumIf(t.size, t.side = 'sell')) as sell_volume
+            FROM exchange.trades AS t
+            WHERE t.market_id = ? AND t.timestamp >= ? AND t.timestamp <= ?
+                AND t.buyer_address != t.seller_address
+            GROUP BY timestamp
+            ORDER BY timestamp",
+            )
+            .bind(interval_secs)
+            .bind(interval_secs)
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<TakerFlowRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(TakerFlow::from).collect())
+    }
+
+    /// Get every fill of a user, oldest first per market
+    /// Trades with themselves are left out since they don't change a position
+    pub async fn get_user_fills(&self, user_address: &str) -> Result<Vec<Fill>> {
+        let rows = self
+            .clickhouse
+            .query(&format!(
+                "{} AND user_address = ? ORDER BY market_id, timestamp",
+                FILLS_QUERY
+            ))
+            .bind(user_address)
+            .fetch_all::<FillRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(Fill::from).collect())
+    }
+
+    /// Get the full fill history of everyone who has traded since `from`, oldest first
+    /// per trader and market, so positions opened before `from` have a cost basis
+    pub async fn get_active_trader_fills(&self, from: i64) -> Result<Vec<Fill>> {
+        let rows = self
+            .clickhouse
+            .query(&format!(
+                "{} AND user_address IN (
+                    SELECT arrayJoin([buyer_address, seller_address])
+                    FROM exchange.trades
+                    WHERE timestamp >= ?
+                )
+                ORDER BY user_address, market_id, timestamp",
+                FILLS_QUERY
+            ))
+            .bind(from as u32)
+            .fetch_all::<FillRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(Fill::from).collect())
+    }
+
+    /// Get each trader's notional (price * size, unscaled) per market since `from`
+    /// Trades with themselves are left out so they can't pad volume
+    pub async fn get_trader_notionals(&self, from: i64) -> Result<Vec<TraderNotionalRow>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT
+                user_address,
+                market_id,
+                toUInt128(sum(price * size)) as notional
+            FROM exchange.trades
+            ARRAY JOIN [buyer_address, seller_address] AS user_address
+            WHERE timestamp >= ? AND buyer_address != seller_address
+            GROUP BY user_address, market_id",
+            )
+            .bind(from as u32)
+            .fetch_all::<TraderNotionalRow>()
+            .await?;
+
+        Ok(rows)
+    }
+
     /// Get recent trades for a market (tick data)
     pub async fn get_recent_trades(&self, market_id: &str, limit: u32) -> Result<Vec<Trade>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_market_trades(market_id, limit);
+        }
+
         let limit = std::cmp::min(limit, 1000);
 
         let trades = self
             .clickhouse
-            .query("SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, timestamp FROM trades WHERE market_id = ? ORDER BY timestamp DESC LIMIT ?")
+            .query("SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp, rfq FROM trades WHERE market_id = ? ORDER BY timestamp DESC LIMIT ?")
             .bind(market_id)
             .bind(limit)
             .fetch_all::<ClickHouseTradeRow>()
@@ -204,6 +705,7 @@ impl Db {
                     },
                     timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
                         .unwrap_or(DateTime::UNIX_EPOCH),
+                    rfq: row.rfq,
                 })
             })
             .collect())
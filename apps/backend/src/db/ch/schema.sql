@@ -13,11 +13,16 @@ CREATE TABLE IF NOT EXISTS exchange.trades (
     price UInt128,
     size UInt128,
     side String,
-    timestamp DateTime
+    timestamp DateTime,
+    rfq Bool DEFAULT false
 ) ENGINE = MergeTree()
 ORDER BY (market_id, timestamp)
 PRIMARY KEY (market_id, timestamp);
 
+-- Trades negotiated through a request for quote rather than matched on the book
+-- Added after the table was first created, so existing deployments need the column too
+ALTER TABLE exchange.trades ADD COLUMN IF NOT EXISTS rfq Bool DEFAULT false;
+
 -- Candles table for pre-aggregated OHLCV data
 -- Uses AggregatingMergeTree to store aggregate states and automatically merge them
 -- This table stores ONE row per (market_id, interval, timestamp) bucket
@@ -108,3 +113,52 @@ AS SELECT
     sumState(t.size) as volume_state
 FROM exchange.trades AS t
 GROUP BY t.market_id, interval, timestamp;
+
+-- Depth metrics sampled from the engine's orderbook snapshots
+-- One row per market every few seconds, for liquidity providers to audit their quoting
+-- Prices and spread are 0 while a side of the book is empty
+CREATE TABLE IF NOT EXISTS exchange.depth_metrics (
+    market_id String,
+    timestamp DateTime,
+    best_bid UInt128,
+    best_ask UInt128,
+    spread UInt128,
+    bid_depth UInt128,
+    ask_depth UInt128,
+    imbalance_bps Int32
+) ENGINE = MergeTree()
+ORDER BY (market_id, timestamp)
+PRIMARY KEY (market_id, timestamp);
+
+-- Open interest snapshots for perpetual markets, written by the engine once derivatives land
+-- open_interest is the total size of open long positions (equal to open shorts), in base atoms
+CREATE TABLE IF NOT EXISTS exchange.open_interest (
+    market_id String,
+    timestamp DateTime,
+    open_interest UInt128,
+    mark_price UInt128
+) ENGINE = MergeTree()
+ORDER BY (market_id, timestamp)
+PRIMARY KEY (market_id, timestamp);
+
+-- Funding rate history for perpetual markets, one row per funding interval
+-- Positive rates mean longs pay shorts; funding_rate_ppm is in millionths of notional
+CREATE TABLE IF NOT EXISTS exchange.funding_rates (
+    market_id String,
+    timestamp DateTime,
+    funding_rate_ppm Int64,
+    mark_price UInt128,
+    index_price UInt128
+) ENGINE = MergeTree()
+ORDER BY (market_id, timestamp)
+PRIMARY KEY (market_id, timestamp);
+
+-- Index prices polled from external oracles, one row per market per poll
+CREATE TABLE IF NOT EXISTS exchange.index_prices (
+    market_id String,
+    timestamp DateTime,
+    price UInt128,
+    source String
+) ENGINE = MergeTree()
+ORDER BY (market_id, timestamp)
+PRIMARY KEY (market_id, timestamp);
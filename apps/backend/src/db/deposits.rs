@@ -0,0 +1,142 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::{Balance, Deposit};
+use chrono::Utc;
+use sqlx::Row;
+
+impl Db {
+    /// Credit an on-chain deposit to its sender's balance, once
+    ///
+    /// The sender is matched to an existing user regardless of address case,
+    /// or becomes a new user. The new balance is announced once committed.
+    /// Returns `None` without changing anything if the transfer was already
+    /// credited.
+    pub async fn credit_deposit(&self, deposit: &Deposit) -> Result<Option<(Deposit, Balance)>> {
+        let mut tx = self.postgres.begin().await?;
+
+        let existing: Option<String> = sqlx::query_scalar(
+            "SELECT address FROM users WHERE LOWER(address) = LOWER($1) LIMIT 1",
+        )
+        .bind(&deposit.user_address)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let user_address = match existing {
+            Some(address) => address,
+            None => {
+                sqlx::query("INSERT INTO users (address) VALUES ($1) ON CONFLICT DO NOTHING")
+                    .bind(&deposit.user_address)
+                    .execute(&mut *tx)
+                    .await?;
+                deposit.user_address.clone()
+            }
+        };
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO deposits
+                (chain_id, tx_hash, log_index, block_number, user_address, token_ticker, amount, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8)
+            ON CONFLICT (chain_id, tx_hash, log_index) DO NOTHING
+            "#,
+        )
+        .bind(deposit.chain_id as i64)
+        .bind(&deposit.tx_hash)
+        .bind(deposit.log_index as i64)
+        .bind(deposit.block_number as i64)
+        .bind(&user_address)
+        .bind(&deposit.token_ticker)
+        .bind(deposit.amount.to_string())
+        .bind(deposit.created_at)
+        .execute(&mut *tx)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        self.add_balance_tx(
+            &mut tx,
+            &user_address,
+            &deposit.token_ticker,
+            deposit.amount,
+        )
+        .await?;
+        self.notify_balance_changed_tx(&mut tx, &user_address, &deposit.token_ticker)
+            .await?;
+        tx.commit().await?;
+
+        let balance = self
+            .get_balance(&user_address, &deposit.token_ticker)
+            .await?;
+        let credited = Deposit {
+            user_address,
+            ..deposit.clone()
+        };
+        Ok(Some((credited, balance)))
+    }
+
+    /// A user's credited deposits, newest first
+    pub async fn list_user_deposits(&self, user_address: &str, limit: u32) -> Result<Vec<Deposit>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT chain_id, tx_hash, log_index, block_number, user_address, token_ticker,
+                   amount::TEXT AS amount, created_at
+            FROM deposits
+            WHERE user_address = $1
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(user_address)
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let amount: String = row.get("amount");
+                Deposit {
+                    chain_id: row.get::<i64, _>("chain_id") as u64,
+                    tx_hash: row.get("tx_hash"),
+                    log_index: row.get::<i64, _>("log_index") as u64,
+                    block_number: row.get::<i64, _>("block_number") as u64,
+                    user_address: row.get("user_address"),
+                    token_ticker: row.get("token_ticker"),
+                    amount: amount.parse().unwrap_or(0),
+                    created_at: row.get("created_at"),
+                }
+            })
+            .collect())
+    }
+
+    /// Last block of a chain whose deposits have all been credited
+    pub async fn get_deposit_cursor(&self, chain_id: u64) -> Result<Option<u64>> {
+        let last_block: Option<i64> =
+            sqlx::query_scalar("SELECT last_block FROM deposit_cursors WHERE chain_id = $1")
+                .bind(chain_id as i64)
+                .fetch_optional(&self.postgres)
+                .await?;
+
+        Ok(last_block.map(|block| block as u64))
+    }
+
+    /// Record that every deposit up to `last_block` has been credited
+    pub async fn set_deposit_cursor(&self, chain_id: u64, last_block: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO deposit_cursors (chain_id, last_block, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (chain_id) DO UPDATE
+            SET last_block = EXCLUDED.last_block,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(chain_id as i64)
+        .bind(last_block as i64)
+        .bind(Utc::now())
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::{
+    db::{FundingRateRow, OpenInterestRow},
+    domain::{FundingRate, OpenInterestSnapshot},
+};
+
+impl Db {
+    /// Insert a batch of open interest snapshots into ClickHouse in a single insert
+    pub async fn insert_open_interest(&self, snapshots: &[OpenInterestSnapshot]) -> Result<()> {
+        if snapshots.is_empty() {
+            return Ok(());
+        }
+
+        let insert = async {
+            let mut insert = self
+                .clickhouse
+                .insert::<OpenInterestRow>("open_interest")
+                .await?;
+            for snapshot in snapshots {
+                insert.write(&OpenInterestRow::from(snapshot)).await?;
+            }
+            insert.end().await
+        };
+        self.timed_insert("open_interest", snapshots.len(), insert)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get a market's open interest snapshots within [from, to], oldest first
+    pub async fn get_open_interest(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<OpenInterestSnapshot>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT market_id, timestamp, open_interest, mark_price
+            FROM exchange.open_interest
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<OpenInterestRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(OpenInterestSnapshot::from).collect())
+    }
+
+    /// Insert a batch of funding rates into ClickHouse in a single insert
+    pub async fn insert_funding_rates(&self, rates: &[FundingRate]) -> Result<()> {
+        if rates.is_empty() {
+            return Ok(());
+        }
+
+        let insert = async {
+            let mut insert = self
+                .clickhouse
+                .insert::<FundingRateRow>("funding_rates")
+                .await?;
+            for rate in rates {
+                insert.write(&FundingRateRow::from(rate)).await?;
+            }
+            insert.end().await
+        };
+        self.timed_insert("funding_rates", rates.len(), insert)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get a market's funding rates within [from, to], oldest first
+    pub async fn get_funding_rates(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<FundingRate>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT market_id, timestamp, funding_rate_ppm, mark_price, index_price
+            FROM exchange.funding_rates
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<FundingRateRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(FundingRate::from).collect())
+    }
+}
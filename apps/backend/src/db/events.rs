@@ -0,0 +1,281 @@
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{EventOutcome, EventStatus, PredictionEvent};
+use crate::prediction::{self, OutcomeHolding, ResolutionPlan};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use std::collections::HashMap;
+
+fn event_from_row(
+    row: &PgRow,
+    outcomes: &mut HashMap<String, Vec<EventOutcome>>,
+) -> PredictionEvent {
+    let id: String = row.get("id");
+    let status: String = row.get("status");
+    PredictionEvent {
+        outcomes: outcomes.remove(&id).unwrap_or_default(),
+        id,
+        title: row.get("title"),
+        quote_ticker: row.get("quote_ticker"),
+        status: status.parse().unwrap_or_default(),
+        winning_outcome: row.get("winning_outcome"),
+        created_at: row.get("created_at"),
+        resolved_at: row.get("resolved_at"),
+    }
+}
+
+impl Db {
+    /// Group existing markets into a prediction event, one per outcome, in the order given
+    ///
+    /// The event is quoted in the first outcome market's quote token, which
+    /// every other outcome market has to share.
+    pub async fn create_event(
+        &self,
+        event_id: &str,
+        title: &str,
+        outcomes: &[(String, String)],
+    ) -> Result<PredictionEvent> {
+        let mut markets = Vec::with_capacity(outcomes.len());
+        for (name, market_id) in outcomes {
+            markets.push((name.clone(), self.get_market(market_id).await?));
+        }
+        let quote_ticker = markets
+            .first()
+            .map(|(_, market)| market.quote_ticker.clone())
+            .unwrap_or_default();
+        prediction::validate_event(event_id, &quote_ticker, &markets)?;
+
+        let mut tx = self.begin_transaction().await?;
+        sqlx::query("INSERT INTO prediction_events (id, title, quote_ticker) VALUES ($1, $2, $3)")
+            .bind(event_id)
+            .bind(title)
+            .bind(&quote_ticker)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                    ExchangeError::InvalidParameter {
+                        message: format!("Event '{}' already exists", event_id),
+                    }
+                }
+                _ => ExchangeError::Database(e),
+            })?;
+
+        let names: Vec<&str> = markets.iter().map(|(name, _)| name.as_str()).collect();
+        let market_ids: Vec<&str> = markets.iter().map(|(_, m)| m.id.as_str()).collect();
+        let positions: Vec<i32> = (0..markets.len() as i32).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO event_outcomes (event_id, outcome, market_id, position)
+            SELECT $1, o, m, p FROM UNNEST($2::text[], $3::text[], $4::int[]) AS u(o, m, p)
+            "#,
+        )
+        .bind(event_id)
+        .bind(&names)
+        .bind(&market_ids)
+        .bind(&positions)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                ExchangeError::InvalidParameter {
+                    message: "A market is already an outcome of another event".to_string(),
+                }
+            }
+            _ => ExchangeError::Database(e),
+        })?;
+        tx.commit().await?;
+
+        self.get_event(event_id).await
+    }
+
+    /// A prediction event and its outcomes
+    pub async fn get_event(&self, event_id: &str) -> Result<PredictionEvent> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, title, quote_ticker, status, winning_outcome, created_at, resolved_at
+            FROM prediction_events
+            WHERE id = $1
+            "#,
+        )
+        .bind(event_id)
+        .fetch_optional(&self.postgres)
+        .await?
+        .ok_or_else(|| ExchangeError::EventNotFound {
+            event_id: event_id.to_string(),
+        })?;
+
+        let mut outcomes = self.list_event_outcomes(&[event_id]).await?;
+        Ok(event_from_row(&row, &mut outcomes))
+    }
+
+    /// Prediction events, newest first, optionally only those with `status`
+    pub async fn list_events(&self, status: Option<EventStatus>) -> Result<Vec<PredictionEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, title, quote_ticker, status, winning_outcome, created_at, resolved_at
+            FROM prediction_events
+            WHERE $1::text IS NULL OR status = $1
+            ORDER BY created_at DESC, id
+            "#,
+        )
+        .bind(status.map(|status| status.to_string()))
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let ids: Vec<String> = rows.iter().map(|row| row.get("id")).collect();
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let mut outcomes = self.list_event_outcomes(&ids).await?;
+        Ok(rows
+            .iter()
+            .map(|row| event_from_row(row, &mut outcomes))
+            .collect())
+    }
+
+    /// Outcomes of each of `event_ids`, in display order
+    async fn list_event_outcomes(
+        &self,
+        event_ids: &[&str],
+    ) -> Result<HashMap<String, Vec<EventOutcome>>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT o.event_id, o.outcome, o.market_id, m.base_ticker
+            FROM event_outcomes o
+            JOIN markets m ON m.id = o.market_id
+            WHERE o.event_id = ANY($1)
+            ORDER BY o.event_id, o.position
+            "#,
+        )
+        .bind(event_ids)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let mut outcomes: HashMap<String, Vec<EventOutcome>> = HashMap::new();
+        for row in &rows {
+            outcomes
+                .entry(row.get("event_id"))
+                .or_default()
+                .push(EventOutcome {
+                    name: row.get("outcome"),
+                    market_id: row.get("market_id"),
+                    token_ticker: row.get("base_ticker"),
+                });
+        }
+        Ok(outcomes)
+    }
+
+    /// Resolve an open event to `winning_outcome` in one transaction
+    ///
+    /// Every balance of the event's outcome tokens is burned, releasing what
+    /// orders still lock, and the winning token's holders are credited one
+    /// quote token per whole token. The outcome markets should be delisted
+    /// first so no order can trade the tokens meanwhile.
+    pub async fn resolve_event(
+        &self,
+        event_id: &str,
+        winning_outcome: &str,
+    ) -> Result<(PredictionEvent, ResolutionPlan)> {
+        let mut tx = self.begin_transaction().await?;
+
+        let row = sqlx::query(
+            "SELECT status, quote_ticker FROM prediction_events WHERE id = $1 FOR UPDATE",
+        )
+        .bind(event_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ExchangeError::EventNotFound {
+            event_id: event_id.to_string(),
+        })?;
+        let status: String = row.get("status");
+        if status.parse::<EventStatus>().unwrap_or_default() == EventStatus::Resolved {
+            return Err(ExchangeError::EventAlreadyResolved {
+                event_id: event_id.to_string(),
+            });
+        }
+        let quote_ticker: String = row.get("quote_ticker");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT o.outcome, m.base_ticker, t.decimals
+            FROM event_outcomes o
+            JOIN markets m ON m.id = o.market_id
+            JOIN tokens t ON t.ticker = m.base_ticker
+            WHERE o.event_id = $1
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let tokens: Vec<String> = rows.iter().map(|row| row.get("base_ticker")).collect();
+        let (winning_token, winning_decimals) = rows
+            .iter()
+            .find(|row| row.get::<String, _>("outcome") == winning_outcome)
+            .map(|row| {
+                (
+                    row.get::<String, _>("base_ticker"),
+                    row.get::<i32, _>("decimals") as u8,
+                )
+            })
+            .ok_or_else(|| ExchangeError::InvalidParameter {
+                message: format!("Event '{}' has no outcome '{}'", event_id, winning_outcome),
+            })?;
+        let quote_decimals: i32 =
+            sqlx::query_scalar("SELECT decimals FROM tokens WHERE ticker = $1")
+                .bind(&quote_ticker)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT user_address, token_ticker, amount::TEXT AS amount,
+                   open_interest::TEXT AS open_interest
+            FROM balances
+            WHERE token_ticker = ANY($1) AND (amount > 0 OR open_interest > 0)
+            ORDER BY user_address, token_ticker
+            FOR UPDATE
+            "#,
+        )
+        .bind(&tokens)
+        .fetch_all(&mut *tx)
+        .await?;
+        let holdings: Vec<OutcomeHolding> = rows
+            .iter()
+            .map(|row| {
+                let amount: String = row.get("amount");
+                let open_interest: String = row.get("open_interest");
+                OutcomeHolding {
+                    user_address: row.get("user_address"),
+                    token_ticker: row.get("token_ticker"),
+                    amount: amount.parse().unwrap_or(0),
+                    open_interest: open_interest.parse().unwrap_or(0),
+                }
+            })
+            .collect();
+
+        let plan = prediction::plan_resolution(
+            &holdings,
+            &winning_token,
+            winning_decimals,
+            &quote_ticker,
+            quote_decimals as u8,
+        )
+        .ok_or(ExchangeError::OrderValueOverflow)?;
+        self.apply_balance_changes_tx(&mut tx, &plan.balance_changes)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE prediction_events
+            SET status = 'resolved', winning_outcome = $2, resolved_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(event_id)
+        .bind(winning_outcome)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok((self.get_event(event_id).await?, plan))
+    }
+}
@@ -0,0 +1,81 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::api::ExportFormat;
+use clickhouse::query::BytesCursor;
+
+/// A user's fills within [from, to], one row per side they were on, so a
+/// trade with themselves appears twice
+const USER_FILLS_QUERY: &str = "SELECT
+        id AS trade_id,
+        timestamp AS traded_at,
+        market_id,
+        fill.1 AS fill_side,
+        side AS taker_side,
+        price AS price_atoms,
+        size AS size_atoms
+    FROM exchange.trades
+    ARRAY JOIN [('buy', buyer_address), ('sell', seller_address)] AS fill
+    WHERE fill.2 = ? AND timestamp >= ? AND timestamp <= ?";
+
+/// ClickHouse output format for an export
+fn clickhouse_format(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "CSVWithNames",
+        ExportFormat::Parquet => "Parquet",
+    }
+}
+
+impl Db {
+    /// Count a user's fills within [from, to]
+    pub async fn count_user_fills(&self, user_address: &str, from: i64, to: i64) -> Result<u64> {
+        let count = self
+            .clickhouse
+            .query(&format!("SELECT count() FROM ({})", USER_FILLS_QUERY))
+            .bind(user_address)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_one::<u64>()
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Stream up to `limit` of a user's fills within [from, to], oldest first,
+    /// encoded by ClickHouse in the requested format
+    ///
+    /// Prices and sizes are atoms written as strings, since Parquet has no
+    /// 128-bit integers. Query errors surface on the first read from the cursor.
+    pub fn export_user_fills(
+        &self,
+        user_address: &str,
+        from: i64,
+        to: i64,
+        format: ExportFormat,
+        limit: u64,
+    ) -> Result<BytesCursor> {
+        let cursor = self
+            .clickhouse
+            .query(&format!(
+                "SELECT
+                trade_id,
+                toUnixTimestamp(traded_at) AS timestamp,
+                formatDateTime(traded_at, '%Y-%m-%d %H:%i:%S', 'UTC') AS time_utc,
+                market_id,
+                fill_side AS side,
+                if(fill_side = taker_side, 'taker', 'maker') AS role,
+                toString(price_atoms) AS price,
+                toString(size_atoms) AS size
+            FROM ({})
+            ORDER BY timestamp, trade_id
+            LIMIT ?",
+                USER_FILLS_QUERY
+            ))
+            .bind(user_address)
+            .bind(from as u32)
+            .bind(to as u32)
+            .bind(limit)
+            .fetch_bytes(clickhouse_format(format))?;
+
+        Ok(cursor)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::FeeOverride;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use std::collections::HashMap;
+
+fn fee_override(row: &PgRow) -> FeeOverride {
+    FeeOverride {
+        user_address: row.get("user_address"),
+        market_id: row.get("market_id"),
+        maker_fee_bps: row.get("maker_fee_bps"),
+        taker_fee_bps: row.get("taker_fee_bps"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+impl Db {
+    /// Set the fees a user pays in a market, replacing any existing override;
+    /// leaving out both sides clears it and returns `None`
+    pub async fn set_fee_override(
+        &self,
+        user_address: &str,
+        market_id: &str,
+        maker_fee_bps: Option<i32>,
+        taker_fee_bps: Option<i32>,
+    ) -> Result<Option<FeeOverride>> {
+        if maker_fee_bps.is_none() && taker_fee_bps.is_none() {
+            sqlx::query("DELETE FROM fee_overrides WHERE user_address = $1 AND market_id = $2")
+                .bind(user_address)
+                .bind(market_id)
+                .execute(&self.postgres)
+                .await?;
+            return Ok(None);
+        }
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO fee_overrides (user_address, market_id, maker_fee_bps, taker_fee_bps, updated_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (user_address, market_id) DO UPDATE
+            SET maker_fee_bps = EXCLUDED.maker_fee_bps,
+                taker_fee_bps = EXCLUDED.taker_fee_bps,
+                updated_at = EXCLUDED.updated_at
+            RETURNING user_address, market_id, maker_fee_bps, taker_fee_bps, updated_at
+            "#,
+        )
+        .bind(user_address)
+        .bind(market_id)
+        .bind(maker_fee_bps)
+        .bind(taker_fee_bps)
+        .fetch_one(&self.postgres)
+        .await?;
+
+        Ok(Some(fee_override(&row)))
+    }
+
+    /// List every fee override
+    pub async fn list_fee_overrides(&self) -> Result<Vec<FeeOverride>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT user_address, market_id, maker_fee_bps, taker_fee_bps, updated_at FROM fee_overrides",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(fee_override).collect())
+    }
+
+    /// A user's fee overrides, keyed by market
+    pub async fn get_user_fee_overrides(
+        &self,
+        user_address: &str,
+    ) -> Result<HashMap<String, FeeOverride>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT user_address, market_id, maker_fee_bps, taker_fee_bps, updated_at
+            FROM fee_overrides
+            WHERE user_address = $1
+            "#,
+        )
+        .bind(user_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let fee_override = fee_override(row);
+                (fee_override.market_id.clone(), fee_override)
+            })
+            .collect())
+    }
+}
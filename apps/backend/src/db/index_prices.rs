@@ -0,0 +1,51 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::{db::IndexPriceRow, domain::IndexPrice};
+
+impl Db {
+    /// Insert a batch of index prices into ClickHouse in a single insert
+    pub async fn insert_index_prices(&self, prices: &[IndexPrice]) -> Result<()> {
+        if prices.is_empty() {
+            return Ok(());
+        }
+
+        let insert = async {
+            let mut insert = self
+                .clickhouse
+                .insert::<IndexPriceRow>("index_prices")
+                .await?;
+            for price in prices {
+                insert.write(&IndexPriceRow::from(price)).await?;
+            }
+            insert.end().await
+        };
+        self.timed_insert("index_prices", prices.len(), insert)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get a market's index prices within [from, to], oldest first
+    pub async fn get_index_prices(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<IndexPrice>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT market_id, timestamp, price, source
+            FROM exchange.index_prices
+            WHERE market_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp",
+            )
+            .bind(market_id)
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<IndexPriceRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(IndexPrice::from).collect())
+    }
+}
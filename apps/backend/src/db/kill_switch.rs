@@ -0,0 +1,74 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::KillSwitch;
+use sqlx::Row;
+
+/// `scope` value of the exchange-wide kill switch
+const GLOBAL_SCOPE: &str = "*";
+
+fn scope(market_id: Option<&str>) -> &str {
+    market_id.unwrap_or(GLOBAL_SCOPE)
+}
+
+impl Db {
+    /// Persist an engaged kill switch, replacing any existing one for the same scope
+    pub async fn engage_kill_switch(&self, switch: &KillSwitch) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO kill_switches (scope, reason, engaged_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (scope) DO UPDATE
+            SET reason = EXCLUDED.reason,
+                engaged_at = EXCLUDED.engaged_at
+            "#,
+        )
+        .bind(scope(switch.market_id.as_deref()))
+        .bind(&switch.reason)
+        .bind(switch.engaged_at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove a kill switch; returns whether one was engaged
+    pub async fn release_kill_switch(&self, market_id: Option<&str>) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM kill_switches WHERE scope = $1")
+            .bind(scope(market_id))
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List every engaged kill switch, exchange-wide first
+    pub async fn list_kill_switches(&self) -> Result<Vec<KillSwitch>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT scope, reason, engaged_at
+            FROM kill_switches
+            ORDER BY scope <> '*', scope
+            "#,
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let switches = rows
+            .iter()
+            .map(|row| {
+                let scope: String = row.get("scope");
+                KillSwitch {
+                    market_id: (scope != GLOBAL_SCOPE).then_some(scope),
+                    reason: row.get("reason"),
+                    engaged_at: row.get("engaged_at"),
+                }
+            })
+            .collect();
+
+        Ok(switches)
+    }
+}
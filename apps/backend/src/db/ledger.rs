@@ -0,0 +1,134 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::{
+    FeeRoute, LedgerEntry, LedgerEntryKind, LedgerPosting, RevenueSource, SystemAccount,
+};
+use chrono::Utc;
+use sqlx::Row;
+
+impl Db {
+    /// List the routing rule for every revenue source
+    pub async fn list_fee_routes(&self) -> Result<Vec<FeeRoute>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query("SELECT source, insurance_bps, updated_at FROM fee_routes")
+            .fetch_all(&self.postgres)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let source: String = row.get("source");
+                Some(FeeRoute {
+                    source: source.parse().ok()?,
+                    insurance_bps: row.get::<i32, _>("insurance_bps") as u32,
+                    updated_at: row.get("updated_at"),
+                })
+            })
+            .collect())
+    }
+
+    /// Set the share of a revenue source paid into the insurance fund
+    pub async fn set_fee_route(
+        &self,
+        source: RevenueSource,
+        insurance_bps: u32,
+    ) -> Result<FeeRoute> {
+        let updated_at = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO fee_routes (source, insurance_bps, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (source) DO UPDATE
+            SET insurance_bps = EXCLUDED.insurance_bps,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(source.to_string())
+        .bind(insurance_bps as i32)
+        .bind(updated_at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(FeeRoute {
+            source,
+            insurance_bps,
+            updated_at,
+        })
+    }
+
+    /// Record system account postings for a batch of trades (within a transaction)
+    pub async fn create_ledger_entries_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        postings: &[LedgerPosting],
+    ) -> Result<()> {
+        if postings.is_empty() {
+            return Ok(());
+        }
+
+        let accounts: Vec<&str> = postings.iter().map(|p| p.account.address()).collect();
+        let tickers: Vec<&str> = postings.iter().map(|p| p.token_ticker.as_str()).collect();
+        let amounts: Vec<String> = postings.iter().map(|p| p.amount.to_string()).collect();
+        let kinds: Vec<String> = postings.iter().map(|p| p.kind.to_string()).collect();
+        let trade_ids: Vec<_> = postings.iter().map(|p| p.trade_id).collect();
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO system_ledger (account, token_ticker, amount, kind, trade_id)
+            SELECT * FROM UNNEST($1::text[], $2::text[], $3::numeric[], $4::text[], $5::uuid[])
+            "#,
+        )
+        .bind(&accounts)
+        .bind(&tickers)
+        .bind(&amounts)
+        .bind(&kinds)
+        .bind(&trade_ids)
+        .execute(&mut **tx);
+        self.timed("create_ledger_entries", query).await?;
+
+        Ok(())
+    }
+
+    /// List a system account's ledger entries, newest first
+    pub async fn list_ledger_entries(
+        &self,
+        account: SystemAccount,
+        token_ticker: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<LedgerEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, token_ticker, amount::TEXT AS amount, kind, trade_id, created_at
+            FROM system_ledger
+            WHERE account = $1 AND ($2::TEXT IS NULL OR token_ticker = $2)
+            ORDER BY id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(account.address())
+        .bind(token_ticker)
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let amount: String = row.get("amount");
+                let kind: String = row.get("kind");
+                LedgerEntry {
+                    id: row.get("id"),
+                    account,
+                    token_ticker: row.get("token_ticker"),
+                    amount: amount.parse().unwrap_or(0),
+                    kind: kind.parse().unwrap_or(LedgerEntryKind::OpeningBalance),
+                    trade_id: row.get("trade_id"),
+                    created_at: row.get("created_at"),
+                }
+            })
+            .collect())
+    }
+}
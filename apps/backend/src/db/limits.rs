@@ -0,0 +1,142 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::UserLimits;
+use crate::utils::decode_atoms;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use sqlx::Row;
+
+impl Db {
+    /// Set a user's limits in a market, replacing any existing ones
+    /// Clearing both limits deletes the row
+    pub async fn set_user_limits(
+        &self,
+        user_address: &str,
+        market_id: &str,
+        max_position: Option<u128>,
+        max_open_notional: Option<u128>,
+    ) -> Result<UserLimits> {
+        let updated_at = Utc::now();
+
+        if max_position.is_none() && max_open_notional.is_none() {
+            sqlx::query("DELETE FROM user_limits WHERE user_address = $1 AND market_id = $2")
+                .bind(user_address)
+                .bind(market_id)
+                .execute(&self.postgres)
+                .await?;
+        } else {
+            sqlx::query(
+                r#"
+                INSERT INTO user_limits (user_address, market_id, max_position, max_open_notional, updated_at)
+                VALUES ($1, $2, $3::numeric, $4::numeric, $5)
+                ON CONFLICT (user_address, market_id) DO UPDATE
+                SET max_position = EXCLUDED.max_position,
+                    max_open_notional = EXCLUDED.max_open_notional,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+            )
+            .bind(user_address)
+            .bind(market_id)
+            .bind(max_position.map(|v| v.to_string()))
+            .bind(max_open_notional.map(|v| v.to_string()))
+            .bind(updated_at)
+            .execute(&self.postgres)
+            .await?;
+        }
+
+        Ok(UserLimits {
+            user_address: user_address.to_string(),
+            market_id: market_id.to_string(),
+            max_position,
+            max_open_notional,
+            updated_at,
+        })
+    }
+
+    /// List every configured user limit
+    pub async fn list_user_limits(&self) -> Result<Vec<UserLimits>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT user_address, market_id, max_position, max_open_notional, updated_at
+            FROM user_limits
+            "#,
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let limits = rows
+            .iter()
+            .map(|row| {
+                let max_position: Option<BigDecimal> = row.get("max_position");
+                let max_open_notional: Option<BigDecimal> = row.get("max_open_notional");
+
+                Ok(UserLimits {
+                    user_address: row.get("user_address"),
+                    market_id: row.get("market_id"),
+                    max_position: max_position
+                        .map(|value| decode_atoms("max_position", &value))
+                        .transpose()?,
+                    max_open_notional: max_open_notional
+                        .map(|value| decode_atoms("max_open_notional", &value))
+                        .transpose()?,
+                    updated_at: row.get("updated_at"),
+                })
+            })
+            .collect::<std::result::Result<_, sqlx::Error>>()?;
+
+        Ok(limits)
+    }
+
+    /// Net position of a user in a market: base atoms bought minus sold
+    pub async fn get_net_position(&self, user_address: &str, market_id: &str) -> Result<i128> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COALESCE(SUM(size) FILTER (WHERE buyer_address = $1), 0) AS bought,
+                COALESCE(SUM(size) FILTER (WHERE seller_address = $1), 0) AS sold
+            FROM trades
+            WHERE market_id = $2 AND (buyer_address = $1 OR seller_address = $1)
+            "#,
+        )
+        .bind(user_address)
+        .bind(market_id)
+        .fetch_one(&self.postgres)
+        .await?;
+
+        let bought: BigDecimal = row.get("bought");
+        let sold: BigDecimal = row.get("sold");
+        Ok(decode_atoms("bought", &bought)? as i128 - decode_atoms("sold", &sold)? as i128)
+    }
+
+    /// Total value of a user's resting orders in a market, in quote atoms
+    /// `base_decimals` converts price (quote atoms per whole base token) times size (base atoms)
+    pub async fn get_open_order_notional(
+        &self,
+        user_address: &str,
+        market_id: &str,
+        base_decimals: u8,
+    ) -> Result<u128> {
+        let row = sqlx::query(
+            r#"
+            SELECT COALESCE(FLOOR(SUM(price * (size - filled_size)) / POWER(10::numeric, $3)), 0) AS notional
+            FROM orders
+            WHERE user_address = $1
+              AND market_id = $2
+              AND status IN ('pending', 'partially_filled')
+              AND type = 'limit'
+            "#,
+        )
+        .bind(user_address)
+        .bind(market_id)
+        .bind(base_decimals as i32)
+        .fetch_one(&self.postgres)
+        .await?;
+
+        let notional: BigDecimal = row.get("notional");
+        Ok(decode_atoms("notional", &notional)?)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::db::rfq::user_not_found;
+use crate::db::Db;
+use crate::errors::Result;
+
+impl Db {
+    /// Let a user read the per-order book; approving twice is a no-op
+    pub async fn approve_market_maker(&self, user_address: &str) -> Result<()> {
+        sqlx::query("INSERT INTO market_makers (user_address) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(user_address)
+            .execute(&self.postgres)
+            .await
+            .map_err(user_not_found(user_address))?;
+
+        Ok(())
+    }
+
+    /// Take back a user's access to the per-order book
+    pub async fn revoke_market_maker(&self, user_address: &str) -> Result<()> {
+        sqlx::query("DELETE FROM market_makers WHERE user_address = $1")
+            .bind(user_address)
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether a user may read the per-order book
+    pub async fn is_market_maker(&self, user_address: &str) -> Result<bool> {
+        let approved = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM market_makers WHERE user_address = $1)",
+        )
+        .bind(user_address)
+        .fetch_one(&self.postgres)
+        .await?;
+
+        Ok(approved)
+    }
+}
@@ -1,8 +1,13 @@
 use bigdecimal::BigDecimal;
+use sqlx::Row;
+use std::collections::HashMap;
 
 use crate::db::Db;
 use crate::errors::{ExchangeError, Result};
-use crate::models::{db::MarketRow, domain::Market};
+use crate::models::{
+    db::MarketRow,
+    domain::{Market, MarketStatus},
+};
 
 impl Db {
     /// Create a new market
@@ -17,6 +22,32 @@ impl Db {
         maker_fee_bps: i32,
         taker_fee_bps: i32,
     ) -> Result<Market> {
+        // Negative maker fees are rebates, which the taker fee must cover
+        if !(-10000..=10000).contains(&maker_fee_bps)
+            || !(0..=10000).contains(&taker_fee_bps)
+            || maker_fee_bps + taker_fee_bps < 0
+        {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Invalid fees: maker {} bps, taker {} bps (a maker rebate cannot exceed the taker fee)",
+                    maker_fee_bps, taker_fee_bps
+                ),
+            });
+        }
+
+        if let Some(memory) = &self.memory {
+            return memory.create_market(Market {
+                id: format!("{}/{}", base_ticker, quote_ticker),
+                base_ticker,
+                quote_ticker,
+                tick_size,
+                lot_size,
+                min_size,
+                maker_fee_bps,
+                taker_fee_bps,
+            });
+        }
+
         // Check if both tokens exist before creating the market
         self.get_token(&base_ticker)
             .await
@@ -53,24 +84,34 @@ impl Db {
             _ => ExchangeError::Database(e),
         })?;
 
-        Ok(row.into())
+        Ok(row.try_into()?)
     }
 
     /// Get a market by id
+    #[tracing::instrument(name = "db.get_market", skip(self))]
     pub async fn get_market(&self, market_id: &str) -> Result<Market> {
-        let row: MarketRow =
+        if let Some(memory) = &self.memory {
+            return memory.get_market(market_id);
+        }
+
+        let query =
             sqlx::query_as!(MarketRow, "SELECT id, base_ticker, quote_ticker, tick_size, lot_size, min_size, maker_fee_bps, taker_fee_bps FROM markets WHERE id = $1", market_id)
-                .fetch_optional(&self.postgres)
-                .await?
-                .ok_or_else(|| ExchangeError::MarketNotFound {
-                    market_id: market_id.to_string(),
-                })?;
+                .fetch_optional(&self.postgres);
+        let row: MarketRow = self.timed("get_market", query).await?.ok_or_else(|| {
+            ExchangeError::MarketNotFound {
+                market_id: market_id.to_string(),
+            }
+        })?;
 
-        Ok(row.into())
+        Ok(row.try_into()?)
     }
 
     /// List all markets
     pub async fn list_markets(&self) -> Result<Vec<Market>> {
+        if let Some(memory) = &self.memory {
+            return memory.list_markets();
+        }
+
         let rows = sqlx::query_as!(
             MarketRow,
             "SELECT id, base_ticker, quote_ticker, tick_size, lot_size, min_size, maker_fee_bps, taker_fee_bps FROM markets ORDER BY id"
@@ -79,6 +120,129 @@ impl Db {
         .await
         .map_err(ExchangeError::from)?;
 
-        Ok(rows.into_iter().map(|row| row.into()).collect())
+        Ok(rows
+            .into_iter()
+            .map(Market::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Decimals of each market's base token, for scaling price * size into quote atoms
+    pub async fn get_base_decimals_by_market(&self) -> Result<HashMap<String, u8>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_base_decimals_by_market();
+        }
+
+        let rows = sqlx::query(
+            "SELECT m.id, t.decimals FROM markets m JOIN tokens t ON t.ticker = m.base_ticker",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("id"), row.get::<i32, _>("decimals") as u8))
+            .collect())
+    }
+
+    /// Numeric id of every market, by market id
+    pub async fn list_market_numeric_ids(&self) -> Result<HashMap<String, u32>> {
+        if let Some(memory) = &self.memory {
+            return memory.list_market_numeric_ids();
+        }
+
+        let rows = sqlx::query("SELECT id, numeric_id FROM markets")
+            .fetch_all(&self.postgres)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("id"), row.get::<i32, _>("numeric_id") as u32))
+            .collect())
+    }
+
+    /// Set a market's trading status
+    pub async fn set_market_status(&self, market_id: &str, status: MarketStatus) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            return memory.set_market_status(market_id, status);
+        }
+
+        let result = sqlx::query("UPDATE markets SET status = $2 WHERE id = $1")
+            .bind(market_id)
+            .bind(status.to_string())
+            .execute(&self.postgres)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ExchangeError::MarketNotFound {
+                market_id: market_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Markets that are not active, with their status
+    pub async fn list_inactive_markets(&self) -> Result<Vec<(String, MarketStatus)>> {
+        if let Some(memory) = &self.memory {
+            return memory.list_inactive_markets();
+        }
+
+        let rows =
+            sqlx::query("SELECT id, status FROM markets WHERE status <> 'active' ORDER BY id")
+                .fetch_all(&self.postgres)
+                .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let status: String = row.get("status");
+                (
+                    row.get("id"),
+                    status.parse().unwrap_or(MarketStatus::Halted),
+                )
+            })
+            .collect())
+    }
+
+    /// Persist an admin override of a market's price collar; `None` disables the collar
+    pub async fn set_price_collar_override(
+        &self,
+        market_id: &str,
+        collar_bps: Option<u32>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO price_collars (market_id, collar_bps, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (market_id) DO UPDATE
+            SET collar_bps = EXCLUDED.collar_bps,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(market_id)
+        .bind(collar_bps.map(|bps| bps as i32))
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Admin overrides of price collars, by market
+    pub async fn list_price_collar_overrides(&self) -> Result<Vec<(String, Option<u32>)>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows =
+            sqlx::query("SELECT market_id, collar_bps FROM price_collars ORDER BY market_id")
+                .fetch_all(&self.postgres)
+                .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let collar_bps: Option<i32> = row.get("collar_bps");
+                (row.get("market_id"), collar_bps.map(|bps| bps as u32))
+            })
+            .collect())
     }
 }
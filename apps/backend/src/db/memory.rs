@@ -0,0 +1,708 @@
+// in-memory stand-in for the tables the simulator uses
+
+use crate::balance_notify::BalanceNotice;
+use crate::db::balances::BalanceChanges;
+use crate::errors::{ExchangeError, Result};
+use crate::models::api::ApiCandle;
+use crate::models::db::MarketStatsRow;
+use crate::models::domain::{
+    Balance, CancelReason, Market, MarketStatus, OcoLink, Order, OrderStatus, OrderType, Token,
+    Trade, TradeFee, User,
+};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Finished orders kept for order history; the oldest are dropped past this
+pub const MAX_FINISHED_ORDERS: usize = 100_000;
+
+/// Trades kept for history, candles and stats; the oldest are dropped past this
+pub const MAX_TRADES: usize = 200_000;
+
+/// Balance changes announced outside the engine, waiting for the listener
+const NOTICE_CAPACITY: usize = 1024;
+
+/// Tokens, markets, users, balances, orders and trades held in memory
+///
+/// Serves the queries of order entry, settlement and the frontend's reads
+/// when `Db` runs without Postgres and ClickHouse, as in `exchange-sim`.
+/// Everything is lost when the process exits.
+pub struct MemoryStore {
+    state: Mutex<State>,
+    notices: broadcast::Sender<BalanceNotice>,
+}
+
+#[derive(Default)]
+struct State {
+    tokens: BTreeMap<String, Token>,
+    markets: BTreeMap<String, Market>,
+    /// Numbered from 1 in order of creation, as the Postgres sequence does
+    numeric_ids: HashMap<String, u32>,
+    inactive_markets: BTreeMap<String, MarketStatus>,
+    users: HashMap<String, User>,
+    balances: BTreeMap<(String, String), Balance>,
+    orders: HashMap<Uuid, Order>,
+    /// Cancelled and filled orders, oldest first, for pruning
+    finished_orders: Vec<Uuid>,
+    /// Oldest first
+    trades: Vec<Trade>,
+    trade_fees: HashMap<Uuid, Vec<TradeFee>>,
+    /// Oldest first
+    oco_links: Vec<OcoLink>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self {
+            state: Mutex::default(),
+            notices: broadcast::channel(NOTICE_CAPACITY).0,
+        }
+    }
+}
+
+impl MemoryStore {
+    fn state(&self) -> MutexGuard<'_, State> {
+        // Every update is applied whole, so a panicked writer leaves nothing half done
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // ===============================
+    // Tokens and markets
+    // ===============================
+
+    pub fn create_token(&self, ticker: String, decimals: u8, name: String) -> Result<Token> {
+        let token = Token {
+            ticker: ticker.clone(),
+            decimals,
+            name,
+        };
+        self.state().tokens.insert(ticker, token.clone());
+        Ok(token)
+    }
+
+    pub fn get_token(&self, ticker: &str) -> Result<Token> {
+        self.state()
+            .tokens
+            .get(ticker)
+            .cloned()
+            .ok_or_else(|| ExchangeError::TokenNotFound {
+                ticker: ticker.to_string(),
+            })
+    }
+
+    pub fn list_tokens(&self) -> Result<Vec<Token>> {
+        Ok(self.state().tokens.values().cloned().collect())
+    }
+
+    /// Add a market whose fees `Db::create_market` has already checked
+    pub fn create_market(&self, market: Market) -> Result<Market> {
+        let mut state = self.state();
+        for ticker in [&market.base_ticker, &market.quote_ticker] {
+            if !state.tokens.contains_key(ticker) {
+                return Err(ExchangeError::TokenNotFound {
+                    ticker: ticker.clone(),
+                });
+            }
+        }
+        if state.markets.contains_key(&market.id) {
+            return Err(ExchangeError::MarketAlreadyExists {
+                market_id: market.id,
+            });
+        }
+        let numeric_id = state.numeric_ids.len() as u32 + 1;
+        state.numeric_ids.insert(market.id.clone(), numeric_id);
+        state.markets.insert(market.id.clone(), market.clone());
+        Ok(market)
+    }
+
+    pub fn get_market(&self, market_id: &str) -> Result<Market> {
+        self.state()
+            .markets
+            .get(market_id)
+            .cloned()
+            .ok_or_else(|| ExchangeError::MarketNotFound {
+                market_id: market_id.to_string(),
+            })
+    }
+
+    pub fn list_markets(&self) -> Result<Vec<Market>> {
+        Ok(self.state().markets.values().cloned().collect())
+    }
+
+    pub fn get_base_decimals_by_market(&self) -> Result<HashMap<String, u8>> {
+        let state = self.state();
+        Ok(state
+            .markets
+            .values()
+            .filter_map(|market| {
+                let token = state.tokens.get(&market.base_ticker)?;
+                Some((market.id.clone(), token.decimals))
+            })
+            .collect())
+    }
+
+    pub fn list_market_numeric_ids(&self) -> Result<HashMap<String, u32>> {
+        Ok(self.state().numeric_ids.clone())
+    }
+
+    pub fn set_market_status(&self, market_id: &str, status: MarketStatus) -> Result<()> {
+        let mut state = self.state();
+        if !state.markets.contains_key(market_id) {
+            return Err(ExchangeError::MarketNotFound {
+                market_id: market_id.to_string(),
+            });
+        }
+        match status {
+            MarketStatus::Active => state.inactive_markets.remove(market_id),
+            _ => state.inactive_markets.insert(market_id.to_string(), status),
+        };
+        Ok(())
+    }
+
+    pub fn list_inactive_markets(&self) -> Result<Vec<(String, MarketStatus)>> {
+        Ok(self
+            .state()
+            .inactive_markets
+            .iter()
+            .map(|(market_id, status)| (market_id.clone(), *status))
+            .collect())
+    }
+
+    // ===============================
+    // Users and balances
+    // ===============================
+
+    /// Create a user, or return the existing one
+    pub fn create_user(&self, address: String) -> Result<User> {
+        Ok(self
+            .state()
+            .users
+            .entry(address.clone())
+            .or_insert_with(|| User {
+                address,
+                created_at: Utc::now(),
+            })
+            .clone())
+    }
+
+    pub fn get_user(&self, address: &str) -> Result<User> {
+        self.state()
+            .users
+            .get(address)
+            .cloned()
+            .ok_or_else(|| ExchangeError::UserNotFound {
+                address: address.to_string(),
+            })
+    }
+
+    pub fn get_balance(&self, user_address: &str, token_ticker: &str) -> Result<Balance> {
+        self.state()
+            .balances
+            .get(&(user_address.to_string(), token_ticker.to_string()))
+            .cloned()
+            .ok_or_else(|| ExchangeError::BalanceNotFound {
+                user_address: user_address.to_string(),
+                token_ticker: token_ticker.to_string(),
+            })
+    }
+
+    pub fn list_balances_by_user(&self, user_address: &str) -> Result<Vec<Balance>> {
+        Ok(self
+            .state()
+            .balances
+            .values()
+            .filter(|balance| balance.user_address == user_address)
+            .cloned()
+            .collect())
+    }
+
+    pub fn add_balance(
+        &self,
+        user_address: &str,
+        token_ticker: &str,
+        amount: u128,
+    ) -> Result<Balance> {
+        let mut state = self.state();
+        let balance = balance_entry(&mut state, user_address, token_ticker);
+        balance.amount += amount;
+        balance.updated_at = Utc::now();
+        Ok(balance.clone())
+    }
+
+    /// Lock `amount` of the available balance, failing if there isn't enough
+    pub fn lock_balance(
+        &self,
+        user_address: &str,
+        token_ticker: &str,
+        amount: u128,
+    ) -> Result<Balance> {
+        let mut state = self.state();
+        let key = (user_address.to_string(), token_ticker.to_string());
+        match state.balances.get_mut(&key) {
+            Some(balance) if balance.amount - balance.open_interest >= amount => {
+                balance.open_interest += amount;
+                balance.updated_at = Utc::now();
+                Ok(balance.clone())
+            }
+            _ => Err(ExchangeError::InsufficientBalance {
+                user_address: user_address.to_string(),
+                token_ticker: token_ticker.to_string(),
+                required: amount,
+            }),
+        }
+    }
+
+    /// Release up to `amount` of the locked balance; a missing balance has nothing to unlock
+    pub fn unlock_balance(
+        &self,
+        user_address: &str,
+        token_ticker: &str,
+        amount: u128,
+    ) -> Result<Balance> {
+        let mut state = self.state();
+        let key = (user_address.to_string(), token_ticker.to_string());
+        let now = Utc::now();
+        Ok(match state.balances.get_mut(&key) {
+            Some(balance) => {
+                balance.open_interest = balance.open_interest.saturating_sub(amount);
+                balance.updated_at = now;
+                balance.clone()
+            }
+            None => Balance {
+                user_address: user_address.to_string(),
+                token_ticker: token_ticker.to_string(),
+                amount: 0,
+                open_interest: 0,
+                updated_at: now,
+            },
+        })
+    }
+
+    /// Announce a balance change to whoever follows `balance_notices`
+    pub fn notify_balance_changed(&self, user_address: &str, token_ticker: &str) {
+        // Nobody listening is fine; the change is already in the balance
+        let _ = self.notices.send(BalanceNotice {
+            user_address: user_address.to_string(),
+            token_ticker: token_ticker.to_string(),
+        });
+    }
+
+    /// Balance changes announced from now on, as Postgres NOTIFY delivers them
+    pub fn balance_notices(&self) -> broadcast::Receiver<BalanceNotice> {
+        self.notices.subscribe()
+    }
+
+    // ===============================
+    // Orders
+    // ===============================
+
+    pub fn create_order(&self, order: &Order) -> Result<()> {
+        self.state().orders.insert(order.id, order.clone());
+        Ok(())
+    }
+
+    pub fn update_order_fill(
+        &self,
+        order_id: Uuid,
+        filled_size: u128,
+        status: OrderStatus,
+    ) -> Result<()> {
+        let mut state = self.state();
+        update_order(&mut state, order_id, filled_size, status, None);
+        Ok(())
+    }
+
+    pub fn cancel_order(
+        &self,
+        order_id: Uuid,
+        filled_size: u128,
+        reason: Option<CancelReason>,
+    ) -> Result<()> {
+        let mut state = self.state();
+        update_order(
+            &mut state,
+            order_id,
+            filled_size,
+            OrderStatus::Cancelled,
+            reason,
+        );
+        Ok(())
+    }
+
+    pub fn get_order(&self, order_id: &Uuid) -> Result<Order> {
+        self.state()
+            .orders
+            .get(order_id)
+            .cloned()
+            .ok_or(ExchangeError::OrderNotFound)
+    }
+
+    /// A user's orders, newest first
+    pub fn get_user_orders(
+        &self,
+        user_address: &str,
+        market_id: Option<&str>,
+        status: Option<OrderStatus>,
+        limit: u32,
+    ) -> Result<Vec<Order>> {
+        let mut orders: Vec<Order> = self
+            .state()
+            .orders
+            .values()
+            .filter(|order| order.user_address == user_address)
+            .filter(|order| market_id.is_none_or(|id| order.market_id == id))
+            .filter(|order| status.is_none_or(|status| order.status == status))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| std::cmp::Reverse(order.created_at));
+        orders.truncate(limit.min(1000) as usize);
+        Ok(orders)
+    }
+
+    /// Open limit orders of a market, oldest first
+    pub fn get_recoverable_orders_for_market(&self, market_id: &str) -> Result<Vec<Order>> {
+        let mut orders: Vec<Order> = self
+            .state()
+            .orders
+            .values()
+            .filter(|order| order.market_id == market_id && is_resting(order))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.created_at);
+        Ok(orders)
+    }
+
+    pub fn count_open_orders(
+        &self,
+        user_address: &str,
+        market_id: Option<&str>,
+    ) -> Result<Vec<(String, u64)>> {
+        let mut counts = BTreeMap::new();
+        for order in self.state().orders.values() {
+            if order.user_address == user_address
+                && market_id.is_none_or(|id| order.market_id == id)
+                && (is_resting(order) || is_untriggered_stop(order))
+            {
+                *counts.entry(order.market_id.clone()).or_insert(0) += 1;
+            }
+        }
+        Ok(counts.into_iter().collect())
+    }
+
+    /// Stop orders waiting for their trigger price, oldest first
+    pub fn get_untriggered_stop_orders(&self) -> Result<Vec<Order>> {
+        let mut orders: Vec<Order> = self
+            .state()
+            .orders
+            .values()
+            .filter(|order| is_untriggered_stop(order))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.created_at);
+        Ok(orders)
+    }
+
+    /// Open orders and untriggered stops with an expiry
+    pub fn get_expiring_orders(&self) -> Result<Vec<Order>> {
+        Ok(self
+            .state()
+            .orders
+            .values()
+            .filter(|order| {
+                order.expires_at.is_some() && (is_resting(order) || is_untriggered_stop(order))
+            })
+            .cloned()
+            .collect())
+    }
+
+    pub fn create_oco_link(&self, link: &OcoLink) -> Result<()> {
+        self.state().oco_links.push(link.clone());
+        Ok(())
+    }
+
+    /// Pairs whose orders are both still open, oldest first
+    pub fn list_open_oco_links(&self) -> Result<Vec<OcoLink>> {
+        let state = self.state();
+        let open = |order_id: &Uuid| {
+            state.orders.get(order_id).is_some_and(|order| {
+                matches!(
+                    order.status,
+                    OrderStatus::Pending | OrderStatus::PartiallyFilled
+                )
+            })
+        };
+        Ok(state
+            .oco_links
+            .iter()
+            .filter(|link| open(&link.first_order_id) && open(&link.second_order_id))
+            .cloned()
+            .collect())
+    }
+
+    pub fn trigger_order(&self, order_id: Uuid, order_type: OrderType) -> Result<()> {
+        if let Some(order) = self.state().orders.get_mut(&order_id) {
+            order.order_type = order_type;
+            order.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    // ===============================
+    // Settlement and trades
+    // ===============================
+
+    /// Apply a settlement batch all at once, like the executor's transaction
+    pub fn settle(
+        &self,
+        balance_changes: &BalanceChanges,
+        order_fills: &[(Uuid, u128, OrderStatus)],
+        trades: &[Trade],
+        trade_fees: &[TradeFee],
+    ) -> Result<()> {
+        let mut state = self.state();
+        let now = Utc::now();
+        for ((user_address, token_ticker), change) in balance_changes {
+            let balance = balance_entry(&mut state, user_address, token_ticker);
+            balance.amount = (balance.amount + change.credit).saturating_sub(change.debit);
+            balance.open_interest = balance.open_interest.saturating_sub(change.unlock);
+            balance.updated_at = now;
+        }
+        for &(order_id, filled_size, status) in order_fills {
+            update_order(&mut state, order_id, filled_size, status, None);
+        }
+        for fee in trade_fees {
+            state
+                .trade_fees
+                .entry(fee.trade_id)
+                .or_default()
+                .push(fee.clone());
+        }
+        state.trades.extend_from_slice(trades);
+        if state.trades.len() > MAX_TRADES {
+            let excess = state.trades.len() - MAX_TRADES;
+            for trade in state.trades.drain(..excess).collect::<Vec<_>>() {
+                state.trade_fees.remove(&trade.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// A user's trades, newest first
+    pub fn get_user_trades(
+        &self,
+        user_address: &str,
+        market_id: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<Trade>> {
+        Ok(self
+            .state()
+            .trades
+            .iter()
+            .rev()
+            .filter(|t| t.buyer_address == user_address || t.seller_address == user_address)
+            .filter(|t| market_id.is_none_or(|id| t.market_id == id))
+            .take(limit.min(1000) as usize)
+            .cloned()
+            .collect())
+    }
+
+    /// A market's trades, newest first
+    pub fn get_market_trades(&self, market_id: &str, limit: u32) -> Result<Vec<Trade>> {
+        Ok(self
+            .state()
+            .trades
+            .iter()
+            .rev()
+            .filter(|t| t.market_id == market_id)
+            .take(limit.min(1000) as usize)
+            .cloned()
+            .collect())
+    }
+
+    pub fn get_user_trade_fees(
+        &self,
+        user_address: &str,
+        trade_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, TradeFee>> {
+        let state = self.state();
+        Ok(trade_ids
+            .iter()
+            .filter_map(|id| state.trade_fees.get(id))
+            .flatten()
+            .filter(|fee| fee.user_address == user_address)
+            .map(|fee| (fee.trade_id, fee.clone()))
+            .collect())
+    }
+
+    pub fn get_user_rebate_totals(&self, user_address: &str) -> Result<Vec<(String, u128)>> {
+        let mut totals = BTreeMap::new();
+        for fee in self.state().trade_fees.values().flatten() {
+            if fee.user_address == user_address && fee.fee < 0 {
+                *totals.entry(fee.token_ticker.clone()).or_insert(0) += fee.fee.unsigned_abs();
+            }
+        }
+        Ok(totals.into_iter().collect())
+    }
+
+    /// Price of the most recent trade in every market that has traded
+    pub fn get_last_trade_prices(&self) -> Result<Vec<(String, u128)>> {
+        let mut prices = BTreeMap::new();
+        for trade in &self.state().trades {
+            prices.insert(trade.market_id.clone(), trade.price);
+        }
+        Ok(prices.into_iter().collect())
+    }
+
+    // ===============================
+    // Market data
+    // ===============================
+
+    /// Candles of the intervals that traded within [from, to], the
+    /// `count_back` most recent if set, computed from the trades on demand
+    pub fn get_candles(
+        &self,
+        market_id: &str,
+        interval_secs: u32,
+        from: i64,
+        to: i64,
+        count_back: Option<usize>,
+    ) -> Result<Vec<ApiCandle>> {
+        let step = interval_secs as i64;
+        let mut candles: BTreeMap<i64, ApiCandle> = BTreeMap::new();
+        for trade in self.trades_of(market_id) {
+            let bucket = trade.timestamp.timestamp().div_euclid(step) * step;
+            if bucket < from || bucket > to {
+                continue;
+            }
+            candles
+                .entry(bucket)
+                .and_modify(|candle| {
+                    candle.high = candle.high.max(trade.price);
+                    candle.low = candle.low.min(trade.price);
+                    candle.close = trade.price;
+                    candle.volume += trade.size;
+                })
+                .or_insert(ApiCandle {
+                    timestamp: bucket as u32,
+                    open: trade.price,
+                    high: trade.price,
+                    low: trade.price,
+                    close: trade.price,
+                    volume: trade.size,
+                });
+        }
+
+        let mut candles: Vec<ApiCandle> = candles.into_values().collect();
+        if let Some(count) = count_back.filter(|&n| n > 0) {
+            candles.drain(..candles.len().saturating_sub(count));
+        }
+        Ok(candles)
+    }
+
+    /// A market's last trade price strictly before `before`
+    pub fn get_last_price_before(&self, market_id: &str, before: i64) -> Result<Option<u128>> {
+        Ok(self
+            .trades_of(market_id)
+            .into_iter()
+            .rev()
+            .find(|trade| trade.timestamp.timestamp() < before)
+            .map(|trade| trade.price))
+    }
+
+    /// Trade statistics for a market over `[from, to]`, quote volume summed per trade
+    pub fn get_market_stats(
+        &self,
+        market_id: &str,
+        base_decimals: u8,
+        from: i64,
+        to: i64,
+    ) -> Result<MarketStatsRow> {
+        let divisor = 10u128.pow(base_decimals as u32);
+        let mut stats = MarketStatsRow::default();
+        for trade in self.trades_of(market_id) {
+            let timestamp = trade.timestamp.timestamp();
+            if timestamp < from || timestamp > to {
+                continue;
+            }
+            if stats.trade_count == 0 {
+                stats.open = trade.price;
+                stats.low = trade.price;
+            }
+            stats.trade_count += 1;
+            stats.base_volume += trade.size;
+            stats.quote_volume += trade.price * trade.size / divisor;
+            stats.high = stats.high.max(trade.price);
+            stats.low = stats.low.min(trade.price);
+            stats.close = trade.price;
+        }
+        Ok(stats)
+    }
+
+    /// A market's trades, oldest first
+    fn trades_of(&self, market_id: &str) -> Vec<Trade> {
+        self.state()
+            .trades
+            .iter()
+            .filter(|trade| trade.market_id == market_id)
+            .cloned()
+            .collect()
+    }
+}
+
+fn balance_entry<'a>(
+    state: &'a mut State,
+    user_address: &str,
+    token_ticker: &str,
+) -> &'a mut Balance {
+    state
+        .balances
+        .entry((user_address.to_string(), token_ticker.to_string()))
+        .or_insert_with(|| Balance {
+            user_address: user_address.to_string(),
+            token_ticker: token_ticker.to_string(),
+            amount: 0,
+            open_interest: 0,
+            updated_at: Utc::now(),
+        })
+}
+
+fn update_order(
+    state: &mut State,
+    order_id: Uuid,
+    filled_size: u128,
+    status: OrderStatus,
+    reason: Option<CancelReason>,
+) {
+    let Some(order) = state.orders.get_mut(&order_id) else {
+        return;
+    };
+    order.filled_size = filled_size;
+    order.status = status;
+    order.cancel_reason = reason.or(order.cancel_reason);
+    order.updated_at = Utc::now();
+    if matches!(status, OrderStatus::Filled | OrderStatus::Cancelled) {
+        state.finished_orders.push(order_id);
+    }
+    if state.finished_orders.len() > MAX_FINISHED_ORDERS {
+        let excess = state.finished_orders.len() - MAX_FINISHED_ORDERS;
+        for id in state.finished_orders.drain(..excess).collect::<Vec<_>>() {
+            state.orders.remove(&id);
+        }
+    }
+}
+
+/// A stop order still waiting for its trigger price
+fn is_untriggered_stop(order: &Order) -> bool {
+    order.order_type.is_stop() && order.status == OrderStatus::Pending
+}
+
+/// A limit order still on the book
+fn is_resting(order: &Order) -> bool {
+    order.order_type == OrderType::Limit
+        && matches!(
+            order.status,
+            OrderStatus::Pending | OrderStatus::PartiallyFilled
+        )
+}
@@ -2,24 +2,58 @@
 pub mod ch;
 pub mod pg;
 
+pub mod api_keys;
+pub mod archive;
 pub mod balances;
 pub mod candles;
+pub mod deposits;
+pub mod derivatives;
+pub mod events;
+pub mod exports;
+pub mod fee_overrides;
+pub mod index_prices;
+pub mod kill_switch;
+pub mod ledger;
+pub mod limits;
+pub mod market_makers;
 pub mod markets;
+pub mod memory;
+pub mod oco;
 pub mod orders;
+pub mod perpetuals;
+pub mod referrals;
+pub mod rfq;
+pub mod statements;
+pub mod stats;
+pub mod sub_accounts;
+pub mod surveillance;
 pub mod tokens;
 pub mod trades;
 pub mod users;
+pub mod webhooks;
+pub mod withdrawals;
 
 // Re-export common types
 pub use clickhouse::Client;
 pub use sqlx::postgres::{PgPool, Postgres};
 pub use sqlx::Transaction;
 
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Where an in-memory Db's never-used pool points; `.invalid` never resolves
+const IN_MEMORY_URL: &str = "postgres://in-memory.invalid/exchange";
+
 /// Main database handle with connections to both databases
 #[derive(Clone)]
 pub struct Db {
     pub postgres: PgPool,
     pub clickhouse: Client,
+    /// Query latency and ClickHouse insert counts, shared by every clone
+    pub stats: Arc<stats::DbStats>,
+    /// Serves trading and market data instead of the databases; see `Db::in_memory`
+    pub memory: Option<Arc<memory::MemoryStore>>,
 }
 
 impl Db {
@@ -31,11 +65,20 @@ impl Db {
 
     /// Create a new Db instance with explicit URLs
     /// Useful for testing to avoid environment variable conflicts
+    /// Queries slower than DB_SLOW_QUERY_MS are logged
     pub async fn connect_with_urls(
         pg_url: Option<String>,
         ch_url: Option<String>,
     ) -> anyhow::Result<Self> {
-        let postgres = pg::create_pool(pg_url)
+        let slow_query_threshold = match env::var("DB_SLOW_QUERY_MS") {
+            Ok(ms) => Duration::from_millis(
+                ms.parse()
+                    .map_err(|e| anyhow::anyhow!("Invalid DB_SLOW_QUERY_MS '{}': {}", ms, e))?,
+            ),
+            Err(_) => stats::DEFAULT_SLOW_QUERY_THRESHOLD,
+        };
+
+        let postgres = pg::create_pool(pg_url, slow_query_threshold)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create PostgreSQL pool: {}", e))?;
 
@@ -46,6 +89,27 @@ impl Db {
         Ok(Self {
             postgres,
             clickhouse,
+            stats: Arc::new(stats::DbStats::new(slow_query_threshold)),
+            memory: None,
+        })
+    }
+
+    /// Create a Db that keeps everything in memory, for running without databases
+    ///
+    /// Order entry, settlement and the queries the frontend makes are served
+    /// from a `MemoryStore`. The database handles never connect, so anything
+    /// else (deposits, statements, analytics) fails fast with a pool timeout.
+    pub fn in_memory() -> anyhow::Result<Self> {
+        let postgres = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy(IN_MEMORY_URL)?;
+        let clickhouse = Client::default().with_url("http://in-memory.invalid");
+
+        Ok(Self {
+            postgres,
+            clickhouse,
+            stats: Arc::new(stats::DbStats::new(stats::DEFAULT_SLOW_QUERY_THRESHOLD)),
+            memory: Some(Arc::new(memory::MemoryStore::default())),
         })
     }
 
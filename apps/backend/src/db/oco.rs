@@ -0,0 +1,65 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::OcoLink;
+use sqlx::Row;
+
+impl Db {
+    /// Record that two orders were placed as a one-cancels-other pair
+    pub async fn create_oco_link(&self, link: &OcoLink) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            return memory.create_oco_link(link);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO oco_links (id, user_address, first_order_id, second_order_id, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(link.id)
+        .bind(&link.user_address)
+        .bind(link.first_order_id)
+        .bind(link.second_order_id)
+        .bind(link.created_at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List the pairs whose orders are both still open, oldest first
+    pub async fn list_open_oco_links(&self) -> Result<Vec<OcoLink>> {
+        if let Some(memory) = &self.memory {
+            return memory.list_open_oco_links();
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT l.id, l.user_address, l.first_order_id, l.second_order_id, l.created_at
+            FROM oco_links l
+            WHERE EXISTS (
+                SELECT 1 FROM orders o
+                WHERE o.id = l.first_order_id AND o.status IN ('pending', 'partially_filled')
+            )
+            AND EXISTS (
+                SELECT 1 FROM orders o
+                WHERE o.id = l.second_order_id AND o.status IN ('pending', 'partially_filled')
+            )
+            ORDER BY l.created_at
+            "#,
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| OcoLink {
+                id: row.get("id"),
+                user_address: row.get("user_address"),
+                first_order_id: row.get("first_order_id"),
+                second_order_id: row.get("second_order_id"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+}
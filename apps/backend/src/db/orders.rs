@@ -1,21 +1,61 @@
 use crate::db::Db;
 use crate::errors::{ExchangeError, Result};
-use crate::models::domain::{Order, OrderStatus, OrderType, Side};
-use crate::utils::BigDecimalExt;
-use bigdecimal::BigDecimal;
+use crate::models::db::OrderRow;
+use crate::models::domain::{CancelReason, Order, OrderStatus, OrderType};
 use chrono::Utc;
-use sqlx::Row;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Months of `orders` partitions kept created past the current one
+pub const ORDER_PARTITION_MONTHS_AHEAD: i32 = 3;
+
+/// How often missing `orders` partitions are created
+pub const ORDER_PARTITION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 impl Db {
+    /// Create any missing monthly `orders` partitions up to `months_ahead` months
+    /// past the current one, returning how many were created
+    pub async fn ensure_order_partitions(&self, months_ahead: i32) -> Result<u32> {
+        let created: i32 = sqlx::query_scalar("SELECT ensure_order_partitions(now(), $1)")
+            .bind(months_ahead)
+            .fetch_one(&self.postgres)
+            .await?;
+        Ok(created as u32)
+    }
+
+    /// Keep `orders` partitioned ahead of the clock until the process exits
+    ///
+    /// Orders falling outside every monthly partition land in the default one,
+    /// which every open-order query then has to scan as well.
+    pub async fn maintain_order_partitions(self) {
+        let mut ticks = tokio::time::interval(ORDER_PARTITION_INTERVAL);
+        loop {
+            ticks.tick().await;
+            match self
+                .ensure_order_partitions(ORDER_PARTITION_MONTHS_AHEAD)
+                .await
+            {
+                Ok(0) => {}
+                Ok(created) => log::info!("Created {} orders partitions", created),
+                Err(e) => log::error!("Failed to create orders partitions: {}", e),
+            }
+        }
+    }
+
     /// Insert a new order into the database
+    #[tracing::instrument(name = "db.create_order", skip_all, fields(order_id = %order.id))]
     pub async fn create_order(&self, order: &Order) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            return memory.create_order(order);
+        }
+
         // For market orders, use price 1 in DB (actual price doesn't matter for market orders)
-        let price_for_db = if order.order_type == OrderType::Market && order.price == 0 {
-            1
-        } else {
-            order.price
-        };
+        let price_for_db =
+            if order.order_type.execution_type() == OrderType::Market && order.price == 0 {
+                1
+            } else {
+                order.price
+            };
 
         let price_str = price_for_db.to_string();
         let size_str = order.size.to_string();
@@ -24,10 +64,10 @@ impl Db {
         let order_type_str = order.order_type.to_string();
         let status_str = order.status.to_string();
 
-        sqlx::query(
+        let query = sqlx::query(
             r#"
-            INSERT INTO orders (id, user_address, market_id, price, size, side, type, status, filled_size, created_at, updated_at)
-            VALUES ($1, $2, $3, $4::numeric, $5::numeric, $6::side, $7::order_type, $8::order_status, $9::numeric, $10, $11)
+            INSERT INTO orders (id, user_address, market_id, price, size, side, type, status, filled_size, created_at, updated_at, trigger_price, trigger_source, time_in_force, expires_at)
+            VALUES ($1, $2, $3, $4::numeric, $5::numeric, $6::side, $7::order_type, $8::order_status, $9::numeric, $10, $11, $12::numeric, $13, $14, $15)
             "#
         )
         .bind(order.id)
@@ -41,7 +81,52 @@ impl Db {
         .bind(filled_size_str)
         .bind(order.created_at)
         .bind(order.updated_at)
-        .execute(&self.postgres)
+        .bind(order.trigger_price.map(|p| p.to_string()))
+        .bind(order.trigger_source.to_string())
+        .bind(order.time_in_force.to_string())
+        .bind(order.expires_at)
+        .execute(&self.postgres);
+        self.timed("create_order", query).await?;
+
+        Ok(())
+    }
+
+    /// Insert a new order into the database (within a transaction)
+    pub async fn create_order_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        order: &Order,
+    ) -> Result<()> {
+        // For market orders, use price 1 in DB (actual price doesn't matter for market orders)
+        let price_for_db =
+            if order.order_type.execution_type() == OrderType::Market && order.price == 0 {
+                1
+            } else {
+                order.price
+            };
+
+        sqlx::query(
+            r#"
+            INSERT INTO orders (id, user_address, market_id, price, size, side, type, status, filled_size, created_at, updated_at, trigger_price, trigger_source, time_in_force, expires_at)
+            VALUES ($1, $2, $3, $4::numeric, $5::numeric, $6::side, $7::order_type, $8::order_status, $9::numeric, $10, $11, $12::numeric, $13, $14, $15)
+            "#
+        )
+        .bind(order.id)
+        .bind(&order.user_address)
+        .bind(&order.market_id)
+        .bind(price_for_db.to_string())
+        .bind(order.size.to_string())
+        .bind(order.side.to_string())
+        .bind(order.order_type.to_string())
+        .bind(order.status.to_string())
+        .bind(order.filled_size.to_string())
+        .bind(order.created_at)
+        .bind(order.updated_at)
+        .bind(order.trigger_price.map(|p| p.to_string()))
+        .bind(order.trigger_source.to_string())
+        .bind(order.time_in_force.to_string())
+        .bind(order.expires_at)
+        .execute(&mut **tx)
         .await?;
 
         Ok(())
@@ -54,6 +139,10 @@ impl Db {
         filled_size: u128,
         status: OrderStatus,
     ) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            return memory.update_order_fill(order_id, filled_size, status);
+        }
+
         let filled_size_str = filled_size.to_string();
         let status_str = status.to_string();
 
@@ -74,6 +163,34 @@ impl Db {
         Ok(())
     }
 
+    /// Mark an order cancelled at its final filled size, recording why if the exchange cancelled it
+    pub async fn cancel_order(
+        &self,
+        order_id: Uuid,
+        filled_size: u128,
+        reason: Option<CancelReason>,
+    ) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            return memory.cancel_order(order_id, filled_size, reason);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE orders
+            SET filled_size = $1::numeric, status = 'cancelled', cancel_reason = $2, updated_at = $3
+            WHERE id = $4
+            "#,
+        )
+        .bind(filled_size.to_string())
+        .bind(reason.map(|r| r.to_string()))
+        .bind(Utc::now())
+        .bind(order_id)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
     /// Update an order's filled size and status (within a transaction)
     pub async fn update_order_fill_tx(
         &self,
@@ -102,39 +219,91 @@ impl Db {
         Ok(())
     }
 
+    /// Update filled size and status for many orders with one statement (within a transaction)
+    #[tracing::instrument(name = "db.update_order_fills", skip_all, fields(count = fills.len()))]
+    pub async fn update_order_fills_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        fills: &[(Uuid, u128, OrderStatus)],
+    ) -> Result<()> {
+        if fills.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<Uuid> = fills.iter().map(|(id, _, _)| *id).collect();
+        let filled_sizes: Vec<String> = fills.iter().map(|(_, f, _)| f.to_string()).collect();
+        let statuses: Vec<String> = fills.iter().map(|(_, _, s)| s.to_string()).collect();
+
+        let query = sqlx::query(
+            r#"
+            UPDATE orders o
+            SET filled_size = f.filled_size, status = f.status::order_status, updated_at = $4
+            FROM UNNEST($1::uuid[], $2::numeric[], $3::text[]) AS f(id, filled_size, status)
+            WHERE o.id = f.id
+            "#,
+        )
+        .bind(&ids)
+        .bind(&filled_sizes)
+        .bind(&statuses)
+        .bind(Utc::now())
+        .execute(&mut **tx);
+        self.timed("update_order_fills", query).await?;
+
+        Ok(())
+    }
+
     pub async fn get_order(&self, order_id: &Uuid) -> Result<Order> {
-        let row = sqlx::query(
+        if let Some(memory) = &self.memory {
+            return memory.get_order(order_id);
+        }
+
+        let row = sqlx::query_as!(
+            OrderRow,
             r#"
-            SELECT id, user_address, market_id, price, size, side::TEXT as side, type::TEXT as type, status::TEXT as status, filled_size, created_at, updated_at
+            SELECT id, user_address, market_id, price, size, side::TEXT AS "side!", type::TEXT AS "order_type!", status::TEXT AS "status!", filled_size, created_at, updated_at, cancel_reason, trigger_price, trigger_source, time_in_force, expires_at
             FROM orders
             WHERE id = $1
-            "#
+            "#,
+            order_id
         )
-        .bind(order_id)
         .fetch_optional(&self.postgres)
         .await?
         .ok_or(ExchangeError::OrderNotFound)?;
 
-        let price: BigDecimal = row.get("price");
-        let size: BigDecimal = row.get("size");
-        let filled_size: BigDecimal = row.get("filled_size");
-        let side_str: String = row.get("side");
-        let type_str: String = row.get("type");
-        let status_str: String = row.get("status");
-
-        Ok(Order {
-            id: row.get("id"),
-            user_address: row.get("user_address"),
-            market_id: row.get("market_id"),
-            price: price.to_u128(),
-            size: size.to_u128(),
-            side: side_str.parse().unwrap_or(Side::Buy),
-            order_type: type_str.parse().unwrap_or(OrderType::Limit),
-            status: status_str.parse().unwrap_or(OrderStatus::Pending),
-            filled_size: filled_size.to_u128(),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
+        Ok(row.try_into()?)
+    }
+
+    /// Count a user's resting and untriggered stop orders per market, optionally for a single market
+    pub async fn count_open_orders(
+        &self,
+        user_address: &str,
+        market_id: Option<&str>,
+    ) -> Result<Vec<(String, u64)>> {
+        if let Some(memory) = &self.memory {
+            return memory.count_open_orders(user_address, market_id);
+        }
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT market_id, COUNT(*) AS "open_orders!"
+            FROM orders
+            WHERE user_address = $1
+              AND ($2::TEXT IS NULL OR market_id = $2)
+              AND status IN ('pending', 'partially_filled')
+              AND type IN ('limit', 'stop_market', 'stop_limit')
+            GROUP BY market_id
+            ORDER BY market_id
+            "#,
+            user_address,
+            market_id
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.market_id, row.open_orders as u64))
+            .collect())
     }
 
     pub async fn get_user_orders(
@@ -144,138 +313,135 @@ impl Db {
         status: Option<OrderStatus>,
         limit: u32,
     ) -> Result<Vec<Order>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_user_orders(user_address, market_id, status, limit);
+        }
+
         let limit = std::cmp::min(limit, 1000); // Cap at 1000
 
         let status_str = status.map(|s| s.to_string());
 
-        let query = if let (Some(market), Some(stat)) = (market_id, &status_str) {
-            sqlx::query(
-                r#"
-                SELECT id, user_address, market_id, price, size, side::TEXT as side, type::TEXT as type, status::TEXT as status, filled_size, created_at, updated_at
-                FROM orders
-                WHERE user_address = $1 AND market_id = $2 AND status = $3::order_status
-                ORDER BY created_at DESC
-                LIMIT $4
-                "#
-            )
-            .bind(user_address)
-            .bind(market)
-            .bind(stat)
-            .bind(limit as i64)
-        } else if let Some(market) = market_id {
-            sqlx::query(
-                r#"
-                SELECT id, user_address, market_id, price, size, side::TEXT as side, type::TEXT as type, status::TEXT as status, filled_size, created_at, updated_at
-                FROM orders
-                WHERE user_address = $1 AND market_id = $2
-                ORDER BY created_at DESC
-                LIMIT $3
-                "#
-            )
-            .bind(user_address)
-            .bind(market)
-            .bind(limit as i64)
-        } else if let Some(stat) = &status_str {
-            sqlx::query(
-                r#"
-                SELECT id, user_address, market_id, price, size, side::TEXT as side, type::TEXT as type, status::TEXT as status, filled_size, created_at, updated_at
-                FROM orders
-                WHERE user_address = $1 AND status = $2::order_status
-                ORDER BY created_at DESC
-                LIMIT $3
-                "#
-            )
-            .bind(user_address)
-            .bind(stat)
-            .bind(limit as i64)
-        } else {
-            sqlx::query(
-                r#"
-                SELECT id, user_address, market_id, price, size, side::TEXT as side, type::TEXT as type, status::TEXT as status, filled_size, created_at, updated_at
-                FROM orders
-                WHERE user_address = $1
-                ORDER BY created_at DESC
-                LIMIT $2
-                "#
-            )
-            .bind(user_address)
-            .bind(limit as i64)
-        };
-
-        let rows = query.fetch_all(&self.postgres).await?;
-
-        let orders = rows
-            .iter()
-            .map(|row| {
-                let price: BigDecimal = row.get("price");
-                let size: BigDecimal = row.get("size");
-                let filled_size: BigDecimal = row.get("filled_size");
-                let side_str: String = row.get("side");
-                let type_str: String = row.get("type");
-                let status_str: String = row.get("status");
-
-                Order {
-                    id: row.get("id"),
-                    user_address: row.get("user_address"),
-                    market_id: row.get("market_id"),
-                    price: price.to_u128(),
-                    size: size.to_u128(),
-                    side: side_str.parse().unwrap_or(Side::Buy),
-                    order_type: type_str.parse().unwrap_or(OrderType::Limit),
-                    status: status_str.parse().unwrap_or(OrderStatus::Pending),
-                    filled_size: filled_size.to_u128(),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                }
-            })
-            .collect();
-
-        Ok(orders)
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id, user_address, market_id, price, size, side::TEXT AS "side!", type::TEXT AS "order_type!", status::TEXT AS "status!", filled_size, created_at, updated_at, cancel_reason, trigger_price, trigger_source, time_in_force, expires_at
+            FROM orders
+            WHERE user_address = $1
+              AND ($2::TEXT IS NULL OR market_id = $2)
+              AND ($3::TEXT IS NULL OR status = $3::TEXT::order_status)
+            ORDER BY created_at DESC
+            LIMIT $4
+            "#,
+            user_address,
+            market_id,
+            status_str,
+            limit as i64
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(Order::try_from)
+            .collect::<std::result::Result<_, _>>()?)
     }
 
     /// Get all recoverable orders for a specific market
     /// Returns orders sorted by created_at ASC to maintain price-time priority
     pub async fn get_recoverable_orders_for_market(&self, market_id: &str) -> Result<Vec<Order>> {
-        let rows = sqlx::query(
+        if let Some(memory) = &self.memory {
+            return memory.get_recoverable_orders_for_market(market_id);
+        }
+
+        let rows = sqlx::query_as!(
+            OrderRow,
             r#"
-            SELECT id, user_address, market_id, price, size, side::TEXT as side, type::TEXT as type, status::TEXT as status, filled_size, created_at, updated_at
+            SELECT id, user_address, market_id, price, size, side::TEXT AS "side!", type::TEXT AS "order_type!", status::TEXT AS "status!", filled_size, created_at, updated_at, cancel_reason, trigger_price, trigger_source, time_in_force, expires_at
             FROM orders
             WHERE market_id = $1
               AND status IN ('pending', 'partially_filled')
               AND type = 'limit'
             ORDER BY created_at ASC
             "#,
+            market_id
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(Order::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Get every stop order still waiting for its trigger price, oldest first
+    pub async fn get_untriggered_stop_orders(&self) -> Result<Vec<Order>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_untriggered_stop_orders();
+        }
+
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id, user_address, market_id, price, size, side::TEXT AS "side!", type::TEXT AS "order_type!", status::TEXT AS "status!", filled_size, created_at, updated_at, cancel_reason, trigger_price, trigger_source, time_in_force, expires_at
+            FROM orders
+            WHERE status = 'pending'
+              AND type IN ('stop_market', 'stop_limit')
+            ORDER BY created_at ASC
+            "#
         )
-        .bind(market_id)
         .fetch_all(&self.postgres)
         .await?;
 
-        let orders = rows
-            .iter()
-            .map(|row| {
-                let price: BigDecimal = row.get("price");
-                let size: BigDecimal = row.get("size");
-                let filled_size: BigDecimal = row.get("filled_size");
-                let side_str: String = row.get("side");
-                let type_str: String = row.get("type");
-                let status_str: String = row.get("status");
-
-                Order {
-                    id: row.get("id"),
-                    user_address: row.get("user_address"),
-                    market_id: row.get("market_id"),
-                    price: price.to_u128(),
-                    size: size.to_u128(),
-                    side: side_str.parse().unwrap_or(Side::Buy),
-                    order_type: type_str.parse().unwrap_or(OrderType::Limit),
-                    status: status_str.parse().unwrap_or(OrderStatus::Pending),
-                    filled_size: filled_size.to_u128(),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                }
-            })
-            .collect();
-
-        Ok(orders)
+        Ok(rows
+            .into_iter()
+            .map(Order::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Get every open order, stops included, that has an expiry
+    pub async fn get_expiring_orders(&self) -> Result<Vec<Order>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_expiring_orders();
+        }
+
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id, user_address, market_id, price, size, side::TEXT AS "side!", type::TEXT AS "order_type!", status::TEXT AS "status!", filled_size, created_at, updated_at, cancel_reason, trigger_price, trigger_source, time_in_force, expires_at
+            FROM orders
+            WHERE expires_at IS NOT NULL
+              AND status IN ('pending', 'partially_filled')
+            "#
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(Order::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Record that a stop order triggered, giving it the type it executes as
+    pub async fn trigger_order(&self, order_id: Uuid, order_type: OrderType) -> Result<()> {
+        if let Some(memory) = &self.memory {
+            return memory.trigger_order(order_id, order_type);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE orders
+            SET type = $1::order_type, updated_at = $2
+            WHERE id = $3
+            "#,
+        )
+        .bind(order_type.to_string())
+        .bind(Utc::now())
+        .bind(order_id)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,670 @@
+use crate::db::balances::BalanceChanges;
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{
+    LedgerEntryKind, LedgerPosting, MarginMode, Market, PerpetualMarket, Position, SystemAccount,
+};
+use crate::perps::{self, FundingSettlement};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use std::collections::HashMap;
+
+const PERPETUAL_MARKET_COLUMNS: &str = r#"
+    market_id, funding_interval_secs, max_funding_rate_ppm, initial_margin_bps,
+    maintenance_margin_bps, liquidation_fee_bps, last_funding_at,
+    mark_price::TEXT AS mark_price, index_price::TEXT AS index_price, mark_updated_at
+"#;
+
+const POSITION_COLUMNS: &str = r#"
+    user_address, market_id, size::TEXT AS size, entry_price::TEXT AS entry_price,
+    margin::TEXT AS margin, cost::TEXT AS cost, realized_pnl::TEXT AS realized_pnl,
+    funding_paid::TEXT AS funding_paid, updated_at
+"#;
+
+fn perpetual_market_from_row(row: &PgRow) -> PerpetualMarket {
+    let mark_price: Option<String> = row.get("mark_price");
+    let index_price: Option<String> = row.get("index_price");
+    PerpetualMarket {
+        market_id: row.get("market_id"),
+        funding_interval_secs: row.get::<i32, _>("funding_interval_secs") as u32,
+        max_funding_rate_ppm: row.get::<i32, _>("max_funding_rate_ppm") as u32,
+        initial_margin_bps: row.get::<i32, _>("initial_margin_bps") as u32,
+        maintenance_margin_bps: row.get::<i32, _>("maintenance_margin_bps") as u32,
+        liquidation_fee_bps: row.get::<i32, _>("liquidation_fee_bps") as u32,
+        last_funding_at: row.get("last_funding_at"),
+        mark_price: mark_price.and_then(|price| price.parse().ok()),
+        index_price: index_price.and_then(|price| price.parse().ok()),
+        mark_updated_at: row.get("mark_updated_at"),
+    }
+}
+
+fn position_from_row(row: &PgRow) -> Position {
+    let size: String = row.get("size");
+    let entry_price: String = row.get("entry_price");
+    let margin: String = row.get("margin");
+    let cost: String = row.get("cost");
+    let realized_pnl: String = row.get("realized_pnl");
+    let funding_paid: String = row.get("funding_paid");
+    Position {
+        user_address: row.get("user_address"),
+        market_id: row.get("market_id"),
+        size: size.parse().unwrap_or(0),
+        entry_price: entry_price.parse().unwrap_or(0),
+        margin: margin.parse().unwrap_or(0),
+        cost: cost.parse().unwrap_or(0),
+        realized_pnl: realized_pnl.parse().unwrap_or(0),
+        funding_paid: funding_paid.parse().unwrap_or(0),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Why the positions of a perpetual market are settled
+#[derive(Clone, Copy)]
+enum Settlement<'a> {
+    /// The funding interval ended
+    Funding { rate_ppm: i64 },
+    /// A position was liquidated, releasing its margin into the user's balance
+    Liquidation {
+        user_address: &'a str,
+        released_margin: u128,
+    },
+}
+
+impl Db {
+    /// Make a market perpetual, or update its funding and margin terms if it already is
+    pub async fn set_perpetual_market(
+        &self,
+        market_id: &str,
+        funding_interval_secs: u32,
+        max_funding_rate_ppm: u32,
+        initial_margin_bps: u32,
+        maintenance_margin_bps: u32,
+        liquidation_fee_bps: u32,
+    ) -> Result<PerpetualMarket> {
+        if funding_interval_secs == 0 {
+            return Err(ExchangeError::InvalidParameter {
+                message: "Funding interval must be greater than 0 seconds".to_string(),
+            });
+        }
+        if initial_margin_bps == 0 || initial_margin_bps > 10000 {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Initial margin must be between 1 and 10000 bps, got {}",
+                    initial_margin_bps
+                ),
+            });
+        }
+        if maintenance_margin_bps == 0 || maintenance_margin_bps > initial_margin_bps {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Maintenance margin must be between 1 bps and the initial margin, got {}",
+                    maintenance_margin_bps
+                ),
+            });
+        }
+        if liquidation_fee_bps > 10000 {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Liquidation fee must be at most 10000 bps, got {}",
+                    liquidation_fee_bps
+                ),
+            });
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO perpetual_markets (
+                market_id, funding_interval_secs, max_funding_rate_ppm, initial_margin_bps,
+                maintenance_margin_bps, liquidation_fee_bps
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (market_id) DO UPDATE
+            SET funding_interval_secs = EXCLUDED.funding_interval_secs,
+                max_funding_rate_ppm = EXCLUDED.max_funding_rate_ppm,
+                initial_margin_bps = EXCLUDED.initial_margin_bps,
+                maintenance_margin_bps = EXCLUDED.maintenance_margin_bps,
+                liquidation_fee_bps = EXCLUDED.liquidation_fee_bps
+            RETURNING {}
+            "#,
+            PERPETUAL_MARKET_COLUMNS
+        ))
+        .bind(market_id)
+        .bind(funding_interval_secs as i32)
+        .bind(max_funding_rate_ppm as i32)
+        .bind(initial_margin_bps as i32)
+        .bind(maintenance_margin_bps as i32)
+        .bind(liquidation_fee_bps as i32)
+        .fetch_one(&self.postgres)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ExchangeError::MarketNotFound {
+                    market_id: market_id.to_string(),
+                }
+            }
+            _ => ExchangeError::Database(e),
+        })?;
+
+        Ok(perpetual_market_from_row(&row))
+    }
+
+    /// Funding terms and latest prices of a market, `None` for spot markets
+    pub async fn get_perpetual_market(&self, market_id: &str) -> Result<Option<PerpetualMarket>> {
+        // Only spot markets are kept in memory
+        if self.memory.is_some() {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM perpetual_markets WHERE market_id = $1",
+            PERPETUAL_MARKET_COLUMNS
+        ))
+        .bind(market_id)
+        .fetch_optional(&self.postgres)
+        .await?;
+
+        Ok(row.as_ref().map(perpetual_market_from_row))
+    }
+
+    /// Every perpetual market
+    pub async fn list_perpetual_markets(&self) -> Result<Vec<PerpetualMarket>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM perpetual_markets ORDER BY market_id",
+            PERPETUAL_MARKET_COLUMNS
+        ))
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(perpetual_market_from_row).collect())
+    }
+
+    /// Record a perpetual market's latest mark and index prices
+    pub async fn set_mark_price(
+        &self,
+        market_id: &str,
+        mark_price: u128,
+        index_price: u128,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE perpetual_markets
+            SET mark_price = $2::numeric, index_price = $3::numeric, mark_updated_at = $4
+            WHERE market_id = $1
+            "#,
+        )
+        .bind(market_id)
+        .bind(mark_price.to_string())
+        .bind(index_price.to_string())
+        .bind(at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A user's positions in every perpetual market, including closed ones not yet settled
+    pub async fn list_positions_by_user(&self, user_address: &str) -> Result<Vec<Position>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM positions WHERE user_address = $1 ORDER BY market_id",
+            POSITION_COLUMNS
+        ))
+        .bind(user_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(position_from_row).collect())
+    }
+
+    /// Positions of `user_addresses` in one market, by user; users without one are left out
+    pub async fn get_positions(
+        &self,
+        market_id: &str,
+        user_addresses: &[&str],
+    ) -> Result<HashMap<String, Position>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM positions WHERE market_id = $1 AND user_address = ANY($2)",
+            POSITION_COLUMNS
+        ))
+        .bind(market_id)
+        .bind(user_addresses)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(position_from_row)
+            .map(|position| (position.user_address.clone(), position))
+            .collect())
+    }
+
+    /// Every position in one market, including closed ones not yet settled
+    pub async fn list_positions_by_market(&self, market_id: &str) -> Result<Vec<Position>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM positions WHERE market_id = $1 ORDER BY user_address",
+            POSITION_COLUMNS
+        ))
+        .bind(market_id)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(position_from_row).collect())
+    }
+
+    /// How a user's positions are margined
+    pub async fn get_margin_mode(&self, user_address: &str) -> Result<MarginMode> {
+        let mode: Option<String> =
+            sqlx::query_scalar("SELECT mode FROM margin_accounts WHERE user_address = $1")
+                .bind(user_address)
+                .fetch_optional(&self.postgres)
+                .await?;
+
+        Ok(mode.and_then(|mode| mode.parse().ok()).unwrap_or_default())
+    }
+
+    /// Users margining their positions across markets
+    pub async fn list_cross_margin_users(&self) -> Result<Vec<String>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let users = sqlx::query_scalar(
+            "SELECT user_address FROM margin_accounts WHERE mode = 'cross' ORDER BY user_address",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(users)
+    }
+
+    /// Set how a user's positions are margined
+    pub async fn set_margin_mode(&self, user_address: &str, mode: MarginMode) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO margin_accounts (user_address, mode, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (user_address) DO UPDATE
+            SET mode = EXCLUDED.mode, updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(user_address)
+        .bind(mode.to_string())
+        .execute(&self.postgres)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                ExchangeError::UserNotFound {
+                    address: user_address.to_string(),
+                }
+            }
+            _ => ExchangeError::Database(e),
+        })?;
+
+        Ok(())
+    }
+
+    /// Charge a liquidated user up to `fee` of their free quote balance
+    ///
+    /// `split` divides what was charged between the fee collector and the
+    /// insurance fund; both postings go to the ledger. Returns the amount charged.
+    pub async fn charge_liquidation_fee(
+        &self,
+        user_address: &str,
+        token_ticker: &str,
+        fee: u128,
+        split: impl FnOnce(u128) -> (u128, u128),
+    ) -> Result<u128> {
+        let mut tx = self.begin_transaction().await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT amount::TEXT AS amount, open_interest::TEXT AS open_interest
+            FROM balances
+            WHERE user_address = $1 AND token_ticker = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_address)
+        .bind(token_ticker)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let free = row.map_or(0, |row| {
+            let amount: String = row.get("amount");
+            let open_interest: String = row.get("open_interest");
+            let amount: u128 = amount.parse().unwrap_or(0);
+            amount.saturating_sub(open_interest.parse().unwrap_or(0))
+        });
+        let charged = fee.min(free);
+        if charged == 0 {
+            return Ok(0);
+        }
+
+        let mut changes = BalanceChanges::new();
+        changes
+            .entry((user_address.to_string(), token_ticker.to_string()))
+            .or_default()
+            .debit = charged;
+        let (collector, insurance) = split(charged);
+        let mut ledger = Vec::new();
+        for (account, amount) in [
+            (SystemAccount::FeeCollector, collector),
+            (SystemAccount::InsuranceFund, insurance),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            changes
+                .entry((account.address().to_string(), token_ticker.to_string()))
+                .or_default()
+                .credit = amount;
+            ledger.push(LedgerPosting {
+                account,
+                token_ticker: token_ticker.to_string(),
+                amount: amount as i128,
+                kind: LedgerEntryKind::Liquidation,
+                trade_id: None,
+            });
+        }
+        self.apply_balance_changes_tx(&mut tx, &changes).await?;
+        self.create_ledger_entries_tx(&mut tx, &ledger).await?;
+        tx.commit().await?;
+
+        Ok(charged)
+    }
+
+    /// Write the size, entry price, margin and cost of positions changed by fills (within a transaction)
+    pub async fn upsert_positions_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        positions: &[Position],
+    ) -> Result<()> {
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let users: Vec<&str> = positions.iter().map(|p| p.user_address.as_str()).collect();
+        let markets: Vec<&str> = positions.iter().map(|p| p.market_id.as_str()).collect();
+        let sizes: Vec<String> = positions.iter().map(|p| p.size.to_string()).collect();
+        let entry_prices: Vec<String> = positions
+            .iter()
+            .map(|p| p.entry_price.to_string())
+            .collect();
+        let margins: Vec<String> = positions.iter().map(|p| p.margin.to_string()).collect();
+        let costs: Vec<String> = positions.iter().map(|p| p.cost.to_string()).collect();
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO positions (user_address, market_id, size, entry_price, margin, cost, updated_at)
+            SELECT u, m, s, e, mg, c, $7
+            FROM UNNEST($1::text[], $2::text[], $3::numeric[], $4::numeric[], $5::numeric[], $6::numeric[])
+                AS p(u, m, s, e, mg, c)
+            ON CONFLICT (user_address, market_id) DO UPDATE
+            SET size = EXCLUDED.size,
+                entry_price = EXCLUDED.entry_price,
+                margin = EXCLUDED.margin,
+                cost = EXCLUDED.cost,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(&users)
+        .bind(&markets)
+        .bind(&sizes)
+        .bind(&entry_prices)
+        .bind(&margins)
+        .bind(&costs)
+        .bind(Utc::now())
+        .execute(&mut **tx);
+        self.timed("upsert_positions", query).await?;
+
+        Ok(())
+    }
+
+    /// Mark every position of a perpetual market to `mark_price` and charge it funding
+    ///
+    /// Runs in one transaction holding the market's positions and their quote
+    /// balances, so PnL and funding move between users all at once. The
+    /// insurance fund absorbs rounding dust and covers shortfalls, each posted
+    /// to its ledger; closed positions are removed once settled.
+    pub async fn settle_funding(
+        &self,
+        market: &Market,
+        scale: u128,
+        mark_price: u128,
+        index_price: u128,
+        funding_rate_ppm: i64,
+    ) -> Result<(FundingSettlement, Vec<String>)> {
+        self.settle_positions(
+            market,
+            scale,
+            mark_price,
+            index_price,
+            Settlement::Funding {
+                rate_ppm: funding_rate_ppm,
+            },
+        )
+        .await
+    }
+
+    /// Settle the PnL of every position of a perpetual market at `mark_price`
+    /// after `user_address` was liquidated, without charging funding or
+    /// ending the funding interval
+    ///
+    /// The closed position's loss is collected from `released_margin`, the
+    /// margin its close just returned to the user's balance, even if they are
+    /// isolated; cross-margined users pay from their whole free balance as usual.
+    pub async fn mark_to_market(
+        &self,
+        market: &Market,
+        scale: u128,
+        mark_price: u128,
+        index_price: u128,
+        user_address: &str,
+        released_margin: u128,
+    ) -> Result<(FundingSettlement, Vec<String>)> {
+        self.settle_positions(
+            market,
+            scale,
+            mark_price,
+            index_price,
+            Settlement::Liquidation {
+                user_address,
+                released_margin,
+            },
+        )
+        .await
+    }
+
+    async fn settle_positions(
+        &self,
+        market: &Market,
+        scale: u128,
+        mark_price: u128,
+        index_price: u128,
+        settlement: Settlement<'_>,
+    ) -> Result<(FundingSettlement, Vec<String>)> {
+        let funding_rate_ppm = match settlement {
+            Settlement::Funding { rate_ppm } => rate_ppm,
+            Settlement::Liquidation { .. } => 0,
+        };
+        let quote = &market.quote_ticker;
+        let mut tx = self.begin_transaction().await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM positions WHERE market_id = $1 ORDER BY user_address FOR UPDATE",
+            POSITION_COLUMNS
+        ))
+        .bind(&market.id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let positions: Vec<Position> = rows.iter().map(position_from_row).collect();
+
+        // Free quote balances of the position holders, and the insurance fund's;
+        // only cross-margined holders pay losses from theirs
+        let mut holders: Vec<&str> = positions.iter().map(|p| p.user_address.as_str()).collect();
+        holders.push(SystemAccount::InsuranceFund.address());
+        let rows = sqlx::query(
+            r#"
+            SELECT b.user_address, b.amount::TEXT AS amount,
+                   b.open_interest::TEXT AS open_interest, m.mode
+            FROM balances b
+            LEFT JOIN margin_accounts m ON m.user_address = b.user_address
+            WHERE b.token_ticker = $1 AND b.user_address = ANY($2)
+            ORDER BY b.user_address
+            FOR UPDATE OF b
+            "#,
+        )
+        .bind(quote)
+        .bind(&holders)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut free = HashMap::new();
+        let mut insurance = 0;
+        for row in &rows {
+            let user_address: String = row.get("user_address");
+            let amount: String = row.get("amount");
+            let open_interest: String = row.get("open_interest");
+            let amount: u128 = amount.parse().unwrap_or(0);
+            let mode: Option<String> = row.get("mode");
+            if user_address == SystemAccount::InsuranceFund.address() {
+                insurance = amount;
+            } else {
+                let locked: u128 = open_interest.parse().unwrap_or(0);
+                let available = amount.saturating_sub(locked);
+                if mode.as_deref() == Some("cross") {
+                    free.insert(user_address, available);
+                } else if let Settlement::Liquidation {
+                    user_address: liquidated,
+                    released_margin,
+                } = settlement
+                {
+                    if user_address == liquidated {
+                        free.insert(user_address, available.min(released_margin));
+                    }
+                }
+            }
+        }
+
+        let mut plan = perps::plan_settlement(
+            positions,
+            &free,
+            insurance,
+            quote,
+            mark_price,
+            funding_rate_ppm,
+            scale,
+        )
+        .ok_or(ExchangeError::OrderValueOverflow)?;
+
+        let ledger = if plan.insurance != 0 {
+            let account = SystemAccount::InsuranceFund;
+            let change = plan
+                .balance_changes
+                .entry((account.address().to_string(), quote.clone()))
+                .or_default();
+            if plan.insurance > 0 {
+                change.credit += plan.insurance.unsigned_abs();
+            } else {
+                change.debit += plan.insurance.unsigned_abs();
+            }
+            vec![LedgerPosting {
+                account,
+                token_ticker: quote.clone(),
+                amount: plan.insurance,
+                kind: LedgerEntryKind::Funding,
+                trade_id: None,
+            }]
+        } else {
+            Vec::new()
+        };
+        self.apply_balance_changes_tx(&mut tx, &plan.balance_changes)
+            .await?;
+        self.create_ledger_entries_tx(&mut tx, &ledger).await?;
+
+        let users: Vec<&str> = plan
+            .positions
+            .iter()
+            .map(|p| p.user_address.as_str())
+            .collect();
+        let margins: Vec<String> = plan
+            .positions
+            .iter()
+            .map(|p| p.margin.to_string())
+            .collect();
+        let costs: Vec<String> = plan.positions.iter().map(|p| p.cost.to_string()).collect();
+        let realized: Vec<String> = plan
+            .positions
+            .iter()
+            .map(|p| p.realized_pnl.to_string())
+            .collect();
+        let funding: Vec<String> = plan
+            .positions
+            .iter()
+            .map(|p| p.funding_paid.to_string())
+            .collect();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            UPDATE positions p
+            SET margin = c.margin, cost = c.cost, realized_pnl = c.realized_pnl,
+                funding_paid = c.funding_paid, updated_at = $7
+            FROM UNNEST($2::text[], $3::numeric[], $4::numeric[], $5::numeric[], $6::numeric[])
+                AS c(user_address, margin, cost, realized_pnl, funding_paid)
+            WHERE p.market_id = $1 AND p.user_address = c.user_address
+            "#,
+        )
+        .bind(&market.id)
+        .bind(&users)
+        .bind(&margins)
+        .bind(&costs)
+        .bind(&realized)
+        .bind(&funding)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM positions WHERE market_id = $1 AND size = 0")
+            .bind(&market.id)
+            .execute(&mut *tx)
+            .await?;
+        if let Settlement::Funding { .. } = settlement {
+            sqlx::query(
+                r#"
+                UPDATE perpetual_markets
+                SET last_funding_at = $2, mark_price = $3::numeric, index_price = $4::numeric,
+                    mark_updated_at = $2
+                WHERE market_id = $1
+                "#,
+            )
+            .bind(&market.id)
+            .bind(now)
+            .bind(mark_price.to_string())
+            .bind(index_price.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        let affected = plan
+            .balance_changes
+            .keys()
+            .map(|(user_address, _)| user_address.clone())
+            .collect();
+        let settlement = FundingSettlement {
+            market_id: market.id.clone(),
+            mark_price,
+            index_price,
+            funding_rate_ppm,
+            positions: plan.positions.len(),
+            open_interest: perps::open_interest(&plan.positions),
+            insurance: plan.insurance,
+            shortfall: plan.shortfall,
+            haircut: plan.haircut,
+        };
+        Ok((settlement, affected))
+    }
+}
@@ -0,0 +1,10 @@
+-- Per-user, per-market risk limits enforced by the matching engine at placement time
+-- NULL means the limit is not set (unlimited)
+CREATE TABLE IF NOT EXISTS user_limits (
+    user_address TEXT NOT NULL REFERENCES users(address),
+    market_id TEXT NOT NULL REFERENCES markets(id),
+    max_position NUMERIC(39, 0) CHECK (max_position >= 0), -- absolute net position in base token atoms (u128)
+    max_open_notional NUMERIC(39, 0) CHECK (max_open_notional >= 0), -- resting order value in quote token atoms (u128)
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_address, market_id)
+);
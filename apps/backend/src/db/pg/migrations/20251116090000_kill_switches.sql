@@ -0,0 +1,7 @@
+-- Engaged kill switches; a row puts its scope into cancel-only mode until deleted
+-- scope is a market id, or '*' for the whole exchange
+CREATE TABLE IF NOT EXISTS kill_switches (
+    scope TEXT PRIMARY KEY,
+    reason TEXT,
+    engaged_at TIMESTAMPTZ NOT NULL
+);
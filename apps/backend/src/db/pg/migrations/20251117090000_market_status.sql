@@ -0,0 +1,4 @@
+-- Trading status of a market: halted and delisted markets reject new orders
+ALTER TABLE markets
+    ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'active'
+    CHECK (status IN ('active', 'halted', 'delisted'));
@@ -0,0 +1,17 @@
+-- Negative maker fees are rebates paid to makers by the fee collector.
+-- The taker fee must always cover the rebate so the exchange never pays out net.
+ALTER TABLE markets DROP CONSTRAINT IF EXISTS markets_maker_fee_bps_check;
+ALTER TABLE markets
+    ADD CONSTRAINT markets_maker_fee_bps_check
+    CHECK (maker_fee_bps >= -10000 AND maker_fee_bps <= 10000 AND maker_fee_bps + taker_fee_bps >= 0);
+
+-- Fees settled per trade participant; negative amounts are rebates
+CREATE TABLE IF NOT EXISTS trade_fees (
+    trade_id UUID NOT NULL REFERENCES trades(id),
+    user_address TEXT NOT NULL REFERENCES users(address),
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    fee NUMERIC(40, 0) NOT NULL, -- in token atoms (i128)
+    PRIMARY KEY (trade_id, user_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trade_fees_user ON trade_fees(user_address);
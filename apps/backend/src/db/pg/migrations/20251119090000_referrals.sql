@@ -0,0 +1,21 @@
+-- Each user has at most one referrer, who earns a share of the user's taker fees
+CREATE TABLE IF NOT EXISTS referrals (
+    user_address TEXT PRIMARY KEY REFERENCES users(address),
+    referrer_address TEXT NOT NULL REFERENCES users(address),
+    share_bps INT NOT NULL CHECK (share_bps >= 0 AND share_bps <= 10000), -- basis points of the taker fee
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (user_address != referrer_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_referrals_referrer ON referrals(referrer_address);
+
+-- Taker fee shares paid to referrers during settlement
+CREATE TABLE IF NOT EXISTS referral_payouts (
+    trade_id UUID PRIMARY KEY REFERENCES trades(id),
+    referrer_address TEXT NOT NULL REFERENCES users(address),
+    user_address TEXT NOT NULL REFERENCES users(address),
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    amount NUMERIC(39, 0) NOT NULL CHECK (amount > 0) -- in token atoms (u128)
+);
+
+CREATE INDEX IF NOT EXISTS idx_referral_payouts_referrer ON referral_payouts(referrer_address);
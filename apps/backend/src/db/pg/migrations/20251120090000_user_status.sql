@@ -0,0 +1,4 @@
+-- Account status of a user: frozen and banned users can cancel but not place orders
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'active'
+    CHECK (status IN ('active', 'frozen', 'banned'));
@@ -0,0 +1,6 @@
+-- Admin overrides of the price collars in config.toml; a NULL collar disables it
+CREATE TABLE IF NOT EXISTS price_collars (
+    market_id TEXT PRIMARY KEY REFERENCES markets(id),
+    collar_bps INT CHECK (collar_bps > 0), -- basis points from the last trade price
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
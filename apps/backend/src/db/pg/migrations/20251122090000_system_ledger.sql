@@ -0,0 +1,33 @@
+-- System accounts are the balances of reserved users:
+-- 'system' is the fee collector, 'insurance' the insurance fund
+INSERT INTO users (address) VALUES ('insurance') ON CONFLICT (address) DO NOTHING;
+
+-- Share of each revenue source paid into the insurance fund; the rest goes to the fee collector
+CREATE TABLE IF NOT EXISTS fee_routes (
+    source TEXT PRIMARY KEY CHECK (source IN ('trading_fees', 'liquidations')),
+    insurance_bps INT NOT NULL CHECK (insurance_bps >= 0 AND insurance_bps <= 10000),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO fee_routes (source, insurance_bps)
+VALUES ('trading_fees', 0), ('liquidations', 10000)
+ON CONFLICT (source) DO NOTHING;
+
+-- Every change to a system account balance, so each one sums to its ledger
+CREATE TABLE IF NOT EXISTS system_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    account TEXT NOT NULL REFERENCES users(address),
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    amount NUMERIC(40, 0) NOT NULL, -- in token atoms (i128), negative for payouts
+    kind TEXT NOT NULL CHECK (kind IN ('opening_balance', 'trading_fee', 'maker_rebate', 'referral_payout', 'liquidation')),
+    trade_id UUID REFERENCES trades(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_system_ledger_account ON system_ledger(account, token_ticker, id);
+
+-- Open the ledger with whatever the system accounts already hold
+INSERT INTO system_ledger (account, token_ticker, amount, kind)
+SELECT user_address, token_ticker, amount, 'opening_balance'
+FROM balances
+WHERE user_address IN ('system', 'insurance') AND amount <> 0;
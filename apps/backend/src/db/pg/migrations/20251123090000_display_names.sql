@@ -0,0 +1,4 @@
+-- Opt-in public name shown on the leaderboard, unique regardless of case
+ALTER TABLE users ADD COLUMN IF NOT EXISTS display_name TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_display_name ON users (LOWER(display_name));
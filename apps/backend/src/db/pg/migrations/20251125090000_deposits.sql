@@ -0,0 +1,22 @@
+-- On-chain deposits credited to user balances; the unique key makes crediting idempotent
+CREATE TABLE IF NOT EXISTS deposits (
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    user_address TEXT NOT NULL REFERENCES users(address),
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    amount NUMERIC NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (chain_id, tx_hash, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deposits_user ON deposits (user_address, created_at DESC);
+
+-- Last block of each chain whose deposits have all been credited
+CREATE TABLE IF NOT EXISTS deposit_cursors (
+    chain_id BIGINT PRIMARY KEY,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,19 @@
+-- Withdrawals to on-chain addresses; the amount stays locked in the balance until
+-- the transfer is confirmed (and debited) or fails (and is released)
+CREATE TABLE IF NOT EXISTS withdrawals (
+    id UUID PRIMARY KEY,
+    user_address TEXT NOT NULL REFERENCES users(address),
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    amount NUMERIC NOT NULL CHECK (amount > 0),
+    destination TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'submitted', 'confirmed', 'failed', 'cancelled')),
+    tx_hash TEXT,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_withdrawals_user ON withdrawals (user_address, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_withdrawals_open ON withdrawals (status, created_at)
+    WHERE status IN ('pending', 'processing', 'submitted');
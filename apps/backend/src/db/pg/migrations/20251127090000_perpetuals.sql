@@ -0,0 +1,31 @@
+-- Markets trading perpetual futures: positions instead of base token transfers,
+-- margined in the quote token and settled against the mark price every funding interval
+CREATE TABLE IF NOT EXISTS perpetual_markets (
+    market_id TEXT PRIMARY KEY REFERENCES markets(id),
+    funding_interval_secs INT NOT NULL CHECK (funding_interval_secs > 0),
+    max_funding_rate_ppm INT NOT NULL CHECK (max_funding_rate_ppm >= 0), -- per interval, millionths of notional
+    last_funding_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    mark_price NUMERIC(39, 0), -- in quote token atoms (u128), NULL until first marked
+    index_price NUMERIC(39, 0),
+    mark_updated_at TIMESTAMPTZ
+);
+
+-- One open position per user and perpetual market
+CREATE TABLE IF NOT EXISTS positions (
+    user_address TEXT NOT NULL REFERENCES users(address),
+    market_id TEXT NOT NULL REFERENCES perpetual_markets(market_id),
+    size NUMERIC(40, 0) NOT NULL, -- in base token atoms (i128), negative for shorts
+    entry_price NUMERIC(39, 0) NOT NULL, -- average price the position was opened at
+    margin NUMERIC(39, 0) NOT NULL CHECK (margin >= 0), -- quote atoms locked for the position
+    cost NUMERIC(40, 0) NOT NULL, -- sum of price * signed size not yet settled, unscaled (i128)
+    realized_pnl NUMERIC(40, 0) NOT NULL DEFAULT 0, -- quote atoms settled so far (i128)
+    funding_paid NUMERIC(40, 0) NOT NULL DEFAULT 0, -- quote atoms, negative when received (i128)
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_address, market_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_positions_market ON positions(market_id);
+
+ALTER TABLE system_ledger DROP CONSTRAINT IF EXISTS system_ledger_kind_check;
+ALTER TABLE system_ledger ADD CONSTRAINT system_ledger_kind_check
+    CHECK (kind IN ('opening_balance', 'trading_fee', 'maker_rebate', 'referral_payout', 'liquidation', 'funding'));
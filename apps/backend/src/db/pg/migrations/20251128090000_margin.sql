@@ -0,0 +1,20 @@
+-- Leveraged perpetuals: orders lock the initial margin of their notional, and
+-- positions whose equity falls below the maintenance margin are liquidated
+ALTER TABLE perpetual_markets
+    ADD COLUMN IF NOT EXISTS initial_margin_bps INT NOT NULL DEFAULT 10000
+        CHECK (initial_margin_bps > 0 AND initial_margin_bps <= 10000),
+    ADD COLUMN IF NOT EXISTS maintenance_margin_bps INT NOT NULL DEFAULT 500
+        CHECK (maintenance_margin_bps > 0),
+    ADD COLUMN IF NOT EXISTS liquidation_fee_bps INT NOT NULL DEFAULT 0
+        CHECK (liquidation_fee_bps >= 0 AND liquidation_fee_bps <= 10000); -- of the closed notional
+
+ALTER TABLE perpetual_markets DROP CONSTRAINT IF EXISTS perpetual_markets_margin_check;
+ALTER TABLE perpetual_markets ADD CONSTRAINT perpetual_markets_margin_check
+    CHECK (maintenance_margin_bps <= initial_margin_bps);
+
+-- Users margining their positions across markets; everyone else is isolated
+CREATE TABLE IF NOT EXISTS margin_accounts (
+    user_address TEXT PRIMARY KEY REFERENCES users(address),
+    mode TEXT NOT NULL CHECK (mode IN ('isolated', 'cross')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
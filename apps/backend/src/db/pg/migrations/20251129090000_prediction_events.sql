@@ -0,0 +1,22 @@
+-- Prediction events: one question whose mutually exclusive outcomes each trade
+-- as a market in the same quote token. On resolution the winning outcome's
+-- token pays one quote token per whole token and every outcome token is burned
+CREATE TABLE IF NOT EXISTS prediction_events (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    quote_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved')),
+    winning_outcome TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CHECK ((status = 'resolved') = (winning_outcome IS NOT NULL))
+);
+
+-- A market belongs to at most one event
+CREATE TABLE IF NOT EXISTS event_outcomes (
+    event_id TEXT NOT NULL REFERENCES prediction_events(id),
+    outcome TEXT NOT NULL,
+    market_id TEXT NOT NULL UNIQUE REFERENCES markets(id),
+    position INT NOT NULL, -- display order, as listed when the event was created
+    PRIMARY KEY (event_id, outcome)
+);
@@ -0,0 +1,35 @@
+-- Request for quote: a taker asks registered makers for a firm price on a
+-- large order and executes against the quote they pick, off the book
+CREATE TABLE IF NOT EXISTS rfq_makers (
+    user_address TEXT PRIMARY KEY REFERENCES users(address),
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS rfq_requests (
+    id UUID PRIMARY KEY,
+    taker_address TEXT NOT NULL REFERENCES users(address),
+    market_id TEXT NOT NULL REFERENCES markets(id),
+    side side NOT NULL, -- the taker's side
+    size NUMERIC(39, 0) NOT NULL CHECK (size > 0), -- in base token atoms (u128)
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'filled', 'cancelled')),
+    trade_id UUID REFERENCES trades(id),
+    expires_at TIMESTAMPTZ NOT NULL, -- quotes are only taken and accepted until then
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((status = 'filled') = (trade_id IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_rfq_requests_open ON rfq_requests(expires_at) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_rfq_requests_taker ON rfq_requests(taker_address, created_at DESC);
+
+-- One live quote per maker and request; quoting again replaces it
+CREATE TABLE IF NOT EXISTS rfq_quotes (
+    id UUID PRIMARY KEY,
+    request_id UUID NOT NULL REFERENCES rfq_requests(id),
+    maker_address TEXT NOT NULL REFERENCES users(address),
+    price NUMERIC(39, 0) NOT NULL CHECK (price > 0), -- in quote token atoms (u128)
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (request_id, maker_address)
+);
+
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS rfq BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Why the exchange cancelled an order (kill_switch, market_halted,
+-- account_restricted, liquidation); NULL while open or if the user cancelled it
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS cancel_reason TEXT;
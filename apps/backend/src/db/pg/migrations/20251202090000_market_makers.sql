@@ -0,0 +1,5 @@
+-- Market makers approved to read the per-order (L3) book
+CREATE TABLE IF NOT EXISTS market_makers (
+    user_address TEXT PRIMARY KEY REFERENCES users(address),
+    approved_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,108 @@
+-- Partition orders by month on created_at, so order history can grow without
+-- slowing the open-order queries the engine relies on. Monthly partitions are
+-- created ahead of time by ensure_order_partitions(), which the backend calls
+-- daily; orders_default catches anything outside them.
+
+-- A foreign key into a partitioned table must include the partition key, which
+-- trades don't carry; every order still gets a fresh UUID
+ALTER TABLE trades DROP CONSTRAINT IF EXISTS trades_buyer_order_id_fkey;
+ALTER TABLE trades DROP CONSTRAINT IF EXISTS trades_seller_order_id_fkey;
+
+ALTER TABLE orders RENAME TO orders_unpartitioned;
+ALTER INDEX orders_pkey RENAME TO orders_unpartitioned_pkey;
+
+CREATE TABLE orders (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+    user_address TEXT NOT NULL REFERENCES users(address),
+    market_id TEXT NOT NULL REFERENCES markets(id),
+    price NUMERIC(39, 0) NOT NULL CHECK (price > 0), -- in quote token atoms (u128)
+    size NUMERIC(39, 0) NOT NULL CHECK (size > 0), -- in base token atoms (u128)
+    side side NOT NULL,
+    type order_type NOT NULL,
+    status order_status NOT NULL,
+    filled_size NUMERIC(39, 0) NOT NULL DEFAULT 0 CHECK (filled_size >= 0 AND filled_size <= size), -- in base token atoms (u128)
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    cancel_reason TEXT,
+    PRIMARY KEY (id, created_at)
+) PARTITION BY RANGE (created_at);
+
+CREATE TABLE IF NOT EXISTS orders_default PARTITION OF orders DEFAULT;
+
+-- Create the monthly partitions from the month of `since` through `months_ahead`
+-- months past the current one; returns how many were missing. Orders already
+-- in the default partition for a new month are moved into it.
+CREATE OR REPLACE FUNCTION ensure_order_partitions(since TIMESTAMPTZ, months_ahead INT)
+RETURNS INT AS $$
+DECLARE
+    month_start DATE := date_trunc('month', since)::DATE;
+    month_end DATE;
+    last_month DATE := (date_trunc('month', now()) + make_interval(months => months_ahead))::DATE;
+    partition_name TEXT;
+    created INT := 0;
+BEGIN
+    WHILE month_start <= last_month LOOP
+        month_end := (month_start + INTERVAL '1 month')::DATE;
+        partition_name := 'orders_' || to_char(month_start, 'YYYY_MM');
+        IF to_regclass(partition_name) IS NULL THEN
+            IF EXISTS (
+                SELECT 1 FROM orders_default
+                WHERE created_at >= month_start AND created_at < month_end
+            ) THEN
+                EXECUTE format(
+                    'CREATE TABLE %I (LIKE orders INCLUDING DEFAULTS INCLUDING CONSTRAINTS)',
+                    partition_name
+                );
+                EXECUTE format(
+                    'WITH moved AS (DELETE FROM orders_default WHERE created_at >= %L AND created_at < %L RETURNING *)
+                     INSERT INTO %I SELECT * FROM moved',
+                    month_start,
+                    month_end,
+                    partition_name
+                );
+                EXECUTE format(
+                    'ALTER TABLE orders ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+                    partition_name,
+                    month_start,
+                    month_end
+                );
+            ELSE
+                EXECUTE format(
+                    'CREATE TABLE %I PARTITION OF orders FOR VALUES FROM (%L) TO (%L)',
+                    partition_name,
+                    month_start,
+                    month_end
+                );
+            END IF;
+            created := created + 1;
+        END IF;
+        month_start := month_end;
+    END LOOP;
+    RETURN created;
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT ensure_order_partitions(
+    COALESCE((SELECT MIN(created_at) FROM orders_unpartitioned), now()),
+    3
+);
+
+INSERT INTO orders (
+    id, user_address, market_id, price, size, side, type, status, filled_size,
+    created_at, updated_at, cancel_reason
+)
+SELECT id, user_address, market_id, price, size, side, type, status, filled_size,
+       created_at, updated_at, cancel_reason
+FROM orders_unpartitioned;
+
+DROP TABLE orders_unpartitioned;
+
+CREATE INDEX IF NOT EXISTS idx_orders_user_status ON orders(user_address, status);
+CREATE INDEX IF NOT EXISTS idx_orders_market_side_price ON orders(market_id, side, price);
+CREATE INDEX IF NOT EXISTS idx_orders_market_status ON orders(market_id, status);
+CREATE INDEX IF NOT EXISTS idx_orders_created_at ON orders(created_at);
+
+-- Resting orders are a sliver of each month; the engine's recovery, open-order
+-- caps and notional limits only ever read these
+CREATE INDEX IF NOT EXISTS idx_orders_open ON orders(market_id, user_address, created_at)
+    WHERE status IN ('pending', 'partially_filled') AND type = 'limit';
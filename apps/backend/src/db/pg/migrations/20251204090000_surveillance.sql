@@ -0,0 +1,33 @@
+-- Who ultimately owns an account, where an operator knows; trades between
+-- accounts of one owner are surveilled as wash trades
+CREATE TABLE IF NOT EXISTS beneficial_owners (
+    user_address TEXT PRIMARY KEY REFERENCES users(address),
+    owner TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Windows the surveillance job has analysed, so none is analysed twice
+CREATE TABLE IF NOT EXISTS surveillance_runs (
+    window_end TIMESTAMPTZ PRIMARY KEY,
+    window_start TIMESTAMPTZ NOT NULL,
+    alerts INT NOT NULL,
+    ran_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Suspected market abuse, one row per kind, market and subject per window
+CREATE TABLE IF NOT EXISTS surveillance_alerts (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('wash_trading', 'spoofing', 'self_matching')),
+    market_id TEXT NOT NULL REFERENCES markets(id),
+    subject TEXT NOT NULL,
+    accounts TEXT[] NOT NULL,
+    count BIGINT NOT NULL,
+    volume NUMERIC(39, 0) NOT NULL, -- in base token atoms (u128)
+    detail TEXT NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    window_end TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, market_id, subject, window_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_surveillance_alerts_market ON surveillance_alerts(market_id, id);
@@ -0,0 +1,10 @@
+-- Fee rates a user pays in a market instead of the market's own; NULL pays the market's fee
+CREATE TABLE IF NOT EXISTS fee_overrides (
+    user_address TEXT NOT NULL REFERENCES users(address),
+    market_id TEXT NOT NULL REFERENCES markets(id),
+    maker_fee_bps INT CHECK (maker_fee_bps >= -10000 AND maker_fee_bps <= 10000), -- negative is a rebate
+    taker_fee_bps INT CHECK (taker_fee_bps >= 0 AND taker_fee_bps <= 10000),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_address, market_id),
+    CHECK (maker_fee_bps IS NOT NULL OR taker_fee_bps IS NOT NULL)
+);
@@ -0,0 +1,42 @@
+-- Months the statement job has closed, so none is closed twice
+CREATE TABLE IF NOT EXISTS statement_runs (
+    period_start TIMESTAMPTZ PRIMARY KEY,
+    period_end TIMESTAMPTZ NOT NULL,
+    balances_at TIMESTAMPTZ NOT NULL, -- when closing balances were taken
+    statements INT NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One monthly account statement per user who held or moved anything that month
+CREATE TABLE IF NOT EXISTS statements (
+    user_address TEXT NOT NULL REFERENCES users(address),
+    period_start TIMESTAMPTZ NOT NULL REFERENCES statement_runs(period_start),
+    PRIMARY KEY (user_address, period_start)
+);
+
+-- Each token on a statement, in token atoms
+CREATE TABLE IF NOT EXISTS statement_balances (
+    user_address TEXT NOT NULL,
+    period_start TIMESTAMPTZ NOT NULL,
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    opening_balance NUMERIC(39, 0), -- NULL when the month before has no statements
+    closing_balance NUMERIC(39, 0) NOT NULL,
+    deposited NUMERIC(39, 0) NOT NULL,
+    withdrawn NUMERIC(39, 0) NOT NULL,
+    fees NUMERIC(40, 0) NOT NULL, -- fees less maker rebates (i128)
+    PRIMARY KEY (user_address, period_start, token_ticker),
+    FOREIGN KEY (user_address, period_start) REFERENCES statements(user_address, period_start)
+);
+
+-- Each market a statement's user traded in
+CREATE TABLE IF NOT EXISTS statement_markets (
+    user_address TEXT NOT NULL,
+    period_start TIMESTAMPTZ NOT NULL,
+    market_id TEXT NOT NULL REFERENCES markets(id),
+    fills BIGINT NOT NULL,
+    bought NUMERIC(39, 0) NOT NULL, -- in base token atoms (u128)
+    sold NUMERIC(39, 0) NOT NULL,
+    notional NUMERIC(39, 0) NOT NULL, -- in quote token atoms (u128)
+    PRIMARY KEY (user_address, period_start, market_id),
+    FOREIGN KEY (user_address, period_start) REFERENCES statements(user_address, period_start)
+);
@@ -0,0 +1,21 @@
+-- Named accounts of a master user; each is also a user row holding its own balances and orders
+CREATE TABLE IF NOT EXISTS sub_accounts (
+    address TEXT PRIMARY KEY REFERENCES users(address), -- master_address || ':' || name
+    master_address TEXT NOT NULL REFERENCES users(address),
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (master_address, name)
+);
+
+-- Free balance masters moved between their own accounts
+CREATE TABLE IF NOT EXISTS account_transfers (
+    id UUID PRIMARY KEY,
+    master_address TEXT NOT NULL REFERENCES users(address),
+    from_address TEXT NOT NULL REFERENCES users(address),
+    to_address TEXT NOT NULL REFERENCES users(address),
+    token_ticker TEXT NOT NULL REFERENCES tokens(ticker),
+    amount NUMERIC(39, 0) NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_transfers_master ON account_transfers(master_address, created_at DESC);
@@ -0,0 +1,16 @@
+-- Keys a user's requests can authenticate with; only a SHA-256 hash of each key is kept
+CREATE TABLE IF NOT EXISTS api_keys (
+    id UUID PRIMARY KEY,
+    user_address TEXT NOT NULL REFERENCES users(address),
+    label TEXT NOT NULL,
+    key_prefix TEXT NOT NULL, -- first characters of the key, to tell keys apart
+    key_hash TEXT NOT NULL UNIQUE, -- hex SHA-256 of the key
+    scopes TEXT[] NOT NULL,
+    ip_allowlist TEXT[] NOT NULL DEFAULT '{}', -- empty allows any address
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user ON api_keys (user_address) WHERE revoked_at IS NULL;
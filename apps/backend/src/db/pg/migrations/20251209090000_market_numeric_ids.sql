@@ -0,0 +1,17 @@
+-- Stable numbers of markets for clients that key on integers; existing markets are numbered by id
+CREATE SEQUENCE IF NOT EXISTS markets_numeric_id_seq;
+
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS numeric_id INT;
+
+UPDATE markets m SET numeric_id = numbered.n
+FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS n FROM markets) numbered
+WHERE m.id = numbered.id AND m.numeric_id IS NULL;
+
+SELECT setval('markets_numeric_id_seq', COALESCE((SELECT MAX(numeric_id) FROM markets), 0) + 1, false);
+
+ALTER SEQUENCE markets_numeric_id_seq OWNED BY markets.numeric_id;
+
+ALTER TABLE markets
+    ALTER COLUMN numeric_id SET DEFAULT nextval('markets_numeric_id_seq'),
+    ALTER COLUMN numeric_id SET NOT NULL,
+    ADD CONSTRAINT markets_numeric_id_key UNIQUE (numeric_id);
@@ -0,0 +1,6 @@
+-- Stop orders wait for a trade at their trigger price, then execute as the
+-- limit or market order they become; the trigger price stays with them
+ALTER TYPE order_type ADD VALUE IF NOT EXISTS 'stop_market';
+ALTER TYPE order_type ADD VALUE IF NOT EXISTS 'stop_limit';
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS trigger_price NUMERIC(39, 0);
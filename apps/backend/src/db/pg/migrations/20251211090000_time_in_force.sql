@@ -0,0 +1,8 @@
+-- How long an order's unfilled remainder rests: good till cancelled (gtc) or
+-- immediate or cancel (ioc), whose remainder is cancelled instead of resting
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS time_in_force TEXT NOT NULL DEFAULT 'gtc';
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_time_in_force_check;
+ALTER TABLE orders ADD CONSTRAINT orders_time_in_force_check
+    CHECK (time_in_force IN ('gtc', 'ioc'));
@@ -0,0 +1,5 @@
+-- Fill-or-kill orders (fok) fill completely on arrival or are refused; a fok
+-- stop that can't fill when it triggers is cancelled without trading
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_time_in_force_check;
+ALTER TABLE orders ADD CONSTRAINT orders_time_in_force_check
+    CHECK (time_in_force IN ('gtc', 'ioc', 'fok'));
@@ -0,0 +1,6 @@
+-- Good-till-date orders: the engine cancels an order still open at expires_at
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+-- The engine reloads every open order with an expiry when it starts
+CREATE INDEX IF NOT EXISTS idx_orders_expires_at ON orders (expires_at)
+    WHERE expires_at IS NOT NULL AND status IN ('pending', 'partially_filled');
@@ -0,0 +1,11 @@
+-- One-cancels-other pairs: two orders placed together, the first fill on
+-- either cancels the other. orders is partitioned by month, so the order ids
+-- can't be foreign keys.
+CREATE TABLE IF NOT EXISTS oco_links (
+    id UUID PRIMARY KEY,
+    user_address TEXT NOT NULL REFERENCES users(address),
+    first_order_id UUID NOT NULL UNIQUE,
+    second_order_id UUID NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (first_order_id <> second_order_id)
+);
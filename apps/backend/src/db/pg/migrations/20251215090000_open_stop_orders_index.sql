@@ -0,0 +1,9 @@
+-- Open-order caps count resting stops too, so the open-orders index has to
+-- cover them; recovery reloads untriggered stops oldest first on its own
+DROP INDEX IF EXISTS idx_orders_open;
+CREATE INDEX IF NOT EXISTS idx_orders_open ON orders(market_id, user_address, created_at)
+    WHERE status IN ('pending', 'partially_filled')
+      AND type IN ('limit', 'stop_market', 'stop_limit');
+
+CREATE INDEX IF NOT EXISTS idx_orders_untriggered_stops ON orders(created_at)
+    WHERE status = 'pending' AND type IN ('stop_market', 'stop_limit');
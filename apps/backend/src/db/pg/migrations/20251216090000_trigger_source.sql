@@ -0,0 +1,8 @@
+-- Which price reaches a stop order's trigger: the last trade here
+-- (last_trade) or the market's index price from external venues (index)
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS trigger_source TEXT NOT NULL DEFAULT 'last_trade';
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_trigger_source_check;
+ALTER TABLE orders ADD CONSTRAINT orders_trigger_source_check
+    CHECK (trigger_source IN ('last_trade', 'index'));
@@ -0,0 +1,35 @@
+-- The partitioned orders table's primary key includes created_at, so on its
+-- own it can't keep order ids unique across partitions, and trades lost their
+-- foreign keys into it. order_ids registers every order id once, as each order
+-- is inserted; a repeated id fails the insert, and trades reference it instead.
+-- Ids stay registered after their order is gone, so they are never reused.
+-- Partition maintenance moves rows with plain DELETEs and INSERTs into a table
+-- not yet attached, which leaves the registry alone.
+CREATE TABLE IF NOT EXISTS order_ids (
+    id UUID PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+INSERT INTO order_ids (id, created_at)
+SELECT id, created_at FROM orders
+ON CONFLICT (id) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION register_order_id()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO order_ids (id, created_at) VALUES (NEW.id, NEW.created_at);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS orders_register_id ON orders;
+CREATE TRIGGER orders_register_id
+    AFTER INSERT ON orders
+    FOR EACH ROW EXECUTE FUNCTION register_order_id();
+
+ALTER TABLE trades DROP CONSTRAINT IF EXISTS trades_buyer_order_id_fkey;
+ALTER TABLE trades DROP CONSTRAINT IF EXISTS trades_seller_order_id_fkey;
+ALTER TABLE trades ADD CONSTRAINT trades_buyer_order_id_fkey
+    FOREIGN KEY (buyer_order_id) REFERENCES order_ids(id);
+ALTER TABLE trades ADD CONSTRAINT trades_seller_order_id_fkey
+    FOREIGN KEY (seller_order_id) REFERENCES order_ids(id);
@@ -1,17 +1,26 @@
 use anyhow::Context;
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::ConnectOptions;
 use std::env;
+use std::time::Duration;
 
 /// Create a PostgreSQL connection pool and run migrations
 /// If url is provided, it will be used instead of reading from environment
-pub async fn create_pool(url: Option<String>) -> anyhow::Result<PgPool> {
+/// Statements slower than slow_statement_threshold are logged as warnings
+pub async fn create_pool(
+    url: Option<String>,
+    slow_statement_threshold: Duration,
+) -> anyhow::Result<PgPool> {
     let database_url = url
         .or_else(|| env::var("PG_URL").ok())
         .context("PG_URL must be provided or set in environment")?;
 
+    let options: PgConnectOptions = database_url.parse().context("Invalid PostgreSQL URL")?;
+    let options = options.log_slow_statements(log::LevelFilter::Warn, slow_statement_threshold);
+
     let pool = PgPoolOptions::new()
         .max_connections(10)
-        .connect(&database_url)
+        .connect_with(options)
         .await
         .context("Failed to connect to PostgreSQL")?;
 
@@ -0,0 +1,132 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::domain::{Referral, ReferralPayout};
+use crate::utils::decode_atoms;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use sqlx::Row;
+
+impl Db {
+    /// Set a user's referrer and fee share, replacing any existing referral
+    pub async fn set_referral(
+        &self,
+        user_address: &str,
+        referrer_address: &str,
+        share_bps: u32,
+    ) -> Result<Referral> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO referrals (user_address, referrer_address, share_bps, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_address) DO UPDATE
+            SET referrer_address = EXCLUDED.referrer_address,
+                share_bps = EXCLUDED.share_bps
+            RETURNING created_at
+            "#,
+        )
+        .bind(user_address)
+        .bind(referrer_address)
+        .bind(share_bps as i32)
+        .bind(Utc::now())
+        .fetch_one(&self.postgres)
+        .await?;
+
+        Ok(Referral {
+            user_address: user_address.to_string(),
+            referrer_address: referrer_address.to_string(),
+            share_bps,
+            created_at: row.get("created_at"),
+        })
+    }
+
+    /// List every referral
+    pub async fn list_referrals(&self) -> Result<Vec<Referral>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT user_address, referrer_address, share_bps, created_at FROM referrals",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| Referral {
+                user_address: row.get("user_address"),
+                referrer_address: row.get("referrer_address"),
+                share_bps: row.get::<i32, _>("share_bps") as u32,
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// Record referral payouts for a batch of trades (within a transaction)
+    pub async fn create_referral_payouts_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        payouts: &[ReferralPayout],
+    ) -> Result<()> {
+        if payouts.is_empty() {
+            return Ok(());
+        }
+
+        let trade_ids: Vec<_> = payouts.iter().map(|p| p.trade_id).collect();
+        let referrers: Vec<&str> = payouts
+            .iter()
+            .map(|p| p.referrer_address.as_str())
+            .collect();
+        let users: Vec<&str> = payouts.iter().map(|p| p.user_address.as_str()).collect();
+        let tickers: Vec<&str> = payouts.iter().map(|p| p.token_ticker.as_str()).collect();
+        let amounts: Vec<String> = payouts.iter().map(|p| p.amount.to_string()).collect();
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO referral_payouts (trade_id, referrer_address, user_address, token_ticker, amount)
+            SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::numeric[])
+            "#,
+        )
+        .bind(&trade_ids)
+        .bind(&referrers)
+        .bind(&users)
+        .bind(&tickers)
+        .bind(&amounts)
+        .execute(&mut **tx);
+        self.timed("create_referral_payouts", query).await?;
+
+        Ok(())
+    }
+
+    /// Total a referrer has earned per token, with how many referred users paid in
+    pub async fn get_referral_earnings(
+        &self,
+        referrer_address: &str,
+    ) -> Result<Vec<(String, u128, u64)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT token_ticker, SUM(amount) AS amount, COUNT(DISTINCT user_address) AS referred_users
+            FROM referral_payouts
+            WHERE referrer_address = $1
+            GROUP BY token_ticker
+            ORDER BY token_ticker
+            "#,
+        )
+        .bind(referrer_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let amount: BigDecimal = row.get("amount");
+                let referred_users: i64 = row.get("referred_users");
+                Ok((
+                    row.get("token_ticker"),
+                    decode_atoms("amount", &amount)?,
+                    referred_users as u64,
+                ))
+            })
+            .collect::<std::result::Result<_, sqlx::Error>>()?)
+    }
+}
@@ -0,0 +1,432 @@
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{
+    Order, OrderStatus, OrderType, Quote, QuoteRequest, RfqStatus, Side, TimeInForce, Trade,
+    TriggerSource,
+};
+use crate::rfq::{self, RfqExecution};
+use chrono::Utc;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+
+/// Columns of `rfq_requests`, with open requests past their expiry reported as expired
+const QUOTE_REQUEST_COLUMNS: &str = "id, taker_address, market_id, side::TEXT AS side, \
+    size::TEXT AS size, \
+    CASE WHEN status = 'open' AND expires_at <= NOW() THEN 'expired' ELSE status END AS status, \
+    trade_id, expires_at, created_at";
+
+const QUOTE_COLUMNS: &str =
+    "id, request_id, maker_address, price::TEXT AS price, expires_at, created_at";
+
+fn quote_request_from_row(row: &PgRow) -> QuoteRequest {
+    let side: String = row.get("side");
+    let size: String = row.get("size");
+    let status: String = row.get("status");
+    QuoteRequest {
+        id: row.get("id"),
+        taker_address: row.get("taker_address"),
+        market_id: row.get("market_id"),
+        side: side.parse().unwrap_or(Side::Buy),
+        size: size.parse().unwrap_or(0),
+        status: status.parse().unwrap_or_default(),
+        trade_id: row.get("trade_id"),
+        expires_at: row.get("expires_at"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn quote_from_row(row: &PgRow) -> Quote {
+    let price: String = row.get("price");
+    Quote {
+        id: row.get("id"),
+        request_id: row.get("request_id"),
+        maker_address: row.get("maker_address"),
+        price: price.parse().unwrap_or(0),
+        expires_at: row.get("expires_at"),
+        created_at: row.get("created_at"),
+    }
+}
+
+pub(super) fn user_not_found(user_address: &str) -> impl FnOnce(sqlx::Error) -> ExchangeError + '_ {
+    move |e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            ExchangeError::UserNotFound {
+                address: user_address.to_string(),
+            }
+        }
+        _ => ExchangeError::Database(e),
+    }
+}
+
+impl Db {
+    /// Let a user answer requests for quote; registering twice is a no-op
+    pub async fn register_rfq_maker(&self, user_address: &str) -> Result<()> {
+        sqlx::query("INSERT INTO rfq_makers (user_address) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(user_address)
+            .execute(&self.postgres)
+            .await
+            .map_err(user_not_found(user_address))?;
+
+        Ok(())
+    }
+
+    /// Stop a user answering requests for quote, withdrawing their quotes on
+    /// requests still open; returns how many were withdrawn
+    pub async fn remove_rfq_maker(&self, user_address: &str) -> Result<u64> {
+        let mut tx = self.begin_transaction().await?;
+        sqlx::query("DELETE FROM rfq_makers WHERE user_address = $1")
+            .bind(user_address)
+            .execute(&mut *tx)
+            .await?;
+        let withdrawn = sqlx::query(
+            r#"
+            DELETE FROM rfq_quotes q
+            USING rfq_requests r
+            WHERE q.request_id = r.id AND r.status = 'open' AND q.maker_address = $1
+            "#,
+        )
+        .bind(user_address)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        Ok(withdrawn)
+    }
+
+    /// Open a request for quote
+    pub async fn create_quote_request(&self, request: &QuoteRequest) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO rfq_requests (id, taker_address, market_id, side, size, status, expires_at, created_at)
+            VALUES ($1, $2, $3, $4::side, $5::numeric, $6, $7, $8)
+            "#,
+        )
+        .bind(request.id)
+        .bind(&request.taker_address)
+        .bind(&request.market_id)
+        .bind(request.side.to_string())
+        .bind(request.size.to_string())
+        .bind(request.status.to_string())
+        .bind(request.expires_at)
+        .bind(request.created_at)
+        .execute(&self.postgres)
+        .await
+        .map_err(user_not_found(&request.taker_address))?;
+
+        Ok(())
+    }
+
+    pub async fn get_quote_request(&self, request_id: Uuid) -> Result<QuoteRequest> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM rfq_requests WHERE id = $1",
+            QUOTE_REQUEST_COLUMNS
+        ))
+        .bind(request_id)
+        .fetch_optional(&self.postgres)
+        .await?
+        .ok_or_else(|| ExchangeError::RfqRequestNotFound {
+            request_id: request_id.to_string(),
+        })?;
+
+        Ok(quote_request_from_row(&row))
+    }
+
+    /// Requests makers can still quote, oldest first, optionally in one market
+    pub async fn list_open_quote_requests(
+        &self,
+        market_id: Option<&str>,
+    ) -> Result<Vec<QuoteRequest>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM rfq_requests
+            WHERE status = 'open' AND expires_at > NOW() AND ($1::text IS NULL OR market_id = $1)
+            ORDER BY created_at, id
+            "#,
+            QUOTE_REQUEST_COLUMNS
+        ))
+        .bind(market_id)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(quote_request_from_row).collect())
+    }
+
+    /// Withdraw one of `taker_address`'s open requests
+    pub async fn cancel_quote_request(
+        &self,
+        request_id: Uuid,
+        taker_address: &str,
+    ) -> Result<QuoteRequest> {
+        let request = self.get_quote_request(request_id).await?;
+        if request.taker_address != taker_address {
+            return Err(ExchangeError::RfqRequestNotFound {
+                request_id: request_id.to_string(),
+            });
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE rfq_requests
+            SET status = 'cancelled'
+            WHERE id = $1 AND status = 'open' AND expires_at > NOW()
+            RETURNING {}
+            "#,
+            QUOTE_REQUEST_COLUMNS
+        ))
+        .bind(request_id)
+        .fetch_optional(&self.postgres)
+        .await?;
+
+        match row {
+            Some(row) => Ok(quote_request_from_row(&row)),
+            None => Err(ExchangeError::RfqRequestNotOpen {
+                request_id: request_id.to_string(),
+                status: self.get_quote_request(request_id).await?.status,
+            }),
+        }
+    }
+
+    /// Quote a firm price on an open request, replacing the maker's previous quote
+    ///
+    /// A replaced quote gets a new id, so a taker accepting the old one never
+    /// trades at a price they haven't seen.
+    pub async fn submit_quote(
+        &self,
+        request_id: Uuid,
+        maker_address: &str,
+        price: u128,
+        ttl_secs: Option<u64>,
+    ) -> Result<Quote> {
+        let registered: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM rfq_makers WHERE user_address = $1)")
+                .bind(maker_address)
+                .fetch_one(&self.postgres)
+                .await?;
+        if !registered {
+            return Err(ExchangeError::NotRfqMaker {
+                user_address: maker_address.to_string(),
+            });
+        }
+
+        let request = self.get_quote_request(request_id).await?;
+        if request.status != RfqStatus::Open {
+            return Err(ExchangeError::RfqRequestNotOpen {
+                request_id: request_id.to_string(),
+                status: request.status,
+            });
+        }
+        if request.taker_address == maker_address {
+            return Err(ExchangeError::InvalidParameter {
+                message: "Makers cannot quote their own requests".to_string(),
+            });
+        }
+        let market = self.get_market(&request.market_id).await?;
+        rfq::validate_price(&market, price)?;
+
+        let now = Utc::now();
+        let expires_at = rfq::quote_expiry(now, ttl_secs, request.expires_at)?;
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO rfq_quotes (id, request_id, maker_address, price, expires_at, created_at)
+            VALUES ($1, $2, $3, $4::numeric, $5, $6)
+            ON CONFLICT (request_id, maker_address) DO UPDATE SET
+                id = EXCLUDED.id,
+                price = EXCLUDED.price,
+                expires_at = EXCLUDED.expires_at,
+                created_at = EXCLUDED.created_at
+            RETURNING {}
+            "#,
+            QUOTE_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(request_id)
+        .bind(maker_address)
+        .bind(price.to_string())
+        .bind(expires_at)
+        .bind(now)
+        .fetch_one(&self.postgres)
+        .await?;
+
+        Ok(quote_from_row(&row))
+    }
+
+    /// A quote, whether or not it is still live
+    pub async fn get_quote(&self, quote_id: Uuid) -> Result<Quote> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM rfq_quotes WHERE id = $1",
+            QUOTE_COLUMNS
+        ))
+        .bind(quote_id)
+        .fetch_optional(&self.postgres)
+        .await?
+        .ok_or_else(|| ExchangeError::QuoteNotFound {
+            quote_id: quote_id.to_string(),
+        })?;
+
+        Ok(quote_from_row(&row))
+    }
+
+    /// Live quotes on a request, best price for the taker first
+    pub async fn list_quotes(&self, request: &QuoteRequest) -> Result<Vec<Quote>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM rfq_quotes
+            WHERE request_id = $1 AND expires_at > NOW()
+            ORDER BY created_at, id
+            "#,
+            QUOTE_COLUMNS
+        ))
+        .bind(request.id)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let mut quotes: Vec<Quote> = rows.iter().map(quote_from_row).collect();
+        match request.side {
+            Side::Buy => quotes.sort_by_key(|q| q.price),
+            Side::Sell => quotes.sort_by_key(|q| std::cmp::Reverse(q.price)),
+        }
+        Ok(quotes)
+    }
+
+    /// Fill `taker_address`'s request at one of its live quotes in one transaction
+    ///
+    /// Both sides get a filled limit order at the quoted price, so the trade
+    /// shows in their order history like any other fill. Each side must have
+    /// what it pays available, outside of what their resting orders lock.
+    pub async fn execute_rfq(
+        &self,
+        request_id: Uuid,
+        quote_id: Uuid,
+        taker_address: &str,
+    ) -> Result<RfqExecution> {
+        let mut tx = self.begin_transaction().await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM rfq_requests WHERE id = $1 FOR UPDATE",
+            QUOTE_REQUEST_COLUMNS
+        ))
+        .bind(request_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .filter(|row| row.get::<String, _>("taker_address") == taker_address)
+        .ok_or_else(|| ExchangeError::RfqRequestNotFound {
+            request_id: request_id.to_string(),
+        })?;
+        let mut request = quote_request_from_row(&row);
+        if request.status != RfqStatus::Open {
+            return Err(ExchangeError::RfqRequestNotOpen {
+                request_id: request_id.to_string(),
+                status: request.status,
+            });
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM rfq_quotes
+            WHERE id = $1 AND request_id = $2 AND expires_at > NOW()
+            FOR UPDATE
+            "#,
+            QUOTE_COLUMNS
+        ))
+        .bind(quote_id)
+        .bind(request_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ExchangeError::QuoteNotFound {
+            quote_id: quote_id.to_string(),
+        })?;
+        let quote = quote_from_row(&row);
+
+        let market = self.get_market(&request.market_id).await?;
+        let base_token = self.get_token(&market.base_ticker).await?;
+        let (buyer_address, seller_address) = rfq::counterparties(&request, &quote.maker_address);
+        let changes = rfq::plan_settlement(
+            &market,
+            buyer_address,
+            seller_address,
+            quote.price,
+            request.size,
+            base_token.decimals,
+        )
+        .ok_or_else(|| ExchangeError::InvalidParameter {
+            message: "Trade value overflow or calculation error".to_string(),
+        })?;
+
+        // Lock what each side pays, failing if they can't cover it; settlement spends the lock
+        for ((user_address, token_ticker), change) in &changes {
+            if change.debit > 0 {
+                self.lock_balance_tx(&mut tx, user_address, token_ticker, change.debit)
+                    .await?;
+            }
+        }
+        self.apply_balance_changes_tx(&mut tx, &changes).await?;
+
+        let now = Utc::now();
+        let order = |user_address: &str, side| Order {
+            id: Uuid::new_v4(),
+            user_address: user_address.to_string(),
+            market_id: request.market_id.clone(),
+            price: quote.price,
+            size: request.size,
+            side,
+            order_type: OrderType::Limit,
+            status: OrderStatus::Filled,
+            filled_size: request.size,
+            created_at: now,
+            updated_at: now,
+            cancel_reason: None,
+            trigger_price: None,
+            trigger_source: TriggerSource::LastTrade,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: None,
+        };
+        let taker_order = order(taker_address, request.side);
+        let maker_side = match request.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let maker_order = order(&quote.maker_address, maker_side);
+        for order in [&taker_order, &maker_order] {
+            self.create_order_tx(&mut tx, order).await?;
+        }
+
+        let (buyer_order_id, seller_order_id) = match request.side {
+            Side::Buy => (taker_order.id, maker_order.id),
+            Side::Sell => (maker_order.id, taker_order.id),
+        };
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            market_id: request.market_id.clone(),
+            buyer_address: buyer_address.to_string(),
+            seller_address: seller_address.to_string(),
+            buyer_order_id,
+            seller_order_id,
+            price: quote.price,
+            size: request.size,
+            side: request.side,
+            timestamp: now,
+            rfq: true,
+        };
+        self.create_trade_tx(&mut tx, &trade).await?;
+
+        sqlx::query("UPDATE rfq_requests SET status = 'filled', trade_id = $2 WHERE id = $1")
+            .bind(request_id)
+            .bind(trade.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        request.status = RfqStatus::Filled;
+        request.trade_id = Some(trade.id);
+        Ok(RfqExecution {
+            request,
+            trade,
+            orders: vec![taker_order, maker_order],
+        })
+    }
+}
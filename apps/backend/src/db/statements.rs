@@ -0,0 +1,391 @@
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::db::{BalanceRow, StatementFillRow};
+use crate::models::domain::{Balance, Statement, StatementBalance, StatementMarket};
+use crate::statements::{StatementFlows, TokenFlows};
+use crate::utils::decode_atoms;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+
+impl Db {
+    /// List every user's balances
+    pub async fn list_balances(&self) -> Result<Vec<Balance>> {
+        let rows = sqlx::query_as::<_, BalanceRow>(
+            "SELECT user_address, token_ticker, amount, open_interest, updated_at FROM balances",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(Balance::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Start of the last month statements were generated for, if any ever were
+    pub async fn last_statement_period(&self) -> Result<Option<DateTime<Utc>>> {
+        let period_start = sqlx::query_scalar("SELECT MAX(period_start) FROM statement_runs")
+            .fetch_one(&self.postgres)
+            .await?;
+
+        Ok(period_start)
+    }
+
+    /// Closing balances of the month starting at `period_start`, keyed by
+    /// (user_address, token_ticker), or `None` if it has no statements
+    pub async fn get_statement_closing_balances(
+        &self,
+        period_start: DateTime<Utc>,
+    ) -> Result<Option<HashMap<(String, String), u128>>> {
+        let closed: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM statement_runs WHERE period_start = $1)",
+        )
+        .bind(period_start)
+        .fetch_one(&self.postgres)
+        .await?;
+        if !closed {
+            return Ok(None);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT user_address, token_ticker, closing_balance
+            FROM statement_balances
+            WHERE period_start = $1
+            "#,
+        )
+        .bind(period_start)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(Some(
+            rows.into_iter()
+                .map(|row| {
+                    let amount: BigDecimal = row.get("closing_balance");
+                    Ok((
+                        (row.get("user_address"), row.get("token_ticker")),
+                        decode_atoms("closing_balance", &amount)?,
+                    ))
+                })
+                .collect::<std::result::Result<_, sqlx::Error>>()?,
+        ))
+    }
+
+    /// Every user's deposits, confirmed withdrawals and trading fees less
+    /// rebates within [from, to), per token
+    pub async fn get_statement_flows(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<StatementFlows> {
+        let rows = sqlx::query(
+            r#"
+            SELECT user_address, token_ticker,
+                SUM(deposited)::TEXT AS deposited,
+                SUM(withdrawn)::TEXT AS withdrawn,
+                SUM(fees)::TEXT AS fees
+            FROM (
+                SELECT user_address, token_ticker, amount AS deposited, 0 AS withdrawn, 0 AS fees
+                FROM deposits
+                WHERE created_at >= $1 AND created_at < $2
+                UNION ALL
+                SELECT user_address, token_ticker, 0, amount, 0
+                FROM withdrawals
+                WHERE status = 'confirmed' AND updated_at >= $1 AND updated_at < $2
+                UNION ALL
+                SELECT f.user_address, f.token_ticker, 0, 0, f.fee
+                FROM trade_fees f
+                JOIN trades t ON t.id = f.trade_id
+                WHERE t.timestamp >= $1 AND t.timestamp < $2
+            ) flows
+            GROUP BY user_address, token_ticker
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let amount = |column: &str| row.get::<String, _>(column);
+                (
+                    (row.get("user_address"), row.get("token_ticker")),
+                    TokenFlows {
+                        deposited: amount("deposited").parse().unwrap_or(0),
+                        withdrawn: amount("withdrawn").parse().unwrap_or(0),
+                        fees: amount("fees").parse().unwrap_or(0),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Every user's fills per market within [from, to), with notional in
+    /// quote atoms; a trade with yourself counts as a buy and a sell
+    pub async fn get_statement_fills(
+        &self,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<(String, StatementMarket)>> {
+        let base_decimals = self.get_base_decimals_by_market().await?;
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT
+                user_address,
+                market_id,
+                count() as fills,
+                toUInt128(sumIf(size, user_address = buyer_address)) as bought,
+                toUInt128(sumIf(size, user_address = seller_address)) as sold,
+                toUInt128(sum(price * size)) as notional
+            FROM exchange.trades
+            ARRAY JOIN [buyer_address, seller_address] AS user_address
+            WHERE timestamp >= ? AND timestamp < ?
+            GROUP BY user_address, market_id",
+            )
+            .bind(from as u32)
+            .bind(to as u32)
+            .fetch_all::<StatementFillRow>()
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let decimals = *base_decimals.get(&row.market_id)?;
+                Some((
+                    row.user_address,
+                    StatementMarket {
+                        market_id: row.market_id,
+                        fills: row.fills,
+                        bought: row.bought,
+                        sold: row.sold,
+                        notional: row.notional / 10u128.pow(decimals as u32),
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Store a month's statements and mark the month closed, together
+    pub async fn record_statements(
+        &self,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        balances_at: DateTime<Utc>,
+        statements: &[Statement],
+    ) -> Result<()> {
+        let mut tx = self.postgres.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO statement_runs (period_start, period_end, balances_at, statements)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .bind(balances_at)
+        .bind(statements.len() as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        let users: Vec<&str> = statements.iter().map(|s| s.user_address.as_str()).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO statements (user_address, period_start)
+            SELECT user_address, $2 FROM UNNEST($1::text[]) AS user_address
+            "#,
+        )
+        .bind(&users)
+        .bind(period_start)
+        .execute(&mut *tx)
+        .await?;
+
+        let balances: Vec<(&str, &StatementBalance)> = statements
+            .iter()
+            .flat_map(|s| s.balances.iter().map(|b| (s.user_address.as_str(), b)))
+            .collect();
+        sqlx::query(
+            r#"
+            INSERT INTO statement_balances
+                (user_address, period_start, token_ticker, opening_balance, closing_balance,
+                 deposited, withdrawn, fees)
+            SELECT user_address, $2, token_ticker, opening_balance, closing_balance,
+                   deposited, withdrawn, fees
+            FROM UNNEST($1::text[], $3::text[], $4::numeric[], $5::numeric[], $6::numeric[],
+                        $7::numeric[], $8::numeric[])
+                AS b(user_address, token_ticker, opening_balance, closing_balance, deposited,
+                     withdrawn, fees)
+            "#,
+        )
+        .bind(balances.iter().map(|(user, _)| *user).collect::<Vec<_>>())
+        .bind(period_start)
+        .bind(
+            balances
+                .iter()
+                .map(|(_, b)| b.token_ticker.as_str())
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            balances
+                .iter()
+                .map(|(_, b)| b.opening_balance.map(|amount| amount.to_string()))
+                .collect::<Vec<_>>(),
+        )
+        .bind(numeric(balances.iter().map(|(_, b)| b.closing_balance)))
+        .bind(numeric(balances.iter().map(|(_, b)| b.deposited)))
+        .bind(numeric(balances.iter().map(|(_, b)| b.withdrawn)))
+        .bind(numeric(balances.iter().map(|(_, b)| b.fees)))
+        .execute(&mut *tx)
+        .await?;
+
+        let markets: Vec<(&str, &StatementMarket)> = statements
+            .iter()
+            .flat_map(|s| s.markets.iter().map(|m| (s.user_address.as_str(), m)))
+            .collect();
+        sqlx::query(
+            r#"
+            INSERT INTO statement_markets
+                (user_address, period_start, market_id, fills, bought, sold, notional)
+            SELECT user_address, $2, market_id, fills, bought, sold, notional
+            FROM UNNEST($1::text[], $3::text[], $4::bigint[], $5::numeric[], $6::numeric[],
+                        $7::numeric[])
+                AS m(user_address, market_id, fills, bought, sold, notional)
+            "#,
+        )
+        .bind(markets.iter().map(|(user, _)| *user).collect::<Vec<_>>())
+        .bind(period_start)
+        .bind(
+            markets
+                .iter()
+                .map(|(_, m)| m.market_id.as_str())
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            markets
+                .iter()
+                .map(|(_, m)| m.fills as i64)
+                .collect::<Vec<_>>(),
+        )
+        .bind(numeric(markets.iter().map(|(_, m)| m.bought)))
+        .bind(numeric(markets.iter().map(|(_, m)| m.sold)))
+        .bind(numeric(markets.iter().map(|(_, m)| m.notional)))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// A user's statements, newest first, optionally only the month starting
+    /// at `period_start`
+    pub async fn list_user_statements(
+        &self,
+        user_address: &str,
+        period_start: Option<DateTime<Utc>>,
+        limit: u32,
+    ) -> Result<Vec<Statement>> {
+        let headers = sqlx::query(
+            r#"
+            SELECT s.period_start, r.period_end, r.balances_at
+            FROM statements s
+            JOIN statement_runs r ON r.period_start = s.period_start
+            WHERE s.user_address = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR s.period_start = $2)
+            ORDER BY s.period_start DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_address)
+        .bind(period_start)
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+        let periods: Vec<DateTime<Utc>> =
+            headers.iter().map(|row| row.get("period_start")).collect();
+
+        let mut balances: BTreeMap<DateTime<Utc>, Vec<StatementBalance>> = BTreeMap::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT period_start, token_ticker, opening_balance::TEXT AS opening_balance,
+                   closing_balance::TEXT AS closing_balance, deposited::TEXT AS deposited,
+                   withdrawn::TEXT AS withdrawn, fees::TEXT AS fees
+            FROM statement_balances
+            WHERE user_address = $1 AND period_start = ANY($2)
+            ORDER BY token_ticker
+            "#,
+        )
+        .bind(user_address)
+        .bind(&periods)
+        .fetch_all(&self.postgres)
+        .await?;
+        for row in rows {
+            let amount = |column: &str| row.get::<String, _>(column);
+            balances
+                .entry(row.get("period_start"))
+                .or_default()
+                .push(StatementBalance {
+                    token_ticker: row.get("token_ticker"),
+                    opening_balance: row
+                        .get::<Option<String>, _>("opening_balance")
+                        .and_then(|amount| amount.parse().ok()),
+                    closing_balance: amount("closing_balance").parse().unwrap_or(0),
+                    deposited: amount("deposited").parse().unwrap_or(0),
+                    withdrawn: amount("withdrawn").parse().unwrap_or(0),
+                    fees: amount("fees").parse().unwrap_or(0),
+                });
+        }
+
+        let mut markets: BTreeMap<DateTime<Utc>, Vec<StatementMarket>> = BTreeMap::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT period_start, market_id, fills, bought::TEXT AS bought, sold::TEXT AS sold,
+                   notional::TEXT AS notional
+            FROM statement_markets
+            WHERE user_address = $1 AND period_start = ANY($2)
+            ORDER BY market_id
+            "#,
+        )
+        .bind(user_address)
+        .bind(&periods)
+        .fetch_all(&self.postgres)
+        .await?;
+        for row in rows {
+            let amount = |column: &str| row.get::<String, _>(column);
+            markets
+                .entry(row.get("period_start"))
+                .or_default()
+                .push(StatementMarket {
+                    market_id: row.get("market_id"),
+                    fills: row.get::<i64, _>("fills") as u64,
+                    bought: amount("bought").parse().unwrap_or(0),
+                    sold: amount("sold").parse().unwrap_or(0),
+                    notional: amount("notional").parse().unwrap_or(0),
+                });
+        }
+
+        Ok(headers
+            .iter()
+            .map(|row| {
+                let period_start = row.get("period_start");
+                Statement {
+                    user_address: user_address.to_string(),
+                    period_start,
+                    period_end: row.get("period_end"),
+                    balances_at: row.get("balances_at"),
+                    balances: balances.remove(&period_start).unwrap_or_default(),
+                    markets: markets.remove(&period_start).unwrap_or_default(),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Amounts as strings for binding to a `numeric[]`
+fn numeric<T: ToString>(amounts: impl Iterator<Item = T>) -> Vec<String> {
+    amounts.map(|amount| amount.to_string()).collect()
+}
@@ -0,0 +1,304 @@
+// query latency, slow queries and ClickHouse insert counts for the db layer
+
+use crate::db::Db;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Queries slower than this are logged when `DB_SLOW_QUERY_MS` is unset
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// Upper bounds, in seconds, of the query latency histogram buckets
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// Latency of one query tag since startup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryStats {
+    pub count: u64,
+    pub errors: u64,
+    pub slow: u64,
+    pub total: Duration,
+    pub max: Duration,
+    /// Queries at or under each of `LATENCY_BUCKETS`, not cumulative
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+/// Batches and rows written to one ClickHouse table since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InsertStats {
+    pub batches: u64,
+    pub rows: u64,
+    pub failed_batches: u64,
+    pub failed_rows: u64,
+    /// Time spent writing and flushing batches, failed ones included
+    pub total: Duration,
+}
+
+/// Per-tag query latency and per-table ClickHouse inserts, shared by every
+/// clone of a [`Db`]
+#[derive(Debug)]
+pub struct DbStats {
+    slow_threshold: Duration,
+    queries: Mutex<BTreeMap<&'static str, QueryStats>>,
+    inserts: Mutex<BTreeMap<&'static str, InsertStats>>,
+}
+
+impl Default for DbStats {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_QUERY_THRESHOLD)
+    }
+}
+
+impl DbStats {
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self {
+            slow_threshold,
+            queries: Mutex::new(BTreeMap::new()),
+            inserts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Queries taking at least this long are logged and counted as slow
+    pub fn slow_threshold(&self) -> Duration {
+        self.slow_threshold
+    }
+
+    /// Record one run of the query tagged `tag`
+    pub fn record_query(&self, tag: &'static str, elapsed: Duration, ok: bool) {
+        let slow = elapsed >= self.slow_threshold;
+        if slow {
+            log::warn!(
+                "Slow query {}: {}ms (threshold {}ms)",
+                tag,
+                elapsed.as_millis(),
+                self.slow_threshold.as_millis()
+            );
+        }
+
+        let mut queries = self.queries.lock().unwrap();
+        let stats = queries.entry(tag).or_default();
+        stats.count += 1;
+        stats.errors += u64::from(!ok);
+        stats.slow += u64::from(slow);
+        stats.total += elapsed;
+        stats.max = stats.max.max(elapsed);
+        if let Some(bucket) = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| elapsed.as_secs_f64() <= *bound)
+        {
+            stats.buckets[bucket] += 1;
+        }
+    }
+
+    /// Record one batch of `rows` written to ClickHouse table `table`
+    pub fn record_insert(&self, table: &'static str, rows: usize, elapsed: Duration, ok: bool) {
+        if !ok {
+            log::warn!(
+                "Failed to write {} rows to ClickHouse table {}",
+                rows,
+                table
+            );
+        }
+
+        let mut inserts = self.inserts.lock().unwrap();
+        let stats = inserts.entry(table).or_default();
+        stats.total += elapsed;
+        if ok {
+            stats.batches += 1;
+            stats.rows += rows as u64;
+        } else {
+            stats.failed_batches += 1;
+            stats.failed_rows += rows as u64;
+        }
+    }
+
+    /// Latency of each query tag, by tag
+    pub fn queries(&self) -> BTreeMap<&'static str, QueryStats> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    /// Inserts into each ClickHouse table, by table
+    pub fn inserts(&self) -> BTreeMap<&'static str, InsertStats> {
+        self.inserts.lock().unwrap().clone()
+    }
+
+    /// The statistics in the Prometheus text exposition format
+    pub fn render_metrics(&self) -> String {
+        let mut out = String::new();
+
+        let queries = self.queries();
+        let _ = writeln!(
+            out,
+            "# HELP exchange_db_query_duration_seconds Latency of tagged database queries"
+        );
+        let _ = writeln!(out, "# TYPE exchange_db_query_duration_seconds histogram");
+        for (tag, stats) in &queries {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "exchange_db_query_duration_seconds_bucket{{tag=\"{}\",le=\"{}\"}} {}",
+                    tag, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "exchange_db_query_duration_seconds_bucket{{tag=\"{}\",le=\"+Inf\"}} {}",
+                tag, stats.count
+            );
+            let _ = writeln!(
+                out,
+                "exchange_db_query_duration_seconds_sum{{tag=\"{}\"}} {}",
+                tag,
+                stats.total.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "exchange_db_query_duration_seconds_count{{tag=\"{}\"}} {}",
+                tag, stats.count
+            );
+        }
+        write_counter(
+            &mut out,
+            "exchange_db_query_errors_total",
+            "Tagged database queries that failed",
+            "tag",
+            &queries,
+            |stats| stats.errors,
+        );
+        write_counter(
+            &mut out,
+            "exchange_db_slow_queries_total",
+            "Tagged database queries over the slow query threshold",
+            "tag",
+            &queries,
+            |stats| stats.slow,
+        );
+
+        let inserts = self.inserts();
+        write_counter(
+            &mut out,
+            "exchange_clickhouse_insert_batches_total",
+            "Batches written to a ClickHouse table",
+            "table",
+            &inserts,
+            |stats| stats.batches,
+        );
+        write_counter(
+            &mut out,
+            "exchange_clickhouse_insert_rows_total",
+            "Rows written to a ClickHouse table",
+            "table",
+            &inserts,
+            |stats| stats.rows,
+        );
+        write_counter(
+            &mut out,
+            "exchange_clickhouse_failed_batches_total",
+            "Batches that failed to reach a ClickHouse table",
+            "table",
+            &inserts,
+            |stats| stats.failed_batches,
+        );
+        write_counter(
+            &mut out,
+            "exchange_clickhouse_failed_rows_total",
+            "Rows that failed to reach a ClickHouse table",
+            "table",
+            &inserts,
+            |stats| stats.failed_rows,
+        );
+        let _ = writeln!(
+            out,
+            "# HELP exchange_clickhouse_insert_seconds_total Time spent writing and flushing batches to a ClickHouse table"
+        );
+        let _ = writeln!(
+            out,
+            "# TYPE exchange_clickhouse_insert_seconds_total counter"
+        );
+        for (table, stats) in &inserts {
+            let _ = writeln!(
+                out,
+                "exchange_clickhouse_insert_seconds_total{{table=\"{}\"}} {}",
+                table,
+                stats.total.as_secs_f64()
+            );
+        }
+        out
+    }
+}
+
+impl Db {
+    /// Run `query`, recording its latency and outcome under `tag`
+    pub async fn timed<T, E>(
+        &self,
+        tag: &'static str,
+        query: impl Future<Output = std::result::Result<T, E>>,
+    ) -> std::result::Result<T, E> {
+        let started = Instant::now();
+        let result = query.await;
+        self.stats
+            .record_query(tag, started.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Run a ClickHouse `insert` of `rows` rows into `table`, recording the
+    /// batch and how long it took to write and flush
+    pub(crate) async fn timed_insert<E>(
+        &self,
+        table: &'static str,
+        rows: usize,
+        insert: impl Future<Output = std::result::Result<(), E>>,
+    ) -> std::result::Result<(), E> {
+        let started = Instant::now();
+        let result = insert.await;
+        self.stats
+            .record_insert(table, rows, started.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Connection pool gauges and query statistics in the Prometheus text format
+    pub fn render_metrics(&self) -> String {
+        let mut out = String::new();
+        let gauges = [
+            (
+                "exchange_db_pool_connections",
+                "Open PostgreSQL connections",
+                self.postgres.size() as u64,
+            ),
+            (
+                "exchange_db_pool_idle_connections",
+                "PostgreSQL connections waiting for a query",
+                self.postgres.num_idle() as u64,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out.push_str(&self.stats.render_metrics());
+        out
+    }
+}
+
+/// One counter with a `label`ed series for each entry of `entries`
+fn write_counter<S>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    entries: &BTreeMap<&'static str, S>,
+    value: impl Fn(&S) -> u64,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    for (key, stats) in entries {
+        let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, key, value(stats));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::db::BalanceRow;
+use crate::models::domain::{AccountTransfer, Balance, SubAccount, UserStatus};
+use chrono::Utc;
+use sqlx::Row;
+use uuid::Uuid;
+
+impl Db {
+    /// Open `master_address`'s sub-account called `name`, with its own user row
+    pub async fn create_sub_account(&self, master_address: &str, name: &str) -> Result<SubAccount> {
+        let address = SubAccount::address_of(master_address, name);
+        let mut tx = self.postgres.begin().await?;
+
+        let master: Option<String> =
+            sqlx::query_scalar("SELECT address FROM users WHERE address = $1 FOR SHARE")
+                .bind(master_address)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if master.is_none() {
+            return Err(ExchangeError::UserNotFound {
+                address: master_address.to_string(),
+            });
+        }
+
+        let exists = |e: sqlx::Error| match e {
+            sqlx::Error::Database(db_err) if db_err.constraint().is_some() => {
+                ExchangeError::SubAccountExists {
+                    address: address.clone(),
+                }
+            }
+            e => e.into(),
+        };
+        sqlx::query("INSERT INTO users (address) VALUES ($1)")
+            .bind(&address)
+            .execute(&mut *tx)
+            .await
+            .map_err(exists)?;
+        let created_at = sqlx::query_scalar(
+            r#"
+            INSERT INTO sub_accounts (address, master_address, name)
+            VALUES ($1, $2, $3)
+            RETURNING created_at
+            "#,
+        )
+        .bind(&address)
+        .bind(master_address)
+        .bind(name)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(exists)?;
+        tx.commit().await?;
+
+        Ok(SubAccount {
+            address,
+            master_address: master_address.to_string(),
+            name: name.to_string(),
+            created_at,
+        })
+    }
+
+    /// A master's sub-accounts, oldest first
+    pub async fn list_sub_accounts(&self, master_address: &str) -> Result<Vec<SubAccount>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT address, master_address, name, created_at
+            FROM sub_accounts
+            WHERE master_address = $1
+            ORDER BY created_at, name
+            "#,
+        )
+        .bind(master_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SubAccount {
+                address: row.get("address"),
+                master_address: row.get("master_address"),
+                name: row.get("name"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// Balances of a master and all of its sub-accounts
+    pub async fn list_account_group_balances(&self, master_address: &str) -> Result<Vec<Balance>> {
+        let rows = sqlx::query_as::<_, BalanceRow>(
+            r#"
+            SELECT user_address, token_ticker, amount, open_interest, updated_at
+            FROM balances
+            WHERE user_address = $1
+               OR user_address IN (SELECT address FROM sub_accounts WHERE master_address = $1)
+            ORDER BY user_address, token_ticker
+            "#,
+        )
+        .bind(master_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(Balance::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Move `amount` of free balance from one of a master's accounts to another
+    ///
+    /// Fails if the master is missing or not active, or the source can't
+    /// cover the amount from its available balance. Returns the transfer with
+    /// the source's and destination's balances after it.
+    pub async fn transfer_between_accounts(
+        &self,
+        master_address: &str,
+        from_address: &str,
+        to_address: &str,
+        token_ticker: &str,
+        amount: u128,
+    ) -> Result<(AccountTransfer, Balance, Balance)> {
+        let mut tx = self.postgres.begin().await?;
+
+        let status: Option<String> =
+            sqlx::query_scalar("SELECT status FROM users WHERE address = $1 FOR SHARE")
+                .bind(master_address)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let status: UserStatus = status
+            .ok_or_else(|| ExchangeError::UserNotFound {
+                address: master_address.to_string(),
+            })?
+            .parse()
+            .unwrap_or(UserStatus::Frozen);
+        if status != UserStatus::Active {
+            return Err(ExchangeError::UserNotActive {
+                user_address: master_address.to_string(),
+                status,
+            });
+        }
+
+        let now = Utc::now();
+        let debited = sqlx::query(
+            r#"
+            UPDATE balances
+            SET amount = amount - $3::numeric, updated_at = $4
+            WHERE user_address = $1
+              AND token_ticker = $2
+              AND amount - open_interest >= $3::numeric
+            "#,
+        )
+        .bind(from_address)
+        .bind(token_ticker)
+        .bind(amount.to_string())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        if debited.rows_affected() == 0 {
+            return Err(ExchangeError::InsufficientBalance {
+                user_address: from_address.to_string(),
+                token_ticker: token_ticker.to_string(),
+                required: amount,
+            });
+        }
+        self.add_balance_tx(&mut tx, to_address, token_ticker, amount)
+            .await?;
+
+        let transfer = AccountTransfer {
+            id: Uuid::new_v4(),
+            master_address: master_address.to_string(),
+            from_address: from_address.to_string(),
+            to_address: to_address.to_string(),
+            token_ticker: token_ticker.to_string(),
+            amount,
+            created_at: now,
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO account_transfers
+                (id, master_address, from_address, to_address, token_ticker, amount, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6::numeric, $7)
+            "#,
+        )
+        .bind(transfer.id)
+        .bind(&transfer.master_address)
+        .bind(&transfer.from_address)
+        .bind(&transfer.to_address)
+        .bind(&transfer.token_ticker)
+        .bind(amount.to_string())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let from_balance = self.get_balance(from_address, token_ticker).await?;
+        let to_balance = self.get_balance(to_address, token_ticker).await?;
+        Ok((transfer, from_balance, to_balance))
+    }
+}
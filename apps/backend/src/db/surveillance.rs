@@ -0,0 +1,223 @@
+use crate::db::rfq::user_not_found;
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::db::{OrderRow, SurveillanceAlertRow, TradePairRow};
+use crate::models::domain::{Order, SurveillanceAlert, SurveillanceKind, TradePair};
+use crate::surveillance::SurveillanceFinding;
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use std::collections::HashMap;
+
+impl Db {
+    /// Record who ultimately owns an account, or forget it with `None`
+    pub async fn set_beneficial_owner(
+        &self,
+        user_address: &str,
+        owner: Option<&str>,
+    ) -> Result<()> {
+        match owner {
+            Some(owner) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO beneficial_owners (user_address, owner, updated_at)
+                    VALUES ($1, $2, NOW())
+                    ON CONFLICT (user_address) DO UPDATE
+                    SET owner = EXCLUDED.owner,
+                        updated_at = EXCLUDED.updated_at
+                    "#,
+                )
+                .bind(user_address)
+                .bind(owner)
+                .execute(&self.postgres)
+                .await
+                .map_err(user_not_found(user_address))?;
+            }
+            None => {
+                sqlx::query("DELETE FROM beneficial_owners WHERE user_address = $1")
+                    .bind(user_address)
+                    .execute(&self.postgres)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The beneficial owner of every account that has one recorded
+    ///
+    /// Sub-accounts without one of their own share their master's, and a
+    /// master with sub-accounts but none recorded owns itself.
+    pub async fn get_beneficial_owners(&self) -> Result<HashMap<String, String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT user_address, owner FROM beneficial_owners
+            UNION ALL
+            SELECT s.address, COALESCE(b.owner, s.master_address)
+            FROM sub_accounts s
+            LEFT JOIN beneficial_owners b ON b.user_address = s.master_address
+            WHERE s.address NOT IN (SELECT user_address FROM beneficial_owners)
+            UNION
+            SELECT s.master_address, s.master_address
+            FROM sub_accounts s
+            WHERE s.master_address NOT IN (SELECT user_address FROM beneficial_owners)
+            "#,
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("user_address"), row.get("owner")))
+            .collect())
+    }
+
+    /// Trades within [from, to) per market, buyer and seller, for trades of an
+    /// account with itself or between two of `accounts`
+    pub async fn get_trade_pairs(
+        &self,
+        accounts: &[String],
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<TradePair>> {
+        let rows = self
+            .clickhouse
+            .query(
+                "SELECT
+                market_id,
+                buyer_address,
+                seller_address,
+                count() as trades,
+                toUInt128(sum(size)) as volume
+            FROM exchange.trades
+            WHERE timestamp >= ? AND timestamp < ?
+                AND (buyer_address = seller_address
+                    OR (has(?, buyer_address) AND has(?, seller_address)))
+            GROUP BY market_id, buyer_address, seller_address
+            ORDER BY market_id, buyer_address, seller_address",
+            )
+            .bind(from as u32)
+            .bind(to as u32)
+            .bind(accounts)
+            .bind(accounts)
+            .fetch_all::<TradePairRow>()
+            .await?;
+
+        Ok(rows.into_iter().map(TradePair::from).collect())
+    }
+
+    /// Limit orders placed within [from, to) that filled at all or that their
+    /// owner cancelled, per market in the order they were placed
+    pub async fn get_concluded_orders(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Order>> {
+        let rows = sqlx::query_as!(
+            OrderRow,
+            r#"
+            SELECT id, user_address, market_id, price, size, side::TEXT AS "side!", type::TEXT AS "order_type!", status::TEXT AS "status!", filled_size, created_at, updated_at, cancel_reason, trigger_price, trigger_source, time_in_force, expires_at
+            FROM orders
+            WHERE created_at >= $1 AND created_at < $2
+              AND type = 'limit'
+              AND (filled_size > 0 OR (status = 'cancelled' AND cancel_reason IS NULL))
+            ORDER BY market_id, created_at
+            "#,
+            from,
+            to
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(Order::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// End of the last window surveillance analysed, if it ever ran
+    pub async fn last_surveillance_window_end(&self) -> Result<Option<DateTime<Utc>>> {
+        let window_end = sqlx::query_scalar("SELECT MAX(window_end) FROM surveillance_runs")
+            .fetch_one(&self.postgres)
+            .await?;
+
+        Ok(window_end)
+    }
+
+    /// Store what surveillance found in [window_start, window_end) and mark the
+    /// window analysed, together
+    pub async fn record_surveillance_window(
+        &self,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+        findings: &[SurveillanceFinding],
+    ) -> Result<()> {
+        let mut tx = self.postgres.begin().await?;
+
+        for finding in findings {
+            sqlx::query(
+                r#"
+                INSERT INTO surveillance_alerts
+                    (kind, market_id, subject, accounts, count, volume, detail, window_start, window_end)
+                VALUES ($1, $2, $3, $4, $5, $6::numeric, $7, $8, $9)
+                ON CONFLICT (kind, market_id, subject, window_start) DO NOTHING
+                "#,
+            )
+            .bind(finding.kind.to_string())
+            .bind(&finding.market_id)
+            .bind(&finding.subject)
+            .bind(&finding.accounts)
+            .bind(finding.count as i64)
+            .bind(finding.volume.to_string())
+            .bind(&finding.detail)
+            .bind(window_start)
+            .bind(window_end)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO surveillance_runs (window_end, window_start, alerts)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (window_end) DO NOTHING
+            "#,
+        )
+        .bind(window_end)
+        .bind(window_start)
+        .bind(findings.len() as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// List surveillance alerts, newest first
+    pub async fn list_surveillance_alerts(
+        &self,
+        kind: Option<SurveillanceKind>,
+        market_id: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<SurveillanceAlert>> {
+        let rows = sqlx::query_as::<_, SurveillanceAlertRow>(
+            r#"
+            SELECT id, kind, market_id, subject, accounts, count, volume, detail,
+                   window_start, window_end, created_at
+            FROM surveillance_alerts
+            WHERE ($1::TEXT IS NULL OR kind = $1) AND ($2::TEXT IS NULL OR market_id = $2)
+            ORDER BY id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(kind.map(|kind| kind.to_string()))
+        .bind(market_id)
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(SurveillanceAlert::try_from)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+}
@@ -5,6 +5,10 @@ use crate::models::{db::TokenRow, domain::Token};
 impl Db {
     /// Create a new token
     pub async fn create_token(&self, ticker: String, decimals: u8, name: String) -> Result<Token> {
+        if let Some(memory) = &self.memory {
+            return memory.create_token(ticker, decimals, name);
+        }
+
         let row = sqlx::query_as!(
             TokenRow,
             "INSERT INTO tokens (ticker, decimals, name) VALUES ($1, $2, $3)
@@ -21,7 +25,12 @@ impl Db {
     }
 
     /// Get a token by ticker
+    #[tracing::instrument(name = "db.get_token", skip(self))]
     pub async fn get_token(&self, ticker: &str) -> Result<Token> {
+        if let Some(memory) = &self.memory {
+            return memory.get_token(ticker);
+        }
+
         let row = sqlx::query_as!(
             TokenRow,
             "SELECT ticker, decimals, name FROM tokens WHERE ticker = $1",
@@ -38,6 +47,10 @@ impl Db {
 
     /// List all tokens
     pub async fn list_tokens(&self) -> Result<Vec<Token>> {
+        if let Some(memory) = &self.memory {
+            return memory.list_tokens();
+        }
+
         let rows = sqlx::query_as!(
             TokenRow,
             "SELECT ticker, decimals, name FROM tokens ORDER BY ticker",
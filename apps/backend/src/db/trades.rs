@@ -1,7 +1,11 @@
 use crate::db::Db;
 use crate::errors::Result;
-use crate::models::domain::Trade;
+use crate::models::domain::{Trade, TradeFee};
+use crate::utils::decode_atoms;
+use bigdecimal::BigDecimal;
 use sqlx::Row;
+use std::collections::HashMap;
+use uuid::Uuid;
 
 impl Db {
     /// Insert a new trade into the database
@@ -15,8 +19,8 @@ impl Db {
 
         sqlx::query(
             r#"
-            INSERT INTO trades (id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp)
-            VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8::numeric, $9::side, $10)
+            INSERT INTO trades (id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp, rfq)
+            VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8::numeric, $9::side, $10, $11)
             "#
         )
         .bind(trade.id)
@@ -29,6 +33,7 @@ impl Db {
         .bind(size_str)
         .bind(side_str)
         .bind(trade.timestamp)
+        .bind(trade.rfq)
         .execute(&self.postgres)
         .await?;
 
@@ -50,8 +55,8 @@ impl Db {
 
         sqlx::query(
             r#"
-            INSERT INTO trades (id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp)
-            VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8::numeric, $9::side, $10)
+            INSERT INTO trades (id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp, rfq)
+            VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8::numeric, $9::side, $10, $11)
             "#
         )
         .bind(trade.id)
@@ -64,24 +69,173 @@ impl Db {
         .bind(size_str)
         .bind(side_str)
         .bind(trade.timestamp)
+        .bind(trade.rfq)
         .execute(&mut **tx)
         .await?;
 
         Ok(())
     }
 
+    /// Insert many trades with one statement (within a transaction)
+    #[tracing::instrument(name = "db.create_trades", skip_all, fields(count = trades.len()))]
+    pub async fn create_trades_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        trades: &[Trade],
+    ) -> Result<()> {
+        if trades.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+        let market_ids: Vec<&str> = trades.iter().map(|t| t.market_id.as_str()).collect();
+        let buyers: Vec<&str> = trades.iter().map(|t| t.buyer_address.as_str()).collect();
+        let sellers: Vec<&str> = trades.iter().map(|t| t.seller_address.as_str()).collect();
+        let buyer_order_ids: Vec<_> = trades.iter().map(|t| t.buyer_order_id).collect();
+        let seller_order_ids: Vec<_> = trades.iter().map(|t| t.seller_order_id).collect();
+        let prices: Vec<String> = trades.iter().map(|t| t.price.to_string()).collect();
+        let sizes: Vec<String> = trades.iter().map(|t| t.size.to_string()).collect();
+        let sides: Vec<String> = trades.iter().map(|t| t.side.to_string()).collect();
+        let timestamps: Vec<_> = trades.iter().map(|t| t.timestamp).collect();
+        let rfqs: Vec<bool> = trades.iter().map(|t| t.rfq).collect();
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO trades (id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp, rfq)
+            SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side::side, timestamp, rfq
+            FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::uuid[], $6::uuid[], $7::numeric[], $8::numeric[], $9::text[], $10::timestamptz[], $11::bool[])
+                AS t(id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price, size, side, timestamp, rfq)
+            "#
+        )
+        .bind(&ids)
+        .bind(&market_ids)
+        .bind(&buyers)
+        .bind(&sellers)
+        .bind(&buyer_order_ids)
+        .bind(&seller_order_ids)
+        .bind(&prices)
+        .bind(&sizes)
+        .bind(&sides)
+        .bind(&timestamps)
+        .bind(&rfqs)
+        .execute(&mut **tx);
+        self.timed("create_trades", query).await?;
+
+        Ok(())
+    }
+
+    /// Record the fees settled on a batch of trades (within a transaction)
+    pub async fn create_trade_fees_tx(
+        &self,
+        tx: &mut crate::db::Transaction<'_, crate::db::Postgres>,
+        fees: &[TradeFee],
+    ) -> Result<()> {
+        if fees.is_empty() {
+            return Ok(());
+        }
+
+        let trade_ids: Vec<_> = fees.iter().map(|f| f.trade_id).collect();
+        let users: Vec<&str> = fees.iter().map(|f| f.user_address.as_str()).collect();
+        let tickers: Vec<&str> = fees.iter().map(|f| f.token_ticker.as_str()).collect();
+        let amounts: Vec<String> = fees.iter().map(|f| f.fee.to_string()).collect();
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO trade_fees (trade_id, user_address, token_ticker, fee)
+            SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::numeric[])
+            "#,
+        )
+        .bind(&trade_ids)
+        .bind(&users)
+        .bind(&tickers)
+        .bind(&amounts)
+        .execute(&mut **tx);
+        self.timed("create_trade_fees", query).await?;
+
+        Ok(())
+    }
+
+    /// Fees a user settled on the given trades, keyed by trade id
+    pub async fn get_user_trade_fees(
+        &self,
+        user_address: &str,
+        trade_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, TradeFee>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_user_trade_fees(user_address, trade_ids);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT trade_id, token_ticker, fee::TEXT AS fee
+            FROM trade_fees
+            WHERE user_address = $1 AND trade_id = ANY($2)
+            "#,
+        )
+        .bind(user_address)
+        .bind(trade_ids)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let fee: String = row.get("fee");
+                let fee = TradeFee {
+                    trade_id: row.get("trade_id"),
+                    user_address: user_address.to_string(),
+                    token_ticker: row.get("token_ticker"),
+                    fee: fee.parse().unwrap_or(0),
+                };
+                (fee.trade_id, fee)
+            })
+            .collect())
+    }
+
+    /// Total maker rebates a user has received, per token
+    pub async fn get_user_rebate_totals(&self, user_address: &str) -> Result<Vec<(String, u128)>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_user_rebate_totals(user_address);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT token_ticker, -SUM(fee) AS amount
+            FROM trade_fees
+            WHERE user_address = $1 AND fee < 0
+            GROUP BY token_ticker
+            ORDER BY token_ticker
+            "#,
+        )
+        .bind(user_address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let amount: BigDecimal = row.get("amount");
+                Ok((row.get("token_ticker"), decode_atoms("amount", &amount)?))
+            })
+            .collect::<std::result::Result<_, sqlx::Error>>()?)
+    }
+
     pub async fn get_user_trades(
         &self,
         user_address: &str,
         market_id: Option<&str>,
         limit: u32,
     ) -> Result<Vec<Trade>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_user_trades(user_address, market_id, limit);
+        }
+
         let limit = std::cmp::min(limit, 1000); // Cap at 1000
 
         let query = if let Some(market) = market_id {
             sqlx::query(
                 r#"
-                SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price::TEXT as price, size::TEXT as size, side::TEXT as side, timestamp
+                SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price::TEXT as price, size::TEXT as size, side::TEXT as side, timestamp, rfq
                 FROM trades
                 WHERE (buyer_address = $1 OR seller_address = $1) AND market_id = $2
                 ORDER BY timestamp DESC
@@ -94,7 +248,7 @@ impl Db {
         } else {
             sqlx::query(
                 r#"
-                SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price::TEXT as price, size::TEXT as size, side::TEXT as side, timestamp
+                SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price::TEXT as price, size::TEXT as size, side::TEXT as side, timestamp, rfq
                 FROM trades
                 WHERE buyer_address = $1 OR seller_address = $1
                 ORDER BY timestamp DESC
@@ -129,6 +283,7 @@ impl Db {
                         crate::models::domain::Side::Sell
                     },
                     timestamp: row.get("timestamp"),
+                    rfq: row.get("rfq"),
                 }
             })
             .collect();
@@ -136,12 +291,41 @@ impl Db {
         Ok(trades)
     }
 
+    /// Price of the most recent trade in every market that has traded
+    pub async fn get_last_trade_prices(&self) -> Result<Vec<(String, u128)>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_last_trade_prices();
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (market_id) market_id, price::TEXT AS price
+            FROM trades
+            ORDER BY market_id, timestamp DESC
+            "#,
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let price: String = row.get("price");
+                Some((row.get("market_id"), price.parse().ok()?))
+            })
+            .collect())
+    }
+
     pub async fn get_market_trades(&self, market_id: &str, limit: u32) -> Result<Vec<Trade>> {
+        if let Some(memory) = &self.memory {
+            return memory.get_market_trades(market_id, limit);
+        }
+
         let limit = std::cmp::min(limit, 1000); // Cap at 1000
 
         let rows = sqlx::query(
             r#"
-            SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price::TEXT as price, size::TEXT as size, side::TEXT as side, timestamp
+            SELECT id, market_id, buyer_address, seller_address, buyer_order_id, seller_order_id, price::TEXT as price, size::TEXT as size, side::TEXT as side, timestamp, rfq
             FROM trades
             WHERE market_id = $1
             ORDER BY timestamp DESC
@@ -175,6 +359,7 @@ impl Db {
                         crate::models::domain::Side::Sell
                     },
                     timestamp: row.get("timestamp"),
+                    rfq: row.get("rfq"),
                 }
             })
             .collect();
@@ -1,10 +1,22 @@
+use sqlx::Row;
+use std::collections::HashMap;
+
 use crate::db::Db;
 use crate::errors::{ExchangeError, Result};
-use crate::models::{db::UserRow, domain::User};
+use crate::models::{
+    db::UserRow,
+    domain::{User, UserStatus, UserSummary},
+};
+use crate::utils::decode_atoms;
+use bigdecimal::BigDecimal;
 
 impl Db {
     /// Create a new user
     pub async fn create_user(&self, address: String) -> Result<User> {
+        if let Some(memory) = &self.memory {
+            return memory.create_user(address);
+        }
+
         let row = sqlx::query_as!(
             UserRow,
             "INSERT INTO users (address) VALUES ($1) RETURNING address, created_at",
@@ -18,6 +30,10 @@ impl Db {
 
     /// Get a user by address
     pub async fn get_user(&self, address: &str) -> Result<User> {
+        if let Some(memory) = &self.memory {
+            return memory.get_user(address);
+        }
+
         let row: UserRow = sqlx::query_as!(
             UserRow,
             "SELECT address, created_at FROM users WHERE address = $1",
@@ -32,6 +48,82 @@ impl Db {
         Ok(row.into())
     }
 
+    /// Get a user with their open orders, 30-day volume, fees paid and balances
+    ///
+    /// Volume is quote notional per quote token, truncated per trade the way
+    /// settlement computes it.
+    pub async fn get_user_summary(&self, address: &str) -> Result<UserSummary> {
+        let row = sqlx::query(
+            r#"
+            SELECT address, created_at, status, display_name,
+                (SELECT COUNT(*) FROM orders
+                 WHERE user_address = $1
+                   AND status IN ('pending', 'partially_filled')
+                   AND type = 'limit') AS open_orders
+            FROM users
+            WHERE address = $1
+            "#,
+        )
+        .bind(address)
+        .fetch_optional(&self.postgres)
+        .await?
+        .ok_or_else(|| ExchangeError::UserNotFound {
+            address: address.to_string(),
+        })?;
+
+        let volume_30d = sqlx::query(
+            r#"
+            SELECT m.quote_ticker AS token_ticker,
+                SUM(TRUNC(t.price * t.size / POWER(10::NUMERIC, b.decimals)))::NUMERIC(39, 0) AS amount
+            FROM trades t
+            JOIN markets m ON m.id = t.market_id
+            JOIN tokens b ON b.ticker = m.base_ticker
+            WHERE (t.buyer_address = $1 OR t.seller_address = $1)
+              AND t.timestamp >= NOW() - INTERVAL '30 days'
+            GROUP BY m.quote_ticker
+            ORDER BY m.quote_ticker
+            "#,
+        )
+        .bind(address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let fees_paid = sqlx::query(
+            r#"
+            SELECT token_ticker, SUM(fee) AS amount
+            FROM trade_fees
+            WHERE user_address = $1 AND fee > 0
+            GROUP BY token_ticker
+            ORDER BY token_ticker
+            "#,
+        )
+        .bind(address)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let amounts = |rows: Vec<sqlx::postgres::PgRow>| {
+            rows.into_iter()
+                .map(|row| {
+                    let amount: BigDecimal = row.get("amount");
+                    Ok((row.get("token_ticker"), decode_atoms("amount", &amount)?))
+                })
+                .collect::<std::result::Result<Vec<_>, sqlx::Error>>()
+        };
+        let status: String = row.get("status");
+        let open_orders: i64 = row.get("open_orders");
+
+        Ok(UserSummary {
+            address: row.get("address"),
+            created_at: row.get("created_at"),
+            status: status.parse().unwrap_or_default(),
+            display_name: row.get("display_name"),
+            open_orders: open_orders as u64,
+            volume_30d: amounts(volume_30d)?,
+            fees_paid: amounts(fees_paid)?,
+            balances: self.list_balances_by_user(address).await?,
+        })
+    }
+
     /// List all users
     pub async fn list_users(&self) -> Result<Vec<User>> {
         let rows: Vec<UserRow> = sqlx::query_as!(
@@ -43,4 +135,83 @@ impl Db {
 
         Ok(rows.into_iter().map(|row| row.into()).collect())
     }
+
+    /// Set a user's account status
+    pub async fn set_user_status(&self, address: &str, status: UserStatus) -> Result<()> {
+        let result = sqlx::query("UPDATE users SET status = $2 WHERE address = $1")
+            .bind(address)
+            .bind(status.to_string())
+            .execute(&self.postgres)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ExchangeError::UserNotFound {
+                address: address.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Set or clear a user's public display name
+    pub async fn set_display_name(&self, address: &str, display_name: Option<&str>) -> Result<()> {
+        let result = sqlx::query("UPDATE users SET display_name = $2 WHERE address = $1")
+            .bind(address)
+            .bind(display_name)
+            .execute(&self.postgres)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_err) if db_err.constraint().is_some() => {
+                    ExchangeError::DisplayNameTaken {
+                        display_name: display_name.unwrap_or_default().to_string(),
+                    }
+                }
+                e => ExchangeError::from(e),
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(ExchangeError::UserNotFound {
+                address: address.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Display names of the given users, for those who have set one
+    pub async fn get_display_names(&self, addresses: &[String]) -> Result<HashMap<String, String>> {
+        let rows = sqlx::query(
+            "SELECT address, display_name FROM users WHERE address = ANY($1) AND display_name IS NOT NULL",
+        )
+        .bind(addresses)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("address"), row.get("display_name")))
+            .collect())
+    }
+
+    /// Users that are not active, with their status
+    pub async fn list_restricted_users(&self) -> Result<Vec<(String, UserStatus)>> {
+        if self.memory.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT address, status FROM users WHERE status <> 'active' ORDER BY address",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let status: String = row.get("status");
+                (
+                    row.get("address"),
+                    status.parse().unwrap_or(UserStatus::Frozen),
+                )
+            })
+            .collect())
+    }
 }
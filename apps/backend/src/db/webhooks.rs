@@ -0,0 +1,144 @@
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Webhook, WebhookDeadLetter};
+use chrono::Utc;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+
+impl Db {
+    /// Register a webhook for a user
+    pub async fn create_webhook(
+        &self,
+        user_address: &str,
+        url: &str,
+        secret: &str,
+    ) -> Result<Webhook> {
+        let webhook = Webhook {
+            id: Uuid::new_v4(),
+            user_address: user_address.to_string(),
+            url: url.to_string(),
+            secret: secret.to_string(),
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            "INSERT INTO webhooks (id, user_address, url, secret, created_at) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(webhook.id)
+        .bind(&webhook.user_address)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(webhook.created_at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(webhook)
+    }
+
+    /// A user's webhooks, oldest first
+    pub async fn list_webhooks(&self, user_address: &str) -> Result<Vec<Webhook>> {
+        self.list_webhooks_for_users(&[user_address.to_string()])
+            .await
+    }
+
+    /// Webhooks of any of the given users, oldest first
+    pub async fn list_webhooks_for_users(&self, addresses: &[String]) -> Result<Vec<Webhook>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_address, url, secret, created_at
+            FROM webhooks
+            WHERE user_address = ANY($1)
+            ORDER BY created_at
+            "#,
+        )
+        .bind(addresses)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(webhook_from_row).collect())
+    }
+
+    /// Remove one of a user's webhooks along with its dead letters
+    pub async fn delete_webhook(&self, user_address: &str, webhook_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_address = $2")
+            .bind(webhook_id)
+            .bind(user_address)
+            .execute(&self.postgres)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ExchangeError::WebhookNotFound {
+                webhook_id: webhook_id.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Set aside a delivery that failed every attempt
+    pub async fn insert_webhook_dead_letter(&self, dead_letter: &WebhookDeadLetter) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_dead_letters
+                (id, webhook_id, event_type, payload, attempts, last_error, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(dead_letter.id)
+        .bind(dead_letter.webhook_id)
+        .bind(&dead_letter.event_type)
+        .bind(&dead_letter.payload)
+        .bind(dead_letter.attempts as i32)
+        .bind(&dead_letter.last_error)
+        .bind(dead_letter.created_at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Dead letters across a user's webhooks, newest first
+    pub async fn list_webhook_dead_letters(
+        &self,
+        user_address: &str,
+        limit: u32,
+    ) -> Result<Vec<WebhookDeadLetter>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.id, d.webhook_id, d.event_type, d.payload, d.attempts, d.last_error, d.created_at
+            FROM webhook_dead_letters d
+            JOIN webhooks w ON w.id = d.webhook_id
+            WHERE w.user_address = $1
+            ORDER BY d.created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(user_address)
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| WebhookDeadLetter {
+                id: row.get("id"),
+                webhook_id: row.get("webhook_id"),
+                event_type: row.get("event_type"),
+                payload: row.get("payload"),
+                attempts: row.get::<i32, _>("attempts") as u32,
+                last_error: row.get("last_error"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+}
+
+fn webhook_from_row(row: &PgRow) -> Webhook {
+    Webhook {
+        id: row.get("id"),
+        user_address: row.get("user_address"),
+        url: row.get("url"),
+        secret: row.get("secret"),
+        created_at: row.get("created_at"),
+    }
+}
@@ -0,0 +1,295 @@
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Balance, UserStatus, Withdrawal, WithdrawalStatus};
+use chrono::Utc;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+
+const WITHDRAWAL_COLUMNS: &str =
+    "id, user_address, token_ticker, amount::TEXT AS amount, destination, \
+     status, tx_hash, error, created_at, updated_at";
+
+impl Db {
+    /// Record a pending withdrawal and lock its amount in the user's balance
+    ///
+    /// Fails if the user is missing or not active, or can't cover the amount
+    /// from their available balance.
+    pub async fn create_withdrawal(
+        &self,
+        user_address: &str,
+        token_ticker: &str,
+        amount: u128,
+        destination: &str,
+    ) -> Result<(Withdrawal, Balance)> {
+        let mut tx = self.postgres.begin().await?;
+
+        let status: Option<String> =
+            sqlx::query_scalar("SELECT status FROM users WHERE address = $1 FOR SHARE")
+                .bind(user_address)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let status: UserStatus = status
+            .ok_or_else(|| ExchangeError::UserNotFound {
+                address: user_address.to_string(),
+            })?
+            .parse()
+            .unwrap_or(UserStatus::Frozen);
+        if status != UserStatus::Active {
+            return Err(ExchangeError::UserNotActive {
+                user_address: user_address.to_string(),
+                status,
+            });
+        }
+
+        self.lock_balance_tx(&mut tx, user_address, token_ticker, amount)
+            .await?;
+
+        let now = Utc::now();
+        let withdrawal = Withdrawal {
+            id: Uuid::new_v4(),
+            user_address: user_address.to_string(),
+            token_ticker: token_ticker.to_string(),
+            amount,
+            destination: destination.to_string(),
+            status: WithdrawalStatus::Pending,
+            tx_hash: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO withdrawals
+                (id, user_address, token_ticker, amount, destination, status, created_at, updated_at)
+            VALUES ($1, $2, $3, $4::numeric, $5, $6, $7, $7)
+            "#,
+        )
+        .bind(withdrawal.id)
+        .bind(&withdrawal.user_address)
+        .bind(&withdrawal.token_ticker)
+        .bind(amount.to_string())
+        .bind(&withdrawal.destination)
+        .bind(withdrawal.status.to_string())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let balance = self.get_balance(user_address, token_ticker).await?;
+        Ok((withdrawal, balance))
+    }
+
+    pub async fn get_withdrawal(&self, id: Uuid) -> Result<Withdrawal> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM withdrawals WHERE id = $1",
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.postgres)
+        .await?
+        .ok_or_else(|| ExchangeError::WithdrawalNotFound {
+            withdrawal_id: id.to_string(),
+        })?;
+
+        Ok(withdrawal_from_row(&row))
+    }
+
+    /// A user's withdrawals, newest first
+    pub async fn list_user_withdrawals(
+        &self,
+        user_address: &str,
+        limit: u32,
+    ) -> Result<Vec<Withdrawal>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM withdrawals WHERE user_address = $1 ORDER BY created_at DESC LIMIT $2",
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(user_address)
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(withdrawal_from_row).collect())
+    }
+
+    /// Withdrawals with a given status, oldest first
+    pub async fn list_withdrawals_by_status(
+        &self,
+        status: WithdrawalStatus,
+        limit: u32,
+    ) -> Result<Vec<Withdrawal>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM withdrawals WHERE status = $1 ORDER BY created_at LIMIT $2",
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(status.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rows.iter().map(withdrawal_from_row).collect())
+    }
+
+    /// Move a withdrawal from `from` to a status that keeps its funds locked,
+    /// recording its transaction hash if given
+    ///
+    /// Returns `None` if the withdrawal is no longer in `from`, for instance
+    /// because the user cancelled it first.
+    pub async fn advance_withdrawal(
+        &self,
+        id: Uuid,
+        from: WithdrawalStatus,
+        to: WithdrawalStatus,
+        tx_hash: Option<&str>,
+    ) -> Result<Option<Withdrawal>> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE withdrawals
+            SET status = $3, tx_hash = COALESCE($4, tx_hash), updated_at = $5
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(id)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .bind(tx_hash)
+        .bind(Utc::now())
+        .fetch_optional(&self.postgres)
+        .await?;
+
+        Ok(row.as_ref().map(withdrawal_from_row))
+    }
+
+    /// Mark a submitted withdrawal confirmed and debit its locked amount
+    pub async fn confirm_withdrawal(&self, id: Uuid) -> Result<Option<(Withdrawal, Balance)>> {
+        self.finish_withdrawal(
+            id,
+            None,
+            WithdrawalStatus::Submitted,
+            WithdrawalStatus::Confirmed,
+            None,
+        )
+        .await
+    }
+
+    /// Mark a withdrawal failed and release its locked amount
+    pub async fn fail_withdrawal(
+        &self,
+        id: Uuid,
+        from: WithdrawalStatus,
+        error: &str,
+    ) -> Result<Option<(Withdrawal, Balance)>> {
+        self.finish_withdrawal(id, None, from, WithdrawalStatus::Failed, Some(error))
+            .await
+    }
+
+    /// Cancel one of a user's pending withdrawals and release its locked amount
+    pub async fn cancel_withdrawal(
+        &self,
+        user_address: &str,
+        id: Uuid,
+    ) -> Result<(Withdrawal, Balance)> {
+        let cancelled = self
+            .finish_withdrawal(
+                id,
+                Some(user_address),
+                WithdrawalStatus::Pending,
+                WithdrawalStatus::Cancelled,
+                None,
+            )
+            .await?;
+        if let Some(cancelled) = cancelled {
+            return Ok(cancelled);
+        }
+
+        // Other users' withdrawals are reported as missing
+        let withdrawal = self.get_withdrawal(id).await?;
+        if withdrawal.user_address != user_address {
+            return Err(ExchangeError::WithdrawalNotFound {
+                withdrawal_id: id.to_string(),
+            });
+        }
+        Err(ExchangeError::WithdrawalNotPending {
+            withdrawal_id: id.to_string(),
+            status: withdrawal.status,
+        })
+    }
+
+    /// Move a withdrawal from `from` to a final status, unlocking its amount
+    /// and debiting it if the withdrawal went through
+    async fn finish_withdrawal(
+        &self,
+        id: Uuid,
+        user_address: Option<&str>,
+        from: WithdrawalStatus,
+        to: WithdrawalStatus,
+        error: Option<&str>,
+    ) -> Result<Option<(Withdrawal, Balance)>> {
+        let mut tx = self.postgres.begin().await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE withdrawals
+            SET status = $3, error = $4, updated_at = $5
+            WHERE id = $1 AND status = $2 AND ($6::TEXT IS NULL OR user_address = $6)
+            RETURNING {}
+            "#,
+            WITHDRAWAL_COLUMNS
+        ))
+        .bind(id)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .bind(error)
+        .bind(Utc::now())
+        .bind(user_address)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let withdrawal = withdrawal_from_row(&row);
+
+        self.unlock_balance_tx(
+            &mut tx,
+            &withdrawal.user_address,
+            &withdrawal.token_ticker,
+            withdrawal.amount,
+        )
+        .await?;
+        if to == WithdrawalStatus::Confirmed {
+            self.subtract_balance_tx(
+                &mut tx,
+                &withdrawal.user_address,
+                &withdrawal.token_ticker,
+                withdrawal.amount,
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        let balance = self
+            .get_balance(&withdrawal.user_address, &withdrawal.token_ticker)
+            .await?;
+        Ok(Some((withdrawal, balance)))
+    }
+}
+
+fn withdrawal_from_row(row: &PgRow) -> Withdrawal {
+    let amount: String = row.get("amount");
+    let status: String = row.get("status");
+    Withdrawal {
+        id: row.get("id"),
+        user_address: row.get("user_address"),
+        token_ticker: row.get("token_ticker"),
+        amount: amount.parse().unwrap_or(0),
+        destination: row.get("destination"),
+        status: status.parse().unwrap_or(WithdrawalStatus::Pending),
+        tx_hash: row.get("tx_hash"),
+        error: row.get("error"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
@@ -0,0 +1,266 @@
+// on-chain deposits: confirmed ERC-20 transfers credited to their senders
+
+pub mod rpc;
+
+use crate::config::{Config, DepositConfig};
+use crate::db::Db;
+use crate::models::domain::Deposit;
+use anyhow::Context;
+use chrono::Utc;
+use rpc::{EvmRpc, TransferLog};
+use std::time::Duration;
+
+/// Seconds between polls of the chain head
+pub const DEPOSIT_POLL_INTERVAL_SECS: u64 = 12;
+
+/// Most blocks requested from the node in one `eth_getLogs` call
+pub const MAX_BLOCK_RANGE: u64 = 1_000;
+
+/// A token accepted for deposit
+#[derive(Debug, Clone)]
+pub struct DepositToken {
+    pub ticker: String,
+    /// ERC-20 contract address, lowercase
+    pub contract: String,
+    pub chain_decimals: u8,
+    pub exchange_decimals: u8,
+}
+
+impl DepositToken {
+    /// Convert an on-chain amount into exchange atoms, dropping digits the
+    /// exchange token can't hold
+    ///
+    /// Returns `None` if the converted amount overflows.
+    pub fn to_exchange_amount(&self, amount: u128) -> Option<u128> {
+        if self.chain_decimals >= self.exchange_decimals {
+            let shift = (self.chain_decimals - self.exchange_decimals) as u32;
+            Some(amount / 10u128.checked_pow(shift)?)
+        } else {
+            let shift = (self.exchange_decimals - self.chain_decimals) as u32;
+            amount.checked_mul(10u128.checked_pow(shift)?)
+        }
+    }
+
+    /// Convert exchange atoms into an on-chain amount
+    ///
+    /// Returns `None` if the amount has digits the contract can't hold, or overflows.
+    pub fn to_chain_amount(&self, amount: u128) -> Option<u128> {
+        if self.exchange_decimals >= self.chain_decimals {
+            let unit = 10u128.checked_pow((self.exchange_decimals - self.chain_decimals) as u32)?;
+            amount.is_multiple_of(unit).then_some(amount / unit)
+        } else {
+            let shift = (self.chain_decimals - self.exchange_decimals) as u32;
+            amount.checked_mul(10u128.checked_pow(shift)?)
+        }
+    }
+}
+
+/// The tokens of `config.deposits`, with the decimals of their exchange tokens
+pub fn deposit_tokens(config: &Config) -> anyhow::Result<Vec<DepositToken>> {
+    let deposits = config
+        .deposits
+        .as_ref()
+        .context("No [deposits] section in config")?;
+    deposits
+        .tokens
+        .iter()
+        .map(|token| {
+            let exchange_decimals = config
+                .token_decimals(&token.ticker)
+                .with_context(|| format!("Unknown deposit token {}", token.ticker))?;
+            Ok(DepositToken {
+                ticker: token.ticker.clone(),
+                contract: token.contract.to_lowercase(),
+                chain_decimals: token.decimals.unwrap_or(exchange_decimals),
+                exchange_decimals,
+            })
+        })
+        .collect()
+}
+
+/// What one poll did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollOutcome {
+    /// Deposits credited in this poll
+    pub credited: usize,
+    /// Whether the scan reached the latest confirmed block
+    pub caught_up: bool,
+}
+
+/// Watches deposit addresses and credits confirmed transfers into the ledger
+///
+/// Each transfer is credited at most once, keyed by chain, transaction hash
+/// and log index, so a restart or an overlapping scan never double-credits.
+/// Progress is kept per chain in Postgres; transfers are only credited once
+/// they have `confirmations` blocks, so shallow reorgs never reach the ledger.
+pub struct DepositWatcher {
+    db: Db,
+    rpc: EvmRpc,
+    chain_id: u64,
+    confirmations: u64,
+    deposit_addresses: Vec<String>,
+    start_block: Option<u64>,
+    tokens: Vec<DepositToken>,
+}
+
+impl DepositWatcher {
+    /// Watch the chain behind `rpc_url` as `config.deposits` describes
+    pub fn new(db: Db, rpc_url: &str, config: &Config) -> anyhow::Result<Self> {
+        let deposits: &DepositConfig = config
+            .deposits
+            .as_ref()
+            .context("No [deposits] section in config")?;
+        if deposits.deposit_addresses.is_empty() {
+            anyhow::bail!("No deposit addresses configured");
+        }
+        let tokens = deposit_tokens(config)?;
+
+        Ok(Self {
+            db,
+            rpc: EvmRpc::new(rpc_url),
+            chain_id: deposits.chain_id,
+            confirmations: deposits.confirmations.max(1),
+            deposit_addresses: deposits
+                .deposit_addresses
+                .iter()
+                .map(|address| address.to_lowercase())
+                .collect(),
+            start_block: deposits.start_block,
+            tokens,
+        })
+    }
+
+    /// Scan the next range of confirmed blocks and credit its transfers
+    ///
+    /// On a fresh database without `start_block`, scanning starts after the
+    /// current confirmed block.
+    pub async fn poll(&self) -> anyhow::Result<PollOutcome> {
+        let idle = PollOutcome {
+            credited: 0,
+            caught_up: true,
+        };
+        let head = self.rpc.block_number().await?;
+        let Some(confirmed) = (head + 1).checked_sub(self.confirmations) else {
+            return Ok(idle);
+        };
+
+        let from = match self.db.get_deposit_cursor(self.chain_id).await? {
+            Some(last_block) => last_block + 1,
+            None => match self.start_block {
+                Some(start_block) => start_block,
+                None => {
+                    self.db.set_deposit_cursor(self.chain_id, confirmed).await?;
+                    log::info!(
+                        "Watching chain {} for deposits after block {}",
+                        self.chain_id,
+                        confirmed
+                    );
+                    return Ok(idle);
+                }
+            },
+        };
+        if from > confirmed {
+            return Ok(idle);
+        }
+        let to = confirmed.min(from + MAX_BLOCK_RANGE - 1);
+
+        let contracts: Vec<String> = self.tokens.iter().map(|t| t.contract.clone()).collect();
+        let transfers = self
+            .rpc
+            .transfer_logs(&contracts, &self.deposit_addresses, from, to)
+            .await?;
+
+        let mut credited = 0;
+        for transfer in &transfers {
+            if self.credit(transfer).await? {
+                credited += 1;
+            }
+        }
+
+        // Only advance once every transfer in the range is credited
+        self.db.set_deposit_cursor(self.chain_id, to).await?;
+        Ok(PollOutcome {
+            credited,
+            caught_up: to == confirmed,
+        })
+    }
+
+    /// Credit one transfer, returning whether it was new
+    async fn credit(&self, transfer: &TransferLog) -> anyhow::Result<bool> {
+        let Some(token) = self
+            .tokens
+            .iter()
+            .find(|token| token.contract == transfer.contract)
+        else {
+            return Ok(false);
+        };
+        if !self.deposit_addresses.contains(&transfer.to) {
+            return Ok(false);
+        }
+        let amount = match token.to_exchange_amount(transfer.amount) {
+            Some(0) => return Ok(false),
+            Some(amount) => amount,
+            None => {
+                log::warn!(
+                    "Skipping deposit {}:{}, {} {} overflows",
+                    transfer.tx_hash,
+                    transfer.log_index,
+                    transfer.amount,
+                    token.ticker
+                );
+                return Ok(false);
+            }
+        };
+
+        let deposit = Deposit {
+            chain_id: self.chain_id,
+            tx_hash: transfer.tx_hash.clone(),
+            log_index: transfer.log_index,
+            block_number: transfer.block_number,
+            user_address: transfer.from.clone(),
+            token_ticker: token.ticker.clone(),
+            amount,
+            created_at: Utc::now(),
+        };
+        match self.db.credit_deposit(&deposit).await? {
+            Some((deposit, _)) => {
+                log::info!(
+                    "Credited deposit of {} {} to {} ({}:{})",
+                    deposit.amount,
+                    deposit.token_ticker,
+                    deposit.user_address,
+                    deposit.tx_hash,
+                    deposit.log_index
+                );
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Poll every `DEPOSIT_POLL_INTERVAL_SECS` until the process exits,
+    /// without waiting while catching up on a backlog of blocks
+    pub async fn run(self) {
+        log::info!(
+            "Watching {} deposit addresses on chain {}",
+            self.deposit_addresses.len(),
+            self.chain_id
+        );
+        let mut interval = tokio::time::interval(Duration::from_secs(DEPOSIT_POLL_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            loop {
+                match self.poll().await {
+                    Ok(outcome) if outcome.caught_up => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        log::warn!("Deposit scan of chain {} failed: {:#}", self.chain_id, e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// minimal EVM JSON-RPC client: the head block, ERC-20 transfer logs and receipts
+
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const TRANSFER_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// How long a node has to answer one call
+const RPC_TIMEOUT_SECS: u64 = 30;
+
+/// An ERC-20 `Transfer` event, with addresses in lowercase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferLog {
+    pub contract: String,
+    pub from: String,
+    pub to: String,
+    pub amount: u128,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+/// A log as `eth_getLogs` returns it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: String,
+    pub transaction_hash: String,
+    pub log_index: String,
+    /// Set on logs dropped by a reorg
+    #[serde(default)]
+    pub removed: bool,
+}
+
+impl RpcLog {
+    /// Decode a `Transfer` log
+    ///
+    /// Returns `None` for removed logs, other events, and amounts above
+    /// `u128::MAX`, which no token the exchange lists can reach.
+    pub fn transfer(&self) -> Option<TransferLog> {
+        if self.removed
+            || self.topics.len() != 3
+            || !self.topics[0].eq_ignore_ascii_case(TRANSFER_TOPIC)
+        {
+            return None;
+        }
+        Some(TransferLog {
+            contract: self.address.to_lowercase(),
+            from: topic_address(&self.topics[1])?,
+            to: topic_address(&self.topics[2])?,
+            amount: parse_hex_u128(&self.data)?,
+            block_number: parse_hex_u128(&self.block_number)?.try_into().ok()?,
+            tx_hash: self.transaction_hash.to_lowercase(),
+            log_index: parse_hex_u128(&self.log_index)?.try_into().ok()?,
+        })
+    }
+}
+
+/// Outcome of a mined transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionReceipt {
+    pub block_number: u64,
+    /// `false` if the transaction reverted
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcReceipt {
+    block_number: String,
+    status: String,
+}
+
+/// A 32-byte log topic holding `address`, for filtering on indexed addresses
+pub fn address_topic(address: &str) -> String {
+    let address = address.trim_start_matches("0x").to_lowercase();
+    format!("0x{:0>64}", address)
+}
+
+/// The address held in a 32-byte topic
+fn topic_address(topic: &str) -> Option<String> {
+    let hex = topic.strip_prefix("0x")?;
+    if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("0x{}", hex[24..].to_lowercase()))
+}
+
+/// Parse a `0x`-prefixed quantity, ignoring leading zeros
+pub fn parse_hex_u128(value: &str) -> Option<u128> {
+    let hex = value.strip_prefix("0x")?.trim_start_matches('0');
+    if hex.is_empty() {
+        return Some(0);
+    }
+    u128::from_str_radix(hex, 16).ok()
+}
+
+/// Talks to one node over HTTP
+#[derive(Debug, Clone)]
+pub struct EvmRpc {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl EvmRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(RPC_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+            url: url.into(),
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> anyhow::Result<T> {
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("{} failed: {}", method, error);
+        }
+        let result = response
+            .get("result")
+            .with_context(|| format!("{} returned no result", method))?;
+        serde_json::from_value(result.clone())
+            .with_context(|| format!("{} returned an unexpected result", method))
+    }
+
+    /// Number of the latest block
+    pub async fn block_number(&self) -> anyhow::Result<u64> {
+        let number: String = self.call("eth_blockNumber", json!([])).await?;
+        parse_hex_u128(&number)
+            .and_then(|n| n.try_into().ok())
+            .with_context(|| format!("Invalid block number '{}'", number))
+    }
+
+    /// Receipt of a transaction, or `None` while it is not mined
+    pub async fn transaction_receipt(
+        &self,
+        tx_hash: &str,
+    ) -> anyhow::Result<Option<TransactionReceipt>> {
+        let receipt: Option<RpcReceipt> = self
+            .call("eth_getTransactionReceipt", json!([tx_hash]))
+            .await?;
+        let Some(receipt) = receipt else {
+            return Ok(None);
+        };
+        let block_number = parse_hex_u128(&receipt.block_number)
+            .and_then(|n| n.try_into().ok())
+            .with_context(|| format!("Invalid block number '{}'", receipt.block_number))?;
+        Ok(Some(TransactionReceipt {
+            block_number,
+            success: parse_hex_u128(&receipt.status) == Some(1),
+        }))
+    }
+
+    /// Transfers of any of `contracts` to any of `recipients` in blocks `from..=to`
+    pub async fn transfer_logs(
+        &self,
+        contracts: &[String],
+        recipients: &[String],
+        from: u64,
+        to: u64,
+    ) -> anyhow::Result<Vec<TransferLog>> {
+        let recipients: Vec<String> = recipients.iter().map(|a| address_topic(a)).collect();
+        let filter = json!({
+            "fromBlock": format!("0x{:x}", from),
+            "toBlock": format!("0x{:x}", to),
+            "address": contracts,
+            "topics": [TRANSFER_TOPIC, Value::Null, recipients],
+        });
+        let logs: Vec<RpcLog> = self.call("eth_getLogs", json!([filter])).await?;
+        Ok(logs.iter().filter_map(RpcLog::transfer).collect())
+    }
+}
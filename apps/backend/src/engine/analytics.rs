@@ -0,0 +1,151 @@
+// writes executed trades to ClickHouse off the matching path
+
+use crate::alerts::{Alert, AlertKind, Alerter};
+use crate::db::Db;
+use crate::models::domain::Trade;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Trades buffered between the engine and the ClickHouse writer
+pub const ANALYTICS_BUFFER_SIZE: usize = 10_000;
+
+/// Most trades written to ClickHouse in a single insert
+const MAX_BATCH_SIZE: usize = 1_000;
+
+/// Counters for the analytics pipeline
+#[derive(Debug, Default)]
+pub struct AnalyticsStats {
+    /// Trades accepted into the buffer
+    pub enqueued: AtomicU64,
+    /// Trades dropped because the buffer was full
+    pub dropped: AtomicU64,
+    /// Trades written to ClickHouse
+    pub written: AtomicU64,
+    /// Trades lost to failed inserts
+    pub failed: AtomicU64,
+}
+
+impl AnalyticsStats {
+    /// Trades buffered or being written, not yet in ClickHouse or lost
+    pub fn queued(&self) -> u64 {
+        let settled = self.written.load(Ordering::Relaxed) + self.failed.load(Ordering::Relaxed);
+        self.enqueued
+            .load(Ordering::Relaxed)
+            .saturating_sub(settled)
+    }
+}
+
+/// Engine-side handle for queueing trades for ClickHouse
+///
+/// `record` never waits: when the buffer is full the trade is dropped and
+/// counted, so a slow or unavailable ClickHouse cannot delay order acks.
+/// Trades are already durable in PostgreSQL, ClickHouse only feeds candles.
+pub struct AnalyticsWriter {
+    trade_tx: mpsc::Sender<Trade>,
+    stats: Arc<AnalyticsStats>,
+}
+
+/// Background half of the analytics pipeline, run with [`AnalyticsTask::run`]
+pub struct AnalyticsTask {
+    trade_rx: mpsc::Receiver<Trade>,
+    stats: Arc<AnalyticsStats>,
+    alerts: Alerter,
+}
+
+impl AnalyticsWriter {
+    /// Create a writer and the task that drains it
+    pub fn new(capacity: usize) -> (Self, AnalyticsTask) {
+        let (trade_tx, trade_rx) = mpsc::channel(capacity);
+        let stats = Arc::new(AnalyticsStats::default());
+
+        (
+            Self {
+                trade_tx,
+                stats: Arc::clone(&stats),
+            },
+            AnalyticsTask {
+                trade_rx,
+                stats,
+                alerts: Alerter::default(),
+            },
+        )
+    }
+
+    /// Queue a trade for ClickHouse without blocking
+    pub fn record(&self, trade: Trade) {
+        match self.trade_tx.try_send(trade) {
+            Ok(()) => {
+                self.stats.enqueued.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Full(trade)) => {
+                let dropped = self.stats.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                log::warn!(
+                    "Analytics buffer full, dropped trade {} ({} dropped so far)",
+                    trade.id,
+                    dropped
+                );
+            }
+            Err(TrySendError::Closed(trade)) => {
+                self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                log::error!("Analytics writer stopped, dropped trade {}", trade.id);
+            }
+        }
+    }
+
+    /// Shared counters for monitoring
+    pub fn stats(&self) -> Arc<AnalyticsStats> {
+        Arc::clone(&self.stats)
+    }
+}
+
+impl AnalyticsTask {
+    /// Raise an alert when a batch fails to reach ClickHouse
+    pub fn with_alerts(mut self, alerts: Alerter) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    /// Write buffered trades to ClickHouse in batches
+    ///
+    /// Returns once every `AnalyticsWriter` is dropped and the buffer has been
+    /// flushed, so awaiting this after shutting the engine down loses nothing.
+    pub async fn run(mut self, db: Db) {
+        let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+
+        while let Some(trade) = self.trade_rx.recv().await {
+            // Take whatever else is already waiting, up to one batch
+            batch.push(trade);
+            while batch.len() < MAX_BATCH_SIZE {
+                match self.trade_rx.try_recv() {
+                    Ok(trade) => batch.push(trade),
+                    Err(_) => break,
+                }
+            }
+
+            let count = batch.len() as u64;
+            match db.insert_trades_to_clickhouse(&batch).await {
+                Ok(()) => {
+                    self.stats.written.fetch_add(count, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    self.stats.failed.fetch_add(count, Ordering::Relaxed);
+                    log::error!("Failed to write {} trades to ClickHouse: {}", count, e);
+                    self.alerts.raise(Alert::new(
+                        AlertKind::PersistenceFailure,
+                        "clickhouse",
+                        format!("Failed to write {} trades: {}", count, e),
+                    ));
+                }
+            }
+            batch.clear();
+        }
+
+        log::info!(
+            "Analytics writer stopped: {} written, {} dropped, {} failed",
+            self.stats.written.load(Ordering::Relaxed),
+            self.stats.dropped.load(Ordering::Relaxed),
+            self.stats.failed.load(Ordering::Relaxed)
+        );
+    }
+}
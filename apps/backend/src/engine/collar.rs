@@ -0,0 +1,88 @@
+// price collars around the index or last trade price
+
+use std::collections::HashMap;
+
+/// Per-market price collars, owned by the engine
+///
+/// A limit order priced more than `collar_bps` away from the market's last
+/// trade is rejected. Markets that have never traded have no reference price
+/// and accept any price. A fresh index price, passed to `check_with_index`,
+/// is used instead of the last trade.
+#[derive(Debug, Default)]
+pub struct PriceCollars {
+    // market_id -> collar from config.toml
+    configured: HashMap<String, u32>,
+    // market_id -> admin override, `None` disables the collar
+    overrides: HashMap<String, Option<u32>>,
+    // market_id -> last trade price
+    reference_prices: HashMap<String, u128>,
+}
+
+/// A price outside a market's collar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollarBreach {
+    pub reference_price: u128,
+    pub collar_bps: u32,
+}
+
+impl PriceCollars {
+    /// Set a market's collar from configuration
+    pub fn configure(&mut self, market_id: &str, collar_bps: u32) {
+        self.configured.insert(market_id.to_string(), collar_bps);
+    }
+
+    /// Override a market's configured collar; `None` disables it
+    pub fn set_override(&mut self, market_id: &str, collar_bps: Option<u32>) {
+        self.overrides.insert(market_id.to_string(), collar_bps);
+    }
+
+    /// The collar in effect for a market
+    pub fn collar_bps(&self, market_id: &str) -> Option<u32> {
+        match self.overrides.get(market_id) {
+            Some(collar) => *collar,
+            None => self.configured.get(market_id).copied(),
+        }
+    }
+
+    pub fn reference_price(&self, market_id: &str) -> Option<u128> {
+        self.reference_prices.get(market_id).copied()
+    }
+
+    /// Move a market's reference price to its latest trade
+    pub fn record_trade(&mut self, market_id: &str, price: u128) {
+        self.reference_prices.insert(market_id.to_string(), price);
+    }
+
+    /// Check a limit price against the market's collar
+    pub fn check(&self, market_id: &str, price: u128) -> Result<(), CollarBreach> {
+        self.check_with_index(market_id, price, None)
+    }
+
+    /// Check a limit price against the market's collar, measured from
+    /// `index_price` when there is one and from the last trade otherwise
+    pub fn check_with_index(
+        &self,
+        market_id: &str,
+        price: u128,
+        index_price: Option<u128>,
+    ) -> Result<(), CollarBreach> {
+        let (Some(collar_bps), Some(reference_price)) = (
+            self.collar_bps(market_id),
+            index_price.or_else(|| self.reference_price(market_id)),
+        ) else {
+            return Ok(());
+        };
+
+        // |price - reference| / reference > collar_bps / 10000, without dividing
+        let distance = price.abs_diff(reference_price);
+        let outside =
+            distance.saturating_mul(10000) > reference_price.saturating_mul(collar_bps as u128);
+        if outside {
+            return Err(CollarBreach {
+                reference_price,
+                collar_bps,
+            });
+        }
+        Ok(())
+    }
+}
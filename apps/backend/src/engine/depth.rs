@@ -0,0 +1,41 @@
+// depth metrics sampled from orderbook snapshots
+
+use crate::models::domain::{DepthMetrics, OrderbookLevel, OrderbookSnapshot};
+use crate::utils::imbalance_bps;
+use chrono::{DateTime, Utc};
+
+/// Price levels per side summed into a depth sample
+pub const DEPTH_METRICS_LEVELS: usize = 5;
+
+/// Seconds between depth samples of each market
+pub const DEPTH_METRICS_INTERVAL_SECS: u64 = 10;
+
+/// Measure the top `levels` price levels of each side of a snapshot
+///
+/// `timestamp` is when the sample is taken, not when the snapshot was built:
+/// an unchanged book is served from a snapshot cached many ticks ago, and it
+/// still counts as quoted at every sample.
+pub fn depth_metrics(
+    snapshot: &OrderbookSnapshot,
+    levels: usize,
+    timestamp: DateTime<Utc>,
+) -> DepthMetrics {
+    let bid_depth = total_size(&snapshot.bids, levels);
+    let ask_depth = total_size(&snapshot.asks, levels);
+
+    DepthMetrics {
+        market_id: snapshot.market_id.clone(),
+        timestamp,
+        best_bid: snapshot.bids.first().map(|level| level.price),
+        best_ask: snapshot.asks.first().map(|level| level.price),
+        bid_depth,
+        ask_depth,
+        imbalance_bps: imbalance_bps(bid_depth, ask_depth),
+    }
+}
+
+fn total_size(side: &[OrderbookLevel], levels: usize) -> u128 {
+    side.iter()
+        .take(levels)
+        .fold(0u128, |total, level| total.saturating_add(level.size))
+}
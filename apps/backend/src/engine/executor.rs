@@ -1,10 +1,17 @@
 // executes trades and persists to database
 
+use crate::db::balances::BalanceChanges;
 use crate::db::Db;
-use crate::errors::Result;
-use crate::models::domain::{Market, Match, Order, OrderStatus, Side, Trade};
+use crate::engine::fees::FeeOverrides;
+use crate::engine::routing::FeeRouting;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{
+    LedgerEntryKind, LedgerPosting, Market, Match, Order, OrderStatus, Position, Referral,
+    ReferralPayout, RevenueSource, Side, SystemAccount, Trade, TradeFee,
+};
+use crate::perps;
 use chrono::Utc;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 pub struct Executor;
@@ -12,19 +19,56 @@ pub struct Executor;
 /// Tracks affected balances that need to be broadcast after request completes
 pub type AffectedBalances = HashSet<(String, String)>; // (user_address, token_ticker)
 
+/// How the matches of an order settle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Base tokens are exchanged for quote tokens
+    Spot,
+    /// Both sides' positions change; orders locked `initial_margin_bps` of their notional
+    Perpetual { initial_margin_bps: u32 },
+    /// Like `Perpetual`, but the taker is closing a position the exchange
+    /// liquidated and locked nothing for the order
+    Liquidation { initial_margin_bps: u32 },
+}
+
+impl ExecutionMode {
+    /// Initial margin of a perpetual market, `None` for spot
+    fn initial_margin_bps(self) -> Option<u32> {
+        match self {
+            ExecutionMode::Spot => None,
+            ExecutionMode::Perpetual { initial_margin_bps }
+            | ExecutionMode::Liquidation { initial_margin_bps } => Some(initial_margin_bps),
+        }
+    }
+}
+
 impl Executor {
     /// Execute a vector of matches
     /// - Creates trade records
     /// - Updates order fill status
-    /// - Calculates and applies fees
-    /// - Unlocks and transfers balances
-    /// - Persists everything to database atomically
+    /// - Calculates and applies fees, with each side's override if it has one,
+    ///   routing them between the system accounts
+    /// - Pays maker rebates and the taker's referrer, if any, from the fee collector
+    /// - Records every system account change in the ledger
+    /// - Unlocks and transfers balances, or in a perpetual market updates both
+    ///   sides' positions and the margin locked for them
+    /// - Persists everything to database atomically, batched per table
     /// - Returns the executed trades and affected balances
+    #[tracing::instrument(
+        name = "engine.execute",
+        skip_all,
+        fields(order_id = %taker_order.id, matches = matches.len())
+    )]
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute(
         db: Db,
-        matches: Vec<Match>,
+        matches: &[Match],
         taker_order: &Order,
         market: &Market,
+        referral: Option<&Referral>,
+        routing: &FeeRouting,
+        fees: &FeeOverrides,
+        mode: ExecutionMode,
     ) -> Result<(Vec<Trade>, AffectedBalances)> {
         if matches.is_empty() {
             return Ok((vec![], HashSet::new()));
@@ -34,12 +78,46 @@ impl Executor {
         let base_token = db.get_token(&market.base_ticker).await?;
         let base_decimals_divisor = 10u128.pow(base_token.decimals as u32);
 
-        // Begin transaction for atomic execution
-        let mut tx = db.begin_transaction().await?;
+        // Rebates are paid out of the collector's balance and never take it below zero
+        let mut settlement = Settlement::default();
+        let rebates = matches
+            .iter()
+            .any(|m| fees.maker_fee_bps(market, &m.maker_order.user_address) < 0);
+        if rebates {
+            let collector = SystemAccount::FeeCollector.address();
+            for ticker in [&market.base_ticker, &market.quote_ticker] {
+                let available = match db.get_balance(collector, ticker).await {
+                    Ok(balance) => balance.amount,
+                    Err(ExchangeError::BalanceNotFound { .. }) => 0,
+                    Err(e) => return Err(e),
+                };
+                settlement.collector_funds.insert(ticker.clone(), available);
+            }
+        }
+
+        // Positions of everyone trading; only the engine changes their size and margin
+        let perpetual = mode != ExecutionMode::Spot;
+        let initial_margin_bps = mode.initial_margin_bps().unwrap_or(10_000);
+        let mut positions = if perpetual {
+            let mut users: Vec<&str> = matches
+                .iter()
+                .map(|m| m.maker_order.user_address.as_str())
+                .collect();
+            users.push(&taker_order.user_address);
+            db.get_positions(&market.id, &users).await?
+        } else {
+            HashMap::new()
+        };
+        let collateral_fee_bps = perps::collateral_fee_bps(market);
+        let mut taker_filled = taker_order.filled_size;
+
+        // Work out every trade, balance change and order fill in memory first
         let mut trades = Vec::new();
+        let mut trade_fees = Vec::with_capacity(matches.len() * 2);
+        let mut referral_payouts = Vec::new();
+        let mut order_fills = Vec::with_capacity(matches.len() + 1);
 
-        // Process each match within transaction
-        for m in &matches {
+        for m in matches {
             let maker_order = &m.maker_order;
 
             // Determine buyer and seller based on sides
@@ -71,6 +149,7 @@ impl Executor {
                 size: m.size,
                 side: taker_order.side, // Trade side is the taker's side
                 timestamp: Utc::now(),
+                rfq: false,
             };
 
             // Calculate trade value in quote tokens
@@ -86,99 +165,168 @@ impl Executor {
             // Calculate fees (charged on what each party receives)
             // Buyer receives base tokens (size), pays taker fee if taker, maker fee if maker
             // Seller receives quote tokens (price * size), pays maker fee if maker, taker fee if taker
+            // A negative maker fee is a rebate added to what the maker receives
+            let maker_fee_bps = fees.maker_fee_bps(market, &maker_order.user_address);
+            let taker_fee_bps = fees.taker_fee_bps(market, &taker_order.user_address);
             let (buyer_fee_bps, seller_fee_bps) = match taker_order.side {
                 Side::Buy => {
                     // Buyer is taker, seller is maker
-                    (market.taker_fee_bps, market.maker_fee_bps)
+                    (taker_fee_bps, maker_fee_bps)
                 }
                 Side::Sell => {
                     // Seller is taker, buyer is maker
-                    (market.maker_fee_bps, market.taker_fee_bps)
+                    (maker_fee_bps, taker_fee_bps)
+                }
+            };
+
+            let base = &market.base_ticker;
+            let quote = &market.quote_ticker;
+
+            let (buyer_fee, buyer_fee_ticker) = if perpetual {
+                // Both sides pay fees on the notional, in quote tokens
+                let buyer_fee = quote_amount as i128 * buyer_fee_bps as i128 / 10000;
+
+                // Each side's order locked collateral for this fill; what the
+                // position doesn't keep as margin is released. A liquidation
+                // locked nothing, and only releases the closed margin.
+                for (order, filled, locked) in [
+                    (maker_order.as_ref(), maker_order.filled_size, true),
+                    (
+                        taker_order,
+                        taker_filled,
+                        !matches!(mode, ExecutionMode::Liquidation { .. }),
+                    ),
+                ] {
+                    let position =
+                        positions
+                            .entry(order.user_address.clone())
+                            .or_insert_with(|| Position {
+                                user_address: order.user_address.clone(),
+                                market_id: market.id.clone(),
+                                ..Default::default()
+                            });
+                    let effect = perps::apply_fill(
+                        position,
+                        order.side,
+                        m.size,
+                        m.price,
+                        order.price,
+                        base_decimals_divisor,
+                        initial_margin_bps,
+                    );
+                    let collateral = |size| {
+                        if !locked {
+                            return Some(0);
+                        }
+                        perps::order_collateral(
+                            order.price,
+                            size,
+                            base_decimals_divisor,
+                            initial_margin_bps,
+                            collateral_fee_bps,
+                        )
+                    };
+                    let released = effect.zip(collateral(filled)).and_then(|(effect, before)| {
+                        collateral(filled + m.size)?
+                            .checked_sub(before)?
+                            .checked_sub(effect.margin_added)?
+                            .checked_add(effect.margin_released)
+                    });
+                    let released = released.ok_or_else(|| ExchangeError::InvalidParameter {
+                        message: "Position value overflow or calculation error".to_string(),
+                    })?;
+                    settlement
+                        .balance_changes
+                        .entry((order.user_address.clone(), quote.clone()))
+                        .or_default()
+                        .unlock += released;
                 }
+                (buyer_fee, quote)
+            } else {
+                // Fee on base tokens (for buyer)
+                let buyer_fee = m.size as i128 * buyer_fee_bps as i128 / 10000;
+
+                // Buyer locked quote_amount, seller locked size when their orders were placed
+                // Buyer: release locked quote, pay quote, receive base
+                let buyer_quote = settlement
+                    .balance_changes
+                    .entry((buyer_address.clone(), quote.clone()))
+                    .or_default();
+                buyer_quote.unlock += quote_amount;
+                buyer_quote.debit += quote_amount;
+                settlement
+                    .balance_changes
+                    .entry((buyer_address.clone(), base.clone()))
+                    .or_default()
+                    .credit += m.size;
+
+                // Seller: release locked base, pay base, receive quote
+                let seller_base = settlement
+                    .balance_changes
+                    .entry((seller_address.clone(), base.clone()))
+                    .or_default();
+                seller_base.unlock += m.size;
+                seller_base.debit += m.size;
+                settlement
+                    .balance_changes
+                    .entry((seller_address.clone(), quote.clone()))
+                    .or_default()
+                    .credit += quote_amount;
+
+                (buyer_fee, base)
             };
+            taker_filled += m.size;
 
-            // Fee on base tokens (for buyer)
-            let buyer_fee = (m.size as i128 * buyer_fee_bps as i128 / 10000) as u128;
             // Fee on quote tokens (for seller)
-            let seller_fee = (quote_amount as i128 * seller_fee_bps as i128 / 10000) as u128;
-
-            // Fee recipient address (hardcoded in db schema)
-            const FEE_RECIPIENT: &str = "system";
-
-            // Calculate amounts to unlock (what was locked when orders were placed)
-            // Buyer locked quote_amount, seller locked size
-            let buyer_unlock_amount = quote_amount;
-            let seller_unlock_amount = m.size;
-
-            // Unlock the locked amounts for both parties
-            db.unlock_balance_tx(
-                &mut tx,
-                &buyer_address,
-                &market.quote_ticker,
-                buyer_unlock_amount,
-            )
-            .await?;
-            db.unlock_balance_tx(
-                &mut tx,
-                &seller_address,
-                &market.base_ticker,
-                seller_unlock_amount,
-            )
-            .await?;
-
-            // Transfer base tokens: seller -> buyer (minus buyer's fee)
-            db.subtract_balance_tx(&mut tx, &seller_address, &market.base_ticker, m.size)
-                .await?;
-            let buyer_receives_base = m.size - buyer_fee;
-            db.add_balance_tx(
-                &mut tx,
-                &buyer_address,
-                &market.base_ticker,
-                buyer_receives_base,
-            )
-            .await?;
-
-            // Send buyer's fee to fee recipient (base tokens)
-            if buyer_fee > 0 {
-                db.add_balance_tx(&mut tx, FEE_RECIPIENT, &market.base_ticker, buyer_fee)
-                    .await?;
+            let seller_fee = quote_amount as i128 * seller_fee_bps as i128 / 10000;
+
+            // Settle fees with the system accounts
+            for (user_address, ticker, fee) in [
+                (&buyer_address, buyer_fee_ticker, buyer_fee),
+                (&seller_address, quote, seller_fee),
+            ] {
+                let fee = settlement.settle_fee(routing, trade.id, user_address, ticker, fee);
+                if fee != 0 {
+                    trade_fees.push(TradeFee {
+                        trade_id: trade.id,
+                        user_address: user_address.clone(),
+                        token_ticker: ticker.clone(),
+                        fee,
+                    });
+                }
             }
 
-            // Transfer quote tokens: buyer -> seller (minus seller's fee)
-            db.subtract_balance_tx(&mut tx, &buyer_address, &market.quote_ticker, quote_amount)
-                .await?;
-            let seller_receives_quote = quote_amount - seller_fee;
-            db.add_balance_tx(
-                &mut tx,
-                &seller_address,
-                &market.quote_ticker,
-                seller_receives_quote,
-            )
-            .await?;
-
-            // Send seller's fee to fee recipient (quote tokens)
-            if seller_fee > 0 {
-                db.add_balance_tx(&mut tx, FEE_RECIPIENT, &market.quote_ticker, seller_fee)
-                    .await?;
+            // The referrer's share comes out of the taker fee the collector just received
+            if let Some(referral) = referral {
+                let (ticker, taker_fee) = match taker_order.side {
+                    Side::Buy => (buyer_fee_ticker, buyer_fee),
+                    Side::Sell => (quote, seller_fee),
+                };
+                let share = settlement.pay_referral(routing, trade.id, referral, ticker, taker_fee);
+                if share > 0 {
+                    referral_payouts.push(ReferralPayout {
+                        trade_id: trade.id,
+                        referrer_address: referral.referrer_address.clone(),
+                        user_address: referral.user_address.clone(),
+                        token_ticker: ticker.clone(),
+                        amount: share,
+                    });
+                }
             }
 
-            // Update maker order fill status (in transaction)
+            // Maker order fill status
             let maker_new_filled = maker_order.filled_size + m.size;
             let maker_status = if maker_new_filled >= maker_order.size {
                 OrderStatus::Filled
             } else {
                 OrderStatus::PartiallyFilled
             };
-            db.update_order_fill_tx(&mut tx, maker_order.id, maker_new_filled, maker_status)
-                .await?;
-
-            // Insert trade into PostgreSQL (in transaction)
-            db.create_trade_tx(&mut tx, &trade).await?;
+            order_fills.push((maker_order.id, maker_new_filled, maker_status));
 
             trades.push(trade);
         }
 
-        // Update taker order fill status (in transaction)
+        // Taker order fill status
         let taker_total_filled: u128 = matches.iter().map(|m| m.size).sum();
         let taker_new_filled = taker_order.filled_size + taker_total_filled;
         let taker_status = if taker_new_filled >= taker_order.size {
@@ -188,38 +336,204 @@ impl Executor {
         } else {
             OrderStatus::Pending
         };
-        db.update_order_fill_tx(&mut tx, taker_order.id, taker_new_filled, taker_status)
-            .await?;
+        order_fills.push((taker_order.id, taker_new_filled, taker_status));
+
+        if let Some(memory) = &db.memory {
+            // Simulated markets are spot only and keep no ledger or referral history
+            memory.settle(
+                &settlement.balance_changes,
+                &order_fills,
+                &trades,
+                &trade_fees,
+            )?;
+        } else {
+            // Persist the whole batch with one statement per table, however many makers were hit
+            let mut tx = db.begin_transaction().await?;
+            db.apply_balance_changes_tx(&mut tx, &settlement.balance_changes)
+                .await?;
+            db.update_order_fills_tx(&mut tx, &order_fills).await?;
+            db.create_trades_tx(&mut tx, &trades).await?;
+            db.create_trade_fees_tx(&mut tx, &trade_fees).await?;
+            db.create_referral_payouts_tx(&mut tx, &referral_payouts)
+                .await?;
+            db.create_ledger_entries_tx(&mut tx, &settlement.ledger)
+                .await?;
+            let positions: Vec<Position> = positions.into_values().collect();
+            db.upsert_positions_tx(&mut tx, &positions).await?;
 
-        // Commit transaction - all or nothing!
-        tx.commit().await?;
+            // Commit transaction - all or nothing!
+            db.timed("commit_settlement", tx.commit()).await?;
+        }
 
         // Collect affected balances (to be broadcast by engine after request completes)
         let mut affected_balances = HashSet::new();
         for trade in &trades {
-            // Buyer balances (base and quote tokens)
-            affected_balances.insert((trade.buyer_address.clone(), market.base_ticker.clone()));
-            affected_balances.insert((trade.buyer_address.clone(), market.quote_ticker.clone()));
+            // Buyer and seller quote balances, and base balances unless perpetual
+            for user_address in [&trade.buyer_address, &trade.seller_address] {
+                affected_balances.insert((user_address.clone(), market.quote_ticker.clone()));
+                if !perpetual {
+                    affected_balances.insert((user_address.clone(), market.base_ticker.clone()));
+                }
+            }
+        }
+        // System account balances (fee collector, insurance fund)
+        for posting in &settlement.ledger {
+            affected_balances.insert((
+                posting.account.address().to_string(),
+                posting.token_ticker.clone(),
+            ));
+        }
+        for payout in &referral_payouts {
+            affected_balances
+                .insert((payout.referrer_address.clone(), payout.token_ticker.clone()));
+        }
 
-            // Seller balances (base and quote tokens)
-            affected_balances.insert((trade.seller_address.clone(), market.base_ticker.clone()));
-            affected_balances.insert((trade.seller_address.clone(), market.quote_ticker.clone()));
+        Ok((trades, affected_balances))
+    }
+}
 
-            // System fee recipient balances (base and quote tokens)
-            affected_balances.insert(("system".to_string(), market.base_ticker.clone()));
-            affected_balances.insert(("system".to_string(), market.quote_ticker.clone()));
+/// Balance changes for a batch, with every system account change posted to the ledger
+#[derive(Default)]
+struct Settlement {
+    balance_changes: BalanceChanges,
+    ledger: Vec<LedgerPosting>,
+    // Fee collector balance available for rebates, per token; only tracked for rebate markets
+    collector_funds: HashMap<String, u128>,
+}
+
+impl Settlement {
+    /// Credit (positive) or debit (negative) a system account and record it in the ledger
+    fn post(
+        &mut self,
+        account: SystemAccount,
+        ticker: &str,
+        amount: i128,
+        kind: LedgerEntryKind,
+        trade_id: Uuid,
+    ) {
+        if amount == 0 {
+            return;
+        }
+        let change = self
+            .balance_changes
+            .entry((account.address().to_string(), ticker.to_string()))
+            .or_default();
+        if amount > 0 {
+            change.credit += amount.unsigned_abs();
+        } else {
+            change.debit += amount.unsigned_abs();
+        }
+        if account == SystemAccount::FeeCollector {
+            if let Some(available) = self.collector_funds.get_mut(ticker) {
+                *available = available.saturating_add_signed(amount);
+            }
+        }
+        self.ledger.push(LedgerPosting {
+            account,
+            token_ticker: ticker.to_string(),
+            amount,
+            kind,
+            trade_id: Some(trade_id),
+        });
+    }
+
+    /// Move a fee between a user and the system accounts; returns the fee actually applied
+    ///
+    /// Positive fees are taken from what the user receives and split between the
+    /// fee collector and insurance fund by the trading fee route. Negative fees
+    /// (maker rebates) are paid from the collector's funds for `ticker`, as far
+    /// as they go: a collector that runs dry pays a partial or zero rebate rather
+    /// than failing the trade.
+    fn settle_fee(
+        &mut self,
+        routing: &FeeRouting,
+        trade_id: Uuid,
+        user_address: &str,
+        ticker: &str,
+        fee: i128,
+    ) -> i128 {
+        if fee > 0 {
+            self.balance_changes
+                .entry((user_address.to_string(), ticker.to_string()))
+                .or_default()
+                .debit += fee.unsigned_abs();
+            let (collector, insurance) =
+                routing.split(RevenueSource::TradingFees, fee.unsigned_abs());
+            let kind = LedgerEntryKind::TradingFee;
+            self.post(
+                SystemAccount::FeeCollector,
+                ticker,
+                collector as i128,
+                kind,
+                trade_id,
+            );
+            self.post(
+                SystemAccount::InsuranceFund,
+                ticker,
+                insurance as i128,
+                kind,
+                trade_id,
+            );
+            return fee;
         }
 
-        // Insert trades into ClickHouse asynchronously (after commit)
-        // This is non-critical, so failures won't affect the core trade execution
-        for trade in &trades {
-            let db_clone = db.clone();
-            let trade_clone = trade.clone();
-            tokio::spawn(async move {
-                let _ = db_clone.insert_trade_to_clickhouse(&trade_clone).await;
-            });
+        let available = self.collector_funds.get(ticker).copied().unwrap_or(0);
+        let rebate = fee.unsigned_abs().min(available);
+        if rebate < fee.unsigned_abs() {
+            log::warn!(
+                "Fee collector is short of {}, paid {} of a {} rebate to {}",
+                ticker,
+                rebate,
+                fee.unsigned_abs(),
+                user_address
+            );
+        }
+        if rebate == 0 {
+            return 0;
         }
+        self.balance_changes
+            .entry((user_address.to_string(), ticker.to_string()))
+            .or_default()
+            .credit += rebate;
+        self.post(
+            SystemAccount::FeeCollector,
+            ticker,
+            -(rebate as i128),
+            LedgerEntryKind::MakerRebate,
+            trade_id,
+        );
+        -(rebate as i128)
+    }
 
-        Ok((trades, affected_balances))
+    /// Pay a referrer their share of a taker fee from the fee collector; returns the amount paid
+    ///
+    /// The share is capped at the collector's part of the fee, so routing to the
+    /// insurance fund is never undone by a referral.
+    fn pay_referral(
+        &mut self,
+        routing: &FeeRouting,
+        trade_id: Uuid,
+        referral: &Referral,
+        ticker: &str,
+        taker_fee: i128,
+    ) -> u128 {
+        let fee = taker_fee.max(0) as u128;
+        let (collector, _) = routing.split(RevenueSource::TradingFees, fee);
+        let share = (fee * referral.share_bps as u128 / 10000).min(collector);
+        if share == 0 {
+            return 0;
+        }
+        self.balance_changes
+            .entry((referral.referrer_address.clone(), ticker.to_string()))
+            .or_default()
+            .credit += share;
+        self.post(
+            SystemAccount::FeeCollector,
+            ticker,
+            -(share as i128),
+            LedgerEntryKind::ReferralPayout,
+            trade_id,
+        );
+        share
     }
 }
@@ -0,0 +1,85 @@
+// good-till-date orders waiting for their expiry
+
+use super::executor::AffectedBalances;
+use super::MatchingEngine;
+use crate::models::domain::{CancelReason, Order};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often the engine cancels orders that have expired
+pub const EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Open orders with an `expires_at`, soonest first, owned by the engine
+///
+/// Entries aren't removed when their order fills or is cancelled; when they
+/// come due, orders no longer on a book or waiting for a trigger are skipped.
+#[derive(Debug, Default)]
+pub struct ExpiryQueue {
+    // (expires_at, order_id) -> user_address
+    orders: BTreeMap<(DateTime<Utc>, Uuid), String>,
+}
+
+impl ExpiryQueue {
+    pub fn new(orders: Vec<Order>) -> Self {
+        let mut queue = Self::default();
+        for order in &orders {
+            queue.insert(order);
+        }
+        queue
+    }
+
+    /// Wait for an order's expiry; orders without one are ignored
+    pub fn insert(&mut self, order: &Order) {
+        if let Some(expires_at) = order.expires_at {
+            self.orders
+                .insert((expires_at, order.id), order.user_address.clone());
+        }
+    }
+
+    /// Remove and return the orders whose expiry is at or before `now`,
+    /// soonest first, with the user each was placed by
+    pub fn take_expired(&mut self, now: DateTime<Utc>) -> Vec<(Uuid, String)> {
+        let mut expired = Vec::new();
+        while let Some(entry) = self.orders.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let ((_, order_id), user_address) = entry.remove_entry();
+            expired.push((order_id, user_address));
+        }
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+impl MatchingEngine {
+    /// Cancel the resting and stop orders whose expiry has passed
+    ///
+    /// Expired orders are taken off their book or out of the trigger book,
+    /// then unlocked, marked cancelled and broadcast like any other
+    /// exchange cancel, with [`CancelReason::Expired`].
+    pub(super) async fn expire_orders(&mut self, affected: &mut AffectedBalances) {
+        let due = self.expiries.take_expired(Utc::now());
+        if due.is_empty() {
+            return;
+        }
+
+        let expired = self.take_open_orders(due).await;
+        if expired.is_empty() {
+            return;
+        }
+
+        log::info!("Expiring {} orders", expired.len());
+        self.settle_cancelled_orders(expired, Some(CancelReason::Expired), affected)
+            .await;
+    }
+}
@@ -0,0 +1,58 @@
+// per-user fee overrides
+
+use crate::models::domain::{FeeOverride, Market};
+use std::collections::HashMap;
+
+/// Fees users pay instead of their market's, owned by the engine
+///
+/// Users without an override, or sides an override leaves out, pay the
+/// market's fee.
+#[derive(Debug, Default)]
+pub struct FeeOverrides {
+    // By user, then market
+    overrides: HashMap<String, HashMap<String, FeeOverride>>,
+}
+
+impl FeeOverrides {
+    pub fn new(overrides: Vec<FeeOverride>) -> Self {
+        let mut fees = Self::default();
+        for fee_override in overrides {
+            fees.set(fee_override);
+        }
+        fees
+    }
+
+    pub fn set(&mut self, fee_override: FeeOverride) {
+        self.overrides
+            .entry(fee_override.user_address.clone())
+            .or_default()
+            .insert(fee_override.market_id.clone(), fee_override);
+    }
+
+    pub fn clear(&mut self, user_address: &str, market_id: &str) {
+        if let Some(markets) = self.overrides.get_mut(user_address) {
+            markets.remove(market_id);
+            if markets.is_empty() {
+                self.overrides.remove(user_address);
+            }
+        }
+    }
+
+    fn get(&self, market: &Market, user_address: &str) -> Option<&FeeOverride> {
+        self.overrides.get(user_address)?.get(&market.id)
+    }
+
+    /// Fee a user pays when their order rests and is hit, negative for a rebate
+    pub fn maker_fee_bps(&self, market: &Market, user_address: &str) -> i32 {
+        self.get(market, user_address)
+            .and_then(|fee_override| fee_override.maker_fee_bps)
+            .unwrap_or(market.maker_fee_bps)
+    }
+
+    /// Fee a user pays when their order takes liquidity
+    pub fn taker_fee_bps(&self, market: &Market, user_address: &str) -> i32 {
+        self.get(market, user_address)
+            .and_then(|fee_override| fee_override.taker_fee_bps)
+            .unwrap_or(market.taker_fee_bps)
+    }
+}
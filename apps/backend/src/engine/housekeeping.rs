@@ -0,0 +1,83 @@
+// periodic engine upkeep, run between requests
+
+use super::executor::AffectedBalances;
+use super::MatchingEngine;
+use crate::models::domain::MarketStatus;
+use std::time::Duration;
+
+/// How often the engine tidies up between requests
+pub const HOUSEKEEPING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What one housekeeping pass did, and the engine's state after it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HousekeepingReport {
+    /// Stop orders the re-check against the latest prices triggered
+    pub stops_triggered: usize,
+    /// Empty books of delisted markets dropped
+    pub books_pruned: usize,
+    /// Markets with a book in memory
+    pub books: usize,
+    /// Orders resting across all books
+    pub resting_orders: usize,
+    /// Stop orders waiting for their trigger
+    pub stop_orders: usize,
+    /// Requests waiting in the engine queue
+    pub queued_requests: usize,
+}
+
+impl MatchingEngine {
+    /// Run one housekeeping pass
+    ///
+    /// Stop orders are checked again against the latest last trade and index
+    /// prices, which catches ones loaded at startup whose trigger was already
+    /// reached while nothing has traded since. Delisted markets never take
+    /// orders again, so their emptied books are dropped rather than kept (and
+    /// snapshotted) forever. The pass is reported on an `engine.housekeeping`
+    /// span for the trace collector.
+    pub(super) async fn housekeeping(
+        &mut self,
+        affected: &mut AffectedBalances,
+    ) -> HousekeepingReport {
+        self.refresh_index_prices();
+        self.triggers.recheck_all();
+        let stops_triggered = self.activate_stop_orders(affected).await;
+
+        let mut orderbooks = self.orderbooks.write().await;
+        let inactive_markets = &self.inactive_markets;
+        let books_pruned = orderbooks.prune_empty(|market_id| {
+            inactive_markets.get(market_id) == Some(&MarketStatus::Delisted)
+        });
+
+        let report = HousekeepingReport {
+            stops_triggered,
+            books_pruned,
+            books: orderbooks.book_count(),
+            resting_orders: orderbooks.resting_order_count(),
+            stop_orders: self.triggers.len(),
+            queued_requests: self.engine_rx.len(),
+        };
+        drop(orderbooks);
+
+        let _span = tracing::info_span!(
+            "engine.housekeeping",
+            stops_triggered = report.stops_triggered,
+            books_pruned = report.books_pruned,
+            books = report.books,
+            resting_orders = report.resting_orders,
+            stop_orders = report.stop_orders,
+            queued_requests = report.queued_requests,
+        )
+        .entered();
+        log::debug!(
+            "Housekeeping: {} stops triggered, {} books ({} pruned), {} resting orders, {} stop orders, {} queued requests",
+            report.stops_triggered,
+            report.books,
+            report.books_pruned,
+            report.resting_orders,
+            report.stop_orders,
+            report.queued_requests
+        );
+
+        report
+    }
+}
@@ -0,0 +1,42 @@
+// checks the books must pass after every request
+
+use super::executor::AffectedBalances;
+use super::MatchingEngine;
+use crate::alerts::{Alert, AlertKind};
+use crate::models::domain::MarketStatus;
+
+impl MatchingEngine {
+    /// Halt every market whose resting book is crossed
+    ///
+    /// A crossed book would match orders that should already have traded, so
+    /// it's never repaired in place: the market is halted, which cancels its
+    /// resting orders and returns their locked funds, and an operator decides
+    /// when to reopen it.
+    pub(super) async fn halt_crossed_books(&mut self, affected: &mut AffectedBalances) {
+        let crossed = self.orderbooks.read().await.crossed_books();
+        for (market_id, best_bid, best_ask) in crossed {
+            log::error!(
+                "CRITICAL: book of {} is crossed (best bid {} >= best ask {}), halting it",
+                market_id,
+                best_bid,
+                best_ask
+            );
+            self.alerts.raise(Alert::new(
+                AlertKind::CrossedBook,
+                &market_id,
+                format!(
+                    "Book crossed at bid {} / ask {}; market halted",
+                    best_bid, best_ask
+                ),
+            ));
+
+            let (result, halted) = self
+                .handle_set_market_status(market_id.clone(), MarketStatus::Halted)
+                .await;
+            affected.extend(halted);
+            if let Err(e) = result {
+                log::error!("Failed to halt crossed market {}: {}", market_id, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// exchange-wide and per-market cancel-only switches
+
+use crate::models::domain::KillSwitch;
+
+/// In-memory copy of the `kill_switches` table, owned by the engine
+#[derive(Debug, Default)]
+pub struct KillSwitches {
+    switches: Vec<KillSwitch>,
+}
+
+impl KillSwitches {
+    pub fn new(switches: Vec<KillSwitch>) -> Self {
+        let mut book = Self::default();
+        for switch in switches {
+            book.engage(switch);
+        }
+        book
+    }
+
+    /// The switch keeping a market cancel-only, if any; the exchange-wide one wins
+    pub fn blocking(&self, market_id: &str) -> Option<&KillSwitch> {
+        self.switches
+            .iter()
+            .find(|s| s.market_id.is_none())
+            .or_else(|| {
+                self.switches
+                    .iter()
+                    .find(|s| s.market_id.as_deref() == Some(market_id))
+            })
+    }
+
+    /// Engage a switch, replacing any existing one for the same scope
+    pub fn engage(&mut self, switch: KillSwitch) {
+        self.release(switch.market_id.as_deref());
+        self.switches.push(switch);
+    }
+
+    /// Release the switch for a scope; returns whether one was engaged
+    pub fn release(&mut self, market_id: Option<&str>) -> bool {
+        let before = self.switches.len();
+        self.switches
+            .retain(|s| s.market_id.as_deref() != market_id);
+        self.switches.len() != before
+    }
+}
@@ -0,0 +1,170 @@
+// price-indexed storage for one side of an orderbook
+
+use crate::models::domain::Order;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+/// Most slots an array ladder may allocate per side
+pub const MAX_LADDER_SLOTS: u128 = 1_000_000;
+
+/// Resting orders at a single price, oldest first
+pub type Queue = VecDeque<Arc<Order>>;
+
+/// How a market's resting orders are indexed by price
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LadderLayout {
+    /// Sorted map of populated prices - any price, O(log n) level access
+    #[default]
+    Tree,
+    /// One slot per tick from `min_price` to `max_price` inclusive - O(1)
+    /// level access and contiguous sweeps, for markets with a bounded price
+    /// range such as prediction markets priced 0-1
+    Array {
+        min_price: u128,
+        max_price: u128,
+        tick_size: u128,
+    },
+}
+
+impl LadderLayout {
+    /// Whether an order at `price` can rest in this layout
+    pub fn accepts_price(&self, price: u128) -> bool {
+        match *self {
+            LadderLayout::Tree => true,
+            LadderLayout::Array {
+                min_price,
+                max_price,
+                tick_size,
+            } => {
+                (min_price..=max_price).contains(&price)
+                    && (price - min_price).is_multiple_of(tick_size)
+            }
+        }
+    }
+}
+
+/// One side of an orderbook: price levels, each holding a FIFO queue
+pub enum PriceLadder {
+    Tree(BTreeMap<u128, Queue>),
+    Array(ArrayLadder),
+}
+
+/// Dense ladder over a bounded price range
+pub struct ArrayLadder {
+    min_price: u128,
+    tick_size: u128,
+    levels: Vec<Queue>,
+    // Slots outside low..=high have never held an order, so iteration skips them
+    low: usize,
+    high: usize,
+}
+
+impl PriceLadder {
+    pub fn new(layout: LadderLayout) -> Self {
+        match layout {
+            LadderLayout::Tree => PriceLadder::Tree(BTreeMap::new()),
+            LadderLayout::Array {
+                min_price,
+                max_price,
+                tick_size,
+            } => {
+                assert!(
+                    tick_size > 0 && min_price <= max_price,
+                    "invalid price ladder range"
+                );
+                let slots = ((max_price - min_price) / tick_size + 1) as usize;
+                PriceLadder::Array(ArrayLadder {
+                    min_price,
+                    tick_size,
+                    levels: vec![Queue::new(); slots],
+                    low: slots,
+                    high: 0,
+                })
+            }
+        }
+    }
+
+    /// Queue for a price, creating the level if needed
+    ///
+    /// Array ladders panic on prices outside the layout; callers check
+    /// [`LadderLayout::accepts_price`] before an order reaches the book.
+    pub fn queue_mut(&mut self, price: u128) -> &mut Queue {
+        match self {
+            PriceLadder::Tree(levels) => levels.entry(price).or_default(),
+            PriceLadder::Array(ladder) => {
+                let index = ladder
+                    .index(price)
+                    .unwrap_or_else(|| panic!("price {} is outside the price ladder", price));
+                ladder.low = ladder.low.min(index);
+                ladder.high = ladder.high.max(index);
+                &mut ladder.levels[index]
+            }
+        }
+    }
+
+    /// Non-empty levels in ascending price order (reverse for best bid first)
+    pub fn iter(&self) -> Box<dyn DoubleEndedIterator<Item = (u128, &Queue)> + '_> {
+        match self {
+            PriceLadder::Tree(levels) => Box::new(
+                levels
+                    .iter()
+                    .filter(|(_, orders)| !orders.is_empty())
+                    .map(|(price, orders)| (*price, orders)),
+            ),
+            PriceLadder::Array(ladder) => Box::new(
+                ladder
+                    .occupied()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, orders)| !orders.is_empty())
+                    .map(|(offset, orders)| (ladder.price(ladder.low + offset), orders)),
+            ),
+        }
+    }
+
+    /// Mutable access to every non-empty level, in ascending price order
+    pub fn iter_mut(&mut self) -> Box<dyn Iterator<Item = (u128, &mut Queue)> + '_> {
+        match self {
+            PriceLadder::Tree(levels) => Box::new(
+                levels
+                    .iter_mut()
+                    .filter(|(_, orders)| !orders.is_empty())
+                    .map(|(price, orders)| (*price, orders)),
+            ),
+            PriceLadder::Array(ladder) => {
+                let (low, min_price, tick_size) = (ladder.low, ladder.min_price, ladder.tick_size);
+                let occupied = ladder.levels.get_mut(low..=ladder.high).unwrap_or_default();
+                Box::new(
+                    occupied
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(_, orders)| !orders.is_empty())
+                        .map(move |(offset, orders)| {
+                            (min_price + (low + offset) as u128 * tick_size, orders)
+                        }),
+                )
+            }
+        }
+    }
+}
+
+impl ArrayLadder {
+    fn index(&self, price: u128) -> Option<usize> {
+        let offset = price.checked_sub(self.min_price)?;
+        if !offset.is_multiple_of(self.tick_size) {
+            return None;
+        }
+        let index = (offset / self.tick_size) as usize;
+        (index < self.levels.len()).then_some(index)
+    }
+
+    fn price(&self, index: usize) -> u128 {
+        self.min_price + index as u128 * self.tick_size
+    }
+
+    fn occupied(&self) -> &[Queue] {
+        self.levels.get(self.low..=self.high).unwrap_or_default()
+    }
+}
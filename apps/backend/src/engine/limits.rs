@@ -0,0 +1,100 @@
+// per-user, per-market position and open-order limits
+
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Side, UserLimits};
+use std::collections::HashMap;
+
+/// In-memory copy of the `user_limits` table, owned by the engine
+///
+/// Only users with limits pay for the position and notional lookups at
+/// placement time; everyone else skips the check entirely.
+#[derive(Debug, Default)]
+pub struct LimitsBook {
+    // (user_address, market_id) -> limits
+    limits: HashMap<(String, String), UserLimits>,
+}
+
+impl LimitsBook {
+    pub fn new(limits: Vec<UserLimits>) -> Self {
+        let mut book = Self::default();
+        for l in limits {
+            book.set(l);
+        }
+        book
+    }
+
+    pub fn get(&self, user_address: &str, market_id: &str) -> Option<&UserLimits> {
+        self.limits
+            .get(&(user_address.to_string(), market_id.to_string()))
+    }
+
+    /// Replace a user's limits; limits with nothing set are removed
+    pub fn set(&mut self, limits: UserLimits) {
+        let key = (limits.user_address.clone(), limits.market_id.clone());
+        if limits.max_position.is_none() && limits.max_open_notional.is_none() {
+            self.limits.remove(&key);
+        } else {
+            self.limits.insert(key, limits);
+        }
+    }
+}
+
+/// Exposure of a user in a market before a new order is placed
+#[derive(Debug, Clone, Copy)]
+pub struct Exposure {
+    /// Net position in base atoms (bought minus sold)
+    pub position: i128,
+    /// Value of resting orders in quote atoms
+    pub open_notional: u128,
+}
+
+/// Check a new order against a user's limits
+///
+/// The position limit assumes the order fills completely and only rejects
+/// orders that grow the absolute position past the limit, so users over
+/// their limit can always trade back towards flat. The notional limit
+/// applies to orders that can rest on the book (`rests`).
+pub fn check_limits(
+    limits: &UserLimits,
+    exposure: Exposure,
+    side: Side,
+    size: u128,
+    notional: u128,
+    rests: bool,
+) -> Result<()> {
+    if let Some(max_position) = limits.max_position {
+        let size = i128::try_from(size).unwrap_or(i128::MAX);
+        let projected = match side {
+            Side::Buy => exposure.position.saturating_add(size),
+            Side::Sell => exposure.position.saturating_sub(size),
+        };
+        if projected.unsigned_abs() > max_position
+            && projected.unsigned_abs() > exposure.position.unsigned_abs()
+        {
+            return Err(ExchangeError::LimitExceeded {
+                user_address: limits.user_address.clone(),
+                market_id: limits.market_id.clone(),
+                message: format!(
+                    "net position would be {}, limit is {}",
+                    projected, max_position
+                ),
+            });
+        }
+    }
+
+    if let (Some(max_open_notional), true) = (limits.max_open_notional, rests) {
+        let projected = exposure.open_notional.saturating_add(notional);
+        if projected > max_open_notional {
+            return Err(ExchangeError::LimitExceeded {
+                user_address: limits.user_address.clone(),
+                market_id: limits.market_id.clone(),
+                message: format!(
+                    "open order notional would be {}, limit is {}",
+                    projected, max_open_notional
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+// interns market symbols ("BTC/USDC") as compact numeric ids
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Compact identifier for a market inside the engine
+///
+/// Assigned by [`MarketRegistry`] the first time a symbol is seen and stable
+/// for the life of the process. Not persisted - the database and the API keep
+/// using the `"BASE/QUOTE"` symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MarketId(u32);
+
+impl MarketId {
+    /// Position in the registry, usable as a dense index
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub(crate) fn from_index(index: usize) -> Self {
+        Self(index as u32)
+    }
+}
+
+impl fmt::Display for MarketId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Default)]
+struct Symbols {
+    ids: HashMap<Arc<str>, MarketId>,
+    symbols: Vec<Arc<str>>,
+}
+
+/// Two-way mapping between market symbols and [`MarketId`]s
+///
+/// Cheap to clone; clones share the same table. Markets are only ever added,
+/// so an id handed out once always resolves to the same symbol.
+#[derive(Clone, Default)]
+pub struct MarketRegistry {
+    inner: Arc<RwLock<Symbols>>,
+}
+
+impl MarketRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id for a symbol, assigning the next one if it hasn't been seen
+    pub fn intern(&self, symbol: &str) -> MarketId {
+        if let Some(id) = self.get(symbol) {
+            return id;
+        }
+
+        let mut inner = self.inner.write().unwrap();
+        // Another caller may have interned it between the read and write lock
+        if let Some(id) = inner.ids.get(symbol) {
+            return *id;
+        }
+        let id = MarketId::from_index(inner.symbols.len());
+        let symbol: Arc<str> = Arc::from(symbol);
+        inner.symbols.push(Arc::clone(&symbol));
+        inner.ids.insert(symbol, id);
+        id
+    }
+
+    /// Id for a symbol that has already been interned
+    pub fn get(&self, symbol: &str) -> Option<MarketId> {
+        self.inner.read().unwrap().ids.get(symbol).copied()
+    }
+
+    /// Symbol for an id
+    pub fn symbol(&self, id: MarketId) -> Option<Arc<str>> {
+        self.inner.read().unwrap().symbols.get(id.index()).cloned()
+    }
+
+    /// Number of interned markets
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::engine::orderbook::Orderbook;
 use crate::models::domain::{Match, Order, OrderType, Side};
+use std::sync::Arc;
 
 pub struct Matcher;
 
@@ -13,10 +14,10 @@ impl Matcher {
         let mut matches = Vec::new();
         let mut remaining_size = taker_order.size - taker_order.filled_size;
 
-        // Iterate through price levels in order (BTreeMap is sorted)
+        // Iterate through price levels in order (ladders iterate ascending)
         // For asks: ascending (lowest price first)
         // For bids: descending (highest price first) - need to reverse
-        let level_iter: Box<dyn Iterator<Item = (&u128, &_)>> = match taker_order.side {
+        let level_iter: Box<dyn Iterator<Item = (u128, &_)>> = match taker_order.side {
             Side::Buy => Box::new(orderbook.asks.iter()), // Lowest ask first
             Side::Sell => Box::new(orderbook.bids.iter().rev()), // Highest bid first
         };
@@ -27,7 +28,7 @@ impl Matcher {
             }
 
             // Check if this price level can match
-            if !Self::can_match_price(taker_order, *price) {
+            if !Self::can_match_price(taker_order, price) {
                 break; // No more matches possible at this or worse prices
             }
 
@@ -47,8 +48,8 @@ impl Matcher {
                 let match_size = remaining_size.min(maker_remaining);
 
                 matches.push(Match {
-                    maker_order: maker_order.clone(),
-                    price: *price, // Match at maker's price (price-time priority)
+                    maker_order: Arc::clone(maker_order),
+                    price, // Match at maker's price (price-time priority)
                     size: match_size,
                 });
 
@@ -59,19 +60,77 @@ impl Matcher {
         matches
     }
 
+    /// Size a taker order would fill against the orderbook right now, at
+    /// prices it accepts and skipping its user's own orders like matching does
+    ///
+    /// Stops counting once the order's remaining size is covered, so it is
+    /// cheap to check that a fill-or-kill order fills completely.
+    pub fn available_liquidity(taker_order: &Order, orderbook: &Orderbook) -> u128 {
+        let wanted = taker_order.size - taker_order.filled_size;
+        let mut available = 0;
+
+        let level_iter: Box<dyn Iterator<Item = (u128, &_)>> = match taker_order.side {
+            Side::Buy => Box::new(orderbook.asks.iter()),
+            Side::Sell => Box::new(orderbook.bids.iter().rev()),
+        };
+        for (price, orders) in level_iter {
+            if available >= wanted || !Self::can_match_price(taker_order, price) {
+                break;
+            }
+            available += orders
+                .iter()
+                .filter(|maker_order| maker_order.user_address != taker_order.user_address)
+                .map(|maker_order| maker_order.size - maker_order.filled_size)
+                .sum::<u128>();
+        }
+
+        available.min(wanted)
+    }
+
+    /// Whether a taker order reaches one of its user's own orders on the
+    /// other side of the book
+    ///
+    /// Other users' orders ahead of it in price-time priority fill first, so
+    /// only size left over once they have is counted. Matching skips the
+    /// user's own orders, so a remainder that reaches one and rests would
+    /// leave the book crossed.
+    pub fn crosses_own_order(taker_order: &Order, orderbook: &Orderbook) -> bool {
+        let mut remaining = taker_order.size - taker_order.filled_size;
+
+        let level_iter: Box<dyn Iterator<Item = (u128, &_)>> = match taker_order.side {
+            Side::Buy => Box::new(orderbook.asks.iter()),
+            Side::Sell => Box::new(orderbook.bids.iter().rev()),
+        };
+        for (price, orders) in level_iter {
+            if !Self::can_match_price(taker_order, price) {
+                break;
+            }
+            for maker_order in orders.iter() {
+                if maker_order.user_address == taker_order.user_address {
+                    return true;
+                }
+                remaining = remaining.saturating_sub(maker_order.size - maker_order.filled_size);
+                if remaining == 0 {
+                    return false;
+                }
+            }
+        }
+        false
+    }
+
     /// Check if a taker order can match at the given maker price
     fn can_match_price(taker: &Order, maker_price: u128) -> bool {
         match (taker.side, taker.order_type) {
             // Buy limit: can match if willing to pay >= maker's asking price
             // considered a taker order if above lowest ask
-            (Side::Buy, OrderType::Limit) => taker.price >= maker_price,
+            (Side::Buy, OrderType::Limit | OrderType::StopLimit) => taker.price >= maker_price,
             // Buy market: match at any price
-            (Side::Buy, OrderType::Market) => true,
+            (Side::Buy, OrderType::Market | OrderType::StopMarket) => true,
             // Sell limit: can match if willing to accept <= maker's bid price
             // considered a taker order if below highest bid
-            (Side::Sell, OrderType::Limit) => taker.price <= maker_price,
+            (Side::Sell, OrderType::Limit | OrderType::StopLimit) => taker.price <= maker_price,
             // Sell market: match at any price
-            (Side::Sell, OrderType::Market) => true,
+            (Side::Sell, OrderType::Market | OrderType::StopMarket) => true,
         }
     }
 }
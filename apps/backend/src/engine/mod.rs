@@ -1,30 +1,156 @@
 // process
 // price time priority
 
+pub mod analytics;
+pub mod collar;
+pub mod depth;
 pub mod executor;
+pub mod expiry;
+pub mod fees;
+pub mod housekeeping;
+pub mod invariants;
+pub mod kill_switch;
+pub mod ladder;
+pub mod limits;
+pub mod markets;
 pub mod matcher;
+pub mod oco;
 pub mod orderbook;
+pub mod routing;
+pub mod triggers;
 
+use crate::alerts::{Alert, AlertKind, Alerter, DEFAULT_MASS_CANCEL_ORDERS};
 use crate::db::Db;
 use crate::errors::ExchangeError;
 use crate::models::api::{OrderCancelled, OrderPlaced, OrdersCancelled};
-use crate::models::domain::{EngineEvent, EngineRequest, OrderStatus};
-use executor::{AffectedBalances, Executor};
+use crate::models::domain::{
+    CancelReason, EngineEvent, EngineRequest, FeeOverride, FeeRoute, KillSwitch, Liquidation,
+    MarginMode, MarketStatus, OrderStatus, OrderType, PerpetualMarket, Position, Referral,
+    RevenueSource, SubAccount, TimeInForce, TriggerSource, UserStatus,
+};
+use crate::perps::margin::{self, Health};
+use crate::perps::{self, FundingSettlement};
+use crate::price_feed::IndexPrices;
+use crate::rfq::RfqExecution;
+use crate::saturation::Saturation;
+use analytics::{AnalyticsStats, AnalyticsTask, AnalyticsWriter, ANALYTICS_BUFFER_SIZE};
+use collar::PriceCollars;
+use depth::{DEPTH_METRICS_INTERVAL_SECS, DEPTH_METRICS_LEVELS};
+use executor::{AffectedBalances, ExecutionMode, Executor};
+use expiry::{ExpiryQueue, EXPIRY_INTERVAL};
+use fees::FeeOverrides;
+use housekeeping::HOUSEKEEPING_INTERVAL;
+use kill_switch::KillSwitches;
+use ladder::LadderLayout;
+use limits::{Exposure, LimitsBook};
+use markets::MarketRegistry;
 use matcher::Matcher;
+use oco::OcoBook;
 use orderbook::Orderbooks;
+use routing::FeeRouting;
+use triggers::{TriggerBook, INDEX_TRIGGER_INTERVAL};
 
-use std::collections::HashSet;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// Markets loaded from the database at once during startup recovery
+pub const RECOVERY_CONCURRENCY: usize = 8;
+
+/// Outcome of recovering one market's orderbook
+#[derive(Debug)]
+pub struct MarketRecovery {
+    pub market_id: String,
+    /// Orders restored to the book, or why the market could not be loaded
+    pub outcome: Result<usize, String>,
+    pub elapsed: Duration,
+}
+
+/// Per-market results of [`MatchingEngine::recover_orderbooks`], in completion order
+#[derive(Debug, Default)]
+pub struct RecoveryReport {
+    pub markets: Vec<MarketRecovery>,
+}
+
+impl RecoveryReport {
+    /// Markets whose books were loaded and are ready for orders
+    pub fn recovered(&self) -> impl Iterator<Item = &MarketRecovery> {
+        self.markets.iter().filter(|m| m.outcome.is_ok())
+    }
+
+    /// Markets that failed to load
+    pub fn failed(&self) -> impl Iterator<Item = &MarketRecovery> {
+        self.markets.iter().filter(|m| m.outcome.is_err())
+    }
+
+    /// Orders restored across all markets
+    pub fn total_orders(&self) -> usize {
+        self.markets
+            .iter()
+            .filter_map(|m| m.outcome.as_ref().ok())
+            .sum()
+    }
+}
+
+/// Price levels per side in broadcast orderbook snapshots
+pub const SNAPSHOT_DEPTH: usize = 50;
+
+/// While degraded, snapshots go out only every this many seconds
+pub const DEGRADED_SNAPSHOT_INTERVAL_SECS: u64 = 5;
+
+/// Most limit and stop orders a user may have open in one market
+pub const MAX_OPEN_ORDERS_PER_MARKET: usize = 200;
 
 pub struct MatchingEngine {
     db: Db,
     orderbooks: Arc<RwLock<Orderbooks>>,
+    markets: MarketRegistry,
+    // Per-user risk limits, loaded when `run()` starts
+    limits: LimitsBook,
+    // Cancel-only switches, loaded when `run()` starts
+    kill_switches: KillSwitches,
+    // Halted and delisted markets, loaded when `run()` starts
+    inactive_markets: HashMap<String, MarketStatus>,
+    // Referrals by referred user, loaded when `run()` starts
+    referrals: HashMap<String, Referral>,
+    // Frozen and banned users, loaded when `run()` starts
+    restricted_users: HashMap<String, UserStatus>,
+    // Price collars from config, with admin overrides and last trade prices loaded by `run()`
+    collars: PriceCollars,
+    // Stop orders waiting for their trigger, loaded with last trade prices when `run()` starts
+    triggers: TriggerBook,
+    // Open orders with an expiry, loaded when `run()` starts
+    expiries: ExpiryQueue,
+    // One-cancels-other pairs whose orders are both open, loaded when `run()` starts
+    oco: OcoBook,
+    // Latest external index prices, published by the price feed
+    index_prices: IndexPrices,
+    // How revenue is split between the system accounts, loaded when `run()` starts
+    fee_routing: FeeRouting,
+    // Fees users pay instead of their market's, loaded when `run()` starts
+    fee_overrides: FeeOverrides,
+    // Perpetual markets by id, loaded when `run()` starts and as markets open
+    perpetuals: HashMap<String, PerpetualMarket>,
+    // Users margining their positions across markets, loaded when `run()` starts
+    cross_margin_users: HashSet<String>,
 
     engine_rx: mpsc::Receiver<EngineRequest>,
     event_tx: broadcast::Sender<EngineEvent>,
+
+    // Trades headed for ClickHouse; the task is spawned by `run()`
+    analytics: AnalyticsWriter,
+    analytics_task: Option<AnalyticsTask>,
+
+    // Operator alerts, and how many orders one cancel-all takes to raise one
+    alerts: Alerter,
+    mass_cancel_orders: usize,
+
+    // Whether to shed snapshot work while the exchange is saturated
+    saturation: Saturation,
 }
 
 impl MatchingEngine {
@@ -33,23 +159,105 @@ impl MatchingEngine {
         engine_rx: mpsc::Receiver<EngineRequest>,
         event_tx: broadcast::Sender<EngineEvent>,
     ) -> Self {
+        let (analytics, analytics_task) = AnalyticsWriter::new(ANALYTICS_BUFFER_SIZE);
+        let markets = MarketRegistry::new();
+
         Self {
             db: db.clone(),
-            orderbooks: Arc::new(RwLock::new(Orderbooks::new())),
+            orderbooks: Arc::new(RwLock::new(Orderbooks::with_registry(markets.clone()))),
+            markets,
+            limits: LimitsBook::default(),
+            kill_switches: KillSwitches::default(),
+            inactive_markets: HashMap::new(),
+            referrals: HashMap::new(),
+            restricted_users: HashMap::new(),
+            collars: PriceCollars::default(),
+            triggers: TriggerBook::default(),
+            expiries: ExpiryQueue::default(),
+            oco: OcoBook::default(),
+            index_prices: IndexPrices::default(),
+            fee_routing: FeeRouting::default(),
+            fee_overrides: FeeOverrides::default(),
+            perpetuals: HashMap::new(),
+            cross_margin_users: HashSet::new(),
             engine_rx,
             event_tx,
+            analytics,
+            analytics_task: Some(analytics_task),
+            alerts: Alerter::default(),
+            mass_cancel_orders: DEFAULT_MASS_CANCEL_ORDERS,
+            saturation: Saturation::default(),
         }
     }
 
+    /// Shared handle to the in-memory orderbooks
+    ///
+    /// Lets callers inspect book state (e.g. invariant checks in tests)
+    /// after `run()` has taken ownership of the engine.
+    pub fn orderbooks(&self) -> Arc<RwLock<Orderbooks>> {
+        Arc::clone(&self.orderbooks)
+    }
+
+    /// Registry of the market ids carried by engine events
+    ///
+    /// Resolve ids back to `"BASE/QUOTE"` symbols at the API boundary.
+    pub fn markets(&self) -> MarketRegistry {
+        self.markets.clone()
+    }
+
+    /// Shared handle to the latest index prices
+    ///
+    /// Give it to a [`crate::price_feed::PriceFeed`] to have its prices
+    /// used as collar references.
+    pub fn index_prices(&self) -> IndexPrices {
+        self.index_prices.clone()
+    }
+
+    /// Raise operator alerts on persistence failures, halts and mass cancels
+    pub fn set_alerts(&mut self, alerts: Alerter) {
+        self.alerts = alerts;
+    }
+
+    /// Alert when a single cancel-all takes out at least `orders` orders
+    pub fn set_mass_cancel_threshold(&mut self, orders: usize) {
+        self.mass_cancel_orders = orders;
+    }
+
+    /// Slow snapshot broadcasts and skip depth samples while `saturation` is degraded
+    pub fn set_saturation(&mut self, saturation: Saturation) {
+        self.saturation = saturation;
+    }
+
+    /// Index a market's orderbook with the given price ladder layout
+    /// Call before `recover_orderbooks` so recovered orders land in the right layout
+    pub async fn set_ladder_layout(&self, market_id: &str, layout: LadderLayout) {
+        self.orderbooks.write().await.set_layout(market_id, layout);
+    }
+
+    /// Reject limit orders priced more than `collar_bps` from a market's last trade
+    /// Admin overrides persisted in the database take precedence once `run()` starts
+    pub fn set_price_collar(&mut self, market_id: &str, collar_bps: u32) {
+        self.collars.configure(market_id, collar_bps);
+    }
+
+    /// Counters for the background ClickHouse trade writer
+    pub fn analytics_stats(&self) -> Arc<AnalyticsStats> {
+        self.analytics.stats()
+    }
+
     /// Recover orderbooks from database on startup
     /// This restores all pending and partially filled limit orders to the in-memory orderbook
     /// Orders are added in created_at order to maintain price-time priority
     ///
-    /// Recovery is done market-by-market for:
+    /// Markets are recovered independently and concurrently, at most
+    /// `RECOVERY_CONCURRENCY` at a time:
     /// - Isolation: One market's failure doesn't affect others
-    /// - Scalability: Easy to parallelize later
-    /// - Memory control: Only one market's orders in memory at a time
-    pub async fn recover_orderbooks(&self) -> crate::errors::Result<()> {
+    /// - Startup time: Loading many markets overlaps their database round trips
+    /// - Memory control: Only a bounded number of markets' orders in flight at once
+    ///
+    /// Each market is logged as ready when its book is loaded; the returned
+    /// report lists every market's outcome.
+    pub async fn recover_orderbooks(&self) -> crate::errors::Result<RecoveryReport> {
         log::info!("Starting orderbook recovery from database...");
         let start_time = std::time::Instant::now();
 
@@ -58,46 +266,56 @@ impl MatchingEngine {
 
         if markets.is_empty() {
             log::info!("No markets configured, skipping recovery");
-            return Ok(());
+            return Ok(RecoveryReport::default());
         }
 
-        log::info!("Recovering orderbooks for {} markets", markets.len());
+        log::info!(
+            "Recovering orderbooks for {} markets ({} at a time)",
+            markets.len(),
+            RECOVERY_CONCURRENCY
+        );
 
-        let mut total_orders = 0usize;
-        let mut recovered_markets = 0usize;
-        let mut failed_markets = Vec::new();
+        let mut report = RecoveryReport::default();
+        let mut recoveries = futures::stream::iter(&markets)
+            .map(|market| async move {
+                let started = std::time::Instant::now();
+                let result = self.recover_market(&market.id).await;
+                (market.id.clone(), result, started.elapsed())
+            })
+            .buffer_unordered(RECOVERY_CONCURRENCY);
 
         // Recover each market independently
-        for (index, market) in markets.iter().enumerate() {
-            match self.recover_market(&market.id).await {
-                Ok(count) => {
-                    if count > 0 {
-                        log::info!(
-                            "[{}/{}] {}: recovered {} orders",
-                            index + 1,
-                            markets.len(),
-                            market.id,
-                            count
-                        );
-                        total_orders += count;
-                        recovered_markets += 1;
-                    }
+        while let Some((market_id, result, elapsed)) = recoveries.next().await {
+            let done = report.markets.len() + 1;
+            let outcome = match result {
+                Ok(orders) => {
+                    log::info!(
+                        "[{}/{}] {}: ready, recovered {} orders in {:.2}s",
+                        done,
+                        markets.len(),
+                        market_id,
+                        orders,
+                        elapsed.as_secs_f64()
+                    );
+                    Ok(orders)
                 }
                 Err(e) => {
                     log::error!(
                         "[{}/{}] {}: failed to recover - {}",
-                        index + 1,
+                        done,
                         markets.len(),
-                        market.id,
+                        market_id,
                         e
                     );
-                    failed_markets.push(market.id.clone());
-                    // Continue to next market - don't let one failure stop recovery
+                    // Keep going - don't let one failure stop recovery
+                    Err(e.to_string())
                 }
-            }
-
-            // Yield between markets to allow other tasks to run
-            tokio::task::yield_now().await;
+            };
+            report.markets.push(MarketRecovery {
+                market_id,
+                outcome,
+                elapsed,
+            });
         }
 
         let elapsed = start_time.elapsed();
@@ -105,20 +323,17 @@ impl MatchingEngine {
         // Log recovery summary
         log::info!(
             "Orderbook recovery complete: {} orders across {} markets in {:.2}s",
-            total_orders,
-            recovered_markets,
+            report.total_orders(),
+            report.recovered().count(),
             elapsed.as_secs_f64()
         );
 
-        if !failed_markets.is_empty() {
-            log::warn!(
-                "Failed to recover {} markets: {:?}",
-                failed_markets.len(),
-                failed_markets
-            );
+        let failed: Vec<&str> = report.failed().map(|m| m.market_id.as_str()).collect();
+        if !failed.is_empty() {
+            log::warn!("Failed to recover {} markets: {:?}", failed.len(), failed);
         }
 
-        Ok(())
+        Ok(report)
     }
 
     /// Recover orders for a single market
@@ -131,7 +346,7 @@ impl MatchingEngine {
             return Ok(0);
         }
 
-        let count = orders.len();
+        let mut count = 0;
 
         // Add orders to the orderbook
         {
@@ -139,58 +354,452 @@ impl MatchingEngine {
             let orderbook = orderbooks.get_or_create(market_id);
 
             for order in orders {
+                // Only possible if the market's ladder range was narrowed since the order was placed
+                if !orderbook.layout().accepts_price(order.price) {
+                    log::error!(
+                        "{}: order {} at price {} is outside the price ladder, not recovered",
+                        market_id,
+                        order.id,
+                        order.price
+                    );
+                    continue;
+                }
                 orderbook.add_order(order);
+                count += 1;
             }
         }
 
         Ok(count)
     }
 
+    /// Load the limits, kill switches, statuses, fees and resting triggers
+    /// the engine checks orders against
+    ///
+    /// Call before [`MatchingEngine::run`]: an engine missing any of them
+    /// would take orders it should refuse, so a failure stops startup.
+    pub async fn load_state(&mut self) -> crate::errors::Result<()> {
+        let limits = self.db.list_user_limits().await?;
+        log::info!("Loaded {} user limits", limits.len());
+        self.limits = LimitsBook::new(limits);
+
+        let switches = self.db.list_kill_switches().await?;
+        for switch in &switches {
+            log::warn!(
+                "Kill switch engaged for {}: {}",
+                switch.market_id.as_deref().unwrap_or("all markets"),
+                switch.reason.as_deref().unwrap_or("no reason given")
+            );
+        }
+        self.kill_switches = KillSwitches::new(switches);
+
+        self.inactive_markets = self.db.list_inactive_markets().await?.into_iter().collect();
+
+        let referrals = self.db.list_referrals().await?;
+        log::info!("Loaded {} referrals", referrals.len());
+        self.referrals = referrals
+            .into_iter()
+            .map(|r| (r.user_address.clone(), r))
+            .collect();
+
+        self.restricted_users = self.db.list_restricted_users().await?.into_iter().collect();
+
+        for (market_id, collar_bps) in self.db.list_price_collar_overrides().await? {
+            self.collars.set_override(&market_id, collar_bps);
+        }
+
+        let orders = self.db.get_untriggered_stop_orders().await?;
+        log::info!("Loaded {} stop orders", orders.len());
+        self.triggers = TriggerBook::new(orders);
+
+        let orders = self.db.get_expiring_orders().await?;
+        log::info!("Loaded {} orders with an expiry", orders.len());
+        self.expiries = ExpiryQueue::new(orders);
+
+        let links = self.db.list_open_oco_links().await?;
+        log::info!("Loaded {} one-cancels-other pairs", links.len());
+        self.oco = OcoBook::new(links);
+
+        // Loaded after the stop orders, so any a trade reached before a
+        // restart trigger with the first request or housekeeping pass
+        for (market_id, price) in self.db.get_last_trade_prices().await? {
+            self.collars.record_trade(&market_id, price);
+            self.triggers.record_trade(&market_id, price);
+        }
+
+        self.fee_routing = FeeRouting::new(self.db.list_fee_routes().await?);
+
+        let overrides = self.db.list_fee_overrides().await?;
+        log::info!("Loaded {} fee overrides", overrides.len());
+        self.fee_overrides = FeeOverrides::new(overrides);
+
+        self.perpetuals = self
+            .db
+            .list_perpetual_markets()
+            .await?
+            .into_iter()
+            .map(|market| (market.market_id.clone(), market))
+            .collect();
+
+        self.cross_margin_users = self
+            .db
+            .list_cross_margin_users()
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(())
+    }
+
     pub async fn run(mut self) {
         // Spawn background task for orderbook snapshots
         let snapshot_handle = self.spawn_snapshot_broadcaster();
 
-        // Main event loop - process incoming requests
-        while let Some(request) = self.engine_rx.recv().await {
+        // Spawn background task writing trades to ClickHouse
+        let analytics_handle = self
+            .analytics_task
+            .take()
+            .map(|task| tokio::spawn(task.with_alerts(self.alerts.clone()).run(self.db.clone())));
+
+        // Main event loop - process incoming requests, tidying up between them
+        let mut housekeeping = tokio::time::interval_at(
+            tokio::time::Instant::now() + HOUSEKEEPING_INTERVAL,
+            HOUSEKEEPING_INTERVAL,
+        );
+        housekeeping.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut expiry = tokio::time::interval(EXPIRY_INTERVAL);
+        expiry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut index_triggers = tokio::time::interval(INDEX_TRIGGER_INTERVAL);
+        index_triggers.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            let request = tokio::select! {
+                request = self.engine_rx.recv() => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+                _ = housekeeping.tick() => {
+                    let mut affected = HashSet::new();
+                    self.housekeeping(&mut affected).await;
+                    self.broadcast_balances(affected).await;
+                    continue;
+                }
+                _ = expiry.tick() => {
+                    let mut affected = HashSet::new();
+                    self.expire_orders(&mut affected).await;
+                    self.broadcast_balances(affected).await;
+                    continue;
+                }
+                _ = index_triggers.tick() => {
+                    let mut affected = HashSet::new();
+                    self.trigger_on_index_prices(&mut affected).await;
+                    self.broadcast_balances(affected).await;
+                    continue;
+                }
+            };
+
             // Process request and collect affected balances
-            let affected = match request {
-                EngineRequest::PlaceOrder { order, response_tx } => {
-                    let (result, affected) = self.handle_place_order(order).await;
+            let mut affected = match request {
+                EngineRequest::PlaceOrder {
+                    order,
+                    span,
+                    response_tx,
+                } => {
+                    let span = tracing::info_span!(
+                        parent: &span,
+                        "engine.place_order",
+                        order_id = %order.id,
+                        market_id = %order.market_id,
+                    );
+                    let (order_id, user_address, market_id) = (
+                        order.id,
+                        order.user_address.clone(),
+                        order.market_id.clone(),
+                    );
+                    let (result, affected) = self.handle_place_order(order).instrument(span).await;
+                    if let Err(e) = &result {
+                        let _ = self.event_tx.send(EngineEvent::OrderRejected {
+                            order_id,
+                            user_address,
+                            market_id,
+                            code: e.error_code(),
+                            reason: e.reject_reason(),
+                            message: e.public_message(),
+                        });
+                    }
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::PlaceOco {
+                    first,
+                    second,
+                    span,
+                    response_tx,
+                } => {
+                    let span = tracing::info_span!(
+                        parent: &span,
+                        "engine.place_oco",
+                        market_id = %first.market_id,
+                    );
+                    let (user_address, market_id) =
+                        (first.user_address.clone(), first.market_id.clone());
+                    let order_ids = [first.id, second.id];
+                    let (result, affected) =
+                        self.handle_place_oco(first, second).instrument(span).await;
+                    // The pair is refused whole, so both of its orders are
+                    if let Err(e) = &result {
+                        for order_id in order_ids {
+                            let _ = self.event_tx.send(EngineEvent::OrderRejected {
+                                order_id,
+                                user_address: user_address.clone(),
+                                market_id: market_id.clone(),
+                                code: e.error_code(),
+                                reason: e.reject_reason(),
+                                message: e.public_message(),
+                            });
+                        }
+                    }
                     let _ = response_tx.send(result);
                     affected
                 }
                 EngineRequest::CancelOrder {
                     order_id,
                     user_address,
+                    span,
                     response_tx,
                 } => {
-                    let (result, affected) = self.handle_cancel_order(order_id, user_address).await;
+                    let span = tracing::info_span!(parent: &span, "engine.cancel_order", %order_id);
+                    let (result, affected) = self
+                        .handle_cancel_order(order_id, user_address)
+                        .instrument(span)
+                        .await;
                     let _ = response_tx.send(result);
                     affected
                 }
                 EngineRequest::CancelAllOrders {
                     user_address,
                     market_id,
+                    span,
                     response_tx,
                 } => {
-                    let (result, affected) =
-                        self.handle_cancel_all_orders(user_address, market_id).await;
+                    let span = tracing::info_span!(parent: &span, "engine.cancel_all_orders");
+                    let (result, affected) = self
+                        .handle_cancel_all_orders(user_address, market_id)
+                        .instrument(span)
+                        .await;
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::SetUserLimits {
+                    user_address,
+                    market_id,
+                    max_position,
+                    max_open_notional,
+                    response_tx,
+                } => {
+                    let result = self
+                        .handle_set_user_limits(
+                            user_address,
+                            market_id,
+                            max_position,
+                            max_open_notional,
+                        )
+                        .await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::EngageKillSwitch {
+                    market_id,
+                    reason,
+                    cancel_orders,
+                    response_tx,
+                } => {
+                    let (result, affected) = self
+                        .handle_engage_kill_switch(market_id, reason, cancel_orders)
+                        .await;
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::ReleaseKillSwitch {
+                    market_id,
+                    response_tx,
+                } => {
+                    let result = self.handle_release_kill_switch(market_id).await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::SetMarketStatus {
+                    market_id,
+                    status,
+                    response_tx,
+                } => {
+                    let (result, affected) = self.handle_set_market_status(market_id, status).await;
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::SetReferral {
+                    user_address,
+                    referrer_address,
+                    share_bps,
+                    response_tx,
+                } => {
+                    let result = self
+                        .handle_set_referral(user_address, referrer_address, share_bps)
+                        .await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::SetUserStatus {
+                    user_address,
+                    status,
+                    cancel_orders,
+                    response_tx,
+                } => {
+                    let (result, affected) = self
+                        .handle_set_user_status(user_address, status, cancel_orders)
+                        .await;
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::SetFeeOverride {
+                    user_address,
+                    market_id,
+                    maker_fee_bps,
+                    taker_fee_bps,
+                    response_tx,
+                } => {
+                    let result = self
+                        .handle_set_fee_override(
+                            user_address,
+                            market_id,
+                            maker_fee_bps,
+                            taker_fee_bps,
+                        )
+                        .await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::SetPriceCollar {
+                    market_id,
+                    collar_bps,
+                    response_tx,
+                } => {
+                    let result = self.handle_set_price_collar(market_id, collar_bps).await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::OpenMarket {
+                    market_id,
+                    layout,
+                    collar_bps,
+                    response_tx,
+                } => {
+                    let result = self.handle_open_market(market_id, layout, collar_bps).await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::SetFeeRoute {
+                    source,
+                    insurance_bps,
+                    response_tx,
+                } => {
+                    let result = self.handle_set_fee_route(source, insurance_bps).await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::SettleFunding {
+                    market_id,
+                    mark_price,
+                    index_price,
+                    funding_rate_ppm,
+                    response_tx,
+                } => {
+                    let (result, affected) = self
+                        .handle_settle_funding(market_id, mark_price, index_price, funding_rate_ppm)
+                        .await;
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::UpdateMarkPrice {
+                    market_id,
+                    mark_price,
+                    index_price,
+                    response_tx,
+                } => {
+                    let (result, affected) = self
+                        .handle_update_mark_price(market_id, mark_price, index_price)
+                        .await;
+                    let _ = response_tx.send(result);
+                    affected
+                }
+                EngineRequest::SetMarginMode {
+                    user_address,
+                    mode,
+                    response_tx,
+                } => {
+                    let result = self.handle_set_margin_mode(user_address, mode).await;
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::QueuePosition {
+                    order_id,
+                    user_address,
+                    response_tx,
+                } => {
+                    let result = self
+                        .orderbooks
+                        .read()
+                        .await
+                        .queue_position(order_id, &user_address);
+                    let _ = response_tx.send(result);
+                    HashSet::new()
+                }
+                EngineRequest::RestingOrders {
+                    market_id,
+                    response_tx,
+                } => {
+                    let orders = self.orderbooks.read().await.resting_orders(&market_id);
+                    let _ = response_tx.send(Ok(orders));
+                    HashSet::new()
+                }
+                EngineRequest::ExecuteRfq {
+                    request_id,
+                    quote_id,
+                    user_address,
+                    response_tx,
+                } => {
+                    let (result, affected) = self
+                        .handle_execute_rfq(request_id, quote_id, user_address)
+                        .await;
                     let _ = response_tx.send(result);
                     affected
                 }
             };
 
-            // Broadcast consolidated balance updates for all affected users
-            // This ensures only one update per user-token pair per request
-            for (user_address, token_ticker) in affected {
-                if let Ok(balance) = self.db.get_balance(&user_address, &token_ticker).await {
-                    let _ = self.event_tx.send(EngineEvent::BalanceUpdated { balance });
-                }
-            }
+            // Trades may have reached stop orders, whose own trades may reach more
+            self.activate_stop_orders(&mut affected).await;
+
+            self.halt_crossed_books(&mut affected).await;
+
+            self.broadcast_balances(affected).await;
         }
 
         // Cleanup: abort the snapshot broadcaster when engine stops
         snapshot_handle.abort();
+
+        // Close the analytics buffer and let the writer flush what's left
+        drop(self.analytics);
+        if let Some(handle) = analytics_handle {
+            let _ = handle.await;
+        }
+    }
+
+    /// Broadcast consolidated balance updates for all affected users
+    /// This ensures only one update per user-token pair per request
+    async fn broadcast_balances(&self, affected: AffectedBalances) {
+        for (user_address, token_ticker) in affected {
+            if let Ok(balance) = self.db.get_balance(&user_address, &token_ticker).await {
+                let _ = self.event_tx.send(EngineEvent::BalanceUpdated { balance });
+            }
+        }
     }
 
     /// Handle placing a new order
@@ -201,14 +810,10 @@ impl MatchingEngine {
     ) -> (Result<OrderPlaced, ExchangeError>, AffectedBalances) {
         let mut affected = HashSet::new();
 
-        // Validate order against market config
-        let market = match self.db.get_market(&order.market_id).await {
-            Ok(m) => m,
+        let (market, mode) = match self.check_order(&order).await {
+            Ok(v) => v,
             Err(e) => return (Err(e), affected),
         };
-        if let Err(e) = Self::validate_order(&order, &market) {
-            return (Err(e), affected);
-        }
 
         // Calculate and lock balance (after validation, before matching)
         let (token_to_lock, amount_to_lock) =
@@ -238,44 +843,517 @@ impl MatchingEngine {
             return (Err(e), affected);
         }
 
-        // Get matches from matcher and apply them
-        let (matches, trades) = {
-            let mut orderbooks = self.orderbooks.write().await;
-            let orderbook = orderbooks.get_or_create(&order.market_id);
-
-            // Match order against orderbook
-            let matches = Matcher::match_order(&order, orderbook);
-
-            // Execute trades if we have matches (also updates order status in DB)
-            let (trades, executor_affected) = if !matches.is_empty() {
-                match Executor::execute(self.db.clone(), matches.clone(), &order, &market).await {
-                    Ok((trades, exec_affected)) => (trades, exec_affected),
-                    Err(e) => {
-                        // Execution failed - unlock the full order amount
-                        let _ = self
-                            .db
-                            .unlock_balance(&order.user_address, &token_to_lock, amount_to_lock)
-                            .await;
-                        return (Err(e), affected);
-                    }
-                }
-            } else {
-                (vec![], HashSet::new())
-            };
-
-            // Track all balances affected by execution
-            affected.extend(executor_affected);
+        // Whatever of the order is still open at its expiry is cancelled then
+        self.expiries.insert(&order);
 
-            // Update orderbook with executed trades
-            orderbook.apply_trades(&order, &trades, &market);
+        // Stop orders wait with their balance locked until trades reach them
+        if order.order_type.is_stop() {
+            self.triggers.insert(order.clone());
+            let _ = self.event_tx.send(EngineEvent::OrderPlaced {
+                order: order.clone(),
+            });
+            return (
+                Ok(OrderPlaced {
+                    order: order.into(),
+                    trades: vec![],
+                }),
+                affected,
+            );
+        }
 
-            (matches, trades)
+        // Match against the book, settle the trades and broadcast them
+        let trades = match self
+            .execute_order(&mut order, &market, mode, &mut affected)
+            .await
+        {
+            Ok(trades) => trades,
+            Err(e) => {
+                // Execution failed - unlock the full order amount
+                let _ = self
+                    .db
+                    .unlock_balance(&order.user_address, &token_to_lock, amount_to_lock)
+                    .await;
+                return (Err(e), affected);
+            }
         };
+        if let Err(e) = self
+            .finish_order(&mut order, &market, mode, &trades, &mut affected)
+            .await
+        {
+            return (Err(e), affected);
+        }
 
-        // Broadcast trade events
-        for trade in &trades {
-            let _ = self.event_tx.send(EngineEvent::TradeExecuted {
-                trade: trade.clone(),
+        (
+            Ok(OrderPlaced {
+                order: order.into(),
+                trades: trades.into_iter().map(|t| t.into()).collect(),
+            }),
+            affected,
+        )
+    }
+
+    /// Check an order may be placed, before anything is locked or stored
+    async fn check_order(
+        &self,
+        order: &crate::models::domain::Order,
+    ) -> Result<(crate::models::domain::Market, ExecutionMode), ExchangeError> {
+        // Frozen and banned users, and their sub-accounts, may only cancel
+        if let Some(status) = self.restriction(&order.user_address) {
+            return Err(ExchangeError::UserNotActive {
+                user_address: order.user_address.clone(),
+                status,
+            });
+        }
+
+        // Validate order against market config
+        let market = self.db.get_market(&order.market_id).await?;
+        Self::validate_order(order, &market)?;
+
+        // Collateral is locked at the order's price, which market orders don't honour
+        let mode = self.execution_mode(&order.market_id);
+        if mode != ExecutionMode::Spot && order.order_type.execution_type() == OrderType::Market {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "{} is a perpetual market and only accepts limit orders",
+                    order.market_id
+                ),
+            });
+        }
+
+        if let Some(status) = self.inactive_markets.get(&order.market_id) {
+            return Err(ExchangeError::MarketNotActive {
+                market_id: order.market_id.clone(),
+                status: *status,
+            });
+        }
+
+        // Markets behind a kill switch only accept cancels
+        if self.kill_switches.blocking(&order.market_id).is_some() {
+            return Err(ExchangeError::CancelOnly {
+                market_id: order.market_id.clone(),
+            });
+        }
+
+        // Limit prices must stay near the index or last trade to catch fat-finger
+        // orders; a stop limit executes once trades reach its trigger, so its
+        // price is measured from there
+        if order.order_type.execution_type() == OrderType::Limit {
+            let index_price = match order.order_type {
+                OrderType::StopLimit => order.trigger_price,
+                _ => self
+                    .index_prices
+                    .fresh(&order.market_id, chrono::Utc::now()),
+            };
+            if let Err(breach) =
+                self.collars
+                    .check_with_index(&order.market_id, order.price, index_price)
+            {
+                return Err(ExchangeError::PriceOutsideCollar {
+                    market_id: order.market_id.clone(),
+                    price: order.price,
+                    reference_price: breach.reference_price,
+                    collar_bps: breach.collar_bps,
+                });
+            }
+        }
+
+        // A stop already reached would execute at once; that's a plain order
+        let reference_price = match order.trigger_source {
+            TriggerSource::LastTrade => self.triggers.last_price(&order.market_id),
+            TriggerSource::Index if order.order_type.is_stop() => {
+                let price = self
+                    .index_prices
+                    .fresh(&order.market_id, chrono::Utc::now());
+                if price.is_none() {
+                    return Err(ExchangeError::InvalidParameter {
+                        message: format!(
+                            "{} has no fresh index price to trigger stop orders off",
+                            order.market_id
+                        ),
+                    });
+                }
+                price
+            }
+            TriggerSource::Index => None,
+        };
+        if let Some(reference_price) = reference_price {
+            if triggers::is_triggered(order, reference_price) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "Trigger price {} is already reached by the {} at {} in {}",
+                        order.trigger_price.unwrap_or_default(),
+                        match order.trigger_source {
+                            TriggerSource::LastTrade => "last trade",
+                            TriggerSource::Index => "index price",
+                        },
+                        reference_price,
+                        order.market_id
+                    ),
+                });
+            }
+        }
+
+        // Bounded markets can only rest orders at prices on their ladder
+        let market_id = self.markets.intern(&order.market_id);
+        let (layout, open_orders) = {
+            let orderbooks = self.orderbooks.read().await;
+            (
+                orderbooks.layout(market_id),
+                orderbooks.open_order_count(market_id, &order.user_address)
+                    + self
+                        .triggers
+                        .open_orders(&order.market_id, &order.user_address)
+                        .filter(|stop| self.oco.partner(stop.id).is_none())
+                        .count(),
+            )
+        };
+        if !layout.accepts_price(order.price) {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Price {} is outside the price range of market {}",
+                    order.price, order.market_id
+                ),
+            });
+        }
+
+        // Orders that may rest or wait for a trigger count against the
+        // open-order cap even if they would fill immediately; a
+        // one-cancels-other pair counts once, by its limit order
+        if (order.rests() || order.order_type.is_stop())
+            && open_orders >= MAX_OPEN_ORDERS_PER_MARKET
+        {
+            return Err(ExchangeError::TooManyOpenOrders {
+                user_address: order.user_address.clone(),
+                market_id: order.market_id.clone(),
+                max_open_orders: MAX_OPEN_ORDERS_PER_MARKET,
+            });
+        }
+
+        // Enforce per-user position and open-order limits
+        self.check_user_limits(order, &market).await?;
+
+        // A fill-or-kill order the book can't fill, or an order that would
+        // rest across its user's own orders, is refused before anything is
+        // locked or stored; a stop is checked once it triggers
+        if !order.order_type.is_stop() {
+            self.check_fill_or_kill(order).await?;
+            self.check_self_trade(order).await?;
+        }
+
+        Ok((market, mode))
+    }
+
+    /// Deal with what an executed order left unfilled: a market or
+    /// immediate-or-cancel order is closed and its remainder unlocked, a
+    /// good-till-cancelled limit order rests and is broadcast
+    async fn finish_order(
+        &mut self,
+        order: &mut crate::models::domain::Order,
+        market: &crate::models::domain::Market,
+        mode: ExecutionMode,
+        trades: &[crate::models::domain::Trade],
+        affected: &mut AffectedBalances,
+    ) -> Result<(), ExchangeError> {
+        // Fills move positions, so check their holders against the last mark
+        if mode != ExecutionMode::Spot && !trades.is_empty() {
+            let users: HashSet<String> = trades
+                .iter()
+                .flat_map(|t| [t.buyer_address.clone(), t.seller_address.clone()])
+                .collect();
+            self.check_margin(&order.market_id, Some(&users), affected)
+                .await;
+        }
+
+        if order.filled_size >= order.size {
+            return Ok(());
+        }
+        if !order.rests() {
+            // Market and IOC orders that don't fully fill are cancelled
+            // Orders that execute (even partially) are marked as Filled
+            // since they cannot remain on the book
+            order.status = if order.filled_size > 0 {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::Cancelled
+            };
+
+            // Update database with final status
+            self.db
+                .update_order_fill(order.id, order.filled_size, order.status)
+                .await?;
+
+            // Unlock the unfilled portion
+            let (token_to_unlock, amount_to_unlock) =
+                self.calculate_unlock_amount(order, market).await?;
+            self.db
+                .unlock_balance(&order.user_address, &token_to_unlock, amount_to_unlock)
+                .await?;
+
+            // Track unlocked balance
+            affected.insert((order.user_address.clone(), token_to_unlock));
+        } else {
+            // Limit orders stay on the book
+            let _ = self.event_tx.send(EngineEvent::OrderPlaced {
+                order: order.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Execute the stop orders that trades have triggered, oldest first,
+    /// until their own trades trigger no more; returns how many were triggered
+    async fn activate_stop_orders(&mut self, affected: &mut AffectedBalances) -> usize {
+        let mut activated = 0;
+        loop {
+            // Filled orders cancel the other order of their pair first, so
+            // it can't trigger too
+            self.cancel_oco_partners(affected).await;
+
+            let triggered = self.triggers.take_triggered();
+            if triggered.is_empty() {
+                break;
+            }
+            activated += triggered.len();
+            for order in triggered {
+                let (order_id, market_id) = (order.id, order.market_id.clone());
+                if let Err(e) = self.activate_stop_order(order, affected).await {
+                    log::error!("Failed to activate stop order {}: {}", order_id, e);
+                    self.alerts.raise(Alert::new(
+                        AlertKind::PersistenceFailure,
+                        &market_id,
+                        format!("Failed to activate stop order {}: {}", order_id, e),
+                    ));
+                }
+            }
+        }
+        activated
+    }
+
+    /// Turn a triggered stop order into the limit or market order it executes
+    /// as, and match it like a newly placed one
+    async fn activate_stop_order(
+        &mut self,
+        mut order: crate::models::domain::Order,
+        affected: &mut AffectedBalances,
+    ) -> Result<(), ExchangeError> {
+        // Users frozen while their stop waited may only cancel
+        if self.restriction(&order.user_address).is_some() {
+            self.settle_cancelled_orders(
+                vec![order],
+                Some(CancelReason::AccountRestricted),
+                affected,
+            )
+            .await;
+            return Ok(());
+        }
+
+        // A stop of a one-cancels-other pair cancels the other order before
+        // it executes
+        self.oco.record_trigger(order.id);
+        self.cancel_oco_partners(affected).await;
+
+        // The stop has already left the trigger book, so if it can't execute
+        // it's cancelled rather than left holding its locked balance
+        if let Err(e) = self.execute_stop_order(&mut order, affected).await {
+            self.settle_cancelled_orders(vec![order], None, affected)
+                .await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Execute a triggered stop order as the limit or market order it becomes
+    async fn execute_stop_order(
+        &mut self,
+        order: &mut crate::models::domain::Order,
+        affected: &mut AffectedBalances,
+    ) -> Result<(), ExchangeError> {
+        let market = self.db.get_market(&order.market_id).await?;
+        order.order_type = order.order_type.execution_type();
+        self.db.trigger_order(order.id, order.order_type).await?;
+        log::info!(
+            "Stop order {} triggered at {:?} in {}",
+            order.id,
+            order.trigger_price,
+            order.market_id
+        );
+
+        // A stop that would rest across its user's own orders is cancelled
+        // without trading
+        if let Err(e) = self.check_self_trade(order).await {
+            log::info!("Stop order {} cancelled: {}", order.id, e);
+            self.settle_cancelled_orders(
+                vec![order.clone()],
+                Some(CancelReason::SelfTrade),
+                affected,
+            )
+            .await;
+            return Ok(());
+        }
+
+        // A fill-or-kill stop the book can't fill is cancelled without trading
+        let mode = self.execution_mode(&order.market_id);
+        let trades = match self.check_fill_or_kill(order).await {
+            Ok(()) => self.execute_order(order, &market, mode, affected).await?,
+            Err(e) => {
+                log::info!("Stop order {} killed: {}", order.id, e);
+                vec![]
+            }
+        };
+        self.finish_order(order, &market, mode, &trades, affected)
+            .await?;
+
+        // Nobody is waiting on a response, so report how an order that
+        // couldn't rest ended
+        if !order.rests() && order.filled_size < order.size {
+            let _ = self.event_tx.send(EngineEvent::OrderPlaced {
+                order: order.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Refuse a fill-or-kill order that the book can't fill completely right
+    /// now; orders with any other time in force always pass
+    async fn check_fill_or_kill(
+        &self,
+        order: &crate::models::domain::Order,
+    ) -> Result<(), ExchangeError> {
+        if order.time_in_force != TimeInForce::Fok {
+            return Ok(());
+        }
+
+        let available = {
+            let orderbooks = self.orderbooks.read().await;
+            self.markets
+                .get(&order.market_id)
+                .and_then(|market_id| orderbooks.get(market_id))
+                .map_or(0, |orderbook| {
+                    Matcher::available_liquidity(order, orderbook)
+                })
+        };
+        let size = order.size - order.filled_size;
+        if available < size {
+            return Err(ExchangeError::InsufficientLiquidity {
+                market_id: order.market_id.clone(),
+                size,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// Refuse an order that would rest across one of its user's own orders
+    ///
+    /// Matching skips a user's own orders rather than trading with them, so
+    /// the remainder of such an order would rest on the far side of them and
+    /// cross the book. Orders that never rest always pass.
+    async fn check_self_trade(
+        &self,
+        order: &crate::models::domain::Order,
+    ) -> Result<(), ExchangeError> {
+        if !order.rests() {
+            return Ok(());
+        }
+
+        let crosses = {
+            let orderbooks = self.orderbooks.read().await;
+            self.markets
+                .get(&order.market_id)
+                .and_then(|market_id| orderbooks.get(market_id))
+                .is_some_and(|orderbook| Matcher::crosses_own_order(order, orderbook))
+        };
+        if crosses {
+            return Err(ExchangeError::SelfTrade {
+                market_id: order.market_id.clone(),
+                price: order.price,
+            });
+        }
+        Ok(())
+    }
+
+    /// How a market's trades are settled
+    fn execution_mode(&self, market_id: &str) -> ExecutionMode {
+        match self.perpetuals.get(market_id) {
+            Some(perpetual) => ExecutionMode::Perpetual {
+                initial_margin_bps: perpetual.initial_margin_bps,
+            },
+            None => ExecutionMode::Spot,
+        }
+    }
+
+    /// Match an order persisted in the database against its book, settle the
+    /// trades and broadcast them along with the orders they filled
+    ///
+    /// Updates the order's fill and status; the caller decides what happens to
+    /// an unfilled remainder. Raises an alert if the trades cannot be persisted.
+    async fn execute_order(
+        &mut self,
+        order: &mut crate::models::domain::Order,
+        market: &crate::models::domain::Market,
+        mode: ExecutionMode,
+        affected: &mut AffectedBalances,
+    ) -> Result<Vec<crate::models::domain::Trade>, ExchangeError> {
+        let market_id = self.markets.intern(&order.market_id);
+
+        // Get matches from matcher and apply them
+        let (matches, trades) = {
+            let mut orderbooks = self.orderbooks.write().await;
+            let orderbook = orderbooks.get_or_create_by_id(market_id);
+
+            // Match order against orderbook
+            let matches = Matcher::match_order(order, orderbook);
+
+            // Execute trades if we have matches (also updates order status in DB)
+            let (trades, executor_affected) = if !matches.is_empty() {
+                let referral = self.referrals.get(&order.user_address);
+                match Executor::execute(
+                    self.db.clone(),
+                    &matches,
+                    order,
+                    market,
+                    referral,
+                    &self.fee_routing,
+                    &self.fee_overrides,
+                    mode,
+                )
+                .await
+                {
+                    Ok((trades, exec_affected)) => (trades, exec_affected),
+                    Err(e) => {
+                        self.alerts.raise(Alert::new(
+                            AlertKind::PersistenceFailure,
+                            &order.market_id,
+                            format!("Failed to persist trades for order {}: {}", order.id, e),
+                        ));
+                        return Err(e);
+                    }
+                }
+            } else {
+                (vec![], HashSet::new())
+            };
+
+            // Track all balances affected by execution
+            affected.extend(executor_affected);
+
+            // Update orderbook with executed trades
+            orderbook.apply_trades(order, &trades, market);
+
+            (matches, trades)
+        };
+
+        if let Some(last) = trades.last() {
+            self.collars.record_trade(&order.market_id, last.price);
+            self.triggers.record_trade(&order.market_id, last.price);
+        }
+
+        // Broadcast trade events and queue them for analytics
+        for trade in &trades {
+            self.oco.record_trade(trade);
+            self.analytics.record(trade.clone());
+            let _ = self.event_tx.send(EngineEvent::TradeExecuted {
+                market: market_id,
+                trade: trade.clone(),
             });
         }
 
@@ -302,6 +1380,11 @@ impl MatchingEngine {
                     status: maker_status,
                     created_at: maker_order.created_at,
                     updated_at: chrono::Utc::now(),
+                    cancel_reason: None,
+                    trigger_price: maker_order.trigger_price,
+                    trigger_source: maker_order.trigger_source,
+                    time_in_force: maker_order.time_in_force,
+                    expires_at: maker_order.expires_at,
                 },
             });
         }
@@ -322,78 +1405,485 @@ impl MatchingEngine {
             });
         }
 
-        // Handle unfilled/partially filled orders based on order type
-        if order.filled_size < order.size {
-            match order.order_type {
-                crate::models::domain::OrderType::Market => {
-                    // Market orders that don't fully fill are cancelled
-                    // (IOC - Immediate or Cancel behavior)
-                    // Market orders that execute (even partially) are marked as Filled
-                    // since they cannot remain on the book
-                    order.status = if total_matched > 0 {
-                        OrderStatus::Filled
-                    } else {
-                        OrderStatus::Cancelled
-                    };
+        Ok(trades)
+    }
 
-                    // Update database with final status
-                    if let Err(e) = self
-                        .db
-                        .update_order_fill(order.id, order.filled_size, order.status)
-                        .await
-                    {
-                        return (Err(e), affected);
-                    }
+    /// Check an order against the user's limits in its market, if they have any
+    async fn check_user_limits(
+        &self,
+        order: &crate::models::domain::Order,
+        market: &crate::models::domain::Market,
+    ) -> Result<(), ExchangeError> {
+        let Some(limits) = self.limits.get(&order.user_address, &order.market_id) else {
+            return Ok(());
+        };
 
-                    // Unlock the unfilled portion
-                    let unfilled_size = order.size - order.filled_size;
-                    if unfilled_size > 0 {
-                        let (token_to_unlock, amount_to_unlock) = match order.side {
-                            crate::models::domain::Side::Buy => {
-                                match order.price.checked_mul(unfilled_size) {
-                                    Some(quote_amount) => {
-                                        (market.quote_ticker.clone(), quote_amount)
-                                    }
-                                    None => {
-                                        return (
-                                            Err(ExchangeError::InvalidParameter {
-                                                message: "Unlock amount overflow".to_string(),
-                                            }),
-                                            affected,
-                                        );
-                                    }
-                                }
-                            }
-                            crate::models::domain::Side::Sell => {
-                                (market.base_ticker.clone(), unfilled_size)
-                            }
-                        };
-
-                        if let Err(e) = self
-                            .db
-                            .unlock_balance(&order.user_address, &token_to_unlock, amount_to_unlock)
-                            .await
-                        {
-                            return (Err(e), affected);
-                        }
+        let base_token = self.db.get_token(&market.base_ticker).await?;
+        let exposure = Exposure {
+            position: self
+                .db
+                .get_net_position(&order.user_address, &order.market_id)
+                .await?,
+            open_notional: self
+                .db
+                .get_open_order_notional(&order.user_address, &order.market_id, base_token.decimals)
+                .await?,
+        };
+        let notional = order
+            .price
+            .checked_mul(order.size)
+            .map(|value| value / 10u128.pow(base_token.decimals as u32))
+            .ok_or(ExchangeError::OrderValueOverflow)?;
+
+        limits::check_limits(
+            limits,
+            exposure,
+            order.side,
+            order.size,
+            notional,
+            order.rests(),
+        )
+    }
 
-                        // Track unlocked balance
-                        affected.insert((order.user_address.clone(), token_to_unlock));
-                    }
-                }
-                crate::models::domain::OrderType::Limit => {
-                    // Limit orders stay on the book
-                    let _ = self.event_tx.send(EngineEvent::OrderPlaced {
-                        order: order.clone(),
-                    });
-                }
+    /// Handle setting a user's limits in a market
+    async fn handle_set_user_limits(
+        &mut self,
+        user_address: String,
+        market_id: String,
+        max_position: Option<u128>,
+        max_open_notional: Option<u128>,
+    ) -> Result<crate::models::domain::UserLimits, ExchangeError> {
+        self.db.get_market(&market_id).await?;
+        self.db.get_user(&user_address).await?;
+
+        let limits = self
+            .db
+            .set_user_limits(&user_address, &market_id, max_position, max_open_notional)
+            .await?;
+        self.limits.set(limits.clone());
+
+        log::info!(
+            "Set limits for {} in {}: max_position={:?}, max_open_notional={:?}",
+            user_address,
+            market_id,
+            max_position,
+            max_open_notional
+        );
+        Ok(limits)
+    }
+
+    /// Handle setting a user's referrer
+    async fn handle_set_referral(
+        &mut self,
+        user_address: String,
+        referrer_address: String,
+        share_bps: u32,
+    ) -> Result<Referral, ExchangeError> {
+        if share_bps > 10000 {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!("Referral share {} bps exceeds 10000", share_bps),
+            });
+        }
+        if user_address == referrer_address {
+            return Err(ExchangeError::InvalidParameter {
+                message: "Users cannot refer themselves".to_string(),
+            });
+        }
+        self.db.get_user(&user_address).await?;
+        self.db.get_user(&referrer_address).await?;
+
+        let referral = self
+            .db
+            .set_referral(&user_address, &referrer_address, share_bps)
+            .await?;
+        self.referrals.insert(user_address, referral.clone());
+
+        log::info!(
+            "Set referrer of {} to {} with a {} bps share",
+            referral.user_address,
+            referral.referrer_address,
+            share_bps
+        );
+        Ok(referral)
+    }
+
+    /// Handle setting or clearing the fees a user pays in a market
+    ///
+    /// Overrides may only lower fees: orders reserve collateral for the
+    /// market's fees, and a higher rate could outgrow it.
+    async fn handle_set_fee_override(
+        &mut self,
+        user_address: String,
+        market_id: String,
+        maker_fee_bps: Option<i32>,
+        taker_fee_bps: Option<i32>,
+    ) -> Result<Option<FeeOverride>, ExchangeError> {
+        self.db.get_user(&user_address).await?;
+        let market = self.db.get_market(&market_id).await?;
+        if let Some(bps) = maker_fee_bps {
+            if !(-10000..=market.maker_fee_bps).contains(&bps) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "Maker fee override {} bps must be between -10000 and the market's {}",
+                        bps, market.maker_fee_bps
+                    ),
+                });
+            }
+        }
+        if let Some(bps) = taker_fee_bps {
+            if !(0..=market.taker_fee_bps).contains(&bps) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "Taker fee override {} bps must be between 0 and the market's {}",
+                        bps, market.taker_fee_bps
+                    ),
+                });
+            }
+        }
+
+        let fee_override = self
+            .db
+            .set_fee_override(&user_address, &market_id, maker_fee_bps, taker_fee_bps)
+            .await?;
+        match &fee_override {
+            Some(fee_override) => {
+                self.fee_overrides.set(fee_override.clone());
+                log::info!(
+                    "Set fees of {} in {} to maker {:?} bps, taker {:?} bps",
+                    user_address,
+                    market_id,
+                    maker_fee_bps,
+                    taker_fee_bps
+                );
+            }
+            None => {
+                self.fee_overrides.clear(&user_address, &market_id);
+                log::info!("Cleared fee override of {} in {}", user_address, market_id);
+            }
+        }
+        Ok(fee_override)
+    }
+
+    /// Handle engaging a kill switch, optionally cancelling every resting order in scope
+    async fn handle_engage_kill_switch(
+        &mut self,
+        market_id: Option<String>,
+        reason: Option<String>,
+        cancel_orders: bool,
+    ) -> (
+        Result<(KillSwitch, OrdersCancelled), ExchangeError>,
+        AffectedBalances,
+    ) {
+        let mut affected = HashSet::new();
+
+        if let Some(market_id) = &market_id {
+            if let Err(e) = self.db.get_market(market_id).await {
+                return (Err(e), affected);
             }
         }
 
+        // Persist first so the switch survives a restart
+        let switch = KillSwitch {
+            market_id,
+            reason,
+            engaged_at: chrono::Utc::now(),
+        };
+        if let Err(e) = self.db.engage_kill_switch(&switch).await {
+            return (Err(e), affected);
+        }
+        self.kill_switches.engage(switch.clone());
+
+        log::warn!(
+            "Kill switch engaged for {}: {}",
+            switch.market_id.as_deref().unwrap_or("all markets"),
+            switch.reason.as_deref().unwrap_or("no reason given")
+        );
+        self.alerts.raise(Alert::new(
+            AlertKind::MarketHalted,
+            switch.market_id.as_deref().unwrap_or("all markets"),
+            format!(
+                "Kill switch engaged: {}",
+                switch.reason.as_deref().unwrap_or("no reason given")
+            ),
+        ));
+
+        let cancelled_order_ids = if cancel_orders {
+            let mut cancelled_orders = self
+                .orderbooks
+                .write()
+                .await
+                .cancel_market_orders(switch.market_id.as_deref());
+            cancelled_orders.extend(
+                self.triggers
+                    .cancel_market_orders(switch.market_id.as_deref()),
+            );
+            self.settle_cancelled_orders(
+                cancelled_orders,
+                Some(CancelReason::KillSwitch),
+                &mut affected,
+            )
+            .await
+        } else {
+            Vec::new()
+        };
+
+        let count = cancelled_order_ids.len();
         (
-            Ok(OrderPlaced {
-                order: order.into(),
-                trades: trades.into_iter().map(|t| t.into()).collect(),
+            Ok((
+                switch,
+                OrdersCancelled {
+                    cancelled_order_ids,
+                    count,
+                },
+            )),
+            affected,
+        )
+    }
+
+    /// Handle releasing a kill switch
+    async fn handle_release_kill_switch(
+        &mut self,
+        market_id: Option<String>,
+    ) -> Result<(), ExchangeError> {
+        self.db.release_kill_switch(market_id.as_deref()).await?;
+        if self.kill_switches.release(market_id.as_deref()) {
+            log::warn!(
+                "Kill switch released for {}",
+                market_id.as_deref().unwrap_or("all markets")
+            );
+        }
+        Ok(())
+    }
+
+    /// Handle a market status change
+    /// Halting or delisting cancels every resting order in the market so none are
+    /// left orphaned in the book with their balances locked
+    async fn handle_set_market_status(
+        &mut self,
+        market_id: String,
+        status: MarketStatus,
+    ) -> (Result<OrdersCancelled, ExchangeError>, AffectedBalances) {
+        let mut affected = HashSet::new();
+
+        let current = self
+            .inactive_markets
+            .get(&market_id)
+            .copied()
+            .unwrap_or_default();
+        if current == MarketStatus::Delisted && status != MarketStatus::Delisted {
+            return (
+                Err(ExchangeError::InvalidParameter {
+                    message: format!("Market {} is delisted and cannot be reopened", market_id),
+                }),
+                affected,
+            );
+        }
+
+        if let Err(e) = self.db.set_market_status(&market_id, status).await {
+            return (Err(e), affected);
+        }
+        if status == MarketStatus::Active {
+            self.inactive_markets.remove(&market_id);
+        } else {
+            self.inactive_markets.insert(market_id.clone(), status);
+        }
+        log::warn!("Market {} is now {} (was {})", market_id, status, current);
+        let _ = self.event_tx.send(EngineEvent::MarketStatusChanged {
+            market_id: market_id.clone(),
+            status,
+        });
+        if status != MarketStatus::Active {
+            self.alerts.raise(Alert::new(
+                AlertKind::MarketHalted,
+                &market_id,
+                format!("Market is now {} (was {})", status, current),
+            ));
+        }
+
+        let cancelled_order_ids = if status == MarketStatus::Active {
+            Vec::new()
+        } else {
+            let mut cancelled_orders = self
+                .orderbooks
+                .write()
+                .await
+                .cancel_market_orders(Some(&market_id));
+            cancelled_orders.extend(self.triggers.cancel_market_orders(Some(&market_id)));
+            self.settle_cancelled_orders(
+                cancelled_orders,
+                Some(CancelReason::MarketHalted),
+                &mut affected,
+            )
+            .await
+        };
+
+        let count = cancelled_order_ids.len();
+        (
+            Ok(OrdersCancelled {
+                cancelled_order_ids,
+                count,
+            }),
+            affected,
+        )
+    }
+
+    /// Handle an admin override of a market's price collar
+    async fn handle_set_price_collar(
+        &mut self,
+        market_id: String,
+        collar_bps: Option<u32>,
+    ) -> Result<(), ExchangeError> {
+        if collar_bps == Some(0) {
+            return Err(ExchangeError::InvalidParameter {
+                message: "Price collar must be greater than 0 bps".to_string(),
+            });
+        }
+        self.db.get_market(&market_id).await?;
+
+        self.db
+            .set_price_collar_override(&market_id, collar_bps)
+            .await?;
+        self.collars.set_override(&market_id, collar_bps);
+
+        log::warn!(
+            "Price collar for {} overridden to {}",
+            market_id,
+            collar_bps.map_or("disabled".to_string(), |bps| format!("{} bps", bps))
+        );
+        Ok(())
+    }
+
+    /// Handle opening a market created while running
+    async fn handle_open_market(
+        &mut self,
+        market_id: String,
+        layout: LadderLayout,
+        collar_bps: Option<u32>,
+    ) -> Result<(), ExchangeError> {
+        if collar_bps == Some(0) {
+            return Err(ExchangeError::InvalidParameter {
+                message: "Price collar must be greater than 0 bps".to_string(),
+            });
+        }
+        self.db.get_market(&market_id).await?;
+
+        if !self.orderbooks.write().await.open(&market_id, layout) {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "{} has resting orders, its price ladder can only change on restart",
+                    market_id
+                ),
+            });
+        }
+        if let Some(collar_bps) = collar_bps {
+            self.collars.configure(&market_id, collar_bps);
+        }
+        if let Some(perpetual) = self.db.get_perpetual_market(&market_id).await? {
+            self.perpetuals.insert(market_id.clone(), perpetual);
+        }
+
+        log::info!("Opened market {} ({:?} price ladder)", market_id, layout);
+        Ok(())
+    }
+
+    /// Handle changing the share of a revenue source routed to the insurance fund
+    async fn handle_set_fee_route(
+        &mut self,
+        source: RevenueSource,
+        insurance_bps: u32,
+    ) -> Result<FeeRoute, ExchangeError> {
+        if insurance_bps > 10000 {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "Insurance share must be at most 10000 bps, got {}",
+                    insurance_bps
+                ),
+            });
+        }
+
+        let route = self.db.set_fee_route(source, insurance_bps).await?;
+        self.fee_routing.set(&route);
+
+        log::warn!(
+            "Fee route for {} set to {} bps to the insurance fund",
+            source,
+            insurance_bps
+        );
+        Ok(route)
+    }
+
+    /// Status of a frozen or banned account, or of the restricted master of a sub-account
+    fn restriction(&self, user_address: &str) -> Option<UserStatus> {
+        self.restricted_users
+            .get(user_address)
+            .or_else(|| {
+                self.restricted_users
+                    .get(SubAccount::owner_of(user_address))
+            })
+            .copied()
+    }
+
+    /// Handle changing a user's account status
+    /// Banning always cancels the user's and its sub-accounts' resting orders;
+    /// freezing only when asked
+    async fn handle_set_user_status(
+        &mut self,
+        user_address: String,
+        status: UserStatus,
+        cancel_orders: bool,
+    ) -> (Result<OrdersCancelled, ExchangeError>, AffectedBalances) {
+        let mut affected = HashSet::new();
+
+        if let Err(e) = self.db.set_user_status(&user_address, status).await {
+            return (Err(e), affected);
+        }
+        let previous = if status == UserStatus::Active {
+            self.restricted_users.remove(&user_address)
+        } else {
+            self.restricted_users.insert(user_address.clone(), status)
+        };
+        log::warn!(
+            "User {} is now {} (was {})",
+            user_address,
+            status,
+            previous.unwrap_or_default()
+        );
+
+        let cancelled_order_ids = if status == UserStatus::Banned
+            || (status == UserStatus::Frozen && cancel_orders)
+        {
+            // Sub-accounts answer to their master, so their orders go too
+            let mut accounts = vec![user_address.clone()];
+            match self.db.list_sub_accounts(&user_address).await {
+                Ok(sub_accounts) => accounts.extend(sub_accounts.into_iter().map(|s| s.address)),
+                Err(e) => return (Err(e), affected),
+            }
+            let cancelled_orders = {
+                let mut orderbooks = self.orderbooks.write().await;
+                accounts
+                    .iter()
+                    .flat_map(|account| {
+                        let mut orders = orderbooks.cancel_all_orders(account, None);
+                        orders.extend(self.triggers.cancel_all_orders(account, None));
+                        orders
+                    })
+                    .collect()
+            };
+            self.settle_cancelled_orders(
+                cancelled_orders,
+                Some(CancelReason::AccountRestricted),
+                &mut affected,
+            )
+            .await
+        } else {
+            Vec::new()
+        };
+
+        let count = cancelled_order_ids.len();
+        (
+            Ok(OrdersCancelled {
+                cancelled_order_ids,
+                count,
             }),
             affected,
         )
@@ -408,13 +1898,17 @@ impl MatchingEngine {
     ) -> (Result<OrderCancelled, ExchangeError>, AffectedBalances) {
         let mut affected = HashSet::new();
 
-        // Cancel order using orderbooks method (handles search and ownership verification)
-        let cancelled_order = {
-            let mut orderbooks = self.orderbooks.write().await;
-            match orderbooks.cancel_order(order_id, &user_address) {
-                Ok(order) => order,
-                Err(e) => return (Err(e), affected),
-            }
+        // Cancel order using orderbooks method (handles search and ownership verification),
+        // falling back to the stop orders still waiting for their trigger
+        let cancelled = self
+            .orderbooks
+            .write()
+            .await
+            .cancel_order(order_id, &user_address)
+            .or_else(|_| self.triggers.cancel_order(order_id, &user_address));
+        let cancelled_order = match cancelled {
+            Ok(order) => order,
+            Err(e) => return (Err(e), affected),
         };
 
         // Get market config to determine which token to unlock
@@ -428,48 +1922,33 @@ impl MatchingEngine {
 
         if unfilled_size > 0 {
             // Determine which token and amount to unlock based on order side
-            let (token_to_unlock, amount_to_unlock) = match cancelled_order.side {
-                crate::models::domain::Side::Buy => {
-                    // Buy order: unlock quote tokens (price * unfilled_size)
-                    match cancelled_order.price.checked_mul(unfilled_size) {
-                        Some(quote_amount) => (market.quote_ticker, quote_amount),
-                        None => {
-                            return (
-                                Err(ExchangeError::InvalidParameter {
-                                    message: "Unlock amount overflow".to_string(),
-                                }),
-                                affected,
-                            );
-                        }
-                    }
-                }
-                crate::models::domain::Side::Sell => {
-                    // Sell order: unlock base tokens (unfilled_size)
-                    (market.base_ticker, unfilled_size)
-                }
-            };
+            let (token_to_unlock, amount_to_unlock) =
+                match self.cancel_unlock_amount(&cancelled_order, &market).await {
+                    Ok(v) => v,
+                    Err(e) => return (Err(e), affected),
+                };
 
-            // Unlock the balance
+            // Unlock the balance of the account the order was placed for
             if let Err(e) = self
                 .db
-                .unlock_balance(&user_address, &token_to_unlock, amount_to_unlock)
+                .unlock_balance(
+                    &cancelled_order.user_address,
+                    &token_to_unlock,
+                    amount_to_unlock,
+                )
                 .await
             {
                 return (Err(e), affected);
             }
 
             // Track unlocked balance
-            affected.insert((user_address.clone(), token_to_unlock));
+            affected.insert((cancelled_order.user_address.clone(), token_to_unlock));
         }
 
         // Update order status in database
         if let Err(e) = self
             .db
-            .update_order_fill(
-                order_id,
-                cancelled_order.filled_size,
-                OrderStatus::Cancelled,
-            )
+            .cancel_order(order_id, cancelled_order.filled_size, None)
             .await
         {
             return (Err(e), affected);
@@ -478,7 +1957,8 @@ impl MatchingEngine {
         // Broadcast cancellation event
         let _ = self.event_tx.send(EngineEvent::OrderCancelled {
             order_id,
-            user_address: user_address.clone(),
+            user_address: cancelled_order.user_address.clone(),
+            reason: None,
         });
 
         (
@@ -497,12 +1977,68 @@ impl MatchingEngine {
         market_id: Option<String>,
     ) -> (Result<OrdersCancelled, ExchangeError>, AffectedBalances) {
         let mut affected = HashSet::new();
-        // Cancel all orders for the user using orderbooks method
-        let cancelled_orders = {
+        // Cancel all orders for the user using orderbooks method, and their stop orders
+        let mut cancelled_orders = {
             let mut orderbooks = self.orderbooks.write().await;
             orderbooks.cancel_all_orders(&user_address, market_id.as_deref())
         };
+        cancelled_orders.extend(
+            self.triggers
+                .cancel_all_orders(&user_address, market_id.as_deref()),
+        );
+
+        let cancelled_order_ids = self
+            .settle_cancelled_orders(cancelled_orders, None, &mut affected)
+            .await;
+
+        let count = cancelled_order_ids.len();
+        if count >= self.mass_cancel_orders {
+            self.alerts.raise(Alert::new(
+                AlertKind::MassCancel,
+                &user_address,
+                format!(
+                    "Cancelled {} orders in {} at once",
+                    count,
+                    market_id.as_deref().unwrap_or("all markets")
+                ),
+            ));
+        }
+
+        (
+            Ok(OrdersCancelled {
+                cancelled_order_ids,
+                count,
+            }),
+            affected,
+        )
+    }
+
+    /// Take orders the exchange is cancelling off their books or out of the
+    /// trigger book; orders that already filled or were cancelled are skipped
+    async fn take_open_orders(
+        &mut self,
+        orders: Vec<(uuid::Uuid, String)>,
+    ) -> Vec<crate::models::domain::Order> {
+        let mut orderbooks = self.orderbooks.write().await;
+        orders
+            .into_iter()
+            .filter_map(|(order_id, user_address)| {
+                orderbooks
+                    .cancel_order(order_id, &user_address)
+                    .or_else(|_| self.triggers.cancel_order(order_id, &user_address))
+                    .ok()
+            })
+            .collect()
+    }
 
+    /// Unlock balances, mark cancelled in the database and broadcast for orders
+    /// already removed from the book; returns the ids of the settled orders
+    async fn settle_cancelled_orders(
+        &mut self,
+        cancelled_orders: Vec<crate::models::domain::Order>,
+        reason: Option<CancelReason>,
+        affected: &mut AffectedBalances,
+    ) -> Vec<String> {
         let mut cancelled_order_ids = Vec::new();
 
         // Process each cancelled order
@@ -524,104 +2060,128 @@ impl MatchingEngine {
 
             if unfilled_size > 0 {
                 // Determine which token and amount to unlock based on order side
-                let (token_to_unlock, unlock_result) = match cancelled_order.side {
-                    crate::models::domain::Side::Buy => {
-                        // Buy order: unlock quote tokens (price * unfilled_size)
-                        match cancelled_order.price.checked_mul(unfilled_size) {
-                            Some(quote_amount) => {
-                                let result = self
-                                    .db
-                                    .unlock_balance(
-                                        &user_address,
-                                        &market.quote_ticker,
-                                        quote_amount,
-                                    )
-                                    .await;
-                                (market.quote_ticker.clone(), result)
-                            }
-                            None => {
-                                log::error!(
-                                    "Overflow calculating unlock amount for order {}",
-                                    order_id
-                                );
-                                continue;
-                            }
+                let (token_to_unlock, amount_to_unlock) =
+                    match self.cancel_unlock_amount(&cancelled_order, &market).await {
+                        Ok(v) => v,
+                        Err(e) => {
+                            log::error!(
+                                "Failed to calculate unlock amount for order {}: {}",
+                                order_id,
+                                e
+                            );
+                            continue;
                         }
-                    }
-                    crate::models::domain::Side::Sell => {
-                        // Sell order: unlock base tokens (unfilled_size)
-                        let result = self
-                            .db
-                            .unlock_balance(&user_address, &market.base_ticker, unfilled_size)
-                            .await;
-                        (market.base_ticker.clone(), result)
-                    }
-                };
+                    };
+                let unlock_result = self
+                    .db
+                    .unlock_balance(
+                        &cancelled_order.user_address,
+                        &token_to_unlock,
+                        amount_to_unlock,
+                    )
+                    .await;
 
                 // Log unlock failures but continue processing
                 if let Err(e) = unlock_result {
                     log::error!("Failed to unlock balance for order {}: {}", order_id, e);
+                    self.alerts.raise(Alert::new(
+                        AlertKind::PersistenceFailure,
+                        &cancelled_order.market_id,
+                        format!(
+                            "Failed to unlock balance for cancelled order {}: {}",
+                            order_id, e
+                        ),
+                    ));
                 } else {
                     // Track unlocked balance
-                    affected.insert((user_address.clone(), token_to_unlock));
+                    affected.insert((cancelled_order.user_address.clone(), token_to_unlock));
                 }
             }
 
-            // Update order status in database
+            // Update order status and why it was cancelled in database
             if let Err(e) = self
                 .db
-                .update_order_fill(
-                    order_id,
-                    cancelled_order.filled_size,
-                    OrderStatus::Cancelled,
-                )
+                .cancel_order(order_id, cancelled_order.filled_size, reason)
                 .await
             {
                 log::error!("Failed to update order {} status: {}", order_id, e);
+                self.alerts.raise(Alert::new(
+                    AlertKind::PersistenceFailure,
+                    &cancelled_order.market_id,
+                    format!("Failed to mark order {} cancelled: {}", order_id, e),
+                ));
                 continue;
             }
 
             // Broadcast cancellation event
             let _ = self.event_tx.send(EngineEvent::OrderCancelled {
                 order_id,
-                user_address: user_address.clone(),
+                user_address: cancelled_order.user_address.clone(),
+                reason,
             });
 
             cancelled_order_ids.push(order_id.to_string());
         }
 
-        let count = cancelled_order_ids.len();
-
-        (
-            Ok(OrdersCancelled {
-                cancelled_order_ids,
-                count,
-            }),
-            affected,
-        )
+        cancelled_order_ids
     }
 
     /// Spawn a background task that periodically broadcasts orderbook snapshots
-    /// Snapshots are sent every 1s for all active markets
+    /// Snapshots are sent every 1s for all active markets, best `SNAPSHOT_DEPTH` levels per side
+    /// Markets that haven't changed since the last tick reuse their cached snapshot
+    ///
+    /// Every `DEPTH_METRICS_INTERVAL_SECS` the snapshots are also measured and
+    /// written to ClickHouse, without holding up the next broadcast.
+    ///
+    /// While degraded, snapshots go out every `DEGRADED_SNAPSHOT_INTERVAL_SECS`
+    /// and depth samples are skipped, leaving the book lock and the event
+    /// channel to order entry.
     fn spawn_snapshot_broadcaster(&self) -> JoinHandle<()> {
         let event_tx = self.event_tx.clone();
         let orderbooks = Arc::clone(&self.orderbooks);
+        let db = self.db.clone();
+        let saturation = self.saturation.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(1000));
+            let mut cache = HashMap::new();
+            let mut ticks: u64 = 0;
             loop {
                 interval.tick().await;
+                ticks += 1;
+                let degraded = saturation.is_degraded();
+                if degraded && !ticks.is_multiple_of(DEGRADED_SNAPSHOT_INTERVAL_SECS) {
+                    continue;
+                }
 
-                // Get snapshots for all markets
-                let snapshots = {
+                // Rebuild snapshots only for markets whose book version moved
+                {
                     let orderbooks_read = orderbooks.read().await;
-                    orderbooks_read.snapshots()
-                };
+                    orderbooks_read.refresh_snapshots(SNAPSHOT_DEPTH, &mut cache);
+                }
 
                 // Broadcast each snapshot
-                for snapshot in snapshots {
+                for (market, snapshot) in &cache {
                     let _ = event_tx.send(EngineEvent::OrderbookSnapshot {
-                        orderbook: snapshot,
+                        market: *market,
+                        orderbook: snapshot.clone(),
+                    });
+                }
+
+                if ticks.is_multiple_of(DEPTH_METRICS_INTERVAL_SECS)
+                    && !cache.is_empty()
+                    && !degraded
+                {
+                    let now = chrono::Utc::now();
+                    let samples: Vec<_> = cache
+                        .values()
+                        .map(|snapshot| depth::depth_metrics(snapshot, DEPTH_METRICS_LEVELS, now))
+                        .collect();
+                    let db = db.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = db.insert_depth_metrics(&samples).await {
+                            log::error!("Failed to write {} depth samples: {}", samples.len(), e);
+                        }
                     });
                 }
             }
@@ -641,16 +2201,15 @@ impl MatchingEngine {
         }
 
         // Validate that price is greater than 0 for limit orders
-        if order.order_type == crate::models::domain::OrderType::Limit && order.price == 0 {
+        let limit = order.order_type.execution_type() == OrderType::Limit;
+        if limit && order.price == 0 {
             return Err(ExchangeError::InvalidParameter {
                 message: "Limit order price must be greater than 0".to_string(),
             });
         }
 
         // Validate tick size for limit orders only (price matters for limit orders)
-        if order.order_type == crate::models::domain::OrderType::Limit
-            && !order.price.is_multiple_of(market.tick_size)
-        {
+        if limit && !order.price.is_multiple_of(market.tick_size) {
             return Err(ExchangeError::InvalidParameter {
                 message: format!(
                     "Price {} is not a multiple of tick size {}",
@@ -659,6 +2218,52 @@ impl MatchingEngine {
             });
         }
 
+        // Stop orders need a trigger price on the tick, and only they take one
+        match order.trigger_price {
+            None if order.order_type.is_stop() => {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!("A {} order needs a trigger price", order.order_type),
+                });
+            }
+            Some(_) if !order.order_type.is_stop() => {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!("A {} order takes no trigger price", order.order_type),
+                });
+            }
+            Some(0) => {
+                return Err(ExchangeError::InvalidParameter {
+                    message: "Trigger price must be greater than 0".to_string(),
+                });
+            }
+            Some(trigger_price) if !trigger_price.is_multiple_of(market.tick_size) => {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "Trigger price {} is not a multiple of tick size {}",
+                        trigger_price, market.tick_size
+                    ),
+                });
+            }
+            _ => {}
+        }
+
+        // Only orders that rest or wait for a trigger live long enough to
+        // expire, and their expiry must still be ahead of them
+        if let Some(expires_at) = order.expires_at {
+            if !(order.rests() || order.order_type.is_stop()) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "A {} {} order never rests, so takes no expiry",
+                        order.time_in_force, order.order_type
+                    ),
+                });
+            }
+            if expires_at <= chrono::Utc::now() {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!("Expiry {} is not in the future", expires_at),
+                });
+            }
+        }
+
         // Validate lot size (size must be multiple of lot_size)
         if !order.size.is_multiple_of(market.lot_size) {
             return Err(ExchangeError::InvalidParameter {
@@ -689,6 +2294,12 @@ impl MatchingEngine {
         order: &crate::models::domain::Order,
         market: &crate::models::domain::Market,
     ) -> Result<(String, u128), ExchangeError> {
+        // Either side of a perpetual locks quote tokens as collateral
+        if self.perpetuals.contains_key(&market.id) {
+            let amount = self.perpetual_collateral(order, market, order.size).await?;
+            return Ok((market.quote_ticker.clone(), amount));
+        }
+
         match order.side {
             crate::models::domain::Side::Buy => {
                 // For buy orders, lock quote tokens
@@ -710,4 +2321,566 @@ impl MatchingEngine {
             }
         }
     }
+
+    /// Calculate which token and amount to unlock for the unfilled part of an order
+    /// Returns (token_ticker, amount_to_unlock)
+    async fn calculate_unlock_amount(
+        &self,
+        order: &crate::models::domain::Order,
+        market: &crate::models::domain::Market,
+    ) -> Result<(String, u128), ExchangeError> {
+        let unfilled_size = order.size - order.filled_size;
+
+        // Release what the unfilled size locked, to the atom
+        if self.perpetuals.contains_key(&market.id) {
+            let locked = self.perpetual_collateral(order, market, order.size).await?;
+            let used = self
+                .perpetual_collateral(order, market, order.filled_size)
+                .await?;
+            return Ok((market.quote_ticker.clone(), locked - used));
+        }
+
+        match order.side {
+            // Buy order: unlock quote tokens (price * unfilled_size)
+            crate::models::domain::Side::Buy => match order.price.checked_mul(unfilled_size) {
+                Some(quote_amount) => Ok((market.quote_ticker.clone(), quote_amount)),
+                None => Err(ExchangeError::InvalidParameter {
+                    message: "Unlock amount overflow".to_string(),
+                }),
+            },
+            // Sell order: unlock base tokens (unfilled_size)
+            crate::models::domain::Side::Sell => Ok((market.base_ticker.clone(), unfilled_size)),
+        }
+    }
+
+    /// Quote tokens an order in a perpetual market locks for `size` of it
+    async fn perpetual_collateral(
+        &self,
+        order: &crate::models::domain::Order,
+        market: &crate::models::domain::Market,
+        size: u128,
+    ) -> Result<u128, ExchangeError> {
+        let initial_margin_bps = self
+            .perpetuals
+            .get(&market.id)
+            .map_or(10_000, |perpetual| perpetual.initial_margin_bps);
+        let base_token = self.db.get_token(&market.base_ticker).await?;
+        perps::order_collateral(
+            order.price,
+            size,
+            10u128.pow(base_token.decimals as u32),
+            initial_margin_bps,
+            perps::collateral_fee_bps(market),
+        )
+        .ok_or_else(|| ExchangeError::InvalidParameter {
+            message: "Order value overflow when calculating collateral".to_string(),
+        })
+    }
+
+    /// Handle marking a perpetual market's positions to market and charging funding
+    async fn handle_settle_funding(
+        &mut self,
+        market_id: String,
+        mark_price: u128,
+        index_price: u128,
+        funding_rate_ppm: i64,
+    ) -> (Result<FundingSettlement, ExchangeError>, AffectedBalances) {
+        let mut affected = HashSet::new();
+        if !self.perpetuals.contains_key(&market_id) {
+            return (
+                Err(ExchangeError::InvalidParameter {
+                    message: format!("{} is not a perpetual market", market_id),
+                }),
+                affected,
+            );
+        }
+
+        let market = match self.db.get_market(&market_id).await {
+            Ok(m) => m,
+            Err(e) => return (Err(e), affected),
+        };
+        let scale = match self.db.get_token(&market.base_ticker).await {
+            Ok(token) => 10u128.pow(token.decimals as u32),
+            Err(e) => return (Err(e), affected),
+        };
+
+        let result = self
+            .db
+            .settle_funding(&market, scale, mark_price, index_price, funding_rate_ppm)
+            .await;
+        match result {
+            Ok((settlement, users)) => {
+                if let Ok(Some(perpetual)) = self.db.get_perpetual_market(&market_id).await {
+                    self.perpetuals.insert(market_id, perpetual);
+                }
+                for user_address in users {
+                    affected.insert((user_address, market.quote_ticker.clone()));
+                }
+                (Ok(settlement), affected)
+            }
+            Err(e) => {
+                self.alerts.raise(Alert::new(
+                    AlertKind::PersistenceFailure,
+                    &market_id,
+                    format!("Failed to settle funding: {}", e),
+                ));
+                (Err(e), affected)
+            }
+        }
+    }
+
+    /// Handle a new mark price for a perpetual market, liquidating the
+    /// positions in it that no longer cover their maintenance margin
+    async fn handle_update_mark_price(
+        &mut self,
+        market_id: String,
+        mark_price: u128,
+        index_price: u128,
+    ) -> (Result<Vec<Liquidation>, ExchangeError>, AffectedBalances) {
+        let mut affected = HashSet::new();
+        let Some(perpetual) = self.perpetuals.get_mut(&market_id) else {
+            return (
+                Err(ExchangeError::InvalidParameter {
+                    message: format!("{} is not a perpetual market", market_id),
+                }),
+                affected,
+            );
+        };
+        perpetual.mark_price = Some(mark_price);
+        perpetual.index_price = Some(index_price);
+        perpetual.mark_updated_at = Some(chrono::Utc::now());
+
+        let liquidations = self.check_margin(&market_id, None, &mut affected).await;
+        (Ok(liquidations), affected)
+    }
+
+    /// Handle changing how a user's positions are margined
+    async fn handle_set_margin_mode(
+        &mut self,
+        user_address: String,
+        mode: MarginMode,
+    ) -> Result<(), ExchangeError> {
+        // Switching would change which funds back positions already open
+        let positions = self.db.list_positions_by_user(&user_address).await?;
+        if positions.iter().any(|p| p.size != 0) {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "{} has open positions, margin mode can only change without any",
+                    user_address
+                ),
+            });
+        }
+        self.db.set_margin_mode(&user_address, mode).await?;
+
+        match mode {
+            MarginMode::Cross => self.cross_margin_users.insert(user_address.clone()),
+            MarginMode::Isolated => self.cross_margin_users.remove(&user_address),
+        };
+        log::info!("Margin mode of {} set to {}", user_address, mode);
+        Ok(())
+    }
+
+    /// Handle a taker accepting a quote: settle their request off the book and
+    /// broadcast the trade and both sides' filled orders
+    ///
+    /// Held to the same user and market checks as an order, but the trade
+    /// never reaches the book, so it fills no resting orders and doesn't move
+    /// the price collar.
+    async fn handle_execute_rfq(
+        &mut self,
+        request_id: uuid::Uuid,
+        quote_id: uuid::Uuid,
+        user_address: String,
+    ) -> (Result<RfqExecution, ExchangeError>, AffectedBalances) {
+        let mut affected = HashSet::new();
+
+        let checked = async {
+            let request = self.db.get_quote_request(request_id).await?;
+            let quote = self.db.get_quote(quote_id).await?;
+            for user in [&user_address, &quote.maker_address] {
+                if let Some(status) = self.restriction(user) {
+                    return Err(ExchangeError::UserNotActive {
+                        user_address: user.clone(),
+                        status,
+                    });
+                }
+            }
+            if self.perpetuals.contains_key(&request.market_id) {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "{} is a perpetual market and takes no RFQs",
+                        request.market_id
+                    ),
+                });
+            }
+            if let Some(status) = self.inactive_markets.get(&request.market_id) {
+                return Err(ExchangeError::MarketNotActive {
+                    market_id: request.market_id.clone(),
+                    status: *status,
+                });
+            }
+            if self.kill_switches.blocking(&request.market_id).is_some() {
+                return Err(ExchangeError::CancelOnly {
+                    market_id: request.market_id.clone(),
+                });
+            }
+            self.db.get_market(&request.market_id).await
+        };
+        let market = match checked.await {
+            Ok(market) => market,
+            Err(e) => return (Err(e), affected),
+        };
+
+        let execution = match self
+            .db
+            .execute_rfq(request_id, quote_id, &user_address)
+            .await
+        {
+            Ok(execution) => execution,
+            Err(e) => return (Err(e), affected),
+        };
+        let trade = &execution.trade;
+        for user in [&trade.buyer_address, &trade.seller_address] {
+            for ticker in [&market.base_ticker, &market.quote_ticker] {
+                affected.insert((user.clone(), ticker.clone()));
+            }
+        }
+
+        self.analytics.record(trade.clone());
+        let _ = self.event_tx.send(EngineEvent::TradeExecuted {
+            market: self.markets.intern(&trade.market_id),
+            trade: trade.clone(),
+        });
+        for order in &execution.orders {
+            let _ = self.event_tx.send(EngineEvent::OrderPlaced {
+                order: order.clone(),
+            });
+        }
+        log::info!(
+            "RFQ {} filled: {} {} at {} in {}",
+            request_id,
+            trade.size,
+            trade.side,
+            trade.price,
+            trade.market_id
+        );
+
+        (Ok(execution), affected)
+    }
+
+    /// Liquidate the positions in a perpetual market whose equity is below
+    /// their maintenance margin at the market's last mark
+    ///
+    /// Checks the holders in `users`, or every holder if `None`. Isolated
+    /// positions are checked on their own margin; cross-margined users on all
+    /// of their positions in the market's quote token plus their free balance.
+    /// Failures are alerted and left to the next check.
+    async fn check_margin(
+        &mut self,
+        market_id: &str,
+        users: Option<&HashSet<String>>,
+        affected: &mut AffectedBalances,
+    ) -> Vec<Liquidation> {
+        let mut liquidations = Vec::new();
+        let Some(perpetual) = self.perpetuals.get(market_id).cloned() else {
+            return liquidations;
+        };
+        // Nothing to check against until the market has been marked
+        let Some(mark_price) = perpetual.mark_price else {
+            return liquidations;
+        };
+
+        let checked = async {
+            let market = self.db.get_market(market_id).await?;
+            let scale = self.scale(&market).await?;
+            let positions = self.db.list_positions_by_market(market_id).await?;
+            Ok::<_, ExchangeError>((market, scale, positions))
+        };
+        let (market, scale, positions) = match checked.await {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("Failed to check margin in {}: {}", market_id, e);
+                return liquidations;
+            }
+        };
+
+        // A liquidation's fills and settlement change other positions in the market
+        let mut stale = false;
+        for mut position in positions {
+            if users.is_some_and(|users| !users.contains(&position.user_address)) {
+                continue;
+            }
+            if stale {
+                let user_address = position.user_address.clone();
+                match self.db.get_positions(market_id, &[&user_address]).await {
+                    Ok(mut fresh) => match fresh.remove(&user_address) {
+                        Some(fresh) => position = fresh,
+                        None => continue,
+                    },
+                    Err(e) => {
+                        log::error!("Failed to reload position of {}: {}", user_address, e);
+                        continue;
+                    }
+                }
+            }
+            if position.size == 0 {
+                continue;
+            }
+            let cross = self.cross_margin_users.contains(&position.user_address);
+            let health = if cross {
+                self.cross_health(&position.user_address, &market.quote_ticker)
+                    .await
+            } else {
+                margin::position_health(
+                    &position,
+                    mark_price,
+                    scale,
+                    perpetual.maintenance_margin_bps,
+                )
+                .ok_or(ExchangeError::OrderValueOverflow)
+            };
+            match health {
+                Ok(health) if health.is_liquidatable() => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    log::error!(
+                        "Failed to check margin of {} in {}: {}",
+                        position.user_address,
+                        market_id,
+                        e
+                    );
+                    continue;
+                }
+            }
+
+            let mode = if cross {
+                MarginMode::Cross
+            } else {
+                MarginMode::Isolated
+            };
+            match self
+                .liquidate(&position, &market, scale, &perpetual, mode, affected)
+                .await
+            {
+                Ok(Some(liquidation)) => {
+                    liquidations.push(liquidation);
+                    stale = true;
+                }
+                Ok(None) => log::warn!(
+                    "Could not liquidate {} in {}: nothing to match within the liquidation price",
+                    position.user_address,
+                    market_id
+                ),
+                Err(e) => {
+                    self.alerts.raise(Alert::new(
+                        AlertKind::PersistenceFailure,
+                        market_id,
+                        format!("Failed to liquidate {}: {}", position.user_address, e),
+                    ));
+                    stale = true;
+                }
+            }
+        }
+
+        liquidations
+    }
+
+    /// Equity and maintenance margin of a cross-margined user's positions in
+    /// markets quoted in `quote_ticker`, at each market's last mark
+    async fn cross_health(
+        &self,
+        user_address: &str,
+        quote_ticker: &str,
+    ) -> Result<Health, ExchangeError> {
+        let mut healths = Vec::new();
+        for position in self.db.list_positions_by_user(user_address).await? {
+            let Some(perpetual) = self.perpetuals.get(&position.market_id) else {
+                continue;
+            };
+            let market = self.db.get_market(&position.market_id).await?;
+            if position.size == 0 || market.quote_ticker != quote_ticker {
+                continue;
+            }
+            let scale = self.scale(&market).await?;
+            let mark_price = perpetual.mark_price.unwrap_or(position.entry_price);
+            healths.push(
+                margin::position_health(
+                    &position,
+                    mark_price,
+                    scale,
+                    perpetual.maintenance_margin_bps,
+                )
+                .ok_or(ExchangeError::OrderValueOverflow)?,
+            );
+        }
+
+        let balance = self.db.get_balance(user_address, quote_ticker).await?;
+        margin::account_health(
+            balance.amount.saturating_sub(balance.open_interest),
+            healths,
+        )
+        .ok_or(ExchangeError::OrderValueOverflow)
+    }
+
+    /// Force-close a position through the book
+    ///
+    /// Cancels the user's other orders in the market, then sends an order
+    /// for the whole position that fills what it can within the liquidation
+    /// price and cancels the rest. The closed part is marked to market right
+    /// away, so the loss is collected from the margin it released, and the
+    /// liquidation fee is charged from what remains. Returns `None` if
+    /// nothing could be closed.
+    async fn liquidate(
+        &mut self,
+        position: &Position,
+        market: &crate::models::domain::Market,
+        scale: u128,
+        perpetual: &PerpetualMarket,
+        margin_mode: MarginMode,
+        affected: &mut AffectedBalances,
+    ) -> Result<Option<Liquidation>, ExchangeError> {
+        let user_address = &position.user_address;
+        let mark_price = perpetual.mark_price.unwrap_or(position.entry_price);
+
+        let mut resting = {
+            let mut orderbooks = self.orderbooks.write().await;
+            orderbooks.cancel_all_orders(user_address, Some(&market.id))
+        };
+        resting.extend(
+            self.triggers
+                .cancel_all_orders(user_address, Some(&market.id)),
+        );
+        self.settle_cancelled_orders(resting, Some(CancelReason::Liquidation), affected)
+            .await;
+
+        let price = margin::liquidation_limit_price(
+            position,
+            mark_price,
+            perpetual.maintenance_margin_bps,
+            market.tick_size,
+        )
+        .ok_or(ExchangeError::OrderValueOverflow)?;
+        let now = chrono::Utc::now();
+        let mut order = crate::models::domain::Order {
+            id: uuid::Uuid::new_v4(),
+            user_address: user_address.clone(),
+            market_id: market.id.clone(),
+            price,
+            size: position.size.unsigned_abs(),
+            side: if position.size > 0 {
+                crate::models::domain::Side::Sell
+            } else {
+                crate::models::domain::Side::Buy
+            },
+            order_type: OrderType::Limit,
+            status: OrderStatus::Pending,
+            filled_size: 0,
+            created_at: now,
+            updated_at: now,
+            cancel_reason: None,
+            trigger_price: None,
+            trigger_source: TriggerSource::LastTrade,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: None,
+        };
+        self.db.create_order(&order).await?;
+
+        let mode = ExecutionMode::Liquidation {
+            initial_margin_bps: perpetual.initial_margin_bps,
+        };
+        self.execute_order(&mut order, market, mode, affected)
+            .await?;
+
+        // Liquidations never rest on the book
+        if order.filled_size == 0 {
+            order.status = OrderStatus::Cancelled;
+            order.cancel_reason = Some(CancelReason::Liquidation);
+            self.db
+                .cancel_order(order.id, 0, order.cancel_reason)
+                .await?;
+        } else if order.filled_size < order.size {
+            order.status = OrderStatus::Filled;
+            self.db
+                .update_order_fill(order.id, order.filled_size, order.status)
+                .await?;
+        }
+        if order.filled_size == 0 {
+            let _ = self.event_tx.send(EngineEvent::OrderCancelled {
+                order_id: order.id,
+                user_address: user_address.clone(),
+                reason: Some(CancelReason::Liquidation),
+            });
+            return Ok(None);
+        }
+
+        let remaining = self
+            .db
+            .get_positions(&market.id, &[user_address.as_str()])
+            .await?
+            .remove(user_address)
+            .map_or(0, |p| p.margin);
+        let index_price = perpetual.index_price.unwrap_or(mark_price);
+        let (_, users) = self
+            .db
+            .mark_to_market(
+                market,
+                scale,
+                mark_price,
+                index_price,
+                user_address,
+                position.margin.saturating_sub(remaining),
+            )
+            .await?;
+        for user in users {
+            affected.insert((user, market.quote_ticker.clone()));
+        }
+
+        let fee = margin::initial_margin(
+            mark_price,
+            order.filled_size,
+            scale,
+            perpetual.liquidation_fee_bps,
+        )
+        .ok_or(ExchangeError::OrderValueOverflow)?;
+        let routing = &self.fee_routing;
+        let fee = self
+            .db
+            .charge_liquidation_fee(user_address, &market.quote_ticker, fee, |amount| {
+                routing.split(RevenueSource::Liquidations, amount)
+            })
+            .await?;
+        affected.insert((user_address.clone(), market.quote_ticker.clone()));
+
+        let liquidation = Liquidation {
+            user_address: user_address.clone(),
+            market_id: market.id.clone(),
+            order_id: order.id,
+            size: position.size,
+            closed_size: order.filled_size,
+            mark_price,
+            margin_mode,
+            fee,
+            timestamp: now,
+        };
+        log::warn!(
+            "Liquidated {} of {}'s {} position in {} at mark {} ({} margin, fee {})",
+            liquidation.closed_size,
+            user_address,
+            position.size,
+            market.id,
+            mark_price,
+            margin_mode,
+            fee
+        );
+        let _ = self.event_tx.send(EngineEvent::PositionLiquidated {
+            liquidation: liquidation.clone(),
+        });
+        Ok(Some(liquidation))
+    }
+
+    /// Atoms in one whole base token of a market
+    async fn scale(&self, market: &crate::models::domain::Market) -> Result<u128, ExchangeError> {
+        let base_token = self.db.get_token(&market.base_ticker).await?;
+        Ok(10u128.pow(base_token.decimals as u32))
+    }
 }
@@ -0,0 +1,291 @@
+// one-cancels-other pairs waiting for either order to fill
+
+use super::executor::AffectedBalances;
+use super::matcher::Matcher;
+use super::MatchingEngine;
+use crate::errors::ExchangeError;
+use crate::models::api::OcoPlaced;
+use crate::models::domain::{CancelReason, EngineEvent, Market, OcoLink, Order, OrderType, Trade};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Open one-cancels-other pairs, owned by the engine
+///
+/// The first trade filling either order of a pair, or its stop triggering,
+/// unlinks it and queues the other order for the engine to cancel. An order
+/// cancelled on its own unlinks its pair and leaves the other order open by
+/// itself.
+#[derive(Debug, Default)]
+pub struct OcoBook {
+    // order_id -> (other order_id, user_address)
+    partners: HashMap<Uuid, (Uuid, String)>,
+    // other orders of filled pairs, waiting to be cancelled
+    filled: Vec<(Uuid, String)>,
+    // queued order_id -> the order of its pair that filled, which keeps
+    // the balance they reserved together
+    kept_by: HashMap<Uuid, Uuid>,
+}
+
+impl OcoBook {
+    pub fn new(links: Vec<OcoLink>) -> Self {
+        let mut book = Self::default();
+        for link in &links {
+            book.link(link);
+        }
+        book
+    }
+
+    pub fn link(&mut self, link: &OcoLink) {
+        self.partners.insert(
+            link.first_order_id,
+            (link.second_order_id, link.user_address.clone()),
+        );
+        self.partners.insert(
+            link.second_order_id,
+            (link.first_order_id, link.user_address.clone()),
+        );
+    }
+
+    /// Unlink the pairs of the orders a trade filled, queueing the other
+    /// order of each for `take_filled`
+    pub fn record_trade(&mut self, trade: &Trade) {
+        for order_id in [trade.buyer_order_id, trade.seller_order_id] {
+            self.record_fill(order_id);
+        }
+    }
+
+    /// Unlink the pair of a stop order that triggered, queueing the other
+    /// order for `take_filled`
+    pub fn record_trigger(&mut self, order_id: Uuid) {
+        self.record_fill(order_id);
+    }
+
+    fn record_fill(&mut self, order_id: Uuid) {
+        if let Some((other_id, user_address)) = self.partners.remove(&order_id) {
+            self.partners.remove(&other_id);
+            self.filled.push((other_id, user_address));
+            self.kept_by.insert(other_id, order_id);
+        }
+    }
+
+    /// Unlink an order that is being cancelled, returning the order it
+    /// shares its pair's reserved balance with
+    pub fn unlink(&mut self, order_id: Uuid) -> Option<Uuid> {
+        if let Some((other_id, _)) = self.partners.remove(&order_id) {
+            self.partners.remove(&other_id);
+            return Some(other_id);
+        }
+        self.kept_by.remove(&order_id)
+    }
+
+    /// Remove and return the orders to cancel because the other order of
+    /// their pair filled, with the user each was placed by
+    pub fn take_filled(&mut self) -> Vec<(Uuid, String)> {
+        std::mem::take(&mut self.filled)
+    }
+
+    /// The other order of an order's pair, if it is linked
+    pub fn partner(&self, order_id: Uuid) -> Option<Uuid> {
+        self.partners.get(&order_id).map(|(other_id, _)| *other_id)
+    }
+
+    /// Pairs linked
+    pub fn len(&self) -> usize {
+        self.partners.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.partners.is_empty()
+    }
+}
+
+impl MatchingEngine {
+    /// Place a limit order and a stop order as a one-cancels-other pair
+    ///
+    /// Only one order of a pair can ever fill, so the pair reserves the
+    /// balance its larger order needs once. The limit order must rest
+    /// without filling on arrival, so the pair is linked before either can
+    /// trade. Both orders are checked before anything is locked or stored:
+    /// the pair is placed whole or refused without trace.
+    pub(super) async fn handle_place_oco(
+        &mut self,
+        first: Order,
+        second: Order,
+    ) -> (Result<OcoPlaced, ExchangeError>, AffectedBalances) {
+        let mut affected = HashSet::new();
+        let result = self.place_oco(first, second, &mut affected).await;
+        (result, affected)
+    }
+
+    async fn place_oco(
+        &mut self,
+        mut first: Order,
+        mut second: Order,
+        affected: &mut AffectedBalances,
+    ) -> Result<OcoPlaced, ExchangeError> {
+        if first.order_type.is_stop() == second.order_type.is_stop() {
+            return Err(ExchangeError::InvalidParameter {
+                message: "A one-cancels-other pair is one limit order and one stop order"
+                    .to_string(),
+            });
+        }
+        let mut checked = Vec::new();
+        for order in [&first, &second] {
+            self.check_oco_order(order).await?;
+            checked.push(self.check_order(order).await?);
+        }
+        let (market, mode) = checked.swap_remove(0);
+
+        let (token, first_lock) = self.calculate_lock_amount(&first, &market).await?;
+        let (_, second_lock) = self.calculate_lock_amount(&second, &market).await?;
+        let reserved = first_lock.max(second_lock);
+        self.db
+            .lock_balance(&first.user_address, &token, reserved)
+            .await?;
+        affected.insert((first.user_address.clone(), token.clone()));
+
+        let link = OcoLink {
+            id: Uuid::new_v4(),
+            user_address: first.user_address.clone(),
+            first_order_id: first.id,
+            second_order_id: second.id,
+            created_at: Utc::now(),
+        };
+        if let Err(e) = self.store_oco(&first, &second, &link).await {
+            let _ = self
+                .db
+                .unlock_balance(&link.user_address, &token, reserved)
+                .await;
+            return Err(e);
+        }
+        self.oco.link(&link);
+
+        // The limit order rests without trading, as checked above; the stop
+        // waits for its trigger
+        self.expiries.insert(&first);
+        self.expiries.insert(&second);
+        let (limit, stop) = if first.order_type.is_stop() {
+            (&mut second, &first)
+        } else {
+            (&mut first, &second)
+        };
+        let placed = match self.execute_order(limit, &market, mode, affected).await {
+            Ok(trades) => {
+                self.finish_order(limit, &market, mode, &trades, affected)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = placed {
+            self.oco.unlink(limit.id);
+            self.withdraw_oco(limit, stop).await;
+            let _ = self
+                .db
+                .unlock_balance(&link.user_address, &token, reserved)
+                .await;
+            return Err(e);
+        }
+        self.triggers.insert(stop.clone());
+        let _ = self.event_tx.send(EngineEvent::OrderPlaced {
+            order: stop.clone(),
+        });
+
+        Ok(OcoPlaced {
+            oco_id: link.id.to_string(),
+            first: first.into(),
+            second: second.into(),
+        })
+    }
+
+    /// Store both orders of a pair and the link between them, withdrawing
+    /// whatever was stored if any of it fails
+    async fn store_oco(
+        &self,
+        first: &Order,
+        second: &Order,
+        link: &OcoLink,
+    ) -> Result<(), ExchangeError> {
+        let stored = async {
+            self.db.create_order(first).await?;
+            self.db.create_order(second).await?;
+            self.db.create_oco_link(link).await
+        }
+        .await;
+        if stored.is_err() {
+            self.withdraw_oco(first, second).await;
+        }
+        stored
+    }
+
+    /// Mark the orders of a pair that couldn't be placed as cancelled, so
+    /// they aren't recovered on restart
+    async fn withdraw_oco(&self, first: &Order, second: &Order) {
+        for order in [first, second] {
+            let _ = self.db.cancel_order(order.id, 0, None).await;
+        }
+    }
+
+    /// Cancel the orders whose pair's other order has filled
+    pub(super) async fn cancel_oco_partners(&mut self, affected: &mut AffectedBalances) {
+        let filled = self.oco.take_filled();
+        if filled.is_empty() {
+            return;
+        }
+
+        let cancelled = self.take_open_orders(filled).await;
+        self.settle_cancelled_orders(cancelled, Some(CancelReason::LinkedOrderFilled), affected)
+            .await;
+    }
+
+    /// What cancelling an order unlocks: the unfilled part of its own lock,
+    /// less what the other order of its pair still needs of the balance
+    /// they reserved together
+    pub(super) async fn cancel_unlock_amount(
+        &mut self,
+        order: &Order,
+        market: &Market,
+    ) -> Result<(String, u128), ExchangeError> {
+        let (token, amount) = self.calculate_unlock_amount(order, market).await?;
+        let Some(other_id) = self.oco.unlink(order.id) else {
+            return Ok((token, amount));
+        };
+        let other = self.db.get_order(&other_id).await?;
+        let (_, own_lock) = self.calculate_lock_amount(order, market).await?;
+        let (_, other_lock) = self.calculate_lock_amount(&other, market).await?;
+        Ok((token, amount.saturating_sub(own_lock.min(other_lock))))
+    }
+
+    /// Refuse an order that can't wait for the other order of its pair to fill
+    async fn check_oco_order(&self, order: &Order) -> Result<(), ExchangeError> {
+        if !(order.rests() || order.order_type.is_stop()) {
+            return Err(ExchangeError::InvalidParameter {
+                message: format!(
+                    "A {} {} order can't be part of a one-cancels-other pair",
+                    order.time_in_force, order.order_type
+                ),
+            });
+        }
+
+        if order.order_type == OrderType::Limit {
+            let available = {
+                let orderbooks = self.orderbooks.read().await;
+                self.markets
+                    .get(&order.market_id)
+                    .and_then(|market_id| orderbooks.get(market_id))
+                    .map_or(0, |orderbook| {
+                        Matcher::available_liquidity(order, orderbook)
+                    })
+            };
+            if available > 0 {
+                return Err(ExchangeError::InvalidParameter {
+                    message: format!(
+                        "One-cancels-other limit order at {} would fill on arrival",
+                        order.price
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,16 +2,23 @@
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::collections::VecDeque;
+use std::sync::Arc;
 
+use crate::engine::ladder::{LadderLayout, PriceLadder};
+use crate::engine::markets::{MarketId, MarketRegistry};
 use crate::errors::{ExchangeError, Result};
-use crate::models::domain::{Market, Order, OrderStatus, OrderbookLevel, OrderbookSnapshot, Side};
+use crate::models::domain::{
+    Market, Order, OrderStatus, OrderbookLevel, OrderbookSnapshot, QueuePosition, Side, SubAccount,
+};
 use chrono::Utc;
 use uuid::Uuid;
 
 pub struct Orderbooks {
-    // market id -> orderbook
-    orderbooks: HashMap<String, Orderbook>,
+    // indexed by MarketId, so lookups never hash the market symbol
+    orderbooks: Vec<Option<Orderbook>>,
+    markets: MarketRegistry,
+    // markets that use something other than the default tree ladder
+    layouts: HashMap<MarketId, LadderLayout>,
 }
 
 impl Default for Orderbooks {
@@ -22,27 +29,157 @@ impl Default for Orderbooks {
 
 impl Orderbooks {
     pub fn new() -> Self {
+        Self::with_registry(MarketRegistry::new())
+    }
+
+    /// Create orderbooks that intern market symbols into a shared registry
+    pub fn with_registry(markets: MarketRegistry) -> Self {
         Self {
-            orderbooks: HashMap::new(),
+            orderbooks: Vec::new(),
+            markets,
+            layouts: HashMap::new(),
         }
     }
 
+    /// Registry mapping market symbols to the ids used here
+    pub fn markets(&self) -> &MarketRegistry {
+        &self.markets
+    }
+
+    /// Choose how a market's orderbook indexes prices
+    ///
+    /// Only applies to books created afterwards, so configure markets before
+    /// recovery or trading starts.
+    pub fn set_layout(&mut self, market_id: &str, layout: LadderLayout) {
+        let id = self.markets.intern(market_id);
+        self.layouts.insert(id, layout);
+    }
+
+    /// Price ladder layout used for a market
+    pub fn layout(&self, id: MarketId) -> LadderLayout {
+        self.layouts.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Create a market's orderbook with `layout`, for a market opened while running
+    ///
+    /// An existing empty book is rebuilt with the new layout; one with resting
+    /// orders keeps its own. Returns whether the book now uses `layout`.
+    pub fn open(&mut self, market_id: &str, layout: LadderLayout) -> bool {
+        let id = self.markets.intern(market_id);
+        if let Some(Some(orderbook)) = self.orderbooks.get(id.index()) {
+            if orderbook.layout() == layout {
+                return true;
+            }
+            if !orderbook.is_empty() {
+                return false;
+            }
+            self.orderbooks[id.index()] = None;
+        }
+
+        self.layouts.insert(id, layout);
+        self.get_or_create_by_id(id);
+        true
+    }
+
     /// Get or create a mutable reference to an orderbook for a market
     /// Creates the orderbook if it doesn't exist
     pub fn get_or_create(&mut self, market_id: &str) -> &mut Orderbook {
+        let id = self.markets.intern(market_id);
+        self.get_or_create_by_id(id)
+    }
+
+    /// Same as `get_or_create` for an already interned market
+    pub fn get_or_create_by_id(&mut self, id: MarketId) -> &mut Orderbook {
+        if self.orderbooks.len() <= id.index() {
+            self.orderbooks.resize_with(id.index() + 1, || None);
+        }
+        let markets = &self.markets;
+        let layout = self.layouts.get(&id).copied().unwrap_or_default();
+        self.orderbooks[id.index()].get_or_insert_with(|| {
+            let symbol = markets.symbol(id).expect("MarketId from another registry");
+            Orderbook::with_layout(symbol.to_string(), layout)
+        })
+    }
+
+    /// Orderbook for a market, if it has one
+    pub fn get(&self, id: MarketId) -> Option<&Orderbook> {
+        self.orderbooks.get(id.index())?.as_ref()
+    }
+
+    /// Number of markets with a book in memory
+    pub fn book_count(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Number of orders resting across all markets
+    pub fn resting_order_count(&self) -> usize {
+        self.iter()
+            .map(|(_, orderbook)| orderbook.order_count())
+            .sum()
+    }
+
+    /// Drop the empty books of the markets `retire` picks, returning how many went
+    ///
+    /// A dropped book is recreated with its market's layout if it's needed again.
+    pub fn prune_empty(&mut self, mut retire: impl FnMut(&str) -> bool) -> usize {
+        let mut pruned = 0;
+        for slot in &mut self.orderbooks {
+            let prune = slot
+                .as_ref()
+                .is_some_and(|orderbook| orderbook.is_empty() && retire(&orderbook.market_id));
+            if prune {
+                *slot = None;
+                pruned += 1;
+            }
+        }
+        pruned
+    }
+
+    /// Markets whose book is crossed, with their best bid and best ask
+    pub fn crossed_books(&self) -> Vec<(String, u128, u128)> {
+        self.iter()
+            .filter_map(|(_, orderbook)| {
+                let (bid, ask) = orderbook.crossed()?;
+                Some((orderbook.market_id.clone(), bid, ask))
+            })
+            .collect()
+    }
+
+    /// A market's resting orders, each price level's in queue order; none
+    /// if it has no book
+    pub fn resting_orders(&self, market_id: &str) -> Vec<Order> {
+        self.markets
+            .get(market_id)
+            .and_then(|id| self.get(id))
+            .map_or_else(Vec::new, Orderbook::resting_orders)
+    }
+
+    /// Number of orders a user has resting in a market
+    pub fn open_order_count(&self, id: MarketId, user_address: &str) -> usize {
+        self.get(id)
+            .map_or(0, |orderbook| orderbook.open_order_count(user_address))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (MarketId, &Orderbook)> {
         self.orderbooks
-            .entry(market_id.to_string())
-            .or_insert_with(|| Orderbook::new(market_id.to_string()))
+            .iter()
+            .enumerate()
+            .filter_map(|(index, book)| Some((MarketId::from_index(index), book.as_ref()?)))
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut Orderbook> {
+        self.orderbooks.iter_mut().flatten()
     }
 
     /// Cancel an order across all markets
-    /// Returns the cancelled order if found and ownership is verified
+    /// Returns the cancelled order if found and ownership is verified; a
+    /// master owns its sub-accounts' orders
     pub fn cancel_order(&mut self, order_id: Uuid, user_address: &str) -> Result<Order> {
         // Search all markets for the order
-        for orderbook in self.orderbooks.values_mut() {
+        for orderbook in self.iter_mut() {
             if let Some(order) = orderbook.remove_order(order_id) {
                 // Verify ownership
-                if order.user_address != user_address {
+                if !SubAccount::owns(user_address, &order.user_address) {
                     // Put the order back since ownership check failed
                     orderbook.add_order(order);
                     return Err(ExchangeError::OrderNotFound); // Return not found for security
@@ -54,6 +191,15 @@ impl Orderbooks {
         Err(ExchangeError::OrderNotFound)
     }
 
+    /// Find where a user's or its sub-accounts' order stands in its queue,
+    /// across all markets
+    /// Another user's order is reported as not found
+    pub fn queue_position(&self, order_id: Uuid, user_address: &str) -> Result<QueuePosition> {
+        self.iter()
+            .find_map(|(_, orderbook)| orderbook.queue_position(order_id, user_address))
+            .ok_or(ExchangeError::OrderNotFound)
+    }
+
     /// Cancel all orders for a user, optionally filtered by market
     /// Returns a vector of all cancelled orders
     pub fn cancel_all_orders(&mut self, user_address: &str, market_id: Option<&str>) -> Vec<Order> {
@@ -61,12 +207,16 @@ impl Orderbooks {
 
         // If market_id is specified, only cancel orders in that market
         if let Some(market) = market_id {
-            if let Some(orderbook) = self.orderbooks.get_mut(market) {
+            let book = self
+                .markets
+                .get(market)
+                .and_then(|id| self.orderbooks.get_mut(id.index())?.as_mut());
+            if let Some(orderbook) = book {
                 cancelled_orders.extend(orderbook.remove_all_user_orders(user_address));
             }
         } else {
             // Cancel orders across all markets
-            for orderbook in self.orderbooks.values_mut() {
+            for orderbook in self.iter_mut() {
                 cancelled_orders.extend(orderbook.remove_all_user_orders(user_address));
             }
         }
@@ -74,34 +224,168 @@ impl Orderbooks {
         cancelled_orders
     }
 
+    /// Cancel every resting order, optionally only in one market
+    pub fn cancel_market_orders(&mut self, market_id: Option<&str>) -> Vec<Order> {
+        match market_id {
+            Some(market) => self
+                .markets
+                .get(market)
+                .and_then(|id| self.orderbooks.get_mut(id.index())?.as_mut())
+                .map(Orderbook::remove_all_orders)
+                .unwrap_or_default(),
+            None => self
+                .iter_mut()
+                .flat_map(Orderbook::remove_all_orders)
+                .collect(),
+        }
+    }
+
     /// Generate snapshots for all markets
     pub fn snapshots(&self) -> Vec<OrderbookSnapshot> {
-        self.orderbooks
-            .values()
-            .map(|orderbook| orderbook.snapshot())
+        self.iter()
+            .map(|(_, orderbook)| orderbook.snapshot())
             .collect()
     }
+
+    /// Best `n` levels per side for one market, if it has a book
+    pub fn snapshot_top_n(&self, market: MarketId, n: usize) -> Option<OrderbookSnapshot> {
+        self.get(market)
+            .map(|orderbook| orderbook.snapshot_top_n(n))
+    }
+
+    /// Bring a per-market cache of depth-limited snapshots up to date
+    /// Only markets whose version moved since their cached snapshot are rebuilt,
+    /// and markets whose book was pruned are dropped
+    pub fn refresh_snapshots(
+        &self,
+        depth: usize,
+        cache: &mut HashMap<MarketId, OrderbookSnapshot>,
+    ) {
+        cache.retain(|id, _| self.get(*id).is_some());
+        for (id, orderbook) in self.iter() {
+            let stale = cache
+                .get(&id)
+                .is_none_or(|cached| cached.version != orderbook.version());
+            if stale {
+                cache.insert(id, orderbook.snapshot_top_n(depth));
+            }
+        }
+    }
 }
 
 pub struct Orderbook {
     pub market_id: String,
-    pub bids: BTreeMap<u128, VecDeque<Order>>, // Descending price (highest first)
-    pub asks: BTreeMap<u128, VecDeque<Order>>, // Ascending price (lowest first)
+    // Orders are shared with in-flight `Match`es, so matching never copies them
+    // Both ladders iterate in ascending price; walk bids in reverse for best first
+    pub bids: PriceLadder,
+    pub asks: PriceLadder,
+    layout: LadderLayout,
+
+    // Remaining size per price level, kept in step with the queues above so
+    // snapshots don't have to walk every resting order
+    bid_depth: BTreeMap<u128, u128>,
+    ask_depth: BTreeMap<u128, u128>,
+    // Resting orders per user, for the open-order cap
+    open_orders: HashMap<String, usize>,
+    version: u64,
 }
 
 impl Orderbook {
     pub fn new(market_id: String) -> Self {
+        Self::with_layout(market_id, LadderLayout::Tree)
+    }
+
+    /// Create an orderbook whose levels are indexed with the given layout
+    pub fn with_layout(market_id: String, layout: LadderLayout) -> Self {
         Self {
             market_id,
-            bids: BTreeMap::new(),
-            asks: BTreeMap::new(),
+            bids: PriceLadder::new(layout),
+            asks: PriceLadder::new(layout),
+            layout,
+            bid_depth: BTreeMap::new(),
+            ask_depth: BTreeMap::new(),
+            open_orders: HashMap::new(),
+            version: 0,
         }
     }
 
+    pub fn layout(&self) -> LadderLayout {
+        self.layout
+    }
+
+    /// Counter bumped on every change to the book
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Whether no orders rest in this book
+    pub fn is_empty(&self) -> bool {
+        self.open_orders.is_empty()
+    }
+
+    /// Number of orders resting in this book
+    pub fn order_count(&self) -> usize {
+        self.open_orders.values().sum()
+    }
+
+    /// Best bid and best ask, if the bid reaches the ask
+    ///
+    /// Matching and self-trade prevention never leave a book crossed, so this
+    /// means the book and the orders behind it have drifted apart.
+    pub fn crossed(&self) -> Option<(u128, u128)> {
+        let (&bid, _) = self.bid_depth.last_key_value()?;
+        let (&ask, _) = self.ask_depth.first_key_value()?;
+        (bid >= ask).then_some((bid, ask))
+    }
+
+    /// Number of orders a user has resting in this book
+    pub fn open_order_count(&self, user_address: &str) -> usize {
+        self.open_orders.get(user_address).copied().unwrap_or(0)
+    }
+
+    /// Forget one of a user's resting orders
+    fn release_open_order(&mut self, user_address: &str) {
+        if let Some(count) = self.open_orders.get_mut(user_address) {
+            *count -= 1;
+            if *count == 0 {
+                self.open_orders.remove(user_address);
+            }
+        }
+    }
+
+    /// Add remaining size to a price level's aggregate
+    fn add_depth(&mut self, side: Side, price: u128, size: u128) {
+        let depth = match side {
+            Side::Buy => &mut self.bid_depth,
+            Side::Sell => &mut self.ask_depth,
+        };
+        if size > 0 {
+            *depth.entry(price).or_insert(0) += size;
+        }
+        self.version += 1;
+    }
+
+    /// Take remaining size off a price level's aggregate, dropping the level when empty
+    fn remove_depth(&mut self, side: Side, price: u128, size: u128) {
+        let depth = match side {
+            Side::Buy => &mut self.bid_depth,
+            Side::Sell => &mut self.ask_depth,
+        };
+        if let Some(level) = depth.get_mut(&price) {
+            *level = level.saturating_sub(size);
+            if *level == 0 {
+                depth.remove(&price);
+            }
+        }
+        self.version += 1;
+    }
+
     /// Apply executed trades to the orderbook
     /// - Updates filled amounts on maker orders
     /// - Removes fully filled orders
-    /// - Adds remaining taker order if not fully filled AND meets minimum size
+    /// - Adds remaining taker order if it rests (a good-till-cancelled limit
+    ///   order), is not fully filled AND meets minimum size; market and
+    ///   immediate-or-cancel orders never rest
     pub fn apply_trades(
         &mut self,
         taker_order: &Order,
@@ -123,7 +407,7 @@ impl Orderbook {
         let remaining_size = taker_order.size - total_matched;
 
         // Only add to book if remaining size meets minimum order size
-        if remaining_size > 0 && remaining_size >= market.min_size {
+        if taker_order.rests() && remaining_size > 0 && remaining_size >= market.min_size {
             let mut remaining_order = taker_order.clone();
             remaining_order.filled_size = total_matched;
             remaining_order.status = if total_matched > 0 {
@@ -140,54 +424,120 @@ impl Orderbook {
     /// Update an order's filled amount, remove if fully filled
     fn update_order_fill(&mut self, order_id: Uuid, fill_size: u128) {
         // Search both bids and asks
+        let mut filled = None;
         for (_, orders) in self.bids.iter_mut().chain(self.asks.iter_mut()) {
             if let Some(pos) = orders.iter().position(|o| o.id == order_id) {
-                let order = &mut orders[pos];
-                order.filled_size += fill_size;
-                order.updated_at = Utc::now();
+                let order = &orders[pos];
+                let remaining = order.size - order.filled_size;
+                filled = Some((
+                    order.side,
+                    order.price,
+                    fill_size.min(remaining),
+                    (fill_size >= remaining).then(|| order.user_address.clone()),
+                ));
 
-                // Remove if fully filled
-                if order.filled_size >= order.size {
-                    order.status = OrderStatus::Filled;
+                if fill_size >= remaining {
+                    // Fully filled: drop it from the book without touching the shared order
                     orders.remove(pos);
+                } else {
+                    // Copies the order only if a `Match` still holds it
+                    let order = Arc::make_mut(&mut orders[pos]);
+                    order.filled_size += fill_size;
+                    order.updated_at = Utc::now();
                 }
-                return;
+                break;
+            }
+        }
+
+        if let Some((side, price, size, closed_by)) = filled {
+            self.remove_depth(side, price, size);
+            if let Some(user_address) = closed_by {
+                self.release_open_order(&user_address);
             }
         }
     }
 
     /// Add an order to the orderbook
     pub fn add_order(&mut self, order: Order) {
+        self.add_depth(order.side, order.price, order.size - order.filled_size);
+        *self
+            .open_orders
+            .entry(order.user_address.clone())
+            .or_insert(0) += 1;
+
         let levels = match order.side {
             Side::Buy => &mut self.bids,
             Side::Sell => &mut self.asks,
         };
 
-        levels
-            .entry(order.price)
-            .or_insert_with(VecDeque::new)
-            .push_back(order);
+        levels.queue_mut(order.price).push_back(Arc::new(order));
     }
 
     /// Remove an order from the orderbook by ID (for cancellation)
     pub fn remove_order(&mut self, order_id: Uuid) -> Option<Order> {
+        let removed = self.take_order(order_id)?;
+        self.remove_depth(
+            removed.side,
+            removed.price,
+            removed.size - removed.filled_size,
+        );
+        self.release_open_order(&removed.user_address);
+        Some(removed)
+    }
+
+    fn take_order(&mut self, order_id: Uuid) -> Option<Order> {
         // Search bids
         for (_, orders) in self.bids.iter_mut() {
             if let Some(pos) = orders.iter().position(|o| o.id == order_id) {
-                return Some(orders.remove(pos).unwrap());
+                return orders.remove(pos).map(Arc::unwrap_or_clone);
             }
         }
 
         // Search asks
         for (_, orders) in self.asks.iter_mut() {
             if let Some(pos) = orders.iter().position(|o| o.id == order_id) {
-                return Some(orders.remove(pos).unwrap());
+                return orders.remove(pos).map(Arc::unwrap_or_clone);
             }
         }
 
         None
     }
 
+    /// Where one of a user's orders stands in its price level's queue
+    pub fn queue_position(&self, order_id: Uuid, user_address: &str) -> Option<QueuePosition> {
+        for (price, orders) in self.bids.iter().chain(self.asks.iter()) {
+            let Some(pos) = orders.iter().position(|o| o.id == order_id) else {
+                continue;
+            };
+            let order = &orders[pos];
+            if !SubAccount::owns(user_address, &order.user_address) {
+                return None;
+            }
+
+            let depth = match order.side {
+                Side::Buy => &self.bid_depth,
+                Side::Sell => &self.ask_depth,
+            };
+            let size_ahead = orders
+                .iter()
+                .take(pos)
+                .map(|o| o.size - o.filled_size)
+                .sum();
+            return Some(QueuePosition {
+                order_id,
+                market_id: self.market_id.clone(),
+                side: order.side,
+                price,
+                remaining: order.size - order.filled_size,
+                orders_ahead: pos as u64,
+                size_ahead,
+                level_size: depth.get(&price).copied().unwrap_or(0),
+            });
+        }
+
+        None
+    }
+
     /// Remove all orders for a specific user from this orderbook
     /// Returns a vector of all removed orders
     pub fn remove_all_user_orders(&mut self, user_address: &str) -> Vec<Order> {
@@ -199,7 +549,7 @@ impl Orderbook {
             while i < orders.len() {
                 if orders[i].user_address == user_address {
                     if let Some(order) = orders.remove(i) {
-                        removed_orders.push(order);
+                        removed_orders.push(Arc::unwrap_or_clone(order));
                     }
                 } else {
                     i += 1;
@@ -213,7 +563,7 @@ impl Orderbook {
             while i < orders.len() {
                 if orders[i].user_address == user_address {
                     if let Some(order) = orders.remove(i) {
-                        removed_orders.push(order);
+                        removed_orders.push(Arc::unwrap_or_clone(order));
                     }
                 } else {
                     i += 1;
@@ -221,45 +571,59 @@ impl Orderbook {
             }
         }
 
+        for order in &removed_orders {
+            self.remove_depth(order.side, order.price, order.size - order.filled_size);
+        }
+        self.open_orders.remove(user_address);
+
+        removed_orders
+    }
+
+    /// Every resting order, each price level's in queue order
+    pub fn resting_orders(&self) -> Vec<Order> {
+        self.bids
+            .iter()
+            .chain(self.asks.iter())
+            .flat_map(|(_, orders)| orders.iter().map(|order| Order::clone(order)))
+            .collect()
+    }
+
+    /// Remove every resting order from this orderbook
+    pub fn remove_all_orders(&mut self) -> Vec<Order> {
+        let mut removed_orders = Vec::new();
+        for (_, orders) in self.bids.iter_mut().chain(self.asks.iter_mut()) {
+            removed_orders.extend(orders.drain(..).map(Arc::unwrap_or_clone));
+        }
+
+        self.bid_depth.clear();
+        self.ask_depth.clear();
+        self.open_orders.clear();
+        self.version += 1;
+
         removed_orders
     }
 
     /// Generate a snapshot of the current orderbook state
+    /// Reads the per-level aggregates, so cost scales with price levels rather than orders
     pub fn snapshot(&self) -> OrderbookSnapshot {
-        // Aggregate bids by price level (highest to lowest)
-        let bids: Vec<OrderbookLevel> = self
-            .bids
-            .iter()
-            .rev() // BTreeMap is ascending, we want descending for bids
-            .map(|(price, orders)| {
-                let total_size: u128 = orders.iter().map(|o| o.size - o.filled_size).sum();
-                OrderbookLevel {
-                    price: *price,
-                    size: total_size,
-                }
-            })
-            .filter(|level| level.size > 0) // Only include levels with size
-            .collect();
+        self.snapshot_top_n(usize::MAX)
+    }
 
-        // Aggregate asks by price level (lowest to highest)
-        let asks: Vec<OrderbookLevel> = self
-            .asks
-            .iter()
-            .map(|(price, orders)| {
-                let total_size: u128 = orders.iter().map(|o| o.size - o.filled_size).sum();
-                OrderbookLevel {
-                    price: *price,
-                    size: total_size,
-                }
-            })
-            .filter(|level| level.size > 0) // Only include levels with size
-            .collect();
+    /// Snapshot of only the best `n` price levels on each side
+    /// Cost scales with `n`, not with how deep the book is
+    pub fn snapshot_top_n(&self, n: usize) -> OrderbookSnapshot {
+        let level = |(price, size): (&u128, &u128)| OrderbookLevel {
+            price: *price,
+            size: *size,
+        };
 
         OrderbookSnapshot {
             market_id: self.market_id.clone(),
-            bids,
-            asks,
+            // BTreeMap is ascending, we want descending for bids
+            bids: self.bid_depth.iter().rev().take(n).map(level).collect(),
+            asks: self.ask_depth.iter().take(n).map(level).collect(),
             timestamp: Utc::now(),
+            version: self.version,
         }
     }
 }
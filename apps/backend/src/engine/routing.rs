@@ -0,0 +1,38 @@
+// fee routing between the system accounts
+
+use crate::models::domain::{FeeRoute, RevenueSource};
+use std::collections::HashMap;
+
+/// Routing rules for exchange revenue, owned by the engine
+///
+/// Each revenue source sends `insurance_bps` of every amount to the insurance
+/// fund and the rest to the fee collector. Sources without a rule go entirely
+/// to the fee collector.
+#[derive(Debug, Default)]
+pub struct FeeRouting {
+    insurance_bps: HashMap<RevenueSource, u32>,
+}
+
+impl FeeRouting {
+    pub fn new(routes: Vec<FeeRoute>) -> Self {
+        let mut routing = Self::default();
+        for route in &routes {
+            routing.set(route);
+        }
+        routing
+    }
+
+    pub fn set(&mut self, route: &FeeRoute) {
+        self.insurance_bps.insert(route.source, route.insurance_bps);
+    }
+
+    pub fn insurance_bps(&self, source: RevenueSource) -> u32 {
+        self.insurance_bps.get(&source).copied().unwrap_or(0)
+    }
+
+    /// Split an amount into (fee collector, insurance fund) shares
+    pub fn split(&self, source: RevenueSource, amount: u128) -> (u128, u128) {
+        let insurance = amount * self.insurance_bps(source) as u128 / 10000;
+        (amount - insurance, insurance)
+    }
+}
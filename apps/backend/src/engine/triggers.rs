@@ -0,0 +1,252 @@
+// stop orders waiting for the last trade or index price to reach their trigger
+
+use super::executor::AffectedBalances;
+use super::MatchingEngine;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Order, Side, SubAccount, TriggerSource};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often the engine checks index-price stop orders against the index
+pub const INDEX_TRIGGER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Untriggered stop orders and the prices they trigger off, owned by the engine
+///
+/// A buy stop triggers once its reference price is at or above its trigger
+/// price, a sell stop once it is at or below it. The reference is the
+/// market's last trade, or its index price for stops placed with
+/// [`TriggerSource::Index`]. Triggered orders are handed back oldest first
+/// for the engine to execute as limit or market orders.
+#[derive(Debug, Default)]
+pub struct TriggerBook {
+    // market_id -> stop orders, oldest first
+    orders: HashMap<String, Vec<Order>>,
+    // market_id -> last trade price
+    last_prices: HashMap<String, u128>,
+    // market_id -> fresh index price
+    index_prices: HashMap<String, u128>,
+    // markets whose last trade or index price moved since triggered orders
+    // were last taken
+    moved: HashSet<String>,
+}
+
+/// Whether `price`, taken from the order's trigger source, reaches a stop
+/// order's trigger
+pub fn is_triggered(order: &Order, price: u128) -> bool {
+    let Some(trigger_price) = order.trigger_price else {
+        return false;
+    };
+    match order.side {
+        Side::Buy => price >= trigger_price,
+        Side::Sell => price <= trigger_price,
+    }
+}
+
+impl TriggerBook {
+    pub fn new(orders: Vec<Order>) -> Self {
+        let mut book = Self::default();
+        for order in orders {
+            book.insert(order);
+        }
+        book
+    }
+
+    /// Wait for a stop order's trigger; orders must be inserted in the order they were placed
+    pub fn insert(&mut self, order: Order) {
+        self.orders
+            .entry(order.market_id.clone())
+            .or_default()
+            .push(order);
+    }
+
+    pub fn last_price(&self, market_id: &str) -> Option<u128> {
+        self.last_prices.get(market_id).copied()
+    }
+
+    /// The price a stop order is checked against: its market's last trade or
+    /// index price, depending on its trigger source
+    pub fn reference_price(&self, order: &Order) -> Option<u128> {
+        let prices = match order.trigger_source {
+            TriggerSource::LastTrade => &self.last_prices,
+            TriggerSource::Index => &self.index_prices,
+        };
+        prices.get(&order.market_id).copied()
+    }
+
+    /// Move a market's last trade price, to be checked by the next `take_triggered`
+    pub fn record_trade(&mut self, market_id: &str, price: u128) {
+        self.last_prices.insert(market_id.to_string(), price);
+        if self.orders.contains_key(market_id) {
+            self.moved.insert(market_id.to_string());
+        }
+    }
+
+    /// Move a market's index price, to be checked by the next `take_triggered`
+    /// if it changed; `None` forgets a price that went stale, so index stops
+    /// wait for the feed to recover
+    pub fn record_index_price(&mut self, market_id: &str, price: Option<u128>) {
+        let Some(price) = price else {
+            self.index_prices.remove(market_id);
+            return;
+        };
+        let previous = self.index_prices.insert(market_id.to_string(), price);
+        if previous != Some(price) && self.orders.contains_key(market_id) {
+            self.moved.insert(market_id.to_string());
+        }
+    }
+
+    /// Check every market's stop orders again with the next `take_triggered`,
+    /// such as ones loaded after a restart whose trigger was already reached
+    pub fn recheck_all(&mut self) {
+        self.moved.extend(self.orders.keys().cloned());
+    }
+
+    /// Markets with stop orders waiting on their index price
+    pub fn index_markets(&self) -> Vec<String> {
+        self.orders
+            .iter()
+            .filter(|(_, orders)| {
+                orders
+                    .iter()
+                    .any(|order| order.trigger_source == TriggerSource::Index)
+            })
+            .map(|(market_id, _)| market_id.clone())
+            .collect()
+    }
+
+    /// Remove and return the stop orders reached by the markets whose prices
+    /// moved since the last call, oldest first
+    pub fn take_triggered(&mut self) -> Vec<Order> {
+        let mut triggered = Vec::new();
+        for market_id in std::mem::take(&mut self.moved) {
+            let last_price = self.last_prices.get(&market_id).copied();
+            let index_price = self.index_prices.get(&market_id).copied();
+            let Some(orders) = self.orders.get_mut(&market_id) else {
+                continue;
+            };
+            let (reached, waiting) = std::mem::take(orders).into_iter().partition(|order| {
+                let price = match order.trigger_source {
+                    TriggerSource::LastTrade => last_price,
+                    TriggerSource::Index => index_price,
+                };
+                price.is_some_and(|price| is_triggered(order, price))
+            });
+            *orders = waiting;
+            triggered.extend(reached);
+            self.prune(&market_id);
+        }
+        triggered.sort_by_key(|order| order.created_at);
+        triggered
+    }
+
+    /// Remove a user's or its sub-accounts' stop order from any market
+    /// Another user's order is reported as not found
+    pub fn cancel_order(&mut self, order_id: Uuid, user_address: &str) -> Result<Order> {
+        let (market_id, index) = self
+            .orders
+            .iter()
+            .find_map(|(market_id, orders)| {
+                let index = orders.iter().position(|order| order.id == order_id)?;
+                Some((market_id.clone(), index))
+            })
+            .ok_or(ExchangeError::OrderNotFound)?;
+        let orders = self.orders.get_mut(&market_id).expect("market just found");
+        if !SubAccount::owns(user_address, &orders[index].user_address) {
+            return Err(ExchangeError::OrderNotFound);
+        }
+        let order = orders.remove(index);
+        self.prune(&market_id);
+        Ok(order)
+    }
+
+    /// Remove all of a user's stop orders, optionally only in one market
+    pub fn cancel_all_orders(&mut self, user_address: &str, market_id: Option<&str>) -> Vec<Order> {
+        self.remove_where(market_id, |order| order.user_address == user_address)
+    }
+
+    /// Remove every stop order, optionally only in one market
+    pub fn cancel_market_orders(&mut self, market_id: Option<&str>) -> Vec<Order> {
+        self.remove_where(market_id, |_| true)
+    }
+
+    /// A user's stop orders waiting in a market
+    pub fn open_orders<'a>(
+        &'a self,
+        market_id: &str,
+        user_address: &'a str,
+    ) -> impl Iterator<Item = &'a Order> {
+        self.orders
+            .get(market_id)
+            .into_iter()
+            .flatten()
+            .filter(move |order| order.user_address == user_address)
+    }
+
+    /// How many stop orders a user has waiting in a market
+    pub fn open_order_count(&self, market_id: &str, user_address: &str) -> usize {
+        self.open_orders(market_id, user_address).count()
+    }
+
+    /// Stop orders waiting across all markets
+    pub fn len(&self) -> usize {
+        self.orders.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    fn remove_where(
+        &mut self,
+        market_id: Option<&str>,
+        matches: impl Fn(&Order) -> bool,
+    ) -> Vec<Order> {
+        let markets: Vec<String> = match market_id {
+            Some(market_id) => vec![market_id.to_string()],
+            None => self.orders.keys().cloned().collect(),
+        };
+        let mut removed = Vec::new();
+        for market_id in markets {
+            let Some(orders) = self.orders.get_mut(&market_id) else {
+                continue;
+            };
+            let (matched, kept): (Vec<_>, Vec<_>) =
+                std::mem::take(orders).into_iter().partition(&matches);
+            *orders = kept;
+            removed.extend(matched);
+            self.prune(&market_id);
+        }
+        removed
+    }
+
+    /// Forget a market with no stop orders left
+    fn prune(&mut self, market_id: &str) {
+        if self.orders.get(market_id).is_some_and(Vec::is_empty) {
+            self.orders.remove(market_id);
+        }
+    }
+}
+
+impl MatchingEngine {
+    /// Execute the stop orders that the markets' fresh index prices have
+    /// reached; an index price that went stale triggers nothing
+    pub(super) async fn trigger_on_index_prices(&mut self, affected: &mut AffectedBalances) {
+        if self.refresh_index_prices() > 0 {
+            self.activate_stop_orders(affected).await;
+        }
+    }
+
+    /// Copy the fresh index price of each market with index stops into the
+    /// trigger book, returning how many markets have them
+    pub(super) fn refresh_index_prices(&mut self) -> usize {
+        let now = Utc::now();
+        let markets = self.triggers.index_markets();
+        for market_id in &markets {
+            let price = self.index_prices.fresh(market_id, now);
+            self.triggers.record_index_price(market_id, price);
+        }
+        markets.len()
+    }
+}
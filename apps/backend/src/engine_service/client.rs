@@ -0,0 +1,198 @@
+// gateway side: stands in for the engine, forwarding to one in another process
+
+use super::wire::{split_request, EngineFrame, GatewayFrame, Responder, WIRE_VERSION};
+use super::{read_frame, write_frame};
+use crate::engine::markets::MarketRegistry;
+use crate::errors::ExchangeError;
+use crate::models::domain::{EngineEvent, EngineRequest};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc};
+
+/// First wait before reconnecting to the engine; doubles up to the max
+const RECONNECT_DELAY: Duration = Duration::from_millis(250);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest wait for the engine to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Frames read ahead from the engine before the reader waits
+const FRAME_BUFFER: usize = 1024;
+
+/// Drains a gateway's engine queue into a matching engine in another process
+///
+/// Handlers keep sending [`EngineRequest`]s on the same channel as with an
+/// in-process engine; this forwards them over TCP and hands each reply back,
+/// and feeds the engine's events into `event_tx` for the WebSocket router.
+/// Events raised by the gateway's own handlers are read from `outbox` and
+/// published through the engine, so every gateway and webhook sees them.
+///
+/// While the engine is unreachable requests fail right away with
+/// `EngineSendFailed` and the connection is retried. Requests in flight when
+/// the connection drops fail with `EngineReceiveFailed`; the engine may or
+/// may not have processed them.
+pub struct RemoteEngine {
+    addr: String,
+    token: Option<String>,
+    engine_rx: mpsc::Receiver<EngineRequest>,
+    outbox: broadcast::Receiver<EngineEvent>,
+    event_tx: broadcast::Sender<EngineEvent>,
+    markets: MarketRegistry,
+}
+
+impl RemoteEngine {
+    /// Forward to the engine server listening on `addr` (`host:port`)
+    pub fn new(
+        addr: impl Into<String>,
+        engine_rx: mpsc::Receiver<EngineRequest>,
+        outbox: broadcast::Receiver<EngineEvent>,
+        event_tx: broadcast::Sender<EngineEvent>,
+    ) -> Self {
+        Self {
+            addr: addr.into(),
+            token: None,
+            engine_rx,
+            outbox,
+            event_tx,
+            markets: MarketRegistry::new(),
+        }
+    }
+
+    /// Present `token` to the engine server
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Forward requests until every sender of the engine queue is dropped
+    /// and the engine has answered what was sent
+    pub async fn run(mut self) {
+        let mut delay = RECONNECT_DELAY;
+
+        loop {
+            match self.connect().await {
+                Ok(stream) => {
+                    log::info!("Connected to engine at {}", self.addr);
+                    delay = RECONNECT_DELAY;
+                    match self.session(stream).await {
+                        Ok(()) => {
+                            log::info!("Engine queue closed, disconnected from {}", self.addr);
+                            return;
+                        }
+                        Err(e) => log::error!("Lost engine at {}: {:#}", self.addr, e),
+                    }
+                }
+                Err(e) => log::warn!("Failed to connect to engine at {}: {:#}", self.addr, e),
+            }
+
+            // Refuse requests instead of holding them until the engine is back
+            let retry = tokio::time::sleep(delay);
+            tokio::pin!(retry);
+            loop {
+                tokio::select! {
+                    _ = &mut retry => break,
+                    request = self.engine_rx.recv() => match request {
+                        Some(request) => split_request(request).1.fail(ExchangeError::EngineSendFailed),
+                        None => return,
+                    },
+                }
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn connect(&self) -> anyhow::Result<TcpStream> {
+        let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&self.addr))
+            .await
+            .map_err(|_| anyhow::anyhow!("Timed out after {:?}", CONNECT_TIMEOUT))??;
+        stream.set_nodelay(true)?;
+
+        let hello = GatewayFrame::Hello {
+            version: WIRE_VERSION,
+            token: self.token.clone(),
+        };
+        write_frame(&mut stream, &hello).await?;
+        Ok(stream)
+    }
+
+    /// Forward over one connection; `Ok` once the queue has closed and drained
+    async fn session(&mut self, stream: TcpStream) -> anyhow::Result<()> {
+        let (mut reader, mut writer) = stream.into_split();
+
+        let (frame_tx, mut frames) = mpsc::channel(FRAME_BUFFER);
+        let reader_task = tokio::spawn(async move {
+            loop {
+                match read_frame::<_, EngineFrame>(&mut reader).await {
+                    Ok(Some(frame)) => {
+                        if frame_tx.send(frame).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        log::warn!("Unreadable frame from engine: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        let mut pending: HashMap<u64, Responder> = HashMap::new();
+        let mut next_id = 0u64;
+        let mut closing = false;
+        let mut outbox_open = true;
+
+        let result = loop {
+            if closing && pending.is_empty() {
+                break Ok(());
+            }
+
+            tokio::select! {
+                request = self.engine_rx.recv(), if !closing => match request {
+                    Some(request) => {
+                        let (request, responder) = split_request(request);
+                        next_id += 1;
+                        pending.insert(next_id, responder);
+                        let frame = GatewayFrame::Request { id: next_id, request };
+                        if let Err(e) = write_frame(&mut writer, &frame).await {
+                            break Err(e.into());
+                        }
+                    }
+                    None => closing = true,
+                },
+
+                event = self.outbox.recv(), if outbox_open => match event {
+                    Ok(event) => {
+                        let frame = GatewayFrame::Publish { event: event.into() };
+                        if let Err(e) = write_frame(&mut writer, &frame).await {
+                            break Err(e.into());
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Gateway outbox lagged, dropped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => outbox_open = false,
+                },
+
+                frame = frames.recv() => match frame {
+                    Some(EngineFrame::Response { id, result }) => {
+                        if let Some(responder) = pending.remove(&id) {
+                            responder.respond(result);
+                        }
+                    }
+                    Some(EngineFrame::Event { event }) => {
+                        let _ = self.event_tx.send(event.into_engine_event(&self.markets));
+                    }
+                    None => break Err(anyhow::anyhow!("Engine closed the connection")),
+                },
+            }
+        };
+
+        reader_task.abort();
+        for (_, responder) in pending.drain() {
+            responder.fail(ExchangeError::EngineReceiveFailed);
+        }
+        result
+    }
+}
@@ -0,0 +1,59 @@
+// the matching engine as a network service, for API gateways in other processes
+
+pub mod client;
+pub mod server;
+pub mod wire;
+
+pub use client::RemoteEngine;
+pub use server::EngineServer;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest frame either side accepts
+pub const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+/// Write one frame: a big-endian `u32` length, then that many bytes of JSON
+pub async fn write_frame<W, T>(writer: &mut W, frame: &T) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let payload = serde_json::to_vec(frame).map_err(io::Error::other)?;
+    if payload.len() > MAX_FRAME_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Frame of {} bytes is too large", payload.len()),
+        ));
+    }
+    writer.write_u32(payload.len() as u32).await?;
+    writer.write_all(&payload).await?;
+    writer.flush().await
+}
+
+/// Read one frame, or `None` if the peer closed the connection between frames
+///
+/// Not cancel safe: give each connection a task that only reads.
+pub async fn read_frame<R, T>(reader: &mut R) -> io::Result<Option<T>>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let len = match reader.read_u32().await {
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if len > MAX_FRAME_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes is too large", len),
+        ));
+    }
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload).await?;
+    let frame = serde_json::from_slice(&payload).map_err(io::Error::other)?;
+    Ok(Some(frame))
+}
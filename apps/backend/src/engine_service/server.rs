@@ -0,0 +1,198 @@
+// engine side: serves the in-process engine to gateways over TCP
+
+use super::wire::{EngineFrame, GatewayFrame, RemoteError, WIRE_VERSION};
+use super::{read_frame, write_frame};
+use crate::engine::markets::MarketRegistry;
+use crate::errors::ExchangeError;
+use crate::models::domain::{EngineEvent, EngineRequest};
+use crate::shutdown::Shutdown;
+use anyhow::Context;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinSet;
+
+/// Frames read ahead from one gateway before its reader waits
+const FRAME_BUFFER: usize = 256;
+
+/// Serves the matching engine to API gateways in other processes
+///
+/// Each gateway's requests go into the engine queue in the order it sent
+/// them, and every engine event is streamed to every gateway so their
+/// WebSocket subscribers see the same feed. Events a gateway publishes, such
+/// as withdrawal requests, are broadcast as if the engine had raised them.
+pub struct EngineServer {
+    engine_tx: mpsc::Sender<EngineRequest>,
+    event_tx: broadcast::Sender<EngineEvent>,
+    markets: MarketRegistry,
+    token: Option<String>,
+}
+
+impl EngineServer {
+    pub fn new(
+        engine_tx: mpsc::Sender<EngineRequest>,
+        event_tx: broadcast::Sender<EngineEvent>,
+        markets: MarketRegistry,
+    ) -> Self {
+        Self {
+            engine_tx,
+            event_tx,
+            markets,
+            token: None,
+        }
+    }
+
+    /// Only serve gateways that present `token`
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Serve gateways connecting to `listener` until shutdown
+    ///
+    /// On shutdown it stops taking requests, waits for the engine to answer
+    /// the ones already queued, then closes every connection - after which
+    /// it holds no handle on the engine queue.
+    pub async fn serve(self, listener: TcpListener, shutdown: Shutdown) {
+        let server = Arc::new(self);
+        let mut connections = JoinSet::new();
+        let stopped = shutdown.triggered();
+        tokio::pin!(stopped);
+
+        loop {
+            tokio::select! {
+                _ = &mut stopped => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, peer)) => {
+                        log::info!("Gateway connected from {}", peer);
+                        let server = Arc::clone(&server);
+                        let shutdown = shutdown.clone();
+                        connections.spawn(async move {
+                            match server.connection(stream, shutdown).await {
+                                Ok(()) => log::info!("Gateway {} disconnected", peer),
+                                Err(e) => log::warn!("Gateway {} dropped: {:#}", peer, e),
+                            }
+                        });
+                    }
+                    Err(e) => log::error!("Failed to accept gateway connection: {}", e),
+                },
+                // Reap finished connections as we go
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+
+        drop(listener);
+        while connections.join_next().await.is_some() {}
+    }
+
+    async fn connection(&self, stream: TcpStream, shutdown: Shutdown) -> anyhow::Result<()> {
+        stream.set_nodelay(true)?;
+        let (mut reader, mut writer) = stream.into_split();
+
+        match read_frame(&mut reader).await? {
+            Some(GatewayFrame::Hello { version, token }) => {
+                anyhow::ensure!(
+                    version == WIRE_VERSION,
+                    "Gateway speaks version {}, engine speaks {}",
+                    version,
+                    WIRE_VERSION
+                );
+                if self.token.is_some() && token != self.token {
+                    anyhow::bail!("Gateway presented a wrong token");
+                }
+            }
+            _ => anyhow::bail!("Gateway didn't say hello"),
+        }
+
+        // Subscribe before taking requests so no event of theirs is missed
+        let mut events = self.event_tx.subscribe();
+
+        let (frame_tx, mut frames) = mpsc::channel(FRAME_BUFFER);
+        let reader_task = tokio::spawn(async move {
+            loop {
+                match read_frame::<_, GatewayFrame>(&mut reader).await {
+                    Ok(Some(frame)) => {
+                        if frame_tx.send(frame).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        log::warn!("Unreadable frame from gateway: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        let (reply_tx, mut replies) = mpsc::unbounded_channel();
+        let mut in_flight = 0usize;
+        let mut open = true;
+        let stopped = shutdown.triggered();
+        tokio::pin!(stopped);
+
+        let result: anyhow::Result<()> = loop {
+            if !open && in_flight == 0 {
+                break Ok(());
+            }
+
+            tokio::select! {
+                _ = &mut stopped, if open => {
+                    // Finish what's queued, take nothing new
+                    open = false;
+                    reader_task.abort();
+                }
+
+                frame = frames.recv(), if open => match frame {
+                    Some(GatewayFrame::Request { id, request }) => {
+                        let (request, reply) = request.into_engine_request();
+                        if self.engine_tx.send(request).await.is_err() {
+                            let result = Err(RemoteError::from(ExchangeError::EngineSendFailed));
+                            if let Err(e) = write_frame(&mut writer, &EngineFrame::Response { id, result }).await {
+                                break Err(e.into());
+                            }
+                            continue;
+                        }
+                        in_flight += 1;
+                        let reply_tx = reply_tx.clone();
+                        tokio::spawn(async move {
+                            let result = reply.await.map_err(RemoteError::from);
+                            let _ = reply_tx.send(EngineFrame::Response { id, result });
+                        });
+                    }
+                    Some(GatewayFrame::Publish { event }) => {
+                        let _ = self.event_tx.send(event.into_engine_event(&self.markets));
+                    }
+                    Some(GatewayFrame::Hello { .. }) => {
+                        log::warn!("Gateway said hello twice, ignoring");
+                    }
+                    // Replies to a gateway that's gone have nowhere to go
+                    None => break Ok(()),
+                },
+
+                Some(reply) = replies.recv() => {
+                    in_flight -= 1;
+                    if let Err(e) = write_frame(&mut writer, &reply).await {
+                        break Err(e.into());
+                    }
+                }
+
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        let frame = EngineFrame::Event { event: event.into() };
+                        if let Err(e) = write_frame(&mut writer, &frame).await {
+                            break Err(e.into());
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Gateway lagged, skipped {} engine events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break Ok(()),
+                },
+            }
+        };
+
+        reader_task.abort();
+        result.context("Gateway connection failed")
+    }
+}
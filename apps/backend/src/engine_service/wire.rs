@@ -0,0 +1,967 @@
+// what gateways and the engine say to each other
+
+use crate::engine::ladder::LadderLayout;
+use crate::engine::markets::MarketRegistry;
+use crate::errors::{ErrorCode, ExchangeError};
+use crate::models::api::{OcoPlaced, OrderCancelled, OrderPlaced, OrdersCancelled};
+use crate::models::domain::{
+    Balance, CancelReason, EngineEvent, EngineRequest, FeeOverride, FeeRoute, KillSwitch,
+    Liquidation, MarginMode, MarketStatus, Order, OrderbookSnapshot, QueuePosition, Referral,
+    RejectReason, RevenueSource, Trade, UserLimits, UserStatus, Withdrawal,
+};
+use crate::perps::FundingSettlement;
+use crate::rfq::RfqExecution;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+/// Bumped whenever a frame changes shape; both sides must agree
+pub const WIRE_VERSION: u32 = 1;
+
+/// Frames a gateway sends
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GatewayFrame {
+    /// First frame of every connection
+    Hello {
+        version: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    /// An engine request; answered by a `Response` with the same id
+    Request { id: u64, request: WireRequest },
+    /// An event raised by the gateway itself, such as a withdrawal request,
+    /// for the engine to pass on to every subscriber
+    Publish { event: WireEvent },
+}
+
+/// Frames the engine sends
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EngineFrame {
+    Response {
+        id: u64,
+        result: Result<EngineReply, RemoteError>,
+    },
+    Event {
+        event: WireEvent,
+    },
+}
+
+/// An [`EngineRequest`] without its reply channel
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WireRequest {
+    PlaceOrder {
+        order: Order,
+    },
+    PlaceOco {
+        first: Order,
+        second: Order,
+    },
+    CancelOrder {
+        order_id: Uuid,
+        user_address: String,
+    },
+    CancelAllOrders {
+        user_address: String,
+        market_id: Option<String>,
+    },
+    SetUserLimits {
+        user_address: String,
+        market_id: String,
+        max_position: Option<u128>,
+        max_open_notional: Option<u128>,
+    },
+    EngageKillSwitch {
+        market_id: Option<String>,
+        reason: Option<String>,
+        cancel_orders: bool,
+    },
+    ReleaseKillSwitch {
+        market_id: Option<String>,
+    },
+    SetMarketStatus {
+        market_id: String,
+        status: MarketStatus,
+    },
+    SetReferral {
+        user_address: String,
+        referrer_address: String,
+        share_bps: u32,
+    },
+    SetPriceCollar {
+        market_id: String,
+        collar_bps: Option<u32>,
+    },
+    OpenMarket {
+        market_id: String,
+        layout: LadderLayout,
+        collar_bps: Option<u32>,
+    },
+    SetFeeRoute {
+        source: RevenueSource,
+        insurance_bps: u32,
+    },
+    SetFeeOverride {
+        user_address: String,
+        market_id: String,
+        maker_fee_bps: Option<i32>,
+        taker_fee_bps: Option<i32>,
+    },
+    SetUserStatus {
+        user_address: String,
+        status: UserStatus,
+        cancel_orders: bool,
+    },
+    SettleFunding {
+        market_id: String,
+        mark_price: u128,
+        index_price: u128,
+        funding_rate_ppm: i64,
+    },
+    UpdateMarkPrice {
+        market_id: String,
+        mark_price: u128,
+        index_price: u128,
+    },
+    SetMarginMode {
+        user_address: String,
+        mode: MarginMode,
+    },
+    ExecuteRfq {
+        request_id: Uuid,
+        quote_id: Uuid,
+        user_address: String,
+    },
+    QueuePosition {
+        order_id: Uuid,
+        user_address: String,
+    },
+    RestingOrders {
+        market_id: String,
+    },
+}
+
+/// What the engine answered a request with
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum EngineReply {
+    OrderPlaced(OrderPlaced),
+    OcoPlaced(OcoPlaced),
+    OrderCancelled(OrderCancelled),
+    OrdersCancelled(OrdersCancelled),
+    UserLimits(UserLimits),
+    KillSwitch(KillSwitch, OrdersCancelled),
+    Referral(Referral),
+    FeeRoute(FeeRoute),
+    FeeOverride(Option<FeeOverride>),
+    FundingSettled(FundingSettlement),
+    Liquidations(Vec<Liquidation>),
+    RfqExecuted(RfqExecution),
+    QueuePosition(QueuePosition),
+    RestingOrders(Vec<Order>),
+    Done,
+}
+
+/// An [`ExchangeError`] as it crosses the wire
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteError {
+    pub code: ErrorCode,
+    pub status: u16,
+    pub message: String,
+}
+
+impl From<ExchangeError> for RemoteError {
+    fn from(error: ExchangeError) -> Self {
+        Self {
+            code: error.error_code(),
+            status: error.status_code().as_u16(),
+            message: error.public_message(),
+        }
+    }
+}
+
+impl From<RemoteError> for ExchangeError {
+    fn from(error: RemoteError) -> Self {
+        ExchangeError::Remote {
+            code: error.code,
+            status: error.status,
+            message: error.message,
+        }
+    }
+}
+
+/// An [`EngineEvent`] with its market named rather than interned
+///
+/// Interned market ids only mean something inside one process.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WireEvent {
+    TradeExecuted {
+        trade: Trade,
+    },
+    OrderPlaced {
+        order: Order,
+    },
+    OrderCancelled {
+        order_id: Uuid,
+        user_address: String,
+        reason: Option<CancelReason>,
+    },
+    OrderRejected {
+        order_id: Uuid,
+        user_address: String,
+        market_id: String,
+        code: ErrorCode,
+        reason: Option<RejectReason>,
+        message: String,
+    },
+    MarketStatusChanged {
+        market_id: String,
+        status: MarketStatus,
+    },
+    BalanceUpdated {
+        balance: Balance,
+    },
+    WithdrawalUpdated {
+        withdrawal: Withdrawal,
+    },
+    PositionLiquidated {
+        liquidation: Liquidation,
+    },
+    OrderbookSnapshot {
+        orderbook: OrderbookSnapshot,
+    },
+}
+
+impl From<EngineEvent> for WireEvent {
+    fn from(event: EngineEvent) -> Self {
+        match event {
+            EngineEvent::TradeExecuted { trade, .. } => WireEvent::TradeExecuted { trade },
+            EngineEvent::OrderPlaced { order } => WireEvent::OrderPlaced { order },
+            EngineEvent::OrderCancelled {
+                order_id,
+                user_address,
+                reason,
+            } => WireEvent::OrderCancelled {
+                order_id,
+                user_address,
+                reason,
+            },
+            EngineEvent::OrderRejected {
+                order_id,
+                user_address,
+                market_id,
+                code,
+                reason,
+                message,
+            } => WireEvent::OrderRejected {
+                order_id,
+                user_address,
+                market_id,
+                code,
+                reason,
+                message,
+            },
+            EngineEvent::MarketStatusChanged { market_id, status } => {
+                WireEvent::MarketStatusChanged { market_id, status }
+            }
+            EngineEvent::BalanceUpdated { balance } => WireEvent::BalanceUpdated { balance },
+            EngineEvent::WithdrawalUpdated { withdrawal } => {
+                WireEvent::WithdrawalUpdated { withdrawal }
+            }
+            EngineEvent::PositionLiquidated { liquidation } => {
+                WireEvent::PositionLiquidated { liquidation }
+            }
+            EngineEvent::OrderbookSnapshot { orderbook, .. } => {
+                WireEvent::OrderbookSnapshot { orderbook }
+            }
+        }
+    }
+}
+
+impl WireEvent {
+    /// Back into an engine event, interning its market in this process's registry
+    pub fn into_engine_event(self, markets: &MarketRegistry) -> EngineEvent {
+        match self {
+            WireEvent::TradeExecuted { trade } => EngineEvent::TradeExecuted {
+                market: markets.intern(&trade.market_id),
+                trade,
+            },
+            WireEvent::OrderPlaced { order } => EngineEvent::OrderPlaced { order },
+            WireEvent::OrderCancelled {
+                order_id,
+                user_address,
+                reason,
+            } => EngineEvent::OrderCancelled {
+                order_id,
+                user_address,
+                reason,
+            },
+            WireEvent::OrderRejected {
+                order_id,
+                user_address,
+                market_id,
+                code,
+                reason,
+                message,
+            } => EngineEvent::OrderRejected {
+                order_id,
+                user_address,
+                market_id,
+                code,
+                reason,
+                message,
+            },
+            WireEvent::MarketStatusChanged { market_id, status } => {
+                EngineEvent::MarketStatusChanged { market_id, status }
+            }
+            WireEvent::BalanceUpdated { balance } => EngineEvent::BalanceUpdated { balance },
+            WireEvent::WithdrawalUpdated { withdrawal } => {
+                EngineEvent::WithdrawalUpdated { withdrawal }
+            }
+            WireEvent::PositionLiquidated { liquidation } => {
+                EngineEvent::PositionLiquidated { liquidation }
+            }
+            WireEvent::OrderbookSnapshot { orderbook } => EngineEvent::OrderbookSnapshot {
+                market: markets.intern(&orderbook.market_id),
+                orderbook,
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Gateway side: requests out, replies back to the waiting handler
+// ============================================================================
+
+/// The reply channel of an [`EngineRequest`] sent to a remote engine
+pub enum Responder {
+    OrderPlaced(oneshot::Sender<Result<OrderPlaced, ExchangeError>>),
+    OcoPlaced(oneshot::Sender<Result<OcoPlaced, ExchangeError>>),
+    OrderCancelled(oneshot::Sender<Result<OrderCancelled, ExchangeError>>),
+    OrdersCancelled(oneshot::Sender<Result<OrdersCancelled, ExchangeError>>),
+    UserLimits(oneshot::Sender<Result<UserLimits, ExchangeError>>),
+    KillSwitch(oneshot::Sender<Result<(KillSwitch, OrdersCancelled), ExchangeError>>),
+    Referral(oneshot::Sender<Result<Referral, ExchangeError>>),
+    FeeRoute(oneshot::Sender<Result<FeeRoute, ExchangeError>>),
+    FeeOverride(oneshot::Sender<Result<Option<FeeOverride>, ExchangeError>>),
+    FundingSettled(oneshot::Sender<Result<FundingSettlement, ExchangeError>>),
+    Liquidations(oneshot::Sender<Result<Vec<Liquidation>, ExchangeError>>),
+    RfqExecuted(oneshot::Sender<Result<RfqExecution, ExchangeError>>),
+    QueuePosition(oneshot::Sender<Result<QueuePosition, ExchangeError>>),
+    RestingOrders(oneshot::Sender<Result<Vec<Order>, ExchangeError>>),
+    Done(oneshot::Sender<Result<(), ExchangeError>>),
+}
+
+/// Split a request into what goes on the wire and where its reply goes
+pub fn split_request(request: EngineRequest) -> (WireRequest, Responder) {
+    match request {
+        EngineRequest::PlaceOrder {
+            order, response_tx, ..
+        } => (
+            WireRequest::PlaceOrder { order },
+            Responder::OrderPlaced(response_tx),
+        ),
+        EngineRequest::PlaceOco {
+            first,
+            second,
+            response_tx,
+            ..
+        } => (
+            WireRequest::PlaceOco { first, second },
+            Responder::OcoPlaced(response_tx),
+        ),
+        EngineRequest::CancelOrder {
+            order_id,
+            user_address,
+            response_tx,
+            ..
+        } => (
+            WireRequest::CancelOrder {
+                order_id,
+                user_address,
+            },
+            Responder::OrderCancelled(response_tx),
+        ),
+        EngineRequest::CancelAllOrders {
+            user_address,
+            market_id,
+            response_tx,
+            ..
+        } => (
+            WireRequest::CancelAllOrders {
+                user_address,
+                market_id,
+            },
+            Responder::OrdersCancelled(response_tx),
+        ),
+        EngineRequest::SetUserLimits {
+            user_address,
+            market_id,
+            max_position,
+            max_open_notional,
+            response_tx,
+        } => (
+            WireRequest::SetUserLimits {
+                user_address,
+                market_id,
+                max_position,
+                max_open_notional,
+            },
+            Responder::UserLimits(response_tx),
+        ),
+        EngineRequest::EngageKillSwitch {
+            market_id,
+            reason,
+            cancel_orders,
+            response_tx,
+        } => (
+            WireRequest::EngageKillSwitch {
+                market_id,
+                reason,
+                cancel_orders,
+            },
+            Responder::KillSwitch(response_tx),
+        ),
+        EngineRequest::ReleaseKillSwitch {
+            market_id,
+            response_tx,
+        } => (
+            WireRequest::ReleaseKillSwitch { market_id },
+            Responder::Done(response_tx),
+        ),
+        EngineRequest::SetMarketStatus {
+            market_id,
+            status,
+            response_tx,
+        } => (
+            WireRequest::SetMarketStatus { market_id, status },
+            Responder::OrdersCancelled(response_tx),
+        ),
+        EngineRequest::SetReferral {
+            user_address,
+            referrer_address,
+            share_bps,
+            response_tx,
+        } => (
+            WireRequest::SetReferral {
+                user_address,
+                referrer_address,
+                share_bps,
+            },
+            Responder::Referral(response_tx),
+        ),
+        EngineRequest::SetPriceCollar {
+            market_id,
+            collar_bps,
+            response_tx,
+        } => (
+            WireRequest::SetPriceCollar {
+                market_id,
+                collar_bps,
+            },
+            Responder::Done(response_tx),
+        ),
+        EngineRequest::OpenMarket {
+            market_id,
+            layout,
+            collar_bps,
+            response_tx,
+        } => (
+            WireRequest::OpenMarket {
+                market_id,
+                layout,
+                collar_bps,
+            },
+            Responder::Done(response_tx),
+        ),
+        EngineRequest::SetFeeRoute {
+            source,
+            insurance_bps,
+            response_tx,
+        } => (
+            WireRequest::SetFeeRoute {
+                source,
+                insurance_bps,
+            },
+            Responder::FeeRoute(response_tx),
+        ),
+        EngineRequest::SetFeeOverride {
+            user_address,
+            market_id,
+            maker_fee_bps,
+            taker_fee_bps,
+            response_tx,
+        } => (
+            WireRequest::SetFeeOverride {
+                user_address,
+                market_id,
+                maker_fee_bps,
+                taker_fee_bps,
+            },
+            Responder::FeeOverride(response_tx),
+        ),
+        EngineRequest::SetUserStatus {
+            user_address,
+            status,
+            cancel_orders,
+            response_tx,
+        } => (
+            WireRequest::SetUserStatus {
+                user_address,
+                status,
+                cancel_orders,
+            },
+            Responder::OrdersCancelled(response_tx),
+        ),
+        EngineRequest::SettleFunding {
+            market_id,
+            mark_price,
+            index_price,
+            funding_rate_ppm,
+            response_tx,
+        } => (
+            WireRequest::SettleFunding {
+                market_id,
+                mark_price,
+                index_price,
+                funding_rate_ppm,
+            },
+            Responder::FundingSettled(response_tx),
+        ),
+        EngineRequest::UpdateMarkPrice {
+            market_id,
+            mark_price,
+            index_price,
+            response_tx,
+        } => (
+            WireRequest::UpdateMarkPrice {
+                market_id,
+                mark_price,
+                index_price,
+            },
+            Responder::Liquidations(response_tx),
+        ),
+        EngineRequest::SetMarginMode {
+            user_address,
+            mode,
+            response_tx,
+        } => (
+            WireRequest::SetMarginMode { user_address, mode },
+            Responder::Done(response_tx),
+        ),
+        EngineRequest::ExecuteRfq {
+            request_id,
+            quote_id,
+            user_address,
+            response_tx,
+        } => (
+            WireRequest::ExecuteRfq {
+                request_id,
+                quote_id,
+                user_address,
+            },
+            Responder::RfqExecuted(response_tx),
+        ),
+        EngineRequest::QueuePosition {
+            order_id,
+            user_address,
+            response_tx,
+        } => (
+            WireRequest::QueuePosition {
+                order_id,
+                user_address,
+            },
+            Responder::QueuePosition(response_tx),
+        ),
+        EngineRequest::RestingOrders {
+            market_id,
+            response_tx,
+        } => (
+            WireRequest::RestingOrders { market_id },
+            Responder::RestingOrders(response_tx),
+        ),
+    }
+}
+
+/// Hand a reply to its waiting handler, if its type matches the request
+fn deliver<T>(
+    tx: oneshot::Sender<Result<T, ExchangeError>>,
+    result: Result<EngineReply, RemoteError>,
+    unwrap: impl FnOnce(EngineReply) -> Option<T>,
+) {
+    let result = result
+        .map_err(ExchangeError::from)
+        .and_then(|reply| unwrap(reply).ok_or(ExchangeError::EngineReceiveFailed));
+    let _ = tx.send(result);
+}
+
+impl Responder {
+    /// Answer the waiting handler with the engine's reply
+    pub fn respond(self, result: Result<EngineReply, RemoteError>) {
+        match self {
+            Responder::OrderPlaced(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::OrderPlaced(placed) => Some(placed),
+                _ => None,
+            }),
+            Responder::OcoPlaced(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::OcoPlaced(placed) => Some(placed),
+                _ => None,
+            }),
+            Responder::OrderCancelled(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::OrderCancelled(cancelled) => Some(cancelled),
+                _ => None,
+            }),
+            Responder::OrdersCancelled(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::OrdersCancelled(cancelled) => Some(cancelled),
+                _ => None,
+            }),
+            Responder::UserLimits(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::UserLimits(limits) => Some(limits),
+                _ => None,
+            }),
+            Responder::KillSwitch(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::KillSwitch(switch, cancelled) => Some((switch, cancelled)),
+                _ => None,
+            }),
+            Responder::Referral(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::Referral(referral) => Some(referral),
+                _ => None,
+            }),
+            Responder::FeeRoute(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::FeeRoute(route) => Some(route),
+                _ => None,
+            }),
+            Responder::FeeOverride(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::FeeOverride(fee_override) => Some(fee_override),
+                _ => None,
+            }),
+            Responder::FundingSettled(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::FundingSettled(settlement) => Some(settlement),
+                _ => None,
+            }),
+            Responder::Liquidations(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::Liquidations(liquidations) => Some(liquidations),
+                _ => None,
+            }),
+            Responder::RfqExecuted(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::RfqExecuted(execution) => Some(execution),
+                _ => None,
+            }),
+            Responder::QueuePosition(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::QueuePosition(position) => Some(position),
+                _ => None,
+            }),
+            Responder::RestingOrders(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::RestingOrders(orders) => Some(orders),
+                _ => None,
+            }),
+            Responder::Done(tx) => deliver(tx, result, |reply| match reply {
+                EngineReply::Done => Some(()),
+                _ => None,
+            }),
+        }
+    }
+
+    /// Answer the waiting handler with an error
+    pub fn fail(self, error: ExchangeError) {
+        match self {
+            Responder::OrderPlaced(tx) => drop(tx.send(Err(error))),
+            Responder::OcoPlaced(tx) => drop(tx.send(Err(error))),
+            Responder::OrderCancelled(tx) => drop(tx.send(Err(error))),
+            Responder::OrdersCancelled(tx) => drop(tx.send(Err(error))),
+            Responder::UserLimits(tx) => drop(tx.send(Err(error))),
+            Responder::KillSwitch(tx) => drop(tx.send(Err(error))),
+            Responder::Referral(tx) => drop(tx.send(Err(error))),
+            Responder::FeeRoute(tx) => drop(tx.send(Err(error))),
+            Responder::FeeOverride(tx) => drop(tx.send(Err(error))),
+            Responder::FundingSettled(tx) => drop(tx.send(Err(error))),
+            Responder::Liquidations(tx) => drop(tx.send(Err(error))),
+            Responder::RfqExecuted(tx) => drop(tx.send(Err(error))),
+            Responder::QueuePosition(tx) => drop(tx.send(Err(error))),
+            Responder::RestingOrders(tx) => drop(tx.send(Err(error))),
+            Responder::Done(tx) => drop(tx.send(Err(error))),
+        }
+    }
+}
+
+// ============================================================================
+// Engine side: requests in, replies out
+// ============================================================================
+
+/// The engine's reply to a forwarded request, once it has one
+pub type PendingReply = Pin<Box<dyn Future<Output = Result<EngineReply, ExchangeError>> + Send>>;
+
+fn pending<T: Send + 'static>(
+    rx: oneshot::Receiver<Result<T, ExchangeError>>,
+    wrap: fn(T) -> EngineReply,
+) -> PendingReply {
+    Box::pin(async move {
+        rx.await
+            .map_err(|_| ExchangeError::EngineReceiveFailed)?
+            .map(wrap)
+    })
+}
+
+impl WireRequest {
+    /// Rebuild the engine request, with a reply channel that resolves `PendingReply`
+    pub fn into_engine_request(self) -> (EngineRequest, PendingReply) {
+        match self {
+            WireRequest::PlaceOrder { order } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::PlaceOrder {
+                    order,
+                    span: tracing::Span::current(),
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::OrderPlaced))
+            }
+            WireRequest::PlaceOco { first, second } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::PlaceOco {
+                    first,
+                    second,
+                    span: tracing::Span::current(),
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::OcoPlaced))
+            }
+            WireRequest::CancelOrder {
+                order_id,
+                user_address,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::CancelOrder {
+                    order_id,
+                    user_address,
+                    span: tracing::Span::current(),
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::OrderCancelled))
+            }
+            WireRequest::CancelAllOrders {
+                user_address,
+                market_id,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::CancelAllOrders {
+                    user_address,
+                    market_id,
+                    span: tracing::Span::current(),
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::OrdersCancelled))
+            }
+            WireRequest::SetUserLimits {
+                user_address,
+                market_id,
+                max_position,
+                max_open_notional,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetUserLimits {
+                    user_address,
+                    market_id,
+                    max_position,
+                    max_open_notional,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::UserLimits))
+            }
+            WireRequest::EngageKillSwitch {
+                market_id,
+                reason,
+                cancel_orders,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::EngageKillSwitch {
+                    market_id,
+                    reason,
+                    cancel_orders,
+                    response_tx,
+                };
+                let reply = pending(rx, |(switch, cancelled)| {
+                    EngineReply::KillSwitch(switch, cancelled)
+                });
+                (request, reply)
+            }
+            WireRequest::ReleaseKillSwitch { market_id } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::ReleaseKillSwitch {
+                    market_id,
+                    response_tx,
+                };
+                (request, pending(rx, |()| EngineReply::Done))
+            }
+            WireRequest::SetMarketStatus { market_id, status } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetMarketStatus {
+                    market_id,
+                    status,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::OrdersCancelled))
+            }
+            WireRequest::SetReferral {
+                user_address,
+                referrer_address,
+                share_bps,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetReferral {
+                    user_address,
+                    referrer_address,
+                    share_bps,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::Referral))
+            }
+            WireRequest::SetPriceCollar {
+                market_id,
+                collar_bps,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetPriceCollar {
+                    market_id,
+                    collar_bps,
+                    response_tx,
+                };
+                (request, pending(rx, |()| EngineReply::Done))
+            }
+            WireRequest::OpenMarket {
+                market_id,
+                layout,
+                collar_bps,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::OpenMarket {
+                    market_id,
+                    layout,
+                    collar_bps,
+                    response_tx,
+                };
+                (request, pending(rx, |()| EngineReply::Done))
+            }
+            WireRequest::SetFeeRoute {
+                source,
+                insurance_bps,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetFeeRoute {
+                    source,
+                    insurance_bps,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::FeeRoute))
+            }
+            WireRequest::SetFeeOverride {
+                user_address,
+                market_id,
+                maker_fee_bps,
+                taker_fee_bps,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetFeeOverride {
+                    user_address,
+                    market_id,
+                    maker_fee_bps,
+                    taker_fee_bps,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::FeeOverride))
+            }
+            WireRequest::SetUserStatus {
+                user_address,
+                status,
+                cancel_orders,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetUserStatus {
+                    user_address,
+                    status,
+                    cancel_orders,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::OrdersCancelled))
+            }
+            WireRequest::SettleFunding {
+                market_id,
+                mark_price,
+                index_price,
+                funding_rate_ppm,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SettleFunding {
+                    market_id,
+                    mark_price,
+                    index_price,
+                    funding_rate_ppm,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::FundingSettled))
+            }
+            WireRequest::UpdateMarkPrice {
+                market_id,
+                mark_price,
+                index_price,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::UpdateMarkPrice {
+                    market_id,
+                    mark_price,
+                    index_price,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::Liquidations))
+            }
+            WireRequest::SetMarginMode { user_address, mode } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::SetMarginMode {
+                    user_address,
+                    mode,
+                    response_tx,
+                };
+                (request, pending(rx, |()| EngineReply::Done))
+            }
+            WireRequest::ExecuteRfq {
+                request_id,
+                quote_id,
+                user_address,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::ExecuteRfq {
+                    request_id,
+                    quote_id,
+                    user_address,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::RfqExecuted))
+            }
+            WireRequest::QueuePosition {
+                order_id,
+                user_address,
+            } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::QueuePosition {
+                    order_id,
+                    user_address,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::QueuePosition))
+            }
+            WireRequest::RestingOrders { market_id } => {
+                let (response_tx, rx) = oneshot::channel();
+                let request = EngineRequest::RestingOrders {
+                    market_id,
+                    response_tx,
+                };
+                (request, pending(rx, EngineReply::RestingOrders))
+            }
+        }
+    }
+}
@@ -6,6 +6,9 @@ use serde::Serialize;
 use thiserror::Error;
 use utoipa::ToSchema;
 
+use crate::models::domain::RejectReason;
+pub use exchange_protocol::error_code::ErrorCode;
+
 #[derive(Error, Debug)]
 pub enum ExchangeError {
     // Business logic errors (4xx)
@@ -18,6 +21,12 @@ pub enum ExchangeError {
     #[error("Market '{market_id}' already exists")]
     MarketAlreadyExists { market_id: String },
 
+    #[error("Display name '{display_name}' is already taken")]
+    DisplayNameTaken { display_name: String },
+
+    #[error("Sub-account '{address}' already exists")]
+    SubAccountExists { address: String },
+
     #[error("Invalid parameter: {message}")]
     InvalidParameter { message: String },
 
@@ -49,12 +58,135 @@ pub enum ExchangeError {
         required: u128,
     },
 
+    #[error("Fill-or-kill order for {size} in market '{market_id}' can only fill {available}")]
+    InsufficientLiquidity {
+        market_id: String,
+        size: u128,
+        available: u128,
+    },
+
+    #[error(
+        "Order at {price} in market '{market_id}' would rest across one of its user's own orders"
+    )]
+    SelfTrade { market_id: String, price: u128 },
+
+    #[error("Order exceeds limits for user '{user_address}' in market '{market_id}': {message}")]
+    LimitExceeded {
+        user_address: String,
+        market_id: String,
+        message: String,
+    },
+
+    #[error(
+        "User '{user_address}' already has {max_open_orders} open orders in market '{market_id}'"
+    )]
+    TooManyOpenOrders {
+        user_address: String,
+        market_id: String,
+        max_open_orders: usize,
+    },
+
+    #[error("Market '{market_id}' is {status}")]
+    MarketNotActive {
+        market_id: String,
+        status: crate::models::domain::MarketStatus,
+    },
+
+    #[error("Price {price} is more than {collar_bps} bps from the last trade at {reference_price} in market '{market_id}'")]
+    PriceOutsideCollar {
+        market_id: String,
+        price: u128,
+        reference_price: u128,
+        collar_bps: u32,
+    },
+
+    #[error("User '{user_address}' is {status}")]
+    UserNotActive {
+        user_address: String,
+        status: crate::models::domain::UserStatus,
+    },
+
+    #[error("Market '{market_id}' is in cancel-only mode")]
+    CancelOnly { market_id: String },
+
+    #[error("Missing or invalid admin token")]
+    Unauthorized,
+
+    #[error("Invalid or revoked API key")]
+    InvalidApiKey,
+
+    #[error("API key not permitted: {message}")]
+    ApiKeyNotPermitted { message: String },
+
+    #[error("Invalid signature: {message}")]
+    InvalidSignature { message: String },
+
+    #[error("API key '{key_id}' not found")]
+    ApiKeyNotFound { key_id: String },
+
     #[error("Order not found")]
     OrderNotFound,
 
     #[error("User '{address}' not found")]
     UserNotFound { address: String },
 
+    #[error("Export '{job_id}' not found or expired")]
+    ExportNotFound { job_id: String },
+
+    #[error("Webhook '{webhook_id}' not found")]
+    WebhookNotFound { webhook_id: String },
+
+    #[error("Withdrawal '{withdrawal_id}' not found")]
+    WithdrawalNotFound { withdrawal_id: String },
+
+    #[error("Sub-account '{address}' not found")]
+    SubAccountNotFound { address: String },
+
+    #[error("No statement for '{user_address}' for {period}")]
+    StatementNotFound {
+        user_address: String,
+        period: String,
+    },
+
+    #[error("Withdrawal '{withdrawal_id}' is {status}")]
+    WithdrawalNotPending {
+        withdrawal_id: String,
+        status: crate::models::domain::WithdrawalStatus,
+    },
+
+    #[error("Event '{event_id}' not found")]
+    EventNotFound { event_id: String },
+
+    #[error("Event '{event_id}' is already resolved")]
+    EventAlreadyResolved { event_id: String },
+
+    #[error("Request for quote '{request_id}' not found")]
+    RfqRequestNotFound { request_id: String },
+
+    #[error("Request for quote '{request_id}' is {status}")]
+    RfqRequestNotOpen {
+        request_id: String,
+        status: crate::models::domain::RfqStatus,
+    },
+
+    #[error("Quote '{quote_id}' not found or expired")]
+    QuoteNotFound { quote_id: String },
+
+    #[error("User '{user_address}' is not a registered RFQ maker")]
+    NotRfqMaker { user_address: String },
+
+    #[error("User '{user_address}' is not an approved market maker")]
+    NotMarketMaker { user_address: String },
+
+    #[error("Rate limited, retry in {retry_after_ms}ms")]
+    RateLimited { retry_after_ms: u64 },
+
+    #[error("Export '{job_id}' is {status}")]
+    ExportNotReady {
+        job_id: String,
+        status: crate::models::api::ExportJobStatus,
+    },
+
     #[error("Balance not found for user '{user_address}' token '{token_ticker}'")]
     BalanceNotFound {
         user_address: String,
@@ -70,6 +202,14 @@ pub enum ExchangeError {
     #[error("Failed to unlock balance")]
     UnlockFailed,
 
+    /// Returned by a matching engine in another process, passed on as it reported it
+    #[error("{message}")]
+    Remote {
+        code: ErrorCode,
+        status: u16,
+        message: String,
+    },
+
     // Infrastructure errors (5xx) - auto-converted
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
@@ -89,48 +229,99 @@ pub type Result<T> = std::result::Result<T, ExchangeError>;
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
-    pub code: String,
+    pub code: ErrorCode,
+    /// Set when an order was refused for a reason clients can act on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<RejectReason>,
 }
 
 impl ExchangeError {
     /// Get the error code for this error
-    fn error_code(&self) -> &'static str {
+    pub(crate) fn error_code(&self) -> ErrorCode {
         match self {
-            ExchangeError::TokenNotFound { .. } => "TOKEN_NOT_FOUND",
-            ExchangeError::MarketNotFound { .. } => "MARKET_NOT_FOUND",
-            ExchangeError::MarketAlreadyExists { .. } => "MARKET_ALREADY_EXISTS",
-            ExchangeError::InvalidParameter { .. } => "INVALID_PARAMETER",
-            ExchangeError::InvalidPrice => "INVALID_PRICE",
-            ExchangeError::InvalidSize => "INVALID_SIZE",
-            ExchangeError::InvalidAmount => "INVALID_AMOUNT",
-            ExchangeError::OrderValueOverflow => "ORDER_VALUE_OVERFLOW",
-            ExchangeError::InvalidTickSize => "INVALID_TICK_SIZE",
-            ExchangeError::InvalidLotSize => "INVALID_LOT_SIZE",
-            ExchangeError::SizeBelowMinimum => "SIZE_BELOW_MINIMUM",
-            ExchangeError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
-            ExchangeError::OrderNotFound => "ORDER_NOT_FOUND",
-            ExchangeError::UserNotFound { .. } => "USER_NOT_FOUND",
-            ExchangeError::BalanceNotFound { .. } => "BALANCE_NOT_FOUND",
-            ExchangeError::EngineSendFailed => "ENGINE_SEND_FAILED",
-            ExchangeError::EngineReceiveFailed => "ENGINE_RECEIVE_FAILED",
-            ExchangeError::UnlockFailed => "UNLOCK_FAILED",
-            ExchangeError::Database(_) => "DATABASE_ERROR",
-            ExchangeError::ClickHouse(_) => "CLICKHOUSE_ERROR",
-            ExchangeError::ParseError(_) => "PARSE_ERROR",
-            ExchangeError::UuidParseError(_) => "UUID_PARSE_ERROR",
+            ExchangeError::TokenNotFound { .. } => ErrorCode::TokenNotFound,
+            ExchangeError::MarketNotFound { .. } => ErrorCode::MarketNotFound,
+            ExchangeError::MarketAlreadyExists { .. } => ErrorCode::MarketAlreadyExists,
+            ExchangeError::DisplayNameTaken { .. } => ErrorCode::DisplayNameTaken,
+            ExchangeError::SubAccountExists { .. } => ErrorCode::SubAccountExists,
+            ExchangeError::InvalidParameter { .. } => ErrorCode::InvalidParameter,
+            ExchangeError::InvalidPrice => ErrorCode::InvalidPrice,
+            ExchangeError::InvalidSize => ErrorCode::InvalidSize,
+            ExchangeError::InvalidAmount => ErrorCode::InvalidAmount,
+            ExchangeError::OrderValueOverflow => ErrorCode::OrderValueOverflow,
+            ExchangeError::InvalidTickSize => ErrorCode::InvalidTickSize,
+            ExchangeError::InvalidLotSize => ErrorCode::InvalidLotSize,
+            ExchangeError::SizeBelowMinimum => ErrorCode::SizeBelowMinimum,
+            ExchangeError::InsufficientBalance { .. } => ErrorCode::InsufficientBalance,
+            ExchangeError::InsufficientLiquidity { .. } => ErrorCode::InsufficientLiquidity,
+            ExchangeError::SelfTrade { .. } => ErrorCode::SelfTrade,
+            ExchangeError::LimitExceeded { .. } => ErrorCode::LimitExceeded,
+            ExchangeError::TooManyOpenOrders { .. } => ErrorCode::TooManyOpenOrders,
+            ExchangeError::MarketNotActive { .. } => ErrorCode::MarketNotActive,
+            ExchangeError::PriceOutsideCollar { .. } => ErrorCode::PriceOutsideCollar,
+            ExchangeError::UserNotActive { .. } => ErrorCode::UserNotActive,
+            ExchangeError::CancelOnly { .. } => ErrorCode::CancelOnly,
+            ExchangeError::Unauthorized => ErrorCode::Unauthorized,
+            ExchangeError::InvalidApiKey => ErrorCode::InvalidApiKey,
+            ExchangeError::ApiKeyNotPermitted { .. } => ErrorCode::ApiKeyNotPermitted,
+            ExchangeError::InvalidSignature { .. } => ErrorCode::InvalidSignature,
+            ExchangeError::ApiKeyNotFound { .. } => ErrorCode::ApiKeyNotFound,
+            ExchangeError::OrderNotFound => ErrorCode::OrderNotFound,
+            ExchangeError::UserNotFound { .. } => ErrorCode::UserNotFound,
+            ExchangeError::ExportNotFound { .. } => ErrorCode::ExportNotFound,
+            ExchangeError::ExportNotReady { .. } => ErrorCode::ExportNotReady,
+            ExchangeError::WebhookNotFound { .. } => ErrorCode::WebhookNotFound,
+            ExchangeError::WithdrawalNotFound { .. } => ErrorCode::WithdrawalNotFound,
+            ExchangeError::SubAccountNotFound { .. } => ErrorCode::SubAccountNotFound,
+            ExchangeError::StatementNotFound { .. } => ErrorCode::StatementNotFound,
+            ExchangeError::WithdrawalNotPending { .. } => ErrorCode::WithdrawalNotPending,
+            ExchangeError::EventNotFound { .. } => ErrorCode::EventNotFound,
+            ExchangeError::EventAlreadyResolved { .. } => ErrorCode::EventAlreadyResolved,
+            ExchangeError::RfqRequestNotFound { .. } => ErrorCode::RfqRequestNotFound,
+            ExchangeError::RfqRequestNotOpen { .. } => ErrorCode::RfqRequestNotOpen,
+            ExchangeError::QuoteNotFound { .. } => ErrorCode::QuoteNotFound,
+            ExchangeError::NotRfqMaker { .. } => ErrorCode::NotRfqMaker,
+            ExchangeError::NotMarketMaker { .. } => ErrorCode::NotMarketMaker,
+            ExchangeError::RateLimited { .. } => ErrorCode::RateLimited,
+            ExchangeError::BalanceNotFound { .. } => ErrorCode::BalanceNotFound,
+            ExchangeError::EngineSendFailed => ErrorCode::EngineSendFailed,
+            ExchangeError::EngineReceiveFailed => ErrorCode::EngineReceiveFailed,
+            ExchangeError::UnlockFailed => ErrorCode::UnlockFailed,
+            ExchangeError::Remote { code, .. } => *code,
+            ExchangeError::Database(_) => ErrorCode::DatabaseError,
+            ExchangeError::ClickHouse(_) => ErrorCode::ClickhouseError,
+            ExchangeError::ParseError(_) => ErrorCode::ParseError,
+            ExchangeError::UuidParseError(_) => ErrorCode::UuidParseError,
         }
     }
 
     /// Get the HTTP status code for this error
-    fn status_code(&self) -> StatusCode {
+    pub(crate) fn status_code(&self) -> StatusCode {
         match self {
             // Client errors
             ExchangeError::TokenNotFound { .. } => StatusCode::NOT_FOUND,
             ExchangeError::MarketNotFound { .. } => StatusCode::NOT_FOUND,
             ExchangeError::OrderNotFound => StatusCode::NOT_FOUND,
             ExchangeError::UserNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::ExportNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::ExportNotReady { .. } => StatusCode::CONFLICT,
+            ExchangeError::WebhookNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::WithdrawalNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::SubAccountNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::StatementNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::WithdrawalNotPending { .. } => StatusCode::CONFLICT,
+            ExchangeError::EventNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::EventAlreadyResolved { .. } => StatusCode::CONFLICT,
+            ExchangeError::RfqRequestNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::RfqRequestNotOpen { .. } => StatusCode::CONFLICT,
+            ExchangeError::QuoteNotFound { .. } => StatusCode::NOT_FOUND,
+            ExchangeError::NotRfqMaker { .. } => StatusCode::FORBIDDEN,
+            ExchangeError::NotMarketMaker { .. } => StatusCode::FORBIDDEN,
+            ExchangeError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
             ExchangeError::BalanceNotFound { .. } => StatusCode::NOT_FOUND,
             ExchangeError::MarketAlreadyExists { .. } => StatusCode::CONFLICT,
+            ExchangeError::DisplayNameTaken { .. } => StatusCode::CONFLICT,
+            ExchangeError::SubAccountExists { .. } => StatusCode::CONFLICT,
             ExchangeError::InvalidParameter { .. } => StatusCode::BAD_REQUEST,
             ExchangeError::InvalidPrice => StatusCode::BAD_REQUEST,
             ExchangeError::InvalidSize => StatusCode::BAD_REQUEST,
@@ -140,6 +331,19 @@ impl ExchangeError {
             ExchangeError::InvalidLotSize => StatusCode::BAD_REQUEST,
             ExchangeError::SizeBelowMinimum => StatusCode::BAD_REQUEST,
             ExchangeError::InsufficientBalance { .. } => StatusCode::BAD_REQUEST,
+            ExchangeError::InsufficientLiquidity { .. } => StatusCode::BAD_REQUEST,
+            ExchangeError::SelfTrade { .. } => StatusCode::BAD_REQUEST,
+            ExchangeError::LimitExceeded { .. } => StatusCode::FORBIDDEN,
+            ExchangeError::TooManyOpenOrders { .. } => StatusCode::FORBIDDEN,
+            ExchangeError::MarketNotActive { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ExchangeError::PriceOutsideCollar { .. } => StatusCode::BAD_REQUEST,
+            ExchangeError::UserNotActive { .. } => StatusCode::FORBIDDEN,
+            ExchangeError::CancelOnly { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ExchangeError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ExchangeError::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            ExchangeError::ApiKeyNotPermitted { .. } => StatusCode::FORBIDDEN,
+            ExchangeError::InvalidSignature { .. } => StatusCode::UNAUTHORIZED,
+            ExchangeError::ApiKeyNotFound { .. } => StatusCode::NOT_FOUND,
             ExchangeError::ParseError(_) => StatusCode::BAD_REQUEST,
             ExchangeError::UuidParseError(_) => StatusCode::BAD_REQUEST,
             // Server errors
@@ -148,6 +352,44 @@ impl ExchangeError {
             ExchangeError::EngineSendFailed => StatusCode::INTERNAL_SERVER_ERROR,
             ExchangeError::EngineReceiveFailed => StatusCode::INTERNAL_SERVER_ERROR,
             ExchangeError::UnlockFailed => StatusCode::INTERNAL_SERVER_ERROR,
+            ExchangeError::Remote { status, .. } => {
+                StatusCode::from_u16(*status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Why an order failing with this error was refused, if clients can act on it
+    pub(crate) fn reject_reason(&self) -> Option<RejectReason> {
+        match self {
+            ExchangeError::InsufficientBalance { .. } => Some(RejectReason::InsufficientBalance),
+            ExchangeError::InsufficientLiquidity { .. } => {
+                Some(RejectReason::InsufficientLiquidity)
+            }
+            ExchangeError::SelfTrade { .. } => Some(RejectReason::SelfTrade),
+            ExchangeError::InvalidPrice
+            | ExchangeError::InvalidSize
+            | ExchangeError::InvalidTickSize
+            | ExchangeError::InvalidLotSize
+            | ExchangeError::SizeBelowMinimum
+            | ExchangeError::OrderValueOverflow => Some(RejectReason::InvalidOrder),
+            ExchangeError::PriceOutsideCollar { .. } => Some(RejectReason::PriceBand),
+            ExchangeError::LimitExceeded { .. } | ExchangeError::TooManyOpenOrders { .. } => {
+                Some(RejectReason::LimitExceeded)
+            }
+            ExchangeError::MarketNotActive { .. } => Some(RejectReason::MarketHalted),
+            ExchangeError::CancelOnly { .. } => Some(RejectReason::CancelOnly),
+            ExchangeError::UserNotActive { .. } => Some(RejectReason::AccountRestricted),
+            _ => None,
+        }
+    }
+
+    /// Message safe to show clients; infrastructure errors are not exposed
+    pub(crate) fn public_message(&self) -> String {
+        match self {
+            ExchangeError::Database(_) | ExchangeError::ClickHouse(_) => {
+                "Internal server error".to_string()
+            }
+            _ => self.to_string(),
         }
     }
 }
@@ -158,21 +400,16 @@ impl IntoResponse for ExchangeError {
         let error_code = self.error_code();
 
         // For server errors, log the details but don't expose them
-        let error_message = match &self {
-            ExchangeError::Database(ref e) => {
-                log::error!("Database error: {}", e);
-                "Internal server error".to_string()
-            }
-            ExchangeError::ClickHouse(ref e) => {
-                log::error!("ClickHouse error: {}", e);
-                "Internal server error".to_string()
-            }
-            _ => self.to_string(),
-        };
+        match &self {
+            ExchangeError::Database(ref e) => log::error!("Database error: {}", e),
+            ExchangeError::ClickHouse(ref e) => log::error!("ClickHouse error: {}", e),
+            _ => {}
+        }
 
         let body = Json(ErrorResponse {
-            error: error_message,
-            code: error_code.to_string(),
+            error: self.public_message(),
+            code: error_code,
+            reason: self.reject_reason(),
         });
 
         (status, body).into_response()
@@ -0,0 +1,176 @@
+// engine events published to NATS or Kafka for downstream consumers
+
+use crate::models::domain::EngineEvent;
+use crate::saturation::Saturation;
+use chrono::Utc;
+use exchange_protocol::events::{BusEvent, BusMessage, EVENT_SCHEMA_VERSION};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Topic prefix used when `EVENT_BUS_PREFIX` is unset
+pub const DEFAULT_TOPIC_PREFIX: &str = "exchange";
+
+/// Where bus messages are sent
+pub enum EventSink {
+    Nats(async_nats::Client),
+    #[cfg(feature = "kafka")]
+    Kafka(rdkafka::producer::FutureProducer),
+}
+
+impl EventSink {
+    /// Connect to `nats://host:port` or, with the `kafka` feature, `kafka://broker1:9092,broker2:9092`
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        if url.starts_with("nats://") || url.starts_with("tls://") {
+            let client = async_nats::connect(url).await?;
+            return Ok(EventSink::Nats(client));
+        }
+
+        if let Some(brokers) = url.strip_prefix("kafka://") {
+            #[cfg(feature = "kafka")]
+            {
+                let producer = rdkafka::ClientConfig::new()
+                    .set("bootstrap.servers", brokers)
+                    .set("enable.idempotence", "true")
+                    .create()?;
+                return Ok(EventSink::Kafka(producer));
+            }
+            #[cfg(not(feature = "kafka"))]
+            anyhow::bail!(
+                "Kafka brokers {} need the backend built with the `kafka` feature",
+                brokers
+            );
+        }
+
+        anyhow::bail!("Unsupported event bus URL '{}'", url)
+    }
+
+    /// Queue a payload in the client without waiting for the broker to acknowledge it
+    #[cfg_attr(not(feature = "kafka"), allow(unused_variables))]
+    async fn publish(&self, topic: String, key: &str, payload: Vec<u8>) -> anyhow::Result<()> {
+        match self {
+            EventSink::Nats(client) => client.publish(topic, payload.into()).await?,
+            #[cfg(feature = "kafka")]
+            EventSink::Kafka(producer) => {
+                let record = rdkafka::producer::FutureRecord::to(&topic)
+                    .key(key)
+                    .payload(&payload);
+                let delivery = producer
+                    .send_result(record)
+                    .map_err(|(e, _)| anyhow::anyhow!("Kafka queue rejected message: {}", e))?;
+                tokio::spawn(async move {
+                    if let Ok(Err((e, _))) = delivery.await {
+                        log::error!("Failed to deliver to Kafka topic {}: {}", topic, e);
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bus event for an engine event; orderbook snapshots, rejections and market
+/// status changes are not published
+pub fn bus_event(event: &EngineEvent) -> Option<BusEvent> {
+    match event {
+        EngineEvent::TradeExecuted { trade, .. } => Some(BusEvent::Trade(trade.clone().into())),
+        EngineEvent::OrderPlaced { order } => Some(BusEvent::Order(order.clone().into())),
+        EngineEvent::OrderCancelled {
+            order_id,
+            user_address,
+            reason,
+        } => Some(BusEvent::OrderCancelled {
+            order_id: order_id.to_string(),
+            user_address: user_address.clone(),
+            reason: *reason,
+        }),
+        EngineEvent::BalanceUpdated { balance } => Some(BusEvent::Balance(balance.clone().into())),
+        EngineEvent::WithdrawalUpdated { withdrawal } => {
+            Some(BusEvent::Withdrawal(withdrawal.clone().into()))
+        }
+        EngineEvent::PositionLiquidated { liquidation } => {
+            Some(BusEvent::Liquidation(liquidation.clone().into()))
+        }
+        EngineEvent::OrderRejected { .. }
+        | EngineEvent::MarketStatusChanged { .. }
+        | EngineEvent::OrderbookSnapshot { .. } => None,
+    }
+}
+
+/// Publishes every trade, order, balance, withdrawal and liquidation event to
+/// `{prefix}.trades`, `{prefix}.orders`, `{prefix}.balances`, `{prefix}.withdrawals`
+/// and `{prefix}.liquidations`
+///
+/// Delivery is at most once: events the publisher falls behind on, or that the
+/// broker rejects, are logged and skipped. Consumers spot the gap in `sequence`.
+pub struct EventPublisher {
+    sink: EventSink,
+    prefix: String,
+    sequence: u64,
+    saturation: Saturation,
+}
+
+impl EventPublisher {
+    pub fn new(sink: EventSink, prefix: impl Into<String>) -> Self {
+        Self {
+            sink,
+            prefix: prefix.into(),
+            sequence: 0,
+            saturation: Saturation::default(),
+        }
+    }
+
+    /// Report events the publisher skips to `saturation`
+    pub fn with_saturation(mut self, saturation: Saturation) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    /// Wrap an event in the next message of the sequence
+    fn message(&mut self, event: BusEvent) -> BusMessage {
+        let message = BusMessage {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: self.sequence,
+            published_at: Utc::now(),
+            event,
+        };
+        self.sequence += 1;
+        message
+    }
+
+    /// Start publishing events from the engine's broadcast channel
+    pub fn spawn(mut self, mut event_rx: broadcast::Receiver<EngineEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match event_rx.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = bus_event(&event) {
+                            self.publish(event).await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Event publisher lagged, skipped {} engine events", skipped);
+                        self.sequence += skipped;
+                        self.saturation.record_lag("event_bus", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    async fn publish(&mut self, event: BusEvent) {
+        let topic = format!("{}.{}", self.prefix, event.topic());
+        let key = event.key().to_string();
+        let message = self.message(event);
+        let payload = match serde_json::to_vec(&message) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::error!("Failed to encode event {}: {}", message.sequence, e);
+                return;
+            }
+        };
+        if let Err(e) = self.sink.publish(topic, &key, payload).await {
+            log::error!("Failed to publish event {}: {:#}", message.sequence, e);
+        }
+    }
+}
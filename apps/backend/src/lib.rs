@@ -1,10 +1,35 @@
+pub mod alerts;
+pub mod analytics;
 pub mod api;
+pub mod archive;
+pub mod balance_notify;
+pub mod bootstrap;
+pub mod cache;
 pub mod config;
 pub mod db;
+pub mod deposits;
 pub mod engine;
+pub mod engine_service;
 pub mod errors;
+pub mod event_bus;
 pub mod models;
+pub mod perps;
+pub mod prediction;
+pub mod price_feed;
+pub mod rfq;
+pub mod saturation;
+pub mod schema;
+pub mod shutdown;
+pub mod sim;
+pub mod snapshot;
+pub mod statements;
+pub mod sub_accounts;
+pub mod surveillance;
+pub mod symbols;
+pub mod telemetry;
 pub mod utils;
+pub mod webhooks;
+pub mod withdrawals;
 
 use tokio::sync::{broadcast, mpsc};
 
@@ -16,4 +41,21 @@ pub struct AppState {
     pub db: db::Db,
     pub engine_tx: mpsc::Sender<EngineRequest>,
     pub event_tx: broadcast::Sender<EngineEvent>,
+    pub event_router: api::ws::EventRouter,
+    /// Bearer token for operator endpoints such as the kill switch; `None` disables them
+    pub admin_token: Option<String>,
+    /// Short-lived cache of public reads: market lists, tickers, top of book
+    pub cache: cache::ReadCache,
+    /// Fills exports too large to stream, running or ready for download
+    pub exports: api::rest::export::ExportJobs,
+    /// Access and rate limits of the per-order (L3) book
+    pub l3: api::rest::l3::L3Access,
+    /// Signatures of requests that move funds or manage an account, used until they expire
+    pub signatures: api::signature::SignatureCheck,
+    /// Triggered when the server starts shutting down
+    pub shutdown: shutdown::Shutdown,
+    /// Queue depths, event lag and degraded mode, exported at `/api/metrics`
+    pub saturation: saturation::Saturation,
+    /// Aliases and numeric ids of the listed markets, for resolving the market ids clients send
+    pub symbols: symbols::Symbols,
 }
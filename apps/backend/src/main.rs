@@ -1,13 +1,37 @@
 use anyhow::Context;
 use axum::Router;
+use backend::alerts::{AlertDispatcher, AlertTarget, Alerter};
 use backend::api::rest;
-use backend::api::ws;
+use backend::api::signature::SignatureCheck;
+use backend::api::{auth, ws};
+use backend::archive::{ArchiveStore, Archiver};
+use backend::balance_notify;
+use backend::bootstrap;
+use backend::cache::{self, ReadCache};
 use backend::config::Config;
 use backend::db::Db;
+use backend::deposits::DepositWatcher;
+use backend::engine::analytics::ANALYTICS_BUFFER_SIZE;
 use backend::engine::MatchingEngine;
+use backend::engine_service::{EngineServer, RemoteEngine};
+use backend::event_bus::{self, EventPublisher, EventSink};
 use backend::models::domain::{EngineEvent, EngineRequest};
+use backend::perps::FundingSettler;
+use backend::price_feed::{IndexFeed, PriceFeed};
+use backend::saturation::{Saturation, SaturationMonitor, SaturationThresholds};
+use backend::shutdown::{self, Shutdown};
+use backend::statements::StatementGenerator;
+use backend::surveillance::Surveiller;
+use backend::symbols::{self, Symbols};
+use backend::telemetry;
+use backend::webhooks::WebhookDispatcher;
+use backend::withdrawals::signer::HttpSigner;
+use backend::withdrawals::WithdrawalProcessor;
 use backend::AppState;
+use std::path::Path;
+use std::time::Duration;
 use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
 use tower_http::cors::CorsLayer;
 
 #[tokio::main]
@@ -17,11 +41,13 @@ async fn main() -> anyhow::Result<()> {
     let _ = dotenvy::from_path_override(".env");
 
     env_logger::init();
+    let _telemetry = telemetry::init().context("Failed to set up tracing")?;
 
     // ===============================
     // Load configuration
     // ===============================
-    let config = Config::load().context("Failed to load configuration")?;
+    let config_path = Config::path();
+    let config = Config::load_from(&config_path).context("Failed to load configuration")?;
     let host = std::env::var("HOST").unwrap_or_else(|_| "localhost".to_string());
     let port = std::env::var("PORT").unwrap_or_else(|_| "8888".to_string());
     let addr = format!("{}:{}", host, port);
@@ -43,21 +69,91 @@ async fn main() -> anyhow::Result<()> {
     // ===============================
     let (engine_tx, engine_rx) = mpsc::channel::<EngineRequest>(100);
     let (event_tx, _) = broadcast::channel::<EngineEvent>(1000); // use event_tx to create more listeners
+    let shutdown = Shutdown::new();
 
-    // ===============================
-    // Run matching engine
-    // ===============================
-    let engine = MatchingEngine::new(db.clone(), engine_rx, event_tx.clone());
+    // Queue depths and event lag, and whether to shed market-data work
+    let saturation = Saturation::default();
 
-    // Recover orderbooks from database (restore pending orders after restart)
-    if let Err(e) = engine.recover_orderbooks().await {
-        log::error!("Failed to recover orderbooks: {}", e);
-        // Continue anyway - orderbooks will be empty but server can still function
-    }
+    // With ENGINE_ADDR set this process is an API gateway for an engine
+    // running elsewhere; otherwise it runs the engine itself
+    let (state_event_tx, background) = match std::env::var("ENGINE_ADDR") {
+        Ok(engine_addr) => {
+            log::info!("Forwarding to the matching engine at {}", engine_addr);
+            // Events raised by this gateway's handlers go out through the engine
+            let (outbox_tx, _) = broadcast::channel::<EngineEvent>(1000);
+            let mut remote = RemoteEngine::new(
+                engine_addr,
+                engine_rx,
+                outbox_tx.subscribe(),
+                event_tx.clone(),
+            );
+            if let Ok(token) = std::env::var("ENGINE_TOKEN") {
+                remote = remote.with_token(token);
+            }
+            tokio::spawn(
+                SaturationMonitor::new(saturation.clone(), engine_tx.downgrade())
+                    .with_thresholds(saturation_thresholds()?)
+                    .run(),
+            );
+            (outbox_tx, Background::Gateway(tokio::spawn(remote.run())))
+        }
+        Err(_) => {
+            let tasks = start_engine(
+                &db,
+                &config,
+                &config_path,
+                engine_tx.clone(),
+                engine_rx,
+                event_tx.clone(),
+                &saturation,
+                &shutdown,
+            )
+            .await?;
+            (event_tx.clone(), Background::Engine(tasks))
+        }
+    };
+
+    // Cache hot public reads, in Redis when shared by several API replicas
+    let cache = match std::env::var("REDIS_URL") {
+        Ok(url) => {
+            let prefix = std::env::var("CACHE_PREFIX")
+                .unwrap_or_else(|_| cache::DEFAULT_KEY_PREFIX.to_string());
+            // The URL may carry a password, so it stays out of the logs
+            log::info!("Caching public reads in Redis under '{}'", prefix);
+            ReadCache::connect(&url, prefix)
+                .await
+                .context("Failed to connect to Redis")?
+        }
+        Err(_) => ReadCache::default(),
+    };
+    cache
+        .clone()
+        .with_saturation(saturation.clone())
+        .spawn_updater(event_tx.subscribe());
+
+    // Route engine events to WebSocket subscribers by market / user
+    let event_router = ws::EventRouter::new().with_saturation(saturation.clone());
+    event_router.spawn(event_tx.subscribe());
+    ws::spawn_tickers(
+        event_router.clone(),
+        db.clone(),
+        cache.clone(),
+        ws::TICKER_INTERVAL,
+    );
 
-    tokio::spawn(async move {
-        engine.run().await;
-    });
+    // Resolve market aliases such as BTC-USDC, picking up markets added later
+    let symbols = Symbols::load(&db)
+        .await
+        .context("Failed to load market symbols")?;
+    symbols
+        .clone()
+        .spawn_refresher(db.clone(), symbols::REFRESH_INTERVAL);
+
+    // Orders without a signature are only for local stacks whose clients don't sign yet
+    let unsigned_trading = env_parse::<bool>("ALLOW_UNSIGNED_TRADING")?.unwrap_or(false);
+    if unsigned_trading {
+        log::warn!("ALLOW_UNSIGNED_TRADING is set: orders are taken without a signature");
+    }
 
     // ===============================
     // Create axum app
@@ -67,13 +163,29 @@ async fn main() -> anyhow::Result<()> {
     let state = AppState {
         db,
         engine_tx,
-        event_tx,
+        event_tx: state_event_tx,
+        event_router,
+        admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+        cache,
+        exports: std::env::var("EXPORT_DIR")
+            .map(|dir| rest::export::ExportJobs::new(dir.into()))
+            .unwrap_or_default(),
+        l3: Default::default(),
+        signatures: SignatureCheck::default().with_unsigned_trading(unsigned_trading),
+        shutdown: shutdown.clone(),
+        saturation,
+        symbols,
     };
 
     let app = Router::new()
         .merge(rest)
         .merge(ws)
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::authenticate,
+        ))
         .with_state(state)
+        .layer(telemetry::http_trace_layer())
         .layer(CorsLayer::permissive());
 
     // ===============================
@@ -86,9 +198,345 @@ async fn main() -> anyhow::Result<()> {
     println!("\n🚀 Backend server running on http://{}", addr);
     println!("📖 OpenAPI docs: http://{}/api/docs", addr);
     println!("📋 OpenAPI spec: http://{}/api/openapi.json", addr);
-    println!("\n💡 Tip: Run 'just db-init' to initialize markets and tokens\n");
+    println!(
+        "\n💡 Tip: Add markets and tokens to {} and send SIGHUP to load them\n",
+        config_path.display()
+    );
 
-    axum::serve(listener, app).await.context("Server error")?;
+    // On SIGTERM stop accepting connections and tell WebSocket clients we're going away.
+    // Peer addresses are kept for API key allowlists.
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown::terminate().await;
+            log::info!("Shutting down, no longer accepting connections");
+            shutdown.trigger();
+        }
+    })
+    .await
+    .context("Server error")?;
 
+    // ===============================
+    // Drain queued work
+    // ===============================
+    background.drain().await;
+
+    log::info!("Shutdown complete");
     Ok(())
 }
+
+/// Background tasks of a process running the matching engine
+struct EngineTasks {
+    engine: JoinHandle<()>,
+    server: Option<JoinHandle<()>>,
+    /// Tasks that only feed new work in; stopped outright on shutdown
+    pollers: Vec<JoinHandle<()>>,
+    publisher: Option<JoinHandle<()>>,
+    webhooks: JoinHandle<()>,
+    alerts: Option<JoinHandle<()>>,
+}
+
+/// Tasks running beside the HTTP server
+enum Background {
+    /// Forwards the engine queue to an engine in another process
+    Gateway(JoinHandle<()>),
+    Engine(EngineTasks),
+}
+
+impl Background {
+    /// Let every task finish its queued work, in dependency order
+    async fn drain(self) {
+        let tasks = match self {
+            // The link closes once the engine has answered what was sent
+            Background::Gateway(remote) => {
+                shutdown::drain("Engine link", remote).await;
+                return;
+            }
+            Background::Engine(tasks) => tasks,
+        };
+
+        // Deposits, withdrawals and feeds pick up where they left off on restart
+        for poller in &tasks.pollers {
+            poller.abort();
+        }
+
+        // Gateway connections hold the engine queue too; they close once the
+        // engine has answered the requests they sent
+        if let Some(handle) = tasks.server {
+            shutdown::drain("Engine server", handle).await;
+        }
+
+        // The engine stops once the last handler lets go of its queue, after
+        // matching everything already in it and flushing trades to ClickHouse
+        shutdown::drain("Matching engine", tasks.engine).await;
+
+        // With the engine gone the event channel closes, and the publisher and
+        // webhook dispatcher stop after delivering what's left of it
+        for poller in tasks.pollers {
+            let _ = poller.await;
+        }
+        if let Some(handle) = tasks.publisher {
+            shutdown::drain("Event publisher", handle).await;
+        }
+        shutdown::drain("Webhook dispatcher", tasks.webhooks).await;
+        if let Some(handle) = tasks.alerts {
+            shutdown::drain("Alert dispatcher", handle).await;
+        }
+    }
+}
+
+/// Set up and spawn the matching engine with everything that feeds it or
+/// consumes its events
+#[allow(clippy::too_many_arguments)]
+async fn start_engine(
+    db: &Db,
+    config: &Config,
+    config_path: &Path,
+    engine_tx: mpsc::Sender<EngineRequest>,
+    engine_rx: mpsc::Receiver<EngineRequest>,
+    event_tx: broadcast::Sender<EngineEvent>,
+    saturation: &Saturation,
+    shutdown: &Shutdown,
+) -> anyhow::Result<EngineTasks> {
+    // Create the configured tokens and markets that don't exist yet
+    let report = bootstrap::create_missing(db, config)
+        .await
+        .context("Failed to set up tokens and markets from configuration")?;
+    log::info!(
+        "  Created {} tokens and {} markets from {}",
+        report.tokens_created.len(),
+        report.markets_created.len(),
+        config_path.display()
+    );
+
+    // ===============================
+    // Run matching engine
+    // ===============================
+    let mut engine = MatchingEngine::new(db.clone(), engine_rx, event_tx.clone());
+
+    // Push persistence failures, halts, mass cancels and saturation to operators
+    let targets = AlertTarget::from_env();
+    let (alerter, alerts_handle) = if targets.is_empty() {
+        (Alerter::default(), None)
+    } else {
+        let mut dispatcher = AlertDispatcher::new(targets.clone());
+        if let Some(secs) = env_parse::<u64>("ALERT_DEDUP_SECS")? {
+            dispatcher = dispatcher.with_dedup_window(Duration::from_secs(secs));
+        }
+        log::info!("Sending alerts to {} targets", targets.len());
+        let (alerter, handle) = dispatcher.spawn();
+        (alerter, Some(handle))
+    };
+    engine.set_alerts(alerter.clone());
+    if let Some(orders) = env_parse::<usize>("ALERT_MASS_CANCEL_ORDERS")? {
+        engine.set_mass_cancel_threshold(orders);
+    }
+    engine.set_saturation(saturation.clone());
+
+    // Bounded-price markets get array-indexed orderbooks (before recovery fills them)
+    for market in &config.markets {
+        let layout = market.ladder_layout()?;
+        if layout != backend::engine::ladder::LadderLayout::Tree {
+            log::info!("  {}: array price ladder {:?}", market.market_id(), layout);
+        }
+        engine.set_ladder_layout(&market.market_id(), layout).await;
+        if let Some(collar_bps) = market.price_collar_bps {
+            engine.set_price_collar(&market.market_id(), collar_bps);
+        }
+    }
+
+    // Recover orderbooks from database (restore pending orders after restart)
+    if let Err(e) = engine.recover_orderbooks().await {
+        log::error!("Failed to recover orderbooks: {}", e);
+        // Continue anyway - orderbooks will be empty but server can still function
+    }
+
+    // Poll external index prices for markets that have a feed
+    let mut price_feed = PriceFeed::new(engine.index_prices());
+    if let Ok(url) = std::env::var("HYPERLIQUID_INFO_URL") {
+        price_feed = price_feed.with_hyperliquid_url(url);
+    }
+    for market in &config.markets {
+        let Some(source) = market.index_feed.clone() else {
+            continue;
+        };
+        let quote_decimals = config
+            .token_decimals(&market.quote_ticker)
+            .with_context(|| format!("Unknown quote token for {}", market.market_id()))?;
+        log::info!(
+            "  {}: index price from {}",
+            market.market_id(),
+            source.name()
+        );
+        price_feed.add(IndexFeed {
+            market_id: market.market_id(),
+            source,
+            quote_decimals,
+        });
+    }
+    // Sample the engine queue, ClickHouse buffer and event lag; shed market data when saturated
+    let monitor = SaturationMonitor::new(saturation.clone(), engine_tx.downgrade())
+        .with_persistence_queue(engine.analytics_stats(), ANALYTICS_BUFFER_SIZE)
+        .with_thresholds(saturation_thresholds()?)
+        .with_alerts(alerter);
+    let mut pollers = vec![tokio::spawn(monitor.run())];
+    if !price_feed.is_empty() {
+        pollers.push(tokio::spawn(price_feed.run(db.clone())));
+    }
+
+    // Mark perpetual markets and settle their funding every interval
+    let perpetuals = config
+        .markets
+        .iter()
+        .filter(|m| m.perpetual.is_some())
+        .count();
+    if perpetuals > 0 {
+        log::info!("  Settling funding for {} perpetual markets", perpetuals);
+        let settler = FundingSettler::new(
+            db.clone(),
+            engine_tx.clone(),
+            engine.orderbooks(),
+            engine.index_prices(),
+        );
+        pollers.push(tokio::spawn(settler.run()));
+    }
+
+    // Refuse to start rather than take orders without the limits, switches
+    // and triggers they're checked against
+    engine
+        .load_state()
+        .await
+        .context("Failed to load engine state")?;
+
+    let engine_markets = engine.markets();
+    let engine_handle = tokio::spawn(engine.run());
+
+    // Serve the engine to API gateways in other processes
+    let mut server_handle = None;
+    if let Ok(listen_addr) = std::env::var("ENGINE_LISTEN_ADDR") {
+        let listener = tokio::net::TcpListener::bind(&listen_addr)
+            .await
+            .with_context(|| format!("Failed to bind engine service to {}", listen_addr))?;
+        let mut server = EngineServer::new(engine_tx.clone(), event_tx.clone(), engine_markets);
+        if let Ok(token) = std::env::var("ENGINE_TOKEN") {
+            server = server.with_token(token);
+        }
+        log::info!("Serving the matching engine to gateways on {}", listen_addr);
+        server_handle = Some(tokio::spawn(server.serve(listener, shutdown.clone())));
+    }
+
+    // Add markets and tokens newly added to the config on SIGHUP
+    pollers.push(tokio::spawn(bootstrap::reload_on_hangup(
+        db.clone(),
+        engine_tx.clone(),
+        config_path.to_path_buf(),
+    )));
+
+    // Publish engine events to NATS or Kafka for downstream consumers
+    let mut publisher = None;
+    if let Ok(url) = std::env::var("EVENT_BUS_URL") {
+        let sink = EventSink::connect(&url)
+            .await
+            .with_context(|| format!("Failed to connect to event bus at {}", url))?;
+        let prefix = std::env::var("EVENT_BUS_PREFIX")
+            .unwrap_or_else(|_| event_bus::DEFAULT_TOPIC_PREFIX.to_string());
+        log::info!("Publishing engine events to {} under '{}'", url, prefix);
+        publisher = Some(
+            EventPublisher::new(sink, prefix)
+                .with_saturation(saturation.clone())
+                .spawn(event_tx.subscribe()),
+        );
+    }
+
+    // Archive each closed day's trades and candles to object storage
+    if let Ok(url) = std::env::var("ARCHIVE_URL") {
+        let store = ArchiveStore::from_url(&url)
+            .with_context(|| format!("Failed to open archive at {}", url))?;
+        log::info!("Archiving trades and candles to {}", url);
+        pollers.push(tokio::spawn(Archiver::new(db.clone(), store).run()));
+    }
+
+    // Create next months' orders partitions before any order needs them
+    pollers.push(tokio::spawn(db.clone().maintain_order_partitions()));
+
+    // Look for wash trading, spoofing and self-matching in each closed hour of trades
+    pollers.push(tokio::spawn(Surveiller::new(db.clone()).run()));
+
+    // Close each calendar month with every user's account statement, posted to their webhooks
+    pollers.push(tokio::spawn(
+        StatementGenerator::new(db.clone())
+            .with_webhooks(WebhookDispatcher::new(db.clone()))
+            .run(),
+    ));
+
+    // Pass on balance changes announced over Postgres, such as faucet and deposit credits
+    pollers.push(balance_notify::spawn_balance_listener(
+        db.clone(),
+        event_tx.clone(),
+    ));
+
+    // Credit confirmed ERC-20 deposits to their senders
+    if let Ok(url) = std::env::var("DEPOSIT_RPC_URL") {
+        let watcher = DepositWatcher::new(db.clone(), &url, config)
+            .context("Invalid deposit configuration")?;
+        pollers.push(tokio::spawn(watcher.run()));
+
+        // Send withdrawals out on the same chain through the signing service
+        if let Ok(signer_url) = std::env::var("WITHDRAWAL_SIGNER_URL") {
+            let mut signer = HttpSigner::new(signer_url);
+            if let Ok(token) = std::env::var("WITHDRAWAL_SIGNER_TOKEN") {
+                signer = signer.with_token(token);
+            }
+            let processor =
+                WithdrawalProcessor::new(db.clone(), &url, config, signer, event_tx.clone())
+                    .context("Invalid withdrawal configuration")?;
+            pollers.push(tokio::spawn(processor.run()));
+        }
+    }
+
+    // Post users' fills and order updates to their registered webhooks
+    let webhooks = WebhookDispatcher::new(db.clone())
+        .with_saturation(saturation.clone())
+        .spawn(event_tx.subscribe());
+
+    Ok(EngineTasks {
+        engine: engine_handle,
+        server: server_handle,
+        pollers,
+        publisher,
+        webhooks,
+        alerts: alerts_handle,
+    })
+}
+
+/// Saturation thresholds, overridden by the `SATURATION_*` environment variables
+fn saturation_thresholds() -> anyhow::Result<SaturationThresholds> {
+    let mut thresholds = SaturationThresholds::default();
+    if let Some(percent) = env_parse("SATURATION_ENGINE_QUEUE_PERCENT")? {
+        thresholds.engine_queue_percent = percent;
+    }
+    if let Some(percent) = env_parse("SATURATION_PERSISTENCE_QUEUE_PERCENT")? {
+        thresholds.persistence_queue_percent = percent;
+    }
+    if let Some(events) = env_parse("SATURATION_LAGGED_EVENTS")? {
+        thresholds.lagged_events = events;
+    }
+    Ok(thresholds)
+}
+
+/// Parse environment variable `name`, `None` while it is unset
+fn env_parse<T>(name: &str) -> anyhow::Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    std::env::var(name)
+        .ok()
+        .map(|value| value.parse())
+        .transpose()
+        .with_context(|| format!("Invalid {}", name))
+}
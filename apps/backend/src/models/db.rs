@@ -3,10 +3,14 @@ use chrono::{DateTime, Utc};
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::models::domain::{Balance, Market, Order, Side, Token, Trade, User};
-use crate::utils::BigDecimalExt;
+use crate::models::domain::{
+    Balance, DepthMetrics, Fill, FundingRate, IndexPrice, MakerVolume, Market,
+    OpenInterestSnapshot, Order, Side, SurveillanceAlert, TakerFlow, Token, Trade, TradePair, User,
+};
+use crate::utils::decode_atoms;
 
 // ============================================================================
 // DATABASE ROW TYPES
@@ -44,13 +48,17 @@ pub struct OrderRow {
     pub market_id: String,
     pub price: BigDecimal,
     pub size: BigDecimal,
-    pub side: String, // Custom type 'side' in DB, stored as TEXT
-    #[sqlx(rename = "type")]
-    pub order_type: String, // Custom type 'order_type' in DB
-    pub status: String, // Custom type 'order_status' in DB
+    pub side: String,       // Custom type 'side' in DB, selected as TEXT
+    pub order_type: String, // Custom type 'order_type' in DB, selected as TEXT
+    pub status: String,     // Custom type 'order_status' in DB
     pub filled_size: BigDecimal,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub cancel_reason: Option<String>,
+    pub trigger_price: Option<BigDecimal>,
+    pub trigger_source: String,
+    pub time_in_force: String,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -65,6 +73,7 @@ pub struct TradeRow {
     pub size: BigDecimal,
     pub side: String, // "buy" or "sell"
     pub timestamp: DateTime<Utc>,
+    pub rfq: bool,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -76,6 +85,21 @@ pub struct BalanceRow {
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, FromRow)]
+pub struct SurveillanceAlertRow {
+    pub id: i64,
+    pub kind: String,
+    pub market_id: String,
+    pub subject: String,
+    pub accounts: Vec<String>,
+    pub count: i64,
+    pub volume: BigDecimal,
+    pub detail: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
 // ClickHouse-specific row types (for tick data and candles)
 #[derive(Debug, Clone, Row, Serialize, Deserialize)]
 pub struct ClickHouseTradeRow {
@@ -89,6 +113,7 @@ pub struct ClickHouseTradeRow {
     pub size: u128,
     pub side: String,   // "buy" or "sell"
     pub timestamp: u32, // Unix timestamp
+    pub rfq: bool,
 }
 
 // Used for querying aggregated candles from ClickHouse
@@ -104,10 +129,303 @@ pub struct CandleRow {
     pub volume: u128,
 }
 
+// ClickHouse row for one side of a trade, with the trade repeated per trader by ARRAY JOIN
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct FillRow {
+    pub user_address: String,
+    pub market_id: String,
+    pub side: String, // the trader's side, "buy" or "sell"
+    pub price: u128,
+    pub size: u128,
+    pub timestamp: u32, // Unix timestamp
+}
+
+// ClickHouse row for a trader's traded notional (price * size) in one market
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct TraderNotionalRow {
+    pub user_address: String,
+    pub market_id: String,
+    pub notional: u128,
+}
+
+// ClickHouse row for a market's traded notional (price * size, unscaled) and size
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct NotionalVolumeRow {
+    pub notional: u128,
+    pub volume: u128,
+}
+
+// ClickHouse row for a market's mean trade price within one second
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct PricePointRow {
+    pub timestamp: u32, // Unix timestamp
+    pub price: u128,
+}
+
+// ClickHouse row for the last trade price before a point in time
+// With no earlier trades, trade_count is 0 and price is meaningless
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct LastPriceRow {
+    pub trade_count: u64,
+    pub price: u128,
+}
+
+// ClickHouse row for a market's trade aggregates over a time window
+// Aggregates over no trades come back as zeros, so check trade_count first
+#[derive(Debug, Clone, Default, Row, Serialize, Deserialize)]
+pub struct MarketStatsRow {
+    pub trade_count: u64,
+    pub base_volume: u128,
+    pub quote_volume: u128,
+    pub open: u128,
+    pub high: u128,
+    pub low: u128,
+    pub close: u128,
+}
+
+// ClickHouse row for one depth sample
+// Prices and spread are 0 while a side of the book is empty
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct DepthMetricsRow {
+    pub market_id: String,
+    pub timestamp: u32, // Unix timestamp
+    pub best_bid: u128,
+    pub best_ask: u128,
+    pub spread: u128,
+    pub bid_depth: u128,
+    pub ask_depth: u128,
+    pub imbalance_bps: i32,
+}
+
+// ClickHouse row for a trader's volume in a market split by liquidity role
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct MakerVolumeRow {
+    pub user_address: String,
+    pub maker_volume: u128,
+    pub taker_volume: u128,
+}
+
+// ClickHouse row for one trader's volume in a market split by liquidity role
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct MarketVolumeRow {
+    pub market_id: String,
+    pub maker_volume: u128,
+    pub taker_volume: u128,
+}
+
+// ClickHouse row for one trader's fills in a market, notional unscaled (price * size)
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct StatementFillRow {
+    pub user_address: String,
+    pub market_id: String,
+    pub fills: u64,
+    pub bought: u128,
+    pub sold: u128,
+    pub notional: u128,
+}
+
+// ClickHouse row for the trades between one buyer and one seller in one market
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct TradePairRow {
+    pub market_id: String,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub trades: u64,
+    pub volume: u128,
+}
+
+// ClickHouse row for a market's taker volume per side within one time bucket
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct TakerFlowRow {
+    pub timestamp: u32, // Unix timestamp, start of the bucket
+    pub buy_volume: u128,
+    pub sell_volume: u128,
+}
+
+// ClickHouse row for a polled index price
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct IndexPriceRow {
+    pub market_id: String,
+    pub timestamp: u32, // Unix timestamp
+    pub price: u128,
+    pub source: String,
+}
+
+// ClickHouse row for an open interest snapshot
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct OpenInterestRow {
+    pub market_id: String,
+    pub timestamp: u32, // Unix timestamp
+    pub open_interest: u128,
+    pub mark_price: u128,
+}
+
+// ClickHouse row for one funding interval
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct FundingRateRow {
+    pub market_id: String,
+    pub timestamp: u32, // Unix timestamp
+    pub funding_rate_ppm: i64,
+    pub mark_price: u128,
+    pub index_price: u128,
+}
+
 // ============================================================================
 // ROW TO DOMAIN TYPE CONVERSIONS
 // ============================================================================
 
+impl From<FillRow> for Fill {
+    fn from(row: FillRow) -> Self {
+        Self {
+            user_address: row.user_address,
+            market_id: row.market_id,
+            side: if row.side == "buy" {
+                Side::Buy
+            } else {
+                Side::Sell
+            },
+            price: row.price,
+            size: row.size,
+            timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
+                .unwrap_or(DateTime::UNIX_EPOCH),
+        }
+    }
+}
+
+impl From<&DepthMetrics> for DepthMetricsRow {
+    fn from(metrics: &DepthMetrics) -> Self {
+        Self {
+            market_id: metrics.market_id.clone(),
+            timestamp: metrics.timestamp.timestamp() as u32,
+            best_bid: metrics.best_bid.unwrap_or(0),
+            best_ask: metrics.best_ask.unwrap_or(0),
+            spread: metrics.spread().unwrap_or(0),
+            bid_depth: metrics.bid_depth,
+            ask_depth: metrics.ask_depth,
+            imbalance_bps: metrics.imbalance_bps,
+        }
+    }
+}
+
+impl From<DepthMetricsRow> for DepthMetrics {
+    fn from(row: DepthMetricsRow) -> Self {
+        Self {
+            market_id: row.market_id,
+            timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
+                .unwrap_or(DateTime::UNIX_EPOCH),
+            best_bid: (row.best_bid > 0).then_some(row.best_bid),
+            best_ask: (row.best_ask > 0).then_some(row.best_ask),
+            bid_depth: row.bid_depth,
+            ask_depth: row.ask_depth,
+            imbalance_bps: row.imbalance_bps,
+        }
+    }
+}
+
+impl From<MakerVolumeRow> for MakerVolume {
+    fn from(row: MakerVolumeRow) -> Self {
+        Self {
+            user_address: row.user_address,
+            maker_volume: row.maker_volume,
+            taker_volume: row.taker_volume,
+        }
+    }
+}
+
+impl From<TradePairRow> for TradePair {
+    fn from(row: TradePairRow) -> Self {
+        Self {
+            market_id: row.market_id,
+            buyer_address: row.buyer_address,
+            seller_address: row.seller_address,
+            trades: row.trades,
+            volume: row.volume,
+        }
+    }
+}
+
+impl From<TakerFlowRow> for TakerFlow {
+    fn from(row: TakerFlowRow) -> Self {
+        Self {
+            timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
+                .unwrap_or(DateTime::UNIX_EPOCH),
+            buy_volume: row.buy_volume,
+            sell_volume: row.sell_volume,
+        }
+    }
+}
+
+impl From<&IndexPrice> for IndexPriceRow {
+    fn from(price: &IndexPrice) -> Self {
+        Self {
+            market_id: price.market_id.clone(),
+            timestamp: price.timestamp.timestamp() as u32,
+            price: price.price,
+            source: price.source.clone(),
+        }
+    }
+}
+
+impl From<IndexPriceRow> for IndexPrice {
+    fn from(row: IndexPriceRow) -> Self {
+        Self {
+            market_id: row.market_id,
+            timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
+                .unwrap_or(DateTime::UNIX_EPOCH),
+            price: row.price,
+            source: row.source,
+        }
+    }
+}
+
+impl From<&OpenInterestSnapshot> for OpenInterestRow {
+    fn from(snapshot: &OpenInterestSnapshot) -> Self {
+        Self {
+            market_id: snapshot.market_id.clone(),
+            timestamp: snapshot.timestamp.timestamp() as u32,
+            open_interest: snapshot.open_interest,
+            mark_price: snapshot.mark_price,
+        }
+    }
+}
+
+impl From<OpenInterestRow> for OpenInterestSnapshot {
+    fn from(row: OpenInterestRow) -> Self {
+        Self {
+            market_id: row.market_id,
+            timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
+                .unwrap_or(DateTime::UNIX_EPOCH),
+            open_interest: row.open_interest,
+            mark_price: row.mark_price,
+        }
+    }
+}
+
+impl From<&FundingRate> for FundingRateRow {
+    fn from(rate: &FundingRate) -> Self {
+        Self {
+            market_id: rate.market_id.clone(),
+            timestamp: rate.timestamp.timestamp() as u32,
+            funding_rate_ppm: rate.funding_rate_ppm,
+            mark_price: rate.mark_price,
+            index_price: rate.index_price,
+        }
+    }
+}
+
+impl From<FundingRateRow> for FundingRate {
+    fn from(row: FundingRateRow) -> Self {
+        Self {
+            market_id: row.market_id,
+            timestamp: DateTime::from_timestamp(row.timestamp as i64, 0)
+                .unwrap_or(DateTime::UNIX_EPOCH),
+            funding_rate_ppm: row.funding_rate_ppm,
+            mark_price: row.mark_price,
+            index_price: row.index_price,
+        }
+    }
+}
+
 impl From<UserRow> for User {
     fn from(row: UserRow) -> Self {
         Self {
@@ -127,74 +445,120 @@ impl From<TokenRow> for Token {
     }
 }
 
-impl From<MarketRow> for Market {
-    fn from(row: MarketRow) -> Self {
-        Self {
+impl TryFrom<MarketRow> for Market {
+    type Error = sqlx::Error;
+
+    fn try_from(row: MarketRow) -> Result<Self, Self::Error> {
+        Ok(Self {
             id: row.id,
             base_ticker: row.base_ticker,
             quote_ticker: row.quote_ticker,
-            tick_size: row.tick_size.to_u128(),
-            lot_size: row.lot_size.to_u128(),
-            min_size: row.min_size.to_u128(),
+            tick_size: decode_atoms("tick_size", &row.tick_size)?,
+            lot_size: decode_atoms("lot_size", &row.lot_size)?,
+            min_size: decode_atoms("min_size", &row.min_size)?,
             maker_fee_bps: row.maker_fee_bps,
             taker_fee_bps: row.taker_fee_bps,
-        }
+        })
     }
 }
 
-impl From<OrderRow> for Order {
-    fn from(row: OrderRow) -> Self {
-        Self {
+impl TryFrom<OrderRow> for Order {
+    type Error = sqlx::Error;
+
+    fn try_from(row: OrderRow) -> Result<Self, Self::Error> {
+        Ok(Self {
             id: row.id,
             user_address: row.user_address,
             market_id: row.market_id,
-            price: row.price.to_u128(),
-            size: row.size.to_u128(),
-            side: row.side.parse().unwrap_or(crate::models::domain::Side::Buy),
-            order_type: row
-                .order_type
-                .parse()
-                .unwrap_or(crate::models::domain::OrderType::Limit),
-            status: row
-                .status
-                .parse()
-                .unwrap_or(crate::models::domain::OrderStatus::Pending),
-            filled_size: row.filled_size.to_u128(),
+            price: decode_atoms("price", &row.price)?,
+            size: decode_atoms("size", &row.size)?,
+            side: decode_column("side", &row.side)?,
+            order_type: decode_column("type", &row.order_type)?,
+            status: decode_column("status", &row.status)?,
+            filled_size: decode_atoms("filled_size", &row.filled_size)?,
             created_at: row.created_at,
             updated_at: row.updated_at,
-        }
+            cancel_reason: row
+                .cancel_reason
+                .as_deref()
+                .map(|reason| decode_column("cancel_reason", reason))
+                .transpose()?,
+            trigger_price: row
+                .trigger_price
+                .as_ref()
+                .map(|price| decode_atoms("trigger_price", price))
+                .transpose()?,
+            trigger_source: decode_column("trigger_source", &row.trigger_source)?,
+            time_in_force: decode_column("time_in_force", &row.time_in_force)?,
+            expires_at: row.expires_at,
+        })
     }
 }
 
-impl From<TradeRow> for Trade {
-    fn from(row: TradeRow) -> Self {
-        Self {
+impl TryFrom<TradeRow> for Trade {
+    type Error = sqlx::Error;
+
+    fn try_from(row: TradeRow) -> Result<Self, Self::Error> {
+        Ok(Self {
             id: row.id,
             market_id: row.market_id,
             buyer_address: row.buyer_address,
             seller_address: row.seller_address,
             buyer_order_id: row.buyer_order_id,
             seller_order_id: row.seller_order_id,
-            price: row.price.to_u128(),
-            size: row.size.to_u128(),
-            side: if row.side == "buy" {
-                Side::Buy
-            } else {
-                Side::Sell
-            },
+            price: decode_atoms("price", &row.price)?,
+            size: decode_atoms("size", &row.size)?,
+            side: decode_column("side", &row.side)?,
             timestamp: row.timestamp,
-        }
+            rfq: row.rfq,
+        })
     }
 }
 
-impl From<BalanceRow> for Balance {
-    fn from(row: BalanceRow) -> Self {
-        Self {
+impl TryFrom<BalanceRow> for Balance {
+    type Error = sqlx::Error;
+
+    fn try_from(row: BalanceRow) -> Result<Self, Self::Error> {
+        Ok(Self {
             user_address: row.user_address,
             token_ticker: row.token_ticker,
-            amount: row.amount.to_u128(),
-            open_interest: row.open_interest.to_u128(),
+            amount: decode_atoms("amount", &row.amount)?,
+            open_interest: decode_atoms("open_interest", &row.open_interest)?,
             updated_at: row.updated_at,
-        }
+        })
     }
 }
+
+impl TryFrom<SurveillanceAlertRow> for SurveillanceAlert {
+    type Error = sqlx::Error;
+
+    fn try_from(row: SurveillanceAlertRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            kind: decode_column("kind", &row.kind)?,
+            market_id: row.market_id,
+            subject: row.subject,
+            accounts: row.accounts,
+            count: row.count as u64,
+            volume: decode_atoms("volume", &row.volume)?,
+            detail: row.detail,
+            window_start: row.window_start,
+            window_end: row.window_end,
+            created_at: row.created_at,
+        })
+    }
+}
+
+/// A TEXT column holding one of a domain enum's names
+fn decode_column<T>(column: &str, value: &str) -> Result<T, sqlx::Error>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e: T::Err| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: e.to_string().into(),
+        })
+}
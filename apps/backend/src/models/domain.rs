@@ -1,232 +1,200 @@
 use chrono::{DateTime, Utc};
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-use std::fmt::{self, Display};
-use std::str::FromStr;
+use std::sync::Arc;
 use tokio::sync::oneshot;
-use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::errors::ExchangeError;
-use crate::models::api::{OrderCancelled, OrderPlaced, OrdersCancelled};
+use crate::engine::ladder::LadderLayout;
+use crate::engine::markets::MarketId;
+use crate::errors::{ErrorCode, ExchangeError};
+use crate::models::api::{OcoPlaced, OrderCancelled, OrderPlaced, OrdersCancelled};
+use crate::perps::FundingSettlement;
+use crate::rfq::RfqExecution;
+
+// Enums and value types shared with clients over the wire
+pub use exchange_protocol::domain::*;
+
 // ============================================================================
-// ENUMS
+// MATCHING ENGINE TYPES
 // ============================================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
-#[serde(rename_all = "lowercase")]
-pub enum Side {
-    Buy,
-    Sell,
+/// Represents a match between two orders
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub maker_order: Arc<Order>, // Shared with the resting order in the book, not a copy
+    pub price: u128,
+    pub size: u128,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
-#[serde(rename_all = "lowercase")]
-pub enum OrderType {
-    Limit,
-    Market,
+/// Fee settled with one side of a trade; negative is a maker rebate
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeFee {
+    pub trade_id: Uuid,
+    pub user_address: String,
+    pub token_ticker: String,
+    pub fee: i128,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum OrderStatus {
-    Pending,
-    Filled,
-    PartiallyFilled,
-    Cancelled,
+/// Share of a taker fee passed on to the taker's referrer
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferralPayout {
+    pub trade_id: Uuid,
+    pub referrer_address: String,
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: u128,
 }
 
-// ============================================================================
-// ENUM STRING CONVERSIONS
-// ============================================================================
-
-impl Display for Side {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Side::Buy => "buy",
-                Side::Sell => "sell",
-            }
-        )
-    }
+/// One side of a trade from a trader's point of view, read back from ClickHouse
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub user_address: String,
+    pub market_id: String,
+    pub side: Side,
+    pub price: u128,
+    pub size: u128,
+    pub timestamp: DateTime<Utc>,
 }
 
-impl FromStr for Side {
-    type Err = String;
+/// Liquidity at the top of one market's book at a point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthMetrics {
+    pub market_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub best_bid: Option<u128>,
+    pub best_ask: Option<u128>,
+    pub bid_depth: u128, // Size resting on the best few bid levels
+    pub ask_depth: u128, // Size resting on the best few ask levels
+    pub imbalance_bps: i32,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "buy" => Ok(Side::Buy),
-            "sell" => Ok(Side::Sell),
-            _ => Err(format!("Invalid side: {}", s)),
+impl DepthMetrics {
+    /// Gap between the best ask and best bid, if both sides are quoted
+    pub fn spread(&self) -> Option<u128> {
+        match (self.best_bid, self.best_ask) {
+            (Some(bid), Some(ask)) => Some(ask.saturating_sub(bid)),
+            _ => None,
         }
     }
 }
 
-impl Display for OrderType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                OrderType::Limit => "limit",
-                OrderType::Market => "market",
-            }
-        )
-    }
+/// A trader's volume in one market split by liquidity role, in base token atoms
+#[derive(Debug, Clone, PartialEq)]
+pub struct MakerVolume {
+    pub user_address: String,
+    pub maker_volume: u128,
+    pub taker_volume: u128,
 }
 
-impl FromStr for OrderType {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "limit" => Ok(OrderType::Limit),
-            "market" => Ok(OrderType::Market),
-            _ => Err(format!("Invalid order type: {}", s)),
-        }
+impl MakerVolume {
+    /// Share of the trader's volume that rested in the book, 0 to 10000
+    pub fn maker_ratio_bps(&self) -> u32 {
+        // imbalance = (maker - taker) / total, so maker / total = (1 + imbalance) / 2
+        ((10_000 + crate::utils::imbalance_bps(self.maker_volume, self.taker_volume)) / 2) as u32
     }
 }
 
-impl Display for OrderStatus {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                OrderStatus::Pending => "pending",
-                OrderStatus::Filled => "filled",
-                OrderStatus::PartiallyFilled => "partially_filled",
-                OrderStatus::Cancelled => "cancelled",
-            }
-        )
-    }
+/// Trades one account bought from another in one market, size in base token atoms
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradePair {
+    pub market_id: String,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub trades: u64,
+    pub volume: u128,
 }
 
-impl FromStr for OrderStatus {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "pending" => Ok(OrderStatus::Pending),
-            "filled" => Ok(OrderStatus::Filled),
-            "partially_filled" => Ok(OrderStatus::PartiallyFilled),
-            "cancelled" => Ok(OrderStatus::Cancelled),
-            _ => Err(format!("Invalid order status: {}", s)),
-        }
-    }
+/// Volume taken from each side of one market's book within a time bucket
+#[derive(Debug, Clone, PartialEq)]
+pub struct TakerFlow {
+    pub timestamp: DateTime<Utc>, // Start of the bucket
+    pub buy_volume: u128,         // Taken from the asks
+    pub sell_volume: u128,        // Taken from the bids
 }
 
-// ============================================================================
-// DOMAIN TYPES
-// ============================================================================
-
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct User {
-    pub address: String,
-    pub created_at: DateTime<Utc>,
+impl TakerFlow {
+    /// Net taker buying as a share of the bucket's volume, -10000 to 10000
+    pub fn imbalance_bps(&self) -> i32 {
+        crate::utils::imbalance_bps(self.buy_volume, self.sell_volume)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct Token {
-    pub ticker: String,
-    pub decimals: u8,
-    pub name: String,
+/// Open interest of a perpetual market at a point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenInterestSnapshot {
+    pub market_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub open_interest: u128, // Open long size, equal to open short size
+    pub mark_price: u128,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Market {
-    pub id: String, // Generated as "base_ticker/quote_ticker"
-    pub base_ticker: String,
-    pub quote_ticker: String,
-    pub tick_size: u128,    // Minimum price increment in quote atoms
-    pub lot_size: u128,     // Minimum size increment in base atoms
-    pub min_size: u128,     // Minimum order size in base atoms
-    pub maker_fee_bps: i32, // Maker fee in basis points (0-10000)
-    pub taker_fee_bps: i32, // Taker fee in basis points (0-10000)
+/// Funding settled for one interval of a perpetual market
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingRate {
+    pub market_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub funding_rate_ppm: i64, // Millionths of notional; positive means longs pay shorts
+    pub mark_price: u128,
+    pub index_price: u128,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Order {
-    pub id: Uuid,
-    pub user_address: String,
-    pub market_id: String, // Generated as "base_ticker/quote_ticker"
-    pub price: u128,
-    pub size: u128,
-    pub side: Side,
-    pub order_type: OrderType,
-    pub status: OrderStatus,
-    pub filled_size: u128,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+/// Funding terms and latest prices of a perpetual market
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerpetualMarket {
+    pub market_id: String,
+    pub funding_interval_secs: u32,
+    pub max_funding_rate_ppm: u32, // Cap on the rate of a single interval
+    pub initial_margin_bps: u32,   // Locked by orders, of their notional
+    pub maintenance_margin_bps: u32, // Below this share of notional a position is liquidated
+    pub liquidation_fee_bps: u32,
+    pub last_funding_at: DateTime<Utc>,
+    pub mark_price: Option<u128>,
+    pub index_price: Option<u128>,
+    pub mark_updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Trade {
-    pub id: Uuid,
-    pub market_id: String,
-    pub buyer_address: String,
-    pub seller_address: String,
-    pub buyer_order_id: Uuid,
-    pub seller_order_id: Uuid,
-    pub price: u128,
-    pub size: u128,
-    pub side: Side, // Taker's side (determines if trade is "buy" or "sell" on tape)
-    pub timestamp: DateTime<Utc>,
+impl PerpetualMarket {
+    /// When the next funding interval is due
+    pub fn next_funding_at(&self) -> DateTime<Utc> {
+        self.last_funding_at + chrono::Duration::seconds(self.funding_interval_secs as i64)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Balance {
+/// A user's position in a perpetual market
+///
+/// `cost` is the unscaled sum of `price * signed size` over the fills since the
+/// last settlement, so `size * mark - cost` is the PnL still to be settled,
+/// scaled by the base token's decimals.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Position {
     pub user_address: String,
-    pub token_ticker: String,
-    pub amount: u128,
-    pub open_interest: u128,
+    pub market_id: String,
+    pub size: i128, // Negative for shorts
+    pub entry_price: u128,
+    pub margin: u128,
+    pub cost: i128,
+    pub realized_pnl: i128,
+    pub funding_paid: i128, // Negative when received
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Candle {
+/// A market's price on an external index, as polled by the price feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexPrice {
     pub market_id: String,
     pub timestamp: DateTime<Utc>,
-    pub open: u128,
-    pub high: u128,
-    pub low: u128,
-    pub close: u128,
-    pub volume: u128,
-}
-
-// ============================================================================
-// MATCHING ENGINE TYPES
-// ============================================================================
-
-/// Represents a match between two orders
-#[derive(Debug, Clone)]
-pub struct Match {
-    pub maker_order: Order, // Full maker order for easy access
     pub price: u128,
-    pub size: u128,
+    pub source: String, // Feed the price came from, e.g. "hyperliquid"
 }
 
-// ============================================================================
-// ORDERBOOK TYPES
-// ============================================================================
-
-/// Represents a price level in the orderbook
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderbookLevel {
-    pub price: u128,
-    pub size: u128,
-}
-
-/// Snapshot of an orderbook at a point in time
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderbookSnapshot {
-    pub market_id: String,
-    pub bids: Vec<OrderbookLevel>, // Sorted by price descending (highest first)
-    pub asks: Vec<OrderbookLevel>, // Sorted by price ascending (lowest first)
-    pub timestamp: DateTime<Utc>,
+/// A change to a system account balance, waiting to be written to the ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerPosting {
+    pub account: SystemAccount,
+    pub token_ticker: String,
+    /// Positive for credits, negative for debits
+    pub amount: i128,
+    pub kind: LedgerEntryKind,
+    pub trade_id: Option<Uuid>,
 }
 
 // ============================================================================
@@ -235,21 +203,141 @@ pub struct OrderbookSnapshot {
 
 /// Requests sent from REST API to matching engine
 /// Each request includes a oneshot channel for synchronous response
+///
+/// Order requests carry the sender's tracing span (usually `Span::current()`)
+/// so the engine's work shows up in the same trace as the HTTP request.
 pub enum EngineRequest {
     PlaceOrder {
         order: Order,
+        span: tracing::Span,
         response_tx: oneshot::Sender<Result<OrderPlaced, ExchangeError>>,
     },
+    /// Place two orders of the same user, market, side and size as a
+    /// one-cancels-other pair
+    PlaceOco {
+        first: Order,
+        second: Order,
+        span: tracing::Span,
+        response_tx: oneshot::Sender<Result<OcoPlaced, ExchangeError>>,
+    },
     CancelOrder {
         order_id: Uuid,
         user_address: String,
+        span: tracing::Span,
         response_tx: oneshot::Sender<Result<OrderCancelled, ExchangeError>>,
     },
     CancelAllOrders {
         user_address: String,
         market_id: Option<String>,
+        span: tracing::Span,
+        response_tx: oneshot::Sender<Result<OrdersCancelled, ExchangeError>>,
+    },
+    SetUserLimits {
+        user_address: String,
+        market_id: String,
+        max_position: Option<u128>,
+        max_open_notional: Option<u128>,
+        response_tx: oneshot::Sender<Result<UserLimits, ExchangeError>>,
+    },
+    /// Put a market, or the whole exchange when `market_id` is `None`, into cancel-only mode
+    EngageKillSwitch {
+        market_id: Option<String>,
+        reason: Option<String>,
+        cancel_orders: bool,
+        response_tx: oneshot::Sender<Result<(KillSwitch, OrdersCancelled), ExchangeError>>,
+    },
+    ReleaseKillSwitch {
+        market_id: Option<String>,
+        response_tx: oneshot::Sender<Result<(), ExchangeError>>,
+    },
+    /// Change a market's status; halting or delisting cancels its resting orders
+    SetMarketStatus {
+        market_id: String,
+        status: MarketStatus,
         response_tx: oneshot::Sender<Result<OrdersCancelled, ExchangeError>>,
     },
+    /// Set who referred a user and their share of the user's taker fees
+    SetReferral {
+        user_address: String,
+        referrer_address: String,
+        share_bps: u32,
+        response_tx: oneshot::Sender<Result<Referral, ExchangeError>>,
+    },
+    /// Set the fees a user pays in a market; `None` on both sides clears them
+    SetFeeOverride {
+        user_address: String,
+        market_id: String,
+        maker_fee_bps: Option<i32>,
+        taker_fee_bps: Option<i32>,
+        response_tx: oneshot::Sender<Result<Option<FeeOverride>, ExchangeError>>,
+    },
+    /// Override a market's price collar; `None` disables it
+    SetPriceCollar {
+        market_id: String,
+        collar_bps: Option<u32>,
+        response_tx: oneshot::Sender<Result<(), ExchangeError>>,
+    },
+    /// Open a market created while running: build its book with `layout`
+    /// and apply its configured price collar
+    OpenMarket {
+        market_id: String,
+        layout: LadderLayout,
+        collar_bps: Option<u32>,
+        response_tx: oneshot::Sender<Result<(), ExchangeError>>,
+    },
+    /// Change how much of a revenue source goes to the insurance fund
+    SetFeeRoute {
+        source: RevenueSource,
+        insurance_bps: u32,
+        response_tx: oneshot::Sender<Result<FeeRoute, ExchangeError>>,
+    },
+    /// Change a user's account status, optionally cancelling their resting orders
+    SetUserStatus {
+        user_address: String,
+        status: UserStatus,
+        cancel_orders: bool,
+        response_tx: oneshot::Sender<Result<OrdersCancelled, ExchangeError>>,
+    },
+    /// Mark a perpetual market's positions to `mark_price` and charge them funding
+    SettleFunding {
+        market_id: String,
+        mark_price: u128,
+        index_price: u128,
+        funding_rate_ppm: i64,
+        response_tx: oneshot::Sender<Result<FundingSettlement, ExchangeError>>,
+    },
+    /// Take a perpetual market's new mark price and liquidate the positions it
+    /// leaves below their maintenance margin
+    UpdateMarkPrice {
+        market_id: String,
+        mark_price: u128,
+        index_price: u128,
+        response_tx: oneshot::Sender<Result<Vec<Liquidation>, ExchangeError>>,
+    },
+    /// Switch a user between isolated and cross margin; only while they hold no positions
+    SetMarginMode {
+        user_address: String,
+        mode: MarginMode,
+        response_tx: oneshot::Sender<Result<(), ExchangeError>>,
+    },
+    /// Where one of a user's resting orders stands in its price level's queue
+    QueuePosition {
+        order_id: Uuid,
+        user_address: String,
+        response_tx: oneshot::Sender<Result<QueuePosition, ExchangeError>>,
+    },
+    /// A market's resting orders as the engine's book holds them
+    RestingOrders {
+        market_id: String,
+        response_tx: oneshot::Sender<Result<Vec<Order>, ExchangeError>>,
+    },
+    /// Fill a taker's request for quote at one of its quotes, off the book
+    ExecuteRfq {
+        request_id: Uuid,
+        quote_id: Uuid,
+        user_address: String,
+        response_tx: oneshot::Sender<Result<RfqExecution, ExchangeError>>,
+    },
 }
 
 /// Events broadcast from matching engine to WebSocket clients
@@ -257,6 +345,7 @@ pub enum EngineRequest {
 #[derive(Debug, Clone)]
 pub enum EngineEvent {
     TradeExecuted {
+        market: MarketId,
         trade: Trade,
     },
     OrderPlaced {
@@ -265,11 +354,34 @@ pub enum EngineEvent {
     OrderCancelled {
         order_id: Uuid,
         user_address: String,
+        /// `None` when the user cancelled the order themselves
+        reason: Option<CancelReason>,
+    },
+    /// The engine refused an order before it reached the book
+    OrderRejected {
+        order_id: Uuid,
+        user_address: String,
+        market_id: String,
+        /// Error code, reason and client-safe message, as REST reports them
+        code: ErrorCode,
+        reason: Option<RejectReason>,
+        message: String,
+    },
+    MarketStatusChanged {
+        market_id: String,
+        status: MarketStatus,
     },
     BalanceUpdated {
         balance: Balance,
     },
+    WithdrawalUpdated {
+        withdrawal: Withdrawal,
+    },
+    PositionLiquidated {
+        liquidation: Liquidation,
+    },
     OrderbookSnapshot {
+        market: MarketId,
         orderbook: OrderbookSnapshot,
     },
 }
@@ -283,10 +395,12 @@ pub enum EngineEvent {
 pub enum Subscription {
     Trades { market_id: String },
     Orderbook { market_id: String },
-    Candles { market_id: String },
+    Candles { market_id: String, interval: String },
     UserFills { user_address: String },
     UserOrders { user_address: String },
     UserBalances { user_address: String },
+    Notifications { user_address: String },
+    Tickers,
 }
 
 impl Subscription {
@@ -299,11 +413,13 @@ impl Subscription {
                 channel,
                 market_id,
                 user_address,
+                interval,
             }
             | ClientMessage::Unsubscribe {
                 channel,
                 market_id,
                 user_address,
+                interval,
             } => match channel {
                 SubscriptionChannel::Trades => market_id.as_ref().map(|id| Subscription::Trades {
                     market_id: id.clone(),
@@ -313,6 +429,15 @@ impl Subscription {
                         market_id: id.clone(),
                     })
                 }
+                SubscriptionChannel::Candles => {
+                    let interval = interval
+                        .as_ref()
+                        .filter(|interval| crate::db::candles::interval_secs(interval).is_some())?;
+                    market_id.as_ref().map(|id| Subscription::Candles {
+                        market_id: id.clone(),
+                        interval: interval.clone(),
+                    })
+                }
                 SubscriptionChannel::UserFills => {
                     user_address.as_ref().map(|addr| Subscription::UserFills {
                         user_address: addr.clone(),
@@ -330,6 +455,14 @@ impl Subscription {
                             user_address: addr.clone(),
                         })
                 }
+                SubscriptionChannel::Notifications => {
+                    user_address
+                        .as_ref()
+                        .map(|addr| Subscription::Notifications {
+                            user_address: addr.clone(),
+                        })
+                }
+                SubscriptionChannel::Tickers => Some(Subscription::Tickers),
             },
             ClientMessage::Ping => None,
         }
@@ -0,0 +1,190 @@
+// marks perpetual markets and asks the engine to settle them each funding interval
+
+use super::{funding_rate_ppm, mark_price, FundingSettlement};
+use crate::db::Db;
+use crate::engine::orderbook::Orderbooks;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{EngineRequest, FundingRate, OpenInterestSnapshot, PerpetualMarket};
+use crate::price_feed::IndexPrices;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, RwLock};
+
+/// Seconds between mark price updates
+pub const MARK_INTERVAL_SECS: u64 = 5;
+
+/// Keeps the mark price of every perpetual market current and settles funding when due
+///
+/// Marks are the median of the index price and the top of the book, and each
+/// one is sent to the engine to liquidate positions it leaves under their
+/// maintenance margin. Settlement goes through the engine, so it never
+/// interleaves with fills touching the same positions, and the engine raises
+/// an alert if it fails. A market whose index price is stale is neither marked
+/// nor settled; its funding waits until the feed recovers.
+pub struct FundingSettler {
+    db: Db,
+    engine_tx: mpsc::Sender<EngineRequest>,
+    orderbooks: Arc<RwLock<Orderbooks>>,
+    index_prices: IndexPrices,
+}
+
+impl FundingSettler {
+    /// Mark from the engine's `orderbooks` and `index_prices`, settling through `engine_tx`
+    pub fn new(
+        db: Db,
+        engine_tx: mpsc::Sender<EngineRequest>,
+        orderbooks: Arc<RwLock<Orderbooks>>,
+        index_prices: IndexPrices,
+    ) -> Self {
+        Self {
+            db,
+            engine_tx,
+            orderbooks,
+            index_prices,
+        }
+    }
+
+    /// Mark every `MARK_INTERVAL_SECS` until the engine shuts down
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(MARK_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        while !self.engine_tx.is_closed() {
+            interval.tick().await;
+            let markets = match self.db.list_perpetual_markets().await {
+                Ok(markets) => markets,
+                Err(e) => {
+                    log::error!("Failed to load perpetual markets: {}", e);
+                    continue;
+                }
+            };
+            for market in &markets {
+                if let Err(e) = self.tick(market, Utc::now()).await {
+                    log::error!("Failed to mark or settle {}: {}", market.market_id, e);
+                }
+            }
+        }
+    }
+
+    /// Mark one market at `now`, settling its funding if the interval is up
+    pub async fn tick(
+        &self,
+        market: &PerpetualMarket,
+        now: DateTime<Utc>,
+    ) -> Result<Option<FundingSettlement>> {
+        let Some(index_price) = self.index_prices.fresh(&market.market_id, now) else {
+            return Ok(None);
+        };
+        let (best_bid, best_ask) = self.top_of_book(&market.market_id).await;
+        let mark = mark_price(index_price, best_bid, best_ask);
+        self.db
+            .set_mark_price(&market.market_id, mark, index_price, now)
+            .await?;
+
+        // The engine checks every position in the market against the new mark
+        let (response_tx, response_rx) = oneshot::channel();
+        self.engine_tx
+            .send(EngineRequest::UpdateMarkPrice {
+                market_id: market.market_id.clone(),
+                mark_price: mark,
+                index_price,
+                response_tx,
+            })
+            .await
+            .map_err(|_| ExchangeError::EngineSendFailed)?;
+        let liquidations = response_rx
+            .await
+            .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+        if !liquidations.is_empty() {
+            log::warn!(
+                "Liquidated {} positions in {} at mark {}",
+                liquidations.len(),
+                market.market_id,
+                mark
+            );
+        }
+
+        if now < market.next_funding_at() {
+            return Ok(None);
+        }
+
+        let rate = funding_rate_ppm(mark, index_price, market.max_funding_rate_ppm);
+        let (response_tx, response_rx) = oneshot::channel();
+        self.engine_tx
+            .send(EngineRequest::SettleFunding {
+                market_id: market.market_id.clone(),
+                mark_price: mark,
+                index_price,
+                funding_rate_ppm: rate,
+                response_tx,
+            })
+            .await
+            .map_err(|_| ExchangeError::EngineSendFailed)?;
+        let settlement = response_rx
+            .await
+            .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+
+        log::info!(
+            "Settled funding for {}: {} ppm at mark {}, {} positions, open interest {}",
+            settlement.market_id,
+            settlement.funding_rate_ppm,
+            settlement.mark_price,
+            settlement.positions,
+            settlement.open_interest
+        );
+        if settlement.shortfall > 0 || settlement.haircut > 0 {
+            log::warn!(
+                "Funding for {} left a shortfall of {} ({} cut from gains)",
+                settlement.market_id,
+                settlement.shortfall,
+                settlement.haircut
+            );
+        }
+
+        // History for the funding and open interest endpoints
+        let rate = FundingRate {
+            market_id: settlement.market_id.clone(),
+            timestamp: now,
+            funding_rate_ppm: settlement.funding_rate_ppm,
+            mark_price: settlement.mark_price,
+            index_price: settlement.index_price,
+        };
+        let open_interest = OpenInterestSnapshot {
+            market_id: settlement.market_id.clone(),
+            timestamp: now,
+            open_interest: settlement.open_interest,
+            mark_price: settlement.mark_price,
+        };
+        if let Err(e) = self.db.insert_funding_rates(&[rate]).await {
+            log::error!(
+                "Failed to store funding rate for {}: {}",
+                market.market_id,
+                e
+            );
+        }
+        if let Err(e) = self.db.insert_open_interest(&[open_interest]).await {
+            log::error!(
+                "Failed to store open interest for {}: {}",
+                market.market_id,
+                e
+            );
+        }
+
+        Ok(Some(settlement))
+    }
+
+    async fn top_of_book(&self, market_id: &str) -> (Option<u128>, Option<u128>) {
+        let orderbooks = self.orderbooks.read().await;
+        let Some(id) = orderbooks.markets().get(market_id) else {
+            return (None, None);
+        };
+        match orderbooks.snapshot_top_n(id, 1) {
+            Some(snapshot) => (
+                snapshot.bids.first().map(|level| level.price),
+                snapshot.asks.first().map(|level| level.price),
+            ),
+            None => (None, None),
+        }
+    }
+}
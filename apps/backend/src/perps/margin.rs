@@ -0,0 +1,111 @@
+// margin requirements and account health of perpetual positions
+
+use super::unrealized_pnl;
+use crate::models::domain::Position;
+
+/// Margin `size` at `price` takes at `margin_bps` of its notional, in quote atoms
+pub fn initial_margin(price: u128, size: u128, scale: u128, margin_bps: u32) -> Option<u128> {
+    let notional = price.checked_mul(size)?.checked_div(scale)?;
+    Some(notional.checked_mul(margin_bps as u128)? / 10_000)
+}
+
+/// Least equity a position must keep at `mark_price`, rounded up
+pub fn maintenance_margin(
+    position: &Position,
+    mark_price: u128,
+    scale: u128,
+    margin_bps: u32,
+) -> Option<u128> {
+    let notional = position
+        .size
+        .unsigned_abs()
+        .checked_mul(mark_price)?
+        .checked_div(scale)?;
+    Some(notional.checked_mul(margin_bps as u128)?.div_ceil(10_000))
+}
+
+/// Equity backing one or more positions and the maintenance margin it has to cover
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Health {
+    /// Margin plus unrealized PnL, and for cross accounts the free balance
+    pub equity: i128,
+    pub maintenance_margin: u128,
+}
+
+impl Health {
+    /// Whether the positions must be liquidated
+    pub fn is_liquidatable(&self) -> bool {
+        self.maintenance_margin > 0 && self.equity < self.maintenance_margin as i128
+    }
+
+    /// Equity as a share of the maintenance margin, in bps; `None` without one
+    pub fn margin_ratio_bps(&self) -> Option<i64> {
+        if self.maintenance_margin == 0 {
+            return None;
+        }
+        let ratio = self.equity.saturating_mul(10_000) / self.maintenance_margin as i128;
+        Some(ratio.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+}
+
+/// Health of one position on its own margin
+pub fn position_health(
+    position: &Position,
+    mark_price: u128,
+    scale: u128,
+    maintenance_margin_bps: u32,
+) -> Option<Health> {
+    let pnl = unrealized_pnl(position, mark_price, scale)?;
+    Some(Health {
+        equity: i128::try_from(position.margin).ok()?.checked_add(pnl)?,
+        maintenance_margin: maintenance_margin(
+            position,
+            mark_price,
+            scale,
+            maintenance_margin_bps,
+        )?,
+    })
+}
+
+/// Health of a cross-margined account in one quote token
+///
+/// The user's free balance of the token backs all of their positions
+/// quoted in it, on top of each position's own margin and PnL.
+pub fn account_health(free: u128, positions: impl IntoIterator<Item = Health>) -> Option<Health> {
+    let mut health = Health {
+        equity: i128::try_from(free).ok()?,
+        maintenance_margin: 0,
+    };
+    for position in positions {
+        health.equity = health.equity.checked_add(position.equity)?;
+        health.maintenance_margin = health
+            .maintenance_margin
+            .checked_add(position.maintenance_margin)?;
+    }
+    Some(health)
+}
+
+/// Worst price the order liquidating `position` may fill at
+///
+/// The mark moved against the position by the maintenance margin, rounded
+/// outwards to the tick: past that the position's margin is gone anyway, and
+/// what the book cannot fill within it is left to the next check.
+pub fn liquidation_limit_price(
+    position: &Position,
+    mark_price: u128,
+    maintenance_margin_bps: u32,
+    tick_size: u128,
+) -> Option<u128> {
+    let band = mark_price.checked_mul(maintenance_margin_bps as u128)? / 10_000;
+    if position.size > 0 {
+        // Selling a long: round down, but never to zero
+        let price = mark_price.saturating_sub(band);
+        Some((price - price % tick_size).max(tick_size))
+    } else {
+        // Buying back a short: round up
+        mark_price
+            .checked_add(band)?
+            .div_ceil(tick_size)
+            .checked_mul(tick_size)
+    }
+}
@@ -0,0 +1,334 @@
+// perpetual futures: position accounting, mark prices and funding settlement
+//
+// Perpetual markets reuse the spot orderbooks and matcher. What differs is
+// settlement: no base tokens change hands, both sides lock quote tokens as
+// margin and hold a signed position instead. Every funding interval each
+// position is marked to market and pays or receives funding, so the PnL of
+// longs and shorts always nets to zero and is settled in one transaction.
+
+pub mod funding;
+pub mod margin;
+
+use crate::db::balances::BalanceChanges;
+use crate::models::domain::{Market, Position, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub use funding::FundingSettler;
+
+/// Funding rates are quoted in millionths of notional
+pub const FUNDING_RATE_SCALE: i128 = 1_000_000;
+
+/// Default time between funding settlements
+pub const DEFAULT_FUNDING_INTERVAL_SECS: u32 = 3600;
+
+/// Default cap on a single interval's funding rate (0.75%)
+pub const DEFAULT_MAX_FUNDING_RATE_PPM: u32 = 7_500;
+
+/// Default share of notional orders lock as margin: unleveraged
+pub const DEFAULT_INITIAL_MARGIN_BPS: u32 = 10_000;
+
+/// Default share of notional a position must keep as equity (5%)
+pub const DEFAULT_MAINTENANCE_MARGIN_BPS: u32 = 500;
+
+/// Fee rate an order's collateral reserves for: the larger of the market's fees
+pub fn collateral_fee_bps(market: &Market) -> i32 {
+    market.maker_fee_bps.max(market.taker_fee_bps)
+}
+
+/// Quote atoms an order in a perpetual market locks for `size` at `price`
+///
+/// The initial margin of the notional at `margin_bps`, plus a reserve for
+/// fees at `fee_bps`. Both sides lock the same amount, whether they open or
+/// close a position.
+pub fn order_collateral(
+    price: u128,
+    size: u128,
+    scale: u128,
+    margin_bps: u32,
+    fee_bps: i32,
+) -> Option<u128> {
+    let notional = price.checked_mul(size)?.checked_div(scale)?;
+    let fee_reserve = notional.checked_mul(fee_bps.max(0) as u128)? / 10_000;
+    margin::initial_margin(price, size, scale, margin_bps)?.checked_add(fee_reserve)
+}
+
+/// How a fill changed the margin held for a position
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FillEffect {
+    pub margin_added: u128,
+    pub margin_released: u128,
+}
+
+/// Apply one fill to a position
+///
+/// Fills that grow the position average into its entry price and add the
+/// initial margin at `margin_bps` of `order_price`, the price the order's
+/// collateral was locked at; fills
+/// that shrink it release margin in proportion. A fill that flips the position
+/// closes it and opens the remainder at the fill price.
+pub fn apply_fill(
+    position: &mut Position,
+    side: Side,
+    size: u128,
+    price: u128,
+    order_price: u128,
+    scale: u128,
+    margin_bps: u32,
+) -> Option<FillEffect> {
+    let delta = match side {
+        Side::Buy => i128::try_from(size).ok()?,
+        Side::Sell => -i128::try_from(size).ok()?,
+    };
+    position.cost = position
+        .cost
+        .checked_add(delta.checked_mul(i128::try_from(price).ok()?)?)?;
+
+    let mut effect = FillEffect::default();
+    let held = position.size.unsigned_abs();
+    let opened = if position.size == 0 || (position.size > 0) == (delta > 0) {
+        // Grow the position at the size-weighted average price
+        let total = held.checked_add(size)?;
+        position.entry_price = held
+            .checked_mul(position.entry_price)?
+            .checked_add(size.checked_mul(price)?)?
+            / total;
+        size
+    } else {
+        let closed = size.min(held);
+        effect.margin_released = if closed == held {
+            position.margin
+        } else {
+            position.margin.checked_mul(closed)? / held
+        };
+        position.margin -= effect.margin_released;
+        if size > closed {
+            position.entry_price = price;
+        } else if closed == held {
+            position.entry_price = 0;
+        }
+        size - closed
+    };
+    position.size = position.size.checked_add(delta)?;
+
+    if opened > 0 {
+        effect.margin_added = margin::initial_margin(order_price, opened, scale, margin_bps)?;
+        position.margin = position.margin.checked_add(effect.margin_added)?;
+    }
+    Some(effect)
+}
+
+/// Mark price of a perpetual market
+///
+/// The median of the index price and the best bid and ask, so neither a thin
+/// book nor a stale quote moves it far from the index; just the index while
+/// either side of the book is empty.
+pub fn mark_price(index_price: u128, best_bid: Option<u128>, best_ask: Option<u128>) -> u128 {
+    match (best_bid, best_ask) {
+        (Some(bid), Some(ask)) => {
+            let mut prices = [index_price, bid, ask];
+            prices.sort_unstable();
+            prices[1]
+        }
+        _ => index_price,
+    }
+}
+
+/// Funding rate for one interval, in millionths of notional
+///
+/// The premium of the mark over the index, capped at `max_rate_ppm` either
+/// way. Positive rates have longs pay shorts.
+pub fn funding_rate_ppm(mark_price: u128, index_price: u128, max_rate_ppm: u32) -> i64 {
+    if index_price == 0 {
+        return 0;
+    }
+    let premium = mark_price as i128 - index_price as i128;
+    let rate = premium.saturating_mul(FUNDING_RATE_SCALE) / index_price as i128;
+    rate.clamp(-(max_rate_ppm as i128), max_rate_ppm as i128) as i64
+}
+
+/// What one position owes or is owed at a settlement, in quote atoms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PositionSettlement {
+    /// PnL against the mark since the last settlement, positive for gains
+    pub pnl: i128,
+    /// Funding for the interval, positive when paid
+    pub funding: i128,
+}
+
+impl PositionSettlement {
+    /// Net amount the position receives, negative when it pays
+    pub fn owed(&self) -> i128 {
+        self.pnl - self.funding
+    }
+}
+
+/// Mark a position to `mark_price` and charge it funding at `rate_ppm`
+///
+/// PnL rounds down and funding rounds up, so a settlement never pays out more
+/// than it collects; the unsettled fraction of an atom of PnL stays in `cost`.
+/// Only `cost` changes here - what is actually paid is recorded by [`plan_settlement`].
+pub fn settle_position(
+    position: &mut Position,
+    mark_price: u128,
+    rate_ppm: i64,
+    scale: u128,
+) -> Option<PositionSettlement> {
+    let scale = i128::try_from(scale).ok()?;
+    let value = position
+        .size
+        .checked_mul(i128::try_from(mark_price).ok()?)?;
+    let pnl = value.checked_sub(position.cost)?.div_euclid(scale);
+    position.cost = position.cost.checked_add(pnl.checked_mul(scale)?)?;
+
+    let funding = value.checked_mul(rate_ppm as i128)?;
+    let funding = ceil_div(funding, FUNDING_RATE_SCALE.checked_mul(scale)?);
+    Some(PositionSettlement { pnl, funding })
+}
+
+fn ceil_div(value: i128, divisor: i128) -> i128 {
+    -(-value).div_euclid(divisor)
+}
+
+/// Outcome of settling one perpetual market's funding interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingSettlement {
+    pub market_id: String,
+    pub mark_price: u128,
+    pub index_price: u128,
+    pub funding_rate_ppm: i64,
+    /// Positions settled, including those closed since the last settlement
+    pub positions: usize,
+    pub open_interest: u128,
+    /// Net change of the insurance fund, in quote atoms
+    pub insurance: i128,
+    pub shortfall: u128,
+    pub haircut: u128,
+}
+
+/// Balance changes and position updates of one funding settlement
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SettlementPlan {
+    pub positions: Vec<Position>,
+    pub balance_changes: BalanceChanges,
+    /// Net change of the insurance fund: rounding dust in, shortfalls out
+    pub insurance: i128,
+    /// Losses the losing positions could not pay
+    pub shortfall: u128,
+    /// Gains withheld from winning positions when the insurance fund ran dry
+    pub haircut: u128,
+}
+
+/// Settle every position of a market against the mark price
+///
+/// Losers pay from their free quote balance first, then from their position's
+/// margin. What they cannot pay is covered by the insurance fund, and only once
+/// that is exhausted are the winners' gains cut, pro rata. `free` is the quote
+/// balance not locked by orders or margin of each cross-margined user; isolated
+/// positions pay from their own margin only. `insurance` is the fund's quote
+/// balance.
+pub fn plan_settlement(
+    mut positions: Vec<Position>,
+    free: &HashMap<String, u128>,
+    insurance: u128,
+    quote_ticker: &str,
+    mark_price: u128,
+    rate_ppm: i64,
+    scale: u128,
+) -> Option<SettlementPlan> {
+    let mut plan = SettlementPlan::default();
+    let mut owed = Vec::with_capacity(positions.len());
+    for position in &mut positions {
+        owed.push(settle_position(position, mark_price, rate_ppm, scale)?);
+    }
+
+    // Collect from the losers
+    let mut collected: u128 = 0;
+    let mut gains: u128 = 0;
+    for (position, settlement) in positions.iter_mut().zip(&owed) {
+        let amount = settlement.owed();
+        if amount >= 0 {
+            gains = gains.checked_add(amount.unsigned_abs())?;
+            continue;
+        }
+        let loss = amount.unsigned_abs();
+        let from_free = loss.min(free.get(&position.user_address).copied().unwrap_or(0));
+        let from_margin = (loss - from_free).min(position.margin);
+        let paid = from_free + from_margin;
+        position.margin -= from_margin;
+
+        let change = plan
+            .balance_changes
+            .entry((position.user_address.clone(), quote_ticker.to_string()))
+            .or_default();
+        change.debit += paid;
+        change.unlock += from_margin;
+
+        collected = collected.checked_add(paid)?;
+        plan.shortfall += loss - paid;
+        record(position, settlement, -(paid as i128))?;
+    }
+
+    // Pay the winners, from the insurance fund where the losers fell short
+    let available = collected.saturating_add(insurance);
+    let mut paid_out: u128 = 0;
+    for (position, settlement) in positions.iter_mut().zip(&owed) {
+        let amount = settlement.owed();
+        if amount <= 0 {
+            if amount == 0 {
+                record(position, settlement, 0)?;
+            }
+            continue;
+        }
+        let gain = amount.unsigned_abs();
+        let paid = if gains <= available {
+            gain
+        } else {
+            gain.checked_mul(available)? / gains
+        };
+        if paid > 0 {
+            plan.balance_changes
+                .entry((position.user_address.clone(), quote_ticker.to_string()))
+                .or_default()
+                .credit += paid;
+        }
+        paid_out += paid;
+        plan.haircut += gain - paid;
+        record(position, settlement, paid as i128)?;
+    }
+
+    plan.insurance = collected as i128 - paid_out as i128;
+    plan.positions = positions;
+    Some(plan)
+}
+
+/// Record what a position actually paid (negative) or received at a settlement
+fn record(position: &mut Position, settlement: &PositionSettlement, paid: i128) -> Option<()> {
+    // Shortfalls and haircuts come out of PnL, funding is recorded as charged
+    position.funding_paid = position.funding_paid.checked_add(settlement.funding)?;
+    position.realized_pnl = position
+        .realized_pnl
+        .checked_add(paid.checked_add(settlement.funding)?)?;
+    Some(())
+}
+
+/// Open interest of a market: the total size of its long positions
+pub fn open_interest(positions: &[Position]) -> u128 {
+    positions
+        .iter()
+        .filter(|position| position.size > 0)
+        .map(|position| position.size.unsigned_abs())
+        .sum()
+}
+
+/// PnL a position would settle at `mark_price`, in quote atoms
+pub fn unrealized_pnl(position: &Position, mark_price: u128, scale: u128) -> Option<i128> {
+    let value = position
+        .size
+        .checked_mul(i128::try_from(mark_price).ok()?)?;
+    Some(
+        value
+            .checked_sub(position.cost)?
+            .div_euclid(i128::try_from(scale).ok()?),
+    )
+}
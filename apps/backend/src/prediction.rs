@@ -0,0 +1,132 @@
+// prediction events: mutually exclusive outcomes trading as markets in one quote token
+//
+// Each outcome is an ordinary spot market whose base token pays one quote
+// token per whole token if that outcome wins, and nothing otherwise. The
+// markets trade independently; their prices only sum to one because makers
+// quote them that way. Resolving an event delists every outcome market,
+// pays the winning token's holders and burns all of the event's outcome tokens
+// in one transaction.
+
+use crate::db::balances::BalanceChanges;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::Market;
+use std::collections::HashSet;
+
+/// Fewest outcomes an event can have
+pub const MIN_OUTCOMES: usize = 2;
+
+/// An outcome token balance held when its event resolves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutcomeHolding {
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: u128,
+    /// Still locked by orders; released as the tokens are burned
+    pub open_interest: u128,
+}
+
+/// Balance changes resolving an event, and what they paid out
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolutionPlan {
+    pub balance_changes: BalanceChanges,
+    pub holders_paid: usize,
+    pub total_payout: u128,
+}
+
+/// Quote atoms `amount` atoms of a winning outcome token pay, rounded down
+pub fn outcome_payout(amount: u128, token_decimals: u8, quote_decimals: u8) -> Option<u128> {
+    let quote_unit = 10u128.checked_pow(quote_decimals as u32)?;
+    let token_unit = 10u128.checked_pow(token_decimals as u32)?;
+    amount.checked_mul(quote_unit)?.checked_div(token_unit)
+}
+
+/// Check that `event_id` and its outcome markets form a valid event in `quote_ticker`
+///
+/// Ids are lowercase slugs so they can sit in URLs unencoded. Outcomes need
+/// distinct names and distinct markets, each quoted in the event's token and
+/// with a base token of its own.
+pub fn validate_event(
+    event_id: &str,
+    quote_ticker: &str,
+    outcomes: &[(String, Market)],
+) -> Result<()> {
+    let invalid = |message: String| Err(ExchangeError::InvalidParameter { message });
+
+    let slug = event_id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if event_id.is_empty() || !slug {
+        return invalid(format!(
+            "Event id must be lowercase letters, digits and dashes, got '{}'",
+            event_id
+        ));
+    }
+    if outcomes.len() < MIN_OUTCOMES {
+        return invalid(format!(
+            "An event needs at least {} outcomes, got {}",
+            MIN_OUTCOMES,
+            outcomes.len()
+        ));
+    }
+
+    let mut names = HashSet::new();
+    let mut tokens = HashSet::new();
+    for (name, market) in outcomes {
+        if name.trim().is_empty() {
+            return invalid("Outcome names must not be empty".to_string());
+        }
+        if !names.insert(name.as_str()) {
+            return invalid(format!("Outcome '{}' is listed twice", name));
+        }
+        if market.quote_ticker != quote_ticker {
+            return invalid(format!(
+                "Market {} is quoted in {}, the event in {}",
+                market.id, market.quote_ticker, quote_ticker
+            ));
+        }
+        if market.base_ticker == quote_ticker || !tokens.insert(market.base_ticker.as_str()) {
+            return invalid(format!(
+                "Market {} does not trade a token of its own",
+                market.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Balance changes burning every outcome token and paying the winning token's holders
+///
+/// Outcome tokens enter balances like any other token, through deposits and
+/// the faucet, so payouts are issued rather than drawn from a collateral pool.
+pub fn plan_resolution(
+    holdings: &[OutcomeHolding],
+    winning_token: &str,
+    winning_decimals: u8,
+    quote_ticker: &str,
+    quote_decimals: u8,
+) -> Option<ResolutionPlan> {
+    let mut plan = ResolutionPlan::default();
+    for holding in holdings {
+        let change = plan
+            .balance_changes
+            .entry((holding.user_address.clone(), holding.token_ticker.clone()))
+            .or_default();
+        change.debit += holding.amount;
+        change.unlock += holding.open_interest;
+
+        if holding.token_ticker != winning_token {
+            continue;
+        }
+        let payout = outcome_payout(holding.amount, winning_decimals, quote_decimals)?;
+        if payout == 0 {
+            continue;
+        }
+        plan.balance_changes
+            .entry((holding.user_address.clone(), quote_ticker.to_string()))
+            .or_default()
+            .credit += payout;
+        plan.holders_paid += 1;
+        plan.total_payout = plan.total_payout.checked_add(payout)?;
+    }
+    Some(plan)
+}
@@ -0,0 +1,169 @@
+// external index prices, polled from oracles and shared with the engine
+
+pub mod sources;
+
+use crate::db::Db;
+use crate::models::domain::IndexPrice;
+use chrono::{DateTime, Utc};
+use sources::IndexSource;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Seconds between polls of every index feed
+pub const INDEX_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Index prices older than this are ignored by the engine
+pub const INDEX_PRICE_MAX_AGE_SECS: i64 = 30;
+
+/// Latest index price of every fed market, shared by the feed and the engine
+///
+/// The feed writes after each poll; the engine reads on the order path, so a
+/// slow or unreachable oracle never delays matching - its prices just go stale.
+#[derive(Debug, Clone, Default)]
+pub struct IndexPrices {
+    inner: Arc<RwLock<HashMap<String, IndexPrice>>>,
+}
+
+impl IndexPrices {
+    /// Replace a market's index price if `price` is newer
+    pub fn record(&self, price: IndexPrice) {
+        let mut inner = self.inner.write().unwrap();
+        match inner.get(&price.market_id) {
+            Some(current) if current.timestamp > price.timestamp => {}
+            _ => {
+                inner.insert(price.market_id.clone(), price);
+            }
+        }
+    }
+
+    /// A market's latest index price, however old
+    pub fn latest(&self, market_id: &str) -> Option<IndexPrice> {
+        self.inner.read().unwrap().get(market_id).cloned()
+    }
+
+    /// A market's index price if it is at most `INDEX_PRICE_MAX_AGE_SECS` old at `now`
+    pub fn fresh(&self, market_id: &str, now: DateTime<Utc>) -> Option<u128> {
+        let inner = self.inner.read().unwrap();
+        let price = inner.get(market_id)?;
+        (now.signed_duration_since(price.timestamp).num_seconds() <= INDEX_PRICE_MAX_AGE_SECS)
+            .then_some(price.price)
+    }
+}
+
+/// A market priced by an external index
+#[derive(Debug, Clone)]
+pub struct IndexFeed {
+    pub market_id: String,
+    pub source: IndexSource,
+    /// Decimals of the market's quote token, to convert prices into atoms
+    pub quote_decimals: u8,
+}
+
+/// Polls index feeds, publishes their prices to [`IndexPrices`] and stores them in ClickHouse
+pub struct PriceFeed {
+    client: reqwest::Client,
+    hyperliquid_url: String,
+    feeds: Vec<IndexFeed>,
+    prices: IndexPrices,
+}
+
+impl PriceFeed {
+    /// Publish polled prices to `prices`, usually the engine's [`IndexPrices`]
+    pub fn new(prices: IndexPrices) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(INDEX_POLL_INTERVAL_SECS))
+                .build()
+                .unwrap_or_default(),
+            hyperliquid_url: sources::HYPERLIQUID_INFO_URL.to_string(),
+            feeds: Vec::new(),
+            prices,
+        }
+    }
+
+    /// Query Hyperliquid at `url` instead of its public API
+    pub fn with_hyperliquid_url(mut self, url: impl Into<String>) -> Self {
+        self.hyperliquid_url = url.into();
+        self
+    }
+
+    /// Poll `feed` along with the others
+    pub fn add(&mut self, feed: IndexFeed) {
+        self.feeds.push(feed);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.feeds.is_empty()
+    }
+
+    /// Fetch every feed once, leaving out those that failed
+    ///
+    /// All Hyperliquid feeds share a single request.
+    pub async fn poll(&self) -> Vec<IndexPrice> {
+        let now = Utc::now();
+        let needs_hyperliquid = self
+            .feeds
+            .iter()
+            .any(|feed| matches!(feed.source, IndexSource::Hyperliquid { .. }));
+        let mids = if needs_hyperliquid {
+            match sources::fetch_hyperliquid_mids(&self.client, &self.hyperliquid_url).await {
+                Ok(mids) => Some(mids),
+                Err(e) => {
+                    log::warn!("Failed to fetch Hyperliquid mids: {:#}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut prices = Vec::with_capacity(self.feeds.len());
+        for feed in &self.feeds {
+            let value = match &feed.source {
+                IndexSource::Hyperliquid { coin } => {
+                    let Some(mids) = &mids else { continue };
+                    mids.get(coin)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("no Hyperliquid mid for {}", coin))
+                }
+                IndexSource::Rest { url, pointer } => {
+                    sources::fetch_rest_price(&self.client, url, pointer).await
+                }
+            };
+            let price = value.and_then(|value| {
+                sources::parse_decimal_price(&value, feed.quote_decimals)
+                    .ok_or_else(|| anyhow::anyhow!("invalid price '{}'", value))
+            });
+            match price {
+                Ok(price) => prices.push(IndexPrice {
+                    market_id: feed.market_id.clone(),
+                    timestamp: now,
+                    price,
+                    source: feed.source.name().to_string(),
+                }),
+                Err(e) => log::warn!("Index price for {} unavailable: {:#}", feed.market_id, e),
+            }
+        }
+
+        prices
+    }
+
+    /// Poll every `INDEX_POLL_INTERVAL_SECS` until the process exits
+    pub async fn run(self, db: Db) {
+        log::info!("Polling index prices for {} markets", self.feeds.len());
+        let mut interval = tokio::time::interval(Duration::from_secs(INDEX_POLL_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            let prices = self.poll().await;
+            for price in &prices {
+                self.prices.record(price.clone());
+            }
+            if let Err(e) = db.insert_index_prices(&prices).await {
+                log::error!("Failed to store index prices: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// where index prices come from
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Hyperliquid's public info endpoint
+pub const HYPERLIQUID_INFO_URL: &str = "https://api.hyperliquid.xyz/info";
+
+/// An external source of a market's index price (`index_feed` in config.toml)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum IndexSource {
+    /// Mid price of a Hyperliquid market, e.g. `coin = "BTC"`
+    Hyperliquid { coin: String },
+    /// A JSON REST oracle; `pointer` locates the price in the response, e.g. `"/data/price"`
+    Rest { url: String, pointer: String },
+}
+
+impl IndexSource {
+    /// Short name stored with each price
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexSource::Hyperliquid { .. } => "hyperliquid",
+            IndexSource::Rest { .. } => "rest",
+        }
+    }
+}
+
+/// Mid prices of every Hyperliquid market by coin, as decimal strings
+pub async fn fetch_hyperliquid_mids(
+    client: &reqwest::Client,
+    url: &str,
+) -> anyhow::Result<HashMap<String, String>> {
+    let mids = client
+        .post(url)
+        .json(&serde_json::json!({ "type": "allMids" }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(mids)
+}
+
+/// The decimal price at `pointer` in a REST oracle's JSON response
+pub async fn fetch_rest_price(
+    client: &reqwest::Client,
+    url: &str,
+    pointer: &str,
+) -> anyhow::Result<String> {
+    let body: Value = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    match body
+        .pointer(pointer)
+        .context("price missing from response")?
+    {
+        Value::String(price) => Ok(price.clone()),
+        Value::Number(price) => Ok(price.to_string()),
+        other => anyhow::bail!("price is not a number: {}", other),
+    }
+}
+
+/// Convert a decimal price such as "90123.45" into quote atoms, truncating
+/// digits beyond the quote token's precision
+///
+/// Returns `None` for anything but a plain non-negative decimal.
+pub fn parse_decimal_price(value: &str, quote_decimals: u8) -> Option<u128> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(fraction) {
+        return None;
+    }
+
+    let scale = 10u128.checked_pow(quote_decimals as u32)?;
+    let fraction: String = fraction
+        .chars()
+        .chain(std::iter::repeat('0'))
+        .take(quote_decimals as usize)
+        .collect();
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u128>().ok()?
+    };
+    whole
+        .parse::<u128>()
+        .ok()?
+        .checked_mul(scale)?
+        .checked_add(fraction)
+}
@@ -0,0 +1,139 @@
+// request for quote: large orders priced by makers and settled off the book
+//
+// A taker asks for a firm price on a size, registered makers answer with
+// quotes until the request expires, and accepting one trades the whole size
+// at its price in one transaction. The quoted price is all-in, so neither
+// side pays trading fees. RFQ trades reach the trade stream, PostgreSQL and
+// ClickHouse like book trades, flagged as `rfq`, but never touch the book or
+// its price collar.
+
+use crate::db::balances::BalanceChanges;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Market, Order, QuoteRequest, Side, Trade};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How long a request stays open when the taker doesn't say
+pub const DEFAULT_REQUEST_TTL_SECS: u64 = 30;
+
+/// Longest a request can stay open; quotes are firm, so makers won't hold them for long
+pub const MAX_REQUEST_TTL_SECS: u64 = 300;
+
+/// What accepting a quote did: the filled request, its trade and both sides' orders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RfqExecution {
+    pub request: QuoteRequest,
+    pub trade: Trade,
+    /// The taker's order first, then the maker's; both filled at the quoted price
+    pub orders: Vec<Order>,
+}
+
+/// When a request opened at `now` for `ttl_secs` expires
+pub fn request_expiry(now: DateTime<Utc>, ttl_secs: Option<u64>) -> Result<DateTime<Utc>> {
+    let ttl_secs = ttl_secs.unwrap_or(DEFAULT_REQUEST_TTL_SECS);
+    if ttl_secs == 0 || ttl_secs > MAX_REQUEST_TTL_SECS {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Request TTL must be between 1 and {} seconds, got {}",
+                MAX_REQUEST_TTL_SECS, ttl_secs
+            ),
+        });
+    }
+    Ok(now + Duration::seconds(ttl_secs as i64))
+}
+
+/// When a quote given at `now` for `ttl_secs` expires; never after its request
+pub fn quote_expiry(
+    now: DateTime<Utc>,
+    ttl_secs: Option<u64>,
+    request_expires_at: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let Some(ttl_secs) = ttl_secs else {
+        return Ok(request_expires_at);
+    };
+    if ttl_secs == 0 {
+        return Err(ExchangeError::InvalidParameter {
+            message: "Quote TTL must be at least one second".to_string(),
+        });
+    }
+    let expires_at = now + Duration::seconds(ttl_secs.min(MAX_REQUEST_TTL_SECS) as i64);
+    Ok(expires_at.min(request_expires_at))
+}
+
+/// Check a requested size against the market's minimum and lot size
+pub fn validate_size(market: &Market, size: u128) -> Result<()> {
+    if size == 0 || size < market.min_size || !size.is_multiple_of(market.lot_size) {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Size must be at least {} and a multiple of {} in {}",
+                market.min_size, market.lot_size, market.id
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Check a quoted price against the market's tick size
+pub fn validate_price(market: &Market, price: u128) -> Result<()> {
+    if price == 0 || !price.is_multiple_of(market.tick_size) {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Price must be a positive multiple of {} in {}",
+                market.tick_size, market.id
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Buyer and seller of a request filled by `maker_address`
+pub fn counterparties<'a>(request: &'a QuoteRequest, maker_address: &'a str) -> (&'a str, &'a str) {
+    match request.side {
+        Side::Buy => (&request.taker_address, maker_address),
+        Side::Sell => (maker_address, &request.taker_address),
+    }
+}
+
+/// Balance changes trading `size` of `market` at `price` from seller to buyer
+///
+/// Every debit is matched by an equal unlock: settlement locks what each side
+/// pays first, which fails if they can't cover it, and spends the lock here.
+/// `None` if the trade's value overflows or rounds to nothing.
+pub fn plan_settlement(
+    market: &Market,
+    buyer_address: &str,
+    seller_address: &str,
+    price: u128,
+    size: u128,
+    base_decimals: u8,
+) -> Option<BalanceChanges> {
+    let quote_amount = price
+        .checked_mul(size)?
+        .checked_div(10u128.checked_pow(base_decimals as u32)?)?;
+    if quote_amount == 0 {
+        return None;
+    }
+
+    let mut changes = BalanceChanges::new();
+    let buyer_quote = changes
+        .entry((buyer_address.to_string(), market.quote_ticker.clone()))
+        .or_default();
+    buyer_quote.debit += quote_amount;
+    buyer_quote.unlock += quote_amount;
+    changes
+        .entry((buyer_address.to_string(), market.base_ticker.clone()))
+        .or_default()
+        .credit += size;
+
+    let seller_base = changes
+        .entry((seller_address.to_string(), market.base_ticker.clone()))
+        .or_default();
+    seller_base.debit += size;
+    seller_base.unlock += size;
+    changes
+        .entry((seller_address.to_string(), market.quote_ticker.clone()))
+        .or_default()
+        .credit += quote_amount;
+
+    Some(changes)
+}
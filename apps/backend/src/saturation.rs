@@ -0,0 +1,370 @@
+// engine backlog and saturation: queue depths, event lag and degraded mode
+
+use crate::alerts::{Alert, AlertKind, Alerter};
+use crate::engine::analytics::AnalyticsStats;
+use crate::models::domain::EngineRequest;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Engine queue fill, in percent, that counts as saturated
+pub const DEFAULT_ENGINE_QUEUE_PERCENT: usize = 90;
+
+/// ClickHouse trade buffer fill, in percent, that counts as saturated
+pub const DEFAULT_PERSISTENCE_QUEUE_PERCENT: usize = 80;
+
+/// Engine events one consumer may skip between checks before it counts as saturated
+pub const DEFAULT_LAGGED_EVENTS: u64 = 100;
+
+/// Calm checks in a row it takes to leave degraded mode
+pub const RECOVERY_CHECKS: u32 = 5;
+
+/// How often queues and lag are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shared saturation gauges and the degraded-mode flag, cheap to clone
+///
+/// Broadcast consumers report the events they skip with [`record_lag`],
+/// a [`SaturationMonitor`] samples the queues, and market-data work checks
+/// [`is_degraded`] to step aside for order entry. The default handle is
+/// standalone: it counts, but nothing samples it and it never degrades.
+///
+/// [`record_lag`]: Saturation::record_lag
+/// [`is_degraded`]: Saturation::is_degraded
+#[derive(Clone, Default)]
+pub struct Saturation {
+    inner: Arc<Gauges>,
+}
+
+#[derive(Default)]
+struct Gauges {
+    engine_queue_depth: AtomicU64,
+    engine_queue_capacity: AtomicU64,
+    persistence_queue_depth: AtomicU64,
+    persistence_queue_capacity: AtomicU64,
+    /// Engine events skipped by each broadcast consumer since startup
+    lagged: Mutex<BTreeMap<&'static str, u64>>,
+    degraded: AtomicBool,
+    degraded_entered: AtomicU64,
+}
+
+impl Saturation {
+    /// Count `skipped` engine events a broadcast consumer lost by falling behind
+    pub fn record_lag(&self, consumer: &'static str, skipped: u64) {
+        *self
+            .inner
+            .lagged
+            .lock()
+            .unwrap()
+            .entry(consumer)
+            .or_default() += skipped;
+    }
+
+    /// Engine events each consumer has skipped since startup, by name
+    pub fn lagged(&self) -> BTreeMap<&'static str, u64> {
+        self.inner.lagged.lock().unwrap().clone()
+    }
+
+    /// Whether market-data work should be shed to keep order entry moving
+    pub fn is_degraded(&self) -> bool {
+        self.inner.degraded.load(Ordering::Relaxed)
+    }
+
+    /// The engine queue's depth and capacity at the last check
+    pub fn engine_queue(&self) -> (u64, u64) {
+        (
+            self.inner.engine_queue_depth.load(Ordering::Relaxed),
+            self.inner.engine_queue_capacity.load(Ordering::Relaxed),
+        )
+    }
+
+    /// The ClickHouse trade buffer's depth and capacity at the last check
+    pub fn persistence_queue(&self) -> (u64, u64) {
+        (
+            self.inner.persistence_queue_depth.load(Ordering::Relaxed),
+            self.inner
+                .persistence_queue_capacity
+                .load(Ordering::Relaxed),
+        )
+    }
+
+    fn set_degraded(&self, degraded: bool) {
+        let was = self.inner.degraded.swap(degraded, Ordering::Relaxed);
+        if degraded && !was {
+            self.inner.degraded_entered.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The gauges in the Prometheus text exposition format
+    pub fn render_metrics(&self) -> String {
+        let (engine_depth, engine_capacity) = self.engine_queue();
+        let (persistence_depth, persistence_capacity) = self.persistence_queue();
+        let gauges = [
+            (
+                "exchange_engine_queue_depth",
+                "gauge",
+                "Requests waiting in the matching engine's queue",
+                engine_depth,
+            ),
+            (
+                "exchange_engine_queue_capacity",
+                "gauge",
+                "Size of the matching engine's queue",
+                engine_capacity,
+            ),
+            (
+                "exchange_persistence_queue_depth",
+                "gauge",
+                "Trades buffered for ClickHouse",
+                persistence_depth,
+            ),
+            (
+                "exchange_persistence_queue_capacity",
+                "gauge",
+                "Size of the ClickHouse trade buffer",
+                persistence_capacity,
+            ),
+            (
+                "exchange_degraded",
+                "gauge",
+                "1 while market-data work is shed for order entry",
+                self.is_degraded() as u64,
+            ),
+            (
+                "exchange_degraded_entered_total",
+                "counter",
+                "Times the exchange entered degraded mode",
+                self.inner.degraded_entered.load(Ordering::Relaxed),
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, kind, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        let _ = writeln!(
+            out,
+            "# HELP exchange_event_lag_total Engine events skipped by a broadcast consumer that fell behind"
+        );
+        let _ = writeln!(out, "# TYPE exchange_event_lag_total counter");
+        for (consumer, skipped) in self.lagged() {
+            let _ = writeln!(
+                out,
+                "exchange_event_lag_total{{consumer=\"{}\"}} {}",
+                consumer, skipped
+            );
+        }
+        out
+    }
+}
+
+/// When queues and lag count as saturated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaturationThresholds {
+    /// Engine queue fill, in percent
+    pub engine_queue_percent: usize,
+    /// ClickHouse trade buffer fill, in percent
+    pub persistence_queue_percent: usize,
+    /// Engine events one consumer may skip between checks
+    pub lagged_events: u64,
+}
+
+impl Default for SaturationThresholds {
+    fn default() -> Self {
+        Self {
+            engine_queue_percent: DEFAULT_ENGINE_QUEUE_PERCENT,
+            persistence_queue_percent: DEFAULT_PERSISTENCE_QUEUE_PERCENT,
+            lagged_events: DEFAULT_LAGGED_EVENTS,
+        }
+    }
+}
+
+/// Queue depths and lag seen by one check
+#[derive(Debug, Clone, Default)]
+pub struct Sample {
+    pub engine_queued: usize,
+    pub engine_capacity: usize,
+    pub persistence_queued: usize,
+    pub persistence_capacity: usize,
+    /// Events each consumer skipped since the previous check
+    pub lagged: Vec<(&'static str, u64)>,
+}
+
+impl SaturationThresholds {
+    /// An alert for each part of `sample` over its threshold; none means calm
+    pub fn check(&self, sample: &Sample) -> Vec<Alert> {
+        let over = |queued: usize, capacity: usize, percent: usize| {
+            capacity > 0 && queued * 100 >= capacity * percent
+        };
+
+        let mut alerts = Vec::new();
+        if over(
+            sample.engine_queued,
+            sample.engine_capacity,
+            self.engine_queue_percent,
+        ) {
+            alerts.push(Alert::new(
+                AlertKind::EngineQueueSaturated,
+                "engine",
+                format!(
+                    "{} of {} engine queue slots in use",
+                    sample.engine_queued, sample.engine_capacity
+                ),
+            ));
+        }
+        if over(
+            sample.persistence_queued,
+            sample.persistence_capacity,
+            self.persistence_queue_percent,
+        ) {
+            alerts.push(Alert::new(
+                AlertKind::PersistenceQueueSaturated,
+                "clickhouse",
+                format!(
+                    "{} of {} trades buffered for ClickHouse",
+                    sample.persistence_queued, sample.persistence_capacity
+                ),
+            ));
+        }
+        for (consumer, skipped) in &sample.lagged {
+            if *skipped >= self.lagged_events {
+                alerts.push(Alert::new(
+                    AlertKind::EventsLagging,
+                    *consumer,
+                    format!("Skipped {} engine events since the last check", skipped),
+                ));
+            }
+        }
+        alerts
+    }
+}
+
+/// Degraded mode with hysteresis: entered on the first saturated check and
+/// left after [`RECOVERY_CHECKS`] calm ones in a row
+#[derive(Debug, Default)]
+pub struct DegradedMode {
+    degraded: bool,
+    calm_checks: u32,
+}
+
+impl DegradedMode {
+    /// Take in one check's outcome; returns whether to be degraded now
+    pub fn update(&mut self, saturated: bool) -> bool {
+        if saturated {
+            self.degraded = true;
+            self.calm_checks = 0;
+        } else if self.degraded {
+            self.calm_checks += 1;
+            if self.calm_checks >= RECOVERY_CHECKS {
+                self.degraded = false;
+                self.calm_checks = 0;
+            }
+        }
+        self.degraded
+    }
+}
+
+/// Samples the engine queue, the ClickHouse trade buffer and consumer lag
+/// into a [`Saturation`], alerting and switching degraded mode on and off
+pub struct SaturationMonitor {
+    saturation: Saturation,
+    engine_tx: mpsc::WeakSender<EngineRequest>,
+    persistence: Option<(Arc<AnalyticsStats>, usize)>,
+    thresholds: SaturationThresholds,
+    alerts: Alerter,
+}
+
+impl SaturationMonitor {
+    /// Holds only a weak handle to the engine queue, so it never keeps the
+    /// engine running, and stops once the queue is gone
+    pub fn new(saturation: Saturation, engine_tx: mpsc::WeakSender<EngineRequest>) -> Self {
+        Self {
+            saturation,
+            engine_tx,
+            persistence: None,
+            thresholds: SaturationThresholds::default(),
+            alerts: Alerter::default(),
+        }
+    }
+
+    /// Also watch the engine's ClickHouse trade buffer of `capacity` trades
+    pub fn with_persistence_queue(mut self, stats: Arc<AnalyticsStats>, capacity: usize) -> Self {
+        self.persistence = Some((stats, capacity));
+        self
+    }
+
+    pub fn with_thresholds(mut self, thresholds: SaturationThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Raise an alert for each threshold crossed
+    pub fn with_alerts(mut self, alerts: Alerter) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    pub async fn run(self) {
+        let gauges = &self.saturation.inner;
+        let mut mode = DegradedMode::default();
+        let mut last_lagged = BTreeMap::new();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(engine_tx) = self.engine_tx.upgrade() else {
+                return;
+            };
+            let mut sample = Sample {
+                engine_capacity: engine_tx.max_capacity(),
+                engine_queued: engine_tx.max_capacity() - engine_tx.capacity(),
+                ..Sample::default()
+            };
+            drop(engine_tx);
+
+            if let Some((stats, capacity)) = &self.persistence {
+                sample.persistence_queued = stats.queued() as usize;
+                sample.persistence_capacity = *capacity;
+            }
+            let lagged = self.saturation.lagged();
+            sample.lagged = lagged
+                .iter()
+                .map(|(consumer, total)| {
+                    let before = last_lagged.get(consumer).copied().unwrap_or(0);
+                    (*consumer, total - before)
+                })
+                .filter(|(_, skipped)| *skipped > 0)
+                .collect();
+            last_lagged = lagged;
+
+            let store =
+                |gauge: &AtomicU64, value: usize| gauge.store(value as u64, Ordering::Relaxed);
+            store(&gauges.engine_queue_depth, sample.engine_queued);
+            store(&gauges.engine_queue_capacity, sample.engine_capacity);
+            store(&gauges.persistence_queue_depth, sample.persistence_queued);
+            store(
+                &gauges.persistence_queue_capacity,
+                sample.persistence_capacity,
+            );
+
+            let alerts = self.thresholds.check(&sample);
+            let was_degraded = self.saturation.is_degraded();
+            let degraded = mode.update(!alerts.is_empty());
+            for alert in alerts {
+                self.alerts.raise(alert);
+            }
+            if degraded != was_degraded {
+                if degraded {
+                    log::warn!("Saturated, shedding market-data work until load eases");
+                } else {
+                    log::info!("Load eased, resuming market-data work");
+                }
+                self.saturation.set_degraded(degraded);
+            }
+        }
+    }
+}
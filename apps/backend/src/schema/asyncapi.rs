@@ -0,0 +1,199 @@
+use serde_json::{json, Map, Value};
+
+use crate::models::api::SubscriptionChannel;
+
+// ============================================================================
+// AsyncAPI - WebSocket Channels and Messages
+// ============================================================================
+
+/// Subscription channels, the field that scopes them and the server message they carry
+/// (mirrors `engine_event_to_messages` in api/ws/server.rs)
+const DATA_CHANNELS: [(SubscriptionChannel, &str, &str); 5] = [
+    (SubscriptionChannel::Trades, "market_id", "trade"),
+    (SubscriptionChannel::Orderbook, "market_id", "orderbook"),
+    (SubscriptionChannel::UserFills, "user_address", "user_fill"),
+    (
+        SubscriptionChannel::UserOrders,
+        "user_address",
+        "user_order",
+    ),
+    (
+        SubscriptionChannel::UserBalances,
+        "user_address",
+        "user_balance",
+    ),
+];
+
+/// Build an AsyncAPI 3.0 document from the generated JSON Schema
+///
+/// Every message shares the single `/ws` connection. Each subscription channel
+/// is modelled as an AsyncAPI channel carrying its data message; subscribe and
+/// unsubscribe requests, acknowledgments, errors and ping/pong live on `connection`.
+pub fn document(root_schema: &Value) -> Value {
+    let mut schemas = root_schema["$defs"]
+        .as_object()
+        .cloned()
+        .expect("Schema has no $defs");
+    for schema in schemas.values_mut() {
+        rewrite_refs(schema);
+    }
+
+    // One AsyncAPI message per enum variant, keyed by its `type` tag
+    let mut messages = Map::new();
+    for enum_name in ["ClientMessage", "ServerMessage"] {
+        for variant in schemas[enum_name]["oneOf"]
+            .as_array()
+            .expect("Message enum is not a oneOf")
+        {
+            let tag = variant["properties"]["type"]["const"]
+                .as_str()
+                .expect("Message variant has no type tag");
+            messages.insert(
+                tag.to_string(),
+                json!({
+                    "name": tag,
+                    "title": pascal_case(tag),
+                    "contentType": "application/json",
+                    "payload": variant,
+                }),
+            );
+        }
+    }
+
+    let message_ref = |tag: &str| json!({ "$ref": format!("#/components/messages/{}", tag) });
+    let channel_message_ref = |channel: &str, tag: &str| {
+        let target = format!("#/channels/{}/messages/{}", channel, tag);
+        json!({ "$ref": target })
+    };
+
+    let control_messages = [
+        "subscribe",
+        "unsubscribe",
+        "ping",
+        "subscribed",
+        "unsubscribed",
+        "error",
+        "pong",
+    ];
+    let mut channels = Map::new();
+    channels.insert(
+        "connection".to_string(),
+        json!({
+            "address": "/ws",
+            "title": "Connection",
+            "description": "Subscription management, acknowledgments, errors and application-level ping/pong",
+            "messages": control_messages
+                .iter()
+                .map(|tag| (tag.to_string(), message_ref(tag)))
+                .collect::<Map<_, _>>(),
+        }),
+    );
+
+    let mut operations = Map::new();
+    for (name, request, reply) in [
+        ("subscribe", "subscribe", "subscribed"),
+        ("unsubscribe", "unsubscribe", "unsubscribed"),
+        ("ping", "ping", "pong"),
+    ] {
+        operations.insert(
+            name.to_string(),
+            json!({
+                "action": "receive",
+                "channel": { "$ref": "#/channels/connection" },
+                "messages": [channel_message_ref("connection", request)],
+                "reply": {
+                    "channel": { "$ref": "#/channels/connection" },
+                    "messages": [channel_message_ref("connection", reply)],
+                },
+            }),
+        );
+    }
+    operations.insert(
+        "sendError".to_string(),
+        json!({
+            "action": "send",
+            "channel": { "$ref": "#/channels/connection" },
+            "messages": [channel_message_ref("connection", "error")],
+        }),
+    );
+
+    for (channel, scope, tag) in DATA_CHANNELS {
+        let channel = serde_json::to_value(channel)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .expect("Subscription channel is not a string");
+
+        channels.insert(
+            channel.clone(),
+            json!({
+                "address": "/ws",
+                "title": pascal_case(&channel),
+                "description": format!(
+                    "Send {{\"type\": \"subscribe\", \"channel\": \"{}\", \"{}\": ...}} to receive `{}` messages",
+                    channel, scope, tag
+                ),
+                "messages": { (tag): message_ref(tag) },
+                "x-subscription": {
+                    "channel": channel,
+                    "requires": scope,
+                },
+            }),
+        );
+        operations.insert(
+            format!("send{}", pascal_case(tag)),
+            json!({
+                "action": "send",
+                "channel": { "$ref": format!("#/channels/{}", channel) },
+                "messages": [channel_message_ref(&channel, tag)],
+            }),
+        );
+    }
+
+    json!({
+        "asyncapi": "3.0.0",
+        "info": {
+            "title": "Exchange WebSocket API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Real-time market data and user updates",
+        },
+        "defaultContentType": "application/json",
+        "channels": channels,
+        "operations": operations,
+        "components": {
+            "schemas": schemas,
+            "messages": messages,
+        },
+    })
+}
+
+/// Point `#/$defs/...` references at `#/components/schemas/...`
+fn rewrite_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                match item {
+                    Value::String(target) if key == "$ref" => {
+                        *target = target.replace("#/$defs/", "#/components/schemas/");
+                    }
+                    _ => rewrite_refs(item),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
+
+/// `user_fill` -> `UserFill`
+fn pascal_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
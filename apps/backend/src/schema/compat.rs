@@ -0,0 +1,195 @@
+use serde_json::{Map, Value};
+use std::fmt;
+
+// ============================================================================
+// Schema Compatibility - Detect Backward-Incompatible Changes
+// ============================================================================
+
+/// A change that can break existing clients generated from the old schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakingChange {
+    /// A named type no longer exists
+    RemovedDefinition { name: String },
+    /// An object lost a property
+    RemovedField { path: String, field: String },
+    /// A tagged union lost a variant
+    RemovedVariant { path: String, tag: String },
+    /// An enum lost a value
+    RemovedEnumValue { path: String, value: String },
+    /// A value changed type (including becoming or ceasing to be nullable)
+    TypeChanged {
+        path: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakingChange::RemovedDefinition { name } => write!(f, "{}: type removed", name),
+            BreakingChange::RemovedField { path, field } => {
+                write!(f, "{}: field `{}` removed", path, field)
+            }
+            BreakingChange::RemovedVariant { path, tag } => {
+                write!(f, "{}: variant `{}` removed", path, tag)
+            }
+            BreakingChange::RemovedEnumValue { path, value } => {
+                write!(f, "{}: enum value {} removed", path, value)
+            }
+            BreakingChange::TypeChanged { path, old, new } => {
+                write!(f, "{}: type changed from {} to {}", path, old, new)
+            }
+        }
+    }
+}
+
+/// Breaking changes between two versions of a schema document
+///
+/// `definitions` is a JSON pointer to the map of named types (e.g.
+/// `/components/schemas` for OpenAPI, `/$defs` for JSON Schema). Additions
+/// such as new types, fields, variants or enum values are not reported.
+pub fn breaking_changes(old: &Value, new: &Value, definitions: &str) -> Vec<BreakingChange> {
+    let empty = Map::new();
+    let old_defs = old
+        .pointer(definitions)
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let new_defs = new
+        .pointer(definitions)
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut changes = Vec::new();
+    for (name, old_schema) in old_defs {
+        match new_defs.get(name) {
+            Some(new_schema) => compare(name, old_schema, new_schema, &mut changes),
+            None => changes.push(BreakingChange::RemovedDefinition { name: name.clone() }),
+        }
+    }
+    changes
+}
+
+fn compare(path: &str, old: &Value, new: &Value, changes: &mut Vec<BreakingChange>) {
+    let (old_type, new_type) = (type_of(old), type_of(new));
+    if old_type != new_type {
+        changes.push(BreakingChange::TypeChanged {
+            path: path.to_string(),
+            old: old_type,
+            new: new_type,
+        });
+        return;
+    }
+
+    if let (Some(old_props), Some(new_props)) = (
+        old.get("properties").and_then(Value::as_object),
+        new.get("properties").and_then(Value::as_object),
+    ) {
+        for (field, old_prop) in old_props {
+            match new_props.get(field) {
+                Some(new_prop) => {
+                    compare(&format!("{}.{}", path, field), old_prop, new_prop, changes)
+                }
+                None => changes.push(BreakingChange::RemovedField {
+                    path: path.to_string(),
+                    field: field.clone(),
+                }),
+            }
+        }
+    }
+
+    if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+        compare(&format!("{}[]", path), old_items, new_items, changes);
+    }
+
+    if let (Some(old_values), Some(new_values)) = (
+        old.get("enum").and_then(Value::as_array),
+        new.get("enum").and_then(Value::as_array),
+    ) {
+        for value in old_values.iter().filter(|v| !new_values.contains(v)) {
+            changes.push(BreakingChange::RemovedEnumValue {
+                path: path.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+
+    for key in ["oneOf", "anyOf"] {
+        if let (Some(old_variants), Some(new_variants)) = (
+            old.get(key).and_then(Value::as_array),
+            new.get(key).and_then(Value::as_array),
+        ) {
+            compare_variants(path, old_variants, new_variants, changes);
+        }
+    }
+}
+
+/// Match union variants by their `type` tag, falling back to position for untagged unions
+fn compare_variants(
+    path: &str,
+    old_variants: &[Value],
+    new_variants: &[Value],
+    changes: &mut Vec<BreakingChange>,
+) {
+    for (index, old_variant) in old_variants.iter().enumerate() {
+        match variant_tag(old_variant) {
+            Some(tag) => {
+                match new_variants
+                    .iter()
+                    .find(|v| variant_tag(v).as_deref() == Some(tag.as_str()))
+                {
+                    Some(new_variant) => compare(
+                        &format!("{}.{}", path, tag),
+                        old_variant,
+                        new_variant,
+                        changes,
+                    ),
+                    None => changes.push(BreakingChange::RemovedVariant {
+                        path: path.to_string(),
+                        tag,
+                    }),
+                }
+            }
+            None => {
+                if let Some(new_variant) = new_variants.get(index) {
+                    compare(
+                        &format!("{}[{}]", path, index),
+                        old_variant,
+                        new_variant,
+                        changes,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Discriminator of a tagged enum variant (`{"type": {"const": "trade"}}` or a single-value enum)
+fn variant_tag(variant: &Value) -> Option<String> {
+    let tag = variant.get("properties")?.get("type")?;
+    let value = tag
+        .get("const")
+        .or_else(|| match tag.get("enum")?.as_array()?.as_slice() {
+            [single] => Some(single),
+            _ => None,
+        })?;
+    value.as_str().map(str::to_string)
+}
+
+/// Short description of a schema's type, e.g. `string`, `string|null`, `$ref:ApiOrder`
+fn type_of(schema: &Value) -> String {
+    if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+        return format!("$ref:{}", target.rsplit('/').next().unwrap_or(target));
+    }
+    match schema.get("type") {
+        Some(Value::String(t)) => t.clone(),
+        Some(Value::Array(types)) => {
+            let mut types: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            types.sort_unstable();
+            types.join("|")
+        }
+        _ if schema.get("oneOf").is_some() => "oneOf".to_string(),
+        _ if schema.get("anyOf").is_some() => "anyOf".to_string(),
+        _ => "any".to_string(),
+    }
+}
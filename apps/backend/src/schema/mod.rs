@@ -0,0 +1,167 @@
+//! Protocol schema generation and compatibility checks
+//!
+//! Produces the files committed under `packages/shared`:
+//! - `openapi.json`   REST API (OpenAPI 3)
+//! - `websocket.json` WebSocket messages (JSON Schema)
+//! - `asyncapi.json`  WebSocket channels and messages (AsyncAPI 3)
+
+pub mod asyncapi;
+pub mod compat;
+
+use schemars::{JsonSchema, SchemaGenerator};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use utoipa::OpenApi;
+
+use crate::api::rest::ApiDoc;
+use crate::models::api::{ClientMessage, ServerMessage};
+
+// Wrapper type to include both message types in the schema
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+enum WebSocketMessages {
+    Client(ClientMessage),
+    Server(ServerMessage),
+}
+
+/// A generated schema file and where its named type definitions live
+pub struct SchemaFile {
+    /// File name under `packages/shared`
+    pub name: &'static str,
+    /// JSON pointer to the map of named definitions
+    pub definitions: &'static str,
+    /// Pretty-printed file contents
+    pub contents: String,
+}
+
+impl SchemaFile {
+    /// Parsed file contents
+    pub fn value(&self) -> Value {
+        serde_json::from_str(&self.contents).expect("Generated schema is not valid JSON")
+    }
+}
+
+/// `packages/shared` in the workspace
+pub fn shared_dir() -> PathBuf {
+    // apps/backend -> apps -> workspace
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir
+        .parent()
+        .and_then(Path::parent)
+        .expect("Backend is not inside the workspace")
+        .join("packages/shared")
+}
+
+/// OpenAPI document for the REST API
+pub fn openapi() -> SchemaFile {
+    SchemaFile {
+        name: "openapi.json",
+        definitions: "/components/schemas",
+        contents: serde_json::to_string_pretty(&ApiDoc::openapi())
+            .expect("Failed to serialize OpenAPI spec"),
+    }
+}
+
+/// JSON Schema covering both WebSocket message directions
+pub fn websocket() -> SchemaFile {
+    // Create a schema generator and generate schemas for both types
+    let mut generator = SchemaGenerator::default();
+
+    // Generate subschemas for both message types to populate definitions
+    generator.subschema_for::<ClientMessage>();
+    generator.subschema_for::<ServerMessage>();
+
+    // Create root schema with all definitions
+    let root_schema = generator.into_root_schema_for::<WebSocketMessages>();
+
+    SchemaFile {
+        name: "websocket.json",
+        definitions: "/$defs",
+        contents: serde_json::to_string_pretty(&root_schema).expect("Failed to serialize schema"),
+    }
+}
+
+/// AsyncAPI document describing WebSocket channels and messages
+pub fn asyncapi() -> SchemaFile {
+    let document = asyncapi::document(&websocket().value());
+
+    SchemaFile {
+        name: "asyncapi.json",
+        definitions: "/components/schemas",
+        contents: serde_json::to_string_pretty(&document)
+            .expect("Failed to serialize AsyncAPI document"),
+    }
+}
+
+/// Result of comparing a generated schema with the committed file
+pub enum Check {
+    /// Committed file matches the generated one
+    UpToDate,
+    /// Generated schema differs only in backward-compatible ways
+    Compatible,
+    /// Generated schema would break clients built from the committed file
+    Breaking(Vec<compat::BreakingChange>),
+    /// No committed file to compare against
+    Missing,
+}
+
+impl SchemaFile {
+    /// Path of the committed file
+    pub fn path(&self) -> PathBuf {
+        shared_dir().join(self.name)
+    }
+
+    /// Write the file to `packages/shared`
+    pub fn write(&self) -> std::io::Result<PathBuf> {
+        let path = self.path();
+        std::fs::create_dir_all(shared_dir())?;
+        std::fs::write(&path, &self.contents)?;
+        Ok(path)
+    }
+
+    /// Compare with the committed file without writing
+    pub fn check(&self) -> Check {
+        let Ok(committed) = std::fs::read_to_string(self.path()) else {
+            return Check::Missing;
+        };
+        if committed == self.contents {
+            return Check::UpToDate;
+        }
+
+        let committed: Value = match serde_json::from_str(&committed) {
+            Ok(value) => value,
+            Err(_) => return Check::Missing,
+        };
+        let changes = compat::breaking_changes(&committed, &self.value(), self.definitions);
+        if changes.is_empty() {
+            Check::Compatible
+        } else {
+            Check::Breaking(changes)
+        }
+    }
+}
+
+/// Check each file, printing a report; returns false if any change is breaking
+///
+/// Used by the generator binaries' `--check` mode.
+pub fn report(files: &[SchemaFile]) -> bool {
+    let mut ok = true;
+    for file in files {
+        match file.check() {
+            Check::UpToDate => println!("✅ {} is up to date", file.name),
+            Check::Compatible => println!(
+                "⚠️  {} is out of date (backward-compatible changes only); run `just types`",
+                file.name
+            ),
+            Check::Missing => println!("⚠️  {} has not been generated yet", file.name),
+            Check::Breaking(changes) => {
+                ok = false;
+                println!("❌ {} has {} breaking change(s):", file.name, changes.len());
+                for change in changes {
+                    println!("   - {}", change);
+                }
+            }
+        }
+    }
+    ok
+}
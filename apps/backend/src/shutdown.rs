@@ -0,0 +1,102 @@
+// graceful shutdown: stop taking work, finish what's queued, then exit
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Longest wait for each background task to finish its queue on shutdown
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tells long-lived connections that the server is going away
+///
+/// Cloned into every handler through `AppState`. Once triggered it stays
+/// triggered, so connections opened during shutdown are closed right away.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            tx: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Start shutting down
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Resolves once shutdown has been triggered
+    pub fn triggered(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut rx = self.tx.subscribe();
+        async move {
+            let _ = rx.wait_for(|triggered| *triggered).await;
+        }
+    }
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM where there are signals
+pub async fn terminate() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let sigterm = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                log::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let sigterm = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => log::info!("Received Ctrl-C"),
+        _ = sigterm => log::info!("Received SIGTERM"),
+    }
+}
+
+/// Wait up to `DRAIN_TIMEOUT` for a background task to run out of work
+///
+/// Returns whether it finished in time; a task still running is left behind
+/// and dies with the process.
+pub async fn drain<T>(name: &str, handle: JoinHandle<T>) -> bool {
+    match tokio::time::timeout(DRAIN_TIMEOUT, handle).await {
+        Ok(Ok(_)) => {
+            log::info!("{} finished", name);
+            true
+        }
+        Ok(Err(e)) => {
+            log::error!("{} failed while shutting down: {}", name, e);
+            false
+        }
+        Err(_) => {
+            log::warn!("{} still busy after {:?}, giving up", name, DRAIN_TIMEOUT);
+            false
+        }
+    }
+}
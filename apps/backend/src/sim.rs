@@ -0,0 +1,281 @@
+// synthetic trading for the in-memory simulator
+
+use crate::db::Db;
+use crate::engine::ladder::LadderLayout;
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{
+    EngineRequest, Market, Order, OrderStatus, OrderType, Side, TimeInForce, TriggerSource,
+};
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+/// Account quoting both sides of every simulated market
+pub const MAKER_ADDRESS: &str = "sim-maker";
+
+/// Account taking the maker's quotes with market orders
+pub const TAKER_ADDRESS: &str = "sim-taker";
+
+/// How often the maker requotes and the taker may trade
+pub const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whole tokens of each kind credited to the synthetic accounts
+pub const FUNDING_UNITS: u128 = 1_000_000_000;
+
+/// Price levels quoted on each side of the mid
+const LEVELS: u128 = 5;
+
+/// Chance that the taker trades on a tick
+const TAKER_PROBABILITY: f64 = 0.6;
+
+/// Starting mid prices, in whole quote tokens, of base tokens with a known price
+const REFERENCE_PRICES: [(&str, u128); 2] = [("BTC", 90_000), ("ETH", 3_000)];
+
+/// Starting mid price, in whole quote tokens, of any other base token
+const DEFAULT_PRICE: u128 = 100;
+
+/// A market the synthetic trader keeps busy
+#[derive(Debug, Clone)]
+pub struct SimMarket {
+    pub market: Market,
+    /// Current mid price in quote atoms, always a multiple of `step`
+    pub mid: u128,
+    /// Distance between quoted levels, and the most the mid moves per tick
+    pub step: u128,
+    /// Highest price the market's ladder accepts, if bounded
+    pub max_price: Option<u128>,
+}
+
+impl SimMarket {
+    /// Start at the middle of a bounded ladder, or otherwise at the base
+    /// token's reference price, moving about 2 bps a tick
+    pub fn new(market: Market, quote_decimals: u8, layout: LadderLayout) -> Self {
+        let tick = market.tick_size.max(1);
+        let (mid, max_price) = match layout {
+            LadderLayout::Array {
+                min_price,
+                max_price,
+                ..
+            } => ((min_price + max_price) / 2, Some(max_price)),
+            LadderLayout::Tree => {
+                let units = REFERENCE_PRICES
+                    .iter()
+                    .find(|(ticker, _)| *ticker == market.base_ticker)
+                    .map_or(DEFAULT_PRICE, |&(_, price)| price);
+                (units * 10u128.pow(quote_decimals as u32), None)
+            }
+        };
+        let step = (mid / 5_000 / tick).max(1) * tick;
+        Self {
+            mid: (mid / step).max(1) * step,
+            step,
+            max_price,
+            market,
+        }
+    }
+
+    /// Move the mid up or down a step or stay, keeping every level in range
+    fn walk(&mut self, rng: &mut impl Rng) {
+        let lowest = self.step * (LEVELS + 1);
+        let highest = self.max_price.map_or(u128::MAX, |max| {
+            max.saturating_sub(self.step * (LEVELS + 1))
+        });
+        self.mid = match rng.gen_range(0..3) {
+            0 => self.mid.saturating_sub(self.step),
+            1 => self.mid.saturating_add(self.step),
+            _ => self.mid,
+        }
+        .clamp(lowest, highest.max(lowest));
+    }
+
+    /// A random order size of one to `lots` minimum sizes, rounded to the lot size
+    fn size(&self, rng: &mut impl Rng, lots: u128) -> u128 {
+        let lot = self.market.lot_size.max(1);
+        let min = self.market.min_size.max(lot);
+        let size = rng.gen_range(min..=min * lots);
+        (size / lot).max(1) * lot
+    }
+}
+
+/// Keeps simulated markets moving: a maker quoting a ladder around a
+/// random-walking mid and a taker hitting it
+///
+/// Orders go through the engine queue like any client's, so the frontend sees
+/// books, trades and candles that look like a live exchange.
+pub struct SyntheticTrader {
+    engine_tx: mpsc::Sender<EngineRequest>,
+    markets: Vec<SimMarket>,
+    /// The maker's quotes from the last tick, replaced on the next
+    quotes: Vec<Uuid>,
+    rng: StdRng,
+}
+
+impl SyntheticTrader {
+    pub fn new(engine_tx: mpsc::Sender<EngineRequest>, markets: Vec<SimMarket>) -> Self {
+        Self {
+            engine_tx,
+            markets,
+            quotes: Vec::new(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Same moves every run, for tests
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Credit the maker and taker `FUNDING_UNITS` of every token they trade
+    pub async fn fund(&self, db: &Db) -> Result<()> {
+        for address in [MAKER_ADDRESS, TAKER_ADDRESS] {
+            db.create_user(address.to_string()).await?;
+            for market in &self.markets {
+                for ticker in [&market.market.base_ticker, &market.market.quote_ticker] {
+                    let token = db.get_token(ticker).await?;
+                    let amount = FUNDING_UNITS * 10u128.pow(token.decimals as u32);
+                    db.add_balance(address, ticker, amount).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Trade every `TICK_INTERVAL` until the engine stops
+    pub async fn run(mut self) {
+        let mut ticks = tokio::time::interval(TICK_INTERVAL);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticks.tick().await;
+            if let Err(e) = self.tick().await {
+                match e {
+                    ExchangeError::EngineSendFailed | ExchangeError::EngineReceiveFailed => return,
+                    e => log::warn!("Synthetic trader: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Requote every market, then maybe take from one
+    ///
+    /// New quotes go in before the old ones come out so the book is never
+    /// empty in between. The mid moves at most one step, so new quotes never
+    /// cross old ones.
+    pub async fn tick(&mut self) -> Result<()> {
+        let previous = std::mem::take(&mut self.quotes);
+        for i in 0..self.markets.len() {
+            self.markets[i].walk(&mut self.rng);
+            let market = self.markets[i].clone();
+            for level in 1..=LEVELS {
+                for (side, price) in [
+                    (Side::Buy, market.mid - level * market.step),
+                    (Side::Sell, market.mid + level * market.step),
+                ] {
+                    let size = market.size(&mut self.rng, 10);
+                    let order = order(
+                        MAKER_ADDRESS,
+                        &market.market,
+                        side,
+                        OrderType::Limit,
+                        price,
+                        size,
+                    );
+                    let id = order.id;
+                    match self.place(order).await {
+                        Ok(()) => self.quotes.push(id),
+                        Err(e) => log::debug!("{}: maker order rejected: {}", market.market.id, e),
+                    }
+                }
+            }
+        }
+        for order_id in previous {
+            // Quotes filled since the last tick are already gone
+            let _ = self.cancel(order_id).await;
+        }
+
+        if self.markets.is_empty() || !self.rng.gen_bool(TAKER_PROBABILITY) {
+            return Ok(());
+        }
+        let market = self.markets[self.rng.gen_range(0..self.markets.len())].clone();
+        let (side, limit) = if self.rng.gen_bool(0.5) {
+            (Side::Buy, market.mid + LEVELS * market.step)
+        } else {
+            (Side::Sell, market.mid - LEVELS * market.step)
+        };
+        let size = market.size(&mut self.rng, 3);
+        self.place(order(
+            TAKER_ADDRESS,
+            &market.market,
+            side,
+            OrderType::Market,
+            limit,
+            size,
+        ))
+        .await
+    }
+
+    async fn place(&self, order: Order) -> Result<()> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.engine_tx
+            .send(EngineRequest::PlaceOrder {
+                order,
+                span: tracing::Span::current(),
+                response_tx,
+            })
+            .await
+            .map_err(|_| ExchangeError::EngineSendFailed)?;
+        response_rx
+            .await
+            .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+        Ok(())
+    }
+
+    async fn cancel(&self, order_id: Uuid) -> Result<()> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.engine_tx
+            .send(EngineRequest::CancelOrder {
+                order_id,
+                user_address: MAKER_ADDRESS.to_string(),
+                span: tracing::Span::current(),
+                response_tx,
+            })
+            .await
+            .map_err(|_| ExchangeError::EngineSendFailed)?;
+        response_rx
+            .await
+            .map_err(|_| ExchangeError::EngineReceiveFailed)??;
+        Ok(())
+    }
+}
+
+fn order(
+    user_address: &str,
+    market: &Market,
+    side: Side,
+    order_type: OrderType,
+    price: u128,
+    size: u128,
+) -> Order {
+    let now = Utc::now();
+    Order {
+        id: Uuid::new_v4(),
+        user_address: user_address.to_string(),
+        market_id: market.id.clone(),
+        side,
+        order_type,
+        price,
+        size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: now,
+        updated_at: now,
+        cancel_reason: None,
+        trigger_price: None,
+        trigger_source: TriggerSource::LastTrade,
+        time_in_force: TimeInForce::Gtc,
+        expires_at: None,
+    }
+}
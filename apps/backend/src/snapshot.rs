@@ -0,0 +1,506 @@
+// point-in-time copies of a whole exchange, restored into fresh environments
+
+use crate::archive::{
+    data_path, day_bounds, read_trades, ArchiveFile, ArchiveStore, Dataset, RESTORE_BATCH_SIZE,
+};
+use crate::db::orders::ORDER_PARTITION_MONTHS_AHEAD;
+use crate::db::Db;
+use anyhow::{bail, ensure, Context};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, Row};
+
+/// Version of the manifest and file layout, bumped on every breaking change
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// Days of ClickHouse trades a snapshot carries unless told otherwise
+pub const DEFAULT_TRADE_DAYS: u64 = 7;
+
+/// Bookkeeping of the migration tool, which the target has from its own migrations
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+/// One Postgres table, as a binary `COPY` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotTable {
+    pub table: String,
+    /// Columns in the order the file holds them; generated columns are left out
+    pub columns: Vec<String>,
+    /// Location relative to the store root
+    pub path: String,
+    pub rows: u64,
+    pub bytes: u64,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+}
+
+/// Where a Postgres sequence stood; `None` if it was never used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSequence {
+    pub sequence: String,
+    pub last_value: Option<i64>,
+}
+
+/// One day of ClickHouse trades, in the archive's Parquet layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotTrades {
+    pub date: NaiveDate,
+    pub file: ArchiveFile,
+}
+
+/// Lists everything a snapshot holds; written last, so a snapshot without
+/// one is incomplete
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub schema_version: u32,
+    pub name: String,
+    /// When the transaction the tables were copied in began
+    pub taken_at: DateTime<Utc>,
+    /// Latest migration of the source; only a database at the same one can take the tables
+    pub migration: i64,
+    pub tables: Vec<SnapshotTable>,
+    pub sequences: Vec<SnapshotSequence>,
+    pub trades: Vec<SnapshotTrades>,
+}
+
+impl SnapshotManifest {
+    pub fn table(&self, table: &str) -> Option<&SnapshotTable> {
+        self.tables.iter().find(|t| t.table == table)
+    }
+
+    /// Postgres rows across all tables
+    pub fn rows(&self) -> u64 {
+        self.tables.iter().map(|t| t.rows).sum()
+    }
+}
+
+/// What a restore loaded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub tables: usize,
+    pub rows: u64,
+    pub trades: u64,
+    /// Days left out because ClickHouse already had trades for them
+    pub skipped_days: Vec<NaiveDate>,
+}
+
+/// Location of a snapshot's manifest, e.g. `staging-1/manifest.json`
+pub fn manifest_path(name: &str) -> String {
+    format!("{}/manifest.json", name)
+}
+
+/// Location of a table's file, e.g. `staging-1/postgres/orders.bin`
+pub fn table_path(name: &str, table: &str) -> String {
+    format!("{}/postgres/{}.bin", name, table)
+}
+
+/// Location of a day's trades, e.g.
+/// `staging-1/clickhouse/trades/date=2025-11-20/trades.parquet`
+pub fn trades_path(name: &str, date: NaiveDate) -> String {
+    format!("{}/clickhouse/{}", name, data_path(Dataset::Trades, date))
+}
+
+/// Snapshot names become directories, so they are kept to letters, digits, `-`, `_` and `.`
+pub fn validate_name(name: &str) -> anyhow::Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    ensure!(
+        valid,
+        "Invalid snapshot name '{}': use letters, digits, '-', '_' and '.'",
+        name
+    );
+    Ok(())
+}
+
+/// The manifest of a snapshot, or `None` if there is no complete one by that name
+pub async fn manifest(
+    store: &ArchiveStore,
+    name: &str,
+) -> anyhow::Result<Option<SnapshotManifest>> {
+    store.get_json(&manifest_path(name)).await
+}
+
+/// Every complete snapshot in the store, oldest first
+pub async fn list(store: &ArchiveStore) -> anyhow::Result<Vec<SnapshotManifest>> {
+    let mut manifests = Vec::new();
+    for name in store.list_dirs("").await? {
+        if let Some(manifest) = manifest(store, &name).await? {
+            manifests.push(manifest);
+        }
+    }
+    manifests.sort_by_key(|m| m.taken_at);
+    Ok(manifests)
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn column_list(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|c| quote(c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Takes and restores snapshots of the whole exchange
+///
+/// A snapshot holds every Postgres table, copied in one repeatable-read
+/// transaction so balances, resting orders and the ledger agree with each
+/// other, the sequences as they stood, and the last few days of ClickHouse
+/// trades. Candles are not copied; the candle views rebuild them from the
+/// restored trades. Restoring is meant for a freshly migrated environment
+/// whose backend isn't running yet, since the engine loads resting orders
+/// and balances only at startup.
+pub struct Snapshotter {
+    db: Db,
+    store: ArchiveStore,
+}
+
+impl Snapshotter {
+    pub fn new(db: Db, store: ArchiveStore) -> Self {
+        Self { db, store }
+    }
+
+    /// Snapshot the exchange under `name`, with the last `trade_days` days of trades
+    pub async fn take(&self, name: &str, trade_days: u64) -> anyhow::Result<SnapshotManifest> {
+        validate_name(name)?;
+        ensure!(
+            self.db.memory.is_none(),
+            "An in-memory exchange has no databases to snapshot"
+        );
+        if manifest(&self.store, name).await?.is_some() {
+            bail!("Snapshot '{}' already exists", name);
+        }
+
+        let mut tx = self.db.postgres.begin().await?;
+        // Every table is read as of the transaction's first query
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        let taken_at: DateTime<Utc> = sqlx::query_scalar("SELECT now()")
+            .fetch_one(&mut *tx)
+            .await?;
+        let migration = latest_migration(&mut tx).await?;
+
+        let mut tables = Vec::new();
+        for table in list_tables(&mut tx).await? {
+            let columns = list_columns(&mut tx, &table).await?;
+            let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", quote(&table)))
+                .fetch_one(&mut *tx)
+                .await?;
+            let path = table_path(name, &table);
+            let statement = format!(
+                "COPY (SELECT {} FROM {}) TO STDOUT (FORMAT binary)",
+                column_list(&columns),
+                quote(&table)
+            );
+            let chunks = tx.copy_out_raw(&statement).await?;
+            let (bytes, sha256) = self
+                .store
+                .upload_stream(&path, chunks)
+                .await
+                .with_context(|| format!("Failed to snapshot {}", table))?;
+            tables.push(SnapshotTable {
+                table,
+                columns,
+                path,
+                rows: rows as u64,
+                bytes,
+                sha256,
+            });
+        }
+        let sequences = list_sequences(&mut tx).await?;
+        tx.commit().await?;
+
+        // Trades up to the moment the tables were read, so none refer to
+        // orders the snapshot doesn't have
+        let mut trades = Vec::new();
+        let today = taken_at.date_naive();
+        for days_ago in (0..trade_days).rev() {
+            let date = today - Days::new(days_ago);
+            let (from, to) = day_bounds(date);
+            let to = to.min(taken_at.timestamp());
+            let rows = self.db.count_trades_between(from, to).await?;
+            let path = trades_path(name, date);
+            let (bytes, sha256) = self
+                .store
+                .upload(&path, self.db.archive_trades(from, to)?)
+                .await
+                .with_context(|| format!("Failed to snapshot trades of {}", date))?;
+            trades.push(SnapshotTrades {
+                date,
+                file: ArchiveFile {
+                    dataset: Dataset::Trades,
+                    path,
+                    rows,
+                    bytes,
+                    sha256,
+                },
+            });
+        }
+
+        let manifest = SnapshotManifest {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            name: name.to_string(),
+            taken_at,
+            migration,
+            tables,
+            sequences,
+            trades,
+        };
+        self.store.put_json(&manifest_path(name), &manifest).await?;
+        Ok(manifest)
+    }
+
+    /// Load a snapshot into this environment's databases
+    ///
+    /// The Postgres tables are replaced in one transaction, so a failed
+    /// restore leaves them as they were. Refuses a database that already
+    /// lists tokens, and ClickHouse days that already have trades, unless
+    /// `force` is set; forced, the tables are replaced and those days are
+    /// skipped rather than counted twice.
+    pub async fn restore(&self, name: &str, force: bool) -> anyhow::Result<RestoreReport> {
+        let manifest = manifest(&self.store, name)
+            .await?
+            .with_context(|| format!("No snapshot named '{}'", name))?;
+        if manifest.schema_version != SNAPSHOT_SCHEMA_VERSION {
+            bail!(
+                "Snapshot '{}' has schema version {}, expected {}",
+                name,
+                manifest.schema_version,
+                SNAPSHOT_SCHEMA_VERSION
+            );
+        }
+        ensure!(
+            self.db.memory.is_none(),
+            "An in-memory exchange has no databases to restore into"
+        );
+
+        let mut tx = self.db.postgres.begin().await?;
+        let migration = latest_migration(&mut tx).await?;
+        if migration != manifest.migration {
+            bail!(
+                "Snapshot '{}' was taken at migration {}, but this database is at {}; restore it with the release that took it",
+                name,
+                manifest.migration,
+                migration
+            );
+        }
+        let tokens: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tokens")
+            .fetch_one(&mut *tx)
+            .await?;
+        if tokens > 0 && !force {
+            bail!(
+                "This database already lists {} tokens; restore into a fresh environment, or force it to replace everything",
+                tokens
+            );
+        }
+
+        // Checked before anything is written, so a refusal changes nothing
+        let mut report = RestoreReport::default();
+        for day in &manifest.trades {
+            let (from, to) = day_bounds(day.date);
+            let existing = self.db.count_trades_between(from, to).await?;
+            if existing > 0 {
+                if !force {
+                    bail!(
+                        "ClickHouse already has {} trades for {}; restoring would count them twice",
+                        existing,
+                        day.date
+                    );
+                }
+                report.skipped_days.push(day.date);
+            }
+        }
+
+        // Foreign keys and triggers stay off while tables load in any order,
+        // and the rows seeded by migrations make way for the snapshot's
+        sqlx::query("SET LOCAL session_replication_role = replica")
+            .execute(&mut *tx)
+            .await?;
+        let tables = manifest
+            .tables
+            .iter()
+            .map(|t| quote(&t.table))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sqlx::query(&format!("TRUNCATE {}", tables))
+            .execute(&mut *tx)
+            .await?;
+        for table in &manifest.tables {
+            report.rows += self
+                .load_table(&mut tx, table)
+                .await
+                .with_context(|| format!("Failed to restore {}", table.table))?;
+            report.tables += 1;
+        }
+
+        for sequence in &manifest.sequences {
+            match sequence.last_value {
+                Some(value) => {
+                    sqlx::query("SELECT setval($1::text::regclass, $2, true)")
+                        .bind(quote(&sequence.sequence))
+                        .bind(value)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                None => {
+                    sqlx::query(&format!(
+                        "ALTER SEQUENCE {} RESTART",
+                        quote(&sequence.sequence)
+                    ))
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+
+        // Orders older than the target's partitions landed in the default
+        // one; this creates their months and moves them there
+        sqlx::query(
+            "SELECT ensure_order_partitions(COALESCE((SELECT MIN(created_at) FROM orders), now()), $1)",
+        )
+        .bind(ORDER_PARTITION_MONTHS_AHEAD)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        for day in &manifest.trades {
+            if report.skipped_days.contains(&day.date) {
+                continue;
+            }
+            let trades = read_trades(self.store.download(&day.file).await?)?;
+            if trades.len() as u64 != day.file.rows {
+                bail!(
+                    "{} has {} trades but the manifest lists {}",
+                    day.file.path,
+                    trades.len(),
+                    day.file.rows
+                );
+            }
+            for batch in trades.chunks(RESTORE_BATCH_SIZE) {
+                self.db.insert_trades_to_clickhouse(batch).await?;
+            }
+            report.trades += trades.len() as u64;
+        }
+
+        Ok(report)
+    }
+
+    /// Stream one table's file into it, checking it against the manifest
+    async fn load_table(
+        &self,
+        conn: &mut PgConnection,
+        table: &SnapshotTable,
+    ) -> anyhow::Result<u64> {
+        let mut chunks = self.store.download_stream(&table.path).await?;
+        let statement = format!(
+            "COPY {} ({}) FROM STDIN (FORMAT binary)",
+            quote(&table.table),
+            column_list(&table.columns)
+        );
+        // Dropped early on an error, the copy is failed and the transaction with it
+        let mut copy = conn.copy_in_raw(&statement).await?;
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            hasher.update(&chunk);
+            copy.send(chunk).await?;
+        }
+
+        let sha256 = hex::encode(hasher.finalize());
+        if sha256 != table.sha256 {
+            let _ = copy.abort("snapshot file is corrupt").await;
+            bail!(
+                "{} is corrupt: SHA-256 {} but the manifest lists {}",
+                table.path,
+                sha256,
+                table.sha256
+            );
+        }
+        let rows = copy.finish().await?;
+        ensure!(
+            rows == table.rows,
+            "{} loaded {} rows but the manifest lists {}",
+            table.path,
+            rows,
+            table.rows
+        );
+        Ok(rows)
+    }
+}
+
+async fn latest_migration(conn: &mut PgConnection) -> anyhow::Result<i64> {
+    let version: Option<i64> = sqlx::query_scalar(&format!(
+        "SELECT MAX(version) FROM {} WHERE success",
+        MIGRATIONS_TABLE
+    ))
+    .fetch_one(conn)
+    .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// The application's tables; partitions are copied through their parent
+async fn list_tables(conn: &mut PgConnection) -> anyhow::Result<Vec<String>> {
+    let tables = sqlx::query_scalar(
+        r#"
+        SELECT c.relname::text
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = 'public'
+          AND c.relkind IN ('r', 'p')
+          AND NOT c.relispartition
+          AND c.relname <> $1
+        ORDER BY c.relname
+        "#,
+    )
+    .bind(MIGRATIONS_TABLE)
+    .fetch_all(conn)
+    .await?;
+    Ok(tables)
+}
+
+/// A table's columns in order, without the generated ones `COPY` can't write
+async fn list_columns(conn: &mut PgConnection, table: &str) -> anyhow::Result<Vec<String>> {
+    let columns = sqlx::query_scalar(
+        r#"
+        SELECT attname::text
+        FROM pg_attribute
+        WHERE attrelid = $1::text::regclass
+          AND attnum > 0
+          AND NOT attisdropped
+          AND attgenerated = ''
+        ORDER BY attnum
+        "#,
+    )
+    .bind(quote(table))
+    .fetch_all(conn)
+    .await?;
+    Ok(columns)
+}
+
+async fn list_sequences(conn: &mut PgConnection) -> anyhow::Result<Vec<SnapshotSequence>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT sequencename::text AS sequence, last_value
+        FROM pg_sequences
+        WHERE schemaname = 'public'
+        ORDER BY sequencename
+        "#,
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(rows
+        .iter()
+        .map(|row| SnapshotSequence {
+            sequence: row.get("sequence"),
+            last_value: row.get("last_value"),
+        })
+        .collect())
+}
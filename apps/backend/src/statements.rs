@@ -0,0 +1,231 @@
+// monthly account statements: balances, fills, fees, deposits and withdrawals per user
+
+use crate::db::Db;
+use crate::models::api::ApiStatement;
+use crate::models::domain::{Balance, Statement, StatementBalance, StatementMarket, SystemAccount};
+use crate::webhooks::WebhookDispatcher;
+use chrono::{DateTime, Datelike, Months, TimeZone, Utc};
+use exchange_protocol::events::BusEvent;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+/// Time given to a closed month's trades to reach ClickHouse
+const STATEMENT_DELAY_SECS: i64 = 60;
+
+/// How often the statement job checks for a closed month
+const STATEMENT_INTERVAL_SECS: u64 = 60;
+
+/// One user's deposits, withdrawals and fees in one token over a month, in its atoms
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenFlows {
+    pub deposited: u128,
+    pub withdrawn: u128,
+    /// Trading fees less maker rebates
+    pub fees: i128,
+}
+
+/// Flows keyed by (user_address, token_ticker)
+pub type StatementFlows = HashMap<(String, String), TokenFlows>;
+
+/// Start of the calendar month (UTC) `at` falls in
+pub fn month_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(at)
+}
+
+/// Start of the month after the one starting at `start`
+pub fn next_month(start: DateTime<Utc>) -> DateTime<Utc> {
+    start.checked_add_months(Months::new(1)).unwrap_or(start)
+}
+
+/// Start of the month before the one starting at `start`
+pub fn previous_month(start: DateTime<Utc>) -> DateTime<Utc> {
+    start.checked_sub_months(Months::new(1)).unwrap_or(start)
+}
+
+/// Build every user's statement for the month starting at `period_start`
+///
+/// `closing` holds balances as of `balances_at`, and `opening` the month
+/// before's closing balances keyed by (user_address, token_ticker), or `None`
+/// when that month has no statements. Users with nothing held or moved in
+/// the month, and the system accounts, get no statement.
+pub fn build_statements(
+    period_start: DateTime<Utc>,
+    balances_at: DateTime<Utc>,
+    closing: &[Balance],
+    opening: Option<&HashMap<(String, String), u128>>,
+    flows: &StatementFlows,
+    fills: &[(String, StatementMarket)],
+) -> Vec<Statement> {
+    let mut tokens: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut closing_balances = HashMap::new();
+    for balance in closing.iter().filter(|b| b.amount > 0) {
+        tokens
+            .entry(&balance.user_address)
+            .or_default()
+            .insert(&balance.token_ticker);
+        closing_balances.insert(
+            (balance.user_address.as_str(), balance.token_ticker.as_str()),
+            balance.amount,
+        );
+    }
+    let opened = opening
+        .into_iter()
+        .flatten()
+        .filter(|(_, amount)| **amount > 0);
+    for ((user_address, token_ticker), _) in opened.chain(flows.keys().map(|key| (key, &0))) {
+        tokens.entry(user_address).or_default().insert(token_ticker);
+    }
+
+    let mut markets: BTreeMap<&str, Vec<StatementMarket>> = BTreeMap::new();
+    for (user_address, market) in fills {
+        markets
+            .entry(user_address)
+            .or_default()
+            .push(market.clone());
+        tokens.entry(user_address).or_default();
+    }
+
+    let period_end = next_month(period_start);
+    tokens
+        .into_iter()
+        .filter(|(user_address, _)| SystemAccount::from_address(user_address).is_none())
+        .map(|(user_address, tickers)| {
+            let balances = tickers
+                .into_iter()
+                .map(|token_ticker| {
+                    let key = (user_address.to_string(), token_ticker.to_string());
+                    let flows = flows.get(&key).cloned().unwrap_or_default();
+                    StatementBalance {
+                        token_ticker: token_ticker.to_string(),
+                        opening_balance: opening
+                            .map(|opening| opening.get(&key).copied().unwrap_or(0)),
+                        closing_balance: closing_balances
+                            .get(&(user_address, token_ticker))
+                            .copied()
+                            .unwrap_or(0),
+                        deposited: flows.deposited,
+                        withdrawn: flows.withdrawn,
+                        fees: flows.fees,
+                    }
+                })
+                .collect();
+            let mut markets = markets.remove(user_address).unwrap_or_default();
+            markets.sort_by(|a, b| a.market_id.cmp(&b.market_id));
+
+            Statement {
+                user_address: user_address.to_string(),
+                period_start,
+                period_end,
+                balances_at,
+                balances,
+                markets,
+            }
+        })
+        .collect()
+}
+
+/// Background job closing each calendar month once with every user's statement
+///
+/// Deposits, withdrawals and fees come from PostgreSQL and fills from
+/// ClickHouse. Nothing records balances over time, so closing balances are
+/// taken when the month is closed, normally within a couple of minutes of
+/// its end, and each month opens with the one before's closing balances.
+/// Statements are stored for the API and, when webhooks are set, posted to
+/// each user's webhooks.
+pub struct StatementGenerator {
+    db: Db,
+    webhooks: Option<WebhookDispatcher>,
+}
+
+impl StatementGenerator {
+    pub fn new(db: Db) -> Self {
+        Self { db, webhooks: None }
+    }
+
+    /// Post each statement to its user's webhooks once generated
+    pub fn with_webhooks(mut self, webhooks: WebhookDispatcher) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Close months as they end
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(STATEMENT_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.close_months(Utc::now()).await {
+                log::error!("Generating statements failed: {:#}", e);
+            }
+        }
+    }
+
+    /// Generate statements for every month ended by `now` not closed yet,
+    /// oldest first, or only the last one if none ever was; returns how many
+    /// statements were generated
+    pub async fn close_months(&self, now: DateTime<Utc>) -> anyhow::Result<usize> {
+        let current = month_start(now - chrono::Duration::seconds(STATEMENT_DELAY_SECS));
+        let mut period_start = match self.db.last_statement_period().await? {
+            Some(last) => next_month(last),
+            None => previous_month(current),
+        };
+
+        let mut generated = 0;
+        while period_start < current {
+            let statements = self.close_month(period_start).await?;
+            log::info!(
+                "Generated {} statements for the month from {}",
+                statements.len(),
+                period_start
+            );
+            generated += statements.len();
+            if let Some(webhooks) = &self.webhooks {
+                let recipients = statements
+                    .into_iter()
+                    .map(|statement| {
+                        let user_address = statement.user_address.clone();
+                        let statement: ApiStatement = statement.into();
+                        (user_address, BusEvent::Statement(statement))
+                    })
+                    .collect();
+                webhooks.send(recipients).await;
+            }
+            period_start = next_month(period_start);
+        }
+        Ok(generated)
+    }
+
+    /// Generate and store every user's statement for the month starting at
+    /// `period_start`
+    pub async fn close_month(&self, period_start: DateTime<Utc>) -> anyhow::Result<Vec<Statement>> {
+        let period_end = next_month(period_start);
+        let balances_at = Utc::now();
+        let closing = self.db.list_balances().await?;
+        let opening = self
+            .db
+            .get_statement_closing_balances(previous_month(period_start))
+            .await?;
+        let flows = self
+            .db
+            .get_statement_flows(period_start, period_end)
+            .await?;
+        let fills = self
+            .db
+            .get_statement_fills(period_start.timestamp(), period_end.timestamp())
+            .await?;
+
+        let statements = build_statements(
+            period_start,
+            balances_at,
+            &closing,
+            opening.as_ref(),
+            &flows,
+            &fills,
+        );
+        self.db
+            .record_statements(period_start, period_end, balances_at, &statements)
+            .await?;
+        Ok(statements)
+    }
+}
@@ -0,0 +1,53 @@
+// sub-accounts: named accounts of a master user with isolated balances and orders
+
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Balance, SubAccount, SystemAccount};
+use std::collections::BTreeMap;
+
+/// Most sub-accounts a single user may open
+pub const MAX_SUB_ACCOUNTS_PER_USER: usize = 20;
+
+/// Sub-account names are 1-32 ASCII letters, digits, '_' or '-'
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !(1..=32).contains(&name.len()) || !valid_chars {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Invalid sub-account name '{}': use 1-32 letters, digits, '_' or '-'",
+                name
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Only ordinary top-level users may open sub-accounts
+pub fn validate_master(master_address: &str) -> Result<()> {
+    if SubAccount::master_of(master_address).is_some()
+        || SystemAccount::from_address(master_address).is_some()
+    {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!("'{}' can't open sub-accounts", master_address),
+        });
+    }
+    Ok(())
+}
+
+/// Total amount and open interest per token over a group of accounts, by ticker
+pub fn aggregate_balances(balances: &[Balance]) -> Vec<(String, u128, u128)> {
+    let mut totals: BTreeMap<&str, (u128, u128)> = BTreeMap::new();
+    for balance in balances {
+        let (amount, open_interest) = totals.entry(&balance.token_ticker).or_default();
+        *amount += balance.amount;
+        *open_interest += balance.open_interest;
+    }
+
+    totals
+        .into_iter()
+        .map(|(token_ticker, (amount, open_interest))| {
+            (token_ticker.to_string(), amount, open_interest)
+        })
+        .collect()
+}
@@ -0,0 +1,319 @@
+// trade surveillance: wash trading, spoofing and self-matching over closed windows
+
+use crate::db::Db;
+use crate::models::domain::{DepthMetrics, Order, Side, SurveillanceKind, TradePair};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+/// Length of the windows trades are surveilled over
+pub const SURVEILLANCE_WINDOW_SECS: i64 = 60 * 60;
+
+/// Closed windows surveillance looks back over for ones not yet surveilled
+pub const SURVEILLANCE_LOOKBACK_WINDOWS: i64 = 24;
+
+/// Time given to a closed window's trades and depth samples to reach ClickHouse
+const SURVEILLANCE_DELAY_SECS: i64 = 60;
+
+/// How often surveillance checks for closed windows
+const SURVEILLANCE_INTERVAL_SECS: u64 = 5 * 60;
+
+/// Oldest depth sample taken as the touch an order was placed against
+pub const MAX_TOUCH_AGE_SECS: i64 = 60;
+
+/// How much of a pattern it takes to raise a surveillance alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurveillanceThresholds {
+    /// Trades between accounts of one beneficial owner in a market and window
+    pub wash_trades: u64,
+    /// Trades of an account with itself in a market and window
+    pub self_matches: u64,
+    /// Orders near the touch an account cancelled in a market and window
+    pub spoof_cancels: u64,
+    /// Cancelled orders near the touch per filled one
+    pub spoof_cancels_per_fill: u64,
+    /// How far behind the best price on its side an order still counts as near the touch
+    pub near_touch_bps: u32,
+}
+
+impl Default for SurveillanceThresholds {
+    fn default() -> Self {
+        Self {
+            wash_trades: 3,
+            self_matches: 1,
+            spoof_cancels: 20,
+            spoof_cancels_per_fill: 10,
+            near_touch_bps: 10,
+        }
+    }
+}
+
+/// Suspected abuse found in one window, before it is stored as an alert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurveillanceFinding {
+    pub kind: SurveillanceKind,
+    pub market_id: String,
+    /// The beneficial owner for wash trading, otherwise the account
+    pub subject: String,
+    pub accounts: Vec<String>,
+    /// Trades behind the finding, or cancelled orders for spoofing
+    pub count: u64,
+    /// Base atoms traded, or cancelled for spoofing
+    pub volume: u128,
+    pub detail: String,
+}
+
+/// Accounts that traded with themselves at least `thresholds.self_matches` times
+pub fn self_matching(
+    pairs: &[TradePair],
+    thresholds: &SurveillanceThresholds,
+) -> Vec<SurveillanceFinding> {
+    pairs
+        .iter()
+        .filter(|pair| pair.buyer_address == pair.seller_address)
+        .filter(|pair| pair.trades >= thresholds.self_matches)
+        .map(|pair| SurveillanceFinding {
+            kind: SurveillanceKind::SelfMatching,
+            market_id: pair.market_id.clone(),
+            subject: pair.buyer_address.clone(),
+            accounts: vec![pair.buyer_address.clone()],
+            count: pair.trades,
+            volume: pair.volume,
+            detail: format!(
+                "{} traded with itself {} times",
+                pair.buyer_address, pair.trades
+            ),
+        })
+        .collect()
+}
+
+/// Beneficial owners whose accounts traded with each other at least
+/// `thresholds.wash_trades` times in a market, in either direction
+pub fn wash_trading(
+    pairs: &[TradePair],
+    owners: &HashMap<String, String>,
+    thresholds: &SurveillanceThresholds,
+) -> Vec<SurveillanceFinding> {
+    let mut by_owner: BTreeMap<(&str, &str), (BTreeSet<&str>, u64, u128)> = BTreeMap::new();
+    for pair in pairs {
+        if pair.buyer_address == pair.seller_address {
+            continue;
+        }
+        let (Some(buyer_owner), Some(seller_owner)) = (
+            owners.get(&pair.buyer_address),
+            owners.get(&pair.seller_address),
+        ) else {
+            continue;
+        };
+        if buyer_owner != seller_owner {
+            continue;
+        }
+        let (accounts, trades, volume) = by_owner
+            .entry((pair.market_id.as_str(), buyer_owner.as_str()))
+            .or_default();
+        accounts.insert(&pair.buyer_address);
+        accounts.insert(&pair.seller_address);
+        *trades += pair.trades;
+        *volume = volume.saturating_add(pair.volume);
+    }
+
+    by_owner
+        .into_iter()
+        .filter(|(_, (_, trades, _))| *trades >= thresholds.wash_trades)
+        .map(
+            |((market_id, owner), (accounts, trades, volume))| SurveillanceFinding {
+                kind: SurveillanceKind::WashTrading,
+                market_id: market_id.to_string(),
+                subject: owner.to_string(),
+                detail: format!(
+                    "{} accounts of {} traded with each other {} times",
+                    accounts.len(),
+                    owner,
+                    trades
+                ),
+                accounts: accounts.into_iter().map(str::to_string).collect(),
+                count: trades,
+                volume,
+            },
+        )
+        .collect()
+}
+
+/// Whether an order was priced within `near_touch_bps` of the best price on
+/// its side of the book, or through it
+fn near_touch(order: &Order, touch: &DepthMetrics, near_touch_bps: u32) -> bool {
+    let bps = near_touch_bps as u128;
+    match order.side {
+        Side::Buy => touch.best_bid.is_some_and(|best_bid| {
+            order.price.saturating_mul(10_000) >= best_bid.saturating_mul(10_000 - bps.min(10_000))
+        }),
+        Side::Sell => touch.best_ask.is_some_and(|best_ask| {
+            order.price.saturating_mul(10_000) <= best_ask.saturating_mul(10_000 + bps)
+        }),
+    }
+}
+
+/// Accounts that cancelled many orders placed near the touch while few of
+/// them filled
+///
+/// Each order is judged against the latest depth sample of its market at
+/// most [`MAX_TOUCH_AGE_SECS`] before it was placed; `touches` holds each
+/// market's samples, oldest first. An order that filled at all counts as
+/// filled, and only orders the account cancelled itself count as cancelled.
+pub fn spoofing(
+    orders: &[Order],
+    touches: &HashMap<String, Vec<DepthMetrics>>,
+    thresholds: &SurveillanceThresholds,
+) -> Vec<SurveillanceFinding> {
+    // (cancelled, cancelled size, filled) per market and account
+    let mut counts: BTreeMap<(&str, &str), (u64, u128, u64)> = BTreeMap::new();
+    for order in orders {
+        let Some(samples) = touches.get(&order.market_id) else {
+            continue;
+        };
+        let placed = samples.partition_point(|sample| sample.timestamp <= order.created_at);
+        let Some(touch) = placed.checked_sub(1).map(|i| &samples[i]) else {
+            continue;
+        };
+        if (order.created_at - touch.timestamp).num_seconds() > MAX_TOUCH_AGE_SECS
+            || !near_touch(order, touch, thresholds.near_touch_bps)
+        {
+            continue;
+        }
+
+        let (cancelled, cancelled_size, filled) = counts
+            .entry((order.market_id.as_str(), order.user_address.as_str()))
+            .or_default();
+        if order.filled_size > 0 {
+            *filled += 1;
+        } else {
+            *cancelled += 1;
+            *cancelled_size = cancelled_size.saturating_add(order.size);
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, (cancelled, _, filled))| {
+            *cancelled >= thresholds.spoof_cancels
+                && *cancelled >= thresholds.spoof_cancels_per_fill * (*filled).max(1)
+        })
+        .map(
+            |((market_id, account), (cancelled, cancelled_size, filled))| SurveillanceFinding {
+                kind: SurveillanceKind::Spoofing,
+                market_id: market_id.to_string(),
+                subject: account.to_string(),
+                accounts: vec![account.to_string()],
+                count: cancelled,
+                volume: cancelled_size,
+                detail: format!(
+                    "{} cancelled {} orders near the touch while {} filled",
+                    account, cancelled, filled
+                ),
+            },
+        )
+        .collect()
+}
+
+/// Background job surveilling each closed window of trades once
+///
+/// Trades come from ClickHouse and orders from PostgreSQL, with the touch
+/// taken from ClickHouse depth samples. Findings are stored as alerts for
+/// admins, together with the window, so a window is never surveilled twice.
+pub struct Surveiller {
+    db: Db,
+    thresholds: SurveillanceThresholds,
+}
+
+impl Surveiller {
+    pub fn new(db: Db) -> Self {
+        Self {
+            db,
+            thresholds: SurveillanceThresholds::default(),
+        }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: SurveillanceThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Surveil closed windows as they close
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(SURVEILLANCE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.survey_closed_windows(Utc::now()).await {
+                log::error!("Surveillance failed: {:#}", e);
+            }
+        }
+    }
+
+    /// Surveil every closed window within the lookback not surveilled yet,
+    /// oldest first; returns how many alerts were raised
+    pub async fn survey_closed_windows(&self, now: DateTime<Utc>) -> anyhow::Result<usize> {
+        let closed = (now.timestamp() - SURVEILLANCE_DELAY_SECS)
+            .div_euclid(SURVEILLANCE_WINDOW_SECS)
+            * SURVEILLANCE_WINDOW_SECS;
+        let earliest = closed - SURVEILLANCE_LOOKBACK_WINDOWS * SURVEILLANCE_WINDOW_SECS;
+        let mut from = match self.db.last_surveillance_window_end().await? {
+            Some(end) => end.timestamp().max(earliest),
+            None => earliest,
+        };
+
+        let mut alerts = 0;
+        while from + SURVEILLANCE_WINDOW_SECS <= closed {
+            let to = from + SURVEILLANCE_WINDOW_SECS;
+            let findings = self.survey_window(from, to).await?;
+            for finding in &findings {
+                log::warn!(
+                    "Surveillance {} in {}: {}",
+                    finding.kind,
+                    finding.market_id,
+                    finding.detail
+                );
+            }
+            self.db
+                .record_surveillance_window(timestamp(from), timestamp(to), &findings)
+                .await?;
+            alerts += findings.len();
+            from = to;
+        }
+        Ok(alerts)
+    }
+
+    /// Look for wash trading, self-matching and spoofing within [from, to)
+    pub async fn survey_window(
+        &self,
+        from: i64,
+        to: i64,
+    ) -> anyhow::Result<Vec<SurveillanceFinding>> {
+        let owners = self.db.get_beneficial_owners().await?;
+        let accounts: Vec<String> = owners.keys().cloned().collect();
+        let pairs = self.db.get_trade_pairs(&accounts, from, to).await?;
+
+        let orders = self
+            .db
+            .get_concluded_orders(timestamp(from), timestamp(to))
+            .await?;
+        let mut touches = HashMap::new();
+        for order in &orders {
+            if !touches.contains_key(&order.market_id) {
+                let samples = self
+                    .db
+                    .get_depth_metrics(&order.market_id, from - MAX_TOUCH_AGE_SECS, to)
+                    .await?;
+                touches.insert(order.market_id.clone(), samples);
+            }
+        }
+
+        let mut findings = wash_trading(&pairs, &owners, &self.thresholds);
+        findings.extend(self_matching(&pairs, &self.thresholds));
+        findings.extend(spoofing(&orders, &touches, &self.thresholds));
+        Ok(findings)
+    }
+}
+
+fn timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or(DateTime::UNIX_EPOCH)
+}
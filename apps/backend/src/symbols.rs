@@ -0,0 +1,76 @@
+// market id aliases, resolved where requests enter the API
+
+use crate::db::Db;
+use crate::errors::Result;
+use crate::models::api::ApiMarket;
+use exchange_protocol::symbol::SymbolRegistry;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often the registry picks up markets added by config reloads or other replicas
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The listed markets' spellings, shared by all handlers
+///
+/// Handlers pass every market id they receive through [`Symbols::canonical`]
+/// before using it, so `BTC-USDC`, `btcusdc` and the market's numeric id all
+/// reach the engine and databases as `BTC/USDC`. Ids naming no known market
+/// pass through unchanged and fail as they always did.
+#[derive(Clone, Default)]
+pub struct Symbols {
+    registry: Arc<RwLock<SymbolRegistry>>,
+}
+
+impl Symbols {
+    /// Registry of the markets in the database
+    pub async fn load(db: &Db) -> Result<Self> {
+        let symbols = Self::default();
+        symbols.refresh(db).await?;
+        Ok(symbols)
+    }
+
+    /// Reload the markets, after one is created
+    pub async fn refresh(&self, db: &Db) -> Result<()> {
+        let markets = db.list_markets().await?;
+        let numeric_ids = db.list_market_numeric_ids().await?;
+        let mut registry = SymbolRegistry::new();
+        for market in &markets {
+            registry.insert(
+                &market.id,
+                &market.base_ticker,
+                &market.quote_ticker,
+                numeric_ids.get(&market.id).copied(),
+            );
+        }
+        *self.registry.write().unwrap() = registry;
+        Ok(())
+    }
+
+    /// Canonical id of the market `symbol` names, or `symbol` unchanged
+    pub fn canonical(&self, symbol: &str) -> String {
+        self.registry.read().unwrap().canonicalize(symbol)
+    }
+
+    /// Fill in a market's numeric id, once the registry has it
+    pub fn numbered(&self, mut market: ApiMarket) -> ApiMarket {
+        market.numeric_id = self.registry.read().unwrap().numeric_id(&market.id);
+        market
+    }
+
+    /// Refresh every `interval` until the process exits
+    pub fn spawn_refresher(self, db: Db, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            // The first tick completes at once, and the caller has just loaded
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                if let Err(e) = self.refresh(&db).await {
+                    log::warn!("Failed to refresh market symbols: {}", e);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,101 @@
+// distributed tracing, exported over OTLP
+
+use axum::http::{HeaderMap, Request};
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tower_http::trace::{MakeSpan, TraceLayer};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::EnvFilter;
+
+/// Service name reported when `OTEL_SERVICE_NAME` is unset
+pub const DEFAULT_SERVICE_NAME: &str = "exchange-backend";
+
+/// Spans recorded when `OTEL_TRACES_FILTER` is unset
+const DEFAULT_FILTER: &str = "backend=info,tower_http=info";
+
+/// Flushes buffered spans when dropped; keep it alive until shutdown
+pub struct TelemetryGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            log::warn!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// Export tracing spans to the OTLP/HTTP collector at `OTEL_EXPORTER_OTLP_ENDPOINT`
+///
+/// Does nothing when the endpoint is unset. Logging stays with `env_logger`;
+/// only spans go to the collector, filtered by `OTEL_TRACES_FILTER`.
+pub fn init() -> anyhow::Result<Option<TelemetryGuard>> {
+    if std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_err() {
+        return Ok(None);
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()?;
+    let service_name =
+        std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string());
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter = EnvFilter::try_from_env("OTEL_TRACES_FILTER")
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME)));
+    tracing::subscriber::set_global_default(subscriber)?;
+
+    Ok(Some(TelemetryGuard { provider }))
+}
+
+/// Opens an `http.request` span per request, continuing the caller's trace
+/// when it sends a W3C `traceparent` header
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpMakeSpan;
+
+impl<B> MakeSpan<B> for HttpMakeSpan {
+    fn make_span(&mut self, request: &Request<B>) -> tracing::Span {
+        let span = tracing::info_span!(
+            "http.request",
+            http.method = %request.method(),
+            http.target = %request.uri().path(),
+        );
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(request.headers()))
+        });
+        let _ = span.set_parent(parent);
+        span
+    }
+}
+
+/// Trace layer for the HTTP server
+pub fn http_trace_layer() -> TraceLayer<
+    tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>,
+    HttpMakeSpan,
+> {
+    TraceLayer::new_for_http().make_span_with(HttpMakeSpan)
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
@@ -1,46 +1,24 @@
-use axum::http::StatusCode;
-use axum::Json;
-use bigdecimal::num_bigint::ToBigInt;
 use bigdecimal::{BigDecimal, ToPrimitive};
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub code: String,
-}
+use exchange_protocol::convert::ConversionError;
 
 /// Trait for converting between BigDecimal and u128
 pub trait BigDecimalExt {
-    fn to_u128(self) -> u128;
+    /// Checked conversion to atoms: fractions, negatives and values past
+    /// `u128::MAX` are errors rather than a panic or a clamped amount
+    fn to_atoms(&self) -> Result<u128, ConversionError>;
     fn from_u128(value: u128) -> Self;
 }
 
 impl BigDecimalExt for BigDecimal {
-    fn to_u128(self) -> u128 {
-        // Convert to BigInt - this will panic if there's a fractional part,
-        // which is correct since we should NEVER have fractions (everything is in atoms)
-        let bigint = self
-            .to_bigint()
-            .expect("BUG: BigDecimal has fractional part - all values should be in atoms");
-
-        // Convert BigInt to u128, clamping to valid range to prevent panics
-        // This handles negative values (clamp to 0) and overflow (clamp to MAX)
-        bigint.to_u128().unwrap_or_else(|| {
-            // Check if negative
-            if bigint.sign() == bigdecimal::num_bigint::Sign::Minus {
-                log::error!(
-                    "Negative value in to_u128 conversion (clamping to 0): {:?}",
-                    bigint
-                );
-                0
-            } else {
-                log::error!(
-                    "Overflow in to_u128 conversion (clamping to MAX): {:?}",
-                    bigint
-                );
-                u128::MAX
-            }
+    fn to_atoms(&self) -> Result<u128, ConversionError> {
+        if !self.is_integer() {
+            return Err(ConversionError::PrecisionLoss {
+                value: self.to_string(),
+                decimals: 0,
+            });
+        }
+        ToPrimitive::to_u128(self).ok_or_else(|| ConversionError::Overflow {
+            value: self.to_string(),
         })
     }
 
@@ -49,18 +27,27 @@ impl BigDecimalExt for BigDecimal {
     }
 }
 
-/// Parse a u128 parameter from a string with proper error handling for REST APIs
-pub fn parse_u128_param(
-    s: &str,
-    param_name: &str,
-) -> Result<u128, (StatusCode, Json<ErrorResponse>)> {
-    s.parse::<u128>().map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!("Invalid {} format: must be a valid u128", param_name),
-                code: format!("INVALID_{}", param_name.to_uppercase()),
-            }),
-        )
+/// A NUMERIC column as atoms, failing like any other undecodable column
+/// when the stored value isn't a whole number of atoms
+pub fn decode_atoms(column: &str, value: &BigDecimal) -> Result<u128, sqlx::Error> {
+    value.to_atoms().map_err(|e| sqlx::Error::ColumnDecode {
+        index: column.to_string(),
+        source: Box::new(e),
     })
 }
+
+/// (a - b) / (a + b) in basis points, from -10000 to 10000; 0 when both are 0
+pub fn imbalance_bps(a: u128, b: u128) -> i32 {
+    let total = a.saturating_add(b);
+    if total == 0 {
+        return 0;
+    }
+
+    let (difference, sign) = if a >= b { (a - b, 1) } else { (b - a, -1) };
+    // Only totals above u128::MAX / 10_000 atoms need the less precise order
+    let bps = match difference.checked_mul(10_000) {
+        Some(scaled) => scaled / total,
+        None => difference / (total / 10_000),
+    };
+    sign * bps as i32
+}
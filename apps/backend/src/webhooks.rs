@@ -0,0 +1,289 @@
+// user webhooks: fills, order updates and withdrawals posted to registered URLs
+
+use crate::db::Db;
+use crate::errors::{ExchangeError, Result};
+use crate::event_bus::bus_event;
+use crate::models::api::ApiTrade;
+use crate::models::domain::{EngineEvent, Webhook, WebhookDeadLetter};
+use crate::saturation::Saturation;
+use chrono::Utc;
+use exchange_protocol::events::{
+    BusEvent, WebhookDelivery, EVENT_SCHEMA_VERSION, WEBHOOK_SIGNATURE_HEADER,
+    WEBHOOK_TIMESTAMP_HEADER,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Most webhooks a single user may register
+pub const MAX_WEBHOOKS_PER_USER: usize = 5;
+
+/// Attempts per delivery before it is dead-lettered
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry, doubled after every further failure
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How long a receiver has to answer one attempt
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// Hex HMAC-SHA256 of `"{timestamp}.{body}"`, sent in [`WEBHOOK_SIGNATURE_HEADER`]
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Fresh random signing secret for a new webhook
+pub fn new_secret() -> String {
+    format!(
+        "whsec_{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Webhook URLs must be absolute http(s) URLs
+pub fn validate_url(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| ExchangeError::InvalidParameter {
+        message: format!("Invalid webhook URL '{}': {}", url, e),
+    })?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host().is_none() {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!("Invalid webhook URL '{}': use an http(s) URL", url),
+        });
+    }
+    Ok(())
+}
+
+/// Users an engine event is delivered to, with the payload each of them gets
+///
+/// Both sides of a trade get it as a fill, with `role` set from their side.
+fn recipients(event: &EngineEvent) -> Vec<(String, BusEvent)> {
+    match event {
+        EngineEvent::TradeExecuted { trade, .. } => {
+            let mut users = vec![trade.buyer_address.clone()];
+            if trade.seller_address != trade.buyer_address {
+                users.push(trade.seller_address.clone());
+            }
+            users
+                .into_iter()
+                .map(|user| {
+                    let mut fill: ApiTrade = trade.clone().into();
+                    fill.role = trade.role_of(&user);
+                    (user, BusEvent::Trade(fill))
+                })
+                .collect()
+        }
+        EngineEvent::OrderPlaced { order } => bus_event(event)
+            .map(|e| vec![(order.user_address.clone(), e)])
+            .unwrap_or_default(),
+        EngineEvent::OrderCancelled { user_address, .. } => bus_event(event)
+            .map(|e| vec![(user_address.clone(), e)])
+            .unwrap_or_default(),
+        EngineEvent::WithdrawalUpdated { withdrawal } => bus_event(event)
+            .map(|e| vec![(withdrawal.user_address.clone(), e)])
+            .unwrap_or_default(),
+        EngineEvent::PositionLiquidated { liquidation } => bus_event(event)
+            .map(|e| vec![(liquidation.user_address.clone(), e)])
+            .unwrap_or_default(),
+        EngineEvent::BalanceUpdated { .. }
+        | EngineEvent::OrderRejected { .. }
+        | EngineEvent::MarketStatusChanged { .. }
+        | EngineEvent::OrderbookSnapshot { .. } => Vec::new(),
+    }
+}
+
+/// Posts each user's fills, order updates, withdrawals and liquidations to their webhooks
+///
+/// Every delivery is retried with exponential backoff until the receiver
+/// answers 2xx; after [`MAX_DELIVERY_ATTEMPTS`] it is stored as a dead letter.
+/// Deliveries run concurrently, so a receiver may see them out of order and
+/// should go by the order's `status` and timestamps.
+pub struct WebhookDispatcher {
+    db: Db,
+    client: reqwest::Client,
+    retry_delay: Duration,
+    saturation: Saturation,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: Db) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()
+            .expect("Failed to build HTTP client");
+        Self {
+            db,
+            client,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            saturation: Saturation::default(),
+        }
+    }
+
+    /// Report events the dispatcher skips to `saturation`
+    pub fn with_saturation(mut self, saturation: Saturation) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    /// Override the first retry delay, mainly so tests don't wait on backoff
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Start delivering events from the engine's broadcast channel
+    pub fn spawn(self, mut event_rx: broadcast::Receiver<EngineEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match event_rx.recv().await {
+                    Ok(event) => self.dispatch(&event).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!(
+                            "Webhook dispatcher lagged, skipped {} engine events",
+                            skipped
+                        );
+                        self.saturation.record_lag("webhooks", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    async fn dispatch(&self, event: &EngineEvent) {
+        self.send(recipients(event)).await;
+    }
+
+    /// Deliver each event to every webhook of the user it is paired with
+    pub async fn send(&self, recipients: Vec<(String, BusEvent)>) {
+        if recipients.is_empty() {
+            return;
+        }
+
+        let users: Vec<String> = recipients.iter().map(|(user, _)| user.clone()).collect();
+        let webhooks = match self.db.list_webhooks_for_users(&users).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                log::error!("Failed to load webhooks for {:?}: {}", users, e);
+                return;
+            }
+        };
+
+        for webhook in webhooks {
+            let Some((_, event)) = recipients
+                .iter()
+                .find(|(user, _)| *user == webhook.user_address)
+            else {
+                continue;
+            };
+            let delivery = WebhookDelivery {
+                schema_version: EVENT_SCHEMA_VERSION,
+                delivery_id: Uuid::new_v4().to_string(),
+                webhook_id: webhook.id.to_string(),
+                created_at: Utc::now(),
+                event: event.clone(),
+            };
+            let body = match serde_json::to_vec(&delivery) {
+                Ok(body) => body,
+                Err(e) => {
+                    log::error!("Failed to encode delivery {}: {}", delivery.delivery_id, e);
+                    continue;
+                }
+            };
+            let event_type = event_type(event);
+
+            let client = self.client.clone();
+            let db = self.db.clone();
+            let retry_delay = self.retry_delay;
+            tokio::spawn(async move {
+                deliver(&client, &db, webhook, event_type, body, retry_delay).await;
+            });
+        }
+    }
+}
+
+/// The `type` tag of an event in its JSON form
+fn event_type(event: &BusEvent) -> &'static str {
+    match event {
+        BusEvent::Trade(_) => "trade",
+        BusEvent::Order(_) => "order",
+        BusEvent::OrderCancelled { .. } => "order_cancelled",
+        BusEvent::Balance(_) => "balance",
+        BusEvent::Withdrawal(_) => "withdrawal",
+        BusEvent::Liquidation(_) => "liquidation",
+        BusEvent::Statement(_) => "statement",
+    }
+}
+
+/// Post one delivery until it succeeds or runs out of attempts
+async fn deliver(
+    client: &reqwest::Client,
+    db: &Db,
+    webhook: Webhook,
+    event_type: &str,
+    body: Vec<u8>,
+    retry_delay: Duration,
+) {
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(retry_delay * 2u32.pow(attempt - 2)).await;
+        }
+
+        let timestamp = Utc::now().timestamp();
+        let result = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp)
+            .header(
+                WEBHOOK_SIGNATURE_HEADER,
+                sign(&webhook.secret, timestamp, &body),
+            )
+            .body(body.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => last_error = format!("Receiver answered {}", response.status()),
+            Err(e) => last_error = e.to_string(),
+        }
+        log::debug!(
+            "Webhook {} attempt {} failed: {}",
+            webhook.id,
+            attempt,
+            last_error
+        );
+    }
+
+    log::warn!(
+        "Dead-lettering {} delivery to webhook {}: {}",
+        event_type,
+        webhook.id,
+        last_error
+    );
+    let dead_letter = WebhookDeadLetter {
+        id: Uuid::new_v4(),
+        webhook_id: webhook.id,
+        event_type: event_type.to_string(),
+        payload: String::from_utf8_lossy(&body).into_owned(),
+        attempts: MAX_DELIVERY_ATTEMPTS,
+        last_error,
+        created_at: Utc::now(),
+    };
+    if let Err(e) = db.insert_webhook_dead_letter(&dead_letter).await {
+        log::error!(
+            "Failed to store dead letter for webhook {}: {}",
+            webhook.id,
+            e
+        );
+    }
+}
@@ -0,0 +1,259 @@
+// withdrawals: locked user funds sent on chain through a pluggable signer
+
+pub mod signer;
+
+use crate::config::Config;
+use crate::db::Db;
+use crate::deposits::rpc::EvmRpc;
+use crate::deposits::{deposit_tokens, DepositToken};
+use crate::errors::{ExchangeError, Result};
+use crate::models::domain::{Balance, EngineEvent, Withdrawal, WithdrawalStatus};
+use anyhow::Context;
+use signer::{Submission, TransferRequest, WithdrawalSigner};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Seconds between passes over open withdrawals
+pub const WITHDRAWAL_POLL_INTERVAL_SECS: u64 = 12;
+
+/// Most withdrawals of one status handled per pass
+const BATCH_SIZE: u32 = 100;
+
+/// Withdrawal destinations are 0x-prefixed 20-byte hex addresses
+pub fn validate_destination(destination: &str) -> Result<()> {
+    let valid = destination
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()));
+    if !valid {
+        return Err(ExchangeError::InvalidParameter {
+            message: format!(
+                "Invalid destination '{}': expected a 0x-prefixed address",
+                destination
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Sends pending withdrawals through a signer and settles them once confirmed
+///
+/// Withdrawals move from `pending` to `processing` when picked up (after
+/// which the user can no longer cancel), to `submitted` once the signer has
+/// broadcast them, and to `confirmed` when the transaction has
+/// `confirmations` blocks, which is when the locked amount leaves the
+/// balance. Signer rejections and reverted transactions end in `failed` and
+/// release the amount. Every step is announced as a `WithdrawalUpdated` event.
+///
+/// Withdrawals go out on the chain of `config.deposits`, in its tokens.
+pub struct WithdrawalProcessor<S> {
+    db: Db,
+    rpc: EvmRpc,
+    signer: S,
+    event_tx: broadcast::Sender<EngineEvent>,
+    chain_id: u64,
+    confirmations: u64,
+    tokens: Vec<DepositToken>,
+}
+
+impl<S: WithdrawalSigner> WithdrawalProcessor<S> {
+    /// Process withdrawals with `signer`, tracking them on the chain behind `rpc_url`
+    pub fn new(
+        db: Db,
+        rpc_url: &str,
+        config: &Config,
+        signer: S,
+        event_tx: broadcast::Sender<EngineEvent>,
+    ) -> anyhow::Result<Self> {
+        let deposits = config
+            .deposits
+            .as_ref()
+            .context("Withdrawals need a [deposits] section in config")?;
+
+        Ok(Self {
+            db,
+            rpc: EvmRpc::new(rpc_url),
+            signer,
+            event_tx,
+            chain_id: deposits.chain_id,
+            confirmations: deposits.confirmations.max(1),
+            tokens: deposit_tokens(config)?,
+        })
+    }
+
+    /// Submit what is pending, then settle what has been confirmed
+    pub async fn poll(&self) -> anyhow::Result<()> {
+        // Withdrawals left processing by a crash or a signer error go first
+        let processing = self
+            .db
+            .list_withdrawals_by_status(WithdrawalStatus::Processing, BATCH_SIZE)
+            .await?;
+        for withdrawal in &processing {
+            self.submit(withdrawal).await?;
+        }
+
+        let pending = self
+            .db
+            .list_withdrawals_by_status(WithdrawalStatus::Pending, BATCH_SIZE)
+            .await?;
+        for withdrawal in &pending {
+            // Lost to a cancellation in the meantime
+            let Some(claimed) = self
+                .db
+                .advance_withdrawal(
+                    withdrawal.id,
+                    WithdrawalStatus::Pending,
+                    WithdrawalStatus::Processing,
+                    None,
+                )
+                .await?
+            else {
+                continue;
+            };
+            self.notify(claimed.clone(), None);
+            self.submit(&claimed).await?;
+        }
+
+        self.settle().await
+    }
+
+    /// Hand a processing withdrawal to the signer
+    async fn submit(&self, withdrawal: &Withdrawal) -> anyhow::Result<()> {
+        let token = self
+            .tokens
+            .iter()
+            .find(|token| token.ticker == withdrawal.token_ticker);
+        let Some(token) = token else {
+            let error = format!(
+                "{} can't be withdrawn on chain {}",
+                withdrawal.token_ticker, self.chain_id
+            );
+            return self
+                .fail(withdrawal, WithdrawalStatus::Processing, &error)
+                .await;
+        };
+        let Some(amount) = token.to_chain_amount(withdrawal.amount) else {
+            let error = format!(
+                "{} {} has more precision than the contract supports",
+                withdrawal.amount, withdrawal.token_ticker
+            );
+            return self
+                .fail(withdrawal, WithdrawalStatus::Processing, &error)
+                .await;
+        };
+
+        let transfer = TransferRequest {
+            withdrawal_id: withdrawal.id.to_string(),
+            chain_id: self.chain_id,
+            token_contract: token.contract.clone(),
+            destination: withdrawal.destination.clone(),
+            amount: amount.to_string(),
+        };
+        match self.signer.submit(&transfer).await {
+            Ok(Submission::Sent { tx_hash }) => {
+                let submitted = self
+                    .db
+                    .advance_withdrawal(
+                        withdrawal.id,
+                        WithdrawalStatus::Processing,
+                        WithdrawalStatus::Submitted,
+                        Some(&tx_hash),
+                    )
+                    .await?;
+                if let Some(submitted) = submitted {
+                    log::info!("Withdrawal {} submitted as {}", submitted.id, tx_hash);
+                    self.notify(submitted, None);
+                }
+                Ok(())
+            }
+            Ok(Submission::Rejected { reason }) => {
+                let error = format!("Rejected by signer: {}", reason);
+                self.fail(withdrawal, WithdrawalStatus::Processing, &error)
+                    .await
+            }
+            Err(e) => {
+                // Stays processing and is retried on the next pass
+                log::warn!("Signer failed for withdrawal {}: {:#}", withdrawal.id, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Confirm or fail submitted withdrawals whose transactions are deep enough
+    async fn settle(&self) -> anyhow::Result<()> {
+        let submitted = self
+            .db
+            .list_withdrawals_by_status(WithdrawalStatus::Submitted, BATCH_SIZE)
+            .await?;
+        if submitted.is_empty() {
+            return Ok(());
+        }
+
+        let head = self.rpc.block_number().await?;
+        for withdrawal in &submitted {
+            let Some(tx_hash) = &withdrawal.tx_hash else {
+                continue;
+            };
+            let Some(receipt) = self.rpc.transaction_receipt(tx_hash).await? else {
+                continue;
+            };
+            if head + 1 < receipt.block_number + self.confirmations {
+                continue;
+            }
+
+            if !receipt.success {
+                let error = format!("Transaction {} reverted", tx_hash);
+                self.fail(withdrawal, WithdrawalStatus::Submitted, &error)
+                    .await?;
+                continue;
+            }
+            if let Some((confirmed, balance)) = self.db.confirm_withdrawal(withdrawal.id).await? {
+                log::info!(
+                    "Withdrawal {} of {} {} confirmed",
+                    confirmed.id,
+                    confirmed.amount,
+                    confirmed.token_ticker
+                );
+                self.notify(confirmed, Some(balance));
+            }
+        }
+        Ok(())
+    }
+
+    async fn fail(
+        &self,
+        withdrawal: &Withdrawal,
+        from: WithdrawalStatus,
+        error: &str,
+    ) -> anyhow::Result<()> {
+        log::warn!("Withdrawal {} failed: {}", withdrawal.id, error);
+        if let Some((failed, balance)) = self.db.fail_withdrawal(withdrawal.id, from, error).await?
+        {
+            self.notify(failed, Some(balance));
+        }
+        Ok(())
+    }
+
+    fn notify(&self, withdrawal: Withdrawal, balance: Option<Balance>) {
+        let _ = self
+            .event_tx
+            .send(EngineEvent::WithdrawalUpdated { withdrawal });
+        if let Some(balance) = balance {
+            let _ = self.event_tx.send(EngineEvent::BalanceUpdated { balance });
+        }
+    }
+
+    /// Process withdrawals every `WITHDRAWAL_POLL_INTERVAL_SECS` until the process exits
+    pub async fn run(self) {
+        log::info!("Processing withdrawals on chain {}", self.chain_id);
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(WITHDRAWAL_POLL_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.poll().await {
+                log::warn!("Withdrawal pass on chain {} failed: {:#}", self.chain_id, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// signers: what turns a withdrawal into a broadcast transaction
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+/// How long a signing service has to answer one transfer
+const SIGNER_TIMEOUT_SECS: u64 = 30;
+
+/// A transfer the processor asks a signer to send
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferRequest {
+    /// The withdrawal's id; a signer sends at most one transaction per id
+    pub withdrawal_id: String,
+    pub chain_id: u64,
+    /// ERC-20 contract address
+    pub token_contract: String,
+    pub destination: String,
+    /// In the contract's own decimals, as a decimal string
+    pub amount: String,
+}
+
+/// What a signer did with a transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Submission {
+    /// Broadcast as `tx_hash`
+    Sent { tx_hash: String },
+    /// Refused for good, for instance by a policy check; the withdrawal fails
+    Rejected { reason: String },
+}
+
+/// Signs and broadcasts withdrawal transfers
+///
+/// The processor asks again for a withdrawal whose submission it never
+/// recorded, after a crash or a failed call, so implementations must be
+/// idempotent on `withdrawal_id`: answer with the first transaction's hash
+/// instead of sending another. Errors are treated as temporary and retried.
+pub trait WithdrawalSigner: Send + Sync + 'static {
+    fn submit(
+        &self,
+        transfer: &TransferRequest,
+    ) -> impl Future<Output = anyhow::Result<Submission>> + Send;
+}
+
+/// Forwards transfers to an external signing service, such as a custody API
+/// or an HSM front end, so keys never live in the exchange
+///
+/// Each transfer is posted as a JSON [`TransferRequest`], and the service
+/// answers with a JSON [`Submission`].
+#[derive(Debug, Clone)]
+pub struct HttpSigner {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl HttpSigner {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(SIGNER_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+            url: url.into(),
+            token: None,
+        }
+    }
+
+    /// Authenticate to the service with a bearer token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+}
+
+impl WithdrawalSigner for HttpSigner {
+    async fn submit(&self, transfer: &TransferRequest) -> anyhow::Result<Submission> {
+        let mut request = self.client.post(&self.url).json(transfer);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let submission = request.send().await?.error_for_status()?.json().await?;
+        Ok(submission)
+    }
+}
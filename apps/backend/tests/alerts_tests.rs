@@ -0,0 +1,138 @@
+use axum::{extract::State, routing::post, Json, Router};
+use backend::alerts::{Alert, AlertDispatcher, AlertKind, AlertTarget, Deduplicator};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+/// Receiver that accepts every alert and forwards its JSON body
+async fn start_receiver() -> (String, mpsc::UnboundedReceiver<Value>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/alerts",
+            post(
+                |State(tx): State<mpsc::UnboundedSender<Value>>, Json(body): Json<Value>| async move {
+                    let _ = tx.send(body);
+                },
+            ),
+        )
+        .with_state(tx);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/alerts", addr), rx)
+}
+
+fn halted(market_id: &str) -> Alert {
+    Alert::new(AlertKind::MarketHalted, market_id, "Market is now halted")
+}
+
+#[test]
+fn test_deduplicator_holds_back_repeats_within_window() {
+    let window = Duration::from_secs(60);
+    let mut dedup = Deduplicator::new(window);
+    let start = Instant::now();
+
+    assert_eq!(dedup.admit(&halted("BTC/USDC"), start), Some(0));
+    assert_eq!(dedup.admit(&halted("BTC/USDC"), start), None);
+    assert_eq!(
+        dedup.admit(&halted("BTC/USDC"), start + Duration::from_secs(30)),
+        None
+    );
+
+    // Other subjects and kinds are limited separately
+    assert_eq!(dedup.admit(&halted("ETH/USDC"), start), Some(0));
+    let mass_cancel = Alert::new(AlertKind::MassCancel, "BTC/USDC", "Cancelled 500 orders");
+    assert_eq!(dedup.admit(&mass_cancel, start), Some(0));
+
+    // Once the window passes the next one goes out with the count held back
+    assert_eq!(dedup.admit(&halted("BTC/USDC"), start + window), Some(2));
+    assert_eq!(
+        dedup.admit(&halted("BTC/USDC"), start + window + Duration::from_secs(1)),
+        None
+    );
+    // A quiet window resets the count
+    assert_eq!(
+        dedup.admit(&halted("ETH/USDC"), start + window * 3),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_alert_text_notes_suppressed_repeats() {
+    let alert = halted("BTC/USDC");
+    assert_eq!(
+        alert.text(0),
+        "[market_halted] BTC/USDC: Market is now halted"
+    );
+    assert!(alert
+        .text(4)
+        .ends_with("(4 similar alerts suppressed since the last one)"));
+}
+
+#[tokio::test]
+async fn test_dispatcher_sends_to_every_target_once_per_window() {
+    let (webhook_url, mut webhook) = start_receiver().await;
+    let (slack_url, mut slack) = start_receiver().await;
+
+    let (alerter, handle) = AlertDispatcher::new(vec![
+        AlertTarget::Webhook { url: webhook_url },
+        AlertTarget::Slack {
+            webhook_url: slack_url,
+        },
+    ])
+    .spawn();
+    alerter.raise(halted("BTC/USDC"));
+    alerter.raise(halted("BTC/USDC"));
+    alerter.raise(Alert::new(
+        AlertKind::PersistenceFailure,
+        "clickhouse",
+        "Failed to write 10 trades",
+    ));
+
+    // Dropping the last handle lets the dispatcher finish and stop
+    drop(alerter);
+    timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("Dispatcher should stop")
+        .unwrap();
+
+    let first = webhook.recv().await.unwrap();
+    assert_eq!(first["kind"], "market_halted");
+    assert_eq!(first["subject"], "BTC/USDC");
+    assert_eq!(first["suppressed"], 0);
+    let second = webhook.recv().await.unwrap();
+    assert_eq!(second["kind"], "persistence_failure");
+    assert!(webhook.try_recv().is_err());
+
+    assert_eq!(
+        slack.recv().await.unwrap()["text"],
+        "[market_halted] BTC/USDC: Market is now halted"
+    );
+    assert_eq!(
+        slack.recv().await.unwrap()["text"],
+        "[persistence_failure] clickhouse: Failed to write 10 trades"
+    );
+    assert!(slack.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_unreachable_target_doesnt_stop_the_rest() {
+    let (url, mut received) = start_receiver().await;
+    let (alerter, handle) = AlertDispatcher::new(vec![
+        AlertTarget::Webhook {
+            url: "http://127.0.0.1:1/alerts".to_string(),
+        },
+        AlertTarget::Webhook { url },
+    ])
+    .spawn();
+
+    alerter.raise(halted("BTC/USDC"));
+    drop(alerter);
+    timeout(Duration::from_secs(15), handle)
+        .await
+        .expect("Dispatcher should stop")
+        .unwrap();
+    assert_eq!(received.recv().await.unwrap()["subject"], "BTC/USDC");
+}
@@ -0,0 +1,173 @@
+use backend::api::amounts::Amounts;
+use backend::db::Db;
+use backend::errors::ExchangeError;
+use backend::models::api::{ApiMarket, ApiOrder, ApiTrade};
+use backend::models::domain::{OrderStatus, OrderType, Side, TimeInForce, TriggerSource};
+use chrono::Utc;
+use exchange_protocol::convert::AmountFormat;
+
+/// BTC with 8 decimals quoted in USDC with 6
+async fn db() -> Db {
+    let db = Db::in_memory().unwrap();
+    db.create_token("BTC".to_string(), 8, "Bitcoin".to_string())
+        .await
+        .unwrap();
+    db.create_token("USDC".to_string(), 6, "USD Coin".to_string())
+        .await
+        .unwrap();
+    db.create_market(
+        "BTC".to_string(),
+        "USDC".to_string(),
+        10_000,
+        1_000,
+        100_000,
+        0,
+        0,
+    )
+    .await
+    .unwrap();
+    db
+}
+
+fn order(price: &str, size: &str, filled_size: &str) -> ApiOrder {
+    ApiOrder {
+        id: "00000000-0000-0000-0000-000000000000".to_string(),
+        user_address: "alice".to_string(),
+        market_id: "BTC/USDC".to_string(),
+        price: price.to_string(),
+        size: size.to_string(),
+        side: Side::Buy,
+        order_type: OrderType::Limit,
+        status: OrderStatus::PartiallyFilled,
+        filled_size: filled_size.to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        cancel_reason: None,
+        trigger_price: None,
+        trigger_source: TriggerSource::LastTrade,
+        time_in_force: TimeInForce::Gtc,
+        expires_at: None,
+    }
+}
+
+#[tokio::test]
+async fn test_decimal_inputs_use_each_tokens_decimals() {
+    let mut amounts = Amounts::new(AmountFormat::Decimal, db().await);
+
+    assert_eq!(
+        amounts.parse_price("BTC/USDC", "65000.5").await.unwrap(),
+        65_000_500_000
+    );
+    assert_eq!(
+        amounts.parse_size("BTC/USDC", "0.001").await.unwrap(),
+        100_000
+    );
+    assert_eq!(
+        amounts.parse_amount("USDC", "1000").await.unwrap(),
+        1_000_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_decimal_inputs_refuse_precision_loss() {
+    let mut amounts = Amounts::new(AmountFormat::Decimal, db().await);
+
+    // One atom is 0.000001 USDC; anything finer would be rounded away
+    let err = amounts
+        .parse_price("BTC/USDC", "65000.0000001")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExchangeError::InvalidParameter { .. }),
+        "{:?}",
+        err
+    );
+    let err = amounts
+        .parse_size("BTC/USDC", "0.000000001")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExchangeError::InvalidParameter { .. }),
+        "{:?}",
+        err
+    );
+    assert!(amounts.parse_amount("USDC", "abc").await.is_err());
+    assert!(matches!(
+        amounts.parse_amount("DOGE", "1").await.unwrap_err(),
+        ExchangeError::TokenNotFound { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_atoms_format_is_unchanged() {
+    let mut amounts = Amounts::new(AmountFormat::Atoms, db().await);
+
+    assert_eq!(
+        amounts.parse_price("BTC/USDC", "65000").await.unwrap(),
+        65000
+    );
+    assert!(matches!(
+        amounts
+            .parse_price("BTC/USDC", "65000.5")
+            .await
+            .unwrap_err(),
+        ExchangeError::InvalidPrice
+    ));
+    assert!(matches!(
+        amounts.parse_size("BTC/USDC", "0.001").await.unwrap_err(),
+        ExchangeError::InvalidSize
+    ));
+
+    let converted = amounts
+        .order(order("65000500000", "100000", "0"))
+        .await
+        .unwrap();
+    assert_eq!(converted.price, "65000500000");
+    assert_eq!(converted.size, "100000");
+}
+
+#[tokio::test]
+async fn test_decimal_responses() {
+    let db = db().await;
+    let mut amounts = Amounts::new(AmountFormat::Decimal, db.clone());
+
+    let converted = amounts
+        .order(order("65000500000", "150000", "50000"))
+        .await
+        .unwrap();
+    assert_eq!(converted.price, "65000.5");
+    assert_eq!(converted.size, "0.0015");
+    assert_eq!(converted.filled_size, "0.0005");
+
+    let trade = ApiTrade {
+        id: "00000000-0000-0000-0000-000000000000".to_string(),
+        market_id: "BTC/USDC".to_string(),
+        buyer_address: "alice".to_string(),
+        seller_address: "bob".to_string(),
+        buyer_order_id: "00000000-0000-0000-0000-000000000000".to_string(),
+        seller_order_id: "00000000-0000-0000-0000-000000000000".to_string(),
+        price: "65000000000".to_string(),
+        size: "100000".to_string(),
+        side: Side::Buy,
+        timestamp: Utc::now(),
+        rfq: false,
+        fee: Some("-1500".to_string()),
+        fee_ticker: Some("USDC".to_string()),
+        role: None,
+    };
+    let converted = amounts.trade(trade).await.unwrap();
+    assert_eq!(converted.price, "65000");
+    assert_eq!(converted.size, "0.001");
+    assert_eq!(converted.fee.as_deref(), Some("-0.0015"));
+
+    let market: ApiMarket = db.get_market("BTC/USDC").await.unwrap().into();
+    let converted = amounts.market_details(market).await.unwrap();
+    assert_eq!(converted.tick_size, "0.01");
+    assert_eq!(converted.lot_size, "0.00001");
+    assert_eq!(converted.min_size, "0.001");
+
+    let balance = db.add_balance("alice", "BTC", 123_456_789).await.unwrap();
+    let converted = amounts.balances(vec![balance.into()]).await.unwrap();
+    assert_eq!(converted[0].amount, "1.23456789");
+    assert_eq!(converted[0].open_interest, "0");
+}
@@ -0,0 +1,362 @@
+use backend::api::auth::{
+    authorize, hash_key, ip_allowed, key_prefix, new_key, validate_allowlist, ApiKeyAuth,
+    API_KEY_HEADER,
+};
+use backend::errors::{ExchangeError, Result};
+use backend::models::api::{UserRequest, UserResponse};
+use backend::models::domain::{ApiKeyScope, SubAccount};
+use exchange_test_utils::{helpers, TestServer, TestWallet};
+use serde_json::json;
+use uuid::Uuid;
+
+fn auth(user_address: &str, scopes: &[ApiKeyScope]) -> ApiKeyAuth {
+    ApiKeyAuth {
+        key_id: Uuid::new_v4(),
+        user_address: user_address.to_string(),
+        scopes: scopes.to_vec(),
+    }
+}
+
+fn allowlist(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|entry| entry.to_string()).collect()
+}
+
+// ============================================================================
+// Key and Allowlist Tests
+// ============================================================================
+
+#[test]
+fn test_keys_are_stored_as_hashes_with_a_visible_prefix() {
+    let key = new_key();
+    assert!(key.starts_with("ak_"));
+    assert_ne!(key, new_key());
+
+    assert_eq!(key_prefix(&key).len(), 11);
+    assert!(key.starts_with(&key_prefix(&key)));
+    let hash = hash_key(&key);
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, hash_key(&key));
+    assert_ne!(hash, hash_key(&new_key()));
+}
+
+#[test]
+fn test_allowlist_entries_must_be_addresses_or_ranges() {
+    assert!(validate_allowlist(&[]).is_ok());
+    assert!(
+        validate_allowlist(&allowlist(&["10.0.0.1", "192.168.0.0/16", "2001:db8::/32"])).is_ok()
+    );
+    assert!(validate_allowlist(&allowlist(&["example.com"])).is_err());
+    assert!(validate_allowlist(&allowlist(&["10.0.0.0/33"])).is_err());
+    assert!(validate_allowlist(&allowlist(&["2001:db8::/129"])).is_err());
+    assert!(validate_allowlist(&allowlist(&["10.0.0.0/"])).is_err());
+}
+
+#[test]
+fn test_ip_allowed_matches_addresses_and_ranges() {
+    let ip = |ip: &str| ip.parse().unwrap();
+
+    // No allowlist, no restriction
+    assert!(ip_allowed(&[], ip("203.0.113.7")));
+
+    let list = allowlist(&["203.0.113.7", "10.1.0.0/16", "2001:db8::/32"]);
+    assert!(ip_allowed(&list, ip("203.0.113.7")));
+    assert!(!ip_allowed(&list, ip("203.0.113.8")));
+    assert!(ip_allowed(&list, ip("10.1.255.255")));
+    assert!(!ip_allowed(&list, ip("10.2.0.1")));
+    assert!(ip_allowed(&list, ip("2001:db8:1::1")));
+    assert!(!ip_allowed(&list, ip("2001:db9::1")));
+    // IPv4 clients seen over an IPv6 socket still match
+    assert!(ip_allowed(&list, ip("::ffff:10.1.2.3")));
+
+    assert!(ip_allowed(&allowlist(&["0.0.0.0/0"]), ip("198.51.100.1")));
+}
+
+// ============================================================================
+// Scope Tests
+// ============================================================================
+
+fn signed() -> Result<()> {
+    Ok(())
+}
+
+fn unsigned() -> Result<()> {
+    Err(ExchangeError::InvalidSignature {
+        message: "missing".to_string(),
+    })
+}
+
+#[test]
+fn test_requests_without_a_key_need_a_signature() {
+    for scope in [Some(ApiKeyScope::Trade), Some(ApiKeyScope::Withdraw), None] {
+        assert!(authorize(None, "alice", scope, signed).is_ok());
+        assert!(matches!(
+            authorize(None, "alice", scope, unsigned),
+            Err(ExchangeError::InvalidSignature { .. })
+        ));
+    }
+    // Reads by address are public
+    assert!(authorize(None, "alice", Some(ApiKeyScope::Read), unsigned).is_ok());
+}
+
+#[test]
+fn test_keys_act_only_within_their_scopes() {
+    let key = auth("alice", &[ApiKeyScope::Read, ApiKeyScope::Trade]);
+
+    assert!(authorize(Some(&key), "alice", Some(ApiKeyScope::Read), unsigned).is_ok());
+    assert!(authorize(Some(&key), "alice", Some(ApiKeyScope::Trade), unsigned).is_ok());
+    // A signature doesn't widen a key
+    assert!(matches!(
+        authorize(Some(&key), "alice", Some(ApiKeyScope::Withdraw), signed),
+        Err(ExchangeError::ApiKeyNotPermitted { .. })
+    ));
+    // Account management needs the account's own signature
+    assert!(matches!(
+        authorize(Some(&key), "alice", None, signed),
+        Err(ExchangeError::ApiKeyNotPermitted { .. })
+    ));
+}
+
+#[test]
+fn test_keys_act_for_their_owner_and_its_sub_accounts() {
+    let desk = SubAccount::address_of("alice", "desk");
+    let key = auth("alice", &[ApiKeyScope::Trade]);
+    assert!(key.permit(&desk, ApiKeyScope::Trade).is_ok());
+    assert!(key.permit("bob", ApiKeyScope::Trade).is_err());
+    assert!(key
+        .permit(&SubAccount::address_of("bob", "desk"), ApiKeyScope::Trade)
+        .is_err());
+
+    // A sub-account's key can't reach its master or siblings
+    let desk_key = auth(&desk, &[ApiKeyScope::Trade]);
+    assert!(desk_key.permit(&desk, ApiKeyScope::Trade).is_ok());
+    assert!(desk_key.permit("alice", ApiKeyScope::Trade).is_err());
+    assert!(desk_key
+        .permit(
+            &SubAccount::address_of("alice", "other"),
+            ApiKeyScope::Trade
+        )
+        .is_err());
+}
+
+// ============================================================================
+// API Key Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_api_key_lifecycle_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let (alice, bob) = (TestWallet::new(), TestWallet::new());
+    for user in [&alice, &bob] {
+        helpers::create_user(&server.test_db, user.address())
+            .await
+            .expect("Failed to create user");
+    }
+
+    let client = reqwest::Client::new();
+    let post_user = |body: serde_json::Value, key: Option<&str>| {
+        let mut request = client.post(server.url("/api/user")).json(&body);
+        if let Some(key) = key {
+            request = request.header(API_KEY_HEADER, key);
+        }
+        request.send()
+    };
+    let signed = |wallet: &TestWallet, body: serde_json::Value| {
+        wallet.sign::<UserRequest>("/api/user", body)
+    };
+    let balances =
+        |user: &TestWallet| json!({ "type": "balances", "user_address": user.address() });
+    let create = |label: &str, scopes: serde_json::Value| {
+        json!({ "type": "create_api_key", "user_address": alice.address(), "label": label,
+                "scopes": scopes })
+    };
+
+    // Keys are only managed with the account's own signature
+    let mut unsigned = create("bot", json!(["withdraw"]));
+    unsigned["signature"] = json!("sig");
+    assert_eq!(post_user(unsigned, None).await.unwrap().status(), 401);
+    let forged = signed(&bob, create("bot", json!(["withdraw"])));
+    assert_eq!(post_user(forged, None).await.unwrap().status(), 401);
+    assert!(server
+        .db()
+        .list_api_keys(alice.address())
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Scopes and allowlists are validated
+    let mut bad_allowlist = create("bot", json!(["read"]));
+    bad_allowlist["ip_allowlist"] = json!(["nope"]);
+    for body in [create("bot", json!([])), bad_allowlist] {
+        assert_eq!(
+            post_user(signed(&alice, body), None)
+                .await
+                .unwrap()
+                .status(),
+            400
+        );
+    }
+
+    let response: UserResponse = post_user(
+        signed(&alice, create("bot", json!(["read", "trade", "read"]))),
+        None,
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let UserResponse::CreateApiKey { api_key, secret } = response else {
+        panic!("Expected CreateApiKey");
+    };
+    assert_eq!(api_key.scopes, vec![ApiKeyScope::Read, ApiKeyScope::Trade]);
+    assert_eq!(api_key.key_prefix, key_prefix(&secret));
+    assert!(api_key.last_used_at.is_none());
+
+    // The key reads its owner's account but not anyone else's
+    let response = post_user(balances(&alice), Some(&secret)).await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        post_user(balances(&bob), Some(&secret))
+            .await
+            .unwrap()
+            .status(),
+        403
+    );
+    // It lacks the withdraw scope, and can't manage keys at all, signed or not
+    let withdraw = json!({ "type": "withdraw", "user_address": alice.address(),
+                           "token_ticker": "USDC", "amount": "1", "destination": "0xabc" });
+    assert_eq!(
+        post_user(signed(&alice, withdraw), Some(&secret))
+            .await
+            .unwrap()
+            .status(),
+        403
+    );
+    let revoke = |wallet: &TestWallet, key_id: &str| {
+        let body = json!({ "type": "revoke_api_key", "user_address": wallet.address(),
+                           "key_id": key_id });
+        signed(wallet, body)
+    };
+    assert_eq!(
+        post_user(revoke(&alice, &api_key.id), Some(&secret))
+            .await
+            .unwrap()
+            .status(),
+        403
+    );
+    // Unknown keys are rejected outright
+    assert_eq!(
+        post_user(balances(&alice), Some("ak_unknown"))
+            .await
+            .unwrap()
+            .status(),
+        401
+    );
+
+    let listed = server.db().list_api_keys(alice.address()).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].last_used_at.is_some());
+
+    // Rotating takes a signature too, then replaces the secret and keeps the rest
+    let rotate = json!({ "type": "rotate_api_key", "user_address": alice.address(),
+                         "key_id": api_key.id });
+    let mut unsigned = rotate.clone();
+    unsigned["signature"] = json!("sig");
+    assert_eq!(post_user(unsigned, None).await.unwrap().status(), 401);
+    let response: UserResponse = post_user(signed(&alice, rotate), None)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let UserResponse::RotateApiKey {
+        api_key: rotated,
+        secret: new_secret,
+    } = response
+    else {
+        panic!("Expected RotateApiKey");
+    };
+    assert_eq!(rotated.id, api_key.id);
+    assert_eq!(rotated.scopes, api_key.scopes);
+    assert!(rotated.rotated_at.is_some());
+    assert_eq!(
+        post_user(balances(&alice), Some(&secret))
+            .await
+            .unwrap()
+            .status(),
+        401
+    );
+    assert!(post_user(balances(&alice), Some(&new_secret))
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+
+    // A key restricted to other addresses can't be used from here
+    let mut office = create("office", json!(["read"]));
+    office["ip_allowlist"] = json!(["203.0.113.0/24"]);
+    let response: UserResponse = post_user(signed(&alice, office), None)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let UserResponse::CreateApiKey {
+        secret: office_secret,
+        ..
+    } = response
+    else {
+        panic!("Expected CreateApiKey");
+    };
+    assert_eq!(
+        post_user(balances(&alice), Some(&office_secret))
+            .await
+            .unwrap()
+            .status(),
+        403
+    );
+
+    // Revoked keys stop working and drop out of the list; bob can't revoke alice's
+    assert_eq!(
+        post_user(revoke(&bob, &api_key.id), None)
+            .await
+            .unwrap()
+            .status(),
+        404
+    );
+    assert!(post_user(revoke(&alice, &api_key.id), None)
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+    assert_eq!(
+        post_user(balances(&alice), Some(&new_secret))
+            .await
+            .unwrap()
+            .status(),
+        401
+    );
+    let response: UserResponse = post_user(
+        json!({ "type": "api_keys", "user_address": alice.address() }),
+        None,
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let UserResponse::ApiKeys { api_keys } = response else {
+        panic!("Expected ApiKeys");
+    };
+    let labels: Vec<&str> = api_keys.iter().map(|k| k.label.as_str()).collect();
+    assert_eq!(labels, vec!["office"]);
+    assert_eq!(
+        post_user(revoke(&alice, "not-a-uuid"), None)
+            .await
+            .unwrap()
+            .status(),
+        404
+    );
+}
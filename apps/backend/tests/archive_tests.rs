@@ -0,0 +1,202 @@
+use backend::archive::{data_path, manifest_path, read_trades, ArchiveStore, Archiver, Dataset};
+use backend::models::domain::{Side, Trade};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use exchange_test_utils::{helpers, TestDb};
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload};
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::sync::Arc;
+
+fn trade_at(timestamp: DateTime<Utc>, price: u128) -> Trade {
+    Trade {
+        timestamp,
+        price,
+        ..helpers::sample_trade("BTC/USDC")
+    }
+}
+
+// ============================================================================
+// Layout Tests
+// ============================================================================
+
+#[test]
+fn test_archive_layout_is_partitioned_by_day() {
+    let date = NaiveDate::from_ymd_opt(2025, 11, 20).unwrap();
+    assert_eq!(
+        data_path(Dataset::Trades, date),
+        "trades/date=2025-11-20/trades.parquet"
+    );
+    assert_eq!(
+        data_path(Dataset::Candles, date),
+        "candles/date=2025-11-20/candles.parquet"
+    );
+    assert_eq!(manifest_path(date), "manifests/date=2025-11-20.json");
+}
+
+#[tokio::test]
+async fn test_archive_store_opens_by_url() {
+    let dir = std::env::temp_dir().join(format!("archive-{}", uuid::Uuid::new_v4()));
+    let store = ArchiveStore::from_url(&format!("file://{}", dir.display())).unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 11, 20).unwrap();
+    assert_eq!(store.manifest(date).await.unwrap(), None);
+
+    assert!(ArchiveStore::from_url("not a url").is_err());
+    assert!(ArchiveStore::from_url("ftp://example.com/archive").is_err());
+}
+
+#[test]
+fn test_archived_trades_decode_from_parquet() {
+    // The columns ClickHouse writes for the trades dataset
+    let schema = Arc::new(
+        parse_message_type(
+            "message schema {
+                required binary id (STRING);
+                required binary market_id (STRING);
+                required binary buyer_address (STRING);
+                required binary seller_address (STRING);
+                required binary buyer_order_id (STRING);
+                required binary seller_order_id (STRING);
+                required binary price (STRING);
+                required binary size (STRING);
+                required binary side (STRING);
+                required int64 timestamp;
+            }",
+        )
+        .unwrap(),
+    );
+    let timestamp = Utc.with_ymd_and_hms(2025, 11, 20, 12, 0, 0).unwrap();
+    // Above u64::MAX, which only survives as a string
+    let trade = Trade {
+        side: Side::Sell,
+        ..trade_at(timestamp, u64::MAX as u128 + 1)
+    };
+
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut file = Vec::new();
+    let mut writer = SerializedFileWriter::new(&mut file, schema, Arc::new(props)).unwrap();
+    let mut row_group = writer.next_row_group().unwrap();
+    let strings = [
+        trade.id.to_string(),
+        trade.market_id.clone(),
+        trade.buyer_address.clone(),
+        trade.seller_address.clone(),
+        trade.buyer_order_id.to_string(),
+        trade.seller_order_id.to_string(),
+        trade.price.to_string(),
+        trade.size.to_string(),
+        trade.side.to_string(),
+    ];
+    for value in strings {
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from(value.as_str())], None, None)
+            .unwrap();
+        column.close().unwrap();
+    }
+    let mut column = row_group.next_column().unwrap().unwrap();
+    column
+        .typed::<Int64Type>()
+        .write_batch(&[timestamp.timestamp()], None, None)
+        .unwrap();
+    column.close().unwrap();
+    row_group.close().unwrap();
+    writer.close().unwrap();
+
+    let decoded = read_trades(file.into()).expect("Failed to decode trades");
+    assert_eq!(decoded, vec![trade]);
+}
+
+// ============================================================================
+// Archive and Restore Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_archive_and_restore_round_trip() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test db");
+    let db = test_db.db.clone();
+
+    let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+    let at = |hour: u32, minute: u32| Utc.with_ymd_and_hms(2025, 3, 14, hour, minute, 0).unwrap();
+    let mut trades = vec![
+        trade_at(at(9, 0), 50_000_000_000),
+        trade_at(at(9, 0), 50_100_000_000),
+        trade_at(at(23, 59), 49_900_000_000),
+    ];
+    // Trades on the neighbouring days stay out of the partition
+    let outside = [
+        trade_at(at(0, 0) - chrono::Duration::seconds(1), 1),
+        trade_at(at(0, 0) + chrono::Duration::days(1), 1),
+    ];
+    db.insert_trades_to_clickhouse(&trades).await.unwrap();
+    db.insert_trades_to_clickhouse(&outside).await.unwrap();
+
+    let memory = Arc::new(InMemory::new());
+    let store = ArchiveStore::new(memory.clone(), "archive");
+    let archiver = Archiver::new(db.clone(), store.clone());
+
+    let manifest = archiver.archive_day(date).await.expect("Failed to archive");
+    assert_eq!(manifest.date, date);
+    let trades_file = manifest.file(Dataset::Trades).unwrap().clone();
+    assert_eq!(trades_file.rows, 3);
+    assert_eq!(trades_file.path, data_path(Dataset::Trades, date));
+    // 1m, 5m, 15m and 1h candles at 09:00 and 23:59, plus one 1d candle
+    assert_eq!(manifest.file(Dataset::Candles).unwrap().rows, 9);
+
+    // Archiving the same day again keeps the first archive
+    let again = archiver.archive_day(date).await.unwrap();
+    assert_eq!(again, manifest);
+    assert_eq!(store.manifest(date).await.unwrap(), Some(manifest));
+
+    let location = Path::from(format!("archive/{}", trades_file.path));
+    let data = memory.get(&location).await.unwrap().bytes().await.unwrap();
+    assert_eq!(data.len() as u64, trades_file.bytes);
+    let mut archived = read_trades(data.clone()).expect("Failed to decode archive");
+    let by_time_and_id = |a: &Trade, b: &Trade| (a.timestamp, a.id).cmp(&(b.timestamp, b.id));
+    archived.sort_by(by_time_and_id);
+    trades.sort_by(by_time_and_id);
+    assert_eq!(archived, trades);
+
+    // Restoring a day ClickHouse still has would double count it
+    let err = archiver.restore_day(date, false).await.unwrap_err();
+    assert!(err.to_string().contains("count them twice"), "{}", err);
+
+    for table in ["exchange.trades", "exchange.candles"] {
+        db.clickhouse
+            .query(&format!("TRUNCATE TABLE {}", table))
+            .execute()
+            .await
+            .unwrap();
+    }
+    let restored = archiver
+        .restore_day(date, false)
+        .await
+        .expect("Failed to restore");
+    assert_eq!(restored, 3);
+
+    // The restored day archives to the same trades, with its candles rebuilt
+    let rebuilt = Archiver::new(db.clone(), ArchiveStore::new(Arc::new(InMemory::new()), ""))
+        .archive_day(date)
+        .await
+        .unwrap();
+    assert_eq!(rebuilt.file(Dataset::Trades).unwrap().rows, 3);
+    assert_eq!(rebuilt.file(Dataset::Candles).unwrap().rows, 9);
+
+    // A file that no longer matches its manifest is refused
+    memory
+        .put(&location, PutPayload::from(data.slice(1..)))
+        .await
+        .unwrap();
+    let err = archiver.restore_day(date, true).await.unwrap_err();
+    assert!(err.to_string().contains("corrupt"), "{}", err);
+
+    let missing = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+    assert!(archiver.restore_day(missing, false).await.is_err());
+}
@@ -0,0 +1,91 @@
+use backend::analytics::average_price::time_weighted;
+use backend::models::api::ApiAveragePrice;
+use backend::models::domain::{Side, Trade};
+use chrono::{DateTime, Utc};
+use exchange_test_utils::{helpers, TestServer};
+
+// ============================================================================
+// Time Weighting Tests
+// ============================================================================
+
+#[test]
+fn test_each_price_holds_until_the_next() {
+    // Without an opening price the average starts at the first trade
+    assert_eq!(
+        time_weighted(None, &[(100, 10), (200, 20)], 0, 300),
+        Some(15)
+    );
+
+    // The opening price covers the range up to the first trade
+    assert_eq!(time_weighted(Some(30), &[(150, 10)], 100, 200), Some(20));
+    assert_eq!(time_weighted(Some(7), &[], 100, 200), Some(7));
+}
+
+#[test]
+fn test_nothing_to_average() {
+    assert_eq!(time_weighted(None, &[], 100, 200), None);
+
+    // A single trade at the very end of the range has no duration of its own
+    assert_eq!(time_weighted(None, &[(200, 42)], 100, 200), Some(42));
+}
+
+// ============================================================================
+// Average Price Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_vwap_and_twap_endpoints_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let now = Utc::now().timestamp();
+    let trade = |seconds_ago: i64, price: u128, size: u128| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "buyer".to_string(),
+        seller_address: "seller".to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price,
+        size,
+        side: Side::Buy,
+        timestamp: DateTime::from_timestamp(now - seconds_ago, 0).unwrap(),
+        rfq: false,
+    };
+    server
+        .db()
+        .insert_trades_to_clickhouse(&[
+            trade(3600, 50_000_000_000, 100_000_000),
+            trade(1800, 60_000_000_000, 300_000_000),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let get = |kind: &str, from: i64, to: i64| {
+        let url = server.url(&format!(
+            "/api/markets/BTC%2FUSDC/{}?from={}&to={}",
+            kind, from, to
+        ));
+        async move { reqwest::get(&url).await.expect("Request failed") }
+    };
+
+    // 1 BTC at $50,000 and 3 BTC at $60,000
+    let vwap: ApiAveragePrice = get("vwap", now - 7200, now).await.json().await.unwrap();
+    assert_eq!(vwap.price.as_deref(), Some("57500000000"));
+
+    // Half an hour at each price
+    let twap: ApiAveragePrice = get("twap", now - 3600, now).await.json().await.unwrap();
+    assert_eq!(twap.price.as_deref(), Some("55000000000"));
+
+    // After the last trade, TWAP carries its price and VWAP has nothing to weigh
+    let twap: ApiAveragePrice = get("twap", now - 600, now).await.json().await.unwrap();
+    assert_eq!(twap.price.as_deref(), Some("60000000000"));
+    let vwap: ApiAveragePrice = get("vwap", now - 600, now).await.json().await.unwrap();
+    assert!(vwap.price.is_none());
+
+    assert_eq!(get("vwap", now, now - 600).await.status(), 400);
+}
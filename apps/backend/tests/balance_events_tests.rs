@@ -1,6 +1,5 @@
 use backend::models::api::{ClientMessage, ServerMessage, SubscriptionChannel};
-use backend::models::domain::{OrderType, Side};
-use exchange_test_utils::{helpers, TestEngine, TestServer};
+use exchange_test_utils::{helpers, OrderBuilder, TestServer};
 use futures::{SinkExt, StreamExt};
 use tokio::time::{timeout, Duration};
 use tokio_tungstenite::tungstenite::Message;
@@ -85,6 +84,7 @@ async fn test_balance_events_on_limit_order_placement() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(user.clone()),
+            interval: None,
         },
     )
     .await
@@ -102,14 +102,10 @@ async fn test_balance_events_on_limit_order_placement() {
     }
 
     // Place a limit order - engine automatically locks balance and broadcasts event
-    let order = TestEngine::create_order(
-        &user,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Limit,
-        50_000_000_000, // price
-        1_000_000,      // size
-    );
+    let order = OrderBuilder::buy(&user, "BTC/USDC")
+        .limit(50_000_000_000) // price
+        .size(1_000_000) // size
+        .build();
     let result = server.test_engine.place_order(order).await;
 
     assert!(result.is_ok(), "Failed to create order: {:?}", result.err());
@@ -194,20 +190,17 @@ async fn test_balance_events_on_limit_order_fill() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
     .expect("Failed to subscribe");
 
     // Maker places sell order
-    let maker_order = TestEngine::create_order(
-        &maker,
-        "BTC/USDC",
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000_000, // price
-        1_000_000,      // size
-    );
+    let maker_order = OrderBuilder::sell(&maker, "BTC/USDC")
+        .limit(50_000_000_000) // price
+        .size(1_000_000) // size
+        .build();
     server
         .test_engine
         .place_order(maker_order)
@@ -215,14 +208,10 @@ async fn test_balance_events_on_limit_order_fill() {
         .expect("Failed to create maker order");
 
     // Taker places matching buy order (should fill and unlock balances)
-    let taker_order = TestEngine::create_order(
-        &taker,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Limit,
-        50_000_000_000, // price
-        1_000_000,      // size
-    );
+    let taker_order = OrderBuilder::buy(&taker, "BTC/USDC")
+        .limit(50_000_000_000) // price
+        .size(1_000_000) // size
+        .build();
     server
         .test_engine
         .place_order(taker_order)
@@ -334,20 +323,17 @@ async fn test_balance_events_on_limit_order_cancellation() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(user.clone()),
+            interval: None,
         },
     )
     .await
     .expect("Failed to subscribe");
 
     // Place a limit order
-    let order = TestEngine::create_order(
-        &user,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Limit,
-        50_000_000_000, // price
-        1_000_000,      // size
-    );
+    let order = OrderBuilder::buy(&user, "BTC/USDC")
+        .limit(50_000_000_000) // price
+        .size(1_000_000) // size
+        .build();
     let result = server
         .test_engine
         .place_order(order)
@@ -470,14 +456,10 @@ async fn test_balance_events_on_market_order_partial_fill() {
         .expect("Failed to add USDC to taker");
 
     // Maker places sell order for 1 BTC
-    let maker_order = TestEngine::create_order(
-        &maker,
-        "BTC/USDC",
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000_000,
-        1_000_000,
-    );
+    let maker_order = OrderBuilder::sell(&maker, "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
     server
         .test_engine
         .place_order(maker_order)
@@ -496,6 +478,7 @@ async fn test_balance_events_on_market_order_partial_fill() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -503,14 +486,11 @@ async fn test_balance_events_on_market_order_partial_fill() {
 
     // Taker places market order for 2 BTC (but only 1 available)
     // Market order should fill 1 BTC and unlock the remaining 1 BTC worth of USDC
-    let taker_order = TestEngine::create_order(
-        &taker,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Market,
-        50_000_000_000, // price
-        2_000_000,      // size - requesting 2 BTC but only 1 available
-    );
+    let taker_order = OrderBuilder::buy(&taker, "BTC/USDC")
+        .market()
+        .price(50_000_000_000) // price
+        .size(2_000_000) // size - requesting 2 BTC but only 1 available
+        .build();
     server
         .test_engine
         .place_order(taker_order)
@@ -575,3 +555,86 @@ async fn test_balance_events_on_market_order_partial_fill() {
 
     ws.close(None).await.expect("Failed to close connection");
 }
+
+// ============================================================================
+// Balance Event Tests for Changes Outside the Engine
+// ============================================================================
+
+#[tokio::test]
+async fn test_balance_events_on_announced_external_changes() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(&server.ws_url)
+        .await
+        .expect("Failed to connect to WebSocket");
+    send_json(
+        &mut ws,
+        &ClientMessage::Subscribe {
+            channel: SubscriptionChannel::UserBalances,
+            market_id: None,
+            user_address: Some("faucet_user".to_string()),
+            interval: None,
+        },
+    )
+    .await
+    .expect("Failed to subscribe");
+    assert!(matches!(
+        receive_message(&mut ws).await.unwrap(),
+        ServerMessage::Subscribed { .. }
+    ));
+
+    // A faucet credit is announced by the handler, not the engine
+    let response = reqwest::Client::new()
+        .post(server.url("/api/drip"))
+        .json(&serde_json::json!({
+            "type": "faucet",
+            "user_address": "faucet_user",
+            "token_ticker": "USDC",
+            "amount": "1000000",
+            "signature": "sig",
+        }))
+        .send()
+        .await
+        .expect("Failed to send drip request");
+    assert!(response.status().is_success());
+
+    match receive_message(&mut ws).await.unwrap() {
+        ServerMessage::UserBalance {
+            token_ticker,
+            available,
+            ..
+        } => {
+            assert_eq!(token_ticker, "USDC");
+            assert_eq!(available, "1000000");
+        }
+        other => panic!("Expected Balance message, got: {:?}", other),
+    }
+
+    // So is a change written by a tool outside the backend
+    let db = server.db();
+    db.add_balance("faucet_user", "BTC", 5_000).await.unwrap();
+    sqlx::query("SELECT pg_notify('balance_updated', $1)")
+        .bind(r#"{"user_address":"faucet_user","token_ticker":"BTC"}"#)
+        .execute(&db.postgres)
+        .await
+        .unwrap();
+
+    match receive_message(&mut ws).await.unwrap() {
+        ServerMessage::UserBalance {
+            token_ticker,
+            available,
+            ..
+        } => {
+            assert_eq!(token_ticker, "BTC");
+            assert_eq!(available, "5000");
+        }
+        other => panic!("Expected Balance message, got: {:?}", other),
+    }
+
+    ws.close(None).await.expect("Failed to close connection");
+}
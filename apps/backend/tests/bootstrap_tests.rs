@@ -0,0 +1,187 @@
+use backend::bootstrap;
+use backend::config::Config;
+use backend::engine::ladder::LadderLayout;
+use exchange_test_utils::{TestDb, TestEngine, TestServer, TEST_ADMIN_TOKEN};
+use serde_json::json;
+
+const CONFIG: &str = r#"
+[[tokens]]
+ticker = "AAA"
+decimals = 8
+name = "Token A"
+
+[[tokens]]
+ticker = "USDC"
+decimals = 6
+name = "USD Coin"
+
+[[markets]]
+base_ticker = "AAA"
+quote_ticker = "USDC"
+tick_size = "1000"
+lot_size = "1000000"
+min_size = "1000000"
+maker_fee_bps = 5
+taker_fee_bps = 10
+price_ladder = { max_price = "1000000" }
+price_collar_bps = 500
+"#;
+
+const AAA_LADDER: LadderLayout = LadderLayout::Array {
+    min_price: 0,
+    max_price: 1_000_000,
+    tick_size: 1_000,
+};
+
+fn config() -> Config {
+    toml::from_str(CONFIG).expect("Failed to parse config")
+}
+
+#[tokio::test]
+async fn test_create_missing_creates_tokens_and_markets_once() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+
+    let report = bootstrap::create_missing(&test_db.db, &config())
+        .await
+        .expect("Failed to apply config");
+    assert_eq!(report.tokens_created, vec!["AAA", "USDC"]);
+    assert_eq!(report.markets_created, vec!["AAA/USDC"]);
+
+    let market = test_db.db.get_market("AAA/USDC").await.unwrap();
+    assert_eq!(market.tick_size, 1_000);
+    assert_eq!(market.maker_fee_bps, 5);
+
+    // Existing tokens and markets are left alone, even when the config differs
+    let mut changed = config();
+    changed.tokens[0].decimals = 18;
+    changed.markets[0].tick_size = "10".to_string();
+    let report = bootstrap::create_missing(&test_db.db, &changed)
+        .await
+        .expect("Failed to apply config again");
+    assert_eq!(report, Default::default());
+    assert_eq!(test_db.db.get_token("AAA").await.unwrap().decimals, 8);
+    assert_eq!(
+        test_db.db.get_market("AAA/USDC").await.unwrap().tick_size,
+        1_000
+    );
+}
+
+#[tokio::test]
+async fn test_create_missing_checks_config_before_writing() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+
+    let mut unknown_token = config();
+    unknown_token.markets[0].quote_ticker = "ZZZ".to_string();
+    assert!(bootstrap::create_missing(&test_db.db, &unknown_token)
+        .await
+        .is_err());
+
+    let mut bad_size = config();
+    bad_size.markets[0].lot_size = "lots".to_string();
+    assert!(bootstrap::create_missing(&test_db.db, &bad_size)
+        .await
+        .is_err());
+
+    assert!(test_db.db.get_token("AAA").await.is_err());
+}
+
+#[tokio::test]
+async fn test_apply_opens_new_markets_in_running_engine() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    let report = bootstrap::apply(&test_db.db, &engine.engine_tx, &config())
+        .await
+        .expect("Failed to apply config");
+    assert_eq!(report.markets_created, vec!["AAA/USDC"]);
+
+    let mut orderbooks = engine.orderbooks.write().await;
+    assert_eq!(orderbooks.get_or_create("AAA/USDC").layout(), AAA_LADDER);
+}
+
+#[tokio::test]
+async fn test_admin_create_market_opens_it_with_its_ladder() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = reqwest::Client::new();
+    let admin = |body: serde_json::Value| {
+        client
+            .post(server.url("/api/admin"))
+            .bearer_auth(TEST_ADMIN_TOKEN)
+            .json(&body)
+            .send()
+    };
+
+    for (ticker, decimals) in [("AAA", 8), ("USDC", 6)] {
+        let response = admin(json!({
+            "type": "create_token",
+            "ticker": ticker,
+            "decimals": decimals,
+            "name": ticker,
+        }))
+        .await
+        .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    let market = json!({
+        "type": "create_market",
+        "base_ticker": "AAA",
+        "quote_ticker": "USDC",
+        "tick_size": "1000",
+        "lot_size": "1000000",
+        "min_size": "1000000",
+        "maker_fee_bps": 5,
+        "taker_fee_bps": 10,
+        "price_ladder": { "max_price": "1000000" },
+        "price_collar_bps": 500,
+    });
+
+    // A ladder too wide to index is refused before anything is created
+    let mut bad_ladder = market.clone();
+    bad_ladder["price_ladder"]["max_price"] = json!("1000000000000000");
+    let response = admin(bad_ladder).await.unwrap();
+    assert_eq!(response.status(), 400);
+    assert!(server.db().get_market("AAA/USDC").await.is_err());
+
+    let response = admin(market).await.unwrap();
+    assert!(response.status().is_success());
+
+    let mut orderbooks = server.engine().orderbooks.write().await;
+    assert_eq!(orderbooks.get_or_create("AAA/USDC").layout(), AAA_LADDER);
+}
+
+#[tokio::test]
+async fn test_admin_requires_admin_token() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = reqwest::Client::new();
+    let create_token = json!({
+        "type": "create_token",
+        "ticker": "AAA",
+        "decimals": 8,
+        "name": "Token A",
+    });
+
+    let response = client
+        .post(server.url("/api/admin"))
+        .json(&create_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 401);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "UNAUTHORIZED");
+
+    let response = client
+        .post(server.url("/api/admin"))
+        .bearer_auth("wrong")
+        .json(&create_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 401);
+    assert!(server.db().get_token("AAA").await.is_err());
+}
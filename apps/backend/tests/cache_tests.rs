@@ -0,0 +1,207 @@
+use backend::cache::{keys, ReadCache};
+use backend::engine::markets::MarketRegistry;
+use backend::models::api::{ApiBookLevel, ApiTopOfBook, InfoResponse};
+use backend::models::domain::{EngineEvent, OrderbookLevel, OrderbookSnapshot, Side, Trade};
+use exchange_test_utils::{helpers, TestServer, TEST_ADMIN_TOKEN};
+use serde_json::json;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, Duration};
+
+fn snapshot(version: u64) -> OrderbookSnapshot {
+    OrderbookSnapshot {
+        market_id: "BTC/USDC".to_string(),
+        bids: vec![
+            OrderbookLevel {
+                price: 49_000,
+                size: 3,
+            },
+            OrderbookLevel {
+                price: 48_000,
+                size: 7,
+            },
+        ],
+        asks: vec![OrderbookLevel {
+            price: 51_000,
+            size: 2,
+        }],
+        timestamp: chrono::Utc::now(),
+        version,
+    }
+}
+
+fn trade() -> Trade {
+    Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: "BTC/USDC".to_string(),
+        buyer_address: "buyer".to_string(),
+        seller_address: "seller".to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price: 50_000,
+        size: 1,
+        side: Side::Buy,
+        timestamp: chrono::Utc::now(),
+        rfq: false,
+    }
+}
+
+#[tokio::test]
+async fn test_entries_expire_and_invalidate() {
+    let cache = ReadCache::default();
+    let key = keys::ticker("BTC/USDC");
+    assert_eq!(cache.get::<u64>(&key).await, None);
+
+    cache.set(&key, &42u64, Duration::from_millis(100)).await;
+    assert_eq!(cache.get::<u64>(&key).await, Some(42));
+
+    sleep(Duration::from_millis(150)).await;
+    assert_eq!(cache.get::<u64>(&key).await, None);
+
+    cache.set(&key, &42u64, Duration::from_secs(60)).await;
+    cache.invalidate(std::slice::from_ref(&key)).await;
+    assert_eq!(cache.get::<u64>(&key).await, None);
+}
+
+#[tokio::test]
+async fn test_entry_of_another_shape_is_a_miss() {
+    let cache = ReadCache::default();
+    cache
+        .set(keys::MARKETS, &"not a list", Duration::from_secs(60))
+        .await;
+    assert!(cache.get::<Vec<String>>(keys::MARKETS).await.is_none());
+}
+
+#[tokio::test]
+async fn test_updater_follows_engine_events() {
+    let cache = ReadCache::default();
+    let (event_tx, _) = broadcast::channel(16);
+    let updater = cache.clone().spawn_updater(event_tx.subscribe());
+    let market = MarketRegistry::new().intern("BTC/USDC");
+
+    // Snapshots refresh top of book
+    event_tx
+        .send(EngineEvent::OrderbookSnapshot {
+            market,
+            orderbook: snapshot(7),
+        })
+        .unwrap();
+    // Trades drop the market's ticker
+    cache
+        .set(&keys::ticker("BTC/USDC"), &1u64, Duration::from_secs(60))
+        .await;
+    event_tx
+        .send(EngineEvent::TradeExecuted {
+            market,
+            trade: trade(),
+        })
+        .unwrap();
+
+    drop(event_tx);
+    updater.await.unwrap();
+
+    let top: ApiTopOfBook = cache.get(&keys::top_of_book("BTC/USDC")).await.unwrap();
+    assert_eq!(
+        top.best_bid,
+        Some(ApiBookLevel {
+            price: "49000".to_string(),
+            size: "3".to_string(),
+        })
+    );
+    assert_eq!(
+        top.best_ask,
+        Some(ApiBookLevel {
+            price: "51000".to_string(),
+            size: "2".to_string(),
+        })
+    );
+    assert_eq!(top.version, 7);
+    assert_eq!(cache.get::<u64>(&keys::ticker("BTC/USDC")).await, None);
+}
+
+#[tokio::test]
+async fn test_top_of_book_endpoint() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    // Nothing has rested on the book yet
+    let url = server.url("/api/markets/BTC%2FUSDC/top-of-book");
+    let top: ApiTopOfBook = reqwest::get(&url).await.unwrap().json().await.unwrap();
+    assert_eq!(top.market_id, "BTC/USDC");
+    assert!(top.best_bid.is_none() && top.best_ask.is_none());
+
+    server
+        .engine()
+        .event_tx()
+        .send(EngineEvent::OrderbookSnapshot {
+            market: MarketRegistry::new().intern("BTC/USDC"),
+            orderbook: snapshot(3),
+        })
+        .unwrap();
+    sleep(Duration::from_millis(100)).await;
+    let top: ApiTopOfBook = reqwest::get(&url).await.unwrap().json().await.unwrap();
+    assert_eq!(top.version, 3);
+    assert_eq!(top.best_bid.unwrap().price, "49000");
+
+    let missing = reqwest::get(server.url("/api/markets/NOPE%2FUSDC/top-of-book"))
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn test_market_list_refreshes_when_admin_adds_a_market() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = reqwest::Client::new();
+    let all_markets = || async {
+        let response: InfoResponse = client
+            .post(server.url("/api/info"))
+            .json(&json!({ "type": "all_markets" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match response {
+            InfoResponse::AllMarkets { markets } => markets.len(),
+            other => panic!("Unexpected response {:?}", other),
+        }
+    };
+
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    assert_eq!(all_markets().await, 1);
+
+    // Written straight to the database, the cached list doesn't see it
+    helpers::create_market_with_tokens(&server.test_db, "ETH", "USDT")
+        .await
+        .expect("Failed to create market");
+    assert_eq!(all_markets().await, 1);
+
+    // Through the admin API, it does
+    let response = client
+        .post(server.url("/api/admin"))
+        .bearer_auth(TEST_ADMIN_TOKEN)
+        .json(&json!({
+            "type": "create_market",
+            "base_ticker": "ETH",
+            "quote_ticker": "USDC",
+            "tick_size": "1000",
+            "lot_size": "1000000",
+            "min_size": "1000000",
+            "maker_fee_bps": 5,
+            "taker_fee_bps": 10,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(all_markets().await, 3);
+}
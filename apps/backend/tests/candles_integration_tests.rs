@@ -1,7 +1,7 @@
 /// Integration tests for the full trade → ClickHouse → candles flow
 /// These tests verify end-to-end functionality from trade execution to candle generation
-use backend::models::domain::{OrderType, Side};
-use exchange_test_utils::{helpers, TestDb, TestEngine};
+use backend::models::domain::{Side, Trade};
+use exchange_test_utils::{helpers, OrderBuilder, TestDb, TestEngine};
 
 /// Test that trades are persisted to ClickHouse when engine executes them
 #[tokio::test]
@@ -26,27 +26,19 @@ async fn test_trades_persisted_to_clickhouse() {
         .expect("Failed to add USDC to buyer");
 
     // Execute a trade
-    let sell_order = TestEngine::create_order(
-        "seller",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        95000000000, // $95,000
-        1000000,     // 1 BTC
-    );
+    let sell_order = OrderBuilder::sell("seller", &market.id)
+        .limit(95000000000) // $95,000
+        .size(1000000) // 1 BTC
+        .build();
     engine
         .place_order(sell_order)
         .await
         .expect("Failed to place sell order");
 
-    let buy_order = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        95000000000,
-        1000000,
-    );
+    let buy_order = OrderBuilder::buy("buyer", &market.id)
+        .limit(95000000000)
+        .size(1000000)
+        .build();
     let result = engine
         .place_order(buy_order)
         .await
@@ -107,27 +99,19 @@ async fn test_candles_generated_from_trades() {
     ];
 
     for (price, size) in trades {
-        let sell_order = TestEngine::create_order(
-            "seller",
-            &market.id,
-            Side::Sell,
-            OrderType::Limit,
-            price,
-            size,
-        );
+        let sell_order = OrderBuilder::sell("seller", &market.id)
+            .limit(price)
+            .size(size)
+            .build();
         engine
             .place_order(sell_order)
             .await
             .expect("Failed to place sell order");
 
-        let buy_order = TestEngine::create_order(
-            "buyer",
-            &market.id,
-            Side::Buy,
-            OrderType::Limit,
-            price,
-            size,
-        );
+        let buy_order = OrderBuilder::buy("buyer", &market.id)
+            .limit(price)
+            .size(size)
+            .build();
         engine
             .place_order(buy_order)
             .await
@@ -232,3 +216,237 @@ async fn test_no_trades_means_no_candles() {
 
     assert_eq!(count, 0, "Expected no candles for market with no trades");
 }
+
+/// Test that market stats aggregate the last 24 hours of tick data
+#[tokio::test]
+async fn test_market_stats_cover_last_24_hours() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let now = chrono::Utc::now();
+    let trade = |hours_ago: i64, price: u128, size: u128| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "buyer".to_string(),
+        seller_address: "seller".to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price,
+        size,
+        side: Side::Buy,
+        timestamp: now - chrono::Duration::hours(hours_ago),
+        rfq: false,
+    };
+    test_db
+        .db
+        .insert_trades_to_clickhouse(&[
+            trade(25, 40_000_000_000, 5_000_000), // outside the window
+            trade(3, 50_000_000_000, 1_000_000),
+            trade(2, 52_000_000_000, 2_000_000),
+            trade(1, 55_000_000_000, 1_000_000),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let to = now.timestamp();
+    let stats = test_db
+        .db
+        .get_market_stats(&market.id, 8, to - 24 * 60 * 60, to)
+        .await
+        .expect("Failed to get market stats");
+
+    assert_eq!(stats.trade_count, 3);
+    assert_eq!(stats.base_volume, "4000000");
+    // $500 + $1,040 + $550 in USDC atoms
+    assert_eq!(stats.quote_volume, "2090000000");
+    assert_eq!(stats.open.as_deref(), Some("50000000000"));
+    assert_eq!(stats.high.as_deref(), Some("55000000000"));
+    assert_eq!(stats.low.as_deref(), Some("50000000000"));
+    assert_eq!(stats.close.as_deref(), Some("55000000000"));
+    assert_eq!(stats.change_percent, Some(10.0));
+
+    // A market with no trades in the window has volume but no prices
+    let empty = test_db
+        .db
+        .get_market_stats("ETH/USDC", 8, to - 24 * 60 * 60, to)
+        .await
+        .expect("Failed to get market stats");
+    assert_eq!(empty.trade_count, 0);
+    assert_eq!(empty.quote_volume, "0");
+    assert!(empty.open.is_none() && empty.close.is_none());
+    assert!(empty.change_percent.is_none());
+}
+
+/// Test that gap filling carries the close through intervals without trades
+#[test]
+fn test_fill_candle_gaps_carries_close_forward() {
+    use backend::db::candles::fill_candle_gaps;
+    use backend::models::api::ApiCandle;
+
+    let candle = |timestamp: u32, open: u128, close: u128, volume: u128| ApiCandle {
+        timestamp,
+        open,
+        high: open.max(close),
+        low: open.min(close),
+        close,
+        volume,
+    };
+    let bars = |candles: &[ApiCandle]| -> Vec<(u32, u128, u128)> {
+        candles
+            .iter()
+            .map(|c| (c.timestamp, c.close, c.volume))
+            .collect()
+    };
+
+    // The previous close fills the start, each close carries to the next trade and the end
+    let filled = fill_candle_gaps(
+        vec![candle(120, 10, 11, 5), candle(240, 11, 12, 7)],
+        Some(9),
+        60,
+        30,
+        300,
+    );
+    assert_eq!(
+        bars(&filled),
+        vec![
+            (60, 9, 0),
+            (120, 11, 5),
+            (180, 11, 0),
+            (240, 12, 7),
+            (300, 12, 0)
+        ]
+    );
+    assert_eq!(
+        (filled[2].open, filled[2].high, filled[2].low),
+        (11, 11, 11)
+    );
+
+    // Nothing to carry before the market's first trade
+    let filled = fill_candle_gaps(vec![candle(120, 10, 11, 5)], None, 60, 0, 180);
+    assert_eq!(bars(&filled), vec![(120, 11, 5), (180, 11, 0)]);
+    assert!(fill_candle_gaps(vec![], None, 60, 0, 180).is_empty());
+}
+
+/// Test that the candle query fills gaps unless asked not to
+#[tokio::test]
+async fn test_candles_for_api_fill_gaps() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let base = chrono::Utc::now().timestamp() / 60 * 60 - 10 * 60;
+    let trade = |at: i64, price: u128| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "buyer".to_string(),
+        seller_address: "seller".to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price,
+        size: 1_000_000,
+        side: Side::Buy,
+        timestamp: chrono::DateTime::from_timestamp(at, 0).unwrap(),
+        rfq: false,
+    };
+    test_db
+        .db
+        .insert_trades_to_clickhouse(&[
+            trade(base - 120, 100),
+            trade(base + 30, 110),
+            trade(base + 190, 120),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let candles = |count_back, fill_gaps| {
+        let db = test_db.db.clone();
+        let market_id = market.id.clone();
+        async move {
+            db.get_candles_for_api(
+                &market_id,
+                "1m",
+                base - 60,
+                base + 300,
+                count_back,
+                fill_gaps,
+            )
+            .await
+            .expect("Failed to get candles")
+            .iter()
+            .map(|c| (c.timestamp as i64 - base, c.close, c.volume))
+            .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        candles(None, true).await,
+        vec![
+            (-60, 100, 0),
+            (0, 110, 1_000_000),
+            (60, 110, 0),
+            (120, 110, 0),
+            (180, 120, 1_000_000),
+            (240, 120, 0),
+            (300, 120, 0),
+        ]
+    );
+    assert_eq!(
+        candles(Some(2), true).await,
+        vec![(240, 120, 0), (300, 120, 0)]
+    );
+    assert_eq!(
+        candles(None, false).await,
+        vec![(0, 110, 1_000_000), (180, 120, 1_000_000)]
+    );
+}
+
+/// Test that the analytics buffer drops and counts trades instead of waiting when full
+#[test]
+fn test_analytics_buffer_overflow_is_counted() {
+    use backend::engine::analytics::AnalyticsWriter;
+    use std::sync::atomic::Ordering;
+
+    // No task draining the buffer, so it stays full after two trades
+    let (writer, _task) = AnalyticsWriter::new(2);
+    for _ in 0..5 {
+        writer.record(helpers::sample_trade("BTC/USDC"));
+    }
+
+    let stats = writer.stats();
+    assert_eq!(stats.enqueued.load(Ordering::Relaxed), 2);
+    assert_eq!(stats.dropped.load(Ordering::Relaxed), 3);
+}
+
+/// Test that buffered trades are flushed to ClickHouse when the writer shuts down
+#[tokio::test]
+async fn test_analytics_writer_flushes_on_shutdown() {
+    use backend::engine::analytics::AnalyticsWriter;
+    use std::sync::atomic::Ordering;
+
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let (writer, task) = AnalyticsWriter::new(100);
+    let stats = writer.stats();
+
+    for _ in 0..10 {
+        writer.record(helpers::sample_trade("ETH/USDC"));
+    }
+
+    // Dropping the last writer closes the buffer; the task drains it and exits
+    let handle = tokio::spawn(task.run(test_db.db.clone()));
+    drop(writer);
+    handle.await.expect("Analytics task panicked");
+
+    assert_eq!(stats.written.load(Ordering::Relaxed), 10);
+    let count: u64 = test_db
+        .db
+        .clickhouse
+        .query("SELECT COUNT(*) FROM exchange.trades WHERE market_id = ?")
+        .bind("ETH/USDC")
+        .fetch_one::<u64>()
+        .await
+        .expect("Failed to query trades");
+    assert_eq!(count, 10);
+}
@@ -22,6 +22,7 @@ async fn test_clickhouse_trades_schema_matches_struct() {
         size: 1000000,
         side: "buy".to_string(),
         timestamp: 1234567890,
+        rfq: false,
     };
 
     // This will panic if schema doesn't match struct
@@ -89,6 +90,7 @@ async fn test_trades_table_has_all_required_columns() {
         "price",
         "size",
         "timestamp",
+        "rfq",
     ];
 
     for col in required {
@@ -163,6 +165,7 @@ async fn test_candles_aggregation_reduces_storage() {
             size: 1000000,
             side: "buy".to_string(),
             timestamp: base_timestamp + i, // Different seconds within same minute
+            rfq: false,
         };
 
         let mut insert = db
@@ -306,6 +309,7 @@ async fn test_trades_roundtrip() {
         size: 1000000,
         side: "buy".to_string(),
         timestamp: 1234567890,
+        rfq: false,
     };
 
     // Insert trade
@@ -0,0 +1,287 @@
+use exchange_test_utils::{helpers, Contract, Probe, TestServer, TEST_ADMIN_TOKEN};
+use reqwest::Method;
+use serde_json::{json, Value};
+
+const MARKET: &str = "BTC%2FUSDC";
+
+/// One request per documented operation, reads of a seeded market and user
+/// plus a few writes; the order matters, as the faucet funds the order
+fn probes(from: i64, to: i64) -> Vec<Probe> {
+    let user = |body: Value| Probe::post("/api/user", body);
+    let unknown = uuid::Uuid::new_v4();
+    let range = format!("from={}&to={}", from, to);
+    let market = |route: &str, query: &str| match query {
+        "" => Probe::get(format!("/api/markets/{}/{}", MARKET, route)),
+        query => Probe::get(format!("/api/markets/{}/{}?{}", MARKET, route, query)),
+    };
+
+    vec![
+        Probe::get("/api/health"),
+        Probe::get("/api/metrics"),
+        Probe::post(
+            "/api/admin",
+            json!({ "type": "create_token", "ticker": "ETH", "decimals": 18, "name": "Ether" }),
+        )
+        .with_bearer(TEST_ADMIN_TOKEN),
+        Probe::post("/api/kill-switch", json!({ "type": "status" })).with_bearer(TEST_ADMIN_TOKEN),
+        Probe::post(
+            "/api/drip",
+            json!({ "type": "faucet", "user_address": "alice", "token_ticker": "USDC",
+                    "amount": "100000000000", "signature": "sig" }),
+        ),
+        Probe::post("/api/info", json!({ "type": "all_markets" })),
+        Probe::post(
+            "/api/info",
+            json!({ "type": "market_details", "market_id": "BTC/USDC" }),
+        ),
+        Probe::post(
+            "/api/info",
+            json!({ "type": "token_details", "ticker": "NOPE" }),
+        ),
+        Probe::post(
+            "/api/candles",
+            json!({ "market_id": "BTC/USDC", "interval": "1m", "from": from, "to": to }),
+        ),
+        Probe::post(
+            "/api/trade",
+            json!({ "type": "place_order", "user_address": "alice", "market_id": "BTC/USDC",
+                    "side": "buy", "order_type": "limit", "price": "50000000000",
+                    "size": "1000000", "signature": "sig" }),
+        ),
+        Probe::post(
+            "/api/oco",
+            json!({ "user_address": "alice", "market_id": "BTC/USDC", "side": "buy",
+                    "size": "1000000", "signature": "sig",
+                    "first": { "order_type": "limit", "price": "49000000000" },
+                    "second": { "order_type": "stop_limit", "price": "51500000000",
+                                "trigger_price": "51000000000" } }),
+        ),
+        Probe::post(
+            "/api/trade",
+            json!({ "type": "cancel_all_orders", "user_address": "alice", "signature": "sig" }),
+        ),
+        Probe::post("/api/rfq", json!({ "type": "open_requests" })),
+        user(json!({ "type": "balances", "user_address": "alice" })),
+        user(json!({ "type": "orders", "user_address": "alice" })),
+        user(json!({ "type": "summary", "user_address": "alice" })),
+        user(json!({ "type": "trades", "user_address": "alice" })),
+        user(json!({ "type": "summary", "user_address": "nobody" })),
+        Probe::get("/api/events"),
+        Probe::get("/api/events/unknown"),
+        Probe::get(format!("/api/exports/{}", unknown)),
+        Probe::get(format!("/api/exports/{}/download", unknown)),
+        Probe::get("/api/leaderboard"),
+        market("depth", &range),
+        market("flow", &range),
+        market("funding", &range),
+        market("index-prices", &range),
+        market("open-interest", &range),
+        market("twap", &range),
+        market("vwap", &range),
+        market("perpetual", ""),
+        market("risk", ""),
+        market("stats", ""),
+        market("top-of-book", ""),
+        Probe::get(format!(
+            "/api/orderbook/{}/l3?user_address=alice&signature=sig",
+            MARKET
+        )),
+        Probe::get("/api/users/alice/fees"),
+        Probe::get(format!("/api/users/alice/fills/export?{}", range)),
+        Probe::get("/api/users/alice/margin"),
+        Probe::get("/api/users/alice/pnl"),
+        Probe::get("/api/users/alice/positions"),
+        Probe::get("/api/users/alice/statements"),
+        Probe::get("/api/users/alice/statements/2026-01"),
+    ]
+}
+
+// ============================================================================
+// Contract Checker Tests
+// ============================================================================
+
+#[test]
+fn test_probes_cover_every_operation_as_documented() {
+    let contract = Contract::load();
+    let probes = probes(0, 3600);
+
+    for (method, template) in contract.operations() {
+        assert!(
+            probes.iter().any(|probe| probe.method == method
+                && contract.operation_for(&method, &probe.path).as_deref()
+                    == Some(template.as_str())),
+            "No probe covers {} {}",
+            method,
+            template
+        );
+    }
+    for probe in &probes {
+        let violations = contract.check_request(probe);
+        assert!(violations.is_empty(), "{:#?}", violations);
+    }
+}
+
+#[test]
+fn test_concrete_paths_resolve_to_their_templates() {
+    let contract = Contract::load();
+
+    assert_eq!(
+        contract.operation_for(
+            &Method::GET,
+            &format!("/api/markets/{}/depth?from=0&to=1", MARKET)
+        ),
+        Some("/api/markets/{market_id}/depth".to_string())
+    );
+    assert_eq!(
+        contract.operation_for(&Method::GET, "/api/users/alice/statements/2026-01"),
+        Some("/api/users/{address}/statements/{month}".to_string())
+    );
+    assert_eq!(contract.operation_for(&Method::POST, "/api/health"), None);
+    assert_eq!(contract.operation_for(&Method::GET, "/api/nope"), None);
+    assert_eq!(
+        contract.operation_for(&Method::GET, "/api/markets//stats"),
+        None
+    );
+}
+
+#[test]
+fn test_requests_off_the_spec_are_reported() {
+    let contract = Contract::load();
+
+    let missing_size = Probe::post(
+        "/api/trade",
+        json!({ "type": "place_order", "user_address": "alice", "market_id": "BTC/USDC",
+                "side": "buy", "order_type": "limit", "price": "1", "signature": "sig" }),
+    );
+    let violations = contract.check_request(&missing_size);
+    assert!(
+        violations.iter().any(|v| v.contains("'size'")),
+        "{:#?}",
+        violations
+    );
+
+    let bad_side = Probe::post(
+        "/api/trade",
+        json!({ "type": "place_order", "user_address": "alice", "market_id": "BTC/USDC",
+                "side": "up", "order_type": "limit", "price": "1", "size": "1",
+                "signature": "sig" }),
+    );
+    let violations = contract.check_request(&bad_side);
+    assert!(
+        violations.iter().any(|v| v.contains("$.side")),
+        "{:#?}",
+        violations
+    );
+
+    let missing_range = Probe::get(format!("/api/markets/{}/depth?from=0", MARKET));
+    assert_eq!(
+        contract.check_request(&missing_range),
+        vec![format!(
+            "GET /api/markets/{}/depth?from=0: missing required query parameter 'to'",
+            MARKET
+        )]
+    );
+    assert_eq!(contract.check_request(&Probe::get("/api/nope")).len(), 1);
+}
+
+#[test]
+fn test_responses_off_the_spec_are_reported() {
+    let contract = Contract::load();
+    let json_type = Some("application/json");
+    let error = json!({ "error": "Market not found", "code": "MARKET_NOT_FOUND" }).to_string();
+    let stats = format!("/api/markets/{}/stats", MARKET);
+
+    assert!(contract
+        .check_response(&Method::GET, &stats, 404, json_type, error.as_bytes())
+        .is_empty());
+    // Statuses, content types and bodies the spec doesn't describe
+    let violations =
+        contract.check_response(&Method::GET, &stats, 409, json_type, error.as_bytes());
+    assert!(violations[0].contains("409, which is not documented"));
+    let violations =
+        contract.check_response(&Method::GET, &stats, 404, Some("text/plain"), b"Not found");
+    assert!(violations[0].contains("content type 'text/plain'"));
+    let violations =
+        contract.check_response(&Method::GET, &stats, 404, json_type, br#"{"error":"x"}"#);
+    assert!(violations[0].contains("missing required field 'code'"));
+    let violations = contract.check_response(&Method::GET, &stats, 404, json_type, b"not json");
+    assert!(violations[0].contains("not valid JSON"));
+
+    // Non-JSON bodies are checked for their content type only
+    assert!(contract
+        .check_response(
+            &Method::GET,
+            "/api/metrics",
+            200,
+            Some("text/plain; version=0.0.4"),
+            b"exchange_degraded 0\n",
+        )
+        .is_empty());
+}
+
+#[test]
+fn test_validation_follows_refs_nullables_and_tagged_unions() {
+    let contract = Contract::load();
+    let info = json!({ "$ref": "#/components/schemas/InfoResponse" });
+
+    assert!(contract
+        .validate(&info, &json!({ "type": "all_markets", "markets": [] }))
+        .is_empty());
+    // A body that fits no variant reports against the one its tag names
+    let errors = contract.validate(
+        &info,
+        &json!({ "type": "all_markets", "markets": [{ "id": "BTC/USDC" }] }),
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.starts_with("$.markets[0]: missing")),
+        "{:#?}",
+        errors
+    );
+    assert!(!contract
+        .validate(&info, &json!({ "type": "everything" }))
+        .is_empty());
+
+    let error = json!({ "$ref": "#/components/schemas/ErrorResponse" });
+    assert!(contract
+        .validate(
+            &error,
+            &json!({ "error": "x", "code": "MARKET_NOT_FOUND", "reason": null })
+        )
+        .is_empty());
+    // Codes come from the catalogue, not free text
+    assert!(!contract
+        .validate(&error, &json!({ "error": "x", "code": "X" }))
+        .is_empty());
+    assert!(!contract
+        .validate(&error, &json!({ "error": "x", "code": 1 }))
+        .is_empty());
+    assert_eq!(
+        contract.validate(&json!({ "$ref": "#/components/schemas/Nope" }), &json!({})),
+        vec!["$: unresolved $ref '#/components/schemas/Nope'".to_string()]
+    );
+}
+
+// ============================================================================
+// Contract Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_every_documented_route_matches_the_spec() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_user(&server.test_db, "alice")
+        .await
+        .expect("Failed to create user");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let to = chrono::Utc::now().timestamp();
+    Contract::load()
+        .verify(&server, &probes(to - 3600, to))
+        .await
+        .unwrap();
+}
@@ -1,6 +1,10 @@
+use backend::models::db::{BalanceRow, OrderRow};
+use backend::models::domain::{Balance, Order, Side, Trade};
+use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
-use exchange_test_utils::{helpers, TestDb};
+use exchange_test_utils::{helpers, OrderBuilder, TestDb};
 use std::str::FromStr;
+use uuid::Uuid;
 
 #[tokio::test]
 async fn test_user_crud_operations() {
@@ -298,3 +302,202 @@ async fn test_market_creation_with_missing_tokens() {
     let error = result.unwrap_err();
     assert!(error.to_string().contains("Token 'BTC' does not exist"));
 }
+
+#[tokio::test]
+async fn test_apply_balance_changes_batch() {
+    use backend::db::balances::{BalanceChange, BalanceChanges};
+
+    let test_db = TestDb::setup()
+        .await
+        .expect("Failed to setup test database");
+    helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    for user in ["alice", "bob"] {
+        helpers::create_user(&test_db, user)
+            .await
+            .expect("Failed to create user");
+    }
+
+    // Alice has 1000 USDC with 400 locked; bob has no USDC row yet
+    test_db.db.add_balance("alice", "USDC", 1000).await.unwrap();
+    test_db.db.lock_balance("alice", "USDC", 400).await.unwrap();
+
+    let mut changes = BalanceChanges::new();
+    changes.insert(
+        ("alice".to_string(), "USDC".to_string()),
+        BalanceChange {
+            credit: 0,
+            debit: 300,
+            unlock: 400,
+        },
+    );
+    changes.insert(
+        ("bob".to_string(), "USDC".to_string()),
+        BalanceChange {
+            credit: 297,
+            ..Default::default()
+        },
+    );
+
+    let mut tx = test_db.db.begin_transaction().await.unwrap();
+    test_db
+        .db
+        .apply_balance_changes_tx(&mut tx, &changes)
+        .await
+        .expect("Failed to apply balance changes");
+    tx.commit().await.unwrap();
+
+    let alice = test_db.db.get_balance("alice", "USDC").await.unwrap();
+    assert_eq!(alice.amount, 700);
+    assert_eq!(alice.open_interest, 0);
+
+    let bob = test_db.db.get_balance("bob", "USDC").await.unwrap();
+    assert_eq!(bob.amount, 297);
+    assert_eq!(bob.open_interest, 0);
+}
+
+#[tokio::test]
+async fn test_orders_are_partitioned_by_month() {
+    let test_db = TestDb::setup()
+        .await
+        .expect("Failed to setup test database");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    helpers::create_user(&test_db, "alice")
+        .await
+        .expect("Failed to create user");
+    let db = &test_db.db;
+
+    // The migration already created this month and the next few
+    assert_eq!(db.ensure_order_partitions(3).await.unwrap(), 0);
+    assert_eq!(db.ensure_order_partitions(4).await.unwrap(), 1);
+
+    let partition_of = |id: uuid::Uuid| async move {
+        sqlx::query_scalar::<_, String>("SELECT tableoid::regclass::TEXT FROM orders WHERE id = $1")
+            .bind(id)
+            .fetch_one(&db.postgres)
+            .await
+            .unwrap()
+    };
+
+    let now = Utc::now();
+    let current = OrderBuilder::buy("alice", &market.id).build();
+    db.create_order(&current).await.unwrap();
+    assert_eq!(
+        partition_of(current.id).await,
+        format!("orders_{}", now.format("%Y_%m"))
+    );
+
+    // Orders outside every month land in the default partition...
+    let mut old = OrderBuilder::buy("alice", &market.id).build();
+    old.created_at = DateTime::from_str("2024-03-15T12:00:00Z").unwrap();
+    db.create_order(&old).await.unwrap();
+    assert_eq!(partition_of(old.id).await, "orders_default");
+
+    // ...until their month gets a partition of its own
+    let created: i32 = sqlx::query_scalar("SELECT ensure_order_partitions($1, 0)")
+        .bind(old.created_at)
+        .fetch_one(&db.postgres)
+        .await
+        .unwrap();
+    assert!(created > 0);
+    assert_eq!(partition_of(old.id).await, "orders_2024_03");
+    assert_eq!(db.get_order(&old.id).await.unwrap().id, old.id);
+}
+
+#[tokio::test]
+async fn test_order_ids_stay_unique_across_partitions() {
+    let test_db = TestDb::setup()
+        .await
+        .expect("Failed to setup test database");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    for user in ["alice", "bob"] {
+        helpers::create_user(&test_db, user)
+            .await
+            .expect("Failed to create user");
+    }
+    let db = &test_db.db;
+
+    let buy = OrderBuilder::buy("alice", &market.id).build();
+    let sell = OrderBuilder::sell("bob", &market.id).build();
+    db.create_order(&buy).await.unwrap();
+    db.create_order(&sell).await.unwrap();
+
+    // The same id in another month's partition is refused
+    let mut repeated = buy.clone();
+    repeated.created_at = DateTime::from_str("2024-03-15T12:00:00Z").unwrap();
+    assert!(db.create_order(&repeated).await.is_err());
+
+    // Trades only reference orders that exist
+    let trade = Trade {
+        id: Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "alice".to_string(),
+        seller_address: "bob".to_string(),
+        buyer_order_id: buy.id,
+        seller_order_id: sell.id,
+        price: buy.price,
+        size: 1,
+        side: Side::Sell,
+        timestamp: Utc::now(),
+        rfq: false,
+    };
+    db.create_trade(&trade).await.unwrap();
+    let orphan = Trade {
+        id: Uuid::new_v4(),
+        buyer_order_id: Uuid::new_v4(),
+        ..trade
+    };
+    assert!(db.create_trade(&orphan).await.is_err());
+}
+
+#[test]
+fn test_corrupt_rows_are_refused() {
+    let now = Utc::now();
+    let order_row = |side: &str, filled_size: &str| OrderRow {
+        id: Uuid::new_v4(),
+        user_address: "alice".to_string(),
+        market_id: "BTC/USDC".to_string(),
+        price: BigDecimal::from(50_000_000_000u64),
+        size: BigDecimal::from(1_000_000u64),
+        side: side.to_string(),
+        order_type: "limit".to_string(),
+        status: "pending".to_string(),
+        filled_size: BigDecimal::from_str(filled_size).unwrap(),
+        created_at: now,
+        updated_at: now,
+        cancel_reason: None,
+        trigger_price: None,
+        trigger_source: "last_trade".to_string(),
+        time_in_force: "gtc".to_string(),
+        expires_at: None,
+    };
+
+    let order = Order::try_from(order_row("sell", "250000")).unwrap();
+    assert_eq!(order.side, Side::Sell);
+    assert_eq!(order.filled_size, 250_000);
+
+    // Unknown names and non-atom amounts are errors, not a default value
+    assert!(Order::try_from(order_row("sideways", "0")).is_err());
+    assert!(Order::try_from(order_row("buy", "0.5")).is_err());
+    assert!(Order::try_from(order_row("buy", "-1")).is_err());
+
+    let balance_row = |amount: &str| BalanceRow {
+        user_address: "alice".to_string(),
+        token_ticker: "USDC".to_string(),
+        amount: BigDecimal::from_str(amount).unwrap(),
+        open_interest: BigDecimal::from(0),
+        updated_at: now,
+    };
+    assert_eq!(
+        Balance::try_from(balance_row(&u128::MAX.to_string()))
+            .unwrap()
+            .amount,
+        u128::MAX
+    );
+    assert!(Balance::try_from(balance_row("340282366920938463463374607431768211456")).is_err());
+}
@@ -0,0 +1,134 @@
+use backend::db::stats::{DbStats, DEFAULT_SLOW_QUERY_THRESHOLD};
+use exchange_test_utils::{helpers, TestDb};
+use std::time::Duration;
+
+// ============================================================================
+// Query Latency Tests
+// ============================================================================
+
+#[test]
+fn test_queries_are_counted_per_tag() {
+    let stats = DbStats::default();
+    assert_eq!(stats.slow_threshold(), DEFAULT_SLOW_QUERY_THRESHOLD);
+
+    stats.record_query("create_order", Duration::from_millis(2), true);
+    stats.record_query("create_order", Duration::from_millis(8), false);
+    stats.record_query("lock_balance", Duration::from_millis(1), true);
+
+    let queries = stats.queries();
+    let create_order = &queries["create_order"];
+    assert_eq!(create_order.count, 2);
+    assert_eq!(create_order.errors, 1);
+    assert_eq!(create_order.slow, 0);
+    assert_eq!(create_order.total, Duration::from_millis(10));
+    assert_eq!(create_order.max, Duration::from_millis(8));
+    assert_eq!(queries["lock_balance"].count, 1);
+}
+
+#[test]
+fn test_queries_at_the_threshold_count_as_slow() {
+    let stats = DbStats::new(Duration::from_millis(50));
+    stats.record_query("get_market", Duration::from_millis(49), true);
+    stats.record_query("get_market", Duration::from_millis(50), true);
+    stats.record_query("get_market", Duration::from_secs(3), true);
+
+    assert_eq!(stats.queries()["get_market"].slow, 2);
+}
+
+#[test]
+fn test_latency_histogram_is_cumulative() {
+    let stats = DbStats::default();
+    stats.record_query("create_trades", Duration::from_micros(500), true);
+    stats.record_query("create_trades", Duration::from_millis(20), true);
+    stats.record_query("create_trades", Duration::from_secs(10), true);
+
+    let metrics = stats.render_metrics();
+    for line in [
+        "exchange_db_query_duration_seconds_bucket{tag=\"create_trades\",le=\"0.001\"} 1",
+        "exchange_db_query_duration_seconds_bucket{tag=\"create_trades\",le=\"0.01\"} 1",
+        "exchange_db_query_duration_seconds_bucket{tag=\"create_trades\",le=\"0.025\"} 2",
+        "exchange_db_query_duration_seconds_bucket{tag=\"create_trades\",le=\"5\"} 2",
+        // Over the last bucket only shows up in +Inf
+        "exchange_db_query_duration_seconds_bucket{tag=\"create_trades\",le=\"+Inf\"} 3",
+        "exchange_db_query_duration_seconds_count{tag=\"create_trades\"} 3",
+        "exchange_db_slow_queries_total{tag=\"create_trades\"} 1",
+        "exchange_db_query_errors_total{tag=\"create_trades\"} 0",
+    ] {
+        assert!(metrics.contains(line), "missing {:?} in\n{}", line, metrics);
+    }
+}
+
+// ============================================================================
+// ClickHouse Insert Tests
+// ============================================================================
+
+#[test]
+fn test_inserts_are_counted_per_table() {
+    let stats = DbStats::default();
+    stats.record_insert("trades", 100, Duration::from_millis(30), true);
+    stats.record_insert("trades", 50, Duration::from_millis(20), true);
+    stats.record_insert("trades", 25, Duration::from_millis(5), false);
+    stats.record_insert("depth_metrics", 4, Duration::from_millis(1), true);
+
+    let inserts = stats.inserts();
+    let trades = inserts["trades"];
+    assert_eq!(trades.batches, 2);
+    assert_eq!(trades.rows, 150);
+    assert_eq!(trades.failed_batches, 1);
+    assert_eq!(trades.failed_rows, 25);
+    assert_eq!(trades.total, Duration::from_millis(55));
+    assert_eq!(inserts["depth_metrics"].rows, 4);
+
+    let metrics = stats.render_metrics();
+    for line in [
+        "exchange_clickhouse_insert_batches_total{table=\"trades\"} 2",
+        "exchange_clickhouse_insert_rows_total{table=\"trades\"} 150",
+        "exchange_clickhouse_failed_batches_total{table=\"trades\"} 1",
+        "exchange_clickhouse_failed_rows_total{table=\"trades\"} 25",
+        "exchange_clickhouse_insert_seconds_total{table=\"trades\"} 0.055",
+        "exchange_clickhouse_insert_rows_total{table=\"depth_metrics\"} 4",
+    ] {
+        assert!(metrics.contains(line), "missing {:?} in\n{}", line, metrics);
+    }
+}
+
+// ============================================================================
+// Database Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_tagged_queries_and_inserts_are_recorded() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    // The handle may be shared with earlier tests, so compare against a baseline
+    let stats = &test_db.db.stats;
+    let before = stats.queries().get("get_market").map_or(0, |q| q.count);
+    let rows_before = stats.inserts().get("trades").map_or(0, |i| i.rows);
+
+    test_db
+        .db
+        .get_market(&market.id)
+        .await
+        .expect("Failed to get market");
+    assert!(test_db.db.get_market("NOPE/USDC").await.is_err());
+    let trades = [
+        helpers::sample_trade(&market.id),
+        helpers::sample_trade(&market.id),
+    ];
+    test_db
+        .db
+        .insert_trades_to_clickhouse(&trades)
+        .await
+        .expect("Failed to insert trades");
+
+    // A missing market is still a successful query
+    let get_market = &stats.queries()["get_market"];
+    assert_eq!(get_market.count, before + 2);
+    assert_eq!(stats.inserts()["trades"].rows, rows_before + 2);
+
+    let metrics = test_db.db.render_metrics();
+    assert!(metrics.contains("exchange_db_pool_connections "));
+    assert!(metrics.contains("exchange_db_query_duration_seconds_count{tag=\"get_market\"}"));
+}
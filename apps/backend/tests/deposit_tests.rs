@@ -0,0 +1,311 @@
+use axum::{extract::State, routing::post, Json, Router};
+use backend::balance_notify;
+use backend::config::{Config, DepositConfig, DepositTokenConfig, TokenConfig};
+use backend::deposits::rpc::{address_topic, parse_hex_u128, EvmRpc, RpcLog, TRANSFER_TOPIC};
+use backend::deposits::{DepositToken, DepositWatcher, PollOutcome, MAX_BLOCK_RANGE};
+use backend::models::domain::EngineEvent;
+use exchange_test_utils::{helpers, TestDb};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+const USDC_CONTRACT: &str = "0xaf88d065e77c8cc2239327c5edb3a432268e5831";
+const DEPOSIT_ADDRESS: &str = "0x00000000000000000000000000000000000d3905";
+const ALICE: &str = "0xa11ce00000000000000000000000000000000001";
+
+fn transfer_log(from: &str, to: &str, amount: u128, block: u64, log_index: u64) -> Value {
+    json!({
+        "address": USDC_CONTRACT,
+        "topics": [TRANSFER_TOPIC, address_topic(from), address_topic(to)],
+        "data": format!("0x{:064x}", amount),
+        "blockNumber": format!("0x{:x}", block),
+        "transactionHash": format!("0x{:064x}", block * 1000 + log_index),
+        "logIndex": format!("0x{:x}", log_index),
+        "removed": false,
+    })
+}
+
+// ============================================================================
+// Decoding Tests
+// ============================================================================
+
+#[test]
+fn test_transfer_logs_decode() {
+    let log: RpcLog =
+        serde_json::from_value(transfer_log(ALICE, DEPOSIT_ADDRESS, 2_500_000, 100, 3)).unwrap();
+    let transfer = log.transfer().expect("Failed to decode transfer");
+    assert_eq!(transfer.contract, USDC_CONTRACT);
+    assert_eq!(transfer.from, ALICE);
+    assert_eq!(transfer.to, DEPOSIT_ADDRESS);
+    assert_eq!(transfer.amount, 2_500_000);
+    assert_eq!(transfer.block_number, 100);
+    assert_eq!(transfer.log_index, 3);
+
+    // Reorged-out logs, other events and amounts beyond u128 are skipped
+    let mut removed = log.clone();
+    removed.removed = true;
+    assert_eq!(removed.transfer(), None);
+    let mut approval = log.clone();
+    approval.topics[0] = format!("0x{:064x}", 1);
+    assert_eq!(approval.transfer(), None);
+    let mut huge = log;
+    huge.data = format!("0x1{:032x}", 0);
+    assert_eq!(huge.transfer(), None);
+}
+
+#[test]
+fn test_hex_quantities_and_topics() {
+    assert_eq!(parse_hex_u128("0x0"), Some(0));
+    assert_eq!(parse_hex_u128("0x"), Some(0));
+    assert_eq!(parse_hex_u128("0x1b4"), Some(436));
+    assert_eq!(parse_hex_u128(&format!("0x{:064x}", 7)), Some(7));
+    assert_eq!(parse_hex_u128("1b4"), None);
+    assert_eq!(parse_hex_u128("0xzz"), None);
+
+    assert_eq!(
+        address_topic("0xAbCd000000000000000000000000000000000001"),
+        "0x000000000000000000000000abcd000000000000000000000000000000000001"
+    );
+}
+
+#[test]
+fn test_deposit_amounts_scale_to_exchange_decimals() {
+    let token = |chain_decimals, exchange_decimals| DepositToken {
+        ticker: "WETH".to_string(),
+        contract: USDC_CONTRACT.to_string(),
+        chain_decimals,
+        exchange_decimals,
+    };
+
+    assert_eq!(token(6, 6).to_exchange_amount(1_234_567), Some(1_234_567));
+    // 18 decimals on chain, 8 on the exchange: dust below 1e-8 is dropped
+    assert_eq!(
+        token(18, 8).to_exchange_amount(1_500_000_009_999_999_999),
+        Some(150_000_000)
+    );
+    assert_eq!(token(18, 8).to_exchange_amount(9_999_999_999), Some(0));
+    assert_eq!(token(6, 8).to_exchange_amount(5), Some(500));
+    assert_eq!(token(0, 38).to_exchange_amount(u128::MAX), None);
+}
+
+// ============================================================================
+// Watcher Tests
+// ============================================================================
+
+/// Chain head and logs served by the mock node
+#[derive(Default)]
+struct MockChain {
+    head: u64,
+    logs: Vec<Value>,
+}
+
+/// JSON-RPC node answering `eth_blockNumber` and `eth_getLogs` from `chain`
+async fn start_node(chain: Arc<Mutex<MockChain>>) -> String {
+    async fn rpc(
+        State(chain): State<Arc<Mutex<MockChain>>>,
+        Json(request): Json<Value>,
+    ) -> Json<Value> {
+        let chain = chain.lock().unwrap();
+        let result = match request["method"].as_str().unwrap() {
+            "eth_blockNumber" => json!(format!("0x{:x}", chain.head)),
+            "eth_getLogs" => {
+                let filter = &request["params"][0];
+                let block = |key: &str| parse_hex_u128(filter[key].as_str().unwrap()).unwrap();
+                let (from, to) = (block("fromBlock"), block("toBlock"));
+                let logs: Vec<Value> = chain
+                    .logs
+                    .iter()
+                    .filter(|log| {
+                        let number = parse_hex_u128(log["blockNumber"].as_str().unwrap()).unwrap();
+                        (from..=to).contains(&number)
+                    })
+                    .cloned()
+                    .collect();
+                json!(logs)
+            }
+            method => panic!("Unexpected method {}", method),
+        };
+        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+
+    let app = Router::new().route("/", post(rpc)).with_state(chain);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn deposit_config(start_block: Option<u64>) -> Config {
+    Config {
+        markets: Vec::new(),
+        tokens: vec![TokenConfig {
+            ticker: "USDC".to_string(),
+            decimals: 6,
+            name: "USD Coin".to_string(),
+        }],
+        deposits: Some(DepositConfig {
+            chain_id: 42161,
+            confirmations: 3,
+            deposit_addresses: vec!["0x00000000000000000000000000000000000D3905".to_string()],
+            start_block,
+            tokens: vec![DepositTokenConfig {
+                ticker: "USDC".to_string(),
+                contract: USDC_CONTRACT.to_string(),
+                decimals: None,
+            }],
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_rpc_client_reads_head_and_transfers() {
+    let chain = Arc::new(Mutex::new(MockChain {
+        head: 0x1b4,
+        logs: vec![
+            transfer_log(ALICE, DEPOSIT_ADDRESS, 1, 10, 0),
+            transfer_log(ALICE, DEPOSIT_ADDRESS, 2, 20, 0),
+        ],
+    }));
+    let rpc = EvmRpc::new(start_node(chain).await);
+
+    assert_eq!(rpc.block_number().await.unwrap(), 436);
+    let transfers = rpc
+        .transfer_logs(
+            &[USDC_CONTRACT.to_string()],
+            &[DEPOSIT_ADDRESS.to_string()],
+            15,
+            25,
+        )
+        .await
+        .unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].amount, 2);
+
+    assert!(EvmRpc::new("http://127.0.0.1:1")
+        .block_number()
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_confirmed_deposits_are_credited_once() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test db");
+    let db = test_db.db.clone();
+    helpers::create_token(&test_db, "USDC", 6, "USD Coin")
+        .await
+        .expect("Failed to create token");
+    // An existing user is matched regardless of address case
+    let alice = "0xA11Ce00000000000000000000000000000000001";
+    db.create_user(alice.to_string()).await.unwrap();
+
+    let chain = Arc::new(Mutex::new(MockChain {
+        head: 100,
+        logs: vec![
+            transfer_log(ALICE, DEPOSIT_ADDRESS, 5_000_000, 97, 0),
+            transfer_log(ALICE, DEPOSIT_ADDRESS, 1_000_000, 97, 1),
+            // Not yet confirmed at head 100
+            transfer_log(ALICE, DEPOSIT_ADDRESS, 7_000_000, 99, 0),
+            // Sent elsewhere
+            transfer_log(DEPOSIT_ADDRESS, ALICE, 9_000_000, 98, 0),
+        ],
+    }));
+    let url = start_node(chain.clone()).await;
+    let (event_tx, mut event_rx) = broadcast::channel(16);
+    balance_notify::spawn_balance_listener(db.clone(), event_tx);
+    let watcher = DepositWatcher::new(db.clone(), &url, &deposit_config(Some(90)))
+        .expect("Failed to create watcher");
+    // Let the listener subscribe before anything is credited
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let outcome = watcher.poll().await.expect("Failed to poll");
+    assert_eq!(
+        outcome,
+        PollOutcome {
+            credited: 2,
+            caught_up: true
+        }
+    );
+    assert_eq!(db.get_deposit_cursor(42161).await.unwrap(), Some(98));
+    assert_eq!(
+        db.get_balance(alice, "USDC").await.unwrap().amount,
+        6_000_000
+    );
+    // Each credit is announced, and read back as the balance is by then
+    for _ in 0..2 {
+        let event = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
+            .await
+            .expect("No balance update announced")
+            .unwrap();
+        match event {
+            EngineEvent::BalanceUpdated { balance } => {
+                assert_eq!(balance.user_address, alice);
+                assert_eq!(balance.token_ticker, "USDC");
+            }
+            other => panic!("Expected a balance update, got {:?}", other),
+        }
+    }
+
+    // Nothing new until the next block confirms the pending transfer
+    assert_eq!(watcher.poll().await.unwrap().credited, 0);
+    chain.lock().unwrap().head = 101;
+    assert_eq!(watcher.poll().await.unwrap().credited, 1);
+    assert_eq!(
+        db.get_balance(alice, "USDC").await.unwrap().amount,
+        13_000_000
+    );
+
+    // Rescanning credited blocks changes nothing
+    db.set_deposit_cursor(42161, 90).await.unwrap();
+    assert_eq!(watcher.poll().await.unwrap().credited, 0);
+    assert_eq!(
+        db.get_balance(alice, "USDC").await.unwrap().amount,
+        13_000_000
+    );
+
+    let deposits = db.list_user_deposits(alice, 10).await.unwrap();
+    assert_eq!(deposits.len(), 3);
+    assert!(deposits.iter().all(|d| d.user_address == alice));
+}
+
+#[tokio::test]
+async fn test_watcher_starts_at_head_and_scans_in_ranges() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test db");
+    let db = test_db.db.clone();
+    helpers::create_token(&test_db, "USDC", 6, "USD Coin")
+        .await
+        .expect("Failed to create token");
+
+    let chain = Arc::new(Mutex::new(MockChain {
+        head: 5_000,
+        logs: vec![transfer_log(ALICE, DEPOSIT_ADDRESS, 1_000_000, 4_000, 0)],
+    }));
+    let url = start_node(chain.clone()).await;
+    // Without a start block, history before the confirmed head is ignored
+    let watcher = DepositWatcher::new(db.clone(), &url, &deposit_config(None)).unwrap();
+    assert_eq!(watcher.poll().await.unwrap().credited, 0);
+    assert_eq!(db.get_deposit_cursor(42161).await.unwrap(), Some(4_998));
+
+    // A long backlog is scanned a range at a time
+    db.set_deposit_cursor(42161, 0).await.unwrap();
+    let first = watcher.poll().await.unwrap();
+    assert!(!first.caught_up);
+    assert_eq!(
+        db.get_deposit_cursor(42161).await.unwrap(),
+        Some(MAX_BLOCK_RANGE)
+    );
+    let mut credited = first.credited;
+    loop {
+        let outcome = watcher.poll().await.unwrap();
+        credited += outcome.credited;
+        if outcome.caught_up {
+            break;
+        }
+    }
+    assert_eq!(credited, 1);
+    assert_eq!(db.get_deposit_cursor(42161).await.unwrap(), Some(4_998));
+    assert_eq!(
+        db.get_balance(ALICE, "USDC").await.unwrap().amount,
+        1_000_000
+    );
+}
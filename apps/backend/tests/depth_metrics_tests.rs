@@ -0,0 +1,134 @@
+use backend::engine::depth::depth_metrics;
+use backend::models::api::DepthHistoryResponse;
+use backend::models::domain::{DepthMetrics, OrderbookLevel, OrderbookSnapshot};
+use chrono::{DateTime, Utc};
+use exchange_test_utils::{helpers, TestServer};
+
+fn levels(levels: &[(u128, u128)]) -> Vec<OrderbookLevel> {
+    levels
+        .iter()
+        .map(|&(price, size)| OrderbookLevel { price, size })
+        .collect()
+}
+
+fn snapshot(bids: &[(u128, u128)], asks: &[(u128, u128)]) -> OrderbookSnapshot {
+    OrderbookSnapshot {
+        market_id: "BTC/USDC".to_string(),
+        bids: levels(bids),
+        asks: levels(asks),
+        timestamp: DateTime::UNIX_EPOCH,
+        version: 1,
+    }
+}
+
+// ============================================================================
+// Depth Metrics Tests
+// ============================================================================
+
+#[test]
+fn test_metrics_cover_only_the_top_levels() {
+    let now = Utc::now();
+    let book = snapshot(
+        &[(100, 10), (99, 10), (98, 10)],
+        &[(102, 5), (103, 5), (104, 1_000)],
+    );
+
+    let metrics = depth_metrics(&book, 2, now);
+    assert_eq!(metrics.timestamp, now);
+    assert_eq!(metrics.best_bid, Some(100));
+    assert_eq!(metrics.best_ask, Some(102));
+    assert_eq!(metrics.spread(), Some(2));
+    assert_eq!(metrics.bid_depth, 20);
+    assert_eq!(metrics.ask_depth, 10);
+    // (20 - 10) / (20 + 10)
+    assert_eq!(metrics.imbalance_bps, 3_333);
+}
+
+#[test]
+fn test_one_sided_and_empty_books() {
+    let now = Utc::now();
+
+    let metrics = depth_metrics(&snapshot(&[], &[(102, 5)]), 5, now);
+    assert_eq!(metrics.best_bid, None);
+    assert_eq!(metrics.spread(), None);
+    assert_eq!(metrics.imbalance_bps, -10_000);
+
+    let metrics = depth_metrics(&snapshot(&[], &[]), 5, now);
+    assert_eq!(metrics.best_ask, None);
+    assert_eq!(metrics.imbalance_bps, 0);
+
+    // Depth that would overflow when scaled to basis points still measures
+    let metrics = depth_metrics(&snapshot(&[(100, u128::MAX / 2)], &[(102, 1)]), 5, now);
+    assert!((9_999..=10_000).contains(&metrics.imbalance_bps));
+}
+
+// ============================================================================
+// Depth History Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_depth_history_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let now = Utc::now().timestamp();
+    let sample = |seconds_ago: i64, best_bid: Option<u128>| DepthMetrics {
+        market_id: "BTC/USDC".to_string(),
+        timestamp: DateTime::from_timestamp(now - seconds_ago, 0).unwrap(),
+        best_bid,
+        best_ask: Some(50_010_000_000),
+        bid_depth: if best_bid.is_some() { 300_000_000 } else { 0 },
+        ask_depth: 100_000_000,
+        imbalance_bps: if best_bid.is_some() { 5_000 } else { -10_000 },
+    };
+    server
+        .db()
+        .insert_depth_metrics(&[
+            sample(3600, Some(50_000_000_000)),
+            sample(20, None),
+            sample(10, Some(50_000_000_000)),
+        ])
+        .await
+        .expect("Failed to insert depth metrics");
+
+    let get = |from: i64, to: i64| {
+        let url = server.url(&format!(
+            "/api/markets/BTC%2FUSDC/depth?from={}&to={}",
+            from, to
+        ));
+        async move { reqwest::get(&url).await.expect("Request failed") }
+    };
+
+    let history: DepthHistoryResponse = get(now - 60, now).await.json().await.unwrap();
+    assert_eq!(history.levels, 5);
+    assert_eq!(history.samples.len(), 2);
+
+    // The bid side was pulled twenty seconds ago
+    let pulled = &history.samples[0];
+    assert_eq!(pulled.timestamp, now - 20);
+    assert!(pulled.best_bid.is_none());
+    assert!(pulled.spread.is_none());
+    assert_eq!(pulled.bid_depth, "0");
+    assert_eq!(pulled.imbalance_bps, -10_000);
+
+    let quoted = &history.samples[1];
+    assert_eq!(quoted.best_bid.as_deref(), Some("50000000000"));
+    assert_eq!(quoted.spread.as_deref(), Some("10000000"));
+    assert_eq!(quoted.bid_depth, "300000000");
+    assert_eq!(quoted.imbalance_bps, 5_000);
+
+    assert_eq!(get(now, now - 60).await.status(), 400);
+    assert_eq!(get(now - 8 * 24 * 3600, now).await.status(), 400);
+    let missing = reqwest::get(server.url(&format!(
+        "/api/markets/NOPE%2FUSDC/depth?from={}&to={}",
+        now - 60,
+        now
+    )))
+    .await
+    .expect("Request failed");
+    assert_eq!(missing.status(), 404);
+}
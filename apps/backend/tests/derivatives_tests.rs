@@ -0,0 +1,98 @@
+use backend::models::api::{FundingHistoryResponse, OpenInterestHistoryResponse};
+use backend::models::domain::{FundingRate, OpenInterestSnapshot};
+use chrono::{DateTime, Utc};
+use exchange_test_utils::{helpers, TestServer};
+
+// ============================================================================
+// Open Interest and Funding Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_open_interest_and_funding_endpoints_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let now = Utc::now().timestamp();
+    let at = |seconds_ago: i64| DateTime::from_timestamp(now - seconds_ago, 0).unwrap();
+    server
+        .db()
+        .insert_open_interest(&[
+            OpenInterestSnapshot {
+                market_id: "BTC/USDC".to_string(),
+                timestamp: at(7200),
+                open_interest: 500_000_000,
+                mark_price: 50_000_000_000,
+            },
+            OpenInterestSnapshot {
+                market_id: "BTC/USDC".to_string(),
+                timestamp: at(60),
+                open_interest: 800_000_000,
+                mark_price: 51_000_000_000,
+            },
+        ])
+        .await
+        .expect("Failed to insert open interest");
+    server
+        .db()
+        .insert_funding_rates(&[
+            FundingRate {
+                market_id: "BTC/USDC".to_string(),
+                timestamp: at(3600),
+                funding_rate_ppm: 100,
+                mark_price: 50_500_000_000,
+                index_price: 50_450_000_000,
+            },
+            // Shorts pay longs when the mark trades below the index
+            FundingRate {
+                market_id: "BTC/USDC".to_string(),
+                timestamp: at(0),
+                funding_rate_ppm: -25,
+                mark_price: 50_900_000_000,
+                index_price: 51_000_000_000,
+            },
+        ])
+        .await
+        .expect("Failed to insert funding rates");
+
+    let get = |kind: &str, from: i64, to: i64| {
+        let url = server.url(&format!(
+            "/api/markets/BTC%2FUSDC/{}?from={}&to={}",
+            kind, from, to
+        ));
+        async move { reqwest::get(&url).await.expect("Request failed") }
+    };
+
+    let history: OpenInterestHistoryResponse = get("open-interest", now - 3600, now)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(history.snapshots.len(), 1);
+    assert_eq!(history.snapshots[0].timestamp, now - 60);
+    assert_eq!(history.snapshots[0].open_interest, "800000000");
+    assert_eq!(history.snapshots[0].mark_price, "51000000000");
+
+    let funding: FundingHistoryResponse =
+        get("funding", now - 7200, now).await.json().await.unwrap();
+    let rates: Vec<_> = funding
+        .rates
+        .iter()
+        .map(|r| (r.timestamp, r.funding_rate_ppm))
+        .collect();
+    assert_eq!(rates, vec![(now - 3600, 100), (now, -25)]);
+    assert_eq!(funding.rates[1].index_price, "51000000000");
+
+    assert_eq!(get("funding", now, now - 60).await.status(), 400);
+    let missing = reqwest::get(server.url(&format!(
+        "/api/markets/NOPE%2FUSDC/open-interest?from={}&to={}",
+        now - 60,
+        now
+    )))
+    .await
+    .expect("Request failed");
+    assert_eq!(missing.status(), 404);
+}
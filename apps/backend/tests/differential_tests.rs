@@ -0,0 +1,96 @@
+use backend::engine::ladder::LadderLayout;
+use backend::models::domain::{OrderType, Side};
+use exchange_test_utils::differential::Fill;
+use exchange_test_utils::invariants::EngineOp;
+use exchange_test_utils::{
+    check_against_reference, DifferentialHarness, OrderBuilder, ReferenceBook,
+};
+
+// ============================================================================
+// Reference Book Tests
+// ============================================================================
+
+#[test]
+fn test_reference_book_matches_by_price_then_time() {
+    let mut book = ReferenceBook::new(1);
+    let first = OrderBuilder::sell("alice", "M").limit(101).size(2).build();
+    let better = OrderBuilder::sell("bob", "M").limit(100).size(1).build();
+    let second = OrderBuilder::sell("carol", "M").limit(101).size(5).build();
+    for order in [&first, &better, &second] {
+        assert!(book.place(order).is_empty());
+    }
+
+    let taker = OrderBuilder::buy("dave", "M").limit(101).size(4).build();
+    let fill = |order: &backend::models::domain::Order, size| Fill {
+        maker_order_id: order.id,
+        price: order.price,
+        size,
+    };
+    assert_eq!(
+        book.place(&taker),
+        vec![fill(&better, 1), fill(&first, 2), fill(&second, 1)]
+    );
+    assert_eq!(book.levels(Side::Sell), vec![(101, 4)]);
+    assert!(book.levels(Side::Buy).is_empty());
+}
+
+#[test]
+fn test_reference_book_skips_own_orders_and_drops_dust() {
+    let mut book = ReferenceBook::new(2);
+    let own = OrderBuilder::sell("alice", "M").limit(100).size(3).build();
+    let other = OrderBuilder::sell("bob", "M").limit(100).size(2).build();
+    book.place(&own);
+    book.place(&other);
+
+    // Alice's buy passes over her own ask; the 1-lot remainder is under the minimum
+    let taker = OrderBuilder::buy("alice", "M").limit(100).size(3).build();
+    assert_eq!(book.place(&taker).len(), 1);
+    assert!(book.orders(Side::Buy).is_empty());
+
+    // Market orders never rest
+    let market = OrderBuilder::buy("carol", "M")
+        .market()
+        .price(100)
+        .size(10)
+        .build();
+    assert_eq!(book.place(&market).len(), 1);
+    assert!(book.orders(Side::Buy).is_empty());
+    assert!(book.levels(Side::Sell).is_empty());
+
+    assert_eq!(book.cancel(own.id), None);
+    assert_eq!(book.open_order_count("alice"), 0);
+}
+
+// ============================================================================
+// Differential Tests
+// ============================================================================
+
+#[test]
+fn test_harness_reports_where_the_books_diverge() {
+    let mut harness = DifferentialHarness::new(LadderLayout::Tree);
+    let place = |user, side, price_offset| EngineOp::Place {
+        user,
+        side,
+        order_type: OrderType::Limit,
+        price_offset,
+        lots: 3,
+    };
+    harness
+        .run(&[place(0, Side::Sell, 1), place(1, Side::Buy, 2)])
+        .unwrap();
+
+    // Drift the reference away from the book behind the harness' back
+    let stray = OrderBuilder::sell("diff_user2", &harness.market.id)
+        .limit(150)
+        .size(5)
+        .build();
+    harness.reference.place(&stray);
+    let error = harness.check().unwrap_err();
+    assert!(error.contains("resting asks"), "{}", error);
+}
+
+/// Random place/cancel sequences must match and rest exactly as the reference does
+#[test]
+fn test_orderbook_matches_reference_for_random_sequences() {
+    check_against_reference(256, 60);
+}
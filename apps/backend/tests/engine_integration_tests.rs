@@ -1,5 +1,6 @@
-use backend::models::domain::{OrderStatus, OrderType, Side};
-use exchange_test_utils::{helpers, TestDb, TestEngine};
+use backend::engine::{MatchingEngine, MAX_OPEN_ORDERS_PER_MARKET};
+use backend::models::domain::{CancelReason, EngineEvent, MarketStatus, OrderStatus, UserStatus};
+use exchange_test_utils::{helpers, OrderBuilder, TestDb, TestEngine};
 
 // ============================================================================
 // TESTS
@@ -15,14 +16,10 @@ async fn test_basic_limit_order_matching() {
     let engine = TestEngine::new(&test_db).await;
 
     // Create sell order first (maker)
-    let sell_order = TestEngine::create_order(
-        "seller",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000_000, // $50,000
-        1_000_000,      // 1 BTC
-    );
+    let sell_order = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000) // $50,000
+        .size(1_000_000) // 1 BTC
+        .build();
 
     let result = engine.place_order(sell_order.clone()).await;
     assert!(result.is_ok(), "Failed to place sell order: {:?}", result);
@@ -31,14 +28,10 @@ async fn test_basic_limit_order_matching() {
     assert_eq!(placed.trades.len(), 0); // No match yet
 
     // Create buy order that matches (taker)
-    let buy_order = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        50_000_000_000, // Willing to pay $50,000
-        1_000_000,      // 1 BTC
-    );
+    let buy_order = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000) // Willing to pay $50,000
+        .size(1_000_000) // 1 BTC
+        .build();
 
     let result = engine.place_order(buy_order.clone()).await;
     assert!(result.is_ok(), "Failed to place buy order: {:?}", result);
@@ -67,27 +60,19 @@ async fn test_partial_fill() {
     let engine = TestEngine::new(&test_db).await;
 
     // Create sell order for 10 ETH (maker)
-    let sell_order = TestEngine::create_order(
-        "seller",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        3_000_000_000, // $3,000
-        10_000_000,    // 10 ETH
-    );
+    let sell_order = OrderBuilder::sell("seller", &market.id)
+        .limit(3_000_000_000) // $3,000
+        .size(10_000_000) // 10 ETH
+        .build();
 
     let result = engine.place_order(sell_order).await;
     assert!(result.is_ok());
 
     // Create buy order for only 3 ETH (taker)
-    let buy_order = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        3_000_000_000,
-        3_000_000, // 3 ETH
-    );
+    let buy_order = OrderBuilder::buy("buyer", &market.id)
+        .limit(3_000_000_000)
+        .size(3_000_000) // 3 ETH
+        .build();
 
     let result = engine.place_order(buy_order).await;
     assert!(result.is_ok());
@@ -112,23 +97,15 @@ async fn test_price_time_priority() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place two sell orders at different prices
-    let sell1 = TestEngine::create_order(
-        "seller1",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        100_000_000, // $100
-        5_000_000,   // 5 SOL
-    );
+    let sell1 = OrderBuilder::sell("seller1", &market.id)
+        .limit(100_000_000) // $100
+        .size(5_000_000) // 5 SOL
+        .build();
 
-    let sell2 = TestEngine::create_order(
-        "seller2",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        95_000_000, // $95 (better price)
-        3_000_000,  // 3 SOL
-    );
+    let sell2 = OrderBuilder::sell("seller2", &market.id)
+        .limit(95_000_000) // $95 (better price)
+        .size(3_000_000) // 3 SOL
+        .build();
 
     engine
         .place_order(sell1)
@@ -140,14 +117,10 @@ async fn test_price_time_priority() {
         .expect("Failed to place sell2");
 
     // Place buy order that can match both
-    let buy = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        105_000_000, // Willing to pay $105
-        8_000_000,   // 8 SOL
-    );
+    let buy = OrderBuilder::buy("buyer", &market.id)
+        .limit(105_000_000) // Willing to pay $105
+        .size(8_000_000) // 8 SOL
+        .build();
 
     let result = engine.place_order(buy).await;
     assert!(result.is_ok());
@@ -175,23 +148,15 @@ async fn test_fifo_time_priority_at_same_price() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place two sell orders at the SAME price (first one should match first)
-    let sell1 = TestEngine::create_order(
-        "seller1",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        40_000_000, // $40
-        2_000_000,  // 2 AVAX
-    );
+    let sell1 = OrderBuilder::sell("seller1", &market.id)
+        .limit(40_000_000) // $40
+        .size(2_000_000) // 2 AVAX
+        .build();
 
-    let sell2 = TestEngine::create_order(
-        "seller2",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        40_000_000, // $40 (same price)
-        2_000_000,  // 2 AVAX
-    );
+    let sell2 = OrderBuilder::sell("seller2", &market.id)
+        .limit(40_000_000) // $40 (same price)
+        .size(2_000_000) // 2 AVAX
+        .build();
 
     engine
         .place_order(sell1)
@@ -203,14 +168,10 @@ async fn test_fifo_time_priority_at_same_price() {
         .expect("Failed to place sell2");
 
     // Place buy order for only 2 AVAX
-    let buy = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        40_000_000,
-        2_000_000,
-    );
+    let buy = OrderBuilder::buy("buyer", &market.id)
+        .limit(40_000_000)
+        .size(2_000_000)
+        .build();
 
     let result = engine.place_order(buy).await;
     assert!(result.is_ok());
@@ -232,23 +193,15 @@ async fn test_market_order_execution() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place limit sell orders at different prices
-    let sell1 = TestEngine::create_order(
-        "seller1",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        1_000_000, // $1.00
-        10_000_000,
-    );
+    let sell1 = OrderBuilder::sell("seller1", &market.id)
+        .limit(1_000_000) // $1.00
+        .size(10_000_000)
+        .build();
 
-    let sell2 = TestEngine::create_order(
-        "seller2",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        1_100_000, // $1.10
-        10_000_000,
-    );
+    let sell2 = OrderBuilder::sell("seller2", &market.id)
+        .limit(1_100_000) // $1.10
+        .size(10_000_000)
+        .build();
 
     engine
         .place_order(sell1)
@@ -260,14 +213,11 @@ async fn test_market_order_execution() {
         .expect("Failed to place sell2");
 
     // Place market buy order (should match at any price)
-    let market_buy = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Market,
-        0, // Price doesn't matter for market orders
-        15_000_000,
-    );
+    let market_buy = OrderBuilder::buy("buyer", &market.id)
+        .market()
+        .price(0) // Price doesn't matter for market orders
+        .size(15_000_000)
+        .build();
 
     let result = engine.place_order(market_buy).await;
     assert!(result.is_ok(), "Failed to place market buy: {:?}", result);
@@ -295,14 +245,10 @@ async fn test_order_cancellation() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place a limit order
-    let order = TestEngine::create_order(
-        "user1",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        7_000_000,
-        5_000_000,
-    );
+    let order = OrderBuilder::sell("user1", &market.id)
+        .limit(7_000_000)
+        .size(5_000_000)
+        .build();
     let order_id = order.id;
 
     let result = engine.place_order(order).await;
@@ -330,14 +276,10 @@ async fn test_cannot_cancel_others_order() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place order as user1
-    let order = TestEngine::create_order(
-        "user1",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        10_000_000,
-        5_000_000,
-    );
+    let order = OrderBuilder::sell("user1", &market.id)
+        .limit(10_000_000)
+        .size(5_000_000)
+        .build();
     let order_id = order.id;
 
     engine
@@ -366,14 +308,10 @@ async fn test_no_match_different_market() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place sell order in BTC/USDC market
-    let sell_btc = TestEngine::create_order(
-        "seller",
-        &market1.id,
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000_000,
-        1_000_000,
-    );
+    let sell_btc = OrderBuilder::sell("seller", &market1.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
 
     engine
         .place_order(sell_btc)
@@ -381,14 +319,10 @@ async fn test_no_match_different_market() {
         .expect("Failed to place BTC sell");
 
     // Place buy order in ETH/USDC market (different market)
-    let buy_eth = TestEngine::create_order(
-        "buyer",
-        &market2.id,
-        Side::Buy,
-        OrderType::Limit,
-        3_000_000_000,
-        10_000_000,
-    );
+    let buy_eth = OrderBuilder::buy("buyer", &market2.id)
+        .limit(3_000_000_000)
+        .size(10_000_000)
+        .build();
 
     let result = engine.place_order(buy_eth).await;
     assert!(result.is_ok());
@@ -409,14 +343,10 @@ async fn test_buy_limit_order_wont_match_above_limit() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place sell order at $20
-    let sell = TestEngine::create_order(
-        "seller",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        20_000_000, // $20
-        5_000_000,
-    );
+    let sell = OrderBuilder::sell("seller", &market.id)
+        .limit(20_000_000) // $20
+        .size(5_000_000)
+        .build();
 
     engine
         .place_order(sell)
@@ -424,14 +354,10 @@ async fn test_buy_limit_order_wont_match_above_limit() {
         .expect("Failed to place sell");
 
     // Place buy limit order at $15 (below sell price)
-    let buy = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        15_000_000, // $15 (won't match $20 ask)
-        5_000_000,
-    );
+    let buy = OrderBuilder::buy("buyer", &market.id)
+        .limit(15_000_000) // $15 (won't match $20 ask)
+        .size(5_000_000)
+        .build();
 
     let result = engine.place_order(buy).await;
     assert!(result.is_ok());
@@ -452,26 +378,18 @@ async fn test_sell_limit_order_wont_match_below_limit() {
     let engine = TestEngine::new(&test_db).await;
 
     // Place buy order at $5
-    let buy = TestEngine::create_order(
-        "buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        5_000_000, // $5
-        10_000_000,
-    );
+    let buy = OrderBuilder::buy("buyer", &market.id)
+        .limit(5_000_000) // $5
+        .size(10_000_000)
+        .build();
 
     engine.place_order(buy).await.expect("Failed to place buy");
 
     // Place sell limit order at $8 (above buy price)
-    let sell = TestEngine::create_order(
-        "seller",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        8_000_000, // $8 (won't match $5 bid)
-        10_000_000,
-    );
+    let sell = OrderBuilder::sell("seller", &market.id)
+        .limit(8_000_000) // $8 (won't match $5 bid)
+        .size(10_000_000)
+        .build();
 
     let result = engine.place_order(sell).await;
     assert!(result.is_ok());
@@ -497,32 +415,20 @@ async fn test_multiple_orders_complex_matching() {
     // $0.52 - 200 ADA (seller2)
     // $0.50 - 150 ADA (seller1)
 
-    let sell1 = TestEngine::create_order(
-        "seller1",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        500_000, // $0.50
-        150_000_000,
-    );
+    let sell1 = OrderBuilder::sell("seller1", &market.id)
+        .limit(500_000) // $0.50
+        .size(150_000_000)
+        .build();
 
-    let sell2 = TestEngine::create_order(
-        "seller2",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        520_000, // $0.52
-        200_000_000,
-    );
+    let sell2 = OrderBuilder::sell("seller2", &market.id)
+        .limit(520_000) // $0.52
+        .size(200_000_000)
+        .build();
 
-    let sell3 = TestEngine::create_order(
-        "seller3",
-        &market.id,
-        Side::Sell,
-        OrderType::Limit,
-        550_000, // $0.55
-        100_000_000,
-    );
+    let sell3 = OrderBuilder::sell("seller3", &market.id)
+        .limit(550_000) // $0.55
+        .size(100_000_000)
+        .build();
 
     engine
         .place_order(sell1)
@@ -538,14 +444,10 @@ async fn test_multiple_orders_complex_matching() {
         .expect("Failed to place sell3");
 
     // Place large buy order that matches multiple levels
-    let big_buy = TestEngine::create_order(
-        "big_buyer",
-        &market.id,
-        Side::Buy,
-        OrderType::Limit,
-        600_000,     // Willing to pay $0.60
-        400_000_000, // 400 ADA
-    );
+    let big_buy = OrderBuilder::buy("big_buyer", &market.id)
+        .limit(600_000) // Willing to pay $0.60
+        .size(400_000_000) // 400 ADA
+        .build();
 
     let result = engine.place_order(big_buy).await;
     assert!(result.is_ok());
@@ -567,3 +469,587 @@ async fn test_multiple_orders_complex_matching() {
     assert_eq!(placed.order.status, OrderStatus::Filled);
     assert_eq!(placed.order.filled_size, "400000000");
 }
+
+#[tokio::test]
+async fn test_recovery_loads_every_market() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let mut markets = Vec::new();
+    for base in ["BTC", "ETH", "SOL"] {
+        markets.push(
+            helpers::create_market_with_tokens(&test_db, base, "USDC")
+                .await
+                .expect("Failed to create market"),
+        );
+    }
+
+    // Rest two orders in each of the first two markets, none in the third
+    let engine = TestEngine::new(&test_db).await;
+    for market in &markets[..2] {
+        for price in [49_000_000_000, 48_000_000_000] {
+            let bid = OrderBuilder::buy("buyer", &market.id)
+                .limit(price)
+                .size(1_000_000)
+                .build();
+            engine.place_order(bid).await.expect("Failed to rest order");
+        }
+    }
+
+    // A fresh engine rebuilds all books from the database
+    let (_engine_tx, engine_rx) = tokio::sync::mpsc::channel(1);
+    let (event_tx, _) = tokio::sync::broadcast::channel(16);
+    let restarted = MatchingEngine::new(test_db.db.clone(), engine_rx, event_tx);
+    let report = restarted
+        .recover_orderbooks()
+        .await
+        .expect("Recovery failed");
+
+    assert_eq!(report.markets.len(), 3);
+    assert_eq!(report.failed().count(), 0);
+    assert_eq!(report.total_orders(), 4);
+
+    let orderbooks = restarted.orderbooks();
+    let mut orderbooks = orderbooks.write().await;
+    for market in &markets[..2] {
+        let snapshot = orderbooks.get_or_create(&market.id).snapshot();
+        assert_eq!(snapshot.bids.len(), 2, "{} not recovered", market.id);
+    }
+}
+
+#[tokio::test]
+async fn test_open_order_cap_per_market() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let engine = TestEngine::new(&test_db).await;
+
+    let bid = || {
+        OrderBuilder::buy("buyer", &market.id)
+            .limit(1_000_000)
+            .size(1_000_000)
+            .build()
+    };
+    for _ in 0..MAX_OPEN_ORDERS_PER_MARKET {
+        engine
+            .place_order(bid())
+            .await
+            .expect("Order under the cap");
+    }
+
+    let result = engine.place_order(bid()).await;
+    assert!(
+        result.as_ref().is_err_and(|e| e.contains("open orders")),
+        "Expected open-order cap rejection, got {:?}",
+        result
+    );
+
+    // Cancelling one frees a slot
+    let resting = test_db
+        .db
+        .get_user_orders("buyer", Some(&market.id), None, 1)
+        .await
+        .expect("Failed to list orders");
+    engine
+        .cancel_order(resting[0].id, "buyer".to_string())
+        .await
+        .expect("Failed to cancel order");
+    assert!(engine.place_order(bid()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_halting_market_cancels_resting_orders() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let mut engine = TestEngine::new(&test_db).await;
+
+    let bid = || {
+        OrderBuilder::buy("buyer", &market.id)
+            .limit(50_000_000_000)
+            .size(1_000_000)
+            .build()
+    };
+    let placed = engine
+        .place_order(bid())
+        .await
+        .expect("Failed to place bid");
+
+    let cancelled = engine
+        .set_market_status(&market.id, MarketStatus::Halted)
+        .await
+        .expect("Failed to halt market");
+    assert_eq!(cancelled.cancelled_order_ids, vec![placed.order.id.clone()]);
+
+    // Cancellation is broadcast with the halt as its reason
+    let reason = loop {
+        match engine.event_rx.recv().await.expect("Event channel closed") {
+            EngineEvent::OrderCancelled { reason, .. } => break reason,
+            _ => continue,
+        }
+    };
+    assert_eq!(reason, Some(CancelReason::MarketHalted));
+
+    // The bid's reservation is released and nothing is left in the book
+    let balance = test_db
+        .db
+        .get_balance("buyer", "USDC")
+        .await
+        .expect("Failed to get balance");
+    assert_eq!(balance.open_interest, 0);
+    let orderbooks = engine.orderbooks.read().await;
+    let id = orderbooks.markets().get(&market.id).unwrap();
+    assert_eq!(orderbooks.open_order_count(id, "buyer"), 0);
+    drop(orderbooks);
+
+    let result = engine.place_order(bid()).await;
+    assert!(
+        result.as_ref().is_err_and(|e| e.contains("halted")),
+        "Expected halted market rejection, got {:?}",
+        result
+    );
+
+    // Reactivating resumes trading; delisting is final
+    engine
+        .set_market_status(&market.id, MarketStatus::Active)
+        .await
+        .expect("Failed to reactivate market");
+    assert!(engine.place_order(bid()).await.is_ok());
+    engine
+        .set_market_status(&market.id, MarketStatus::Delisted)
+        .await
+        .expect("Failed to delist market");
+    assert!(engine
+        .set_market_status(&market.id, MarketStatus::Active)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_maker_rebate_paid_from_fee_collector() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    // A rebate larger than the taker fee would pay out net
+    let result = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            -30,
+            20,
+        )
+        .await;
+    assert!(result.is_err_and(|e| e.to_string().contains("Invalid fees")));
+
+    // 0.1% maker rebate, 0.2% taker fee
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            -10,
+            20,
+        )
+        .await
+        .expect("Failed to create market");
+
+    // The collector only holds enough USDC for one and a bit rebates
+    test_db
+        .db
+        .add_balance("system", "USDC", 700_000)
+        .await
+        .expect("Failed to fund fee collector");
+
+    for _ in 0..2 {
+        let ask = OrderBuilder::sell("seller", &market.id)
+            .limit(50_000_000_000)
+            .size(1_000_000)
+            .build();
+        engine.place_order(ask).await.expect("Failed to place ask");
+        let bid = OrderBuilder::buy("buyer", &market.id)
+            .limit(50_000_000_000)
+            .size(1_000_000)
+            .build();
+        let placed = engine.place_order(bid).await.expect("Failed to place bid");
+        assert_eq!(placed.trades.len(), 1);
+    }
+
+    // Each fill is worth 500 USDC: the first earns the full 0.5 USDC rebate, the second what is left
+    let seller_usdc = test_db.db.get_balance("seller", "USDC").await.unwrap();
+    assert_eq!(
+        seller_usdc.amount,
+        10_000_000_000_000 + 2 * 500_000_000 + 700_000
+    );
+    let collector_usdc = test_db.db.get_balance("system", "USDC").await.unwrap();
+    assert_eq!(collector_usdc.amount, 0);
+
+    // The taker still pays the full fee in base tokens
+    let buyer_btc = test_db.db.get_balance("buyer", "BTC").await.unwrap();
+    assert_eq!(buyer_btc.amount, 1_000_000_000 + 2 * (1_000_000 - 2_000));
+    let collector_btc = test_db.db.get_balance("system", "BTC").await.unwrap();
+    assert_eq!(collector_btc.amount, 2 * 2_000);
+
+    // Rebates show up in the maker's fill history
+    let trades = test_db
+        .db
+        .get_user_trades("seller", Some(&market.id), 10)
+        .await
+        .unwrap();
+    let ids: Vec<_> = trades.iter().map(|t| t.id).collect();
+    let mut fees: Vec<i128> = test_db
+        .db
+        .get_user_trade_fees("seller", &ids)
+        .await
+        .unwrap()
+        .into_values()
+        .map(|f| f.fee)
+        .collect();
+    fees.sort();
+    assert_eq!(fees, vec![-500_000, -200_000]);
+    assert_eq!(
+        test_db.db.get_user_rebate_totals("seller").await.unwrap(),
+        vec![("USDC".to_string(), 700_000)]
+    );
+    assert!(test_db
+        .db
+        .get_user_rebate_totals("buyer")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_referrer_earns_share_of_taker_fees() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    // 0.1% maker fee, 0.2% taker fee
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            10,
+            20,
+        )
+        .await
+        .expect("Failed to create market");
+
+    assert!(engine.set_referral("buyer", "buyer", 2500).await.is_err());
+    assert!(engine.set_referral("buyer", "alice", 10001).await.is_err());
+    engine
+        .set_referral("buyer", "alice", 2500)
+        .await
+        .expect("Failed to set referral");
+
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+
+    // The taker fee is 2000 BTC atoms, a quarter of which goes to the referrer
+    let alice_btc = test_db.db.get_balance("alice", "BTC").await.unwrap();
+    assert_eq!(alice_btc.amount, 1_000_000_000 + 500);
+    let collector_btc = test_db.db.get_balance("system", "BTC").await.unwrap();
+    assert_eq!(collector_btc.amount, 1_500);
+
+    // Maker fees are not shared, and the seller has no referrer anyway
+    let collector_usdc = test_db.db.get_balance("system", "USDC").await.unwrap();
+    assert_eq!(collector_usdc.amount, 500_000);
+
+    assert_eq!(
+        test_db.db.get_referral_earnings("alice").await.unwrap(),
+        vec![("BTC".to_string(), 500, 1)]
+    );
+    assert!(test_db
+        .db
+        .get_referral_earnings("buyer")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_fee_overrides_replace_market_fees() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    // 0.1% maker fee, 0.2% taker fee
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            10,
+            20,
+        )
+        .await
+        .expect("Failed to create market");
+
+    // Overrides may lower fees but not raise them
+    assert!(engine
+        .set_fee_override("buyer", &market.id, None, Some(21))
+        .await
+        .is_err());
+    assert!(engine
+        .set_fee_override("nobody", &market.id, None, Some(5))
+        .await
+        .is_err());
+    engine
+        .set_fee_override("buyer", &market.id, None, Some(5))
+        .await
+        .expect("Failed to set buyer fees");
+    engine
+        .set_fee_override("seller", &market.id, Some(0), None)
+        .await
+        .expect("Failed to set seller fees");
+
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+
+    // The taker pays 0.05% instead of 0.2%, and the maker nothing
+    let buyer_btc = test_db.db.get_balance("buyer", "BTC").await.unwrap();
+    assert_eq!(buyer_btc.amount, 1_000_000_000 + 1_000_000 - 500);
+    let collector_btc = test_db.db.get_balance("system", "BTC").await.unwrap();
+    assert_eq!(collector_btc.amount, 500);
+    let seller_usdc = test_db.db.get_balance("seller", "USDC").await.unwrap();
+    assert_eq!(seller_usdc.amount, 10_000_000_000_000 + 500_000_000);
+
+    // Clearing brings back the market's fees
+    assert_eq!(
+        engine
+            .set_fee_override("buyer", &market.id, None, None)
+            .await
+            .unwrap(),
+        None
+    );
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+    let collector_btc = test_db.db.get_balance("system", "BTC").await.unwrap();
+    assert_eq!(collector_btc.amount, 500 + 2_000);
+}
+
+#[tokio::test]
+async fn test_frozen_user_can_cancel_but_not_place() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let mut engine = TestEngine::new(&test_db).await;
+
+    let bid = || {
+        OrderBuilder::buy("buyer", &market.id)
+            .limit(50_000_000_000)
+            .size(1_000_000)
+            .build()
+    };
+    let first = engine
+        .place_order(bid())
+        .await
+        .expect("Failed to place bid");
+    let second = engine
+        .place_order(bid())
+        .await
+        .expect("Failed to place bid");
+
+    // Freezing without cancelling leaves resting orders alone
+    let cancelled = engine
+        .set_user_status("buyer", UserStatus::Frozen, false)
+        .await
+        .expect("Failed to freeze user");
+    assert_eq!(cancelled.count, 0);
+
+    let result = engine.place_order(bid()).await;
+    assert!(
+        result.as_ref().is_err_and(|e| e.contains("frozen")),
+        "Expected frozen user rejection, got {:?}",
+        result
+    );
+
+    // The user can still cancel their own orders
+    engine
+        .cancel_order(first.order.id.parse().unwrap(), "buyer".to_string())
+        .await
+        .expect("Frozen user should be able to cancel");
+
+    // Banning cancels whatever is left, with the restriction as the reason
+    let cancelled = engine
+        .set_user_status("buyer", UserStatus::Banned, false)
+        .await
+        .expect("Failed to ban user");
+    assert_eq!(cancelled.cancelled_order_ids, vec![second.order.id.clone()]);
+    let reason = loop {
+        match engine.event_rx.recv().await.expect("Event channel closed") {
+            EngineEvent::OrderCancelled {
+                reason: Some(reason),
+                ..
+            } => break reason,
+            _ => continue,
+        }
+    };
+    assert_eq!(reason, CancelReason::AccountRestricted);
+
+    let balance = test_db
+        .db
+        .get_balance("buyer", "USDC")
+        .await
+        .expect("Failed to get balance");
+    assert_eq!(balance.open_interest, 0);
+
+    // Reinstated users trade again
+    engine
+        .set_user_status("buyer", UserStatus::Active, false)
+        .await
+        .expect("Failed to reinstate user");
+    assert!(engine.place_order(bid()).await.is_ok());
+
+    // The status is persisted for the next engine start
+    assert!(test_db.db.list_restricted_users().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_user_summary_aggregates_activity() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let engine = TestEngine::new(&test_db).await;
+
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+    let resting = OrderBuilder::sell("seller", &market.id)
+        .limit(51_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine
+        .place_order(resting)
+        .await
+        .expect("Failed to place resting ask");
+
+    // One 500 USDC fill: the maker pays 10 bps in USDC, the taker 20 bps in BTC
+    let seller = test_db.db.get_user_summary("seller").await.unwrap();
+    assert_eq!(seller.open_orders, 1);
+    assert_eq!(seller.volume_30d, vec![("USDC".to_string(), 500_000_000)]);
+    assert_eq!(seller.fees_paid, vec![("USDC".to_string(), 500_000)]);
+    let locked_btc = seller
+        .balances
+        .iter()
+        .find(|b| b.token_ticker == "BTC")
+        .map(|b| b.open_interest);
+    assert_eq!(locked_btc, Some(1_000_000));
+
+    let buyer = test_db.db.get_user_summary("buyer").await.unwrap();
+    assert_eq!(buyer.open_orders, 0);
+    assert_eq!(buyer.volume_30d, vec![("USDC".to_string(), 500_000_000)]);
+    assert_eq!(buyer.fees_paid, vec![("BTC".to_string(), 2_000)]);
+
+    assert!(test_db.db.get_user_summary("nobody").await.is_err());
+}
+
+#[tokio::test]
+async fn test_stop_that_fails_to_execute_is_cancelled_and_unlocked() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let mut engine = TestEngine::new(&test_db).await;
+
+    let stop = OrderBuilder::sell("seller", &market.id)
+        .stop_market(49_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(stop.clone()).await.unwrap();
+    engine
+        .place_order(
+            OrderBuilder::buy("buyer", &market.id)
+                .limit(48_000_000_000)
+                .size(2_000_000)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    // Trades of the stop can't be persisted, but the one triggering it can
+    sqlx::query(&format!(
+        "ALTER TABLE trades ADD CONSTRAINT block_stop CHECK (seller_order_id <> '{}')",
+        stop.id
+    ))
+    .execute(&test_db.db.postgres)
+    .await
+    .unwrap();
+    engine
+        .place_order(
+            OrderBuilder::sell("seller1", &market.id)
+                .limit(48_000_000_000)
+                .size(1_000_000)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            if let Ok(EngineEvent::OrderCancelled { order_id, .. }) = engine.event_rx.recv().await {
+                if order_id == stop.id {
+                    break;
+                }
+            }
+        }
+    })
+    .await
+    .expect("stop was never cancelled");
+
+    assert_eq!(
+        test_db.db.get_order(&stop.id).await.unwrap().status,
+        OrderStatus::Cancelled
+    );
+    let seller_btc = test_db.db.get_balance("seller", "BTC").await.unwrap();
+    assert_eq!(seller_btc.open_interest, 0);
+}
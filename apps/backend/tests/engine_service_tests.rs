@@ -0,0 +1,289 @@
+use axum::response::IntoResponse;
+use backend::engine::markets::MarketRegistry;
+use backend::engine_service::wire::RemoteError;
+use backend::engine_service::{read_frame, write_frame, EngineServer, RemoteEngine};
+use backend::errors::{ErrorCode, ExchangeError};
+use backend::models::domain::{EngineEvent, EngineRequest};
+use backend::shutdown::Shutdown;
+use exchange_protocol::api::OrderCancelled;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+use uuid::Uuid;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+/// A gateway forwarding to an engine server, with the test playing the engine
+struct Link {
+    /// The gateway's engine queue, as its handlers see it
+    gateway_tx: mpsc::Sender<EngineRequest>,
+    /// Events the gateway's handlers raise
+    outbox_tx: broadcast::Sender<EngineEvent>,
+    /// Events the gateway's WebSocket router receives
+    gateway_events: broadcast::Receiver<EngineEvent>,
+    /// Requests reaching the engine
+    engine_rx: mpsc::Receiver<EngineRequest>,
+    /// Events the engine raises
+    engine_events: broadcast::Sender<EngineEvent>,
+    shutdown: Shutdown,
+    server: JoinHandle<()>,
+    remote: JoinHandle<()>,
+}
+
+async fn link(server_token: Option<&str>, gateway_token: Option<&str>) -> Link {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let (engine_tx, engine_rx) = mpsc::channel(16);
+    let (engine_events, _) = broadcast::channel(16);
+    let shutdown = Shutdown::new();
+    let mut server = EngineServer::new(engine_tx, engine_events.clone(), MarketRegistry::new());
+    if let Some(token) = server_token {
+        server = server.with_token(token);
+    }
+    let server = tokio::spawn(server.serve(listener, shutdown.clone()));
+
+    let (gateway_tx, gateway_rx) = mpsc::channel(16);
+    let (outbox_tx, _) = broadcast::channel(16);
+    let (gateway_event_tx, gateway_events) = broadcast::channel(16);
+    let mut remote = RemoteEngine::new(addr, gateway_rx, outbox_tx.subscribe(), gateway_event_tx);
+    if let Some(token) = gateway_token {
+        remote = remote.with_token(token);
+    }
+    let remote = tokio::spawn(remote.run());
+
+    Link {
+        gateway_tx,
+        outbox_tx,
+        gateway_events,
+        engine_rx,
+        engine_events,
+        shutdown,
+        server,
+        remote,
+    }
+}
+
+fn cancel_order(
+    order_id: Uuid,
+) -> (
+    EngineRequest,
+    oneshot::Receiver<Result<OrderCancelled, ExchangeError>>,
+) {
+    let (response_tx, response_rx) = oneshot::channel();
+    let request = EngineRequest::CancelOrder {
+        order_id,
+        user_address: "0xalice".to_string(),
+        span: tracing::Span::none(),
+        response_tx,
+    };
+    (request, response_rx)
+}
+
+/// Send cancels through the gateway until one reaches the engine, riding out the connect
+async fn cancel_via_gateway(
+    link: &mut Link,
+    order_id: Uuid,
+) -> (
+    EngineRequest,
+    oneshot::Receiver<Result<OrderCancelled, ExchangeError>>,
+) {
+    for _ in 0..50 {
+        let (request, response_rx) = cancel_order(order_id);
+        link.gateway_tx.send(request).await.unwrap();
+        // Refused straight away while the gateway isn't connected yet
+        if let Ok(Some(request)) = timeout(Duration::from_millis(100), link.engine_rx.recv()).await
+        {
+            return (request, response_rx);
+        }
+    }
+    panic!("Gateway never connected to the engine");
+}
+
+async fn next_request(link: &mut Link) -> EngineRequest {
+    timeout(WAIT, link.engine_rx.recv())
+        .await
+        .expect("Engine should receive the request")
+        .expect("Engine queue closed")
+}
+
+#[tokio::test]
+async fn test_frames_round_trip() {
+    let (mut client, mut server) = tokio::io::duplex(1024);
+    let error = RemoteError::from(ExchangeError::OrderNotFound);
+
+    write_frame(&mut client, &error).await.unwrap();
+    drop(client);
+
+    let read: Option<RemoteError> = read_frame(&mut server).await.unwrap();
+    assert_eq!(read, Some(error));
+    // A clean close between frames is the end of the stream, not an error
+    let read: Option<RemoteError> = read_frame(&mut server).await.unwrap();
+    assert_eq!(read, None);
+}
+
+#[tokio::test]
+async fn test_oversized_frame_is_refused() {
+    let (mut client, mut server) = tokio::io::duplex(1024);
+    use tokio::io::AsyncWriteExt;
+    client.write_u32(u32::MAX).await.unwrap();
+
+    let read = read_frame::<_, RemoteError>(&mut server).await;
+    assert!(read.is_err());
+}
+
+#[tokio::test]
+async fn test_remote_error_keeps_code_and_status() {
+    let error = ExchangeError::from(RemoteError::from(ExchangeError::OrderNotFound));
+    assert!(matches!(
+        &error,
+        ExchangeError::Remote {
+            code: ErrorCode::OrderNotFound,
+            status: 404,
+            ..
+        }
+    ));
+    assert_eq!(error.into_response().status(), 404);
+
+    // Internal details stay inside the engine
+    let error = RemoteError::from(ExchangeError::Database(sqlx::Error::PoolTimedOut));
+    assert_eq!(error.status, 500);
+    assert!(!error.message.contains("pool"));
+}
+
+#[tokio::test]
+async fn test_gateway_requests_reach_engine_and_replies_return() {
+    let mut link = link(None, None).await;
+    let order_id = Uuid::new_v4();
+
+    let (request, response_rx) = cancel_via_gateway(&mut link, order_id).await;
+    let EngineRequest::CancelOrder {
+        order_id: received,
+        user_address,
+        response_tx,
+        ..
+    } = request
+    else {
+        panic!("Expected a cancel");
+    };
+    assert_eq!(received, order_id);
+    assert_eq!(user_address, "0xalice");
+    response_tx
+        .send(Ok(OrderCancelled {
+            order_id: order_id.to_string(),
+        }))
+        .unwrap();
+
+    let reply = timeout(WAIT, response_rx).await.unwrap().unwrap().unwrap();
+    assert_eq!(reply.order_id, order_id.to_string());
+
+    // Engine errors come back with their code and status
+    let (request, response_rx) = cancel_order(order_id);
+    link.gateway_tx.send(request).await.unwrap();
+    let EngineRequest::CancelOrder { response_tx, .. } = next_request(&mut link).await else {
+        panic!("Expected a cancel");
+    };
+    response_tx.send(Err(ExchangeError::OrderNotFound)).unwrap();
+    let error = timeout(WAIT, response_rx)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.into_response().status(), 404);
+}
+
+#[tokio::test]
+async fn test_events_flow_both_ways() {
+    let mut link = link(None, None).await;
+    let mut engine_side = link.engine_events.subscribe();
+
+    // Once connected, the server is subscribed to the engine's events
+    cancel_via_gateway(&mut link, Uuid::new_v4()).await;
+
+    let order_id = Uuid::new_v4();
+    link.engine_events
+        .send(EngineEvent::OrderCancelled {
+            order_id,
+            user_address: "0xalice".to_string(),
+            reason: None,
+        })
+        .unwrap();
+    let event = timeout(WAIT, link.gateway_events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(event, EngineEvent::OrderCancelled { order_id: id, .. } if id == order_id));
+
+    // Events raised on the gateway are broadcast by the engine
+    let published = Uuid::new_v4();
+    link.outbox_tx
+        .send(EngineEvent::OrderCancelled {
+            order_id: published,
+            user_address: "0xbob".to_string(),
+            reason: None,
+        })
+        .unwrap();
+    let event = loop {
+        let event = timeout(WAIT, engine_side.recv()).await.unwrap().unwrap();
+        if !matches!(&event, EngineEvent::OrderCancelled { order_id: id, .. } if *id == order_id) {
+            break event;
+        }
+    };
+    assert!(matches!(event, EngineEvent::OrderCancelled { order_id: id, .. } if id == published));
+}
+
+#[tokio::test]
+async fn test_gateway_with_wrong_token_is_refused() {
+    let mut link = link(Some("secret"), Some("guess")).await;
+
+    for _ in 0..5 {
+        let (request, response_rx) = cancel_order(Uuid::new_v4());
+        link.gateway_tx.send(request).await.unwrap();
+        let result = timeout(WAIT, response_rx).await.unwrap().unwrap();
+        assert!(result.is_err());
+    }
+    assert!(link.engine_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_answers_queued_requests_then_lets_go() {
+    let mut link = link(None, None).await;
+    let (request, response_rx) = cancel_via_gateway(&mut link, Uuid::new_v4()).await;
+    let EngineRequest::CancelOrder {
+        order_id,
+        response_tx,
+        ..
+    } = request
+    else {
+        panic!("Expected a cancel");
+    };
+
+    // The server waits for the engine to answer before closing
+    link.shutdown.trigger();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!link.server.is_finished());
+    response_tx
+        .send(Ok(OrderCancelled {
+            order_id: order_id.to_string(),
+        }))
+        .unwrap();
+    assert!(timeout(WAIT, response_rx).await.unwrap().unwrap().is_ok());
+
+    timeout(WAIT, link.server)
+        .await
+        .expect("Server should stop")
+        .unwrap();
+    // With every connection closed nothing holds the engine queue
+    assert!(timeout(WAIT, link.engine_rx.recv())
+        .await
+        .unwrap()
+        .is_none());
+
+    // The gateway stops once its handlers let go of the queue
+    drop(link.gateway_tx);
+    timeout(WAIT, link.remote)
+        .await
+        .expect("Gateway link should stop")
+        .unwrap();
+}
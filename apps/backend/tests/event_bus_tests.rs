@@ -0,0 +1,120 @@
+use backend::engine::markets::MarketRegistry;
+use backend::event_bus::{bus_event, EventPublisher, EventSink};
+use backend::models::domain::{CancelReason, EngineEvent, OrderbookSnapshot};
+use exchange_protocol::events::{BusEvent, BusMessage, EVENT_SCHEMA_VERSION};
+use exchange_test_utils::helpers::sample_trade;
+use futures::StreamExt;
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::GenericImage;
+use tokio::sync::broadcast;
+
+fn trade_executed(market_id: &str) -> EngineEvent {
+    EngineEvent::TradeExecuted {
+        market: MarketRegistry::new().intern(market_id),
+        trade: sample_trade(market_id),
+    }
+}
+
+fn order_cancelled(user_address: &str) -> EngineEvent {
+    EngineEvent::OrderCancelled {
+        order_id: uuid::Uuid::nil(),
+        user_address: user_address.to_string(),
+        reason: Some(CancelReason::KillSwitch),
+    }
+}
+
+// ============================================================================
+// Event Mapping Tests
+// ============================================================================
+
+#[test]
+fn test_events_map_to_topics_and_partition_keys() {
+    let trade = bus_event(&trade_executed("BTC/USDC")).unwrap();
+    assert_eq!(trade.topic(), "trades");
+    assert_eq!(trade.key(), "BTC/USDC");
+
+    let cancelled = bus_event(&order_cancelled("alice")).unwrap();
+    assert_eq!(cancelled.topic(), "orders");
+    assert_eq!(cancelled.key(), "alice");
+
+    // Snapshots are served over WebSocket only
+    let snapshot = EngineEvent::OrderbookSnapshot {
+        market: MarketRegistry::new().intern("BTC/USDC"),
+        orderbook: OrderbookSnapshot {
+            market_id: "BTC/USDC".to_string(),
+            bids: vec![],
+            asks: vec![],
+            timestamp: chrono::Utc::now(),
+            version: 0,
+        },
+    };
+    assert!(bus_event(&snapshot).is_none());
+}
+
+#[test]
+fn test_messages_carry_schema_version_and_tagged_payload() {
+    let message = BusMessage {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: 7,
+        published_at: chrono::Utc::now(),
+        event: bus_event(&order_cancelled("alice")).unwrap(),
+    };
+
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["sequence"], 7);
+    assert_eq!(json["type"], "order_cancelled");
+    assert_eq!(json["data"]["user_address"], "alice");
+    assert_eq!(json["data"]["reason"], "kill_switch");
+
+    let decoded: BusMessage = serde_json::from_value(json).unwrap();
+    assert!(matches!(decoded.event, BusEvent::OrderCancelled { .. }));
+}
+
+// ============================================================================
+// NATS Publishing Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_publisher_sends_engine_events_to_nats_e2e() {
+    let nats = GenericImage::new("nats", "2.10")
+        .with_exposed_port(4222.tcp())
+        .with_wait_for(WaitFor::message_on_stderr("Server is ready"))
+        .start()
+        .await
+        .expect("Failed to start NATS container");
+    let url = format!(
+        "nats://127.0.0.1:{}",
+        nats.get_host_port_ipv4(4222.tcp()).await.unwrap()
+    );
+
+    let consumer = async_nats::connect(&url).await.unwrap();
+    let mut subscriber = consumer.subscribe("exchange.>").await.unwrap();
+    consumer.flush().await.unwrap();
+
+    let sink = EventSink::connect(&url)
+        .await
+        .expect("Failed to connect to NATS");
+    let (event_tx, event_rx) = broadcast::channel(16);
+    EventPublisher::new(sink, "exchange").spawn(event_rx);
+    event_tx.send(trade_executed("BTC/USDC")).unwrap();
+    event_tx.send(order_cancelled("alice")).unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..2 {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), subscriber.next())
+            .await
+            .expect("Timed out waiting for event")
+            .unwrap();
+        let decoded: BusMessage = serde_json::from_slice(&message.payload).unwrap();
+        received.push((message.subject.to_string(), decoded.sequence));
+    }
+    assert_eq!(
+        received,
+        vec![
+            ("exchange.trades".to_string(), 0),
+            ("exchange.orders".to_string(), 1),
+        ]
+    );
+}
@@ -0,0 +1,476 @@
+use axum::extract::ws::Utf8Bytes;
+use backend::api::ws::{ticker_data, EventRouter};
+use backend::engine::markets::MarketRegistry;
+use backend::errors::ErrorCode;
+use backend::models::api::{
+    ApiBookLevel, ApiMarketStats, ApiTopOfBook, ClientMessage, Notification, ServerMessage,
+    SubscriptionChannel,
+};
+use backend::models::domain::{
+    CancelReason, EngineEvent, MarketStatus, RejectReason, Subscription, Withdrawal,
+    WithdrawalStatus,
+};
+use chrono::{TimeZone, Utc};
+use exchange_test_utils::helpers::sample_trade;
+
+fn trade_executed(market_id: &str) -> EngineEvent {
+    EngineEvent::TradeExecuted {
+        market: MarketRegistry::new().intern(market_id),
+        trade: sample_trade(market_id),
+    }
+}
+
+fn decode(payload: &Utf8Bytes) -> ServerMessage {
+    serde_json::from_str(payload.as_str()).expect("payload should be a ServerMessage")
+}
+
+/// A BTC/USDC trade at `price` and `secs` past the epoch
+fn trade_at(secs: i64, price: u128) -> EngineEvent {
+    let mut trade = sample_trade("BTC/USDC");
+    trade.timestamp = Utc.timestamp_opt(secs, 0).unwrap();
+    trade.price = price;
+    EngineEvent::TradeExecuted {
+        market: MarketRegistry::new().intern("BTC/USDC"),
+        trade,
+    }
+}
+
+fn candles(interval: &str) -> Subscription {
+    Subscription::Candles {
+        market_id: "BTC/USDC".to_string(),
+        interval: interval.to_string(),
+    }
+}
+
+/// (interval, bar start, open, high, low, close, volume) of a candle message
+fn candle(payload: &Utf8Bytes) -> (String, i64, String, String, String, String, String) {
+    match decode(payload) {
+        ServerMessage::Candle {
+            interval,
+            timestamp,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            ..
+        } => (interval, timestamp, open, high, low, close, volume),
+        other => panic!("Expected a candle, got {:?}", other),
+    }
+}
+
+fn notifications(user_address: &str) -> Subscription {
+    Subscription::Notifications {
+        user_address: user_address.to_string(),
+    }
+}
+
+fn notification(payload: &Utf8Bytes) -> Notification {
+    match decode(payload) {
+        ServerMessage::UserNotification { notification } => notification,
+        other => panic!("Expected a notification, got {:?}", other),
+    }
+}
+
+fn trades(market_id: &str) -> Subscription {
+    Subscription::Trades {
+        market_id: market_id.to_string(),
+    }
+}
+
+// ============================================================================
+// Topic Routing Tests
+// ============================================================================
+
+#[test]
+fn test_events_reach_only_subscribed_connections() {
+    let router = EventRouter::new();
+    let (btc, mut btc_rx) = router.connect();
+    let (eth, mut eth_rx) = router.connect();
+    btc.subscribe(trades("BTC/USDC"));
+    eth.subscribe(trades("ETH/USDC"));
+
+    router.route(&trade_executed("BTC/USDC"));
+
+    let message = btc_rx
+        .try_recv()
+        .expect("BTC subscriber should get the trade");
+    assert!(
+        matches!(decode(&message), ServerMessage::Trade { trade } if trade.market_id == "BTC/USDC")
+    );
+    assert!(btc_rx.try_recv().is_err());
+    assert!(eth_rx.try_recv().is_err());
+}
+
+#[test]
+fn test_fill_for_both_sides_is_delivered_once() {
+    let router = EventRouter::new();
+    let (connection, mut rx) = router.connect();
+    for user_address in ["test_buyer", "test_seller"] {
+        connection.subscribe(Subscription::UserFills {
+            user_address: user_address.to_string(),
+        });
+    }
+
+    router.route(&trade_executed("BTC/USDC"));
+
+    assert!(matches!(
+        decode(&rx.try_recv().unwrap()),
+        ServerMessage::UserFill { .. }
+    ));
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_subscribers_share_one_serialized_payload() {
+    let router = EventRouter::new();
+    let (first, mut first_rx) = router.connect();
+    let (second, mut second_rx) = router.connect();
+    first.subscribe(trades("BTC/USDC"));
+    second.subscribe(trades("BTC/USDC"));
+
+    router.route(&trade_executed("BTC/USDC"));
+
+    let (a, b) = (first_rx.try_recv().unwrap(), second_rx.try_recv().unwrap());
+    assert_eq!(a, b);
+    // Same buffer, not two encodings of the same message
+    assert_eq!(a.as_str().as_ptr(), b.as_str().as_ptr());
+}
+
+#[test]
+fn test_unsubscribe_and_disconnect_remove_routes() {
+    let router = EventRouter::new();
+    let (first, mut first_rx) = router.connect();
+    let (second, _second_rx) = router.connect();
+    first.subscribe(trades("BTC/USDC"));
+    second.subscribe(trades("BTC/USDC"));
+    assert_eq!(router.subscriber_count(&trades("BTC/USDC")), 2);
+
+    first.unsubscribe(&trades("BTC/USDC"));
+    router.route(&trade_executed("BTC/USDC"));
+    assert!(first_rx.try_recv().is_err());
+
+    drop(second);
+    assert_eq!(router.subscriber_count(&trades("BTC/USDC")), 0);
+}
+
+#[test]
+fn test_exchange_cancellations_carry_their_reason() {
+    let router = EventRouter::new();
+    let (connection, mut rx) = router.connect();
+    connection.subscribe(Subscription::UserOrders {
+        user_address: "alice".to_string(),
+    });
+
+    for reason in [None, Some(CancelReason::MarketHalted)] {
+        router.route(&EngineEvent::OrderCancelled {
+            order_id: uuid::Uuid::new_v4(),
+            user_address: "alice".to_string(),
+            reason,
+        });
+        let payload = rx.try_recv().unwrap();
+        assert!(matches!(
+            decode(&payload),
+            ServerMessage::UserOrder { reason: r, .. } if r == reason
+        ));
+        // User cancellations keep the original message shape
+        assert_eq!(payload.as_str().contains("reason"), reason.is_some());
+    }
+}
+
+// ============================================================================
+// Live Candle Tests
+// ============================================================================
+
+#[test]
+fn test_connection_follows_several_candle_intervals() {
+    let router = EventRouter::new();
+    let (connection, mut rx) = router.connect();
+    connection.subscribe(candles("1m"));
+    connection.subscribe(candles("1h"));
+
+    // 10:00:30 and 10:01:10 fall in different minutes but the same hour
+    router.route(&trade_at(36_030, 100));
+    router.route(&trade_at(36_070, 120));
+
+    let bars: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+        .map(|payload| candle(&payload))
+        .collect();
+    let bar = |interval: &str, start: i64, ohlc: [u128; 4], volume: u128| {
+        (
+            interval.to_string(),
+            start,
+            ohlc[0].to_string(),
+            ohlc[1].to_string(),
+            ohlc[2].to_string(),
+            ohlc[3].to_string(),
+            volume.to_string(),
+        )
+    };
+    assert_eq!(
+        bars,
+        [
+            bar("1m", 36_000, [100, 100, 100, 100], 1_000_000),
+            bar("1h", 36_000, [100, 100, 100, 100], 1_000_000),
+            // A new minute opens a new 1m bar while the hour keeps building
+            bar("1m", 36_060, [120, 120, 120, 120], 1_000_000),
+            bar("1h", 36_000, [100, 120, 100, 120], 2_000_000),
+        ]
+    );
+
+    connection.unsubscribe(&candles("1m"));
+    router.route(&trade_at(36_080, 90));
+    let (interval, _, _, high, low, close, _) = candle(&rx.try_recv().unwrap());
+    assert_eq!(
+        (
+            interval.as_str(),
+            high.as_str(),
+            low.as_str(),
+            close.as_str()
+        ),
+        ("1h", "120", "90", "90")
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_late_trade_does_not_rewind_live_bar() {
+    let router = EventRouter::new();
+    let (connection, mut rx) = router.connect();
+    connection.subscribe(candles("1m"));
+
+    router.route(&trade_at(36_070, 120));
+    rx.try_recv().unwrap();
+    router.route(&trade_at(36_030, 100));
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_candle_subscription_requires_known_interval() {
+    let subscribe = |interval: Option<&str>| ClientMessage::Subscribe {
+        channel: SubscriptionChannel::Candles,
+        market_id: Some("BTC/USDC".to_string()),
+        user_address: None,
+        interval: interval.map(str::to_string),
+    };
+    assert_eq!(
+        Subscription::from_message(&subscribe(Some("15m"))),
+        Some(candles("15m"))
+    );
+    assert_eq!(Subscription::from_message(&subscribe(Some("2m"))), None);
+    assert_eq!(Subscription::from_message(&subscribe(None)), None);
+}
+
+// ============================================================================
+// Notification Tests
+// ============================================================================
+
+#[test]
+fn test_rejections_notify_only_their_user() {
+    let router = EventRouter::new();
+    let (alice, mut alice_rx) = router.connect();
+    let (bob, mut bob_rx) = router.connect();
+    alice.subscribe(notifications("alice"));
+    bob.subscribe(notifications("bob"));
+
+    router.route(&EngineEvent::OrderRejected {
+        order_id: uuid::Uuid::new_v4(),
+        user_address: "alice".to_string(),
+        market_id: "BTC/USDC".to_string(),
+        code: ErrorCode::InsufficientBalance,
+        message: "Insufficient balance".to_string(),
+        reason: Some(RejectReason::InsufficientBalance),
+    });
+
+    assert!(matches!(
+        notification(&alice_rx.try_recv().unwrap()),
+        Notification::OrderRejected {
+            code: ErrorCode::InsufficientBalance,
+            reason: Some(RejectReason::InsufficientBalance),
+            ..
+        }
+    ));
+    assert!(bob_rx.try_recv().is_err());
+}
+
+#[test]
+fn test_only_exchange_cancels_are_notified() {
+    let router = EventRouter::new();
+    let (connection, mut rx) = router.connect();
+    connection.subscribe(notifications("alice"));
+
+    for reason in [None, Some(CancelReason::AccountRestricted)] {
+        router.route(&EngineEvent::OrderCancelled {
+            order_id: uuid::Uuid::new_v4(),
+            user_address: "alice".to_string(),
+            reason,
+        });
+    }
+
+    assert!(matches!(
+        notification(&rx.try_recv().unwrap()),
+        Notification::OrderCancelled {
+            reason: CancelReason::AccountRestricted,
+            ..
+        }
+    ));
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_market_status_changes_reach_every_subscriber() {
+    let router = EventRouter::new();
+    let (alice, mut alice_rx) = router.connect();
+    let (bob, mut bob_rx) = router.connect();
+    let (trader, mut trader_rx) = router.connect();
+    alice.subscribe(notifications("alice"));
+    bob.subscribe(notifications("bob"));
+    trader.subscribe(trades("BTC/USDC"));
+
+    router.route(&EngineEvent::MarketStatusChanged {
+        market_id: "BTC/USDC".to_string(),
+        status: MarketStatus::Halted,
+    });
+
+    for rx in [&mut alice_rx, &mut bob_rx] {
+        assert!(matches!(
+            notification(&rx.try_recv().unwrap()),
+            Notification::MarketStatusChanged {
+                status: MarketStatus::Halted,
+                ..
+            }
+        ));
+    }
+    assert!(trader_rx.try_recv().is_err());
+}
+
+#[test]
+fn test_withdrawal_updates_are_notified() {
+    let router = EventRouter::new();
+    let (connection, mut rx) = router.connect();
+    connection.subscribe(notifications("alice"));
+
+    let now = Utc::now();
+    router.route(&EngineEvent::WithdrawalUpdated {
+        withdrawal: Withdrawal {
+            id: uuid::Uuid::new_v4(),
+            user_address: "alice".to_string(),
+            token_ticker: "USDC".to_string(),
+            amount: 5_000_000,
+            destination: "0xdead".to_string(),
+            status: WithdrawalStatus::Failed,
+            tx_hash: None,
+            error: Some("Destination rejected the transfer".to_string()),
+            created_at: now,
+            updated_at: now,
+        },
+    });
+
+    match notification(&rx.try_recv().unwrap()) {
+        Notification::WithdrawalUpdated {
+            amount,
+            status,
+            error,
+            ..
+        } => {
+            assert_eq!(amount, "5000000");
+            assert_eq!(status, WithdrawalStatus::Failed);
+            assert!(error.is_some());
+        }
+        other => panic!("Expected a withdrawal update, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_cancel_reason_round_trips() {
+    for reason in [
+        CancelReason::KillSwitch,
+        CancelReason::MarketHalted,
+        CancelReason::AccountRestricted,
+        CancelReason::Liquidation,
+        CancelReason::Expired,
+        CancelReason::LinkedOrderFilled,
+        CancelReason::SelfTrade,
+    ] {
+        assert_eq!(reason.to_string().parse::<CancelReason>(), Ok(reason));
+    }
+    assert!("timed_out".parse::<CancelReason>().is_err());
+}
+
+// ============================================================================
+// Ticker Tests
+// ============================================================================
+
+fn stats(market_id: &str, close: Option<&str>) -> ApiMarketStats {
+    ApiMarketStats {
+        market_id: market_id.to_string(),
+        base_volume: "300000000".to_string(),
+        quote_volume: "0".to_string(),
+        trade_count: 2,
+        open: None,
+        high: None,
+        low: None,
+        close: close.map(str::to_string),
+        change_percent: close.map(|_| 2.5),
+        from: 0,
+        to: 86_400,
+    }
+}
+
+#[test]
+fn test_ticker_combines_stats_and_top_of_book() {
+    let top = ApiTopOfBook {
+        market_id: "BTC/USDC".to_string(),
+        best_bid: Some(ApiBookLevel {
+            price: "49000000000".to_string(),
+            size: "1000000".to_string(),
+        }),
+        best_ask: None,
+        version: 7,
+        timestamp: 0,
+    };
+
+    let ticker = ticker_data(&stats("BTC/USDC", Some("50000000000")), Some(&top));
+    assert_eq!(ticker.last.as_deref(), Some("50000000000"));
+    assert_eq!(ticker.best_bid.as_deref(), Some("49000000000"));
+    assert_eq!(ticker.best_ask, None);
+    assert_eq!(ticker.change_percent, Some(2.5));
+    assert_eq!(ticker.volume, "300000000");
+
+    // A market with no trades and no snapshot yet
+    let ticker = ticker_data(&stats("ETH/USDC", None), None);
+    assert_eq!(ticker.last, None);
+    assert_eq!(ticker.best_bid, None);
+}
+
+#[test]
+fn test_tickers_reach_only_ticker_subscribers() {
+    let router = EventRouter::new();
+    let (follower, mut follower_rx) = router.connect();
+    let (trader, mut trader_rx) = router.connect();
+
+    let subscribe = ClientMessage::Subscribe {
+        channel: SubscriptionChannel::Tickers,
+        market_id: None,
+        user_address: None,
+        interval: None,
+    };
+    let topic = Subscription::from_message(&subscribe).unwrap();
+    assert_eq!(topic, Subscription::Tickers);
+    follower.subscribe(topic);
+    trader.subscribe(trades("BTC/USDC"));
+
+    router.publish_tickers(vec![
+        ticker_data(&stats("BTC/USDC", Some("50000000000")), None),
+        ticker_data(&stats("ETH/USDC", None), None),
+    ]);
+
+    match decode(&follower_rx.try_recv().unwrap()) {
+        ServerMessage::Tickers { tickers, .. } => {
+            let markets: Vec<&str> = tickers.iter().map(|t| t.market_id.as_str()).collect();
+            assert_eq!(markets, ["BTC/USDC", "ETH/USDC"]);
+        }
+        other => panic!("Expected tickers, got {:?}", other),
+    }
+    assert!(trader_rx.try_recv().is_err());
+}
@@ -0,0 +1,220 @@
+use backend::errors::ExchangeError;
+use backend::models::domain::{EventStatus, Market};
+use backend::prediction::{outcome_payout, plan_resolution, validate_event, OutcomeHolding};
+use exchange_test_utils::TestDb;
+
+fn market(base_ticker: &str, quote_ticker: &str) -> Market {
+    Market {
+        id: format!("{}/{}", base_ticker, quote_ticker),
+        base_ticker: base_ticker.to_string(),
+        quote_ticker: quote_ticker.to_string(),
+        tick_size: 1000,
+        lot_size: 1_000_000,
+        min_size: 1_000_000,
+        maker_fee_bps: 0,
+        taker_fee_bps: 0,
+    }
+}
+
+fn outcomes(markets: &[(&str, Market)]) -> Vec<(String, Market)> {
+    markets
+        .iter()
+        .map(|(name, market)| (name.to_string(), market.clone()))
+        .collect()
+}
+
+fn holding(user_address: &str, token_ticker: &str, amount: u128) -> OutcomeHolding {
+    OutcomeHolding {
+        user_address: user_address.to_string(),
+        token_ticker: token_ticker.to_string(),
+        amount,
+        open_interest: 0,
+    }
+}
+
+// ============================================================================
+// Event Invariant Tests
+// ============================================================================
+
+#[test]
+fn test_outcome_payout_scales_by_decimals() {
+    // 2.5 outcome tokens with 8 decimals pay 2.5 USDC with 6 decimals
+    assert_eq!(outcome_payout(250_000_000, 8, 6), Some(2_500_000));
+    // Dust below one quote atom is rounded down
+    assert_eq!(outcome_payout(99, 8, 6), Some(0));
+    assert_eq!(outcome_payout(1_000_000, 6, 8), Some(100_000_000));
+    assert_eq!(outcome_payout(u128::MAX, 8, 18), None);
+}
+
+#[test]
+fn test_validate_event_requires_distinct_outcomes_in_one_quote() {
+    let yes = market("YES", "USDC");
+    let no = market("NO", "USDC");
+    assert!(validate_event(
+        "election-2028",
+        "USDC",
+        &outcomes(&[("Yes", yes.clone()), ("No", no.clone())])
+    )
+    .is_ok());
+
+    let invalid = [
+        // Not a slug
+        (
+            "Election 2028",
+            outcomes(&[("Yes", yes.clone()), ("No", no.clone())]),
+        ),
+        // A single outcome
+        ("election-2028", outcomes(&[("Yes", yes.clone())])),
+        // Duplicate names
+        (
+            "election-2028",
+            outcomes(&[("Yes", yes.clone()), ("Yes", no.clone())]),
+        ),
+        // The same token twice
+        (
+            "election-2028",
+            outcomes(&[("Yes", yes.clone()), ("Also yes", yes.clone())]),
+        ),
+        // Another quote token
+        (
+            "election-2028",
+            outcomes(&[("Yes", yes.clone()), ("No", market("NO", "USDT"))]),
+        ),
+    ];
+    for (event_id, outcomes) in invalid {
+        assert!(matches!(
+            validate_event(event_id, "USDC", &outcomes),
+            Err(ExchangeError::InvalidParameter { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_plan_resolution_burns_outcomes_and_pays_winners() {
+    let mut locked = holding("alice", "NO", 300_000_000);
+    locked.open_interest = 100_000_000;
+    let holdings = [
+        holding("alice", "YES", 200_000_000),
+        locked,
+        holding("bob", "YES", 50_000_000),
+        holding("carol", "NO", 700_000_000),
+    ];
+
+    let plan = plan_resolution(&holdings, "YES", 8, "USDC", 6).unwrap();
+    assert_eq!(plan.holders_paid, 2);
+    assert_eq!(plan.total_payout, 2_500_000);
+
+    let change = |user: &str, token: &str| {
+        plan.balance_changes[&(user.to_string(), token.to_string())].clone()
+    };
+    assert_eq!(change("alice", "YES").debit, 200_000_000);
+    assert_eq!(change("alice", "NO").debit, 300_000_000);
+    assert_eq!(change("alice", "NO").unlock, 100_000_000);
+    assert_eq!(change("alice", "USDC").credit, 2_000_000);
+    assert_eq!(change("bob", "USDC").credit, 500_000);
+    assert_eq!(change("carol", "NO").debit, 700_000_000);
+    assert!(!plan
+        .balance_changes
+        .contains_key(&("carol".to_string(), "USDC".to_string())));
+}
+
+// ============================================================================
+// Database Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_create_and_resolve_event() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let db = &test_db.db;
+
+    db.create_token("USDC".to_string(), 6, "USD Coin".to_string())
+        .await
+        .unwrap();
+    let mut market_ids = Vec::new();
+    for ticker in ["RED", "BLUE", "GREEN"] {
+        db.create_token(ticker.to_string(), 8, format!("{} wins", ticker))
+            .await
+            .unwrap();
+        let market = db
+            .create_market(
+                ticker.to_string(),
+                "USDC".to_string(),
+                1000,
+                1_000_000,
+                1_000_000,
+                0,
+                0,
+            )
+            .await
+            .unwrap();
+        market_ids.push(market.id);
+    }
+    let outcomes: Vec<(String, String)> = ["Red", "Blue", "Green"]
+        .iter()
+        .zip(&market_ids)
+        .map(|(name, market_id)| (name.to_string(), market_id.clone()))
+        .collect();
+
+    let event = db
+        .create_event("race-winner", "Who wins the race?", &outcomes)
+        .await
+        .expect("Failed to create event");
+    assert_eq!(event.quote_ticker, "USDC");
+    assert_eq!(event.status, EventStatus::Open);
+    let names: Vec<&str> = event.outcomes.iter().map(|o| o.name.as_str()).collect();
+    assert_eq!(names, ["Red", "Blue", "Green"]);
+    assert_eq!(event.outcomes[1].token_ticker, "BLUE");
+
+    // A market belongs to one event only
+    let taken = db
+        .create_event("rematch", "Who wins the rematch?", &outcomes)
+        .await;
+    assert!(matches!(taken, Err(ExchangeError::InvalidParameter { .. })));
+    assert_eq!(
+        db.list_events(Some(EventStatus::Open)).await.unwrap().len(),
+        1
+    );
+
+    for user in ["alice", "bob"] {
+        db.create_user(user.to_string()).await.unwrap();
+    }
+    db.add_balance("alice", "BLUE", 300_000_000).await.unwrap();
+    db.add_balance("alice", "RED", 100_000_000).await.unwrap();
+    db.add_balance("bob", "BLUE", 100_000_000).await.unwrap();
+    db.add_balance("bob", "USDC", 1_000_000).await.unwrap();
+
+    let unknown = db.resolve_event("race-winner", "Purple").await;
+    assert!(matches!(
+        unknown,
+        Err(ExchangeError::InvalidParameter { .. })
+    ));
+
+    let (event, plan) = db.resolve_event("race-winner", "Blue").await.unwrap();
+    assert_eq!(event.status, EventStatus::Resolved);
+    assert_eq!(event.winning_outcome.as_deref(), Some("Blue"));
+    assert!(event.resolved_at.is_some());
+    assert_eq!(plan.holders_paid, 2);
+    assert_eq!(plan.total_payout, 4_000_000);
+
+    assert_eq!(
+        db.get_balance("alice", "USDC").await.unwrap().amount,
+        3_000_000
+    );
+    assert_eq!(
+        db.get_balance("bob", "USDC").await.unwrap().amount,
+        2_000_000
+    );
+    for (user, token) in [("alice", "BLUE"), ("alice", "RED"), ("bob", "BLUE")] {
+        assert_eq!(db.get_balance(user, token).await.unwrap().amount, 0);
+    }
+
+    let again = db.resolve_event("race-winner", "Red").await;
+    assert!(matches!(
+        again,
+        Err(ExchangeError::EventAlreadyResolved { .. })
+    ));
+    assert!(matches!(
+        db.get_event("no-such-event").await,
+        Err(ExchangeError::EventNotFound { .. })
+    ));
+}
@@ -0,0 +1,156 @@
+use backend::api::rest::export::MAX_STREAMED_EXPORT_ROWS;
+use backend::models::api::{ApiExportJob, ExportFormat, ExportJobStatus};
+use backend::models::domain::{Side, Trade};
+use chrono::{DateTime, Utc};
+use exchange_test_utils::{helpers, TestServer};
+
+fn trade(buyer: &str, seller: &str, side: Side, price: u128, timestamp: i64) -> Trade {
+    Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: "BTC/USDC".to_string(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price,
+        size: 100_000_000,
+        side,
+        timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+        rfq: false,
+    }
+}
+
+// ============================================================================
+// Fills Export Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_small_export_streams_csv() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    helpers::create_user(&server.test_db, "alice")
+        .await
+        .expect("Failed to create user");
+
+    let t = 1_700_000_000;
+    let bought = trade("alice", "bob", Side::Buy, 50_000_000_000, t);
+    let sold = trade("bob", "alice", Side::Buy, 60_000_000_000, t + 60);
+    server
+        .db()
+        .insert_trades_to_clickhouse(&[
+            bought.clone(),
+            sold.clone(),
+            // Outside the range, and someone else's
+            trade("alice", "bob", Side::Buy, 1, t + 3600),
+            trade("carol", "bob", Side::Buy, 1, t),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let url = server.url(&format!(
+        "/api/users/alice/fills/export?from={}&to={}",
+        t,
+        t + 60
+    ));
+    let response = reqwest::get(&url).await.expect("Request failed");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "text/csv");
+    assert!(response.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .starts_with("attachment"));
+
+    let csv = response.text().await.unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "\"trade_id\",\"timestamp\",\"time_utc\",\"market_id\",\"side\",\"role\",\"price\",\"size\"",
+            format!(
+                "\"{}\",{},\"2023-11-14 22:13:20\",\"BTC/USDC\",\"buy\",\"taker\",\"50000000000\",\"100000000\"",
+                bought.id, t
+            )
+            .as_str(),
+            format!(
+                "\"{}\",{},\"2023-11-14 22:14:20\",\"BTC/USDC\",\"sell\",\"maker\",\"60000000000\",\"100000000\"",
+                sold.id,
+                t + 60
+            )
+            .as_str(),
+        ]
+    );
+
+    let missing = reqwest::get(server.url("/api/users/nobody/fills/export?from=0&to=60"))
+        .await
+        .expect("Request failed");
+    assert_eq!(missing.status(), 404);
+    let backwards = reqwest::get(server.url("/api/users/alice/fills/export?from=60&to=0"))
+        .await
+        .expect("Request failed");
+    assert_eq!(backwards.status(), 400);
+}
+
+#[tokio::test]
+async fn test_large_export_runs_as_job() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    helpers::create_user(&server.test_db, "alice")
+        .await
+        .expect("Failed to create user");
+
+    let t = 1_700_000_000;
+    let trades: Vec<_> = (0..=MAX_STREAMED_EXPORT_ROWS as i64)
+        .map(|i| trade("alice", "bob", Side::Buy, 50_000_000_000, t + i % 86_400))
+        .collect();
+    server
+        .db()
+        .insert_trades_to_clickhouse(&trades)
+        .await
+        .expect("Failed to insert trades");
+
+    let url = server.url(&format!(
+        "/api/users/alice/fills/export?format=parquet&from={}&to={}",
+        t,
+        t + 86_400
+    ));
+    let response = reqwest::get(&url).await.expect("Request failed");
+    assert_eq!(response.status(), 202);
+    let job: ApiExportJob = response.json().await.unwrap();
+    assert_eq!(job.format, ExportFormat::Parquet);
+    assert!(job.expires_at > Utc::now().timestamp());
+
+    let mut job = job;
+    for _ in 0..100 {
+        if job.status != ExportJobStatus::Pending {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        job = reqwest::get(server.url(&format!("/api/exports/{}", job.job_id)))
+            .await
+            .expect("Request failed")
+            .json()
+            .await
+            .unwrap();
+    }
+    assert_eq!(job.status, ExportJobStatus::Ready);
+
+    let file = reqwest::get(server.url(&job.download_url))
+        .await
+        .expect("Request failed");
+    assert_eq!(file.status(), 200);
+    let bytes = file.bytes().await.unwrap();
+    assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+
+    let unknown = reqwest::get(server.url(&format!("/api/exports/{}", uuid::Uuid::new_v4())))
+        .await
+        .expect("Request failed");
+    assert_eq!(unknown.status(), 404);
+}
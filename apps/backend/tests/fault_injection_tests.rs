@@ -0,0 +1,107 @@
+use backend::db::Db;
+use backend::engine::MatchingEngine;
+use backend::models::domain::OrderStatus;
+use exchange_test_utils::{helpers, OrderBuilder, Service, TestDb, TestEngine};
+use std::time::Duration;
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn test_matching_survives_clickhouse_outage() {
+    let test_db = TestDb::setup_dedicated()
+        .await
+        .expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let engine = TestEngine::new(&test_db).await;
+
+    let sell = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    let buy = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+
+    // Trades are written to ClickHouse off the order path, so matching must not stall
+    let placed = test_db
+        .with_paused(Service::ClickHouse, async {
+            engine.place_order(sell).await.expect("Sell failed");
+            tokio::time::timeout(Duration::from_secs(5), engine.place_order(buy)).await
+        })
+        .await
+        .expect("Failed to toggle ClickHouse");
+
+    let placed = placed
+        .expect("Order placement blocked on ClickHouse")
+        .expect("Buy failed");
+    assert_eq!(placed.order.status, OrderStatus::Filled);
+
+    test_db
+        .wait_until_healthy(Service::ClickHouse, Duration::from_secs(10))
+        .await
+        .expect("ClickHouse did not recover");
+}
+
+#[tokio::test]
+async fn test_engine_recovers_after_postgres_pause() {
+    let test_db = TestDb::setup_dedicated()
+        .await
+        .expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let engine = TestEngine::new(&test_db).await;
+
+    test_db
+        .pause(Service::Postgres)
+        .await
+        .expect("Failed to pause Postgres");
+
+    // While Postgres is frozen the order cannot complete
+    let stalled = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    let stalled_result =
+        tokio::time::timeout(Duration::from_millis(500), engine.place_order(stalled)).await;
+    assert!(
+        stalled_result.is_err(),
+        "Order should not complete while Postgres is paused"
+    );
+
+    test_db
+        .unpause(Service::Postgres)
+        .await
+        .expect("Failed to unpause Postgres");
+    test_db
+        .wait_until_healthy(Service::Postgres, Duration::from_secs(10))
+        .await
+        .expect("Postgres did not recover");
+
+    // The engine keeps processing requests once the database is back
+    let order = OrderBuilder::sell("seller", &market.id)
+        .limit(51_000_000_000)
+        .size(1_000_000)
+        .build();
+    let result = tokio::time::timeout(Duration::from_secs(10), engine.place_order(order))
+        .await
+        .expect("Engine did not recover after Postgres pause");
+    assert!(result.is_ok(), "Order failed after recovery: {:?}", result);
+}
+
+#[tokio::test]
+async fn test_engine_refuses_to_start_without_its_state() {
+    // An in-memory Db without its store sends every query to a database that isn't there
+    let mut db = Db::in_memory().expect("Failed to create Db");
+    db.memory = None;
+    let (_engine_tx, engine_rx) = tokio::sync::mpsc::channel(1);
+    let (event_tx, _) = tokio::sync::broadcast::channel(16);
+    let mut engine = MatchingEngine::new(db, engine_rx, event_tx);
+
+    assert!(engine.load_state().await.is_err());
+}
@@ -0,0 +1,193 @@
+use backend::engine::fees::FeeOverrides;
+use backend::models::api::UserFeesResponse;
+use backend::models::domain::{FeeOverride, Market, Side, Trade};
+use exchange_test_utils::{helpers, TestServer, TEST_ADMIN_TOKEN};
+use serde_json::json;
+
+const BTC: u128 = 100_000_000;
+
+// ============================================================================
+// Fee Override Tests
+// ============================================================================
+
+fn market(id: &str) -> Market {
+    Market {
+        id: id.to_string(),
+        base_ticker: "BTC".to_string(),
+        quote_ticker: "USDC".to_string(),
+        tick_size: 1000,
+        lot_size: 1000000,
+        min_size: 1000000,
+        maker_fee_bps: 10,
+        taker_fee_bps: 20,
+    }
+}
+
+fn fee_override(
+    user_address: &str,
+    market_id: &str,
+    maker_fee_bps: Option<i32>,
+    taker_fee_bps: Option<i32>,
+) -> FeeOverride {
+    FeeOverride {
+        user_address: user_address.to_string(),
+        market_id: market_id.to_string(),
+        maker_fee_bps,
+        taker_fee_bps,
+        updated_at: chrono::Utc::now(),
+    }
+}
+
+#[test]
+fn test_users_without_override_pay_market_fees() {
+    let fees = FeeOverrides::new(vec![fee_override("alice", "BTC/USDC", Some(-5), Some(5))]);
+    let btc = market("BTC/USDC");
+    let eth = market("ETH/USDC");
+
+    assert_eq!(fees.maker_fee_bps(&btc, "alice"), -5);
+    assert_eq!(fees.taker_fee_bps(&btc, "alice"), 5);
+    // Overrides are per market and per user
+    assert_eq!(fees.maker_fee_bps(&eth, "alice"), 10);
+    assert_eq!(fees.taker_fee_bps(&btc, "bob"), 20);
+}
+
+#[test]
+fn test_override_sides_left_out_pay_market_fee() {
+    let mut fees = FeeOverrides::default();
+    let btc = market("BTC/USDC");
+
+    fees.set(fee_override("alice", "BTC/USDC", None, Some(0)));
+    assert_eq!(fees.maker_fee_bps(&btc, "alice"), 10);
+    assert_eq!(fees.taker_fee_bps(&btc, "alice"), 0);
+
+    // Setting again replaces rather than merges
+    fees.set(fee_override("alice", "BTC/USDC", Some(2), None));
+    assert_eq!(fees.maker_fee_bps(&btc, "alice"), 2);
+    assert_eq!(fees.taker_fee_bps(&btc, "alice"), 20);
+
+    fees.clear("alice", "BTC/USDC");
+    assert_eq!(fees.maker_fee_bps(&btc, "alice"), 10);
+    // Clearing what isn't there is fine
+    fees.clear("bob", "BTC/USDC");
+}
+
+// ============================================================================
+// Fees Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_user_fees_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    for user in ["alice", "bob"] {
+        helpers::create_user(&server.test_db, user)
+            .await
+            .expect("Failed to create user");
+    }
+
+    let now = chrono::Utc::now();
+    let trade = |days_ago: i64, buyer: &str, seller: &str, side: Side| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price: 50_000_000_000,
+        size: BTC,
+        side,
+        timestamp: now - chrono::Duration::days(days_ago),
+        rfq: false,
+    };
+    server
+        .db()
+        .insert_trades_to_clickhouse(&[
+            // Alice takes once and makes twice in the window
+            trade(1, "alice", "bob", Side::Buy),
+            trade(2, "alice", "bob", Side::Sell),
+            trade(3, "bob", "alice", Side::Buy),
+            // Too old, and with herself
+            trade(31, "alice", "bob", Side::Buy),
+            trade(1, "alice", "alice", Side::Buy),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let client = reqwest::Client::new();
+    let set_override = |body: serde_json::Value| {
+        client
+            .post(server.url("/api/admin"))
+            .bearer_auth(TEST_ADMIN_TOKEN)
+            .json(&body)
+            .send()
+    };
+
+    // Overrides can only lower fees
+    let response = set_override(json!({
+        "type": "set_fee_override",
+        "user_address": "alice",
+        "market_id": market.id,
+        "taker_fee_bps": 25,
+    }))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = set_override(json!({
+        "type": "set_fee_override",
+        "user_address": "alice",
+        "market_id": market.id,
+        "maker_fee_bps": -2,
+    }))
+    .await
+    .unwrap();
+    assert!(response.status().is_success());
+
+    let user_fees = |address: &'static str| {
+        let url = server.url(&format!("/api/users/{}/fees", address));
+        async move { reqwest::get(&url).await.expect("Failed to make request") }
+    };
+
+    let fees: UserFeesResponse = user_fees("alice").await.json().await.unwrap();
+    assert_eq!(fees.markets.len(), 1);
+    let btc = &fees.markets[0];
+    assert_eq!(btc.market_id, market.id);
+    assert_eq!(btc.maker_fee_bps, -2);
+    assert_eq!(btc.taker_fee_bps, 20);
+    assert_eq!(btc.market_maker_fee_bps, 10);
+    assert_eq!(btc.market_taker_fee_bps, 20);
+    assert_eq!(
+        btc.fee_override
+            .as_ref()
+            .map(|o| (o.maker_fee_bps, o.taker_fee_bps)),
+        Some((Some(-2), None))
+    );
+    assert_eq!(btc.maker_volume_30d, (2 * BTC).to_string());
+    assert_eq!(btc.taker_volume_30d, BTC.to_string());
+
+    // Bob has no override and pays the market's fees
+    let fees: UserFeesResponse = user_fees("bob").await.json().await.unwrap();
+    assert_eq!(fees.markets[0].maker_fee_bps, 10);
+    assert!(fees.markets[0].fee_override.is_none());
+    assert_eq!(fees.markets[0].maker_volume_30d, BTC.to_string());
+    assert_eq!(fees.markets[0].taker_volume_30d, (2 * BTC).to_string());
+
+    // Leaving out both sides clears the override
+    let response = set_override(json!({
+        "type": "set_fee_override",
+        "user_address": "alice",
+        "market_id": market.id,
+    }))
+    .await
+    .unwrap();
+    assert!(response.status().is_success());
+    let fees: UserFeesResponse = user_fees("alice").await.json().await.unwrap();
+    assert_eq!(fees.markets[0].maker_fee_bps, 10);
+    assert!(fees.markets[0].fee_override.is_none());
+
+    assert_eq!(user_fees("nobody").await.status(), 404);
+}
@@ -0,0 +1,150 @@
+use backend::models::api::FlowAnalyticsResponse;
+use backend::models::domain::{LiquidityRole, MakerVolume, Side, TakerFlow, Trade};
+use chrono::{DateTime, Utc};
+use exchange_test_utils::{helpers, TestServer};
+
+const BTC: u128 = 100_000_000;
+
+fn trade(buyer: &str, seller: &str, side: Side, size: u128, timestamp: i64) -> Trade {
+    Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: "BTC/USDC".to_string(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price: 50_000_000_000,
+        size,
+        side,
+        timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+        rfq: false,
+    }
+}
+
+// ============================================================================
+// Liquidity Role Tests
+// ============================================================================
+
+#[test]
+fn test_trade_side_marks_the_taker() {
+    let bought = trade("alice", "bob", Side::Buy, BTC, 0);
+    assert_eq!(bought.role_of("alice"), Some(LiquidityRole::Taker));
+    assert_eq!(bought.role_of("bob"), Some(LiquidityRole::Maker));
+    assert_eq!(bought.role_of("carol"), None);
+
+    let sold = trade("alice", "bob", Side::Sell, BTC, 0);
+    assert_eq!(sold.role_of("alice"), Some(LiquidityRole::Maker));
+    assert_eq!(sold.role_of("bob"), Some(LiquidityRole::Taker));
+
+    let own = trade("alice", "alice", Side::Sell, BTC, 0);
+    assert_eq!(own.role_of("alice"), Some(LiquidityRole::Taker));
+}
+
+#[test]
+fn test_maker_ratio_and_taker_imbalance() {
+    let volume = |maker_volume, taker_volume| MakerVolume {
+        user_address: "alice".to_string(),
+        maker_volume,
+        taker_volume,
+    };
+    assert_eq!(volume(3 * BTC, BTC).maker_ratio_bps(), 7_500);
+    assert_eq!(volume(BTC, 0).maker_ratio_bps(), 10_000);
+    assert_eq!(volume(0, BTC).maker_ratio_bps(), 0);
+    assert_eq!(volume(0, 0).maker_ratio_bps(), 5_000);
+
+    let flow = |buy_volume, sell_volume| TakerFlow {
+        timestamp: Utc::now(),
+        buy_volume,
+        sell_volume,
+    };
+    assert_eq!(flow(BTC, 3 * BTC).imbalance_bps(), -5_000);
+    assert_eq!(flow(BTC, 0).imbalance_bps(), 10_000);
+}
+
+// ============================================================================
+// Flow Analytics Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_flow_analytics_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    // Buckets are aligned to the hour
+    let hour = Utc::now().timestamp() / 3600 * 3600 - 2 * 3600;
+    server
+        .db()
+        .insert_trades_to_clickhouse(&[
+            // alice rests orders that bob and carol trade against
+            trade("bob", "alice", Side::Buy, 3 * BTC, hour + 60),
+            trade("alice", "carol", Side::Sell, BTC, hour + 120),
+            // Next hour alice takes from bob
+            trade("alice", "bob", Side::Buy, BTC, hour + 3600 + 60),
+            // Trades with yourself don't count
+            trade("carol", "carol", Side::Buy, 100 * BTC, hour + 3600 + 120),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let get = |query: String| {
+        let url = server.url(&format!("/api/markets/BTC%2FUSDC/flow?{}", query));
+        async move { reqwest::get(&url).await.expect("Request failed") }
+    };
+
+    let flow: FlowAnalyticsResponse = get(format!("from={}&to={}", hour, hour + 7200))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(flow.interval, "1h");
+
+    let makers: Vec<_> = flow
+        .makers
+        .iter()
+        .map(|m| {
+            (
+                m.user_address.as_str(),
+                m.maker_volume.as_str(),
+                m.taker_volume.as_str(),
+                m.maker_ratio_bps,
+            )
+        })
+        .collect();
+    assert_eq!(
+        makers,
+        vec![
+            ("alice", "400000000", "100000000", 8_000),
+            ("bob", "100000000", "300000000", 2_500),
+            ("carol", "0", "100000000", 0),
+        ]
+    );
+
+    let taker_flow: Vec<_> = flow
+        .taker_flow
+        .iter()
+        .map(|b| {
+            (
+                b.timestamp,
+                b.buy_volume.as_str(),
+                b.sell_volume.as_str(),
+                b.imbalance_bps,
+            )
+        })
+        .collect();
+    assert_eq!(
+        taker_flow,
+        vec![
+            (hour, "300000000", "100000000", 5_000),
+            (hour + 3600, "100000000", "0", 10_000),
+        ]
+    );
+
+    let response = get(format!("from={}&to={}&interval=2h", hour, hour + 7200)).await;
+    assert_eq!(response.status(), 400);
+    let response = get(format!("from=0&to={}&interval=1m", hour)).await;
+    assert_eq!(response.status(), 400);
+}
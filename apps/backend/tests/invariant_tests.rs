@@ -0,0 +1,17 @@
+use exchange_test_utils::{check_engine_invariants, check_engine_invariants_in_memory};
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+/// Random place/cancel sequences must never break book, fill or balance invariants
+#[test]
+fn test_engine_invariants_hold_for_random_sequences() {
+    check_engine_invariants(16, 40);
+}
+
+/// The same sequences over the in-memory database, which needs no containers
+#[test]
+fn test_engine_invariants_hold_for_random_sequences_in_memory() {
+    check_engine_invariants_in_memory(64, 40);
+}
@@ -0,0 +1,163 @@
+use backend::engine::kill_switch::KillSwitches;
+use backend::models::domain::KillSwitch;
+use chrono::Utc;
+use exchange_test_utils::{helpers, TestServer, TEST_ADMIN_TOKEN};
+use serde_json::{json, Value};
+
+fn switch(market_id: Option<&str>) -> KillSwitch {
+    KillSwitch {
+        market_id: market_id.map(str::to_string),
+        reason: None,
+        engaged_at: Utc::now(),
+    }
+}
+
+async fn kill_switch(server: &TestServer, token: Option<&str>, body: Value) -> (u16, Value) {
+    let mut request = reqwest::Client::new()
+        .post(server.url("/api/kill-switch"))
+        .json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.expect("Failed to send request");
+    let status = response.status().as_u16();
+    (status, response.json().await.expect("Failed to parse JSON"))
+}
+
+async fn place_bid(server: &TestServer, market_id: &str) -> (u16, Value) {
+    let response = reqwest::Client::new()
+        .post(server.url("/api/trade"))
+        .json(&json!({
+            "type": "place_order",
+            "user_address": "trader",
+            "market_id": market_id,
+            "side": "buy",
+            "order_type": "limit",
+            "price": "50000000000",
+            "size": "1000000",
+            "signature": "test_signature"
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    let status = response.status().as_u16();
+    (status, response.json().await.expect("Failed to parse JSON"))
+}
+
+// ============================================================================
+// Switch State Tests
+// ============================================================================
+
+#[test]
+fn test_global_switch_blocks_every_market() {
+    let mut switches = KillSwitches::default();
+    switches.engage(switch(Some("BTC/USDC")));
+    assert!(switches.blocking("BTC/USDC").is_some());
+    assert!(switches.blocking("ETH/USDC").is_none());
+
+    switches.engage(switch(None));
+    assert!(switches.blocking("ETH/USDC").is_some());
+
+    // Releasing the global switch leaves the market switch engaged
+    assert!(switches.release(None));
+    assert!(switches.blocking("BTC/USDC").is_some());
+    assert!(switches.blocking("ETH/USDC").is_none());
+    assert!(!switches.release(None));
+}
+
+#[test]
+fn test_engaging_twice_replaces_the_switch() {
+    let mut switches = KillSwitches::new(vec![switch(Some("BTC/USDC"))]);
+    switches.engage(KillSwitch {
+        reason: Some("bad oracle".to_string()),
+        ..switch(Some("BTC/USDC"))
+    });
+
+    assert_eq!(
+        switches.blocking("BTC/USDC").unwrap().reason.as_deref(),
+        Some("bad oracle")
+    );
+    assert!(switches.release(Some("BTC/USDC")));
+    assert!(switches.blocking("BTC/USDC").is_none());
+}
+
+// ============================================================================
+// Kill Switch API Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_kill_switch_requires_admin_token() {
+    let server = TestServer::start().await.expect("Failed to start server");
+
+    let (status, body) = kill_switch(&server, None, json!({ "type": "status" })).await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "UNAUTHORIZED");
+
+    let (status, _) = kill_switch(&server, Some("wrong"), json!({ "type": "status" })).await;
+    assert_eq!(status, 401);
+}
+
+#[tokio::test]
+async fn test_kill_switch_cancels_orders_and_blocks_placement() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    helpers::create_user(&server.test_db, "trader")
+        .await
+        .expect("Failed to create user");
+    server
+        .db()
+        .add_balance("trader", "USDC", 100_000_000_000)
+        .await
+        .expect("Failed to fund trader");
+
+    let (status, _) = place_bid(&server, &market.id).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = kill_switch(
+        &server,
+        Some(TEST_ADMIN_TOKEN),
+        json!({
+            "type": "engage",
+            "market_id": market.id,
+            "reason": "incident",
+            "cancel_orders": true
+        }),
+    )
+    .await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["cancelled_orders"], 1);
+
+    // Cancelling released the funds locked by the bid
+    let balance = server
+        .db()
+        .get_balance("trader", "USDC")
+        .await
+        .expect("Failed to get balance");
+    assert_eq!(balance.open_interest, 0);
+
+    let (status, body) = place_bid(&server, &market.id).await;
+    assert_eq!(status, 503);
+    assert_eq!(body["code"], "CANCEL_ONLY");
+
+    // The switch is persisted
+    let switches = server
+        .db()
+        .list_kill_switches()
+        .await
+        .expect("Failed to list kill switches");
+    assert_eq!(switches.len(), 1);
+    assert_eq!(switches[0].market_id.as_deref(), Some(market.id.as_str()));
+
+    let (status, _) = kill_switch(
+        &server,
+        Some(TEST_ADMIN_TOKEN),
+        json!({ "type": "release", "market_id": market.id }),
+    )
+    .await;
+    assert_eq!(status, 200);
+
+    let (status, _) = place_bid(&server, &market.id).await;
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,159 @@
+use backend::api::rest::l3::{L3Access, L3Query};
+use backend::errors::ExchangeError;
+use backend::models::api::ApiL3Book;
+use backend::models::domain::OrderStatus;
+use chrono::{Duration as ChronoDuration, Utc};
+use exchange_test_utils::{helpers, OrderBuilder, TestServer, TestWallet};
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// Per-Order Book Tests
+// ============================================================================
+
+#[test]
+fn test_book_keeps_price_time_priority() {
+    let access = L3Access::default();
+    let now = Utc::now();
+    let at = |secs_ago: i64| now - ChronoDuration::seconds(secs_ago);
+
+    // Resting orders come in queue order, as the engine holds them
+    let mut orders = vec![
+        OrderBuilder::buy("alice", "BTC/USDC")
+            .limit(49_000_000_000)
+            .build(),
+        OrderBuilder::buy("bob", "BTC/USDC")
+            .limit(50_000_000_000)
+            .build(),
+        OrderBuilder::buy("carol", "BTC/USDC")
+            .limit(50_000_000_000)
+            .size(2_000_000)
+            .filled(500_000)
+            .status(OrderStatus::PartiallyFilled)
+            .build(),
+        OrderBuilder::sell("dave", "BTC/USDC")
+            .limit(52_000_000_000)
+            .build(),
+        OrderBuilder::sell("erin", "BTC/USDC")
+            .limit(51_000_000_000)
+            .build(),
+    ];
+    for (order, secs_ago) in orders.iter_mut().zip([50, 40, 30, 20, 10]) {
+        order.created_at = at(secs_ago);
+    }
+
+    let book = access.book("BTC/USDC", &orders, now);
+    let levels = |side: &[backend::models::api::ApiL3Order]| {
+        side.iter()
+            .map(|order| (order.price.clone(), order.size.clone(), order.age_ms))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        levels(&book.bids),
+        [
+            ("50000000000".to_string(), "1000000".to_string(), 40_000),
+            ("50000000000".to_string(), "1500000".to_string(), 30_000),
+            ("49000000000".to_string(), "1000000".to_string(), 50_000),
+        ]
+    );
+    assert_eq!(
+        levels(&book.asks),
+        [
+            ("51000000000".to_string(), "1000000".to_string(), 10_000),
+            ("52000000000".to_string(), "1000000".to_string(), 20_000),
+        ]
+    );
+    assert_eq!(book.timestamp, now.timestamp_millis());
+}
+
+#[test]
+fn test_order_ids_are_disguised_but_stable() {
+    let access = L3Access::default();
+    let order = OrderBuilder::buy("alice", "BTC/USDC").build();
+
+    let id = access.anonymize(&order.id);
+    assert_eq!(id, access.anonymize(&order.id));
+    assert_ne!(id, order.id.to_string());
+    assert!(!id.contains(&order.id.simple().to_string()));
+
+    // Another server's key gives other IDs
+    assert_ne!(id, L3Access::default().anonymize(&order.id));
+}
+
+#[test]
+fn test_reads_are_rate_limited_per_user() {
+    let access = L3Access::new(Duration::from_secs(1));
+    let start = Instant::now();
+
+    assert!(access.admit("alice", start).is_ok());
+    assert!(access.admit("bob", start).is_ok());
+    assert!(matches!(
+        access.admit("alice", start + Duration::from_millis(400)),
+        Err(ExchangeError::RateLimited {
+            retry_after_ms: 600
+        })
+    ));
+    assert!(access
+        .admit("alice", start + Duration::from_millis(1000))
+        .is_ok());
+}
+
+// ============================================================================
+// Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_l3_endpoint_requires_approval_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let maker = TestWallet::new();
+    let db = server.db();
+    for user in [maker.address(), "trader"] {
+        db.create_user(user.to_string()).await.unwrap();
+    }
+    db.add_balance("trader", "BTC", 1_000_000).await.unwrap();
+    let resting = OrderBuilder::sell("trader", &market.id)
+        .limit(51_000_000_000)
+        .build();
+    server
+        .engine()
+        .place_order(resting.clone())
+        .await
+        .expect("Failed to rest order");
+
+    let client = reqwest::Client::new();
+    let get = |signature: String| {
+        let request = client
+            .get(server.url("/api/orderbook/BTC%2FUSDC/l3"))
+            .query(&[("user_address", maker.address()), ("signature", &signature)]);
+        async move { request.send().await.expect("Request failed") }
+    };
+    let query = L3Query {
+        user_address: maker.address().to_string(),
+        signature: String::new(),
+    };
+    let signed = || maker.signature("/api/orderbook/BTC/USDC/l3", &query);
+
+    // Unsigned or forged reads are refused before anything else
+    assert_eq!(get("sig".to_string()).await.status(), 401);
+    let forged = TestWallet::new().signature("/api/orderbook/BTC/USDC/l3", &query);
+    assert_eq!(get(forged).await.status(), 401);
+
+    assert_eq!(get(signed()).await.status(), 403);
+
+    db.approve_market_maker(maker.address()).await.unwrap();
+    let book: ApiL3Book = get(signed()).await.json().await.unwrap();
+    assert!(book.bids.is_empty());
+    assert_eq!(book.asks.len(), 1);
+    assert_eq!(book.asks[0].price, "51000000000");
+    assert_ne!(book.asks[0].id, resting.id.to_string());
+
+    // A second read inside the interval is refused
+    assert_eq!(get(signed()).await.status(), 429);
+
+    db.revoke_market_maker(maker.address()).await.unwrap();
+    assert!(!db.is_market_maker(maker.address()).await.unwrap());
+}
@@ -0,0 +1,203 @@
+use backend::analytics::leaderboard;
+use backend::models::api::{LeaderboardResponse, UserRequest};
+use backend::models::db::TraderNotionalRow;
+use backend::models::domain::{Fill, Side, Trade};
+use chrono::{Duration, Utc};
+use exchange_test_utils::{helpers, TestServer, TestWallet};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const BTC: u128 = 100_000_000;
+
+fn base_decimals() -> HashMap<String, u8> {
+    HashMap::from([("BTC/USDC".to_string(), 8)])
+}
+
+// ============================================================================
+// Ranking Tests
+// ============================================================================
+
+#[test]
+fn test_rank_orders_by_value_then_address() {
+    let values = HashMap::from([
+        ("carol".to_string(), 5),
+        ("alice".to_string(), 10),
+        ("bob".to_string(), 10),
+        ("dave".to_string(), -3),
+    ]);
+
+    assert_eq!(
+        leaderboard::rank(values, 3),
+        vec![
+            (1, "alice".to_string(), 10),
+            (2, "bob".to_string(), 10),
+            (3, "carol".to_string(), 5),
+        ]
+    );
+}
+
+#[test]
+fn test_volumes_scale_notional_into_quote_atoms() {
+    let notionals = vec![
+        TraderNotionalRow {
+            user_address: "alice".to_string(),
+            market_id: "BTC/USDC".to_string(),
+            notional: 50_000_000_000 * BTC,
+        },
+        TraderNotionalRow {
+            user_address: "alice".to_string(),
+            market_id: "DELISTED/USDC".to_string(),
+            notional: 1,
+        },
+    ];
+
+    let volumes = leaderboard::volumes(notionals, &base_decimals());
+    assert_eq!(
+        volumes,
+        HashMap::from([("alice".to_string(), 50_000_000_000)])
+    );
+}
+
+#[test]
+fn test_pnl_only_counts_sales_in_period_against_earlier_cost() {
+    let now = Utc::now();
+    let fill = |user: &str, side, price, hours_ago| Fill {
+        user_address: user.to_string(),
+        market_id: "BTC/USDC".to_string(),
+        side,
+        price,
+        size: BTC,
+        timestamp: now - Duration::hours(hours_ago),
+    };
+    let fills = vec![
+        fill("alice", Side::Buy, 50_000_000_000, 72),
+        fill("alice", Side::Sell, 60_000_000_000, 1),
+        fill("bob", Side::Sell, 50_000_000_000, 72),
+        fill("bob", Side::Buy, 60_000_000_000, 1),
+    ];
+
+    let pnl = leaderboard::realized_pnl_since(fills, now - Duration::hours(24), &base_decimals());
+    assert_eq!(
+        pnl,
+        HashMap::from([
+            ("alice".to_string(), 10_000_000_000),
+            ("bob".to_string(), 0),
+        ])
+    );
+}
+
+// ============================================================================
+// Leaderboard Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_leaderboard_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let (alice_wallet, bob_wallet) = (TestWallet::new(), TestWallet::new());
+    let (alice, bob) = (alice_wallet.address(), bob_wallet.address());
+    for user in [alice, bob, "carol"] {
+        helpers::create_user(&server.test_db, user)
+            .await
+            .expect("Failed to create user");
+    }
+
+    let now = Utc::now();
+    let trade = |hours_ago: i64, buyer: &str, seller: &str, price: u128, size: u128| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price,
+        size,
+        side: Side::Buy,
+        timestamp: now - Duration::hours(hours_ago),
+        rfq: false,
+    };
+    server
+        .db()
+        .insert_trades_to_clickhouse(&[
+            trade(72, alice, "carol", 50_000_000_000, BTC),
+            trade(1, bob, alice, 60_000_000_000, BTC),
+            trade(1, bob, "carol", 60_000_000_000, BTC / 2),
+            // Trades with yourself don't count
+            trade(1, "carol", "carol", 60_000_000_000, 10 * BTC),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    // alice opts in with a display name, which must be unique
+    let client = reqwest::Client::new();
+    let set_name = |user: &TestWallet, name: &str| {
+        let body = json!({
+            "type": "set_display_name",
+            "user_address": user.address(),
+            "display_name": name,
+        });
+        client
+            .post(server.url("/api/user"))
+            .json(&user.sign::<UserRequest>("/api/user", body))
+            .send()
+    };
+    let response = set_name(&alice_wallet, "whale")
+        .await
+        .expect("Request failed");
+    assert_eq!(response.status(), 200);
+    let response = set_name(&bob_wallet, "WHALE")
+        .await
+        .expect("Request failed");
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "DISPLAY_NAME_TAKEN");
+    let response = set_name(&bob_wallet, "no spaces")
+        .await
+        .expect("Request failed");
+    assert_eq!(response.status(), 400);
+
+    let get = |query: &str| {
+        let url = server.url(&format!("/api/leaderboard{}", query));
+        async move {
+            reqwest::get(&url)
+                .await
+                .expect("Request failed")
+                .json::<LeaderboardResponse>()
+                .await
+                .expect("Failed to parse leaderboard")
+        }
+    };
+
+    // Volume over 24h: bob bought $90,000, alice sold $60,000, carol sold $30,000
+    let board = get("").await;
+    let ranked: Vec<_> = board
+        .entries
+        .iter()
+        .map(|e| (e.rank, e.user_address.as_deref().unwrap(), e.value.as_str()))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![
+            (1, bob, "90000000000"),
+            (2, alice, "60000000000"),
+            (3, "carol", "30000000000"),
+        ]
+    );
+    assert_eq!(board.entries[1].display_name.as_deref(), Some("whale"));
+
+    // The week includes alice's first purchase
+    let board = get("?period=7d&metric=volume&limit=1").await;
+    assert_eq!(board.entries.len(), 1);
+    assert_eq!(board.entries[0].user_address.as_deref(), Some(alice));
+    assert_eq!(board.entries[0].value, "110000000000");
+
+    // alice realized $10,000 selling in the last 24h what she bought three days ago
+    let board = get("?period=24h&metric=pnl&hide_addresses=true").await;
+    assert_eq!(board.entries[0].value, "10000000000");
+    assert_eq!(board.entries[0].display_name.as_deref(), Some("whale"));
+    assert!(board.entries.iter().all(|e| e.user_address.is_none()));
+}
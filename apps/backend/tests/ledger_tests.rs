@@ -0,0 +1,122 @@
+use backend::engine::routing::FeeRouting;
+use backend::models::domain::{FeeRoute, LedgerEntryKind, RevenueSource, SystemAccount};
+use chrono::Utc;
+use exchange_test_utils::{OrderBuilder, TestDb, TestEngine};
+
+// ============================================================================
+// Fee Routing Tests
+// ============================================================================
+
+#[test]
+fn test_fee_routing_splits_between_system_accounts() {
+    let mut routing = FeeRouting::default();
+
+    // Without a rule everything goes to the fee collector
+    assert_eq!(routing.split(RevenueSource::TradingFees, 1_000), (1_000, 0));
+
+    routing.set(&FeeRoute {
+        source: RevenueSource::TradingFees,
+        insurance_bps: 2500,
+        updated_at: Utc::now(),
+    });
+    assert_eq!(routing.split(RevenueSource::TradingFees, 1_000), (750, 250));
+    // Rounding favours the fee collector
+    assert_eq!(routing.split(RevenueSource::TradingFees, 3), (3, 0));
+    assert_eq!(
+        routing.split(RevenueSource::Liquidations, 1_000),
+        (1_000, 0)
+    );
+}
+
+// ============================================================================
+// System Ledger Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_routed_fees_are_recorded_in_system_ledger() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    // 0.1% maker fee, 0.2% taker fee
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            10,
+            20,
+        )
+        .await
+        .expect("Failed to create market");
+
+    assert!(engine
+        .set_fee_route(RevenueSource::TradingFees, 10001)
+        .await
+        .is_err());
+    engine
+        .set_fee_route(RevenueSource::TradingFees, 5000)
+        .await
+        .expect("Failed to set fee route");
+    engine
+        .set_referral("buyer", "alice", 2500)
+        .await
+        .expect("Failed to set referral");
+
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+
+    // Half of the 2000 atom taker fee is insured; the referrer is paid from the other half
+    let insurance_btc = test_db.db.get_balance("insurance", "BTC").await.unwrap();
+    assert_eq!(insurance_btc.amount, 1_000);
+    let collector_btc = test_db.db.get_balance("system", "BTC").await.unwrap();
+    assert_eq!(collector_btc.amount, 500);
+    let insurance_usdc = test_db.db.get_balance("insurance", "USDC").await.unwrap();
+    assert_eq!(insurance_usdc.amount, 250_000);
+
+    // Every system balance is the sum of its ledger
+    for account in SystemAccount::ALL {
+        for balance in test_db
+            .db
+            .list_balances_by_user(account.address())
+            .await
+            .unwrap()
+        {
+            let entries = test_db
+                .db
+                .list_ledger_entries(account, Some(&balance.token_ticker), 100)
+                .await
+                .unwrap();
+            let total: i128 = entries.iter().map(|e| e.amount).sum();
+            assert_eq!(total, balance.amount as i128);
+        }
+    }
+
+    let collector_entries = test_db
+        .db
+        .list_ledger_entries(SystemAccount::FeeCollector, Some("BTC"), 100)
+        .await
+        .unwrap();
+    let kinds: Vec<_> = collector_entries
+        .iter()
+        .map(|e| (e.kind, e.amount))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (LedgerEntryKind::ReferralPayout, -500),
+            (LedgerEntryKind::TradingFee, 1_000),
+        ]
+    );
+    assert!(collector_entries.iter().all(|e| e.trade_id.is_some()));
+}
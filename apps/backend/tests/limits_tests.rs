@@ -0,0 +1,127 @@
+use backend::engine::limits::{check_limits, Exposure, LimitsBook};
+use backend::errors::ExchangeError;
+use backend::models::domain::{Side, UserLimits};
+use chrono::Utc;
+use exchange_test_utils::{helpers, OrderBuilder, TestDb, TestEngine};
+
+fn limits(max_position: Option<u128>, max_open_notional: Option<u128>) -> UserLimits {
+    UserLimits {
+        user_address: "trader".to_string(),
+        market_id: "BTC/USDC".to_string(),
+        max_position,
+        max_open_notional,
+        updated_at: Utc::now(),
+    }
+}
+
+fn exposure(position: i128, open_notional: u128) -> Exposure {
+    Exposure {
+        position,
+        open_notional,
+    }
+}
+
+// ============================================================================
+// Limit Check Tests
+// ============================================================================
+
+#[test]
+fn test_position_limit_rejects_orders_past_the_limit() {
+    let limits = limits(Some(100), None);
+
+    assert!(check_limits(&limits, exposure(60, 0), Side::Buy, 40, 0, true).is_ok());
+    assert!(matches!(
+        check_limits(&limits, exposure(60, 0), Side::Buy, 41, 0, true),
+        Err(ExchangeError::LimitExceeded { .. })
+    ));
+    // Short positions count against the same limit
+    assert!(matches!(
+        check_limits(&limits, exposure(-60, 0), Side::Sell, 41, 0, true),
+        Err(ExchangeError::LimitExceeded { .. })
+    ));
+}
+
+#[test]
+fn test_position_limit_allows_reducing_orders() {
+    let limits = limits(Some(100), None);
+
+    // Already over the limit, but selling moves towards flat
+    assert!(check_limits(&limits, exposure(150, 0), Side::Sell, 50, 0, true).is_ok());
+    // Flipping to a short larger than the current long is still growth
+    assert!(check_limits(&limits, exposure(150, 0), Side::Sell, 400, 0, true).is_err());
+}
+
+#[test]
+fn test_notional_limit_applies_only_to_resting_orders() {
+    let limits = limits(None, Some(1_000));
+
+    assert!(check_limits(&limits, exposure(0, 600), Side::Buy, 1, 400, true).is_ok());
+    assert!(check_limits(&limits, exposure(0, 600), Side::Buy, 1, 401, true).is_err());
+    // Market orders never rest, so they add no open notional
+    assert!(check_limits(&limits, exposure(0, 600), Side::Buy, 1, 401, false).is_ok());
+}
+
+#[test]
+fn test_limits_book_drops_cleared_limits() {
+    let mut book = LimitsBook::new(vec![limits(Some(100), None)]);
+    assert!(book.get("trader", "BTC/USDC").is_some());
+    assert!(book.get("trader", "ETH/USDC").is_none());
+
+    book.set(limits(None, None));
+    assert!(book.get("trader", "BTC/USDC").is_none());
+}
+
+// ============================================================================
+// Engine Enforcement Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_engine_rejects_orders_over_limits() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let engine = TestEngine::new(&test_db).await;
+
+    // 0.01 BTC at $50,000 rests $500 of notional
+    engine
+        .set_user_limits("buyer", &market.id, Some(1_000_000), Some(500_000_000))
+        .await
+        .expect("Failed to set limits");
+
+    let first = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    assert!(engine.place_order(first).await.is_ok());
+
+    let second = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    let result = engine.place_order(second).await;
+    assert!(
+        result.as_ref().is_err_and(|e| e.contains("exceeds limits")),
+        "Expected limit rejection, got {:?}",
+        result
+    );
+
+    // Other users are unaffected
+    let other = OrderBuilder::buy("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    assert!(engine.place_order(other).await.is_ok());
+
+    // Clearing the limits lifts the restriction
+    engine
+        .set_user_limits("buyer", &market.id, None, None)
+        .await
+        .expect("Failed to clear limits");
+    let third = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    assert!(engine.place_order(third).await.is_ok());
+}
@@ -0,0 +1,388 @@
+use backend::engine::oco::OcoBook;
+use backend::models::domain::{
+    CancelReason, EngineEvent, OcoLink, OrderStatus, OrderType, Side, Trade,
+};
+use chrono::Utc;
+use exchange_test_utils::{memory_market, usd, OrderBuilder, TestEngine, BTC};
+use std::collections::HashSet;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn link(first_order_id: Uuid, second_order_id: Uuid) -> OcoLink {
+    OcoLink {
+        id: Uuid::new_v4(),
+        user_address: "bob".to_string(),
+        first_order_id,
+        second_order_id,
+        created_at: Utc::now(),
+    }
+}
+
+fn trade(buyer_order_id: Uuid, seller_order_id: Uuid) -> Trade {
+    Trade {
+        id: Uuid::new_v4(),
+        market_id: "BTC/USDC".to_string(),
+        buyer_address: "alice".to_string(),
+        seller_address: "bob".to_string(),
+        buyer_order_id,
+        seller_order_id,
+        price: usd(50_000),
+        size: BTC,
+        side: Side::Buy,
+        timestamp: Utc::now(),
+        rfq: false,
+    }
+}
+
+/// Wait for the engine to broadcast the cancellation of `order_id`
+async fn wait_for_cancel(engine: &mut TestEngine, order_id: Uuid) -> Option<CancelReason> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(EngineEvent::OrderCancelled {
+                order_id: cancelled,
+                reason,
+                ..
+            }) = engine.event_rx.recv().await
+            {
+                if cancelled == order_id {
+                    return reason;
+                }
+            }
+        }
+    })
+    .await
+    .expect("order was never cancelled")
+}
+
+// ============================================================================
+// OCO Book Tests
+// ============================================================================
+
+#[test]
+fn test_oco_book_queues_the_other_order_once() {
+    let (first, second, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let mut book = OcoBook::new(vec![link(first, second)]);
+    assert_eq!(book.len(), 1);
+    assert_eq!(book.partner(first), Some(second));
+    assert_eq!(book.partner(second), Some(first));
+
+    // Trades between unlinked orders change nothing
+    book.record_trade(&trade(other, Uuid::new_v4()));
+    assert!(book.take_filled().is_empty());
+
+    // The first fill unlinks the pair; later fills of either order don't
+    // queue anything again
+    book.record_trade(&trade(other, second));
+    book.record_trade(&trade(other, second));
+    book.record_trade(&trade(first, other));
+    assert_eq!(book.take_filled(), vec![(first, "bob".to_string())]);
+    assert!(book.take_filled().is_empty());
+    assert_eq!(book.partner(first), None);
+    assert!(book.is_empty());
+}
+
+#[test]
+fn test_oco_book_unlinks_cancelled_orders() {
+    let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+    let mut book = OcoBook::new(vec![link(first, second)]);
+
+    // Cancelling either order leaves the other on its own
+    assert_eq!(book.unlink(first), Some(second));
+    assert_eq!(book.partner(second), None);
+    assert_eq!(book.unlink(second), None);
+    assert!(book.is_empty());
+
+    // An order queued because its stop triggered shares the balance with it
+    // until it is cancelled
+    book.link(&link(first, second));
+    book.record_trigger(second);
+    assert_eq!(book.take_filled(), vec![(first, "bob".to_string())]);
+    assert_eq!(book.unlink(first), Some(second));
+    assert_eq!(book.unlink(first), None);
+    assert_eq!(book.unlink(second), None);
+}
+
+// ============================================================================
+// Engine OCO Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_fill_cancels_the_other_order() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+
+    // Bob takes profit at 55,000 or stops out below 45,000, never both
+    let take_profit = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(usd(55_000))
+        .size(BTC)
+        .build();
+    let stop_loss = OrderBuilder::sell("bob", "BTC/USDC")
+        .stop_limit(usd(45_000), usd(44_900))
+        .size(BTC)
+        .build();
+    let placed = engine
+        .place_oco(take_profit.clone(), stop_loss.clone())
+        .await
+        .unwrap();
+    assert_eq!(placed.first.status, OrderStatus::Pending);
+    assert_eq!(placed.second.order_type, OrderType::StopLimit);
+    assert_eq!(db.list_open_oco_links().await.unwrap().len(), 1);
+    // Only one order can fill, so the pair locks bob's size once
+    assert_eq!(
+        db.get_balance("bob", "BTC").await.unwrap().open_interest,
+        BTC
+    );
+
+    let bought = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(55_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(bought.trades.len(), 1);
+
+    assert_eq!(
+        wait_for_cancel(&mut engine, stop_loss.id).await,
+        Some(CancelReason::LinkedOrderFilled)
+    );
+
+    let stored = db.get_order(&stop_loss.id).await.unwrap();
+    assert_eq!(stored.status, OrderStatus::Cancelled);
+    assert_eq!(stored.cancel_reason, Some(CancelReason::LinkedOrderFilled));
+    assert_eq!(
+        db.get_order(&take_profit.id).await.unwrap().status,
+        OrderStatus::Filled
+    );
+    let bob_btc = db.get_balance("bob", "BTC").await.unwrap();
+    assert_eq!((bob_btc.amount, bob_btc.open_interest), (9 * BTC, 0));
+    assert!(db.list_open_oco_links().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_pairs_that_cannot_wait_are_refused() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let stop_loss = || {
+        OrderBuilder::sell("bob", "BTC/USDC")
+            .stop_market(usd(45_000))
+            .size(BTC)
+            .build()
+    };
+
+    // A limit order crossing alice's bid would fill before it is linked
+    let crossing = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(usd(49_000))
+        .size(BTC)
+        .build();
+    let stop = stop_loss();
+    let mut refused = HashSet::from([crossing.id, stop.id]);
+    let err = engine.place_oco(crossing, stop).await.unwrap_err();
+    assert!(err.contains("would fill on arrival"), "{}", err);
+    // Both orders of the pair are reported rejected, like a single order is
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !refused.is_empty() {
+            if let Ok(EngineEvent::OrderRejected {
+                order_id,
+                user_address,
+                ..
+            }) = engine.event_rx.recv().await
+            {
+                assert_eq!(user_address, "bob");
+                refused.remove(&order_id);
+            }
+        }
+    })
+    .await
+    .expect("pair was never reported rejected");
+
+    // Market and immediate-or-cancel orders never wait
+    let market = OrderBuilder::sell("bob", "BTC/USDC")
+        .market()
+        .size(BTC)
+        .build();
+    assert!(engine.place_oco(stop_loss(), market).await.is_err());
+    let ioc = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(usd(55_000))
+        .size(BTC)
+        .ioc()
+        .build();
+    assert!(engine.place_oco(ioc, stop_loss()).await.is_err());
+
+    // Nothing was placed or locked
+    assert!(db.count_open_orders("bob", None).await.unwrap().is_empty());
+    assert_eq!(db.get_balance("bob", "BTC").await.unwrap().open_interest, 0);
+    assert!(db.list_open_oco_links().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_triggered_stop_cancels_the_limit_order() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+
+    let take_profit = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(usd(55_000))
+        .size(BTC)
+        .build();
+    let stop_loss = OrderBuilder::sell("bob", "BTC/USDC")
+        .stop_limit(usd(45_000), usd(44_900))
+        .size(BTC)
+        .build();
+    engine
+        .place_oco(take_profit.clone(), stop_loss.clone())
+        .await
+        .unwrap();
+
+    // A trade at 45,000 between others triggers the stop, which takes the
+    // take profit down with it and rests at 44,900 on the empty book
+    engine
+        .place_order(
+            OrderBuilder::sell("carol", "BTC/USDC")
+                .limit(usd(45_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(45_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        wait_for_cancel(&mut engine, take_profit.id).await,
+        Some(CancelReason::LinkedOrderFilled)
+    );
+
+    let stop = db.get_order(&stop_loss.id).await.unwrap();
+    assert_eq!(stop.order_type, OrderType::Limit);
+    assert_eq!(stop.status, OrderStatus::Pending);
+    // The stop keeps the size the pair locked
+    assert_eq!(
+        db.get_balance("bob", "BTC").await.unwrap().open_interest,
+        BTC
+    );
+    assert!(db.list_open_oco_links().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cancelling_one_order_keeps_the_others_balance() {
+    let db = memory_market(&["bob"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    let take_profit = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(usd(55_000))
+        .size(BTC)
+        .build();
+    let stop_loss = OrderBuilder::sell("bob", "BTC/USDC")
+        .stop_market(usd(45_000))
+        .size(BTC)
+        .build();
+    engine
+        .place_oco(take_profit.clone(), stop_loss.clone())
+        .await
+        .unwrap();
+    let open_interest = || async { db.get_balance("bob", "BTC").await.unwrap().open_interest };
+    assert_eq!(open_interest().await, BTC);
+
+    // Cancelling the take profit leaves the stop what it needs
+    engine
+        .cancel_order(take_profit.id, "bob".to_string())
+        .await
+        .unwrap();
+    assert_eq!(open_interest().await, BTC);
+
+    engine
+        .cancel_order(stop_loss.id, "bob".to_string())
+        .await
+        .unwrap();
+    assert_eq!(open_interest().await, 0);
+}
+
+#[tokio::test]
+async fn test_refused_pair_places_nothing() {
+    let db = memory_market(&["bob"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+    let pair = |size: u128| {
+        (
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(55_000))
+                .size(size)
+                .build(),
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .stop_market(usd(45_000))
+                .size(size)
+                .build(),
+        )
+    };
+
+    // Two limit orders could both fill, overdrawing the balance they share
+    let (first, _) = pair(BTC);
+    let (second, _) = pair(BTC);
+    let err = engine.place_oco(first, second).await.unwrap_err();
+    assert!(
+        err.contains("one limit order and one stop order"),
+        "{}",
+        err
+    );
+
+    // Bob's 10 BTC cover one pair of 6 BTC orders, not two
+    let (take_profit, stop_loss) = pair(6 * BTC);
+    engine.place_oco(take_profit, stop_loss).await.unwrap();
+    assert_eq!(
+        db.get_balance("bob", "BTC").await.unwrap().open_interest,
+        6 * BTC
+    );
+    let (take_profit, stop_loss) = pair(6 * BTC);
+    let refused_ids = HashSet::from([take_profit.id, stop_loss.id]);
+    assert!(engine
+        .place_oco(take_profit.clone(), stop_loss.clone())
+        .await
+        .is_err());
+
+    // The refused pair is only ever reported rejected
+    let mut rejected = HashSet::new();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while rejected != refused_ids {
+            match engine.event_rx.recv().await {
+                Ok(EngineEvent::OrderRejected { order_id, .. })
+                    if refused_ids.contains(&order_id) =>
+                {
+                    rejected.insert(order_id);
+                }
+                Ok(EngineEvent::OrderPlaced { order }) => {
+                    assert!(!refused_ids.contains(&order.id))
+                }
+                Ok(EngineEvent::OrderCancelled { order_id, .. }) => {
+                    assert!(!refused_ids.contains(&order_id))
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("pair was never reported rejected");
+
+    assert!(db.get_order(&take_profit.id).await.is_err());
+    assert!(db.get_order(&stop_loss.id).await.is_err());
+    assert_eq!(
+        db.get_balance("bob", "BTC").await.unwrap().open_interest,
+        6 * BTC
+    );
+    assert_eq!(db.list_open_oco_links().await.unwrap().len(), 1);
+}
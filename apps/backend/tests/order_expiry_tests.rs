@@ -0,0 +1,169 @@
+use backend::engine::expiry::ExpiryQueue;
+use backend::models::domain::{CancelReason, EngineEvent, OrderStatus};
+use chrono::{Duration as ChronoDuration, Utc};
+use exchange_test_utils::{memory_market, usd, OrderBuilder, TestEngine, BTC};
+use std::collections::HashSet;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Wait for the engine to broadcast that each of `order_ids` expired
+async fn wait_for_expiry(engine: &mut TestEngine, order_ids: &[Uuid]) {
+    let mut waiting: HashSet<Uuid> = order_ids.iter().copied().collect();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !waiting.is_empty() {
+            if let Ok(EngineEvent::OrderCancelled {
+                order_id,
+                reason: Some(CancelReason::Expired),
+                ..
+            }) = engine.event_rx.recv().await
+            {
+                waiting.remove(&order_id);
+            }
+        }
+    })
+    .await
+    .expect("orders never expired")
+}
+
+// ============================================================================
+// Expiry Queue Tests
+// ============================================================================
+
+#[test]
+fn test_expiry_queue_hands_back_due_orders_soonest_first() {
+    let now = Utc::now();
+    let later = OrderBuilder::sell("bob", "BTC/USDC")
+        .expires_at(now + ChronoDuration::seconds(10))
+        .build();
+    let soon = OrderBuilder::sell("bob", "BTC/USDC")
+        .expires_at(now - ChronoDuration::seconds(1))
+        .build();
+    let sooner = OrderBuilder::buy("alice", "BTC/USDC")
+        .expires_at(now - ChronoDuration::seconds(2))
+        .build();
+    let never = OrderBuilder::buy("alice", "BTC/USDC").build();
+
+    let mut queue = ExpiryQueue::new(vec![later.clone(), soon.clone(), never]);
+    queue.insert(&sooner);
+    assert_eq!(queue.len(), 3);
+
+    assert_eq!(
+        queue.take_expired(now),
+        vec![
+            (sooner.id, "alice".to_string()),
+            (soon.id, "bob".to_string())
+        ]
+    );
+    assert!(queue.take_expired(now).is_empty());
+    assert_eq!(
+        queue.take_expired(now + ChronoDuration::seconds(10)),
+        vec![(later.id, "bob".to_string())]
+    );
+    assert!(queue.is_empty());
+}
+
+// ============================================================================
+// Engine Expiry Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_expired_orders_are_cancelled_and_unlocked() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+    let expires_at = Utc::now() + ChronoDuration::milliseconds(500);
+
+    // Half of bob's ask fills before it expires
+    let ask = engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(2 * BTC)
+                .expires_at(expires_at)
+                .build(),
+        )
+        .await
+        .unwrap();
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let stop = engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .stop_limit(usd(45_000), usd(44_900))
+                .size(BTC)
+                .expires_at(expires_at)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(ask.order.expires_at, Some(expires_at));
+
+    let ask_id = ask.order.id.parse().unwrap();
+    let stop_id = stop.order.id.parse().unwrap();
+    wait_for_expiry(&mut engine, &[ask_id, stop_id]).await;
+
+    let ask = db.get_order(&ask_id).await.unwrap();
+    assert_eq!(ask.status, OrderStatus::Cancelled);
+    assert_eq!(ask.filled_size, BTC);
+    assert_eq!(ask.cancel_reason, Some(CancelReason::Expired));
+    let stop = db.get_order(&stop_id).await.unwrap();
+    assert_eq!(stop.status, OrderStatus::Cancelled);
+    assert_eq!(stop.cancel_reason, Some(CancelReason::Expired));
+
+    assert!(engine.orderbooks.read().await.snapshots()[0]
+        .asks
+        .is_empty());
+    let balance = db.get_balance("bob", "BTC").await.unwrap();
+    assert_eq!((balance.amount, balance.open_interest), (9 * BTC, 0));
+}
+
+#[tokio::test]
+async fn test_orders_that_cannot_rest_or_are_already_expired_are_refused() {
+    let db = memory_market(&["alice"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    let err = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .expires_at(Utc::now() - ChronoDuration::seconds(1))
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("not in the future"), "{}", err);
+
+    let err = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .ioc()
+                .expires_at(Utc::now() + ChronoDuration::minutes(1))
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("takes no expiry"), "{}", err);
+
+    // A good-till-date order rests like any other until it expires
+    let placed = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .expires_at(Utc::now() + ChronoDuration::minutes(1))
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(placed.order.status, OrderStatus::Pending);
+    assert_eq!(engine.orderbooks.read().await.snapshots()[0].bids.len(), 1);
+}
@@ -0,0 +1,659 @@
+use backend::engine::ladder::LadderLayout;
+use backend::engine::markets::MarketRegistry;
+use backend::engine::matcher::Matcher;
+use backend::engine::orderbook::{Orderbook, Orderbooks};
+use backend::models::domain::{OrderbookLevel, Trade};
+use exchange_test_utils::{MarketBuilder, OrderBuilder};
+use std::collections::HashMap;
+
+fn levels(levels: &[OrderbookLevel]) -> Vec<(u128, u128)> {
+    levels.iter().map(|l| (l.price, l.size)).collect()
+}
+
+// ============================================================================
+// Snapshot Maintenance Tests
+// ============================================================================
+// These run purely in memory: snapshots are built from per-level aggregates
+// kept up to date by every book mutation, never by walking the orders.
+
+#[test]
+fn test_snapshot_tracks_adds_fills_and_removals() {
+    let market = MarketBuilder::new("BTC", "USDC").build();
+    let mut book = Orderbook::new(market.id.clone());
+
+    let ask = OrderBuilder::sell("alice", &market.id)
+        .limit(50_000_000_000)
+        .size(3_000_000)
+        .build();
+    book.add_order(ask.clone());
+    book.add_order(
+        OrderBuilder::sell("bob", &market.id)
+            .limit(50_000_000_000)
+            .size(1_000_000)
+            .build(),
+    );
+    let bid = OrderBuilder::buy("carol", &market.id)
+        .limit(49_000_000_000)
+        .size(2_000_000)
+        .build();
+    book.add_order(bid.clone());
+
+    let snapshot = book.snapshot();
+    assert_eq!(levels(&snapshot.asks), vec![(50_000_000_000, 4_000_000)]);
+    assert_eq!(levels(&snapshot.bids), vec![(49_000_000_000, 2_000_000)]);
+
+    // A taker buys 1 lot from alice
+    let taker = OrderBuilder::buy("dave", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    let fill = Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "dave".to_string(),
+        seller_address: "alice".to_string(),
+        buyer_order_id: taker.id,
+        seller_order_id: ask.id,
+        price: 50_000_000_000,
+        size: 1_000_000,
+        side: taker.side,
+        timestamp: chrono::Utc::now(),
+        rfq: false,
+    };
+    book.apply_trades(&taker, &[fill], &market);
+    assert_eq!(
+        levels(&book.snapshot().asks),
+        vec![(50_000_000_000, 3_000_000)]
+    );
+
+    // Cancelling the only bid removes its level entirely
+    book.remove_order(bid.id).expect("bid should be resting");
+    book.remove_all_user_orders("alice");
+    let snapshot = book.snapshot();
+    assert!(snapshot.bids.is_empty());
+    assert_eq!(levels(&snapshot.asks), vec![(50_000_000_000, 1_000_000)]);
+}
+
+#[test]
+fn test_refresh_snapshots_rebuilds_only_changed_markets() {
+    let mut orderbooks = Orderbooks::new();
+    for market_id in ["BTC/USDC", "ETH/USDC"] {
+        orderbooks.get_or_create(market_id).add_order(
+            OrderBuilder::sell("alice", market_id)
+                .limit(50_000_000_000)
+                .size(1_000_000)
+                .build(),
+        );
+    }
+
+    let btc = orderbooks.markets().get("BTC/USDC").unwrap();
+    let eth = orderbooks.markets().get("ETH/USDC").unwrap();
+
+    let mut cache = HashMap::new();
+    orderbooks.refresh_snapshots(10, &mut cache);
+    let btc_version = cache[&btc].version;
+    let eth_taken_at = cache[&eth].timestamp;
+
+    orderbooks.get_or_create("BTC/USDC").add_order(
+        OrderBuilder::buy("bob", "BTC/USDC")
+            .limit(49_000_000_000)
+            .size(1_000_000)
+            .build(),
+    );
+    orderbooks.refresh_snapshots(10, &mut cache);
+
+    assert!(cache[&btc].version > btc_version);
+    assert_eq!(cache[&btc].bids.len(), 1);
+    // Untouched market keeps the snapshot built on the first pass
+    assert_eq!(cache[&eth].timestamp, eth_taken_at);
+}
+
+#[test]
+fn test_prune_empty_drops_only_retired_empty_books() {
+    let mut orderbooks = Orderbooks::new();
+    orderbooks.set_layout(
+        "ETH/USDC",
+        LadderLayout::Array {
+            min_price: 0,
+            max_price: 1_000_000,
+            tick_size: 1_000,
+        },
+    );
+    orderbooks.get_or_create("BTC/USDC").add_order(
+        OrderBuilder::sell("alice", "BTC/USDC")
+            .limit(50_000_000_000)
+            .build(),
+    );
+    orderbooks.get_or_create("ETH/USDC");
+    orderbooks.get_or_create("SOL/USDC");
+    assert_eq!(orderbooks.book_count(), 3);
+    assert_eq!(orderbooks.resting_order_count(), 1);
+
+    let mut cache = HashMap::new();
+    orderbooks.refresh_snapshots(10, &mut cache);
+    assert_eq!(cache.len(), 3);
+
+    // BTC still has an order and SOL isn't retired
+    let pruned = orderbooks.prune_empty(|market_id| market_id != "SOL/USDC");
+    assert_eq!(pruned, 1);
+    assert_eq!(orderbooks.book_count(), 2);
+    let eth = orderbooks.markets().get("ETH/USDC").unwrap();
+    assert!(orderbooks.get(eth).is_none());
+
+    // The snapshot cache forgets the pruned market
+    orderbooks.refresh_snapshots(10, &mut cache);
+    assert_eq!(cache.len(), 2);
+    assert!(!cache.contains_key(&eth));
+
+    // Needed again, the book comes back with its layout
+    assert_ne!(
+        orderbooks.get_or_create("ETH/USDC").layout(),
+        LadderLayout::Tree
+    );
+}
+
+#[test]
+fn test_queue_position_counts_size_ahead_at_price() {
+    let mut orderbooks = Orderbooks::new();
+    let book = orderbooks.get_or_create("BTC/USDC");
+    let first = OrderBuilder::buy("alice", "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(2_000_000)
+        .filled(500_000)
+        .build();
+    let other_price = OrderBuilder::buy("bob", "BTC/USDC")
+        .limit(49_000_000_000)
+        .size(5_000_000)
+        .build();
+    let second = OrderBuilder::buy("carol", "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    let third = OrderBuilder::buy("dave", "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(3_000_000)
+        .build();
+    for order in [&first, &other_price, &second, &third] {
+        book.add_order(order.clone());
+    }
+
+    let position = orderbooks.queue_position(third.id, "dave").unwrap();
+    assert_eq!(position.market_id, "BTC/USDC");
+    assert_eq!(position.price, 50_000_000_000);
+    assert_eq!(position.remaining, 3_000_000);
+    assert_eq!(position.orders_ahead, 2);
+    assert_eq!(position.size_ahead, 2_500_000);
+    assert_eq!(position.level_size, 5_500_000);
+
+    let position = orderbooks.queue_position(first.id, "alice").unwrap();
+    assert_eq!((position.orders_ahead, position.size_ahead), (0, 0));
+
+    // Cancelling an order ahead moves the queue up
+    orderbooks.cancel_order(first.id, "alice").unwrap();
+    let position = orderbooks.queue_position(third.id, "dave").unwrap();
+    assert_eq!((position.orders_ahead, position.size_ahead), (1, 1_000_000));
+    assert_eq!(position.level_size, 4_000_000);
+
+    // Only the owner can look an order up
+    assert!(orderbooks.queue_position(third.id, "carol").is_err());
+    assert!(orderbooks.queue_position(first.id, "alice").is_err());
+}
+
+#[test]
+fn test_resting_orders_keep_queue_order() {
+    let mut orderbooks = Orderbooks::new();
+    let book = orderbooks.get_or_create("BTC/USDC");
+    let first = OrderBuilder::buy("alice", "BTC/USDC")
+        .limit(50_000_000_000)
+        .build();
+    let ask = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(51_000_000_000)
+        .build();
+    let second = OrderBuilder::buy("carol", "BTC/USDC")
+        .limit(50_000_000_000)
+        .build();
+    for order in [&first, &ask, &second] {
+        book.add_order(order.clone());
+    }
+
+    let ids = |orders: Vec<backend::models::domain::Order>| {
+        orders.into_iter().map(|order| order.id).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        ids(orderbooks.resting_orders("BTC/USDC")),
+        [first.id, second.id, ask.id]
+    );
+    assert!(orderbooks.resting_orders("ETH/USDC").is_empty());
+}
+
+#[test]
+fn test_crossed_books_are_detected() {
+    let mut orderbooks = Orderbooks::new();
+    let btc = orderbooks.get_or_create("BTC/USDC");
+    btc.add_order(
+        OrderBuilder::buy("alice", "BTC/USDC")
+            .limit(50_000_000_000)
+            .build(),
+    );
+    btc.add_order(
+        OrderBuilder::sell("bob", "BTC/USDC")
+            .limit(51_000_000_000)
+            .build(),
+    );
+    assert_eq!(btc.crossed(), None);
+    assert!(orderbooks.crossed_books().is_empty());
+
+    // Matching never rests these together; drift between the book and storage could
+    let eth = orderbooks.get_or_create("ETH/USDC");
+    eth.add_order(OrderBuilder::buy("alice", "ETH/USDC").limit(3_010).build());
+    eth.add_order(OrderBuilder::sell("bob", "ETH/USDC").limit(3_000).build());
+    assert_eq!(eth.crossed(), Some((3_010, 3_000)));
+    assert_eq!(
+        orderbooks.crossed_books(),
+        vec![("ETH/USDC".to_string(), 3_010, 3_000)]
+    );
+
+    // A bid touching the ask counts as crossed too
+    let sol = orderbooks.get_or_create("SOL/USDC");
+    sol.add_order(OrderBuilder::buy("alice", "SOL/USDC").limit(150).build());
+    sol.add_order(OrderBuilder::sell("bob", "SOL/USDC").limit(150).build());
+    assert_eq!(sol.crossed(), Some((150, 150)));
+    assert_eq!(orderbooks.crossed_books().len(), 2);
+
+    orderbooks.cancel_market_orders(Some("ETH/USDC"));
+    assert_eq!(orderbooks.crossed_books()[0].0, "SOL/USDC");
+}
+
+#[test]
+fn test_market_registry_interns_symbols_once() {
+    let markets = MarketRegistry::new();
+    let btc = markets.intern("BTC/USDC");
+    let eth = markets.intern("ETH/USDC");
+
+    assert_ne!(btc, eth);
+    assert_eq!(markets.intern("BTC/USDC"), btc);
+    assert_eq!(markets.get("ETH/USDC"), Some(eth));
+    assert_eq!(markets.get("SOL/USDC"), None);
+    assert_eq!(markets.symbol(btc).as_deref(), Some("BTC/USDC"));
+    assert_eq!(markets.len(), 2);
+
+    // Clones share the table
+    let orderbooks = Orderbooks::with_registry(markets.clone());
+    orderbooks.markets().intern("SOL/USDC");
+    assert!(markets.get("SOL/USDC").is_some());
+}
+
+#[test]
+fn test_snapshot_top_n_keeps_best_levels() {
+    let mut orderbook = Orderbook::new("BTC/USDC".to_string());
+    for i in 0..5u128 {
+        orderbook.add_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(49_000_000_000 - i * 1_000)
+                .size(1_000_000)
+                .build(),
+        );
+        orderbook.add_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(51_000_000_000 + i * 1_000)
+                .size(1_000_000)
+                .build(),
+        );
+    }
+
+    let top = orderbook.snapshot_top_n(2);
+    let full = orderbook.snapshot();
+
+    assert_eq!(levels(&top.bids), levels(&full.bids[..2]));
+    assert_eq!(levels(&top.asks), levels(&full.asks[..2]));
+    assert_eq!(top.bids[0].price, 49_000_000_000);
+    assert_eq!(top.asks[0].price, 51_000_000_000);
+    assert_eq!(top.version, full.version);
+}
+
+#[test]
+fn test_open_order_counts_follow_resting_orders() {
+    let market = MarketBuilder::new("BTC", "USDC").build();
+    let mut book = Orderbook::new(market.id.clone());
+
+    let ask = OrderBuilder::sell("alice", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    book.add_order(ask.clone());
+    let partial = OrderBuilder::sell("alice", &market.id)
+        .limit(51_000_000_000)
+        .size(2_000_000)
+        .build();
+    book.add_order(partial.clone());
+    assert_eq!(book.open_order_count("alice"), 2);
+    assert_eq!(book.open_order_count("bob"), 0);
+
+    // Fully filling an order frees its slot, partial fills keep it resting
+    let taker = OrderBuilder::buy("bob", &market.id)
+        .limit(51_000_000_000)
+        .size(2_000_000)
+        .build();
+    let fill = |maker: &backend::models::domain::Order, size| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "bob".to_string(),
+        seller_address: "alice".to_string(),
+        buyer_order_id: taker.id,
+        seller_order_id: maker.id,
+        price: maker.price,
+        size,
+        side: taker.side,
+        timestamp: chrono::Utc::now(),
+        rfq: false,
+    };
+    book.apply_trades(
+        &taker,
+        &[fill(&ask, 1_000_000), fill(&partial, 1_000_000)],
+        &market,
+    );
+    assert_eq!(book.open_order_count("alice"), 1);
+    assert_eq!(book.open_order_count("bob"), 0);
+
+    book.remove_order(partial.id)
+        .expect("order should be resting");
+    assert_eq!(book.open_order_count("alice"), 0);
+}
+
+#[test]
+fn test_market_order_remainder_never_rests() {
+    let market = MarketBuilder::new("BTC", "USDC").build();
+    let mut book = Orderbook::new(market.id.clone());
+    let ask = OrderBuilder::sell("alice", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    book.add_order(ask.clone());
+
+    // A market buy for 3 lots takes the only lot; the other 2 are cancelled, not rested
+    let taker = OrderBuilder::buy("bob", &market.id)
+        .market()
+        .price(50_000_000_000)
+        .size(3_000_000)
+        .build();
+    let matches = Matcher::match_order(&taker, &book);
+    assert_eq!(matches.len(), 1);
+    let fill = Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "bob".to_string(),
+        seller_address: "alice".to_string(),
+        buyer_order_id: taker.id,
+        seller_order_id: ask.id,
+        price: 50_000_000_000,
+        size: 1_000_000,
+        side: taker.side,
+        timestamp: chrono::Utc::now(),
+        rfq: false,
+    };
+    book.apply_trades(&taker, &[fill], &market);
+
+    assert!(book.is_empty());
+    assert_eq!(book.open_order_count("bob"), 0);
+    assert!(book.snapshot().bids.is_empty());
+}
+
+#[test]
+fn test_ioc_limit_remainder_never_rests() {
+    let market = MarketBuilder::new("BTC", "USDC").build();
+    let mut book = Orderbook::new(market.id.clone());
+
+    // With nothing to match, an IOC bid is dropped while a plain one rests
+    let ioc = OrderBuilder::buy("bob", &market.id)
+        .limit(50_000_000_000)
+        .ioc()
+        .build();
+    assert!(Matcher::match_order(&ioc, &book).is_empty());
+    book.apply_trades(&ioc, &[], &market);
+    assert!(book.is_empty());
+
+    let gtc = OrderBuilder::buy("bob", &market.id)
+        .limit(50_000_000_000)
+        .build();
+    book.apply_trades(&gtc, &[], &market);
+    assert_eq!(book.open_order_count("bob"), 1);
+    assert_eq!(
+        levels(&book.snapshot().bids),
+        vec![(50_000_000_000, 1_000_000)]
+    );
+}
+
+#[test]
+fn test_available_liquidity_counts_acceptable_prices_only() {
+    let market = MarketBuilder::new("BTC", "USDC").build();
+    let mut book = Orderbook::new(market.id.clone());
+    for (user, price) in [
+        ("alice", 50_000_000_000),
+        ("bob", 50_000_000_000),
+        ("alice", 51_000_000_000),
+    ] {
+        book.add_order(
+            OrderBuilder::sell(user, &market.id)
+                .limit(price)
+                .size(1_000_000)
+                .build(),
+        );
+    }
+
+    // Only the asks at or below the limit count, capped at the size wanted
+    let buy = |user: &str, price: u128, size: u128| {
+        OrderBuilder::buy(user, &market.id)
+            .limit(price)
+            .size(size)
+            .fok()
+            .build()
+    };
+    assert_eq!(
+        Matcher::available_liquidity(&buy("dave", 50_000_000_000, 5_000_000), &book),
+        2_000_000
+    );
+    assert_eq!(
+        Matcher::available_liquidity(&buy("dave", 51_000_000_000, 5_000_000), &book),
+        3_000_000
+    );
+    assert_eq!(
+        Matcher::available_liquidity(&buy("dave", 51_000_000_000, 1_500_000), &book),
+        1_500_000
+    );
+    // Like matching, a user's own orders don't count
+    assert_eq!(
+        Matcher::available_liquidity(&buy("alice", 51_000_000_000, 5_000_000), &book),
+        1_000_000
+    );
+    let sell = OrderBuilder::sell("dave", &market.id)
+        .market()
+        .fok()
+        .build();
+    assert_eq!(Matcher::available_liquidity(&sell, &book), 0);
+}
+
+#[test]
+fn test_crosses_own_order_counts_size_reaching_own_orders_only() {
+    let market = MarketBuilder::new("BTC", "USDC").build();
+    let mut book = Orderbook::new(market.id.clone());
+    for (user, price) in [("bob", 50_000_000_000), ("alice", 51_000_000_000)] {
+        book.add_order(
+            OrderBuilder::sell(user, &market.id)
+                .limit(price)
+                .size(1_000_000)
+                .build(),
+        );
+    }
+
+    let buy = |user: &str, price: u128, size: u128| {
+        OrderBuilder::buy(user, &market.id)
+            .limit(price)
+            .size(size)
+            .build()
+    };
+    assert!(!Matcher::crosses_own_order(
+        &buy("alice", 50_500_000_000, 2_000_000),
+        &book
+    ));
+    assert!(Matcher::crosses_own_order(
+        &buy("alice", 51_000_000_000, 2_000_000),
+        &book
+    ));
+    // Bob's ask ahead of alice's fills her before she reaches her own
+    assert!(!Matcher::crosses_own_order(
+        &buy("alice", 51_000_000_000, 1_000_000),
+        &book
+    ));
+    assert!(!Matcher::crosses_own_order(
+        &buy("bob", 49_000_000_000, 2_000_000),
+        &book
+    ));
+    assert!(!Matcher::crosses_own_order(
+        &buy("carol", 52_000_000_000, 2_000_000),
+        &book
+    ));
+    // A sell only reaches bids, and alice has none
+    let sell = OrderBuilder::sell("alice", &market.id).market().build();
+    assert!(!Matcher::crosses_own_order(&sell, &book));
+}
+
+// ============================================================================
+// Price Ladder Tests
+// ============================================================================
+
+const BP_LADDER: LadderLayout = LadderLayout::Array {
+    min_price: 0,
+    max_price: 1_000_000,
+    tick_size: 1_000,
+};
+
+#[test]
+fn test_array_ladder_accepts_only_prices_on_its_ticks() {
+    assert!(BP_LADDER.accepts_price(0));
+    assert!(BP_LADDER.accepts_price(999_000));
+    assert!(BP_LADDER.accepts_price(1_000_000));
+    assert!(!BP_LADDER.accepts_price(1_001_000));
+    assert!(!BP_LADDER.accepts_price(500_500));
+    assert!(LadderLayout::Tree.accepts_price(u128::MAX));
+}
+
+#[test]
+fn test_array_ladder_matches_like_tree_ladder() {
+    let market = MarketBuilder::new("BP", "USDC")
+        .tick_size(1_000)
+        .lot_size(1_000_000)
+        .min_size(1_000_000)
+        .build();
+    let mut tree = Orderbook::new(market.id.clone());
+    let mut array = Orderbook::with_layout(market.id.clone(), BP_LADDER);
+
+    for (i, price) in [600_000, 550_000, 550_000, 700_000].into_iter().enumerate() {
+        let ask = OrderBuilder::sell(&format!("maker{}", i), &market.id)
+            .limit(price)
+            .size(2_000_000)
+            .build();
+        tree.add_order(ask.clone());
+        array.add_order(ask);
+    }
+    let bid = OrderBuilder::buy("bidder", &market.id)
+        .limit(400_000)
+        .size(1_000_000)
+        .build();
+    tree.add_order(bid.clone());
+    array.add_order(bid);
+
+    // Sweeps both 0.55 asks and part of the 0.60 one
+    let taker = OrderBuilder::buy("taker", &market.id)
+        .limit(650_000)
+        .size(5_000_000)
+        .build();
+    let tree_matches = Matcher::match_order(&taker, &tree);
+    let array_matches = Matcher::match_order(&taker, &array);
+
+    let fills = |matches: &[backend::models::domain::Match]| -> Vec<(u128, u128, uuid::Uuid)> {
+        matches
+            .iter()
+            .map(|m| (m.price, m.size, m.maker_order.id))
+            .collect()
+    };
+    assert_eq!(fills(&tree_matches), fills(&array_matches));
+    assert_eq!(
+        fills(&array_matches)
+            .iter()
+            .map(|(price, size, _)| (*price, *size))
+            .collect::<Vec<_>>(),
+        vec![
+            (550_000, 2_000_000),
+            (550_000, 2_000_000),
+            (600_000, 1_000_000)
+        ]
+    );
+
+    let trades: Vec<Trade> = array_matches
+        .iter()
+        .map(|m| Trade {
+            id: uuid::Uuid::new_v4(),
+            market_id: market.id.clone(),
+            buyer_address: taker.user_address.clone(),
+            seller_address: m.maker_order.user_address.clone(),
+            buyer_order_id: taker.id,
+            seller_order_id: m.maker_order.id,
+            price: m.price,
+            size: m.size,
+            side: taker.side,
+            timestamp: chrono::Utc::now(),
+            rfq: false,
+        })
+        .collect();
+    tree.apply_trades(&taker, &trades, &market);
+    array.apply_trades(&taker, &trades, &market);
+
+    let (tree, array) = (tree.snapshot(), array.snapshot());
+    assert_eq!(levels(&tree.asks), levels(&array.asks));
+    assert_eq!(levels(&tree.bids), levels(&array.bids));
+    assert_eq!(
+        levels(&array.asks),
+        vec![(600_000, 1_000_000), (700_000, 2_000_000)]
+    );
+    assert_eq!(levels(&array.bids), vec![(400_000, 1_000_000)]);
+}
+
+#[test]
+fn test_orderbooks_use_configured_layout() {
+    let mut orderbooks = Orderbooks::new();
+    orderbooks.set_layout("BP/USDC", BP_LADDER);
+
+    assert_eq!(orderbooks.get_or_create("BP/USDC").layout(), BP_LADDER);
+    assert_eq!(
+        orderbooks.get_or_create("BTC/USDC").layout(),
+        LadderLayout::Tree
+    );
+}
+
+#[test]
+fn test_open_relays_out_only_empty_books() {
+    let mut orderbooks = Orderbooks::new();
+
+    assert!(orderbooks.open("BP/USDC", BP_LADDER));
+    assert_eq!(orderbooks.get_or_create("BP/USDC").layout(), BP_LADDER);
+
+    // An empty book is rebuilt with the new layout
+    assert!(orderbooks.open("BP/USDC", LadderLayout::Tree));
+    assert_eq!(
+        orderbooks.get_or_create("BP/USDC").layout(),
+        LadderLayout::Tree
+    );
+
+    // One with resting orders keeps its own
+    let order = OrderBuilder::sell("alice", "BP/USDC")
+        .limit(500_000)
+        .size(1_000_000)
+        .build();
+    orderbooks.get_or_create("BP/USDC").add_order(order);
+    assert!(!orderbooks.open("BP/USDC", BP_LADDER));
+    assert!(orderbooks.open("BP/USDC", LadderLayout::Tree));
+    assert_eq!(
+        orderbooks.get_or_create("BP/USDC").layout(),
+        LadderLayout::Tree
+    );
+}
@@ -0,0 +1,547 @@
+use backend::models::domain::{MarginMode, Position, Side};
+use backend::perps::margin::{
+    account_health, initial_margin, liquidation_limit_price, maintenance_margin, position_health,
+    Health,
+};
+use backend::perps::{
+    apply_fill, funding_rate_ppm, mark_price, order_collateral, plan_settlement, settle_position,
+    FillEffect,
+};
+use exchange_test_utils::{OrderBuilder, TestDb, TestEngine};
+use std::collections::HashMap;
+
+fn position(user_address: &str, size: i128, price: u128, margin: u128) -> Position {
+    Position {
+        user_address: user_address.to_string(),
+        market_id: "BTC-PERP/USDC".to_string(),
+        size,
+        entry_price: price,
+        margin,
+        cost: size * price as i128,
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Position Accounting Tests
+// ============================================================================
+
+#[test]
+fn test_order_collateral_reserves_fees() {
+    // 1 BTC at 50,000 USDC with a 0.2% fee reserve
+    assert_eq!(
+        order_collateral(50_000_000_000, 100_000_000, 100_000_000, 10_000, 20),
+        Some(50_100_000_000)
+    );
+    // At 10x only a tenth of the notional is margin, but fees are on all of it
+    assert_eq!(
+        order_collateral(50_000_000_000, 100_000_000, 100_000_000, 1_000, 20),
+        Some(5_100_000_000)
+    );
+    // Rebates do not reduce collateral
+    assert_eq!(order_collateral(100, 10, 1, 10_000, -5), Some(1_000));
+    assert_eq!(order_collateral(u128::MAX, 2, 1, 10_000, 0), None);
+}
+
+#[test]
+fn test_apply_fill_opens_grows_closes_and_flips() {
+    let mut long = Position::default();
+
+    let effect = apply_fill(&mut long, Side::Buy, 2, 100, 100, 1, 10_000).unwrap();
+    assert_eq!(effect.margin_added, 200);
+    assert_eq!((long.size, long.entry_price, long.margin), (2, 100, 200));
+
+    // Growing averages the entry price
+    apply_fill(&mut long, Side::Buy, 2, 110, 110, 1, 10_000).unwrap();
+    assert_eq!((long.size, long.entry_price, long.margin), (4, 105, 420));
+
+    // Partial close releases margin pro rata and keeps the entry price
+    let effect = apply_fill(&mut long, Side::Sell, 1, 120, 120, 1, 10_000).unwrap();
+    assert_eq!(
+        effect,
+        FillEffect {
+            margin_added: 0,
+            margin_released: 105
+        }
+    );
+    assert_eq!((long.size, long.entry_price, long.margin), (3, 105, 315));
+    assert_eq!(long.cost, 300);
+
+    // Flipping closes the long and opens a short at the fill price
+    let effect = apply_fill(&mut long, Side::Sell, 5, 90, 95, 1, 10_000).unwrap();
+    assert_eq!(
+        effect,
+        FillEffect {
+            margin_added: 190,
+            margin_released: 315
+        }
+    );
+    assert_eq!((long.size, long.entry_price, long.margin), (-2, 90, 190));
+    assert_eq!(long.cost, -150);
+
+    // Closing completely clears the entry price and margin
+    apply_fill(&mut long, Side::Buy, 2, 80, 80, 1, 10_000).unwrap();
+    assert_eq!((long.size, long.entry_price, long.margin), (0, 0, 0));
+}
+
+#[test]
+fn test_apply_fill_keeps_initial_margin_of_leveraged_orders() {
+    let mut position = Position::default();
+
+    // 5x: a fifth of the order's notional backs the position
+    let effect = apply_fill(&mut position, Side::Buy, 10, 100, 100, 1, 2_000).unwrap();
+    assert_eq!(effect.margin_added, 200);
+    assert_eq!(position.margin, 200);
+
+    // Closing half releases half of it
+    let effect = apply_fill(&mut position, Side::Sell, 5, 90, 90, 1, 2_000).unwrap();
+    assert_eq!(effect.margin_released, 100);
+    assert_eq!(position.margin, 100);
+}
+
+// ============================================================================
+// Margin Tests
+// ============================================================================
+
+#[test]
+fn test_initial_and_maintenance_margin() {
+    // 1 BTC at 50,000 USDC at 10% initial margin
+    assert_eq!(
+        initial_margin(50_000_000_000, 100_000_000, 100_000_000, 1_000),
+        Some(5_000_000_000)
+    );
+    // Initial margin rounds down, maintenance margin up
+    assert_eq!(initial_margin(333, 1, 1, 100), Some(3));
+    let long = position("alice", 1, 333, 333);
+    assert_eq!(maintenance_margin(&long, 333, 1, 100), Some(4));
+    // Maintenance follows the mark, and shorts count their absolute size
+    let short = position("bob", -2, 100, 20);
+    assert_eq!(maintenance_margin(&short, 150, 1, 500), Some(15));
+    assert_eq!(initial_margin(u128::MAX, 2, 1, 1), None);
+}
+
+#[test]
+fn test_position_health_counts_unrealized_pnl() {
+    // 10 long at 100 with 100 of margin: 10x
+    let long = position("alice", 10, 100, 100);
+
+    let health = position_health(&long, 100, 1, 500).unwrap();
+    assert_eq!(
+        health,
+        Health {
+            equity: 100,
+            maintenance_margin: 50
+        }
+    );
+    assert!(!health.is_liquidatable());
+
+    // 5% down eats half the margin, just at maintenance
+    let health = position_health(&long, 95, 1, 500).unwrap();
+    assert_eq!((health.equity, health.maintenance_margin), (50, 48));
+    assert!(!health.is_liquidatable());
+
+    // 6% down leaves 40 against 47
+    let health = position_health(&long, 94, 1, 500).unwrap();
+    assert_eq!((health.equity, health.maintenance_margin), (40, 47));
+    assert!(health.is_liquidatable());
+
+    // The same move is a gain for a short
+    let short = position("bob", -10, 100, 100);
+    let health = position_health(&short, 94, 1, 500).unwrap();
+    assert_eq!(health.equity, 160);
+    assert!(!health.is_liquidatable());
+
+    // A closed position has nothing to maintain
+    assert!(!Health::default().is_liquidatable());
+}
+
+#[test]
+fn test_account_health_pools_free_balance_and_positions() {
+    let losing = position_health(&position("alice", 10, 100, 100), 90, 1, 500).unwrap();
+    let winning = position_health(&position("alice", -1, 200, 20), 190, 1, 500).unwrap();
+    assert!(losing.is_liquidatable());
+
+    // The other position's gain and the free balance keep the account healthy
+    let account = account_health(25, [losing, winning]).unwrap();
+    assert_eq!(
+        account,
+        Health {
+            equity: 55,
+            maintenance_margin: 55
+        }
+    );
+    assert!(!account.is_liquidatable());
+
+    // Without the free balance it is not enough
+    assert!(account_health(0, [losing, winning])
+        .unwrap()
+        .is_liquidatable());
+}
+
+#[test]
+fn test_liquidation_limit_price_rounds_outwards_to_the_tick() {
+    let long = position("alice", 10, 100_000, 100_000);
+    let short = position("bob", -10, 100_000, 100_000);
+
+    // 5% band around a mark of 100,003, on a tick of 10
+    assert_eq!(
+        liquidation_limit_price(&long, 100_003, 500, 10),
+        Some(95_000)
+    );
+    assert_eq!(
+        liquidation_limit_price(&short, 100_003, 500, 10),
+        Some(105_010)
+    );
+    // A long is never sold below one tick
+    assert_eq!(liquidation_limit_price(&long, 5, 10_000, 10), Some(10));
+}
+
+// ============================================================================
+// Mark Price and Funding Rate Tests
+// ============================================================================
+
+#[test]
+fn test_mark_price_is_median_of_index_and_book() {
+    assert_eq!(mark_price(100, Some(90), Some(120)), 100);
+    assert_eq!(mark_price(100, Some(105), Some(110)), 105);
+    assert_eq!(mark_price(100, Some(80), Some(95)), 95);
+    // One-sided books fall back to the index
+    assert_eq!(mark_price(100, None, Some(1)), 100);
+    assert_eq!(mark_price(100, Some(1_000), None), 100);
+}
+
+#[test]
+fn test_funding_rate_follows_premium_and_is_capped() {
+    assert_eq!(funding_rate_ppm(100_050, 100_000, 7_500), 500);
+    assert_eq!(funding_rate_ppm(99_950, 100_000, 7_500), -500);
+    assert_eq!(funding_rate_ppm(101, 100, 7_500), 7_500);
+    assert_eq!(funding_rate_ppm(99, 100, 7_500), -7_500);
+    assert_eq!(funding_rate_ppm(100, 0, 7_500), 0);
+}
+
+// ============================================================================
+// Settlement Tests
+// ============================================================================
+
+#[test]
+fn test_settlement_rounding_never_pays_out_more_than_collected() {
+    let mut long = position("alice", 1, 100, 100);
+    let mut short = position("bob", -1, 100, 100);
+
+    let long_owed = settle_position(&mut long, 110, 1, 3).unwrap();
+    let short_owed = settle_position(&mut short, 110, 1, 3).unwrap();
+
+    // PnL rounds down and funding rounds up for both sides
+    assert_eq!((long_owed.pnl, long_owed.funding), (3, 1));
+    assert_eq!((short_owed.pnl, short_owed.funding), (-4, 0));
+    assert!(long_owed.owed() + short_owed.owed() <= 0);
+
+    // The unsettled remainder stays in cost for the next settlement
+    assert_eq!(long.cost, 109);
+    assert_eq!(short.cost, -112);
+}
+
+#[test]
+fn test_plan_settlement_moves_pnl_from_losers_to_winners() {
+    let positions = vec![
+        position("alice", 10, 1_000, 10_000),
+        position("bob", -10, 1_000, 10_000),
+    ];
+    let free = HashMap::from([("bob".to_string(), 400u128)]);
+
+    let plan = plan_settlement(positions, &free, 0, "USDC", 1_100, 0, 1).unwrap();
+
+    // Bob pays from his free balance first, then from margin
+    let bob = &plan.balance_changes[&("bob".to_string(), "USDC".to_string())];
+    assert_eq!((bob.debit, bob.unlock, bob.credit), (1_000, 600, 0));
+    let alice = &plan.balance_changes[&("alice".to_string(), "USDC".to_string())];
+    assert_eq!((alice.debit, alice.unlock, alice.credit), (0, 0, 1_000));
+    assert_eq!((plan.insurance, plan.shortfall, plan.haircut), (0, 0, 0));
+
+    let bob = plan
+        .positions
+        .iter()
+        .find(|p| p.user_address == "bob")
+        .unwrap();
+    assert_eq!((bob.margin, bob.realized_pnl), (9_400, -1_000));
+    let alice = plan
+        .positions
+        .iter()
+        .find(|p| p.user_address == "alice")
+        .unwrap();
+    assert_eq!((alice.margin, alice.realized_pnl), (10_000, 1_000));
+    assert_eq!(alice.cost, 11_000);
+}
+
+#[test]
+fn test_plan_settlement_takes_isolated_losses_from_margin_only() {
+    let positions = vec![
+        position("alice", 10, 1_000, 10_000),
+        position("bob", -10, 1_000, 500),
+    ];
+    // Bob is isolated, so his free balance is not offered to the settlement
+    let free = HashMap::new();
+
+    let plan = plan_settlement(positions, &free, 0, "USDC", 1_100, 0, 1).unwrap();
+
+    let bob = &plan.balance_changes[&("bob".to_string(), "USDC".to_string())];
+    assert_eq!((bob.debit, bob.unlock), (500, 500));
+    assert_eq!((plan.shortfall, plan.haircut), (500, 500));
+    let alice = &plan.balance_changes[&("alice".to_string(), "USDC".to_string())];
+    assert_eq!(alice.credit, 500);
+}
+
+#[test]
+fn test_plan_settlement_covers_shortfall_from_insurance_then_haircuts() {
+    let positions = || {
+        vec![
+            position("alice", 10, 1_000, 10_000),
+            position("bob", -10, 1_000, 1_000),
+        ]
+    };
+    let free = HashMap::from([("bob".to_string(), 500u128)]);
+
+    // Bob owes 3000 but only has 1500; the insurance fund covers the rest
+    let plan = plan_settlement(positions(), &free, 2_000, "USDC", 1_300, 0, 1).unwrap();
+    assert_eq!((plan.shortfall, plan.haircut), (1_500, 0));
+    assert_eq!(plan.insurance, -1_500);
+    let alice = &plan.balance_changes[&("alice".to_string(), "USDC".to_string())];
+    assert_eq!(alice.credit, 3_000);
+
+    // With too little insurance, the gains are cut
+    let plan = plan_settlement(positions(), &free, 1_000, "USDC", 1_300, 0, 1).unwrap();
+    assert_eq!((plan.shortfall, plan.haircut), (1_500, 500));
+    assert_eq!(plan.insurance, -1_000);
+    let alice = plan
+        .positions
+        .iter()
+        .find(|p| p.user_address == "alice")
+        .unwrap();
+    assert_eq!(alice.realized_pnl, 2_500);
+}
+
+// ============================================================================
+// Engine Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_perpetual_fills_open_positions_and_settle_funding() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create market");
+    test_db
+        .db
+        .set_perpetual_market(&market.id, 3600, 7_500, 10_000, 500, 0)
+        .await
+        .expect("Failed to make market perpetual");
+    engine
+        .open_market(&market.id)
+        .await
+        .expect("Failed to open market");
+
+    let usdc_before = test_db
+        .db
+        .get_balance("buyer", "USDC")
+        .await
+        .unwrap()
+        .amount;
+    let btc_before = test_db.db.get_balance("buyer", "BTC").await.unwrap().amount;
+
+    // Market orders are not accepted in perpetual markets
+    let market_order = OrderBuilder::buy("buyer", &market.id)
+        .market()
+        .size(100_000_000)
+        .build();
+    assert!(engine.place_order(market_order).await.is_err());
+
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(100_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(100_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+
+    // Both sides lock quote margin; no base tokens change hands
+    let positions = test_db
+        .db
+        .get_positions(&market.id, &["buyer", "seller"])
+        .await
+        .unwrap();
+    assert_eq!(positions["buyer"].size, 100_000_000);
+    assert_eq!(positions["seller"].size, -100_000_000);
+    assert_eq!(positions["buyer"].margin, 50_000_000_000);
+    let buyer_usdc = test_db.db.get_balance("buyer", "USDC").await.unwrap();
+    assert_eq!(buyer_usdc.amount, usdc_before);
+    assert_eq!(buyer_usdc.open_interest, 50_000_000_000);
+    let buyer_btc = test_db.db.get_balance("buyer", "BTC").await.unwrap();
+    assert_eq!(buyer_btc.amount, btc_before);
+
+    // Mark 2% above entry, longs pay 100 ppm funding
+    let settlement = engine
+        .settle_funding(&market.id, 51_000_000_000, 50_990_000_000, 100)
+        .await
+        .expect("Failed to settle funding");
+    assert_eq!(settlement.positions, 2);
+    assert_eq!(settlement.open_interest, 100_000_000);
+    assert_eq!((settlement.insurance, settlement.shortfall), (0, 0));
+
+    // 1000 USDC of PnL less 5.1 USDC of funding
+    let buyer_usdc = test_db.db.get_balance("buyer", "USDC").await.unwrap();
+    assert_eq!(buyer_usdc.amount, usdc_before + 994_900_000);
+    let seller_usdc = test_db.db.get_balance("seller", "USDC").await.unwrap();
+    assert_eq!(seller_usdc.amount, usdc_before - 994_900_000);
+
+    let positions = test_db
+        .db
+        .get_positions(&market.id, &["buyer", "seller"])
+        .await
+        .unwrap();
+    assert_eq!(positions["buyer"].realized_pnl, 1_000_000_000);
+    assert_eq!(positions["buyer"].funding_paid, 5_100_000);
+    assert_eq!(positions["seller"].funding_paid, -5_100_000);
+
+    // Spot markets cannot be settled
+    let spot = test_db
+        .db
+        .create_market(
+            "ETH".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create market");
+    assert!(engine.settle_funding(&spot.id, 1, 1, 0).await.is_err());
+}
+
+#[tokio::test]
+async fn test_mark_price_liquidates_positions_under_maintenance_margin() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create market");
+    // 10x leverage, liquidated under 5%, with a 1% liquidation fee
+    test_db
+        .db
+        .set_perpetual_market(&market.id, 3600, 7_500, 1_000, 500, 100)
+        .await
+        .expect("Failed to make market perpetual");
+    engine
+        .open_market(&market.id)
+        .await
+        .expect("Failed to open market");
+
+    let usdc_before = test_db
+        .db
+        .get_balance("buyer", "USDC")
+        .await
+        .unwrap()
+        .amount;
+
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(100_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(100_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+
+    // The long only locked a tenth of the notional
+    let buyer_usdc = test_db.db.get_balance("buyer", "USDC").await.unwrap();
+    assert_eq!(buyer_usdc.open_interest, 5_000_000_000);
+
+    // Positions cannot switch margin mode while open
+    assert!(engine
+        .set_margin_mode("buyer", MarginMode::Cross)
+        .await
+        .is_err());
+
+    // Liquidity for the liquidation to sell into
+    let support = OrderBuilder::buy("alice", &market.id)
+        .limit(49_000_000_000)
+        .size(100_000_000)
+        .build();
+    engine
+        .place_order(support)
+        .await
+        .expect("Failed to place bid");
+
+    // A 5% drop leaves the long just above maintenance
+    let liquidations = engine
+        .update_mark_price(&market.id, 47_500_000_000, 47_500_000_000)
+        .await
+        .expect("Failed to update mark price");
+    assert!(liquidations.is_empty());
+
+    // At 9% down its 500 USDC of equity is under the 2,275 USDC it must keep
+    let liquidations = engine
+        .update_mark_price(&market.id, 45_500_000_000, 45_500_000_000)
+        .await
+        .expect("Failed to update mark price");
+    assert_eq!(liquidations.len(), 1);
+    let liquidation = &liquidations[0];
+    assert_eq!(liquidation.user_address, "buyer");
+    assert_eq!(liquidation.size, 100_000_000);
+    assert_eq!(liquidation.closed_size, 100_000_000);
+    assert_eq!(liquidation.margin_mode, MarginMode::Isolated);
+    assert_eq!(liquidation.fee, 455_000_000);
+
+    // Sold at 49,000: a 1,000 USDC loss plus the fee, with the margin unlocked
+    let buyer_usdc = test_db.db.get_balance("buyer", "USDC").await.unwrap();
+    assert_eq!(buyer_usdc.amount, usdc_before - 1_000_000_000 - 455_000_000);
+    assert_eq!(buyer_usdc.open_interest, 0);
+
+    let positions = test_db
+        .db
+        .get_positions(&market.id, &["buyer", "seller", "alice"])
+        .await
+        .unwrap();
+    assert!(!positions.contains_key("buyer"));
+    assert_eq!(positions["alice"].size, 100_000_000);
+    assert_eq!(positions["seller"].size, -100_000_000);
+
+    // With no positions left the buyer may go cross
+    engine
+        .set_margin_mode("buyer", MarginMode::Cross)
+        .await
+        .expect("Failed to set margin mode");
+    assert_eq!(
+        test_db.db.get_margin_mode("buyer").await.unwrap(),
+        MarginMode::Cross
+    );
+}
@@ -0,0 +1,124 @@
+use backend::analytics::pnl::PositionTracker;
+use backend::models::api::UserPnlResponse;
+use backend::models::domain::{CostBasisMethod, Side, Trade};
+use exchange_test_utils::{helpers, TestServer};
+
+const BTC: u128 = 100_000_000;
+
+// ============================================================================
+// Position Tracker Tests
+// ============================================================================
+
+fn buy_two_sell_one(method: CostBasisMethod) -> PositionTracker {
+    let mut tracker = PositionTracker::new(method, 8);
+    tracker.apply(Side::Buy, 50_000_000_000, BTC);
+    tracker.apply(Side::Buy, 60_000_000_000, BTC);
+    tracker.apply(Side::Sell, 70_000_000_000, BTC);
+    tracker
+}
+
+#[test]
+fn test_fifo_closes_oldest_purchase_first() {
+    let mut tracker = buy_two_sell_one(CostBasisMethod::Fifo);
+    assert_eq!(tracker.realized_pnl(), 20_000_000_000);
+    assert_eq!(tracker.position(), BTC);
+    assert_eq!(tracker.average_entry_price(), Some(60_000_000_000));
+
+    // Selling more than is held only realizes the held part
+    tracker.apply(Side::Sell, 40_000_000_000, 2 * BTC);
+    assert_eq!(tracker.realized_pnl(), 0);
+    assert_eq!(tracker.position(), 0);
+    assert_eq!(tracker.average_entry_price(), None);
+}
+
+#[test]
+fn test_average_cost_closes_at_average_price() {
+    let mut tracker = buy_two_sell_one(CostBasisMethod::AverageCost);
+    assert_eq!(tracker.realized_pnl(), 15_000_000_000);
+    assert_eq!(tracker.position(), BTC);
+    assert_eq!(tracker.average_entry_price(), Some(55_000_000_000));
+
+    tracker.apply(Side::Sell, 40_000_000_000, 2 * BTC);
+    assert_eq!(tracker.realized_pnl(), 0);
+    assert_eq!(tracker.position(), 0);
+}
+
+#[test]
+fn test_sales_without_a_position_realize_nothing() {
+    let mut tracker = PositionTracker::new(CostBasisMethod::Fifo, 8);
+    tracker.apply(Side::Sell, 50_000_000_000, BTC);
+    assert_eq!(tracker.realized_pnl(), 0);
+    assert_eq!(tracker.position(), 0);
+}
+
+// ============================================================================
+// PnL Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_user_pnl_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    for user in ["alice", "bob"] {
+        helpers::create_user(&server.test_db, user)
+            .await
+            .expect("Failed to create user");
+    }
+
+    let now = chrono::Utc::now();
+    let trade = |minutes_ago: i64, buyer: &str, seller: &str, price: u128| Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price,
+        size: BTC,
+        side: Side::Buy,
+        timestamp: now - chrono::Duration::minutes(minutes_ago),
+        rfq: false,
+    };
+    server
+        .db()
+        .insert_trades_to_clickhouse(&[
+            trade(3, "alice", "bob", 50_000_000_000),
+            trade(2, "alice", "bob", 60_000_000_000),
+            trade(1, "bob", "alice", 70_000_000_000),
+        ])
+        .await
+        .expect("Failed to insert trades");
+
+    let url = server.url("/api/users/alice/pnl?method=average_cost");
+    let response = reqwest::get(&url).await.expect("Failed to make request");
+    assert_eq!(response.status(), 200);
+    let pnl: UserPnlResponse = response.json().await.expect("Failed to parse PnL");
+    assert_eq!(pnl.method, CostBasisMethod::AverageCost);
+    assert_eq!(pnl.markets.len(), 1);
+    assert_eq!(pnl.markets[0].market_id, "BTC/USDC");
+    assert_eq!(pnl.markets[0].realized_pnl, "15000000000");
+    assert_eq!(pnl.markets[0].position, BTC.to_string());
+    assert_eq!(
+        pnl.markets[0].average_entry_price.as_deref(),
+        Some("55000000000")
+    );
+
+    // FIFO is the default
+    let pnl: UserPnlResponse = reqwest::get(server.url("/api/users/alice/pnl"))
+        .await
+        .expect("Failed to make request")
+        .json()
+        .await
+        .expect("Failed to parse PnL");
+    assert_eq!(pnl.method, CostBasisMethod::Fifo);
+    assert_eq!(pnl.markets[0].realized_pnl, "20000000000");
+
+    let missing = reqwest::get(server.url("/api/users/nobody/pnl"))
+        .await
+        .expect("Failed to make request");
+    assert_eq!(missing.status(), 404);
+}
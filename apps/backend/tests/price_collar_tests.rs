@@ -0,0 +1,130 @@
+use backend::engine::collar::{CollarBreach, PriceCollars};
+use exchange_test_utils::{helpers, OrderBuilder, TestDb, TestEngine};
+
+// ============================================================================
+// Collar Check Tests
+// ============================================================================
+
+#[test]
+fn test_collar_rejects_prices_too_far_from_last_trade() {
+    let mut collars = PriceCollars::default();
+    collars.configure("BTC/USDC", 1000);
+
+    // No trades yet, so nothing to measure against
+    assert!(collars.check("BTC/USDC", 1).is_ok());
+
+    collars.record_trade("BTC/USDC", 50_000);
+    assert!(collars.check("BTC/USDC", 55_000).is_ok());
+    assert!(collars.check("BTC/USDC", 45_000).is_ok());
+    assert_eq!(
+        collars.check("BTC/USDC", 55_001),
+        Err(CollarBreach {
+            reference_price: 50_000,
+            collar_bps: 1000,
+        })
+    );
+    assert!(collars.check("BTC/USDC", 44_999).is_err());
+
+    // Other markets have no collar
+    collars.record_trade("ETH/USDC", 3_000);
+    assert!(collars.check("ETH/USDC", 1).is_ok());
+}
+
+#[test]
+fn test_index_price_replaces_last_trade_as_reference() {
+    let mut collars = PriceCollars::default();
+    collars.configure("BTC/USDC", 1000);
+    collars.record_trade("BTC/USDC", 50_000);
+
+    assert!(collars
+        .check_with_index("BTC/USDC", 60_000, Some(60_000))
+        .is_ok());
+    assert_eq!(
+        collars.check_with_index("BTC/USDC", 50_000, Some(60_000)),
+        Err(CollarBreach {
+            reference_price: 60_000,
+            collar_bps: 1000,
+        })
+    );
+
+    // Without an index price the last trade is the reference again
+    assert!(collars.check_with_index("BTC/USDC", 50_000, None).is_ok());
+}
+
+#[test]
+fn test_admin_override_replaces_configured_collar() {
+    let mut collars = PriceCollars::default();
+    collars.configure("BTC/USDC", 1000);
+    collars.record_trade("BTC/USDC", 50_000);
+
+    collars.set_override("BTC/USDC", Some(100));
+    assert_eq!(collars.collar_bps("BTC/USDC"), Some(100));
+    assert!(collars.check("BTC/USDC", 51_000).is_err());
+
+    // Overriding with nothing disables the configured collar
+    collars.set_override("BTC/USDC", None);
+    assert_eq!(collars.collar_bps("BTC/USDC"), None);
+    assert!(collars.check("BTC/USDC", 500_000).is_ok());
+}
+
+// ============================================================================
+// Engine Collar Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_engine_rejects_orders_outside_collar() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let engine = TestEngine::new(&test_db).await;
+
+    engine
+        .set_price_collar(&market.id, Some(500))
+        .await
+        .expect("Failed to set price collar");
+    assert!(engine.set_price_collar(&market.id, Some(0)).await.is_err());
+
+    // Trade at $50,000 to set the reference price
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+
+    // A bid 10% above the last trade is a fat finger at a 5% collar
+    let fat_finger = OrderBuilder::buy("buyer", &market.id)
+        .limit(55_000_000_000)
+        .size(1_000_000)
+        .build();
+    let result = engine.place_order(fat_finger.clone()).await;
+    assert!(
+        result
+            .as_ref()
+            .is_err_and(|e| e.contains("bps from the last trade")),
+        "Expected collar rejection, got {:?}",
+        result
+    );
+
+    let within = OrderBuilder::buy("buyer", &market.id)
+        .limit(52_000_000_000)
+        .size(1_000_000)
+        .build();
+    assert!(engine.place_order(within).await.is_ok());
+
+    // Admins can lift the collar, and the override is persisted
+    engine
+        .set_price_collar(&market.id, None)
+        .await
+        .expect("Failed to disable price collar");
+    assert!(engine.place_order(fat_finger).await.is_ok());
+    assert_eq!(
+        test_db.db.list_price_collar_overrides().await.unwrap(),
+        vec![(market.id.clone(), None)]
+    );
+}
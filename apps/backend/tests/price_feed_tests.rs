@@ -0,0 +1,174 @@
+use axum::{routing::get, routing::post, Json, Router};
+use backend::models::api::IndexPriceHistoryResponse;
+use backend::models::domain::IndexPrice;
+use backend::price_feed::sources::{parse_decimal_price, IndexSource};
+use backend::price_feed::{IndexFeed, IndexPrices, PriceFeed, INDEX_PRICE_MAX_AGE_SECS};
+use chrono::{DateTime, Duration, Utc};
+use exchange_test_utils::{helpers, TestServer};
+use serde_json::json;
+
+// ============================================================================
+// Price Parsing Tests
+// ============================================================================
+
+#[test]
+fn test_decimal_prices_convert_to_quote_atoms() {
+    assert_eq!(parse_decimal_price("90123.45", 6), Some(90_123_450_000));
+    assert_eq!(parse_decimal_price("90123", 6), Some(90_123_000_000));
+    assert_eq!(parse_decimal_price("0.5", 2), Some(50));
+
+    // Digits beyond the quote precision are truncated
+    assert_eq!(parse_decimal_price("1.23456789", 6), Some(1_234_567));
+    assert_eq!(parse_decimal_price("7.9", 0), Some(7));
+
+    for invalid in ["", ".5", "-1", "1e5", "12,5", "NaN"] {
+        assert_eq!(parse_decimal_price(invalid, 6), None, "{}", invalid);
+    }
+}
+
+// ============================================================================
+// Index Price Tests
+// ============================================================================
+
+#[test]
+fn test_only_fresh_index_prices_are_used() {
+    let now = Utc::now();
+    let prices = IndexPrices::default();
+    let price = |seconds_ago: i64, price: u128| IndexPrice {
+        market_id: "BTC/USDC".to_string(),
+        timestamp: now - Duration::seconds(seconds_ago),
+        price,
+        source: "hyperliquid".to_string(),
+    };
+
+    assert_eq!(prices.fresh("BTC/USDC", now), None);
+
+    prices.record(price(5, 50_000));
+    assert_eq!(prices.fresh("BTC/USDC", now), Some(50_000));
+
+    // A late response never replaces a newer price
+    prices.record(price(10, 49_000));
+    assert_eq!(prices.latest("BTC/USDC").unwrap().price, 50_000);
+
+    let later = now + Duration::seconds(INDEX_PRICE_MAX_AGE_SECS);
+    assert_eq!(prices.fresh("BTC/USDC", later), None);
+    assert!(prices.latest("BTC/USDC").is_some());
+}
+
+#[tokio::test]
+async fn test_feed_polls_hyperliquid_and_rest_oracles() {
+    let oracle = Router::new()
+        .route(
+            "/info",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(body["type"], "allMids");
+                Json(json!({ "BTC": "90123.456789", "ETH": "3000.1" }))
+            }),
+        )
+        .route(
+            "/oracle",
+            get(|| async { Json(json!({ "data": { "price": 1.25 } })) }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, oracle).await.unwrap() });
+
+    let mut feed = PriceFeed::new(IndexPrices::default())
+        .with_hyperliquid_url(format!("http://{}/info", addr));
+    let mut add = |market_id: &str, source| {
+        feed.add(IndexFeed {
+            market_id: market_id.to_string(),
+            source,
+            quote_decimals: 6,
+        })
+    };
+    add(
+        "BTC/USDC",
+        IndexSource::Hyperliquid {
+            coin: "BTC".to_string(),
+        },
+    );
+    add(
+        "BP/USDC",
+        IndexSource::Rest {
+            url: format!("http://{}/oracle", addr),
+            pointer: "/data/price".to_string(),
+        },
+    );
+    // Failing feeds are skipped without affecting the others
+    add(
+        "DOGE/USDC",
+        IndexSource::Hyperliquid {
+            coin: "DOGE".to_string(),
+        },
+    );
+    add(
+        "SOL/USDC",
+        IndexSource::Rest {
+            url: format!("http://{}/missing", addr),
+            pointer: "/price".to_string(),
+        },
+    );
+
+    let prices: Vec<_> = feed
+        .poll()
+        .await
+        .into_iter()
+        .map(|p| (p.market_id, p.price, p.source))
+        .collect();
+    assert_eq!(
+        prices,
+        vec![
+            (
+                "BTC/USDC".to_string(),
+                90_123_456_789,
+                "hyperliquid".to_string()
+            ),
+            ("BP/USDC".to_string(), 1_250_000, "rest".to_string()),
+        ]
+    );
+}
+
+// ============================================================================
+// Index Price History Endpoint Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_index_price_history_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let now = Utc::now().timestamp();
+    let price = |seconds_ago: i64, price: u128| IndexPrice {
+        market_id: "BTC/USDC".to_string(),
+        timestamp: DateTime::from_timestamp(now - seconds_ago, 0).unwrap(),
+        price,
+        source: "hyperliquid".to_string(),
+    };
+    server
+        .db()
+        .insert_index_prices(&[price(3600, 49_000_000_000), price(5, 50_000_000_000)])
+        .await
+        .expect("Failed to insert index prices");
+
+    let get = |from: i64, to: i64| {
+        let url = server.url(&format!(
+            "/api/markets/BTC%2FUSDC/index-prices?from={}&to={}",
+            from, to
+        ));
+        async move { reqwest::get(&url).await.expect("Request failed") }
+    };
+
+    let history: IndexPriceHistoryResponse = get(now - 60, now).await.json().await.unwrap();
+    assert_eq!(history.prices.len(), 1);
+    assert_eq!(history.prices[0].timestamp, now - 5);
+    assert_eq!(history.prices[0].price, "50000000000");
+    assert_eq!(history.prices[0].source, "hyperliquid");
+
+    assert_eq!(get(now, now - 60).await.status(), 400);
+    assert_eq!(get(now - 8 * 24 * 3600, now).await.status(), 400);
+}
@@ -1,4 +1,7 @@
-use exchange_test_utils::TestServer;
+use backend::api::rest::stats::MARKET_STATS_TTL;
+use backend::models::api::ApiMarketStats;
+use backend::models::domain::{Side, Trade};
+use exchange_test_utils::{helpers, TestServer};
 use serde_json::Value;
 
 #[tokio::test]
@@ -167,3 +170,159 @@ async fn test_database_accessible_from_api() {
     // 2. We can setup test data via direct DB access
     // 3. The server and test share the same database instance
 }
+
+#[tokio::test]
+async fn test_market_stats_endpoint_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let trade = || Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market.id.clone(),
+        buyer_address: "buyer".to_string(),
+        seller_address: "seller".to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price: 50_000_000_000,
+        size: 1_000_000,
+        side: Side::Sell,
+        timestamp: chrono::Utc::now(),
+        rfq: false,
+    };
+    server
+        .db()
+        .insert_trade_to_clickhouse(&trade())
+        .await
+        .expect("Failed to insert trade");
+
+    // Market ids contain a slash, so it is escaped in the path
+    let url = server.url("/api/markets/BTC%2FUSDC/stats");
+    let response = reqwest::get(&url).await.expect("Failed to make request");
+    assert_eq!(response.status(), 200);
+    let stats: ApiMarketStats = response.json().await.expect("Failed to parse stats");
+    assert_eq!(stats.market_id, "BTC/USDC");
+    assert_eq!(stats.trade_count, 1);
+    assert_eq!(stats.quote_volume, "500000000");
+
+    // Served from the cache until it expires
+    server
+        .db()
+        .insert_trade_to_clickhouse(&trade())
+        .await
+        .expect("Failed to insert trade");
+    let cached: ApiMarketStats = reqwest::get(&url)
+        .await
+        .expect("Failed to make request")
+        .json()
+        .await
+        .expect("Failed to parse stats");
+    assert_eq!(cached.trade_count, 1);
+
+    tokio::time::sleep(MARKET_STATS_TTL).await;
+    let refreshed: ApiMarketStats = reqwest::get(&url)
+        .await
+        .expect("Failed to make request")
+        .json()
+        .await
+        .expect("Failed to parse stats");
+    assert_eq!(refreshed.trade_count, 2);
+
+    let missing = reqwest::get(server.url("/api/markets/NOPE%2FUSDC/stats"))
+        .await
+        .expect("Failed to make request");
+    assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn test_market_aliases_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    // The test server picks up new markets every 100ms
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(server.url("/api/info"))
+        .json(&serde_json::json!({"type": "market_details", "market_id": "btc-usdc"}))
+        .send()
+        .await
+        .expect("Failed to make request");
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(body["market"]["id"], "BTC/USDC");
+    let numeric_id = body["market"]["numeric_id"]
+        .as_u64()
+        .expect("Missing numeric id");
+
+    for alias in [
+        "BTC-USDC".to_string(),
+        "btcusdc".to_string(),
+        numeric_id.to_string(),
+    ] {
+        let response = reqwest::get(server.url(&format!("/api/markets/{}/stats", alias)))
+            .await
+            .expect("Failed to make request");
+        assert_eq!(response.status(), 200, "{}", alias);
+        let stats: ApiMarketStats = response.json().await.expect("Failed to parse stats");
+        assert_eq!(stats.market_id, "BTC/USDC");
+    }
+}
+
+#[tokio::test]
+async fn test_decimal_amount_format_e2e() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    let client = reqwest::Client::new();
+    let drip = |format: &'static str, amount: &'static str| {
+        client
+            .post(server.url("/api/drip"))
+            .header("X-Amount-Format", format)
+            .json(&serde_json::json!({
+                "type": "faucet",
+                "user_address": "alice",
+                "token_ticker": "BTC",
+                "amount": amount,
+                "signature": "sig",
+            }))
+            .send()
+    };
+
+    let response = drip("decimal", "1.5")
+        .await
+        .expect("Failed to make request");
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(body["amount"], "1.5");
+    assert_eq!(body["new_balance"], "1.5");
+
+    // BTC has 8 decimals; a ninth would be lost
+    let response = drip("decimal", "0.000000001")
+        .await
+        .expect("Failed to make request");
+    assert_eq!(response.status(), 400);
+    let response = drip("hex", "1").await.expect("Failed to make request");
+    assert_eq!(response.status(), 400);
+
+    // Without the header the same balance is in atoms
+    let response = client
+        .post(server.url("/api/user"))
+        .json(&serde_json::json!({"type": "balances", "user_address": "alice"}))
+        .send()
+        .await
+        .expect("Failed to make request");
+    let body: Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(body["balances"][0]["amount"], "150000000");
+}
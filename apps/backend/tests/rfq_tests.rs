@@ -0,0 +1,332 @@
+use backend::errors::ExchangeError;
+use backend::models::domain::{Market, OrderStatus, QuoteRequest, RfqStatus, Side};
+use backend::rfq::{
+    counterparties, plan_settlement, quote_expiry, request_expiry, validate_price, validate_size,
+    DEFAULT_REQUEST_TTL_SECS, MAX_REQUEST_TTL_SECS,
+};
+use chrono::{Duration, Utc};
+use exchange_test_utils::TestDb;
+use uuid::Uuid;
+
+fn market() -> Market {
+    Market {
+        id: "BTC/USDC".to_string(),
+        base_ticker: "BTC".to_string(),
+        quote_ticker: "USDC".to_string(),
+        tick_size: 1000,
+        lot_size: 1_000_000,
+        min_size: 1_000_000,
+        maker_fee_bps: 10,
+        taker_fee_bps: 20,
+    }
+}
+
+fn quote_request(side: Side) -> QuoteRequest {
+    let now = Utc::now();
+    QuoteRequest {
+        id: Uuid::new_v4(),
+        taker_address: "taker".to_string(),
+        market_id: "BTC/USDC".to_string(),
+        side,
+        size: 100_000_000,
+        status: RfqStatus::Open,
+        trade_id: None,
+        expires_at: now + Duration::seconds(30),
+        created_at: now,
+    }
+}
+
+// ============================================================================
+// RFQ Rule Tests
+// ============================================================================
+
+#[test]
+fn test_request_expiry_bounds() {
+    let now = Utc::now();
+    assert_eq!(
+        request_expiry(now, None).unwrap(),
+        now + Duration::seconds(DEFAULT_REQUEST_TTL_SECS as i64)
+    );
+    assert_eq!(
+        request_expiry(now, Some(MAX_REQUEST_TTL_SECS)).unwrap(),
+        now + Duration::seconds(MAX_REQUEST_TTL_SECS as i64)
+    );
+    for ttl_secs in [0, MAX_REQUEST_TTL_SECS + 1] {
+        assert!(matches!(
+            request_expiry(now, Some(ttl_secs)),
+            Err(ExchangeError::InvalidParameter { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_quote_expiry_never_outlives_request() {
+    let now = Utc::now();
+    let request_expires_at = now + Duration::seconds(60);
+
+    // Without a TTL a quote stands for as long as the request
+    assert_eq!(
+        quote_expiry(now, None, request_expires_at).unwrap(),
+        request_expires_at
+    );
+    assert_eq!(
+        quote_expiry(now, Some(10), request_expires_at).unwrap(),
+        now + Duration::seconds(10)
+    );
+    assert_eq!(
+        quote_expiry(now, Some(120), request_expires_at).unwrap(),
+        request_expires_at
+    );
+    assert!(matches!(
+        quote_expiry(now, Some(0), request_expires_at),
+        Err(ExchangeError::InvalidParameter { .. })
+    ));
+}
+
+#[test]
+fn test_validate_size_and_price() {
+    let market = market();
+    assert!(validate_size(&market, 1_000_000).is_ok());
+    assert!(validate_size(&market, 25_000_000).is_ok());
+    for size in [0, 500_000, 1_500_000] {
+        assert!(matches!(
+            validate_size(&market, size),
+            Err(ExchangeError::InvalidParameter { .. })
+        ));
+    }
+
+    assert!(validate_price(&market, 50_000_000_000).is_ok());
+    for price in [0, 50_000_000_500] {
+        assert!(matches!(
+            validate_price(&market, price),
+            Err(ExchangeError::InvalidParameter { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_counterparties_follow_taker_side() {
+    let buy = quote_request(Side::Buy);
+    assert_eq!(counterparties(&buy, "maker"), ("taker", "maker"));
+    let sell = quote_request(Side::Sell);
+    assert_eq!(counterparties(&sell, "maker"), ("maker", "taker"));
+}
+
+#[test]
+fn test_plan_settlement_moves_both_legs_without_fees() {
+    let market = market();
+    // 1 BTC (8 decimals) at 50,000 USDC (6 decimals)
+    let changes =
+        plan_settlement(&market, "buyer", "seller", 50_000_000_000, 100_000_000, 8).unwrap();
+    assert_eq!(changes.len(), 4);
+
+    let buyer_quote = &changes[&("buyer".to_string(), "USDC".to_string())];
+    assert_eq!(buyer_quote.debit, 50_000_000_000);
+    assert_eq!(buyer_quote.unlock, 50_000_000_000);
+    assert_eq!(
+        changes[&("buyer".to_string(), "BTC".to_string())].credit,
+        100_000_000
+    );
+
+    let seller_base = &changes[&("seller".to_string(), "BTC".to_string())];
+    assert_eq!(seller_base.debit, 100_000_000);
+    assert_eq!(seller_base.unlock, 100_000_000);
+    assert_eq!(
+        changes[&("seller".to_string(), "USDC".to_string())].credit,
+        50_000_000_000
+    );
+
+    // Worth less than one quote atom, or too large to price
+    assert!(plan_settlement(&market, "buyer", "seller", 1, 1, 8).is_none());
+    assert!(plan_settlement(&market, "buyer", "seller", u128::MAX, 2, 8).is_none());
+}
+
+// ============================================================================
+// Database Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_request_quote_and_execute() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let db = &test_db.db;
+
+    db.create_token("BTC".to_string(), 8, "Bitcoin".to_string())
+        .await
+        .unwrap();
+    db.create_token("USDC".to_string(), 6, "USD Coin".to_string())
+        .await
+        .unwrap();
+    let market = db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1_000_000,
+            1_000_000,
+            10,
+            20,
+        )
+        .await
+        .unwrap();
+    for user in ["taker", "maker_a", "maker_b"] {
+        db.create_user(user.to_string()).await.unwrap();
+    }
+    db.add_balance("taker", "USDC", 100_000_000_000)
+        .await
+        .unwrap();
+    db.add_balance("maker_a", "BTC", 100_000_000).await.unwrap();
+    db.add_balance("maker_b", "BTC", 100_000_000).await.unwrap();
+
+    let mut request = quote_request(Side::Buy);
+    request.market_id = market.id.clone();
+    db.create_quote_request(&request).await.unwrap();
+    assert_eq!(
+        db.list_open_quote_requests(Some(&market.id))
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Only registered makers quote
+    let unregistered = db
+        .submit_quote(request.id, "maker_a", 50_000_000_000, None)
+        .await;
+    assert!(matches!(
+        unregistered,
+        Err(ExchangeError::NotRfqMaker { .. })
+    ));
+    db.register_rfq_maker("maker_a").await.unwrap();
+    db.register_rfq_maker("maker_b").await.unwrap();
+
+    let first = db
+        .submit_quote(request.id, "maker_a", 51_000_000_000, None)
+        .await
+        .unwrap();
+    // Quoting again replaces the maker's quote under a new id
+    let replaced = db
+        .submit_quote(request.id, "maker_a", 50_500_000_000, None)
+        .await
+        .unwrap();
+    assert_ne!(first.id, replaced.id);
+    let best = db
+        .submit_quote(request.id, "maker_b", 50_000_000_000, Some(10))
+        .await
+        .unwrap();
+
+    let quotes = db.list_quotes(&request).await.unwrap();
+    let prices: Vec<u128> = quotes.iter().map(|q| q.price).collect();
+    assert_eq!(prices, [50_000_000_000, 50_500_000_000]);
+
+    let stale = db.execute_rfq(request.id, first.id, "taker").await;
+    assert!(matches!(stale, Err(ExchangeError::QuoteNotFound { .. })));
+    let not_taker = db.execute_rfq(request.id, best.id, "maker_a").await;
+    assert!(matches!(
+        not_taker,
+        Err(ExchangeError::RfqRequestNotFound { .. })
+    ));
+
+    let execution = db.execute_rfq(request.id, best.id, "taker").await.unwrap();
+    assert_eq!(execution.request.status, RfqStatus::Filled);
+    assert_eq!(execution.request.trade_id, Some(execution.trade.id));
+    assert!(execution.trade.rfq);
+    assert_eq!(execution.trade.buyer_address, "taker");
+    assert_eq!(execution.trade.seller_address, "maker_b");
+    assert_eq!(execution.trade.price, 50_000_000_000);
+    for order in &execution.orders {
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.filled_size, request.size);
+    }
+
+    // No fees: the quoted price is all-in
+    assert_eq!(
+        db.get_balance("taker", "USDC").await.unwrap().amount,
+        50_000_000_000
+    );
+    assert_eq!(
+        db.get_balance("taker", "BTC").await.unwrap().amount,
+        100_000_000
+    );
+    assert_eq!(
+        db.get_balance("maker_b", "USDC").await.unwrap().amount,
+        50_000_000_000
+    );
+    let maker_btc = db.get_balance("maker_b", "BTC").await.unwrap();
+    assert_eq!(maker_btc.amount, 0);
+    assert_eq!(maker_btc.open_interest, 0);
+
+    let trades = db.get_market_trades(&market.id, 10).await.unwrap();
+    assert_eq!(trades.len(), 1);
+    assert!(trades[0].rfq);
+
+    let again = db.execute_rfq(request.id, replaced.id, "taker").await;
+    assert!(matches!(
+        again,
+        Err(ExchangeError::RfqRequestNotOpen {
+            status: RfqStatus::Filled,
+            ..
+        })
+    ));
+    assert!(db
+        .list_open_quote_requests(Some(&market.id))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_execute_rfq_requires_funds() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let db = &test_db.db;
+
+    db.create_token("BTC".to_string(), 8, "Bitcoin".to_string())
+        .await
+        .unwrap();
+    db.create_token("USDC".to_string(), 6, "USD Coin".to_string())
+        .await
+        .unwrap();
+    let market = db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1_000_000,
+            1_000_000,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+    for user in ["taker", "maker"] {
+        db.create_user(user.to_string()).await.unwrap();
+    }
+    db.register_rfq_maker("maker").await.unwrap();
+    db.add_balance("taker", "BTC", 100_000_000).await.unwrap();
+
+    let mut request = quote_request(Side::Sell);
+    request.market_id = market.id.clone();
+    db.create_quote_request(&request).await.unwrap();
+    let quote = db
+        .submit_quote(request.id, "maker", 50_000_000_000, None)
+        .await
+        .unwrap();
+
+    // The maker has no USDC to pay with, and nothing moves
+    let unfunded = db.execute_rfq(request.id, quote.id, "taker").await;
+    assert!(matches!(
+        unfunded,
+        Err(ExchangeError::InsufficientBalance { .. })
+    ));
+    assert_eq!(
+        db.get_balance("taker", "BTC").await.unwrap().amount,
+        100_000_000
+    );
+    assert_eq!(
+        db.get_quote_request(request.id).await.unwrap().status,
+        RfqStatus::Open
+    );
+
+    let cancelled = db.cancel_quote_request(request.id, "taker").await.unwrap();
+    assert_eq!(cancelled.status, RfqStatus::Cancelled);
+    assert_eq!(db.remove_rfq_maker("maker").await.unwrap(), 0);
+}
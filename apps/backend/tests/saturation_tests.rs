@@ -0,0 +1,215 @@
+use axum::{extract::State, routing::post, Json, Router};
+use backend::alerts::{AlertDispatcher, AlertKind, AlertTarget};
+use backend::engine::analytics::AnalyticsWriter;
+use backend::models::domain::EngineRequest;
+use backend::saturation::{
+    DegradedMode, Sample, Saturation, SaturationMonitor, SaturationThresholds, RECOVERY_CHECKS,
+};
+use exchange_test_utils::helpers;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+/// Receiver that accepts every alert and forwards its JSON body
+async fn start_receiver() -> (String, mpsc::UnboundedReceiver<Value>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/alerts",
+            post(
+                |State(tx): State<mpsc::UnboundedSender<Value>>, Json(body): Json<Value>| async move {
+                    let _ = tx.send(body);
+                },
+            ),
+        )
+        .with_state(tx);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/alerts", addr), rx)
+}
+
+/// An engine queue of `capacity` with `queued` requests waiting in it
+fn engine_queue(
+    capacity: usize,
+    queued: usize,
+) -> (mpsc::Sender<EngineRequest>, mpsc::Receiver<EngineRequest>) {
+    let (engine_tx, engine_rx) = mpsc::channel(capacity);
+    for _ in 0..queued {
+        let (response_tx, _) = tokio::sync::oneshot::channel();
+        engine_tx
+            .try_send(EngineRequest::ReleaseKillSwitch {
+                market_id: None,
+                response_tx,
+            })
+            .unwrap();
+    }
+    (engine_tx, engine_rx)
+}
+
+// ============================================================================
+// Threshold Tests
+// ============================================================================
+
+#[test]
+fn test_thresholds_flag_each_saturated_part() {
+    let thresholds = SaturationThresholds::default();
+    let calm = Sample {
+        engine_queued: 89,
+        engine_capacity: 100,
+        persistence_queued: 7_999,
+        persistence_capacity: 10_000,
+        lagged: vec![("webhooks", 99)],
+    };
+    assert!(thresholds.check(&calm).is_empty());
+
+    let saturated = Sample {
+        engine_queued: 90,
+        persistence_queued: 8_000,
+        lagged: vec![("webhooks", 100), ("ws_router", 3)],
+        ..calm
+    };
+    let alerts = thresholds.check(&saturated);
+    let kinds: Vec<_> = alerts
+        .iter()
+        .map(|a| (a.kind, a.subject.as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (AlertKind::EngineQueueSaturated, "engine"),
+            (AlertKind::PersistenceQueueSaturated, "clickhouse"),
+            (AlertKind::EventsLagging, "webhooks"),
+        ]
+    );
+    assert_eq!(alerts[0].message, "90 of 100 engine queue slots in use");
+}
+
+#[test]
+fn test_thresholds_are_configurable() {
+    let thresholds = SaturationThresholds {
+        engine_queue_percent: 50,
+        persistence_queue_percent: 100,
+        lagged_events: 1,
+    };
+    let sample = Sample {
+        engine_queued: 5,
+        engine_capacity: 10,
+        lagged: vec![("read_cache", 1)],
+        ..Sample::default()
+    };
+    assert_eq!(thresholds.check(&sample).len(), 2);
+
+    // A queue that isn't watched never counts as saturated
+    let unwatched = Sample {
+        persistence_queued: 0,
+        persistence_capacity: 0,
+        ..Sample::default()
+    };
+    assert!(thresholds.check(&unwatched).is_empty());
+}
+
+#[test]
+fn test_degraded_mode_waits_for_load_to_ease() {
+    let mut mode = DegradedMode::default();
+    assert!(!mode.update(false));
+    assert!(mode.update(true));
+
+    for _ in 1..RECOVERY_CHECKS {
+        assert!(mode.update(false));
+    }
+    // A saturated check starts the count again
+    assert!(mode.update(true));
+    for _ in 1..RECOVERY_CHECKS {
+        assert!(mode.update(false));
+    }
+    assert!(!mode.update(false));
+}
+
+// ============================================================================
+// Metrics Tests
+// ============================================================================
+
+#[test]
+fn test_lag_is_counted_per_consumer_and_exported() {
+    let saturation = Saturation::default();
+    saturation.record_lag("webhooks", 3);
+    saturation.record_lag("ws_router", 1);
+    saturation.record_lag("webhooks", 4);
+    assert_eq!(saturation.lagged()["webhooks"], 7);
+
+    let metrics = saturation.render_metrics();
+    assert!(metrics.contains("# TYPE exchange_engine_queue_depth gauge\n"));
+    assert!(metrics.contains("exchange_degraded 0\n"));
+    assert!(metrics.contains("exchange_event_lag_total{consumer=\"webhooks\"} 7\n"));
+    assert!(metrics.contains("exchange_event_lag_total{consumer=\"ws_router\"} 1\n"));
+}
+
+#[test]
+fn test_persistence_queue_counts_unsettled_trades() {
+    let (writer, _task) = AnalyticsWriter::new(10);
+    for _ in 0..3 {
+        writer.record(helpers::sample_trade("BTC/USDC"));
+    }
+    assert_eq!(writer.stats().queued(), 3);
+}
+
+// ============================================================================
+// Monitor Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_saturated_engine_queue_raises_alert_and_degrades() {
+    let (url, mut received) = start_receiver().await;
+    let (alerter, _handle) = AlertDispatcher::new(vec![AlertTarget::Webhook { url }]).spawn();
+
+    let (engine_tx, _engine_rx) = engine_queue(10, 9);
+    let saturation = Saturation::default();
+    let monitor =
+        SaturationMonitor::new(saturation.clone(), engine_tx.downgrade()).with_alerts(alerter);
+    let watcher = tokio::spawn(monitor.run());
+
+    let alert = timeout(Duration::from_secs(5), received.recv())
+        .await
+        .expect("Should alert on a 90% full queue")
+        .unwrap();
+    assert_eq!(alert["kind"], "engine_queue_saturated");
+    assert_eq!(alert["message"], "9 of 10 engine queue slots in use");
+    assert!(saturation.is_degraded());
+    assert_eq!(saturation.engine_queue(), (9, 10));
+    assert!(saturation
+        .render_metrics()
+        .contains("exchange_engine_queue_depth 9\n"));
+
+    // The monitor doesn't keep the queue alive
+    drop(engine_tx);
+    timeout(Duration::from_secs(5), watcher)
+        .await
+        .expect("Monitor should stop with the queue")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_lagging_consumer_raises_alert() {
+    let (url, mut received) = start_receiver().await;
+    let (alerter, _handle) = AlertDispatcher::new(vec![AlertTarget::Webhook { url }]).spawn();
+
+    let (engine_tx, _engine_rx) = engine_queue(10, 0);
+    let saturation = Saturation::default();
+    let monitor = SaturationMonitor::new(saturation.clone(), engine_tx.downgrade())
+        .with_thresholds(SaturationThresholds {
+            lagged_events: 5,
+            ..Default::default()
+        })
+        .with_alerts(alerter);
+    tokio::spawn(monitor.run());
+
+    saturation.record_lag("event_bus", 5);
+    let alert = timeout(Duration::from_secs(5), received.recv())
+        .await
+        .expect("Should alert on a lagging consumer")
+        .unwrap();
+    assert_eq!(alert["kind"], "events_lagging");
+    assert_eq!(alert["subject"], "event_bus");
+}
@@ -0,0 +1,136 @@
+use backend::schema::{self, compat::breaking_changes, compat::BreakingChange, Check};
+use serde_json::json;
+
+// ============================================================================
+// Committed Schema Tests
+// ============================================================================
+
+#[test]
+fn test_committed_schemas_have_no_breaking_changes() {
+    for file in [schema::openapi(), schema::websocket(), schema::asyncapi()] {
+        if let Check::Breaking(changes) = file.check() {
+            let report: Vec<String> = changes.iter().map(ToString::to_string).collect();
+            panic!(
+                "{} has breaking changes against packages/shared:\n  {}",
+                file.name,
+                report.join("\n  ")
+            );
+        }
+    }
+}
+
+// ============================================================================
+// Compatibility Check Tests
+// ============================================================================
+
+fn defs(order: serde_json::Value) -> serde_json::Value {
+    json!({ "$defs": { "Order": order } })
+}
+
+#[test]
+fn test_added_fields_and_variants_are_compatible() {
+    let old = defs(json!({
+        "type": "object",
+        "properties": { "id": { "type": "string" } },
+        "required": ["id"]
+    }));
+    let new = defs(json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "label": { "type": ["string", "null"] }
+        },
+        "required": ["id"]
+    }));
+
+    assert!(breaking_changes(&old, &new, "/$defs").is_empty());
+}
+
+#[test]
+fn test_removed_field_is_breaking() {
+    let old = defs(json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "price": { "type": "string" }
+        }
+    }));
+    let new = defs(json!({
+        "type": "object",
+        "properties": { "id": { "type": "string" } }
+    }));
+
+    assert_eq!(
+        breaking_changes(&old, &new, "/$defs"),
+        vec![BreakingChange::RemovedField {
+            path: "Order".to_string(),
+            field: "price".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_type_change_is_breaking() {
+    let old = defs(json!({
+        "type": "object",
+        "properties": { "size": { "type": "string" } }
+    }));
+    let new = defs(json!({
+        "type": "object",
+        "properties": { "size": { "type": ["string", "null"] } }
+    }));
+
+    assert_eq!(
+        breaking_changes(&old, &new, "/$defs"),
+        vec![BreakingChange::TypeChanged {
+            path: "Order.size".to_string(),
+            old: "string".to_string(),
+            new: "null|string".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_removed_variant_and_enum_value_are_breaking() {
+    let old = json!({
+        "components": { "schemas": {
+            "Side": { "type": "string", "enum": ["buy", "sell"] },
+            "Message": { "oneOf": [
+                { "type": "object", "properties": { "type": { "type": "string", "enum": ["trade"] } } },
+                { "type": "object", "properties": { "type": { "type": "string", "enum": ["pong"] } } }
+            ] }
+        } }
+    });
+    let new = json!({
+        "components": { "schemas": {
+            "Side": { "type": "string", "enum": ["buy"] },
+            "Message": { "oneOf": [
+                { "type": "object", "properties": { "type": { "type": "string", "enum": ["trade"] } } }
+            ] }
+        } }
+    });
+
+    let changes = breaking_changes(&old, &new, "/components/schemas");
+    assert_eq!(changes.len(), 2);
+    assert!(changes.contains(&BreakingChange::RemovedVariant {
+        path: "Message".to_string(),
+        tag: "pong".to_string(),
+    }));
+    assert!(changes.contains(&BreakingChange::RemovedEnumValue {
+        path: "Side".to_string(),
+        value: "\"sell\"".to_string(),
+    }));
+}
+
+#[test]
+fn test_removed_definition_is_breaking() {
+    let old = json!({ "$defs": { "Order": { "type": "object" }, "Trade": { "type": "object" } } });
+    let new = json!({ "$defs": { "Order": { "type": "object" } } });
+
+    assert_eq!(
+        breaking_changes(&old, &new, "/$defs"),
+        vec![BreakingChange::RemovedDefinition {
+            name: "Trade".to_string()
+        }]
+    );
+}
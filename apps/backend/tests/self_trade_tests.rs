@@ -0,0 +1,175 @@
+use backend::models::domain::{CancelReason, EngineEvent, OrderStatus};
+use exchange_test_utils::{memory_market, usd, OrderBuilder, TestEngine, BTC};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_bid_across_own_ask_is_refused_and_the_market_stays_open() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    let bob_bid = OrderBuilder::buy("bob", "BTC/USDC")
+        .limit(usd(49_000))
+        .size(BTC / 10)
+        .build();
+    engine.place_order(bob_bid.clone()).await.unwrap();
+    engine
+        .place_order(
+            OrderBuilder::sell("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    // Matching would skip alice's own ask and rest her bid above it
+    let err = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(51_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("own orders"), "{}", err);
+
+    // Nothing was locked for it, the book isn't crossed and bob's bid rests on
+    assert_eq!(
+        db.get_balance("alice", "USDC").await.unwrap().open_interest,
+        0
+    );
+    assert!(engine.orderbooks.read().await.crossed_books().is_empty());
+    assert_eq!(
+        db.get_order(&bob_bid.id).await.unwrap().status,
+        OrderStatus::Pending
+    );
+
+    // Other users still trade, and orders that never rest skip alice's ask
+    let placed = engine
+        .place_order(
+            OrderBuilder::buy("carol", "BTC/USDC")
+                .limit(usd(51_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(placed.trades.len(), 1);
+    engine
+        .place_order(
+            OrderBuilder::sell("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let ioc = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(51_000))
+                .size(BTC / 10)
+                .ioc()
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(ioc.trades.is_empty());
+    assert_eq!(ioc.order.status, OrderStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn test_bid_filled_before_reaching_own_ask_is_placed() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    for (user, price) in [("bob", usd(50_000)), ("alice", usd(50_500))] {
+        engine
+            .place_order(
+                OrderBuilder::sell(user, "BTC/USDC")
+                    .limit(price)
+                    .size(BTC / 10)
+                    .build(),
+            )
+            .await
+            .unwrap();
+    }
+
+    // Bob's cheaper ask fills alice's bid before it gets to her own ask
+    let placed = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(51_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(placed.trades.len(), 1);
+    assert_eq!(placed.order.status, OrderStatus::Filled);
+
+    // A bid for more would rest above her ask once bob's is gone
+    let err = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(51_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("own orders"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_triggered_stop_across_own_order_is_cancelled() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+    engine
+        .print_trade("bob", "carol", usd(49_000), BTC / 10)
+        .await;
+    engine
+        .place_order(
+            OrderBuilder::sell("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let stop = OrderBuilder::buy("alice", "BTC/USDC")
+        .stop_limit(usd(49_500), usd(50_500))
+        .size(BTC / 10)
+        .build();
+    engine.place_order(stop.clone()).await.unwrap();
+
+    engine
+        .print_trade("bob", "carol", usd(49_500), BTC / 10)
+        .await;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(EngineEvent::OrderCancelled {
+                order_id,
+                reason: Some(CancelReason::SelfTrade),
+                ..
+            }) = engine.event_rx.recv().await
+            {
+                if order_id == stop.id {
+                    break;
+                }
+            }
+        }
+    })
+    .await
+    .expect("stop was never cancelled");
+
+    let stored = db.get_order(&stop.id).await.unwrap();
+    assert_eq!(stored.status, OrderStatus::Cancelled);
+    assert_eq!(stored.cancel_reason, Some(CancelReason::SelfTrade));
+    assert_eq!(
+        db.get_balance("alice", "USDC").await.unwrap().open_interest,
+        0
+    );
+    assert!(engine.orderbooks.read().await.crossed_books().is_empty());
+}
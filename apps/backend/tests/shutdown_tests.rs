@@ -0,0 +1,39 @@
+use backend::shutdown::{self, Shutdown};
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn test_triggered_waits_for_trigger() {
+    let shutdown = Shutdown::new();
+    let triggered = tokio::spawn(shutdown.triggered());
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!triggered.is_finished());
+    assert!(!shutdown.is_triggered());
+
+    shutdown.clone().trigger();
+    timeout(Duration::from_secs(1), triggered)
+        .await
+        .expect("Should resolve once triggered")
+        .unwrap();
+    assert!(shutdown.is_triggered());
+}
+
+#[tokio::test]
+async fn test_triggered_resolves_after_the_fact() {
+    // Connections opened during shutdown are closed straight away
+    let shutdown = Shutdown::new();
+    shutdown.trigger();
+
+    timeout(Duration::from_secs(1), shutdown.triggered())
+        .await
+        .expect("Should resolve immediately");
+}
+
+#[tokio::test]
+async fn test_drain_reports_outcome() {
+    let done = tokio::spawn(async {});
+    assert!(shutdown::drain("Done task", done).await);
+
+    let panicked = tokio::spawn(async { panic!("boom") });
+    assert!(!shutdown::drain("Panicking task", panicked).await);
+}
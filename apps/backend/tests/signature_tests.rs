@@ -0,0 +1,199 @@
+use backend::api::signature::{SignatureCheck, MAX_SIGNATURE_LIFETIME_MS};
+use backend::errors::ExchangeError;
+use backend::models::api::{TradeRequest, UserRequest};
+use backend::models::domain::SubAccount;
+use exchange_test_utils::TestWallet;
+
+const ROUTE: &str = "/api/user";
+
+fn withdraw(user_address: &str, amount: &str) -> UserRequest {
+    UserRequest::Withdraw {
+        user_address: user_address.to_string(),
+        token_ticker: "USDC".to_string(),
+        amount: amount.to_string(),
+        destination: "0x00000000000000000000000000000000000b0b01".to_string(),
+        signature: String::new(),
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn refused(result: backend::errors::Result<()>) -> bool {
+    matches!(result, Err(ExchangeError::InvalidSignature { .. }))
+}
+
+#[test]
+fn test_the_accounts_own_signature_is_accepted_once() {
+    let check = SignatureCheck::default();
+    let wallet = TestWallet::new();
+    let request = withdraw(wallet.address(), "1000000");
+    let signature = wallet.signature(ROUTE, &request);
+
+    assert!(check
+        .verify(ROUTE, &request, wallet.address(), &signature)
+        .is_ok());
+    // Replaying it is refused
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        wallet.address(),
+        &signature
+    )));
+    // Addresses are compared without regard to case
+    let request = withdraw(&wallet.address().to_uppercase(), "1000000");
+    let signature = wallet.signature(ROUTE, &request);
+    assert!(check
+        .verify(
+            ROUTE,
+            &request,
+            &wallet.address().to_uppercase(),
+            &signature
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_missing_and_forged_signatures_are_refused() {
+    let check = SignatureCheck::default();
+    let alice = TestWallet::new();
+    let mallory = TestWallet::new();
+    let request = withdraw(alice.address(), "1000000");
+
+    for missing in ["", "sig", "0x", "1700000000", "soon:0xabcd"] {
+        assert!(
+            refused(check.verify(ROUTE, &request, alice.address(), missing)),
+            "{}",
+            missing
+        );
+    }
+    // Signed by someone else
+    let forged = mallory.signature(ROUTE, &request);
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        alice.address(),
+        &forged
+    )));
+    // Signed for another amount, or another endpoint
+    let other_amount = alice.signature(ROUTE, &withdraw(alice.address(), "1"));
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        alice.address(),
+        &other_amount
+    )));
+    let other_route = alice.signature("/api/trade", &request);
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        alice.address(),
+        &other_route
+    )));
+    // A signature with its bytes tampered with
+    let signature = alice.signature(ROUTE, &request);
+    let mut tampered = signature.clone().into_bytes();
+    let last = tampered.len() - 3;
+    tampered[last] = if tampered[last] == b'0' { b'1' } else { b'0' };
+    let tampered = String::from_utf8(tampered).unwrap();
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        alice.address(),
+        &tampered
+    )));
+    // Nothing refused counts as used
+    assert!(check
+        .verify(ROUTE, &request, alice.address(), &signature)
+        .is_ok());
+}
+
+#[test]
+fn test_signatures_expire() {
+    let check = SignatureCheck::default();
+    let wallet = TestWallet::new();
+    let request = withdraw(wallet.address(), "1000000");
+
+    let expired = wallet.signature_expiring(ROUTE, &request, now() - 1);
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        wallet.address(),
+        &expired
+    )));
+    // Signatures valid for longer than the limit would be replayable for too long
+    let too_long =
+        wallet.signature_expiring(ROUTE, &request, now() + MAX_SIGNATURE_LIFETIME_MS + 60_000);
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        wallet.address(),
+        &too_long
+    )));
+
+    let signature = wallet.signature_expiring(ROUTE, &request, now() + 30_000);
+    assert!(refused(check.verify_at(
+        ROUTE,
+        &request,
+        wallet.address(),
+        &signature,
+        now() + 30_000
+    )));
+    assert!(check
+        .verify_at(ROUTE, &request, wallet.address(), &signature, now())
+        .is_ok());
+}
+
+#[test]
+fn test_masters_sign_for_their_sub_accounts() {
+    let check = SignatureCheck::default();
+    let alice = TestWallet::new();
+    let bob = TestWallet::new();
+    let desk = SubAccount::address_of(alice.address(), "desk");
+    let request = withdraw(&desk, "1000000");
+
+    assert!(check
+        .verify(ROUTE, &request, &desk, &alice.signature(ROUTE, &request))
+        .is_ok());
+    assert!(refused(check.verify(
+        ROUTE,
+        &request,
+        &desk,
+        &bob.signature(ROUTE, &request)
+    )));
+}
+
+#[test]
+fn test_unsigned_trading_only_covers_orders() {
+    let wallet = TestWallet::new();
+    let cancel = TradeRequest::CancelAllOrders {
+        user_address: wallet.address().to_string(),
+        market_id: None,
+        signature: "sig".to_string(),
+    };
+    let request = withdraw(wallet.address(), "1000000");
+
+    let strict = SignatureCheck::default();
+    assert!(refused(strict.verify_trading(
+        "/api/trade",
+        &cancel,
+        wallet.address(),
+        "sig"
+    )));
+    let signature = wallet.signature("/api/trade", &cancel);
+    assert!(strict
+        .verify_trading("/api/trade", &cancel, wallet.address(), &signature)
+        .is_ok());
+
+    let local = SignatureCheck::default().with_unsigned_trading(true);
+    assert!(local
+        .verify_trading("/api/trade", &cancel, wallet.address(), "sig")
+        .is_ok());
+    assert!(refused(local.verify(
+        ROUTE,
+        &request,
+        wallet.address(),
+        "sig"
+    )));
+}
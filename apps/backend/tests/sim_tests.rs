@@ -0,0 +1,158 @@
+use backend::bootstrap;
+use backend::config::Config;
+use backend::db::memory::MemoryStore;
+use backend::db::Db;
+use backend::errors::ExchangeError;
+use backend::models::domain::{OrderStatus, Side};
+use backend::sim::{SimMarket, SyntheticTrader, MAKER_ADDRESS, TAKER_ADDRESS};
+use exchange_test_utils::{memory_market, OrderBuilder, TestEngine};
+
+#[test]
+fn test_memory_balances_lock_and_unlock() {
+    let store = MemoryStore::default();
+    store.add_balance("alice", "USDC", 100).unwrap();
+
+    let balance = store.lock_balance("alice", "USDC", 60).unwrap();
+    assert_eq!((balance.amount, balance.open_interest), (100, 60));
+
+    // Only 40 is still available
+    assert!(matches!(
+        store.lock_balance("alice", "USDC", 41),
+        Err(ExchangeError::InsufficientBalance { required: 41, .. })
+    ));
+    assert!(matches!(
+        store.lock_balance("bob", "USDC", 1),
+        Err(ExchangeError::InsufficientBalance { .. })
+    ));
+
+    // Unlocking never goes below zero, and a missing balance has nothing to unlock
+    store.unlock_balance("alice", "USDC", 100).unwrap();
+    assert_eq!(store.get_balance("alice", "USDC").unwrap().open_interest, 0);
+    store.unlock_balance("bob", "USDC", 1).unwrap();
+    assert!(matches!(
+        store.get_balance("bob", "USDC"),
+        Err(ExchangeError::BalanceNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_in_memory_engine_matches_and_settles() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    // 1 BTC at 50,000 USDC
+    let sell = OrderBuilder::sell("alice", "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(100_000_000)
+        .build();
+    let placed = engine.place_order(sell.clone()).await.unwrap();
+    assert_eq!(placed.order.status, OrderStatus::Pending);
+    assert_eq!(
+        db.get_balance("alice", "BTC").await.unwrap().open_interest,
+        100_000_000
+    );
+
+    let buy = OrderBuilder::buy("bob", "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(40_000_000)
+        .build();
+    let placed = engine.place_order(buy).await.unwrap();
+    assert_eq!(placed.order.status, OrderStatus::Filled);
+    assert_eq!(placed.trades.len(), 1);
+
+    // 0.4 BTC changed hands for 20,000 USDC
+    let alice_btc = db.get_balance("alice", "BTC").await.unwrap();
+    assert_eq!(alice_btc.amount, 960_000_000);
+    assert_eq!(alice_btc.open_interest, 60_000_000);
+    let bob_usdc = db.get_balance("bob", "USDC").await.unwrap();
+    assert_eq!(bob_usdc.amount, 980_000 * 1_000_000);
+    assert_eq!(bob_usdc.open_interest, 0);
+    assert_eq!(
+        db.get_balance("alice", "USDC").await.unwrap().amount,
+        1_020_000 * 1_000_000
+    );
+
+    let orders = db
+        .get_user_orders("alice", Some("BTC/USDC"), None, 100)
+        .await
+        .unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].status, OrderStatus::PartiallyFilled);
+    assert_eq!(orders[0].filled_size, 40_000_000);
+
+    let trades = db.get_user_trades("bob", None, 100).await.unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].side, Side::Buy);
+
+    // Candles and stats come from the same trades
+    let now = chrono::Utc::now().timestamp();
+    let candles = db
+        .get_candles_for_api("BTC/USDC", "1m", now - 3600, now + 60, None, false)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 1);
+    assert_eq!(candles[0].close, 50_000_000_000);
+    assert_eq!(candles[0].volume, 40_000_000);
+    let stats = db
+        .get_market_stats("BTC/USDC", 8, now - 3600, now + 60)
+        .await
+        .unwrap();
+    assert_eq!(stats.trade_count, 1);
+    assert_eq!(stats.quote_volume, "20000000000");
+
+    // Cancelling releases the rest of the lock
+    engine
+        .cancel_order(sell.id, "alice".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_balance("alice", "BTC").await.unwrap().open_interest,
+        0
+    );
+    assert!(db
+        .get_recoverable_orders_for_market("BTC/USDC")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_synthetic_trader_trades_without_crossing() {
+    let db = Db::in_memory().unwrap();
+    let config = Config::load_from(&Config::path()).unwrap();
+    bootstrap::create_missing(&db, &config).await.unwrap();
+    let engine = TestEngine::spawn(db.clone());
+
+    let mut markets = Vec::new();
+    for market in &config.markets {
+        let quote_decimals = config.token_decimals(&market.quote_ticker).unwrap();
+        markets.push(SimMarket::new(
+            db.get_market(&market.market_id()).await.unwrap(),
+            quote_decimals,
+            market.ladder_layout().unwrap(),
+        ));
+    }
+    let mut trader = SyntheticTrader::new(engine.engine_tx.clone(), markets).with_seed(7);
+    trader.fund(&db).await.unwrap();
+
+    for _ in 0..30 {
+        trader.tick().await.unwrap();
+        assert!(engine.orderbooks.read().await.crossed_books().is_empty());
+    }
+
+    // Only the last tick's quotes rest, and the taker has traded with them
+    for market in &config.markets {
+        let market_id = market.market_id();
+        let open = db
+            .count_open_orders(MAKER_ADDRESS, Some(&market_id))
+            .await
+            .unwrap();
+        let resting = open.first().map_or(0, |(_, count)| *count);
+        assert!((1..=10).contains(&resting), "{}: {}", market_id, resting);
+    }
+    assert!(!db
+        .get_user_trades(TAKER_ADDRESS, None, 100)
+        .await
+        .unwrap()
+        .is_empty());
+}
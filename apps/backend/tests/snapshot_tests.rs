@@ -0,0 +1,247 @@
+use backend::archive::ArchiveStore;
+use backend::db::Db;
+use backend::snapshot::{
+    self, manifest_path, table_path, trades_path, validate_name, SnapshotManifest, Snapshotter,
+    SNAPSHOT_SCHEMA_VERSION,
+};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use exchange_test_utils::{helpers, OrderBuilder, TestDb};
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload};
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn empty_manifest(name: &str, taken_at: DateTime<Utc>) -> SnapshotManifest {
+    SnapshotManifest {
+        schema_version: SNAPSHOT_SCHEMA_VERSION,
+        name: name.to_string(),
+        taken_at,
+        migration: 1,
+        tables: vec![],
+        sequences: vec![],
+        trades: vec![],
+    }
+}
+
+async fn put_manifest(memory: &InMemory, manifest: &SnapshotManifest) {
+    memory
+        .put(
+            &Path::from(format!("snapshots/{}", manifest_path(&manifest.name))),
+            PutPayload::from(serde_json::to_vec(manifest).unwrap()),
+        )
+        .await
+        .unwrap();
+}
+
+// ============================================================================
+// Layout Tests
+// ============================================================================
+
+#[test]
+fn test_snapshot_layout() {
+    let date = NaiveDate::from_ymd_opt(2025, 11, 20).unwrap();
+    assert_eq!(manifest_path("staging-1"), "staging-1/manifest.json");
+    assert_eq!(
+        table_path("staging-1", "orders"),
+        "staging-1/postgres/orders.bin"
+    );
+    assert_eq!(
+        trades_path("staging-1", date),
+        "staging-1/clickhouse/trades/date=2025-11-20/trades.parquet"
+    );
+}
+
+#[test]
+fn test_snapshot_names() {
+    for name in ["staging-1", "dr_drill.2025-11-20", "A1"] {
+        assert!(validate_name(name).is_ok(), "{}", name);
+    }
+    for name in ["", ".hidden", "a/b", "../up", "with space"] {
+        assert!(validate_name(name).is_err(), "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn test_list_skips_incomplete_snapshots() {
+    let memory = Arc::new(InMemory::new());
+    let store = ArchiveStore::new(memory.clone(), "snapshots");
+
+    let newer = empty_manifest(
+        "newer",
+        Utc.with_ymd_and_hms(2025, 11, 21, 0, 0, 0).unwrap(),
+    );
+    let older = empty_manifest(
+        "older",
+        Utc.with_ymd_and_hms(2025, 11, 20, 0, 0, 0).unwrap(),
+    );
+    put_manifest(&memory, &newer).await;
+    put_manifest(&memory, &older).await;
+    // A snapshot still being taken has files but no manifest yet
+    memory
+        .put(
+            &Path::from(format!("snapshots/{}", table_path("partial", "orders"))),
+            PutPayload::from_static(b"PGCOPY"),
+        )
+        .await
+        .unwrap();
+
+    let listed = snapshot::list(&store).await.unwrap();
+    assert_eq!(listed, vec![older, newer.clone()]);
+    assert_eq!(
+        snapshot::manifest(&store, "newer").await.unwrap(),
+        Some(newer)
+    );
+    assert_eq!(snapshot::manifest(&store, "partial").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_snapshots_need_databases() {
+    let memory = Arc::new(InMemory::new());
+    let store = ArchiveStore::new(memory.clone(), "snapshots");
+    let snapshotter = Snapshotter::new(Db::in_memory().unwrap(), store);
+
+    let err = snapshotter.take("staging-1", 1).await.unwrap_err();
+    assert!(err.to_string().contains("in-memory"), "{}", err);
+    let err = snapshotter.take("../up", 1).await.unwrap_err();
+    assert!(err.to_string().contains("Invalid snapshot name"), "{}", err);
+
+    let err = snapshotter.restore("missing", false).await.unwrap_err();
+    assert!(err.to_string().contains("No snapshot named"), "{}", err);
+
+    let mut future = empty_manifest("future", Utc::now());
+    future.schema_version = SNAPSHOT_SCHEMA_VERSION + 1;
+    put_manifest(&memory, &future).await;
+    let err = snapshotter.restore("future", false).await.unwrap_err();
+    assert!(err.to_string().contains("schema version"), "{}", err);
+}
+
+// ============================================================================
+// Snapshot and Restore Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_snapshot_and_restore_round_trip() {
+    let source = TestDb::setup_dedicated()
+        .await
+        .expect("Failed to setup source db");
+    let market = helpers::create_market_with_tokens(&source, "BTC", "USDC")
+        .await
+        .unwrap();
+    helpers::create_user(&source, "alice").await.unwrap();
+    source
+        .db
+        .add_balance("alice", "USDC", 1_000_000_000)
+        .await
+        .unwrap();
+
+    // One resting order from this month and one from a month the target
+    // has no partition for
+    let current = OrderBuilder::buy("alice", &market.id).build();
+    let mut old = OrderBuilder::buy("alice", &market.id).build();
+    old.created_at = DateTime::from_str("2024-03-15T12:00:00Z").unwrap();
+    source.db.create_order(&current).await.unwrap();
+    source.db.create_order(&old).await.unwrap();
+
+    let trade = helpers::sample_trade(&market.id);
+    let trade = backend::models::domain::Trade {
+        timestamp: trade.timestamp - Duration::minutes(1),
+        ..trade
+    };
+    source
+        .db
+        .insert_trades_to_clickhouse(std::slice::from_ref(&trade))
+        .await
+        .unwrap();
+
+    let memory = Arc::new(InMemory::new());
+    let store = ArchiveStore::new(memory.clone(), "snapshots");
+    let manifest = Snapshotter::new(source.db.clone(), store.clone())
+        .take("staging-1", 2)
+        .await
+        .expect("Failed to take snapshot");
+    assert_eq!(manifest.table("orders").unwrap().rows, 2);
+    assert_eq!(manifest.table("balances").unwrap().rows, 1);
+    assert!(manifest.table("_sqlx_migrations").is_none());
+    assert_eq!(manifest.trades.len(), 2);
+    assert_eq!(manifest.trades.iter().map(|d| d.file.rows).sum::<u64>(), 1);
+    assert_eq!(
+        snapshot::list(&store).await.unwrap(),
+        vec![manifest.clone()]
+    );
+
+    // Names are never reused
+    let err = Snapshotter::new(source.db.clone(), store.clone())
+        .take("staging-1", 2)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+
+    let target = TestDb::setup_dedicated()
+        .await
+        .expect("Failed to setup target db");
+    let snapshotter = Snapshotter::new(target.db.clone(), store.clone());
+    let report = snapshotter
+        .restore("staging-1", false)
+        .await
+        .expect("Failed to restore");
+    assert_eq!(report.tables, manifest.tables.len());
+    assert_eq!(report.rows, manifest.rows());
+    assert_eq!(report.trades, 1);
+    assert!(report.skipped_days.is_empty());
+
+    let db = &target.db;
+    assert_eq!(db.get_market(&market.id).await.unwrap(), market);
+    let stored = source.db.get_order(&current.id).await.unwrap();
+    assert_eq!(db.get_order(&current.id).await.unwrap(), stored);
+    assert_eq!(
+        db.get_balance("alice", "USDC").await.unwrap(),
+        source.db.get_balance("alice", "USDC").await.unwrap()
+    );
+    let partition: String =
+        sqlx::query_scalar("SELECT tableoid::regclass::TEXT FROM orders WHERE id = $1")
+            .bind(old.id)
+            .fetch_one(&db.postgres)
+            .await
+            .unwrap();
+    assert_eq!(partition, "orders_2024_03");
+    let day = trade.timestamp.timestamp();
+    assert_eq!(db.count_trades_between(day, day + 1).await.unwrap(), 1);
+
+    // The sequences continue where the source's stood
+    let sequences = |db: Db| async move {
+        sqlx::query_as::<_, (String, Option<i64>)>(
+            "SELECT sequencename::text, last_value FROM pg_sequences WHERE schemaname = 'public' ORDER BY 1",
+        )
+        .fetch_all(&db.postgres)
+        .await
+        .unwrap()
+    };
+    assert_eq!(
+        sequences(target.db.clone()).await,
+        sequences(source.db.clone()).await
+    );
+
+    // A database with data is only replaced when forced, and trades it
+    // already has aren't loaded twice
+    let err = snapshotter.restore("staging-1", false).await.unwrap_err();
+    assert!(err.to_string().contains("already lists"), "{}", err);
+    let report = snapshotter.restore("staging-1", true).await.unwrap();
+    assert_eq!(report.rows, manifest.rows());
+    assert_eq!(report.trades, 0);
+    assert_eq!(report.skipped_days, vec![trade.timestamp.date_naive()]);
+    assert_eq!(db.count_trades_between(day, day + 1).await.unwrap(), 1);
+
+    // A file that no longer matches its manifest is refused, and the
+    // tables are left as they were
+    let orders = manifest.table("orders").unwrap();
+    let location = Path::from(format!("snapshots/{}", orders.path));
+    let data = memory.get(&location).await.unwrap().bytes().await.unwrap();
+    memory
+        .put(&location, PutPayload::from(data.slice(..data.len() - 2)))
+        .await
+        .unwrap();
+    let err = snapshotter.restore("staging-1", true).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("orders"), "{:#}", err);
+    assert_eq!(db.get_order(&current.id).await.unwrap(), stored);
+}
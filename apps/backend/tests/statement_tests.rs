@@ -0,0 +1,246 @@
+use backend::models::domain::{Balance, Deposit, StatementMarket};
+use backend::statements::{
+    build_statements, month_start, next_month, previous_month, StatementFlows, StatementGenerator,
+    TokenFlows,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use exchange_test_utils::{helpers, OrderBuilder, TestDb, TestEngine};
+use std::collections::HashMap;
+
+fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+}
+
+fn balance(user: &str, token: &str, amount: u128) -> Balance {
+    Balance {
+        user_address: user.to_string(),
+        token_ticker: token.to_string(),
+        amount,
+        open_interest: 0,
+        updated_at: Utc::now(),
+    }
+}
+
+fn key(user: &str, token: &str) -> (String, String) {
+    (user.to_string(), token.to_string())
+}
+
+// ============================================================================
+// Month Tests
+// ============================================================================
+
+#[test]
+fn test_months_are_calendar_months_in_utc() {
+    let start = month_start(Utc.with_ymd_and_hms(2025, 12, 31, 23, 59, 59).unwrap());
+    assert_eq!(start, at(2025, 12, 1));
+    assert_eq!(next_month(start), at(2026, 1, 1));
+    assert_eq!(previous_month(at(2025, 3, 1)), at(2025, 2, 1));
+    assert_eq!(month_start(at(2025, 3, 1)), at(2025, 3, 1));
+}
+
+// ============================================================================
+// Statement Building Tests
+// ============================================================================
+
+#[test]
+fn test_statements_cover_holders_and_active_users() {
+    let period_start = at(2025, 11, 1);
+    let closing = vec![
+        balance("alice", "USDC", 900),
+        balance("alice", "BTC", 0),
+        balance("bob", "BTC", 50),
+        balance("system", "USDC", 7),
+    ];
+    let mut flows = StatementFlows::new();
+    flows.insert(
+        key("alice", "USDC"),
+        TokenFlows {
+            deposited: 1_000,
+            withdrawn: 100,
+            fees: 0,
+        },
+    );
+    flows.insert(
+        key("carol", "BTC"),
+        TokenFlows {
+            fees: -3,
+            ..Default::default()
+        },
+    );
+    let fills = vec![(
+        "bob".to_string(),
+        StatementMarket {
+            market_id: "BTC/USDC".to_string(),
+            fills: 2,
+            bought: 50,
+            sold: 0,
+            notional: 25,
+        },
+    )];
+
+    let statements = build_statements(period_start, Utc::now(), &closing, None, &flows, &fills);
+    let users: Vec<_> = statements.iter().map(|s| s.user_address.as_str()).collect();
+    // Empty balances don't count and system accounts get none
+    assert_eq!(users, vec!["alice", "bob", "carol"]);
+
+    let alice = &statements[0];
+    assert_eq!(alice.period_start, period_start);
+    assert_eq!(alice.period_end, at(2025, 12, 1));
+    assert_eq!(alice.balances.len(), 1);
+    assert_eq!(alice.balances[0].token_ticker, "USDC");
+    assert_eq!(alice.balances[0].opening_balance, None);
+    assert_eq!(alice.balances[0].closing_balance, 900);
+    assert_eq!(alice.balances[0].deposited, 1_000);
+    assert_eq!(alice.balances[0].withdrawn, 100);
+    assert!(alice.markets.is_empty());
+
+    assert_eq!(statements[1].markets[0].fills, 2);
+    // A rebate larger than the fees paid leaves fees negative
+    assert_eq!(statements[2].balances[0].fees, -3);
+    assert_eq!(statements[2].balances[0].closing_balance, 0);
+}
+
+#[test]
+fn test_statements_open_with_last_months_closing() {
+    let closing = vec![balance("alice", "USDC", 900)];
+    let opening = HashMap::from([(key("alice", "USDC"), 900), (key("bob", "BTC"), 40)]);
+
+    let statements = build_statements(
+        at(2025, 11, 1),
+        Utc::now(),
+        &closing,
+        Some(&opening),
+        &StatementFlows::new(),
+        &[],
+    );
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0].balances[0].opening_balance, Some(900));
+    // Bob spent everything he held, so his statement shows it going
+    assert_eq!(statements[1].user_address, "bob");
+    assert_eq!(statements[1].balances[0].opening_balance, Some(40));
+    assert_eq!(statements[1].balances[0].closing_balance, 0);
+}
+
+// ============================================================================
+// Statement Job Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_closed_months_get_statements_once() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            10,
+            20,
+        )
+        .await
+        .expect("Failed to create market");
+
+    // 0.01 BTC changes hands at 50,000 USDC
+    let ask = OrderBuilder::sell("seller", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(ask).await.expect("Failed to place ask");
+    let bid = OrderBuilder::buy("buyer", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    engine.place_order(bid).await.expect("Failed to place bid");
+    let trades = test_db
+        .db
+        .get_user_trades("buyer", Some(&market.id), 10)
+        .await
+        .unwrap();
+    test_db
+        .db
+        .insert_trades_to_clickhouse(&trades)
+        .await
+        .unwrap();
+
+    helpers::create_user(&test_db, "depositor").await.unwrap();
+    test_db
+        .db
+        .credit_deposit(&Deposit {
+            chain_id: 42161,
+            tx_hash: "0xabc".to_string(),
+            log_index: 0,
+            block_number: 1,
+            user_address: "depositor".to_string(),
+            token_ticker: "USDC".to_string(),
+            amount: 5_000_000,
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+    // Shortly after this month ends, only this month is closed
+    let this_month = month_start(Utc::now());
+    let generator = StatementGenerator::new(test_db.db.clone());
+    let after_month = next_month(this_month) + Duration::minutes(5);
+    let generated = generator.close_months(after_month).await.unwrap();
+    assert!(generated >= 3);
+    assert_eq!(generator.close_months(after_month).await.unwrap(), 0);
+
+    let buyer = test_db
+        .db
+        .list_user_statements("buyer", Some(this_month), 1)
+        .await
+        .unwrap()
+        .pop()
+        .expect("Buyer statement");
+    assert_eq!(buyer.markets.len(), 1);
+    assert_eq!(buyer.markets[0].fills, 1);
+    assert_eq!(buyer.markets[0].bought, 1_000_000);
+    assert_eq!(buyer.markets[0].notional, 500_000_000);
+    let btc = buyer
+        .balances
+        .iter()
+        .find(|b| b.token_ticker == "BTC")
+        .expect("BTC line");
+    // The taker pays 0.2% in the base token
+    assert_eq!(btc.fees, 2_000);
+    assert_eq!(btc.opening_balance, None);
+    assert_eq!(
+        btc.closing_balance,
+        test_db.db.get_balance("buyer", "BTC").await.unwrap().amount
+    );
+
+    let depositor = test_db
+        .db
+        .list_user_statements("depositor", None, 12)
+        .await
+        .unwrap();
+    assert_eq!(depositor.len(), 1);
+    assert_eq!(depositor[0].balances[0].deposited, 5_000_000);
+    assert_eq!(depositor[0].balances[0].closing_balance, 5_000_000);
+    assert!(test_db
+        .db
+        .list_user_statements("system", None, 12)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // The next month opens with this one's closing balances
+    let next = next_month(this_month);
+    generator
+        .close_months(next_month(next) + Duration::minutes(5))
+        .await
+        .unwrap();
+    let depositor = test_db
+        .db
+        .list_user_statements("depositor", None, 12)
+        .await
+        .unwrap();
+    assert_eq!(depositor.len(), 2);
+    assert_eq!(depositor[0].period_start, next);
+    assert_eq!(depositor[0].balances[0].opening_balance, Some(5_000_000));
+    assert_eq!(depositor[0].balances[0].deposited, 0);
+}
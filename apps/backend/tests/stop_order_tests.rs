@@ -0,0 +1,524 @@
+use backend::engine::housekeeping::HOUSEKEEPING_INTERVAL;
+use backend::engine::triggers::{is_triggered, TriggerBook};
+use backend::errors::ExchangeError;
+use backend::models::domain::{
+    CancelReason, EngineEvent, IndexPrice, MarketStatus, Order, OrderStatus, OrderType, SubAccount,
+    TriggerSource,
+};
+use exchange_test_utils::{memory_market, usd, OrderBuilder, TestEngine, BTC};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Move the engine's BTC/USDC index price to `price`, as the price feed would
+fn record_index_price(engine: &TestEngine, price: u128) {
+    engine.index_prices.record(IndexPrice {
+        market_id: "BTC/USDC".to_string(),
+        timestamp: chrono::Utc::now(),
+        price,
+        source: "test".to_string(),
+    });
+}
+
+/// Wait for the engine to broadcast triggered stop `order_id` reaching `status`
+async fn wait_for_activation(
+    engine: &mut TestEngine,
+    order_id: Uuid,
+    status: OrderStatus,
+) -> Order {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(EngineEvent::OrderPlaced { order }) = engine.event_rx.recv().await {
+                if order.id == order_id && !order.order_type.is_stop() && order.status == status {
+                    return order;
+                }
+            }
+        }
+    })
+    .await
+    .expect("stop order never reached its status")
+}
+
+// ============================================================================
+// Trigger Book Tests
+// ============================================================================
+
+#[test]
+fn test_stops_trigger_on_their_side_of_the_last_trade() {
+    let buy = OrderBuilder::buy("carol", "BTC/USDC")
+        .stop_market(usd(51_000))
+        .build();
+    let sell = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_limit(usd(49_000), usd(48_900))
+        .build();
+
+    assert!(!is_triggered(&buy, usd(50_999)));
+    assert!(is_triggered(&buy, usd(51_000)));
+    assert!(is_triggered(&buy, usd(52_000)));
+    assert!(!is_triggered(&sell, usd(49_001)));
+    assert!(is_triggered(&sell, usd(49_000)));
+    assert!(is_triggered(&sell, usd(1)));
+
+    // Orders without a trigger never trigger
+    let limit = OrderBuilder::buy("carol", "BTC/USDC").build();
+    assert!(!is_triggered(&limit, u128::MAX));
+}
+
+#[test]
+fn test_trigger_book_takes_reached_orders_oldest_first() {
+    let first = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .build();
+    let second = OrderBuilder::sell("dave", "BTC/USDC")
+        .stop_market(usd(49_500))
+        .build();
+    let further = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(45_000))
+        .build();
+    let other_market = OrderBuilder::sell("carol", "ETH/USDC")
+        .stop_market(usd(3_000))
+        .build();
+    let mut book = TriggerBook::new(vec![
+        first.clone(),
+        second.clone(),
+        further.clone(),
+        other_market,
+    ]);
+    assert_eq!(book.len(), 4);
+    assert_eq!(book.open_order_count("BTC/USDC", "carol"), 2);
+
+    // Nothing traded yet
+    assert!(book.take_triggered().is_empty());
+
+    book.record_trade("BTC/USDC", usd(49_200));
+    assert_eq!(book.take_triggered(), vec![second.clone()]);
+    // Each trade is only checked once
+    assert!(book.take_triggered().is_empty());
+
+    book.record_trade("BTC/USDC", usd(48_000));
+    book.record_trade("ETH/USDC", usd(3_100));
+    assert_eq!(book.take_triggered(), vec![first]);
+    assert_eq!(book.last_price("BTC/USDC"), Some(usd(48_000)));
+    assert_eq!(book.len(), 2);
+}
+
+#[test]
+fn test_index_stops_trigger_off_the_index_price() {
+    let index_stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .trigger_source(TriggerSource::Index)
+        .build();
+    let trade_stop = OrderBuilder::sell("dave", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .build();
+    let mut book = TriggerBook::new(vec![index_stop.clone(), trade_stop.clone()]);
+    assert_eq!(book.index_markets(), vec!["BTC/USDC".to_string()]);
+
+    // A wick in the last trade only reaches the last-trade stop
+    book.record_trade("BTC/USDC", usd(48_000));
+    assert_eq!(book.take_triggered(), vec![trade_stop]);
+    assert_eq!(book.reference_price(&index_stop), None);
+
+    book.record_index_price("BTC/USDC", Some(usd(49_500)));
+    assert!(book.take_triggered().is_empty());
+    // A stale index price is forgotten, and triggers nothing
+    book.record_index_price("BTC/USDC", None);
+    assert_eq!(book.reference_price(&index_stop), None);
+
+    book.record_index_price("BTC/USDC", Some(usd(49_000)));
+    assert_eq!(book.reference_price(&index_stop), Some(usd(49_000)));
+    assert_eq!(book.take_triggered(), vec![index_stop]);
+    assert!(book.is_empty());
+}
+
+#[test]
+fn test_trigger_book_cancels() {
+    let master = "carol";
+    let sub_account = SubAccount::address_of(master, "bot");
+    let stop = OrderBuilder::sell(&sub_account, "BTC/USDC")
+        .stop_market(usd(49_000))
+        .build();
+    let other = OrderBuilder::sell("dave", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .build();
+    let mut book = TriggerBook::new(vec![stop.clone(), other.clone()]);
+
+    // Another user's stop is not found, and stays put
+    assert!(matches!(
+        book.cancel_order(stop.id, "dave"),
+        Err(ExchangeError::OrderNotFound)
+    ));
+    // A master cancels its sub-account's stops
+    assert_eq!(book.cancel_order(stop.id, master).unwrap(), stop);
+    assert!(book.cancel_order(stop.id, master).is_err());
+
+    assert!(book.cancel_all_orders("dave", Some("ETH/USDC")).is_empty());
+    assert_eq!(book.cancel_market_orders(None), vec![other]);
+    assert!(book.is_empty());
+}
+
+// ============================================================================
+// Engine Stop Order Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_stop_market_triggers_and_fills() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+
+    engine
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(49_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    // Waits with its size locked, counted as an open order
+    let stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_500))
+        .size(BTC / 2)
+        .build();
+    let placed = engine.place_order(stop.clone()).await.unwrap();
+    assert_eq!(placed.order.status, OrderStatus::Pending);
+    assert_eq!(placed.order.order_type, OrderType::StopMarket);
+    assert!(placed.trades.is_empty());
+    assert_eq!(
+        db.get_balance("carol", "BTC").await.unwrap().open_interest,
+        BTC / 2
+    );
+    assert_eq!(
+        db.count_open_orders("carol", None).await.unwrap(),
+        vec![("BTC/USDC".to_string(), 1)]
+    );
+
+    // A trade at 49,000 reaches the trigger, and the stop sells into the bid
+    let sell = OrderBuilder::sell("bob", "BTC/USDC")
+        .limit(usd(49_000))
+        .size(BTC / 10)
+        .build();
+    engine.place_order(sell).await.unwrap();
+    let filled = wait_for_activation(&mut engine, stop.id, OrderStatus::Filled).await;
+    assert_eq!(filled.order_type, OrderType::Market);
+    assert_eq!(filled.filled_size, BTC / 2);
+
+    let stored = db.get_order(&stop.id).await.unwrap();
+    assert_eq!(stored.status, OrderStatus::Filled);
+    assert_eq!(stored.order_type, OrderType::Market);
+    assert_eq!(stored.trigger_price, Some(usd(49_500)));
+    let carol_btc = db.get_balance("carol", "BTC").await.unwrap();
+    assert_eq!(
+        (carol_btc.amount, carol_btc.open_interest),
+        (10 * BTC - BTC / 2, 0)
+    );
+    assert_eq!(
+        db.get_balance("carol", "USDC").await.unwrap().amount,
+        usd(1_000_000 + 24_500)
+    );
+}
+
+#[tokio::test]
+async fn test_triggered_stop_limit_rests_and_can_trigger_more() {
+    let db = memory_market(&["alice", "bob", "carol", "dave"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+    engine
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+
+    // Carol's stop buys at 51,000 on a trade there, which dave's stop
+    // above it waits on
+    let carol = OrderBuilder::buy("carol", "BTC/USDC")
+        .stop_limit(usd(51_000), usd(51_000))
+        .size(BTC / 10)
+        .build();
+    let dave = OrderBuilder::buy("dave", "BTC/USDC")
+        .stop_market(usd(51_000))
+        .price(usd(52_000))
+        .size(BTC / 10)
+        .build();
+    engine.place_order(carol.clone()).await.unwrap();
+    engine.place_order(dave.clone()).await.unwrap();
+    engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(51_500))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    engine
+        .print_trade("alice", "bob", usd(51_000), BTC / 10)
+        .await;
+
+    // Nothing at 51,000 is left for carol, so her order rests; dave's market
+    // order takes bob's ask
+    let resting = wait_for_activation(&mut engine, carol.id, OrderStatus::Pending).await;
+    assert_eq!(resting.order_type, OrderType::Limit);
+    let filled = wait_for_activation(&mut engine, dave.id, OrderStatus::Filled).await;
+    assert_eq!(filled.order_type, OrderType::Market);
+
+    let recovered = db
+        .get_recoverable_orders_for_market("BTC/USDC")
+        .await
+        .unwrap();
+    assert!(recovered.iter().any(|order| order.id == carol.id));
+    engine
+        .cancel_order(carol.id, "carol".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_balance("carol", "USDC").await.unwrap().open_interest,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_fok_stop_is_killed_when_the_book_cannot_fill_it() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+    engine
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(49_000))
+                .size(BTC / 5)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_500))
+        .size(BTC / 2)
+        .fok()
+        .build();
+    engine.place_order(stop.clone()).await.unwrap();
+
+    // The trade that triggers it leaves a tenth of a BTC bid, short of its half
+    engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(49_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let killed = wait_for_activation(&mut engine, stop.id, OrderStatus::Cancelled).await;
+    assert_eq!(killed.filled_size, 0);
+
+    assert_eq!(
+        db.get_order(&stop.id).await.unwrap().status,
+        OrderStatus::Cancelled
+    );
+    let carol_btc = db.get_balance("carol", "BTC").await.unwrap();
+    assert_eq!((carol_btc.amount, carol_btc.open_interest), (10 * BTC, 0));
+    let snapshot = engine.orderbooks.read().await.snapshots().remove(0);
+    assert_eq!(
+        snapshot.bids.iter().map(|l| l.size).collect::<Vec<_>>(),
+        vec![BTC / 10]
+    );
+}
+
+#[tokio::test]
+async fn test_waiting_stops_cancel() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let engine = TestEngine::spawn(db.clone());
+    engine
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+
+    let stop = OrderBuilder::buy("carol", "BTC/USDC")
+        .stop_limit(usd(51_000), usd(51_500))
+        .size(BTC / 10)
+        .build();
+    engine.place_order(stop.clone()).await.unwrap();
+    assert_eq!(
+        db.get_balance("carol", "USDC").await.unwrap().open_interest,
+        usd(5_150)
+    );
+
+    // Only their owner can cancel them
+    assert!(engine
+        .cancel_order(stop.id, "bob".to_string())
+        .await
+        .is_err());
+    engine
+        .cancel_order(stop.id, "carol".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_balance("carol", "USDC").await.unwrap().open_interest,
+        0
+    );
+    assert_eq!(
+        db.get_order(&stop.id).await.unwrap().status,
+        OrderStatus::Cancelled
+    );
+
+    // Halting the market cancels them along with the book
+    let stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .size(BTC / 10)
+        .build();
+    engine.place_order(stop.clone()).await.unwrap();
+    let cancelled = engine
+        .set_market_status("BTC/USDC", MarketStatus::Halted)
+        .await
+        .unwrap();
+    assert!(cancelled.cancelled_order_ids.contains(&stop.id.to_string()));
+    let stored = db.get_order(&stop.id).await.unwrap();
+    assert_eq!(stored.status, OrderStatus::Cancelled);
+    assert_eq!(stored.cancel_reason, Some(CancelReason::MarketHalted));
+    assert_eq!(
+        db.get_balance("carol", "BTC").await.unwrap().open_interest,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_index_stop_ignores_trade_wicks() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let mut engine = TestEngine::spawn(db.clone());
+
+    engine
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+    record_index_price(&engine, usd(50_000));
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(49_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_500))
+        .trigger_source(TriggerSource::Index)
+        .size(BTC / 2)
+        .build();
+    let placed = engine.place_order(stop.clone()).await.unwrap();
+    assert_eq!(placed.order.trigger_source, TriggerSource::Index);
+
+    // A trade at 49,000 on the thin book leaves the index stop waiting
+    engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(49_000))
+                .size(BTC / 10)
+                .build(),
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(1_500)).await;
+    let stored = db.get_order(&stop.id).await.unwrap();
+    assert_eq!(stored.status, OrderStatus::Pending);
+    assert_eq!(stored.order_type, OrderType::StopMarket);
+    assert_eq!(stored.trigger_source, TriggerSource::Index);
+
+    // The index reaching the trigger sells into the bid
+    record_index_price(&engine, usd(49_400));
+    let filled = wait_for_activation(&mut engine, stop.id, OrderStatus::Filled).await;
+    assert_eq!(filled.order_type, OrderType::Market);
+    assert_eq!(filled.filled_size, BTC / 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_housekeeping_triggers_stops_reached_before_a_restart() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    TestEngine::spawn(db.clone())
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+
+    // Stored while the engine was down, and already reached by the last trade
+    let stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_limit(usd(51_000), usd(50_500))
+        .size(BTC / 2)
+        .build();
+    db.lock_balance("carol", "BTC", BTC / 2).await.unwrap();
+    db.create_order(&stop).await.unwrap();
+
+    let mut engine = TestEngine::spawn(db.clone());
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(
+        db.get_order(&stop.id).await.unwrap().order_type,
+        OrderType::StopLimit
+    );
+
+    // Nothing trades, but the next housekeeping pass triggers it onto the book
+    tokio::time::sleep(HOUSEKEEPING_INTERVAL).await;
+    let resting = wait_for_activation(&mut engine, stop.id, OrderStatus::Pending).await;
+    assert_eq!(resting.order_type, OrderType::Limit);
+    assert_eq!(
+        db.get_balance("carol", "BTC").await.unwrap().open_interest,
+        BTC / 2
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_stops_rejected() {
+    let db = memory_market(&["alice", "bob", "carol"]).await;
+    let engine = TestEngine::spawn(db.clone());
+    engine
+        .print_trade("alice", "bob", usd(50_000), BTC / 10)
+        .await;
+
+    let rejected = |order: Order, reason: &'static str| {
+        let engine = &engine;
+        async move {
+            let err = engine.place_order(order).await.unwrap_err();
+            assert!(err.contains(reason), "{}", err);
+        }
+    };
+    let mut no_trigger = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .build();
+    no_trigger.trigger_price = None;
+    rejected(no_trigger, "needs a trigger price").await;
+    rejected(
+        OrderBuilder::buy("carol", "BTC/USDC")
+            .trigger_price(usd(51_000))
+            .build(),
+        "takes no trigger price",
+    )
+    .await;
+    rejected(
+        OrderBuilder::sell("carol", "BTC/USDC")
+            .stop_market(usd(50_000))
+            .build(),
+        "already reached",
+    )
+    .await;
+    rejected(
+        OrderBuilder::buy("carol", "BTC/USDC")
+            .stop_limit(usd(49_000), usd(49_000))
+            .build(),
+        "already reached",
+    )
+    .await;
+    let index_stop = OrderBuilder::sell("carol", "BTC/USDC")
+        .stop_market(usd(49_000))
+        .trigger_source(TriggerSource::Index)
+        .build();
+    rejected(index_stop.clone(), "no fresh index price").await;
+    record_index_price(&engine, usd(48_000));
+    rejected(index_stop, "already reached by the index price").await;
+
+    // Nothing was locked for them
+    let carol_usdc = db.get_balance("carol", "USDC").await.unwrap();
+    assert_eq!(carol_usdc.open_interest, 0);
+    assert_eq!(
+        db.get_balance("carol", "BTC").await.unwrap().open_interest,
+        0
+    );
+}
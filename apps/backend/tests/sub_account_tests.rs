@@ -0,0 +1,203 @@
+use backend::engine::orderbook::Orderbooks;
+use backend::errors::ExchangeError;
+use backend::models::domain::{Balance, SubAccount, UserStatus};
+use backend::sub_accounts::{aggregate_balances, validate_master, validate_name};
+use chrono::Utc;
+use exchange_test_utils::{OrderBuilder, TestDb, TestEngine};
+
+fn balance(user: &str, token: &str, amount: u128, open_interest: u128) -> Balance {
+    Balance {
+        user_address: user.to_string(),
+        token_ticker: token.to_string(),
+        amount,
+        open_interest,
+        updated_at: Utc::now(),
+    }
+}
+
+// ============================================================================
+// Address and Validation Tests
+// ============================================================================
+
+#[test]
+fn test_sub_account_addresses_name_their_master() {
+    let address = SubAccount::address_of("0xabc", "desk-1");
+    assert_eq!(address, "0xabc:desk-1");
+    assert_eq!(SubAccount::master_of(&address), Some("0xabc"));
+    assert_eq!(SubAccount::master_of("0xabc"), None);
+    assert_eq!(SubAccount::owner_of(&address), "0xabc");
+    assert_eq!(SubAccount::owner_of("0xabc"), "0xabc");
+
+    assert!(SubAccount::owns("0xabc", &address));
+    assert!(SubAccount::owns(&address, &address));
+    assert!(!SubAccount::owns(&address, "0xabc"));
+    assert!(!SubAccount::owns("0xabc:other", &address));
+    assert!(!SubAccount::owns("0xdef", &address));
+}
+
+#[test]
+fn test_sub_account_names_and_masters_are_validated() {
+    assert!(validate_name("desk_1").is_ok());
+    assert!(validate_name("").is_err());
+    assert!(validate_name("has:colon").is_err());
+    assert!(validate_name(&"x".repeat(33)).is_err());
+
+    assert!(validate_master("0xabc").is_ok());
+    // Sub-accounts don't nest, and the system accounts have none
+    assert!(validate_master("0xabc:desk").is_err());
+    assert!(validate_master("system").is_err());
+}
+
+#[test]
+fn test_aggregate_balances_sum_per_token() {
+    let balances = vec![
+        balance("0xabc", "USDC", 1_000, 100),
+        balance("0xabc", "BTC", 5, 0),
+        balance("0xabc:desk", "USDC", 500, 50),
+    ];
+
+    assert_eq!(
+        aggregate_balances(&balances),
+        vec![("BTC".to_string(), 5, 0), ("USDC".to_string(), 1_500, 150)]
+    );
+}
+
+// ============================================================================
+// Ownership Tests
+// ============================================================================
+
+#[test]
+fn test_masters_own_their_sub_accounts_orders() {
+    let mut orderbooks = Orderbooks::new();
+    let desk = SubAccount::address_of("alice", "desk");
+    let book = orderbooks.get_or_create("BTC/USDC");
+    let order = OrderBuilder::buy(&desk, "BTC/USDC")
+        .limit(50_000_000_000)
+        .build();
+    let own = OrderBuilder::buy("alice", "BTC/USDC")
+        .limit(49_000_000_000)
+        .build();
+    book.add_order(order.clone());
+    book.add_order(own.clone());
+
+    // A sibling or another user can't see or cancel it
+    let sibling = SubAccount::address_of("alice", "other");
+    assert!(orderbooks.queue_position(order.id, &sibling).is_err());
+    assert!(orderbooks.cancel_order(order.id, "bob").is_err());
+    // Nor can a sub-account touch its master's orders
+    assert!(orderbooks.cancel_order(own.id, &desk).is_err());
+
+    assert!(orderbooks.queue_position(order.id, "alice").is_ok());
+    let cancelled = orderbooks.cancel_order(order.id, "alice").unwrap();
+    assert_eq!(cancelled.user_address, desk);
+}
+
+// ============================================================================
+// Sub-Account Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_sub_accounts_hold_isolated_funds_and_orders() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let engine = TestEngine::new(&test_db).await;
+    let market = test_db
+        .db
+        .create_market(
+            "BTC".to_string(),
+            "USDC".to_string(),
+            1000,
+            1000000,
+            1000000,
+            10,
+            20,
+        )
+        .await
+        .expect("Failed to create market");
+
+    let desk = test_db
+        .db
+        .create_sub_account("buyer", "desk")
+        .await
+        .unwrap();
+    assert_eq!(desk.address, "buyer:desk");
+    assert!(matches!(
+        test_db.db.create_sub_account("buyer", "desk").await,
+        Err(ExchangeError::SubAccountExists { .. })
+    ));
+    assert_eq!(
+        test_db.db.list_sub_accounts("buyer").await.unwrap(),
+        vec![desk.clone()]
+    );
+
+    // The sub-account starts empty and is funded from its master
+    let master_before = test_db.db.get_balance("buyer", "USDC").await.unwrap();
+    let (transfer, from, to) = test_db
+        .db
+        .transfer_between_accounts("buyer", "buyer", &desk.address, "USDC", 1_000_000_000)
+        .await
+        .unwrap();
+    assert_eq!(transfer.master_address, "buyer");
+    assert_eq!(from.amount, master_before.amount - 1_000_000_000);
+    assert_eq!(to.amount, 1_000_000_000);
+    assert!(matches!(
+        test_db
+            .db
+            .transfer_between_accounts("buyer", &desk.address, "buyer", "USDC", 1_000_000_001)
+            .await,
+        Err(ExchangeError::InsufficientBalance { .. })
+    ));
+
+    // Its order locks its own balance, and its master can cancel it
+    let bid = OrderBuilder::buy(&desk.address, &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    let placed = engine.place_order(bid).await.expect("Failed to place bid");
+    let locked = test_db.db.get_balance(&desk.address, "USDC").await.unwrap();
+    assert_eq!(locked.open_interest, 500_000_000);
+    assert_eq!(
+        test_db
+            .db
+            .get_balance("buyer", "USDC")
+            .await
+            .unwrap()
+            .open_interest,
+        0
+    );
+
+    let order_id = placed.order.id.parse().unwrap();
+    assert!(engine
+        .cancel_order(order_id, "seller".to_string())
+        .await
+        .is_err());
+    engine
+        .cancel_order(order_id, "buyer".to_string())
+        .await
+        .expect("Master cancels its sub-account's order");
+    let unlocked = test_db.db.get_balance(&desk.address, "USDC").await.unwrap();
+    assert_eq!(unlocked.open_interest, 0);
+
+    // Reporting covers the master and its sub-accounts together
+    let group = test_db
+        .db
+        .list_account_group_balances("buyer")
+        .await
+        .unwrap();
+    let usdc: u128 = aggregate_balances(&group)
+        .into_iter()
+        .find(|(ticker, _, _)| ticker == "USDC")
+        .map(|(_, amount, _)| amount)
+        .unwrap();
+    assert_eq!(usdc, master_before.amount);
+
+    // Freezing the master stops its sub-accounts trading too
+    engine
+        .set_user_status("buyer", UserStatus::Frozen, false)
+        .await
+        .unwrap();
+    let bid = OrderBuilder::buy(&desk.address, &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    assert!(engine.place_order(bid).await.is_err());
+}
@@ -0,0 +1,231 @@
+use backend::models::domain::{
+    DepthMetrics, Order, OrderStatus, OrderType, Side, SurveillanceKind, TimeInForce, Trade,
+    TradePair, TriggerSource,
+};
+use backend::surveillance::{
+    self_matching, spoofing, wash_trading, SurveillanceThresholds, Surveiller,
+    SURVEILLANCE_WINDOW_SECS,
+};
+use chrono::{DateTime, Duration, Utc};
+use exchange_test_utils::{helpers, TestDb};
+use std::collections::HashMap;
+
+const MARKET: &str = "BTC/USDC";
+
+fn pair(buyer: &str, seller: &str, trades: u64, volume: u128) -> TradePair {
+    TradePair {
+        market_id: MARKET.to_string(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        trades,
+        volume,
+    }
+}
+
+fn touch(secs: i64, best_bid: u128, best_ask: u128) -> DepthMetrics {
+    DepthMetrics {
+        market_id: MARKET.to_string(),
+        timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+        best_bid: Some(best_bid),
+        best_ask: Some(best_ask),
+        bid_depth: 0,
+        ask_depth: 0,
+        imbalance_bps: 0,
+    }
+}
+
+fn order(user: &str, side: Side, price: u128, filled: bool, secs: i64) -> Order {
+    let created_at = DateTime::from_timestamp(secs, 0).unwrap();
+    Order {
+        id: uuid::Uuid::new_v4(),
+        user_address: user.to_string(),
+        market_id: MARKET.to_string(),
+        price,
+        size: 1_000,
+        side,
+        order_type: OrderType::Limit,
+        status: if filled {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::Cancelled
+        },
+        filled_size: if filled { 1_000 } else { 0 },
+        created_at,
+        updated_at: created_at,
+        cancel_reason: None,
+        trigger_price: None,
+        trigger_source: TriggerSource::LastTrade,
+        time_in_force: TimeInForce::Gtc,
+        expires_at: None,
+    }
+}
+
+// ============================================================================
+// Detection Tests
+// ============================================================================
+
+#[test]
+fn test_self_matching_flags_trades_with_yourself() {
+    let pairs = [
+        pair("alice", "alice", 2, 5_000),
+        pair("alice", "bob", 7, 9_000),
+    ];
+    let findings = self_matching(&pairs, &SurveillanceThresholds::default());
+
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].kind, SurveillanceKind::SelfMatching);
+    assert_eq!(findings[0].subject, "alice");
+    assert_eq!(findings[0].count, 2);
+    assert_eq!(findings[0].volume, 5_000);
+}
+
+#[test]
+fn test_wash_trading_groups_accounts_by_beneficial_owner() {
+    let owners: HashMap<String, String> = [
+        ("alice", "acme"),
+        ("alice2", "acme"),
+        ("bob", "bobco"),
+        ("bob2", "bobco"),
+    ]
+    .into_iter()
+    .map(|(account, owner)| (account.to_string(), owner.to_string()))
+    .collect();
+    let pairs = [
+        // acme trades with itself both ways, three times in all
+        pair("alice", "alice2", 2, 2_000),
+        pair("alice2", "alice", 1, 1_000),
+        // bobco only once, under the threshold
+        pair("bob", "bob2", 1, 1_000),
+        // Different owners, or an account without one, aren't wash trades
+        pair("alice", "bob", 10, 10_000),
+        pair("alice", "carol", 10, 10_000),
+        // Self-matching is reported on its own
+        pair("alice", "alice", 10, 10_000),
+    ];
+    let findings = wash_trading(&pairs, &owners, &SurveillanceThresholds::default());
+
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].kind, SurveillanceKind::WashTrading);
+    assert_eq!(findings[0].subject, "acme");
+    assert_eq!(findings[0].accounts, vec!["alice", "alice2"]);
+    assert_eq!(findings[0].count, 3);
+    assert_eq!(findings[0].volume, 3_000);
+}
+
+#[test]
+fn test_spoofing_counts_cancels_near_the_touch() {
+    let thresholds = SurveillanceThresholds {
+        spoof_cancels: 3,
+        spoof_cancels_per_fill: 4,
+        near_touch_bps: 10,
+        ..Default::default()
+    };
+    let touches = HashMap::from([(
+        MARKET.to_string(),
+        vec![touch(100, 100_000, 100_100), touch(200, 90_000, 90_100)],
+    )]);
+
+    let mut orders = vec![
+        // Within 10 bps behind the best bid and ask, then cancelled
+        order("spoofer", Side::Buy, 99_900, false, 110),
+        order("spoofer", Side::Sell, 100_200, false, 120),
+        order("spoofer", Side::Buy, 100_000, false, 130),
+        // Judged against the later touch
+        order("spoofer", Side::Sell, 90_100, false, 210),
+        // Far from the touch, or placed before any sample: not counted
+        order("spoofer", Side::Buy, 95_000, false, 140),
+        order("spoofer", Side::Buy, 100_000, false, 50),
+        // An honest account whose orders near the touch fill
+        order("trader", Side::Buy, 100_000, true, 110),
+        order("trader", Side::Buy, 100_000, false, 120),
+        order("trader", Side::Buy, 100_000, false, 130),
+        order("trader", Side::Buy, 100_000, false, 140),
+    ];
+    // A sample older than a minute is no longer the touch
+    orders.push(order("spoofer", Side::Buy, 90_000, false, 300));
+
+    let findings = spoofing(&orders, &touches, &thresholds);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].kind, SurveillanceKind::Spoofing);
+    assert_eq!(findings[0].subject, "spoofer");
+    assert_eq!(findings[0].count, 4);
+    assert_eq!(findings[0].volume, 4_000);
+}
+
+// ============================================================================
+// Surveillance Job Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_closed_windows_are_surveilled_once() {
+    let test_db = TestDb::setup().await.expect("Failed to setup test DB");
+    let market = helpers::create_market_with_tokens(&test_db, "BTC", "USDC")
+        .await
+        .unwrap();
+    for user in ["alice", "alice2", "bob"] {
+        helpers::create_user(&test_db, user).await.unwrap();
+    }
+    test_db
+        .db
+        .set_beneficial_owner("alice", Some("acme"))
+        .await
+        .unwrap();
+    test_db
+        .db
+        .set_beneficial_owner("alice2", Some("acme"))
+        .await
+        .unwrap();
+
+    // Trades in the middle of the last closed window
+    let now = Utc::now();
+    let window_end = (now - Duration::minutes(1)).timestamp() / SURVEILLANCE_WINDOW_SECS
+        * SURVEILLANCE_WINDOW_SECS;
+    let at = DateTime::from_timestamp(window_end - SURVEILLANCE_WINDOW_SECS / 2, 0).unwrap();
+    let trade = |buyer: &str, seller: &str| Trade {
+        market_id: market.id.clone(),
+        buyer_address: buyer.to_string(),
+        seller_address: seller.to_string(),
+        timestamp: at,
+        ..helpers::sample_trade(&market.id)
+    };
+    let trades = vec![
+        trade("alice", "alice2"),
+        trade("alice2", "alice"),
+        trade("alice", "alice2"),
+        trade("bob", "bob"),
+        trade("alice", "bob"),
+    ];
+    test_db
+        .db
+        .insert_trades_to_clickhouse(&trades)
+        .await
+        .unwrap();
+
+    let surveiller = Surveiller::new(test_db.db.clone());
+    assert_eq!(surveiller.survey_closed_windows(now).await.unwrap(), 2);
+    // Windows already surveilled are skipped
+    assert_eq!(surveiller.survey_closed_windows(now).await.unwrap(), 0);
+
+    let alerts = test_db
+        .db
+        .list_surveillance_alerts(None, Some(&market.id), 10)
+        .await
+        .unwrap();
+    assert_eq!(alerts.len(), 2);
+    let wash = alerts
+        .iter()
+        .find(|alert| alert.kind == SurveillanceKind::WashTrading)
+        .expect("Wash trading alert");
+    assert_eq!(wash.subject, "acme");
+    assert_eq!(wash.accounts, vec!["alice", "alice2"]);
+    assert_eq!(wash.count, 3);
+    assert_eq!(wash.window_end.timestamp(), window_end);
+
+    let self_matches = test_db
+        .db
+        .list_surveillance_alerts(Some(SurveillanceKind::SelfMatching), None, 10)
+        .await
+        .unwrap();
+    assert_eq!(self_matches.len(), 1);
+    assert_eq!(self_matches[0].subject, "bob");
+}
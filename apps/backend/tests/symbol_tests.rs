@@ -0,0 +1,58 @@
+use backend::db::Db;
+use backend::models::api::ApiMarket;
+use backend::symbols::Symbols;
+
+async fn create_market(db: &Db, base_ticker: &str, quote_ticker: &str) {
+    for ticker in [base_ticker, quote_ticker] {
+        if db.get_token(ticker).await.is_err() {
+            db.create_token(ticker.to_string(), 6, ticker.to_string())
+                .await
+                .unwrap();
+        }
+    }
+    db.create_market(
+        base_ticker.to_string(),
+        quote_ticker.to_string(),
+        1,
+        1,
+        1,
+        0,
+        0,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_symbols_resolve_aliases_of_listed_markets() {
+    let db = Db::in_memory().unwrap();
+    create_market(&db, "BTC", "USDC").await;
+    create_market(&db, "ETH", "USDC").await;
+    let symbols = Symbols::load(&db).await.unwrap();
+
+    for alias in ["BTC/USDC", "btc-usdc", "BTC_USDC", "BTCUSDC", "1"] {
+        assert_eq!(symbols.canonical(alias), "BTC/USDC", "{}", alias);
+    }
+    assert_eq!(symbols.canonical("2"), "ETH/USDC");
+
+    // Unknown markets pass through, to fail as market not found
+    assert_eq!(symbols.canonical("sol-usdc"), "sol-usdc");
+}
+
+#[tokio::test]
+async fn test_symbols_number_markets_and_refresh() {
+    let db = Db::in_memory().unwrap();
+    create_market(&db, "BTC", "USDC").await;
+    let symbols = Symbols::load(&db).await.unwrap();
+
+    let market: ApiMarket = db.get_market("BTC/USDC").await.unwrap().into();
+    assert_eq!(market.numeric_id, None);
+    assert_eq!(symbols.numbered(market).numeric_id, Some(1));
+
+    // Markets listed after loading are known once refreshed
+    create_market(&db, "SOL", "USDC").await;
+    assert_eq!(symbols.canonical("SOLUSDC"), "SOLUSDC");
+    symbols.refresh(&db).await.unwrap();
+    assert_eq!(symbols.canonical("SOLUSDC"), "SOL/USDC");
+    assert_eq!(symbols.canonical("2"), "SOL/USDC");
+}
@@ -0,0 +1,42 @@
+use axum::http::Request;
+use backend::telemetry::HttpMakeSpan;
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tower_http::trace::MakeSpan;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+// ============================================================================
+// Trace Propagation Tests
+// ============================================================================
+
+#[test]
+fn test_http_spans_continue_the_callers_trace() {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let provider = SdkTracerProvider::builder().build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+    tracing::subscriber::with_default(subscriber, || {
+        let request = Request::post("/api/trade")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .body(())
+            .unwrap();
+        let span = HttpMakeSpan.make_span(&request);
+        let context = span.context();
+        assert_eq!(
+            context.span().span_context().trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+
+        // Without a header the request starts its own trace
+        let request = Request::get("/api/health").body(()).unwrap();
+        let span = HttpMakeSpan.make_span(&request);
+        let trace_id = span.context().span().span_context().trace_id();
+        assert_ne!(trace_id.to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    });
+}
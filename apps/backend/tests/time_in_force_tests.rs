@@ -0,0 +1,165 @@
+use backend::models::domain::{OrderStatus, TimeInForce};
+use exchange_test_utils::{memory_market, usd, OrderBuilder, TestEngine, BTC};
+
+// ============================================================================
+// Immediate-or-Cancel Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_ioc_remainder_is_cancelled_instead_of_resting() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    // Alice takes the one BTC offered; the other two are cancelled
+    let placed = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(3 * BTC)
+                .ioc()
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(placed.trades.len(), 1);
+    assert_eq!(placed.order.time_in_force, TimeInForce::Ioc);
+    assert_eq!(placed.order.status, OrderStatus::Filled);
+    assert_eq!(placed.order.filled_size, BTC.to_string());
+
+    let stored = db
+        .get_order(&placed.order.id.parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(stored.status, OrderStatus::Filled);
+    assert_eq!(stored.time_in_force, TimeInForce::Ioc);
+    assert!(db
+        .count_open_orders("alice", Some("BTC/USDC"))
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(engine.orderbooks.read().await.snapshots()[0]
+        .bids
+        .is_empty());
+    assert_eq!(
+        db.get_balance("alice", "USDC").await.unwrap().open_interest,
+        0
+    );
+    assert_eq!(
+        db.get_balance("alice", "BTC").await.unwrap().amount,
+        11 * BTC
+    );
+}
+
+#[tokio::test]
+async fn test_ioc_without_liquidity_is_cancelled() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    // A resting bid below the IOC sell's limit doesn't match it
+    engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(49_000))
+                .size(BTC)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let placed = engine
+        .place_order(
+            OrderBuilder::sell("bob", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(BTC)
+                .ioc()
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(placed.trades.is_empty());
+    assert_eq!(placed.order.status, OrderStatus::Cancelled);
+    assert_eq!(
+        db.get_order(&placed.order.id.parse().unwrap())
+            .await
+            .unwrap()
+            .status,
+        OrderStatus::Cancelled
+    );
+
+    let snapshot = engine.orderbooks.read().await.snapshots().remove(0);
+    assert!(snapshot.asks.is_empty());
+    assert_eq!(snapshot.bids.len(), 1);
+    let balance = db.get_balance("bob", "BTC").await.unwrap();
+    assert_eq!((balance.amount, balance.open_interest), (10 * BTC, 0));
+}
+
+// ============================================================================
+// Fill-or-Kill Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_fok_is_refused_unless_it_fills_completely() {
+    let db = memory_market(&["alice", "bob"]).await;
+    let engine = TestEngine::spawn(db.clone());
+
+    for price in [usd(50_000), usd(50_100)] {
+        engine
+            .place_order(
+                OrderBuilder::sell("bob", "BTC/USDC")
+                    .limit(price)
+                    .size(BTC)
+                    .build(),
+            )
+            .await
+            .unwrap();
+    }
+
+    // Two BTC are offered, but only one at or below the limit
+    let err = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_000))
+                .size(2 * BTC)
+                .fok()
+                .build(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("can only fill 100000000"), "{}", err);
+    assert!(db
+        .get_user_orders("alice", None, None, 10)
+        .await
+        .unwrap()
+        .is_empty());
+    let balance = db.get_balance("alice", "USDC").await.unwrap();
+    assert_eq!(balance.open_interest, 0);
+    let snapshot = engine.orderbooks.read().await.snapshots().remove(0);
+    assert_eq!(snapshot.asks.len(), 2);
+
+    // Paying up to the second ask fills it all
+    let placed = engine
+        .place_order(
+            OrderBuilder::buy("alice", "BTC/USDC")
+                .limit(usd(50_100))
+                .size(2 * BTC)
+                .fok()
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(placed.trades.len(), 2);
+    assert_eq!(placed.order.status, OrderStatus::Filled);
+    assert_eq!(placed.order.time_in_force, TimeInForce::Fok);
+    assert!(engine.orderbooks.read().await.snapshots()[0]
+        .asks
+        .is_empty());
+}
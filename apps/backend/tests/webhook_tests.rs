@@ -0,0 +1,308 @@
+use axum::{body::Bytes, extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+use backend::engine::markets::MarketRegistry;
+use backend::models::api::{UserRequest, UserResponse};
+use backend::models::domain::{CancelReason, EngineEvent};
+use backend::webhooks::{self, MAX_DELIVERY_ATTEMPTS, MAX_WEBHOOKS_PER_USER};
+use exchange_protocol::events::{
+    BusEvent, WebhookDelivery, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use exchange_test_utils::{helpers, TestServer, TestWallet};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+// ============================================================================
+// Signing Tests
+// ============================================================================
+
+#[test]
+fn test_signature_covers_timestamp_and_body() {
+    // HMAC-SHA256("key", "1700000000.{}")
+    let signature = webhooks::sign("key", 1_700_000_000, b"{}");
+    assert_eq!(
+        signature,
+        "9d713ed406bb7076d4123f0dc2c39d2df5c654ed4b0cd56b52c8b4c940bd63ae"
+    );
+
+    assert_ne!(signature, webhooks::sign("other", 1_700_000_000, b"{}"));
+    assert_ne!(signature, webhooks::sign("key", 1_700_000_001, b"{}"));
+    assert_ne!(signature, webhooks::sign("key", 1_700_000_000, b"{ }"));
+}
+
+#[test]
+fn test_webhook_urls_must_be_http() {
+    for valid in ["https://example.com/hooks", "http://127.0.0.1:8080/fills"] {
+        assert!(webhooks::validate_url(valid).is_ok(), "{}", valid);
+    }
+    for invalid in [
+        "",
+        "example.com/hooks",
+        "ftp://example.com",
+        "file:///etc/passwd",
+    ] {
+        assert!(webhooks::validate_url(invalid).is_err(), "{}", invalid);
+    }
+}
+
+// ============================================================================
+// Delivery Tests
+// ============================================================================
+
+/// Receiver that answers every delivery with `status` and forwards it with its headers
+async fn start_receiver(
+    status: StatusCode,
+) -> (String, mpsc::UnboundedReceiver<(HeaderMap, Bytes)>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(
+                move |State(tx): State<mpsc::UnboundedSender<(HeaderMap, Bytes)>>,
+                      headers: HeaderMap,
+                      body: Bytes| async move {
+                    let _ = tx.send((headers, body));
+                    status
+                },
+            ),
+        )
+        .with_state(tx);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/hook", addr), rx)
+}
+
+async fn post_user(server: &TestServer, body: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(server.url("/api/user"))
+        .json(&body)
+        .send()
+        .await
+        .expect("Request failed")
+}
+
+fn registration(user: &TestWallet, url: &str) -> Value {
+    let body = json!({
+        "type": "register_webhook",
+        "user_address": user.address(),
+        "url": url,
+    });
+    user.sign::<UserRequest>("/api/user", body)
+}
+
+async fn register(server: &TestServer, user: &TestWallet, url: &str) -> (String, String) {
+    let response = post_user(server, registration(user, url)).await;
+    assert_eq!(response.status(), 200);
+    match response.json::<UserResponse>().await.unwrap() {
+        UserResponse::RegisterWebhook { webhook, secret } => (webhook.id, secret),
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
+
+async fn next_delivery(
+    rx: &mut mpsc::UnboundedReceiver<(HeaderMap, Bytes)>,
+    secret: &str,
+) -> WebhookDelivery {
+    let (headers, body) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("Timed out waiting for delivery")
+        .expect("Receiver closed");
+    let timestamp: i64 = headers[WEBHOOK_TIMESTAMP_HEADER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(
+        headers[WEBHOOK_SIGNATURE_HEADER].to_str().unwrap(),
+        webhooks::sign(secret, timestamp, &body)
+    );
+    serde_json::from_slice(&body).expect("Failed to parse delivery")
+}
+
+#[tokio::test]
+async fn test_fills_and_order_updates_are_delivered_signed() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let (alice, bob) = (TestWallet::new(), TestWallet::new());
+    for user in [&alice, &bob] {
+        helpers::create_user(&server.test_db, user.address())
+            .await
+            .expect("Failed to create user");
+    }
+
+    let (url, mut rx) = start_receiver(StatusCode::OK).await;
+    let (webhook_id, secret) = register(&server, &alice, &url).await;
+
+    // Only alice's events reach her webhook
+    let events = server.engine().event_tx();
+    let mut trade = helpers::sample_trade(&market.id);
+    trade.buyer_address = bob.address().to_string();
+    trade.seller_address = alice.address().to_string();
+    events
+        .send(EngineEvent::OrderCancelled {
+            order_id: uuid::Uuid::new_v4(),
+            user_address: bob.address().to_string(),
+            reason: None,
+        })
+        .unwrap();
+    events
+        .send(EngineEvent::TradeExecuted {
+            market: MarketRegistry::new().intern(&market.id),
+            trade: trade.clone(),
+        })
+        .unwrap();
+
+    let delivery = next_delivery(&mut rx, &secret).await;
+    assert_eq!(delivery.webhook_id, webhook_id);
+    match delivery.event {
+        BusEvent::Trade(fill) => {
+            assert_eq!(fill.id, trade.id.to_string());
+            assert_eq!(fill.role, trade.role_of(alice.address()));
+        }
+        other => panic!("Expected a fill, got {:?}", other),
+    }
+
+    let order_id = uuid::Uuid::new_v4();
+    events
+        .send(EngineEvent::OrderCancelled {
+            order_id,
+            user_address: alice.address().to_string(),
+            reason: Some(CancelReason::MarketHalted),
+        })
+        .unwrap();
+    match next_delivery(&mut rx, &secret).await.event {
+        BusEvent::OrderCancelled {
+            order_id: id,
+            reason,
+            ..
+        } => {
+            assert_eq!(id, order_id.to_string());
+            assert_eq!(reason, Some(CancelReason::MarketHalted));
+        }
+        other => panic!("Expected a cancellation, got {:?}", other),
+    }
+    assert!(rx.try_recv().is_err());
+
+    // The webhook can be listed and removed
+    let response = post_user(
+        &server,
+        json!({ "type": "webhooks", "user_address": alice.address() }),
+    )
+    .await;
+    match response.json::<UserResponse>().await.unwrap() {
+        UserResponse::Webhooks { webhooks } => {
+            assert_eq!(webhooks.len(), 1);
+            assert_eq!(webhooks[0].url, url);
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+    let delete = || {
+        let body = json!({
+            "type": "delete_webhook",
+            "user_address": alice.address(),
+            "webhook_id": webhook_id,
+        });
+        alice.sign::<UserRequest>("/api/user", body)
+    };
+    assert_eq!(post_user(&server, delete()).await.status(), 200);
+    let response = post_user(&server, delete()).await;
+    assert_eq!(response.status(), 404);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "WEBHOOK_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_failed_deliveries_are_retried_then_dead_lettered() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let alice = TestWallet::new();
+    helpers::create_user(&server.test_db, alice.address())
+        .await
+        .expect("Failed to create user");
+
+    let (url, mut rx) = start_receiver(StatusCode::SERVICE_UNAVAILABLE).await;
+    let (webhook_id, secret) = register(&server, &alice, &url).await;
+
+    let mut trade = helpers::sample_trade(&market.id);
+    trade.buyer_address = alice.address().to_string();
+    server
+        .engine()
+        .event_tx()
+        .send(EngineEvent::TradeExecuted {
+            market: MarketRegistry::new().intern(&market.id),
+            trade,
+        })
+        .unwrap();
+
+    // Every attempt carries the same delivery
+    let first = next_delivery(&mut rx, &secret).await;
+    assert!(matches!(first.event, BusEvent::Trade(_)));
+    for _ in 1..MAX_DELIVERY_ATTEMPTS {
+        let retry = next_delivery(&mut rx, &secret).await;
+        assert_eq!(retry.delivery_id, first.delivery_id);
+    }
+
+    let dead_letters = async {
+        loop {
+            let response = post_user(
+                &server,
+                json!({ "type": "webhook_dead_letters", "user_address": alice.address() }),
+            )
+            .await;
+            if let UserResponse::WebhookDeadLetters { dead_letters } =
+                response.json::<UserResponse>().await.unwrap()
+            {
+                if !dead_letters.is_empty() {
+                    return dead_letters;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+    let dead_letters = tokio::time::timeout(Duration::from_secs(5), dead_letters)
+        .await
+        .expect("Delivery was not dead-lettered");
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].webhook_id, webhook_id);
+    assert_eq!(dead_letters[0].event_type, "trade");
+    assert_eq!(dead_letters[0].attempts, MAX_DELIVERY_ATTEMPTS);
+    assert!(dead_letters[0].last_error.contains("503"));
+    let payload: WebhookDelivery = serde_json::from_str(&dead_letters[0].payload).unwrap();
+    assert_eq!(payload.delivery_id, first.delivery_id);
+}
+
+#[tokio::test]
+async fn test_webhook_registration_is_validated() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let alice = TestWallet::new();
+    helpers::create_user(&server.test_db, alice.address())
+        .await
+        .expect("Failed to create user");
+
+    let response = post_user(&server, registration(&alice, "not a url")).await;
+    assert_eq!(response.status(), 400);
+    let nobody = TestWallet::new();
+    let response = post_user(&server, registration(&nobody, "https://example.com")).await;
+    assert_eq!(response.status(), 404);
+    // Registering for someone else takes their signature
+    let mut forged = registration(&nobody, "https://example.com");
+    forged["user_address"] = json!(alice.address());
+    assert_eq!(post_user(&server, forged).await.status(), 401);
+
+    for _ in 0..MAX_WEBHOOKS_PER_USER {
+        let response = post_user(&server, registration(&alice, "https://example.com")).await;
+        assert_eq!(response.status(), 200);
+    }
+    let response = post_user(&server, registration(&alice, "https://example.com")).await;
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,533 @@
+use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+use backend::config::{Config, DepositConfig, DepositTokenConfig, TokenConfig};
+use backend::deposits::DepositToken;
+use backend::models::api::{UserRequest, UserResponse};
+use backend::models::domain::{EngineEvent, WithdrawalStatus};
+use backend::withdrawals::signer::{HttpSigner, Submission, TransferRequest, WithdrawalSigner};
+use backend::withdrawals::{validate_destination, WithdrawalProcessor};
+use exchange_test_utils::{helpers, TestServer, TestWallet};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc};
+
+const USDC_CONTRACT: &str = "0xaf88d065e77c8cc2239327c5edb3a432268e5831";
+const DESTINATION: &str = "0x00000000000000000000000000000000000b0b01";
+
+// ============================================================================
+// Validation Tests
+// ============================================================================
+
+#[test]
+fn test_destinations_must_be_addresses() {
+    for valid in [DESTINATION, "0xAF88d065e77c8cC2239327C5EDb3A432268e5831"] {
+        assert!(validate_destination(valid).is_ok(), "{}", valid);
+    }
+    for invalid in [
+        "",
+        "0x",
+        "af88d065e77c8cc2239327c5edb3a432268e5831",
+        "0xaf88d065e77c8cc2239327c5edb3a432268e583",
+        "0xzf88d065e77c8cc2239327c5edb3a432268e5831",
+        "alice.eth",
+    ] {
+        assert!(validate_destination(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_withdrawal_amounts_scale_to_chain_decimals() {
+    let token = |chain_decimals, exchange_decimals| DepositToken {
+        ticker: "WETH".to_string(),
+        contract: USDC_CONTRACT.to_string(),
+        chain_decimals,
+        exchange_decimals,
+    };
+
+    assert_eq!(token(6, 6).to_chain_amount(1_234_567), Some(1_234_567));
+    assert_eq!(
+        token(18, 8).to_chain_amount(150_000_000),
+        Some(1_500_000_000_000_000_000)
+    );
+    // Digits the contract can't hold are never rounded away
+    assert_eq!(token(6, 8).to_chain_amount(500), Some(5));
+    assert_eq!(token(6, 8).to_chain_amount(501), None);
+    assert_eq!(token(38, 0).to_chain_amount(10), None);
+}
+
+// ============================================================================
+// Signer Tests
+// ============================================================================
+
+/// Signing service answering every transfer with `reply`, forwarding it with its headers
+async fn start_signing_service(
+    reply: Value,
+) -> (
+    String,
+    mpsc::UnboundedReceiver<(HeaderMap, TransferRequest)>,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/transfers",
+            post(
+                move |State(tx): State<mpsc::UnboundedSender<(HeaderMap, TransferRequest)>>,
+                      headers: HeaderMap,
+                      Json(transfer): Json<TransferRequest>| async move {
+                    let _ = tx.send((headers, transfer));
+                    Json(reply)
+                },
+            ),
+        )
+        .with_state(tx);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/transfers", addr), rx)
+}
+
+#[tokio::test]
+async fn test_http_signer_posts_transfers() {
+    let transfer = TransferRequest {
+        withdrawal_id: uuid::Uuid::new_v4().to_string(),
+        chain_id: 42161,
+        token_contract: USDC_CONTRACT.to_string(),
+        destination: DESTINATION.to_string(),
+        amount: "2500000".to_string(),
+    };
+
+    let (url, mut rx) =
+        start_signing_service(json!({ "status": "sent", "tx_hash": "0xabc" })).await;
+    let signer = HttpSigner::new(url).with_token("secret");
+    let submission = signer.submit(&transfer).await.expect("Failed to submit");
+    assert_eq!(
+        submission,
+        Submission::Sent {
+            tx_hash: "0xabc".to_string()
+        }
+    );
+    let (headers, received) = rx.recv().await.unwrap();
+    assert_eq!(headers["authorization"], "Bearer secret");
+    assert_eq!(received, transfer);
+
+    let (url, _rx) =
+        start_signing_service(json!({ "status": "rejected", "reason": "sanctioned" })).await;
+    let submission = HttpSigner::new(url).submit(&transfer).await.unwrap();
+    assert_eq!(
+        submission,
+        Submission::Rejected {
+            reason: "sanctioned".to_string()
+        }
+    );
+
+    assert!(HttpSigner::new("http://127.0.0.1:1/transfers")
+        .submit(&transfer)
+        .await
+        .is_err());
+}
+
+// ============================================================================
+// Processing Tests
+// ============================================================================
+
+type Reply = dyn Fn(&TransferRequest) -> anyhow::Result<Submission> + Send + Sync;
+
+/// Signer that records every transfer and answers with `reply`
+#[derive(Clone)]
+struct ScriptedSigner {
+    transfers: Arc<Mutex<Vec<TransferRequest>>>,
+    reply: Arc<Reply>,
+}
+
+impl ScriptedSigner {
+    fn new(
+        reply: impl Fn(&TransferRequest) -> anyhow::Result<Submission> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            transfers: Arc::default(),
+            reply: Arc::new(reply),
+        }
+    }
+
+    fn transfers(&self) -> Vec<TransferRequest> {
+        self.transfers.lock().unwrap().clone()
+    }
+}
+
+impl WithdrawalSigner for ScriptedSigner {
+    async fn submit(&self, transfer: &TransferRequest) -> anyhow::Result<Submission> {
+        self.transfers.lock().unwrap().push(transfer.clone());
+        (self.reply)(transfer)
+    }
+}
+
+/// Chain head and mined transactions served by the mock node
+struct MockChain {
+    head: u64,
+    /// Block and success of each mined transaction
+    receipts: HashMap<String, (u64, bool)>,
+}
+
+/// JSON-RPC node answering `eth_blockNumber` and `eth_getTransactionReceipt` from `chain`
+async fn start_node(chain: Arc<Mutex<MockChain>>) -> String {
+    async fn rpc(
+        State(chain): State<Arc<Mutex<MockChain>>>,
+        Json(request): Json<Value>,
+    ) -> Json<Value> {
+        let chain = chain.lock().unwrap();
+        let result = match request["method"].as_str().unwrap() {
+            "eth_blockNumber" => json!(format!("0x{:x}", chain.head)),
+            "eth_getTransactionReceipt" => {
+                let tx_hash = request["params"][0].as_str().unwrap();
+                match chain.receipts.get(tx_hash) {
+                    Some((block, success)) => json!({
+                        "transactionHash": tx_hash,
+                        "blockNumber": format!("0x{:x}", block),
+                        "status": if *success { "0x1" } else { "0x0" },
+                    }),
+                    None => Value::Null,
+                }
+            }
+            method => panic!("Unexpected method {}", method),
+        };
+        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+
+    let app = Router::new().route("/", post(rpc)).with_state(chain);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn chain_config() -> Config {
+    Config {
+        markets: Vec::new(),
+        tokens: vec![TokenConfig {
+            ticker: "USDC".to_string(),
+            decimals: 6,
+            name: "USD Coin".to_string(),
+        }],
+        deposits: Some(DepositConfig {
+            chain_id: 42161,
+            confirmations: 3,
+            deposit_addresses: vec!["0x00000000000000000000000000000000000d3905".to_string()],
+            start_block: None,
+            tokens: vec![DepositTokenConfig {
+                ticker: "USDC".to_string(),
+                contract: "0xAF88d065e77c8cC2239327C5EDb3A432268e5831".to_string(),
+                decimals: None,
+            }],
+        }),
+    }
+}
+
+async fn post_user(server: &TestServer, body: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(server.url("/api/user"))
+        .json(&body)
+        .send()
+        .await
+        .expect("Request failed")
+}
+
+/// A funded user on a server with a USDC token, and another user, bob
+async fn setup(amount: u128) -> (TestServer, TestWallet, TestWallet) {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+    let (alice, bob) = (TestWallet::new(), TestWallet::new());
+    for user in [&alice, &bob] {
+        helpers::create_user(&server.test_db, user.address())
+            .await
+            .expect("Failed to create user");
+    }
+    server
+        .test_db
+        .db
+        .add_balance(alice.address(), "USDC", amount)
+        .await
+        .expect("Failed to fund user");
+    (server, alice, bob)
+}
+
+fn withdrawal(user: &str, amount: &str, destination: &str) -> Value {
+    json!({
+        "type": "withdraw",
+        "user_address": user,
+        "token_ticker": "USDC",
+        "amount": amount,
+        "destination": destination,
+    })
+}
+
+async fn withdraw(
+    server: &TestServer,
+    wallet: &TestWallet,
+    amount: &str,
+    destination: &str,
+) -> reqwest::Response {
+    let body = withdrawal(wallet.address(), amount, destination);
+    post_user(server, wallet.sign::<UserRequest>("/api/user", body)).await
+}
+
+async fn withdrawal_id(response: reqwest::Response) -> uuid::Uuid {
+    assert_eq!(response.status(), 200);
+    match response.json::<UserResponse>().await.unwrap() {
+        UserResponse::Withdraw { withdrawal } => {
+            assert_eq!(withdrawal.status, WithdrawalStatus::Pending);
+            withdrawal.id.parse().unwrap()
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
+
+/// Statuses of every `WithdrawalUpdated` event received so far
+fn withdrawal_updates(rx: &mut broadcast::Receiver<EngineEvent>) -> Vec<WithdrawalStatus> {
+    let mut statuses = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        if let EngineEvent::WithdrawalUpdated { withdrawal } = event {
+            statuses.push(withdrawal.status);
+        }
+    }
+    statuses
+}
+
+#[tokio::test]
+async fn test_withdrawals_are_submitted_then_settled_once_confirmed() {
+    let (server, alice, _) = setup(10_000_000).await;
+    let db = server.test_db.db.clone();
+    let mut events = server.engine().event_tx().subscribe();
+
+    let id = withdrawal_id(withdraw(&server, &alice, "4000000", DESTINATION).await).await;
+    let balance = db.get_balance(alice.address(), "USDC").await.unwrap();
+    assert_eq!(balance.amount, 10_000_000);
+    assert_eq!(balance.open_interest, 4_000_000);
+
+    // The rest of the balance is all that is left to withdraw
+    let response = withdraw(&server, &alice, "6000001", DESTINATION).await;
+    assert_eq!(response.status(), 400);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "INSUFFICIENT_BALANCE");
+    for (amount, destination) in [("0", DESTINATION), ("1", "alice.eth")] {
+        let response = withdraw(&server, &alice, amount, destination).await;
+        assert_eq!(response.status(), 400);
+    }
+
+    let chain = Arc::new(Mutex::new(MockChain {
+        head: 100,
+        receipts: HashMap::new(),
+    }));
+    let url = start_node(chain.clone()).await;
+    let signer = ScriptedSigner::new(|_| {
+        Ok(Submission::Sent {
+            tx_hash: "0xabc".to_string(),
+        })
+    });
+    let processor = WithdrawalProcessor::new(
+        db.clone(),
+        &url,
+        &chain_config(),
+        signer.clone(),
+        server.engine().event_tx(),
+    )
+    .expect("Failed to create processor");
+
+    processor.poll().await.expect("Failed to process");
+    let transfers = signer.transfers();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].withdrawal_id, id.to_string());
+    assert_eq!(transfers[0].chain_id, 42161);
+    assert_eq!(transfers[0].token_contract, USDC_CONTRACT);
+    assert_eq!(transfers[0].destination, DESTINATION);
+    assert_eq!(transfers[0].amount, "4000000");
+    let withdrawal = db.get_withdrawal(id).await.unwrap();
+    assert_eq!(withdrawal.status, WithdrawalStatus::Submitted);
+    assert_eq!(withdrawal.tx_hash.as_deref(), Some("0xabc"));
+
+    // Not mined, then mined but not yet deep enough: nothing moves
+    processor.poll().await.unwrap();
+    chain
+        .lock()
+        .unwrap()
+        .receipts
+        .insert("0xabc".to_string(), (99, true));
+    processor.poll().await.unwrap();
+    assert_eq!(
+        db.get_withdrawal(id).await.unwrap().status,
+        WithdrawalStatus::Submitted
+    );
+    assert_eq!(signer.transfers().len(), 1);
+
+    chain.lock().unwrap().head = 101;
+    processor.poll().await.unwrap();
+    assert_eq!(
+        db.get_withdrawal(id).await.unwrap().status,
+        WithdrawalStatus::Confirmed
+    );
+    let balance = db.get_balance(alice.address(), "USDC").await.unwrap();
+    assert_eq!(balance.amount, 6_000_000);
+    assert_eq!(balance.open_interest, 0);
+
+    assert_eq!(
+        withdrawal_updates(&mut events),
+        vec![
+            WithdrawalStatus::Pending,
+            WithdrawalStatus::Processing,
+            WithdrawalStatus::Submitted,
+            WithdrawalStatus::Confirmed,
+        ]
+    );
+
+    let response = post_user(
+        &server,
+        json!({ "type": "withdrawals", "user_address": alice.address() }),
+    )
+    .await;
+    match response.json::<UserResponse>().await.unwrap() {
+        UserResponse::Withdrawals { withdrawals } => {
+            assert_eq!(withdrawals.len(), 1);
+            assert_eq!(withdrawals[0].status, WithdrawalStatus::Confirmed);
+            assert_eq!(withdrawals[0].tx_hash.as_deref(), Some("0xabc"));
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_rejected_and_reverted_withdrawals_release_funds() {
+    let (server, alice, bob) = setup(10_000_000).await;
+    let db = server.test_db.db.clone();
+
+    let cancelled = withdrawal_id(withdraw(&server, &alice, "1000000", DESTINATION).await).await;
+    let cancel = |wallet: &TestWallet| {
+        let body = json!({
+            "type": "cancel_withdrawal",
+            "user_address": wallet.address(),
+            "withdrawal_id": cancelled,
+        });
+        wallet.sign::<UserRequest>("/api/user", body)
+    };
+    // Someone else's withdrawal can't be cancelled, or even seen
+    assert_eq!(post_user(&server, cancel(&bob)).await.status(), 404);
+    let response = post_user(&server, cancel(&alice)).await;
+    assert_eq!(response.status(), 200);
+    match response.json::<UserResponse>().await.unwrap() {
+        UserResponse::CancelWithdrawal { withdrawal } => {
+            assert_eq!(withdrawal.status, WithdrawalStatus::Cancelled)
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+    let response = post_user(&server, cancel(&alice)).await;
+    assert_eq!(response.status(), 409);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "WITHDRAWAL_NOT_PENDING");
+
+    let rejected = withdrawal_id(withdraw(&server, &alice, "2000000", DESTINATION).await).await;
+    let reverted = withdrawal_id(withdraw(&server, &alice, "3000000", DESTINATION).await).await;
+    assert_eq!(
+        db.get_balance(alice.address(), "USDC")
+            .await
+            .unwrap()
+            .open_interest,
+        5_000_000
+    );
+
+    // The signer refuses one transfer, and is down the first time it sees the other
+    let outages = Arc::new(AtomicUsize::new(1));
+    let signer = {
+        let outages = outages.clone();
+        ScriptedSigner::new(move |transfer| match transfer.amount.as_str() {
+            "2000000" => Ok(Submission::Rejected {
+                reason: "destination blocked".to_string(),
+            }),
+            _ if outages
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok() =>
+            {
+                anyhow::bail!("signer unavailable")
+            }
+            _ => Ok(Submission::Sent {
+                tx_hash: "0xdead".to_string(),
+            }),
+        })
+    };
+    let chain = Arc::new(Mutex::new(MockChain {
+        head: 200,
+        receipts: HashMap::from([("0xdead".to_string(), (150, false))]),
+    }));
+    let url = start_node(chain).await;
+    let processor = WithdrawalProcessor::new(
+        db.clone(),
+        &url,
+        &chain_config(),
+        signer.clone(),
+        server.engine().event_tx(),
+    )
+    .unwrap();
+
+    processor.poll().await.expect("Failed to process");
+    let withdrawal = db.get_withdrawal(rejected).await.unwrap();
+    assert_eq!(withdrawal.status, WithdrawalStatus::Failed);
+    assert!(withdrawal.error.unwrap().contains("destination blocked"));
+    assert_eq!(
+        db.get_withdrawal(reverted).await.unwrap().status,
+        WithdrawalStatus::Processing
+    );
+
+    // Retried under the same id, then failed once the revert is confirmed
+    processor.poll().await.unwrap();
+    let transfers = signer.transfers();
+    assert_eq!(transfers.len(), 3);
+    assert_eq!(transfers[1].withdrawal_id, transfers[2].withdrawal_id);
+    let withdrawal = db.get_withdrawal(reverted).await.unwrap();
+    assert_eq!(withdrawal.status, WithdrawalStatus::Failed);
+    assert!(withdrawal.error.unwrap().contains("reverted"));
+
+    let balance = db.get_balance(alice.address(), "USDC").await.unwrap();
+    assert_eq!(balance.amount, 10_000_000);
+    assert_eq!(balance.open_interest, 0);
+}
+
+#[tokio::test]
+async fn test_unsigned_and_forged_withdrawals_are_refused() {
+    let (server, alice, bob) = setup(10_000_000).await;
+    let db = server.test_db.db.clone();
+
+    let mut unsigned = withdrawal(alice.address(), "1000000", DESTINATION);
+    unsigned["signature"] = json!("sig");
+    // Signed by bob, for alice's funds
+    let mut forged = withdrawal(alice.address(), "1000000", DESTINATION);
+    forged["signature"] = json!("");
+    let request: UserRequest = serde_json::from_value(forged.clone()).unwrap();
+    forged["signature"] = json!(bob.signature("/api/user", &request));
+    // Signed by alice, with the destination changed afterwards
+    let mut altered = alice.sign::<UserRequest>(
+        "/api/user",
+        withdrawal(alice.address(), "1000000", DESTINATION),
+    );
+    altered["destination"] = json!("0x00000000000000000000000000000000000bad01");
+
+    for body in [unsigned, forged, altered.clone()] {
+        let response = post_user(&server, body).await;
+        assert_eq!(response.status(), 401);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "INVALID_SIGNATURE");
+    }
+    let balance = db.get_balance(alice.address(), "USDC").await.unwrap();
+    assert_eq!(balance.open_interest, 0);
+
+    // A signed withdrawal goes through once, and can't be replayed
+    let signed = alice.sign::<UserRequest>(
+        "/api/user",
+        withdrawal(alice.address(), "1000000", DESTINATION),
+    );
+    withdrawal_id(post_user(&server, signed.clone()).await).await;
+    assert_eq!(post_user(&server, signed).await.status(), 401);
+    let withdrawals = db.list_user_withdrawals(alice.address(), 10).await.unwrap();
+    assert_eq!(withdrawals.len(), 1);
+}
@@ -0,0 +1,104 @@
+use backend::models::api::SubscriptionChannel;
+use exchange_test_utils::{helpers, Golden, OrderBuilder, TestServer, UserBuilder, WsRecorder};
+use tokio::time::Duration;
+
+/// How long the socket must stay silent before a step is considered complete
+const QUIET: Duration = Duration::from_millis(300);
+
+fn golden(name: &str) -> Golden {
+    Golden::new(format!(
+        "{}/tests/golden/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ))
+}
+
+// ============================================================================
+// Golden-File Protocol Tests
+// ============================================================================
+// Each test scripts a scenario and compares the exact JSON sequence sent over
+// the WebSocket with a committed file in tests/golden/. A failure here means a
+// client-visible change to `ServerMessage`; if intended, regenerate with
+// `UPDATE_GOLDEN=1 cargo test --test ws_golden_tests` and review the diff.
+//
+// Balance and orderbook channels are left out: balance updates are broadcast
+// in hash-set order and snapshots are sent on a timer, so neither is stable.
+
+#[tokio::test]
+async fn test_ws_golden_place_match_cancel() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC")
+        .await
+        .expect("Failed to create market");
+
+    UserBuilder::new("alice")
+        .balance("BTC", 10_000_000) // 0.1 BTC
+        .create(&server.test_db)
+        .await
+        .expect("Failed to create alice");
+    UserBuilder::new("bob")
+        .balance("USDC", 1_000_000_000_000) // 1,000,000 USDC
+        .create(&server.test_db)
+        .await
+        .expect("Failed to create bob");
+
+    let mut recorder = WsRecorder::connect(&server.ws_url)
+        .await
+        .expect("Failed to connect WebSocket");
+    recorder
+        .subscribe(SubscriptionChannel::Trades, Some(&market.id), None)
+        .await
+        .expect("Failed to subscribe to trades");
+    recorder
+        .subscribe(SubscriptionChannel::UserOrders, None, Some("alice"))
+        .await
+        .expect("Failed to subscribe to alice's orders");
+    recorder
+        .subscribe(SubscriptionChannel::UserFills, None, Some("alice"))
+        .await
+        .expect("Failed to subscribe to alice's fills");
+    recorder
+        .subscribe(SubscriptionChannel::UserOrders, None, Some("bob"))
+        .await
+        .expect("Failed to subscribe to bob's orders");
+
+    // Alice rests 0.02 BTC at $50,000
+    let sell = OrderBuilder::sell("alice", &market.id)
+        .limit(50_000_000_000)
+        .size(2_000_000)
+        .build();
+    server
+        .test_engine
+        .place_order(sell.clone())
+        .await
+        .expect("Failed to place sell order");
+    recorder.settle(QUIET).await.expect("Failed to record");
+
+    // Bob takes half of it
+    let buy = OrderBuilder::buy("bob", &market.id)
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
+    server
+        .test_engine
+        .place_order(buy.clone())
+        .await
+        .expect("Failed to place buy order");
+    recorder.settle(QUIET).await.expect("Failed to record");
+
+    // Alice cancels the remainder
+    server
+        .test_engine
+        .cancel_order(sell.id, "alice".to_string())
+        .await
+        .expect("Failed to cancel sell order");
+    recorder.settle(QUIET).await.expect("Failed to record");
+
+    golden("ws_place_match_cancel")
+        .label(sell.id, "alice_sell")
+        .label(buy.id, "bob_buy")
+        .assert(recorder.messages());
+}
@@ -1,6 +1,7 @@
+use backend::errors::ErrorCode;
 use backend::models::api::{ClientMessage, ServerMessage, SubscriptionChannel};
-use backend::models::domain::{OrderType, Side};
-use exchange_test_utils::{helpers, TestEngine, TestServer};
+use backend::models::domain::OrderStatus;
+use exchange_test_utils::{helpers, OrderBuilder, TestServer};
 use futures::{SinkExt, StreamExt};
 use serde_json::json;
 use tokio::time::{timeout, Duration};
@@ -17,6 +18,18 @@ async fn send_json<T: serde::Serialize>(ws: &mut WsStream, msg: &T) -> anyhow::R
     Ok(())
 }
 
+/// The next message the server sends, skipping transport pings
+async fn next_server_message(ws: &mut WsStream) -> anyhow::Result<ServerMessage> {
+    loop {
+        match timeout(Duration::from_secs(2), ws.next()).await? {
+            Some(Ok(Message::Text(text))) => return Ok(serde_json::from_str(&text)?),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.into()),
+            None => anyhow::bail!("connection closed"),
+        }
+    }
+}
+
 // ============================================================================
 // Connection Tests
 // ============================================================================
@@ -90,6 +103,44 @@ async fn test_ws_multiple_concurrent_connections() {
     }
 }
 
+#[tokio::test]
+async fn test_ws_closed_going_away_on_shutdown() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(&server.ws_url)
+        .await
+        .expect("Failed to connect to WebSocket");
+
+    server.begin_shutdown();
+
+    // The server says goodbye with 1001 rather than just dropping the socket
+    let frame = timeout(Duration::from_secs(5), async {
+        while let Some(msg) = ws.next().await {
+            if let Ok(Message::Close(frame)) = msg {
+                return frame;
+            }
+        }
+        None
+    })
+    .await
+    .expect("Timed out waiting for close frame")
+    .expect("Connection dropped without a close frame");
+    assert_eq!(u16::from(frame.code), 1001);
+
+    // And takes no new connections
+    let reconnect = timeout(
+        Duration::from_secs(5),
+        tokio_tungstenite::connect_async(&server.ws_url),
+    )
+    .await;
+    assert!(
+        !matches!(reconnect, Ok(Ok(_))),
+        "Should refuse new connections"
+    );
+}
+
 // ============================================================================
 // Subscription Tests
 // ============================================================================
@@ -115,6 +166,7 @@ async fn test_ws_subscribe_to_trades() {
         channel: SubscriptionChannel::Trades,
         market_id: Some("BTC/USD".to_string()),
         user_address: None,
+        interval: None,
     };
 
     send_json(&mut ws, &subscribe_msg)
@@ -148,6 +200,7 @@ async fn test_ws_subscribe_to_orderbook() {
         channel: SubscriptionChannel::Orderbook,
         market_id: Some("ETH/USD".to_string()),
         user_address: None,
+        interval: None,
     };
 
     send_json(&mut ws, &subscribe_msg)
@@ -173,6 +226,7 @@ async fn test_ws_subscribe_to_user_updates() {
         channel: SubscriptionChannel::UserBalances,
         market_id: None,
         user_address: Some("0x1234567890abcdef".to_string()),
+        interval: None,
     };
 
     send_json(&mut ws, &subscribe_msg)
@@ -203,6 +257,7 @@ async fn test_ws_unsubscribe_from_channel() {
         channel: SubscriptionChannel::Trades,
         market_id: Some("BTC/USD".to_string()),
         user_address: None,
+        interval: None,
     };
     send_json(&mut ws, &subscribe_msg)
         .await
@@ -213,6 +268,7 @@ async fn test_ws_unsubscribe_from_channel() {
         channel: SubscriptionChannel::Trades,
         market_id: Some("BTC/USD".to_string()),
         user_address: None,
+        interval: None,
     };
     send_json(&mut ws, &unsubscribe_msg)
         .await
@@ -246,16 +302,19 @@ async fn test_ws_multiple_subscriptions_same_connection() {
             channel: SubscriptionChannel::Trades,
             market_id: Some("BTC/USD".to_string()),
             user_address: None,
+            interval: None,
         },
         ClientMessage::Subscribe {
             channel: SubscriptionChannel::Orderbook,
             market_id: Some("ETH/USD".to_string()),
             user_address: None,
+            interval: None,
         },
         ClientMessage::Subscribe {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some("0xuser123".to_string()),
+            interval: None,
         },
     ];
 
@@ -374,11 +433,29 @@ async fn test_ws_handles_invalid_json() {
         .await
         .expect("Failed to send message");
 
-    // Connection should remain open (server just ignores invalid messages)
-    // Send a valid message to verify
+    let reply = next_server_message(&mut ws)
+        .await
+        .expect("No reply to invalid JSON");
+    assert!(
+        matches!(
+            reply,
+            ServerMessage::Error {
+                code: ErrorCode::InvalidMessage,
+                ..
+            }
+        ),
+        "{:?}",
+        reply
+    );
+
+    // Connection should remain open; send a valid message to verify
     send_json(&mut ws, &ClientMessage::Ping)
         .await
         .expect("Failed to send ping after invalid JSON");
+    assert!(matches!(
+        next_server_message(&mut ws).await,
+        Ok(ServerMessage::Pong)
+    ));
 
     ws.close(None).await.expect("Failed to close connection");
 }
@@ -404,6 +481,14 @@ async fn test_ws_handles_unknown_message_type() {
         .await
         .expect("Failed to send message");
 
+    assert!(matches!(
+        next_server_message(&mut ws).await,
+        Ok(ServerMessage::Error {
+            code: ErrorCode::InvalidMessage,
+            ..
+        })
+    ));
+
     // Connection should remain open
     send_json(&mut ws, &ClientMessage::Ping)
         .await
@@ -412,6 +497,40 @@ async fn test_ws_handles_unknown_message_type() {
     ws.close(None).await.expect("Failed to close connection");
 }
 
+#[tokio::test]
+async fn test_ws_rejects_incomplete_subscription() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(&server.ws_url)
+        .await
+        .expect("Failed to connect to WebSocket");
+
+    // The trades channel needs a market
+    send_json(
+        &mut ws,
+        &ClientMessage::Subscribe {
+            channel: SubscriptionChannel::Trades,
+            market_id: None,
+            user_address: None,
+            interval: None,
+        },
+    )
+    .await
+    .expect("Failed to send subscribe message");
+
+    assert!(matches!(
+        next_server_message(&mut ws).await,
+        Ok(ServerMessage::Error {
+            code: ErrorCode::InvalidSubscription,
+            ..
+        })
+    ));
+
+    ws.close(None).await.expect("Failed to close connection");
+}
+
 // ============================================================================
 // Stress Tests
 // ============================================================================
@@ -438,6 +557,7 @@ async fn test_ws_rapid_subscribe_unsubscribe() {
             channel: SubscriptionChannel::Trades,
             market_id: Some("BTC/USD".to_string()),
             user_address: None,
+            interval: None,
         };
         send_json(&mut ws, &subscribe_msg)
             .await
@@ -447,6 +567,7 @@ async fn test_ws_rapid_subscribe_unsubscribe() {
             channel: SubscriptionChannel::Trades,
             market_id: Some("BTC/USD".to_string()),
             user_address: None,
+            interval: None,
         };
         send_json(&mut ws, &unsubscribe_msg)
             .await
@@ -565,6 +686,7 @@ async fn test_two_users_limit_order_matching_full_events() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(maker.clone()),
+            interval: None,
         },
     )
     .await
@@ -582,6 +704,7 @@ async fn test_two_users_limit_order_matching_full_events() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -593,6 +716,7 @@ async fn test_two_users_limit_order_matching_full_events() {
             channel: SubscriptionChannel::UserFills,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -605,6 +729,7 @@ async fn test_two_users_limit_order_matching_full_events() {
             channel: SubscriptionChannel::Trades,
             market_id: Some("BTC/USDC".to_string()),
             user_address: None,
+            interval: None,
         },
     )
     .await
@@ -617,6 +742,7 @@ async fn test_two_users_limit_order_matching_full_events() {
             channel: SubscriptionChannel::Orderbook,
             market_id: Some("BTC/USDC".to_string()),
             user_address: None,
+            interval: None,
         },
     )
     .await
@@ -626,14 +752,10 @@ async fn test_two_users_limit_order_matching_full_events() {
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
     // Maker places sell order
-    let maker_order = TestEngine::create_order(
-        &maker,
-        "BTC/USDC",
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000_000,
-        1_000_000,
-    );
+    let maker_order = OrderBuilder::sell(&maker, "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
 
     // Engine automatically locks maker's balance
     server
@@ -644,14 +766,10 @@ async fn test_two_users_limit_order_matching_full_events() {
 
     // Taker places matching buy order
     // Engine automatically locks taker's balance, executes trade, and broadcasts balance updates
-    let taker_order = TestEngine::create_order(
-        &taker,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Limit,
-        50_000_000_000,
-        1_000_000,
-    );
+    let taker_order = OrderBuilder::buy(&taker, "BTC/USDC")
+        .limit(50_000_000_000)
+        .size(1_000_000)
+        .build();
 
     server
         .test_engine
@@ -825,6 +943,7 @@ async fn test_two_users_partial_fill_with_cancellation() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(maker.clone()),
+            interval: None,
         },
     )
     .await
@@ -836,6 +955,7 @@ async fn test_two_users_partial_fill_with_cancellation() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -847,6 +967,7 @@ async fn test_two_users_partial_fill_with_cancellation() {
             channel: SubscriptionChannel::UserFills,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -858,6 +979,7 @@ async fn test_two_users_partial_fill_with_cancellation() {
             channel: SubscriptionChannel::UserOrders,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -870,6 +992,7 @@ async fn test_two_users_partial_fill_with_cancellation() {
             channel: SubscriptionChannel::Trades,
             market_id: Some("BTC/USDC".to_string()),
             user_address: None,
+            interval: None,
         },
     )
     .await
@@ -879,14 +1002,10 @@ async fn test_two_users_partial_fill_with_cancellation() {
 
     // Maker places sell order for 0.01 BTC at $50/BTC
     // Price: 50 USDC per BTC = 50 * 10^6 = 50_000_000 (USDC atoms per whole BTC)
-    let maker_order = TestEngine::create_order(
-        &maker,
-        "BTC/USDC",
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000, // $50 per BTC
-        1_000_000,  // 0.01 BTC
-    );
+    let maker_order = OrderBuilder::sell(&maker, "BTC/USDC")
+        .limit(50_000_000) // $50 per BTC
+        .size(1_000_000) // 0.01 BTC
+        .build();
     // Place order - engine will handle balance locking automatically
     server
         .test_engine
@@ -895,14 +1014,10 @@ async fn test_two_users_partial_fill_with_cancellation() {
         .expect("Failed to place order");
 
     // Taker places buy order for 0.02 BTC (will partially fill with 0.01 BTC)
-    let taker_order = TestEngine::create_order(
-        &taker,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Limit,
-        50_000_000, // $50 per BTC
-        2_000_000,  // 0.02 BTC (but only 0.01 BTC available)
-    );
+    let taker_order = OrderBuilder::buy(&taker, "BTC/USDC")
+        .limit(50_000_000) // $50 per BTC
+        .size(2_000_000) // 0.02 BTC (but only 0.01 BTC available)
+        .build();
     // Place order - engine will handle balance locking automatically
     let placed = server
         .test_engine
@@ -1004,7 +1119,15 @@ async fn test_two_users_partial_fill_with_cancellation() {
     // Verify taker receives order cancellation
     let _cancel_msg = receive_message_of_type(
         &mut ws_taker,
-        |msg| matches!(msg, ServerMessage::UserOrder { status, .. } if status == "cancelled"),
+        |msg| {
+            matches!(
+                msg,
+                ServerMessage::UserOrder {
+                    status: OrderStatus::Cancelled,
+                    ..
+                }
+            )
+        },
         5,
     )
     .await
@@ -1089,6 +1212,7 @@ async fn test_market_order_immediate_execution_all_events() {
             channel: SubscriptionChannel::Trades,
             market_id: Some("BTC/USDC".to_string()),
             user_address: None,
+            interval: None,
         },
     )
     .await
@@ -1100,6 +1224,7 @@ async fn test_market_order_immediate_execution_all_events() {
             channel: SubscriptionChannel::Orderbook,
             market_id: Some("BTC/USDC".to_string()),
             user_address: None,
+            interval: None,
         },
     )
     .await
@@ -1112,6 +1237,7 @@ async fn test_market_order_immediate_execution_all_events() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(taker.clone()),
+            interval: None,
         },
     )
     .await
@@ -1121,14 +1247,10 @@ async fn test_market_order_immediate_execution_all_events() {
 
     // Maker places limit sell order for 0.02 BTC at $50/BTC
     // Note: Engine will automatically lock the required balance
-    let maker_order = TestEngine::create_order(
-        &maker,
-        "BTC/USDC",
-        Side::Sell,
-        OrderType::Limit,
-        50_000_000, // $50 per BTC
-        2_000_000,  // 0.02 BTC
-    );
+    let maker_order = OrderBuilder::sell(&maker, "BTC/USDC")
+        .limit(50_000_000) // $50 per BTC
+        .size(2_000_000) // 0.02 BTC
+        .build();
     server
         .test_engine
         .place_order(maker_order)
@@ -1146,14 +1268,11 @@ async fn test_market_order_immediate_execution_all_events() {
 
     // Taker places market buy order (immediate execution) for 0.02 BTC at $50/BTC = $1 USDC
     // Note: Engine will automatically lock/unlock the required balance
-    let taker_order = TestEngine::create_order(
-        &taker,
-        "BTC/USDC",
-        Side::Buy,
-        OrderType::Market,
-        50_000_000, // $50 per BTC
-        2_000_000,  // 0.02 BTC
-    );
+    let taker_order = OrderBuilder::buy(&taker, "BTC/USDC")
+        .market()
+        .price(50_000_000) // $50 per BTC
+        .size(2_000_000) // 0.02 BTC
+        .build();
     server
         .test_engine
         .place_order(taker_order)
@@ -1276,6 +1395,7 @@ async fn test_multiple_orders_and_cancellations_event_flow() {
             channel: SubscriptionChannel::UserBalances,
             market_id: None,
             user_address: Some(user.clone()),
+            interval: None,
         },
     )
     .await
@@ -1287,6 +1407,7 @@ async fn test_multiple_orders_and_cancellations_event_flow() {
             channel: SubscriptionChannel::UserOrders,
             market_id: None,
             user_address: Some(user.clone()),
+            interval: None,
         },
     )
     .await
@@ -1298,6 +1419,7 @@ async fn test_multiple_orders_and_cancellations_event_flow() {
             channel: SubscriptionChannel::Orderbook,
             market_id: Some("ETH/USDC".to_string()),
             user_address: None,
+            interval: None,
         },
     )
     .await
@@ -1311,8 +1433,10 @@ async fn test_multiple_orders_and_cancellations_event_flow() {
     for i in 1..=3 {
         let price = 3_000_000_000 + (i * 100_000_000); // $3000, $3100, $3200 per ETH (in USDC atoms per whole ETH)
         let size = 1_000_000; // 0.01 ETH
-        let order =
-            TestEngine::create_order(&user, "ETH/USDC", Side::Buy, OrderType::Limit, price, size);
+        let order = OrderBuilder::buy(&user, "ETH/USDC")
+            .limit(price)
+            .size(size)
+            .build();
 
         // Engine will automatically lock the required balance
         let placed = server
@@ -1356,7 +1480,15 @@ async fn test_multiple_orders_and_cancellations_event_flow() {
         // Verify cancellation event
         let _cancel = receive_message_of_type(
             &mut ws,
-            |msg| matches!(msg, ServerMessage::UserOrder { status, .. } if status == "cancelled"),
+            |msg| {
+                matches!(
+                    msg,
+                    ServerMessage::UserOrder {
+                        status: OrderStatus::Cancelled,
+                        ..
+                    }
+                )
+            },
             5,
         )
         .await
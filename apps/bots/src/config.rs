@@ -1,3 +1,5 @@
+use crate::utils::market_impact::MarketImpactLimit;
+use crate::utils::volume_profile::VolumeProfile;
 use serde::{Deserialize, Serialize};
 
 /// Bots configuration (from apps/bots/config.toml)
@@ -18,6 +20,10 @@ pub struct MarketsConfig {
     pub btc_usdc: Option<BtcUsdcMarketConfig>,
     #[serde(default)]
     pub bp_usdc: Option<BpUsdcMarketConfig>,
+    #[serde(default)]
+    pub events: Option<EventsMarketConfig>,
+    #[serde(default)]
+    pub perps: Option<PerpsMarketConfig>,
 }
 
 // ===========================
@@ -46,6 +52,8 @@ pub struct BtcOrderbookMirrorConfig {
 pub struct BtcTradeMirrorConfig {
     pub enabled: bool,
     pub user_address: String,
+    #[serde(default)]
+    pub market_impact: Option<MarketImpactLimit>, // Cap on each trade's share of the book; uncapped without one
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +93,54 @@ pub struct SyntheticTraderConfig {
     pub min_size: f64,        // Min trade size
     pub max_size: f64,        // Max trade size
     pub buy_probability: f64, // Probability of buy vs sell (0.0-1.0)
+    #[serde(default)]
+    pub volume_profile: Option<VolumeProfile>, // Time-of-day activity; uniform without one
+    #[serde(default)]
+    pub market_impact: Option<MarketImpactLimit>, // Cap on each trade's share of the book; uncapped without one
+}
+
+// ===========================
+// Prediction Event Configuration
+// ===========================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsMarketConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub outcome_makers: Vec<OutcomeMakerConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeMakerConfig {
+    pub enabled: bool,
+    pub event_id: String,
+    pub user_address: String,
+    pub liquidity_param: f64, // b parameter in LMSR (controls market depth)
+    pub update_interval_ms: u64, // How often to update quotes
+    pub spread_bps: u64,      // Spread in basis points
+    pub order_size: f64,      // Size quoted on each side of each outcome
+}
+
+// ===========================
+// Perpetual Market Configuration
+// ===========================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpsMarketConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub liquidators: Vec<LiquidatorConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidatorConfig {
+    pub enabled: bool,
+    pub market_id: String,
+    pub user_address: String,
+    pub within_bps: u32, // Back positions this close to their maintenance margin
+    pub aggressiveness_bps: u32, // 0 quotes at the liquidation price, 10000 at the mark
+    pub max_position: f64, // Largest position the bot takes on, in base tokens
+    pub update_interval_ms: u64, // How often account health is checked
 }
 
 impl Config {
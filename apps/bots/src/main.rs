@@ -9,6 +9,8 @@ use markets::bp_usdc::{LmsrConfig, LmsrMarketMakerBot, SyntheticTraderBot, Synth
 use markets::btc_usdc::{
     OrderbookMirrorBot, OrderbookMirrorConfig, TradeMirrorBot, TradeMirrorConfig,
 };
+use markets::events::{OutcomeMakerConfig, OutcomeMarketMakerBot};
+use markets::perps::{LiquidationBot, LiquidationBotConfig};
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 
@@ -73,6 +75,7 @@ async fn main() -> Result<()> {
                     let bot_config = TradeMirrorConfig {
                         market_id: "BTC/USDC".to_string(),
                         user_address: tm_config.user_address.clone(),
+                        market_impact: tm_config.market_impact.clone(),
                     };
 
                     info!("💱 Initializing trade mirror bot for BTC/USDC");
@@ -136,6 +139,8 @@ async fn main() -> Result<()> {
                         min_size: trader_config.min_size,
                         max_size: trader_config.max_size,
                         buy_probability: trader_config.buy_probability,
+                        volume_profile: trader_config.volume_profile.clone(),
+                        market_impact: trader_config.market_impact.clone(),
                     };
 
                     info!("🎲 Initializing synthetic trader for BP/USDC");
@@ -155,6 +160,88 @@ async fn main() -> Result<()> {
         }
     }
 
+    // ===========================
+    // Prediction Event Bots
+    // ===========================
+
+    if let Some(events_config) = &config.markets.events {
+        if events_config.enabled {
+            info!("🟣 Prediction events enabled");
+
+            // Multi-outcome LMSR market makers, one per event
+            for maker_config in &events_config.outcome_makers {
+                if !maker_config.enabled {
+                    continue;
+                }
+                let bot_config = OutcomeMakerConfig {
+                    event_id: maker_config.event_id.clone(),
+                    user_address: maker_config.user_address.clone(),
+                    liquidity_param: maker_config.liquidity_param,
+                    update_interval_ms: maker_config.update_interval_ms,
+                    spread_bps: maker_config.spread_bps,
+                    order_size: maker_config.order_size,
+                };
+
+                info!(
+                    "📊 Initializing outcome market maker for event {}",
+                    maker_config.event_id
+                );
+                let client = ExchangeClient::new(&exchange_url);
+                let mut bot = OutcomeMarketMakerBot::new(bot_config, client)
+                    .await
+                    .context("Failed to initialize outcome market maker")?;
+
+                let handle = tokio::spawn(async move {
+                    if let Err(e) = bot.start().await {
+                        tracing::error!("❌ Outcome market maker error: {}", e);
+                    }
+                });
+                handles.push(handle);
+            }
+        }
+    }
+
+    // ===========================
+    // Perpetual Market Bots
+    // ===========================
+
+    if let Some(perps_config) = &config.markets.perps {
+        if perps_config.enabled {
+            info!("🟠 Perpetual markets enabled");
+
+            // Liquidation backstops, one per market
+            for liquidator_config in &perps_config.liquidators {
+                if !liquidator_config.enabled {
+                    continue;
+                }
+                let bot_config = LiquidationBotConfig {
+                    market_id: liquidator_config.market_id.clone(),
+                    user_address: liquidator_config.user_address.clone(),
+                    within_bps: liquidator_config.within_bps,
+                    aggressiveness_bps: liquidator_config.aggressiveness_bps,
+                    max_position: liquidator_config.max_position,
+                    update_interval_ms: liquidator_config.update_interval_ms,
+                };
+
+                info!(
+                    "🛟 Initializing liquidation bot for {}",
+                    liquidator_config.market_id
+                );
+                let client = ExchangeClient::new(&exchange_url);
+                let mut bot = LiquidationBot::new(bot_config, client)
+                    .await
+                    .context("Failed to initialize liquidation bot")?;
+
+                let handle = tokio::spawn(async move {
+                    if let Err(e) = bot.start().await {
+                        tracing::error!("❌ Liquidation bot error: {}", e);
+                    }
+                });
+                handles.push(handle);
+            }
+        }
+    }
+
     // ===========================
     // Run all bots
     // ===========================
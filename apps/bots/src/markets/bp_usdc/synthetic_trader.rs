@@ -1,7 +1,9 @@
 use crate::utils::bot_helpers;
+use crate::utils::market_impact::{atoms_to_decimal, MarketImpactLimit};
+use crate::utils::volume_profile::VolumeProfile;
 use anyhow::Result;
-use backend::models::domain::{Market, OrderType, Side};
-use exchange_sdk::ExchangeClient;
+use exchange_protocol::domain::{Market, OrderType, Side};
+use exchange_sdk::{to_atoms, ExchangeClient, SharedOrderbook};
 use rand::{Rng, SeedableRng};
 use std::time::Duration;
 use tracing::{info, warn};
@@ -10,11 +12,13 @@ use tracing::{info, warn};
 #[derive(Clone, Debug)]
 pub struct SyntheticTraderConfig {
     pub user_address: String,
-    pub min_interval_ms: u64, // Minimum time between trades
-    pub max_interval_ms: u64, // Maximum time between trades
-    pub min_size: f64,        // Minimum trade size (BP)
-    pub max_size: f64,        // Maximum trade size (BP)
-    pub buy_probability: f64, // Probability of buy vs sell [0.0, 1.0]
+    pub min_interval_ms: u64,                  // Minimum time between trades
+    pub max_interval_ms: u64,                  // Maximum time between trades
+    pub min_size: f64,                         // Minimum trade size (BP)
+    pub max_size: f64,                         // Maximum trade size (BP)
+    pub buy_probability: f64,                  // Probability of buy vs sell [0.0, 1.0]
+    pub volume_profile: Option<VolumeProfile>, // Time-of-day activity curve; uniform when None
+    pub market_impact: Option<MarketImpactLimit>, // Cap on each trade's share of the book; uncapped when None
 }
 
 /// Synthetic Trader bot - generates realistic trading activity for prediction markets
@@ -22,11 +26,15 @@ pub struct SyntheticTraderConfig {
 /// Creates trades by hitting the LMSR market maker's orders:
 /// - Random buy/sell decisions
 /// - Random sizes and intervals
+/// - Trades more or less often through the day when given a volume profile
+/// - Takes at most a share of the book's depth when given a market impact limit
 /// - Uses market orders to ensure execution
 pub struct SyntheticTraderBot {
     config: SyntheticTraderConfig,
     exchange_client: ExchangeClient,
     market: Market,
+    orderbook: Option<SharedOrderbook>, // Followed only when trades are capped by market impact
+    base_decimals: u8,
 }
 
 impl SyntheticTraderBot {
@@ -45,11 +53,37 @@ impl SyntheticTraderBot {
             "Trade intervals: {}-{}ms, Size range: {}-{} BP",
             config.min_interval_ms, config.max_interval_ms, config.min_size, config.max_size
         );
+        if let Some(profile) = &config.volume_profile {
+            info!(
+                "Volume profile: session {:.2}-{:.2}h UTC, {} news bursts, now at {:.2}x",
+                profile.open_hour,
+                profile.close_hour,
+                profile.news.len(),
+                profile.multiplier_now()
+            );
+        }
+
+        let base_decimals = exchange_client
+            .get_token(&market.base_ticker)
+            .await?
+            .decimals;
+        let orderbook = match &config.market_impact {
+            Some(limit) => {
+                info!(
+                    "Market impact limit: {} bps of the top {} levels",
+                    limit.max_share_bps, limit.levels
+                );
+                Some(bot_helpers::follow_orderbook(&exchange_client, "BP/USDC").await?)
+            }
+            None => None,
+        };
 
         Ok(Self {
             config,
             exchange_client,
             market,
+            orderbook,
+            base_decimals,
         })
     }
 
@@ -64,11 +98,25 @@ impl SyntheticTraderBot {
 
         let mut rng = rand::rngs::StdRng::from_entropy();
 
+        // Candidate trades come as often as the profile's peak allows, and each
+        // goes ahead with the share of the peak the profile is at right now
+        let peak = self
+            .config
+            .volume_profile
+            .as_ref()
+            .map_or(1.0, VolumeProfile::peak);
+
         loop {
             // Random interval between trades
             let interval_ms =
                 rng.gen_range(self.config.min_interval_ms..=self.config.max_interval_ms);
-            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            tokio::time::sleep(Duration::from_secs_f64(interval_ms as f64 / peak / 1000.0)).await;
+
+            if let Some(profile) = &self.config.volume_profile {
+                if rng.gen::<f64>() * peak >= profile.multiplier_now() {
+                    continue;
+                }
+            }
 
             // Decide buy or sell
             let side = if rng.gen::<f64>() < self.config.buy_probability {
@@ -77,8 +125,15 @@ impl SyntheticTraderBot {
                 Side::Sell
             };
 
-            // Random size
+            // Random size, cut down to the market impact limit
             let size = rng.gen_range(self.config.min_size..=self.config.max_size);
+            let Some(size) = self.impact_limited_size(side, size) else {
+                info!(
+                    "Skipping {:?} trade of {:.2} BP: too little liquidity within the impact limit",
+                    side, size
+                );
+                continue;
+            };
 
             // Execute trade
             if let Err(e) = self.execute_trade(side, size).await {
@@ -97,7 +152,7 @@ impl SyntheticTraderBot {
     }
 
     /// Execute a trade by placing a limit order at the expected LMSR price
-    async fn execute_trade(&self, side: Side, size: f64) -> Result<()> {
+    async fn execute_trade(&self, side: Side, size: String) -> Result<()> {
         // Place limit orders at the expected LMSR bot prices
         // This works around a backend matching bug where trades execute at taker's price
         // For LMSR at p=0.5 with 50bps spread: bid=$0.497, ask=$0.503
@@ -114,16 +169,34 @@ impl SyntheticTraderBot {
                 side,
                 OrderType::Limit, // Changed from Market to Limit
                 limit_price.to_string(),
-                format!("{:.6}", size),
+                size.clone(),
                 "synthetic_trader".to_string(),
             )
             .await?;
 
         info!(
-            "🎲 Synthetic trade executed: {:?} {:.2} BP (order: {})",
+            "🎲 Synthetic trade executed: {:?} {} BP (order: {})",
             side, size, result.order.id
         );
 
         Ok(())
     }
+
+    /// The trade's size as placed, cut down to the market impact limit if
+    /// there is one, or `None` when what is left is too small to place
+    fn impact_limited_size(&self, side: Side, size: f64) -> Option<String> {
+        let (Some(limit), Some(orderbook)) = (&self.config.market_impact, &self.orderbook) else {
+            return Some(format!("{:.6}", size));
+        };
+
+        let book = orderbook.read().unwrap();
+        limit
+            .limit(
+                &book,
+                &self.market,
+                side,
+                to_atoms(size, self.base_decimals),
+            )
+            .map(|size| atoms_to_decimal(size, self.base_decimals))
+    }
 }
@@ -1,7 +1,7 @@
 use super::hyperliquid::{HlMessage, HyperliquidClient, Orderbook};
 use crate::utils::bot_helpers;
 use anyhow::Result;
-use backend::models::domain::{Market, OrderType, Side};
+use exchange_protocol::domain::{Market, OrderType, Side};
 use exchange_sdk::ExchangeClient;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
@@ -1,15 +1,18 @@
 use super::hyperliquid::{HlMessage, HyperliquidClient};
 use crate::utils::bot_helpers;
+use crate::utils::market_impact::{atoms_to_decimal, MarketImpactLimit};
 use anyhow::Result;
-use backend::models::domain::{Market, OrderType, Side};
-use exchange_sdk::ExchangeClient;
+use exchange_protocol::convert::decimal_to_atoms;
+use exchange_protocol::domain::{Market, OrderType, Side};
+use exchange_sdk::{ExchangeClient, SharedOrderbook};
 use tracing::{error, info, warn};
 
 /// Configuration for the trade mirror bot
 #[derive(Clone)]
 pub struct TradeMirrorConfig {
-    pub market_id: String,    // e.g., "BTC/USDC"
-    pub user_address: String, // Bot's wallet address
+    pub market_id: String,                        // e.g., "BTC/USDC"
+    pub user_address: String,                     // Bot's wallet address
+    pub market_impact: Option<MarketImpactLimit>, // Cap on each trade's share of the book; uncapped when None
 }
 
 /// Trade mirror bot - creates realistic trading activity by copying Hyperliquid trades
@@ -19,6 +22,11 @@ pub struct TradeMirrorBot {
 
     // Market configuration fetched from backend
     market: Market,
+
+    // Our exchange's book and the base token's decimals, followed only when
+    // trades are capped by market impact
+    orderbook: Option<SharedOrderbook>,
+    base_decimals: u8,
 }
 
 impl TradeMirrorBot {
@@ -36,10 +44,27 @@ impl TradeMirrorBot {
         )
         .await?;
 
+        let base_decimals = exchange_client
+            .get_token(&market.base_ticker)
+            .await?
+            .decimals;
+        let orderbook = match &config.market_impact {
+            Some(limit) => {
+                info!(
+                    "Market impact limit: {} bps of the top {} levels",
+                    limit.max_share_bps, limit.levels
+                );
+                Some(bot_helpers::follow_orderbook(&exchange_client, &config.market_id).await?)
+            }
+            None => None,
+        };
+
         Ok(Self {
             config,
             exchange_client,
             market,
+            orderbook,
+            base_decimals,
         })
     }
 
@@ -88,9 +113,17 @@ impl TradeMirrorBot {
             }
         };
 
+        let Some(size) = self.impact_limited_size(side, size_str)? else {
+            info!(
+                "Skipping {} trade of {}: too little liquidity within the impact limit",
+                self.market.base_ticker, size_str
+            );
+            return Ok(());
+        };
+
         info!(
             "Mirroring {} trade: {:?} {} @ {}",
-            self.market.base_ticker, side, size_str, price_str
+            self.market.base_ticker, side, size, price_str
         );
 
         // Place market order with human-readable decimal values
@@ -103,7 +136,7 @@ impl TradeMirrorBot {
                 side,
                 OrderType::Market,
                 price_str.to_string(),
-                size_str.to_string(),
+                size,
                 "trade_mirror".to_string(),
             )
             .await
@@ -126,6 +159,20 @@ impl TradeMirrorBot {
         Ok(())
     }
 
+    /// The trade's size cut down to the market impact limit, or `None` when
+    /// what is left is too small to place
+    fn impact_limited_size(&self, side: Side, size_str: &str) -> Result<Option<String>> {
+        let (Some(limit), Some(orderbook)) = (&self.config.market_impact, &self.orderbook) else {
+            return Ok(Some(size_str.to_string()));
+        };
+
+        let size = decimal_to_atoms(size_str, self.base_decimals)?;
+        let book = orderbook.read().unwrap();
+        Ok(limit
+            .limit(&book, &self.market, side, size)
+            .map(|size| atoms_to_decimal(size, self.base_decimals)))
+    }
+
     /// Auto-faucet funds if we detect insufficient balance error
     async fn auto_faucet_on_error(&self, error_msg: &str) -> bool {
         bot_helpers::auto_faucet_on_error(
@@ -0,0 +1,3 @@
+pub mod outcome_market_maker;
+
+pub use outcome_market_maker::{OutcomeMakerConfig, OutcomeMarketMakerBot};
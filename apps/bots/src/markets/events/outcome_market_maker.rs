@@ -0,0 +1,237 @@
+use crate::utils::bot_helpers;
+use anyhow::{Context, Result};
+use exchange_protocol::api::ApiPredictionEvent;
+use exchange_protocol::domain::{EventStatus, Market, OrderType, Side};
+use exchange_sdk::ExchangeClient;
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Configuration for the multi-outcome market maker bot
+#[derive(Clone, Debug)]
+pub struct OutcomeMakerConfig {
+    pub event_id: String,
+    pub user_address: String,
+    pub liquidity_param: f64,    // b parameter in LMSR
+    pub update_interval_ms: u64, // Quote update frequency
+    pub spread_bps: u64,         // Spread in basis points (1 bps = 0.01%)
+    pub order_size: f64,         // Size quoted on each side of each outcome
+}
+
+/// One outcome market the bot quotes
+struct OutcomeBook {
+    name: String,
+    market: Market,
+    tick: f64,      // Tick size in quote units
+    base_unit: f64, // Atoms per whole outcome token
+    shares: f64,    // LMSR q_i: net outcome tokens the bot has sold
+}
+
+/// LMSR prices of every outcome: exp(q_i / b) / sum(exp(q_j / b))
+///
+/// The prices are probabilities summing to one. The largest exponent is
+/// subtracted first so large share counts don't overflow.
+pub fn lmsr_prices(shares: &[f64], liquidity_param: f64) -> Vec<f64> {
+    let max = shares.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = shares
+        .iter()
+        .map(|q| ((q - max) / liquidity_param).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+/// Bid and ask around `price`, rounded outwards to `tick` and kept inside (0, 1)
+///
+/// Rounding outwards keeps the bids of all outcomes summing to at most one
+/// and the asks to at least one, so buying or selling every outcome at once
+/// never pays.
+pub fn outcome_quotes(price: f64, spread_bps: u64, tick: f64) -> (f64, f64) {
+    let spread = spread_bps as f64 / 10000.0;
+    let bid = ((price * (1.0 - spread)) / tick).floor() * tick;
+    let ask = ((price * (1.0 + spread)) / tick).ceil() * tick;
+    let bid = bid.clamp(tick, 1.0 - 2.0 * tick);
+    let ask = ask.clamp(bid + tick, 1.0 - tick);
+    (bid, ask)
+}
+
+/// Market maker for every outcome of a prediction event
+///
+/// Prices all outcomes with one Logarithmic Market Scoring Rule, so its mid
+/// prices always sum to one, and quotes a bid and an ask around each. Its own
+/// fills move the shares: selling an outcome raises its price, buying lowers
+/// it, and the other outcomes' prices move the opposite way. Stops once the
+/// event resolves.
+pub struct OutcomeMarketMakerBot {
+    config: OutcomeMakerConfig,
+    exchange_client: ExchangeClient,
+    outcomes: Vec<OutcomeBook>,
+    seen_trades: HashSet<Uuid>,
+}
+
+impl OutcomeMarketMakerBot {
+    pub async fn new(config: OutcomeMakerConfig, exchange_client: ExchangeClient) -> Result<Self> {
+        let event = exchange_client
+            .get_event(&config.event_id)
+            .await
+            .with_context(|| format!("Failed to fetch event {}", config.event_id))?;
+        info!(
+            "Outcome market maker initialized for event {} ({} outcomes)",
+            event.id,
+            event.outcomes.len()
+        );
+
+        let mut outcomes = Vec::with_capacity(event.outcomes.len());
+        let mut seen_trades = HashSet::new();
+        for outcome in &event.outcomes {
+            let market = bot_helpers::fetch_market_and_faucet(
+                &exchange_client,
+                &outcome.market_id,
+                &config.user_address,
+            )
+            .await?;
+            let base = exchange_client.get_token(&market.base_ticker).await?;
+            let quote = exchange_client.get_token(&market.quote_ticker).await?;
+
+            // Fills from previous runs are already priced in the book
+            for trade in exchange_client
+                .get_trades(&config.user_address, Some(market.id.clone()))
+                .await?
+            {
+                seen_trades.insert(trade.id);
+            }
+
+            outcomes.push(OutcomeBook {
+                name: outcome.name.clone(),
+                tick: market.tick_size as f64 / 10f64.powi(quote.decimals as i32),
+                base_unit: 10f64.powi(base.decimals as i32),
+                shares: 0.0,
+                market,
+            });
+        }
+
+        Ok(Self {
+            config,
+            exchange_client,
+            outcomes,
+            seen_trades,
+        })
+    }
+
+    /// Start the bot; returns once the event resolves
+    pub async fn start(&mut self) -> Result<()> {
+        info!(
+            "Starting outcome market maker for {}: update interval {}ms, spread {} bps",
+            self.config.event_id, self.config.update_interval_ms, self.config.spread_bps
+        );
+
+        loop {
+            let event = self.exchange_client.get_event(&self.config.event_id).await;
+            match event {
+                Ok(ApiPredictionEvent {
+                    status: EventStatus::Resolved,
+                    winning_outcome,
+                    ..
+                }) => {
+                    info!(
+                        "Event {} resolved to {}; stopping",
+                        self.config.event_id,
+                        winning_outcome.unwrap_or_default()
+                    );
+                    return Ok(());
+                }
+                Ok(_) => {
+                    if let Err(e) = self.apply_fills().await {
+                        error!("Error fetching fills: {}", e);
+                    }
+                    if let Err(e) = self.update_quotes().await {
+                        error!("Error updating quotes: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to fetch event {}: {}", self.config.event_id, e),
+            }
+
+            tokio::time::sleep(Duration::from_millis(self.config.update_interval_ms)).await;
+        }
+    }
+
+    /// Move each outcome's shares by the bot's fills since the last update
+    async fn apply_fills(&mut self) -> Result<()> {
+        for outcome in &mut self.outcomes {
+            let trades = self
+                .exchange_client
+                .get_trades(&self.config.user_address, Some(outcome.market.id.clone()))
+                .await?;
+            for trade in trades {
+                if !self.seen_trades.insert(trade.id) {
+                    continue;
+                }
+                let size = trade.size as f64 / outcome.base_unit;
+                if trade.seller_address == self.config.user_address {
+                    outcome.shares += size;
+                }
+                if trade.buyer_address == self.config.user_address {
+                    outcome.shares -= size;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Requote every outcome around its LMSR price
+    async fn update_quotes(&mut self) -> Result<()> {
+        let shares: Vec<f64> = self.outcomes.iter().map(|o| o.shares).collect();
+        let prices = lmsr_prices(&shares, self.config.liquidity_param);
+
+        for (outcome, price) in self.outcomes.iter().zip(prices) {
+            let (bid, ask) = outcome_quotes(price, self.config.spread_bps, outcome.tick);
+            info!(
+                "{} ({}): p={:.4}, bid={:.4}, ask={:.4}",
+                outcome.name, outcome.market.id, price, bid, ask
+            );
+
+            if let Err(e) = self
+                .exchange_client
+                .cancel_all_orders(
+                    self.config.user_address.clone(),
+                    Some(outcome.market.id.clone()),
+                    "outcome_market_maker".to_string(),
+                )
+                .await
+            {
+                warn!("Failed to cancel orders in {}: {}", outcome.market.id, e);
+            }
+
+            for (side, price) in [(Side::Buy, bid), (Side::Sell, ask)] {
+                if let Err(e) = self
+                    .exchange_client
+                    .place_order_decimal(
+                        self.config.user_address.clone(),
+                        outcome.market.id.clone(),
+                        side,
+                        OrderType::Limit,
+                        format!("{:.6}", price),
+                        format!("{:.6}", self.config.order_size),
+                        "outcome_market_maker".to_string(),
+                    )
+                    .await
+                {
+                    warn!(
+                        "❌ Failed to place {:?} in {}: {}",
+                        side, outcome.market.id, e
+                    );
+                    bot_helpers::auto_faucet_on_error(
+                        &self.exchange_client,
+                        &self.config.user_address,
+                        &outcome.market,
+                        &e.to_string(),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
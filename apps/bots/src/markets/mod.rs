@@ -1,2 +1,4 @@
 pub mod bp_usdc;
 pub mod btc_usdc;
+pub mod events;
+pub mod perps;
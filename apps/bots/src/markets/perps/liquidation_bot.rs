@@ -0,0 +1,304 @@
+use crate::utils::bot_helpers;
+use anyhow::{bail, Context, Result};
+use exchange_protocol::api::MarketRiskResponse;
+use exchange_protocol::convert::decimal_to_atoms;
+use exchange_protocol::domain::{Market, OrderType, Side};
+use exchange_sdk::ExchangeClient;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Configuration for the liquidation bot
+#[derive(Clone, Debug)]
+pub struct LiquidationBotConfig {
+    pub market_id: String,
+    pub user_address: String,
+    pub within_bps: u32, // Back positions this close to their maintenance margin
+    pub aggressiveness_bps: u32, // 0 quotes at the liquidation price, 10000 at the mark
+    pub max_position: f64, // Largest position the bot takes on, in base tokens
+    pub update_interval_ms: u64, // How often account health is checked
+}
+
+/// Price the bot backs a liquidation at, on the side opposite the position
+///
+/// The engine's liquidation order fills at or better than the liquidation
+/// price. Aggressiveness moves the quote from there towards the mark, giving
+/// up discount for priority over other liquidity; it is rounded towards the
+/// liquidation price, so the order can always fill.
+pub fn backstop_price(
+    position_size: i128,
+    mark_price: u128,
+    liquidation_price: u128,
+    aggressiveness_bps: u32,
+    tick_size: u128,
+) -> u128 {
+    let aggressiveness = aggressiveness_bps.min(10_000) as u128;
+    let tick_size = tick_size.max(1);
+    if position_size > 0 {
+        // Buying a long's position below the mark
+        let discount = mark_price.saturating_sub(liquidation_price);
+        let price = liquidation_price + discount * aggressiveness / 10_000;
+        (price - price % tick_size).max(liquidation_price)
+    } else {
+        // Selling to a short above the mark
+        let premium = liquidation_price.saturating_sub(mark_price);
+        let price = liquidation_price - premium * aggressiveness / 10_000;
+        price
+            .div_ceil(tick_size)
+            .saturating_mul(tick_size)
+            .min(liquidation_price)
+    }
+}
+
+/// Running profit of the bot's liquidation fills, valued at the mark
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LiquidationPnl {
+    pub fills: u64,
+    pub volume: u128, // Base atoms taken on
+    pub profit: i128, // Quote atoms: the discount to the mark the fills were bought or sold at
+}
+
+impl LiquidationPnl {
+    /// Count one fill of the bot's `side` against the mark at the time
+    pub fn record(&mut self, side: Side, price: u128, size: u128, mark_price: u128, scale: u128) {
+        let value = |price: u128| (price.saturating_mul(size) / scale.max(1)) as i128;
+        let profit = match side {
+            Side::Buy => value(mark_price) - value(price),
+            Side::Sell => value(price) - value(mark_price),
+        };
+        self.fills += 1;
+        self.volume = self.volume.saturating_add(size);
+        self.profit = self.profit.saturating_add(profit);
+    }
+}
+
+/// Backstops liquidations in one perpetual market
+///
+/// The engine liquidates a position by sending an order through the book
+/// that fills no worse than its liquidation price, and cancels what it can't
+/// fill. The bot watches the market's positions nearing their maintenance
+/// margin and rests orders for them ahead of the next mark, so liquidations
+/// have something to fill against. Its own position is capped, and every fill
+/// is valued against the mark to account for what backstopping earns.
+pub struct LiquidationBot {
+    config: LiquidationBotConfig,
+    exchange_client: ExchangeClient,
+    market: Market,
+    scale: u128,        // Base atoms per whole base token
+    max_position: u128, // In base atoms
+    pnl: LiquidationPnl,
+    seen_trades: HashSet<Uuid>,
+}
+
+impl LiquidationBot {
+    pub async fn new(
+        config: LiquidationBotConfig,
+        exchange_client: ExchangeClient,
+    ) -> Result<Self> {
+        exchange_client
+            .get_perpetual_market(&config.market_id)
+            .await
+            .with_context(|| format!("{} is not a perpetual market", config.market_id))?;
+        let market = bot_helpers::fetch_market_and_faucet(
+            &exchange_client,
+            &config.market_id,
+            &config.user_address,
+        )
+        .await?;
+        let base = exchange_client.get_token(&market.base_ticker).await?;
+        let max_position = decimal_to_atoms(&config.max_position.to_string(), base.decimals)
+            .context("Invalid max_position")?;
+
+        // Fills from previous runs were accounted for then
+        let seen_trades = exchange_client
+            .get_trades(&config.user_address, Some(market.id.clone()))
+            .await?
+            .into_iter()
+            .map(|trade| trade.id)
+            .collect();
+
+        info!(
+            "Liquidation bot initialized for {} (max position {} atoms)",
+            market.id, max_position
+        );
+        Ok(Self {
+            config,
+            exchange_client,
+            market,
+            scale: 10u128.pow(base.decimals as u32),
+            max_position,
+            pnl: LiquidationPnl::default(),
+            seen_trades,
+        })
+    }
+
+    /// Start the bot (runs forever)
+    pub async fn start(&mut self) -> Result<()> {
+        info!(
+            "Starting liquidation bot for {}: within {} bps of maintenance, aggressiveness {} bps",
+            self.market.id, self.config.within_bps, self.config.aggressiveness_bps
+        );
+
+        loop {
+            match self
+                .exchange_client
+                .get_market_risk(&self.market.id, Some(self.config.within_bps))
+                .await
+            {
+                Ok(risk) => {
+                    if let Err(e) = self.account_fills(&risk).await {
+                        error!("Error accounting fills: {}", e);
+                    }
+                    if let Err(e) = self.update_backstops(&risk).await {
+                        error!("Error updating backstops: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to fetch risk of {}: {}", self.market.id, e),
+            }
+
+            tokio::time::sleep(Duration::from_millis(self.config.update_interval_ms)).await;
+        }
+    }
+
+    /// Value the bot's fills since the last update against the mark
+    async fn account_fills(&mut self, risk: &MarketRiskResponse) -> Result<()> {
+        let Some(mark_price) = mark_price(risk)? else {
+            return Ok(());
+        };
+        let trades = self
+            .exchange_client
+            .get_trades(&self.config.user_address, Some(self.market.id.clone()))
+            .await?;
+        for trade in trades {
+            if !self.seen_trades.insert(trade.id) {
+                continue;
+            }
+            let side = if trade.buyer_address == self.config.user_address {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            self.pnl
+                .record(side, trade.price, trade.size, mark_price, self.scale);
+            info!(
+                "💥 Backstopped {:?} {} atoms at {} (mark {}): {} fills, {} atoms, profit {} quote atoms",
+                side,
+                trade.size,
+                trade.price,
+                mark_price,
+                self.pnl.fills,
+                self.pnl.volume,
+                self.pnl.profit
+            );
+        }
+        Ok(())
+    }
+
+    /// Replace the bot's orders with one per backstop price, within its position cap
+    async fn update_backstops(&mut self, risk: &MarketRiskResponse) -> Result<()> {
+        if let Err(e) = self
+            .exchange_client
+            .cancel_all_orders(
+                self.config.user_address.clone(),
+                Some(self.market.id.clone()),
+                "liquidation_bot".to_string(),
+            )
+            .await
+        {
+            warn!("Failed to cancel orders in {}: {}", self.market.id, e);
+        }
+        let Some(mark_price) = mark_price(risk)? else {
+            return Ok(());
+        };
+
+        let mut backstops: BTreeMap<(bool, u128), u128> = BTreeMap::new();
+        for position in &risk.positions {
+            let size: i128 = position.size.parse().context("Invalid position size")?;
+            let liquidation_price: u128 = position
+                .liquidation_price
+                .parse()
+                .context("Invalid liquidation price")?;
+            let price = backstop_price(
+                size,
+                mark_price,
+                liquidation_price,
+                self.config.aggressiveness_bps,
+                self.market.tick_size,
+            );
+            *backstops.entry((size > 0, price)).or_default() += size.unsigned_abs();
+        }
+        if backstops.is_empty() {
+            return Ok(());
+        }
+
+        // Room left under the cap on each side of the bot's own position
+        let position = self
+            .exchange_client
+            .get_positions(&self.config.user_address)
+            .await?
+            .positions
+            .into_iter()
+            .find(|position| position.market_id == self.market.id)
+            .map(|position| position.size.parse::<i128>())
+            .transpose()
+            .context("Invalid bot position size")?
+            .unwrap_or(0);
+        let cap = self.max_position as i128;
+        let mut buy_room = (cap - position).max(0) as u128;
+        let mut sell_room = (cap + position).max(0) as u128;
+
+        for ((backs_long, price), size) in backstops {
+            let (side, room) = if backs_long {
+                (Side::Buy, &mut buy_room)
+            } else {
+                (Side::Sell, &mut sell_room)
+            };
+            let size = ExchangeClient::round_size_to_lot(size.min(*room), self.market.lot_size);
+            if size == 0 || size < self.market.min_size {
+                continue;
+            }
+            *room -= size;
+
+            info!(
+                "🛟 Backstopping {:?} {} atoms at {} in {}",
+                side, size, price, self.market.id
+            );
+            if let Err(e) = self
+                .exchange_client
+                .place_order(
+                    self.config.user_address.clone(),
+                    self.market.id.clone(),
+                    side,
+                    OrderType::Limit,
+                    price.to_string(),
+                    size.to_string(),
+                    "liquidation_bot".to_string(),
+                )
+                .await
+            {
+                warn!("❌ Failed to place {:?} backstop at {}: {}", side, price, e);
+                bot_helpers::auto_faucet_on_error(
+                    &self.exchange_client,
+                    &self.config.user_address,
+                    &self.market,
+                    &e.to_string(),
+                )
+                .await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The market's mark price; `None` until it is first marked
+fn mark_price(risk: &MarketRiskResponse) -> Result<Option<u128>> {
+    match &risk.mark_price {
+        Some(price) => match price.parse() {
+            Ok(price) => Ok(Some(price)),
+            Err(_) => bail!("Invalid mark price {}", price),
+        },
+        None => Ok(None),
+    }
+}
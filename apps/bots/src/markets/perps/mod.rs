@@ -0,0 +1,3 @@
+pub mod liquidation_bot;
+
+pub use liquidation_bot::{LiquidationBot, LiquidationBotConfig};
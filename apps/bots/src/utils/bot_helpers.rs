@@ -1,6 +1,6 @@
 use anyhow::Result;
-use backend::models::domain::Market;
-use exchange_sdk::ExchangeClient;
+use exchange_protocol::domain::Market;
+use exchange_sdk::{ExchangeClient, LocalOrderbook, SharedOrderbook, WebSocketClient};
 use tracing::{error, info, warn};
 
 /// Fetch market configuration and auto-faucet initial funds for a bot
@@ -23,10 +23,11 @@ pub async fn fetch_market_and_faucet(
 
     for token in [&market.base_ticker, &market.quote_ticker] {
         match client
-            .admin_faucet(
+            .faucet(
                 user_address.to_string(),
                 token.to_string(),
                 faucet_amount.to_string(),
+                "bot_faucet".to_string(),
             )
             .await
         {
@@ -67,10 +68,11 @@ pub async fn auto_faucet_on_error(
 
         for token_name in tokens {
             match client
-                .admin_faucet(
+                .faucet(
                     user_address.to_string(),
                     token_name.to_string(),
                     faucet_amount.to_string(),
+                    "bot_faucet".to_string(),
                 )
                 .await
             {
@@ -87,3 +89,22 @@ pub async fn auto_faucet_on_error(
 
     false
 }
+
+/// WebSocket endpoint of the exchange a REST URL points at
+pub fn websocket_url(exchange_url: &str) -> String {
+    let url = exchange_url.trim_end_matches('/');
+    let url = match url.split_once("://") {
+        Some(("https", rest)) => format!("wss://{}", rest),
+        Some(("http", rest)) => format!("ws://{}", rest),
+        _ => url.to_string(),
+    };
+    format!("{}/ws", url)
+}
+
+/// Keep a local copy of a market's book current over the exchange's WebSocket
+pub async fn follow_orderbook(client: &ExchangeClient, market_id: &str) -> Result<SharedOrderbook> {
+    let ws = WebSocketClient::new(websocket_url(client.base_url()));
+    let book = LocalOrderbook::follow(&ws, market_id).await?;
+    info!("📖 Following the {} book", market_id);
+    Ok(book)
+}
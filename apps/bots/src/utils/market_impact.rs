@@ -0,0 +1,39 @@
+use exchange_protocol::domain::{Market, Side};
+use exchange_sdk::{ExchangeClient, LocalOrderbook};
+use serde::{Deserialize, Serialize};
+
+// Sizes go to the exchange as the decimal strings orders are placed with
+pub use exchange_protocol::convert::atoms_to_decimal;
+
+/// Caps taker orders to a share of the liquidity near the touch
+///
+/// With `levels = 3` and `max_share_bps = 500`, an order takes at most 5% of
+/// what rests in the best three price levels it would trade against, so demo
+/// taker flow moves the book without sweeping it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketImpactLimit {
+    pub levels: usize,      // Price levels across the book counted as liquidity
+    pub max_share_bps: u32, // Share of that liquidity one order may take
+}
+
+impl MarketImpactLimit {
+    /// Largest order a taker on `side` may place against `book`, in base atoms
+    pub fn max_size(&self, book: &LocalOrderbook, side: Side) -> u128 {
+        let share = self.max_share_bps.min(10_000) as u128;
+        book.liquidity_for(side, self.levels).saturating_mul(share) / 10_000
+    }
+
+    /// `size` in base atoms cut down to the limit and to the market's lot
+    /// size, or `None` when what is left is under the market's minimum
+    pub fn limit(
+        &self,
+        book: &LocalOrderbook,
+        market: &Market,
+        side: Side,
+        size: u128,
+    ) -> Option<u128> {
+        let size =
+            ExchangeClient::round_size_to_lot(size.min(self.max_size(book, side)), market.lot_size);
+        (size > 0 && size >= market.min_size).then_some(size)
+    }
+}
@@ -1 +1,3 @@
 pub mod bot_helpers;
+pub mod market_impact;
+pub mod volume_profile;
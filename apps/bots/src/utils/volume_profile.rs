@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// A burst of activity around a scheduled news time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsBurst {
+    pub hour: f64,       // Hours after midnight UTC, e.g. 14.5 for 14:30
+    pub multiplier: f64, // Activity at the news time, relative to the curve without it
+    pub decay_secs: f64, // How quickly activity returns to the curve either side of it
+}
+
+/// How busy a market is through the day, relative to a bot's base trading rate
+///
+/// Activity is U-shaped over the session: highest at the open and close,
+/// lowest in the middle, and flat outside it. News bursts multiply the curve
+/// around their time, fading out exponentially on both sides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeProfile {
+    pub open_hour: f64,            // Session open, hours after midnight UTC
+    pub close_hour: f64,           // Session close; before the open for sessions spanning midnight
+    pub open_multiplier: f64,      // Activity at the open and close
+    pub midday_multiplier: f64,    // Activity in the middle of the session
+    pub off_hours_multiplier: f64, // Activity outside the session
+    #[serde(default)]
+    pub news: Vec<NewsBurst>,
+}
+
+impl VolumeProfile {
+    /// Activity at `secs` after midnight UTC, relative to the base rate
+    pub fn multiplier(&self, secs: f64) -> f64 {
+        let secs = secs.rem_euclid(SECS_PER_DAY);
+        let open = (self.open_hour * 3600.0).rem_euclid(SECS_PER_DAY);
+        let length = (self.close_hour * 3600.0 - open).rem_euclid(SECS_PER_DAY);
+        let elapsed = (secs - open).rem_euclid(SECS_PER_DAY);
+
+        let mut multiplier = if length > 0.0 && elapsed < length {
+            // 0 at the open and close, 1 in the middle of the session
+            let x = 2.0 * elapsed / length - 1.0;
+            let dip = 1.0 - x * x;
+            self.open_multiplier + (self.midday_multiplier - self.open_multiplier) * dip
+        } else {
+            self.off_hours_multiplier
+        };
+
+        for burst in &self.news {
+            // Distance to the news time, the short way round the clock
+            let distance = (secs - burst.hour * 3600.0).rem_euclid(SECS_PER_DAY);
+            let distance = distance.min(SECS_PER_DAY - distance);
+            let fade = (-distance / burst.decay_secs.max(1.0)).exp();
+            multiplier *= 1.0 + (burst.multiplier - 1.0) * fade;
+        }
+
+        multiplier.max(0.0)
+    }
+
+    /// Most activity the profile reaches at any time of day
+    pub fn peak(&self) -> f64 {
+        let curve = self
+            .open_multiplier
+            .max(self.midday_multiplier)
+            .max(self.off_hours_multiplier);
+        let news: f64 = self
+            .news
+            .iter()
+            .map(|burst| burst.multiplier.max(1.0))
+            .product();
+        (curve * news).max(f64::MIN_POSITIVE)
+    }
+
+    /// Activity right now, relative to the base rate
+    pub fn multiplier_now(&self) -> f64 {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or(0.0);
+        self.multiplier(secs)
+    }
+}
@@ -1,32 +1,32 @@
-/// Integration tests for bot orders using testcontainers
-/// These tests verify end-to-end functionality including proper formatting for frontend display
-use backend::models::domain::{OrderStatus, OrderType, Side};
 use exchange_bots::markets::bp_usdc::{
     LmsrConfig, LmsrMarketMakerBot, SyntheticTraderBot, SyntheticTraderConfig,
 };
-use exchange_sdk::ExchangeClient;
-use exchange_test_utils::TestServer;
+/// Integration tests for bot orders using testcontainers
+/// These tests verify end-to-end functionality including proper formatting for frontend display
+use exchange_protocol::domain::{OrderStatus, OrderType, Side};
+use exchange_sdk::{AdminClient, ExchangeClient};
+use exchange_test_utils::{TestServer, TEST_ADMIN_TOKEN};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::str::FromStr;
 
 /// Helper to setup test market with proper configuration
 async fn setup_test_market(server: &TestServer, _market_id: &str) -> anyhow::Result<()> {
-    let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
 
     // Create BTC token
-    client
-        .admin_create_token("BTC".to_string(), 6, "Bitcoin".to_string())
+    admin
+        .create_token("BTC".to_string(), 6, "Bitcoin".to_string())
         .await?;
 
     // Create USDC token
-    client
-        .admin_create_token("USDC".to_string(), 6, "USD Coin".to_string())
+    admin
+        .create_token("USDC".to_string(), 6, "USD Coin".to_string())
         .await?;
 
     // Create market with proper constraints
-    client
-        .admin_create_market(
+    admin
+        .create_market(
             "BTC".to_string(),
             "USDC".to_string(),
             1000000, // tick_size: 1 USDC (6 decimals)
@@ -51,21 +51,22 @@ async fn test_bot_order_with_proper_rounding() {
         .expect("Failed to setup market");
 
     let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
     let user_address = "test_bot".to_string();
     let market_id = "BTC/USDC".to_string();
 
     // Fund the test bot (need enough for collateral)
     // Note: Collateral calculation uses raw values, so we need massive amounts
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "BTC".to_string(),
             "100000000000000".to_string(),
         )
         .await
         .expect("Failed to fund BTC");
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "USDC".to_string(),
             "100000000000000000".to_string(),
@@ -127,20 +128,21 @@ async fn test_bot_order_below_min_size_rounded_to_zero() {
         .expect("Failed to setup market");
 
     let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
     let user_address = "test_bot_small".to_string();
     let market_id = "BTC/USDC".to_string();
 
     // Fund the bot (massive amounts for collateral)
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "BTC".to_string(),
             "100000000000000".to_string(),
         )
         .await
         .expect("Failed to fund BTC");
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "USDC".to_string(),
             "100000000000000000".to_string(),
@@ -197,20 +199,21 @@ async fn test_bot_order_displays_correctly() {
         .expect("Failed to setup market");
 
     let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
     let user_address = "test_bot_display".to_string();
     let market_id = "BTC/USDC".to_string();
 
     // Fund the bot (massive amounts for collateral)
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "BTC".to_string(),
             "100000000000000".to_string(),
         )
         .await
         .expect("Failed to fund BTC");
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "USDC".to_string(),
             "100000000000000000".to_string(),
@@ -284,20 +287,21 @@ async fn test_bot_order_with_fractional_price() {
         .expect("Failed to setup market");
 
     let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
     let user_address = "test_bot_frac".to_string();
     let market_id = "BTC/USDC".to_string();
 
     // Fund the bot (massive amounts for collateral)
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "BTC".to_string(),
             "100000000000000".to_string(),
         )
         .await
         .expect("Failed to fund BTC");
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "USDC".to_string(),
             "100000000000000000".to_string(),
@@ -367,20 +371,21 @@ async fn test_bot_multiple_orders() {
         .expect("Failed to setup market");
 
     let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
     let user_address = "test_bot_multi".to_string();
     let market_id = "BTC/USDC".to_string();
 
     // Fund the bot generously
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "BTC".to_string(),
             "100000000000000".to_string(),
         )
         .await
         .expect("Failed to fund BTC");
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "USDC".to_string(),
             "100000000000000000".to_string(),
@@ -464,20 +469,21 @@ async fn test_bot_cancel_all_orders() {
         .expect("Failed to setup market");
 
     let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
     let user_address = "test_bot_cancel".to_string();
     let market_id = "BTC/USDC".to_string();
 
     // Fund the bot (massive amounts for collateral)
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "BTC".to_string(),
             "100000000000000".to_string(),
         )
         .await
         .expect("Failed to fund BTC");
-    client
-        .admin_faucet(
+    admin
+        .faucet(
             user_address.clone(),
             "USDC".to_string(),
             "100000000000000000".to_string(),
@@ -553,22 +559,22 @@ async fn test_bot_cancel_all_orders() {
 
 /// Helper to setup BP/USDC prediction market
 async fn setup_bp_market(server: &TestServer) -> anyhow::Result<()> {
-    let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
 
     // Create BP token (Binary Prediction)
-    client
-        .admin_create_token("BP".to_string(), 6, "Binary Prediction".to_string())
+    admin
+        .create_token("BP".to_string(), 6, "Binary Prediction".to_string())
         .await?;
 
     // Create USDC token
-    client
-        .admin_create_token("USDC".to_string(), 6, "USD Coin".to_string())
+    admin
+        .create_token("USDC".to_string(), 6, "USD Coin".to_string())
         .await?;
 
     // Create BP/USDC market with appropriate constraints for prediction market
     // Prices range from $0 to $1 (representing probabilities)
-    client
-        .admin_create_market(
+    admin
+        .create_market(
             "BP".to_string(),
             "USDC".to_string(),
             1000,    // tick_size: 0.001 USDC (6 decimals) - $0.001 increments
@@ -684,6 +690,8 @@ async fn test_synthetic_trader_initialization() {
         min_size: 10.0,
         max_size: 50.0,
         buy_probability: 0.5,
+        volume_profile: None,
+        market_impact: None,
     };
 
     let _trader_bot = SyntheticTraderBot::new(trader_config.clone(), client.clone())
@@ -744,6 +752,8 @@ async fn test_bp_market_bots_initialization() {
         min_size: 10.0,
         max_size: 50.0,
         buy_probability: 0.5,
+        volume_profile: None,
+        market_impact: None,
     };
 
     let _trader_bot = SyntheticTraderBot::new(trader_config.clone(), client.clone())
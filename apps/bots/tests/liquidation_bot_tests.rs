@@ -0,0 +1,72 @@
+use exchange_bots::markets::perps::liquidation_bot::{backstop_price, LiquidationPnl};
+use exchange_protocol::domain::Side;
+
+#[test]
+fn test_backstop_price_spans_liquidation_to_mark() {
+    // A long liquidated at 90, marked at 100: the bot bids between the two
+    assert_eq!(backstop_price(5, 100_000, 90_000, 0, 1), 90_000);
+    assert_eq!(backstop_price(5, 100_000, 90_000, 2_500, 1), 92_500);
+    assert_eq!(backstop_price(5, 100_000, 90_000, 10_000, 1), 100_000);
+
+    // A short liquidated at 110: the bot offers between the two
+    assert_eq!(backstop_price(-5, 100_000, 110_000, 0, 1), 110_000);
+    assert_eq!(backstop_price(-5, 100_000, 110_000, 2_500, 1), 107_500);
+    assert_eq!(backstop_price(-5, 100_000, 110_000, 10_000, 1), 100_000);
+
+    // Aggressiveness past 100% never quotes through the mark
+    assert_eq!(backstop_price(5, 100_000, 90_000, 20_000, 1), 100_000);
+    assert_eq!(backstop_price(-5, 100_000, 110_000, 20_000, 1), 100_000);
+}
+
+#[test]
+fn test_backstop_price_rounds_towards_liquidation() {
+    // Rounded onto ticks away from the mark
+    assert_eq!(backstop_price(5, 100_000, 90_000, 2_600, 1_000), 92_000);
+    assert_eq!(backstop_price(-5, 100_000, 110_000, 2_600, 1_000), 108_000);
+
+    // An off-tick liquidation price is never quoted past
+    assert_eq!(backstop_price(5, 100_000, 90_500, 0, 1_000), 90_500);
+    assert_eq!(backstop_price(-5, 100_000, 109_500, 0, 1_000), 109_500);
+    assert_eq!(backstop_price(5, 100_000, 90_500, 1_000, 1_000), 91_000);
+    assert_eq!(backstop_price(-5, 100_000, 109_500, 1_000, 1_000), 109_000);
+
+    // A mark already past the liquidation price quotes at the liquidation price
+    assert_eq!(backstop_price(5, 85_000, 90_000, 5_000, 1_000), 90_000);
+    assert_eq!(backstop_price(-5, 115_000, 110_000, 5_000, 1_000), 110_000);
+}
+
+#[test]
+fn test_liquidation_pnl_values_fills_at_the_mark() {
+    let mut pnl = LiquidationPnl::default();
+
+    // Bought 2 tokens (8 decimals) at 95 with the mark at 100
+    pnl.record(Side::Buy, 95_000_000, 200_000_000, 100_000_000, 100_000_000);
+    assert_eq!(pnl.profit, 10_000_000);
+
+    // Sold 1 token at 104 with the mark at 100
+    pnl.record(
+        Side::Sell,
+        104_000_000,
+        100_000_000,
+        100_000_000,
+        100_000_000,
+    );
+    assert_eq!(pnl.profit, 14_000_000);
+
+    // Bought 1 token above the mark loses
+    pnl.record(
+        Side::Buy,
+        101_000_000,
+        100_000_000,
+        100_000_000,
+        100_000_000,
+    );
+    assert_eq!(
+        pnl,
+        LiquidationPnl {
+            fills: 3,
+            volume: 400_000_000,
+            profit: 13_000_000,
+        }
+    );
+}
@@ -0,0 +1,108 @@
+use exchange_bots::utils::bot_helpers::websocket_url;
+use exchange_bots::utils::market_impact::{atoms_to_decimal, MarketImpactLimit};
+use exchange_protocol::domain::{Market, OrderbookLevel, Side};
+use exchange_sdk::LocalOrderbook;
+
+fn level(price: u128, size: u128) -> OrderbookLevel {
+    OrderbookLevel { price, size }
+}
+
+fn market() -> Market {
+    Market {
+        id: "BTC/USDC".to_string(),
+        base_ticker: "BTC".to_string(),
+        quote_ticker: "USDC".to_string(),
+        tick_size: 1,
+        lot_size: 10,
+        min_size: 100,
+        maker_fee_bps: 0,
+        taker_fee_bps: 0,
+    }
+}
+
+fn book() -> LocalOrderbook {
+    let mut book = LocalOrderbook::new("BTC/USDC");
+    book.replace(
+        vec![
+            level(100, 10_000),
+            level(99, 20_000),
+            level(98, 30_000),
+            level(97, 1_000_000),
+        ],
+        vec![level(101, 4_000), level(102, 6_000)],
+    );
+    book
+}
+
+fn five_percent_of_top_three() -> MarketImpactLimit {
+    MarketImpactLimit {
+        levels: 3,
+        max_share_bps: 500,
+    }
+}
+
+#[test]
+fn test_max_size_is_share_of_top_levels_taken_from() {
+    let limit = five_percent_of_top_three();
+    let book = book();
+
+    // Sellers hit the bids: 5% of 60,000, ignoring the deep fourth level
+    assert_eq!(limit.max_size(&book, Side::Sell), 3_000);
+    // Buyers lift the asks: 5% of the 10,000 there is
+    assert_eq!(limit.max_size(&book, Side::Buy), 500);
+}
+
+#[test]
+fn test_limit_caps_and_rounds_to_lot() {
+    let limit = MarketImpactLimit {
+        levels: 3,
+        max_share_bps: 333,
+    };
+    let book = book();
+    let market = market();
+
+    // Small orders pass through untouched
+    assert_eq!(limit.limit(&book, &market, Side::Sell, 150), Some(150));
+    // Large ones are cut to 3.33% of 60,000 and down to the lot size
+    assert_eq!(
+        limit.limit(&book, &market, Side::Sell, 1_000_000),
+        Some(1_990)
+    );
+}
+
+#[test]
+fn test_limit_skips_orders_under_minimum() {
+    let limit = five_percent_of_top_three();
+    let market = market();
+
+    // Too thin a book leaves less than the minimum order
+    let mut thin = LocalOrderbook::new("BTC/USDC");
+    thin.replace(vec![level(100, 1_000)], Vec::new());
+    assert_eq!(limit.limit(&thin, &market, Side::Sell, 10_000), None);
+
+    // Nothing is taken from an empty side or a book not yet synced
+    assert_eq!(limit.limit(&thin, &market, Side::Buy, 10_000), None);
+    let unsynced = LocalOrderbook::new("BTC/USDC");
+    assert_eq!(limit.limit(&unsynced, &market, Side::Sell, 10_000), None);
+}
+
+#[test]
+fn test_atoms_to_decimal() {
+    assert_eq!(atoms_to_decimal(150_000_000, 8), "1.5");
+    assert_eq!(atoms_to_decimal(1_990, 8), "0.0000199");
+    assert_eq!(atoms_to_decimal(200_000_000, 8), "2");
+    assert_eq!(atoms_to_decimal(0, 6), "0");
+    assert_eq!(atoms_to_decimal(42, 0), "42");
+}
+
+#[test]
+fn test_websocket_url_from_exchange_url() {
+    assert_eq!(
+        websocket_url("http://localhost:8888"),
+        "ws://localhost:8888/ws"
+    );
+    assert_eq!(
+        websocket_url("https://exchange.example.com/"),
+        "wss://exchange.example.com/ws"
+    );
+}
@@ -0,0 +1,51 @@
+use exchange_bots::markets::events::outcome_market_maker::{lmsr_prices, outcome_quotes};
+
+#[test]
+fn test_lmsr_prices_sum_to_one() {
+    for shares in [
+        vec![0.0, 0.0],
+        vec![0.0, 0.0, 0.0, 0.0],
+        vec![250.0, -100.0, 0.0],
+        vec![1e6, 0.0, -1e6],
+    ] {
+        let prices = lmsr_prices(&shares, 1000.0);
+        let total: f64 = prices.iter().sum();
+        assert!((total - 1.0).abs() < 1e-9, "{:?} sums to {}", prices, total);
+        assert!(prices.iter().all(|p| (0.0..=1.0).contains(p)));
+    }
+
+    // Equal shares price every outcome equally
+    let prices = lmsr_prices(&[0.0, 0.0, 0.0, 0.0], 1000.0);
+    assert!(prices.iter().all(|p| (p - 0.25).abs() < 1e-12));
+}
+
+#[test]
+fn test_selling_an_outcome_raises_its_price() {
+    let before = lmsr_prices(&[0.0, 0.0, 0.0], 1000.0);
+    let after = lmsr_prices(&[100.0, 0.0, 0.0], 1000.0);
+    assert!(after[0] > before[0]);
+    assert!(after[1] < before[1]);
+    assert!((after[1] - after[2]).abs() < 1e-12);
+}
+
+#[test]
+fn test_outcome_quotes_round_outwards_inside_bounds() {
+    let (bid, ask) = outcome_quotes(0.3333, 100, 0.001);
+    assert!((bid - 0.329).abs() < 1e-9);
+    assert!((ask - 0.337).abs() < 1e-9);
+
+    // Bids of every outcome sum to at most one, asks to at least one
+    let prices = lmsr_prices(&[0.0, 0.0, 0.0], 1000.0);
+    let quotes: Vec<(f64, f64)> = prices
+        .iter()
+        .map(|p| outcome_quotes(*p, 100, 0.001))
+        .collect();
+    assert!(quotes.iter().map(|(bid, _)| bid).sum::<f64>() <= 1.0);
+    assert!(quotes.iter().map(|(_, ask)| ask).sum::<f64>() >= 1.0);
+
+    // Extreme prices stay quotable
+    let (bid, ask) = outcome_quotes(0.0, 100, 0.001);
+    assert!((bid - 0.001).abs() < 1e-9 && (ask - 0.002).abs() < 1e-9);
+    let (bid, ask) = outcome_quotes(1.0, 100, 0.001);
+    assert!((bid - 0.99).abs() < 1e-9 && (ask - 0.999).abs() < 1e-9);
+}
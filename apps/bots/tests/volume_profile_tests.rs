@@ -0,0 +1,75 @@
+use exchange_bots::utils::volume_profile::{NewsBurst, VolumeProfile};
+
+const HOUR: f64 = 3600.0;
+
+fn profile() -> VolumeProfile {
+    VolumeProfile {
+        open_hour: 13.5,
+        close_hour: 20.0,
+        open_multiplier: 3.0,
+        midday_multiplier: 0.8,
+        off_hours_multiplier: 0.2,
+        news: Vec::new(),
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_session_is_u_shaped() {
+    let profile = profile();
+
+    // Busiest at the open and close, quietest midway through
+    assert_close(profile.multiplier(13.5 * HOUR), 3.0);
+    assert_close(profile.multiplier(16.75 * HOUR), 0.8);
+    assert!(profile.multiplier(14.0 * HOUR) > profile.multiplier(15.0 * HOUR));
+    assert!(profile.multiplier(19.0 * HOUR) > profile.multiplier(18.0 * HOUR));
+
+    // Flat outside the session, on any day
+    assert_close(profile.multiplier(20.0 * HOUR), 0.2);
+    assert_close(profile.multiplier(3.0 * HOUR), 0.2);
+    assert_close(profile.multiplier(3.0 * HOUR + 7.0 * 24.0 * HOUR), 0.2);
+    assert_close(profile.peak(), 3.0);
+}
+
+#[test]
+fn test_session_can_span_midnight() {
+    let profile = VolumeProfile {
+        open_hour: 22.0,
+        close_hour: 2.0,
+        ..profile()
+    };
+
+    assert_close(profile.multiplier(22.0 * HOUR), 3.0);
+    assert_close(profile.multiplier(0.0), 0.8);
+    assert_close(profile.multiplier(12.0 * HOUR), 0.2);
+}
+
+#[test]
+fn test_news_bursts_fade_either_side() {
+    let profile = VolumeProfile {
+        news: vec![NewsBurst {
+            hour: 3.0,
+            multiplier: 5.0,
+            decay_secs: 300.0,
+        }],
+        ..profile()
+    };
+
+    assert_close(profile.multiplier(3.0 * HOUR), 0.2 * 5.0);
+    let before = profile.multiplier(3.0 * HOUR - 300.0);
+    let after = profile.multiplier(3.0 * HOUR + 300.0);
+    assert_close(before, after);
+    assert_close(after, 0.2 * (1.0 + 4.0 * (-1.0f64).exp()));
+
+    // Long after the news the curve is back to normal
+    assert!((profile.multiplier(5.0 * HOUR) - 0.2).abs() < 1e-6);
+    assert_close(profile.peak(), 15.0);
+}
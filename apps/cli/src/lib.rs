@@ -0,0 +1,373 @@
+//! Admin CLI
+//!
+//! Day-to-day operations against a running exchange (creating tokens and
+//! markets, fauceting, halting markets, cancelling a user's orders and
+//! inspecting balances and orders) over the SDK, instead of curl scripts
+//! against the admin endpoints.
+
+use anyhow::{bail, Context, Result};
+use exchange_sdk::{
+    format_price, format_size, AdminClient, ExchangeClient, MarketStatus, Order, Token,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub const USAGE: &str = "Usage:
+  exchange-cli token create <TICKER> <DECIMALS> <NAME>
+  exchange-cli market create <BASE> <QUOTE> --tick <ATOMS> --lot <ATOMS> --min <ATOMS>
+                             [--maker-fee <BPS>] [--taker-fee <BPS>]
+  exchange-cli market halt|resume|delist <MARKET>
+  exchange-cli faucet <USER> <TOKEN> <ATOMS>
+  exchange-cli orders list <USER> [--market <MARKET>]
+  exchange-cli orders cancel <USER> [--market <MARKET>]
+  exchange-cli balances <USER>
+
+Reads EXCHANGE_URL (default http://localhost:8888) and, for token, market and
+faucet commands, ADMIN_TOKEN from the environment.";
+
+/// Exchange the CLI talks to when `EXCHANGE_URL` is unset
+pub const DEFAULT_URL: &str = "http://localhost:8888";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    CreateToken {
+        ticker: String,
+        decimals: u8,
+        name: String,
+    },
+    CreateMarket {
+        base_ticker: String,
+        quote_ticker: String,
+        tick_size: u128,
+        lot_size: u128,
+        min_size: u128,
+        maker_fee_bps: i32,
+        taker_fee_bps: i32,
+    },
+    SetMarketStatus {
+        market_id: String,
+        status: MarketStatus,
+    },
+    Faucet {
+        user_address: String,
+        token_ticker: String,
+        amount: u128,
+    },
+    ListOrders {
+        user_address: String,
+        market_id: Option<String>,
+    },
+    CancelOrders {
+        user_address: String,
+        market_id: Option<String>,
+    },
+    Balances {
+        user_address: String,
+    },
+}
+
+impl Command {
+    /// Parse the command line, without the program name
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut args = Args::split(args)?;
+        let words: Vec<&str> = args.positional.iter().map(String::as_str).collect();
+
+        let command = match words.as_slice() {
+            ["token", "create", ticker, decimals, name @ ..] if !name.is_empty() => {
+                Command::CreateToken {
+                    ticker: ticker.to_string(),
+                    decimals: number("decimals", decimals)?,
+                    name: name.join(" "),
+                }
+            }
+            ["market", "create", base, quote] => Command::CreateMarket {
+                base_ticker: base.to_string(),
+                quote_ticker: quote.to_string(),
+                tick_size: args.required("tick")?,
+                lot_size: args.required("lot")?,
+                min_size: args.required("min")?,
+                maker_fee_bps: args.optional("maker-fee")?.unwrap_or(0),
+                taker_fee_bps: args.optional("taker-fee")?.unwrap_or(0),
+            },
+            ["market", action @ ("halt" | "resume" | "delist"), market_id] => {
+                Command::SetMarketStatus {
+                    market_id: market_id.to_string(),
+                    status: match *action {
+                        "halt" => MarketStatus::Halted,
+                        "resume" => MarketStatus::Active,
+                        _ => MarketStatus::Delisted,
+                    },
+                }
+            }
+            ["faucet", user, token, amount] => Command::Faucet {
+                user_address: user.to_string(),
+                token_ticker: token.to_string(),
+                amount: number("amount", amount)?,
+            },
+            ["orders", "list", user] => Command::ListOrders {
+                user_address: user.to_string(),
+                market_id: args.optional("market")?,
+            },
+            ["orders", "cancel", user] => Command::CancelOrders {
+                user_address: user.to_string(),
+                market_id: args.optional("market")?,
+            },
+            ["balances", user] => Command::Balances {
+                user_address: user.to_string(),
+            },
+            _ => bail!("{}", USAGE),
+        };
+
+        if let Some(flag) = args.flags.keys().next() {
+            bail!("Unexpected option --{}\n\n{}", flag, USAGE);
+        }
+        Ok(command)
+    }
+}
+
+/// Positional arguments and `--flag value` options, in any order
+struct Args {
+    positional: Vec<String>,
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    fn split(args: &[String]) -> Result<Self> {
+        let mut positional = Vec::new();
+        let mut flags = HashMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(flag) => {
+                    let value = args
+                        .next()
+                        .with_context(|| format!("--{} needs a value\n\n{}", flag, USAGE))?;
+                    flags.insert(flag.to_string(), value.clone());
+                }
+                None => positional.push(arg.clone()),
+            }
+        }
+        Ok(Self { positional, flags })
+    }
+
+    /// Take an option out, so anything left over is reported as unexpected
+    fn optional<T: std::str::FromStr>(&mut self, flag: &str) -> Result<Option<T>> {
+        self.flags
+            .remove(flag)
+            .map(|value| number(flag, &value))
+            .transpose()
+    }
+
+    fn required<T: std::str::FromStr>(&mut self, flag: &str) -> Result<T> {
+        self.optional(flag)?
+            .with_context(|| format!("Missing --{}\n\n{}", flag, USAGE))
+    }
+}
+
+fn number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .ok()
+        .with_context(|| format!("Invalid {} '{}'", name, value))
+}
+
+/// Runs commands against one exchange
+pub struct Cli {
+    client: ExchangeClient,
+    admin: Option<AdminClient>,
+}
+
+impl Cli {
+    /// Admin commands fail without `admin_token`; the rest don't need it
+    pub fn new(base_url: impl Into<String>, admin_token: Option<String>) -> Self {
+        let client = ExchangeClient::new(base_url);
+        let admin = admin_token.map(|token| AdminClient::from_client(client.clone(), token));
+        Self { client, admin }
+    }
+
+    /// Run `command`, returning what to print
+    pub async fn run(&self, command: &Command) -> Result<String> {
+        let mut out = String::new();
+        match command.clone() {
+            Command::CreateToken {
+                ticker,
+                decimals,
+                name,
+            } => {
+                let token = self.admin()?.create_token(ticker, decimals, name).await?;
+                writeln!(
+                    out,
+                    "Created token {} ({}, {} decimals)",
+                    token.ticker, token.name, token.decimals
+                )?;
+            }
+            Command::CreateMarket {
+                base_ticker,
+                quote_ticker,
+                tick_size,
+                lot_size,
+                min_size,
+                maker_fee_bps,
+                taker_fee_bps,
+            } => {
+                let market = self
+                    .admin()?
+                    .create_market(
+                        base_ticker,
+                        quote_ticker,
+                        tick_size,
+                        lot_size,
+                        min_size,
+                        maker_fee_bps,
+                        taker_fee_bps,
+                    )
+                    .await?;
+                writeln!(
+                    out,
+                    "Created market {} (tick {}, lot {}, min {}, fees {}/{} bps)",
+                    market.id,
+                    market.tick_size,
+                    market.lot_size,
+                    market.min_size,
+                    market.maker_fee_bps,
+                    market.taker_fee_bps
+                )?;
+            }
+            Command::SetMarketStatus { market_id, status } => {
+                let cancelled = self
+                    .admin()?
+                    .set_market_status(market_id.clone(), status)
+                    .await?;
+                writeln!(
+                    out,
+                    "{} is now {}, {} orders cancelled",
+                    market_id, status, cancelled
+                )?;
+            }
+            Command::Faucet {
+                user_address,
+                token_ticker,
+                amount,
+            } => {
+                let balance = self
+                    .admin()?
+                    .faucet(
+                        user_address.clone(),
+                        token_ticker.clone(),
+                        amount.to_string(),
+                    )
+                    .await?;
+                writeln!(
+                    out,
+                    "Credited {} {} to {}, balance now {}",
+                    amount, token_ticker, user_address, balance
+                )?;
+            }
+            Command::ListOrders {
+                user_address,
+                market_id,
+            } => {
+                let orders = self.client.get_orders(&user_address, market_id).await?;
+                self.write_orders(&mut out, &orders).await?;
+            }
+            Command::CancelOrders {
+                user_address,
+                market_id,
+            } => {
+                // The trade endpoint doesn't check signatures yet, only API keys,
+                // and a request without a key acts for any user
+                let cancelled = self
+                    .client
+                    .cancel_all_orders(user_address.clone(), market_id, "admin".to_string())
+                    .await?;
+                writeln!(
+                    out,
+                    "Cancelled {} orders for {}",
+                    cancelled.count, user_address
+                )?;
+                for id in cancelled.cancelled_order_ids {
+                    writeln!(out, "  {}", id)?;
+                }
+            }
+            Command::Balances { user_address } => {
+                let balances = self.client.get_balances(&user_address).await?;
+                let tokens = self.tokens().await?;
+                if balances.is_empty() {
+                    writeln!(out, "{} has no balances", user_address)?;
+                }
+                for balance in balances {
+                    let decimals = tokens.get(&balance.token_ticker).map(|t| t.decimals);
+                    writeln!(
+                        out,
+                        "{:<8} {:>24} available  {:>24} locked",
+                        balance.token_ticker,
+                        amount(
+                            balance.amount.saturating_sub(balance.open_interest),
+                            decimals
+                        ),
+                        amount(balance.open_interest, decimals),
+                    )?;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn admin(&self) -> Result<&AdminClient> {
+        self.admin
+            .as_ref()
+            .context("ADMIN_TOKEN must be set for admin commands")
+    }
+
+    async fn tokens(&self) -> Result<HashMap<String, Token>> {
+        Ok(self
+            .client
+            .get_tokens()
+            .await?
+            .into_iter()
+            .map(|token| (token.ticker.clone(), token))
+            .collect())
+    }
+
+    /// One line per order, prices and sizes in their tokens' units
+    async fn write_orders(&self, out: &mut String, orders: &[Order]) -> Result<()> {
+        if orders.is_empty() {
+            writeln!(out, "No orders")?;
+            return Ok(());
+        }
+        let tokens = self.tokens().await?;
+        let decimals = |ticker: &str| tokens.get(ticker).map(|t| t.decimals);
+        let markets: HashMap<String, (Option<u8>, Option<u8>)> = self
+            .client
+            .get_markets()
+            .await?
+            .into_iter()
+            .map(|m| {
+                let units = (decimals(&m.base_ticker), decimals(&m.quote_ticker));
+                (m.id, units)
+            })
+            .collect();
+
+        for order in orders {
+            let (base, quote) = markets.get(&order.market_id).copied().unwrap_or_default();
+            writeln!(
+                out,
+                "{}  {:<10} {:<4} {:<6} {:>16} @ {:<16} filled {:<16} {}",
+                order.id,
+                order.market_id,
+                order.side.to_string(),
+                order.order_type.to_string(),
+                amount(order.size, base),
+                quote.map_or(order.price.to_string(), |d| format_price(order.price, d)),
+                amount(order.filled_size, base),
+                order.status,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// `atoms` in whole units when the token's decimals are known
+fn amount(atoms: u128, decimals: Option<u8>) -> String {
+    decimals.map_or(atoms.to_string(), |d| format_size(atoms, d))
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use exchange_cli::{Cli, Command, DEFAULT_URL, USAGE};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Load .env files for ADMIN_TOKEN
+    let _ = dotenvy::from_path(".env.defaults");
+    let _ = dotenvy::from_path(".env");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if matches!(
+        args.first().map(String::as_str),
+        Some("help" | "--help" | "-h")
+    ) {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let command = Command::parse(&args)?;
+
+    let url = std::env::var("EXCHANGE_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let admin_token = std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+
+    print!("{}", Cli::new(url, admin_token).run(&command).await?);
+    Ok(())
+}
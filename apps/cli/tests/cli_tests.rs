@@ -0,0 +1,167 @@
+use exchange_cli::{Cli, Command};
+use exchange_sdk::MarketStatus;
+use exchange_test_utils::{TestServer, TEST_ADMIN_TOKEN};
+
+fn parse(line: &str) -> anyhow::Result<Command> {
+    let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+    Command::parse(&args)
+}
+
+// ============================================================================
+// Parsing Tests
+// ============================================================================
+
+#[test]
+fn test_parses_every_command() {
+    assert_eq!(
+        parse("token create ETH 18 Wrapped Ether").unwrap(),
+        Command::CreateToken {
+            ticker: "ETH".to_string(),
+            decimals: 18,
+            name: "Wrapped Ether".to_string(),
+        }
+    );
+    assert_eq!(
+        parse("market create ETH USDC --tick 10000 --lot 1000 --min 2000 --taker-fee 10").unwrap(),
+        Command::CreateMarket {
+            base_ticker: "ETH".to_string(),
+            quote_ticker: "USDC".to_string(),
+            tick_size: 10_000,
+            lot_size: 1_000,
+            min_size: 2_000,
+            maker_fee_bps: 0,
+            taker_fee_bps: 10,
+        }
+    );
+    assert_eq!(
+        parse("market halt BTC/USDC").unwrap(),
+        Command::SetMarketStatus {
+            market_id: "BTC/USDC".to_string(),
+            status: MarketStatus::Halted,
+        }
+    );
+    assert_eq!(
+        parse("market resume BTC/USDC").unwrap(),
+        Command::SetMarketStatus {
+            market_id: "BTC/USDC".to_string(),
+            status: MarketStatus::Active,
+        }
+    );
+    assert_eq!(
+        parse("faucet alice USDC 1000000").unwrap(),
+        Command::Faucet {
+            user_address: "alice".to_string(),
+            token_ticker: "USDC".to_string(),
+            amount: 1_000_000,
+        }
+    );
+    // Options may come before the positional arguments
+    assert_eq!(
+        parse("orders --market BTC/USDC cancel alice").unwrap(),
+        Command::CancelOrders {
+            user_address: "alice".to_string(),
+            market_id: Some("BTC/USDC".to_string()),
+        }
+    );
+    assert_eq!(
+        parse("orders list alice").unwrap(),
+        Command::ListOrders {
+            user_address: "alice".to_string(),
+            market_id: None,
+        }
+    );
+    assert_eq!(
+        parse("balances alice").unwrap(),
+        Command::Balances {
+            user_address: "alice".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_rejects_malformed_commands() {
+    let error = |line: &str| parse(line).unwrap_err().to_string();
+
+    assert!(error("").starts_with("Usage:"));
+    assert!(error("market pause BTC/USDC").starts_with("Usage:"));
+    assert!(error("token create ETH 18").starts_with("Usage:"));
+    assert!(error("market create ETH USDC --tick 1 --lot 1").starts_with("Missing --min"));
+    assert!(
+        error("market create ETH USDC --tick 1 --lot 1 --min -5").starts_with("Invalid min '-5'")
+    );
+    assert!(error("faucet alice USDC 1.5").starts_with("Invalid amount '1.5'"));
+    assert!(error("token create ETH 300 Ether").starts_with("Invalid decimals '300'"));
+    assert!(error("balances alice --market BTC/USDC").starts_with("Unexpected option --market"));
+    assert!(error("orders list alice --market").starts_with("--market needs a value"));
+}
+
+#[tokio::test]
+async fn test_admin_commands_need_a_token() {
+    // Refused before any request is sent, so no server is needed
+    let cli = Cli::new("http://127.0.0.1:1", None);
+    let error = cli
+        .run(&parse("faucet alice USDC 1").unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ADMIN_TOKEN must be set for admin commands"
+    );
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_operates_a_market_end_to_end() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let cli = Cli::new(&server.base_url, Some(TEST_ADMIN_TOKEN.to_string()));
+    let run = |line: &'static str| {
+        let cli = &cli;
+        async move {
+            cli.run(&parse(line).unwrap())
+                .await
+                .unwrap_or_else(|e| panic!("{}: {}", line, e))
+        }
+    };
+
+    run("token create BTC 8 Bitcoin").await;
+    run("token create USDC 6 USD Coin").await;
+    let created = run("market create BTC USDC --tick 1000 --lot 1000 --min 1000").await;
+    assert!(
+        created.starts_with("Created market BTC/USDC"),
+        "{}",
+        created
+    );
+
+    run("faucet alice USDC 100000000").await;
+    let balances = run("balances alice").await;
+    assert!(balances.contains("USDC"), "{}", balances);
+    assert!(balances.contains("100"), "{}", balances);
+
+    // A resting bid, then cancelled by the operator
+    let client = exchange_sdk::ExchangeClient::new(&server.base_url);
+    client
+        .place_order(
+            "alice".to_string(),
+            "BTC/USDC".to_string(),
+            exchange_sdk::Side::Buy,
+            exchange_sdk::OrderType::Limit,
+            "10000000".to_string(),
+            "100000".to_string(),
+            "sig".to_string(),
+        )
+        .await
+        .expect("Failed to place order");
+    let orders = run("orders list alice").await;
+    assert!(orders.contains("BTC/USDC"), "{}", orders);
+    let cancelled = run("orders cancel alice --market BTC/USDC").await;
+    assert!(cancelled.starts_with("Cancelled 1 orders"), "{}", cancelled);
+
+    let halted = run("market halt BTC/USDC").await;
+    assert!(halted.starts_with("BTC/USDC is now halted"), "{}", halted);
+    run("market resume BTC/USDC").await;
+}
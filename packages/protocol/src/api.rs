@@ -0,0 +1,2587 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::domain::{
+    AccountTransfer, ApiKey, ApiKeyScope, Balance, CancelReason, CostBasisMethod, Deposit,
+    EventOutcome, EventStatus, FeeOverride, FeeRoute, KillSwitch, LedgerEntry, LedgerEntryKind,
+    Liquidation, LiquidityRole, MarginMode, Market, MarketStatus, Order, OrderStatus, OrderType,
+    PlacedOco, PlacedOrder, PredictionEvent, QueuePosition, Quote, QuoteRequest, Referral,
+    RejectReason, RevenueSource, RfqStatus, Side, Statement, StatementBalance, StatementMarket,
+    SubAccount, SurveillanceAlert, SurveillanceKind, SystemAccount, TimeInForce, Token, Trade,
+    TriggerSource, UserLimits, UserStatus, UserSummary, Webhook, WebhookDeadLetter, Withdrawal,
+    WithdrawalStatus,
+};
+use super::error_code::ErrorCode;
+
+// ============================================================================
+// REST API TYPES
+// ============================================================================
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiResponse {
+    pub message: String,
+    pub timestamp: u64,
+}
+
+/// Response after successfully placing an order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderPlaced {
+    pub order: ApiOrder,
+    pub trades: Vec<ApiTrade>,
+}
+
+/// Response after successfully placing a one-cancels-other pair
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OcoPlaced {
+    pub oco_id: String, // UUID as string
+    pub first: ApiOrder,
+    pub second: ApiOrder,
+}
+
+/// Response after successfully cancelling an order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderCancelled {
+    pub order_id: String, // UUID as string for OpenAPI compatibility
+}
+
+/// Response after successfully cancelling all orders
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrdersCancelled {
+    pub cancelled_order_ids: Vec<String>, // UUIDs as strings for OpenAPI compatibility
+    pub count: usize,
+}
+
+// ============================================================================
+// INFO API TYPES
+// ============================================================================
+
+/// Info request with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InfoRequest {
+    TokenDetails { ticker: String },
+    MarketDetails { market_id: String },
+    AllMarkets,
+    AllTokens,
+}
+
+/// Info response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InfoResponse {
+    TokenDetails { token: Token },
+    MarketDetails { market: ApiMarket },
+    AllMarkets { markets: Vec<ApiMarket> },
+    AllTokens { tokens: Vec<Token> },
+}
+
+// ============================================================================
+// USER API TYPES
+// ============================================================================
+
+/// User request with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UserRequest {
+    Orders {
+        user_address: String,
+        market_id: Option<String>,
+        status: Option<String>,
+        limit: Option<u32>,
+    },
+    Balances {
+        user_address: String,
+    },
+    Trades {
+        user_address: String,
+        market_id: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Resting orders per market against the open-order cap
+    OpenOrderUsage {
+        user_address: String,
+        market_id: Option<String>,
+    },
+    /// Size resting ahead of one of the user's orders at its price
+    QueuePosition {
+        user_address: String,
+        order_id: String, // UUID as string
+    },
+    /// Profile, open orders, 30-day volume, fees paid and balances in one read
+    Summary {
+        user_address: String,
+    },
+    /// Fees earned as a referrer, per token
+    ReferralEarnings {
+        user_address: String,
+    },
+    /// Opt in to the leaderboard under a public name; `None` opts out
+    SetDisplayName {
+        user_address: String,
+        display_name: Option<String>,
+        signature: String, // Cryptographic signature for authentication
+    },
+    /// Post the user's fills and order updates to `url`
+    RegisterWebhook {
+        user_address: String,
+        url: String,
+        signature: String, // Cryptographic signature for authentication
+    },
+    Webhooks {
+        user_address: String,
+    },
+    DeleteWebhook {
+        user_address: String,
+        webhook_id: String, // UUID as string
+        signature: String,  // Cryptographic signature for authentication
+    },
+    /// Deliveries that failed every retry, newest first
+    WebhookDeadLetters {
+        user_address: String,
+        limit: Option<u32>,
+    },
+    /// On-chain deposits credited to the user, newest first
+    Deposits {
+        user_address: String,
+        limit: Option<u32>,
+    },
+    /// Send `amount` of a token to `destination` on chain; the amount is locked until it settles
+    Withdraw {
+        user_address: String,
+        token_ticker: String,
+        amount: String,      // u128 as string
+        destination: String, // 0x-prefixed address
+        signature: String,   // Cryptographic signature for authentication
+    },
+    /// Cancel a withdrawal the processor has not picked up yet
+    CancelWithdrawal {
+        user_address: String,
+        withdrawal_id: String, // UUID as string
+        signature: String,     // Cryptographic signature for authentication
+    },
+    /// The user's withdrawals, newest first
+    Withdrawals {
+        user_address: String,
+        limit: Option<u32>,
+    },
+    /// Margin perpetual positions on their own or together; only without open positions
+    SetMarginMode {
+        user_address: String,
+        mode: MarginMode,
+        signature: String, // Cryptographic signature for authentication
+    },
+    /// Open a named sub-account with its own balances and orders
+    CreateSubAccount {
+        user_address: String,
+        name: String,
+        signature: String, // Cryptographic signature for authentication
+    },
+    SubAccounts {
+        user_address: String,
+    },
+    /// Move free balance between the user and its sub-accounts, or between two of them
+    InternalTransfer {
+        user_address: String,
+        from_address: String,
+        to_address: String,
+        token_ticker: String,
+        amount: String,    // u128 as string
+        signature: String, // Cryptographic signature for authentication
+    },
+    /// Balances of the user and each of its sub-accounts, with totals per token
+    AggregateBalances {
+        user_address: String,
+    },
+    /// Issue an API key limited to `scopes` and, if any are given, to requests from `ip_allowlist`
+    CreateApiKey {
+        user_address: String,
+        label: String,
+        scopes: Vec<ApiKeyScope>,
+        #[serde(default)]
+        ip_allowlist: Vec<String>, // IP addresses or CIDR ranges
+        signature: String, // Cryptographic signature for authentication
+    },
+    /// The user's API keys that haven't been revoked
+    ApiKeys {
+        user_address: String,
+    },
+    /// Replace an API key's secret, keeping its scopes and allowlist; the old one stops working
+    RotateApiKey {
+        user_address: String,
+        key_id: String,    // UUID as string
+        signature: String, // Cryptographic signature for authentication
+    },
+    RevokeApiKey {
+        user_address: String,
+        key_id: String,    // UUID as string
+        signature: String, // Cryptographic signature for authentication
+    },
+}
+
+/// User response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UserResponse {
+    Orders {
+        orders: Vec<ApiOrder>,
+    },
+    Balances {
+        balances: Vec<ApiBalance>,
+    },
+    Trades {
+        trades: Vec<ApiTrade>,
+        /// Maker rebates received across all of the user's fills, per token
+        #[serde(default)]
+        rebate_totals: Vec<ApiRebateTotal>,
+    },
+    OpenOrderUsage {
+        usage: Vec<ApiOpenOrderUsage>,
+    },
+    QueuePosition {
+        position: ApiQueuePosition,
+    },
+    Summary {
+        summary: ApiUserSummary,
+    },
+    ReferralEarnings {
+        earnings: Vec<ApiReferralEarnings>,
+    },
+    SetDisplayName {
+        user_address: String,
+        display_name: Option<String>,
+    },
+    /// `secret` signs every delivery and is only ever returned here
+    RegisterWebhook {
+        webhook: ApiWebhook,
+        secret: String,
+    },
+    Webhooks {
+        webhooks: Vec<ApiWebhook>,
+    },
+    DeleteWebhook {
+        webhook_id: String,
+    },
+    WebhookDeadLetters {
+        dead_letters: Vec<ApiWebhookDeadLetter>,
+    },
+    Deposits {
+        deposits: Vec<ApiDeposit>,
+    },
+    Withdraw {
+        withdrawal: ApiWithdrawal,
+    },
+    CancelWithdrawal {
+        withdrawal: ApiWithdrawal,
+    },
+    Withdrawals {
+        withdrawals: Vec<ApiWithdrawal>,
+    },
+    SetMarginMode {
+        user_address: String,
+        mode: MarginMode,
+    },
+    CreateSubAccount {
+        sub_account: ApiSubAccount,
+    },
+    SubAccounts {
+        sub_accounts: Vec<ApiSubAccount>,
+    },
+    /// Both accounts' balances after the transfer
+    InternalTransfer {
+        transfer: ApiAccountTransfer,
+        balances: Vec<ApiBalance>,
+    },
+    AggregateBalances {
+        accounts: Vec<ApiBalance>,
+        totals: Vec<ApiAggregateBalance>,
+    },
+    /// `secret` is the key itself and is only ever returned here
+    CreateApiKey {
+        api_key: ApiKeyDetails,
+        secret: String,
+    },
+    ApiKeys {
+        api_keys: Vec<ApiKeyDetails>,
+    },
+    /// `secret` is the key's new value and is only ever returned here
+    RotateApiKey {
+        api_key: ApiKeyDetails,
+        secret: String,
+    },
+    RevokeApiKey {
+        key_id: String,
+    },
+}
+
+/// A user's resting orders in one market and the most they may have
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiOpenOrderUsage {
+    pub market_id: String,
+    pub open_orders: u64,
+    pub max_open_orders: u64,
+}
+
+/// How much rests ahead of an order at its price
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiQueuePosition {
+    pub order_id: String, // UUID as string
+    pub market_id: String,
+    pub side: Side,
+    pub price: String,     // u128 as string
+    pub remaining: String, // u128 as string
+    pub orders_ahead: u64,
+    pub size_ahead: String, // u128 as string
+    pub level_size: String, // u128 as string, this order included
+}
+
+/// API representation of LedgerEntry with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiLedgerEntry {
+    pub id: i64,
+    pub account: SystemAccount,
+    pub token_ticker: String,
+    pub amount: String, // i128 as string, negative for payouts
+    pub kind: LedgerEntryKind,
+    pub trade_id: Option<String>, // UUID as string
+    pub created_at: DateTime<Utc>,
+}
+
+/// API representation of SurveillanceAlert with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiSurveillanceAlert {
+    pub id: i64,
+    pub kind: SurveillanceKind,
+    pub market_id: String,
+    pub subject: String,
+    pub accounts: Vec<String>,
+    pub count: u64,
+    pub volume: String, // u128 as string, base atoms
+    pub detail: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Taker fees a referrer has earned from the users they referred, in one token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiReferralEarnings {
+    pub token_ticker: String,
+    pub amount: String, // u128 as string
+    pub referred_users: u64,
+}
+
+/// A registered webhook, without its signing secret
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiWebhook {
+    pub id: String, // UUID as string
+    pub user_address: String,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An API key, without the key itself
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyDetails {
+    pub id: String, // UUID as string
+    pub user_address: String,
+    pub label: String,
+    pub key_prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub ip_allowlist: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A webhook delivery that failed every attempt
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiWebhookDeadLetter {
+    pub id: String,         // UUID as string
+    pub webhook_id: String, // UUID as string
+    pub event_type: String,
+    /// The JSON body that was posted
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An on-chain deposit credited to a user's balance
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiDeposit {
+    pub chain_id: u64,
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: String, // u128 as string
+    pub created_at: DateTime<Utc>,
+}
+
+/// A user's withdrawal and how far it has got
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiWithdrawal {
+    pub id: String, // UUID as string
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: String, // u128 as string
+    pub destination: String,
+    pub status: WithdrawalStatus,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A named account of a master user with its own balances and orders
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiSubAccount {
+    pub address: String,
+    pub master_address: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Free balance a master moved between its own accounts
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiAccountTransfer {
+    pub id: String, // UUID as string
+    pub master_address: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub token_ticker: String,
+    pub amount: String, // u128 as string
+    pub created_at: DateTime<Utc>,
+}
+
+/// One token summed over a master and all of its sub-accounts
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiAggregateBalance {
+    pub token_ticker: String,
+    pub amount: String,        // u128 as string
+    pub open_interest: String, // u128 as string
+}
+
+/// Total maker rebates a user has received in one token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiRebateTotal {
+    pub token_ticker: String,
+    pub amount: String, // u128 as string
+}
+
+/// An amount of one token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiTokenAmount {
+    pub token_ticker: String,
+    pub amount: String, // u128 as string
+}
+
+/// A user's profile with their activity and holdings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiUserSummary {
+    pub user_address: String,
+    pub created_at: DateTime<Utc>,
+    pub status: UserStatus,
+    pub display_name: Option<String>,
+    pub open_orders: u64,
+    /// Quote notional traded in the last 30 days, per quote token
+    pub volume_30d: Vec<ApiTokenAmount>,
+    /// Trading fees paid, per token, before any maker rebates
+    pub fees_paid: Vec<ApiTokenAmount>,
+    pub balances: Vec<ApiBalance>,
+}
+
+// ============================================================================
+// TRADE API TYPES
+// ============================================================================
+
+/// Trade request with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TradeRequest {
+    PlaceOrder {
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        price: String,     // u128 as string
+        size: String,      // u128 as string
+        signature: String, // Cryptographic signature for authentication
+        /// Price that activates a stop order; required for stops
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trigger_price: Option<String>, // u128 as string
+        /// Whether the last trade (default) or the index price reaches a
+        /// stop's trigger price
+        #[serde(default)]
+        trigger_source: TriggerSource,
+        /// How long an unfilled remainder rests; good till cancelled if omitted
+        #[serde(default)]
+        time_in_force: TimeInForce,
+        /// When to cancel the order if it's still resting; only for orders
+        /// that rest
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<DateTime<Utc>>,
+    },
+    CancelOrder {
+        user_address: String,
+        order_id: String,  // UUID as string
+        signature: String, // Cryptographic signature for authentication
+    },
+    CancelAllOrders {
+        user_address: String,
+        market_id: Option<String>, // Optional: cancel only for specific market
+        signature: String,         // Cryptographic signature for authentication
+    },
+}
+
+/// Trade response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum TradeResponse {
+    PlaceOrder {
+        order: ApiOrder,
+        trades: Vec<ApiTrade>,
+    },
+    CancelOrder {
+        order_id: String,
+    },
+    CancelAllOrders {
+        cancelled_order_ids: Vec<String>,
+        count: usize,
+    },
+}
+
+// ============================================================================
+// OCO API TYPES
+// ============================================================================
+
+/// One order of a one-cancels-other pair
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OcoLeg {
+    /// `limit`, `stop_market` or `stop_limit`
+    pub order_type: OrderType,
+    pub price: String, // u128 as string
+    /// Price that activates a stop leg; required for stops
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger_price: Option<String>, // u128 as string
+    /// Whether the last trade (default) or the index price reaches a stop
+    /// leg's trigger price
+    #[serde(default)]
+    pub trigger_source: TriggerSource,
+}
+
+/// Place two linked orders for the same side and size, a take-profit
+/// limit and a stop; the first fill on either, or the stop triggering,
+/// cancels the other
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OcoRequest {
+    pub user_address: String,
+    pub market_id: String,
+    pub side: Side,
+    pub size: String, // u128 as string
+    pub first: OcoLeg,
+    pub second: OcoLeg,
+    pub signature: String, // Cryptographic signature for authentication
+}
+
+// ============================================================================
+// DRIP API TYPES
+// ============================================================================
+
+/// Drip request with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DripRequest {
+    Faucet {
+        user_address: String,
+        token_ticker: String,
+        amount: String,    // u128 as string
+        signature: String, // Cryptographic signature for authentication
+    },
+}
+
+/// Drip response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DripResponse {
+    Faucet {
+        user_address: String,
+        token_ticker: String,
+        amount: String,
+        new_balance: String,
+    },
+}
+
+// ============================================================================
+// RFQ API TYPES
+// ============================================================================
+
+/// Request-for-quote request with type discriminator
+///
+/// A taker requests a quote, registered makers answer with firm prices until
+/// the request expires, and the taker accepts one to trade off the book.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RfqRequest {
+    /// Ask makers for a firm price on `size`, open for `ttl_secs`
+    RequestQuote {
+        user_address: String,
+        market_id: String,
+        side: Side,   // The taker's side
+        size: String, // u128 as string
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl_secs: Option<u64>,
+        signature: String, // Cryptographic signature for authentication
+    },
+    /// Quote a firm price on an open request; quoting again replaces the maker's quote
+    SubmitQuote {
+        user_address: String,
+        request_id: String, // UUID as string
+        price: String,      // u128 as string
+        /// Defaults to the request's remaining TTL, which it can't outlive
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl_secs: Option<u64>,
+        signature: String, // Cryptographic signature for authentication
+    },
+    /// Trade the request's size at a live quote's price
+    AcceptQuote {
+        user_address: String,
+        request_id: String, // UUID as string
+        quote_id: String,   // UUID as string
+        signature: String,  // Cryptographic signature for authentication
+    },
+    CancelRequest {
+        user_address: String,
+        request_id: String, // UUID as string
+        signature: String,  // Cryptographic signature for authentication
+    },
+    /// Open requests makers can quote, oldest first
+    OpenRequests {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        market_id: Option<String>,
+    },
+    /// A taker's request and the live quotes on it, best price first
+    Quotes {
+        user_address: String,
+        request_id: String, // UUID as string
+    },
+}
+
+/// Request-for-quote response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum RfqResponse {
+    RequestQuote {
+        request: ApiQuoteRequest,
+    },
+    SubmitQuote {
+        quote: ApiQuote,
+    },
+    AcceptQuote {
+        request: ApiQuoteRequest,
+        trade: ApiTrade,
+    },
+    CancelRequest {
+        request: ApiQuoteRequest,
+    },
+    OpenRequests {
+        requests: Vec<ApiQuoteRequest>,
+    },
+    Quotes {
+        request: ApiQuoteRequest,
+        quotes: Vec<ApiQuote>,
+    },
+}
+
+/// API representation of a request for quote
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiQuoteRequest {
+    pub id: String, // UUID as string
+    pub taker_address: String,
+    pub market_id: String,
+    pub side: Side,   // The taker's side
+    pub size: String, // u128 as string
+    pub status: RfqStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_id: Option<String>, // UUID as string
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// API representation of a maker's firm quote
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiQuote {
+    pub id: String,         // UUID as string
+    pub request_id: String, // UUID as string
+    pub maker_address: String,
+    pub price: String, // u128 as string
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// ADMIN API TYPES
+// ============================================================================
+
+/// Price range of a market with an array-indexed orderbook
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiPriceLadder {
+    /// Defaults to 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<String>, // u128 as string
+    pub max_price: String, // u128 as string
+}
+
+/// Admin request with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminRequest {
+    CreateToken {
+        ticker: String,
+        decimals: u8,
+        name: String,
+    },
+    CreateMarket {
+        base_ticker: String,
+        quote_ticker: String,
+        tick_size: String, // u128 as string
+        lot_size: String,  // u128 as string
+        min_size: String,  // u128 as string
+        maker_fee_bps: i32,
+        taker_fee_bps: i32,
+        /// Bounded price range; the market gets an array-indexed orderbook
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        price_ladder: Option<ApiPriceLadder>,
+        /// Reject limit orders priced further than this from the last trade, in basis points
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        price_collar_bps: Option<u32>,
+    },
+    Faucet {
+        user_address: String,
+        token_ticker: String,
+        amount: String,
+        signature: String,
+    },
+    /// Set a user's limits in a market; omit both limits to clear them
+    SetUserLimits {
+        user_address: String,
+        market_id: String,
+        #[serde(default)]
+        max_position: Option<String>, // u128 as string, base atoms
+        #[serde(default)]
+        max_open_notional: Option<String>, // u128 as string, quote atoms
+    },
+    /// Halt, delist or reactivate a market; halting cancels its resting orders
+    SetMarketStatus {
+        market_id: String,
+        status: MarketStatus,
+    },
+    /// Record who referred a user and the share of their taker fees the referrer earns
+    SetReferral {
+        user_address: String,
+        referrer_address: String,
+        share_bps: u32,
+    },
+    /// Freeze, ban or reinstate a user; banning always cancels their resting orders
+    SetUserStatus {
+        user_address: String,
+        status: UserStatus,
+        #[serde(default)]
+        cancel_orders: bool,
+    },
+    /// Override a market's configured price collar; omit `collar_bps` to disable it
+    SetPriceCollar {
+        market_id: String,
+        #[serde(default)]
+        collar_bps: Option<u32>,
+    },
+    /// Set the share of a revenue source paid into the insurance fund
+    SetFeeRoute {
+        source: RevenueSource,
+        insurance_bps: u32,
+    },
+    /// Balances and recent ledger entries of a system account, newest first
+    SystemLedger {
+        account: SystemAccount,
+        token_ticker: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Group existing markets into a prediction event, one per outcome
+    CreateEvent {
+        event_id: String,
+        title: String,
+        outcomes: Vec<ApiEventOutcomeSpec>,
+    },
+    /// Delist an event's outcome markets and pay the winning outcome's holders
+    ResolveEvent {
+        event_id: String,
+        winning_outcome: String,
+    },
+    /// Let a user answer requests for quote
+    RegisterRfqMaker { user_address: String },
+    /// Stop a user answering requests for quote; their live quotes are withdrawn
+    RemoveRfqMaker { user_address: String },
+    /// Let a user read the per-order (L3) book
+    ApproveMarketMaker { user_address: String },
+    /// Take back a user's access to the per-order (L3) book
+    RevokeMarketMaker { user_address: String },
+    /// Record who ultimately owns an account, so trades between accounts of one
+    /// owner are surveilled as wash trades; omit `owner` to clear it
+    SetBeneficialOwner {
+        user_address: String,
+        #[serde(default)]
+        owner: Option<String>,
+    },
+    /// Trade surveillance alerts, newest first
+    SurveillanceAlerts {
+        kind: Option<SurveillanceKind>,
+        market_id: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Charge a user their own fees in a market, at most the market's; a side
+    /// left out pays the market's fee, and leaving out both clears the override
+    SetFeeOverride {
+        user_address: String,
+        market_id: String,
+        #[serde(default)]
+        maker_fee_bps: Option<i32>,
+        #[serde(default)]
+        taker_fee_bps: Option<i32>,
+    },
+}
+
+/// Admin response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminResponse {
+    CreateToken {
+        token: Token,
+    },
+    CreateMarket {
+        market: ApiMarket,
+    },
+    Faucet {
+        user_address: String,
+        token_ticker: String,
+        amount: String,
+        new_balance: String,
+    },
+    SetUserLimits {
+        limits: ApiUserLimits,
+    },
+    SetMarketStatus {
+        market_id: String,
+        status: MarketStatus,
+        cancelled_orders: usize,
+    },
+    SetReferral {
+        referral: Referral,
+    },
+    SetUserStatus {
+        user_address: String,
+        status: UserStatus,
+        cancelled_orders: usize,
+    },
+    SetPriceCollar {
+        market_id: String,
+        collar_bps: Option<u32>,
+    },
+    SetFeeRoute {
+        route: FeeRoute,
+    },
+    SystemLedger {
+        account: SystemAccount,
+        balances: Vec<ApiBalance>,
+        entries: Vec<ApiLedgerEntry>,
+    },
+    CreateEvent {
+        event: ApiPredictionEvent,
+    },
+    ResolveEvent {
+        event: ApiPredictionEvent,
+        /// Users paid out for the winning outcome
+        holders_paid: usize,
+        total_payout: String, // u128 as string, in quote atoms
+    },
+    RegisterRfqMaker {
+        user_address: String,
+    },
+    RemoveRfqMaker {
+        user_address: String,
+        withdrawn_quotes: u64,
+    },
+    ApproveMarketMaker {
+        user_address: String,
+    },
+    RevokeMarketMaker {
+        user_address: String,
+    },
+    SetBeneficialOwner {
+        user_address: String,
+        owner: Option<String>,
+    },
+    SurveillanceAlerts {
+        alerts: Vec<ApiSurveillanceAlert>,
+    },
+    SetFeeOverride {
+        user_address: String,
+        market_id: String,
+        fee_override: Option<FeeOverride>,
+    },
+}
+
+// ============================================================================
+// KILL SWITCH API TYPES
+// ============================================================================
+
+/// Kill switch request with type discriminator
+///
+/// Omitting `market_id` targets the whole exchange.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KillSwitchRequest {
+    Status,
+    /// Put a market (or the exchange) into cancel-only mode
+    Engage {
+        market_id: Option<String>,
+        reason: Option<String>,
+        /// Also cancel every resting order in scope
+        #[serde(default)]
+        cancel_orders: bool,
+    },
+    /// Resume normal trading
+    Release {
+        market_id: Option<String>,
+    },
+}
+
+/// Kill switch response with type discriminator
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KillSwitchResponse {
+    Status {
+        switches: Vec<KillSwitch>,
+    },
+    Engaged {
+        switch: KillSwitch,
+        cancelled_orders: usize,
+    },
+    Released {
+        market_id: Option<String>,
+    },
+}
+
+// ============================================================================
+// CANDLES API TYPES
+// ============================================================================
+
+/// Request for OHLCV candles
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CandlesRequest {
+    pub market_id: String,
+    pub interval: String, // 1m, 5m, 15m, 1h, 1d
+    pub from: i64,        // Unix timestamp in seconds
+    pub to: i64,          // Unix timestamp in seconds
+    #[serde(default)]
+    pub count_back: Option<usize>, // Limit results to N most recent bars before 'to'
+    /// Fill intervals without trades with the previous close and zero volume
+    /// Defaults to true; false returns only intervals that traded
+    #[serde(default)]
+    pub fill_gaps: Option<bool>,
+}
+
+/// OHLCV candle data
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[cfg_attr(feature = "clickhouse", derive(clickhouse::Row))]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCandle {
+    pub timestamp: u32,
+    pub open: u128,
+    pub high: u128,
+    pub low: u128,
+    pub close: u128,
+    pub volume: u128,
+}
+
+/// Response containing candles
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CandlesResponse {
+    pub candles: Vec<ApiCandle>,
+}
+
+// ============================================================================
+// FEE API TYPES
+// ============================================================================
+
+/// The fees a user pays in one market and their recent volume there
+///
+/// Volumes are in base token atoms over the last 30 days, leaving out trades
+/// with themselves.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiUserFees {
+    pub market_id: String,
+    pub maker_fee_bps: i32, // Charged to this user, negative is a rebate
+    pub taker_fee_bps: i32, // Charged to this user
+    pub market_maker_fee_bps: i32, // The market's own schedule
+    pub market_taker_fee_bps: i32,
+    pub fee_override: Option<FeeOverride>,
+    pub maker_volume_30d: String, // u128 as string
+    pub taker_volume_30d: String, // u128 as string
+}
+
+/// The fees a user pays in every market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserFeesResponse {
+    pub user_address: String,
+    pub markets: Vec<ApiUserFees>,
+}
+
+// ============================================================================
+// PNL API TYPES
+// ============================================================================
+
+/// Realized PnL and open position for one user in one market
+///
+/// PnL is in quote token atoms and excludes fees.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiMarketPnl {
+    pub market_id: String,
+    pub realized_pnl: String,                // i128 as string
+    pub position: String,                    // u128 as string, in base token atoms
+    pub average_entry_price: Option<String>, // u128 as string, None when flat
+}
+
+/// A user's realized PnL across every market they have traded
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserPnlResponse {
+    pub user_address: String,
+    pub method: CostBasisMethod,
+    pub markets: Vec<ApiMarketPnl>,
+}
+
+// ============================================================================
+// STATEMENT API TYPES
+// ============================================================================
+
+/// A user's monthly account statement
+///
+/// Covers [period_start, period_end), one calendar month in UTC. Closing
+/// balances were taken at `balances_at`, shortly after the month ended.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiStatement {
+    pub user_address: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub balances_at: DateTime<Utc>,
+    pub balances: Vec<ApiStatementBalance>,
+    pub markets: Vec<ApiStatementMarket>,
+}
+
+/// One token on a statement, in token atoms
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiStatementBalance {
+    pub token_ticker: String,
+    pub opening_balance: Option<String>, // u128 as string, None without a statement the month before
+    pub closing_balance: String,         // u128 as string
+    pub deposited: String,               // u128 as string
+    pub withdrawn: String,               // u128 as string, confirmed withdrawals
+    pub fees: String,                    // i128 as string, fees less maker rebates
+}
+
+/// A user's fills in one market over a statement's month
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiStatementMarket {
+    pub market_id: String,
+    pub fills: u64,
+    pub bought: String,   // u128 as string, base atoms
+    pub sold: String,     // u128 as string, base atoms
+    pub notional: String, // u128 as string, quote atoms
+}
+
+/// A user's statements, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StatementsResponse {
+    pub user_address: String,
+    pub statements: Vec<ApiStatement>,
+}
+
+// ============================================================================
+// LEADERBOARD API TYPES
+// ============================================================================
+
+/// Window a leaderboard is ranked over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum LeaderboardPeriod {
+    #[default]
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+}
+
+/// What a leaderboard ranks traders by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardMetric {
+    /// Quote volume traded
+    #[default]
+    Volume,
+    /// Realized PnL on sales in the period, FIFO cost basis, excluding fees
+    Pnl,
+}
+
+/// One ranked trader
+///
+/// `user_address` is omitted when addresses are hidden; traders who have not
+/// opted in with a display name are then anonymous.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiLeaderboardEntry {
+    pub rank: u32,
+    pub user_address: Option<String>,
+    pub display_name: Option<String>,
+    pub value: String, // i128 as string, in quote token atoms
+}
+
+/// Top traders for a period and metric
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LeaderboardResponse {
+    pub period: LeaderboardPeriod,
+    pub metric: LeaderboardMetric,
+    pub entries: Vec<ApiLeaderboardEntry>,
+}
+
+// ============================================================================
+// FILLS EXPORT API TYPES
+// ============================================================================
+
+/// File format of a fills export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+/// Progress of an export too large to stream in one response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportJobStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+/// A fills export running in the background
+///
+/// Once `status` is ready the file can be fetched from `download_url` until
+/// the job expires.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiExportJob {
+    pub job_id: String, // UUID as string
+    pub user_address: String,
+    pub format: ExportFormat,
+    pub status: ExportJobStatus,
+    pub download_url: String, // Path relative to the API host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub from: i64,       // Unix timestamp in seconds
+    pub to: i64,         // Unix timestamp in seconds
+    pub expires_at: i64, // Unix timestamp in seconds
+}
+
+// ============================================================================
+// AVERAGE PRICE API TYPES
+// ============================================================================
+
+/// A market's VWAP or TWAP over a time range
+///
+/// `price` is `None` when there is nothing to average: no trades in the range
+/// for VWAP, or no trades at all by the end of it for TWAP.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiAveragePrice {
+    pub market_id: String,
+    pub price: Option<String>, // u128 as string
+    pub from: i64,             // Unix timestamp in seconds
+    pub to: i64,               // Unix timestamp in seconds
+}
+
+// ============================================================================
+// DEPTH METRICS API TYPES
+// ============================================================================
+
+/// Liquidity at the top of a market's book, sampled periodically
+///
+/// Price fields are `None` while that side of the book is empty; depths are
+/// summed over the best `levels` price levels of each side.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiDepthSample {
+    pub timestamp: i64,           // Unix timestamp in seconds
+    pub best_bid: Option<String>, // u128 as string
+    pub best_ask: Option<String>, // u128 as string
+    pub spread: Option<String>,   // u128 as string
+    pub bid_depth: String,        // u128 as string, in base token atoms
+    pub ask_depth: String,        // u128 as string, in base token atoms
+    pub imbalance_bps: i32,       // (bid - ask) / (bid + ask), -10000 to 10000
+}
+
+/// A market's depth samples over a time range, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DepthHistoryResponse {
+    pub market_id: String,
+    pub levels: usize,
+    pub interval_secs: u64,
+    pub samples: Vec<ApiDepthSample>,
+    pub from: i64, // Unix timestamp in seconds
+    pub to: i64,   // Unix timestamp in seconds
+}
+
+// ============================================================================
+// FLOW ANALYTICS API TYPES
+// ============================================================================
+
+/// A trader's volume in a market split by whether they made or took liquidity
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiMakerVolume {
+    pub user_address: String,
+    pub maker_volume: String, // u128 as string, in base token atoms
+    pub taker_volume: String, // u128 as string, in base token atoms
+    pub maker_ratio_bps: u32, // maker / (maker + taker), 0 to 10000
+}
+
+/// Volume taken from each side of the book within one time bucket
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiTakerFlow {
+    pub timestamp: i64,      // Unix timestamp in seconds, start of the bucket
+    pub buy_volume: String,  // u128 as string, in base token atoms
+    pub sell_volume: String, // u128 as string, in base token atoms
+    pub imbalance_bps: i32,  // (buy - sell) / (buy + sell), -10000 to 10000
+}
+
+/// Maker/taker breakdown of a market's trading over a time range
+///
+/// Trades with yourself are left out of both views.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FlowAnalyticsResponse {
+    pub market_id: String,
+    pub interval: String, // Bucket width of `taker_flow`: 1m, 5m, 15m, 1h, 1d
+    /// Traders by maker volume, largest first
+    pub makers: Vec<ApiMakerVolume>,
+    /// Non-empty buckets, oldest first
+    pub taker_flow: Vec<ApiTakerFlow>,
+    pub from: i64, // Unix timestamp in seconds
+    pub to: i64,   // Unix timestamp in seconds
+}
+
+// ============================================================================
+// DERIVATIVES API TYPES
+// ============================================================================
+
+/// Open interest of a perpetual market at one snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiOpenInterest {
+    pub timestamp: i64,        // Unix timestamp in seconds
+    pub open_interest: String, // u128 as string, in base token atoms
+    pub mark_price: String,    // u128 as string
+}
+
+/// A perpetual market's open interest snapshots over a time range, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OpenInterestHistoryResponse {
+    pub market_id: String,
+    pub snapshots: Vec<ApiOpenInterest>,
+    pub from: i64, // Unix timestamp in seconds
+    pub to: i64,   // Unix timestamp in seconds
+}
+
+/// Funding settled for one interval of a perpetual market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiFundingRate {
+    pub timestamp: i64,        // Unix timestamp in seconds
+    pub funding_rate_ppm: i64, // Millionths of notional; positive means longs pay shorts
+    pub mark_price: String,    // u128 as string
+    pub index_price: String,   // u128 as string
+}
+
+/// A perpetual market's funding rates over a time range, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FundingHistoryResponse {
+    pub market_id: String,
+    pub rates: Vec<ApiFundingRate>,
+    pub from: i64, // Unix timestamp in seconds
+    pub to: i64,   // Unix timestamp in seconds
+}
+
+/// Funding terms and latest prices of a perpetual market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiPerpetualMarket {
+    pub market_id: String,
+    pub funding_interval_secs: u32,
+    pub max_funding_rate_ppm: u32, // Cap on one interval's rate, in millionths of notional
+    pub initial_margin_bps: u32,   // Locked by orders, of their notional
+    pub maintenance_margin_bps: u32, // Positions below this share of notional are liquidated
+    pub liquidation_fee_bps: u32,  // Charged on the notional a liquidation closes
+    pub next_funding_at: i64,      // Unix timestamp in seconds
+    pub mark_price: Option<String>, // u128 as string, until the market is first marked
+    pub index_price: Option<String>, // u128 as string
+    pub mark_updated_at: Option<i64>, // Unix timestamp in seconds
+}
+
+/// A user's position in a perpetual market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiPosition {
+    pub market_id: String,
+    pub size: String, // i128 as string, in base token atoms; negative for shorts
+    pub entry_price: String, // u128 as string
+    pub margin: String, // u128 as string, quote atoms locked for the position
+    pub mark_price: Option<String>, // u128 as string
+    pub unrealized_pnl: Option<String>, // i128 as string, quote atoms due at the next settlement
+    pub realized_pnl: String, // i128 as string, quote atoms settled so far
+    pub funding_paid: String, // i128 as string, quote atoms; negative when received
+    pub updated_at: i64, // Unix timestamp in seconds
+}
+
+/// A user's positions in every perpetual market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PositionsResponse {
+    pub user_address: String,
+    pub positions: Vec<ApiPosition>,
+}
+
+/// Equity backing positions against the maintenance margin they need
+///
+/// An isolated position has one of its own; a cross-margined user has one per
+/// quote token, covering all their positions quoted in it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiMarginHealth {
+    pub token_ticker: String,
+    pub market_ids: Vec<String>,
+    pub equity: String, // i128 as string, quote atoms at the latest marks
+    pub maintenance_margin: String, // u128 as string, quote atoms
+    pub liquidatable: bool,
+}
+
+/// How a user's positions are margined and how close they are to liquidation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarginAccountResponse {
+    pub user_address: String,
+    pub mode: MarginMode,
+    pub health: Vec<ApiMarginHealth>,
+}
+
+/// A position close to liquidation, without its holder
+///
+/// Cross-margined positions share the equity and maintenance margin of
+/// every position their holder has in the same quote token.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiPositionRisk {
+    pub size: String, // i128 as string, in base token atoms; negative for shorts
+    pub margin_mode: MarginMode,
+    pub equity: String, // i128 as string, quote atoms at the latest marks
+    pub maintenance_margin: String, // u128 as string, quote atoms
+    pub margin_ratio_bps: i64, // Equity over maintenance margin; liquidated below 10000
+    pub liquidation_price: String, // u128 as string, worst price its liquidation order fills at
+    pub liquidatable: bool,
+}
+
+/// A perpetual market's positions nearest to liquidation, most at risk first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketRiskResponse {
+    pub market_id: String,
+    pub mark_price: Option<String>, // u128 as string, until the market is first marked
+    pub positions: Vec<ApiPositionRisk>,
+}
+
+/// A position the exchange force-closed because its equity fell below the maintenance margin
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, JsonSchema)]
+pub struct ApiLiquidation {
+    pub user_address: String,
+    pub market_id: String,
+    pub order_id: String, // UUID as string, of the order that closed the position
+    pub size: String,     // i128 as string, position size before; negative for shorts
+    pub closed_size: String, // u128 as string, what the book filled
+    pub mark_price: String, // u128 as string
+    pub margin_mode: MarginMode,
+    pub fee: String,    // u128 as string, quote atoms charged
+    pub timestamp: i64, // Unix timestamp in seconds
+}
+
+// ============================================================================
+// INDEX PRICE API TYPES
+// ============================================================================
+
+/// A market's price on an external index at one poll
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiIndexPrice {
+    pub timestamp: i64, // Unix timestamp in seconds
+    pub price: String,  // u128 as string
+    pub source: String, // Feed the price came from, e.g. "hyperliquid"
+}
+
+/// A market's index prices over a time range, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IndexPriceHistoryResponse {
+    pub market_id: String,
+    pub prices: Vec<ApiIndexPrice>,
+    pub from: i64, // Unix timestamp in seconds
+    pub to: i64,   // Unix timestamp in seconds
+}
+
+// ============================================================================
+// TOP OF BOOK API TYPES
+// ============================================================================
+
+/// One side's best price and the size resting at it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApiBookLevel {
+    pub price: String, // u128 as string
+    pub size: String,  // u128 as string
+}
+
+/// Best bid and ask of a market
+///
+/// A side is `None` when nothing rests on it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiTopOfBook {
+    pub market_id: String,
+    pub best_bid: Option<ApiBookLevel>,
+    pub best_ask: Option<ApiBookLevel>,
+    pub version: u64,   // Book version, bumps on every change
+    pub timestamp: i64, // Unix timestamp in milliseconds
+}
+
+/// One resting order in the per-order (L3) book
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiL3Order {
+    /// Stands in for the order ID; stable while the order rests, but not linkable to it or its owner
+    pub id: String,
+    pub price: String, // u128 as string
+    pub size: String,  // u128 as string, what's left to fill
+    pub age_ms: i64,   // Time resting on the book
+}
+
+/// Every resting order of a market, in queue order
+///
+/// Bids run from the best price down and asks from the best price up; orders
+/// at one price are in time priority, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiL3Book {
+    pub market_id: String,
+    pub bids: Vec<ApiL3Order>,
+    pub asks: Vec<ApiL3Order>,
+    pub timestamp: i64, // Unix timestamp in milliseconds
+}
+
+// ============================================================================
+// MARKET STATS API TYPES
+// ============================================================================
+
+/// Rolling 24h statistics for a market
+///
+/// Price fields are `None` when the market has not traded in the window.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiMarketStats {
+    pub market_id: String,
+    pub base_volume: String,  // u128 as string
+    pub quote_volume: String, // u128 as string
+    pub trade_count: u64,
+    pub open: Option<String>,  // u128 as string
+    pub high: Option<String>,  // u128 as string
+    pub low: Option<String>,   // u128 as string
+    pub close: Option<String>, // u128 as string
+    pub change_percent: Option<f64>,
+    pub from: i64, // Unix timestamp in seconds
+    pub to: i64,   // Unix timestamp in seconds
+}
+
+// ============================================================================
+// PREDICTION EVENT API TYPES
+// ============================================================================
+
+/// A question whose mutually exclusive outcomes each trade as a market
+///
+/// Outcome tokens pay one quote token per whole token if their outcome wins,
+/// so the outcome markets' prices are probabilities summing to about one.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiPredictionEvent {
+    pub id: String,
+    pub title: String,
+    pub quote_ticker: String,
+    pub status: EventStatus,
+    pub winning_outcome: Option<String>,
+    pub outcomes: Vec<ApiEventOutcome>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// One outcome of a prediction event and the market trading its token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiEventOutcome {
+    pub name: String,
+    pub market_id: String,
+    pub token_ticker: String,
+}
+
+/// An outcome to list when creating a prediction event
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiEventOutcomeSpec {
+    pub name: String,
+    /// Existing market quoted in the event's quote token, not part of another event
+    pub market_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EventsResponse {
+    pub events: Vec<ApiPredictionEvent>,
+}
+
+// ============================================================================
+// WEBSOCKET MESSAGE TYPES (Client → Server)
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        channel: SubscriptionChannel,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        market_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        user_address: Option<String>,
+        /// Candle interval (1m, 5m, 15m, 1h or 1d), required for the candles channel
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+    },
+    Unsubscribe {
+        channel: SubscriptionChannel,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        market_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        user_address: Option<String>,
+        /// Candle interval (1m, 5m, 15m, 1h or 1d), required for the candles channel
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+    },
+    Ping,
+}
+
+/// Channel types for WebSocket subscriptions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionChannel {
+    Trades,
+    Orderbook,
+    /// Live bars of one market and interval; subscribe once per interval
+    Candles,
+    UserFills,
+    UserOrders,
+    UserBalances,
+    /// A user's rejections, exchange cancels, market halts and withdrawal updates
+    Notifications,
+    /// A summary of every market, refreshed every few seconds; takes no market or user
+    Tickers,
+}
+
+// ============================================================================
+// WEBSOCKET MESSAGE TYPES (Server → Client)
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    // Subscription acknowledgments
+    Subscribed {
+        channel: SubscriptionChannel,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        market_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        user_address: Option<String>,
+        /// Candle interval, set for the candles channel
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+    },
+    Unsubscribed {
+        channel: SubscriptionChannel,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        market_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        user_address: Option<String>,
+        /// Candle interval, set for the candles channel
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interval: Option<String>,
+    },
+
+    // Market-wide real-time data updates
+    Trade {
+        trade: TradeData,
+    },
+    Orderbook {
+        orderbook: OrderbookData,
+    },
+    /// Every market's summary, sent every few seconds while anyone follows tickers
+    Tickers {
+        tickers: Vec<TickerData>,
+        timestamp: i64, // Unix timestamp in milliseconds
+    },
+    /// The bar in progress, sent after every trade that moves it
+    Candle {
+        market_id: String,
+        interval: String,
+        timestamp: i64, // Unix timestamp in seconds of the bar's start
+        open: String,
+        high: String,
+        low: String,
+        close: String,
+        volume: String,
+    },
+
+    // User-specific real-time data updates
+    UserFill {
+        trade: TradeData,
+    },
+    UserOrder {
+        order_id: String,
+        status: OrderStatus,
+        filled_size: String,
+        /// Set when the exchange cancelled the order
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<CancelReason>,
+    },
+    UserBalance {
+        user_address: String,
+        token_ticker: String,
+        available: String,
+        locked: String,
+        updated_at: i64, // Unix timestamp
+    },
+    /// Sent on the user's orders channel when one of their positions is liquidated
+    UserLiquidation {
+        liquidation: ApiLiquidation,
+    },
+    /// Sent on the user's notifications channel
+    UserNotification {
+        notification: Notification,
+    },
+
+    // Connection management
+    /// A client message the server couldn't act on
+    Error {
+        /// Absent from servers that predate error codes
+        #[serde(default)]
+        code: ErrorCode,
+        message: String,
+    },
+    Pong,
+}
+
+/// Something that happened to a user's orders, markets or withdrawals, other than a fill
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Notification {
+    /// The engine refused an order; `code` and `reason` are what REST answers with
+    OrderRejected {
+        order_id: String, // UUID as string
+        market_id: String,
+        code: ErrorCode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<RejectReason>,
+        message: String,
+    },
+    /// The exchange, rather than the user, cancelled an order
+    OrderCancelled {
+        order_id: String, // UUID as string
+        reason: CancelReason,
+    },
+    /// A market stopped or resumed trading; every subscriber gets these
+    MarketStatusChanged {
+        market_id: String,
+        status: MarketStatus,
+    },
+    /// A withdrawal moved on towards the chain, or failed
+    WithdrawalUpdated {
+        withdrawal_id: String, // UUID as string
+        token_ticker: String,
+        amount: String, // u128 as string
+        status: WithdrawalStatus,
+        tx_hash: Option<String>,
+        error: Option<String>,
+        updated_at: i64, // Unix timestamp in seconds
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PriceLevel {
+    pub price: String,
+    pub size: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OrderbookData {
+    pub market_id: String,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+}
+
+/// One market's line on the tickers channel
+///
+/// Prices are `None` when the market hasn't traded in 24 hours or has nothing
+/// resting on that side of the book.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TickerData {
+    pub market_id: String,
+    pub last: Option<String>,     // u128 as string
+    pub best_bid: Option<String>, // u128 as string
+    pub best_ask: Option<String>, // u128 as string
+    pub change_percent: Option<f64>,
+    pub volume: String, // u128 as string, base volume over 24h
+}
+
+/// Trade data for WebSocket messages (API layer with String fields)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TradeData {
+    pub id: String, // UUID as string
+    pub market_id: String,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub buyer_order_id: String,  // UUID as string
+    pub seller_order_id: String, // UUID as string
+    pub price: String,           // u128 as string
+    pub size: String,            // u128 as string
+    pub side: Side,              // Taker's side (determines if trade is "buy" or "sell" on tape)
+    pub timestamp: i64,          // Unix timestamp for WebSocket compatibility
+    /// Negotiated through a request for quote rather than matched on the book
+    #[serde(default)]
+    pub rfq: bool,
+}
+
+// ============================================================================
+// API DTOs (for HTTP responses)
+// ============================================================================
+
+/// API representation of Market with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiMarket {
+    pub id: String,
+    pub base_ticker: String,
+    pub quote_ticker: String,
+    pub tick_size: String, // u128 as string
+    pub lot_size: String,  // u128 as string
+    pub min_size: String,  // u128 as string
+    pub maker_fee_bps: i32,
+    pub taker_fee_bps: i32,
+    /// Stable number of the market, accepted wherever a market id is; never reused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numeric_id: Option<u32>,
+}
+
+/// API representation of Order with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiOrder {
+    pub id: String, // UUID as string
+    pub user_address: String,
+    pub market_id: String,
+    pub price: String, // u128 as string
+    pub size: String,  // u128 as string
+    pub side: Side,
+    pub order_type: OrderType,
+    pub status: OrderStatus,
+    pub filled_size: String, // u128 as string
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Why the exchange cancelled the order; absent unless it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+    /// Price that activates a stop order; absent for other orders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger_price: Option<String>, // u128 as string
+    #[serde(default)]
+    pub trigger_source: TriggerSource,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+    /// When the exchange cancels the order if it's still open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// API representation of Trade with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiTrade {
+    pub id: String, // UUID as string
+    pub market_id: String,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub buyer_order_id: String,  // UUID as string
+    pub seller_order_id: String, // UUID as string
+    pub price: String,           // u128 as string
+    pub size: String,            // u128 as string
+    pub side: Side,              // Taker's side (determines if trade is "buy" or "sell" on tape)
+    pub timestamp: DateTime<Utc>,
+    /// Negotiated through a request for quote rather than matched on the book
+    #[serde(default)]
+    pub rfq: bool,
+    /// Fee the requesting user paid on this fill, negative for a maker rebate
+    /// Only set in a user's own trade history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<String>, // i128 as string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_ticker: Option<String>,
+    /// Whether the requesting user made or took liquidity on this fill
+    /// Only set in a user's own trade history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<LiquidityRole>,
+}
+
+/// API representation of Balance with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiBalance {
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: String,        // u128 as string
+    pub open_interest: String, // u128 as string
+    pub updated_at: DateTime<Utc>,
+}
+
+/// API representation of UserLimits with String fields for JSON compatibility
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiUserLimits {
+    pub user_address: String,
+    pub market_id: String,
+    pub max_position: Option<String>, // u128 as string, null = unlimited
+    pub max_open_notional: Option<String>, // u128 as string, null = unlimited
+    pub updated_at: DateTime<Utc>,
+}
+
+// Conversion implementations from domain to API types
+impl From<Market> for ApiMarket {
+    fn from(m: Market) -> Self {
+        Self {
+            id: m.id,
+            base_ticker: m.base_ticker,
+            quote_ticker: m.quote_ticker,
+            tick_size: m.tick_size.to_string(),
+            lot_size: m.lot_size.to_string(),
+            min_size: m.min_size.to_string(),
+            maker_fee_bps: m.maker_fee_bps,
+            taker_fee_bps: m.taker_fee_bps,
+            numeric_id: None,
+        }
+    }
+}
+
+impl From<Order> for ApiOrder {
+    fn from(o: Order) -> Self {
+        Self {
+            id: o.id.to_string(),
+            user_address: o.user_address,
+            market_id: o.market_id,
+            price: o.price.to_string(),
+            size: o.size.to_string(),
+            side: o.side,
+            order_type: o.order_type,
+            status: o.status,
+            filled_size: o.filled_size.to_string(),
+            created_at: o.created_at,
+            updated_at: o.updated_at,
+            cancel_reason: o.cancel_reason,
+            trigger_price: o.trigger_price.map(|p| p.to_string()),
+            trigger_source: o.trigger_source,
+            time_in_force: o.time_in_force,
+            expires_at: o.expires_at,
+        }
+    }
+}
+
+impl From<Trade> for ApiTrade {
+    fn from(t: Trade) -> Self {
+        Self {
+            id: t.id.to_string(),
+            market_id: t.market_id,
+            buyer_address: t.buyer_address,
+            seller_address: t.seller_address,
+            buyer_order_id: t.buyer_order_id.to_string(),
+            seller_order_id: t.seller_order_id.to_string(),
+            price: t.price.to_string(),
+            size: t.size.to_string(),
+            side: t.side,
+            timestamp: t.timestamp,
+            rfq: t.rfq,
+            fee: None,
+            fee_ticker: None,
+            role: None,
+        }
+    }
+}
+
+impl From<LedgerEntry> for ApiLedgerEntry {
+    fn from(e: LedgerEntry) -> Self {
+        Self {
+            id: e.id,
+            account: e.account,
+            token_ticker: e.token_ticker,
+            amount: e.amount.to_string(),
+            kind: e.kind,
+            trade_id: e.trade_id.map(|id| id.to_string()),
+            created_at: e.created_at,
+        }
+    }
+}
+
+impl From<SurveillanceAlert> for ApiSurveillanceAlert {
+    fn from(a: SurveillanceAlert) -> Self {
+        Self {
+            id: a.id,
+            kind: a.kind,
+            market_id: a.market_id,
+            subject: a.subject,
+            accounts: a.accounts,
+            count: a.count,
+            volume: a.volume.to_string(),
+            detail: a.detail,
+            window_start: a.window_start,
+            window_end: a.window_end,
+            created_at: a.created_at,
+        }
+    }
+}
+
+impl From<QueuePosition> for ApiQueuePosition {
+    fn from(q: QueuePosition) -> Self {
+        Self {
+            order_id: q.order_id.to_string(),
+            market_id: q.market_id,
+            side: q.side,
+            price: q.price.to_string(),
+            remaining: q.remaining.to_string(),
+            orders_ahead: q.orders_ahead,
+            size_ahead: q.size_ahead.to_string(),
+            level_size: q.level_size.to_string(),
+        }
+    }
+}
+
+impl From<UserSummary> for ApiUserSummary {
+    fn from(u: UserSummary) -> Self {
+        let amounts = |amounts: Vec<(String, u128)>| {
+            amounts
+                .into_iter()
+                .map(|(token_ticker, amount)| ApiTokenAmount {
+                    token_ticker,
+                    amount: amount.to_string(),
+                })
+                .collect()
+        };
+        Self {
+            user_address: u.address,
+            created_at: u.created_at,
+            status: u.status,
+            display_name: u.display_name,
+            open_orders: u.open_orders,
+            volume_30d: amounts(u.volume_30d),
+            fees_paid: amounts(u.fees_paid),
+            balances: u.balances.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Statement> for ApiStatement {
+    fn from(s: Statement) -> Self {
+        Self {
+            user_address: s.user_address,
+            period_start: s.period_start,
+            period_end: s.period_end,
+            balances_at: s.balances_at,
+            balances: s.balances.into_iter().map(Into::into).collect(),
+            markets: s.markets.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<StatementBalance> for ApiStatementBalance {
+    fn from(b: StatementBalance) -> Self {
+        Self {
+            token_ticker: b.token_ticker,
+            opening_balance: b.opening_balance.map(|amount| amount.to_string()),
+            closing_balance: b.closing_balance.to_string(),
+            deposited: b.deposited.to_string(),
+            withdrawn: b.withdrawn.to_string(),
+            fees: b.fees.to_string(),
+        }
+    }
+}
+
+impl From<StatementMarket> for ApiStatementMarket {
+    fn from(m: StatementMarket) -> Self {
+        Self {
+            market_id: m.market_id,
+            fills: m.fills,
+            bought: m.bought.to_string(),
+            sold: m.sold.to_string(),
+            notional: m.notional.to_string(),
+        }
+    }
+}
+
+impl From<UserLimits> for ApiUserLimits {
+    fn from(l: UserLimits) -> Self {
+        Self {
+            user_address: l.user_address,
+            market_id: l.market_id,
+            max_position: l.max_position.map(|v| v.to_string()),
+            max_open_notional: l.max_open_notional.map(|v| v.to_string()),
+            updated_at: l.updated_at,
+        }
+    }
+}
+
+impl From<Balance> for ApiBalance {
+    fn from(b: Balance) -> Self {
+        Self {
+            user_address: b.user_address,
+            token_ticker: b.token_ticker,
+            amount: b.amount.to_string(),
+            open_interest: b.open_interest.to_string(),
+            updated_at: b.updated_at,
+        }
+    }
+}
+
+impl From<Deposit> for ApiDeposit {
+    fn from(d: Deposit) -> Self {
+        Self {
+            chain_id: d.chain_id,
+            tx_hash: d.tx_hash,
+            log_index: d.log_index,
+            block_number: d.block_number,
+            user_address: d.user_address,
+            token_ticker: d.token_ticker,
+            amount: d.amount.to_string(),
+            created_at: d.created_at,
+        }
+    }
+}
+
+impl From<Liquidation> for ApiLiquidation {
+    fn from(l: Liquidation) -> Self {
+        Self {
+            user_address: l.user_address,
+            market_id: l.market_id,
+            order_id: l.order_id.to_string(),
+            size: l.size.to_string(),
+            closed_size: l.closed_size.to_string(),
+            mark_price: l.mark_price.to_string(),
+            margin_mode: l.margin_mode,
+            fee: l.fee.to_string(),
+            timestamp: l.timestamp.timestamp(),
+        }
+    }
+}
+
+impl From<Withdrawal> for ApiWithdrawal {
+    fn from(w: Withdrawal) -> Self {
+        Self {
+            id: w.id.to_string(),
+            user_address: w.user_address,
+            token_ticker: w.token_ticker,
+            amount: w.amount.to_string(),
+            destination: w.destination,
+            status: w.status,
+            tx_hash: w.tx_hash,
+            error: w.error,
+            created_at: w.created_at,
+            updated_at: w.updated_at,
+        }
+    }
+}
+
+impl From<ApiKey> for ApiKeyDetails {
+    fn from(k: ApiKey) -> Self {
+        Self {
+            id: k.id.to_string(),
+            user_address: k.user_address,
+            label: k.label,
+            key_prefix: k.key_prefix,
+            scopes: k.scopes,
+            ip_allowlist: k.ip_allowlist,
+            created_at: k.created_at,
+            rotated_at: k.rotated_at,
+            last_used_at: k.last_used_at,
+        }
+    }
+}
+
+impl From<SubAccount> for ApiSubAccount {
+    fn from(s: SubAccount) -> Self {
+        Self {
+            address: s.address,
+            master_address: s.master_address,
+            name: s.name,
+            created_at: s.created_at,
+        }
+    }
+}
+
+impl From<AccountTransfer> for ApiAccountTransfer {
+    fn from(t: AccountTransfer) -> Self {
+        Self {
+            id: t.id.to_string(),
+            master_address: t.master_address,
+            from_address: t.from_address,
+            to_address: t.to_address,
+            token_ticker: t.token_ticker,
+            amount: t.amount.to_string(),
+            created_at: t.created_at,
+        }
+    }
+}
+
+impl From<PredictionEvent> for ApiPredictionEvent {
+    fn from(e: PredictionEvent) -> Self {
+        Self {
+            id: e.id,
+            title: e.title,
+            quote_ticker: e.quote_ticker,
+            status: e.status,
+            winning_outcome: e.winning_outcome,
+            outcomes: e.outcomes.into_iter().map(Into::into).collect(),
+            created_at: e.created_at,
+            resolved_at: e.resolved_at,
+        }
+    }
+}
+
+impl From<QuoteRequest> for ApiQuoteRequest {
+    fn from(r: QuoteRequest) -> Self {
+        Self {
+            id: r.id.to_string(),
+            taker_address: r.taker_address,
+            market_id: r.market_id,
+            side: r.side,
+            size: r.size.to_string(),
+            status: r.status,
+            trade_id: r.trade_id.map(|id| id.to_string()),
+            expires_at: r.expires_at,
+            created_at: r.created_at,
+        }
+    }
+}
+
+impl From<Quote> for ApiQuote {
+    fn from(q: Quote) -> Self {
+        Self {
+            id: q.id.to_string(),
+            request_id: q.request_id.to_string(),
+            maker_address: q.maker_address,
+            price: q.price.to_string(),
+            expires_at: q.expires_at,
+            created_at: q.created_at,
+        }
+    }
+}
+
+impl From<EventOutcome> for ApiEventOutcome {
+    fn from(o: EventOutcome) -> Self {
+        Self {
+            name: o.name,
+            market_id: o.market_id,
+            token_ticker: o.token_ticker,
+        }
+    }
+}
+
+impl From<Webhook> for ApiWebhook {
+    fn from(w: Webhook) -> Self {
+        Self {
+            id: w.id.to_string(),
+            user_address: w.user_address,
+            url: w.url,
+            created_at: w.created_at,
+        }
+    }
+}
+
+impl From<WebhookDeadLetter> for ApiWebhookDeadLetter {
+    fn from(d: WebhookDeadLetter) -> Self {
+        Self {
+            id: d.id.to_string(),
+            webhook_id: d.webhook_id.to_string(),
+            event_type: d.event_type,
+            payload: d.payload,
+            attempts: d.attempts,
+            last_error: d.last_error,
+            created_at: d.created_at,
+        }
+    }
+}
+
+// Reverse conversions from API to domain types (for clients)
+impl TryFrom<ApiMarket> for Market {
+    type Error = std::num::ParseIntError;
+
+    fn try_from(m: ApiMarket) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: m.id,
+            base_ticker: m.base_ticker,
+            quote_ticker: m.quote_ticker,
+            tick_size: m.tick_size.parse()?,
+            lot_size: m.lot_size.parse()?,
+            min_size: m.min_size.parse()?,
+            maker_fee_bps: m.maker_fee_bps,
+            taker_fee_bps: m.taker_fee_bps,
+        })
+    }
+}
+
+impl TryFrom<ApiOrder> for Order {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(o: ApiOrder) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&o.id)?,
+            user_address: o.user_address,
+            market_id: o.market_id,
+            price: o.price.parse()?,
+            size: o.size.parse()?,
+            side: o.side,
+            order_type: o.order_type,
+            status: o.status,
+            filled_size: o.filled_size.parse()?,
+            created_at: o.created_at,
+            updated_at: o.updated_at,
+            cancel_reason: o.cancel_reason,
+            trigger_price: o.trigger_price.map(|p| p.parse()).transpose()?,
+            trigger_source: o.trigger_source,
+            time_in_force: o.time_in_force,
+            expires_at: o.expires_at,
+        })
+    }
+}
+
+impl TryFrom<ApiTrade> for Trade {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(t: ApiTrade) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&t.id)?,
+            market_id: t.market_id,
+            buyer_address: t.buyer_address,
+            seller_address: t.seller_address,
+            buyer_order_id: Uuid::parse_str(&t.buyer_order_id)?,
+            seller_order_id: Uuid::parse_str(&t.seller_order_id)?,
+            price: t.price.parse()?,
+            size: t.size.parse()?,
+            side: t.side,
+            timestamp: t.timestamp,
+            rfq: t.rfq,
+        })
+    }
+}
+
+impl TryFrom<ApiBalance> for Balance {
+    type Error = std::num::ParseIntError;
+
+    fn try_from(b: ApiBalance) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user_address: b.user_address,
+            token_ticker: b.token_ticker,
+            amount: b.amount.parse()?,
+            open_interest: b.open_interest.parse()?,
+            updated_at: b.updated_at,
+        })
+    }
+}
+
+impl TryFrom<OrderPlaced> for PlacedOrder {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(p: OrderPlaced) -> Result<Self, Self::Error> {
+        Ok(Self {
+            order: p.order.try_into()?,
+            trades: p
+                .trades
+                .into_iter()
+                .map(Trade::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<OcoPlaced> for PlacedOco {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(p: OcoPlaced) -> Result<Self, Self::Error> {
+        Ok(Self {
+            oco_id: Uuid::parse_str(&p.oco_id)?,
+            first: p.first.try_into()?,
+            second: p.second.try_into()?,
+        })
+    }
+}
+
+impl LeaderboardPeriod {
+    /// Length of the period in seconds
+    pub fn seconds(&self) -> i64 {
+        match self {
+            LeaderboardPeriod::Day => 24 * 60 * 60,
+            LeaderboardPeriod::Week => 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl std::fmt::Display for LeaderboardPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LeaderboardPeriod::Day => write!(f, "24h"),
+            LeaderboardPeriod::Week => write!(f, "7d"),
+        }
+    }
+}
+
+impl std::fmt::Display for LeaderboardMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LeaderboardMetric::Volume => write!(f, "volume"),
+            LeaderboardMetric::Pnl => write!(f, "pnl"),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Parquet => write!(f, "parquet"),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportJobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ExportJobStatus::Pending => write!(f, "pending"),
+            ExportJobStatus::Ready => write!(f, "ready"),
+            ExportJobStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_order(id: &str) -> ApiOrder {
+        let now = Utc::now();
+        ApiOrder {
+            id: id.to_string(),
+            user_address: "alice".to_string(),
+            market_id: "BTC/USDC".to_string(),
+            price: "50000000000".to_string(),
+            size: "1000000".to_string(),
+            side: Side::Buy,
+            order_type: OrderType::Limit,
+            status: OrderStatus::Pending,
+            filled_size: "0".to_string(),
+            cancel_reason: None,
+            trigger_price: None,
+            trigger_source: TriggerSource::LastTrade,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_order_placed_to_placed_order() {
+        let id = Uuid::new_v4();
+        let placed: PlacedOrder = OrderPlaced {
+            order: api_order(&id.to_string()),
+            trades: vec![],
+        }
+        .try_into()
+        .unwrap();
+
+        assert_eq!(placed.order.id, id);
+        assert_eq!(placed.order.price, 50_000_000_000);
+        assert!(placed.trades.is_empty());
+    }
+
+    #[test]
+    fn test_oco_placed_to_placed_oco() {
+        let (oco_id, first, second) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let placed: PlacedOco = OcoPlaced {
+            oco_id: oco_id.to_string(),
+            first: api_order(&first.to_string()),
+            second: api_order(&second.to_string()),
+        }
+        .try_into()
+        .unwrap();
+
+        assert_eq!(placed.oco_id, oco_id);
+        assert_eq!((placed.first.id, placed.second.id), (first, second));
+        assert!(PlacedOco::try_from(OcoPlaced {
+            oco_id: "not-a-uuid".to_string(),
+            first: api_order(&first.to_string()),
+            second: api_order(&second.to_string()),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_order_placed_with_invalid_id_fails() {
+        let result = PlacedOrder::try_from(OrderPlaced {
+            order: api_order("not-a-uuid"),
+            trades: vec![],
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_stop_order_types() {
+        for (order_type, name) in [
+            (OrderType::StopMarket, "stop_market"),
+            (OrderType::StopLimit, "stop_limit"),
+        ] {
+            assert_eq!(serde_json::to_value(order_type).unwrap(), name);
+            assert_eq!(name.parse::<OrderType>(), Ok(order_type));
+            assert_eq!(order_type.to_string(), name);
+            assert!(order_type.is_stop());
+        }
+        assert_eq!(OrderType::StopMarket.execution_type(), OrderType::Market);
+        assert_eq!(OrderType::StopLimit.execution_type(), OrderType::Limit);
+        assert_eq!(OrderType::Limit.execution_type(), OrderType::Limit);
+        assert!(!OrderType::Market.is_stop());
+
+        let mut order = api_order(&Uuid::new_v4().to_string());
+        order.order_type = OrderType::StopLimit;
+        order.trigger_price = Some("49000000000".to_string());
+        let order = Order::try_from(order).unwrap();
+        assert_eq!(order.trigger_price, Some(49_000_000_000));
+        assert_eq!(
+            ApiOrder::from(order).trigger_price.as_deref(),
+            Some("49000000000")
+        );
+    }
+
+    #[test]
+    fn test_trigger_source() {
+        for (trigger_source, name) in [
+            (TriggerSource::LastTrade, "last_trade"),
+            (TriggerSource::Index, "index"),
+        ] {
+            assert_eq!(serde_json::to_value(trigger_source).unwrap(), name);
+            assert_eq!(name.parse::<TriggerSource>(), Ok(trigger_source));
+            assert_eq!(trigger_source.to_string(), name);
+        }
+
+        // Orders from before trigger sources trigger off the last trade
+        let mut order = serde_json::to_value(api_order(&Uuid::new_v4().to_string())).unwrap();
+        order.as_object_mut().unwrap().remove("trigger_source");
+        let order: ApiOrder = serde_json::from_value(order).unwrap();
+        assert_eq!(order.trigger_source, TriggerSource::LastTrade);
+        let leg: OcoLeg = serde_json::from_value(serde_json::json!({
+            "order_type": "stop_market",
+            "price": "0",
+            "trigger_price": "45000000000",
+        }))
+        .unwrap();
+        assert_eq!(leg.trigger_source, TriggerSource::LastTrade);
+    }
+
+    #[test]
+    fn test_time_in_force() {
+        for (time_in_force, name) in [
+            (TimeInForce::Gtc, "gtc"),
+            (TimeInForce::Ioc, "ioc"),
+            (TimeInForce::Fok, "fok"),
+        ] {
+            assert_eq!(serde_json::to_value(time_in_force).unwrap(), name);
+            assert_eq!(name.parse::<TimeInForce>(), Ok(time_in_force));
+            assert_eq!(time_in_force.to_string(), name);
+        }
+
+        // Requests and orders from before time in force are good till cancelled
+        let request: TradeRequest = serde_json::from_value(serde_json::json!({
+            "type": "place_order",
+            "user_address": "alice",
+            "market_id": "BTC/USDC",
+            "side": "buy",
+            "order_type": "limit",
+            "price": "50000000000",
+            "size": "1000000",
+            "signature": "sig",
+        }))
+        .unwrap();
+        assert!(matches!(
+            request,
+            TradeRequest::PlaceOrder {
+                time_in_force: TimeInForce::Gtc,
+                ..
+            }
+        ));
+        let mut order = serde_json::to_value(api_order(&Uuid::new_v4().to_string())).unwrap();
+        order.as_object_mut().unwrap().remove("time_in_force");
+        let order: ApiOrder = serde_json::from_value(order).unwrap();
+        assert_eq!(order.time_in_force, TimeInForce::Gtc);
+
+        let mut order = Order::try_from(order).unwrap();
+        assert!(order.rests());
+        order.time_in_force = TimeInForce::Ioc;
+        assert!(!order.rests());
+        order.time_in_force = TimeInForce::Fok;
+        assert!(!order.rests());
+        order.time_in_force = TimeInForce::Gtc;
+        order.order_type = OrderType::StopMarket;
+        assert!(!order.rests());
+    }
+
+    #[test]
+    fn test_expires_at() {
+        let request = serde_json::json!({
+            "type": "place_order",
+            "user_address": "alice",
+            "market_id": "BTC/USDC",
+            "side": "buy",
+            "order_type": "limit",
+            "price": "50000000000",
+            "size": "1000000",
+            "signature": "sig",
+        });
+        let parsed: TradeRequest = serde_json::from_value(request.clone()).unwrap();
+        assert!(matches!(
+            parsed,
+            TradeRequest::PlaceOrder {
+                expires_at: None,
+                ..
+            }
+        ));
+        // Orders without an expiry serialize as they did before
+        assert!(serde_json::to_value(&parsed)
+            .unwrap()
+            .get("expires_at")
+            .is_none());
+
+        let mut request = request;
+        request["expires_at"] = "2025-12-13T09:00:00Z".into();
+        let parsed: TradeRequest = serde_json::from_value(request).unwrap();
+        let TradeRequest::PlaceOrder { expires_at, .. } = parsed else {
+            panic!("expected a place order request");
+        };
+        let expires_at = expires_at.unwrap();
+        assert_eq!(expires_at.to_rfc3339(), "2025-12-13T09:00:00+00:00");
+
+        let mut order = api_order(&Uuid::new_v4().to_string());
+        assert!(serde_json::to_value(&order)
+            .unwrap()
+            .get("expires_at")
+            .is_none());
+        order.expires_at = Some(expires_at);
+        assert_eq!(Order::try_from(order).unwrap().expires_at, Some(expires_at));
+    }
+}
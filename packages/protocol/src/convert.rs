@@ -0,0 +1,261 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Request header choosing how token amounts are written; see [`AmountFormat`]
+pub const AMOUNT_FORMAT_HEADER: &str = "x-amount-format";
+
+/// How a request writes the token amounts it sends, and its response those it returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmountFormat {
+    /// Integer atoms, as the exchange stores them
+    #[default]
+    Atoms,
+    /// Whole tokens with up to the token's decimals, e.g. "0.5" for 50_000_000 BTC atoms
+    Decimal,
+}
+
+impl AmountFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AmountFormat::Atoms => "atoms",
+            AmountFormat::Decimal => "decimal",
+        }
+    }
+}
+
+impl Display for AmountFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AmountFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "atoms" => Ok(AmountFormat::Atoms),
+            "decimal" => Ok(AmountFormat::Decimal),
+            other => Err(format!(
+                "Unknown amount format '{}'; expected atoms or decimal",
+                other
+            )),
+        }
+    }
+}
+
+/// Why a number can't be represented exactly in atoms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// Negative, or larger than `u128::MAX` atoms
+    Overflow { value: String },
+    /// More decimal places than the token has; converting would drop them
+    PrecisionLoss { value: String, decimals: u8 },
+    /// A price that isn't a whole number of ticks
+    NotTickAligned { value: u128, tick_size: u128 },
+    /// Not a plain decimal number
+    Malformed { value: String },
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::Overflow { value } => {
+                write!(f, "{} is outside the range of atoms", value)
+            }
+            ConversionError::PrecisionLoss { value, decimals } => {
+                write!(f, "{} has more than {} decimal places", value, decimals)
+            }
+            ConversionError::NotTickAligned { value, tick_size } => {
+                write!(
+                    f,
+                    "{} is not a multiple of the tick size {}",
+                    value, tick_size
+                )
+            }
+            ConversionError::Malformed { value } => write!(f, "{} is not a decimal number", value),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Convert a human-readable amount (e.g. "0.5") to atoms of a token with `decimals`
+///
+/// Trailing zeros past the token's precision are fine; any other digit there
+/// is refused rather than truncated.
+pub fn decimal_to_atoms(value: &str, decimals: u8) -> Result<u128, ConversionError> {
+    let overflow = || ConversionError::Overflow {
+        value: value.to_string(),
+    };
+    let malformed = || ConversionError::Malformed {
+        value: value.to_string(),
+    };
+
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(malformed());
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(ConversionError::PrecisionLoss {
+            value: value.to_string(),
+            decimals,
+        });
+    }
+    if digits.len() < value.len() && (whole.trim_start_matches('0'), fraction) != ("", "") {
+        return Err(overflow());
+    }
+
+    let scale = 10u128.checked_pow(decimals as u32).ok_or_else(overflow)?;
+    let whole_atoms = match whole {
+        "" => 0,
+        whole => whole.parse::<u128>().map_err(|_| overflow())?,
+    };
+    let fraction_atoms = match fraction {
+        "" => 0,
+        fraction => {
+            // At most `decimals` digits, so this never exceeds `scale`
+            fraction.parse::<u128>().map_err(|_| overflow())?
+                * 10u128.pow(decimals as u32 - fraction.len() as u32)
+        }
+    };
+    whole_atoms
+        .checked_mul(scale)
+        .and_then(|atoms| atoms.checked_add(fraction_atoms))
+        .ok_or_else(overflow)
+}
+
+/// Render atoms of a token with `decimals` as a decimal string, without trailing zeros
+///
+/// The inverse of [`decimal_to_atoms`]: no digit is lost, however small the amount.
+pub fn atoms_to_decimal(atoms: u128, decimals: u8) -> String {
+    let Some(divisor) = 10u128.checked_pow(decimals as u32) else {
+        // More decimals than atoms can hold; every digit is fractional
+        return format!("0.{:0>width$}", atoms, width = decimals as usize)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+    };
+    let (whole, fraction) = (atoms / divisor, atoms % divisor);
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+/// [`atoms_to_decimal`] for signed amounts such as fees, where negative is a rebate
+pub fn signed_atoms_to_decimal(atoms: i128, decimals: u8) -> String {
+    let magnitude = atoms_to_decimal(atoms.unsigned_abs(), decimals);
+    if atoms < 0 {
+        format!("-{}", magnitude)
+    } else {
+        magnitude
+    }
+}
+
+/// Check that a price is a whole number of ticks; a tick size of 0 accepts any price
+pub fn check_tick_aligned(value: u128, tick_size: u128) -> Result<u128, ConversionError> {
+    if tick_size != 0 && !value.is_multiple_of(tick_size) {
+        return Err(ConversionError::NotTickAligned { value, tick_size });
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_to_atoms_is_exact() {
+        assert_eq!(decimal_to_atoms("110000.50", 6), Ok(110_000_500_000));
+        assert_eq!(decimal_to_atoms("0.5", 8), Ok(50_000_000));
+        assert_eq!(decimal_to_atoms(".25", 2), Ok(25));
+        assert_eq!(decimal_to_atoms("3.", 0), Ok(3));
+        assert_eq!(decimal_to_atoms("-0.000", 6), Ok(0));
+
+        // Zeros past the token's precision are harmless, other digits aren't
+        assert_eq!(decimal_to_atoms("1.2300000000", 2), Ok(123));
+        assert_eq!(
+            decimal_to_atoms("0.0000001", 6),
+            Err(ConversionError::PrecisionLoss {
+                value: "0.0000001".to_string(),
+                decimals: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_decimal_to_atoms_refuses_out_of_range_and_malformed() {
+        assert!(matches!(
+            decimal_to_atoms("-1", 6),
+            Err(ConversionError::Overflow { .. })
+        ));
+        assert_eq!(decimal_to_atoms(&u128::MAX.to_string(), 0), Ok(u128::MAX));
+        assert!(matches!(
+            decimal_to_atoms(&u128::MAX.to_string(), 1),
+            Err(ConversionError::Overflow { .. })
+        ));
+        assert!(matches!(
+            decimal_to_atoms("1", 40),
+            Err(ConversionError::Overflow { .. })
+        ));
+        for value in ["", ".", "1e6", "1.2.3", "+1", " 1", "NaN"] {
+            assert!(
+                matches!(
+                    decimal_to_atoms(value, 6),
+                    Err(ConversionError::Malformed { .. })
+                ),
+                "{:?} should be malformed",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_atoms_to_decimal_round_trips() {
+        assert_eq!(atoms_to_decimal(150_000_000, 8), "1.5");
+        assert_eq!(atoms_to_decimal(1, 6), "0.000001");
+        assert_eq!(atoms_to_decimal(42, 0), "42");
+        assert_eq!(atoms_to_decimal(0, 6), "0");
+        assert_eq!(atoms_to_decimal(u128::MAX, 40), format!("0.0{}", u128::MAX));
+        assert_eq!(atoms_to_decimal(0, 40), "0");
+        assert_eq!(signed_atoms_to_decimal(-250, 6), "-0.00025");
+        assert_eq!(signed_atoms_to_decimal(i128::MIN, 0), i128::MIN.to_string());
+
+        for (atoms, decimals) in [(1, 18), (123_456_789, 6), (u128::MAX, 0), (u128::MAX, 38)] {
+            let decimal = atoms_to_decimal(atoms, decimals);
+            assert_eq!(
+                decimal_to_atoms(&decimal, decimals),
+                Ok(atoms),
+                "{}",
+                decimal
+            );
+        }
+    }
+
+    #[test]
+    fn test_amount_format_parses() {
+        assert_eq!("decimal".parse(), Ok(AmountFormat::Decimal));
+        assert_eq!("atoms".parse(), Ok(AmountFormat::Atoms));
+        assert!("Decimal".parse::<AmountFormat>().is_err());
+        assert_eq!(AmountFormat::default().to_string(), "atoms");
+    }
+
+    #[test]
+    fn test_check_tick_aligned() {
+        assert_eq!(check_tick_aligned(50_000_000, 1_000_000), Ok(50_000_000));
+        assert_eq!(
+            check_tick_aligned(50_500_000, 1_000_000),
+            Err(ConversionError::NotTickAligned {
+                value: 50_500_000,
+                tick_size: 1_000_000
+            })
+        );
+        assert_eq!(check_tick_aligned(7, 0), Ok(7));
+    }
+}
@@ -0,0 +1,1307 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    Limit,
+    Market,
+    /// Waits for a trade at its trigger price, then executes as a market order
+    StopMarket,
+    /// Waits for a trade at its trigger price, then executes as a limit order
+    StopLimit,
+}
+
+/// How long an order's unfilled remainder stays on the book
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    /// Good till cancelled: rests until filled or cancelled
+    #[default]
+    Gtc,
+    /// Immediate or cancel: fills what it can on arrival and cancels the rest
+    Ioc,
+    /// Fill or kill: fills completely on arrival or is rejected without trading
+    Fok,
+}
+
+/// Which price a stop order's trigger price is compared with
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerSource {
+    /// The market's last trade on this exchange
+    #[default]
+    LastTrade,
+    /// The market's index price from external venues, which thin-book
+    /// wicks here don't move
+    Index,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Filled,
+    PartiallyFilled,
+    Cancelled,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketStatus {
+    #[default]
+    Active,
+    /// Trading paused; resting orders are cancelled and new orders rejected
+    Halted,
+    /// Permanently closed; like halted, but cannot be reactivated
+    Delisted,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum UserStatus {
+    #[default]
+    Active,
+    /// New orders rejected; resting orders may be cancelled by the user or the exchange
+    Frozen,
+    /// Like frozen, but resting orders are always cancelled
+    Banned,
+}
+
+/// Where a withdrawal is on its way to the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalStatus {
+    /// Funds locked, waiting for the processor; the user may still cancel
+    Pending,
+    /// Handed to the signer
+    Processing,
+    /// Broadcast as `tx_hash`, waiting for confirmations
+    Submitted,
+    /// Confirmed on chain and debited from the balance
+    Confirmed,
+    /// Rejected or reverted; the funds were released
+    Failed,
+    /// Cancelled by the user before processing; the funds were released
+    Cancelled,
+}
+
+/// Exchange-owned accounts, held as the balances of reserved user addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemAccount {
+    /// Receives trading fees and pays maker rebates and referral shares
+    FeeCollector,
+    /// Backstop for losses, funded by its share of fees and liquidation proceeds
+    InsuranceFund,
+}
+
+/// Revenue the exchange routes between its system accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RevenueSource {
+    TradingFees,
+    Liquidations,
+}
+
+/// Why a system account balance changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEntryKind {
+    /// Balance held before the ledger was introduced
+    OpeningBalance,
+    TradingFee,
+    MakerRebate,
+    ReferralPayout,
+    Liquidation,
+    /// Perpetual funding and mark-to-market settlement: rounding dust and shortfalls
+    Funding,
+}
+
+/// Market abuse trade surveillance looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SurveillanceKind {
+    /// Accounts with the same beneficial owner trading with each other
+    WashTrading,
+    /// Orders near the touch cancelled far more often than they fill
+    Spoofing,
+    /// An account trading with itself
+    SelfMatching,
+}
+
+/// How the cost of a position is matched against sales when realizing PnL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisMethod {
+    /// Sales close the oldest purchases first
+    #[default]
+    Fifo,
+    /// Sales close purchases at their average price
+    AverageCost,
+}
+
+/// Which side of a trade a user was on: resting in the book or crossing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidityRole {
+    Maker,
+    Taker,
+}
+
+/// Why the exchange, rather than the user, cancelled an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    KillSwitch,
+    MarketHalted,
+    AccountRestricted,
+    /// The user's position in the market was being liquidated
+    Liquidation,
+    /// The order was still open at its `expires_at`
+    Expired,
+    /// The other order of its one-cancels-other pair filled, or triggered
+    /// if it was the stop
+    LinkedOrderFilled,
+    /// A triggered stop would have rested across one of its user's own orders
+    SelfTrade,
+}
+
+/// Why the exchange refused an order, for clients to act on without parsing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    InsufficientBalance,
+    /// Price or size doesn't fit the market's tick size, lot size or minimum
+    InvalidOrder,
+    /// Price too far from the market's reference price
+    PriceBand,
+    /// Over one of the user's size, notional or open order limits
+    LimitExceeded,
+    /// The market is halted or delisted
+    MarketHalted,
+    /// A kill switch only lets the market's orders be cancelled
+    CancelOnly,
+    /// The user is frozen or banned
+    AccountRestricted,
+    /// A fill-or-kill order couldn't fill completely on arrival
+    InsufficientLiquidity,
+    /// The order would rest across one of the user's own orders
+    SelfTrade,
+}
+
+/// How a user's perpetual positions are margined
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginMode {
+    /// Each position stands on its own margin; losing it never touches the rest of the balance
+    #[default]
+    Isolated,
+    /// Positions share the user's free balance of their quote token
+    Cross,
+}
+
+/// What a request authenticated with an API key may do
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Read orders, balances, trades and the rest of the account
+    Read,
+    /// Place and cancel orders and choose how positions are margined
+    Trade,
+    /// Request withdrawals and move funds between sub-accounts
+    Withdraw,
+}
+
+/// Whether a prediction event's outcome is known
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EventStatus {
+    /// Outcome markets may trade
+    #[default]
+    Open,
+    /// Outcome markets are delisted and the winning outcome's holders were paid
+    Resolved,
+}
+
+/// Where a request for quote stands
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RfqStatus {
+    /// Makers may quote and the taker may accept until it expires
+    #[default]
+    Open,
+    /// The taker accepted a quote and the trade settled
+    Filled,
+    /// Withdrawn by the taker
+    Cancelled,
+    /// Ran out its TTL without a quote being accepted
+    Expired,
+}
+
+// ============================================================================
+// ENUM STRING CONVERSIONS
+// ============================================================================
+
+impl Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Side::Buy => "buy",
+                Side::Sell => "sell",
+            }
+        )
+    }
+}
+
+impl FromStr for Side {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buy" => Ok(Side::Buy),
+            "sell" => Ok(Side::Sell),
+            _ => Err(format!("Invalid side: {}", s)),
+        }
+    }
+}
+
+impl OrderType {
+    /// Whether orders of this type wait for a trigger price
+    pub fn is_stop(&self) -> bool {
+        matches!(self, OrderType::StopMarket | OrderType::StopLimit)
+    }
+
+    /// The type a stop order executes as once triggered; other types are their own
+    pub fn execution_type(&self) -> OrderType {
+        match self {
+            OrderType::Limit | OrderType::StopLimit => OrderType::Limit,
+            OrderType::Market | OrderType::StopMarket => OrderType::Market,
+        }
+    }
+}
+
+impl Display for OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OrderType::Limit => "limit",
+                OrderType::Market => "market",
+                OrderType::StopMarket => "stop_market",
+                OrderType::StopLimit => "stop_limit",
+            }
+        )
+    }
+}
+
+impl FromStr for OrderType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "limit" => Ok(OrderType::Limit),
+            "market" => Ok(OrderType::Market),
+            "stop_market" => Ok(OrderType::StopMarket),
+            "stop_limit" => Ok(OrderType::StopLimit),
+            _ => Err(format!("Invalid order type: {}", s)),
+        }
+    }
+}
+
+impl Display for TimeInForce {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TimeInForce::Gtc => "gtc",
+                TimeInForce::Ioc => "ioc",
+                TimeInForce::Fok => "fok",
+            }
+        )
+    }
+}
+
+impl FromStr for TimeInForce {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gtc" => Ok(TimeInForce::Gtc),
+            "ioc" => Ok(TimeInForce::Ioc),
+            "fok" => Ok(TimeInForce::Fok),
+            _ => Err(format!("Invalid time in force: {}", s)),
+        }
+    }
+}
+
+impl Display for TriggerSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TriggerSource::LastTrade => "last_trade",
+                TriggerSource::Index => "index",
+            }
+        )
+    }
+}
+
+impl FromStr for TriggerSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "last_trade" => Ok(TriggerSource::LastTrade),
+            "index" => Ok(TriggerSource::Index),
+            _ => Err(format!("Invalid trigger source: {}", s)),
+        }
+    }
+}
+
+impl Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OrderStatus::Pending => "pending",
+                OrderStatus::Filled => "filled",
+                OrderStatus::PartiallyFilled => "partially_filled",
+                OrderStatus::Cancelled => "cancelled",
+            }
+        )
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(OrderStatus::Pending),
+            "filled" => Ok(OrderStatus::Filled),
+            "partially_filled" => Ok(OrderStatus::PartiallyFilled),
+            "cancelled" => Ok(OrderStatus::Cancelled),
+            _ => Err(format!("Invalid order status: {}", s)),
+        }
+    }
+}
+
+impl Display for MarketStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MarketStatus::Active => "active",
+                MarketStatus::Halted => "halted",
+                MarketStatus::Delisted => "delisted",
+            }
+        )
+    }
+}
+
+impl Display for CancelReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CancelReason::KillSwitch => "kill_switch",
+                CancelReason::MarketHalted => "market_halted",
+                CancelReason::AccountRestricted => "account_restricted",
+                CancelReason::Liquidation => "liquidation",
+                CancelReason::Expired => "expired",
+                CancelReason::LinkedOrderFilled => "linked_order_filled",
+                CancelReason::SelfTrade => "self_trade",
+            }
+        )
+    }
+}
+
+impl FromStr for CancelReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kill_switch" => Ok(CancelReason::KillSwitch),
+            "market_halted" => Ok(CancelReason::MarketHalted),
+            "account_restricted" => Ok(CancelReason::AccountRestricted),
+            "liquidation" => Ok(CancelReason::Liquidation),
+            "expired" => Ok(CancelReason::Expired),
+            "linked_order_filled" => Ok(CancelReason::LinkedOrderFilled),
+            "self_trade" => Ok(CancelReason::SelfTrade),
+            _ => Err(format!("Invalid cancel reason: {}", s)),
+        }
+    }
+}
+
+impl FromStr for MarketStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(MarketStatus::Active),
+            "halted" => Ok(MarketStatus::Halted),
+            "delisted" => Ok(MarketStatus::Delisted),
+            _ => Err(format!("Invalid market status: {}", s)),
+        }
+    }
+}
+
+impl SystemAccount {
+    pub const ALL: [SystemAccount; 2] = [SystemAccount::FeeCollector, SystemAccount::InsuranceFund];
+
+    /// Address of the user row holding this account's balances
+    pub fn address(&self) -> &'static str {
+        match self {
+            SystemAccount::FeeCollector => "system",
+            SystemAccount::InsuranceFund => "insurance",
+        }
+    }
+
+    pub fn from_address(address: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.address() == address)
+    }
+}
+
+impl SubAccount {
+    /// Address of `master`'s sub-account called `name`
+    pub fn address_of(master: &str, name: &str) -> String {
+        format!("{}:{}", master, name)
+    }
+
+    /// The master of a sub-account address, `None` for any other address
+    pub fn master_of(address: &str) -> Option<&str> {
+        address.split_once(':').map(|(master, _)| master)
+    }
+
+    /// Who answers for an account: a sub-account's master, otherwise itself
+    pub fn owner_of(address: &str) -> &str {
+        Self::master_of(address).unwrap_or(address)
+    }
+
+    /// Whether `user_address` may act for `account`: its own, or one of its sub-accounts
+    pub fn owns(user_address: &str, account: &str) -> bool {
+        account == user_address || Self::master_of(account) == Some(user_address)
+    }
+}
+
+impl Display for RevenueSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RevenueSource::TradingFees => "trading_fees",
+                RevenueSource::Liquidations => "liquidations",
+            }
+        )
+    }
+}
+
+impl FromStr for RevenueSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trading_fees" => Ok(RevenueSource::TradingFees),
+            "liquidations" => Ok(RevenueSource::Liquidations),
+            _ => Err(format!("Invalid revenue source: {}", s)),
+        }
+    }
+}
+
+impl Display for LedgerEntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                LedgerEntryKind::OpeningBalance => "opening_balance",
+                LedgerEntryKind::TradingFee => "trading_fee",
+                LedgerEntryKind::MakerRebate => "maker_rebate",
+                LedgerEntryKind::ReferralPayout => "referral_payout",
+                LedgerEntryKind::Liquidation => "liquidation",
+                LedgerEntryKind::Funding => "funding",
+            }
+        )
+    }
+}
+
+impl FromStr for LedgerEntryKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "opening_balance" => Ok(LedgerEntryKind::OpeningBalance),
+            "trading_fee" => Ok(LedgerEntryKind::TradingFee),
+            "maker_rebate" => Ok(LedgerEntryKind::MakerRebate),
+            "referral_payout" => Ok(LedgerEntryKind::ReferralPayout),
+            "liquidation" => Ok(LedgerEntryKind::Liquidation),
+            "funding" => Ok(LedgerEntryKind::Funding),
+            _ => Err(format!("Invalid ledger entry kind: {}", s)),
+        }
+    }
+}
+
+impl Display for SurveillanceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SurveillanceKind::WashTrading => "wash_trading",
+                SurveillanceKind::Spoofing => "spoofing",
+                SurveillanceKind::SelfMatching => "self_matching",
+            }
+        )
+    }
+}
+
+impl FromStr for SurveillanceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wash_trading" => Ok(SurveillanceKind::WashTrading),
+            "spoofing" => Ok(SurveillanceKind::Spoofing),
+            "self_matching" => Ok(SurveillanceKind::SelfMatching),
+            _ => Err(format!("Invalid surveillance kind: {}", s)),
+        }
+    }
+}
+
+impl Display for UserStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                UserStatus::Active => "active",
+                UserStatus::Frozen => "frozen",
+                UserStatus::Banned => "banned",
+            }
+        )
+    }
+}
+
+impl FromStr for UserStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(UserStatus::Active),
+            "frozen" => Ok(UserStatus::Frozen),
+            "banned" => Ok(UserStatus::Banned),
+            _ => Err(format!("Invalid user status: {}", s)),
+        }
+    }
+}
+
+impl Display for MarginMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MarginMode::Isolated => "isolated",
+                MarginMode::Cross => "cross",
+            }
+        )
+    }
+}
+
+impl FromStr for MarginMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "isolated" => Ok(MarginMode::Isolated),
+            "cross" => Ok(MarginMode::Cross),
+            _ => Err(format!("Invalid margin mode: {}", s)),
+        }
+    }
+}
+
+impl Display for ApiKeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ApiKeyScope::Read => "read",
+                ApiKeyScope::Trade => "trade",
+                ApiKeyScope::Withdraw => "withdraw",
+            }
+        )
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(ApiKeyScope::Read),
+            "trade" => Ok(ApiKeyScope::Trade),
+            "withdraw" => Ok(ApiKeyScope::Withdraw),
+            _ => Err(format!("Invalid API key scope: {}", s)),
+        }
+    }
+}
+
+impl Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EventStatus::Open => "open",
+                EventStatus::Resolved => "resolved",
+            }
+        )
+    }
+}
+
+impl FromStr for EventStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(EventStatus::Open),
+            "resolved" => Ok(EventStatus::Resolved),
+            _ => Err(format!("Invalid event status: {}", s)),
+        }
+    }
+}
+
+impl Display for RfqStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RfqStatus::Open => "open",
+                RfqStatus::Filled => "filled",
+                RfqStatus::Cancelled => "cancelled",
+                RfqStatus::Expired => "expired",
+            }
+        )
+    }
+}
+
+impl FromStr for RfqStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(RfqStatus::Open),
+            "filled" => Ok(RfqStatus::Filled),
+            "cancelled" => Ok(RfqStatus::Cancelled),
+            "expired" => Ok(RfqStatus::Expired),
+            _ => Err(format!("Invalid RFQ status: {}", s)),
+        }
+    }
+}
+
+impl Display for WithdrawalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                WithdrawalStatus::Pending => "pending",
+                WithdrawalStatus::Processing => "processing",
+                WithdrawalStatus::Submitted => "submitted",
+                WithdrawalStatus::Confirmed => "confirmed",
+                WithdrawalStatus::Failed => "failed",
+                WithdrawalStatus::Cancelled => "cancelled",
+            }
+        )
+    }
+}
+
+impl FromStr for WithdrawalStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(WithdrawalStatus::Pending),
+            "processing" => Ok(WithdrawalStatus::Processing),
+            "submitted" => Ok(WithdrawalStatus::Submitted),
+            "confirmed" => Ok(WithdrawalStatus::Confirmed),
+            "failed" => Ok(WithdrawalStatus::Failed),
+            "cancelled" => Ok(WithdrawalStatus::Cancelled),
+            _ => Err(format!("Invalid withdrawal status: {}", s)),
+        }
+    }
+}
+
+impl Display for LiquidityRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                LiquidityRole::Maker => "maker",
+                LiquidityRole::Taker => "taker",
+            }
+        )
+    }
+}
+
+impl Display for CostBasisMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CostBasisMethod::Fifo => "fifo",
+                CostBasisMethod::AverageCost => "average_cost",
+            }
+        )
+    }
+}
+
+// ============================================================================
+// DOMAIN TYPES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct User {
+    pub address: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A user's account over one calendar month (UTC)
+///
+/// Closing balances are the balances when the month's statements were
+/// generated, at `balances_at`, shortly after the month ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub user_address: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub balances_at: DateTime<Utc>,
+    /// Tokens the user held or moved, by ticker
+    pub balances: Vec<StatementBalance>,
+    /// Markets the user traded in, by id
+    pub markets: Vec<StatementMarket>,
+}
+
+/// One token on a statement, in its atoms
+///
+/// Whatever opening plus deposits, less withdrawals and fees, leaves
+/// unexplained in the closing balance came from trading, funding, PnL
+/// settlement and faucet drips.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementBalance {
+    pub token_ticker: String,
+    /// The month before's closing balance; `None` when no statements were
+    /// generated for the month before
+    pub opening_balance: Option<u128>,
+    pub closing_balance: u128,
+    pub deposited: u128,
+    /// Confirmed withdrawals
+    pub withdrawn: u128,
+    /// Trading fees less maker rebates, negative when rebates were larger
+    pub fees: i128,
+}
+
+/// A user's fills in one market over a statement's month
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementMarket {
+    pub market_id: String,
+    pub fills: u64,
+    /// Base atoms bought and sold
+    pub bought: u128,
+    pub sold: u128,
+    /// Quote atoms traded, bought and sold together
+    pub notional: u128,
+}
+
+/// A user's account page in one read: who they are, what they're doing, what they hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserSummary {
+    pub address: String,
+    pub created_at: DateTime<Utc>,
+    pub status: UserStatus,
+    pub display_name: Option<String>,
+    /// Limit orders resting across all markets
+    pub open_orders: u64,
+    /// Quote notional traded in the last 30 days, per quote token
+    pub volume_30d: Vec<(String, u128)>,
+    /// Trading fees paid over the account's life, per token; rebates aren't netted off
+    pub fees_paid: Vec<(String, u128)>,
+    pub balances: Vec<Balance>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Token {
+    pub ticker: String,
+    pub decimals: u8,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Market {
+    pub id: String, // Generated as "base_ticker/quote_ticker"
+    pub base_ticker: String,
+    pub quote_ticker: String,
+    pub tick_size: u128,    // Minimum price increment in quote atoms
+    pub lot_size: u128,     // Minimum size increment in base atoms
+    pub min_size: u128,     // Minimum order size in base atoms
+    pub maker_fee_bps: i32, // Maker fee in basis points (-10000-10000, negative is a rebate)
+    pub taker_fee_bps: i32, // Taker fee in basis points (0-10000)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Order {
+    pub id: Uuid,
+    pub user_address: String,
+    pub market_id: String, // Generated as "base_ticker/quote_ticker"
+    pub price: u128,
+    pub size: u128,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub status: OrderStatus,
+    pub filled_size: u128,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Why the exchange cancelled the order; `None` unless it did
+    #[serde(default)]
+    pub cancel_reason: Option<CancelReason>,
+    /// Price that activates a stop order; kept once the order has
+    /// triggered and taken its execution type
+    #[serde(default)]
+    pub trigger_price: Option<u128>,
+    /// Whether `trigger_price` is reached by the last trade or the index price
+    #[serde(default)]
+    pub trigger_source: TriggerSource,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+    /// When the exchange cancels the order if it's still open; `None` keeps
+    /// it until cancelled
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Order {
+    /// Whether an unfilled remainder rests on the book once the order
+    /// executes; market, immediate-or-cancel and fill-or-kill orders never rest
+    pub fn rests(&self) -> bool {
+        self.order_type.execution_type() == OrderType::Limit
+            && self.time_in_force == TimeInForce::Gtc
+    }
+}
+
+/// Two orders placed together as one-cancels-other: the first fill on either
+/// cancels the other
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OcoLink {
+    pub id: Uuid,
+    pub user_address: String,
+    pub first_order_id: Uuid,
+    pub second_order_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Trade {
+    pub id: Uuid,
+    pub market_id: String,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub buyer_order_id: Uuid,
+    pub seller_order_id: Uuid,
+    pub price: u128,
+    pub size: u128,
+    pub side: Side, // Taker's side (determines if trade is "buy" or "sell" on tape)
+    pub timestamp: DateTime<Utc>,
+    /// Negotiated through a request for quote rather than matched on the book
+    #[serde(default)]
+    pub rfq: bool,
+}
+
+impl Trade {
+    /// The role `user_address` played in this trade, if they were part of it
+    /// A trade with yourself counts as taking
+    pub fn role_of(&self, user_address: &str) -> Option<LiquidityRole> {
+        let taker = match self.side {
+            Side::Buy => &self.buyer_address,
+            Side::Sell => &self.seller_address,
+        };
+        if taker == user_address {
+            Some(LiquidityRole::Taker)
+        } else if self.buyer_address == user_address || self.seller_address == user_address {
+            Some(LiquidityRole::Maker)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Balance {
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: u128,
+    pub open_interest: u128,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A perpetual position the exchange force-closed for falling below its maintenance margin
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Liquidation {
+    pub user_address: String,
+    pub market_id: String,
+    pub order_id: Uuid, // The order that closed the position
+    pub size: i128,     // Position size before liquidation, negative for shorts
+    pub closed_size: u128,
+    pub mark_price: u128,
+    pub margin_mode: MarginMode,
+    pub fee: u128, // Liquidation fee charged, in quote atoms
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Share of a revenue source paid into the insurance fund; the rest goes to the fee collector
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct FeeRoute {
+    pub source: RevenueSource,
+    pub insurance_bps: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One change to a system account balance; negative amounts are payouts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub account: SystemAccount,
+    pub token_ticker: String,
+    pub amount: i128,
+    pub kind: LedgerEntryKind,
+    pub trade_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Suspected market abuse in one market over one surveillance window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SurveillanceAlert {
+    pub id: i64,
+    pub kind: SurveillanceKind,
+    pub market_id: String,
+    /// The beneficial owner for wash trading, otherwise the account
+    pub subject: String,
+    /// Accounts involved
+    pub accounts: Vec<String>,
+    /// Trades behind the alert, or cancelled orders for spoofing
+    pub count: u64,
+    /// Base atoms traded, or cancelled for spoofing
+    pub volume: u128,
+    pub detail: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Risk limits for one user in one market; `None` means unlimited
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserLimits {
+    pub user_address: String,
+    pub market_id: String,
+    /// Largest absolute net position (base atoms bought minus sold)
+    pub max_position: Option<u128>,
+    /// Largest total notional of resting orders, in quote atoms
+    pub max_open_notional: Option<u128>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where a resting order stands in its price level's time-priority queue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueuePosition {
+    pub order_id: Uuid,
+    pub market_id: String,
+    pub side: Side,
+    pub price: u128,
+    /// The order's own unfilled size
+    pub remaining: u128,
+    /// Orders at the same price that fill before this one
+    pub orders_ahead: u64,
+    /// Unfilled size of those orders
+    pub size_ahead: u128,
+    /// Unfilled size of the whole level, this order included
+    pub level_size: u128,
+}
+
+/// An engaged kill switch: its market, or the whole exchange when `market_id`
+/// is `None`, accepts cancels but no new orders
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct KillSwitch {
+    pub market_id: Option<String>,
+    pub reason: Option<String>,
+    pub engaged_at: DateTime<Utc>,
+}
+
+/// A referred user and who referred them
+///
+/// `share_bps` of every taker fee the user pays is passed on to the referrer.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct Referral {
+    pub user_address: String,
+    pub referrer_address: String,
+    pub share_bps: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Fee rates a user pays in one market instead of the market's own
+///
+/// A side without a rate pays the market's fee. Overrides never exceed the
+/// market's fees, so they can't outgrow what orders reserve for them.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct FeeOverride {
+    pub user_address: String,
+    pub market_id: String,
+    pub maker_fee_bps: Option<i32>,
+    pub taker_fee_bps: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An ERC-20 transfer to an exchange deposit address, credited to its sender
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deposit {
+    pub chain_id: u64,
+    pub tx_hash: String,
+    /// Position of the transfer event in its block, telling apart transfers of one transaction
+    pub log_index: u64,
+    pub block_number: u64,
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: u128,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A request to send part of a user's balance to an on-chain address
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Withdrawal {
+    pub id: Uuid,
+    pub user_address: String,
+    pub token_ticker: String,
+    pub amount: u128,
+    pub destination: String,
+    pub status: WithdrawalStatus,
+    pub tx_hash: Option<String>,
+    /// Why the withdrawal failed
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A named account of a master user with its own balances and orders
+///
+/// Its address is the master's followed by `:` and the name, so whoever
+/// signs for the master signs for its sub-accounts too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubAccount {
+    pub address: String,
+    pub master_address: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Free balance a master moved between its own accounts
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountTransfer {
+    pub id: Uuid,
+    pub master_address: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub token_ticker: String,
+    pub amount: u128,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A URL a user's fills and order updates are posted to, signed with `secret`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub user_address: String,
+    pub url: String,
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A key a user's requests can authenticate with instead of a signature
+///
+/// Only a hash of the key is stored; `key_prefix` is its first characters,
+/// kept so the user can tell their keys apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_address: String,
+    pub label: String,
+    pub key_prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    /// Addresses or CIDR ranges the key may be used from; empty allows any
+    pub ip_allowlist: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A webhook delivery that failed every attempt and was set aside
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDeadLetter {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_type: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A question whose mutually exclusive outcomes each trade as a market in `quote_ticker`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictionEvent {
+    pub id: String,
+    pub title: String,
+    pub quote_ticker: String,
+    pub status: EventStatus,
+    pub winning_outcome: Option<String>,
+    /// In display order
+    pub outcomes: Vec<EventOutcome>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// One outcome of a prediction event: the market trading its token against the event's quote token
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventOutcome {
+    pub name: String,
+    pub market_id: String,
+    /// Pays one quote token per whole token if this outcome wins
+    pub token_ticker: String,
+}
+
+/// A taker's request for makers to quote a firm price on `size` of a market
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuoteRequest {
+    pub id: Uuid,
+    pub taker_address: String,
+    pub market_id: String,
+    pub side: Side, // The taker's side
+    pub size: u128,
+    pub status: RfqStatus,
+    /// The trade that filled the request
+    pub trade_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A maker's firm price for a request for quote, executable until it expires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Quote {
+    pub id: Uuid,
+    pub request_id: Uuid,
+    pub maker_address: String,
+    pub price: u128,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Result of placing an order, with the order and its fills parsed from the wire
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedOrder {
+    pub order: Order,
+    pub trades: Vec<Trade>,
+}
+
+/// Result of placing a one-cancels-other pair, parsed from the wire
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedOco {
+    pub oco_id: Uuid,
+    pub first: Order,
+    pub second: Order,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub market_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub open: u128,
+    pub high: u128,
+    pub low: u128,
+    pub close: u128,
+    pub volume: u128,
+}
+
+// ============================================================================
+// ORDERBOOK TYPES
+// ============================================================================
+
+/// Represents a price level in the orderbook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookLevel {
+    pub price: u128,
+    pub size: u128,
+}
+
+/// Snapshot of an orderbook at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookSnapshot {
+    pub market_id: String,
+    pub bids: Vec<OrderbookLevel>, // Sorted by price descending (highest first)
+    pub asks: Vec<OrderbookLevel>, // Sorted by price ascending (lowest first)
+    pub timestamp: DateTime<Utc>,
+    pub version: u64, // Book version the snapshot was taken at (bumps on every change)
+}
@@ -0,0 +1,174 @@
+//! Machine-readable error codes
+//!
+//! Every error the exchange reports carries one of these codes: REST error
+//! bodies in `code`, WebSocket `error` messages and order-rejected
+//! notifications alike. Messages may be reworded between releases; codes
+//! are not, so clients branch on the code. Codes are only ever added, and a
+//! client built before one was added reads it as [`ErrorCode::Unknown`].
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+macro_rules! error_codes {
+    ($($(#[doc = $doc:literal])* $variant:ident = $code:literal,)*) => {
+        /// What went wrong, as a stable `SCREAMING_SNAKE_CASE` string
+        #[derive(
+            Debug,
+            Clone,
+            Copy,
+            Default,
+            PartialEq,
+            Eq,
+            Hash,
+            Serialize,
+            Deserialize,
+            ToSchema,
+            JsonSchema,
+        )]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        pub enum ErrorCode {
+            $(
+                $(#[doc = $doc])*
+                $variant,
+            )*
+            /// A code added after this client was built
+            #[default]
+            #[serde(other)]
+            Unknown,
+        }
+
+        impl ErrorCode {
+            /// Every code the exchange reports
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$variant),*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $code,)*
+                    ErrorCode::Unknown => "UNKNOWN",
+                }
+            }
+        }
+    };
+}
+
+// Each code is its variant's name in SCREAMING_SNAKE_CASE, which is what
+// serde writes; the tests below hold the two together
+error_codes! {
+    TokenNotFound = "TOKEN_NOT_FOUND",
+    MarketNotFound = "MARKET_NOT_FOUND",
+    MarketAlreadyExists = "MARKET_ALREADY_EXISTS",
+    DisplayNameTaken = "DISPLAY_NAME_TAKEN",
+    SubAccountExists = "SUB_ACCOUNT_EXISTS",
+    InvalidParameter = "INVALID_PARAMETER",
+    InvalidPrice = "INVALID_PRICE",
+    InvalidSize = "INVALID_SIZE",
+    InvalidAmount = "INVALID_AMOUNT",
+    OrderValueOverflow = "ORDER_VALUE_OVERFLOW",
+    /// Price isn't a multiple of the market's tick size
+    InvalidTickSize = "INVALID_TICK_SIZE",
+    /// Size isn't a multiple of the market's lot size
+    InvalidLotSize = "INVALID_LOT_SIZE",
+    SizeBelowMinimum = "SIZE_BELOW_MINIMUM",
+    InsufficientBalance = "INSUFFICIENT_BALANCE",
+    /// The book can't fill a fill-or-kill order completely
+    InsufficientLiquidity = "INSUFFICIENT_LIQUIDITY",
+    /// The order would rest across one of the user's own orders
+    SelfTrade = "SELF_TRADE",
+    /// Over one of the user's position or open notional limits
+    LimitExceeded = "LIMIT_EXCEEDED",
+    TooManyOpenOrders = "TOO_MANY_OPEN_ORDERS",
+    /// The market is halted or delisted
+    MarketNotActive = "MARKET_NOT_ACTIVE",
+    PriceOutsideCollar = "PRICE_OUTSIDE_COLLAR",
+    /// The user is frozen or banned
+    UserNotActive = "USER_NOT_ACTIVE",
+    /// A kill switch only lets the market's orders be cancelled
+    CancelOnly = "CANCEL_ONLY",
+    /// Missing or wrong admin token
+    Unauthorized = "UNAUTHORIZED",
+    InvalidApiKey = "INVALID_API_KEY",
+    ApiKeyNotPermitted = "API_KEY_NOT_PERMITTED",
+    /// Missing, malformed, expired or reused signature, or not the account's
+    InvalidSignature = "INVALID_SIGNATURE",
+    ApiKeyNotFound = "API_KEY_NOT_FOUND",
+    OrderNotFound = "ORDER_NOT_FOUND",
+    UserNotFound = "USER_NOT_FOUND",
+    ExportNotFound = "EXPORT_NOT_FOUND",
+    ExportNotReady = "EXPORT_NOT_READY",
+    WebhookNotFound = "WEBHOOK_NOT_FOUND",
+    WithdrawalNotFound = "WITHDRAWAL_NOT_FOUND",
+    SubAccountNotFound = "SUB_ACCOUNT_NOT_FOUND",
+    StatementNotFound = "STATEMENT_NOT_FOUND",
+    WithdrawalNotPending = "WITHDRAWAL_NOT_PENDING",
+    EventNotFound = "EVENT_NOT_FOUND",
+    EventAlreadyResolved = "EVENT_ALREADY_RESOLVED",
+    RfqRequestNotFound = "RFQ_REQUEST_NOT_FOUND",
+    RfqRequestNotOpen = "RFQ_REQUEST_NOT_OPEN",
+    QuoteNotFound = "QUOTE_NOT_FOUND",
+    NotRfqMaker = "NOT_RFQ_MAKER",
+    NotMarketMaker = "NOT_MARKET_MAKER",
+    RateLimited = "RATE_LIMITED",
+    BalanceNotFound = "BALANCE_NOT_FOUND",
+    /// A WebSocket message that isn't a client message
+    InvalidMessage = "INVALID_MESSAGE",
+    /// A WebSocket subscription missing the market, user or interval its channel needs
+    InvalidSubscription = "INVALID_SUBSCRIPTION",
+    EngineSendFailed = "ENGINE_SEND_FAILED",
+    EngineReceiveFailed = "ENGINE_RECEIVE_FAILED",
+    UnlockFailed = "UNLOCK_FAILED",
+    DatabaseError = "DATABASE_ERROR",
+    ClickhouseError = "CLICKHOUSE_ERROR",
+    ParseError = "PARSE_ERROR",
+    UuidParseError = "UUID_PARSE_ERROR",
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ErrorCode::ALL
+            .iter()
+            .find(|code| code.as_str() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown error code: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_serialize_as_their_strings() {
+        for code in ErrorCode::ALL {
+            let json = serde_json::to_value(code).unwrap();
+            assert_eq!(json, code.as_str());
+            assert_eq!(serde_json::from_value::<ErrorCode>(json).unwrap(), *code);
+            assert_eq!(code.as_str().parse::<ErrorCode>(), Ok(*code));
+        }
+    }
+
+    #[test]
+    fn test_codes_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for code in ErrorCode::ALL {
+            assert!(seen.insert(code.as_str()), "{} listed twice", code);
+        }
+    }
+
+    #[test]
+    fn test_unknown_codes() {
+        let code: ErrorCode = serde_json::from_str("\"SOMETHING_NEW\"").unwrap();
+        assert_eq!(code, ErrorCode::Unknown);
+        assert!("SOMETHING_NEW".parse::<ErrorCode>().is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::api::{ApiBalance, ApiLiquidation, ApiOrder, ApiStatement, ApiTrade, ApiWithdrawal};
+use super::domain::CancelReason;
+
+/// Version of the event bus payloads, bumped on every breaking change
+///
+/// Consumers should skip messages with a version they don't know.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// One message published to the event bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusMessage {
+    pub schema_version: u32,
+    /// Counts up from 0 per publisher, which restarts with the backend;
+    /// a gap means messages were dropped
+    pub sequence: u64,
+    pub published_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: BusEvent,
+}
+
+/// Engine events published to the event bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum BusEvent {
+    Trade(ApiTrade),
+    /// An order was placed, filled or partially filled; `status` tells which
+    Order(ApiOrder),
+    OrderCancelled {
+        order_id: String, // UUID as string
+        user_address: String,
+        /// `None` when the user cancelled the order themselves
+        reason: Option<CancelReason>,
+    },
+    Balance(ApiBalance),
+    /// A withdrawal changed status
+    Withdrawal(ApiWithdrawal),
+    /// A position was force-closed
+    Liquidation(ApiLiquidation),
+    /// A monthly account statement was generated; delivered to webhooks only
+    Statement(ApiStatement),
+}
+
+impl BusEvent {
+    /// Topic the event is published to, under the bus prefix
+    pub fn topic(&self) -> &'static str {
+        match self {
+            BusEvent::Trade(_) => "trades",
+            BusEvent::Order(_) | BusEvent::OrderCancelled { .. } => "orders",
+            BusEvent::Balance(_) => "balances",
+            BusEvent::Withdrawal(_) => "withdrawals",
+            BusEvent::Liquidation(_) => "liquidations",
+            BusEvent::Statement(_) => "statements",
+        }
+    }
+
+    /// Partition key: events with the same key keep their order on Kafka
+    pub fn key(&self) -> &str {
+        match self {
+            BusEvent::Trade(trade) => &trade.market_id,
+            BusEvent::Order(order) => &order.user_address,
+            BusEvent::OrderCancelled { user_address, .. } => user_address,
+            BusEvent::Balance(balance) => &balance.user_address,
+            BusEvent::Withdrawal(withdrawal) => &withdrawal.user_address,
+            BusEvent::Liquidation(liquidation) => &liquidation.user_address,
+            BusEvent::Statement(statement) => &statement.user_address,
+        }
+    }
+}
+
+/// Header carrying the hex HMAC-SHA256 of `"{timestamp}.{body}"` under the webhook secret
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Exchange-Signature";
+
+/// Header carrying the Unix timestamp, in seconds, the delivery was signed at
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Exchange-Timestamp";
+
+/// Body posted to a user's webhook
+///
+/// Only the user's own events are delivered: `trade` for their fills,
+/// `order` and `order_cancelled` for their order state changes,
+/// `withdrawal` as their withdrawals progress, `liquidation` when
+/// one of their positions is force-closed, and `statement` once a month
+/// with their account statement for the month before. Retries of a
+/// delivery keep its `delivery_id`, so receivers can drop duplicates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub schema_version: u32,
+    pub delivery_id: String, // UUID as string
+    pub webhook_id: String,  // UUID as string
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: BusEvent,
+}
@@ -0,0 +1,25 @@
+//! Exchange Protocol
+//!
+//! Wire types shared by the backend and every Rust client. The backend
+//! re-exports these from `backend::models`, and the SDK builds its requests
+//! and parses its responses with the same definitions, so a field added here
+//! reaches both sides (and the generated schemas in `packages/shared`) at once.
+//!
+//! - [`api`]: REST request/response bodies and WebSocket messages
+//! - [`convert`]: checked conversions between decimal amounts and atoms
+//! - [`domain`]: enums and value types with native (`u128`, `Uuid`) fields
+//! - [`error_code`]: the codes every REST and WebSocket error carries
+//! - [`events`]: engine events published to the event bus
+//! - [`symbol`]: market id aliases and numeric ids
+
+pub mod api;
+pub mod convert;
+pub mod domain;
+pub mod error_code;
+pub mod events;
+pub mod symbol;
+
+pub use api::*;
+pub use domain::*;
+pub use error_code::ErrorCode;
+pub use events::*;
@@ -0,0 +1,203 @@
+//! Market symbol aliases
+//!
+//! Market ids are `BASE/QUOTE`, but integrations spell them many ways:
+//! `BTC-USDC`, `btc_usdc`, `BTCUSDC`. A [`SymbolRegistry`] built from the
+//! market list maps any of these, and each market's numeric id, to the
+//! canonical id. The backend resolves every market id it receives with one,
+//! so clients can send whichever spelling they have and will always get the
+//! canonical id back.
+
+use std::collections::HashMap;
+
+use crate::api::ApiMarket;
+
+/// Characters accepted between the base and quote ticker
+pub const SEPARATORS: [char; 4] = ['/', '-', '_', ':'];
+
+/// `BASE/QUOTE` in upper case, if `symbol` is two tickers joined by one separator
+pub fn normalize(symbol: &str) -> Option<String> {
+    let mut parts = symbol.trim().split(SEPARATORS);
+    let (base, quote) = (parts.next()?, parts.next()?);
+    if parts.next().is_some() || base.is_empty() || quote.is_empty() {
+        return None;
+    }
+    Some(format!("{}/{}", base.to_uppercase(), quote.to_uppercase()))
+}
+
+/// `symbol` with every separator dropped
+fn compact(symbol: &str) -> String {
+    symbol.chars().filter(|c| !SEPARATORS.contains(c)).collect()
+}
+
+/// Every accepted spelling of the listed markets
+#[derive(Debug, Clone, Default)]
+pub struct SymbolRegistry {
+    /// Upper-cased `BASE/QUOTE` to market id
+    pairs: HashMap<String, String>,
+    /// Upper-cased `BASEQUOTE`, without separators, to market id; `None` when
+    /// two markets share the spelling
+    compact: HashMap<String, Option<String>>,
+    by_number: HashMap<u32, String>,
+    numbers: HashMap<String, u32>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a market, with its numeric id once it has been assigned one
+    pub fn insert(
+        &mut self,
+        market_id: &str,
+        base_ticker: &str,
+        quote_ticker: &str,
+        numeric_id: Option<u32>,
+    ) {
+        let (base, quote) = (base_ticker.to_uppercase(), quote_ticker.to_uppercase());
+        self.pairs
+            .insert(format!("{}/{}", base, quote), market_id.to_string());
+        self.compact
+            .entry(compact(&format!("{}{}", base, quote)))
+            .and_modify(|existing| {
+                if existing.as_deref() != Some(market_id) {
+                    *existing = None;
+                }
+            })
+            .or_insert_with(|| Some(market_id.to_string()));
+        if let Some(number) = numeric_id {
+            self.by_number.insert(number, market_id.to_string());
+            self.numbers.insert(market_id.to_string(), number);
+        }
+    }
+
+    /// The market id `symbol` names, if it names a listed market
+    ///
+    /// Accepts the market id itself in any case, the tickers joined by any of
+    /// [`SEPARATORS`] or by none, and the market's numeric id in decimal.
+    /// Tickers may contain separators themselves (`BTC-PERP`), so a spelling
+    /// is tried as a whole before it is split. Without a separator the tickers
+    /// can run together ambiguously (`AB` + `CD` and `ABC` + `D`); such
+    /// spellings resolve to nothing.
+    pub fn resolve(&self, symbol: &str) -> Option<&str> {
+        let symbol = symbol.trim();
+        if !symbol.is_empty() && symbol.bytes().all(|b| b.is_ascii_digit()) {
+            return symbol
+                .parse()
+                .ok()
+                .and_then(|number: u32| self.by_number.get(&number))
+                .map(String::as_str);
+        }
+        let upper = symbol.to_uppercase();
+        self.pairs
+            .get(&upper)
+            .or_else(|| self.pairs.get(&normalize(symbol)?))
+            .map(String::as_str)
+            .or_else(|| self.compact.get(&compact(&upper))?.as_deref())
+    }
+
+    /// The market id `symbol` names, or `symbol` unchanged when it names no listed market
+    pub fn canonicalize(&self, symbol: &str) -> String {
+        self.resolve(symbol).unwrap_or(symbol).to_string()
+    }
+
+    /// Numeric id of a market, by its canonical id
+    pub fn numeric_id(&self, market_id: &str) -> Option<u32> {
+        self.numbers.get(market_id).copied()
+    }
+}
+
+impl<'a> FromIterator<&'a ApiMarket> for SymbolRegistry {
+    fn from_iter<I: IntoIterator<Item = &'a ApiMarket>>(markets: I) -> Self {
+        let mut registry = Self::new();
+        for market in markets {
+            registry.insert(
+                &market.id,
+                &market.base_ticker,
+                &market.quote_ticker,
+                market.numeric_id,
+            );
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> SymbolRegistry {
+        let mut registry = SymbolRegistry::new();
+        registry.insert("BTC/USDC", "BTC", "USDC", Some(1));
+        registry.insert("ETH/USDC", "ETH", "USDC", Some(2));
+        registry.insert("ETH/BTC", "ETH", "BTC", None);
+        registry.insert("BTC-PERP/USDC", "BTC-PERP", "USDC", Some(3));
+        registry
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("btc-usdc").as_deref(), Some("BTC/USDC"));
+        assert_eq!(normalize(" BTC_USDC ").as_deref(), Some("BTC/USDC"));
+        assert_eq!(normalize("BTC:USDC").as_deref(), Some("BTC/USDC"));
+        assert_eq!(normalize("BTCUSDC"), None);
+        assert_eq!(normalize("BTC//USDC"), None);
+        assert_eq!(normalize("BTC-USDC-PERP"), None);
+        assert_eq!(normalize("/USDC"), None);
+    }
+
+    #[test]
+    fn test_resolve_aliases() {
+        let registry = registry();
+        for symbol in [
+            "BTC/USDC", "BTC-USDC", "btc_usdc", "BTC:USDC", "BTCUSDC", "btcusdc", "1", " 1 ",
+        ] {
+            assert_eq!(registry.resolve(symbol), Some("BTC/USDC"), "{}", symbol);
+        }
+        assert_eq!(registry.resolve("eth-btc"), Some("ETH/BTC"));
+        assert_eq!(registry.resolve("2"), Some("ETH/USDC"));
+        for symbol in [
+            "btc-perp/usdc",
+            "BTC-PERP-USDC",
+            "btc_perp_usdc",
+            "BTCPERPUSDC",
+            "3",
+        ] {
+            assert_eq!(
+                registry.resolve(symbol),
+                Some("BTC-PERP/USDC"),
+                "{}",
+                symbol
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_unknown() {
+        let registry = registry();
+        for symbol in ["", "4", "99999999999", "SOL/USDC", "SOLUSDC", "BTC/USDC/X"] {
+            assert_eq!(registry.resolve(symbol), None, "{}", symbol);
+        }
+        assert_eq!(registry.canonicalize("SOL-USDC"), "SOL-USDC");
+        assert_eq!(registry.canonicalize("ethusdc"), "ETH/USDC");
+    }
+
+    #[test]
+    fn test_ambiguous_compact_spelling() {
+        let mut registry = SymbolRegistry::new();
+        registry.insert("AB/CD", "AB", "CD", None);
+        registry.insert("ABC/D", "ABC", "D", None);
+
+        assert_eq!(registry.resolve("ABCD"), None);
+        assert_eq!(registry.resolve("ab-cd"), Some("AB/CD"));
+        assert_eq!(registry.resolve("abc-d"), Some("ABC/D"));
+    }
+
+    #[test]
+    fn test_numeric_ids() {
+        let registry = registry();
+        assert_eq!(registry.numeric_id("BTC/USDC"), Some(1));
+        assert_eq!(registry.numeric_id("ETH/BTC"), None);
+        assert_eq!(registry.numeric_id("btc-usdc"), None);
+    }
+}
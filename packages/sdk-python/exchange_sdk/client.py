@@ -23,16 +23,20 @@ from .types import (
 class ExchangeClient:
     """REST API client for the exchange."""
 
-    def __init__(self, base_url: str, timeout: float = 30.0):
+    def __init__(
+        self, base_url: str, timeout: float = 30.0, admin_token: Optional[str] = None
+    ):
         """
         Initialize the exchange client.
 
         Args:
             base_url: Base URL of the exchange API (e.g., "http://localhost:8001")
             timeout: Request timeout in seconds
+            admin_token: The exchange's admin token, needed for the admin endpoints
         """
         self.base_url = base_url.rstrip("/")
         self.timeout = timeout
+        self.admin_token = admin_token
         self._client = httpx.AsyncClient(timeout=timeout)
 
     async def close(self):
@@ -375,7 +379,7 @@ class ExchangeClient:
         response = await self._post_candles(request)
         return [Candle(**c) for c in response["candles"]]
 
-    # ===== Admin Endpoints (Test/Dev Only) =====
+    # ===== Admin Endpoints (Need admin_token) =====
 
     async def admin_create_token(
         self, ticker: str, decimals: int, name: str
@@ -528,7 +532,10 @@ class ExchangeClient:
     async def _post_admin(self, request: dict) -> dict:
         """Make request to /api/admin endpoint."""
         try:
-            response = await self._client.post(f"{self.base_url}/api/admin", json=request)
+            headers = {"Authorization": f"Bearer {self.admin_token}"} if self.admin_token else {}
+            response = await self._client.post(
+                f"{self.base_url}/api/admin", json=request, headers=headers
+            )
             if response.status_code == 200:
                 return response.json()
             error = response.json()
@@ -0,0 +1,449 @@
+//! Admin client
+//!
+//! Administrative operations (creating tokens and markets, funding accounts,
+//! account and market controls, surveillance) live on their own client so a
+//! trading client can never reach them. An [`AdminClient`] can't be built
+//! without an admin credential, which it sends as a bearer token with every
+//! request.
+
+use crate::client::ExchangeClient;
+use crate::error::{SdkError, SdkResult};
+use exchange_protocol::{api::*, domain::*};
+
+/// REST client for the exchange's admin endpoint
+#[derive(Clone)]
+pub struct AdminClient {
+    client: ExchangeClient,
+    admin_token: String,
+}
+
+impl AdminClient {
+    /// Create an admin client for the exchange at `base_url`
+    pub fn new(base_url: impl Into<String>, admin_token: impl Into<String>) -> Self {
+        Self::from_client(ExchangeClient::new(base_url), admin_token)
+    }
+
+    /// Create an admin client with an existing client's HTTP settings
+    pub fn from_client(client: ExchangeClient, admin_token: impl Into<String>) -> Self {
+        Self {
+            client,
+            admin_token: admin_token.into(),
+        }
+    }
+
+    /// Create a token
+    pub async fn create_token(
+        &self,
+        ticker: String,
+        decimals: u8,
+        name: String,
+    ) -> SdkResult<Token> {
+        let request = AdminRequest::CreateToken {
+            ticker,
+            decimals,
+            name,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::CreateToken { token } => Ok(token),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CreateToken".to_string(),
+            )),
+        }
+    }
+
+    /// Create a market
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_market(
+        &self,
+        base_ticker: String,
+        quote_ticker: String,
+        tick_size: u128,
+        lot_size: u128,
+        min_size: u128,
+        maker_fee_bps: i32,
+        taker_fee_bps: i32,
+    ) -> SdkResult<Market> {
+        let request = AdminRequest::CreateMarket {
+            base_ticker,
+            quote_ticker,
+            tick_size: tick_size.to_string(),
+            lot_size: lot_size.to_string(),
+            min_size: min_size.to_string(),
+            maker_fee_bps,
+            taker_fee_bps,
+            price_ladder: None,
+            price_collar_bps: None,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::CreateMarket { market } => market
+                .try_into()
+                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse market: {}", e))),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CreateMarket".to_string(),
+            )),
+        }
+    }
+
+    /// Credit a user's balance directly, returning the new balance
+    pub async fn faucet(
+        &self,
+        user_address: String,
+        token_ticker: String,
+        amount: String,
+    ) -> SdkResult<String> {
+        let request = AdminRequest::Faucet {
+            user_address,
+            token_ticker,
+            amount,
+            signature: "admin".to_string(),
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::Faucet { new_balance, .. } => Ok(new_balance),
+            _ => Err(SdkError::InvalidResponse("Expected Faucet".to_string())),
+        }
+    }
+
+    /// Override a market's price collar; `None` disables it
+    pub async fn set_price_collar(
+        &self,
+        market_id: String,
+        collar_bps: Option<u32>,
+    ) -> SdkResult<()> {
+        let request = AdminRequest::SetPriceCollar {
+            market_id,
+            collar_bps,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetPriceCollar { .. } => Ok(()),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetPriceCollar".to_string(),
+            )),
+        }
+    }
+
+    /// Set the share of a revenue source paid into the insurance fund
+    pub async fn set_fee_route(
+        &self,
+        source: RevenueSource,
+        insurance_bps: u32,
+    ) -> SdkResult<FeeRoute> {
+        let request = AdminRequest::SetFeeRoute {
+            source,
+            insurance_bps,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetFeeRoute { route } => Ok(route),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetFeeRoute".to_string(),
+            )),
+        }
+    }
+
+    /// Set the fees a user pays in a market instead of the market's; a side
+    /// left as `None` keeps the market's fee, and leaving out both clears the
+    /// override
+    pub async fn set_fee_override(
+        &self,
+        user_address: String,
+        market_id: String,
+        maker_fee_bps: Option<i32>,
+        taker_fee_bps: Option<i32>,
+    ) -> SdkResult<Option<FeeOverride>> {
+        let request = AdminRequest::SetFeeOverride {
+            user_address,
+            market_id,
+            maker_fee_bps,
+            taker_fee_bps,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetFeeOverride { fee_override, .. } => Ok(fee_override),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetFeeOverride".to_string(),
+            )),
+        }
+    }
+
+    /// Get a system account's balances and recent ledger entries, newest first
+    pub async fn system_ledger(
+        &self,
+        account: SystemAccount,
+        token_ticker: Option<String>,
+        limit: Option<u32>,
+    ) -> SdkResult<(Vec<ApiBalance>, Vec<ApiLedgerEntry>)> {
+        let request = AdminRequest::SystemLedger {
+            account,
+            token_ticker,
+            limit,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SystemLedger {
+                balances, entries, ..
+            } => Ok((balances, entries)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SystemLedger".to_string(),
+            )),
+        }
+    }
+
+    /// Freeze, ban or reinstate a user; returns how many orders were cancelled
+    pub async fn set_user_status(
+        &self,
+        user_address: String,
+        status: UserStatus,
+        cancel_orders: bool,
+    ) -> SdkResult<usize> {
+        let request = AdminRequest::SetUserStatus {
+            user_address,
+            status,
+            cancel_orders,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetUserStatus {
+                cancelled_orders, ..
+            } => Ok(cancelled_orders),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetUserStatus".to_string(),
+            )),
+        }
+    }
+
+    /// Group existing markets into a prediction event, as (outcome name, market id)
+    pub async fn create_event(
+        &self,
+        event_id: String,
+        title: String,
+        outcomes: Vec<(String, String)>,
+    ) -> SdkResult<ApiPredictionEvent> {
+        let request = AdminRequest::CreateEvent {
+            event_id,
+            title,
+            outcomes: outcomes
+                .into_iter()
+                .map(|(name, market_id)| ApiEventOutcomeSpec { name, market_id })
+                .collect(),
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::CreateEvent { event } => Ok(event),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CreateEvent".to_string(),
+            )),
+        }
+    }
+
+    /// Resolve a prediction event to its winning outcome, delisting its markets
+    /// and paying the winning token's holders
+    pub async fn resolve_event(
+        &self,
+        event_id: String,
+        winning_outcome: String,
+    ) -> SdkResult<ApiPredictionEvent> {
+        let request = AdminRequest::ResolveEvent {
+            event_id,
+            winning_outcome,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::ResolveEvent { event, .. } => Ok(event),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected ResolveEvent".to_string(),
+            )),
+        }
+    }
+
+    /// Let a user answer requests for quote
+    pub async fn register_rfq_maker(&self, user_address: String) -> SdkResult<()> {
+        let request = AdminRequest::RegisterRfqMaker { user_address };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::RegisterRfqMaker { .. } => Ok(()),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RegisterRfqMaker".to_string(),
+            )),
+        }
+    }
+
+    /// Stop a user answering requests for quote; returns how many live quotes were withdrawn
+    pub async fn remove_rfq_maker(&self, user_address: String) -> SdkResult<u64> {
+        let request = AdminRequest::RemoveRfqMaker { user_address };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::RemoveRfqMaker {
+                withdrawn_quotes, ..
+            } => Ok(withdrawn_quotes),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RemoveRfqMaker".to_string(),
+            )),
+        }
+    }
+
+    /// Let a user read the per-order book
+    pub async fn approve_market_maker(&self, user_address: String) -> SdkResult<()> {
+        let request = AdminRequest::ApproveMarketMaker { user_address };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::ApproveMarketMaker { .. } => Ok(()),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected ApproveMarketMaker".to_string(),
+            )),
+        }
+    }
+
+    /// Take back a user's access to the per-order book
+    pub async fn revoke_market_maker(&self, user_address: String) -> SdkResult<()> {
+        let request = AdminRequest::RevokeMarketMaker { user_address };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::RevokeMarketMaker { .. } => Ok(()),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RevokeMarketMaker".to_string(),
+            )),
+        }
+    }
+
+    /// Record who ultimately owns an account for wash trade surveillance;
+    /// `None` clears it
+    pub async fn set_beneficial_owner(
+        &self,
+        user_address: String,
+        owner: Option<String>,
+    ) -> SdkResult<()> {
+        let request = AdminRequest::SetBeneficialOwner {
+            user_address,
+            owner,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetBeneficialOwner { .. } => Ok(()),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetBeneficialOwner".to_string(),
+            )),
+        }
+    }
+
+    /// Get trade surveillance alerts, newest first
+    pub async fn surveillance_alerts(
+        &self,
+        kind: Option<SurveillanceKind>,
+        market_id: Option<String>,
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiSurveillanceAlert>> {
+        let request = AdminRequest::SurveillanceAlerts {
+            kind,
+            market_id,
+            limit,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SurveillanceAlerts { alerts } => Ok(alerts),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SurveillanceAlerts".to_string(),
+            )),
+        }
+    }
+
+    /// Halt, delist or reactivate a market; returns how many orders were cancelled
+    pub async fn set_market_status(
+        &self,
+        market_id: String,
+        status: MarketStatus,
+    ) -> SdkResult<usize> {
+        let request = AdminRequest::SetMarketStatus { market_id, status };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetMarketStatus {
+                cancelled_orders, ..
+            } => Ok(cancelled_orders),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetMarketStatus".to_string(),
+            )),
+        }
+    }
+
+    /// Set a user's limits in a market; `None` leaves that limit unset
+    pub async fn set_user_limits(
+        &self,
+        user_address: String,
+        market_id: String,
+        max_position: Option<u128>,
+        max_open_notional: Option<u128>,
+    ) -> SdkResult<exchange_protocol::api::ApiUserLimits> {
+        let request = AdminRequest::SetUserLimits {
+            user_address,
+            market_id,
+            max_position: max_position.map(|v| v.to_string()),
+            max_open_notional: max_open_notional.map(|v| v.to_string()),
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetUserLimits { limits } => Ok(limits),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetUserLimits".to_string(),
+            )),
+        }
+    }
+
+    /// Set who referred a user and their share of the user's taker fees
+    pub async fn set_referral(
+        &self,
+        user_address: String,
+        referrer_address: String,
+        share_bps: u32,
+    ) -> SdkResult<Referral> {
+        let request = AdminRequest::SetReferral {
+            user_address,
+            referrer_address,
+            share_bps,
+        };
+        let response = self.post(request).await?;
+
+        match response {
+            AdminResponse::SetReferral { referral } => Ok(referral),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetReferral".to_string(),
+            )),
+        }
+    }
+
+    async fn post(&self, request: AdminRequest) -> SdkResult<AdminResponse> {
+        self.client.post_admin(request, &self.admin_token).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_client_construction() {
+        let admin = AdminClient::new("http://localhost:8001", "secret");
+        assert_eq!(admin.client.base_url(), "http://localhost:8001");
+        assert_eq!(admin.admin_token, "secret");
+    }
+}
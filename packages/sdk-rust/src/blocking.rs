@@ -0,0 +1,580 @@
+//! Blocking REST client
+//!
+//! A synchronous variant of [`crate::ExchangeClient`] for scripts and simple
+//! tooling that don't want to run a tokio runtime. Each call runs the async
+//! client's request to completion on a runtime the blocking client owns, so
+//! both build requests and decode responses the same way.
+//!
+//! Like `reqwest::blocking`, it must not be used from within an async runtime.
+//!
+//! Enabled with the `blocking` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use exchange_sdk::blocking::ExchangeClient;
+//!
+//! let client = ExchangeClient::new("http://localhost:8001");
+//! let markets = client.get_markets().unwrap();
+//! println!("{} markets", markets.len());
+//! ```
+
+use crate::client::FillsExport;
+use crate::error::SdkResult;
+use chrono::{DateTime, Utc};
+use exchange_protocol::symbol::SymbolRegistry;
+use exchange_protocol::{api::*, domain::*};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Blocking REST API client for the exchange
+#[derive(Clone)]
+pub struct ExchangeClient {
+    inner: crate::ExchangeClient,
+    runtime: Arc<Runtime>,
+}
+
+impl ExchangeClient {
+    /// Create a new blocking client with the given base URL
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::from_async(crate::ExchangeClient::new(base_url))
+    }
+
+    /// Wrap an async client, keeping its HTTP settings, API key and metadata cache
+    pub fn from_async(inner: crate::ExchangeClient) -> Self {
+        // One worker keeps pooled connections alive between blocking calls
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .expect("Failed to start the blocking client's runtime");
+        Self {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// Return a copy of this client that authenticates every request with `api_key`
+    pub fn with_api_key(&self, api_key: impl Into<String>) -> Self {
+        Self {
+            inner: self.inner.with_api_key(api_key),
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+/// Blocking forms of async client methods, with the same signatures minus the
+/// `async`
+macro_rules! blocking_methods {
+    ($($(#[$meta:meta])* fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;)*) => {
+        impl ExchangeClient {
+            $(
+                $(#[$meta])*
+                pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                    self.runtime.block_on(self.inner.$name($($arg),*))
+                }
+            )*
+        }
+    };
+}
+
+blocking_methods! {
+    /// Health check
+    fn health(&self) -> SdkResult<String>;
+
+    // ===== Info Endpoints =====
+
+    /// Get token details
+    fn get_token(&self, ticker: &str) -> SdkResult<Token>;
+
+    /// Get market details
+    fn get_market(&self, market_id: &str) -> SdkResult<Market>;
+
+    /// Get all markets
+    fn get_markets(&self) -> SdkResult<Vec<Market>>;
+
+    /// Get every market's aliases and numeric id
+    fn get_symbols(&self) -> SdkResult<SymbolRegistry>;
+
+    /// Get all tokens
+    fn get_tokens(&self) -> SdkResult<Vec<Token>>;
+
+    // ===== User Endpoints =====
+
+    /// Get user orders
+    fn get_orders(&self, user_address: &str, market_id: Option<String>) -> SdkResult<Vec<Order>>;
+
+    /// Get user balances
+    fn get_balances(&self, user_address: &str) -> SdkResult<Vec<Balance>>;
+
+    /// Get user trades
+    fn get_trades(&self, user_address: &str, market_id: Option<String>) -> SdkResult<Vec<Trade>>;
+
+    /// Get a user's resting orders per market against the open-order cap
+    fn get_open_order_usage(
+        &self,
+        user_address: &str,
+        market_id: Option<String>,
+    ) -> SdkResult<Vec<ApiOpenOrderUsage>>;
+
+    /// Get how much rests ahead of one of a user's orders at its price
+    fn get_queue_position(&self, user_address: &str, order_id: &str) -> SdkResult<ApiQueuePosition>;
+
+    /// Get a user's profile, open orders, 30-day volume, fees paid and balances in one call
+    fn get_user_summary(&self, user_address: &str) -> SdkResult<ApiUserSummary>;
+
+    /// Get the fees a user has earned as a referrer, per token
+    fn get_referral_earnings(&self, user_address: &str) -> SdkResult<Vec<ApiReferralEarnings>>;
+
+    /// Set the name a user appears under on the leaderboard; `None` opts out
+    fn set_display_name(
+        &self,
+        user_address: String,
+        display_name: Option<String>,
+        signature: String,
+    ) -> SdkResult<Option<String>>;
+
+    /// Register a URL to receive the user's fills and order updates
+    ///
+    /// Returns the webhook and the secret its deliveries are signed with,
+    /// which is not shown again.
+    fn register_webhook(
+        &self,
+        user_address: String,
+        url: String,
+        signature: String,
+    ) -> SdkResult<(ApiWebhook, String)>;
+
+    /// List a user's webhooks
+    fn list_webhooks(&self, user_address: String) -> SdkResult<Vec<ApiWebhook>>;
+
+    /// Remove one of a user's webhooks
+    fn delete_webhook(
+        &self,
+        user_address: String,
+        webhook_id: String,
+        signature: String,
+    ) -> SdkResult<()>;
+
+    /// Deliveries to a user's webhooks that failed every retry, newest first
+    fn get_webhook_dead_letters(
+        &self,
+        user_address: String,
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiWebhookDeadLetter>>;
+
+    /// On-chain deposits credited to a user, newest first
+    fn get_deposits(&self, user_address: String, limit: Option<u32>) -> SdkResult<Vec<ApiDeposit>>;
+
+    /// Request a withdrawal of `amount` (in atoms) to an on-chain address
+    fn withdraw(
+        &self,
+        user_address: String,
+        token_ticker: String,
+        amount: u128,
+        destination: String,
+        signature: String,
+    ) -> SdkResult<ApiWithdrawal>;
+
+    /// Cancel a withdrawal that is still pending
+    fn cancel_withdrawal(
+        &self,
+        user_address: String,
+        withdrawal_id: String,
+        signature: String,
+    ) -> SdkResult<ApiWithdrawal>;
+
+    /// Margin a user's perpetual positions on their own or together; fails while any are open
+    fn set_margin_mode(
+        &self,
+        user_address: String,
+        mode: MarginMode,
+        signature: String,
+    ) -> SdkResult<MarginMode>;
+
+    /// A user's withdrawals, newest first
+    fn get_withdrawals(
+        &self,
+        user_address: String,
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiWithdrawal>>;
+
+    /// Open a named sub-account with its own balances and orders
+    fn create_sub_account(
+        &self,
+        user_address: String,
+        name: String,
+        signature: String,
+    ) -> SdkResult<ApiSubAccount>;
+
+    /// A user's sub-accounts, oldest first
+    fn get_sub_accounts(&self, user_address: String) -> SdkResult<Vec<ApiSubAccount>>;
+
+    /// Move free balance between a user's own accounts; returns the transfer
+    /// and both accounts' balances after it
+    fn transfer_between_accounts(
+        &self,
+        user_address: String,
+        from_address: String,
+        to_address: String,
+        token_ticker: String,
+        amount: String,
+        signature: String,
+    ) -> SdkResult<(ApiAccountTransfer, Vec<ApiBalance>)>;
+
+    /// Balances of a user and each of its sub-accounts, with totals per token
+    fn get_aggregate_balances(
+        &self,
+        user_address: String,
+    ) -> SdkResult<(Vec<ApiBalance>, Vec<ApiAggregateBalance>)>;
+
+    /// Issue an API key limited to `scopes` and, if non-empty, to requests
+    /// from `ip_allowlist`; returns the key's details and the key itself,
+    /// which is shown only this once
+    fn create_api_key(
+        &self,
+        user_address: String,
+        label: String,
+        scopes: Vec<ApiKeyScope>,
+        ip_allowlist: Vec<String>,
+        signature: String,
+    ) -> SdkResult<(ApiKeyDetails, String)>;
+
+    /// A user's API keys that haven't been revoked
+    fn get_api_keys(&self, user_address: String) -> SdkResult<Vec<ApiKeyDetails>>;
+
+    /// Replace an API key's secret; returns its details and the new key
+    fn rotate_api_key(
+        &self,
+        user_address: String,
+        key_id: String,
+        signature: String,
+    ) -> SdkResult<(ApiKeyDetails, String)>;
+
+    /// Revoke an API key; requests made with it are rejected from then on
+    fn revoke_api_key(
+        &self,
+        user_address: String,
+        key_id: String,
+        signature: String,
+    ) -> SdkResult<String>;
+
+    // ===== Trade Endpoints =====
+
+    /// Place an order
+    #[allow(clippy::too_many_arguments)]
+    fn place_order(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder>;
+
+    /// Place an order that rests until cancelled (`Gtc`) or cancels whatever
+    /// doesn't fill on arrival (`Ioc`)
+    #[allow(clippy::too_many_arguments)]
+    fn place_order_with_time_in_force(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        time_in_force: TimeInForce,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder>;
+
+    /// Place a limit order that rests until `expires_at`, when the exchange
+    /// cancels whatever is still open
+    #[allow(clippy::too_many_arguments)]
+    fn place_order_with_expiry(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        price: String,
+        size: String,
+        expires_at: DateTime<Utc>,
+        signature: String,
+    ) -> SdkResult<PlacedOrder>;
+
+    /// Place a stop order, which waits until a trade reaches `trigger_price`
+    /// and then executes as a market (`StopMarket`) or limit (`StopLimit`) order
+    #[allow(clippy::too_many_arguments)]
+    fn place_stop_order(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        trigger_price: String,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder>;
+
+    /// Place a stop order that triggers off the market's last trade
+    /// (`LastTrade`) or its external index price (`Index`)
+    #[allow(clippy::too_many_arguments)]
+    fn place_stop_order_with_trigger_source(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        trigger_price: String,
+        trigger_source: TriggerSource,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder>;
+
+    /// Place two linked orders for the same side and size, a take-profit
+    /// limit and a stop; the first fill on either, or the stop triggering,
+    /// cancels the other
+    #[allow(clippy::too_many_arguments)]
+    fn place_oco_order(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        size: String,
+        first: OcoLeg,
+        second: OcoLeg,
+        signature: String,
+    ) -> SdkResult<PlacedOco>;
+
+    /// Cancel an order
+    fn cancel_order(
+        &self,
+        user_address: String,
+        order_id: String,
+        signature: String,
+    ) -> SdkResult<OrderCancelled>;
+
+    /// Cancel all orders for a user, optionally filtered by market
+    fn cancel_all_orders(
+        &self,
+        user_address: String,
+        market_id: Option<String>,
+        signature: String,
+    ) -> SdkResult<OrdersCancelled>;
+
+    // ===== Drip/Faucet Endpoint =====
+
+    /// Request testnet tokens from faucet
+    fn faucet(
+        &self,
+        user_address: String,
+        token_ticker: String,
+        amount: String,
+        signature: String,
+    ) -> SdkResult<(String, String, String, String)>;
+
+    // ===== Candles Endpoints =====
+
+    /// Get OHLCV candles for a market
+    fn get_candles(
+        &self,
+        market_id: &str,
+        interval: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<Vec<ApiCandle>>;
+
+    /// Get rolling 24h volume, trade count and OHLC for a market
+    fn get_market_stats(&self, market_id: &str) -> SdkResult<ApiMarketStats>;
+
+    /// Get a market's best bid and ask, up to a second behind the live book
+    fn get_top_of_book(&self, market_id: &str) -> SdkResult<ApiTopOfBook>;
+
+    /// Get every resting order of a market (approved market makers only, once a second)
+    fn get_l3_orderbook(
+        &self,
+        market_id: &str,
+        user_address: &str,
+        signature: &str,
+    ) -> SdkResult<ApiL3Book>;
+
+    /// Get a market's volume-weighted average price between two Unix timestamps
+    fn get_vwap(&self, market_id: &str, from: i64, to: i64) -> SdkResult<Option<u128>>;
+
+    /// Get a market's time-weighted average price between two Unix timestamps
+    fn get_twap(&self, market_id: &str, from: i64, to: i64) -> SdkResult<Option<u128>>;
+
+    /// Get a market's sampled spread, top-of-book depth and imbalance between two
+    /// Unix timestamps, for checking liquidity obligations
+    fn get_depth_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<DepthHistoryResponse>;
+
+    /// Get a market's external index prices between two Unix timestamps
+    fn get_index_price_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<IndexPriceHistoryResponse>;
+
+    /// Get a perpetual market's open interest snapshots between two Unix timestamps
+    fn get_open_interest_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<OpenInterestHistoryResponse>;
+
+    /// Get a perpetual market's funding rates between two Unix timestamps
+    fn get_funding_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<FundingHistoryResponse>;
+
+    /// Get a perpetual market's funding terms and latest mark price
+    fn get_perpetual_market(&self, market_id: &str) -> SdkResult<ApiPerpetualMarket>;
+
+    /// Get a perpetual market's positions within `within_bps` of their maintenance
+    /// margin (the backend's default when `None`), most at risk first
+    fn get_market_risk(
+        &self,
+        market_id: &str,
+        within_bps: Option<u32>,
+    ) -> SdkResult<MarketRiskResponse>;
+
+    /// Get a market's per-trader maker ratios and taker flow imbalance between two
+    /// Unix timestamps, bucketed by `interval` (1m, 5m, 15m, 1h or 1d)
+    fn get_flow_analytics(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+        interval: &str,
+    ) -> SdkResult<FlowAnalyticsResponse>;
+
+    /// Export a user's fills between two Unix timestamps as CSV or Parquet
+    ///
+    /// Small exports come back as the file itself; larger ones start a job to
+    /// poll with `get_export_job` and fetch with `download_export` once ready.
+    fn export_user_fills(
+        &self,
+        user_address: &str,
+        format: ExportFormat,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<FillsExport>;
+
+    /// Get the status of a background fills export
+    fn get_export_job(&self, job_id: &str) -> SdkResult<ApiExportJob>;
+
+    /// Download the file of a finished fills export
+    fn download_export(&self, job_id: &str) -> SdkResult<Vec<u8>>;
+
+    /// Get a user's realized PnL, position and average entry price per market
+    fn get_user_pnl(
+        &self,
+        user_address: &str,
+        method: CostBasisMethod,
+    ) -> SdkResult<UserPnlResponse>;
+
+    /// Get a user's positions in perpetual markets, with unrealized PnL at the mark price
+    fn get_positions(&self, user_address: &str) -> SdkResult<PositionsResponse>;
+
+    /// Get a user's margin mode and how close their positions are to liquidation
+    fn get_margin_account(&self, user_address: &str) -> SdkResult<MarginAccountResponse>;
+
+    /// Get the top traders for a period by volume or realized PnL
+    fn get_leaderboard(
+        &self,
+        period: LeaderboardPeriod,
+        metric: LeaderboardMetric,
+        hide_addresses: bool,
+    ) -> SdkResult<Vec<ApiLeaderboardEntry>>;
+
+    /// List prediction events and their outcome markets, newest first,
+    /// optionally only those with `status`
+    fn get_events(&self, status: Option<EventStatus>) -> SdkResult<Vec<ApiPredictionEvent>>;
+
+    /// Get a prediction event and its outcome markets
+    fn get_event(&self, event_id: &str) -> SdkResult<ApiPredictionEvent>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SdkError;
+    use exchange_protocol::error_code::ErrorCode;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Answer one request with `status` and a JSON `body`; returns the base URL
+    /// and a receiver for the request head the server saw
+    fn serve_once(status: &'static str, body: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0; 1024];
+            while !head.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            tx.send(String::from_utf8_lossy(&head).to_lowercase())
+                .unwrap();
+        });
+        (base_url, rx)
+    }
+
+    #[test]
+    fn test_requests_and_decodes_like_the_async_client() {
+        let (base_url, head) = serve_once("200 OK", r#"{"events":[]}"#);
+        let client = ExchangeClient::new(base_url).with_api_key("ak_test");
+
+        assert!(client.get_events(None).unwrap().is_empty());
+        let head = head.recv().unwrap();
+        assert!(head.starts_with("get /api/events http/1.1"), "{}", head);
+        assert!(head.contains("x-api-key: ak_test"), "{}", head);
+    }
+
+    #[test]
+    fn test_error_bodies_become_api_errors() {
+        let (base_url, _) = serve_once(
+            "404 Not Found",
+            r#"{"error":"Market not found","code":"MARKET_NOT_FOUND"}"#,
+        );
+        let client = ExchangeClient::new(base_url);
+
+        match client.get_market_stats("BTC/USDC") {
+            Err(SdkError::ApiError {
+                status: 404,
+                code: Some(ErrorCode::MarketNotFound),
+                ..
+            }) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
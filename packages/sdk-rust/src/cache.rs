@@ -3,9 +3,14 @@
 //! Provides in-memory caching of market and token data to avoid
 //! repeated REST API calls.
 
-use backend::models::{api::ApiMarket, domain::Token};
+use exchange_protocol::{
+    api::ApiMarket,
+    domain::{Market, Token},
+    symbol::SymbolRegistry,
+};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use crate::logger::Logger;
 
@@ -132,6 +137,104 @@ impl CacheService {
     }
 }
 
+/// Market and token metadata cache with a time-to-live
+///
+/// Used by [`crate::ExchangeClient`] so that order placement helpers don't
+/// refetch static market config (tick, lot, decimals) on every call.
+/// Entries older than the TTL are treated as missing. Markets can be looked
+/// up by any alias the exchange accepts, such as `BTC-USDC`.
+pub struct MetadataCache {
+    ttl: Duration,
+    markets: RwLock<HashMap<String, (Instant, Market)>>,
+    symbols: RwLock<SymbolRegistry>,
+    tokens: RwLock<HashMap<String, (Instant, Token)>>,
+}
+
+impl MetadataCache {
+    /// Create an empty cache whose entries expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            markets: RwLock::new(HashMap::new()),
+            symbols: RwLock::new(SymbolRegistry::new()),
+            tokens: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Configured time-to-live
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Get a market if cached and not expired
+    pub fn get_market(&self, market_id: &str) -> Option<Market> {
+        let market_id = self.symbols.read().unwrap().canonicalize(market_id);
+        let cache = self.markets.read().unwrap();
+        cache
+            .get(&market_id)
+            .filter(|(at, _)| at.elapsed() < self.ttl)
+            .map(|(_, market)| market.clone())
+    }
+
+    /// Insert or refresh a market
+    pub fn insert_market(&self, market: Market) {
+        self.symbols.write().unwrap().insert(
+            &market.id,
+            &market.base_ticker,
+            &market.quote_ticker,
+            None,
+        );
+        self.markets
+            .write()
+            .unwrap()
+            .insert(market.id.clone(), (Instant::now(), market));
+    }
+
+    /// Insert or refresh many markets at once
+    pub fn insert_markets(&self, markets: impl IntoIterator<Item = Market>) {
+        let now = Instant::now();
+        let mut cache = self.markets.write().unwrap();
+        let mut symbols = self.symbols.write().unwrap();
+        for market in markets {
+            symbols.insert(&market.id, &market.base_ticker, &market.quote_ticker, None);
+            cache.insert(market.id.clone(), (now, market));
+        }
+    }
+
+    /// Get a token if cached and not expired
+    pub fn get_token(&self, ticker: &str) -> Option<Token> {
+        let cache = self.tokens.read().unwrap();
+        cache
+            .get(ticker)
+            .filter(|(at, _)| at.elapsed() < self.ttl)
+            .map(|(_, token)| token.clone())
+    }
+
+    /// Insert or refresh a token
+    pub fn insert_token(&self, token: Token) {
+        self.tokens
+            .write()
+            .unwrap()
+            .insert(token.ticker.clone(), (Instant::now(), token));
+    }
+
+    /// Insert or refresh many tokens at once
+    pub fn insert_tokens(&self, tokens: impl IntoIterator<Item = Token>) {
+        let now = Instant::now();
+        let mut cache = self.tokens.write().unwrap();
+        for token in tokens {
+            cache.insert(token.ticker.clone(), (now, token));
+        }
+    }
+
+    /// Drop all cached entries
+    pub fn clear(&self) {
+        self.markets.write().unwrap().clear();
+        *self.symbols.write().unwrap() = SymbolRegistry::new();
+        self.tokens.write().unwrap().clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,9 +258,54 @@ mod tests {
             min_size: "1000000".to_string(),
             maker_fee_bps: 10,
             taker_fee_bps: 20,
+            numeric_id: None,
         }
     }
 
+    fn create_domain_market(id: &str) -> Market {
+        create_test_market(id, "BTC", "USDC").try_into().unwrap()
+    }
+
+    #[test]
+    fn test_metadata_cache_hit() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        assert!(cache.get_market("BTC/USDC").is_none());
+
+        cache.insert_market(create_domain_market("BTC/USDC"));
+        cache.insert_tokens(vec![create_test_token("BTC"), create_test_token("USDC")]);
+
+        assert_eq!(cache.get_market("BTC/USDC").unwrap().lot_size, 1000000);
+        assert_eq!(cache.get_token("USDC").unwrap().decimals, 6);
+
+        cache.clear();
+        assert!(cache.get_market("BTC/USDC").is_none());
+        assert!(cache.get_token("USDC").is_none());
+    }
+
+    #[test]
+    fn test_metadata_cache_aliases() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        cache.insert_markets(vec![create_domain_market("BTC/USDC")]);
+
+        for alias in ["BTC-USDC", "btc_usdc", "BTCUSDC"] {
+            assert_eq!(cache.get_market(alias).unwrap().id, "BTC/USDC");
+        }
+        assert!(cache.get_market("ETH-USDC").is_none());
+
+        cache.clear();
+        assert!(cache.get_market("BTC-USDC").is_none());
+    }
+
+    #[test]
+    fn test_metadata_cache_expiry() {
+        let cache = MetadataCache::new(Duration::ZERO);
+        cache.insert_market(create_domain_market("BTC/USDC"));
+        cache.insert_token(create_test_token("BTC"));
+
+        assert!(cache.get_market("BTC/USDC").is_none());
+        assert!(cache.get_token("BTC").is_none());
+    }
+
     #[test]
     fn test_cache_tokens() {
         let cache = CacheService::new(Arc::new(NoopLogger));
@@ -1,15 +1,38 @@
+use crate::cache::MetadataCache;
 use crate::error::{SdkError, SdkResult};
-use backend::models::{api::*, domain::*};
-use reqwest::Client;
-use rust_decimal::prelude::ToPrimitive;
-use rust_decimal::Decimal;
-use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use exchange_protocol::convert::{check_tick_aligned, decimal_to_atoms};
+use exchange_protocol::symbol::SymbolRegistry;
+use exchange_protocol::{api::*, domain::*};
+use reqwest::{Client, Proxy, RequestBuilder};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Path prefix the backend mounts its REST routes under
+const DEFAULT_API_PATH: &str = "/api";
+
+/// Header the backend reads API keys from
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+
+/// Result of requesting a fills export
+#[derive(Debug, Clone)]
+pub enum FillsExport {
+    /// The exported file, small enough to be returned directly
+    File(Vec<u8>),
+    /// A background export to poll with `get_export_job` and then download
+    Job(ApiExportJob),
+}
 
 /// REST API client for the exchange
 #[derive(Clone)]
 pub struct ExchangeClient {
     base_url: String,
+    api_path: String,
     client: Client,
+    timeout: Option<Duration>,
+    metadata: Option<Arc<MetadataCache>>,
+    api_key: Option<String>,
 }
 
 impl ExchangeClient {
@@ -17,14 +40,86 @@ impl ExchangeClient {
     pub fn new(base_url: impl Into<String>) -> Self {
         Self {
             base_url: base_url.into(),
+            api_path: DEFAULT_API_PATH.to_string(),
             client: Client::new(),
+            timeout: None,
+            metadata: None,
+            api_key: None,
+        }
+    }
+
+    /// Create a client that reuses an existing `reqwest::Client`
+    pub fn with_http_client(base_url: impl Into<String>, client: Client) -> Self {
+        Self {
+            client,
+            ..Self::new(base_url)
+        }
+    }
+
+    /// Start building a client with custom HTTP settings
+    pub fn builder(base_url: impl Into<String>) -> ExchangeClientBuilder {
+        ExchangeClientBuilder::new(base_url)
+    }
+
+    /// The exchange URL this client was created with
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Return a copy of this client that applies `timeout` to every request
+    ///
+    /// The underlying connection pool is shared with the original client.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client that authenticates every request with `api_key`
+    ///
+    /// The key's scopes and IP allowlist then decide what the client may do.
+    pub fn with_api_key(&self, api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: Some(api_key.into()),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client that caches market and token metadata for `ttl`
+    ///
+    /// Affects `get_market`, `get_markets`, `get_token`, `get_tokens` and the
+    /// order helpers that look up lot size and decimals.
+    pub fn with_metadata_cache(&self, ttl: Duration) -> Self {
+        Self {
+            metadata: Some(Arc::new(MetadataCache::new(ttl))),
+            ..self.clone()
+        }
+    }
+
+    /// Fetch all markets and tokens and store them in the metadata cache
+    ///
+    /// Does nothing when the metadata cache is disabled.
+    pub async fn prefetch_metadata(&self) -> SdkResult<()> {
+        if self.metadata.is_none() {
+            return Ok(());
+        }
+        self.get_markets().await?;
+        self.get_tokens().await?;
+        Ok(())
+    }
+
+    /// Drop all cached market and token metadata
+    pub fn invalidate_metadata(&self) {
+        if let Some(cache) = &self.metadata {
+            cache.clear();
         }
     }
 
     /// Health check
     pub async fn health(&self) -> SdkResult<String> {
-        let url = format!("{}/api/health", self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let builder = self.client.get(self.url("health"));
+        let response = self.request(builder).send().await?;
 
         if response.status().is_success() {
             Ok(response.text().await?)
@@ -32,6 +127,8 @@ impl ExchangeClient {
             Err(SdkError::ApiError {
                 status: response.status().as_u16(),
                 message: response.text().await.unwrap_or_default(),
+                code: None,
+                reason: None,
             })
         }
     }
@@ -40,13 +137,22 @@ impl ExchangeClient {
 
     /// Get token details
     pub async fn get_token(&self, ticker: &str) -> SdkResult<Token> {
+        if let Some(token) = self.metadata.as_ref().and_then(|c| c.get_token(ticker)) {
+            return Ok(token);
+        }
+
         let request = InfoRequest::TokenDetails {
             ticker: ticker.to_string(),
         };
         let response = self.post_info(request).await?;
 
         match response {
-            InfoResponse::TokenDetails { token } => Ok(token),
+            InfoResponse::TokenDetails { token } => {
+                if let Some(cache) = &self.metadata {
+                    cache.insert_token(token.clone());
+                }
+                Ok(token)
+            }
             _ => Err(SdkError::InvalidResponse(
                 "Expected TokenDetails".to_string(),
             )),
@@ -55,15 +161,25 @@ impl ExchangeClient {
 
     /// Get market details
     pub async fn get_market(&self, market_id: &str) -> SdkResult<Market> {
+        if let Some(market) = self.metadata.as_ref().and_then(|c| c.get_market(market_id)) {
+            return Ok(market);
+        }
+
         let request = InfoRequest::MarketDetails {
             market_id: market_id.to_string(),
         };
         let response = self.post_info(request).await?;
 
         match response {
-            InfoResponse::MarketDetails { market } => market
-                .try_into()
-                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse market: {}", e))),
+            InfoResponse::MarketDetails { market } => {
+                let market: Market = market.try_into().map_err(|e| {
+                    SdkError::InvalidResponse(format!("Failed to parse market: {}", e))
+                })?;
+                if let Some(cache) = &self.metadata {
+                    cache.insert_market(market.clone());
+                }
+                Ok(market)
+            }
             _ => Err(SdkError::InvalidResponse(
                 "Expected MarketDetails".to_string(),
             )),
@@ -76,11 +192,28 @@ impl ExchangeClient {
         let response = self.post_info(request).await?;
 
         match response {
-            InfoResponse::AllMarkets { markets } => markets
-                .into_iter()
-                .map(|m| m.try_into())
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse markets: {}", e))),
+            InfoResponse::AllMarkets { markets } => {
+                let markets = markets
+                    .into_iter()
+                    .map(|m| m.try_into())
+                    .collect::<Result<Vec<Market>, _>>()
+                    .map_err(|e| {
+                        SdkError::InvalidResponse(format!("Failed to parse markets: {}", e))
+                    })?;
+                if let Some(cache) = &self.metadata {
+                    cache.insert_markets(markets.iter().cloned());
+                }
+                Ok(markets)
+            }
+            _ => Err(SdkError::InvalidResponse("Expected AllMarkets".to_string())),
+        }
+    }
+
+    /// Get every market's aliases and numeric id, for matching ids from
+    /// other sources to the ones the exchange sends
+    pub async fn get_symbols(&self) -> SdkResult<SymbolRegistry> {
+        match self.post_info(InfoRequest::AllMarkets).await? {
+            InfoResponse::AllMarkets { markets } => Ok(markets.iter().collect()),
             _ => Err(SdkError::InvalidResponse("Expected AllMarkets".to_string())),
         }
     }
@@ -91,7 +224,12 @@ impl ExchangeClient {
         let response = self.post_info(request).await?;
 
         match response {
-            InfoResponse::AllTokens { tokens } => Ok(tokens),
+            InfoResponse::AllTokens { tokens } => {
+                if let Some(cache) = &self.metadata {
+                    cache.insert_tokens(tokens.iter().cloned());
+                }
+                Ok(tokens)
+            }
             _ => Err(SdkError::InvalidResponse("Expected AllTokens".to_string())),
         }
     }
@@ -153,7 +291,7 @@ impl ExchangeClient {
         let response = self.post_user(request).await?;
 
         match response {
-            UserResponse::Trades { trades } => trades
+            UserResponse::Trades { trades, .. } => trades
                 .into_iter()
                 .map(|t| t.try_into())
                 .collect::<Result<Vec<_>, _>>()
@@ -162,258 +300,975 @@ impl ExchangeClient {
         }
     }
 
-    // ===== Trade Endpoints =====
+    /// Get a user's resting orders per market against the open-order cap
+    pub async fn get_open_order_usage(
+        &self,
+        user_address: &str,
+        market_id: Option<String>,
+    ) -> SdkResult<Vec<ApiOpenOrderUsage>> {
+        let request = UserRequest::OpenOrderUsage {
+            user_address: user_address.to_string(),
+            market_id,
+        };
+        let response = self.post_user(request).await?;
 
-    /// Round a size to the nearest multiple of lot_size (rounds down)
-    pub fn round_size_to_lot(size: u128, lot_size: u128) -> u128 {
-        if lot_size == 0 {
-            return size;
+        match response {
+            UserResponse::OpenOrderUsage { usage } => Ok(usage),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected OpenOrderUsage".to_string(),
+            )),
         }
-        (size / lot_size) * lot_size
     }
 
-    /// Round a size string to the nearest multiple of lot_size (rounds down)
-    pub fn round_size_to_lot_str(size: &str, lot_size: &str) -> SdkResult<String> {
-        let size_val = size
-            .parse::<u128>()
-            .map_err(|e| SdkError::InvalidResponse(format!("Invalid size: {}", e)))?;
-        let lot_size_val = lot_size
-            .parse::<u128>()
-            .map_err(|e| SdkError::InvalidResponse(format!("Invalid lot_size: {}", e)))?;
+    /// Get how much rests ahead of one of a user's orders at its price
+    pub async fn get_queue_position(
+        &self,
+        user_address: &str,
+        order_id: &str,
+    ) -> SdkResult<ApiQueuePosition> {
+        let request = UserRequest::QueuePosition {
+            user_address: user_address.to_string(),
+            order_id: order_id.to_string(),
+        };
+        let response = self.post_user(request).await?;
 
-        let rounded = Self::round_size_to_lot(size_val, lot_size_val);
-        Ok(rounded.to_string())
+        match response {
+            UserResponse::QueuePosition { position } => Ok(position),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected QueuePosition".to_string(),
+            )),
+        }
     }
 
-    /// Place an order
-    #[allow(clippy::too_many_arguments)]
-    pub async fn place_order(
-        &self,
-        user_address: String,
-        market_id: String,
-        side: Side,
-        order_type: OrderType,
-        price: String,
-        size: String,
-        signature: String,
-    ) -> SdkResult<crate::OrderPlaced> {
-        let request = TradeRequest::PlaceOrder {
-            user_address,
-            market_id,
-            side,
-            order_type,
-            price,
-            size,
-            signature,
+    /// Get a user's profile, open orders, 30-day volume, fees paid and balances in one call
+    pub async fn get_user_summary(&self, user_address: &str) -> SdkResult<ApiUserSummary> {
+        let request = UserRequest::Summary {
+            user_address: user_address.to_string(),
         };
-        let response = self.post_trade(request).await?;
+        let response = self.post_user(request).await?;
 
         match response {
-            TradeResponse::PlaceOrder { order, trades } => Ok(crate::OrderPlaced {
-                order: order.try_into().map_err(|e| {
-                    SdkError::InvalidResponse(format!("Failed to parse order: {}", e))
-                })?,
-                trades: trades
-                    .into_iter()
-                    .map(|t| t.try_into())
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| {
-                        SdkError::InvalidResponse(format!("Failed to parse trades: {}", e))
-                    })?,
-            }),
-            _ => Err(SdkError::InvalidResponse("Expected PlaceOrder".to_string())),
+            UserResponse::Summary { summary } => Ok(summary),
+            _ => Err(SdkError::InvalidResponse("Expected Summary".to_string())),
         }
     }
 
-    /// Place an order with automatic size rounding to lot_size
-    #[allow(clippy::too_many_arguments)]
-    pub async fn place_order_with_rounding(
+    /// Get the fees a user has earned as a referrer, per token
+    pub async fn get_referral_earnings(
         &self,
-        user_address: String,
-        market_id: String,
-        side: Side,
-        order_type: OrderType,
-        price: String,
-        size: String,
-        signature: String,
-    ) -> SdkResult<crate::OrderPlaced> {
-        // Get market details to find lot_size
-        let market = self.get_market(&market_id).await?;
-
-        // Parse size
-        let size_val = size
-            .parse::<u128>()
-            .map_err(|e| SdkError::InvalidResponse(format!("Invalid size: {}", e)))?;
-
-        // Round size to lot_size
-        let rounded_size = Self::round_size_to_lot(size_val, market.lot_size);
+        user_address: &str,
+    ) -> SdkResult<Vec<ApiReferralEarnings>> {
+        let request = UserRequest::ReferralEarnings {
+            user_address: user_address.to_string(),
+        };
+        let response = self.post_user(request).await?;
 
-        // Skip if rounded size is 0
-        if rounded_size == 0 {
-            return Err(SdkError::InvalidResponse(format!(
-                "Size {} is too small for lot_size {} (rounded to 0)",
-                size, market.lot_size
-            )));
+        match response {
+            UserResponse::ReferralEarnings { earnings } => Ok(earnings),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected ReferralEarnings".to_string(),
+            )),
         }
+    }
 
-        // Place order with rounded size
-        self.place_order(
+    /// Set the name a user appears under on the leaderboard; `None` opts out
+    pub async fn set_display_name(
+        &self,
+        user_address: String,
+        display_name: Option<String>,
+        signature: String,
+    ) -> SdkResult<Option<String>> {
+        let request = UserRequest::SetDisplayName {
             user_address,
-            market_id,
-            side,
-            order_type,
-            price,
-            rounded_size.to_string(),
+            display_name,
             signature,
-        )
-        .await
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::SetDisplayName { display_name, .. } => Ok(display_name),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetDisplayName".to_string(),
+            )),
+        }
     }
 
-    /// Place an order with human-readable decimal values (e.g., "0.5" BTC, "110000" USDC)
-    /// Automatically converts to atoms using token decimals from market config
-    #[allow(clippy::too_many_arguments)]
-    pub async fn place_order_decimal(
+    /// Register a URL to receive the user's fills and order updates
+    ///
+    /// Returns the webhook and the secret its deliveries are signed with,
+    /// which is not shown again.
+    pub async fn register_webhook(
         &self,
         user_address: String,
-        market_id: String,
-        side: Side,
-        order_type: OrderType,
-        price_decimal: String, // Human-readable price (e.g., "110000.50")
-        size_decimal: String,  // Human-readable size (e.g., "0.5")
+        url: String,
         signature: String,
-    ) -> SdkResult<crate::OrderPlaced> {
-        // Get market and token details
-        let market = self.get_market(&market_id).await?;
-        let base_token = self.get_token(&market.base_ticker).await?;
-        let quote_token = self.get_token(&market.quote_ticker).await?;
+    ) -> SdkResult<(ApiWebhook, String)> {
+        let request = UserRequest::RegisterWebhook {
+            user_address,
+            url,
+            signature,
+        };
+        let response = self.post_user(request).await?;
 
-        // Convert price from decimal to atoms using quote token decimals
-        let price_dec = Decimal::from_str(&price_decimal)
-            .map_err(|e| SdkError::InvalidResponse(format!("Invalid price: {}", e)))?;
-        let price_multiplier = Decimal::from(10u128.pow(quote_token.decimals as u32));
-        let price_atoms = price_dec * price_multiplier;
-        let price_u128 = price_atoms.to_u128().ok_or_else(|| {
-            SdkError::InvalidResponse(format!("Price overflow: {}", price_decimal))
-        })?;
-
-        // Convert size from decimal to atoms using base token decimals
-        let size_dec = Decimal::from_str(&size_decimal)
-            .map_err(|e| SdkError::InvalidResponse(format!("Invalid size: {}", e)))?;
-        let size_multiplier = Decimal::from(10u128.pow(base_token.decimals as u32));
-        let size_atoms = size_dec * size_multiplier;
-        let size_u128 = size_atoms
-            .to_u128()
-            .ok_or_else(|| SdkError::InvalidResponse(format!("Size overflow: {}", size_decimal)))?;
+        match response {
+            UserResponse::RegisterWebhook { webhook, secret } => Ok((webhook, secret)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RegisterWebhook".to_string(),
+            )),
+        }
+    }
 
-        // Round size to lot_size
-        let rounded_size = Self::round_size_to_lot(size_u128, market.lot_size);
+    /// List a user's webhooks
+    pub async fn list_webhooks(&self, user_address: String) -> SdkResult<Vec<ApiWebhook>> {
+        let request = UserRequest::Webhooks { user_address };
+        let response = self.post_user(request).await?;
 
-        // Check minimum size
-        if rounded_size < market.min_size {
-            return Err(SdkError::InvalidResponse(format!(
-                "Size {} ({} atoms) is below minimum {} atoms",
-                size_decimal, rounded_size, market.min_size
-            )));
+        match response {
+            UserResponse::Webhooks { webhooks } => Ok(webhooks),
+            _ => Err(SdkError::InvalidResponse("Expected Webhooks".to_string())),
         }
-
-        // Place order with converted values
-        self.place_order(
-            user_address,
-            market_id,
-            side,
-            order_type,
-            price_u128.to_string(),
-            rounded_size.to_string(),
-            signature,
-        )
-        .await
     }
 
-    /// Cancel an order
-    pub async fn cancel_order(
+    /// Remove one of a user's webhooks
+    pub async fn delete_webhook(
         &self,
         user_address: String,
-        order_id: String,
+        webhook_id: String,
         signature: String,
-    ) -> SdkResult<OrderCancelled> {
-        let request = TradeRequest::CancelOrder {
+    ) -> SdkResult<()> {
+        let request = UserRequest::DeleteWebhook {
             user_address,
-            order_id,
+            webhook_id,
             signature,
         };
-        let response = self.post_trade(request).await?;
+        let response = self.post_user(request).await?;
 
         match response {
-            TradeResponse::CancelOrder { order_id } => Ok(OrderCancelled { order_id }),
+            UserResponse::DeleteWebhook { .. } => Ok(()),
             _ => Err(SdkError::InvalidResponse(
-                "Expected CancelOrder".to_string(),
+                "Expected DeleteWebhook".to_string(),
             )),
         }
     }
 
-    /// Cancel all orders for a user, optionally filtered by market
-    pub async fn cancel_all_orders(
+    /// Deliveries to a user's webhooks that failed every retry, newest first
+    pub async fn get_webhook_dead_letters(
         &self,
         user_address: String,
-        market_id: Option<String>,
-        signature: String,
-    ) -> SdkResult<OrdersCancelled> {
-        let request = TradeRequest::CancelAllOrders {
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiWebhookDeadLetter>> {
+        let request = UserRequest::WebhookDeadLetters {
             user_address,
-            market_id,
-            signature,
+            limit,
         };
-        let response = self.post_trade(request).await?;
+        let response = self.post_user(request).await?;
 
         match response {
-            TradeResponse::CancelAllOrders {
-                cancelled_order_ids,
-                count,
-            } => Ok(OrdersCancelled {
-                cancelled_order_ids,
-                count,
-            }),
+            UserResponse::WebhookDeadLetters { dead_letters } => Ok(dead_letters),
             _ => Err(SdkError::InvalidResponse(
-                "Expected CancelAllOrders".to_string(),
+                "Expected WebhookDeadLetters".to_string(),
             )),
         }
     }
 
-    // ===== Drip/Faucet Endpoint =====
+    /// On-chain deposits credited to a user, newest first
+    pub async fn get_deposits(
+        &self,
+        user_address: String,
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiDeposit>> {
+        let request = UserRequest::Deposits {
+            user_address,
+            limit,
+        };
+        let response = self.post_user(request).await?;
 
-    /// Request testnet tokens from faucet
-    pub async fn faucet(
+        match response {
+            UserResponse::Deposits { deposits } => Ok(deposits),
+            _ => Err(SdkError::InvalidResponse("Expected Deposits".to_string())),
+        }
+    }
+
+    /// Request a withdrawal of `amount` (in atoms) to an on-chain address
+    pub async fn withdraw(
         &self,
         user_address: String,
         token_ticker: String,
-        amount: String,
+        amount: u128,
+        destination: String,
         signature: String,
-    ) -> SdkResult<(String, String, String, String)> {
-        let request = DripRequest::Faucet {
+    ) -> SdkResult<ApiWithdrawal> {
+        let request = UserRequest::Withdraw {
             user_address,
             token_ticker,
-            amount,
+            amount: amount.to_string(),
+            destination,
             signature,
         };
-        let response = self.post_drip(request).await?;
+        let response = self.post_user(request).await?;
 
         match response {
-            DripResponse::Faucet {
-                user_address,
-                token_ticker,
-                amount,
-                new_balance,
-            } => Ok((user_address, token_ticker, amount, new_balance)),
+            UserResponse::Withdraw { withdrawal } => Ok(withdrawal),
+            _ => Err(SdkError::InvalidResponse("Expected Withdraw".to_string())),
         }
     }
 
-    // ===== Candles Endpoints =====
-
-    /// Get OHLCV candles for a market
-    pub async fn get_candles(
+    /// Cancel a withdrawal that is still pending
+    pub async fn cancel_withdrawal(
         &self,
-        market_id: &str,
-        interval: &str,
+        user_address: String,
+        withdrawal_id: String,
+        signature: String,
+    ) -> SdkResult<ApiWithdrawal> {
+        let request = UserRequest::CancelWithdrawal {
+            user_address,
+            withdrawal_id,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::CancelWithdrawal { withdrawal } => Ok(withdrawal),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CancelWithdrawal".to_string(),
+            )),
+        }
+    }
+
+    /// A user's withdrawals, newest first
+    pub async fn get_withdrawals(
+        &self,
+        user_address: String,
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiWithdrawal>> {
+        let request = UserRequest::Withdrawals {
+            user_address,
+            limit,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::Withdrawals { withdrawals } => Ok(withdrawals),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected Withdrawals".to_string(),
+            )),
+        }
+    }
+
+    /// Margin a user's perpetual positions on their own or together; fails while any are open
+    pub async fn set_margin_mode(
+        &self,
+        user_address: String,
+        mode: MarginMode,
+        signature: String,
+    ) -> SdkResult<MarginMode> {
+        let request = UserRequest::SetMarginMode {
+            user_address,
+            mode,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::SetMarginMode { mode, .. } => Ok(mode),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SetMarginMode".to_string(),
+            )),
+        }
+    }
+
+    /// Open a named sub-account with its own balances and orders
+    pub async fn create_sub_account(
+        &self,
+        user_address: String,
+        name: String,
+        signature: String,
+    ) -> SdkResult<ApiSubAccount> {
+        let request = UserRequest::CreateSubAccount {
+            user_address,
+            name,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::CreateSubAccount { sub_account } => Ok(sub_account),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CreateSubAccount".to_string(),
+            )),
+        }
+    }
+
+    /// A user's sub-accounts, oldest first
+    pub async fn get_sub_accounts(&self, user_address: String) -> SdkResult<Vec<ApiSubAccount>> {
+        let request = UserRequest::SubAccounts { user_address };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::SubAccounts { sub_accounts } => Ok(sub_accounts),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SubAccounts".to_string(),
+            )),
+        }
+    }
+
+    /// Move free balance between a user's own accounts; returns the transfer
+    /// and both accounts' balances after it
+    pub async fn transfer_between_accounts(
+        &self,
+        user_address: String,
+        from_address: String,
+        to_address: String,
+        token_ticker: String,
+        amount: String,
+        signature: String,
+    ) -> SdkResult<(ApiAccountTransfer, Vec<ApiBalance>)> {
+        let request = UserRequest::InternalTransfer {
+            user_address,
+            from_address,
+            to_address,
+            token_ticker,
+            amount,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::InternalTransfer { transfer, balances } => Ok((transfer, balances)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected InternalTransfer".to_string(),
+            )),
+        }
+    }
+
+    /// Balances of a user and each of its sub-accounts, with totals per token
+    pub async fn get_aggregate_balances(
+        &self,
+        user_address: String,
+    ) -> SdkResult<(Vec<ApiBalance>, Vec<ApiAggregateBalance>)> {
+        let request = UserRequest::AggregateBalances { user_address };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::AggregateBalances { accounts, totals } => Ok((accounts, totals)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected AggregateBalances".to_string(),
+            )),
+        }
+    }
+
+    /// Issue an API key limited to `scopes` and, if non-empty, to requests
+    /// from `ip_allowlist`; returns the key's details and the key itself,
+    /// which is shown only this once
+    pub async fn create_api_key(
+        &self,
+        user_address: String,
+        label: String,
+        scopes: Vec<ApiKeyScope>,
+        ip_allowlist: Vec<String>,
+        signature: String,
+    ) -> SdkResult<(ApiKeyDetails, String)> {
+        let request = UserRequest::CreateApiKey {
+            user_address,
+            label,
+            scopes,
+            ip_allowlist,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::CreateApiKey { api_key, secret } => Ok((api_key, secret)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CreateApiKey".to_string(),
+            )),
+        }
+    }
+
+    /// A user's API keys that haven't been revoked
+    pub async fn get_api_keys(&self, user_address: String) -> SdkResult<Vec<ApiKeyDetails>> {
+        let request = UserRequest::ApiKeys { user_address };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::ApiKeys { api_keys } => Ok(api_keys),
+            _ => Err(SdkError::InvalidResponse("Expected ApiKeys".to_string())),
+        }
+    }
+
+    /// Replace an API key's secret; returns its details and the new key
+    pub async fn rotate_api_key(
+        &self,
+        user_address: String,
+        key_id: String,
+        signature: String,
+    ) -> SdkResult<(ApiKeyDetails, String)> {
+        let request = UserRequest::RotateApiKey {
+            user_address,
+            key_id,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::RotateApiKey { api_key, secret } => Ok((api_key, secret)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RotateApiKey".to_string(),
+            )),
+        }
+    }
+
+    /// Revoke an API key; requests made with it are rejected from then on
+    pub async fn revoke_api_key(
+        &self,
+        user_address: String,
+        key_id: String,
+        signature: String,
+    ) -> SdkResult<String> {
+        let request = UserRequest::RevokeApiKey {
+            user_address,
+            key_id,
+            signature,
+        };
+        let response = self.post_user(request).await?;
+
+        match response {
+            UserResponse::RevokeApiKey { key_id } => Ok(key_id),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RevokeApiKey".to_string(),
+            )),
+        }
+    }
+
+    // ===== Trade Endpoints =====
+
+    /// Round a size to the nearest multiple of lot_size (rounds down)
+    pub fn round_size_to_lot(size: u128, lot_size: u128) -> u128 {
+        if lot_size == 0 {
+            return size;
+        }
+        (size / lot_size) * lot_size
+    }
+
+    /// Round a size string to the nearest multiple of lot_size (rounds down)
+    pub fn round_size_to_lot_str(size: &str, lot_size: &str) -> SdkResult<String> {
+        let size_val = size
+            .parse::<u128>()
+            .map_err(|e| SdkError::InvalidResponse(format!("Invalid size: {}", e)))?;
+        let lot_size_val = lot_size
+            .parse::<u128>()
+            .map_err(|e| SdkError::InvalidResponse(format!("Invalid lot_size: {}", e)))?;
+
+        let rounded = Self::round_size_to_lot(size_val, lot_size_val);
+        Ok(rounded.to_string())
+    }
+
+    /// Place an order
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_order(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        self.place_order_with_time_in_force(
+            user_address,
+            market_id,
+            side,
+            order_type,
+            TimeInForce::Gtc,
+            price,
+            size,
+            signature,
+        )
+        .await
+    }
+
+    /// Place an order that rests until cancelled (`Gtc`) or cancels whatever
+    /// doesn't fill on arrival (`Ioc`)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_order_with_time_in_force(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        time_in_force: TimeInForce,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        let request = TradeRequest::PlaceOrder {
+            user_address,
+            market_id,
+            side,
+            order_type,
+            price,
+            size,
+            signature,
+            trigger_price: None,
+            trigger_source: TriggerSource::LastTrade,
+            time_in_force,
+            expires_at: None,
+        };
+        let response = self.post_trade(request).await?;
+
+        match response {
+            TradeResponse::PlaceOrder { order, trades } => OrderPlaced { order, trades }
+                .try_into()
+                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse order: {}", e))),
+            _ => Err(SdkError::InvalidResponse("Expected PlaceOrder".to_string())),
+        }
+    }
+
+    /// Place a limit order that rests until `expires_at`, when the exchange
+    /// cancels whatever is still open
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_order_with_expiry(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        price: String,
+        size: String,
+        expires_at: DateTime<Utc>,
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        let request = TradeRequest::PlaceOrder {
+            user_address,
+            market_id,
+            side,
+            order_type: OrderType::Limit,
+            price,
+            size,
+            signature,
+            trigger_price: None,
+            trigger_source: TriggerSource::LastTrade,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: Some(expires_at),
+        };
+        let response = self.post_trade(request).await?;
+
+        match response {
+            TradeResponse::PlaceOrder { order, trades } => OrderPlaced { order, trades }
+                .try_into()
+                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse order: {}", e))),
+            _ => Err(SdkError::InvalidResponse("Expected PlaceOrder".to_string())),
+        }
+    }
+
+    /// Place a stop order, which waits until a trade reaches `trigger_price`
+    /// and then executes as a market (`StopMarket`) or limit (`StopLimit`) order
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_stop_order(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        trigger_price: String,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        self.place_stop_order_with_trigger_source(
+            user_address,
+            market_id,
+            side,
+            order_type,
+            trigger_price,
+            TriggerSource::LastTrade,
+            price,
+            size,
+            signature,
+        )
+        .await
+    }
+
+    /// Place a stop order that triggers off the market's last trade
+    /// (`LastTrade`) or its external index price (`Index`)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_stop_order_with_trigger_source(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        trigger_price: String,
+        trigger_source: TriggerSource,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        let request = TradeRequest::PlaceOrder {
+            user_address,
+            market_id,
+            side,
+            order_type,
+            price,
+            size,
+            signature,
+            trigger_price: Some(trigger_price),
+            trigger_source,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: None,
+        };
+        let response = self.post_trade(request).await?;
+
+        match response {
+            TradeResponse::PlaceOrder { order, trades } => OrderPlaced { order, trades }
+                .try_into()
+                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse order: {}", e))),
+            _ => Err(SdkError::InvalidResponse("Expected PlaceOrder".to_string())),
+        }
+    }
+
+    /// Place an order with automatic size rounding to lot_size
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_order_with_rounding(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        price: String,
+        size: String,
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        // Get market details to find lot_size
+        let market = self.get_market(&market_id).await?;
+
+        // Parse size
+        let size_val = size
+            .parse::<u128>()
+            .map_err(|e| SdkError::InvalidResponse(format!("Invalid size: {}", e)))?;
+
+        // Round size to lot_size
+        let rounded_size = Self::round_size_to_lot(size_val, market.lot_size);
+
+        // Skip if rounded size is 0
+        if rounded_size == 0 {
+            return Err(SdkError::InvalidResponse(format!(
+                "Size {} is too small for lot_size {} (rounded to 0)",
+                size, market.lot_size
+            )));
+        }
+
+        // Place order with rounded size
+        self.place_order(
+            user_address,
+            market_id,
+            side,
+            order_type,
+            price,
+            rounded_size.to_string(),
+            signature,
+        )
+        .await
+    }
+
+    /// Place an order with human-readable decimal values (e.g., "0.5" BTC, "110000" USDC)
+    /// Automatically converts to atoms using token decimals from market config; a price
+    /// off the tick or more decimal places than a token holds is refused, not truncated
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_order_decimal(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        price_decimal: String, // Human-readable price (e.g., "110000.50")
+        size_decimal: String,  // Human-readable size (e.g., "0.5")
+        signature: String,
+    ) -> SdkResult<PlacedOrder> {
+        // Get market and token details
+        let market = self.get_market(&market_id).await?;
+        let base_token = self.get_token(&market.base_ticker).await?;
+        let quote_token = self.get_token(&market.quote_ticker).await?;
+
+        // Convert price and size to atoms, refusing digits the tokens can't hold
+        let price_u128 = check_tick_aligned(
+            decimal_to_atoms(&price_decimal, quote_token.decimals)?,
+            market.tick_size,
+        )?;
+        let size_u128 = decimal_to_atoms(&size_decimal, base_token.decimals)?;
+
+        // Round size to lot_size
+        let rounded_size = Self::round_size_to_lot(size_u128, market.lot_size);
+
+        // Check minimum size
+        if rounded_size < market.min_size {
+            return Err(SdkError::InvalidResponse(format!(
+                "Size {} ({} atoms) is below minimum {} atoms",
+                size_decimal, rounded_size, market.min_size
+            )));
+        }
+
+        // Place order with converted values
+        self.place_order(
+            user_address,
+            market_id,
+            side,
+            order_type,
+            price_u128.to_string(),
+            rounded_size.to_string(),
+            signature,
+        )
+        .await
+    }
+
+    /// Place two linked orders for the same side and size, a take-profit
+    /// limit and a stop; the first fill on either, or the stop triggering,
+    /// cancels the other
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_oco_order(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        size: String,
+        first: OcoLeg,
+        second: OcoLeg,
+        signature: String,
+    ) -> SdkResult<PlacedOco> {
+        let request = OcoRequest {
+            user_address,
+            market_id,
+            side,
+            size,
+            first,
+            second,
+            signature,
+        };
+        let placed: OcoPlaced = self.post("oco", &request).await?;
+
+        placed
+            .try_into()
+            .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse order: {}", e)))
+    }
+
+    /// Cancel an order
+    pub async fn cancel_order(
+        &self,
+        user_address: String,
+        order_id: String,
+        signature: String,
+    ) -> SdkResult<OrderCancelled> {
+        let request = TradeRequest::CancelOrder {
+            user_address,
+            order_id,
+            signature,
+        };
+        let response = self.post_trade(request).await?;
+
+        match response {
+            TradeResponse::CancelOrder { order_id } => Ok(OrderCancelled { order_id }),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CancelOrder".to_string(),
+            )),
+        }
+    }
+
+    /// Cancel all orders for a user, optionally filtered by market
+    pub async fn cancel_all_orders(
+        &self,
+        user_address: String,
+        market_id: Option<String>,
+        signature: String,
+    ) -> SdkResult<OrdersCancelled> {
+        let request = TradeRequest::CancelAllOrders {
+            user_address,
+            market_id,
+            signature,
+        };
+        let response = self.post_trade(request).await?;
+
+        match response {
+            TradeResponse::CancelAllOrders {
+                cancelled_order_ids,
+                count,
+            } => Ok(OrdersCancelled {
+                cancelled_order_ids,
+                count,
+            }),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CancelAllOrders".to_string(),
+            )),
+        }
+    }
+
+    // ===== Drip/Faucet Endpoint =====
+
+    /// Request testnet tokens from faucet
+    pub async fn faucet(
+        &self,
+        user_address: String,
+        token_ticker: String,
+        amount: String,
+        signature: String,
+    ) -> SdkResult<(String, String, String, String)> {
+        let request = DripRequest::Faucet {
+            user_address,
+            token_ticker,
+            amount,
+            signature,
+        };
+        let response = self.post_drip(request).await?;
+
+        match response {
+            DripResponse::Faucet {
+                user_address,
+                token_ticker,
+                amount,
+                new_balance,
+            } => Ok((user_address, token_ticker, amount, new_balance)),
+        }
+    }
+
+    // ===== RFQ Endpoints =====
+
+    /// Ask registered makers for a firm price on `size`, open for `ttl_secs`
+    /// (the server's default if `None`)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn request_quote(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        size: String,
+        ttl_secs: Option<u64>,
+        signature: String,
+    ) -> SdkResult<ApiQuoteRequest> {
+        let request = RfqRequest::RequestQuote {
+            user_address,
+            market_id,
+            side,
+            size,
+            ttl_secs,
+            signature,
+        };
+
+        match self.post_rfq(request).await? {
+            RfqResponse::RequestQuote { request } => Ok(request),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected RequestQuote".to_string(),
+            )),
+        }
+    }
+
+    /// Quote a firm price on an open request as a registered maker
+    pub async fn submit_quote(
+        &self,
+        user_address: String,
+        request_id: String,
+        price: String,
+        ttl_secs: Option<u64>,
+        signature: String,
+    ) -> SdkResult<ApiQuote> {
+        let request = RfqRequest::SubmitQuote {
+            user_address,
+            request_id,
+            price,
+            ttl_secs,
+            signature,
+        };
+
+        match self.post_rfq(request).await? {
+            RfqResponse::SubmitQuote { quote } => Ok(quote),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected SubmitQuote".to_string(),
+            )),
+        }
+    }
+
+    /// Trade a request's size at one of its quotes; returns the filled request and its trade
+    pub async fn accept_quote(
+        &self,
+        user_address: String,
+        request_id: String,
+        quote_id: String,
+        signature: String,
+    ) -> SdkResult<(ApiQuoteRequest, ApiTrade)> {
+        let request = RfqRequest::AcceptQuote {
+            user_address,
+            request_id,
+            quote_id,
+            signature,
+        };
+
+        match self.post_rfq(request).await? {
+            RfqResponse::AcceptQuote { request, trade } => Ok((request, trade)),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected AcceptQuote".to_string(),
+            )),
+        }
+    }
+
+    /// Withdraw an open request for quote
+    pub async fn cancel_quote_request(
+        &self,
+        user_address: String,
+        request_id: String,
+        signature: String,
+    ) -> SdkResult<ApiQuoteRequest> {
+        let request = RfqRequest::CancelRequest {
+            user_address,
+            request_id,
+            signature,
+        };
+
+        match self.post_rfq(request).await? {
+            RfqResponse::CancelRequest { request } => Ok(request),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected CancelRequest".to_string(),
+            )),
+        }
+    }
+
+    /// Requests makers can still quote, oldest first
+    pub async fn get_open_quote_requests(
+        &self,
+        market_id: Option<String>,
+    ) -> SdkResult<Vec<ApiQuoteRequest>> {
+        match self
+            .post_rfq(RfqRequest::OpenRequests { market_id })
+            .await?
+        {
+            RfqResponse::OpenRequests { requests } => Ok(requests),
+            _ => Err(SdkError::InvalidResponse(
+                "Expected OpenRequests".to_string(),
+            )),
+        }
+    }
+
+    /// A taker's request and its live quotes, best price first
+    pub async fn get_quotes(
+        &self,
+        user_address: String,
+        request_id: String,
+    ) -> SdkResult<(ApiQuoteRequest, Vec<ApiQuote>)> {
+        let request = RfqRequest::Quotes {
+            user_address,
+            request_id,
+        };
+
+        match self.post_rfq(request).await? {
+            RfqResponse::Quotes { request, quotes } => Ok((request, quotes)),
+            _ => Err(SdkError::InvalidResponse("Expected Quotes".to_string())),
+        }
+    }
+
+    // ===== Candles Endpoints =====
+
+    /// Get OHLCV candles for a market
+    pub async fn get_candles(
+        &self,
+        market_id: &str,
+        interval: &str,
         from: i64,
         to: i64,
     ) -> SdkResult<Vec<ApiCandle>> {
@@ -423,6 +1278,7 @@ impl ExchangeClient {
             from,
             to,
             count_back: None,
+            fill_gaps: None,
         };
         let response = self.post_candles(request).await?;
 
@@ -431,230 +1287,609 @@ impl ExchangeClient {
         Ok(response.candles)
     }
 
-    // ===== Admin Endpoints (Test/Dev Only) =====
+    /// Get rolling 24h volume, trade count and OHLC for a market
+    pub async fn get_market_stats(&self, market_id: &str) -> SdkResult<ApiMarketStats> {
+        self.get(&market_stats_endpoint(market_id)).await
+    }
+
+    /// Get a market's best bid and ask, up to a second behind the live book
+    pub async fn get_top_of_book(&self, market_id: &str) -> SdkResult<ApiTopOfBook> {
+        self.get(&top_of_book_endpoint(market_id)).await
+    }
 
-    /// Create a token (admin)
-    pub async fn admin_create_token(
+    /// Get every resting order of a market, in queue order at each price
+    ///
+    /// Only approved market makers may read it, once a second each, with
+    /// their signature of the read.
+    pub async fn get_l3_orderbook(
         &self,
-        ticker: String,
-        decimals: u8,
-        name: String,
-    ) -> SdkResult<Token> {
-        let request = backend::models::api::AdminRequest::CreateToken {
-            ticker,
-            decimals,
-            name,
-        };
-        let response = self.post_admin(request).await?;
+        market_id: &str,
+        user_address: &str,
+        signature: &str,
+    ) -> SdkResult<ApiL3Book> {
+        self.get(&l3_orderbook_endpoint(market_id, user_address, signature))
+            .await
+    }
 
-        match response {
-            backend::models::api::AdminResponse::CreateToken { token } => Ok(token),
-            _ => Err(SdkError::InvalidResponse(
-                "Expected CreateToken".to_string(),
-            )),
+    /// Get a market's volume-weighted average price between two Unix timestamps
+    pub async fn get_vwap(&self, market_id: &str, from: i64, to: i64) -> SdkResult<Option<u128>> {
+        let response: ApiAveragePrice = self
+            .get(&market_range_endpoint(market_id, "vwap", from, to))
+            .await?;
+        parse_average_price(response)
+    }
+
+    /// Get a market's time-weighted average price between two Unix timestamps
+    pub async fn get_twap(&self, market_id: &str, from: i64, to: i64) -> SdkResult<Option<u128>> {
+        let response: ApiAveragePrice = self
+            .get(&market_range_endpoint(market_id, "twap", from, to))
+            .await?;
+        parse_average_price(response)
+    }
+
+    /// Get a market's sampled spread, top-of-book depth and imbalance between two
+    /// Unix timestamps, for checking liquidity obligations
+    pub async fn get_depth_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<DepthHistoryResponse> {
+        self.get(&market_range_endpoint(market_id, "depth", from, to))
+            .await
+    }
+
+    /// Get a market's external index prices between two Unix timestamps
+    pub async fn get_index_price_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<IndexPriceHistoryResponse> {
+        self.get(&market_range_endpoint(market_id, "index-prices", from, to))
+            .await
+    }
+
+    /// Get a perpetual market's open interest snapshots between two Unix timestamps
+    pub async fn get_open_interest_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<OpenInterestHistoryResponse> {
+        self.get(&market_range_endpoint(market_id, "open-interest", from, to))
+            .await
+    }
+
+    /// Get a perpetual market's funding rates between two Unix timestamps
+    pub async fn get_funding_history(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<FundingHistoryResponse> {
+        self.get(&market_range_endpoint(market_id, "funding", from, to))
+            .await
+    }
+
+    /// Get a perpetual market's funding terms and latest mark price
+    pub async fn get_perpetual_market(&self, market_id: &str) -> SdkResult<ApiPerpetualMarket> {
+        self.get(&format!(
+            "markets/{}/perpetual",
+            market_id.replace('/', "%2F")
+        ))
+        .await
+    }
+
+    /// Get a perpetual market's positions within `within_bps` of their maintenance
+    /// margin (the backend's default when `None`), most at risk first
+    pub async fn get_market_risk(
+        &self,
+        market_id: &str,
+        within_bps: Option<u32>,
+    ) -> SdkResult<MarketRiskResponse> {
+        let mut endpoint = format!("markets/{}/risk", market_id.replace('/', "%2F"));
+        if let Some(within_bps) = within_bps {
+            endpoint.push_str(&format!("?within_bps={}", within_bps));
         }
+        self.get(&endpoint).await
     }
 
-    /// Create a market (admin)
-    #[allow(clippy::too_many_arguments)]
-    pub async fn admin_create_market(
-        &self,
-        base_ticker: String,
-        quote_ticker: String,
-        tick_size: u128,
-        lot_size: u128,
-        min_size: u128,
-        maker_fee_bps: i32,
-        taker_fee_bps: i32,
-    ) -> SdkResult<Market> {
-        let request = backend::models::api::AdminRequest::CreateMarket {
-            base_ticker,
-            quote_ticker,
-            tick_size: tick_size.to_string(),
-            lot_size: lot_size.to_string(),
-            min_size: min_size.to_string(),
-            maker_fee_bps,
-            taker_fee_bps,
-        };
-        let response = self.post_admin(request).await?;
-
-        match response {
-            backend::models::api::AdminResponse::CreateMarket { market } => market
-                .try_into()
-                .map_err(|e| SdkError::InvalidResponse(format!("Failed to parse market: {}", e))),
-            _ => Err(SdkError::InvalidResponse(
-                "Expected CreateMarket".to_string(),
-            )),
+    /// Get a market's per-trader maker ratios and taker flow imbalance between two
+    /// Unix timestamps, bucketed by `interval` (1m, 5m, 15m, 1h or 1d)
+    pub async fn get_flow_analytics(
+        &self,
+        market_id: &str,
+        from: i64,
+        to: i64,
+        interval: &str,
+    ) -> SdkResult<FlowAnalyticsResponse> {
+        self.get(&format!(
+            "{}&interval={}",
+            market_range_endpoint(market_id, "flow", from, to),
+            interval
+        ))
+        .await
+    }
+
+    /// Export a user's fills between two Unix timestamps as CSV or Parquet
+    ///
+    /// Small exports come back as the file itself; larger ones start a job to
+    /// poll with `get_export_job` and fetch with `download_export` once ready.
+    pub async fn export_user_fills(
+        &self,
+        user_address: &str,
+        format: ExportFormat,
+        from: i64,
+        to: i64,
+    ) -> SdkResult<FillsExport> {
+        let endpoint = fills_export_endpoint(user_address, format, from, to);
+        let builder = self.client.get(self.url(&endpoint));
+        let response = self.request(builder).send().await?;
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            Ok(FillsExport::Job(Self::read_response(response).await?))
+        } else {
+            Ok(FillsExport::File(Self::read_bytes(response).await?))
         }
     }
 
-    /// Faucet via admin endpoint
-    pub async fn admin_faucet(
+    /// Get the status of a background fills export
+    pub async fn get_export_job(&self, job_id: &str) -> SdkResult<ApiExportJob> {
+        self.get(&format!("exports/{}", job_id)).await
+    }
+
+    /// Download the file of a finished fills export
+    pub async fn download_export(&self, job_id: &str) -> SdkResult<Vec<u8>> {
+        let builder = self
+            .client
+            .get(self.url(&format!("exports/{}/download", job_id)));
+        let response = self.request(builder).send().await?;
+        Self::read_bytes(response).await
+    }
+
+    /// Get a user's realized PnL, position and average entry price per market
+    pub async fn get_user_pnl(
         &self,
-        user_address: String,
-        token_ticker: String,
-        amount: String,
-    ) -> SdkResult<String> {
-        let request = backend::models::api::AdminRequest::Faucet {
-            user_address,
-            token_ticker,
-            amount,
-            signature: "admin".to_string(),
-        };
-        let response = self.post_admin(request).await?;
+        user_address: &str,
+        method: CostBasisMethod,
+    ) -> SdkResult<UserPnlResponse> {
+        self.get(&format!("users/{}/pnl?method={}", user_address, method))
+            .await
+    }
 
-        match response {
-            backend::models::api::AdminResponse::Faucet { new_balance, .. } => Ok(new_balance),
-            _ => Err(SdkError::InvalidResponse("Expected Faucet".to_string())),
+    /// Get the fees a user pays in every market and their 30-day volume there
+    pub async fn get_user_fees(&self, user_address: &str) -> SdkResult<UserFeesResponse> {
+        self.get(&format!("users/{}/fees", user_address)).await
+    }
+
+    /// Get a user's monthly account statements, newest first
+    pub async fn get_statements(
+        &self,
+        user_address: &str,
+        limit: Option<u32>,
+    ) -> SdkResult<Vec<ApiStatement>> {
+        let mut endpoint = format!("users/{}/statements", user_address);
+        if let Some(limit) = limit {
+            endpoint.push_str(&format!("?limit={}", limit));
         }
+        let response: StatementsResponse = self.get(&endpoint).await?;
+        Ok(response.statements)
+    }
+
+    /// Get a user's statement for one calendar month, given as `YYYY-MM`
+    pub async fn get_statement(&self, user_address: &str, month: &str) -> SdkResult<ApiStatement> {
+        self.get(&format!("users/{}/statements/{}", user_address, month))
+            .await
+    }
+
+    /// Get a user's positions in perpetual markets, with unrealized PnL at the mark price
+    pub async fn get_positions(&self, user_address: &str) -> SdkResult<PositionsResponse> {
+        self.get(&format!("users/{}/positions", user_address)).await
+    }
+
+    /// Get a user's margin mode and how close their positions are to liquidation
+    pub async fn get_margin_account(&self, user_address: &str) -> SdkResult<MarginAccountResponse> {
+        self.get(&format!("users/{}/margin", user_address)).await
+    }
+
+    /// Get the top traders for a period by volume or realized PnL
+    pub async fn get_leaderboard(
+        &self,
+        period: LeaderboardPeriod,
+        metric: LeaderboardMetric,
+        hide_addresses: bool,
+    ) -> SdkResult<Vec<ApiLeaderboardEntry>> {
+        let response: LeaderboardResponse = self
+            .get(&leaderboard_endpoint(period, metric, hide_addresses))
+            .await?;
+        Ok(response.entries)
+    }
+
+    /// List prediction events and their outcome markets, newest first,
+    /// optionally only those with `status`
+    pub async fn get_events(
+        &self,
+        status: Option<EventStatus>,
+    ) -> SdkResult<Vec<ApiPredictionEvent>> {
+        let response: EventsResponse = self.get(&events_endpoint(status)).await?;
+        Ok(response.events)
+    }
+
+    /// Get a prediction event and its outcome markets
+    pub async fn get_event(&self, event_id: &str) -> SdkResult<ApiPredictionEvent> {
+        self.get(&format!("events/{}", event_id)).await
     }
 
     // ===== Internal Helper Methods =====
 
-    async fn post_info(&self, request: InfoRequest) -> SdkResult<InfoResponse> {
-        let url = format!("{}/api/info", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}{}/{}", self.base_url, self.api_path, endpoint)
+    }
 
-        if response.status().is_success() {
-            Ok(response.json().await?)
-        } else {
-            let error: serde_json::Value = response.json().await?;
-            Err(SdkError::ApiError {
-                status: error
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("500")
-                    .parse()
-                    .unwrap_or(500),
-                message: error
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-            })
+    fn request(&self, builder: RequestBuilder) -> RequestBuilder {
+        let builder = match &self.api_key {
+            Some(api_key) => builder.header(API_KEY_HEADER, api_key),
+            None => builder,
+        };
+        match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
         }
     }
 
-    async fn post_user(&self, request: UserRequest) -> SdkResult<UserResponse> {
-        let url = format!("{}/api/user", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
+    async fn post<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        request: &Req,
+    ) -> SdkResult<Resp> {
+        let builder = self.client.post(self.url(endpoint)).json(request);
+        let response = self.request(builder).send().await?;
+        Self::read_response(response).await
+    }
+
+    async fn get<Resp: DeserializeOwned>(&self, endpoint: &str) -> SdkResult<Resp> {
+        let builder = self.client.get(self.url(endpoint));
+        let response = self.request(builder).send().await?;
+        Self::read_response(response).await
+    }
 
+    async fn read_response<Resp: DeserializeOwned>(response: reqwest::Response) -> SdkResult<Resp> {
         if response.status().is_success() {
             Ok(response.json().await?)
         } else {
-            let error: serde_json::Value = response.json().await?;
-            Err(SdkError::ApiError {
-                status: error
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("500")
-                    .parse()
-                    .unwrap_or(500),
-                message: error
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-            })
+            Err(Self::read_error(response).await?)
         }
     }
 
-    async fn post_trade(&self, request: TradeRequest) -> SdkResult<TradeResponse> {
-        let url = format!("{}/api/trade", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
-
+    async fn read_bytes(response: reqwest::Response) -> SdkResult<Vec<u8>> {
         if response.status().is_success() {
-            Ok(response.json().await?)
+            Ok(response.bytes().await?.to_vec())
         } else {
-            let error: serde_json::Value = response.json().await?;
-            Err(SdkError::ApiError {
-                status: error
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("500")
-                    .parse()
-                    .unwrap_or(500),
-                message: error
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-            })
+            Err(Self::read_error(response).await?)
         }
     }
 
+    async fn read_error(response: reqwest::Response) -> SdkResult<SdkError> {
+        let status = response.status().as_u16();
+        let error: serde_json::Value = response.json().await?;
+        Ok(SdkError::from_error_body(status, &error))
+    }
+
+    async fn post_info(&self, request: InfoRequest) -> SdkResult<InfoResponse> {
+        self.post("info", &request).await
+    }
+
+    async fn post_user(&self, request: UserRequest) -> SdkResult<UserResponse> {
+        self.post("user", &request).await
+    }
+
+    async fn post_trade(&self, request: TradeRequest) -> SdkResult<TradeResponse> {
+        self.post("trade", &request).await
+    }
+
     async fn post_drip(&self, request: DripRequest) -> SdkResult<DripResponse> {
-        let url = format!("{}/api/drip", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
+        self.post("drip", &request).await
+    }
 
-        if response.status().is_success() {
-            Ok(response.json().await?)
-        } else {
-            let error: serde_json::Value = response.json().await?;
-            Err(SdkError::ApiError {
-                status: error
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("500")
-                    .parse()
-                    .unwrap_or(500),
-                message: error
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-            })
-        }
+    async fn post_rfq(&self, request: RfqRequest) -> SdkResult<RfqResponse> {
+        self.post("rfq", &request).await
     }
 
-    async fn post_admin(
+    /// Send an admin request, authenticated with `admin_token`
+    pub(crate) async fn post_admin(
         &self,
-        request: backend::models::api::AdminRequest,
-    ) -> SdkResult<backend::models::api::AdminResponse> {
-        let url = format!("{}/api/admin", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
-
-        if response.status().is_success() {
-            Ok(response.json().await?)
-        } else {
-            let error: serde_json::Value = response.json().await?;
-            Err(SdkError::ApiError {
-                status: error
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("500")
-                    .parse()
-                    .unwrap_or(500),
-                message: error
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-            })
-        }
+        request: AdminRequest,
+        admin_token: &str,
+    ) -> SdkResult<AdminResponse> {
+        let builder = self
+            .client
+            .post(self.url("admin"))
+            .bearer_auth(admin_token)
+            .json(&request);
+        let response = self.request(builder).send().await?;
+        Self::read_response(response).await
     }
 
     async fn post_candles(&self, request: CandlesRequest) -> SdkResult<CandlesResponse> {
-        let url = format!("{}/api/candles", self.base_url);
-        let response = self.client.post(&url).json(&request).send().await?;
+        self.post("candles", &request).await
+    }
+}
 
-        if response.status().is_success() {
-            Ok(response.json().await?)
-        } else {
-            let error: serde_json::Value = response.json().await?;
-            Err(SdkError::ApiError {
-                status: error
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("500")
-                    .parse()
-                    .unwrap_or(500),
-                message: error
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-            })
+/// Builder for [`ExchangeClient`]
+///
+/// Lets callers inject their own `reqwest::Client`, route through a proxy,
+/// mount the API under a different path prefix, and set default timeouts.
+///
+/// ```no_run
+/// use exchange_sdk::ExchangeClient;
+/// use std::time::Duration;
+///
+/// let client = ExchangeClient::builder("https://example.com/exchange")
+///     .api_path("/v1")
+///     .timeout(Duration::from_secs(5))
+///     .proxy("http://proxy.internal:3128")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExchangeClientBuilder {
+    base_url: String,
+    api_path: String,
+    client: Option<Client>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<String>,
+    metadata_ttl: Option<Duration>,
+}
+
+impl ExchangeClientBuilder {
+    fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_path: DEFAULT_API_PATH.to_string(),
+            client: None,
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            metadata_ttl: None,
         }
     }
+
+    /// Path prefix the REST API is mounted under (default: `/api`)
+    pub fn api_path(mut self, api_path: impl Into<String>) -> Self {
+        self.api_path = api_path.into();
+        self
+    }
+
+    /// Use a preconfigured `reqwest::Client`
+    ///
+    /// When set, `connect_timeout` and `proxy` are ignored since they are
+    /// properties of the underlying client.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Default timeout applied to every request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for establishing the TCP connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Route all requests through the given proxy URL
+    pub fn proxy(mut self, proxy_url: impl Into<String>) -> Self {
+        self.proxy = Some(proxy_url.into());
+        self
+    }
+
+    /// Cache market and token metadata for `ttl` (disabled by default)
+    pub fn metadata_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttl = Some(ttl);
+        self
+    }
+
+    /// Build the client
+    pub fn build(self) -> SdkResult<ExchangeClient> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder();
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(proxy_url) = &self.proxy {
+                    builder = builder.proxy(Proxy::all(proxy_url)?);
+                }
+                builder.build()?
+            }
+        };
+
+        Ok(ExchangeClient {
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            api_path: normalize_api_path(&self.api_path),
+            client,
+            timeout: self.timeout,
+            metadata: self
+                .metadata_ttl
+                .map(|ttl| Arc::new(MetadataCache::new(ttl))),
+            api_key: None,
+        })
+    }
+}
+
+/// Normalize an API path prefix to either "" or "/segment[/segment...]"
+fn normalize_api_path(path: &str) -> String {
+    let trimmed = path.trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+/// Path of a market's stats endpoint; market ids contain a `/` that must be escaped
+pub(crate) fn market_stats_endpoint(market_id: &str) -> String {
+    format!("markets/{}/stats", market_id.replace('/', "%2F"))
+}
+
+/// Path of a market's top of book endpoint
+pub(crate) fn top_of_book_endpoint(market_id: &str) -> String {
+    format!("markets/{}/top-of-book", market_id.replace('/', "%2F"))
+}
+
+/// Path of a market's per-order book endpoint, read as `user_address`
+pub(crate) fn l3_orderbook_endpoint(
+    market_id: &str,
+    user_address: &str,
+    signature: &str,
+) -> String {
+    format!(
+        "orderbook/{}/l3?user_address={}&signature={}",
+        market_id.replace('/', "%2F"),
+        user_address,
+        signature
+    )
+}
+
+/// Path of a market's VWAP or TWAP endpoint over `[from, to]`
+pub(crate) fn market_range_endpoint(market_id: &str, kind: &str, from: i64, to: i64) -> String {
+    format!(
+        "markets/{}/{}?from={}&to={}",
+        market_id.replace('/', "%2F"),
+        kind,
+        from,
+        to
+    )
+}
+
+pub(crate) fn parse_average_price(response: ApiAveragePrice) -> SdkResult<Option<u128>> {
+    response
+        .price
+        .map(|price| {
+            price
+                .parse()
+                .map_err(|_| SdkError::InvalidResponse(format!("Invalid price: {}", price)))
+        })
+        .transpose()
+}
+
+pub(crate) fn fills_export_endpoint(
+    user_address: &str,
+    format: ExportFormat,
+    from: i64,
+    to: i64,
+) -> String {
+    format!(
+        "users/{}/fills/export?format={}&from={}&to={}",
+        user_address, format, from, to
+    )
+}
+
+pub(crate) fn leaderboard_endpoint(
+    period: LeaderboardPeriod,
+    metric: LeaderboardMetric,
+    hide_addresses: bool,
+) -> String {
+    format!(
+        "leaderboard?period={}&metric={}&hide_addresses={}",
+        period, metric, hide_addresses
+    )
+}
+
+pub(crate) fn events_endpoint(status: Option<EventStatus>) -> String {
+    match status {
+        Some(status) => format!("events?status={}", status),
+        None => "events".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_urls() {
+        let client = ExchangeClient::new("http://localhost:8001");
+        assert_eq!(client.url("info"), "http://localhost:8001/api/info");
+        assert_eq!(
+            client.url(&market_stats_endpoint("BTC/USDC")),
+            "http://localhost:8001/api/markets/BTC%2FUSDC/stats"
+        );
+        assert_eq!(
+            client.url(&top_of_book_endpoint("BTC/USDC")),
+            "http://localhost:8001/api/markets/BTC%2FUSDC/top-of-book"
+        );
+        assert_eq!(
+            client.url(&l3_orderbook_endpoint("BTC/USDC", "alice", "sig")),
+            "http://localhost:8001/api/orderbook/BTC%2FUSDC/l3?user_address=alice&signature=sig"
+        );
+        assert_eq!(
+            client.url(&fills_export_endpoint(
+                "alice",
+                ExportFormat::Parquet,
+                0,
+                60
+            )),
+            "http://localhost:8001/api/users/alice/fills/export?format=parquet&from=0&to=60"
+        );
+    }
+
+    #[test]
+    fn test_builder_api_path() {
+        let client = ExchangeClient::builder("http://localhost:8001/")
+            .api_path("/exchange/v1/")
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.url("trade"),
+            "http://localhost:8001/exchange/v1/trade"
+        );
+
+        let client = ExchangeClient::builder("http://localhost:8001")
+            .api_path("")
+            .build()
+            .unwrap();
+        assert_eq!(client.url("trade"), "http://localhost:8001/trade");
+    }
+
+    #[test]
+    fn test_with_timeout_keeps_config() {
+        let client = ExchangeClient::builder("http://localhost:8001")
+            .api_path("/v1")
+            .build()
+            .unwrap()
+            .with_timeout(Duration::from_millis(250));
+        assert_eq!(client.timeout, Some(Duration::from_millis(250)));
+        assert_eq!(client.url("info"), "http://localhost:8001/v1/info");
+    }
+
+    #[test]
+    fn test_with_api_key_sends_header() {
+        let client = ExchangeClient::new("http://localhost:8001").with_api_key("ak_test");
+        let request = client
+            .request(client.client.get(client.url("info")))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[API_KEY_HEADER], "ak_test");
+
+        let request = ExchangeClient::new("http://localhost:8001")
+            .request(client.client.get(client.url("info")))
+            .build()
+            .unwrap();
+        assert!(request.headers().get(API_KEY_HEADER).is_none());
+    }
+
+    #[test]
+    fn test_invalid_proxy_rejected() {
+        let result = ExchangeClient::builder("http://localhost:8001")
+            .proxy("not a url")
+            .build();
+        assert!(result.is_err());
+    }
 }
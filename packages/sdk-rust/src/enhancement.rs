@@ -2,11 +2,11 @@
 //!
 //! Adds human-readable display values and formatting to raw atom-based data.
 
-use backend::models::{
+use chrono::{DateTime, Utc};
+use exchange_protocol::{
     api::{ApiBalance, ApiOrder, ApiTrade},
     domain::OrderbookLevel,
 };
-use chrono::{DateTime, Utc};
 use std::sync::Arc;
 
 use crate::{cache::CacheService, format::*, SdkError, SdkResult};
@@ -280,7 +280,7 @@ impl EnhancementService {
 mod tests {
     use super::*;
     use crate::logger::NoopLogger;
-    use backend::models::{api::ApiMarket, domain::Token};
+    use exchange_protocol::{api::ApiMarket, domain::Token};
 
     fn setup_cache() -> Arc<CacheService> {
         let cache = Arc::new(CacheService::new(Arc::new(NoopLogger)));
@@ -309,6 +309,7 @@ mod tests {
             min_size: "1000000".to_string(),
             maker_fee_bps: 10,
             taker_fee_bps: 20,
+            numeric_id: Some(1),
         }]);
 
         cache.mark_initialized();
@@ -329,8 +330,12 @@ mod tests {
             seller_order_id: "order2".to_string(),
             price: "50000000000".to_string(), // 50000 USDC (6 decimals)
             size: "100000000".to_string(),    // 1 BTC (8 decimals)
-            side: backend::models::domain::Side::Buy,
+            side: exchange_protocol::domain::Side::Buy,
             timestamp: Utc::now(),
+            rfq: false,
+            fee: None,
+            fee_ticker: None,
+            role: None,
         };
 
         let enhanced = enhancer.enhance_trade(trade).unwrap();
@@ -1,3 +1,6 @@
+use exchange_protocol::convert::ConversionError;
+use exchange_protocol::domain::RejectReason;
+use exchange_protocol::error_code::ErrorCode;
 use thiserror::Error;
 
 pub type SdkResult<T> = Result<T, SdkError>;
@@ -14,7 +17,14 @@ pub enum SdkError {
     SerializationError(#[from] serde_json::Error),
 
     #[error("API error ({status}): {message}")]
-    ApiError { status: u16, message: String },
+    ApiError {
+        status: u16,
+        message: String,
+        /// The backend's error code, e.g. `INSUFFICIENT_BALANCE`
+        code: Option<ErrorCode>,
+        /// Why an order was refused, when the backend says
+        reason: Option<RejectReason>,
+    },
 
     #[error("Connection error: {0}")]
     ConnectionError(String),
@@ -27,4 +37,91 @@ pub enum SdkError {
 
     #[error("Enhancement error: {0}")]
     Enhancement(String),
+
+    #[error("Invalid amount: {0}")]
+    Conversion(#[from] ConversionError),
+}
+
+impl SdkError {
+    /// An API error from the backend's JSON error body
+    pub(crate) fn from_error_body(status: u16, body: &serde_json::Value) -> Self {
+        SdkError::ApiError {
+            status,
+            message: body
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown error")
+                .to_string(),
+            code: body
+                .get("code")
+                .and_then(|v| serde_json::from_value(v.clone()).ok()),
+            reason: body
+                .get("reason")
+                .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        }
+    }
+
+    /// The backend's code for this error, if it sent one
+    ///
+    /// Branch on this rather than on the message, which may be reworded.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            SdkError::ApiError { code, .. } => *code,
+            _ => None,
+        }
+    }
+
+    /// Why the backend refused an order, if this error is such a refusal
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        match self {
+            SdkError::ApiError { reason, .. } => *reason,
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_body_keeps_code_and_reason() {
+        let body = serde_json::json!({
+            "error": "Insufficient balance",
+            "code": "INSUFFICIENT_BALANCE",
+            "reason": "insufficient_balance",
+        });
+        let error = SdkError::from_error_body(400, &body);
+        assert!(matches!(
+            &error,
+            SdkError::ApiError {
+                status: 400,
+                code: Some(ErrorCode::InsufficientBalance),
+                ..
+            }
+        ));
+        assert_eq!(error.code(), Some(ErrorCode::InsufficientBalance));
+        assert_eq!(
+            error.reject_reason(),
+            Some(RejectReason::InsufficientBalance)
+        );
+
+        // Codes newer than this client still parse
+        let error = SdkError::from_error_body(
+            400,
+            &serde_json::json!({"error": "Nope", "code": "SOMETHING_NEW"}),
+        );
+        assert_eq!(error.code(), Some(ErrorCode::Unknown));
+
+        // Older backends send neither
+        let error = SdkError::from_error_body(500, &serde_json::json!({"error": "boom"}));
+        assert!(matches!(
+            error,
+            SdkError::ApiError {
+                code: None,
+                reason: None,
+                ..
+            }
+        ));
+    }
 }
@@ -4,8 +4,12 @@
 //!
 //! This SDK provides:
 //! - REST client for trading operations
+//! - Admin client for test/dev operations, authenticated with an admin token
+//! - Blocking REST client (with the `blocking` feature)
 //! - WebSocket client for real-time data
-//! - Type-safe API using backend types
+//! - Order tracking (place and await fill)
+//! - Local orderbook kept current over WebSocket
+//! - Type-safe API using the shared `exchange-protocol` types
 //! - Caching for markets and tokens
 //! - Enhancement service for display values
 //! - Formatting utilities
@@ -35,33 +39,39 @@
 //! }
 //! ```
 
+pub mod admin;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod cache;
 pub mod client;
 pub mod enhancement;
 pub mod error;
 pub mod format;
 pub mod logger;
+pub mod orderbook;
+pub mod tracking;
 pub mod websocket;
 
-pub use cache::{CacheService, CacheStats};
-pub use client::ExchangeClient;
+pub use admin::AdminClient;
+pub use cache::{CacheService, CacheStats, MetadataCache};
+pub use client::{ExchangeClient, ExchangeClientBuilder, FillsExport};
 pub use enhancement::{
     EnhancedBalance, EnhancedOrder, EnhancedOrderbookLevel, EnhancedTrade, EnhancementService,
 };
 pub use error::{SdkError, SdkResult};
 pub use format::{format_number, format_price, format_size, to_atoms, to_display_value};
 pub use logger::{ConsoleLogger, LogLevel, Logger, NoopLogger};
+pub use orderbook::{LocalOrderbook, SharedOrderbook};
+pub use tracking::{OrderTracker, TrackedOrder};
 pub use websocket::{WebSocketClient, WebSocketHandle};
 
-// Re-export backend types for convenience
-pub use backend::models::api::{
+// Re-export protocol types for convenience
+pub use exchange_protocol::api::{
     ApiCandle, CandlesRequest, CandlesResponse, ClientMessage, OrderCancelled, SubscriptionChannel,
 };
-pub use backend::models::domain::*;
-
-/// SDK-specific OrderPlaced with domain types (pure u128)
-#[derive(Debug, Clone)]
-pub struct OrderPlaced {
-    pub order: Order,
-    pub trades: Vec<Trade>,
-}
+pub use exchange_protocol::convert::{
+    atoms_to_decimal, decimal_to_atoms, AmountFormat, AMOUNT_FORMAT_HEADER,
+};
+pub use exchange_protocol::domain::*;
+pub use exchange_protocol::error_code::ErrorCode;
+pub use exchange_protocol::symbol::SymbolRegistry;
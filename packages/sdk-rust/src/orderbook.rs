@@ -0,0 +1,243 @@
+//! Local orderbook
+//!
+//! Keeps a copy of one market's aggregated book current from the WebSocket
+//! orderbook channel, so bots can size orders against depth without a REST
+//! round trip per decision. Every orderbook message is a full snapshot of
+//! the market, so the copy is replaced rather than patched.
+
+use crate::error::{SdkError, SdkResult};
+use crate::websocket::WebSocketClient;
+use exchange_protocol::api::{OrderbookData, PriceLevel, SubscriptionChannel};
+use exchange_protocol::domain::{OrderbookLevel, Side};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// A local orderbook shared with the task keeping it current
+pub type SharedOrderbook = Arc<RwLock<LocalOrderbook>>;
+
+/// One market's book as last published by the exchange
+#[derive(Debug, Clone)]
+pub struct LocalOrderbook {
+    market_id: String,
+    bids: Vec<OrderbookLevel>, // Sorted by price descending (highest first)
+    asks: Vec<OrderbookLevel>, // Sorted by price ascending (lowest first)
+    updated_at: Option<Instant>,
+}
+
+impl LocalOrderbook {
+    /// An empty book for a market, not yet synced
+    pub fn new(market_id: impl Into<String>) -> Self {
+        Self {
+            market_id: market_id.into(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            updated_at: None,
+        }
+    }
+
+    /// Subscribe to a market's book and keep a shared copy of it current
+    ///
+    /// The copy is empty until the book next changes, since the exchange
+    /// publishes the book on changes only, and is emptied again if the
+    /// connection closes.
+    pub async fn follow(ws: &WebSocketClient, market_id: &str) -> SdkResult<SharedOrderbook> {
+        let mut handle = ws.connect().await?;
+        handle.subscribe(
+            SubscriptionChannel::Orderbook,
+            Some(market_id.to_string()),
+            None,
+        )?;
+
+        let book = Arc::new(RwLock::new(LocalOrderbook::new(market_id)));
+        let shared = book.clone();
+        tokio::spawn(async move {
+            while let Some(message) = handle.recv().await {
+                let mut book = shared.write().unwrap();
+                // The exchange acknowledges with the canonical id of whatever alias was given
+                if message.get("type").and_then(|v| v.as_str()) == Some("subscribed") {
+                    if let Some(market_id) = message.get("market_id").and_then(|v| v.as_str()) {
+                        book.market_id = market_id.to_string();
+                    }
+                    continue;
+                }
+                if let Err(e) = book.apply(&message) {
+                    log::warn!("Ignoring {} book update: {}", book.market_id, e);
+                }
+            }
+            shared.write().unwrap().clear();
+        });
+
+        Ok(book)
+    }
+
+    /// Fold a WebSocket message into the book; returns whether it was this
+    /// market's book
+    pub fn apply(&mut self, message: &serde_json::Value) -> SdkResult<bool> {
+        if message.get("type").and_then(|v| v.as_str()) != Some("orderbook") {
+            return Ok(false);
+        }
+        let data: OrderbookData = serde_json::from_value(
+            message
+                .get("orderbook")
+                .cloned()
+                .ok_or_else(|| SdkError::InvalidResponse("Missing orderbook".to_string()))?,
+        )?;
+        if data.market_id != self.market_id {
+            return Ok(false);
+        }
+
+        self.replace(levels(&data.bids)?, levels(&data.asks)?);
+        Ok(true)
+    }
+
+    /// Replace both sides of the book, each sorted best price first
+    pub fn replace(&mut self, bids: Vec<OrderbookLevel>, asks: Vec<OrderbookLevel>) {
+        self.bids = bids;
+        self.asks = asks;
+        self.updated_at = Some(Instant::now());
+    }
+
+    /// Forget the book until the next snapshot
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.updated_at = None;
+    }
+
+    pub fn market_id(&self) -> &str {
+        &self.market_id
+    }
+
+    pub fn bids(&self) -> &[OrderbookLevel] {
+        &self.bids
+    }
+
+    pub fn asks(&self) -> &[OrderbookLevel] {
+        &self.asks
+    }
+
+    pub fn best_bid(&self) -> Option<u128> {
+        self.bids.first().map(|level| level.price)
+    }
+
+    pub fn best_ask(&self) -> Option<u128> {
+        self.asks.first().map(|level| level.price)
+    }
+
+    /// Whether a snapshot has arrived since the book was created or cleared
+    pub fn is_synced(&self) -> bool {
+        self.updated_at.is_some()
+    }
+
+    /// Time since the last snapshot, if any
+    pub fn age(&self) -> Option<Duration> {
+        self.updated_at.map(|updated_at| updated_at.elapsed())
+    }
+
+    /// Size resting in the best `levels` price levels of one side of the book
+    pub fn depth(&self, side: Side, levels: usize) -> u128 {
+        let book = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        book.iter()
+            .take(levels)
+            .fold(0u128, |depth, level| depth.saturating_add(level.size))
+    }
+
+    /// Size a taker on `side` could take from the best `levels` price levels
+    /// across the book
+    pub fn liquidity_for(&self, side: Side, levels: usize) -> u128 {
+        match side {
+            Side::Buy => self.depth(Side::Sell, levels),
+            Side::Sell => self.depth(Side::Buy, levels),
+        }
+    }
+}
+
+fn levels(levels: &[PriceLevel]) -> SdkResult<Vec<OrderbookLevel>> {
+    levels
+        .iter()
+        .map(|level| {
+            Ok(OrderbookLevel {
+                price: level
+                    .price
+                    .parse()
+                    .map_err(|_| SdkError::InvalidResponse("Invalid level price".to_string()))?,
+                size: level
+                    .size
+                    .parse()
+                    .map_err(|_| SdkError::InvalidResponse("Invalid level size".to_string()))?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(market_id: &str) -> serde_json::Value {
+        json!({
+            "type": "orderbook",
+            "orderbook": {
+                "market_id": market_id,
+                "bids": [
+                    {"price": "100", "size": "10"},
+                    {"price": "99", "size": "20"},
+                    {"price": "98", "size": "30"},
+                    {"price": "97", "size": "40"}
+                ],
+                "asks": [
+                    {"price": "101", "size": "5"},
+                    {"price": "102", "size": "15"}
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_snapshot_replaces_book() {
+        let mut book = LocalOrderbook::new("BTC/USDC");
+        assert!(!book.is_synced());
+
+        assert!(book.apply(&snapshot("BTC/USDC")).unwrap());
+        assert!(book.is_synced());
+        assert_eq!(book.best_bid(), Some(100));
+        assert_eq!(book.best_ask(), Some(101));
+
+        book.apply(&json!({
+            "type": "orderbook",
+            "orderbook": {"market_id": "BTC/USDC", "bids": [], "asks": [{"price": "103", "size": "1"}]}
+        }))
+        .unwrap();
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.asks().len(), 1);
+    }
+
+    #[test]
+    fn test_ignores_other_messages() {
+        let mut book = LocalOrderbook::new("BTC/USDC");
+
+        assert!(!book.apply(&snapshot("ETH/USDC")).unwrap());
+        assert!(!book.apply(&json!({"type": "pong"})).unwrap());
+        assert!(!book.is_synced());
+    }
+
+    #[test]
+    fn test_depth_over_top_levels() {
+        let mut book = LocalOrderbook::new("BTC/USDC");
+        book.apply(&snapshot("BTC/USDC")).unwrap();
+
+        assert_eq!(book.depth(Side::Buy, 3), 60);
+        assert_eq!(book.depth(Side::Sell, 3), 20);
+        // Buyers take from the asks and sellers from the bids
+        assert_eq!(book.liquidity_for(Side::Buy, 1), 5);
+        assert_eq!(book.liquidity_for(Side::Sell, 2), 30);
+
+        book.clear();
+        assert_eq!(book.liquidity_for(Side::Sell, 3), 0);
+        assert!(!book.is_synced());
+    }
+}
@@ -0,0 +1,303 @@
+//! Order tracking helpers
+//!
+//! Places an order over REST and follows it over WebSocket until it reaches a
+//! terminal state (filled or cancelled), so taker-style bots and tests don't
+//! have to poll or hand-roll the subscription logic.
+
+use crate::client::ExchangeClient;
+use crate::error::{SdkError, SdkResult};
+use crate::websocket::{WebSocketClient, WebSocketHandle};
+use chrono::{TimeZone, Utc};
+use exchange_protocol::api::{SubscriptionChannel, TradeData};
+use exchange_protocol::domain::{Order, OrderStatus, OrderType, Side, Trade};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::time::{timeout_at, Instant};
+
+/// Final state of a tracked order
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    /// Order with its last known status and filled size
+    pub order: Order,
+    /// Every fill seen for the order, including those returned at placement
+    pub trades: Vec<Trade>,
+}
+
+impl TrackedOrder {
+    /// Whether the order reached a terminal state
+    pub fn is_terminal(&self) -> bool {
+        is_terminal(self.order.status)
+    }
+}
+
+/// Places orders and waits for them to reach a terminal state
+pub struct OrderTracker {
+    client: ExchangeClient,
+    ws: WebSocketClient,
+}
+
+impl OrderTracker {
+    /// Create a tracker from a REST client and a WebSocket client pointing at the same exchange
+    pub fn new(client: ExchangeClient, ws: WebSocketClient) -> Self {
+        Self { client, ws }
+    }
+
+    /// Place an order and wait until it is filled or cancelled
+    ///
+    /// Subscribes to the user's order and fill channels before placing the
+    /// order so no update can be missed. Returns `SdkError::Timeout` if the
+    /// order is still open when `timeout` elapses.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_and_await(
+        &self,
+        user_address: String,
+        market_id: String,
+        side: Side,
+        order_type: OrderType,
+        price: String,
+        size: String,
+        signature: String,
+        timeout: Duration,
+    ) -> SdkResult<TrackedOrder> {
+        let deadline = Instant::now() + timeout;
+
+        let mut handle = self.ws.connect().await?;
+        for channel in [
+            SubscriptionChannel::UserOrders,
+            SubscriptionChannel::UserFills,
+        ] {
+            handle.subscribe(channel, None, Some(user_address.clone()))?;
+            wait_for_subscribed(&mut handle, channel, deadline).await?;
+        }
+
+        let placed = self
+            .client
+            .place_order(
+                user_address,
+                market_id,
+                side,
+                order_type,
+                price,
+                size,
+                signature,
+            )
+            .await?;
+
+        let mut tracked = TrackedOrder {
+            order: placed.order,
+            trades: placed.trades,
+        };
+
+        while !tracked.is_terminal() {
+            let message = match timeout_at(deadline, handle.recv()).await {
+                Ok(Some(message)) => message,
+                Ok(None) => {
+                    return Err(SdkError::ConnectionError(
+                        "WebSocket closed while awaiting order".to_string(),
+                    ))
+                }
+                Err(_) => return Err(SdkError::Timeout),
+            };
+            apply_update(&mut tracked, message)?;
+        }
+
+        Ok(tracked)
+    }
+}
+
+fn is_terminal(status: OrderStatus) -> bool {
+    matches!(status, OrderStatus::Filled | OrderStatus::Cancelled)
+}
+
+/// Wait for the server to acknowledge a subscription
+async fn wait_for_subscribed(
+    handle: &mut WebSocketHandle,
+    channel: SubscriptionChannel,
+    deadline: Instant,
+) -> SdkResult<()> {
+    let expected = serde_json::to_value(channel)?;
+    loop {
+        match timeout_at(deadline, handle.recv()).await {
+            Ok(Some(message)) => {
+                if message.get("type").and_then(|v| v.as_str()) == Some("subscribed")
+                    && message.get("channel") == Some(&expected)
+                {
+                    return Ok(());
+                }
+            }
+            Ok(None) => {
+                return Err(SdkError::ConnectionError(
+                    "WebSocket closed before subscription was acknowledged".to_string(),
+                ))
+            }
+            Err(_) => return Err(SdkError::Timeout),
+        }
+    }
+}
+
+/// Fold a WebSocket message into the tracked order state
+fn apply_update(tracked: &mut TrackedOrder, message: serde_json::Value) -> SdkResult<()> {
+    let order_id = tracked.order.id.to_string();
+
+    match message.get("type").and_then(|v| v.as_str()) {
+        Some("user_order") => {
+            if message.get("order_id").and_then(|v| v.as_str()) != Some(order_id.as_str()) {
+                return Ok(());
+            }
+
+            let status = message
+                .get("status")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| SdkError::InvalidResponse("Missing order status".to_string()))?;
+            tracked.order.status =
+                OrderStatus::from_str(status).map_err(SdkError::InvalidResponse)?;
+
+            // Cancellation updates don't carry the filled size, so never move it backwards
+            if let Some(filled) = message
+                .get("filled_size")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.parse::<u128>().ok())
+            {
+                tracked.order.filled_size = tracked.order.filled_size.max(filled);
+            }
+        }
+        Some("user_fill") => {
+            let data: TradeData = serde_json::from_value(
+                message
+                    .get("trade")
+                    .cloned()
+                    .ok_or_else(|| SdkError::InvalidResponse("Missing trade".to_string()))?,
+            )?;
+
+            let ours = data.buyer_order_id == order_id || data.seller_order_id == order_id;
+            let seen = tracked.trades.iter().any(|t| t.id.to_string() == data.id);
+            if ours && !seen {
+                tracked.trades.push(trade_from_data(data)?);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn trade_from_data(data: TradeData) -> SdkResult<Trade> {
+    let parse_err = |field: &str| SdkError::InvalidResponse(format!("Invalid trade {}", field));
+
+    Ok(Trade {
+        id: data.id.parse().map_err(|_| parse_err("id"))?,
+        market_id: data.market_id,
+        buyer_address: data.buyer_address,
+        seller_address: data.seller_address,
+        buyer_order_id: data
+            .buyer_order_id
+            .parse()
+            .map_err(|_| parse_err("buyer_order_id"))?,
+        seller_order_id: data
+            .seller_order_id
+            .parse()
+            .map_err(|_| parse_err("seller_order_id"))?,
+        price: data.price.parse().map_err(|_| parse_err("price"))?,
+        size: data.size.parse().map_err(|_| parse_err("size"))?,
+        side: data.side,
+        timestamp: Utc
+            .timestamp_opt(data.timestamp, 0)
+            .single()
+            .ok_or_else(|| parse_err("timestamp"))?,
+        rfq: data.rfq,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exchange_protocol::domain::{TimeInForce, TriggerSource};
+    use serde_json::json;
+
+    fn pending_order() -> TrackedOrder {
+        let now = Utc::now();
+        TrackedOrder {
+            order: Order {
+                id: "7b8f3c1e-3c55-4b5e-9d2c-2f4f0a9b6d11".parse().unwrap(),
+                user_address: "alice".to_string(),
+                market_id: "BTC/USDC".to_string(),
+                price: 50_000,
+                size: 100,
+                side: Side::Buy,
+                order_type: OrderType::Limit,
+                status: OrderStatus::Pending,
+                filled_size: 0,
+                created_at: now,
+                updated_at: now,
+                cancel_reason: None,
+                trigger_price: None,
+                trigger_source: TriggerSource::LastTrade,
+                time_in_force: TimeInForce::Gtc,
+                expires_at: None,
+            },
+            trades: vec![],
+        }
+    }
+
+    #[test]
+    fn test_user_order_updates_status() {
+        let mut tracked = pending_order();
+        let id = tracked.order.id.to_string();
+
+        apply_update(
+            &mut tracked,
+            json!({"type": "user_order", "order_id": id, "status": "partially_filled", "filled_size": "40"}),
+        )
+        .unwrap();
+        assert_eq!(tracked.order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(tracked.order.filled_size, 40);
+        assert!(!tracked.is_terminal());
+
+        // Cancellation reports filled_size 0; the known fill must be kept
+        apply_update(
+            &mut tracked,
+            json!({"type": "user_order", "order_id": id, "status": "cancelled", "filled_size": "0"}),
+        )
+        .unwrap();
+        assert_eq!(tracked.order.status, OrderStatus::Cancelled);
+        assert_eq!(tracked.order.filled_size, 40);
+        assert!(tracked.is_terminal());
+    }
+
+    #[test]
+    fn test_ignores_other_orders() {
+        let mut tracked = pending_order();
+
+        apply_update(
+            &mut tracked,
+            json!({"type": "user_order", "order_id": "other", "status": "filled", "filled_size": "100"}),
+        )
+        .unwrap();
+        assert_eq!(tracked.order.status, OrderStatus::Pending);
+    }
+
+    #[test]
+    fn test_user_fill_collected_once() {
+        let mut tracked = pending_order();
+        let fill = json!({
+            "type": "user_fill",
+            "trade": {
+                "id": "0d5a2f6e-8d0b-4c1c-a3a4-5e0f1b2c3d4e",
+                "market_id": "BTC/USDC",
+                "buyer_address": "alice",
+                "seller_address": "bob",
+                "buyer_order_id": tracked.order.id.to_string(),
+                "seller_order_id": "1d5a2f6e-8d0b-4c1c-a3a4-5e0f1b2c3d4e",
+                "price": "50000",
+                "size": "100",
+                "side": "buy",
+                "timestamp": 1_700_000_000
+            }
+        });
+
+        apply_update(&mut tracked, fill.clone()).unwrap();
+        apply_update(&mut tracked, fill).unwrap();
+        assert_eq!(tracked.trades.len(), 1);
+        assert_eq!(tracked.trades[0].size, 100);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::{SdkError, SdkResult};
-use backend::models::api::{ClientMessage, SubscriptionChannel};
+use exchange_protocol::api::{ClientMessage, SubscriptionChannel};
 use futures_util::{SinkExt, StreamExt};
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -155,6 +155,7 @@ impl WebSocketHandle {
                 channel,
                 market_id,
                 user_address,
+                interval: None,
             })
             .map_err(|e| SdkError::WebSocketError(e.to_string()))
     }
@@ -171,6 +172,33 @@ impl WebSocketHandle {
                 channel,
                 market_id,
                 user_address,
+                interval: None,
+            })
+            .map_err(|e| SdkError::WebSocketError(e.to_string()))
+    }
+
+    /// Subscribe to a market's live bars at one interval (1m, 5m, 15m, 1h or 1d)
+    ///
+    /// Subscribe once per interval to follow several of the same market.
+    pub fn subscribe_candles(&self, market_id: String, interval: String) -> SdkResult<()> {
+        self.tx
+            .send(ClientMessage::Subscribe {
+                channel: SubscriptionChannel::Candles,
+                market_id: Some(market_id),
+                user_address: None,
+                interval: Some(interval),
+            })
+            .map_err(|e| SdkError::WebSocketError(e.to_string()))
+    }
+
+    /// Unsubscribe from a market's live bars at one interval
+    pub fn unsubscribe_candles(&self, market_id: String, interval: String) -> SdkResult<()> {
+        self.tx
+            .send(ClientMessage::Unsubscribe {
+                channel: SubscriptionChannel::Candles,
+                market_id: Some(market_id),
+                user_address: None,
+                interval: Some(interval),
             })
             .map_err(|e| SdkError::WebSocketError(e.to_string()))
     }
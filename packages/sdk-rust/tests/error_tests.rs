@@ -1,9 +1,10 @@
 /// SDK error handling and validation tests
 ///
 /// These tests verify that the SDK properly handles errors and edge cases.
+#[allow(dead_code)]
 mod helpers;
 
-use backend::models::domain::{OrderType, Side};
+use exchange_protocol::domain::{OrderType, Side};
 use helpers::TestExchange;
 
 // ============================================================================
@@ -1,5 +1,13 @@
-use exchange_sdk::ExchangeClient;
-use exchange_test_utils::TestServer;
+use exchange_protocol::domain::{Order, OrderType, Side};
+use exchange_sdk::{AdminClient, ExchangeClient, OrderTracker, TrackedOrder, WebSocketClient};
+use exchange_test_utils::{TestServer, TEST_ADMIN_TOKEN};
+use std::time::Duration;
+
+/// Account that rests the orders of a seeded orderbook
+pub const SEED_MAKER: &str = "seed_maker";
+
+/// How long `fund_and_trade` waits for an order to fill
+const FILL_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// High-level test fixture for SDK testing
 ///
@@ -8,6 +16,7 @@ use exchange_test_utils::TestServer;
 pub struct TestExchange {
     pub server: TestServer,
     pub client: ExchangeClient,
+    pub admin: AdminClient,
     pub market_id: String,
     pub base_ticker: String,
     pub quote_ticker: String,
@@ -68,17 +77,18 @@ impl TestExchange {
     ) -> anyhow::Result<Self> {
         let server = TestServer::start().await?;
         let client = ExchangeClient::new(&server.base_url);
+        let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
 
         // Setup tokens via admin API
-        client
-            .admin_create_token(
+        admin
+            .create_token(
                 base.to_string(),
                 base_decimals as u8,
                 format!("{} Token", base),
             )
             .await?;
-        client
-            .admin_create_token(
+        admin
+            .create_token(
                 quote.to_string(),
                 quote_decimals as u8,
                 format!("{} Token", quote),
@@ -86,8 +96,8 @@ impl TestExchange {
             .await?;
 
         // Setup market via admin API
-        let market = client
-            .admin_create_market(
+        let market = admin
+            .create_market(
                 base.to_string(),
                 quote.to_string(),
                 1000,    // tick_size
@@ -101,6 +111,7 @@ impl TestExchange {
         Ok(Self {
             server,
             client,
+            admin,
             market_id: market.id,
             base_ticker: base.to_string(),
             quote_ticker: quote.to_string(),
@@ -120,8 +131,8 @@ impl TestExchange {
     ) -> anyhow::Result<()> {
         // Use admin faucet to give tokens (this also creates user if needed)
         if base_amount > 0 {
-            self.client
-                .admin_faucet(
+            self.admin
+                .faucet(
                     address.to_string(),
                     self.base_ticker.clone(),
                     base_amount.to_string(),
@@ -129,8 +140,8 @@ impl TestExchange {
                 .await?;
         }
         if quote_amount > 0 {
-            self.client
-                .admin_faucet(
+            self.admin
+                .faucet(
                     address.to_string(),
                     self.quote_ticker.clone(),
                     quote_amount.to_string(),
@@ -168,15 +179,15 @@ impl TestExchange {
 
     /// Convert base token atoms to human-readable amount
     ///
-    /// Example: `from_base_atoms(10_500_000)` with 6 decimals = 10.5
-    pub fn from_base_atoms(&self, atoms: u128) -> f64 {
+    /// Example: `to_base_amount(10_500_000)` with 6 decimals = 10.5
+    pub fn to_base_amount(&self, atoms: u128) -> f64 {
         atoms as f64 / 10f64.powi(self.base_decimals as i32)
     }
 
     /// Convert quote token atoms to human-readable amount
     ///
-    /// Example: `from_quote_atoms(50_000_000_000)` with 6 decimals = 50000.0
-    pub fn from_quote_atoms(&self, atoms: u128) -> f64 {
+    /// Example: `to_quote_amount(50_000_000_000)` with 6 decimals = 50000.0
+    pub fn to_quote_amount(&self, atoms: u128) -> f64 {
         atoms as f64 / 10f64.powi(self.quote_decimals as i32)
     }
 
@@ -187,4 +198,121 @@ impl TestExchange {
     pub fn price_to_atoms(&self, price: f64) -> u128 {
         (price * 10f64.powi(self.quote_decimals as i32)) as u128
     }
+
+    // ===== Scenario Helpers =====
+
+    /// Rest `levels` bids and asks of `size` base atoms each around `mid`, one
+    /// tick apart, from [`SEED_MAKER`]
+    ///
+    /// The best bid and ask sit one tick either side of `mid`. Returns the
+    /// resting orders, bids first, each side best price first.
+    ///
+    /// ```rust,ignore
+    /// let mid = fixture.price_to_atoms(50000.0);
+    /// fixture.seed_orderbook(&fixture.market_id, mid, 3, fixture.to_base_atoms(1.0)).await?;
+    /// ```
+    pub async fn seed_orderbook(
+        &self,
+        market_id: &str,
+        mid: u128,
+        levels: usize,
+        size: u128,
+    ) -> anyhow::Result<Vec<Order>> {
+        let market = self.client.get_market(market_id).await?;
+        let prices = |side: Side| {
+            (1..=levels as u128).map(move |level| match side {
+                Side::Buy => mid - level * market.tick_size,
+                Side::Sell => mid + level * market.tick_size,
+            })
+        };
+
+        let mut orders = Vec::with_capacity(levels * 2);
+        for side in [Side::Buy, Side::Sell] {
+            for price in prices(side) {
+                self.fund_order(SEED_MAKER, market_id, side, price, size)
+                    .await?;
+                let placed = self
+                    .client
+                    .place_order(
+                        SEED_MAKER.to_string(),
+                        market_id.to_string(),
+                        side,
+                        OrderType::Limit,
+                        price.to_string(),
+                        size.to_string(),
+                        "seed".to_string(),
+                    )
+                    .await?;
+                orders.push(placed.order);
+            }
+        }
+
+        Ok(orders)
+    }
+
+    /// Fund `user` for a limit order, place it and wait until it has filled
+    ///
+    /// Fails if the order hasn't completely filled within ten seconds.
+    pub async fn fund_and_trade(
+        &self,
+        user: &str,
+        market_id: &str,
+        side: Side,
+        price: u128,
+        size: u128,
+    ) -> anyhow::Result<TrackedOrder> {
+        self.fund_order(user, market_id, side, price, size).await?;
+
+        let tracker = OrderTracker::new(
+            self.client.clone(),
+            WebSocketClient::new(&self.server.ws_url),
+        );
+        let tracked = tracker
+            .place_and_await(
+                user.to_string(),
+                market_id.to_string(),
+                side,
+                OrderType::Limit,
+                price.to_string(),
+                size.to_string(),
+                "test_sig".to_string(),
+                FILL_TIMEOUT,
+            )
+            .await?;
+        anyhow::ensure!(
+            tracked.order.filled_size == tracked.order.size,
+            "Order {} only filled {} of {}",
+            tracked.order.id,
+            tracked.order.filled_size,
+            tracked.order.size
+        );
+
+        Ok(tracked)
+    }
+
+    /// Give `user` exactly what a limit order locks: base tokens to sell, or
+    /// quote tokens to buy
+    async fn fund_order(
+        &self,
+        user: &str,
+        market_id: &str,
+        side: Side,
+        price: u128,
+        size: u128,
+    ) -> anyhow::Result<()> {
+        let market = self.client.get_market(market_id).await?;
+        let (ticker, amount) = match side {
+            Side::Sell => (market.base_ticker, size),
+            Side::Buy => {
+                let base = self.client.get_token(&market.base_ticker).await?;
+                let quote = price * size / 10u128.pow(base.decimals as u32);
+                (market.quote_ticker, quote)
+            }
+        };
+
+        self.admin
+            .faucet(user.to_string(), ticker, amount.to_string())
+            .await?;
+        Ok(())
+    }
 }
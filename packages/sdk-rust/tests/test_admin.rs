@@ -1,14 +1,14 @@
 /// Quick test of admin endpoint
-use exchange_sdk::ExchangeClient;
-use exchange_test_utils::TestServer;
+use exchange_sdk::AdminClient;
+use exchange_test_utils::{TestServer, TEST_ADMIN_TOKEN};
 
 #[tokio::test]
 async fn test_admin_create_token() {
     let server = TestServer::start().await.expect("Failed to start server");
-    let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
 
-    let result = client
-        .admin_create_token("BTC".to_string(), 18, "Bitcoin".to_string())
+    let result = admin
+        .create_token("BTC".to_string(), 18, "Bitcoin".to_string())
         .await;
 
     match &result {
@@ -22,22 +22,22 @@ async fn test_admin_create_token() {
 #[tokio::test]
 async fn test_admin_create_market() {
     let server = TestServer::start().await.expect("Failed to start server");
-    let client = ExchangeClient::new(&server.base_url);
+    let admin = AdminClient::new(&server.base_url, TEST_ADMIN_TOKEN);
 
     // First create tokens
-    client
-        .admin_create_token("BTC".to_string(), 18, "Bitcoin".to_string())
+    admin
+        .create_token("BTC".to_string(), 18, "Bitcoin".to_string())
         .await
         .expect("Failed to create BTC");
 
-    client
-        .admin_create_token("USDC".to_string(), 18, "USD Coin".to_string())
+    admin
+        .create_token("USDC".to_string(), 18, "USD Coin".to_string())
         .await
         .expect("Failed to create USDC");
 
     // Then create market
-    let result = client
-        .admin_create_market(
+    let result = admin
+        .create_market(
             "BTC".to_string(),
             "USDC".to_string(),
             1000,
@@ -1,5 +1,5 @@
 /// Raw HTTP test of admin endpoint
-use exchange_test_utils::TestServer;
+use exchange_test_utils::{TestServer, TEST_ADMIN_TOKEN};
 
 #[tokio::test]
 async fn test_admin_raw_http() {
@@ -17,6 +17,7 @@ async fn test_admin_raw_http() {
 
     let resp = client
         .post(format!("{}/api/admin", server.base_url))
+        .bearer_auth(TEST_ADMIN_TOKEN)
         .json(&token_req)
         .send()
         .await
@@ -35,6 +36,7 @@ async fn test_admin_raw_http() {
 
     client
         .post(format!("{}/api/admin", server.base_url))
+        .bearer_auth(TEST_ADMIN_TOKEN)
         .json(&token_req2)
         .send()
         .await
@@ -53,6 +55,7 @@ async fn test_admin_raw_http() {
 
     let resp = client
         .post(format!("{}/api/admin", server.base_url))
+        .bearer_auth(TEST_ADMIN_TOKEN)
         .json(&market_req)
         .send()
         .await
@@ -2,10 +2,11 @@
 ///
 /// These tests verify the SDK through realistic trading scenarios,
 /// using ONLY the public REST and WebSocket APIs (no direct DB access for verification).
+#[allow(dead_code)]
 mod helpers;
 
-use backend::models::domain::{OrderType, Side};
-use helpers::TestExchange;
+use exchange_protocol::domain::{OrderType, Side};
+use helpers::{TestExchange, SEED_MAKER};
 
 // ============================================================================
 // Basic Trading Workflows
@@ -54,7 +55,7 @@ async fn test_complete_trading_workflow() {
 
     assert_eq!(
         alice_order.order.status,
-        backend::models::domain::OrderStatus::Pending
+        exchange_protocol::domain::OrderStatus::Pending
     );
     assert_eq!(alice_order.trades.len(), 0); // No match yet
 
@@ -87,7 +88,7 @@ async fn test_complete_trading_workflow() {
 
     assert_eq!(
         bob_order.order.status,
-        backend::models::domain::OrderStatus::Filled
+        exchange_protocol::domain::OrderStatus::Filled
     );
     assert_eq!(bob_order.trades.len(), 1); // Matched!
 
@@ -170,7 +171,7 @@ async fn test_partial_fill() {
 
     assert_eq!(
         sell_order.order.status,
-        backend::models::domain::OrderStatus::Pending
+        exchange_protocol::domain::OrderStatus::Pending
     );
 
     // Buyer only wants 3 BTC
@@ -191,7 +192,7 @@ async fn test_partial_fill() {
     // Buyer order should be fully filled
     assert_eq!(
         buy_order.order.status,
-        backend::models::domain::OrderStatus::Filled
+        exchange_protocol::domain::OrderStatus::Filled
     );
     assert_eq!(buy_order.order.filled_size, 3_000_000);
 
@@ -208,7 +209,7 @@ async fn test_partial_fill() {
     assert_eq!(remaining_order.size, 10_000_000); // Original 10
     assert_eq!(
         remaining_order.status,
-        backend::models::domain::OrderStatus::PartiallyFilled
+        exchange_protocol::domain::OrderStatus::PartiallyFilled
     );
 }
 
@@ -263,7 +264,7 @@ async fn test_order_cancellation() {
     // Should be empty or only contain non-pending orders
     let pending_orders: Vec<_> = orders
         .iter()
-        .filter(|o| o.status == backend::models::domain::OrderStatus::Pending)
+        .filter(|o| o.status == exchange_protocol::domain::OrderStatus::Pending)
         .collect();
     assert_eq!(pending_orders.len(), 0);
 }
@@ -370,3 +371,39 @@ async fn test_multiple_concurrent_orders() {
 
     assert!(orders.len() >= 5);
 }
+
+// ============================================================================
+// Scenarios
+// ============================================================================
+
+#[tokio::test]
+async fn test_taker_walks_a_seeded_book() {
+    let fixture = TestExchange::new()
+        .await
+        .expect("Failed to create test exchange");
+    let market_id = fixture.market_id.clone();
+    let mid = fixture.price_to_atoms(50000.0);
+    let size = fixture.to_base_atoms(1.0);
+    let tick = 1000; // The fixture market's tick size
+
+    let seeded = fixture
+        .seed_orderbook(&market_id, mid, 3, size)
+        .await
+        .expect("Failed to seed orderbook");
+    assert_eq!(seeded.len(), 6);
+
+    // Buying two lots up to the second ask takes both of the best asks
+    let second_ask = mid + 2 * tick;
+    let tracked = fixture
+        .fund_and_trade("bob", &market_id, Side::Buy, second_ask, 2 * size)
+        .await
+        .expect("Bob's order should fill");
+    let mut prices: Vec<u128> = tracked.trades.iter().map(|t| t.price).collect();
+    prices.sort();
+    assert_eq!(prices, vec![mid + tick, second_ask]);
+
+    assert!(tracked
+        .trades
+        .iter()
+        .all(|trade| trade.buyer_address == "bob" && trade.seller_address == SEED_MAKER));
+}
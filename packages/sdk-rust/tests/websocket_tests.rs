@@ -1,9 +1,10 @@
 /// Comprehensive SDK WebSocket tests
 ///
 /// These tests verify real-time event streams using only the WebSocket API.
+#[allow(dead_code)]
 mod helpers;
 
-use backend::models::domain::{OrderType, Side};
+use exchange_protocol::domain::{OrderType, Side};
 use exchange_sdk::{SubscriptionChannel, WebSocketClient};
 use helpers::TestExchange;
 
@@ -99,6 +99,8 @@ export interface ExchangeClientConfig {
   restUrl: string;
   wsUrl: string;
   restTimeout?: number;
+  /** The exchange's admin token, needed for the admin endpoints */
+  adminToken?: string;
   wsReconnectDelays?: number[];
   wsPingInterval?: number;
   logLevel?: LogLevel;
@@ -133,6 +135,7 @@ export class ExchangeClient {
     this.rest = new RestClient({
       baseUrl: cfg.restUrl,
       timeout: cfg.restTimeout,
+      adminToken: cfg.adminToken,
       cache: this.cache,
       enhancer: this.enhancer,
       logger: this.logger,
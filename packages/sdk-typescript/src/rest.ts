@@ -43,6 +43,8 @@ export type CandlesResponse = components["schemas"]["CandlesResponse"];
 export interface RestClientConfig {
   baseUrl: string;
   timeout?: number;
+  /** The exchange's admin token, needed for the admin endpoints */
+  adminToken?: string;
   cache: CacheService;
   enhancer: EnhancementService;
   logger: Logger;
@@ -51,6 +53,7 @@ export interface RestClientConfig {
 export class RestClient {
   private baseUrl: string;
   private timeout: number;
+  private adminToken?: string;
   private cache: CacheService;
   private enhancer: EnhancementService;
   private logger: Logger;
@@ -61,6 +64,7 @@ export class RestClient {
     }
     this.baseUrl = config.baseUrl.replace(/\/$/, ""); // Remove trailing slash
     this.timeout = config.timeout ?? 30000;
+    this.adminToken = config.adminToken;
     this.cache = config.cache;
     this.enhancer = config.enhancer;
     this.logger = config.logger;
@@ -412,7 +416,7 @@ export class RestClient {
       decimals: params.decimals as any, // OpenAPI types u8 as number
       name: params.name,
     };
-    const response = await this.post<AdminResponse>("/api/admin", request);
+    const response = await this.postAdmin(request);
     if (response.type !== "create_token") {
       throw new ApiError("Invalid response type", 500);
     }
@@ -438,7 +442,7 @@ export class RestClient {
       maker_fee_bps: params.makerFeeBps as any, // OpenAPI types i32 as number
       taker_fee_bps: params.takerFeeBps as any,
     };
-    const response = await this.post<AdminResponse>("/api/admin", request);
+    const response = await this.postAdmin(request);
     if (response.type !== "create_market") {
       throw new ApiError("Invalid response type", 500);
     }
@@ -457,7 +461,7 @@ export class RestClient {
       amount: params.amount,
       signature: "admin",
     };
-    const response = await this.post<AdminResponse>("/api/admin", request);
+    const response = await this.postAdmin(request);
     if (response.type !== "faucet") {
       throw new ApiError("Invalid response type", 500);
     }
@@ -486,7 +490,12 @@ export class RestClient {
 
   // ===== HTTP Helpers =====
 
-  private async post<T>(path: string, body: unknown): Promise<T> {
+  private async postAdmin(request: AdminRequest): Promise<AdminResponse> {
+    const headers: Record<string, string> = this.adminToken ? { Authorization: `Bearer ${this.adminToken}` } : {};
+    return this.post<AdminResponse>("/api/admin", request, headers);
+  }
+
+  private async post<T>(path: string, body: unknown, headers: Record<string, string> = {}): Promise<T> {
     const controller = new AbortController();
     const timeoutId = setTimeout(() => controller.abort(), this.timeout);
 
@@ -495,6 +504,7 @@ export class RestClient {
         method: "POST",
         headers: {
           "Content-Type": "application/json",
+          ...headers,
         },
         body: JSON.stringify(body),
         signal: controller.signal,
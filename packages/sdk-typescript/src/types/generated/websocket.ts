@@ -66,6 +66,10 @@ export type ServerMessage =
   | {
       filled_size: string;
       order_id: string;
+      /**
+       * Set when the exchange cancelled the order
+       */
+      reason?: CancelReason | null;
       status: string;
       type: "user_order";
     }
@@ -87,6 +91,12 @@ export type ServerMessage =
 
 export type Side = "buy" | "sell";
 
+/**
+ * Why the exchange, rather than the user, cancelled an order
+ */
+
+export type CancelReason = "kill_switch" | "market_halted" | "account_restricted";
+
 /**
  * Trade data for WebSocket messages (API layer with String fields)
  */
@@ -317,10 +317,24 @@ export interface components {
             /** @enum {string} */
             type: "all_tokens";
         };
+        /** @description Response after successfully cancelling an order */
+        OrderCancelled: {
+            order_id: string;
+        };
+        /** @description Response after successfully placing an order */
+        OrderPlaced: {
+            order: components["schemas"]["ApiOrder"];
+            trades: components["schemas"]["ApiTrade"][];
+        };
         /** @enum {string} */
         OrderStatus: "pending" | "filled" | "partially_filled" | "cancelled";
         /** @enum {string} */
         OrderType: "limit" | "market";
+        /** @description Response after successfully cancelling all orders */
+        OrdersCancelled: {
+            cancelled_order_ids: string[];
+            count: number;
+        };
         /** @enum {string} */
         Side: "buy" | "sell";
         Token: {
@@ -0,0 +1,118 @@
+//! Load test the exchange over REST
+//!
+//! Usage:
+//!   loadtest [--url URL] [--market ID] [--orders N] [--concurrency N]
+//!            [--users N] [--mid PRICE] [--seed N] [--fund]
+//!
+//! Without `--url`, a local TestServer (Postgres + ClickHouse containers) is
+//! started with a BTC/USDC market and funded users. `--fund` against another
+//! server needs its admin token in `ADMIN_TOKEN`.
+
+use anyhow::{bail, Context, Result};
+use exchange_test_utils::load::{fetch_market, fund_users, run_load_test, LoadTestConfig};
+use exchange_test_utils::{helpers, ScenarioConfig, TestServer, TEST_ADMIN_TOKEN};
+
+struct Args {
+    url: Option<String>,
+    market_id: String,
+    orders: usize,
+    concurrency: usize,
+    users: usize,
+    mid_price: u128,
+    seed: u64,
+    fund: bool,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Args {
+            url: None,
+            market_id: "BTC/USDC".to_string(),
+            orders: 5_000,
+            concurrency: 64,
+            users: 20,
+            mid_price: 50_000_000_000,
+            seed: 42,
+            fund: false,
+        };
+
+        let mut iter = std::env::args().skip(1);
+        while let Some(flag) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .with_context(|| format!("{} needs a value", flag))
+            };
+            match flag.as_str() {
+                "--url" => args.url = Some(value()?),
+                "--market" => args.market_id = value()?,
+                "--orders" => args.orders = value()?.parse()?,
+                "--concurrency" => args.concurrency = value()?.parse()?,
+                "--users" => args.users = value()?.parse()?,
+                "--mid" => args.mid_price = value()?.parse()?,
+                "--seed" => args.seed = value()?.parse()?,
+                "--fund" => args.fund = true,
+                other => bail!("Unknown argument: {}", other),
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = Args::parse()?;
+
+    // Keep the local server alive for the duration of the run
+    let mut _server = None;
+    let mut admin_token = std::env::var("ADMIN_TOKEN").unwrap_or_default();
+    let base_url = match args.url.clone() {
+        Some(url) => url,
+        None => {
+            println!("Starting local test server...");
+            let server = TestServer::start().await?;
+            let market = helpers::create_market_with_tokens(&server.test_db, "BTC", "USDC").await?;
+            args.market_id = market.id;
+            args.fund = true;
+            admin_token = TEST_ADMIN_TOKEN.to_string();
+            let url = server.base_url.clone();
+            _server = Some(server);
+            url
+        }
+    };
+
+    let market = fetch_market(&base_url, &args.market_id).await?;
+    let users: Vec<String> = (0..args.users).map(|i| format!("load_{}", i)).collect();
+
+    if args.fund {
+        println!("Funding {} users...", users.len());
+        fund_users(
+            &base_url,
+            &admin_token,
+            &users,
+            &[
+                (market.base_ticker.as_str(), 1_000_000_000_000),
+                (market.quote_ticker.as_str(), 1_000_000_000_000_000),
+            ],
+        )
+        .await?;
+    }
+
+    let user_refs: Vec<&str> = users.iter().map(String::as_str).collect();
+    let config = LoadTestConfig {
+        base_url,
+        scenario: ScenarioConfig::for_market(&market, &user_refs, args.mid_price),
+        seed: args.seed,
+        total_orders: args.orders,
+        concurrency: args.concurrency,
+    };
+
+    println!(
+        "Sending {} orders to {} with concurrency {}...",
+        config.total_orders, market.id, config.concurrency
+    );
+    let report = run_load_test(&config).await?;
+    println!("{}", report);
+
+    Ok(())
+}
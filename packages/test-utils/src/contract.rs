@@ -0,0 +1,434 @@
+use crate::server::TestServer;
+use backend::schema;
+use reqwest::Method;
+use serde_json::Value;
+
+// ============================================================================
+// Contract Tests - Check a Running Server Against the Generated OpenAPI Document
+// ============================================================================
+
+/// One request to send to a [`TestServer`] and check against the spec
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub method: Method,
+    /// Path with any query string, e.g. `/api/markets/BTC%2FUSDC/depth?from=0&to=1`
+    pub path: String,
+    pub body: Option<Value>,
+    pub bearer: Option<String>,
+}
+
+impl Probe {
+    pub fn get(path: impl Into<String>) -> Self {
+        Self {
+            method: Method::GET,
+            path: path.into(),
+            body: None,
+            bearer: None,
+        }
+    }
+
+    pub fn post(path: impl Into<String>, body: Value) -> Self {
+        Self {
+            method: Method::POST,
+            path: path.into(),
+            body: Some(body),
+            bearer: None,
+        }
+    }
+
+    /// Send `token` as a bearer token, for operator endpoints
+    pub fn with_bearer(mut self, token: &str) -> Self {
+        self.bearer = Some(token.to_string());
+        self
+    }
+}
+
+/// The OpenAPI document, used to check requests and responses against it
+///
+/// Covers the subset of OpenAPI 3.1 schemas the backend's document uses:
+/// `$ref`, `type` (including type lists), `enum`, `minimum`, `properties`,
+/// `required`, `additionalProperties`, `items`, `oneOf`, `anyOf` and `allOf`.
+/// Formats are not checked.
+pub struct Contract {
+    spec: Value,
+}
+
+impl Contract {
+    /// The document generated from the handlers, as written to `packages/shared`
+    pub fn load() -> Self {
+        Self::new(schema::openapi().value())
+    }
+
+    pub fn new(spec: Value) -> Self {
+        Self { spec }
+    }
+
+    /// Every documented operation as its method and path template
+    pub fn operations(&self) -> Vec<(Method, String)> {
+        let Some(paths) = self.spec["paths"].as_object() else {
+            return Vec::new();
+        };
+        paths
+            .iter()
+            .flat_map(|(template, operations)| {
+                operations
+                    .as_object()
+                    .into_iter()
+                    .flat_map(|operations| operations.keys())
+                    .filter_map(|method| Method::from_bytes(method.to_uppercase().as_bytes()).ok())
+                    .map(move |method| (method, template.clone()))
+            })
+            .collect()
+    }
+
+    /// The path template of the operation documented for `method` on `path`
+    pub fn operation_for(&self, method: &Method, path: &str) -> Option<String> {
+        let path = path.split('?').next().unwrap_or(path);
+        self.operations()
+            .into_iter()
+            .find(|(documented, template)| documented == method && path_matches(template, path))
+            .map(|(_, template)| template)
+    }
+
+    fn operation(&self, method: &Method, template: &str) -> &Value {
+        &self.spec["paths"][template][method.as_str().to_lowercase()]
+    }
+
+    /// Check `probe` is a documented operation called as documented: the
+    /// required query parameters present and the body matching its schema
+    pub fn check_request(&self, probe: &Probe) -> Vec<String> {
+        let route = format!("{} {}", probe.method, probe.path);
+        let Some(template) = self.operation_for(&probe.method, &probe.path) else {
+            return vec![format!("{}: not a documented operation", route)];
+        };
+        let operation = self.operation(&probe.method, &template);
+
+        let mut violations = Vec::new();
+        let query: Vec<&str> = probe
+            .path
+            .split_once('?')
+            .map(|(_, query)| {
+                query
+                    .split('&')
+                    .map(|pair| pair.split('=').next().unwrap_or(pair))
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+        for parameter in operation["parameters"].as_array().into_iter().flatten() {
+            let name = parameter["name"].as_str().unwrap_or_default();
+            if parameter["in"] == "query" && parameter["required"] == true && !query.contains(&name)
+            {
+                violations.push(format!(
+                    "{}: missing required query parameter '{}'",
+                    route, name
+                ));
+            }
+        }
+
+        let schema = &operation["requestBody"]["content"]["application/json"]["schema"];
+        match &probe.body {
+            Some(body) if !schema.is_null() => {
+                for error in self.validate(schema, body) {
+                    violations.push(format!("{}: request {}", route, error));
+                }
+            }
+            Some(_) => violations.push(format!("{}: the operation takes no JSON body", route)),
+            None if operation["requestBody"]["required"] == true => {
+                violations.push(format!("{}: the operation requires a body", route))
+            }
+            None => {}
+        }
+        violations
+    }
+
+    /// Check a response to `method` on `path`: its status is documented, and
+    /// its body has a documented content type and matches that schema
+    pub fn check_response(
+        &self,
+        method: &Method,
+        path: &str,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Vec<String> {
+        let route = format!("{} {}", method, path);
+        let Some(template) = self.operation_for(method, path) else {
+            return vec![format!("{}: not a documented operation", route)];
+        };
+        let responses = &self.operation(method, &template)["responses"];
+        let response = match &responses[status.to_string()] {
+            Value::Null => &responses["default"],
+            response => response,
+        };
+        if response.is_null() {
+            return vec![format!(
+                "{}: responded {}, which is not documented",
+                route, status
+            )];
+        }
+
+        let Some(content) = response["content"].as_object().filter(|c| !c.is_empty()) else {
+            return Vec::new();
+        };
+        let media_type = content_type
+            .and_then(|content_type| content_type.split(';').next())
+            .map(str::trim)
+            .unwrap_or_default();
+        let Some(media) = content.get(media_type) else {
+            let documented: Vec<&str> = content.keys().map(String::as_str).collect();
+            return vec![format!(
+                "{}: responded {} with content type '{}', documented as {:?}",
+                route, status, media_type, documented
+            )];
+        };
+        if !media_type.ends_with("json") {
+            return Vec::new();
+        }
+
+        match serde_json::from_slice::<Value>(body) {
+            Ok(body) => self
+                .validate(&media["schema"], &body)
+                .into_iter()
+                .map(|error| format!("{}: {} response {}", route, status, error))
+                .collect(),
+            Err(e) => vec![format!(
+                "{}: {} response is not valid JSON: {}",
+                route, status, e
+            )],
+        }
+    }
+
+    /// Send `probe` to `server` and check both ends of it against the spec
+    pub async fn check(&self, server: &TestServer, probe: &Probe) -> anyhow::Result<Vec<String>> {
+        let mut violations = self.check_request(probe);
+
+        let mut request =
+            reqwest::Client::new().request(probe.method.clone(), server.url(&probe.path));
+        if let Some(body) = &probe.body {
+            request = request.json(body);
+        }
+        if let Some(token) = &probe.bearer {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{} {}: {}", probe.method, probe.path, e))?;
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await?;
+
+        violations.extend(self.check_response(
+            &probe.method,
+            &probe.path,
+            status,
+            content_type.as_deref(),
+            &body,
+        ));
+        Ok(violations)
+    }
+
+    /// Send every probe and fail listing each violation, including any
+    /// documented operation no probe reaches
+    pub async fn verify(&self, server: &TestServer, probes: &[Probe]) -> anyhow::Result<()> {
+        let mut violations: Vec<String> = self
+            .operations()
+            .into_iter()
+            .filter(|(method, template)| {
+                !probes.iter().any(|probe| {
+                    probe.method == *method
+                        && path_matches(template, probe.path.split('?').next().unwrap_or_default())
+                })
+            })
+            .map(|(method, template)| format!("{} {}: no probe covers it", method, template))
+            .collect();
+        for probe in probes {
+            violations.extend(self.check(server, probe).await?);
+        }
+
+        if !violations.is_empty() {
+            anyhow::bail!(
+                "{} contract violations:\n  {}",
+                violations.len(),
+                violations.join("\n  ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Errors from checking `value` against `schema`, empty when it matches
+    pub fn validate(&self, schema: &Value, value: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        self.validate_at(schema, value, "$", &mut errors);
+        errors
+    }
+
+    fn validate_at(&self, schema: &Value, value: &Value, at: &str, errors: &mut Vec<String>) {
+        if let Some(reference) = schema["$ref"].as_str() {
+            match self.resolve(reference) {
+                Some(schema) => self.validate_at(schema, value, at, errors),
+                None => errors.push(format!("{}: unresolved $ref '{}'", at, reference)),
+            }
+            return;
+        }
+
+        for schema in schema["allOf"].as_array().into_iter().flatten() {
+            self.validate_at(schema, value, at, errors);
+        }
+        if let Some(variants) = schema["anyOf"].as_array() {
+            if !variants.iter().any(|v| self.validate(v, value).is_empty()) {
+                errors.push(format!("{}: matches none of the anyOf variants", at));
+            }
+        }
+        if let Some(variants) = schema["oneOf"].as_array() {
+            self.validate_one_of(variants, value, at, errors);
+        }
+
+        if let Some(types) = schema_types(schema) {
+            if !types.iter().any(|t| is_type(value, t)) {
+                errors.push(format!(
+                    "{}: expected {}, got {}",
+                    at,
+                    types.join(" or "),
+                    type_name(value)
+                ));
+                return;
+            }
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                errors.push(format!("{}: {} is not one of {:?}", at, value, allowed));
+            }
+        }
+        if let (Some(minimum), Some(number)) = (schema["minimum"].as_f64(), value.as_f64()) {
+            if number < minimum {
+                errors.push(format!(
+                    "{}: {} is below the minimum {}",
+                    at, number, minimum
+                ));
+            }
+        }
+
+        if let Some(object) = value.as_object() {
+            for field in schema["required"].as_array().into_iter().flatten() {
+                let field = field.as_str().unwrap_or_default();
+                if !object.contains_key(field) {
+                    errors.push(format!("{}: missing required field '{}'", at, field));
+                }
+            }
+            let properties = &schema["properties"];
+            for (key, field) in object {
+                let at = format!("{}.{}", at, key);
+                match (&properties[key], &schema["additionalProperties"]) {
+                    (Value::Null, Value::Bool(false)) => {
+                        errors.push(format!("{}: undocumented field", at))
+                    }
+                    (Value::Null, Value::Object(_)) => {
+                        self.validate_at(&schema["additionalProperties"], field, &at, errors)
+                    }
+                    (Value::Null, _) => {}
+                    (property, _) => self.validate_at(property, field, &at, errors),
+                }
+            }
+        }
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (index, item) in array.iter().enumerate() {
+                self.validate_at(items, item, &format!("{}[{}]", at, index), errors);
+            }
+        }
+    }
+
+    /// Exactly one variant must match; on none, report the variant whose
+    /// `type` tag matches the value, since that's the one it meant to be
+    fn validate_one_of(
+        &self,
+        variants: &[Value],
+        value: &Value,
+        at: &str,
+        errors: &mut Vec<String>,
+    ) {
+        let matching = variants
+            .iter()
+            .filter(|variant| self.validate(variant, value).is_empty())
+            .count();
+        match matching {
+            1 => {}
+            0 => {
+                let tagged = variants.iter().find(|variant| {
+                    let tag = &self.resolved(variant)["properties"]["type"]["enum"];
+                    tag.as_array()
+                        .is_some_and(|tag| tag.contains(&value["type"]))
+                });
+                match tagged {
+                    Some(variant) => self.validate_at(variant, value, at, errors),
+                    None => errors.push(format!(
+                        "{}: matches none of the {} oneOf variants",
+                        at,
+                        variants.len()
+                    )),
+                }
+            }
+            n => errors.push(format!(
+                "{}: matches {} oneOf variants, expected exactly one",
+                at, n
+            )),
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.spec.pointer(pointer)
+    }
+
+    /// `schema`, or what it refers to
+    fn resolved<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        schema["$ref"]
+            .as_str()
+            .and_then(|reference| self.resolve(reference))
+            .unwrap_or(schema)
+    }
+}
+
+/// Whether concrete `path` fits `template`, `{param}` segments matching any one segment
+fn path_matches(template: &str, path: &str) -> bool {
+    let template: Vec<&str> = template.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    template.len() == path.len()
+        && template.iter().zip(&path).all(|(expected, actual)| {
+            (expected.starts_with('{') && expected.ends_with('}') && !actual.is_empty())
+                || expected == actual
+        })
+}
+
+fn schema_types(schema: &Value) -> Option<Vec<&str>> {
+    match &schema["type"] {
+        Value::String(t) => Some(vec![t.as_str()]),
+        Value::Array(types) => Some(types.iter().filter_map(Value::as_str).collect()),
+        _ => None,
+    }
+}
+
+fn is_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
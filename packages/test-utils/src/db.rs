@@ -1,6 +1,27 @@
 use backend::db::Db;
 use testcontainers::runners::AsyncRunner;
+use testcontainers::ImageExt;
 use testcontainers_modules::{clickhouse::ClickHouse, postgres::Postgres};
+use tokio::sync::{Mutex, MutexGuard, OnceCell};
+
+/// Set to `1` to share one pair of containers across every test in a process
+///
+/// ```sh
+/// TEST_SHARED_CONTAINERS=1 cargo test -p backend
+/// ```
+pub const SHARED_ENV: &str = "TEST_SHARED_CONTAINERS";
+
+/// Label applied to shared containers, which outlive the test process
+pub const SHARED_LABEL: &str = "exchange-test-utils.shared";
+
+/// PostgreSQL tables cleared between tests in shared mode (migrations table excluded)
+const PG_TABLES: [&str; 6] = ["balances", "trades", "orders", "markets", "tokens", "users"];
+
+/// ClickHouse tables cleared between tests in shared mode (materialized views write into these)
+const CH_TABLES: [&str; 2] = ["exchange.trades", "exchange.candles"];
+
+static SHARED_CONTAINERS: OnceCell<TestContainers> = OnceCell::const_new();
+static SHARED_LEASE: Mutex<()> = Mutex::const_new(());
 
 // ============================================================================
 // Test Containers - Database Setup
@@ -9,6 +30,8 @@ use testcontainers_modules::{clickhouse::ClickHouse, postgres::Postgres};
 /// Container handles for test databases
 pub struct TestContainers {
     pub(crate) db: Db,
+    pub(crate) postgres_url: String,
+    pub(crate) clickhouse_url: String,
     pub(crate) _postgres_container: testcontainers::ContainerAsync<Postgres>,
     pub(crate) _clickhouse_container: testcontainers::ContainerAsync<ClickHouse>,
 }
@@ -31,6 +54,32 @@ impl TestContainers {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to start ClickHouse container: {}", e))?;
 
+        Self::connect(postgres_container, clickhouse_container).await
+    }
+
+    /// Set up containers for shared mode, labeled so leftovers can be found with
+    /// `docker ps --filter label=exchange-test-utils.shared`
+    async fn setup_shared() -> anyhow::Result<Self> {
+        let postgres_container = Postgres::default()
+            .with_label(SHARED_LABEL, "true")
+            .start()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start PostgreSQL container: {}", e))?;
+
+        let clickhouse_container = ClickHouse::default()
+            .with_label(SHARED_LABEL, "true")
+            .start()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start ClickHouse container: {}", e))?;
+
+        Self::connect(postgres_container, clickhouse_container).await
+    }
+
+    /// Connect to started containers, running migrations
+    async fn connect(
+        postgres_container: testcontainers::ContainerAsync<Postgres>,
+        clickhouse_container: testcontainers::ContainerAsync<ClickHouse>,
+    ) -> anyhow::Result<Self> {
         let postgres_port = postgres_container
             .get_host_port_ipv4(5432)
             .await
@@ -55,12 +104,14 @@ impl TestContainers {
         );
 
         // Connect with explicit URLs to avoid conflicts in parallel tests
-        let db = Db::connect_with_urls(Some(postgres_url), Some(clickhouse_url))
+        let db = Db::connect_with_urls(Some(postgres_url.clone()), Some(clickhouse_url.clone()))
             .await
             .map_err(|e| anyhow::anyhow!("Failed to connect to databases: {}", e))?;
 
         Ok(TestContainers {
             db,
+            postgres_url,
+            clickhouse_url,
             _postgres_container: postgres_container,
             _clickhouse_container: clickhouse_container,
         })
@@ -76,6 +127,27 @@ impl TestContainers {
 // Test Database - Wrapper with Direct DB Access
 // ============================================================================
 
+/// Containers backing a `TestDb`
+pub(crate) enum Containers {
+    /// Started for this test and removed when it ends
+    Dedicated(Box<TestContainers>),
+    /// Process-wide containers; the lease keeps other tests out until this one ends
+    Shared { _lease: MutexGuard<'static, ()> },
+}
+
+impl Containers {
+    /// Containers that this test may pause or stop without affecting other tests
+    pub(crate) fn dedicated(&self) -> anyhow::Result<&TestContainers> {
+        match self {
+            Containers::Dedicated(containers) => Ok(containers),
+            Containers::Shared { .. } => Err(anyhow::anyhow!(
+                "Fault injection needs dedicated containers; unset {} or use TestDb::setup_dedicated",
+                SHARED_ENV
+            )),
+        }
+    }
+}
+
 /// Test database wrapper
 ///
 /// Provides access to the database for backend tests that need to verify internal state.
@@ -83,19 +155,102 @@ impl TestContainers {
 #[allow(dead_code)]
 pub struct TestDb {
     pub db: Db,
-    _containers: TestContainers,
+    pub(crate) containers: Containers,
 }
 
 #[allow(dead_code)]
 impl TestDb {
     /// Set up test databases with containers
+    ///
+    /// Starts fresh containers for every call, unless `TEST_SHARED_CONTAINERS=1`
+    /// is set, in which case this behaves like [`TestDb::setup_shared`].
     pub async fn setup() -> anyhow::Result<Self> {
+        if std::env::var(SHARED_ENV).is_ok_and(|v| v == "1") {
+            Self::setup_shared().await
+        } else {
+            Self::setup_dedicated().await
+        }
+    }
+
+    /// Set up test databases with containers owned by this `TestDb`
+    pub async fn setup_dedicated() -> anyhow::Result<Self> {
         let containers = TestContainers::setup().await?;
         let db = containers.db_clone();
 
         Ok(TestDb {
             db,
-            _containers: containers,
+            containers: Containers::Dedicated(Box::new(containers)),
         })
     }
+
+    /// Set up test databases on containers shared by the whole test process
+    ///
+    /// The first call starts the containers; later calls reuse them, clearing all
+    /// tables first. Tests holding a shared `TestDb` run one at a time, so a test
+    /// must not hold two at once. Shared containers are not removed when the
+    /// process exits; clean them up with
+    /// `docker rm -f $(docker ps -aq --filter label=exchange-test-utils.shared)`.
+    pub async fn setup_shared() -> anyhow::Result<Self> {
+        let lease = SHARED_LEASE.lock().await;
+        let containers = SHARED_CONTAINERS
+            .get_or_try_init(TestContainers::setup_shared)
+            .await?;
+
+        // Connection pools are tied to the runtime that created them, and each
+        // #[tokio::test] has its own runtime, so connect afresh for every test
+        let db = Db::connect_with_urls(
+            Some(containers.postgres_url.clone()),
+            Some(containers.clickhouse_url.clone()),
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to databases: {}", e))?;
+
+        let test_db = TestDb {
+            db,
+            containers: Containers::Shared { _lease: lease },
+        };
+        test_db.reset().await?;
+
+        Ok(test_db)
+    }
+
+    /// Whether this `TestDb` runs on process-wide shared containers
+    pub fn is_shared(&self) -> bool {
+        matches!(self.containers, Containers::Shared { .. })
+    }
+
+    /// Clear all data, leaving the schema, the seeded system accounts and default fee routes in place
+    pub async fn reset(&self) -> anyhow::Result<()> {
+        sqlx::query(&format!("TRUNCATE {} CASCADE", PG_TABLES.join(", ")))
+            .execute(&self.db.postgres)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to truncate PostgreSQL tables: {}", e))?;
+
+        // Seeded by migrations; the fee collector and insurance fund
+        sqlx::query("INSERT INTO users (address) VALUES ('system'), ('insurance')")
+            .execute(&self.db.postgres)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to seed system users: {}", e))?;
+        sqlx::query(
+            r#"
+            INSERT INTO fee_routes (source, insurance_bps)
+            VALUES ('trading_fees', 0), ('liquidations', 10000)
+            ON CONFLICT (source) DO UPDATE SET insurance_bps = EXCLUDED.insurance_bps
+            "#,
+        )
+        .execute(&self.db.postgres)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to reset fee routes: {}", e))?;
+
+        for table in CH_TABLES {
+            self.db
+                .clickhouse
+                .query(&format!("TRUNCATE TABLE IF EXISTS {}", table))
+                .execute()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to truncate {}: {}", table, e))?;
+        }
+
+        Ok(())
+    }
 }
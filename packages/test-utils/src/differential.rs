@@ -0,0 +1,419 @@
+use crate::fixtures::{MarketBuilder, OrderBuilder};
+use crate::invariants::{engine_ops, EngineOp};
+use backend::engine::ladder::LadderLayout;
+use backend::engine::matcher::Matcher;
+use backend::engine::orderbook::Orderbook;
+use backend::models::domain::{Market, Order, OrderType, Side, Trade};
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+// ============================================================================
+// Differential Matching Tests - Orderbook vs. a Reference Model
+// ============================================================================
+
+/// Users trading in every generated case
+const USERS: usize = 4;
+/// Mid price of generated orders, in ticks
+const MID_PRICE: u128 = 100;
+/// Minimum order size, in lots, so remainders under it are dropped rather than rested
+const MIN_LOTS: u128 = 2;
+/// Array ladder covering every price `engine_ops` can generate around `MID_PRICE`
+const ARRAY_LAYOUT: LadderLayout = LadderLayout::Array {
+    min_price: 1,
+    max_price: 2 * MID_PRICE,
+    tick_size: 1,
+};
+
+/// An order resting in a [`ReferenceBook`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestingOrder {
+    pub id: Uuid,
+    pub user_address: String,
+    pub price: u128,
+    pub remaining: u128,
+}
+
+/// One fill of a taker order against a resting order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    pub maker_order_id: Uuid,
+    pub price: u128,
+    pub size: u128,
+}
+
+/// Slow, obviously-correct order book to check [`Orderbook`] and [`Matcher`] against
+///
+/// Each side is a plain vector in arrival order. Matching repeatedly picks the
+/// best-priced, oldest resting order from another user that the taker's price
+/// reaches, so price-time priority holds by construction. Nothing is cached:
+/// levels are summed from the orders whenever asked for.
+#[derive(Debug, Clone)]
+pub struct ReferenceBook {
+    min_size: u128,
+    bids: Vec<RestingOrder>,
+    asks: Vec<RestingOrder>,
+}
+
+impl ReferenceBook {
+    /// Remainders under `min_size` are dropped instead of rested
+    pub fn new(min_size: u128) -> Self {
+        Self {
+            min_size,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+
+    /// Match `order` against the book, then rest what's left of a limit order
+    pub fn place(&mut self, order: &Order) -> Vec<Fill> {
+        let mut remaining = order.size - order.filled_size;
+        let makers = match order.side {
+            Side::Buy => &mut self.asks,
+            Side::Sell => &mut self.bids,
+        };
+
+        let mut fills = Vec::new();
+        while remaining > 0 {
+            let mut candidates: Vec<usize> = (0..makers.len())
+                .filter(|&i| {
+                    let maker = &makers[i];
+                    maker.user_address != order.user_address
+                        && match (order.order_type, order.side) {
+                            (OrderType::Market | OrderType::StopMarket, _) => true,
+                            (OrderType::Limit | OrderType::StopLimit, Side::Buy) => {
+                                maker.price <= order.price
+                            }
+                            (OrderType::Limit | OrderType::StopLimit, Side::Sell) => {
+                                maker.price >= order.price
+                            }
+                        }
+                })
+                .collect();
+            // Best price first, then arrival order
+            candidates.sort_by_key(|&i| match order.side {
+                Side::Buy => (makers[i].price, i),
+                Side::Sell => (u128::MAX - makers[i].price, i),
+            });
+            let Some(&best) = candidates.first() else {
+                break;
+            };
+
+            let maker = &mut makers[best];
+            let size = remaining.min(maker.remaining);
+            fills.push(Fill {
+                maker_order_id: maker.id,
+                price: maker.price,
+                size,
+            });
+            remaining -= size;
+            maker.remaining -= size;
+            if maker.remaining == 0 {
+                makers.remove(best);
+            }
+        }
+
+        if order.order_type == OrderType::Limit && remaining > 0 && remaining >= self.min_size {
+            let resting = RestingOrder {
+                id: order.id,
+                user_address: order.user_address.clone(),
+                price: order.price,
+                remaining,
+            };
+            match order.side {
+                Side::Buy => self.bids.push(resting),
+                Side::Sell => self.asks.push(resting),
+            }
+        }
+        fills
+    }
+
+    /// Take a resting order off the book
+    pub fn cancel(&mut self, order_id: Uuid) -> Option<RestingOrder> {
+        for side in [&mut self.bids, &mut self.asks] {
+            if let Some(pos) = side.iter().position(|o| o.id == order_id) {
+                return Some(side.remove(pos));
+            }
+        }
+        None
+    }
+
+    /// Take all of a user's resting orders off the book
+    pub fn cancel_all(&mut self, user_address: &str) -> Vec<RestingOrder> {
+        let mut removed = Vec::new();
+        for side in [&mut self.bids, &mut self.asks] {
+            let (mine, others) = side.drain(..).partition(|o| o.user_address == user_address);
+            *side = others;
+            removed.extend::<Vec<_>>(mine);
+        }
+        removed
+    }
+
+    /// Resting orders on one side, best price first and oldest first within a price
+    pub fn orders(&self, side: Side) -> Vec<RestingOrder> {
+        let mut orders: Vec<(usize, RestingOrder)> = match side {
+            Side::Buy => self.bids.iter().cloned().enumerate().collect(),
+            Side::Sell => self.asks.iter().cloned().enumerate().collect(),
+        };
+        orders.sort_by_key(|(i, o)| match side {
+            Side::Buy => (u128::MAX - o.price, *i),
+            Side::Sell => (o.price, *i),
+        });
+        orders.into_iter().map(|(_, o)| o).collect()
+    }
+
+    /// Remaining size at each price on one side, best price first
+    pub fn levels(&self, side: Side) -> Vec<(u128, u128)> {
+        let mut levels: Vec<(u128, u128)> = Vec::new();
+        for order in self.orders(side) {
+            match levels.last_mut() {
+                Some((price, size)) if *price == order.price => *size += order.remaining,
+                _ => levels.push((order.price, order.remaining)),
+            }
+        }
+        levels
+    }
+
+    /// Number of orders a user has resting
+    pub fn open_order_count(&self, user_address: &str) -> usize {
+        self.bids
+            .iter()
+            .chain(&self.asks)
+            .filter(|o| o.user_address == user_address)
+            .count()
+    }
+}
+
+/// Feeds the same operations to an [`Orderbook`], the way the engine drives
+/// it, and to a [`ReferenceBook`], failing as soon as they disagree
+///
+/// Compared after every operation: the fills, the orders cancels return, every
+/// resting order in priority order, the per-level depth snapshots are built
+/// from, and per-user open order counts.
+pub struct DifferentialHarness {
+    pub market: Market,
+    pub book: Orderbook,
+    pub reference: ReferenceBook,
+    pub users: Vec<String>,
+    placed: Vec<Uuid>,
+}
+
+impl DifferentialHarness {
+    /// An empty market with prices in ticks of 1 and sizes in lots of 1
+    pub fn new(layout: LadderLayout) -> Self {
+        let market = MarketBuilder::new("DIFF", "USDC")
+            .tick_size(1)
+            .lot_size(1)
+            .min_size(MIN_LOTS)
+            .build();
+        Self {
+            book: Orderbook::with_layout(market.id.clone(), layout),
+            reference: ReferenceBook::new(market.min_size),
+            users: (0..USERS).map(|i| format!("diff_user{}", i)).collect(),
+            placed: Vec::new(),
+            market,
+        }
+    }
+
+    /// Apply every operation in order, comparing the books after each one
+    pub fn run(&mut self, ops: &[EngineOp]) -> Result<(), String> {
+        for (step, op) in ops.iter().enumerate() {
+            self.apply(op)
+                .and_then(|()| self.check())
+                .map_err(|e| format!("step {} ({:?}): {}", step, op, e))?;
+        }
+        Ok(())
+    }
+
+    /// Apply one operation to both books, comparing what each returns
+    pub fn apply(&mut self, op: &EngineOp) -> Result<(), String> {
+        match op {
+            EngineOp::Place {
+                user,
+                side,
+                order_type,
+                price_offset,
+                lots,
+            } => {
+                let price = (MID_PRICE as i128 + *price_offset as i128) as u128;
+                let order = OrderBuilder::new(&self.users[*user], &self.market.id)
+                    .side(*side)
+                    .order_type(*order_type)
+                    .price(price)
+                    .size(lots * self.market.lot_size)
+                    .build();
+                self.placed.push(order.id);
+
+                let matches = Matcher::match_order(&order, &self.book);
+                let fills: Vec<Fill> = matches
+                    .iter()
+                    .map(|m| Fill {
+                        maker_order_id: m.maker_order.id,
+                        price: m.price,
+                        size: m.size,
+                    })
+                    .collect();
+                let trades: Vec<Trade> = matches
+                    .iter()
+                    .map(|m| {
+                        let (buyer, seller) = match order.side {
+                            Side::Buy => (&order, m.maker_order.as_ref()),
+                            Side::Sell => (m.maker_order.as_ref(), &order),
+                        };
+                        Trade {
+                            id: Uuid::new_v4(),
+                            market_id: self.market.id.clone(),
+                            buyer_address: buyer.user_address.clone(),
+                            seller_address: seller.user_address.clone(),
+                            buyer_order_id: buyer.id,
+                            seller_order_id: seller.id,
+                            price: m.price,
+                            size: m.size,
+                            side: order.side,
+                            timestamp: chrono::Utc::now(),
+                            rfq: false,
+                        }
+                    })
+                    .collect();
+                drop(matches);
+                self.book.apply_trades(&order, &trades, &self.market);
+
+                let expected = self.reference.place(&order);
+                if fills != expected {
+                    return Err(format!(
+                        "fills {:?} differ from reference {:?}",
+                        fills, expected
+                    ));
+                }
+            }
+            EngineOp::Cancel { pick } => {
+                if self.placed.is_empty() {
+                    return Ok(());
+                }
+                let id = self.placed[pick % self.placed.len()];
+                let removed = self
+                    .book
+                    .remove_order(id)
+                    .map(|o| (o.id, o.size - o.filled_size));
+                let expected = self.reference.cancel(id).map(|o| (o.id, o.remaining));
+                if removed != expected {
+                    return Err(format!(
+                        "cancel returned {:?}, reference {:?}",
+                        removed, expected
+                    ));
+                }
+            }
+            EngineOp::CancelAll { user } => {
+                let user = &self.users[*user];
+                let removed: BTreeMap<Uuid, u128> = self
+                    .book
+                    .remove_all_user_orders(user)
+                    .into_iter()
+                    .map(|o| (o.id, o.size - o.filled_size))
+                    .collect();
+                let expected: BTreeMap<Uuid, u128> = self
+                    .reference
+                    .cancel_all(user)
+                    .into_iter()
+                    .map(|o| (o.id, o.remaining))
+                    .collect();
+                if removed != expected {
+                    return Err(format!(
+                        "cancel all returned {:?}, reference {:?}",
+                        removed, expected
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare the resting orders, depth levels and open order counts
+    pub fn check(&self) -> Result<(), String> {
+        let queued = |side: Side| -> Vec<(Uuid, u128, u128)> {
+            let levels: Vec<_> = match side {
+                Side::Buy => self.book.bids.iter().rev().collect(),
+                Side::Sell => self.book.asks.iter().collect(),
+            };
+            levels
+                .into_iter()
+                .flat_map(|(price, orders)| {
+                    orders
+                        .iter()
+                        .map(move |o| (o.id, price, o.size - o.filled_size))
+                })
+                .collect()
+        };
+        let snapshot = self.book.snapshot();
+        let depth = |levels: &[backend::models::domain::OrderbookLevel]| -> Vec<(u128, u128)> {
+            levels.iter().map(|l| (l.price, l.size)).collect()
+        };
+
+        for (side, name, snapshot_levels) in [
+            (Side::Buy, "bid", &snapshot.bids),
+            (Side::Sell, "ask", &snapshot.asks),
+        ] {
+            let expected: Vec<(Uuid, u128, u128)> = self
+                .reference
+                .orders(side)
+                .into_iter()
+                .map(|o| (o.id, o.price, o.remaining))
+                .collect();
+            let actual = queued(side);
+            if actual != expected {
+                return Err(format!(
+                    "resting {}s {:?} differ from reference {:?}",
+                    name, actual, expected
+                ));
+            }
+
+            let expected = self.reference.levels(side);
+            let actual = depth(snapshot_levels);
+            if actual != expected {
+                return Err(format!(
+                    "{} depth {:?} differs from reference {:?}",
+                    name, actual, expected
+                ));
+            }
+        }
+
+        for user in &self.users {
+            let (actual, expected) = (
+                self.book.open_order_count(user),
+                self.reference.open_order_count(user),
+            );
+            if actual != expected {
+                return Err(format!(
+                    "{} has {} open orders, reference {}",
+                    user, actual, expected
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run `cases` random operation sequences through the orderbook, with both
+/// ladder layouts, and a [`ReferenceBook`], panicking on the first
+/// disagreement with the shrunk failing sequence
+pub fn check_against_reference(cases: u32, max_ops: usize) {
+    for layout in [LadderLayout::Tree, ARRAY_LAYOUT] {
+        let mut runner = TestRunner::new(Config {
+            cases,
+            ..Config::default()
+        });
+
+        let result = runner.run(&engine_ops(max_ops), |ops| {
+            DifferentialHarness::new(layout)
+                .run(&ops)
+                .map_err(TestCaseError::fail)
+        });
+
+        if let Err(e) = result {
+            panic!(
+                "Orderbook ({:?} layout) diverged from the reference: {}",
+                layout, e
+            );
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use crate::db::TestDb;
+use crate::fixtures::OrderBuilder;
 use crate::helpers;
 use backend::db::Db;
+use backend::engine::orderbook::Orderbooks;
 use backend::engine::MatchingEngine;
-use backend::models::domain::{EngineEvent, EngineRequest, Order, OrderStatus, OrderType, Side};
-use chrono::Utc;
-use tokio::sync::{broadcast, mpsc, oneshot};
+use backend::models::domain::{EngineEvent, EngineRequest, Order};
+use backend::price_feed::IndexPrices;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 use uuid::Uuid;
 
 /// Helper for matching engine testing
@@ -16,6 +19,10 @@ pub struct TestEngine {
     pub db: Db,
     pub engine_tx: mpsc::Sender<EngineRequest>,
     pub event_rx: broadcast::Receiver<EngineEvent>,
+    /// In-memory orderbooks of the running engine, for state inspection
+    pub orderbooks: Arc<RwLock<Orderbooks>>,
+    /// Index prices the running engine checks collars and index stops against
+    pub index_prices: IndexPrices,
     event_tx: broadcast::Sender<EngineEvent>,
 }
 
@@ -90,20 +97,33 @@ impl TestEngine {
             }
         }
 
+        Self::spawn(test_db.db.clone())
+    }
+
+    /// Run an engine on `db` as it is, such as one from `Db::in_memory`
+    pub fn spawn(db: Db) -> Self {
         let (engine_tx, engine_rx) = mpsc::channel::<EngineRequest>(100);
         let (event_tx, event_rx) = broadcast::channel::<EngineEvent>(1000);
 
-        let engine = MatchingEngine::new(test_db.db.clone(), engine_rx, event_tx.clone());
+        let mut engine = MatchingEngine::new(db.clone(), engine_rx, event_tx.clone());
+        let orderbooks = engine.orderbooks();
+        let index_prices = engine.index_prices();
 
         // Spawn engine in background
         tokio::spawn(async move {
+            engine
+                .load_state()
+                .await
+                .expect("Failed to load engine state");
             engine.run().await;
         });
 
         Self {
-            db: test_db.db.clone(),
+            db,
             engine_tx,
             event_rx,
+            orderbooks,
+            index_prices,
             event_tx,
         }
     }
@@ -121,7 +141,11 @@ impl TestEngine {
         let (response_tx, response_rx) = oneshot::channel();
 
         self.engine_tx
-            .send(EngineRequest::PlaceOrder { order, response_tx })
+            .send(EngineRequest::PlaceOrder {
+                order,
+                span: tracing::Span::current(),
+                response_tx,
+            })
             .await
             .map_err(|e| format!("Failed to send order: {}", e))?;
 
@@ -131,6 +155,53 @@ impl TestEngine {
             .map_err(|e| format!("Order placement failed: {}", e))
     }
 
+    /// Helper to place two orders as a one-cancels-other pair
+    pub async fn place_oco(
+        &self,
+        first: Order,
+        second: Order,
+    ) -> Result<backend::models::api::OcoPlaced, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::PlaceOco {
+                first,
+                second,
+                span: tracing::Span::current(),
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send pair: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Pair placement failed: {}", e))
+    }
+
+    /// Trade `size` at `price` in `memory_market`'s BTC/USDC between
+    /// `buyer` and `seller`, moving the market's last trade price there
+    pub async fn print_trade(&self, buyer: &str, seller: &str, price: u128, size: u128) {
+        self.place_order(
+            OrderBuilder::buy(buyer, "BTC/USDC")
+                .limit(price)
+                .size(size)
+                .build(),
+        )
+        .await
+        .unwrap();
+        let placed = self
+            .place_order(
+                OrderBuilder::sell(seller, "BTC/USDC")
+                    .limit(price)
+                    .size(size)
+                    .build(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(placed.trades.len(), 1);
+    }
+
     /// Helper to cancel an order
     pub async fn cancel_order(
         &self,
@@ -143,6 +214,7 @@ impl TestEngine {
             .send(EngineRequest::CancelOrder {
                 order_id,
                 user_address,
+                span: tracing::Span::current(),
                 response_tx,
             })
             .await
@@ -154,27 +226,271 @@ impl TestEngine {
             .map_err(|e| format!("Order cancellation failed: {}", e))
     }
 
-    /// Helper to create a test order
-    pub fn create_order(
+    /// Helper to set a user's limits in a market
+    pub async fn set_user_limits(
+        &self,
         user_address: &str,
         market_id: &str,
-        side: Side,
-        order_type: OrderType,
-        price: u128,
-        size: u128,
-    ) -> Order {
-        Order {
-            id: Uuid::new_v4(),
-            user_address: user_address.to_string(),
-            market_id: market_id.to_string(),
-            price,
-            size,
-            side,
-            order_type,
-            status: OrderStatus::Pending,
-            filled_size: 0,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        }
+        max_position: Option<u128>,
+        max_open_notional: Option<u128>,
+    ) -> Result<backend::models::domain::UserLimits, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetUserLimits {
+                user_address: user_address.to_string(),
+                market_id: market_id.to_string(),
+                max_position,
+                max_open_notional,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send limits request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting limits failed: {}", e))
+    }
+
+    /// Helper to change a market's status
+    pub async fn set_market_status(
+        &self,
+        market_id: &str,
+        status: backend::models::domain::MarketStatus,
+    ) -> Result<backend::models::api::OrdersCancelled, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetMarketStatus {
+                market_id: market_id.to_string(),
+                status,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send status request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting market status failed: {}", e))
+    }
+
+    /// Helper to override a market's price collar
+    pub async fn set_price_collar(
+        &self,
+        market_id: &str,
+        collar_bps: Option<u32>,
+    ) -> Result<(), String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetPriceCollar {
+                market_id: market_id.to_string(),
+                collar_bps,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send collar request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting price collar failed: {}", e))
+    }
+
+    /// Helper to route a share of a revenue source to the insurance fund
+    pub async fn set_fee_route(
+        &self,
+        source: backend::models::domain::RevenueSource,
+        insurance_bps: u32,
+    ) -> Result<backend::models::domain::FeeRoute, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetFeeRoute {
+                source,
+                insurance_bps,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send fee route request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting fee route failed: {}", e))
+    }
+
+    /// Helper to freeze, ban or reinstate a user
+    pub async fn set_user_status(
+        &self,
+        user_address: &str,
+        status: backend::models::domain::UserStatus,
+        cancel_orders: bool,
+    ) -> Result<backend::models::api::OrdersCancelled, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetUserStatus {
+                user_address: user_address.to_string(),
+                status,
+                cancel_orders,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send status request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting user status failed: {}", e))
+    }
+
+    /// Helper to set who referred a user
+    pub async fn set_referral(
+        &self,
+        user_address: &str,
+        referrer_address: &str,
+        share_bps: u32,
+    ) -> Result<backend::models::domain::Referral, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetReferral {
+                user_address: user_address.to_string(),
+                referrer_address: referrer_address.to_string(),
+                share_bps,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send referral request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting referral failed: {}", e))
+    }
+
+    /// Helper to set the fees a user pays in a market
+    pub async fn set_fee_override(
+        &self,
+        user_address: &str,
+        market_id: &str,
+        maker_fee_bps: Option<i32>,
+        taker_fee_bps: Option<i32>,
+    ) -> Result<Option<backend::models::domain::FeeOverride>, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetFeeOverride {
+                user_address: user_address.to_string(),
+                market_id: market_id.to_string(),
+                maker_fee_bps,
+                taker_fee_bps,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send fee override request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting fee override failed: {}", e))
+    }
+
+    /// Helper to open a market created after the engine started
+    pub async fn open_market(&self, market_id: &str) -> Result<(), String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::OpenMarket {
+                market_id: market_id.to_string(),
+                layout: Default::default(),
+                collar_bps: None,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send open market request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Opening market failed: {}", e))
+    }
+
+    /// Helper to settle a perpetual market's funding at `mark_price`
+    pub async fn settle_funding(
+        &self,
+        market_id: &str,
+        mark_price: u128,
+        index_price: u128,
+        funding_rate_ppm: i64,
+    ) -> Result<backend::perps::FundingSettlement, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SettleFunding {
+                market_id: market_id.to_string(),
+                mark_price,
+                index_price,
+                funding_rate_ppm,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send funding request: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Settling funding failed: {}", e))
+    }
+
+    /// Helper to mark a perpetual market, liquidating positions under their maintenance margin
+    pub async fn update_mark_price(
+        &self,
+        market_id: &str,
+        mark_price: u128,
+        index_price: u128,
+    ) -> Result<Vec<backend::models::domain::Liquidation>, String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::UpdateMarkPrice {
+                market_id: market_id.to_string(),
+                mark_price,
+                index_price,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send mark price: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Updating mark price failed: {}", e))
+    }
+
+    /// Helper to set how a user's positions are margined
+    pub async fn set_margin_mode(
+        &self,
+        user_address: &str,
+        mode: backend::models::domain::MarginMode,
+    ) -> Result<(), String> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.engine_tx
+            .send(EngineRequest::SetMarginMode {
+                user_address: user_address.to_string(),
+                mode,
+                response_tx,
+            })
+            .await
+            .map_err(|e| format!("Failed to send margin mode: {}", e))?;
+
+        response_rx
+            .await
+            .map_err(|e| format!("Failed to receive response: {}", e))?
+            .map_err(|e| format!("Setting margin mode failed: {}", e))
     }
 }
@@ -0,0 +1,130 @@
+use crate::db::{TestContainers, TestDb};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+// ============================================================================
+// Fault Injection - Pause/Stop Test Containers Mid-Test
+// ============================================================================
+
+/// Database service backing the test environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    Postgres,
+    ClickHouse,
+}
+
+impl TestContainers {
+    /// Freeze a container; open connections hang until it is unpaused
+    pub async fn pause(&self, service: Service) -> anyhow::Result<()> {
+        match service {
+            Service::Postgres => self._postgres_container.pause().await,
+            Service::ClickHouse => self._clickhouse_container.pause().await,
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to pause {:?}: {}", service, e))
+    }
+
+    /// Resume a paused container
+    pub async fn unpause(&self, service: Service) -> anyhow::Result<()> {
+        match service {
+            Service::Postgres => self._postgres_container.unpause().await,
+            Service::ClickHouse => self._clickhouse_container.unpause().await,
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to unpause {:?}: {}", service, e))
+    }
+
+    /// Stop a container; connections are refused until it is started again
+    ///
+    /// Docker may assign a new host port on restart, in which case existing
+    /// connection pools cannot reconnect. Prefer `pause` for recovery tests.
+    pub async fn stop(&self, service: Service) -> anyhow::Result<()> {
+        match service {
+            Service::Postgres => self._postgres_container.stop().await,
+            Service::ClickHouse => self._clickhouse_container.stop().await,
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to stop {:?}: {}", service, e))
+    }
+
+    /// Start a stopped container
+    pub async fn start(&self, service: Service) -> anyhow::Result<()> {
+        match service {
+            Service::Postgres => self._postgres_container.start().await,
+            Service::ClickHouse => self._clickhouse_container.start().await,
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to start {:?}: {}", service, e))
+    }
+}
+
+impl TestDb {
+    /// Pause a database container (see [`TestContainers::pause`])
+    pub async fn pause(&self, service: Service) -> anyhow::Result<()> {
+        self.containers.dedicated()?.pause(service).await
+    }
+
+    /// Unpause a database container
+    pub async fn unpause(&self, service: Service) -> anyhow::Result<()> {
+        self.containers.dedicated()?.unpause(service).await
+    }
+
+    /// Stop a database container (see [`TestContainers::stop`])
+    pub async fn stop(&self, service: Service) -> anyhow::Result<()> {
+        self.containers.dedicated()?.stop(service).await
+    }
+
+    /// Start a stopped database container
+    pub async fn start(&self, service: Service) -> anyhow::Result<()> {
+        self.containers.dedicated()?.start(service).await
+    }
+
+    /// Run `fut` while `service` is paused, unpausing afterwards
+    ///
+    /// ```rust,ignore
+    /// let result = test_db
+    ///     .with_paused(Service::ClickHouse, engine.place_order(order))
+    ///     .await?;
+    /// assert!(result.is_ok(), "order flow must not depend on ClickHouse");
+    /// ```
+    pub async fn with_paused<F: Future>(
+        &self,
+        service: Service,
+        fut: F,
+    ) -> anyhow::Result<F::Output> {
+        self.pause(service).await?;
+        let output = fut.await;
+        self.unpause(service).await?;
+        Ok(output)
+    }
+
+    /// Check whether a service answers a trivial query
+    pub async fn is_healthy(&self, service: Service) -> bool {
+        match service {
+            Service::Postgres => sqlx::query("SELECT 1")
+                .execute(&self.db.postgres)
+                .await
+                .is_ok(),
+            Service::ClickHouse => self.db.clickhouse.query("SELECT 1").execute().await.is_ok(),
+        }
+    }
+
+    /// Poll until a service is reachable again, failing after `timeout`
+    pub async fn wait_until_healthy(
+        &self,
+        service: Service,
+        timeout: Duration,
+    ) -> anyhow::Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.is_healthy(service).await {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow::anyhow!(
+                    "{:?} did not recover within {:?}",
+                    service,
+                    timeout
+                ));
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
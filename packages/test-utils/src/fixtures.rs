@@ -0,0 +1,351 @@
+use crate::db::TestDb;
+use crate::helpers;
+use backend::db::Db;
+use backend::models::domain::{
+    Market, Order, OrderStatus, OrderType, Side, TimeInForce, TriggerSource, User,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+// ============================================================================
+// Fluent Fixture Builders - Orders, Markets and Users with Sensible Defaults
+// ============================================================================
+
+/// Builder for domain `Order`s
+///
+/// Defaults to a pending 1,000,000-atom buy limit order at 50,000,000,000.
+///
+/// ```rust,ignore
+/// let ask = OrderBuilder::sell("seller", &market.id)
+///     .limit(50_000_000_000)
+///     .size(1_000_000)
+///     .build();
+/// let sweep = OrderBuilder::buy("buyer", &market.id).market().size(3_000_000).build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    order: Order,
+}
+
+impl OrderBuilder {
+    pub fn new(user_address: &str, market_id: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            order: Order {
+                id: Uuid::new_v4(),
+                user_address: user_address.to_string(),
+                market_id: market_id.to_string(),
+                price: 50_000_000_000,
+                size: 1_000_000,
+                side: Side::Buy,
+                order_type: OrderType::Limit,
+                status: OrderStatus::Pending,
+                filled_size: 0,
+                created_at: now,
+                updated_at: now,
+                cancel_reason: None,
+                trigger_price: None,
+                trigger_source: TriggerSource::LastTrade,
+                time_in_force: TimeInForce::Gtc,
+                expires_at: None,
+            },
+        }
+    }
+
+    /// Start a buy order
+    pub fn buy(user_address: &str, market_id: &str) -> Self {
+        Self::new(user_address, market_id).side(Side::Buy)
+    }
+
+    /// Start a sell order
+    pub fn sell(user_address: &str, market_id: &str) -> Self {
+        Self::new(user_address, market_id).side(Side::Sell)
+    }
+
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.order.id = id;
+        self
+    }
+
+    pub fn side(mut self, side: Side) -> Self {
+        self.order.side = side;
+        self
+    }
+
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order.order_type = order_type;
+        self
+    }
+
+    /// Limit order at `price`
+    pub fn limit(self, price: u128) -> Self {
+        self.order_type(OrderType::Limit).price(price)
+    }
+
+    /// Market order; the price is still used as the worst-case lock amount
+    pub fn market(self) -> Self {
+        self.order_type(OrderType::Market)
+    }
+
+    /// Stop market order that triggers at `trigger_price`; the price is still
+    /// used as the worst-case lock amount
+    pub fn stop_market(self, trigger_price: u128) -> Self {
+        self.order_type(OrderType::StopMarket)
+            .trigger_price(trigger_price)
+    }
+
+    /// Stop limit order at `price` that triggers at `trigger_price`
+    pub fn stop_limit(self, trigger_price: u128, price: u128) -> Self {
+        self.order_type(OrderType::StopLimit)
+            .trigger_price(trigger_price)
+            .price(price)
+    }
+
+    pub fn trigger_price(mut self, trigger_price: u128) -> Self {
+        self.order.trigger_price = Some(trigger_price);
+        self
+    }
+
+    pub fn trigger_source(mut self, trigger_source: TriggerSource) -> Self {
+        self.order.trigger_source = trigger_source;
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.order.time_in_force = time_in_force;
+        self
+    }
+
+    /// Immediate or cancel: whatever doesn't fill on arrival is cancelled
+    pub fn ioc(self) -> Self {
+        self.time_in_force(TimeInForce::Ioc)
+    }
+
+    /// Fill or kill: rejected unless it fills completely on arrival
+    pub fn fok(self) -> Self {
+        self.time_in_force(TimeInForce::Fok)
+    }
+
+    /// Good till date: cancelled by the engine if still open at `expires_at`
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.order.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn price(mut self, price: u128) -> Self {
+        self.order.price = price;
+        self
+    }
+
+    pub fn size(mut self, size: u128) -> Self {
+        self.order.size = size;
+        self
+    }
+
+    pub fn filled(mut self, filled_size: u128) -> Self {
+        self.order.filled_size = filled_size;
+        self
+    }
+
+    pub fn status(mut self, status: OrderStatus) -> Self {
+        self.order.status = status;
+        self
+    }
+
+    pub fn build(self) -> Order {
+        self.order
+    }
+}
+
+/// Builder for markets, creating the tokens and market in the test database
+///
+/// Defaults match `helpers::create_market_with_tokens`: 8 base decimals,
+/// 6 quote decimals, tick 1,000, lot and min size 1,000,000, fees 10/20 bps.
+///
+/// ```rust,ignore
+/// let market = MarketBuilder::new("ETH", "USDC")
+///     .tick_size(10_000)
+///     .fees(0, 5)
+///     .create(&test_db)
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarketBuilder {
+    base_ticker: String,
+    quote_ticker: String,
+    base_decimals: u8,
+    quote_decimals: u8,
+    tick_size: u128,
+    lot_size: u128,
+    min_size: u128,
+    maker_fee_bps: i32,
+    taker_fee_bps: i32,
+}
+
+impl MarketBuilder {
+    pub fn new(base_ticker: &str, quote_ticker: &str) -> Self {
+        Self {
+            base_ticker: base_ticker.to_string(),
+            quote_ticker: quote_ticker.to_string(),
+            base_decimals: 8,
+            quote_decimals: 6,
+            tick_size: 1000,
+            lot_size: 1000000,
+            min_size: 1000000,
+            maker_fee_bps: 10,
+            taker_fee_bps: 20,
+        }
+    }
+
+    pub fn decimals(mut self, base_decimals: u8, quote_decimals: u8) -> Self {
+        self.base_decimals = base_decimals;
+        self.quote_decimals = quote_decimals;
+        self
+    }
+
+    pub fn tick_size(mut self, tick_size: u128) -> Self {
+        self.tick_size = tick_size;
+        self
+    }
+
+    pub fn lot_size(mut self, lot_size: u128) -> Self {
+        self.lot_size = lot_size;
+        self
+    }
+
+    pub fn min_size(mut self, min_size: u128) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn fees(mut self, maker_fee_bps: i32, taker_fee_bps: i32) -> Self {
+        self.maker_fee_bps = maker_fee_bps;
+        self.taker_fee_bps = taker_fee_bps;
+        self
+    }
+
+    /// Domain `Market` without touching the database (for pure engine tests and benches)
+    pub fn build(&self) -> Market {
+        Market {
+            id: format!("{}/{}", self.base_ticker, self.quote_ticker),
+            base_ticker: self.base_ticker.clone(),
+            quote_ticker: self.quote_ticker.clone(),
+            tick_size: self.tick_size,
+            lot_size: self.lot_size,
+            min_size: self.min_size,
+            maker_fee_bps: self.maker_fee_bps,
+            taker_fee_bps: self.taker_fee_bps,
+        }
+    }
+
+    /// Create any missing tokens, then the market
+    pub async fn create(self, test_db: &TestDb) -> anyhow::Result<Market> {
+        for (ticker, decimals) in [
+            (&self.base_ticker, self.base_decimals),
+            (&self.quote_ticker, self.quote_decimals),
+        ] {
+            if test_db.db.get_token(ticker).await.is_err() {
+                helpers::create_token(test_db, ticker, decimals, &format!("{} Token", ticker))
+                    .await?;
+            }
+        }
+
+        test_db
+            .db
+            .create_market(
+                self.base_ticker,
+                self.quote_ticker,
+                self.tick_size,
+                self.lot_size,
+                self.min_size,
+                self.maker_fee_bps,
+                self.taker_fee_bps,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create test market: {}", e))
+    }
+}
+
+/// Builder for users with starting balances
+///
+/// ```rust,ignore
+/// let alice = UserBuilder::new("alice")
+///     .balance("BTC", 100_000_000)
+///     .balance("USDC", 50_000_000_000)
+///     .create(&test_db)
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct UserBuilder {
+    address: String,
+    balances: Vec<(String, u128)>,
+}
+
+impl UserBuilder {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            balances: Vec::new(),
+        }
+    }
+
+    /// Credit `amount` atoms of `token_ticker` (the token must already exist)
+    pub fn balance(mut self, token_ticker: &str, amount: u128) -> Self {
+        self.balances.push((token_ticker.to_string(), amount));
+        self
+    }
+
+    /// Create the user and credit its balances
+    pub async fn create(self, test_db: &TestDb) -> anyhow::Result<User> {
+        let user = helpers::create_user(test_db, &self.address).await?;
+
+        for (ticker, amount) in &self.balances {
+            test_db
+                .db
+                .add_balance(&self.address, ticker, *amount)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to fund {}: {}", self.address, e))?;
+        }
+
+        Ok(user)
+    }
+}
+
+// ============================================================================
+// In-Memory Market - BTC/USDC Without a Database
+// ============================================================================
+
+/// Atoms of one BTC in `memory_market`
+pub const BTC: u128 = 100_000_000;
+
+/// USDC atoms of a whole-dollar price
+pub fn usd(dollars: u128) -> u128 {
+    dollars * 1_000_000
+}
+
+/// An in-memory `Db` with BTC/USDC at 8 and 6 decimals, tick, lot and
+/// minimum size 1 and no fees, and each of `users` holding 10 BTC and
+/// 1,000,000 USDC
+///
+/// ```rust,ignore
+/// let db = memory_market(&["alice", "bob"]).await;
+/// let engine = TestEngine::spawn(db.clone());
+/// ```
+pub async fn memory_market(users: &[&str]) -> Db {
+    let db = Db::in_memory().unwrap();
+    db.create_token("BTC".to_string(), 8, "Bitcoin".to_string())
+        .await
+        .unwrap();
+    db.create_token("USDC".to_string(), 6, "USD Coin".to_string())
+        .await
+        .unwrap();
+    db.create_market("BTC".to_string(), "USDC".to_string(), 1, 1, 1, 0, 0)
+        .await
+        .unwrap();
+    for user in users {
+        db.create_user(user.to_string()).await.unwrap();
+        db.add_balance(user, "BTC", 10 * BTC).await.unwrap();
+        db.add_balance(user, "USDC", usd(1_000_000)).await.unwrap();
+    }
+    db
+}
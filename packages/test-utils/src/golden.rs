@@ -0,0 +1,247 @@
+use backend::models::api::{ClientMessage, SubscriptionChannel};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+
+// ============================================================================
+// Golden-File Tests - Record WebSocket Output and Diff Against Committed JSON
+// ============================================================================
+
+/// Set to `1` to rewrite golden files from the current output instead of comparing
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// Object keys whose values are wall-clock times and vary between runs
+const TIMESTAMP_KEYS: [&str; 2] = ["timestamp", "updated_at"];
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// WebSocket client that records every JSON message the server sends
+///
+/// Pings and other control frames are skipped; text frames are parsed and
+/// kept in arrival order.
+pub struct WsRecorder {
+    ws: WsStream,
+    messages: Vec<Value>,
+}
+
+impl WsRecorder {
+    pub async fn connect(ws_url: &str) -> anyhow::Result<Self> {
+        let (ws, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to {}: {}", ws_url, e))?;
+
+        Ok(Self {
+            ws,
+            messages: Vec::new(),
+        })
+    }
+
+    /// Subscribe to a channel and wait for the server's acknowledgment
+    ///
+    /// The acknowledgment is recorded like any other message, so golden files
+    /// also cover the `subscribed` format.
+    pub async fn subscribe(
+        &mut self,
+        channel: SubscriptionChannel,
+        market_id: Option<&str>,
+        user_address: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let msg = ClientMessage::Subscribe {
+            channel,
+            market_id: market_id.map(str::to_string),
+            user_address: user_address.map(str::to_string),
+            interval: None,
+        };
+        self.ws
+            .send(Message::Text(serde_json::to_string(&msg)?.into()))
+            .await?;
+
+        let recorded = self.messages.len();
+        while !self.messages[recorded..]
+            .iter()
+            .any(|m| m["type"] == "subscribed")
+        {
+            if !self.recv(Duration::from_secs(5)).await? {
+                anyhow::bail!("Timeout waiting for {:?} subscription ack", channel);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record messages until none arrives for `quiet`
+    pub async fn settle(&mut self, quiet: Duration) -> anyhow::Result<()> {
+        while self.recv(quiet).await? {}
+        Ok(())
+    }
+
+    /// Messages recorded so far, in arrival order
+    pub fn messages(&self) -> &[Value] {
+        &self.messages
+    }
+
+    /// Wait up to `wait` for one text message; returns false on timeout
+    async fn recv(&mut self, wait: Duration) -> anyhow::Result<bool> {
+        loop {
+            match timeout(wait, self.ws.next()).await {
+                Ok(Some(Ok(Message::Text(text)))) => {
+                    self.messages.push(serde_json::from_str(&text)?);
+                    return Ok(true);
+                }
+                Ok(Some(Ok(_))) => continue,
+                Ok(Some(Err(e))) => anyhow::bail!("WebSocket error: {}", e),
+                Ok(None) => anyhow::bail!("Connection closed"),
+                Err(_) => return Ok(false),
+            }
+        }
+    }
+}
+
+/// Comparison of recorded messages against a committed golden file
+///
+/// Values that change from run to run are replaced before comparing:
+/// - UUIDs registered with [`Golden::label`] become `<label>`
+/// - any other UUID becomes `<uuid:N>`, numbered by first appearance
+/// - `timestamp` and `updated_at` fields become `<timestamp>`
+///
+/// ```rust,ignore
+/// Golden::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/ws_trade.json"))
+///     .label(sell.id, "alice_sell")
+///     .assert(recorder.messages());
+/// ```
+///
+/// Run with `UPDATE_GOLDEN=1` to write the file after an intentional protocol change.
+pub struct Golden {
+    path: PathBuf,
+    labels: HashMap<String, String>,
+}
+
+impl Golden {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Render a known value (e.g. an order id) as `<name>` in the golden file
+    pub fn label(mut self, value: impl ToString, name: &str) -> Self {
+        self.labels.insert(value.to_string(), format!("<{}>", name));
+        self
+    }
+
+    /// Normalize `messages` into the form stored in golden files
+    pub fn normalize(&self, messages: &[Value]) -> Value {
+        let mut unlabeled = HashMap::new();
+        let mut normalized = Value::Array(messages.to_vec());
+        self.normalize_value(&mut normalized, &mut unlabeled);
+        normalized
+    }
+
+    /// Panic with a diff if `messages` do not match the golden file
+    pub fn assert(&self, messages: &[Value]) {
+        let actual = format!(
+            "{}\n",
+            serde_json::to_string_pretty(&self.normalize(messages))
+                .expect("Failed to serialize messages")
+        );
+
+        if std::env::var(UPDATE_ENV).is_ok_and(|v| v == "1") {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir).expect("Failed to create golden directory");
+            }
+            std::fs::write(&self.path, &actual).expect("Failed to write golden file");
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&self.path).unwrap_or_else(|e| {
+            panic!(
+                "Failed to read golden file {}: {}\nRun with {}=1 to create it.\n\nActual output:\n{}",
+                self.path.display(),
+                e,
+                UPDATE_ENV,
+                actual
+            )
+        });
+
+        if expected != actual {
+            panic!(
+                "WebSocket output differs from golden file {}\n{}\nIf the change is intentional, rerun with {}=1.",
+                self.path.display(),
+                line_diff(&expected, &actual),
+                UPDATE_ENV
+            );
+        }
+    }
+
+    fn normalize_value(&self, value: &mut Value, unlabeled: &mut HashMap<String, String>) {
+        match value {
+            Value::String(s) => {
+                if let Some(label) = self.labels.get(s.as_str()) {
+                    *s = label.clone();
+                } else if uuid::Uuid::parse_str(s).is_ok() {
+                    let next = unlabeled.len() + 1;
+                    *s = unlabeled
+                        .entry(s.clone())
+                        .or_insert_with(|| format!("<uuid:{}>", next))
+                        .clone();
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.normalize_value(item, unlabeled);
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    if TIMESTAMP_KEYS.contains(&key.as_str()) {
+                        *item = Value::String("<timestamp>".to_string());
+                    } else {
+                        self.normalize_value(item, unlabeled);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Line-by-line diff of two texts, marking removed lines with `-` and added lines with `+`
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            out.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        }
+    }
+    out
+}
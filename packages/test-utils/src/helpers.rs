@@ -83,6 +83,25 @@ pub async fn create_market_with_tokens(
     create_market(test_db, base_ticker, quote_ticker).await
 }
 
+/// A one-lot trade at $50,000 between two placeholder users, stamped now
+///
+/// Not persisted anywhere; for feeding code that consumes `Trade`s directly.
+pub fn sample_trade(market_id: &str) -> Trade {
+    Trade {
+        id: uuid::Uuid::new_v4(),
+        market_id: market_id.to_string(),
+        buyer_address: "test_buyer".to_string(),
+        seller_address: "test_seller".to_string(),
+        buyer_order_id: uuid::Uuid::new_v4(),
+        seller_order_id: uuid::Uuid::new_v4(),
+        price: 50_000_000_000,
+        size: 1_000_000,
+        side: backend::models::domain::Side::Buy,
+        timestamp: chrono::Utc::now(),
+        rfq: false,
+    }
+}
+
 /// Create test candle by inserting trades
 ///
 /// Generates candles via materialized views (the real production flow).
@@ -117,6 +136,7 @@ pub async fn create_candle(
             side: backend::models::domain::Side::Buy,
             timestamp: chrono::DateTime::from_timestamp(*ts as i64, 0)
                 .unwrap_or(chrono::DateTime::UNIX_EPOCH),
+            rfq: false,
         };
 
         test_db
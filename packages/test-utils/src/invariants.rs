@@ -0,0 +1,392 @@
+use crate::db::TestDb;
+use crate::engine::TestEngine;
+use crate::fixtures::OrderBuilder;
+use backend::db::Db;
+use backend::models::domain::{Market, OrderType, Side};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use uuid::Uuid;
+
+// ============================================================================
+// Property-Based Invariant Harness - Random Order/Cancel Sequences
+// ============================================================================
+
+/// Users trading in every generated case
+const USERS: usize = 4;
+/// Distance from the mid price, in price steps, that generated orders span
+const PRICE_STEPS: u128 = 20;
+/// Mid price in quote atoms (50,000 USDC with 6 decimals)
+const MID_PRICE: u128 = 50_000_000_000;
+/// One price step in quote atoms (1 USDC)
+const PRICE_STEP: u128 = 1_000_000;
+/// Starting balances per user: 10 base tokens (8 decimals), 1,000,000 quote tokens (6 decimals)
+const BASE_FUNDING: u128 = 1_000_000_000;
+const QUOTE_FUNDING: u128 = 1_000_000_000_000;
+
+/// Address that receives trading fees
+const FEE_RECIPIENT: &str = "system";
+
+/// Unique suffix for the market created by each case, so cases can share one database
+static CASE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A single operation fired at the engine
+#[derive(Debug, Clone)]
+pub enum EngineOp {
+    Place {
+        user: usize,
+        side: Side,
+        order_type: OrderType,
+        /// Offset from mid in price steps, in `-PRICE_STEPS..=PRICE_STEPS`
+        price_offset: i64,
+        lots: u128,
+    },
+    /// Cancel one previously placed order, chosen by index modulo the number placed
+    Cancel {
+        pick: usize,
+    },
+    CancelAll {
+        user: usize,
+    },
+}
+
+/// Strategy producing a sequence of up to `max_len` engine operations
+pub fn engine_ops(max_len: usize) -> impl Strategy<Value = Vec<EngineOp>> {
+    let side = prop_oneof![Just(Side::Buy), Just(Side::Sell)];
+    let order_type = prop_oneof![9 => Just(OrderType::Limit), 1 => Just(OrderType::Market)];
+    let steps = PRICE_STEPS as i64;
+
+    let place = (0..USERS, side, order_type, -steps..=steps, 1u128..=10).prop_map(
+        |(user, side, order_type, price_offset, lots)| EngineOp::Place {
+            user,
+            side,
+            order_type,
+            price_offset,
+            lots,
+        },
+    );
+    let cancel = any::<usize>().prop_map(|pick| EngineOp::Cancel { pick });
+    let cancel_all = (0..USERS).prop_map(|user| EngineOp::CancelAll { user });
+
+    prop::collection::vec(
+        prop_oneof![6 => place, 2 => cancel, 1 => cancel_all],
+        1..=max_len,
+    )
+}
+
+/// Runs operations against a fresh market on a live engine and checks invariants after each one
+///
+/// Invariants:
+/// - the resting book is never crossed (best bid < best ask), and the engine
+///   never halts the market to repair a crossed book
+/// - no order has `filled_size > size`
+/// - total balances per token (users + fee recipient) are conserved
+/// - locked funds never exceed a user's balance
+/// - resting orders in memory match the open orders in the database
+pub struct InvariantHarness {
+    pub engine: TestEngine,
+    pub market: Market,
+    pub users: Vec<String>,
+    placed: Vec<(Uuid, String)>,
+    initial_totals: HashMap<String, u128>,
+}
+
+impl InvariantHarness {
+    /// Create a new market with funded users on its own engine over `db`
+    pub async fn new(db: &Db) -> anyhow::Result<Self> {
+        let case = CASE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let base = format!("INV{}B", case);
+        let quote = format!("INV{}Q", case);
+
+        // Same decimals, ticks and fees as `helpers::create_market_with_tokens`
+        db.create_token(base.clone(), 8, format!("{} Token", base))
+            .await?;
+        db.create_token(quote.clone(), 6, format!("{} Token", quote))
+            .await?;
+        let market = db
+            .create_market(base.clone(), quote.clone(), 1000, 1000000, 1000000, 10, 20)
+            .await?;
+
+        let mut users = Vec::with_capacity(USERS);
+        for i in 0..USERS {
+            let address = format!("inv{}_user{}", case, i);
+            db.create_user(address.clone()).await?;
+            db.add_balance(&address, &base, BASE_FUNDING).await?;
+            db.add_balance(&address, &quote, QUOTE_FUNDING).await?;
+            users.push(address);
+        }
+
+        let engine = TestEngine::spawn(db.clone());
+
+        let mut harness = Self {
+            engine,
+            market,
+            users,
+            placed: Vec::new(),
+            initial_totals: HashMap::new(),
+        };
+        harness.initial_totals = harness.token_totals().await?;
+
+        Ok(harness)
+    }
+
+    /// Apply every operation in order, checking invariants after each one
+    pub async fn run(&mut self, ops: &[EngineOp]) -> Result<(), String> {
+        for (step, op) in ops.iter().enumerate() {
+            self.apply(op).await;
+            self.check()
+                .await
+                .map_err(|e| format!("step {} ({:?}): {}", step, op, e))?;
+        }
+        Ok(())
+    }
+
+    /// Send one operation to the engine
+    ///
+    /// Rejections (insufficient balance, unknown order, ...) are valid outcomes
+    /// and are ignored; only the resulting state is checked.
+    pub async fn apply(&mut self, op: &EngineOp) {
+        match op {
+            EngineOp::Place {
+                user,
+                side,
+                order_type,
+                price_offset,
+                lots,
+            } => {
+                let price =
+                    (MID_PRICE as i128 + *price_offset as i128 * PRICE_STEP as i128) as u128;
+                let user = &self.users[*user];
+                let order = OrderBuilder::new(user, &self.market.id)
+                    .side(*side)
+                    .order_type(*order_type)
+                    .price(price)
+                    .size(lots * self.market.lot_size)
+                    .build();
+                let id = order.id;
+                if self.engine.place_order(order).await.is_ok() {
+                    self.placed.push((id, user.clone()));
+                }
+            }
+            EngineOp::Cancel { pick } => {
+                if self.placed.is_empty() {
+                    return;
+                }
+                let (id, user) = self.placed[pick % self.placed.len()].clone();
+                let _ = self.engine.cancel_order(id, user).await;
+            }
+            EngineOp::CancelAll { user } => {
+                let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+                let request = backend::models::domain::EngineRequest::CancelAllOrders {
+                    user_address: self.users[*user].clone(),
+                    market_id: Some(self.market.id.clone()),
+                    span: tracing::Span::current(),
+                    response_tx,
+                };
+                if self.engine.engine_tx.send(request).await.is_ok() {
+                    let _ = response_rx.await;
+                }
+            }
+        }
+    }
+
+    /// Check all invariants against the current engine and database state
+    pub async fn check(&self) -> Result<(), String> {
+        // Resting book from the engine's memory: order id -> remaining size
+        let (memory_orders, best_bid, best_ask) = {
+            let mut orderbooks = self.engine.orderbooks.write().await;
+            let book = orderbooks.get_or_create(&self.market.id);
+
+            let mut resting = BTreeMap::new();
+            for (_, orders) in book.bids.iter().chain(book.asks.iter()) {
+                for order in orders {
+                    if order.filled_size > order.size {
+                        return Err(format!(
+                            "in-memory order {} overfilled: {} > {}",
+                            order.id, order.filled_size, order.size
+                        ));
+                    }
+                    resting.insert(order.id, order.size - order.filled_size);
+                }
+            }
+
+            // Incrementally maintained snapshot levels agree with the order queues
+            let snapshot = book.snapshot();
+            for (levels, queues, side) in [
+                (&snapshot.bids, &book.bids, "bid"),
+                (&snapshot.asks, &book.asks, "ask"),
+            ] {
+                let expected: BTreeMap<u128, u128> = queues
+                    .iter()
+                    .map(|(price, orders)| {
+                        let size = orders.iter().map(|o| o.size - o.filled_size).sum();
+                        (price, size)
+                    })
+                    .filter(|(_, size)| *size > 0)
+                    .collect();
+                let actual: BTreeMap<u128, u128> =
+                    levels.iter().map(|l| (l.price, l.size)).collect();
+                if actual != expected {
+                    return Err(format!(
+                        "{} snapshot levels {:?} disagree with resting orders {:?}",
+                        side, actual, expected
+                    ));
+                }
+            }
+
+            let best_bid = book
+                .bids
+                .iter()
+                .rev()
+                .find(|(_, orders)| !orders.is_empty())
+                .map(|(p, _)| p);
+            let best_ask = book
+                .asks
+                .iter()
+                .find(|(_, orders)| !orders.is_empty())
+                .map(|(p, _)| p);
+            (resting, best_bid, best_ask)
+        };
+
+        if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+            if bid >= ask {
+                return Err(format!(
+                    "crossed book: best bid {} >= best ask {}",
+                    bid, ask
+                ));
+            }
+        }
+
+        // Halting cancels the whole book, which would hide a cross
+        let inactive = self
+            .engine
+            .db
+            .list_inactive_markets()
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some((_, status)) = inactive.iter().find(|(id, _)| *id == self.market.id) {
+            return Err(format!("market became {:?}", status));
+        }
+
+        // Every order ever placed in this market respects filled <= size
+        for user in &self.users {
+            let orders = self
+                .engine
+                .db
+                .get_user_orders(user, Some(&self.market.id), None, 1000)
+                .await
+                .map_err(|e| e.to_string())?;
+            if let Some(order) = orders.iter().find(|o| o.filled_size > o.size) {
+                return Err(format!(
+                    "order {} overfilled: {} > {}",
+                    order.id, order.filled_size, order.size
+                ));
+            }
+        }
+
+        // Resting orders in memory agree with open orders in the database
+        let db_orders: BTreeMap<Uuid, u128> = self
+            .engine
+            .db
+            .get_recoverable_orders_for_market(&self.market.id)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|o| (o.id, o.size - o.filled_size))
+            .collect();
+        if db_orders != memory_orders {
+            return Err(format!(
+                "memory/database drift: memory={:?} database={:?}",
+                memory_orders, db_orders
+            ));
+        }
+
+        // Balances are conserved and locks stay within balances
+        let totals = self.token_totals().await.map_err(|e| e.to_string())?;
+        if totals != self.initial_totals {
+            return Err(format!(
+                "balance totals changed: {:?} -> {:?}",
+                self.initial_totals, totals
+            ));
+        }
+        for user in &self.users {
+            for balance in self
+                .engine
+                .db
+                .list_balances_by_user(user)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                if balance.open_interest > balance.amount {
+                    return Err(format!(
+                        "{} has {} {} locked but only {} total",
+                        user, balance.open_interest, balance.token_ticker, balance.amount
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sum of balances per market token across users and the fee recipient
+    async fn token_totals(&self) -> anyhow::Result<HashMap<String, u128>> {
+        let tokens = [&self.market.base_ticker, &self.market.quote_ticker];
+        let mut totals: HashMap<String, u128> = tokens.iter().map(|t| (t.to_string(), 0)).collect();
+
+        for holder in self.users.iter().map(String::as_str).chain([FEE_RECIPIENT]) {
+            for balance in self.engine.db.list_balances_by_user(holder).await? {
+                if let Some(total) = totals.get_mut(&balance.token_ticker) {
+                    *total += balance.amount;
+                }
+            }
+        }
+
+        Ok(totals)
+    }
+}
+
+/// Run `cases` random operation sequences against the engine over
+/// PostgreSQL and panic on the first invariant violation, reporting the
+/// shrunk failing sequence
+///
+/// Must be called from a plain `#[test]` (not `#[tokio::test]`) since it
+/// drives its own runtime.
+pub fn check_engine_invariants(cases: u32, max_ops: usize) {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+    let test_db = runtime
+        .block_on(TestDb::setup())
+        .expect("Failed to setup test DB");
+    run_invariant_cases(&runtime, &test_db.db, cases, max_ops);
+}
+
+/// Like [`check_engine_invariants`], over `Db::in_memory` so it runs without
+/// containers
+pub fn check_engine_invariants_in_memory(cases: u32, max_ops: usize) {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+    let db = runtime
+        .block_on(async { Db::in_memory() })
+        .expect("Failed to create in-memory DB");
+    run_invariant_cases(&runtime, &db, cases, max_ops);
+}
+
+fn run_invariant_cases(runtime: &tokio::runtime::Runtime, db: &Db, cases: u32, max_ops: usize) {
+    let mut runner = TestRunner::new(Config {
+        cases,
+        ..Config::default()
+    });
+
+    let result = runner.run(&engine_ops(max_ops), |ops| {
+        runtime.block_on(async {
+            let mut harness = InvariantHarness::new(db)
+                .await
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            harness.run(&ops).await.map_err(TestCaseError::fail)
+        })
+    });
+
+    if let Err(e) = result {
+        panic!("Engine invariant violated: {}", e);
+    }
+}
@@ -1,8 +1,27 @@
+pub mod contract;
 pub mod db;
+pub mod differential;
 pub mod engine;
+pub mod faults;
+pub mod fixtures;
+pub mod golden;
 pub mod helpers;
+pub mod invariants;
+pub mod load;
+pub mod scenario;
 pub mod server;
+pub mod wallet;
 
+pub use contract::{Contract, Probe};
 pub use db::{TestContainers, TestDb};
+pub use differential::{check_against_reference, DifferentialHarness, ReferenceBook};
 pub use engine::TestEngine;
-pub use server::TestServer;
+pub use faults::Service;
+pub use fixtures::{memory_market, usd, MarketBuilder, OrderBuilder, UserBuilder, BTC};
+pub use golden::{Golden, WsRecorder};
+pub use invariants::{
+    check_engine_invariants, check_engine_invariants_in_memory, InvariantHarness,
+};
+pub use scenario::{ScenarioConfig, ScenarioGenerator};
+pub use server::{TestServer, TEST_ADMIN_TOKEN};
+pub use wallet::TestWallet;
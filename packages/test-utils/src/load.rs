@@ -0,0 +1,219 @@
+use crate::scenario::{ScenarioConfig, ScenarioGenerator};
+use backend::models::api::{AdminRequest, InfoRequest, InfoResponse, TradeRequest};
+use backend::models::domain::Market;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+// ============================================================================
+// Load Testing - Concurrent Order Flow Over REST
+// ============================================================================
+
+/// Load test parameters
+///
+/// Orders come from a [`ScenarioGenerator`], so a given seed always sends the
+/// same order stream and runs can be compared against each other.
+#[derive(Debug, Clone)]
+pub struct LoadTestConfig {
+    /// Exchange base URL, e.g. `http://localhost:8001` or `TestServer::base_url`
+    pub base_url: String,
+    pub scenario: ScenarioConfig,
+    pub seed: u64,
+    /// Total number of orders to send
+    pub total_orders: usize,
+    /// Maximum number of in-flight requests
+    pub concurrency: usize,
+}
+
+/// Results of a load test run
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    /// Orders accepted by the exchange
+    pub accepted: usize,
+    /// Orders rejected with an API error (e.g. insufficient balance)
+    pub rejected: usize,
+    /// Requests that failed at the transport level
+    pub failed: usize,
+    pub elapsed: Duration,
+    /// Request latencies for accepted and rejected orders, sorted ascending
+    latencies: Vec<Duration>,
+}
+
+impl LoadReport {
+    /// Total requests sent
+    pub fn total(&self) -> usize {
+        self.accepted + self.rejected + self.failed
+    }
+
+    /// Completed requests per second
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        (self.accepted + self.rejected) as f64 / secs
+    }
+
+    /// Latency at percentile `p` (0-100), using the nearest-rank method
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = ((p / 100.0) * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "orders: {} ({} accepted, {} rejected, {} failed) in {:.2}s",
+            self.total(),
+            self.accepted,
+            self.rejected,
+            self.failed,
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(f, "throughput: {:.1} orders/s", self.throughput())?;
+        write!(
+            f,
+            "latency: p50={:?} p90={:?} p99={:?} max={:?}",
+            self.percentile(50.0),
+            self.percentile(90.0),
+            self.percentile(99.0),
+            self.percentile(100.0)
+        )
+    }
+}
+
+enum Outcome {
+    Accepted(Duration),
+    Rejected(Duration),
+    Failed,
+}
+
+/// Drive `config.total_orders` orders through `POST /api/trade` with bounded concurrency
+pub async fn run_load_test(config: &LoadTestConfig) -> anyhow::Result<LoadReport> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/trade", config.base_url);
+    let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+    let generator = ScenarioGenerator::new(config.scenario.clone(), config.seed);
+
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(config.total_orders);
+
+    for order in generator.take(config.total_orders) {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let client = client.clone();
+        let url = url.clone();
+
+        let request = TradeRequest::PlaceOrder {
+            user_address: order.user_address,
+            market_id: order.market_id,
+            side: order.side,
+            order_type: order.order_type,
+            price: order.price.to_string(),
+            size: order.size.to_string(),
+            signature: "loadtest".to_string(),
+            trigger_price: order.trigger_price.map(|p| p.to_string()),
+            trigger_source: order.trigger_source,
+            time_in_force: order.time_in_force,
+            expires_at: order.expires_at,
+        };
+
+        handles.push(tokio::spawn(async move {
+            let sent_at = Instant::now();
+            let result = client.post(&url).json(&request).send().await;
+            drop(permit);
+
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    Outcome::Accepted(sent_at.elapsed())
+                }
+                Ok(_) => Outcome::Rejected(sent_at.elapsed()),
+                Err(_) => Outcome::Failed,
+            }
+        }));
+    }
+
+    let mut report = LoadReport {
+        accepted: 0,
+        rejected: 0,
+        failed: 0,
+        elapsed: Duration::ZERO,
+        latencies: Vec::with_capacity(config.total_orders),
+    };
+
+    for handle in handles {
+        match handle.await {
+            Ok(Outcome::Accepted(latency)) => {
+                report.accepted += 1;
+                report.latencies.push(latency);
+            }
+            Ok(Outcome::Rejected(latency)) => {
+                report.rejected += 1;
+                report.latencies.push(latency);
+            }
+            Ok(Outcome::Failed) | Err(_) => report.failed += 1,
+        }
+    }
+
+    report.elapsed = start.elapsed();
+    report.latencies.sort_unstable();
+
+    Ok(report)
+}
+
+/// Fetch market config over REST (tick, lot and min size for the scenario)
+pub async fn fetch_market(base_url: &str, market_id: &str) -> anyhow::Result<Market> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/info", base_url))
+        .json(&InfoRequest::MarketDetails {
+            market_id: market_id.to_string(),
+        })
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<InfoResponse>()
+        .await?;
+
+    match response {
+        InfoResponse::MarketDetails { market } => market
+            .try_into()
+            .map_err(|e| anyhow::anyhow!("Failed to parse market: {}", e)),
+        _ => Err(anyhow::anyhow!("Expected MarketDetails response")),
+    }
+}
+
+/// Credit every user with the given token amounts via the admin faucet,
+/// authenticated with `admin_token`
+pub async fn fund_users(
+    base_url: &str,
+    admin_token: &str,
+    users: &[String],
+    amounts: &[(&str, u128)],
+) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/admin", base_url);
+
+    for user in users {
+        for (ticker, amount) in amounts {
+            client
+                .post(&url)
+                .bearer_auth(admin_token)
+                .json(&AdminRequest::Faucet {
+                    user_address: user.clone(),
+                    token_ticker: ticker.to_string(),
+                    amount: amount.to_string(),
+                    signature: "admin".to_string(),
+                })
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+    }
+
+    Ok(())
+}
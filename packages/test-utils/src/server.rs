@@ -1,11 +1,21 @@
 use crate::db::TestDb;
 use crate::engine::TestEngine;
 use axum::Router;
-use backend::api::{rest, ws};
+use backend::api::signature::SignatureCheck;
+use backend::api::{auth, rest, ws};
+use backend::balance_notify;
+use backend::cache::ReadCache;
 use backend::db::Db;
+use backend::shutdown::Shutdown;
+use backend::symbols::Symbols;
+use backend::webhooks::WebhookDispatcher;
 use backend::AppState;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
 
+/// Admin token the test server accepts on operator endpoints
+pub const TEST_ADMIN_TOKEN: &str = "test-admin-token";
+
 /// Handle to a running test server
 ///
 /// Provides both simple URL-based testing (for SDK) and internal access to DB/engine (for backend tests)
@@ -17,6 +27,7 @@ pub struct TestServer {
     pub address: String,
     pub test_db: TestDb,
     pub test_engine: TestEngine,
+    shutdown: Shutdown,
     _shutdown_tx: tokio::sync::oneshot::Sender<()>,
 }
 
@@ -41,14 +52,50 @@ impl TestServer {
         // Create REST and WebSocket routes
         let rest = rest::create_rest();
         let ws = ws::create_ws();
+        let event_router = ws::EventRouter::new();
+        event_router.spawn(test_engine.event_tx().subscribe());
+        WebhookDispatcher::new(test_engine.db.clone())
+            .with_retry_delay(Duration::from_millis(50))
+            .spawn(test_engine.event_tx().subscribe());
+        let cache = ReadCache::default();
+        cache
+            .clone()
+            .spawn_updater(test_engine.event_tx().subscribe());
+        ws::spawn_tickers(
+            event_router.clone(),
+            test_engine.db.clone(),
+            cache.clone(),
+            ws::TICKER_INTERVAL,
+        );
+        balance_notify::spawn_balance_listener(test_engine.db.clone(), test_engine.event_tx());
+        // Tests add markets straight to the database, so pick them up quickly
+        let symbols = Symbols::load(&test_engine.db).await?;
+        symbols
+            .clone()
+            .spawn_refresher(test_engine.db.clone(), Duration::from_millis(100));
+        let shutdown = Shutdown::new();
         let state = AppState {
             db: test_engine.db.clone(),
             engine_tx: test_engine.engine_tx.clone(),
             event_tx: test_engine.event_tx(),
+            event_router,
+            admin_token: Some(TEST_ADMIN_TOKEN.to_string()),
+            cache,
+            exports: Default::default(),
+            l3: Default::default(),
+            // Orders go unsigned; funds and account management are signed with a `TestWallet`
+            signatures: SignatureCheck::default().with_unsigned_trading(true),
+            shutdown: shutdown.clone(),
+            saturation: Default::default(),
+            symbols,
         };
         let app = Router::new()
             .merge(rest)
             .merge(ws)
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                auth::authenticate,
+            ))
             .with_state(state)
             .layer(CorsLayer::permissive());
 
@@ -68,13 +115,21 @@ impl TestServer {
         let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
 
         // Spawn server in background
+        let signal = shutdown.clone();
         tokio::spawn(async move {
-            axum::serve(listener, app)
-                .with_graceful_shutdown(async {
-                    shutdown_rx.await.ok();
-                })
-                .await
-                .expect("Server failed to start");
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            )
+            .with_graceful_shutdown(async move {
+                tokio::select! {
+                    _ = shutdown_rx => {}
+                    _ = signal.triggered() => {}
+                }
+                signal.trigger();
+            })
+            .await
+            .expect("Server failed to start");
         });
 
         // Give server a moment to start
@@ -86,6 +141,7 @@ impl TestServer {
             address: base_url, // Alias for backwards compatibility
             test_db,
             test_engine,
+            shutdown,
             _shutdown_tx: shutdown_tx,
         })
     }
@@ -109,6 +165,12 @@ impl TestServer {
         format!("{}{}", self.base_url, path)
     }
 
+    /// Shut the server down as on SIGTERM: stop accepting connections and
+    /// close WebSockets with a going-away frame
+    pub fn begin_shutdown(&self) {
+        self.shutdown.trigger();
+    }
+
     // ============================================================================
     // Database Access - Backend tests can access internals
     // ============================================================================
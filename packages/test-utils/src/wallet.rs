@@ -0,0 +1,83 @@
+use backend::api::signature::{address_of, message_hash, signing_message};
+use k256::ecdsa::SigningKey;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// How long the signatures a test wallet makes stay valid, in milliseconds
+const SIGNATURE_LIFETIME_MS: i64 = 60_000;
+
+/// A random key standing in for a user's wallet, to sign requests with
+///
+/// Use its [`address`](TestWallet::address) as the user's address.
+pub struct TestWallet {
+    key: SigningKey,
+    address: String,
+    /// Expiry of the last signature, kept increasing so identical requests
+    /// signed back to back aren't taken for replays
+    last_expiry: AtomicI64,
+}
+
+impl TestWallet {
+    pub fn new() -> Self {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let address = address_of(key.verifying_key());
+        Self {
+            key,
+            address,
+            last_expiry: AtomicI64::new(0),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// The `signature` for `request` sent to `route`
+    pub fn signature(&self, route: &str, request: &impl Serialize) -> String {
+        let earliest = chrono::Utc::now().timestamp_millis() + SIGNATURE_LIFETIME_MS;
+        let next = |last: i64| earliest.max(last + 1);
+        let last = self
+            .last_expiry
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(next(last)))
+            .unwrap_or_default();
+        let expires_at = next(last);
+        self.signature_expiring(route, request, expires_at)
+    }
+
+    /// The `signature` for `request` sent to `route`, valid until `expires_at`
+    pub fn signature_expiring(
+        &self,
+        route: &str,
+        request: &impl Serialize,
+        expires_at: i64,
+    ) -> String {
+        let message =
+            signing_message(route, request, expires_at).expect("Failed to encode request");
+        let (signature, recovery) = self
+            .key
+            .sign_prehash_recoverable(&message_hash(&message))
+            .expect("Failed to sign request");
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery.to_byte() + 27);
+        format!("{}:0x{}", expires_at, hex::encode(bytes))
+    }
+
+    /// `body`, a JSON `T` sent to `route`, with its `signature` filled in
+    ///
+    /// The body is read as a `T` first, so fields it leaves to their
+    /// defaults are signed the way the server reads them.
+    pub fn sign<T: Serialize + DeserializeOwned>(&self, route: &str, mut body: Value) -> Value {
+        body["signature"] = Value::from("");
+        let request: T = serde_json::from_value(body.clone()).expect("Not a valid request");
+        body["signature"] = Value::from(self.signature(route, &request));
+        body
+    }
+}
+
+impl Default for TestWallet {
+    fn default() -> Self {
+        Self::new()
+    }
+}